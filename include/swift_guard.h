@@ -4,8 +4,19 @@
 
 /* 상수 정의 */
 #define MAX_FILTER_RULES   10240
+#define MAX_WILDCARD_RULES 64
 #define MAX_REDIRECT_IFS   64
+#define MAX_SYN_SOURCES    65536
+#define MAX_CONNTRACK      65536
+#define MAX_CPUS           128
+#define REDIRECT_CPU_NONE  0xFFFFFFFF
 #define MAX_RULE_LABEL_LEN 32
+#define MAX_RULE_PORTS     8
+
+/* 패킷 크기 히스토그램 (common/types.rs SIZE_BUCKET_BOUNDS와 일치) */
+#define SIZE_BUCKET_COUNT  7   /* <=64, 128, 256, 512, 1024, 1514, 점보 */
+#define SIZE_HIST_SUM_IDX  SIZE_BUCKET_COUNT
+#define SIZE_HIST_SLOTS    (SIZE_BUCKET_COUNT + 1)
 
 /* 프로토콜 정의 */
 #define IPPROTO_ANY 255
@@ -34,12 +45,27 @@ struct filter_stats {
     __u64 packets;      /* 처리된 패킷 수 */
     __u64 bytes;        /* 처리된 바이트 수 */
     __u64 last_matched; /* 마지막 매치 타임스탬프 */
+    __u64 redirected_packets; /* 리디렉션 대상으로 넘긴 패킷 수 */
+    __u64 redirect_failures;  /* 리디렉션 대상이 없거나 헬퍼가 거부한 패킷 수 */
+    __u64 aborted_packets;    /* 규칙 액션 대신 XDP_ABORTED로 끝난 패킷 수 */
+};
+
+struct global_stats {
+    __u64 packets;       /* 처리된 패킷 수 */
+    __u64 bytes;         /* 처리된 바이트 수 */
+    __u64 last_matched;  /* 마지막 매치 타임스탬프 */
+    __u64 tcp_packets;   /* TCP 패킷 수 */
+    __u64 udp_packets;   /* UDP 패킷 수 */
+    __u64 icmp_packets;  /* ICMP 패킷 수 */
+    __u64 other_packets; /* 기타 프로토콜 패킷 수 */
+    __u64 syn_packets;   /* TCP SYN(ACK 없음) 패킷 수 */
+    __u64 aborted_packets; /* XDP_ABORTED로 끝난 패킷 수 */
 };
 
 struct filter_rule {
     __u32 priority;          /* 룰 우선순위 */
     __u8 action;             /* 액션 (통과, 드롭, 리디렉션) */
-    __u8 protocol;           /* 프로토콜 (TCP, UDP, ICMP, ANY) */
+    __u8 protocol;           /* IP 프로토콜 번호 (255 = ANY) */
     __u16 src_port_min;      /* 소스 포트 범위 최소값 */
     __u16 src_port_max;      /* 소스 포트 범위 최대값 */
     __u16 dst_port_min;      /* 대상 포트 범위 최소값 */
@@ -48,10 +74,38 @@ struct filter_rule {
     __u32 redirect_ifindex;  /* 리디렉션 인터페이스 인덱스 */
     __u32 rate_limit;        /* 초당 패킷 수 레이트 리밋 */
     __u32 expire;            /* 룰 만료 시간 (초) */
+    __u32 redirect_cpu;      /* CPU 맵 리디렉션 대상 (REDIRECT_CPU_NONE = 미사용) */
     char label[MAX_RULE_LABEL_LEN]; /* 룰 레이블 */
+    __u16 src_ports[MAX_RULE_PORTS]; /* 소스 포트 목록 (0으로 끝남, 비어 있으면 범위만 비교) */
+    __u16 dst_ports[MAX_RULE_PORTS]; /* 목적지 포트 목록 (0으로 끝남, 비어 있으면 범위만 비교) */
     struct filter_stats stats; /* 통계 */
 };
 
+/* 연결 추적 상태 */
+#define CT_STATE_NONE        0  /* 상태 없는 프로토콜 (UDP, ICMP 등) */
+#define CT_STATE_SYN_SENT    1
+#define CT_STATE_ESTABLISHED 2
+#define CT_STATE_FIN_WAIT    3
+#define CT_STATE_CLOSED      4
+
+struct ct_key {
+    __u32 saddr;    /* 소스 IPv4 주소 (네트워크 순서) */
+    __u32 daddr;    /* 대상 IPv4 주소 (네트워크 순서) */
+    __u16 sport;    /* 소스 포트 (호스트 순서) */
+    __u16 dport;    /* 대상 포트 (호스트 순서) */
+    __u8 protocol;  /* IP 프로토콜 */
+    __u8 pad[3];
+};
+
+struct ct_entry {
+    __u8 state;        /* 연결 상태 (CT_STATE_*) */
+    __u8 pad[7];
+    __u64 packets;     /* 패킷 수 */
+    __u64 bytes;       /* 바이트 수 */
+    __u64 first_seen;  /* 최초 관측 시각 (bpf_ktime_get_ns) */
+    __u64 last_seen;   /* 마지막 관측 시각 (bpf_ktime_get_ns) */
+};
+
 struct if_redirect {
     __u32 ifindex;           /* 인터페이스 인덱스 */
     char ifname[16];         /* 인터페이스 이름 */
@@ -83,25 +83,60 @@ struct udphdr {
 
 /* 맵 상수 */
 #define MAX_FILTER_RULES 10240
+#define MAX_WILDCARD_RULES 64
 #define MAX_REDIRECT_IFS 64
+#define MAX_SYN_SOURCES 65536
+#define MAX_CONNTRACK 65536
+#define MAX_CPUS 128
+#define REDIRECT_CPU_NONE 0xFFFFFFFF
 #define MAX_RULE_LABEL_LEN 32
+#define MAX_RULE_PORTS 8
+#define NSEC_PER_SEC 1000000000ULL
+
+/* 패킷 크기 히스토그램 (common/types.rs SIZE_BUCKET_BOUNDS와 일치) */
+#define SIZE_BUCKET_COUNT  7   /* <=64, 128, 256, 512, 1024, 1514, 점보 */
+#define SIZE_HIST_SUM_IDX  SIZE_BUCKET_COUNT
+#define SIZE_HIST_SLOTS    (SIZE_BUCKET_COUNT + 1)
+
+/* 매치 기능 비트맵 (common/types.rs XdpFeatures와 일치)
+ * 새 매치 필드를 처리하게 되면 비트를 추가해 _features에 포함한다 */
+#define FEATURE_TCP_FLAGS    (1ULL << 0)
+#define FEATURE_PORT_LIST    (1ULL << 1)
+#define FEATURE_REDIRECT_CPU (1ULL << 2)
+#define FEATURE_RATE_LIMIT   (1ULL << 3)
+#define FEATURE_DST_IP       (1ULL << 4)  /* 목적지 주소는 아직 비교하지 않으므로 _features에 넣지 않음 */
 
 /* 구조체 정의 */
 struct prefix_key {
     uint32_t prefix_len;  /* LPM 트라이의 프리픽스 길이 */
-    uint32_t addr;        /* IPv4 주소 */
+    uint32_t addr;        /* IPv4 주소 (네트워크 순서, 패킷 헤더 그대로) */
 };
 
 struct filter_stats {
-    uint64_t packets;      /* 처리된 패킷 수 */
-    uint64_t bytes;        /* 처리된 바이트 수 */
-    uint64_t last_matched; /* 마지막 매치 타임스탬프 */
+    uint64_t packets;            /* 처리된 패킷 수 */
+    uint64_t bytes;              /* 처리된 바이트 수 */
+    uint64_t last_matched;       /* 마지막 매치 타임스탬프 */
+    uint64_t redirected_packets; /* 리디렉션 대상으로 넘긴 패킷 수 */
+    uint64_t redirect_failures;  /* 리디렉션 대상이 없거나 헬퍼가 거부한 패킷 수 */
+    uint64_t aborted_packets;    /* 규칙 액션 대신 XDP_ABORTED로 끝난 패킷 수 */
+};
+
+struct global_stats {
+    uint64_t packets;       /* 처리된 패킷 수 */
+    uint64_t bytes;         /* 처리된 바이트 수 */
+    uint64_t last_matched;  /* 마지막 매치 타임스탬프 */
+    uint64_t tcp_packets;   /* TCP 패킷 수 */
+    uint64_t udp_packets;   /* UDP 패킷 수 */
+    uint64_t icmp_packets;  /* ICMP 패킷 수 */
+    uint64_t other_packets; /* 기타 프로토콜 패킷 수 */
+    uint64_t syn_packets;   /* TCP SYN(ACK 없음) 패킷 수 */
+    uint64_t aborted_packets; /* XDP_ABORTED로 끝난 패킷 수 */
 };
 
 struct filter_rule {
     uint32_t priority;          /* 룰 우선순위 */
     uint8_t action;             /* 액션 (통과, 드롭, 리디렉션) */
-    uint8_t protocol;           /* 프로토콜 (TCP, UDP, ICMP, ANY) */
+    uint8_t protocol;           /* IP 프로토콜 번호 (255 = ANY) */
     uint16_t src_port_min;      /* 소스 포트 범위 최소값 */
     uint16_t src_port_max;      /* 소스 포트 범위 최대값 */
     uint16_t dst_port_min;      /* 대상 포트 범위 최소값 */
@@ -110,15 +145,68 @@ struct filter_rule {
     uint32_t redirect_ifindex;  /* 리디렉션 인터페이스 인덱스 */
     uint32_t rate_limit;        /* 초당 패킷 수 레이트 리밋 */
     uint32_t expire;            /* 룰 만료 시간 (초) */
+    uint32_t redirect_cpu;      /* CPU 맵 리디렉션 대상 (REDIRECT_CPU_NONE = 미사용) */
     char label[MAX_RULE_LABEL_LEN]; /* 룰 레이블 */
+    uint16_t src_ports[MAX_RULE_PORTS]; /* 소스 포트 목록 (0으로 끝남, 비어 있으면 범위만 비교) */
+    uint16_t dst_ports[MAX_RULE_PORTS]; /* 목적지 포트 목록 (0으로 끝남, 비어 있으면 범위만 비교) */
     struct filter_stats stats; /* 통계 */
 };
 
+/* 연결 추적 상태 */
+#define CT_STATE_NONE        0  /* 상태 없는 프로토콜 (UDP, ICMP 등) */
+#define CT_STATE_SYN_SENT    1
+#define CT_STATE_ESTABLISHED 2
+#define CT_STATE_FIN_WAIT    3
+#define CT_STATE_CLOSED      4
+
+struct ct_key {
+    uint32_t saddr;    /* 소스 IPv4 주소 (네트워크 순서) */
+    uint32_t daddr;    /* 대상 IPv4 주소 (네트워크 순서) */
+    uint16_t sport;    /* 소스 포트 (호스트 순서) */
+    uint16_t dport;    /* 대상 포트 (호스트 순서) */
+    uint8_t protocol;  /* IP 프로토콜 */
+    uint8_t pad[3];
+};
+
+struct ct_entry {
+    uint8_t state;        /* 연결 상태 (CT_STATE_*) */
+    uint8_t pad[7];
+    uint64_t packets;     /* 패킷 수 */
+    uint64_t bytes;       /* 바이트 수 */
+    uint64_t first_seen;  /* 최초 관측 시각 (bpf_ktime_get_ns) */
+    uint64_t last_seen;   /* 마지막 관측 시각 (bpf_ktime_get_ns) */
+};
+
+/* 레이트 리밋 구간 키 (규칙 레이블) */
+struct rate_key {
+    char label[MAX_RULE_LABEL_LEN];
+};
+
+/* 규칙별 1초 고정 구간 */
+struct rate_window {
+    uint64_t start;    /* 구간 시작 (bpf_ktime_get_ns) */
+    uint64_t packets;  /* 구간 안에서 매치된 패킷 수 */
+};
+
 struct if_redirect {
     uint32_t ifindex;           /* 인터페이스 인덱스 */
     char ifname[16];         /* 인터페이스 이름 */
 };
 
+/* 규칙 적중 이벤트 (daemon/src/events.rs RuleEventRecord와 일치, 64바이트) */
+struct rule_event {
+    uint64_t timestamp;         /* 매치 시각 (bpf_ktime_get_ns) */
+    struct prefix_key key;      /* 매치된 규칙 키 */
+    uint32_t saddr;             /* 소스 IPv4 주소 (네트워크 순서) */
+    uint32_t daddr;             /* 대상 IPv4 주소 (네트워크 순서) */
+    uint16_t sport;             /* 소스 포트 (호스트 순서) */
+    uint16_t dport;             /* 대상 포트 (호스트 순서) */
+    uint8_t action;             /* 수행한 액션 (ACTION_*) */
+    uint8_t protocol;           /* IP 프로토콜 */
+    uint8_t pad[2];
+    char label[MAX_RULE_LABEL_LEN]; /* 룰 레이블 */
+};
+
 /* 맵 정의 */
 struct {
     __uint(type, BPF_MAP_TYPE_LPM_TRIE);
@@ -128,6 +216,14 @@ struct {
     __uint(map_flags, BPF_F_NO_PREALLOC);
 } filter_rules SEC(".maps");
 
+/* 소스 IP 없는 규칙 (프로토콜/포트만 지정, action 0 = 빈 슬롯) */
+struct {
+    __uint(type, BPF_MAP_TYPE_ARRAY);
+    __type(key, uint32_t);
+    __type(value, struct filter_rule);
+    __uint(max_entries, MAX_WILDCARD_RULES);
+} wildcard_rules SEC(".maps");
+
 struct {
     __uint(type, BPF_MAP_TYPE_HASH);
     __type(key, uint32_t);
@@ -135,18 +231,66 @@ struct {
     __uint(max_entries, MAX_REDIRECT_IFS);
 } redirect_map SEC(".maps");
 
+struct {
+    __uint(type, BPF_MAP_TYPE_CPUMAP);
+    __type(key, uint32_t);
+    __type(value, struct bpf_cpumap_val);
+    __uint(max_entries, MAX_CPUS);
+} cpu_map SEC(".maps");
+
 struct {
     __uint(type, BPF_MAP_TYPE_PERCPU_ARRAY);
     __type(key, uint32_t);
-    __type(value, struct filter_stats);
+    __type(value, struct global_stats);
     __uint(max_entries, 1);
 } stats_map SEC(".maps");
 
+struct {
+    __uint(type, BPF_MAP_TYPE_PERCPU_ARRAY);
+    __type(key, uint32_t);
+    __type(value, uint64_t);
+    __uint(max_entries, SIZE_HIST_SLOTS);
+} size_hist_map SEC(".maps");
+
+struct {
+    __uint(type, BPF_MAP_TYPE_LRU_HASH);
+    __type(key, uint32_t);      /* 소스 IPv4 주소 (네트워크 순서) */
+    __type(value, uint64_t);    /* SYN 패킷 수 */
+    __uint(max_entries, MAX_SYN_SOURCES);
+} syn_src_map SEC(".maps");
+
+struct {
+    __uint(type, BPF_MAP_TYPE_LRU_HASH);
+    __type(key, struct ct_key);
+    __type(value, struct ct_entry);
+    __uint(max_entries, MAX_CONNTRACK);
+} conntrack_map SEC(".maps");
+
+struct {
+    __uint(type, BPF_MAP_TYPE_LRU_HASH);
+    __type(key, struct rate_key);
+    __type(value, struct rate_window);
+    __uint(max_entries, MAX_FILTER_RULES + MAX_WILDCARD_RULES);
+} rate_windows SEC(".maps");
+
+struct {
+    __uint(type, BPF_MAP_TYPE_PERF_EVENT_ARRAY);
+    __uint(key_size, sizeof(uint32_t));
+    __uint(value_size, sizeof(uint32_t));
+} rule_events SEC(".maps");
+
 /* 헬퍼 함수 */
+
+/* 매치된 규칙의 카운터와 전역 카운터 갱신
+ * 규칙 카운터는 맵 값(filter_rule.stats)에 바로 기록하므로 데몬이 규칙 항목을 읽어 보고한다. */
 static __always_inline void update_stats(struct filter_stats *stats, uint32_t packets, uint32_t bytes)
 {
     uint32_t key = 0;
-    struct filter_stats *value;
+    struct global_stats *value;
+    
+    __sync_fetch_and_add(&stats->packets, packets);
+    __sync_fetch_and_add(&stats->bytes, bytes);
+    stats->last_matched = bpf_ktime_get_ns();
     
     value = bpf_map_lookup_elem(&stats_map, &key);
     if (value) {
@@ -155,6 +299,297 @@ static __always_inline void update_stats(struct filter_stats *stats, uint32_t pa
     }
 }
 
+/* XDP_ABORTED로 끝나는 패킷 기록 (규칙별, 전역) */
+static __always_inline void count_aborted(struct filter_stats *stats)
+{
+    uint32_t key = 0;
+    struct global_stats *value;
+    
+    __sync_fetch_and_add(&stats->aborted_packets, 1);
+    value = bpf_map_lookup_elem(&stats_map, &key);
+    if (value)
+        __sync_fetch_and_add(&value->aborted_packets, 1);
+}
+
+/* 리디렉션 헬퍼 결과 기록 (XDP_REDIRECT가 아니면 실패)
+ * CPU 맵에 없는 대상이면 헬퍼가 XDP_ABORTED를 돌려주며 중단으로도 센다.
+ * 헬퍼가 받아들인 뒤 대상 큐가 가득 차 버려지는 패킷은 여기서 보이지 않는다. */
+static __always_inline int count_redirect(struct filter_stats *stats, int ret)
+{
+    if (ret == XDP_REDIRECT) {
+        __sync_fetch_and_add(&stats->redirected_packets, 1);
+    } else {
+        __sync_fetch_and_add(&stats->redirect_failures, 1);
+        if (ret == XDP_ABORTED)
+            count_aborted(stats);
+    }
+    return ret;
+}
+
+static __always_inline void update_proto_stats(uint8_t protocol)
+{
+    uint32_t key = 0;
+    struct global_stats *value;
+    
+    value = bpf_map_lookup_elem(&stats_map, &key);
+    if (!value)
+        return;
+        
+    switch (protocol) {
+    case IPPROTO_TCP:
+        __sync_fetch_and_add(&value->tcp_packets, 1);
+        break;
+    case IPPROTO_UDP:
+        __sync_fetch_and_add(&value->udp_packets, 1);
+        break;
+    case IPPROTO_ICMP:
+        __sync_fetch_and_add(&value->icmp_packets, 1);
+        break;
+    default:
+        __sync_fetch_and_add(&value->other_packets, 1);
+        break;
+    }
+}
+
+static __always_inline void update_syn_stats(uint32_t saddr)
+{
+    uint32_t key = 0;
+    struct global_stats *stats;
+    uint64_t *count;
+    uint64_t one = 1;
+    
+    stats = bpf_map_lookup_elem(&stats_map, &key);
+    if (stats)
+        __sync_fetch_and_add(&stats->syn_packets, 1);
+        
+    count = bpf_map_lookup_elem(&syn_src_map, &saddr);
+    if (count)
+        __sync_fetch_and_add(count, 1);
+    else
+        bpf_map_update_elem(&syn_src_map, &saddr, &one, BPF_NOEXIST);
+}
+
+static __always_inline void update_conntrack(uint32_t saddr, uint32_t daddr,
+                                             uint16_t sport, uint16_t dport,
+                                             uint8_t protocol, uint8_t tcp_flags,
+                                             uint64_t len)
+{
+    struct ct_key key = {0};
+    struct ct_entry *entry;
+    uint64_t now = bpf_ktime_get_ns();
+    uint8_t state = CT_STATE_NONE;
+    
+    key.saddr = saddr;
+    key.daddr = daddr;
+    key.sport = sport;
+    key.dport = dport;
+    key.protocol = protocol;
+    
+    if (protocol == IPPROTO_TCP) {
+        if (tcp_flags & TCP_FLAG_RST)
+            state = CT_STATE_CLOSED;
+        else if (tcp_flags & TCP_FLAG_FIN)
+            state = CT_STATE_FIN_WAIT;
+        else if ((tcp_flags & TCP_FLAG_SYN) && !(tcp_flags & TCP_FLAG_ACK))
+            state = CT_STATE_SYN_SENT;
+        else
+            state = CT_STATE_ESTABLISHED;
+    }
+    
+    entry = bpf_map_lookup_elem(&conntrack_map, &key);
+    if (entry) {
+        __sync_fetch_and_add(&entry->packets, 1);
+        __sync_fetch_and_add(&entry->bytes, len);
+        entry->last_seen = now;
+        entry->state = state;
+    } else {
+        struct ct_entry new_entry = {0};
+        
+        new_entry.state = state;
+        new_entry.packets = 1;
+        new_entry.bytes = len;
+        new_entry.first_seen = now;
+        new_entry.last_seen = now;
+        bpf_map_update_elem(&conntrack_map, &key, &new_entry, BPF_ANY);
+    }
+}
+
+static __always_inline void update_size_hist(uint64_t len)
+{
+    uint32_t key;
+    uint64_t *value;
+    
+    if (len <= 64)
+        key = 0;
+    else if (len <= 128)
+        key = 1;
+    else if (len <= 256)
+        key = 2;
+    else if (len <= 512)
+        key = 3;
+    else if (len <= 1024)
+        key = 4;
+    else if (len <= 1514)
+        key = 5;
+    else
+        key = 6;
+        
+    value = bpf_map_lookup_elem(&size_hist_map, &key);
+    if (value)
+        __sync_fetch_and_add(value, 1);
+        
+    key = SIZE_HIST_SUM_IDX;
+    value = bpf_map_lookup_elem(&size_hist_map, &key);
+    if (value)
+        __sync_fetch_and_add(value, len);
+}
+
+/* 규칙 적중 이벤트 전송 (버퍼가 가득 차면 커널이 버리고 유실 수를 보고) */
+static __always_inline void emit_rule_event(struct xdp_md *ctx, struct prefix_key *key,
+                                            struct filter_rule *rule, uint32_t saddr, uint32_t daddr,
+                                            uint16_t sport, uint16_t dport, uint8_t protocol)
+{
+    struct rule_event event = {0};
+
+    event.timestamp = bpf_ktime_get_ns();
+    event.key = *key;
+    event.saddr = saddr;
+    event.daddr = daddr;
+    event.sport = sport;
+    event.dport = dport;
+    event.action = rule->action;
+    event.protocol = protocol;
+    __builtin_memcpy(event.label, rule->label, sizeof(event.label));
+
+    bpf_perf_event_output(ctx, &rule_events, BPF_F_CURRENT_CPU, &event, sizeof(event));
+}
+
+/* 포트가 규칙의 포트 목록에 있는지 (목록이 비어 있으면 항상 참) */
+static __always_inline bool port_listed(const uint16_t *ports, uint16_t port)
+{
+    if (ports[0] == 0)
+        return true;
+
+    for (int i = 0; i < MAX_RULE_PORTS; i++) {
+        if (ports[i] == 0)
+            break;
+        if (ports[i] == port)
+            return true;
+    }
+    return false;
+}
+
+/* 규칙의 프로토콜, 포트, TCP 플래그 조건 확인 */
+static __always_inline bool rule_matches(struct filter_rule *rule, uint8_t protocol,
+                                         uint16_t src_port, uint16_t dst_port, uint8_t tcp_flags)
+{
+    return (rule->protocol == IPPROTO_ANY || rule->protocol == protocol) &&
+           (rule->src_port_min <= src_port && src_port <= rule->src_port_max) &&
+           port_listed(rule->src_ports, src_port) &&
+           (rule->dst_port_min <= dst_port && dst_port <= rule->dst_port_max) &&
+           port_listed(rule->dst_ports, dst_port) &&
+           ((protocol != IPPROTO_TCP) || ((rule->tcp_flags & tcp_flags) == rule->tcp_flags));
+}
+
+/* 조건이 맞는 소스 IP 없는 규칙 중 우선순위가 가장 높은 규칙 (같으면 앞 슬롯) */
+static __always_inline struct filter_rule *find_wildcard_rule(uint8_t protocol, uint16_t src_port,
+                                                              uint16_t dst_port, uint8_t tcp_flags)
+{
+    struct filter_rule *best = NULL;
+
+    /* 유계 루프 (커널 5.3 이상) */
+    for (uint32_t slot = 0; slot < MAX_WILDCARD_RULES; slot++) {
+        uint32_t key = slot;
+        struct filter_rule *rule = bpf_map_lookup_elem(&wildcard_rules, &key);
+
+        if (!rule || rule->action == 0)
+            continue;
+        if (!rule_matches(rule, protocol, src_port, dst_port, tcp_flags))
+            continue;
+        if (!best || rule->priority > best->priority)
+            best = rule;
+    }
+
+    return best;
+}
+
+/* 규칙의 초당 패킷 한도를 넘었는지 (rate_limit 0은 무제한)
+ * 레이블별 1초 고정 구간으로 센다. 구간을 새로 시작하는 갱신은 CPU 사이에 경합할 수 있어
+ * 구간 경계에서 한도보다 조금 더 통과시킬 수 있다. */
+static __always_inline bool rate_limited(struct filter_rule *rule)
+{
+    struct rate_key key = {0};
+    struct rate_window *window;
+    uint64_t now;
+
+    if (rule->rate_limit == 0)
+        return false;
+
+    __builtin_memcpy(key.label, rule->label, sizeof(key.label));
+    now = bpf_ktime_get_ns();
+    window = bpf_map_lookup_elem(&rate_windows, &key);
+    if (!window || now - window->start >= NSEC_PER_SEC) {
+        struct rate_window fresh = { .start = now, .packets = 1 };
+
+        bpf_map_update_elem(&rate_windows, &key, &fresh, BPF_ANY);
+        return false;
+    }
+
+    return __sync_fetch_and_add(&window->packets, 1) + 1 > rule->rate_limit;
+}
+
+/* 매치된 규칙의 액션 수행 (리디렉션 대상이 없으면 통과)
+ * 한도(rate_limit)를 넘은 패킷은 액션과 관계없이 버리고 매치 카운터에만 센다. */
+static __always_inline int apply_rule(struct xdp_md *ctx, struct prefix_key *key, struct filter_rule *rule,
+                                      uint32_t ip_src, uint32_t ip_dst,
+                                      uint16_t src_port, uint16_t dst_port, uint8_t protocol)
+{
+    if (rate_limited(rule)) {
+        update_stats(&rule->stats, 1, ctx->data_end - ctx->data);
+        return XDP_DROP;
+    }
+
+    switch (rule->action) {
+    case ACTION_DROP:
+        update_stats(&rule->stats, 1, ctx->data_end - ctx->data);
+        emit_rule_event(ctx, key, rule, ip_src, ip_dst, src_port, dst_port, protocol);
+        return XDP_DROP;
+        
+    case ACTION_REDIRECT:
+        /* 매치 카운터는 리디렉션 성공 여부와 관계없이 증가 */
+        update_stats(&rule->stats, 1, ctx->data_end - ctx->data);
+        emit_rule_event(ctx, key, rule, ip_src, ip_dst, src_port, dst_port, protocol);
+        if (rule->redirect_cpu != REDIRECT_CPU_NONE)
+            return count_redirect(&rule->stats, bpf_redirect_map(&cpu_map, rule->redirect_cpu, 0));
+        {
+            uint32_t ifindex = rule->redirect_ifindex;
+            struct if_redirect *redirect;
+            
+            redirect = bpf_map_lookup_elem(&redirect_map, &ifindex);
+            if (redirect && redirect->ifindex > 0)
+                return count_redirect(&rule->stats, bpf_redirect(redirect->ifindex, 0));
+        }
+        /* 대상 인터페이스가 없으면 통과 */
+        __sync_fetch_and_add(&rule->stats.redirect_failures, 1);
+        break;
+        
+    case ACTION_PASS:
+        update_stats(&rule->stats, 1, ctx->data_end - ctx->data);
+        emit_rule_event(ctx, key, rule, ip_src, ip_dst, src_port, dst_port, protocol);
+        return XDP_PASS;
+        
+    case ACTION_COUNT:
+        /* 세기만 하고 통과 */
+        update_stats(&rule->stats, 1, ctx->data_end - ctx->data);
+        return XDP_PASS;
+        
+    default:
+        break;
+    }
+    
+    return XDP_PASS;
+}
+
 static __always_inline int handle_ipv4(struct xdp_md *ctx, void *data, void *data_end)
 {
     /* 이더넷 헤더 추출 */
@@ -174,6 +609,9 @@ static __always_inline int handle_ipv4(struct xdp_md *ctx, void *data, void *dat
     uint16_t dst_port = 0;
     uint8_t tcp_flags = 0;
     
+    /* 프로토콜별 통계 */
+    update_proto_stats(protocol);
+    
     /* 5-tuple 정보 추출 */
     if (protocol == IPPROTO_TCP) {
         struct tcphdr *tcph = (void *)(iph + 1);
@@ -186,6 +624,9 @@ static __always_inline int handle_ipv4(struct xdp_md *ctx, void *data, void *dat
         tcp_flags = (tcph->fin) | (tcph->syn << 1) | (tcph->rst << 2) | 
                     (tcph->psh << 3) | (tcph->ack << 4) | (tcph->urg << 5);
                     
+        if (tcph->syn && !tcph->ack)
+            update_syn_stats(ip_src);
+                    
     } else if (protocol == IPPROTO_UDP) {
         struct udphdr *udph = (void *)(iph + 1);
         
@@ -196,6 +637,10 @@ static __always_inline int handle_ipv4(struct xdp_md *ctx, void *data, void *dat
         dst_port = bpf_ntohs(udph->dest);
     }
     
+    /* 연결 추적 */
+    update_conntrack(ip_src, ip_dst, src_port, dst_port, protocol, tcp_flags,
+                     ctx->data_end - ctx->data);
+    
     /* 필터 룰 확인 */
     struct prefix_key key = {0};
     struct filter_rule *rule;
@@ -203,41 +648,17 @@ static __always_inline int handle_ipv4(struct xdp_md *ctx, void *data, void *dat
     key.prefix_len = 32; // 정확한 IP 매치
     key.addr = ip_src;
     
+    /* 소스 프리픽스 규칙이 우선 (가장 긴 프리픽스) */
     rule = bpf_map_lookup_elem(&filter_rules, &key);
+    if (rule && rule_matches(rule, protocol, src_port, dst_port, tcp_flags))
+        return apply_rule(ctx, &key, rule, ip_src, ip_dst, src_port, dst_port, protocol);
+    
+    /* 프리픽스 규칙이 없거나 조건이 맞지 않으면 소스 IP 없는 규칙 확인 */
+    rule = find_wildcard_rule(protocol, src_port, dst_port, tcp_flags);
     if (rule) {
-        /* 포트 및 프로토콜 매치 확인 */
-        if ((rule->protocol == IPPROTO_ANY || rule->protocol == protocol) &&
-            (rule->src_port_min <= src_port && src_port <= rule->src_port_max) &&
-            (rule->dst_port_min <= dst_port && dst_port <= rule->dst_port_max) &&
-            ((protocol != IPPROTO_TCP) || ((rule->tcp_flags & tcp_flags) == rule->tcp_flags))) {
-            
-            /* 룰에 따른 액션 수행 */
-            switch (rule->action) {
-            case ACTION_DROP:
-                update_stats(&rule->stats, 1, ctx->data_end - ctx->data);
-                return XDP_DROP;
-                
-            case ACTION_REDIRECT:
-                {
-                    uint32_t ifindex = rule->redirect_ifindex;
-                    struct if_redirect *redirect;
-                    
-                    redirect = bpf_map_lookup_elem(&redirect_map, &ifindex);
-                    if (redirect && redirect->ifindex > 0) {
-                        update_stats(&rule->stats, 1, ctx->data_end - ctx->data);
-                        return bpf_redirect(redirect->ifindex, 0);
-                    }
-                }
-                break;
-                
-            case ACTION_PASS:
-                update_stats(&rule->stats, 1, ctx->data_end - ctx->data);
-                return XDP_PASS;
-                
-            default:
-                break;
-            }
-        }
+        key.prefix_len = 0;
+        key.addr = 0;
+        return apply_rule(ctx, &key, rule, ip_src, ip_dst, src_port, dst_port, protocol);
     }
     
     /* 기본적으로 패킷 통과 */
@@ -255,6 +676,9 @@ int xdp_filter_func(struct xdp_md *ctx)
     if ((void *)(eth + 1) > data_end)
         return XDP_PASS;
         
+    /* 패킷 크기 히스토그램 */
+    update_size_hist(data_end - data);
+        
     /* IP 헤더 파싱 */
     if (eth->h_proto == bpf_htons(ETH_P_IP)) {
         return handle_ipv4(ctx, data, data_end);
@@ -265,3 +689,7 @@ int xdp_filter_func(struct xdp_md *ctx)
 }
 
 char _license[] SEC("license") = "GPL";
+
+/* 이 프로그램이 처리하는 매치 기능 (데몬이 로드할 때 ELF 섹션에서 읽음, libbpf는 무시) */
+uint64_t _features SEC("features") = FEATURE_TCP_FLAGS | FEATURE_PORT_LIST | FEATURE_REDIRECT_CPU |
+                                        FEATURE_RATE_LIMIT;
@@ -0,0 +1,447 @@
+//! 규칙 변환 모듈
+//! 다른 방화벽의 규칙 내보내기(iptables-save)를 규칙 집합 문서(YAML)로 변환
+//!
+//! 변환은 모두 클라이언트에서 하며, 옮길 수 없는 줄은 전체를 실패시키지 않고 이유와 함께
+//! 건너뛴 줄로 모은다. 지원 범위는 filter 테이블의 INPUT/FORWARD 체인에서
+//! `-s`/`-d`/`-p`/`--sport`/`--dport`/`-m multiport`/`-m comment`와 `-j DROP|ACCEPT`이다.
+
+use std::collections::HashSet;
+use std::path::Path;
+
+use anyhow::{anyhow, Context, Result};
+use serde::Serialize;
+
+use crate::ruleset::{PortSpec, RuleEntry};
+use swift_guard::utils::MAX_LABEL_LEN;
+
+/// 변환할 규칙 형식
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ConvertSource {
+    /// iptables-save 출력 (IPv4)
+    Iptables,
+}
+
+/// 변환한 규칙 (규칙 집합 문서의 항목과 같은 표기)
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ConvertedRule {
+    pub label: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub src_ip: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dst_ip: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub src_port: Option<PortSpec>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dst_port: Option<PortSpec>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub protocol: Option<String>,
+    pub action: String,
+    pub priority: u32,
+    /// 레이블로 옮기면서 바뀐 원래 주석
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+/// 변환하지 못한 줄
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SkippedLine {
+    /// 줄 번호 (1부터)
+    pub line: usize,
+    pub text: String,
+    pub reason: String,
+}
+
+/// 변환 결과
+#[derive(Debug, Default, Serialize)]
+pub struct Conversion {
+    pub rules: Vec<ConvertedRule>,
+    pub skipped: Vec<SkippedLine>,
+}
+
+impl Conversion {
+    /// 규칙 집합 문서(YAML)로 출력
+    pub fn to_yaml(&self) -> Result<String> {
+        #[derive(Serialize)]
+        struct Document<'a> {
+            rules: &'a [ConvertedRule],
+        }
+
+        serde_yaml::to_string(&Document { rules: &self.rules })
+            .context("Failed to render the converted ruleset")
+    }
+
+    fn skip(&mut self, line: usize, text: &str, reason: impl Into<String>) {
+        self.skipped.push(SkippedLine { line, text: text.to_string(), reason: reason.into() });
+    }
+}
+
+/// 파일을 읽어 변환
+pub fn convert_file(source: ConvertSource, path: &Path) -> Result<Conversion> {
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+
+    match source {
+        ConvertSource::Iptables => Ok(convert_iptables(&text)),
+    }
+}
+
+/// 규칙 한 줄을 해석한 결과 (레이블과 우선순위는 모든 줄을 읽은 뒤 정함)
+struct ParsedRule {
+    line: usize,
+    text: String,
+    chain: String,
+    comment: Option<String>,
+    rule: ConvertedRule,
+}
+
+/// iptables-save 출력 변환
+///
+/// iptables는 위에서부터 처음 맞는 규칙을 쓰므로 앞선 규칙일수록 높은 우선순위를 준다.
+/// 우선순위는 소스 주소가 없는 규칙끼리만 비교되며, 소스 프리픽스가 겹치면 더 구체적인
+/// 프리픽스가 이긴다.
+pub fn convert_iptables(text: &str) -> Conversion {
+    let mut conversion = Conversion::default();
+    let mut parsed = Vec::new();
+    // iptables-save는 항상 *table 줄로 시작하지만 직접 쓴 파일은 filter로 본다
+    let mut table = "filter".to_string();
+
+    for (index, raw) in text.lines().enumerate() {
+        let line = index + 1;
+        let text = raw.trim();
+
+        if text.is_empty() || text.starts_with('#') || text == "COMMIT" {
+            continue;
+        }
+
+        if let Some(name) = text.strip_prefix('*') {
+            table = name.to_string();
+            continue;
+        }
+
+        if let Some(declaration) = text.strip_prefix(':') {
+            // 내장 체인의 기본 정책 DROP은 옮기지 않으므로 알려 줌
+            let mut fields = declaration.split_whitespace();
+            let (chain, policy) = (fields.next().unwrap_or(""), fields.next().unwrap_or("-"));
+            if table == "filter" && policy == "DROP" && matches!(chain, "INPUT" | "FORWARD") {
+                conversion.skip(line, text, "default policy DROP is not converted (add a catch-all drop rule)");
+            }
+            continue;
+        }
+
+        let Some(rule) = text.strip_prefix("-A ") else {
+            conversion.skip(line, text, "unrecognized line (expected iptables-save output)");
+            continue;
+        };
+
+        if table != "filter" {
+            conversion.skip(line, text, format!("table {} is not supported (only filter)", table));
+            continue;
+        }
+
+        match parse_rule(rule) {
+            Ok((chain, comment, rule)) => parsed.push(ParsedRule { line, text: text.to_string(), chain, comment, rule }),
+            Err(e) => conversion.skip(line, text, e.to_string()),
+        }
+    }
+
+    let mut labels = HashSet::new();
+    let total = parsed.len() as u32;
+    for (index, ParsedRule { line, text, chain, comment, mut rule }) in parsed.into_iter().enumerate() {
+        rule.label = unique_label(&mut labels, comment.as_deref(), &chain, line);
+        rule.description = comment.filter(|comment| *comment != rule.label);
+        rule.priority = total - index as u32;
+
+        // 규칙 집합 문서와 같은 검증 (포트 목록 길이 등)
+        match check_rule(&rule) {
+            Ok(()) => conversion.rules.push(rule),
+            Err(e) => {
+                labels.remove(&rule.label);
+                conversion.skip(line, &text, format!("{:#}", e));
+            },
+        }
+    }
+
+    conversion.skipped.sort_by_key(|skipped| skipped.line);
+    conversion
+}
+
+/// `-A` 뒤의 규칙 해석 (체인, 주석, 규칙)
+fn parse_rule(rule: &str) -> Result<(String, Option<String>, ConvertedRule)> {
+    let tokens = tokenize(rule)?;
+    let mut tokens = tokens.iter().map(String::as_str);
+
+    let chain = tokens.next().ok_or_else(|| anyhow!("missing chain name"))?;
+    match chain {
+        "INPUT" | "FORWARD" => {},
+        "OUTPUT" => return Err(anyhow!("chain OUTPUT is not supported (XDP only sees incoming packets)")),
+        _ => return Err(anyhow!("user-defined chain {} is not supported", chain)),
+    }
+
+    let mut rule = ConvertedRule {
+        label: String::new(),
+        src_ip: None,
+        dst_ip: None,
+        src_port: None,
+        dst_port: None,
+        protocol: None,
+        action: String::new(),
+        priority: 0,
+        description: None,
+    };
+    let mut comment = None;
+
+    while let Some(option) = tokens.next() {
+        if option == "!" {
+            return Err(anyhow!("negation ('!') is not supported"));
+        }
+
+        let mut value = || tokens.next().ok_or_else(|| anyhow!("missing value for {}", option));
+        match option {
+            "-s" | "--source" => rule.src_ip = Some(address(value()?)?),
+            "-d" | "--destination" => rule.dst_ip = Some(address(value()?)?),
+            "-p" | "--protocol" => {
+                let protocol = value()?.to_ascii_lowercase();
+                rule.protocol = (protocol != "all").then_some(protocol);
+            },
+            "-m" | "--match" => match value()? {
+                "tcp" | "udp" | "multiport" | "comment" => {},
+                module => return Err(anyhow!("match module {} is not supported", module)),
+            },
+            "--sport" | "--source-port" => rule.src_port = Some(port_range(value()?)?),
+            "--dport" | "--destination-port" => rule.dst_port = Some(port_range(value()?)?),
+            "--sports" | "--source-ports" => rule.src_port = Some(port_list(value()?)?),
+            "--dports" | "--destination-ports" => rule.dst_port = Some(port_list(value()?)?),
+            "--comment" => comment = Some(value()?.to_string()),
+            "-j" | "--jump" => {
+                rule.action = match value()? {
+                    "DROP" => "drop".to_string(),
+                    "ACCEPT" => "pass".to_string(),
+                    target => return Err(anyhow!("target {} is not supported (only DROP and ACCEPT)", target)),
+                };
+            },
+            "-i" | "--in-interface" | "-o" | "--out-interface" => {
+                return Err(anyhow!("interface match {} is not supported", option));
+            },
+            _ => return Err(anyhow!("option {} is not supported", option)),
+        }
+    }
+
+    if rule.action.is_empty() {
+        return Err(anyhow!("rule has no DROP or ACCEPT target"));
+    }
+
+    // XDP 프로그램은 소스 주소가 있는 규칙만 목적지를 비교함
+    if rule.dst_ip.is_some() && rule.src_ip.is_none() {
+        return Err(anyhow!("a destination address (-d) requires a source address (-s)"));
+    }
+
+    Ok((chain.to_string(), comment, rule))
+}
+
+/// 공백으로 나누되 큰따옴표 안은 한 토큰으로 (`\"`와 `\\`는 이스케이프)
+fn tokenize(text: &str) -> Result<Vec<String>> {
+    let mut tokens = Vec::new();
+    let mut chars = text.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+            continue;
+        }
+
+        let mut token = String::new();
+        let mut quoted = false;
+        while let Some(c) = chars.next() {
+            match c {
+                '"' => quoted = !quoted,
+                '\\' if quoted => token.extend(chars.next()),
+                c if c.is_whitespace() && !quoted => break,
+                c => token.push(c),
+            }
+        }
+        if quoted {
+            return Err(anyhow!("unterminated quote"));
+        }
+        tokens.push(token);
+    }
+
+    Ok(tokens)
+}
+
+/// 주소 또는 CIDR (쉼표로 여러 개를 주면 규칙이 여러 개가 되므로 지원하지 않음)
+fn address(value: &str) -> Result<String> {
+    if value.contains(',') {
+        return Err(anyhow!("multiple addresses ({}) are not supported", value));
+    }
+    Ok(value.to_string())
+}
+
+fn port(value: &str) -> Result<u16> {
+    value.parse().map_err(|_| anyhow!("invalid port: {}", value))
+}
+
+/// `--sport`/`--dport` 값 (`22`, `1024:65535`, `:1023`, `1024:`)
+fn port_range(value: &str) -> Result<PortSpec> {
+    let Some((min, max)) = value.split_once(':') else {
+        return Ok(PortSpec::Number(port(value)?));
+    };
+
+    let min = if min.is_empty() { 0 } else { port(min)? };
+    let max = if max.is_empty() { 65535 } else { port(max)? };
+    Ok(PortSpec::Range(format!("{}-{}", min, max)))
+}
+
+/// `-m multiport`의 `--sports`/`--dports` 값 (범위가 섞인 목록은 지원하지 않음)
+fn port_list(value: &str) -> Result<PortSpec> {
+    if value.contains(':') {
+        return Err(anyhow!("port ranges in a multiport list ({}) are not supported", value));
+    }
+    Ok(PortSpec::List(value.split(',').map(port).collect::<Result<_>>()?))
+}
+
+/// 주석이 있으면 주석에서, 없으면 체인과 줄 번호로 레이블을 만들고 겹치면 줄 번호를 붙임
+fn unique_label(labels: &mut HashSet<String>, comment: Option<&str>, chain: &str, line: usize) -> String {
+    let fallback = format!("{}-{}", chain.to_ascii_lowercase(), line);
+    let mut label = comment.map(sanitize_label)
+        .filter(|label| label.chars().any(|c| c.is_ascii_alphanumeric()))
+       .unwrap_or(fallback);
+
+    if labels.contains(&label) {
+        let suffix = format!("-{}", line);
+        label.truncate(MAX_LABEL_LEN - suffix.len());
+        label.push_str(&suffix);
+    }
+    labels.insert(label.clone());
+    label
+}
+
+/// 레이블에 쓸 수 없는 문자는 '-'로 바꾸고 최대 길이로 자름
+fn sanitize_label(comment: &str) -> String {
+    let mut label: String = comment.trim()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') { c } else { '-' })
+        .collect();
+    label.truncate(MAX_LABEL_LEN);
+    label
+}
+
+/// 규칙 집합 항목으로 읽어 검증
+fn check_rule(rule: &ConvertedRule) -> Result<()> {
+    let value = serde_yaml::to_value(rule)?;
+    let entry: RuleEntry = serde_yaml::from_value(value)?;
+    entry.to_spec()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ruleset::parse_ruleset;
+
+    const BASIC: &str = include_str!("../testdata/iptables/basic.v4");
+    const UNSUPPORTED: &str = include_str!("../testdata/iptables/unsupported.v4");
+
+    #[test]
+    fn test_convert_basic() {
+        let conversion = convert_iptables(BASIC);
+        assert!(conversion.skipped.is_empty(), "{:?}", conversion.skipped);
+
+        let labels: Vec<_> = conversion.rules.iter().map(|rule| rule.label.as_str()).collect();
+        assert_eq!(labels, ["input-6", "office-ssh", "block-ssh", "input-9", "input-10", "forward-11"]);
+
+        // 앞선 규칙일수록 높은 우선순위
+        let priorities: Vec<_> = conversion.rules.iter().map(|rule| rule.priority).collect();
+        assert_eq!(priorities, [6, 5, 4, 3, 2, 1]);
+
+        let rules = &conversion.rules;
+        assert_eq!((rules[0].src_ip.as_deref(), rules[0].protocol.as_deref(), rules[0].action.as_str()),
+            (Some("203.0.113.0/24"), None, "drop"));
+        assert_eq!(rules[1].src_ip.as_deref(), Some("198.51.100.7/32"));
+        assert_eq!(rules[1].description.as_deref(), Some("office ssh"));
+        assert_eq!(rules[1].action, "pass");
+        assert_eq!(rules[2].description, None);
+
+        // YAML로 내보내 규칙 집합으로 다시 읽을 수 있음
+        let specs = parse_ruleset(&conversion.to_yaml().unwrap()).unwrap();
+        assert_eq!(specs.len(), 6);
+        assert_eq!((specs[1].protocol, specs[1].dst_port_min, specs[1].dst_port_max), (6, 22, 22));
+        assert_eq!(specs[3].dst_port_list, vec![80, 443, 8080]);
+        assert_eq!((specs[4].protocol, specs[4].src_port_min, specs[4].src_port_max), (17, 1024, 65535));
+        assert_eq!((specs[4].dst_port_min, specs[4].dst_port_max), (53, 53));
+        assert_eq!(specs[5].dst_ip.as_deref(), Some("10.0.0.0/8"));
+        assert_eq!(specs[5].src_port_list, vec![19, 123, 1900]);
+        assert_eq!(specs[5].action, 2);
+    }
+
+    #[test]
+    fn test_convert_unsupported() {
+        let conversion = convert_iptables(UNSUPPORTED);
+
+        let labels: Vec<_> = conversion.rules.iter().map(|rule| rule.label.as_str()).collect();
+        assert_eq!(labels, ["admin-panel"]);
+
+        let skipped: Vec<_> = conversion.skipped.iter()
+            .map(|skipped| (skipped.line, skipped.reason.as_str()))
+            .collect();
+        assert_eq!(skipped, [
+            (5, "table nat is not supported (only filter)"),
+            (8, "default policy DROP is not converted (add a catch-all drop rule)"),
+            (12, "interface match -i is not supported"),
+            (13, "match module conntrack is not supported"),
+            (14, "negation ('!') is not supported"),
+            (15, "negation ('!') is not supported"),
+            (16, "port ranges in a multiport list (6000:6063,7000) are not supported"),
+            (17, "multiple addresses (10.1.0.0/16,10.2.0.0/16) are not supported"),
+            (18, "target REJECT is not supported (only DROP and ACCEPT)"),
+            (19, "a destination address (-d) requires a source address (-s)"),
+            (20, "target LOGDROP is not supported (only DROP and ACCEPT)"),
+            (21, "unterminated quote"),
+            (23, "user-defined chain LOGDROP is not supported"),
+            (24, "chain OUTPUT is not supported (XDP only sees incoming packets)"),
+        ]);
+        assert_eq!(conversion.skipped[2].text, "-A INPUT -i lo -j ACCEPT");
+    }
+
+    #[test]
+    fn test_convert_validates_rules() {
+        // 포트 목록은 8개까지
+        let conversion = convert_iptables("-A INPUT -p tcp -m multiport --dports 1,2,3,4,5,6,7,8,9 -j DROP\n\
+                                           -A INPUT -m multiport --dports 80,443 -j DROP\n\
+                                           -A INPUT -p tcp --dport 70000 -j DROP\n");
+        assert!(conversion.rules.is_empty());
+        assert!(conversion.skipped[0].reason.contains("Too many ports in list"), "{:?}", conversion.skipped);
+        assert!(conversion.skipped[1].reason.contains("requires the tcp or udp protocol"));
+        assert_eq!(conversion.skipped[2].reason, "invalid port: 70000");
+    }
+
+    #[test]
+    fn test_labels() {
+        let conversion = convert_iptables(r#"
+-A INPUT -s 10.0.0.1 -m comment --comment "web" -j DROP
+-A INPUT -s 10.0.0.2 -m comment --comment "web" -j DROP
+-A INPUT -s 10.0.0.3 -m comment --comment "a very long comment that is \"quoted\" and overflows" -j DROP
+-A INPUT -s 10.0.0.4 -m comment --comment "!!!" -j DROP
+"#);
+        let labels: Vec<_> = conversion.rules.iter().map(|rule| rule.label.as_str()).collect();
+        assert_eq!(labels, ["web", "web-3", "a-very-long-comment-that-is--qu", "input-5"]);
+        assert_eq!(conversion.rules[2].description.as_deref(),
+            Some("a very long comment that is \"quoted\" and overflows"));
+    }
+
+    #[test]
+    fn test_tokenize() {
+        assert_eq!(tokenize(r#"INPUT -m comment --comment "a \"b\" c" -j DROP"#).unwrap(),
+            ["INPUT", "-m", "comment", "--comment", "a \"b\" c", "-j", "DROP"]);
+        assert_eq!(tokenize("  INPUT   -j  DROP ").unwrap(), ["INPUT", "-j", "DROP"]);
+        assert!(tokenize(r#"INPUT --comment "open"#).is_err());
+    }
+
+    #[test]
+    fn test_port_range() {
+        assert!(matches!(port_range("22").unwrap(), PortSpec::Number(22)));
+        assert!(matches!(port_range("1024:2048").unwrap(), PortSpec::Range(range) if range == "1024-2048"));
+        assert!(matches!(port_range(":1023").unwrap(), PortSpec::Range(range) if range == "0-1023"));
+        assert!(matches!(port_range("1024:").unwrap(), PortSpec::Range(range) if range == "1024-65535"));
+        assert!(port_range("ssh").is_err());
+    }
+}
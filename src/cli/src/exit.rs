@@ -0,0 +1,191 @@
+//! 종료 코드 모듈
+//! CLI 오류를 스크립트에서 구분할 수 있는 종료 코드로 변환
+
+use std::fmt;
+use std::process::ExitCode;
+
+use crate::api::{ApiResponse, ClientError};
+use crate::output::Output;
+use swift_guard::error::ErrorCode;
+
+/// `--help`에 표시되는 종료 코드 설명
+pub const EXIT_CODES_HELP: &str = "\
+Exit codes:
+  0   success
+  2   invalid arguments (parse or validation failure)
+  3   daemon unreachable or timed out
+  4   not found
+  5   already exists
+  6   permission denied
+  7   rate limited by the daemon
+  10  internal daemon error";
+
+/// 종료 코드 종류
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExitKind {
+    InvalidArgument = 2,
+    Unreachable = 3,
+    NotFound = 4,
+    AlreadyExists = 5,
+    PermissionDenied = 6,
+    RateLimited = 7,
+    Internal = 10,
+}
+
+impl ExitKind {
+    /// `--output jsonl` 오류 봉투의 코드 이름
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::InvalidArgument => "invalid_argument",
+            Self::Unreachable => "unreachable",
+            Self::NotFound => "not_found",
+            Self::AlreadyExists => "already_exists",
+            Self::PermissionDenied => "permission_denied",
+            Self::RateLimited => "rate_limited",
+            Self::Internal => "internal",
+        }
+    }
+}
+
+impl From<ErrorCode> for ExitKind {
+    fn from(code: ErrorCode) -> Self {
+        match code {
+            ErrorCode::InvalidArgument => Self::InvalidArgument,
+            ErrorCode::NotFound => Self::NotFound,
+            ErrorCode::AlreadyExists => Self::AlreadyExists,
+            ErrorCode::PermissionDenied => Self::PermissionDenied,
+            ErrorCode::Internal => Self::Internal,
+        }
+    }
+}
+
+/// 종료 코드가 지정된 CLI 오류
+#[derive(Debug)]
+pub struct CliError {
+    pub kind: ExitKind,
+    pub message: String,
+}
+
+impl CliError {
+    /// 새로운 오류 생성
+    pub fn new(kind: ExitKind, message: impl Into<String>) -> Self {
+        Self {
+            kind,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for CliError {}
+
+impl From<&ClientError> for ExitKind {
+    fn from(error: &ClientError) -> Self {
+        match error {
+            ClientError::Unreachable(_) => Self::Unreachable,
+            ClientError::PermissionDenied(_) => Self::PermissionDenied,
+            ClientError::InvalidConfig(_) => Self::InvalidArgument,
+            ClientError::Protocol(_) => Self::Internal,
+            ClientError::RateLimited { .. } => Self::RateLimited,
+            ClientError::Api { code, .. } => (*code).into(),
+        }
+    }
+}
+
+/// 오류 응답(또는 예상하지 못한 응답)을 CLI 오류로 변환
+pub fn response_error(response: ApiResponse) -> anyhow::Error {
+    ClientError::from_response(response).into()
+}
+
+/// 오류 분류
+///
+/// 명시적으로 분류되지 않은 오류는 요청 전 CLI에서 발생한 인자 파싱/검증 오류로 본다.
+pub fn classify(error: &anyhow::Error) -> ExitKind {
+    error.chain()
+        .find_map(|cause| match cause.downcast_ref::<CliError>() {
+            Some(e) => Some(e.kind),
+            None => cause.downcast_ref::<ClientError>().map(ExitKind::from),
+        })
+        .unwrap_or(ExitKind::InvalidArgument)
+}
+
+/// 실행 결과를 종료 코드로 변환 (오류는 stderr로, jsonl 모드는 봉투로 stdout에 출력)
+pub fn finish(result: anyhow::Result<()>, out: &Output) -> ExitCode {
+    match result {
+        Ok(()) => {
+            if out.is_jsonl() {
+                if let Some(line) = out.success_line() {
+                    println!("{}", line);
+                }
+            }
+            ExitCode::SUCCESS
+        },
+        Err(error) => {
+            let kind = classify(&error);
+            if out.is_jsonl() {
+                println!("{}", out.failure_line(kind, &error));
+            } else {
+                eprintln!("Error: {:#}", error);
+            }
+            ExitCode::from(kind as u8)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::{anyhow, Context};
+
+    #[test]
+    fn test_classify_response_errors() {
+        let not_found = response_error(ApiResponse::NotFound {
+            resource: "rule".to_string(),
+            name: "web".to_string(),
+        });
+        assert_eq!(classify(&not_found), ExitKind::NotFound);
+        assert_eq!(not_found.to_string(), "rule 'web' not found");
+
+        let exists = response_error(ApiResponse::Error {
+            message: "exists".to_string(),
+            code: ErrorCode::AlreadyExists,
+        });
+        assert_eq!(classify(&exists), ExitKind::AlreadyExists);
+
+        let limited = response_error(ApiResponse::RateLimited { retry_after_ms: 250, limit: 4 });
+        assert_eq!(classify(&limited), ExitKind::RateLimited);
+        assert_eq!(limited.to_string(), "Rate limited by daemon (4 requests/sec), retry after 250 ms");
+
+        let partial = response_error(ApiResponse::PartialResults {
+            returned: 2,
+            total: 9,
+            hint: "use pagination".to_string(),
+            results: Box::new(ApiResponse::Rules { rules: Vec::new(), total_count: 9, next_cursor: None, seq: None }),
+        });
+        assert_eq!(classify(&partial), ExitKind::Internal);
+        assert_eq!(partial.to_string(), "Response exceeded the size limit, daemon returned 2 of 9 items (use pagination)");
+
+        let unexpected = response_error(ApiResponse::Rules { rules: Vec::new(), total_count: 0, next_cursor: None, seq: None });
+        assert_eq!(classify(&unexpected), ExitKind::Internal);
+    }
+
+    #[test]
+    fn test_classify_through_context() {
+        let error: anyhow::Result<()> = Err(CliError::new(ExitKind::Unreachable, "refused").into());
+        let error = error.context("Failed to send request").unwrap_err();
+        assert_eq!(classify(&error), ExitKind::Unreachable);
+
+        assert_eq!(classify(&anyhow!("Invalid action: foo")), ExitKind::InvalidArgument);
+
+        // 라이브러리 클라이언트의 전송 오류
+        let error: anyhow::Result<()> = Err(ClientError::PermissionDenied("denied".to_string()).into());
+        let error = error.context("Failed to send request").unwrap_err();
+        assert_eq!(classify(&error), ExitKind::PermissionDenied);
+        assert_eq!(classify(&ClientError::Protocol("bad frame".to_string()).into()), ExitKind::Internal);
+    }
+}
@@ -0,0 +1,339 @@
+//! 다중 데몬 모듈
+//! 같은 요청을 여러 API 서버에 동시에 보내고 서버별 결과를 모아 요약
+//!
+//! `--api-server`에 쉼표로 구분한 주소를 주거나 `--servers-file`로 주소 목록을 주면 변경 명령은
+//! 모든 서버에 동시에(최대 `--parallel`개씩) 보내고 서버별 결과(HOST, STATUS, MESSAGE)를 표로
+//! 출력한다. 한 서버라도 실패하면 첫 실패의 종료 코드로 끝난다. 조회 명령은 `list-rules --merge`만
+//! 지원하며, 모든 서버의 규칙을 HOST 열과 함께 합쳐 보여준다.
+//!
+//! 클라이언트 요청은 `Send`가 아니므로 작업은 현재 스레드의 `LocalSet`에서 함께 실행한다.
+
+use std::fs;
+use std::future::Future;
+use std::path::Path;
+use std::rc::Rc;
+use std::time::Duration;
+
+use anyhow::{anyhow, Context, Result};
+use serde::Serialize;
+use tokio::sync::Semaphore;
+use tokio::task::{JoinSet, LocalSet};
+
+use crate::api::{ApiResponse, RuleInfo};
+use crate::exit::{self, CliError, ExitKind};
+use crate::output::Output;
+use crate::ruleset;
+use crate::table;
+
+/// 쉼표로 구분한 API 서버 주소 목록 파싱
+pub fn parse_servers(list: &str) -> Result<Vec<String>> {
+    check_servers(list.split(',').map(str::trim).filter(|addr| !addr.is_empty()).map(str::to_string).collect())
+        .context("Invalid --api-server")
+}
+
+/// API 서버 목록 파일 읽기 (한 줄에 주소 하나, `#` 뒤는 주석, 빈 줄 무시)
+pub fn read_servers_file(path: &Path) -> Result<Vec<String>> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    let servers = content.lines()
+        .map(|line| line.split('#').next().unwrap_or_default().trim())
+        .filter(|addr| !addr.is_empty())
+        .map(str::to_string)
+        .collect();
+    check_servers(servers).with_context(|| format!("Invalid servers file {}", path.display()))
+}
+
+/// 주소 목록 확인 (비어 있거나 같은 주소가 두 번 나오면 오류)
+fn check_servers(servers: Vec<String>) -> Result<Vec<String>> {
+    if servers.is_empty() {
+        return Err(anyhow!("No API server given"));
+    }
+    for (i, addr) in servers.iter().enumerate() {
+        if servers[..i].contains(addr) {
+            return Err(anyhow!("API server {} is listed twice", addr));
+        }
+    }
+    Ok(servers)
+}
+
+/// 서버 하나의 결과
+#[derive(Debug)]
+pub struct HostResult<T> {
+    pub host: String,
+    pub outcome: Result<T>,
+}
+
+/// 여러 서버에 작업을 동시에 실행하는 설정
+#[derive(Debug, Clone, Copy)]
+pub struct FanOut {
+    /// 동시에 실행할 최대 작업 수
+    parallelism: usize,
+    /// 서버마다 작업을 기다릴 최대 시간 (차례를 기다린 시간 제외)
+    timeout: Duration,
+}
+
+impl FanOut {
+    /// 새로운 설정 생성 (동시 작업 수는 최소 1)
+    pub fn new(parallelism: usize, timeout: Duration) -> Self {
+        Self { parallelism: parallelism.max(1), timeout }
+    }
+
+    /// 대상마다 작업을 실행하고 결과를 대상 순서대로 반환
+    ///
+    /// 시간 제한을 넘긴 작업은 취소하고 `Unreachable` 오류로 기록한다.
+    pub async fn run<C, T, F, Fut>(&self, targets: Vec<(String, C)>, task: F) -> Vec<HostResult<T>>
+    where
+        C: 'static,
+        T: 'static,
+        F: Fn(C) -> Fut,
+        Fut: Future<Output = Result<T>> + 'static,
+    {
+        let permits = Rc::new(Semaphore::new(self.parallelism));
+        let timeout = self.timeout;
+        let mut hosts = Vec::with_capacity(targets.len());
+        let mut outcomes: Vec<Option<Result<T>>> = Vec::with_capacity(targets.len());
+
+        LocalSet::new().run_until(async {
+            let mut tasks = JoinSet::new();
+            for (index, (host, target)) in targets.into_iter().enumerate() {
+                let permits = permits.clone();
+                let work = task(target);
+                tasks.spawn_local(async move {
+                    // 세마포어를 닫지 않으므로 허가는 항상 얻음
+                    let _permit = permits.acquire().await.expect("semaphore is never closed");
+                    let outcome = match tokio::time::timeout(timeout, work).await {
+                        Ok(outcome) => outcome,
+                        Err(_) => Err(CliError::new(ExitKind::Unreachable,
+                            format!("No response within {:?}", timeout)).into()),
+                    };
+                    (index, outcome)
+                });
+                hosts.push(host);
+                outcomes.push(None);
+            }
+
+            while let Some(joined) = tasks.join_next().await {
+                let (index, outcome) = joined.expect("fan-out task panicked");
+                outcomes[index] = Some(outcome);
+            }
+        }).await;
+
+        hosts.into_iter().zip(outcomes)
+            .map(|(host, outcome)| HostResult { host, outcome: outcome.expect("every task reports a result") })
+            .collect()
+    }
+}
+
+/// 변경 명령 응답의 한 줄 요약 (오류 응답이나 일부만 적용된 결과는 오류)
+pub fn response_message(response: ApiResponse) -> Result<String> {
+    match response {
+        ApiResponse::Success { message, warning: None } => Ok(message),
+        ApiResponse::Success { message, warning: Some(warning) } => Ok(format!("{} (warning: {})", message, warning)),
+        ApiResponse::RulesetApplied { summary } => {
+            let counts = ruleset::render_summary(&summary, false).pop().unwrap_or_default();
+            match summary.errors.first() {
+                Some(first) => Err(CliError::new(first.code.into(), format!("{} rule(s) could not be applied ({})",
+                    summary.errors.len(), counts)).into()),
+                None => Ok(counts),
+            }
+        },
+        ApiResponse::RulesRepaired { repaired, errors } => match errors.first() {
+            Some(first) => Err(CliError::new(first.code.into(), format!("{} rule(s) could not be repaired ({} repaired)",
+                errors.len(), repaired.len())).into()),
+            None => Ok(format!("{} repaired", repaired.len())),
+        },
+        ApiResponse::CountryBlocks { blocks } => {
+            let failed: Vec<&str> = blocks.iter().filter(|block| block.error.is_some()).map(|block| block.code.as_str()).collect();
+            if failed.is_empty() {
+                Ok(format!("{} country block(s)", blocks.len()))
+            } else {
+                Err(anyhow!("Failed to refresh country block(s): {}", failed.join(", ")))
+            }
+        },
+        other => Err(exit::response_error(other)),
+    }
+}
+
+/// 서버별 결과 요약 (표의 행, `--output jsonl` 봉투의 data 항목)
+#[derive(Debug, Serialize)]
+pub struct HostSummary {
+    pub host: String,
+    /// `ok` 또는 `failed`
+    pub status: &'static str,
+    pub message: String,
+}
+
+/// 요약 메시지 결과를 요약 행으로 변환
+pub fn summarize(results: &[HostResult<String>]) -> Vec<HostSummary> {
+    results.iter()
+        .map(|result| match &result.outcome {
+            Ok(message) => HostSummary { host: result.host.clone(), status: "ok", message: message.clone() },
+            Err(error) => HostSummary { host: result.host.clone(), status: "failed", message: format!("{:#}", error) },
+        })
+        .collect()
+}
+
+/// 실패한 서버가 있으면 오류 (종료 코드는 첫 실패의 종류)
+pub fn check<T>(results: &[HostResult<T>]) -> Result<()> {
+    let failed: Vec<&HostResult<T>> = results.iter().filter(|result| result.outcome.is_err()).collect();
+    let Some(Err(first)) = failed.first().map(|result| &result.outcome) else {
+        return Ok(());
+    };
+    let hosts: Vec<&str> = failed.iter().map(|result| result.host.as_str()).collect();
+    Err(CliError::new(exit::classify(first),
+        format!("{} of {} API servers failed: {}", failed.len(), results.len(), hosts.join(", "))).into())
+}
+
+/// 변경 명령의 서버별 결과 출력 (text 모드는 표, jsonl 모드는 요약 행 목록)
+pub fn print_results(out: &mut Output, results: &[HostResult<String>]) -> Result<()> {
+    let summaries = summarize(results);
+    if out.is_jsonl() {
+        out.json(&summaries)?;
+    } else {
+        for line in table::host_results_table(&summaries).render() {
+            println!("{}", line);
+        }
+    }
+    check(results)
+}
+
+/// 서버 주소가 붙은 규칙 (`list-rules --merge --format json`의 항목)
+#[derive(Debug, Serialize)]
+pub struct HostRule<'a> {
+    pub host: &'a str,
+    #[serde(flatten)]
+    pub rule: &'a RuleInfo,
+}
+
+/// 조회에 성공한 서버의 규칙을 서버 순서대로 합침
+pub fn merge_rules(results: &[HostResult<Vec<RuleInfo>>]) -> Vec<HostRule<'_>> {
+    results.iter()
+        .filter_map(|result| result.outcome.as_ref().ok().map(|rules| (result.host.as_str(), rules)))
+        .flat_map(|(host, rules)| rules.iter().map(move |rule| HostRule { host, rule }))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use swift_guard::api::{ApplySummary, RuleError};
+    use swift_guard::error::ErrorCode;
+
+    fn targets(delays_ms: &[u64]) -> Vec<(String, u64)> {
+        delays_ms.iter().enumerate().map(|(i, delay)| (format!("10.0.0.{}:7654", i + 1), *delay)).collect()
+    }
+
+    #[test]
+    fn test_parse_servers() {
+        assert_eq!(parse_servers("10.0.0.1:7654, 10.0.0.2:7654,,").unwrap(), vec!["10.0.0.1:7654", "10.0.0.2:7654"]);
+        assert_eq!(parse_servers("unix:/run/swift-guard.sock").unwrap(), vec!["unix:/run/swift-guard.sock"]);
+        assert!(parse_servers(" , ").is_err());
+        assert_eq!(format!("{:#}", parse_servers("a:1,b:1,a:1").unwrap_err()),
+            "Invalid --api-server: API server a:1 is listed twice");
+    }
+
+    #[test]
+    fn test_read_servers_file() {
+        let path = std::env::temp_dir().join(format!("swift-guard-servers-{}.txt", std::process::id()));
+        fs::write(&path, "# edge nodes\nedge-1:7654\n\n  edge-2:7654  # rack 2\n").unwrap();
+        assert_eq!(read_servers_file(&path).unwrap(), vec!["edge-1:7654", "edge-2:7654"]);
+
+        fs::write(&path, "# nothing yet\n").unwrap();
+        assert!(read_servers_file(&path).is_err());
+        fs::remove_file(&path).unwrap();
+        assert!(read_servers_file(&path).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_run_keeps_order_and_times_out() {
+        let fan_out = FanOut::new(4, Duration::from_millis(200));
+        let results = fan_out.run(targets(&[50, 0, 5_000, 10]), |delay| async move {
+            tokio::time::sleep(Duration::from_millis(delay)).await;
+            match delay {
+                10 => Err(anyhow!("rule 'web' not found")),
+                _ => Ok(delay),
+            }
+        }).await;
+
+        let hosts: Vec<&str> = results.iter().map(|result| result.host.as_str()).collect();
+        assert_eq!(hosts, ["10.0.0.1:7654", "10.0.0.2:7654", "10.0.0.3:7654", "10.0.0.4:7654"]);
+        assert_eq!(results[0].outcome.as_ref().unwrap(), &50);
+        assert_eq!(results[1].outcome.as_ref().unwrap(), &0);
+
+        let timed_out = results[2].outcome.as_ref().unwrap_err();
+        assert_eq!(timed_out.to_string(), "No response within 200ms");
+        assert_eq!(exit::classify(timed_out), ExitKind::Unreachable);
+        assert_eq!(results[3].outcome.as_ref().unwrap_err().to_string(), "rule 'web' not found");
+
+        let error = check(&results).unwrap_err();
+        assert_eq!(error.to_string(), "2 of 4 API servers failed: 10.0.0.3:7654, 10.0.0.4:7654");
+        assert_eq!(exit::classify(&error), ExitKind::Unreachable);
+        assert!(check(&results[..2]).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_run_bounds_parallelism() {
+        let running = Rc::new(Cell::new(0));
+        let peak = Rc::new(Cell::new(0));
+        let fan_out = FanOut::new(2, Duration::from_secs(5));
+        let results = fan_out.run(targets(&[20; 6]), |delay| {
+            let (running, peak) = (running.clone(), peak.clone());
+            async move {
+                running.set(running.get() + 1);
+                peak.set(peak.get().max(running.get()));
+                tokio::time::sleep(Duration::from_millis(delay)).await;
+                running.set(running.get() - 1);
+                Ok(())
+            }
+        }).await;
+
+        assert_eq!(results.len(), 6);
+        assert!(results.iter().all(|result| result.outcome.is_ok()));
+        assert_eq!(peak.get(), 2);
+    }
+
+    #[test]
+    fn test_response_message() {
+        let success = ApiResponse::Success { message: "Rule 'web' added successfully".to_string(), warning: None };
+        assert_eq!(response_message(success).unwrap(), "Rule 'web' added successfully");
+
+        let summary = ApplySummary {
+            added: vec!["web".to_string()],
+            unchanged: vec!["ssh".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(response_message(ApiResponse::RulesetApplied { summary }).unwrap(),
+            "1 added, 0 updated, 0 deleted, 1 unchanged, 0 failed");
+
+        let summary = ApplySummary {
+            errors: vec![RuleError { label: "dns".to_string(), message: "map full".to_string(), code: ErrorCode::Internal }],
+            ..Default::default()
+        };
+        let error = response_message(ApiResponse::RulesetApplied { summary }).unwrap_err();
+        assert_eq!(error.to_string(), "1 rule(s) could not be applied (0 added, 0 updated, 0 deleted, 0 unchanged, 1 failed)");
+        assert_eq!(exit::classify(&error), ExitKind::Internal);
+
+        let error = response_message(ApiResponse::NotFound { resource: "rule".to_string(), name: "web".to_string() }).unwrap_err();
+        assert_eq!(exit::classify(&error), ExitKind::NotFound);
+    }
+
+    #[test]
+    fn test_summarize() {
+        let results = vec![
+            HostResult { host: "edge-1:7654".to_string(), outcome: Ok("Rule 'web' deleted".to_string()) },
+            HostResult { host: "edge-2:7654".to_string(), outcome: Err(anyhow!("refused").context("Failed to send delete rule request")) },
+        ];
+        let summaries = summarize(&results);
+        assert_eq!(serde_json::to_value(&summaries).unwrap(), serde_json::json!([
+            { "host": "edge-1:7654", "status": "ok", "message": "Rule 'web' deleted" },
+            { "host": "edge-2:7654", "status": "failed", "message": "Failed to send delete rule request: refused" },
+        ]));
+        assert_eq!(table::host_results_table(&summaries).render(), vec![
+            "HOST         STATUS  MESSAGE",
+            "----------------------------------------------------------------",
+            "edge-1:7654  ok      Rule 'web' deleted",
+            "edge-2:7654  failed  Failed to send delete rule request: refused",
+        ]);
+    }
+}
@@ -4,34 +4,126 @@
 use anyhow::{anyhow, Context, Result};
 use clap::{Parser, Subcommand};
 //use ipnet::IpNet;
-use log::{debug, error, info};
-use serde::Serialize;
-use std::net::IpAddr;
+use log::debug;
 use std::path::PathBuf;
-use tokio::net::TcpStream;
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use std::process::ExitCode;
+use std::rc::Rc;
+use std::time::Duration;
 
 mod api;
+mod convert;
+mod exit;
+mod fanout;
+mod output;
+mod profile;
+mod ruleset;
+mod style;
+mod table;
 mod utils;
+mod watch;
 
-use api::{ApiClient, ApiRequest, ApiResponse};
-use utils::parse_port_range;
+use api::{
+    AddressFamily, ApiRequest, ApiResponse, ClientError, ConnectionFilter, FallbackMode, ReconnectPolicy, Reconnector, SwiftGuardClient,
+    WasmErrorPolicy, WasmModuleMode,
+};
+use swift_guard::build_info::BuildInfo;
+use swift_guard::types::XdpFeatures;
+use output::{Output, OutputMode};
+use profile::{CliConfig, ConnectionFlags, Profile, Settings};
+use utils::{parse_port_range, render_histogram};
 
 #[derive(Parser, Debug)]
-#[clap(name = "xdp-filter", about = "XDP Filtering Tool", version)]
+#[clap(name = "xdp-filter", about = "XDP Filtering Tool", version,
+    long_version = swift_guard::build_info::LONG_VERSION, after_help = exit::EXIT_CODES_HELP)]
 struct Cli {
-    /// API 서버 주소
-    #[clap(long, default_value = "127.0.0.1:7654")]
-    api_server: String,
+    /// API 서버 주소 (기본값: 127.0.0.1:7654, 쉼표로 구분해 여러 서버 지정)
+    #[clap(long)]
+    api_server: Option<String>,
+
+    /// API 서버 주소를 한 줄에 하나씩 적은 파일 (# 뒤는 주석)
+    #[clap(long, value_name = "PATH", conflicts_with = "api_server")]
+    servers_file: Option<PathBuf>,
+
+    /// 여러 API 서버에 동시에 보낼 최대 요청 수
+    #[clap(long, default_value = "8")]
+    parallel: usize,
+
+    /// 여러 API 서버에 보낼 때 서버마다 기다릴 최대 시간 (예: 30s, 2m)
+    #[clap(long, default_value = "30s", value_parser = swift_guard::utils::parse_duration)]
+    host_timeout: u64,
+
+    /// 계속 실행되는 명령(stats, monitor, list-rules --watch)이 연결이 끊긴 뒤 다시 연결을 시도할 시간 (0이면 바로 종료)
+    #[clap(long, default_value = "30s", value_parser = swift_guard::utils::parse_duration)]
+    reconnect_window: u64,
+
+    /// 연결 설정을 가져올 CLI 설정 파일 프로필
+    #[clap(long)]
+    profile: Option<String>,
+
+    /// CLI 설정 파일 (기본값: ~/.config/swift-guard/cli.yaml)
+    #[clap(long, value_name = "PATH")]
+    config: Option<PathBuf>,
+
+    /// TLS로 API 서버에 연결
+    #[clap(long)]
+    tls: bool,
+
+    /// 서버 인증서를 검증할 CA 인증서 (PEM)
+    #[clap(long, value_name = "PATH")]
+    ca_cert: Option<PathBuf>,
+
+    /// 상호 TLS용 클라이언트 인증서 (PEM)
+    #[clap(long, value_name = "PATH", requires = "client_key")]
+    client_cert: Option<PathBuf>,
+
+    /// 상호 TLS용 클라이언트 개인 키 (PEM)
+    #[clap(long, value_name = "PATH", requires = "client_cert")]
+    client_key: Option<PathBuf>,
+
+    /// 규칙 네임스페이스 (생략 시 토큰의 네임스페이스, 환경 변수 SWIFT_GUARD_NAMESPACE)
+    #[clap(long)]
+    namespace: Option<String>,
+
+    /// API 토큰 (환경 변수 SWIFT_GUARD_TOKEN)
+    #[clap(long)]
+    token: Option<String>,
 
     /// 상세 로깅
     #[clap(short, long)]
     verbose: bool,
 
+    /// stdout 출력 모드 (jsonl: 명령마다 한 줄짜리 JSON 봉투, 오류 포함)
+    #[clap(long, value_enum, default_value = "text", global = true)]
+    output: OutputMode,
+
+    /// 색상 사용 (auto: 터미널이고 NO_COLOR가 없을 때)
+    #[clap(long, value_enum, default_value = "auto", global = true)]
+    color: style::ColorChoice,
+
+    /// 색상 끄기 (--color보다 우선)
+    #[clap(long, global = true)]
+    no_color: bool,
+
     #[clap(subcommand)]
     command: Commands,
 }
 
+impl Cli {
+    /// 명령줄에서 직접 지정한 연결 옵션
+    fn connection_flags(&self) -> ConnectionFlags {
+        ConnectionFlags {
+            profile: self.profile.clone(),
+            api_server: self.api_server.clone(),
+            tls: self.tls,
+            ca_cert: self.ca_cert.clone(),
+            client_cert: self.client_cert.clone(),
+            client_key: self.client_key.clone(),
+            namespace: self.namespace.clone(),
+            token: self.token.clone(),
+        }
+    }
+}
+
 #[derive(Subcommand, Debug)]
 enum Commands {
     /// XDP 프로그램을 인터페이스에 연결
@@ -46,6 +138,14 @@ enum Commands {
         /// 지원 여부 확인 스킵
         #[clap(long)]
         force: bool,
+
+        /// XDP 연결 실패 시 대체 백엔드 (none, nftables; 생략 시 데몬 구성)
+        #[clap(long)]
+        fallback: Option<String>,
+
+        /// 기본 오브젝트 대신 연결할 BPF 오브젝트 (데몬 object_dir 기준 경로)
+        #[clap(long)]
+        obj: Option<String>,
     },
 
     /// XDP 프로그램을 인터페이스에서 분리
@@ -55,248 +155,927 @@ enum Commands {
     },
 
     /// 필터링 규칙 추가
-    AddRule {
-        /// 소스 IP 주소 (a.b.c.d 또는 a.b.c.d/prefix)
+    AddRule(Box<AddRuleArgs>),
+
+    /// 필터링 규칙 삭제
+    DeleteRule {
+        /// 규칙 레이블
+        #[clap(long)]
+        label: String,
+    },
+
+    /// 규칙 집합 파일의 상태로 규칙 동기화
+    Apply {
+        /// 규칙 집합 파일 (YAML)
+        #[clap(short = 'f', long)]
+        file: PathBuf,
+
+        /// 파일에 없는 규칙 삭제 (자동 생성 규칙 제외)
+        #[clap(long)]
+        prune: bool,
+
+        /// 적용하지 않고 변경 사항만 표시
         #[clap(long)]
-        src_ip: Option<String>,
+        dry_run: bool,
 
-        /// 대상 IP 주소 (a.b.c.d 또는 a.b.c.d/prefix)
+        /// 출력 형식 (text, json)
         #[clap(long)]
-        dst_ip: Option<String>,
+        format: Option<String>,
+    },
+
+    /// 다른 방화벽의 규칙 내보내기를 규칙 집합 문서(YAML)로 변환
+    Convert {
+        /// 입력 형식
+        #[clap(long, value_enum)]
+        from: convert::ConvertSource,
+
+        /// 변환할 파일 (예: iptables-save 출력)
+        #[clap(short = 'f', long)]
+        file: PathBuf,
 
-        /// 소스 포트 또는 포트 범위 (포트 또는 포트1-포트2)
+        /// 출력하지 않고 변환한 규칙을 바로 추가 (파일에 없는 규칙은 그대로 둠)
         #[clap(long)]
-        src_port: Option<String>,
+        apply: bool,
+    },
+
+    /// 설치에 실패했거나 일부만 설치된 규칙 다시 설치
+    RepairRules,
 
-        /// 대상 포트 또는 포트 범위 (포트 또는 포트1-포트2)
+    /// 커널 규칙 맵과 데몬 규칙 캐시 비교 (옵션을 주면 불일치 수정)
+    Reconcile {
+        /// 데몬 캐시에 없는 커널 항목 삭제
         #[clap(long)]
-        dst_port: Option<String>,
+        delete_orphans: bool,
 
-        /// 프로토콜 (tcp, udp, icmp, any)
+        /// 커널에서 사라진 규칙 항목 다시 기록
         #[clap(long)]
-        protocol: Option<String>,
+        reinstall: bool,
 
-        /// TCP 플래그 (SYN,ACK,FIN,RST,PSH,URG)
+        /// 출력 형식 (table, json)
         #[clap(long)]
-        tcp_flags: Option<String>,
+        format: Option<String>,
+    },
 
-        /// 패킷 길이 범위 (min-max)
+    /// 활성 규칙 나열
+    ListRules {
+        /// 통계 포함 (PACKETS/BYTES/LAST-MATCH 열)
         #[clap(long)]
-        pkt_len: Option<String>,
+        stats: bool,
 
-        /// 액션 (pass, drop, redirect, count)
+        /// 직전 텔레메트리 수집 구간의 초당 매치율 포함 (PPS/BPS 열)
         #[clap(long)]
-        action: String,
+        rates: bool,
 
-        /// 리디렉션 인터페이스 (리디렉션 액션에 필요)
+        /// 모든 열 표시
         #[clap(long)]
-        redirect_if: Option<String>,
+        wide: bool,
 
-        /// 규칙 우선순위 (높을수록 우선)
-        #[clap(long, default_value = "0")]
-        priority: u32,
+        /// 간격마다 다시 그리며 카운터가 바뀐 규칙 강조, PPS/BPS는 갱신 사이 증가량 (예: --watch, --watch 5s, 기본 2초, q 또는 Ctrl+C로 종료)
+        #[clap(long, value_name = "INTERVAL", num_args = 0..=1, default_missing_value = "2",
+            value_parser = swift_guard::utils::parse_duration)]
+        watch: Option<u64>,
 
-        /// 초당 패킷 수 레이트 리밋 (0 = 무제한)
-        #[clap(long, default_value = "0")]
-        rate_limit: u32,
+        /// 이 기간 안에 만료되는 규칙만 남은 수명 순으로 표시 (예: 90s, 10m, 2h, 1d)
+        #[clap(long)]
+        expiring_within: Option<String>,
 
-        /// 규칙 만료 시간 (초, 0 = 만료 없음)
-        #[clap(long, default_value = "0")]
-        expire: u32,
+        /// 정렬 기준 (priority: 우선순위 높은 순, created: 오래된 순, 기본값 priority)
+        #[clap(long)]
+        sort: Option<String>,
 
-        /// 규칙 이름/레이블
+        /// 모든 네임스페이스의 규칙 표시 (관리자 토큰 필요)
         #[clap(long)]
-        label: String,
+        all_namespaces: bool,
+
+        /// 이 주소 체계(4, 6)의 트래픽에 적용되는 규칙만 표시 (주소 조건이 없는 규칙 포함)
+        #[clap(long, value_parser = utils::parse_family)]
+        family: Option<AddressFamily>,
+
+        /// WASM 모듈이 설치한 동적 차단 규칙도 표시 (기본은 숨김, 요약은 dynamic-blocks)
+        #[clap(long)]
+        all: bool,
+
+        /// 이 주체가 설치한 규칙만 표시 (wasm: 모든 모듈의 동적 차단 규칙, wasm:<모듈>: 그 모듈)
+        #[clap(long)]
+        origin: Option<String>,
+
+        /// 규칙 키 순서로 최대 이 개수만 조회 (스크립트용, 없으면 모든 페이지를 이어 받음)
+        #[clap(long)]
+        limit: Option<u32>,
+
+        /// 규칙 키 순서로 앞에서 건너뛸 규칙 수 (--limit과 함께 사용)
+        #[clap(long)]
+        offset: Option<u32>,
+
+        /// 모든 API 서버의 규칙을 HOST 열과 함께 합쳐 표시 (여러 서버를 지정했을 때 필요)
+        #[clap(long, conflicts_with_all = ["watch", "limit", "offset"])]
+        merge: bool,
+
+        /// 출력 형식 (table, json, yaml)
+        #[clap(long)]
+        format: Option<String>,
     },
 
-    /// 필터링 규칙 삭제
-    DeleteRule {
+    /// 단일 규칙의 상세 정보 표시
+    ShowRule {
         /// 규칙 레이블
         #[clap(long)]
         label: String,
+
+        /// 출력 형식 (table, json)
+        #[clap(long)]
+        format: Option<String>,
     },
 
-    /// 활성 규칙 나열
-    ListRules {
-        /// 통계 포함
+    /// count 액션 규칙의 카운터 표시 (같은 counter_group 규칙은 합산)
+    Counters {
+        /// 이름이 이 문자열로 시작하는 카운터만 표시 (예: web-)
         #[clap(long)]
-        stats: bool,
+        prefix: Option<String>,
+
+        /// 간격마다 다시 그림 (예: --watch, --watch 5s, 기본 2초, q 또는 Ctrl+C로 종료)
+        #[clap(long, value_name = "INTERVAL", num_args = 0..=1, default_missing_value = "2",
+            value_parser = swift_guard::utils::parse_duration)]
+        watch: Option<u64>,
+
+        /// 출력 형식 (table, json)
+        #[clap(long)]
+        format: Option<String>,
     },
 
     /// 성능 통계 표시
     Stats {
-        /// 통계 업데이트 간격 (초)
-        #[clap(long, default_value = "1")]
+        /// 통계 업데이트 간격 (예: 1, 30s, 5m)
+        #[clap(long, default_value = "1", value_parser = swift_guard::utils::parse_duration)]
+        interval: u64,
+
+        /// 출력 횟수 (0 = Ctrl+C까지 계속)
+        #[clap(long, default_value = "0")]
+        count: u64,
+
+        /// 데몬 프로세스 자체 통계 (CPU, 메모리, fd 등) 표시
+        #[clap(long = "self")]
+        self_stats: bool,
+    },
+
+    /// 데몬 이벤트(규칙 적중, 자동 완화)를 순번과 함께 표시
+    Monitor {
+        /// 이 순번 이후의 이벤트부터 표시 (재연결 시 마지막으로 본 순번)
+        #[clap(long, conflicts_with = "replay")]
+        since_seq: Option<u64>,
+
+        /// 새 이벤트 전에 표시할 최근 이벤트 수
+        #[clap(long, default_value = "0")]
+        replay: usize,
+
+        /// 표시할 이벤트 종류 (rule_hit, mitigation, rule_matched, link_state, 여러 번 지정 가능)
+        #[clap(long = "kind")]
+        kinds: Vec<String>,
+
+        /// 밀린 이벤트만 표시하고 종료
+        #[clap(long)]
+        no_follow: bool,
+
+        /// 새 이벤트 조회 간격 (예: 1, 30s, 5m)
+        #[clap(long, default_value = "1", value_parser = swift_guard::utils::parse_duration)]
         interval: u64,
+
+        /// 이벤트 대신 규칙 변경 구독 (현재 규칙을 먼저 표시한 뒤 추가, 갱신, 삭제를 순번과 함께 표시)
+        #[clap(long, conflicts_with_all = ["since_seq", "replay", "kinds", "no_follow"])]
+        rules: bool,
+
+        /// 모든 네임스페이스의 규칙 변경 구독 (--rules, 관리자 토큰 필요)
+        #[clap(long, requires = "rules")]
+        all_namespaces: bool,
+    },
+
+    /// WASM 검사 모듈 관리
+    Wasm {
+        #[clap(subcommand)]
+        command: WasmCommands,
+    },
+
+    /// WASM 모듈이 설치한 동적 차단 규칙 요약 (주소, 모듈, 사유, 남은 수명, 매치 수)
+    DynamicBlocks {
+        /// 이 모듈이 설치한 규칙만
+        #[clap(long)]
+        module: Option<String>,
+
+        /// 표시하는 대신 삭제 (관리자 토큰 필요)
+        #[clap(long)]
+        clear: bool,
+
+        /// 출력 형식 (table, json)
+        #[clap(long)]
+        format: Option<String>,
+    },
+
+    /// 연결된 인터페이스와 규칙 설치 백엔드 나열
+    ListInterfaces {
+        /// 출력 형식 (table, json)
+        #[clap(long)]
+        format: Option<String>,
+    },
+
+    /// 리디렉션 대상 인터페이스(redirect_map 항목)와 참조하는 규칙 나열
+    Redirects {
+        #[clap(subcommand)]
+        command: Option<RedirectCommands>,
+
+        /// 출력 형식 (table, json)
+        #[clap(long)]
+        format: Option<String>,
+    },
+
+    /// 검증된 규칙 묶음(프리셋) 적용, 제거, 나열
+    Preset {
+        #[clap(subcommand)]
+        command: PresetCommands,
+    },
+
+    /// 클라이언트 빌드 정보 표시 (--remote이면 데몬 빌드 정보와 나란히)
+    Version {
+        /// 데몬 빌드 정보도 조회
+        #[clap(long)]
+        remote: bool,
+
+        /// 출력 형식 (table, json)
+        #[clap(long)]
+        format: Option<String>,
+    },
+
+    /// 데몬 커널 기능 점검 결과와 해결 방법 표시
+    Doctor {
+        /// 출력 형식 (text, json)
+        #[clap(long)]
+        format: Option<String>,
+    },
+
+    /// 표식 패킷을 보내 연결된 인터페이스의 데이터 경로가 규칙을 적용하는지 확인
+    Selftest {
+        /// 패킷을 보낼 인터페이스 (lo 외의 인터페이스에서는 패킷이 호스트 밖으로 나감)
+        #[clap(long, default_value = "lo")]
+        interface: String,
+
+        /// 출력 형식 (text, json)
+        #[clap(long)]
+        format: Option<String>,
+    },
+
+    /// 합성 규칙과 트래픽으로 XDP 모드별 처리량 측정 (데몬 호스트에 veth, 규칙, XDP 연결을 만들었다 지움)
+    Bench {
+        /// XDP를 연결해 측정할 인터페이스 (생략하면 데몬이 veth 쌍을 만듦)
+        #[clap(long, requires = "peer")]
+        interface: Option<String>,
+
+        /// 트래픽을 보낼 인터페이스 (--interface와 연결된 링크)
+        #[clap(long, requires = "interface")]
+        peer: Option<String>,
+
+        /// 모드별 전송 시간 (예: 10, 30s, 1m)
+        #[clap(long, default_value = "10s", value_parser = utils::parse_duration_u32)]
+        duration: u32,
+
+        /// 프레임 크기 (바이트, FCS 제외)
+        #[clap(long, default_value = "64")]
+        pkt_size: u32,
+
+        /// 합성 규칙 수 (예: 1000, 10k)
+        #[clap(long, default_value = "1000", value_parser = utils::parse_count_u32)]
+        rules: u32,
+
+        /// 측정할 XDP 모드 (driver, generic, 여러 번 지정 가능, 생략하면 둘 다)
+        #[clap(long = "mode")]
+        modes: Vec<String>,
+
+        /// 확인 없이 실행
+        #[clap(long, short = 'y')]
+        yes: bool,
+
+        /// 출력 형식 (table, json)
+        #[clap(long)]
+        format: Option<String>,
+    },
+
+    /// 소스 프리픽스가 겹치는 규칙과 실제 적용되는 규칙 표시
+    Conflicts {
+        /// 출력 형식 (table, json)
+        #[clap(long)]
+        format: Option<String>,
+    },
+
+    /// LPM 규칙 맵의 프리픽스 길이 분포, 겹침과 사용률 표시
+    Rulespace {
+        /// 출력 형식 (text, json)
+        #[clap(long)]
+        format: Option<String>,
+    },
+
+    /// 데몬이 로드한 BPF 프로그램과 맵 정보 표시
+    BpfInfo {
+        /// 출력 형식 (table, json)
+        #[clap(long)]
+        format: Option<String>,
+    },
+
+    /// 패킷 캡처(pcap) 파일 나열
+    Captures {
+        /// 출력 형식 (table, json)
+        #[clap(long)]
+        format: Option<String>,
+    },
+
+    /// CLI 설정 파일의 데몬 프로필 관리
+    Config {
+        #[clap(subcommand)]
+        command: ConfigCommands,
+    },
+
+    /// 추적 중인 연결 나열
+    Conntrack {
+        /// 소스 IP 필터 (a.b.c.d 또는 a.b.c.d/prefix)
+        #[clap(long)]
+        filter_src_ip: Option<String>,
+
+        /// 대상 IP 필터 (a.b.c.d 또는 a.b.c.d/prefix)
+        #[clap(long)]
+        filter_dst_ip: Option<String>,
+
+        /// 소스 포트 필터
+        #[clap(long)]
+        filter_src_port: Option<u16>,
+
+        /// 대상 포트 필터
+        #[clap(long)]
+        filter_dst_port: Option<u16>,
+
+        /// 프로토콜 필터 (tcp, udp, icmp)
+        /// 프로토콜 필터 (이름 또는 번호)
+        filter_protocol: Option<String>,
+
+        /// 최대 표시 개수 (0 = 무제한)
+        #[clap(long, default_value = "100")]
+        limit: u32,
+
+        /// 출력 형식 (table, json)
+        #[clap(long)]
+        format: Option<String>,
+    },
+
+    /// IP 주소의 국가와 AS 조회 (데몬의 GeoIP 데이터베이스)
+    Lookup {
+        /// 조회할 IPv4 또는 IPv6 주소
+        ip: String,
+
+        /// 출력 형식 (table, json)
+        #[clap(long)]
+        format: Option<String>,
+    },
+
+    /// 국가의 IPv4 프리픽스 집합을 소스로 하는 규칙 설치 (country-<코드>, 이미 있으면 갱신)
+    BlockCountry {
+        /// ISO 3166-1 국가 코드 (예: RU)
+        #[clap(long)]
+        code: String,
+
+        /// 액션 (pass, drop, count)
+        #[clap(long, default_value = "drop", value_parser = utils::action_name_to_num)]
+        action: u8,
+
+        /// 규칙 만료 시간 (예: 3600, 90s, 15m, 2h, 1d, 0 = 만료 없음)
+        #[clap(long, default_value = "0", value_parser = utils::parse_duration_u32)]
+        expire: u32,
+
+        /// GeoIP 데이터베이스 대신 쓸 `<CIDR>,<국가 코드>` CSV 파일 (데몬 호스트 경로)
+        #[clap(long)]
+        csv: Option<String>,
+
+        /// 출력 형식 (table, json)
+        #[clap(long)]
+        format: Option<String>,
+    },
+
+    /// 국가 차단 규칙의 프리픽스 집합을 원본에서 다시 만듦 (데이터베이스 갱신 후)
+    RefreshCountries {
+        /// 이 국가만 갱신 (생략하면 모두)
+        #[clap(long)]
+        code: Option<String>,
+
+        /// 출력 형식 (table, json)
+        #[clap(long)]
+        format: Option<String>,
+    },
+
+    /// 국가 차단 규칙과 프리픽스 집합 삭제
+    UnblockCountry {
+        /// ISO 3166-1 국가 코드
+        #[clap(long)]
+        code: String,
     },
 }
 
-#[tokio::main]
-async fn main() -> Result<()> {
-    // 로깅 초기화
-    env_logger::init();
+/// `add-rule` 옵션
+#[derive(clap::Args, Debug)]
+struct AddRuleArgs {
+    /// 소스 IP 주소 (a.b.c.d 또는 a.b.c.d/prefix)
+    #[clap(long)]
+    src_ip: Option<String>,
 
-    // 명령줄 인수 파싱
-    let cli = Cli::parse();
+    /// 대상 IP 주소 (a.b.c.d 또는 a.b.c.d/prefix)
+    #[clap(long)]
+    dst_ip: Option<String>,
 
-    if cli.verbose {
-        std::env::set_var("RUST_LOG", "debug");
-    } else {
-        std::env::set_var("RUST_LOG", "info");
-    }
+    /// 소스 호스트 이름 (데몬이 A 레코드마다 /32 항목으로 설치하고 주기적으로 다시 해석)
+    #[clap(long, conflicts_with = "src_ip")]
+    src_host: Option<String>,
 
-    // API 클라이언트 생성
-    let client = ApiClient::new(&cli.api_server)
-        .context("Failed to create API client")?;
+    /// 대상 호스트 이름 (XDP 프로그램이 목적지를 비교하지 않으므로 데몬이 거부함)
+    #[clap(long, conflicts_with = "dst_ip")]
+    dst_host: Option<String>,
 
-    // 명령 실행
-    match &cli.command {
-        Commands::Attach { interface, mode, force } => {
-            debug!("Attaching XDP program to interface: {}", interface);
-            
-            let mode_value = match mode.as_str() {
-                "driver" => 0,
-                "generic" => 1,
-                "offload" => 2,
-                _ => return Err(anyhow!("Invalid mode: {}", mode)),
-            };
-            
-            let request = ApiRequest::Attach {
-                interface: interface.clone(),
-                mode: mode_value,
-                force: *force,
-            };
-            
-            let response = client.send_request(&request).await
-                .context("Failed to send attach request")?;
-            
-            match response {
-                ApiResponse::Success { message } => {
-                    println!("Success: {}", message);
-                },
-                ApiResponse::Error { message } => {
-                    return Err(anyhow!("Error: {}", message));
-                },
-                ApiResponse::Rules { .. } | ApiResponse::Stats { .. } => {
-                    return Err(anyhow!("Unexpected response type"))
-                }
-            }
-        },
+    /// 소스 포트 또는 포트 범위 (포트 또는 포트1-포트2)
+    #[clap(long)]
+    src_port: Option<String>,
+
+    /// 소스 포트 목록 (최대 8개의 포트1,포트2,..., tcp, udp만)
+    #[clap(long, conflicts_with = "src_port")]
+    src_ports: Option<String>,
+
+    /// 대상 포트 또는 포트 범위 (포트 또는 포트1-포트2)
+    #[clap(long)]
+    dst_port: Option<String>,
+
+    /// 대상 포트 목록 (최대 8개의 포트1,포트2,..., tcp, udp만)
+    #[clap(long, conflicts_with = "dst_port")]
+    dst_ports: Option<String>,
+
+    /// 프로토콜 (tcp, udp, icmp, icmpv6, gre, esp, ah, sctp, ipip, any 또는 번호 0-255)
+    #[clap(long, value_parser = utils::protocol_name_to_num)]
+    protocol: Option<u8>,
+
+    /// TCP 플래그 (SYN,ACK,FIN,RST,PSH,URG)
+    #[clap(long, value_parser = utils::parse_tcp_flags)]
+    tcp_flags: Option<u8>,
+
+    /// 패킷 길이 범위 (min-max)
+    #[clap(long)]
+    pkt_len: Option<String>,
+
+    /// 액션 (pass, drop, redirect, count)
+    #[clap(long)]
+    action: String,
+
+    /// 리디렉션 인터페이스 (리디렉션 액션에 필요)
+    #[clap(long, conflicts_with = "redirect_cpu")]
+    redirect_if: Option<String>,
+
+    /// 리디렉션 대상 CPU (인터페이스 대신 CPU 맵으로 리디렉션)
+    #[clap(long)]
+    redirect_cpu: Option<u32>,
+
+    /// 규칙 우선순위 (높을수록 우선)
+    #[clap(long, default_value = "0")]
+    priority: u32,
+
+    /// 초당 패킷 수 레이트 리밋 (예: 1000, 10k, 0 = 무제한)
+    #[clap(long, default_value = "0", value_parser = utils::parse_count_u32)]
+    rate_limit: u32,
+
+    /// 규칙 만료 시간 (예: 3600, 90s, 15m, 2h, 1d, 0 = 만료 없음)
+    #[clap(long, default_value = "0", value_parser = utils::parse_duration_u32)]
+    expire: u32,
+
+    /// 규칙 이름/레이블 (최대 31바이트, 영숫자와 - _ . 만 허용)
+    #[clap(long)]
+    label: String,
+
+    /// 규칙 설명
+    #[clap(long)]
+    description: Option<String>,
+
+    /// 규칙 작성자 (생략 시 데몬이 요청자 정보로 채움)
+    #[clap(long)]
+    created_by: Option<String>,
+
+    /// 하루 바이트 할당량 (예: 500M, 10G)
+    #[clap(long, value_parser = swift_guard::utils::parse_size)]
+    quota_bytes: Option<u64>,
+
+    /// 하루 패킷 할당량 (예: 500k, 2M)
+    #[clap(long, value_parser = swift_guard::utils::parse_count)]
+    quota_packets: Option<u64>,
+
+    /// 할당량을 넘었을 때 적용할 액션 (pass, drop, count, 기본 drop)
+    #[clap(long, value_parser = utils::action_name_to_num)]
+    quota_action: Option<u8>,
+
+    /// 통계를 합산해 보여줄 카운터 그룹 (count 액션만, `counters` 명령에서 조회)
+    #[clap(long)]
+    counter_group: Option<String>,
+
+    /// 규칙이 만료되기 전과 만료될 때 알림을 받을 웹훅 URL (--expire 필요)
+    #[clap(long)]
+    owner_webhook: Option<String>,
+
+    /// 만료 예고 알림을 보낼 남은 시간 (예: 10m, 생략 시 데몬 구성 값)
+    #[clap(long, value_parser = utils::parse_duration_u32)]
+    notify_before: Option<u32>,
+
+    /// 로드된 XDP 프로그램이 처리하지 못하는 매치 필드가 있어도 추가 (그 필드는 무시되고 경고 출력)
+    #[clap(long)]
+    force: bool,
+}
+
+impl AddRuleArgs {
+    /// 옵션을 검증해 규칙 추가 요청 생성
+    fn to_request(&self) -> Result<ApiRequest> {
+        // 액션 파싱
+        let action_value = match self.action.as_str() {
+            "pass" => 1,
+            "drop" => 2,
+            "redirect" => 3,
+            "count" => 4,
+            _ => return Err(anyhow!("Invalid action: {}", self.action)),
+        };
         
-        Commands::Detach { interface } => {
-            debug!("Detaching XDP program from interface: {}", interface);
-            
-            let request = ApiRequest::Detach {
-                interface: interface.clone(),
-            };
-            
-            let response = client.send_request(&request).await
-                .context("Failed to send detach request")?;
-            
-            match response {
-                ApiResponse::Success { message } => {
-                    println!("Success: {}", message);
-                },
-                ApiResponse::Error { message } => {
-                    return Err(anyhow!("Error: {}", message));
-                },
-                ApiResponse::Rules { .. } | ApiResponse::Stats { .. } => {
-                    return Err(anyhow!("Unexpected response type"))
-                }
-            }
-        },
+        // 프로토콜 (생략 시 ANY)
+        let protocol_value = self.protocol.unwrap_or(255);
         
-        Commands::AddRule { src_ip, dst_ip, src_port, dst_port, protocol, tcp_flags, 
-                          pkt_len, action, redirect_if, priority, rate_limit, expire, label } => {
-            debug!("Adding filter rule: {}", label);
-            
-            // 액션 파싱
-            let action_value = match action.as_str() {
-                "pass" => 1,
-                "drop" => 2,
-                "redirect" => 3,
-                "count" => 4,
-                _ => return Err(anyhow!("Invalid action: {}", action)),
-            };
-            
-            // 프로토콜 파싱
-            let protocol_value = match protocol {
-                Some(p) => match p.as_str() {
-                    "tcp" => 6,
-                    "udp" => 17,
-                    "icmp" => 1,
-                    "any" => 255,
-                    _ => return Err(anyhow!("Invalid protocol: {}", p)),
-                },
-                None => 255, // ANY
-            };
-            
-            // 포트 범위 파싱
-            let (src_port_min, src_port_max) = match src_port {
-                Some(p) => parse_port_range(p)?,
-                None => (0, 65535),
-            };
-            
-            let (dst_port_min, dst_port_max) = match dst_port {
-                Some(p) => parse_port_range(p)?,
-                None => (0, 65535),
-            };
-            
-            // TCP 플래그 파싱
-            let tcp_flags_value = match tcp_flags {
-                Some(f) => {
-                    let mut flags = 0;
-                    for flag in f.split(',') {
-                        match flag.trim() {
-                            "FIN" => flags |= 0x01,
-                            "SYN" => flags |= 0x02,
-                            "RST" => flags |= 0x04,
-                            "PSH" => flags |= 0x08,
-                            "ACK" => flags |= 0x10,
-                            "URG" => flags |= 0x20,
-                            _ => return Err(anyhow!("Invalid TCP flag: {}", flag)),
-                        }
-                    }
-                    flags
-                },
-                None => 0,
-            };
+        // 포트 범위 파싱 (목록을 주면 범위는 전체)
+        let (src_port_min, src_port_max) = match &self.src_port {
+            Some(p) => parse_port_range(p)?,
+            None => (0, 65535),
+        };
+        
+        let (dst_port_min, dst_port_max) = match &self.dst_port {
+            Some(p) => parse_port_range(p)?,
+            None => (0, 65535),
+        };
+        
+        let src_port_list = self.src_ports.as_deref().map(utils::parse_port_list).transpose()?.unwrap_or_default();
+        let dst_port_list = self.dst_ports.as_deref().map(utils::parse_port_list).transpose()?.unwrap_or_default();
+        
+        let spec = api::RuleSpec {
+            label: self.label.clone(),
+            src_ip: self.src_ip.clone(),
+            dst_ip: self.dst_ip.clone(),
+            src_port_min,
+            src_port_max,
+            dst_port_min,
+            dst_port_max,
+            protocol: protocol_value,
+            tcp_flags: self.tcp_flags.unwrap_or(0),
+            action: action_value,
+            redirect_if: self.redirect_if.clone(),
+            redirect_cpu: self.redirect_cpu,
+            priority: self.priority,
+            rate_limit: self.rate_limit,
+            expire: self.expire,
+            description: self.description.clone(),
+            quota_bytes: self.quota_bytes,
+            quota_packets: self.quota_packets,
+            quota_action: self.quota_action,
+            counter_group: self.counter_group.clone(),
+            src_port_list,
+            dst_port_list,
+            owner_webhook: self.owner_webhook.clone(),
+            notify_before: self.notify_before,
+        };
+        
+        // 데몬과 같은 사전 검증 (문제를 모두 한 번에 보고)
+        utils::check_rule_spec(&spec)?;
+        
+        // 규칙 정의에 없는 작성자와 호스트 이름
+        let mut request = spec.into_add_request();
+        if let ApiRequest::AddRule(rule) = &mut request {
+            rule.created_by.clone_from(&self.created_by);
+            rule.src_host.clone_from(&self.src_host);
+            rule.dst_host.clone_from(&self.dst_host);
+            rule.force = self.force;
+        }
+        Ok(request)
+    }
+}
+
+#[derive(Subcommand, Debug)]
+enum ConfigCommands {
+    /// 프로필 추가 또는 수정 (지정한 항목만 바뀜)
+    SetProfile {
+        /// 프로필 이름
+        name: String,
+
+        /// API 서버 주소
+        #[clap(long)]
+        api_server: Option<String>,
+
+        /// TLS 사용 여부 (true, false)
+        #[clap(long)]
+        tls: Option<bool>,
+
+        /// 서버 인증서를 검증할 CA 인증서 (PEM)
+        #[clap(long, value_name = "PATH")]
+        ca_cert: Option<PathBuf>,
+
+        /// 상호 TLS용 클라이언트 인증서 (PEM)
+        #[clap(long, value_name = "PATH")]
+        client_cert: Option<PathBuf>,
+
+        /// 상호 TLS용 클라이언트 개인 키 (PEM)
+        #[clap(long, value_name = "PATH")]
+        client_key: Option<PathBuf>,
+
+        /// 기본 출력 형식 (table, json)
+        #[clap(long)]
+        format: Option<String>,
+
+        /// --profile을 생략했을 때 이 프로필 사용
+        #[clap(long)]
+        default: bool,
+    },
+
+    /// 프로필 삭제
+    DeleteProfile {
+        /// 프로필 이름
+        name: String,
+    },
+
+    /// 프로필 나열
+    ListProfiles,
+}
+
+#[derive(Subcommand, Debug)]
+enum RedirectCommands {
+    /// 규칙 없이 리디렉션 대상 등록 (규칙이 추가되기 전에 미리 준비)
+    Add {
+        /// 대상 인터페이스 이름
+        interface: String,
+    },
+
+    /// 리디렉션 대상 제거 (규칙이 참조 중이면 거부)
+    Remove {
+        /// 대상 인터페이스 이름 또는 슬롯 번호
+        interface: String,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum PresetCommands {
+    /// ICMP 레이트 리밋으로 ping flood 방어 (다시 실행하면 바뀐 규칙만 갱신,
+    /// 로드된 XDP 프로그램에 rate_limit 기능이 없으면 데몬이 거부)
+    IcmpProtect {
+        /// 허용할 초당 ICMP 패킷 수 (예: 100, 1k)
+        #[clap(long, value_parser = swift_guard::utils::parse_count)]
+        limit: u64,
+
+        /// 보호할 인터페이스 (연결되어 있어야 함, 규칙은 연결된 모든 인터페이스에 적용)
+        #[clap(long)]
+        interface: Option<String>,
+
+        /// 출력 형식 (text, json)
+        #[clap(long)]
+        format: Option<String>,
+    },
+
+    /// 증폭 공격에 쓰이는 UDP 포트(19, 53, 123, 161, 1900, 11211)에서 오는 트래픽 집계
+    /// (--drop을 주면 임계값을 넘는 패킷 드롭, 다시 실행하면 같은 규칙을 제자리 갱신)
+    AmplificationProtect {
+        /// 세기만 하지 않고 임계값을 넘는 패킷 드롭
+        #[clap(long)]
+        drop: bool,
+
+        /// --drop에서 허용할 초당 패킷 수 (예: 500, 10k, 기본 1k)
+        #[clap(long, requires = "drop", value_parser = swift_guard::utils::parse_count)]
+        threshold: Option<u64>,
+
+        /// 보호할 인터페이스 (연결되어 있어야 함, 규칙은 연결된 모든 인터페이스에 적용)
+        #[clap(long)]
+        interface: Option<String>,
+
+        /// 출력 형식 (text, json)
+        #[clap(long)]
+        format: Option<String>,
+    },
+
+    /// 프리셋이 설치한 규칙 모두 삭제
+    Remove {
+        /// 프리셋 이름 (예: icmp-protect)
+        name: String,
+    },
+
+    /// 프리셋 목록과 적용된 규칙 표시
+    List {
+        /// 출력 형식 (table, json)
+        #[clap(long)]
+        format: Option<String>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum WasmCommands {
+    /// WASM 모듈 로드
+    Load {
+        /// 모듈 이름
+        #[clap(long)]
+        name: String,
+
+        /// WASM 파일 경로
+        #[clap(long)]
+        file: PathBuf,
+
+        /// 검사 오류 시 처리 (pass, drop, disable_module, 기본값: 데몬 구성)
+        #[clap(long)]
+        on_error: Option<String>,
+
+        /// 모듈 설정 파일 (내용을 데몬에 보내 모듈이 init에서 읽음)
+        #[clap(long)]
+        config_file: Option<PathBuf>,
+
+        /// 판정 적용 방식 (enforce, shadow: 검사만 하고 차단하지 않음, 기본값 enforce)
+        #[clap(long)]
+        mode: Option<String>,
+    },
+
+    /// WASM 모듈 언로드
+    Unload {
+        /// 모듈 이름
+        #[clap(long)]
+        name: String,
+    },
+
+    /// 오류로 비활성화된 WASM 모듈 재개
+    Resume {
+        /// 모듈 이름
+        #[clap(long)]
+        name: String,
+    },
+
+    /// WASM 모듈 판정 적용 방식 변경 (통계는 유지)
+    SetMode {
+        /// 모듈 이름
+        #[clap(long)]
+        name: String,
+
+        /// 판정 적용 방식 (enforce, shadow)
+        #[clap(long)]
+        mode: String,
+    },
+
+    /// 로드된 WASM 모듈 나열
+    List,
+
+    /// WASM 모듈 통계 표시
+    Stats {
+        /// 모듈 이름
+        #[clap(long)]
+        name: String,
+    },
+
+    /// WASM 모듈 통계 초기화 (처리 수, 오류 수, 검사 시간 분포)
+    ResetStats {
+        /// 모듈 이름
+        #[clap(long)]
+        name: String,
+    },
+
+    /// WASM 모듈 로그 표시
+    Logs {
+        /// 모듈 이름
+        #[clap(long)]
+        name: String,
+
+        /// 표시할 최근 줄 수 (0 = 보관된 전체)
+        #[clap(long, default_value = "50")]
+        lines: usize,
+
+        /// 새 로그를 계속 표시 (Ctrl+C로 종료)
+        #[clap(long)]
+        follow: bool,
+
+        /// --follow 조회 간격 (예: 1, 30s, 5m)
+        #[clap(long, default_value = "1", value_parser = swift_guard::utils::parse_duration)]
+        interval: u64,
+    },
+
+    /// 컴파일된 WASM 모듈 캐시 삭제
+    CacheClear,
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    // 로깅 초기화
+    env_logger::init();
+
+    // 명령줄 인수 파싱 (jsonl 모드를 요청했으면 파싱 오류도 봉투로 출력)
+    let args: Vec<std::ffi::OsString> = std::env::args_os().collect();
+    let cli = match Cli::try_parse_from(&args) {
+        Ok(cli) => cli,
+        Err(error) if error.use_stderr() && output::requested(&args) => {
+            let message = output::parse_error_message(&error.to_string());
+            return exit::finish(Err(anyhow!(message)), &Output::new(OutputMode::Jsonl));
+        },
+        Err(error) => error.exit(),
+    };
+
+    style::init(cli.color, cli.no_color);
+    let mut out = Output::new(cli.output);
+    let result = run(cli, &mut out).await;
+    exit::finish(result, &out)
+}
+
+/// 명령 실행
+async fn run(cli: Cli, out: &mut Output) -> Result<()> {
+    if cli.verbose {
+        std::env::set_var("RUST_LOG", "debug");
+    } else {
+        std::env::set_var("RUST_LOG", "info");
+    }
+
+    // 연결 설정 결정 (명령줄 옵션 > 환경 변수 > 프로필 > 기본값)
+    let config_path = profile::config_path(cli.config.as_deref(), |key| std::env::var(key).ok());
+    if let Commands::Config { command } = &cli.command {
+        let path = config_path
+            .ok_or_else(|| anyhow!("Cannot determine the CLI config file location; use --config"))?;
+        return run_config(out, &path, command);
+    }
+    if let Commands::Convert { from, file, apply: false } = &cli.command {
+        return run_convert(out, *from, file);
+    }
+    
+    let config = match &config_path {
+        Some(path) => CliConfig::load(path)?,
+        None => CliConfig::default(),
+    };
+    let settings = profile::resolve(&cli.connection_flags(), |key| std::env::var(key).ok(), &config)?;
+    debug!("Using API server {} (profile: {:?})", settings.api_server, settings.profile);
+    
+    // 여러 서버를 지정했거나 결과를 합치는 명령은 모든 서버에 동시에 보냄
+    let servers = match &cli.servers_file {
+        Some(path) => fanout::read_servers_file(path)?,
+        None => fanout::parse_servers(&settings.api_server)?,
+    };
+    if servers.len() > 1 || matches!(cli.command, Commands::ListRules { merge: true, .. }) {
+        return run_fanout(&cli, &settings, &servers, out).await;
+    }
+    
+    // API 클라이언트 생성
+    let client = connect(&settings, &servers[0], cli.verbose)?;
+    let reconnect = ReconnectPolicy::new(Duration::from_secs(cli.reconnect_window));
+    
+    // 명령 실행
+    match &cli.command {
+        Commands::Attach { interface, mode, force, fallback, obj } => {
+            debug!("Attaching XDP program to interface: {}", interface);
             
-            // 리디렉션 인터페이스 확인
-            if action_value == 3 && redirect_if.is_none() {
-                return Err(anyhow!("Redirect action requires 'redirect_if' parameter"));
-            }
-            
-            let request = ApiRequest::AddRule {
-                src_ip: src_ip.clone(),
-                dst_ip: dst_ip.clone(),
-                src_port_min,
-                src_port_max,
-                dst_port_min,
-                dst_port_max,
-                protocol: protocol_value,
-                tcp_flags: tcp_flags_value,
-                action: action_value,
-                redirect_if: redirect_if.clone(),
-                priority: *priority,
-                rate_limit: *rate_limit,
-                expire: *expire,
-                label: label.clone(),
+            let request = attach_request(interface, mode, *force, fallback.as_deref(), obj)?;
+            
+            let response = client.send_request(&request).await
+                .context("Failed to send attach request")?;
+            
+            match response {
+                ApiResponse::Success { message, .. } => {
+                    out.message("Success", &message);
+                },
+                other => return Err(exit::response_error(other)),
+            }
+        },
+        
+        Commands::Detach { interface } => {
+            debug!("Detaching XDP program from interface: {}", interface);
+            
+            let request = ApiRequest::Detach {
+                interface: interface.clone(),
             };
             
+            let response = client.send_request(&request).await
+                .context("Failed to send detach request")?;
+            
+            match response {
+                ApiResponse::Success { message, .. } => {
+                    out.message("Success", &message);
+                },
+                other => return Err(exit::response_error(other)),
+            }
+        },
+        
+        Commands::AddRule(args) => {
+            debug!("Adding filter rule: {}", args.label);
+            
+            let request = args.to_request()?;
+            
             let response = client.send_request(&request).await
                 .context("Failed to send add rule request")?;
             
             match response {
-                ApiResponse::Success { message } => {
-                    println!("Rule added: {}", message);
+                ApiResponse::Success { message, warning } if out.is_jsonl() => {
+                    out.set(serde_json::json!({ "message": message, "warning": warning }));
                 },
-                ApiResponse::Error { message } => {
-                    return Err(anyhow!("Error: {}", message));
+                ApiResponse::Success { message, warning } => {
+                    println!("Rule added: {}", message);
+                    if let Some(warning) = warning {
+                        println!("{}", utils::format_warning(&warning, style::enabled()));
+                    }
                 },
-                ApiResponse::Rules { .. } | ApiResponse::Stats { .. } => {
-                    return Err(anyhow!("Unexpected response type"))
-                }
+                other => return Err(exit::response_error(other)),
             }
         },
         
@@ -311,82 +1090,1850 @@ async fn main() -> Result<()> {
                 .context("Failed to send delete rule request")?;
             
             match response {
-                ApiResponse::Success { message } => {
-                    println!("Rule deleted: {}", message);
+                ApiResponse::Success { message, .. } => {
+                    out.message("Rule deleted", &message);
+                },
+                other => return Err(exit::response_error(other)),
+            }
+        },
+        
+        Commands::Apply { file, prune, dry_run, format } => {
+            let format = out.format(settings.format(format, "text"));
+            debug!("Applying ruleset: {}", file.display());
+            
+            let rules = ruleset::load_ruleset(file)?;
+            apply_ruleset(&client, out, rules, *prune, *dry_run, format).await?;
+        },
+        
+        Commands::Convert { from, file, .. } => {
+            let format = out.format(settings.format(&None, "text"));
+            debug!("Converting and applying {}", file.display());
+            
+            let rules = convert_rules(*from, file)?;
+            apply_ruleset(&client, out, rules, false, false, format).await?;
+        },
+        
+        Commands::RepairRules => {
+            debug!("Repairing rules");
+            
+            let response = client.send_request(&ApiRequest::RepairRules {}).await
+                .context("Failed to send repair rules request")?;
+            
+            match response {
+                ApiResponse::RulesRepaired { repaired, errors } => {
+                    if out.is_jsonl() {
+                        out.set(serde_json::json!({ "repaired": repaired, "errors": errors }));
+                    } else {
+                        for label in &repaired {
+                            println!("+ {}", label);
+                        }
+                        for e in &errors {
+                            println!("! {}: {}", e.label, e.message);
+                        }
+                        println!("{} repaired, {} failed", repaired.len(), errors.len());
+                    }
+                    
+                    if let Some(first) = errors.first() {
+                        return Err(exit::CliError::new(first.code.into(),
+                            format!("{} rule(s) could not be repaired", errors.len())).into());
+                    }
+                },
+                other => return Err(exit::response_error(other)),
+            }
+        },
+        
+        Commands::Reconcile { delete_orphans, reinstall, format } => {
+            let format = out.format(settings.format(format, "table"));
+            debug!("Reconciling kernel maps");
+            
+            let request = if *delete_orphans || *reinstall {
+                ApiRequest::ReconcileMaps { delete_orphans: *delete_orphans, reinstall_missing: *reinstall }
+            } else {
+                ApiRequest::AuditMaps {}
+            };
+            let response = client.send_request(&request).await
+                .context("Failed to send reconcile request")?;
+            
+            let summary = match response {
+                ApiResponse::MapAudit { audit } => api::ReconcileSummary { audit, ..Default::default() },
+                ApiResponse::MapsReconciled { summary } => summary,
+                other => return Err(exit::response_error(other)),
+            };
+            
+            match format {
+                "json" => {
+                    out.json(&summary)?;
                 },
-                ApiResponse::Error { message } => {
-                    return Err(anyhow!("Error: {}", message));
+                "table" => {
+                    let audit = &summary.audit;
+                    if audit.is_clean() {
+                        println!("Kernel maps match the rule cache");
+                    }
+                    if !audit.orphans.is_empty() {
+                        println!("Orphaned kernel entries:");
+                        for line in table::orphans_table(&audit.orphans).render() {
+                            println!("{}", line);
+                        }
+                    }
+                    if !audit.missing.is_empty() {
+                        if !audit.orphans.is_empty() {
+                            println!();
+                        }
+                        println!("Rule entries missing from the kernel:");
+                        for line in table::missing_entries_table(&audit.missing).render() {
+                            println!("{}", line);
+                        }
+                    }
+                    for e in &summary.errors {
+                        println!("! {}", e);
+                    }
+                    if matches!(request, ApiRequest::ReconcileMaps { .. }) {
+                        println!("{} orphaned entries deleted, {} missing entries reinstalled, {} failed",
+                            summary.deleted_orphans, summary.reinstalled, summary.errors.len());
+                    } else if !audit.is_clean() {
+                        println!("{} orphaned, {} missing (use --delete-orphans/--reinstall to fix)",
+                            audit.orphans.len(), audit.missing.len());
+                    }
                 },
-                ApiResponse::Rules { .. } | ApiResponse::Stats { .. } => {
-                    return Err(anyhow!("Unexpected response type"))
-                }
+                _ => return Err(anyhow!("Invalid format: {}", format)),
+            }
+            
+            if !summary.errors.is_empty() {
+                return Err(exit::CliError::new(exit::ExitKind::Internal,
+                    format!("{} kernel map entries could not be reconciled", summary.errors.len())).into());
             }
         },
         
-        Commands::ListRules { stats } => {
+        Commands::ListRules { stats, rates, wide, watch, expiring_within, sort, all_namespaces, family, all, origin, limit, offset, format, .. } => {
+            let format = out.format(settings.format(format, "table"));
             debug!("Listing filter rules");
             
-            let request = ApiRequest::ListRules {
-                include_stats: *stats,
+            let expiring_within_secs = match expiring_within {
+                Some(within) => Some(swift_guard::utils::parse_duration(within)?),
+                None => None,
+            };
+            let sort = match sort {
+                Some(sort) => Some(utils::parse_rule_sort(sort)?),
+                None => None,
+            };
+            
+            let paged = limit.is_some() || offset.is_some();
+            // 감시 모드는 갱신마다 같은 요청을 다시 보냄
+            let request = || ApiRequest::ListRules {
+                // 감시 모드는 카운터 변화를 보므로 항상 통계 포함 (매치율은 클라이언트가 계산)
+                include_stats: *stats || watch.is_some(),
+                expiring_within_secs,
+                include_rates: *rates && watch.is_none(),
+                all_namespaces: *all_namespaces,
+                family: *family,
+                include_dynamic: *all,
+                origin: origin.clone(),
+                cursor: None,
+                offset: offset.unwrap_or(0),
+                limit: if paged { limit.unwrap_or(0) } else { api::RULES_PAGE_SIZE },
             };
             
-            let response = client.send_request(&request).await
-                .context("Failed to send list rules request")?;
+            if let Some(interval) = *watch {
+                if format != "table" {
+                    return Err(anyhow!("--watch only supports table output"));
+                }
+                if interval == 0 {
+                    return Err(anyhow!("--watch interval must be at least 1s"));
+                }
+                let expiring = expiring_within_secs.is_some();
+                let order = |rules: &mut [api::RuleInfo]| order_rules(rules, expiring, sort, paged);
+                return watch_rules(&client, request, paged, order, *wide, interval, reconnect).await;
+            }
             
-            match response {
-                ApiResponse::Rules { rules } => {
+            let (mut rules, page_info) = fetch_rules(&client, request(), paged).await?;
+            order_rules(&mut rules, expiring_within_secs.is_some(), sort, paged);
+            
+            match format {
+                "json" => {
+                    out.json(&rules)?;
+                },
+                "yaml" => {
+                    print!("{}", serde_yaml::to_string(&rules)?);
+                },
+                "table" => {
                     if rules.is_empty() {
-                        println!("No rules found");
+                        match expiring_within {
+                            Some(within) => println!("No rules expiring within {}", within),
+                            None => println!("No rules found"),
+                        }
                     } else {
-                        println!("{:<20} {:<15} {:<20} {:<10} {:<10}", 
-                                "LABEL", "ACTION", "SOURCE", "DEST", "PROTOCOL");
-                        println!("{}", "-".repeat(80));
-                        
-                        for rule in rules {
-                            println!("{}", rule);
-                            if *stats {
-                                println!("  Packets: {}, Bytes: {}", 
-                                        rule.stats.packets, rule.stats.bytes);
-                            }
+                        let table = match expiring_within {
+                            Some(_) => table::expiring_rules_table(&rules, swift_guard::utils::current_time_secs()),
+                            None => table::rules_table(&rules, *stats, *wide),
+                        };
+                        for line in table.with_color(style::enabled()).render() {
+                            println!("{}", line);
                         }
+                        if let Some(total_count) = page_info {
+                            let first = offset.unwrap_or(0) as usize;
+                            println!("Showing {}-{} of {} rules", first + 1, first + rules.len(), total_count);
+                        }
+                    }
+                },
+                _ => return Err(anyhow!("Invalid format: {}", format)),
+            }
+        },
+        
+        Commands::ShowRule { label, format } => {
+            let format = out.format(settings.format(format, "table"));
+            debug!("Showing filter rule: {}", label);
+            
+            let request = ApiRequest::GetRule {
+                label: label.clone(),
+            };
+            
+            let response = client.send_request(&request).await
+                .context("Failed to send get rule request")?;
+            
+            match response {
+                ApiResponse::RuleDetail { rule: detail } => {
+                    match format {
+                        "json" => {
+                            out.json(&detail)?;
+                        },
+                        "table" => {
+                            let rule = &detail.info;
+                            let created = chrono::DateTime::<chrono::Utc>::from_timestamp(rule.creation_time as i64, 0)
+                                .map(|t| t.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M:%S").to_string())
+                                .unwrap_or_else(|| "-".to_string());
+                            let or_any = |v: &Option<String>| v.clone().unwrap_or_else(|| "*".to_string());
+                            let mark_down = |name: &String| if detail.down_interfaces.contains(name) {
+                                format!("{} (down)", name)
+                            } else {
+                                name.clone()
+                            };
+                            let interfaces = if detail.interfaces.is_empty() {
+                                "-".to_string()
+                            } else {
+                                detail.interfaces.iter().map(mark_down).collect::<Vec<_>>().join(", ")
+                            };
+                            
+                            println!("Label:        {}", rule.label);
+                            println!("Description:  {}", rule.description.as_deref().unwrap_or("-"));
+                            println!("Created by:   {}", rule.created_by.as_deref().unwrap_or("-"));
+                            println!("Created at:   {}", created);
+                            println!("TTL:          {}", utils::format_ttl(detail.ttl_remaining, rule.expire));
+                            println!("Action:       {}", rule.action);
+                            if let Some(group) = &rule.counter_group {
+                                println!("Counter:      {}", group);
+                            }
+                            if let Some(preset) = &rule.preset {
+                                println!("Preset:       {}", preset);
+                            }
+                            if let Some(ifname) = &rule.redirect_if {
+                                println!("Redirect if:  {}", mark_down(ifname));
+                            }
+                            if let Some(cpu) = rule.redirect_cpu {
+                                println!("Redirect cpu: {}", cpu);
+                            }
+                            println!("Source:       {} port {}", or_any(&rule.source_addr()), or_any(&rule.src_port));
+                            if let Some(error) = rule.src_host.as_ref().and_then(|host| host.error.as_ref()) {
+                                println!("Resolve err:  {} (using last known addresses)", error);
+                            }
+                            if let Some(geo) = &detail.src_geo {
+                                println!("Source geo:   {}", geo);
+                            }
+                            println!("Destination:  {} port {}", or_any(&rule.dst_ip), or_any(&rule.dst_port));
+                            println!("Protocol:     {}", rule.protocol);
+                            println!("TCP flags:    {}", rule.tcp_flags.as_deref().unwrap_or("-"));
+                            println!("Priority:     {}", rule.priority);
+                            println!("Rate limit:   {}", if rule.rate_limit == 0 { "-".to_string() } else { format!("{} pps", swift_guard::utils::format_count(rule.rate_limit.into())) });
+                            if let Some(quota) = &rule.quota {
+                                println!("Quota:        {}{}", utils::format_quota(quota),
+                                    if rule.quota_exceeded { " (exceeded)" } else { "" });
+                            }
+                            println!("Auto:         {}", rule.auto);
+                            if let Some(origin) = &rule.origin {
+                                println!("Origin:       {}", origin);
+                            }
+                            println!("Degraded:     {}", rule.degraded);
+                            println!("Installed:    {}", detail.installed);
+                            println!("Effective:    {}", rule.effective);
+                            match detail.wildcard_slot {
+                                Some(slot) => println!("Map:          {} (slot {})", rule.map.as_str(), slot),
+                                None => println!("Map:          {}", rule.map.as_str()),
+                            }
+                            println!("LPM key:      {}", detail.lpm_key.as_deref().unwrap_or("-"));
+                            println!("Interfaces:   {}", interfaces);
+                            println!("Packets:      {}", rule.stats.packets);
+                            println!("Bytes:        {}", rule.stats.bytes);
+                            if rule.action == "redirect" {
+                                println!("Redirected:   {}", rule.stats.redirected_packets);
+                                println!("Redirect err: {}", rule.stats.redirect_failures);
+                                println!("Aborted:      {}", rule.stats.aborted_packets);
+                            }
+                            if !detail.recent_matches.is_empty() {
+                                let matches: Vec<String> = detail.recent_matches.iter().map(|t| t.to_string()).collect();
+                                println!("Last matches: {}", matches.join(", "));
+                            }
+                            if let Some(peak) = detail.history_1m.iter().copied().max() {
+                                println!("Last hour:    {} (peak {}/min)", utils::render_sparkline(&detail.history_1m),
+                                    swift_guard::utils::format_count(peak));
+                            }
+                        },
+                        _ => return Err(anyhow!("Invalid format: {}", format)),
                     }
                 },
-                _ => {
-                    return Err(anyhow!("Unexpected response from server"));
+                other => return Err(exit::response_error(other)),
+            }
+        },
+        
+        Commands::Counters { prefix, watch, format } => {
+            let format = out.format(settings.format(format, "table"));
+            debug!("Showing named counters");
+            
+            let request = ApiRequest::GetCounters { prefix: prefix.clone() };
+            
+            if let Some(interval) = *watch {
+                if format != "table" {
+                    return Err(anyhow!("--watch only supports table output"));
                 }
+                if interval == 0 {
+                    return Err(anyhow!("--watch interval must be at least 1s"));
+                }
+                return watch_counters(&client, &request, interval).await;
+            }
+            
+            let counters = fetch_counters(&client, &request).await?;
+            match format {
+                "json" => {
+                    out.json(&counters)?;
+                },
+                "table" => {
+                    if counters.is_empty() {
+                        println!("No counters found");
+                    } else {
+                        for line in table::counters_table(&counters).render() {
+                            println!("{}", line);
+                        }
+                    }
+                },
+                _ => return Err(anyhow!("Invalid format: {}", format)),
             }
         },
         
-        Commands::Stats { interval } => {
+        Commands::Stats { interval, count, self_stats } => {
             debug!("Showing performance statistics");
             
-            println!("Collecting statistics (press Ctrl+C to exit)...");
+            if *count == 0 && !out.is_jsonl() {
+                println!("Collecting statistics (press Ctrl+C to exit)...");
+            }
             
-            loop {
+            // 데몬이 재시작되면 다시 연결될 때까지 기다렸다가 이어서 출력
+            let mut reconnector = Reconnector::new(reconnect);
+            for round in 1.. {
                 let request = ApiRequest::GetStats {};
                 
-                let response = client.send_request(&request).await
+                let (response, reconnected) = reconnector.run(|| client.send_request(&request)).await
                     .context("Failed to send get stats request")?;
+                if let Some(reconnected) = reconnected {
+                    eprintln!("{}", utils::format_reconnected(&reconnected));
+                }
                 
                 match response {
+                    // jsonl 모드는 갱신마다 봉투 하나
+                    ApiResponse::Stats { stats } if out.is_jsonl() => out.emit(&stats)?,
                     ApiResponse::Stats { stats } => {
                         println!("Timestamp: {}", chrono::Local::now().format("%Y-%m-%d %H:%M:%S"));
+                        // 중단 패킷과 이상 트래픽은 맨 위에 경고 색으로 표시
+                        let color = style::enabled();
+                        println!("{}", style::paint_with(&utils::format_aborted(stats.aborted_packets),
+                            (stats.aborted_packets > 0).then_some(style::RED), color));
+                        if stats.anomaly {
+                            println!("{}", style::paint(&utils::format_anomaly(&stats.baseline), style::RED, color));
+                        }
                         println!("Total packets: {}", stats.total_packets);
                         println!("Total bytes: {} ({:.2} MB)", 
                                 stats.total_bytes, 
                                 stats.total_bytes as f64 / (1024.0 * 1024.0));
                         println!("Packets/sec: {}", stats.packets_per_sec);
                         println!("Bandwidth: {:.2} Mbps", stats.mbps);
+                        if stats.baseline.samples > 0 {
+                            println!("{}", utils::format_baseline(&stats.baseline));
+                        }
+                        println!("Protocols:");
+                        for (name, count) in stats.protocols.entries() {
+                            println!("  {:<6} {:>12} ({:>5.1}%)",
+                                    utils::protocol_stat_name(name), count, stats.protocols.percent(count));
+                        }
+                        if !stats.size_histogram.counts.is_empty() {
+                            println!("Packet sizes:");
+                            for line in render_histogram(&stats.size_histogram.counts, 40) {
+                                println!("  {}", line);
+                            }
+                        }
+                        println!("{}", utils::format_rule_summary(&stats.rules));
+                        if *self_stats {
+                            println!("Daemon:");
+                            for line in utils::format_daemon_stats(&stats.daemon) {
+                                println!("  {}", line);
+                            }
+                            println!("  {}", utils::format_api_summary(&stats.api));
+                            println!("  {}", utils::format_webhook_summary(&stats.webhooks));
+                        }
                         println!("{}", "-".repeat(40));
                     },
-                    _ => {
-                        return Err(anyhow!("Unexpected response from server"));
-                    }
+                    other => return Err(exit::response_error(other)),
+                }
+                
+                if round == *count {
+                    break;
                 }
                 
                 tokio::time::sleep(std::time::Duration::from_secs(*interval)).await;
             }
         },
+        
+        Commands::Monitor { rules: true, all_namespaces, .. } => {
+            debug!("Monitoring rule changes");
+            
+            let request = ApiRequest::WatchRules { all_namespaces: *all_namespaces };
+            let mut reconnector = Reconnector::new(reconnect);
+            
+            // 변경은 마지막 스냅샷이나 변경보다 큰 순번으로만 와야 함
+            let mut last_seq = None;
+            loop {
+                // 다시 구독하면 재시작한 데몬이 새 순번의 스냅샷부터 보냄
+                let (mut subscription, mut reconnected) = reconnector.run(|| client.subscribe(&request)).await
+                    .context("Failed to send rule watch request")?;
+                if reconnected.is_some() {
+                    last_seq = None;
+                }
+                
+                let closed = loop {
+                    let response = match subscription.next().await {
+                        Ok(Some(response)) => response,
+                        Ok(None) => break ClientError::Unreachable("API server closed the rule watch".to_string()),
+                        Err(e) => break e,
+                    };
+                    match response {
+                        ApiResponse::Rules { rules, seq: Some(seq), .. } => {
+                            if let Some(reconnected) = reconnected.take() {
+                                eprintln!("{}, snapshot of {} rules replayed", utils::format_reconnected(&reconnected), rules.len());
+                            } else if last_seq.is_some() {
+                                eprintln!("{}", utils::format_warning(
+                                    "rule watch fell behind; the daemon sent a new snapshot", style::enabled()));
+                            }
+                            if out.is_jsonl() {
+                                out.emit(&serde_json::json!({ "seq": seq, "kind": "snapshot", "rules": rules }))?;
+                            } else {
+                                println!("#{} snapshot {} rules", seq, rules.len());
+                                for rule in &rules {
+                                    println!("{}", utils::format_rule_change(seq, "present", rule));
+                                }
+                            }
+                            last_seq = Some(seq);
+                        },
+                        ApiResponse::RuleChanged { seq, kind, rule } => {
+                            let misordered = match last_seq {
+                                Some(last) if seq > last => None,
+                                Some(last) => Some(format!("API server sent rule change {} out of order (after {})", seq, last)),
+                                None => Some(format!("API server sent rule change {} before a snapshot", seq)),
+                            };
+                            if let Some(message) = misordered {
+                                return Err(exit::CliError::new(exit::ExitKind::Internal, message).into());
+                            }
+                            if out.is_jsonl() {
+                                out.emit(&serde_json::json!({ "seq": seq, "kind": kind.as_str(), "rule": rule }))?;
+                            } else {
+                                println!("{}", style::paint_with(&utils::format_rule_change(seq, kind.as_str(), &rule),
+                                    style::kind_color(kind.as_str()), style::enabled()));
+                            }
+                            last_seq = Some(seq);
+                        },
+                        other => return Err(exit::response_error(other)),
+                    }
+                };
+                
+                // 연결이 끊겼으면 기다렸다가 다시 구독 (시도 시간이 지나면 종료)
+                match reconnector.on_failure(&closed, std::time::Instant::now()) {
+                    Some(delay) => tokio::time::sleep(delay).await,
+                    None => return Err(closed.into()),
+                }
+            }
+        },
+        
+        Commands::Monitor { since_seq, replay, kinds, no_follow, interval, .. } => {
+            debug!("Monitoring daemon events");
+            
+            // 순번을 지정하지 않으면 최근 --replay개만 받고 이후 새 이벤트를 따라감
+            let mut subscription = client.subscribe_events()
+                .with_since_seq(*since_seq)
+                .with_replay(*replay)
+                .with_kinds(kinds.clone())
+                .with_interval(std::time::Duration::from_secs(*interval))
+                .with_reconnect(reconnect);
+            
+            // 다시 연결된 뒤 최신 이벤트까지 받으면 재연결 알림 하나 출력
+            let mut replaying = None;
+            loop {
+                let batch = subscription.next().await
+                    .context("Failed to send event history request")?;
+                if let Some(reconnected) = batch.reconnected {
+                    replaying = Some((reconnected, 0));
+                }
+                if let Some((_, replayed)) = &mut replaying {
+                    *replayed += batch.events.len();
+                }
+                if let Some(gap) = batch.gap {
+                    eprintln!("{}", utils::format_warning(&utils::format_event_gap(&gap), style::enabled()));
+                }
+                for event in &batch.events {
+                    if out.is_jsonl() {
+                        out.emit(event)?;
+                    } else {
+                        println!("{}", style::paint_with(&utils::format_event(event),
+                            style::kind_color(event.event.kind()), style::enabled()));
+                    }
+                }
+                if let Some((reconnected, replayed)) = replaying.filter(|_| batch.caught_up) {
+                    eprintln!("{}, {} events replayed", utils::format_reconnected(&reconnected), replayed);
+                    replaying = None;
+                }
+                
+                if batch.caught_up && *no_follow {
+                    break;
+                }
+            }
+        },
+        
+        Commands::Wasm { command: WasmCommands::Logs { name, lines, follow, interval } } => {
+            debug!("Showing WASM module log: {}", name);
+            
+            let mut request = ApiRequest::GetWasmModuleLog { name: name.clone(), lines: *lines, after: None };
+            
+            for round in 0.. {
+                let response = client.send_request(&request).await
+                    .context("Failed to send WASM log request")?;
+                
+                let lines = match response {
+                    ApiResponse::WasmModuleLog { lines, .. } => lines,
+                    other => return Err(exit::response_error(other)),
+                };
+                if !out.is_jsonl() {
+                    for line in &lines {
+                        println!("{}", utils::format_log_line(line));
+                    }
+                } else if round == 0 || !lines.is_empty() {
+                    // jsonl 모드는 첫 조회와 새 줄이 있는 조회마다 봉투 하나
+                    out.emit(&lines)?;
+                }
+                
+                if !*follow {
+                    break;
+                }
+                
+                // 이후에는 마지막으로 받은 줄 다음부터 전부 조회
+                if let ApiRequest::GetWasmModuleLog { lines: count, after, .. } = &mut request {
+                    *count = 0;
+                    if let Some(last) = lines.last() {
+                        *after = Some(last.seq);
+                    }
+                }
+                
+                tokio::time::sleep(std::time::Duration::from_secs(*interval)).await;
+            }
+        },
+        
+        Commands::Wasm { command } => {
+            let request = match command {
+                WasmCommands::Load { name, file, on_error, config_file, mode } => ApiRequest::LoadWasmModule {
+                    name: name.clone(),
+                    file_path: file.display().to_string(),
+                    on_error: on_error.as_deref().map(str::parse::<WasmErrorPolicy>).transpose()?,
+                    config: config_file.as_ref()
+                        .map(|path| std::fs::read_to_string(path)
+                            .with_context(|| format!("Failed to read module config file: {}", path.display())))
+                        .transpose()?,
+                    mode: mode.as_deref().map(str::parse::<WasmModuleMode>).transpose()?.unwrap_or_default(),
+                },
+                WasmCommands::Unload { name } => ApiRequest::UnloadWasmModule {
+                    name: name.clone(),
+                },
+                WasmCommands::Resume { name } => ApiRequest::ResumeWasmModule {
+                    name: name.clone(),
+                },
+                WasmCommands::SetMode { name, mode } => ApiRequest::SetWasmModuleState {
+                    name: name.clone(),
+                    mode: mode.parse()?,
+                },
+                WasmCommands::List => ApiRequest::ListWasmModules {},
+                WasmCommands::Stats { name } => ApiRequest::WasmModuleStats {
+                    name: name.clone(),
+                },
+                WasmCommands::ResetStats { name } => ApiRequest::ResetWasmModuleStats {
+                    name: name.clone(),
+                },
+                WasmCommands::CacheClear => ApiRequest::ClearWasmCache {},
+                WasmCommands::Logs { .. } => unreachable!("handled above"),
+            };
+            debug!("Sending WASM request: {:?}", request);
+            
+            let response = client.send_request(&request).await
+                .context("Failed to send WASM request")?;
+            
+            match response {
+                ApiResponse::Success { message, .. } => {
+                    out.message("Success", &message);
+                },
+                ApiResponse::WasmModules { modules } if out.is_jsonl() => out.json(&modules)?,
+                ApiResponse::WasmModules { modules } => {
+                    if modules.is_empty() {
+                        println!("No WASM modules loaded");
+                    } else {
+                        let mut table = table::Table::new(["NAME", "STATE", "MODE", "WOULD-BLOCK", "LOADED-AT"]);
+                        for module in modules {
+                            table.add_row(vec![
+                                module.name,
+                                module.state,
+                                module.mode.as_str().to_string(),
+                                module.would_block_packets.to_string(),
+                                module.loaded_at.to_string(),
+                            ]);
+                        }
+                        for line in table.render() {
+                            println!("{}", line);
+                        }
+                    }
+                },
+                ApiResponse::WasmModuleStats { name, processed_packets, blocked_packets, avg_processing_time_us,
+                                               state, errors, last_error, latency, p50_us, p95_us, p99_us,
+                                               mode, would_block_packets } if out.is_jsonl() => {
+                    out.set(serde_json::json!({
+                        "name": name,
+                        "state": state,
+                        "mode": mode,
+                        "would_block_packets": would_block_packets,
+                        "processed_packets": processed_packets,
+                        "blocked_packets": blocked_packets,
+                        "avg_processing_time_us": avg_processing_time_us,
+                        "errors": errors,
+                        "last_error": last_error,
+                        "latency": latency,
+                        "p50_us": p50_us,
+                        "p95_us": p95_us,
+                        "p99_us": p99_us,
+                    }));
+                },
+                ApiResponse::WasmModuleStats { name, processed_packets, blocked_packets, avg_processing_time_us,
+                                               state, errors, last_error, latency, p50_us, p95_us, p99_us,
+                                               mode, would_block_packets } => {
+                    println!("Module: {}", name);
+                    if let Some(state) = state {
+                        println!("State: {}", state);
+                    }
+                    println!("Mode: {}", mode.as_str());
+                    println!("Processed packets: {}", processed_packets);
+                    println!("Blocked packets: {}", blocked_packets);
+                    println!("Would-block packets: {}", would_block_packets);
+                    println!("Avg processing time: {:.2} us", avg_processing_time_us);
+                    if let (Some(p50), Some(p95), Some(p99)) = (p50_us, p95_us, p99_us) {
+                        println!("Processing time: p50 {:.2} us, p95 {:.2} us, p99 {:.2} us", p50, p95, p99);
+                        for line in utils::render_latency_histogram(&latency, 40) {
+                            println!("{}", line);
+                        }
+                    }
+                    println!("Errors: {}", errors);
+                    if let Some(error) = last_error {
+                        println!("Last error: {}", error);
+                    }
+                },
+                other => return Err(exit::response_error(other)),
+            }
+        },
+        
+        Commands::Conntrack { filter_src_ip, filter_dst_ip, filter_src_port, filter_dst_port,
+                              filter_protocol, limit, format } => {
+            let format = out.format(settings.format(format, "table"));
+            debug!("Listing tracked connections");
+            
+            let protocol = match filter_protocol {
+                Some(p) => Some(utils::protocol_name_to_num(p)?),
+                None => None,
+            };
+            
+            let request = ApiRequest::ListConnections {
+                limit: *limit,
+                filter: ConnectionFilter {
+                    src_ip: filter_src_ip.clone(),
+                    dst_ip: filter_dst_ip.clone(),
+                    src_port: *filter_src_port,
+                    dst_port: *filter_dst_port,
+                    protocol,
+                },
+            };
+            
+            let response = client.send_request(&request).await
+                .context("Failed to send list connections request")?;
+            
+            match response {
+                ApiResponse::Connections { connections, total } => {
+                    match format {
+                        "json" => {
+                            out.json(&connections)?;
+                        },
+                        "table" => {
+                            if connections.is_empty() {
+                                println!("No connections found");
+                            } else {
+                                println!("{:<22} {:<22} {:<6} {:<12} {:>8} {:>8} {:>10} {:>12}",
+                                        "SOURCE", "DEST", "PROTO", "STATE", "AGE", "IDLE", "PACKETS", "BYTES");
+                                println!("{}", "-".repeat(108));
+                                
+                                for conn in &connections {
+                                    let state = if conn.expired {
+                                        format!("{}*", conn.state)
+                                    } else {
+                                        conn.state.clone()
+                                    };
+                                    println!("{:<22} {:<22} {:<6} {:<12} {:>7}s {:>7}s {:>10} {:>12}",
+                                            format!("{}:{}", conn.src_ip, conn.src_port),
+                                            format!("{}:{}", conn.dst_ip, conn.dst_port),
+                                            conn.protocol, state, conn.age_secs, conn.idle_secs,
+                                            conn.packets, conn.bytes);
+                                }
+                                
+                                println!("Showing {} of {} connections (* = idle past TTL, pending eviction)",
+                                        connections.len(), total);
+                            }
+                        },
+                        _ => return Err(anyhow!("Invalid format: {}", format)),
+                    }
+                },
+                other => return Err(exit::response_error(other)),
+            }
+        },
+        
+        Commands::Lookup { ip, format } => {
+            let format = out.format(settings.format(format, "table"));
+            debug!("Looking up GeoIP information for {}", ip);
+            
+            ip.parse::<std::net::IpAddr>()
+                .map_err(|_| anyhow!("Invalid IP address: {}", ip))?;
+            
+            let response = client.send_request(&ApiRequest::GeoLookup { ip: ip.clone() }).await
+                .context("Failed to send GeoIP lookup request")?;
+            
+            match response {
+                ApiResponse::Geo { ip, geo } => {
+                    match format {
+                        "json" => {
+                            out.json(&serde_json::json!({ "ip": ip, "geo": geo }))?;
+                        },
+                        "table" => {
+                            println!("IP:       {}", ip);
+                            println!("Country:  {}", geo.country.as_deref().unwrap_or("-"));
+                            println!("ASN:      {}", geo.asn.map(|asn| format!("AS{}", asn)).unwrap_or_else(|| "-".to_string()));
+                            println!("AS org:   {}", geo.as_org.as_deref().unwrap_or("-"));
+                        },
+                        _ => return Err(anyhow!("Invalid format: {}", format)),
+                    }
+                },
+                other => return Err(exit::response_error(other)),
+            }
+        },
+        
+        Commands::BlockCountry { code, action, expire, csv, format } => {
+            let format = out.format(settings.format(format, "table"));
+            debug!("Blocking country {}", code);
+            
+            let request = ApiRequest::BlockCountry {
+                code: code.clone(),
+                action: *action,
+                expire: *expire,
+                csv_path: csv.clone(),
+            };
+            
+            let response = client.send_request(&request).await
+                .context("Failed to send block country request")?;
+            print_country_blocks(out, response, format)?;
+        },
+        
+        Commands::RefreshCountries { code, format } => {
+            let format = out.format(settings.format(format, "table"));
+            debug!("Refreshing country blocks");
+            
+            let response = client.send_request(&ApiRequest::RefreshCountryBlocks { code: code.clone() }).await
+                .context("Failed to send refresh country blocks request")?;
+            print_country_blocks(out, response, format)?;
+        },
+        
+        Commands::UnblockCountry { code } => {
+            debug!("Unblocking country {}", code);
+            
+            let response = client.send_request(&ApiRequest::UnblockCountry { code: code.clone() }).await
+                .context("Failed to send unblock country request")?;
+            
+            match response {
+                ApiResponse::Success { message, .. } => {
+                    out.message("Success", &message);
+                },
+                other => return Err(exit::response_error(other)),
+            }
+        },
+        
+        Commands::ListInterfaces { format } => {
+            let format = out.format(settings.format(format, "table"));
+            debug!("Listing interfaces");
+            
+            let response = client.send_request(&ApiRequest::ListInterfaces {}).await
+                .context("Failed to send list interfaces request")?;
+            
+            match response {
+                ApiResponse::Interfaces { interfaces } => {
+                    match format {
+                        "json" => {
+                            out.json(&interfaces)?;
+                        },
+                        "table" => {
+                            if interfaces.is_empty() {
+                                println!("No interfaces attached");
+                            } else {
+                                for line in table::interfaces_table(&interfaces).render() {
+                                    println!("{}", line);
+                                }
+                            }
+                        },
+                        _ => return Err(anyhow!("Invalid format: {}", format)),
+                    }
+                },
+                other => return Err(exit::response_error(other)),
+            }
+        },
+        
+        Commands::Redirects { command: Some(command), .. } => {
+            let request = match command {
+                RedirectCommands::Add { interface } => ApiRequest::AddRedirectTarget { interface: interface.clone() },
+                RedirectCommands::Remove { interface } => ApiRequest::RemoveRedirectTarget { interface: interface.clone() },
+            };
+            
+            let response = client.send_request(&request).await
+                .context("Failed to send redirect target request")?;
+            
+            match response {
+                ApiResponse::Success { message, .. } => {
+                    out.message("Success", &message);
+                },
+                other => return Err(exit::response_error(other)),
+            }
+        },
+        
+        Commands::Redirects { command: None, format } => {
+            let format = out.format(settings.format(format, "table"));
+            debug!("Listing redirect targets");
+            
+            let response = client.send_request(&ApiRequest::ListRedirectTargets {}).await
+                .context("Failed to send list redirect targets request")?;
+            
+            match response {
+                ApiResponse::RedirectTargets { targets } => {
+                    match format {
+                        "json" => {
+                            out.json(&targets)?;
+                        },
+                        "table" => {
+                            if targets.is_empty() {
+                                println!("No redirect targets");
+                            } else {
+                                for line in table::redirect_targets_table(&targets).render() {
+                                    println!("{}", line);
+                                }
+                            }
+                        },
+                        _ => return Err(anyhow!("Invalid format: {}", format)),
+                    }
+                },
+                other => return Err(exit::response_error(other)),
+            }
+        },
+        
+        Commands::DynamicBlocks { module, clear: true, .. } => {
+            debug!("Clearing dynamic blocks");
+            
+            let response = client.send_request(&ApiRequest::ClearDynamicBlocks { module: module.clone() }).await
+                .context("Failed to send clear dynamic blocks request")?;
+            
+            match response {
+                ApiResponse::Success { message, .. } => {
+                    out.message("Success", &message);
+                },
+                other => return Err(exit::response_error(other)),
+            }
+        },
+        
+        Commands::DynamicBlocks { module, clear: false, format } => {
+            let format = out.format(settings.format(format, "table"));
+            debug!("Listing dynamic blocks");
+            
+            let response = client.send_request(&ApiRequest::ListDynamicBlocks { module: module.clone() }).await
+                .context("Failed to send list dynamic blocks request")?;
+            
+            match response {
+                ApiResponse::DynamicBlocks { blocks } => {
+                    match format {
+                        "json" => {
+                            out.json(&blocks)?;
+                        },
+                        "table" => {
+                            if blocks.is_empty() {
+                                println!("No dynamic blocks");
+                            } else {
+                                for line in table::dynamic_blocks_table(&blocks).render() {
+                                    println!("{}", line);
+                                }
+                            }
+                        },
+                        _ => return Err(anyhow!("Invalid format: {}", format)),
+                    }
+                },
+                other => return Err(exit::response_error(other)),
+            }
+        },
+        
+        Commands::Preset { command: PresetCommands::IcmpProtect { limit, interface, format } } => {
+            let format = out.format(settings.format(format, "text"));
+            debug!("Applying preset icmp-protect");
+            
+            let request = icmp_protect_request(*limit, interface);
+            
+            let response = client.send_request(&request).await
+                .context("Failed to send apply preset request")?;
+            print_preset_summary(out, response, format)?;
+        },
+        
+        Commands::Preset { command: PresetCommands::AmplificationProtect { drop, threshold, interface, format } } => {
+            let format = out.format(settings.format(format, "text"));
+            debug!("Applying preset amplification-protect");
+            
+            let request = amplification_protect_request(*drop, *threshold, interface);
+            
+            let response = client.send_request(&request).await
+                .context("Failed to send apply preset request")?;
+            print_preset_summary(out, response, format)?;
+        },
+        
+        Commands::Preset { command: PresetCommands::Remove { name } } => {
+            debug!("Removing preset: {}", name);
+            
+            let response = client.send_request(&ApiRequest::RemovePreset { name: name.clone() }).await
+                .context("Failed to send remove preset request")?;
+            
+            match response {
+                ApiResponse::Success { message, .. } => {
+                    out.message("Success", &message);
+                },
+                other => return Err(exit::response_error(other)),
+            }
+        },
+        
+        Commands::Preset { command: PresetCommands::List { format } } => {
+            let format = out.format(settings.format(format, "table"));
+            debug!("Listing presets");
+            
+            let response = client.send_request(&ApiRequest::ListPresets {}).await
+                .context("Failed to send list presets request")?;
+            
+            match response {
+                ApiResponse::Presets { presets } => {
+                    match format {
+                        "json" => {
+                            out.json(&presets)?;
+                        },
+                        "table" => {
+                            for line in table::presets_table(&presets).render() {
+                                println!("{}", line);
+                            }
+                        },
+                        _ => return Err(anyhow!("Invalid format: {}", format)),
+                    }
+                },
+                other => return Err(exit::response_error(other)),
+            }
+        },
+        
+        Commands::Version { remote, format } => {
+            let format = out.format(settings.format(format, "table"));
+            let client_build = BuildInfo::current();
+            
+            let daemon_build = if *remote {
+                debug!("Requesting daemon build info");
+                match client.send_request(&ApiRequest::GetVersion {}).await
+                    .context("Failed to send version request")? {
+                    ApiResponse::Version { build } => Some(build),
+                    other => return Err(exit::response_error(other)),
+                }
+            } else {
+                None
+            };
+            
+            match format {
+                "json" => {
+                    let mut versions = serde_json::json!({ "client": client_build });
+                    if let Some(daemon) = daemon_build {
+                        versions["daemon"] = serde_json::to_value(daemon)?;
+                    }
+                    out.json(&versions)?;
+                },
+                "table" => {
+                    for line in table::build_info_table(&client_build, daemon_build.as_ref()).render() {
+                        println!("{}", line);
+                    }
+                    if let Some(daemon) = &daemon_build {
+                        if daemon.version != client_build.version || daemon.git_sha != client_build.git_sha {
+                            println!();
+                            println!("Client and daemon were built from different sources.");
+                        }
+                    }
+                },
+                _ => return Err(anyhow!("Invalid format: {}", format)),
+            }
+        },
+        
+        Commands::Doctor { format } => {
+            let format = out.format(settings.format(format, "text"));
+            debug!("Requesting capability checks");
+            
+            let response = client.send_request(&ApiRequest::Ping {}).await
+                .context("Failed to send ping request")?;
+            
+            match response {
+                ApiResponse::Pong { version, capabilities, api, webhooks, build, load_failure, peer, features } => {
+                    match format {
+                        "json" => {
+                            out.json(&capabilities)?;
+                        },
+                        "text" | "table" => {
+                            match build {
+                                Some(build) => println!("swift-guard-daemon {}", build),
+                                None => println!("swift-guard-daemon {}", version),
+                            }
+                            if let Some(peer) = &peer {
+                                println!("Connected as {}", peer);
+                            }
+                            println!("{}", utils::format_api_summary(&api));
+                            println!("{}", utils::format_webhook_summary(&webhooks));
+                            if let Some(features) = features {
+                                println!("{}", utils::format_xdp_features(XdpFeatures(features)));
+                            }
+                            for line in utils::render_checklist(&capabilities, style::enabled()) {
+                                println!("{}", line);
+                            }
+                            if let Some(failure) = &load_failure {
+                                println!();
+                                for line in utils::format_load_failure(failure) {
+                                    println!("{}", line);
+                                }
+                            }
+                        },
+                        _ => return Err(anyhow!("Invalid format: {}", format)),
+                    }
+                },
+                other => return Err(exit::response_error(other)),
+            }
+        },
+        
+        Commands::Selftest { interface, format } => {
+            let format = out.format(settings.format(format, "text"));
+            debug!("Running self-test on {}", interface);
+            
+            let response = client.send_request(&ApiRequest::SelfTest { interface: interface.clone() }).await
+                .context("Failed to send self-test request")?;
+            
+            let report = match response {
+                ApiResponse::SelfTest { report } => report,
+                other => return Err(exit::response_error(other)),
+            };
+            
+            match format {
+                "json" => {
+                    out.json(&report)?;
+                },
+                "text" | "table" => {
+                    println!("Marker: {}", report.marker);
+                    println!("Rule: {}", report.label);
+                    println!("Self-test {}", report.summary());
+                },
+                _ => return Err(anyhow!("Invalid format: {}", format)),
+            }
+            
+            if let Some(e) = &report.cleanup_error {
+                eprintln!("{}", utils::format_warning(
+                    &format!("temporary rule '{}' was not removed ({}), it expires on its own", report.label, e),
+                    style::enabled()));
+            }
+            if !report.passed {
+                return Err(exit::CliError::new(exit::ExitKind::Internal,
+                    format!("Self-test failed on {}", report.interface)).into());
+            }
+        },
+        
+        Commands::Bench { interface, peer, duration, pkt_size, rules, modes, yes, format } => {
+            let format = out.format(settings.format(format, "table"));
+            let target = interface.clone().unwrap_or_else(|| "a temporary veth pair".to_string());
+            if !yes && !utils::confirm(&format!(
+                "Benchmark installs {} rules, attaches XDP to {} and sends traffic for {}s per mode. Continue?",
+                rules, target, duration))?
+            {
+                return Err(exit::CliError::new(exit::ExitKind::InvalidArgument,
+                    "Benchmark not confirmed (use --yes to run without a prompt)").into());
+            }
+            debug!("Running benchmark on {}", target);
+            
+            // 측정하는 동안 데몬이 초마다 보내는 진행 상황은 터미널이면 stderr에 막대로 표시
+            let request = ApiRequest::RunBenchmark {
+                interface: interface.clone(),
+                peer: peer.clone(),
+                duration: *duration,
+                pkt_size: *pkt_size,
+                rules: *rules,
+                modes: modes.clone(),
+                confirm: true,
+            };
+            let show_progress = utils::show_progress();
+            let mut drawn = false;
+            let response = client.send_request_with_progress(&request, |done, total| {
+                if show_progress {
+                    eprint!("\r{}", utils::format_progress(done, total, 30));
+                    drawn = true;
+                }
+            }).await;
+            if drawn {
+                eprintln!();
+            }
+            let report = match response.context("Failed to send benchmark request")? {
+                ApiResponse::Benchmark { report } => report,
+                other => return Err(exit::response_error(other)),
+            };
+            
+            match format {
+                "json" => {
+                    out.json(&report)?;
+                },
+                "text" | "table" => {
+                    println!("Benchmark on {} (traffic from {}), {} rules, {}-byte frames, {}s per mode",
+                        report.interface, report.peer, report.rules, report.pkt_size, report.duration_secs);
+                    if !report.modes.is_empty() {
+                        for line in table::bench_table(&report.modes).with_color(style::enabled()).render() {
+                            println!("{}", line);
+                        }
+                    }
+                },
+                _ => return Err(anyhow!("Invalid format: {}", format)),
+            }
+            
+            for error in &report.cleanup_errors {
+                eprintln!("{}", utils::format_warning(
+                    &format!("benchmark cleanup failed: {} (rules expire on their own)", error), style::enabled()));
+            }
+            if report.aborted {
+                eprintln!("{}", utils::format_warning("benchmark was aborted before all modes ran", style::enabled()));
+            }
+            if let (Some(stage), Some(error)) = (&report.failed_stage, &report.error) {
+                return Err(exit::CliError::new(exit::ExitKind::Internal,
+                    format!("Benchmark failed at {}: {}", stage, error)).into());
+            }
+        },
+        
+        Commands::Conflicts { format } => {
+            let format = out.format(settings.format(format, "table"));
+            debug!("Explaining rule conflicts");
+            
+            let response = client.send_request(&ApiRequest::ExplainConflicts {}).await
+                .context("Failed to send explain conflicts request")?;
+            
+            match response {
+                ApiResponse::Conflicts { conflicts } => {
+                    match format {
+                        "json" => {
+                            out.json(&conflicts)?;
+                        },
+                        "table" => {
+                            if conflicts.is_empty() {
+                                println!("No overlapping rules");
+                            } else {
+                                for line in table::conflicts_table(&conflicts).render() {
+                                    println!("{}", line);
+                                }
+                                println!();
+                                println!("Overlapping source prefixes are matched by the longest prefix, not by priority.");
+                            }
+                        },
+                        _ => return Err(anyhow!("Invalid format: {}", format)),
+                    }
+                },
+                other => return Err(exit::response_error(other)),
+            }
+        },
+        
+        Commands::Rulespace { format } => {
+            let format = out.format(settings.format(format, "text"));
+            debug!("Getting rule space stats");
+            
+            let response = client.send_request(&ApiRequest::RuleSpaceStats {}).await
+                .context("Failed to send rule space stats request")?;
+            
+            let stats = match response {
+                ApiResponse::RuleSpace { stats } => stats,
+                other => return Err(exit::response_error(other)),
+            };
+            
+            match format {
+                "json" => {
+                    out.json(&stats)?;
+                },
+                "text" => {
+                    println!("LPM entries: {} of {} ({:.1}% full)", stats.kernel_entries.unwrap_or(stats.lpm_entries),
+                        stats.capacity, stats.fill_percent);
+                    println!("Wildcard rules: {}", stats.wildcard_rules);
+                    if stats.kernel_mismatch() {
+                        eprintln!("{}", utils::format_warning(&format!(
+                            "filter_rules holds {} entries but the daemon expects {}; run `xdp-filter reconcile`",
+                            stats.kernel_entries.unwrap_or_default(), stats.lpm_entries), style::enabled()));
+                    }
+                    println!();
+                    println!("Prefix lengths:");
+                    let histogram = utils::render_prefix_histogram(&stats.prefix_lengths, 40);
+                    if histogram.is_empty() {
+                        println!("  (no source prefix rules)");
+                    }
+                    for line in histogram {
+                        println!("  {}", line);
+                    }
+                    if !stats.top_overlaps.is_empty() {
+                        println!();
+                        println!("Most overlapped prefixes:");
+                        for line in table::prefix_overlaps_table(&stats.top_overlaps).render() {
+                            println!("  {}", line);
+                        }
+                    }
+                },
+                _ => return Err(anyhow!("Invalid format: {}", format)),
+            }
+        },
+        
+        Commands::BpfInfo { format } => {
+            let format = out.format(settings.format(format, "table"));
+            debug!("Getting BPF program and map info");
+            
+            let response = client.send_request(&ApiRequest::GetBpfInfo {}).await
+                .context("Failed to send BPF info request")?;
+            
+            match response {
+                ApiResponse::BpfInfo { programs, maps, layouts, features } => {
+                    match format {
+                        "json" => {
+                            let mut info = serde_json::json!({
+                                "programs": programs,
+                                "maps": maps,
+                                "layouts": layouts,
+                            });
+                            if let Some(features) = features {
+                                info["features"] = serde_json::json!(XdpFeatures(features).names());
+                            }
+                            out.json(&info)?;
+                        },
+                        "table" => {
+                            for line in table::bpf_programs_table(&programs).render() {
+                                println!("{}", line);
+                            }
+                            println!();
+                            for line in table::bpf_maps_table(&maps).render() {
+                                println!("{}", line);
+                            }
+                            // 이전 데몬은 레이아웃과 매치 기능을 보내지 않음
+                            if !layouts.is_empty() {
+                                println!();
+                                for line in table::value_layout_table(&layouts).render() {
+                                    println!("{}", line);
+                                }
+                            }
+                            if let Some(features) = features {
+                                println!();
+                                println!("{}", utils::format_xdp_features(XdpFeatures(features)));
+                            }
+                        },
+                        _ => return Err(anyhow!("Invalid format: {}", format)),
+                    }
+                },
+                other => return Err(exit::response_error(other)),
+            }
+        },
+        
+        Commands::Captures { format } => {
+            let format = out.format(settings.format(format, "table"));
+            debug!("Listing capture files");
+            
+            let response = client.send_request(&ApiRequest::ListCaptures {}).await
+                .context("Failed to send list captures request")?;
+            
+            match response {
+                ApiResponse::Captures { captures } => {
+                    match format {
+                        "json" => {
+                            out.json(&captures)?;
+                        },
+                        "table" => {
+                            if captures.is_empty() {
+                                println!("No capture files found");
+                            } else {
+                                for line in table::captures_table(&captures).render() {
+                                    println!("{}", line);
+                                }
+                            }
+                        },
+                        _ => return Err(anyhow!("Invalid format: {}", format)),
+                    }
+                },
+                other => return Err(exit::response_error(other)),
+            }
+        },
+        
+        Commands::Config { .. } => unreachable!("handled before connecting"),
+    }
+    
+    Ok(())
+}
+
+/// 연결 설정으로 API 서버 하나의 클라이언트 생성
+fn connect(settings: &Settings, addr: &str, verbose: bool) -> Result<SwiftGuardClient> {
+    let mut client = SwiftGuardClient::new(addr)
+        .with_token(settings.token.clone())
+        .with_namespace(settings.namespace.clone());
+    if verbose {
+        // 데몬 로그와 대조할 수 있도록 응답마다 요청 ID 출력
+        client = client.with_request_id_hook(|request_id| eprintln!("Request ID: {}", request_id));
+    }
+    
+    if settings.tls {
+        let ca_cert = settings.ca_cert.as_deref()
+            .ok_or_else(|| anyhow!("--tls requires --ca-cert"))?;
+        let identity = match (settings.client_cert.as_deref(), settings.client_key.as_deref()) {
+            (Some(cert), Some(key)) => Some((cert, key)),
+            (None, None) => None,
+            _ => return Err(anyhow!("--client-cert and --client-key must be given together")),
+        };
+        let config = swift_guard::tls::client_config(ca_cert, identity)
+            .context("Failed to load TLS configuration")?;
+        client = client.with_tls(config)?;
+    } else if settings.client_cert.is_some() {
+        return Err(anyhow!("--client-cert requires --tls"));
+    }
+    
+    Ok(client)
+}
+
+/// 여러 API 서버에 명령 실행
+///
+/// 변경 명령은 같은 요청을 모든 서버에 보내 서버별 결과를 요약하고, `list-rules --merge`는
+/// 모든 서버의 규칙을 합쳐 출력한다. 그 밖의 명령은 서버 하나에만 보낼 수 있다.
+async fn run_fanout(cli: &Cli, settings: &Settings, servers: &[String], out: &mut Output) -> Result<()> {
+    let fan_out = fanout::FanOut::new(cli.parallel, Duration::from_secs(cli.host_timeout));
+    let clients = servers.iter()
+        .map(|addr| Ok((addr.clone(), connect(settings, addr, cli.verbose)?)))
+        .collect::<Result<Vec<_>>>()?;
+    
+    if let Commands::ListRules { stats, rates, wide, expiring_within, sort, all_namespaces, family, all, origin, merge, format, .. } = &cli.command {
+        if !*merge {
+            return Err(anyhow!("list-rules with several API servers requires --merge"));
+        }
+        let format = out.format(settings.format(format, "table"));
+        debug!("Listing filter rules from {} API servers", servers.len());
+        
+        let expiring_within_secs = expiring_within.as_deref().map(swift_guard::utils::parse_duration).transpose()?;
+        let sort = sort.as_deref().map(utils::parse_rule_sort).transpose()?;
+        let (include_stats, include_rates, all_namespaces, family, include_dynamic) = (*stats, *rates, *all_namespaces, *family, *all);
+        let results = fan_out.run(clients, |client| {
+            let origin = origin.clone();
+            async move {
+                let request = ApiRequest::ListRules {
+                    include_stats,
+                    expiring_within_secs,
+                    include_rates,
+                    all_namespaces,
+                    family,
+                    include_dynamic,
+                    origin,
+                    cursor: None,
+                    offset: 0,
+                    limit: api::RULES_PAGE_SIZE,
+                };
+                let (mut rules, _) = fetch_rules(&client, request, false).await?;
+                order_rules(&mut rules, expiring_within_secs.is_some(), sort, false);
+                Ok(rules)
+            }
+        }).await;
+        
+        print_merged_rules(out, &results, format, *stats, *wide, expiring_within_secs.is_some())?;
+        return fanout::check(&results);
+    }
+    
+    let request = fanout_request(&cli.command)?
+        .ok_or_else(|| anyhow!("This command cannot be sent to several API servers (only changes and list-rules --merge can)"))?;
+    debug!("Sending {} request to {} API servers", request.kind(), servers.len());
+    
+    let request = Rc::new(request);
+    let results = fan_out.run(clients, |client| {
+        let request = request.clone();
+        async move {
+            let response = client.send_request(&request).await
+                .with_context(|| format!("Failed to send {} request", request.kind()))?;
+            fanout::response_message(response)
+        }
+    }).await;
+    fanout::print_results(out, &results)
+}
+
+/// 여러 서버에 같은 요청으로 보낼 수 있는 변경 명령의 요청 (그 밖의 명령은 None)
+fn fanout_request(command: &Commands) -> Result<Option<ApiRequest>> {
+    let request = match command {
+        Commands::Attach { interface, mode, force, fallback, obj } => {
+            attach_request(interface, mode, *force, fallback.as_deref(), obj)?
+        },
+        Commands::Detach { interface } => ApiRequest::Detach { interface: interface.clone() },
+        Commands::AddRule(args) => args.to_request()?,
+        Commands::DeleteRule { label } => ApiRequest::DeleteRule { label: label.clone() },
+        Commands::Apply { file, prune, dry_run: true, .. } => ApiRequest::PlanChanges {
+            rules: ruleset::load_ruleset(file)?,
+            prune: *prune,
+        },
+        Commands::Apply { file, prune, .. } => ApiRequest::ApplyRuleset {
+            rules: ruleset::load_ruleset(file)?,
+            prune: *prune,
+            dry_run: false,
+            // 서버마다 진행 막대를 그릴 수 없으므로 최종 결과만 받음
+            progress: false,
+        },
+        Commands::Convert { from, file, apply: true } => ApiRequest::ApplyRuleset {
+            rules: convert_rules(*from, file)?,
+            prune: false,
+            dry_run: false,
+            progress: false,
+        },
+        Commands::RepairRules => ApiRequest::RepairRules {},
+        Commands::BlockCountry { code, action, expire, csv, .. } => ApiRequest::BlockCountry {
+            code: code.clone(),
+            action: *action,
+            expire: *expire,
+            csv_path: csv.clone(),
+        },
+        Commands::UnblockCountry { code } => ApiRequest::UnblockCountry { code: code.clone() },
+        Commands::Preset { command: PresetCommands::IcmpProtect { limit, interface, .. } } => {
+            icmp_protect_request(*limit, interface)
+        },
+        Commands::Preset { command: PresetCommands::AmplificationProtect { drop, threshold, interface, .. } } => {
+            amplification_protect_request(*drop, *threshold, interface)
+        },
+        Commands::Preset { command: PresetCommands::Remove { name } } => ApiRequest::RemovePreset { name: name.clone() },
+        _ => return Ok(None),
+    };
+    Ok(Some(request))
+}
+
+/// `list-rules --merge` 결과 출력 (조회에 실패한 서버는 text 모드에서 stderr에 표시)
+fn print_merged_rules(out: &mut Output, results: &[fanout::HostResult<Vec<api::RuleInfo>>], format: &str,
+                      stats: bool, wide: bool, expiring: bool) -> Result<()> {
+    let merged = fanout::merge_rules(results);
+    match format {
+        "json" => out.json(&merged)?,
+        "yaml" => print!("{}", serde_yaml::to_string(&merged)?),
+        "table" => {
+            if merged.is_empty() {
+                println!("No rules found");
+            } else {
+                let rules: Vec<api::RuleInfo> = merged.iter().map(|entry| entry.rule.clone()).collect();
+                let mut table = if expiring {
+                    table::expiring_rules_table(&rules, swift_guard::utils::current_time_secs())
+                } else {
+                    table::rules_table(&rules, stats, wide)
+                };
+                table.prepend_column("HOST", merged.iter().map(|entry| entry.host.to_string()).collect());
+                for line in table.with_color(style::enabled()).render() {
+                    println!("{}", line);
+                }
+            }
+        },
+        _ => return Err(anyhow!("Invalid format: {}", format)),
+    }
+    
+    if !out.is_jsonl() {
+        for result in results {
+            if let Err(error) = &result.outcome {
+                eprintln!("{}: {:#}", result.host, error);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// attach 요청 생성 (연결 모드와 대체 백엔드 이름 확인)
+fn attach_request(interface: &str, mode: &str, force: bool, fallback: Option<&str>, obj: &Option<String>) -> Result<ApiRequest> {
+    let mode_value = match mode {
+        "driver" => 0,
+        "generic" => 1,
+        "offload" => 2,
+        _ => return Err(anyhow!("Invalid mode: {}", mode)),
+    };
+    
+    let fallback = match fallback {
+        None => None,
+        Some("none") => Some(FallbackMode::None),
+        Some("nftables") => Some(FallbackMode::Nftables),
+        Some(other) => return Err(anyhow!("Invalid fallback: {}", other)),
+    };
+    
+    Ok(ApiRequest::Attach {
+        interface: interface.to_string(),
+        mode: mode_value,
+        force,
+        fallback,
+        obj_path: obj.clone(),
+    })
+}
+
+/// icmp-protect 프리셋 적용 요청
+fn icmp_protect_request(limit: u64, interface: &Option<String>) -> ApiRequest {
+    let mut params = std::collections::BTreeMap::from([("limit".to_string(), limit.to_string())]);
+    if let Some(interface) = interface {
+        params.insert("interface".to_string(), interface.clone());
+    }
+    ApiRequest::ApplyPreset { name: "icmp-protect".to_string(), params }
+}
+
+/// amplification-protect 프리셋 적용 요청
+fn amplification_protect_request(drop: bool, threshold: Option<u64>, interface: &Option<String>) -> ApiRequest {
+    let mut params = std::collections::BTreeMap::new();
+    if drop {
+        params.insert("action".to_string(), "drop".to_string());
+    }
+    if let Some(threshold) = threshold {
+        params.insert("threshold".to_string(), threshold.to_string());
+    }
+    if let Some(interface) = interface {
+        params.insert("interface".to_string(), interface.clone());
+    }
+    ApiRequest::ApplyPreset { name: "amplification-protect".to_string(), params }
+}
+
+/// 국가 차단 결과 출력 (갱신에 실패한 국가가 있으면 오류)
+fn print_country_blocks(out: &mut Output, response: ApiResponse, format: &str) -> Result<()> {
+    let blocks = match response {
+        ApiResponse::CountryBlocks { blocks } => blocks,
+        other => return Err(exit::response_error(other)),
+    };
+    
+    match format {
+        "json" => out.json(&blocks)?,
+        "table" => {
+            if blocks.is_empty() {
+                println!("No country blocks");
+            } else {
+                for line in table::country_blocks_table(&blocks).render() {
+                    println!("{}", line);
+                }
+                if let Some(last) = blocks.last() {
+                    println!("filter_rules map: {}/{} entries in use", last.map_entries, last.map_capacity);
+                }
+            }
+        },
+        _ => return Err(anyhow!("Invalid format: {}", format)),
+    }
+    
+    let failed: Vec<&str> = blocks.iter().filter(|block| block.error.is_some()).map(|block| block.code.as_str()).collect();
+    if !failed.is_empty() {
+        return Err(anyhow!("Failed to refresh country block(s): {}", failed.join(", ")));
+    }
+    Ok(())
+}
+
+/// 프리셋 적용 결과 출력 (적용하지 못한 규칙이 있으면 첫 규칙의 오류 코드)
+fn print_preset_summary(out: &mut Output, response: ApiResponse, format: &str) -> Result<()> {
+    let summary = match response {
+        ApiResponse::RulesetApplied { summary } => summary,
+        other => return Err(exit::response_error(other)),
+    };
+    
+    match format {
+        "json" => out.json(&summary)?,
+        "text" | "table" => {
+            for line in ruleset::render_summary(&summary, style::enabled()) {
+                println!("{}", line);
+            }
+        },
+        _ => return Err(anyhow!("Invalid format: {}", format)),
+    }
+    
+    if let Some(first) = summary.errors.first() {
+        return Err(exit::CliError::new(first.code.into(),
+            format!("{} rule(s) could not be applied", summary.errors.len())).into());
+    }
+    Ok(())
+}
+
+/// list-rules 조회 (페이지를 지정하면 그 페이지와 전체 규칙 수, 아니면 모든 페이지)
+async fn fetch_rules(client: &SwiftGuardClient, request: ApiRequest, paged: bool) -> Result<(Vec<api::RuleInfo>, Option<usize>)> {
+    query_rules(client, request, paged).await
+        .context("Failed to send list rules request")
+}
+
+/// 규칙 조회 요청 (`fetch_rules`, 재연결하며 다시 시도할 수 있도록 클라이언트 오류 그대로)
+async fn query_rules(client: &SwiftGuardClient, request: ApiRequest, paged: bool)
+    -> std::result::Result<(Vec<api::RuleInfo>, Option<usize>), ClientError> {
+    if paged {
+        let (rules, total_count) = client.list_rules_page(request).await?;
+        Ok((rules, Some(total_count)))
+    } else {
+        Ok((client.list_all_rules(request).await?, None))
+    }
+}
+
+/// list-rules 표시 순서 적용
+///
+/// 페이지는 규칙 키 순서이므로 모두 받았을 때만 기본 순서(만료 임박 또는 우선순위)로 정렬한다.
+fn order_rules(rules: &mut [api::RuleInfo], expiring: bool, sort: Option<utils::RuleSort>, paged: bool) {
+    match (expiring, sort) {
+        (true, _) if !paged => utils::sort_rules_by_ttl(rules, swift_guard::utils::current_time_secs()),
+        (false, Some(sort)) => utils::sort_rules(rules, sort),
+        (false, None) if !paged => utils::sort_rules(rules, utils::RuleSort::Priority),
+        _ => {},
+    }
+}
+
+/// `list-rules --watch`: 간격마다 다시 조회해 화면을 새로 그림 (q 또는 Ctrl+C로 종료)
+///
+/// 종료하거나 조회가 실패하면 `watch::Terminal`이 원래 화면과 입력 설정을 복원한다. 데몬이
+/// 재시작되면 다시 연결될 때까지 기다리고, 다시 연결된 뒤 첫 화면에 재연결 알림을 표시한다.
+async fn watch_rules(
+    client: &SwiftGuardClient,
+    request: impl Fn() -> ApiRequest,
+    paged: bool,
+    order: impl Fn(&mut [api::RuleInfo]),
+    wide: bool,
+    interval: u64,
+    reconnect: ReconnectPolicy,
+) -> Result<()> {
+    // 첫 조회 중에 누른 Ctrl+C도 받도록 화면 전환 전에 등록
+    let mut interrupt = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::interrupt())
+        .context("Failed to install the Ctrl+C handler")?;
+    let terminal = watch::Terminal::enter();
+    let mut keys = watch::spawn_key_reader();
+    let mut state = watch::WatchState::default();
+    let mut sampled: Option<std::time::Instant> = None;
+    let color = style::enabled();
+    let mut reconnector = Reconnector::new(reconnect);
+    
+    loop {
+        // 재연결을 기다리는 동안에도 종료 키를 받음
+        let fetched = tokio::select! {
+            fetched = reconnector.run(|| query_rules(client, request(), paged)) => fetched,
+            _ = interrupt.recv() => break,
+            _ = watch::quit_pressed(&mut keys) => break,
+        };
+        let ((mut rules, _), reconnected) = fetched.context("Failed to send list rules request")?;
+        if reconnected.is_some() {
+            // 재시작한 데몬의 카운터는 처음부터 다시 셈
+            state = watch::WatchState::default();
+            sampled = None;
+        }
+        let now = std::time::Instant::now();
+        order(&mut rules);
+        let changed = state.update(&mut rules, sampled.map(|at| now - at).unwrap_or_default());
+        sampled = Some(now);
+        
+        let title = format!("Every {}: xdp-filter list-rules (q to quit)    {}",
+            swift_guard::utils::format_duration(interval), chrono::Utc::now().format("%Y-%m-%d %H:%M:%S UTC"));
+        let mut frame = watch::render_frame(&title, &rules, &changed, wide, color);
+        if let Some(reconnected) = reconnected {
+            frame.insert(1, utils::format_reconnected(&reconnected));
+        }
+        terminal.draw(&frame)?;
+        
+        tokio::select! {
+            _ = tokio::time::sleep(std::time::Duration::from_secs(interval)) => {},
+            _ = interrupt.recv() => break,
+            _ = watch::quit_pressed(&mut keys) => break,
+        }
+    }
+    
+    Ok(())
+}
+
+/// 카운터 조회
+async fn fetch_counters(client: &SwiftGuardClient, request: &ApiRequest) -> Result<Vec<api::CounterInfo>> {
+    let response = client.send_request(request).await
+        .context("Failed to send get counters request")?;
+    
+    match response {
+        ApiResponse::Counters { counters } => Ok(counters),
+        other => Err(exit::response_error(other)),
+    }
+}
+
+/// `counters --watch`: 간격마다 카운터 테이블을 다시 그림 (q 또는 Ctrl+C로 종료)
+async fn watch_counters(client: &SwiftGuardClient, request: &ApiRequest, interval: u64) -> Result<()> {
+    let mut interrupt = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::interrupt())
+        .context("Failed to install the Ctrl+C handler")?;
+    let terminal = watch::Terminal::enter();
+    let mut keys = watch::spawn_key_reader();
+    
+    loop {
+        let counters = fetch_counters(client, request).await?;
+        let title = format!("Every {}: xdp-filter counters (q to quit)    {}",
+            swift_guard::utils::format_duration(interval), chrono::Utc::now().format("%Y-%m-%d %H:%M:%S UTC"));
+        let mut lines = vec![title, String::new()];
+        if counters.is_empty() {
+            lines.push("No counters found".to_string());
+        } else {
+            lines.extend(table::counters_table(&counters).render());
+        }
+        terminal.draw(&lines)?;
+        
+        tokio::select! {
+            _ = tokio::time::sleep(std::time::Duration::from_secs(interval)) => {},
+            _ = interrupt.recv() => break,
+            _ = watch::quit_pressed(&mut keys) => break,
+        }
+    }
+    
+    Ok(())
+}
+
+/// 규칙 집합 적용 요청을 보내고 결과 출력 (apply, convert --apply)
+async fn apply_ruleset(client: &SwiftGuardClient, out: &mut Output, rules: Vec<api::RuleSpec>,
+                       prune: bool, dry_run: bool, format: &str) -> Result<()> {
+    // 드라이런은 데몬이 맵을 복제해 시뮬레이션한 계획 (용량과 리디렉션 대상까지 검사)
+    let request = if dry_run {
+        ApiRequest::PlanChanges { rules, prune }
+    } else {
+        ApiRequest::ApplyRuleset { rules, prune, dry_run: false, progress: true }
+    };
+    
+    // 데몬이 맵 쓰기 청크마다 보내는 진행 상황은 터미널이면 stderr에 막대로 표시
+    let show_progress = utils::show_progress();
+    let mut drawn = false;
+    let response = client.send_request_with_progress(&request, |done, total| {
+        if show_progress {
+            eprint!("\r{}", utils::format_progress(done, total, 30));
+            drawn = true;
+        }
+    }).await;
+    if drawn {
+        eprintln!();
+    }
+    let response = response.context("Failed to send apply ruleset request")?;
+    
+    match response {
+        ApiResponse::RulesetApplied { summary } => {
+            match format {
+                "json" => out.json(&summary)?,
+                "text" | "table" => {
+                    for line in ruleset::render_summary(&summary, style::enabled()) {
+                        println!("{}", line);
+                    }
+                },
+                _ => return Err(anyhow!("Invalid format: {}", format)),
+            }
+            
+            match summary.errors.first() {
+                Some(first) => Err(exit::CliError::new(first.code.into(),
+                    format!("{} rule(s) could not be applied", summary.errors.len())).into()),
+                None => Ok(()),
+            }
+        },
+        other => Err(exit::response_error(other)),
+    }
+}
+
+/// 변환 결과를 규칙 집합으로 (건너뛴 줄은 stderr에 표시)
+fn convert_rules(from: convert::ConvertSource, file: &std::path::Path) -> Result<Vec<api::RuleSpec>> {
+    let conversion = convert::convert_file(from, file)?;
+    print_skipped(&conversion);
+    ruleset::parse_ruleset(&conversion.to_yaml()?)
+}
+
+/// 건너뛴 줄과 변환 요약을 stderr에 출력
+fn print_skipped(conversion: &convert::Conversion) {
+    for skipped in &conversion.skipped {
+        eprintln!("skipped line {}: {} ({})", skipped.line, skipped.reason, skipped.text);
+    }
+    eprintln!("{} rule(s) converted, {} line(s) skipped", conversion.rules.len(), conversion.skipped.len());
+}
+
+/// 규칙 변환 명령 실행 (데몬 연결 없음, 규칙 집합 문서를 stdout에 출력)
+fn run_convert(out: &mut Output, from: convert::ConvertSource, file: &std::path::Path) -> Result<()> {
+    let conversion = convert::convert_file(from, file)?;
+    
+    if out.is_jsonl() {
+        out.json(&conversion)?;
+    } else {
+        print!("{}", conversion.to_yaml()?);
+        print_skipped(&conversion);
+    }
+    Ok(())
+}
+
+/// CLI 설정 파일 명령 실행 (데몬 연결 없음)
+fn run_config(out: &mut Output, path: &std::path::Path, command: &ConfigCommands) -> Result<()> {
+    let mut config = CliConfig::load(path)?;
+    
+    match command {
+        ConfigCommands::SetProfile { name, api_server, tls, ca_cert, client_cert, client_key, format, default } => {
+            let update = Profile {
+                api_server: api_server.clone(),
+                tls: *tls,
+                ca_cert: ca_cert.clone(),
+                client_cert: client_cert.clone(),
+                client_key: client_key.clone(),
+                format: format.clone(),
+            };
+            update.validate()?;
+            
+            config.profiles.entry(name.clone()).or_default().merge(update);
+            if *default {
+                config.default_profile = Some(name.clone());
+            }
+            config.save(path)?;
+            out.message("Success", &format!("Profile '{}' saved to {}", name, path.display()));
+        },
+        ConfigCommands::DeleteProfile { name } => {
+            if config.profiles.remove(name).is_none() {
+                return Err(exit::CliError::new(exit::ExitKind::NotFound, format!("profile '{}' not found", name)).into());
+            }
+            if config.default_profile.as_deref() == Some(name.as_str()) {
+                config.default_profile = None;
+            }
+            config.save(path)?;
+            out.message("Success", &format!("Profile '{}' deleted", name));
+        },
+        ConfigCommands::ListProfiles => {
+            if out.is_jsonl() {
+                out.json(&config)?;
+                return Ok(());
+            }
+            if config.profiles.is_empty() {
+                println!("No profiles in {}", path.display());
+                return Ok(());
+            }
+            
+            let mut table = table::Table::new(["", "NAME", "API-SERVER", "TLS", "FORMAT"]);
+            for (name, profile) in &config.profiles {
+                let default = config.default_profile.as_deref() == Some(name.as_str());
+                table.add_row(vec![
+                    if default { "*" } else { "" }.to_string(),
+                    name.clone(),
+                    profile.api_server.clone().unwrap_or_else(|| "-".to_string()),
+                    profile.tls.map(|tls| if tls { "yes" } else { "no" }).unwrap_or("-").to_string(),
+                    profile.format.clone().unwrap_or_else(|| "-".to_string()),
+                ]);
+            }
+            for line in table.render() {
+                println!("{}", line);
+            }
+        },
     }
     
     Ok(())
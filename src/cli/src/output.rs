@@ -0,0 +1,208 @@
+//! 출력 모드 모듈
+//! `--output jsonl`에서 명령 결과와 오류를 한 줄짜리 JSON 봉투로 stdout에 출력
+//!
+//! 봉투 형식 (한 줄에 객체 하나, 키는 항상 모두 포함):
+//!
+//! ```text
+//! {"ok": true,  "error": null, "data": <결과 또는 null>}
+//! {"ok": false, "error": {"code": "not_found", "exit_code": 4, "message": "rule 'web' not found"}, "data": null}
+//! ```
+//!
+//! - `data`는 해당 명령의 `--format json` 출력과 같다. `--format`이 없는 명령은
+//!   `{"message": ...}`(add-rule은 `"warning"`도 포함) 또는 명령별 객체를 담는다.
+//! - 일부 결과를 낸 뒤 실패한 명령(apply, repair-rules, reconcile 등)은 `ok: false`인 봉투의
+//!   `data`에 그 결과를 함께 담는다.
+//! - `error.code`는 종료 코드 이름(`invalid_argument`, `unreachable`, `not_found`, `already_exists`,
+//!   `permission_denied`, `rate_limited`, `internal`)이고 `exit_code`는 프로세스 종료 코드와 같다.
+//! - 한 번 조회하는 명령은 봉투를 정확히 하나 출력한다. 계속 갱신되는 명령(`monitor`, `stats`,
+//!   `wasm logs --follow`)은 갱신마다 봉투를 하나씩 출력하고, 실패하면 마지막 줄에 오류 봉투를 출력한다.
+//! - `--watch` 화면 모드는 지원하지 않는다 (종료 코드 2).
+
+use std::ffi::OsStr;
+
+use anyhow::Result;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::exit::ExitKind;
+
+/// stdout 출력 모드
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum OutputMode {
+    /// 사람이 읽는 출력 (기본값)
+    #[default]
+    Text,
+    /// 한 줄에 JSON 봉투 하나
+    Jsonl,
+}
+
+/// 오류 봉투 본문
+#[derive(Debug, Serialize)]
+pub struct ErrorBody {
+    pub code: &'static str,
+    pub exit_code: u8,
+    pub message: String,
+}
+
+/// JSON lines 봉투
+#[derive(Debug, Serialize)]
+pub struct Envelope<'a> {
+    pub ok: bool,
+    pub error: Option<ErrorBody>,
+    pub data: Option<&'a Value>,
+}
+
+impl<'a> Envelope<'a> {
+    /// 성공 봉투
+    pub fn success(data: Option<&'a Value>) -> Self {
+        Self { ok: true, error: None, data }
+    }
+
+    /// 오류 봉투
+    pub fn failure(kind: ExitKind, message: String, data: Option<&'a Value>) -> Self {
+        let error = ErrorBody { code: kind.as_str(), exit_code: kind as u8, message };
+        Self { ok: false, error: Some(error), data }
+    }
+
+    /// 한 줄로 직렬화
+    pub fn to_line(&self) -> String {
+        // Value와 문자열만 담으므로 직렬화는 실패하지 않음
+        serde_json::to_string(self).expect("envelope serializes")
+    }
+}
+
+/// 명령 결과 출력
+///
+/// text 모드는 받은 결과를 바로 출력하고, jsonl 모드는 결과를 모아 두었다가
+/// 명령이 끝나면(`exit::finish`) 봉투 하나로 출력한다.
+#[derive(Debug, Default)]
+pub struct Output {
+    mode: OutputMode,
+    data: Option<Value>,
+    /// 이미 출력한 스트리밍 봉투가 있음
+    emitted: bool,
+}
+
+impl Output {
+    /// 새로운 출력 생성
+    pub fn new(mode: OutputMode) -> Self {
+        Self { mode, ..Default::default() }
+    }
+
+    /// jsonl 모드 여부
+    pub fn is_jsonl(&self) -> bool {
+        self.mode == OutputMode::Jsonl
+    }
+
+    /// 명령의 `--format` 값 (jsonl 모드는 항상 json)
+    pub fn format<'a>(&self, format: &'a str) -> &'a str {
+        if self.is_jsonl() { "json" } else { format }
+    }
+
+    /// `--format json` 결과 (text 모드는 보기 좋게 출력, jsonl 모드는 봉투의 data)
+    pub fn json<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        match self.mode {
+            OutputMode::Text => println!("{}", serde_json::to_string_pretty(value)?),
+            OutputMode::Jsonl => self.data = Some(serde_json::to_value(value)?),
+        }
+        Ok(())
+    }
+
+    /// 한 줄 결과 메시지 (text 모드는 `prefix: message`)
+    pub fn message(&mut self, prefix: &str, message: &str) {
+        match self.mode {
+            OutputMode::Text => println!("{}: {}", prefix, message),
+            OutputMode::Jsonl => self.data = Some(serde_json::json!({ "message": message })),
+        }
+    }
+
+    /// 봉투의 data 지정 (jsonl 모드 전용 결과)
+    pub fn set(&mut self, data: Value) {
+        self.data = Some(data);
+    }
+
+    /// 스트리밍 명령의 갱신 하나를 봉투로 바로 출력 (jsonl 모드 전용)
+    pub fn emit<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        let data = serde_json::to_value(value)?;
+        println!("{}", Envelope::success(Some(&data)).to_line());
+        self.emitted = true;
+        Ok(())
+    }
+
+    /// 성공으로 끝난 명령의 마지막 봉투 (스트리밍 봉투를 이미 출력했으면 없음)
+    pub fn success_line(&self) -> Option<String> {
+        (!self.emitted || self.data.is_some()).then(|| Envelope::success(self.data.as_ref()).to_line())
+    }
+
+    /// 실패한 명령의 오류 봉투 (그때까지 모은 결과 포함)
+    pub fn failure_line(&self, kind: ExitKind, error: &anyhow::Error) -> String {
+        Envelope::failure(kind, format!("{:#}", error), self.data.as_ref()).to_line()
+    }
+}
+
+/// 파싱 전 명령줄 인수에서 jsonl 모드를 요청했는지 확인 (인수 파싱 오류도 봉투로 출력하기 위함)
+pub fn requested<S: AsRef<OsStr>>(args: &[S]) -> bool {
+    let mut args = args.iter().map(|arg| arg.as_ref().to_str().unwrap_or_default());
+    while let Some(arg) = args.next() {
+        match arg {
+            "--" => return false,
+            "--output" => return args.next() == Some("jsonl"),
+            "--output=jsonl" => return true,
+            _ => {},
+        }
+    }
+    false
+}
+
+/// clap 오류 출력에서 사용법 안내를 뺀 한 줄 메시지
+pub fn parse_error_message(rendered: &str) -> String {
+    let first = rendered.split("\n\n").next().unwrap_or_default();
+    first.trim_start_matches("error: ").split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+
+    #[test]
+    fn test_envelope_lines() {
+        let data = serde_json::json!({ "message": "done" });
+        assert_eq!(Envelope::success(Some(&data)).to_line(),
+            r#"{"ok":true,"error":null,"data":{"message":"done"}}"#);
+        assert_eq!(Envelope::success(None).to_line(), r#"{"ok":true,"error":null,"data":null}"#);
+        assert_eq!(Envelope::failure(ExitKind::NotFound, "rule 'web' not found".to_string(), None).to_line(),
+            r#"{"ok":false,"error":{"code":"not_found","exit_code":4,"message":"rule 'web' not found"},"data":null}"#);
+    }
+
+    #[test]
+    fn test_output_collects_in_jsonl_mode() {
+        let mut out = Output::new(OutputMode::Jsonl);
+        assert_eq!(out.format("table"), "json");
+        assert_eq!(out.success_line().as_deref(), Some(r#"{"ok":true,"error":null,"data":null}"#));
+
+        out.json(&vec![1, 2]).unwrap();
+        let error = anyhow!("inner").context("outer");
+        assert_eq!(out.failure_line(ExitKind::Internal, &error),
+            r#"{"ok":false,"error":{"code":"internal","exit_code":10,"message":"outer: inner"},"data":[1,2]}"#);
+
+        let text = Output::new(OutputMode::Text);
+        assert_eq!(text.format("table"), "table");
+    }
+
+    #[test]
+    fn test_requested() {
+        assert!(requested(&["xdp-filter", "--output", "jsonl", "list-rules"]));
+        assert!(requested(&["xdp-filter", "list-rules", "--output=jsonl"]));
+        assert!(!requested(&["xdp-filter", "--output", "text", "list-rules"]));
+        assert!(!requested(&["xdp-filter", "--", "--output=jsonl"]));
+        assert!(!requested(&["xdp-filter", "--output"]));
+    }
+
+    #[test]
+    fn test_parse_error_message() {
+        let rendered = "error: the following required arguments were not provided:\n  --label <LABEL>\n\n\
+            Usage: xdp-filter delete-rule --label <LABEL>\n\nFor more information, try '--help'.\n";
+        assert_eq!(parse_error_message(rendered), "the following required arguments were not provided: --label <LABEL>");
+    }
+}
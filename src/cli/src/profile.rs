@@ -0,0 +1,404 @@
+//! CLI 프로필 모듈
+//! 데몬별 연결 설정을 이름 있는 프로필로 저장하는 설정 파일(cli.yaml)과
+//! 명령줄 옵션, 환경 변수, 프로필, 기본값의 우선순위 결정
+
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Write;
+use std::os::unix::fs::{DirBuilderExt, OpenOptionsExt, PermissionsExt};
+use std::path::{Path, PathBuf};
+
+/// 기본 API 서버 주소
+pub const DEFAULT_API_SERVER: &str = "127.0.0.1:7654";
+
+/// 설정 파일 경로 환경 변수
+pub const CONFIG_ENV: &str = "SWIFT_GUARD_CLI_CONFIG";
+/// 프로필 이름 환경 변수
+pub const PROFILE_ENV: &str = "SWIFT_GUARD_PROFILE";
+/// API 서버 주소 환경 변수
+pub const API_SERVER_ENV: &str = "SWIFT_GUARD_API_SERVER";
+/// TLS 사용 여부 환경 변수 (true/false, 1/0)
+pub const TLS_ENV: &str = "SWIFT_GUARD_TLS";
+/// CA 인증서 경로 환경 변수
+pub const CA_CERT_ENV: &str = "SWIFT_GUARD_CA_CERT";
+/// 클라이언트 인증서 경로 환경 변수
+pub const CLIENT_CERT_ENV: &str = "SWIFT_GUARD_CLIENT_CERT";
+/// 클라이언트 개인 키 경로 환경 변수
+pub const CLIENT_KEY_ENV: &str = "SWIFT_GUARD_CLIENT_KEY";
+/// 기본 출력 형식 환경 변수
+pub const FORMAT_ENV: &str = "SWIFT_GUARD_FORMAT";
+/// 규칙 네임스페이스 환경 변수
+pub const NAMESPACE_ENV: &str = "SWIFT_GUARD_NAMESPACE";
+/// API 토큰 환경 변수 (설정 파일에는 저장하지 않음)
+pub const TOKEN_ENV: &str = "SWIFT_GUARD_TOKEN";
+
+/// 프로필에 지정할 수 있는 기본 출력 형식
+pub const PROFILE_FORMATS: &[&str] = &["table", "json"];
+
+/// 데몬 연결 프로필
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Profile {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_server: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tls: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ca_cert: Option<PathBuf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_cert: Option<PathBuf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_key: Option<PathBuf>,
+    /// 기본 출력 형식 (table, json)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub format: Option<String>,
+}
+
+impl Profile {
+    /// 다른 프로필에서 지정된 항목만 덮어쓰기
+    pub fn merge(&mut self, update: Profile) {
+        if update.api_server.is_some() {
+            self.api_server = update.api_server;
+        }
+        if update.tls.is_some() {
+            self.tls = update.tls;
+        }
+        if update.ca_cert.is_some() {
+            self.ca_cert = update.ca_cert;
+        }
+        if update.client_cert.is_some() {
+            self.client_cert = update.client_cert;
+        }
+        if update.client_key.is_some() {
+            self.client_key = update.client_key;
+        }
+        if update.format.is_some() {
+            self.format = update.format;
+        }
+    }
+
+    /// 항목 값 검증
+    pub fn validate(&self) -> Result<()> {
+        if let Some(format) = &self.format {
+            if !PROFILE_FORMATS.contains(&format.as_str()) {
+                return Err(anyhow!("Invalid format: {} (expected one of {})", format, PROFILE_FORMATS.join(", ")));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// CLI 설정 파일 (cli.yaml)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CliConfig {
+    /// --profile을 생략했을 때 쓸 프로필
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_profile: Option<String>,
+    #[serde(default)]
+    pub profiles: BTreeMap<String, Profile>,
+}
+
+impl CliConfig {
+    /// 설정 파일 읽기 (없으면 빈 설정)
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+        };
+
+        let config: Self = serde_yaml::from_str(&content)
+            .with_context(|| format!("Failed to parse {}", path.display()))?;
+        for (name, profile) in &config.profiles {
+            profile.validate().with_context(|| format!("Invalid profile '{}' in {}", name, path.display()))?;
+        }
+        Ok(config)
+    }
+
+    /// 설정 파일 저장
+    ///
+    /// 파일에 인증서 경로 등 접속 정보가 있으므로 소유자만 읽을 수 있게(0600) 만들고,
+    /// 임시 파일에 쓴 뒤 이름을 바꿔 중간에 실패해도 기존 파일이 깨지지 않게 한다.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            fs::DirBuilder::new().recursive(true).mode(0o700).create(dir)
+                .with_context(|| format!("Failed to create {}", dir.display()))?;
+        }
+
+        let content = serde_yaml::to_string(self)?;
+        let tmp = path.with_extension("yaml.tmp");
+        let mut file = fs::OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .mode(0o600)
+            .open(&tmp)
+            .with_context(|| format!("Failed to create {}", tmp.display()))?;
+        // 이전에 남은 임시 파일은 권한이 다를 수 있음
+        file.set_permissions(fs::Permissions::from_mode(0o600))?;
+        file.write_all(content.as_bytes())?;
+        file.sync_all()?;
+
+        fs::rename(&tmp, path).with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    /// 이름으로 프로필 찾기
+    pub fn profile(&self, name: &str) -> Result<&Profile> {
+        self.profiles.get(name).ok_or_else(|| anyhow!("Unknown profile: {}", name))
+    }
+}
+
+/// 설정 파일 경로 (--config > SWIFT_GUARD_CLI_CONFIG > $XDG_CONFIG_HOME 또는 ~/.config)
+pub fn config_path(explicit: Option<&Path>, env: impl Fn(&str) -> Option<String>) -> Option<PathBuf> {
+    if let Some(path) = explicit {
+        return Some(path.to_path_buf());
+    }
+    if let Some(path) = env(CONFIG_ENV).filter(|path| !path.is_empty()) {
+        return Some(PathBuf::from(path));
+    }
+
+    let config_dir = env("XDG_CONFIG_HOME").filter(|dir| !dir.is_empty()).map(PathBuf::from)
+        .or_else(|| env("HOME").filter(|home| !home.is_empty()).map(|home| Path::new(&home).join(".config")))?;
+    Some(config_dir.join("swift-guard").join("cli.yaml"))
+}
+
+/// 명령줄에서 직접 지정한 연결 옵션
+#[derive(Debug, Clone, Default)]
+pub struct ConnectionFlags {
+    pub profile: Option<String>,
+    pub api_server: Option<String>,
+    pub tls: bool,
+    pub ca_cert: Option<PathBuf>,
+    pub client_cert: Option<PathBuf>,
+    pub client_key: Option<PathBuf>,
+    pub namespace: Option<String>,
+    pub token: Option<String>,
+}
+
+/// 결정된 연결 설정
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Settings {
+    /// 사용한 프로필 (없으면 None)
+    pub profile: Option<String>,
+    pub api_server: String,
+    pub tls: bool,
+    pub ca_cert: Option<PathBuf>,
+    pub client_cert: Option<PathBuf>,
+    pub client_key: Option<PathBuf>,
+    /// 명령의 --format을 생략했을 때 쓸 출력 형식
+    pub format: Option<String>,
+    /// 규칙 요청의 네임스페이스 (없으면 데몬이 토큰의 네임스페이스 사용)
+    pub namespace: Option<String>,
+    /// API 토큰
+    pub token: Option<String>,
+}
+
+impl Settings {
+    /// 명령의 출력 형식 (명령 옵션 > 설정 > 명령 기본값)
+    pub fn format<'a>(&'a self, explicit: &'a Option<String>, default: &'a str) -> &'a str {
+        explicit.as_deref().or(self.format.as_deref()).unwrap_or(default)
+    }
+}
+
+/// 연결 설정 결정
+///
+/// 항목마다 명령줄 옵션 > 환경 변수 > 선택된 프로필 > 기본값 순으로 정한다. 프로필은
+/// --profile, SWIFT_GUARD_PROFILE, 설정 파일의 default_profile 순으로 고른다.
+/// 명시적으로 고른 프로필이 없으면 오류다.
+pub fn resolve(flags: &ConnectionFlags, env: impl Fn(&str) -> Option<String>, config: &CliConfig) -> Result<Settings> {
+    let env = |key: &str| env(key).filter(|value| !value.is_empty());
+
+    let profile_name = flags.profile.clone()
+        .or_else(|| env(PROFILE_ENV))
+        .or_else(|| config.default_profile.clone());
+    let profile = match &profile_name {
+        Some(name) => config.profile(name)?.clone(),
+        None => Profile::default(),
+    };
+
+    let tls = if flags.tls {
+        true
+    } else if let Some(value) = env(TLS_ENV) {
+        parse_bool(&value).with_context(|| format!("Invalid {}", TLS_ENV))?
+    } else {
+        profile.tls.unwrap_or(false)
+    };
+
+    let path = |flag: &Option<PathBuf>, key: &str, profile: Option<PathBuf>| {
+        flag.clone().or_else(|| env(key).map(PathBuf::from)).or(profile)
+    };
+
+    Ok(Settings {
+        api_server: flags.api_server.clone()
+            .or_else(|| env(API_SERVER_ENV))
+            .or(profile.api_server)
+            .unwrap_or_else(|| DEFAULT_API_SERVER.to_string()),
+        tls,
+        ca_cert: path(&flags.ca_cert, CA_CERT_ENV, profile.ca_cert),
+        client_cert: path(&flags.client_cert, CLIENT_CERT_ENV, profile.client_cert),
+        client_key: path(&flags.client_key, CLIENT_KEY_ENV, profile.client_key),
+        format: env(FORMAT_ENV).or(profile.format),
+        namespace: flags.namespace.clone().or_else(|| env(NAMESPACE_ENV)),
+        token: flags.token.clone().or_else(|| env(TOKEN_ENV)),
+        profile: profile_name,
+    })
+}
+
+/// 불리언 환경 변수 값 해석
+fn parse_bool(value: &str) -> Result<bool> {
+    match value.to_ascii_lowercase().as_str() {
+        "1" | "true" | "yes" | "on" => Ok(true),
+        "0" | "false" | "no" | "off" => Ok(false),
+        _ => Err(anyhow!("Invalid boolean value: {}", value)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn config() -> CliConfig {
+        serde_yaml::from_str(r#"
+default_profile: lab
+profiles:
+  lab:
+    api_server: 192.168.0.10:7654
+  prod:
+    api_server: 10.0.0.5:7654
+    tls: true
+    ca_cert: /etc/swift-guard/ca.pem
+    format: json
+"#).unwrap()
+    }
+
+    fn env(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let vars: HashMap<String, String> = vars.iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect();
+        move |key| vars.get(key).cloned()
+    }
+
+    #[test]
+    fn test_resolve_precedence() {
+        let config = config();
+        let no_flags = ConnectionFlags::default();
+
+        // 설정 파일이 없으면 기본값
+        let settings = resolve(&no_flags, env(&[]), &CliConfig::default()).unwrap();
+        assert_eq!(settings.api_server, DEFAULT_API_SERVER);
+        assert_eq!((settings.profile, settings.tls, settings.format), (None, false, None));
+
+        // default_profile < 환경 변수 프로필 < --profile
+        let settings = resolve(&no_flags, env(&[]), &config).unwrap();
+        assert_eq!((settings.profile.as_deref(), settings.api_server.as_str()), (Some("lab"), "192.168.0.10:7654"));
+
+        let settings = resolve(&no_flags, env(&[(PROFILE_ENV, "prod")]), &config).unwrap();
+        assert_eq!(settings.api_server, "10.0.0.5:7654");
+        assert!(settings.tls);
+        assert_eq!(settings.ca_cert, Some(PathBuf::from("/etc/swift-guard/ca.pem")));
+        assert_eq!(settings.format.as_deref(), Some("json"));
+
+        let flags = ConnectionFlags { profile: Some("lab".to_string()), ..Default::default() };
+        let settings = resolve(&flags, env(&[(PROFILE_ENV, "prod")]), &config).unwrap();
+        assert_eq!(settings.profile.as_deref(), Some("lab"));
+
+        // 프로필 < 환경 변수 < 명령줄 옵션 (항목별)
+        let vars = env(&[
+            (PROFILE_ENV, "prod"),
+            (API_SERVER_ENV, "10.0.0.6:7654"),
+            (CA_CERT_ENV, "/tmp/ca.pem"),
+            (TLS_ENV, "false"),
+            (FORMAT_ENV, "table"),
+        ]);
+        let settings = resolve(&no_flags, &vars, &config).unwrap();
+        assert_eq!(settings.api_server, "10.0.0.6:7654");
+        assert_eq!(settings.ca_cert, Some(PathBuf::from("/tmp/ca.pem")));
+        assert!(!settings.tls);
+        assert_eq!(settings.format.as_deref(), Some("table"));
+
+        let flags = ConnectionFlags {
+            api_server: Some("127.0.0.1:9000".to_string()),
+            tls: true,
+            client_cert: Some(PathBuf::from("client.pem")),
+            ..Default::default()
+        };
+        let settings = resolve(&flags, &vars, &config).unwrap();
+        assert_eq!(settings.api_server, "127.0.0.1:9000");
+        assert!(settings.tls);
+        assert_eq!(settings.ca_cert, Some(PathBuf::from("/tmp/ca.pem")));
+        assert_eq!(settings.client_cert, Some(PathBuf::from("client.pem")));
+
+        // 빈 환경 변수는 지정하지 않은 것으로 봄
+        let settings = resolve(&no_flags, env(&[(API_SERVER_ENV, "")]), &config).unwrap();
+        assert_eq!(settings.api_server, "192.168.0.10:7654");
+
+        // 네임스페이스와 토큰은 명령줄 옵션 > 환경 변수
+        let vars = env(&[(NAMESPACE_ENV, "team-a"), (TOKEN_ENV, "secret")]);
+        let settings = resolve(&no_flags, &vars, &config).unwrap();
+        assert_eq!((settings.namespace.as_deref(), settings.token.as_deref()), (Some("team-a"), Some("secret")));
+        let flags = ConnectionFlags { namespace: Some("team-b".to_string()), ..Default::default() };
+        assert_eq!(resolve(&flags, &vars, &config).unwrap().namespace.as_deref(), Some("team-b"));
+
+        // 명령 옵션 > 설정 > 명령 기본값
+        assert_eq!(settings.format(&None, "table"), "table");
+        let settings = resolve(&no_flags, env(&[(PROFILE_ENV, "prod")]), &config).unwrap();
+        assert_eq!(settings.format(&None, "table"), "json");
+        assert_eq!(settings.format(&Some("yaml".to_string()), "table"), "yaml");
+    }
+
+    #[test]
+    fn test_resolve_errors() {
+        let config = config();
+
+        let flags = ConnectionFlags { profile: Some("staging".to_string()), ..Default::default() };
+        assert_eq!(resolve(&flags, env(&[]), &config).unwrap_err().to_string(), "Unknown profile: staging");
+        assert!(resolve(&ConnectionFlags::default(), env(&[(TLS_ENV, "maybe")]), &config).is_err());
+    }
+
+    #[test]
+    fn test_config_path() {
+        let explicit = Path::new("/tmp/cli.yaml");
+        assert_eq!(config_path(Some(explicit), env(&[(CONFIG_ENV, "/etc/cli.yaml")])), Some(explicit.to_path_buf()));
+        assert_eq!(config_path(None, env(&[(CONFIG_ENV, "/etc/cli.yaml"), ("HOME", "/root")])),
+            Some(PathBuf::from("/etc/cli.yaml")));
+        assert_eq!(config_path(None, env(&[("XDG_CONFIG_HOME", "/xdg"), ("HOME", "/root")])),
+            Some(PathBuf::from("/xdg/swift-guard/cli.yaml")));
+        assert_eq!(config_path(None, env(&[("HOME", "/home/ops")])),
+            Some(PathBuf::from("/home/ops/.config/swift-guard/cli.yaml")));
+        assert_eq!(config_path(None, env(&[])), None);
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let dir = std::env::temp_dir().join(format!("swift-guard-cli-config-{}", std::process::id()));
+        let path = dir.join("swift-guard").join("cli.yaml");
+        assert_eq!(CliConfig::load(&path).unwrap(), CliConfig::default());
+
+        let mut config = config();
+        config.save(&path).unwrap();
+        assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+        assert_eq!(CliConfig::load(&path).unwrap(), config);
+
+        // 지정한 항목만 바뀜
+        config.profiles.get_mut("prod").unwrap().merge(Profile {
+            api_server: Some("10.0.0.7:7654".to_string()),
+            ..Default::default()
+        });
+        config.save(&path).unwrap();
+        let prod = CliConfig::load(&path).unwrap().profiles.remove("prod").unwrap();
+        assert_eq!(prod.api_server.as_deref(), Some("10.0.0.7:7654"));
+        assert_eq!(prod.tls, Some(true));
+
+        // 잘못된 형식이나 알 수 없는 항목은 거부
+        fs::write(&path, "profiles:\n  prod:\n    format: yaml\n").unwrap();
+        assert!(CliConfig::load(&path).is_err());
+        fs::write(&path, "profiles:\n  prod:\n    token: secret\n").unwrap();
+        assert!(CliConfig::load(&path).is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
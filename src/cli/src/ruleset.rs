@@ -0,0 +1,410 @@
+//! 규칙 집합 모듈
+//! 선언형 규칙 집합 문서(YAML) 파싱 및 적용 결과 출력
+
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::api::{ApplySummary, RuleDiff, RuleSpec};
+use crate::style::{paint, GREEN, RED, YELLOW};
+use crate::utils::{
+    action_name_to_num, check_port_list, check_rule_spec, parse_port_list, parse_port_range, parse_tcp_flags, protocol_name_to_num,
+};
+use swift_guard::utils::{parse_count, parse_duration, parse_size};
+
+/// 규칙 집합 문서
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RulesetDocument {
+    #[serde(default)]
+    pub rules: Vec<RuleEntry>,
+}
+
+/// 포트, 포트 범위 또는 포트 목록 (YAML에서 숫자, "1024-2048", [80, 443] 또는 "80,443")
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum PortSpec {
+    Number(u16),
+    List(Vec<u16>),
+    Range(String),
+}
+
+/// 숫자 또는 단위가 붙은 값 (YAML에서 3600 또는 "1h", 10000 또는 "10k")
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum Quantity {
+    Number(u64),
+    Text(String),
+}
+
+impl Quantity {
+    /// 숫자는 그대로, 문자열은 `parse`로 해석
+    fn parse(&self, parse: fn(&str) -> Result<u64>) -> Result<u64> {
+        match self {
+            Quantity::Number(value) => Ok(*value),
+            Quantity::Text(text) => parse(text),
+        }
+    }
+}
+
+/// 사람이 작성하는 형식의 규칙 (add-rule 옵션과 같은 표기)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RuleEntry {
+    pub label: String,
+    pub src_ip: Option<String>,
+    pub dst_ip: Option<String>,
+    pub src_port: Option<PortSpec>,
+    pub dst_port: Option<PortSpec>,
+    /// 프로토콜 이름 또는 번호 (생략 시 any)
+    pub protocol: Option<String>,
+    /// TCP 플래그 (SYN,ACK,...)
+    pub tcp_flags: Option<String>,
+    pub action: String,
+    pub redirect_if: Option<String>,
+    pub redirect_cpu: Option<u32>,
+    #[serde(default)]
+    pub priority: u32,
+    /// 초당 패킷 수 (1000, "10k")
+    pub rate_limit: Option<Quantity>,
+    /// 만료 시간 (3600, "1h")
+    pub expire: Option<Quantity>,
+    pub description: Option<String>,
+    /// 하루 바이트 할당량 (10737418240, "10G")
+    pub quota_bytes: Option<Quantity>,
+    /// 하루 패킷 할당량 (500000, "500k")
+    pub quota_packets: Option<Quantity>,
+    /// 할당량을 넘었을 때 적용할 액션 (생략 시 drop)
+    pub quota_action: Option<String>,
+    /// 통계를 합산할 카운터 그룹 (count 액션만)
+    pub counter_group: Option<String>,
+    /// 만료 전과 만료 때 알림을 받을 웹훅 URL (expire가 있는 규칙만)
+    pub owner_webhook: Option<String>,
+    /// 만료 예고 알림을 보낼 남은 시간 (600, "10m")
+    pub notify_before: Option<Quantity>,
+}
+
+/// 포트 범위와 포트 목록 (목록이면 범위는 전체)
+fn ports(port: &Option<PortSpec>) -> Result<((u16, u16), Vec<u16>)> {
+    match port {
+        Some(PortSpec::Number(port)) => Ok(((*port, *port), Vec::new())),
+        Some(PortSpec::List(ports)) => Ok(((0, 65535), check_port_list(ports)?)),
+        Some(PortSpec::Range(range)) if range.contains(',') => Ok(((0, 65535), parse_port_list(range)?)),
+        Some(PortSpec::Range(range)) => Ok((parse_port_range(range)?, Vec::new())),
+        None => Ok(((0, 65535), Vec::new())),
+    }
+}
+
+fn quantity(value: &Option<Quantity>, parse: fn(&str) -> Result<u64>) -> Result<Option<u64>> {
+    value.as_ref().map(|value| value.parse(parse)).transpose()
+}
+
+fn quantity_u32(name: &str, value: &Option<Quantity>, parse: fn(&str) -> Result<u64>) -> Result<u32> {
+    let value = quantity(value, parse)?.unwrap_or(0);
+    u32::try_from(value).map_err(|_| anyhow!("'{}' is too large: {} (at most {})", name, value, u32::MAX))
+}
+
+impl RuleEntry {
+    /// 요청용 규칙 정의로 변환 (CLI에서 확인할 수 있는 항목 검증)
+    pub fn to_spec(&self) -> Result<RuleSpec> {
+        let action = action_name_to_num(&self.action)?;
+
+        let protocol = match &self.protocol {
+            Some(p) => protocol_name_to_num(p)?,
+            None => 255, // ANY
+        };
+
+        let ((src_port_min, src_port_max), src_port_list) = ports(&self.src_port)?;
+        let ((dst_port_min, dst_port_max), dst_port_list) = ports(&self.dst_port)?;
+
+        let tcp_flags = match &self.tcp_flags {
+            Some(flags) => parse_tcp_flags(flags)?,
+            None => 0,
+        };
+
+        let quota_bytes = quantity(&self.quota_bytes, parse_size)?;
+        let quota_packets = quantity(&self.quota_packets, parse_count)?;
+        let quota_action = self.quota_action.as_deref().map(action_name_to_num).transpose()?;
+        let notify_before = self.notify_before.as_ref()
+            .map(|_| quantity_u32("notify_before", &self.notify_before, parse_duration))
+            .transpose()?;
+
+        let spec = RuleSpec {
+            label: self.label.clone(),
+            src_ip: self.src_ip.clone(),
+            dst_ip: self.dst_ip.clone(),
+            src_port_min,
+            src_port_max,
+            dst_port_min,
+            dst_port_max,
+            protocol,
+            tcp_flags,
+            action,
+            redirect_if: self.redirect_if.clone(),
+            redirect_cpu: self.redirect_cpu,
+            priority: self.priority,
+            rate_limit: quantity_u32("rate_limit", &self.rate_limit, parse_count)?,
+            expire: quantity_u32("expire", &self.expire, parse_duration)?,
+            description: self.description.clone(),
+            quota_bytes,
+            quota_packets,
+            quota_action,
+            counter_group: self.counter_group.clone(),
+            src_port_list,
+            dst_port_list,
+            owner_webhook: self.owner_webhook.clone(),
+            notify_before,
+        };
+        check_rule_spec(&spec)?;
+        Ok(spec)
+    }
+}
+
+/// 규칙 집합 문서 파싱
+pub fn parse_ruleset(yaml: &str) -> Result<Vec<RuleSpec>> {
+    let document: RulesetDocument = serde_yaml::from_str(yaml)
+        .context("Invalid ruleset document")?;
+
+    document.rules.iter()
+        .enumerate()
+        .map(|(i, entry)| entry.to_spec()
+            .with_context(|| format!("rules[{}] ('{}')", i, entry.label)))
+        .collect()
+}
+
+/// 규칙 집합 파일 읽기
+pub fn load_ruleset(path: &Path) -> Result<Vec<RuleSpec>> {
+    let yaml = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read ruleset file: {}", path.display()))?;
+
+    parse_ruleset(&yaml)
+        .with_context(|| format!("Failed to load {}", path.display()))
+}
+
+/// 규칙의 항목별 변경 줄 (`    priority: 0 -> 10`, 이전 값은 빨강, 새 값은 초록)
+pub fn render_diff(diff: &RuleDiff, color: bool) -> Vec<String> {
+    diff.changes.iter()
+        .map(|change| format!("    {}: {} -> {}", change.field,
+            paint(&change.old, RED, color), paint(&change.new, GREEN, color)))
+        .collect()
+}
+
+/// 적용 결과 출력 줄 (갱신 규칙 아래에 항목별 변경 표시)
+pub fn render_summary(summary: &ApplySummary, color: bool) -> Vec<String> {
+    let mut lines = Vec::new();
+
+    if summary.dry_run {
+        lines.push("Dry run: no changes applied".to_string());
+    }
+
+    let (add, update, delete) = if summary.dry_run {
+        ("would add", "would update", "would delete")
+    } else {
+        ("added", "updated", "deleted")
+    };
+
+    lines.extend(summary.added.iter()
+        .map(|label| paint(&format!("+ {} ({})", label, add), GREEN, color)));
+    for label in &summary.updated {
+        lines.push(paint(&format!("~ {} ({})", label, update), YELLOW, color));
+        if let Some(diff) = summary.diffs.iter().find(|diff| &diff.label == label) {
+            lines.extend(render_diff(diff, color));
+        }
+    }
+    lines.extend(summary.deleted.iter()
+        .map(|label| paint(&format!("- {} ({})", label, delete), RED, color)));
+    lines.extend(summary.errors.iter()
+        .map(|e| paint(&format!("! {}: {}", e.label, e.message), RED, color)));
+    if !summary.pending.is_empty() {
+        lines.push(paint(&format!("! apply stopped, {} change(s) not applied: {}",
+            summary.pending.len(), summary.pending.join(", ")), RED, color));
+    }
+
+    lines.push(format!("{} added, {} updated, {} deleted, {} unchanged, {} failed",
+        summary.added.len(), summary.updated.len(), summary.deleted.len(),
+        summary.unchanged.len(), summary.errors.len()));
+    if let Some(capacity) = &summary.capacity {
+        lines.push(format!("After apply: filter_rules {}/{} ({:.1}%), wildcard_rules {}/{}",
+            capacity.lpm_entries, capacity.lpm_capacity, capacity.fill_percent,
+            capacity.wildcard_rules, capacity.wildcard_capacity));
+    }
+
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::{FieldChange, RuleError};
+    use swift_guard::error::ErrorCode;
+
+    #[test]
+    fn test_parse_ruleset() {
+        let specs = parse_ruleset(r#"
+rules:
+  - label: block-external-ssh
+    src_ip: "0.0.0.0/0"
+    dst_port: 22
+    protocol: tcp
+    action: drop
+    priority: 100
+    expire: 1h
+    rate_limit: 10k
+  - label: web-range
+    dst_port: "8000-8080"
+    tcp_flags: SYN
+    action: redirect
+    redirect_cpu: 1
+  - label: bulk-transfer
+    dst_port: 873
+    action: pass
+    quota_bytes: 10G
+    quota_action: count
+  - label: dns-budget
+    dst_port: 53
+    action: pass
+    quota_bytes: 1048576
+    quota_packets: 500k
+    expire: 600
+    owner_webhook: "http://ops.example:8080/expiry"
+    notify_before: 2m
+  - label: web-https
+    dst_port: 443
+    action: count
+    counter_group: web-total
+  - label: reflectors
+    src_port: [19, 53, 11211]
+    protocol: udp
+    action: count
+  - label: ntp-ssdp
+    src_port: "123,1900"
+    protocol: udp
+    action: count
+  - label: web
+    dst_port: [80, 443, 8080]
+    protocol: tcp
+    action: pass
+"#).unwrap();
+
+        assert_eq!(specs.len(), 8);
+        assert_eq!((specs[0].dst_port_min, specs[0].dst_port_max), (22, 22));
+        assert_eq!((specs[0].src_port_min, specs[0].src_port_max), (0, 65535));
+        assert_eq!(specs[0].protocol, 6);
+        assert_eq!(specs[0].action, 2);
+        assert_eq!(specs[0].priority, 100);
+        assert_eq!((specs[0].expire, specs[0].rate_limit), (3600, 10_000));
+        assert_eq!((specs[1].expire, specs[1].rate_limit), (0, 0));
+        assert_eq!((specs[1].dst_port_min, specs[1].dst_port_max), (8000, 8080));
+        assert_eq!(specs[1].protocol, 255);
+        assert_eq!(specs[1].tcp_flags, 0x02);
+        assert_eq!(specs[1].redirect_cpu, Some(1));
+        assert_eq!(specs[1].quota_bytes, None);
+        assert_eq!((specs[2].quota_bytes, specs[2].quota_action), (Some(10 << 30), Some(4)));
+        assert_eq!((specs[3].quota_bytes, specs[3].quota_packets, specs[3].quota_action), (Some(1 << 20), Some(500_000), None));
+        assert_eq!(specs[3].expire, 600);
+        assert_eq!((specs[3].owner_webhook.as_deref(), specs[3].notify_before), (Some("http://ops.example:8080/expiry"), Some(120)));
+        assert_eq!((specs[0].owner_webhook.as_deref(), specs[0].notify_before), (None, None));
+        assert_eq!((specs[4].action, specs[4].counter_group.as_deref()), (4, Some("web-total")));
+        assert_eq!(specs[0].counter_group, None);
+        assert_eq!(specs[0].src_port_list, Vec::<u16>::new());
+        assert_eq!((specs[5].src_port_min, specs[5].src_port_max, &specs[5].src_port_list), (0, 65535, &vec![19, 53, 11211]));
+        assert_eq!(specs[6].src_port_list, vec![123, 1900]);
+        assert_eq!(specs[5].dst_port_list, Vec::<u16>::new());
+        assert_eq!((specs[7].dst_port_min, specs[7].dst_port_max, &specs[7].dst_port_list), (0, 65535, &vec![80, 443, 8080]));
+
+        assert!(parse_ruleset("rules: []").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_parse_ruleset_errors() {
+        let err = parse_ruleset("rules:\n  - label: a\n    action: drop\n  - label: b\n    action: explode\n").unwrap_err();
+        assert!(format!("{:#}", err).contains("rules[1] ('b')"));
+
+        // 오타는 조용히 무시하지 않음
+        let err = parse_ruleset("rules:\n  - label: a\n    action: drop\n    dst_prot: 22\n").unwrap_err();
+        assert!(format!("{:#}", err).contains("dst_prot"));
+
+        let err = parse_ruleset("rules:\n  - label: a\n    action: redirect\n").unwrap_err();
+        assert!(format!("{:#}", err).contains("requires"));
+
+        let err = parse_ruleset("rules:\n  - label: a\n    action: pass\n    quota_bytes: 10X\n").unwrap_err();
+        assert!(format!("{:#}", err).contains("10X"));
+
+        let err = parse_ruleset("rules:\n  - label: a\n    action: drop\n    expire: 2w\n").unwrap_err();
+        assert!(format!("{:#}", err).contains("s, m, h or d"));
+
+        let err = parse_ruleset("rules:\n  - label: a\n    action: drop\n    rate_limit: 5G\n").unwrap_err();
+        assert!(format!("{:#}", err).contains("rate_limit"));
+
+        let err = parse_ruleset("rules:\n  - label: a\n    action: drop\n    src_port: [53, 123]\n").unwrap_err();
+        assert!(format!("{:#}", err).contains("requires the tcp or udp protocol"));
+
+        let err = parse_ruleset("rules:\n  - label: a\n    action: drop\n    dst_port: [53, 123]\n").unwrap_err();
+        assert!(format!("{:#}", err).contains("requires the tcp or udp protocol"));
+
+        let err = parse_ruleset("rules:\n  - label: a\n    protocol: tcp\n    action: drop\n    dst_port: \"80,8000-8080\"\n").unwrap_err();
+        assert!(format!("{:#}", err).contains("Port ranges cannot be used in a port list"));
+
+        let err = parse_ruleset("rules:\n  - label: a\n    protocol: tcp\n    action: drop\n    dst_port: [1, 2, 3, 4, 5, 6, 7, 8, 9]\n").unwrap_err();
+        assert!(format!("{:#}", err).contains("Too many ports in list"));
+    }
+
+    #[test]
+    fn test_render_summary() {
+        let summary = ApplySummary {
+            dry_run: true,
+            added: vec!["new".to_string()],
+            updated: vec!["changed".to_string()],
+            deleted: vec!["extra".to_string()],
+            unchanged: vec!["same".to_string()],
+            errors: vec![RuleError {
+                label: "broken".to_string(),
+                message: "Invalid src_ip: bad".to_string(),
+                code: ErrorCode::InvalidArgument,
+            }],
+            diffs: vec![RuleDiff {
+                label: "changed".to_string(),
+                changes: vec![
+                    FieldChange { field: "action".to_string(), old: "pass".to_string(), new: "drop".to_string() },
+                    FieldChange { field: "priority".to_string(), old: "0".to_string(), new: "10".to_string() },
+                ],
+            }],
+            pending: Vec::new(),
+            capacity: None,
+        };
+
+        assert_eq!(render_summary(&summary, false), [
+            "Dry run: no changes applied",
+            "+ new (would add)",
+            "~ changed (would update)",
+            "    action: pass -> drop",
+            "    priority: 0 -> 10",
+            "- extra (would delete)",
+            "! broken: Invalid src_ip: bad",
+            "1 added, 1 updated, 1 deleted, 1 unchanged, 1 failed",
+        ]);
+
+        assert_eq!(render_summary(&summary, true), [
+            "Dry run: no changes applied",
+            "\x1b[32m+ new (would add)\x1b[0m",
+            "\x1b[33m~ changed (would update)\x1b[0m",
+            "    action: \x1b[31mpass\x1b[0m -> \x1b[32mdrop\x1b[0m",
+            "    priority: \x1b[31m0\x1b[0m -> \x1b[32m10\x1b[0m",
+            "\x1b[31m- extra (would delete)\x1b[0m",
+            "\x1b[31m! broken: Invalid src_ip: bad\x1b[0m",
+            "1 added, 1 updated, 1 deleted, 1 unchanged, 1 failed",
+        ]);
+    }
+
+    #[test]
+    fn test_render_summary_without_diffs() {
+        // 이전 데몬은 항목별 변경을 보내지 않음
+        let summary: ApplySummary = serde_json::from_str(
+            r#"{"dry_run":true,"added":[],"updated":["changed"],"deleted":[],"unchanged":[],"errors":[]}"#).unwrap();
+        assert_eq!(render_summary(&summary, false), [
+            "Dry run: no changes applied",
+            "~ changed (would update)",
+            "0 added, 1 updated, 0 deleted, 0 unchanged, 0 failed",
+        ]);
+    }
+}
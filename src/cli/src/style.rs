@@ -0,0 +1,181 @@
+//! 터미널 스타일 모듈
+//! ANSI 색상 적용과 색상 사용 여부 결정 (`--color`, `--no-color`, NO_COLOR, 터미널 여부)
+//!
+//! 사용 여부는 `init`으로 명령 시작 때 한 번 정한다. 출력 함수는 `color: bool`을 받으므로
+//! 터미널 없이 시험할 수 있다. 테이블은 셀에 색을 칠해 두고, 색상을 끈 출력에서는
+//! 렌더링할 때 코드를 지워 파일로 보낸 출력에 제어 문자가 남지 않게 한다.
+
+use std::ffi::OsStr;
+use std::io::IsTerminal;
+use std::sync::OnceLock;
+
+use clap::ValueEnum;
+
+/// ANSI 색상
+pub const RED: &str = "\x1b[31m";
+pub const GREEN: &str = "\x1b[32m";
+pub const YELLOW: &str = "\x1b[33m";
+/// 굵은 노란색 (강조)
+pub const HIGHLIGHT: &str = "\x1b[1;33m";
+pub const RESET: &str = "\x1b[0m";
+
+/// 색상 사용 방식 (`--color`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ColorChoice {
+    /// 표준 출력이 터미널이고 NO_COLOR가 없을 때
+    Auto,
+    Always,
+    Never,
+}
+
+/// 명령 시작 때 정한 색상 사용 여부
+static ENABLED: OnceLock<bool> = OnceLock::new();
+
+/// 색상 사용 여부 결정
+///
+/// 우선순위는 `--no-color`, `--color always|never`, NO_COLOR(비어 있지 않으면 끔), 표준 출력이
+/// 터미널인지 순이다.
+pub fn resolve(choice: ColorChoice, no_color_flag: bool, no_color_env: Option<&OsStr>, tty: bool) -> bool {
+    if no_color_flag {
+        return false;
+    }
+    match choice {
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+        ColorChoice::Auto => {
+            let no_color = no_color_env.is_some_and(|value| !value.is_empty());
+            !no_color && tty
+        },
+    }
+}
+
+/// 명령줄 옵션과 환경으로 색상 사용 여부 결정 (처음 한 번만 반영)
+pub fn init(choice: ColorChoice, no_color_flag: bool) {
+    let no_color_env = std::env::var_os("NO_COLOR");
+    let _ = ENABLED.set(resolve(choice, no_color_flag, no_color_env.as_deref(), std::io::stdout().is_terminal()));
+}
+
+/// 색상 사용 여부 (`init` 전이면 `--color auto`로 결정)
+pub fn enabled() -> bool {
+    *ENABLED.get_or_init(|| {
+        let no_color_env = std::env::var_os("NO_COLOR");
+        resolve(ColorChoice::Auto, false, no_color_env.as_deref(), std::io::stdout().is_terminal())
+    })
+}
+
+/// 색상 적용 (color가 false면 그대로)
+pub fn paint(text: &str, code: &str, color: bool) -> String {
+    if color {
+        format!("{}{}{}", code, text, RESET)
+    } else {
+        text.to_string()
+    }
+}
+
+/// 규칙 액션 색상 (drop 빨강, pass 초록, redirect 노랑)
+pub fn action_color(action: &str) -> Option<&'static str> {
+    match action {
+        "drop" => Some(RED),
+        "pass" => Some(GREEN),
+        "redirect" => Some(YELLOW),
+        _ => None,
+    }
+}
+
+/// 이벤트나 규칙 변경 종류 색상 (monitor 출력)
+pub fn kind_color(kind: &str) -> Option<&'static str> {
+    match kind {
+        "xdp_aborted" | "anomaly" | "deleted" => Some(RED),
+        "mitigation" | "link_state" | "rule_expiring" | "rule_expired" | "updated" => Some(YELLOW),
+        "added" => Some(GREEN),
+        _ => None,
+    }
+}
+
+/// 색상이 있으면 적용 (없거나 color가 false면 그대로)
+pub fn paint_with(text: &str, code: Option<&str>, color: bool) -> String {
+    match code {
+        Some(code) => paint(text, code, color),
+        None => text.to_string(),
+    }
+}
+
+/// ANSI 제어 코드 제거 (`ESC [` 로 시작해 0x40~0x7e 문자로 끝나는 시퀀스)
+pub fn strip(text: &str) -> String {
+    if !text.contains('\x1b') {
+        return text.to_string();
+    }
+
+    let mut plain = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\x1b' || chars.peek() != Some(&'[') {
+            plain.push(c);
+            continue;
+        }
+        chars.next();
+        for c in chars.by_ref() {
+            if ('\x40'..='\x7e').contains(&c) {
+                break;
+            }
+        }
+    }
+    plain
+}
+
+/// 화면에 보이는 글자 수 (ANSI 제어 코드 제외)
+pub fn width(text: &str) -> usize {
+    strip(text).chars().count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_precedence() {
+        let set = Some(OsStr::new("1"));
+        let empty = Some(OsStr::new(""));
+
+        // auto: 터미널이고 NO_COLOR가 없거나 비어 있을 때만
+        assert!(resolve(ColorChoice::Auto, false, None, true));
+        assert!(!resolve(ColorChoice::Auto, false, None, false));
+        assert!(!resolve(ColorChoice::Auto, false, set, true));
+        assert!(resolve(ColorChoice::Auto, false, empty, true));
+
+        // --color always|never는 NO_COLOR와 터미널 여부보다 우선
+        assert!(resolve(ColorChoice::Always, false, set, false));
+        assert!(!resolve(ColorChoice::Never, false, None, true));
+
+        // --no-color가 가장 우선
+        assert!(!resolve(ColorChoice::Always, true, None, true));
+        assert!(!resolve(ColorChoice::Auto, true, None, true));
+    }
+
+    #[test]
+    fn test_paint_and_strip() {
+        assert_eq!(paint("drop", RED, true), "\x1b[31mdrop\x1b[0m");
+        assert_eq!(paint("drop", RED, false), "drop");
+        assert_eq!(paint_with("x", None, true), "x");
+        assert_eq!(paint_with("x", action_color("pass"), true), "\x1b[32mx\x1b[0m");
+
+        assert_eq!(strip("\x1b[31mdrop\x1b[0m"), "drop");
+        assert_eq!(strip("a \x1b[1;33mb\x1b[0m c"), "a b c");
+        assert_eq!(strip("plain"), "plain");
+        // CSI가 아닌 ESC는 그대로
+        assert_eq!(strip("\x1bx"), "\x1bx");
+
+        assert_eq!(width("\x1b[33mredirect:veth1\x1b[0m"), 14);
+        assert_eq!(width("10.0.0.0/8"), 10);
+    }
+
+    #[test]
+    fn test_colors() {
+        assert_eq!(action_color("drop"), Some(RED));
+        assert_eq!(action_color("redirect"), Some(YELLOW));
+        assert_eq!(action_color("count"), None);
+        assert_eq!(kind_color("anomaly"), Some(RED));
+        assert_eq!(kind_color("added"), Some(GREEN));
+        assert_eq!(kind_color("rule_hit"), None);
+    }
+}
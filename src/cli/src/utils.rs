@@ -2,6 +2,18 @@
 //! 다양한 유틸리티 함수 제공
 
 use anyhow::{anyhow, Result};
+use swift_guard::histogram::LatencySnapshot;
+use swift_guard::types::{size_bucket_label, XdpFeatures};
+use swift_guard::validation::validate_rule_spec;
+use swift_guard::utils::{format_duration, format_size, parse_count, parse_duration, ttl_remaining};
+
+use crate::api::{
+    AddressFamily, ApiServerStats, BpfLoadFailure, CapabilityCheck, CheckStatus, DaemonEvent, DaemonSelfStats, EventGap, GeoInfo, QuotaInfo, RuleExpirySummary,
+    Reconnected, RuleExpiryEvent, RuleInfo, RuleSpec, SequencedEvent, TrafficBaseline, WasmLogLine, WebhookStats,
+};
+use crate::style;
+
+pub use swift_guard::utils::{check_port_list, parse_port_list, protocol_name_to_num};
 
 /// 포트 범위 문자열 파싱 (예: "80" 또는 "1024-2048")
 pub fn parse_port_range(s: &str) -> Result<(u16, u16)> {
@@ -30,49 +42,6 @@ pub fn parse_port_range(s: &str) -> Result<(u16, u16)> {
     }
 }
 
-/// IP 주소 문자열에서 IP 주소와 프리픽스 길이 추출
-pub fn parse_ip_prefix(s: &str) -> Result<(u32, u32)> {
-    let parts: Vec<&str> = s.split('/').collect();
-    
-    let ip_str = parts[0].trim();
-    let octets: Vec<&str> = ip_str.split('.').collect();
-    if octets.len() != 4 {
-        return Err(anyhow!("Invalid IP address format: {}", ip_str));
-    }
-    
-    let mut ip: u32 = 0;
-    for (i, octet) in octets.iter().enumerate() {
-        let value = octet.parse::<u8>()
-            .map_err(|_| anyhow!("Invalid IP address octet: {}", octet))?;
-        
-        ip |= (value as u32) << (8 * (3 - i));
-    }
-    
-    let prefix_len = if parts.len() > 1 {
-        parts[1].trim().parse::<u32>()
-            .map_err(|_| anyhow!("Invalid prefix length: {}", parts[1]))?
-    } else {
-        32 // 프리픽스가 지정되지 않은 경우 32(정확한 IP 매치)
-    };
-    
-    if prefix_len > 32 {
-        return Err(anyhow!("Invalid prefix length: {}", prefix_len));
-    }
-    
-    Ok((ip, prefix_len))
-}
-
-/// 프로토콜 이름을 프로토콜 번호로 변환
-pub fn protocol_name_to_num(name: &str) -> Result<u8> {
-    match name.to_lowercase().as_str() {
-        "tcp" => Ok(6),
-        "udp" => Ok(17),
-        "icmp" => Ok(1),
-        "any" => Ok(255),
-        _ => Err(anyhow!("Unknown protocol: {}", name)),
-    }
-}
-
 /// 액션 이름을 액션 번호로 변환
 pub fn action_name_to_num(name: &str) -> Result<u8> {
     match name.to_lowercase().as_str() {
@@ -84,7 +53,33 @@ pub fn action_name_to_num(name: &str) -> Result<u8> {
     }
 }
 
+/// 규칙 정의 사전 검증 (데몬과 같은 검증, 문제를 모두 한 번에 보고)
+pub fn check_rule_spec(spec: &RuleSpec) -> Result<()> {
+    let issues = validate_rule_spec(spec);
+    match issues.as_slice() {
+        [] => Ok(()),
+        [issue] => Err(anyhow!("Invalid rule: {}", issue)),
+        issues => {
+            let lines: Vec<String> = issues.iter().map(|issue| format!("  - {}", issue)).collect();
+            Err(anyhow!("Invalid rule ({} issues):\n{}", issues.len(), lines.join("\n")))
+        },
+    }
+}
+
+/// 할당량 표시 (예: "10.00 GB, 1000000 packets -> drop")
+pub fn format_quota(quota: &QuotaInfo) -> String {
+    let mut limits = Vec::new();
+    if let Some(bytes) = quota.bytes {
+        limits.push(format_size(bytes));
+    }
+    if let Some(packets) = quota.packets {
+        limits.push(format!("{} packets", packets));
+    }
+    format!("{} -> {}", limits.join(", "), quota.action)
+}
+
 /// 액션 번호를 액션 이름으로 변환
+#[allow(dead_code)]
 pub fn action_num_to_name(num: u8) -> String {
     match num {
         1 => "pass".to_string(),
@@ -95,18 +90,25 @@ pub fn action_num_to_name(num: u8) -> String {
     }
 }
 
-/// 프로토콜 번호를 프로토콜 이름으로 변환
-pub fn protocol_num_to_name(num: u8) -> String {
-    match num {
-        1 => "icmp".to_string(),
-        6 => "tcp".to_string(),
-        17 => "udp".to_string(),
-        255 => "any".to_string(),
-        _ => format!("{}", num),
+/// TCP 플래그 목록(SYN,ACK,...)을 비트맵으로 변환
+pub fn parse_tcp_flags(s: &str) -> Result<u8> {
+    let mut flags = 0;
+    for flag in s.split(',') {
+        match flag.trim() {
+            "FIN" => flags |= 0x01,
+            "SYN" => flags |= 0x02,
+            "RST" => flags |= 0x04,
+            "PSH" => flags |= 0x08,
+            "ACK" => flags |= 0x10,
+            "URG" => flags |= 0x20,
+            _ => return Err(anyhow!("Invalid TCP flag: {}", flag)),
+        }
     }
+    Ok(flags)
 }
 
 /// TCP 플래그 비트맵을 문자열로 변환
+#[allow(dead_code)]
 pub fn tcp_flags_to_string(flags: u8) -> String {
     let mut result = Vec::new();
     
@@ -124,7 +126,16 @@ pub fn tcp_flags_to_string(flags: u8) -> String {
     }
 }
 
+/// 통계 표시용 프로토콜 이름 (`ProtocolStats::entries`의 "tcp" → "TCP", "other" → "Other")
+pub fn protocol_stat_name(name: &str) -> String {
+    match name {
+        "other" => "Other".to_string(),
+        name => name.to_uppercase(),
+    }
+}
+
 /// 포트 범위를 문자열로 변환
+#[allow(dead_code)]
 pub fn port_range_to_string(min: u16, max: u16) -> Option<String> {
     if min == 0 && max == 65535 {
         None
@@ -135,7 +146,8 @@ pub fn port_range_to_string(min: u16, max: u16) -> Option<String> {
     }
 }
 
-/// IPv4 주소를 문자열로 변환
+/// IPv4 주소를 문자열로 변환 (`parse_ip_prefix`와 같은 호스트 순서 값)
+#[allow(dead_code)]
 pub fn ipv4_to_string(addr: u32) -> String {
     format!("{}.{}.{}.{}", 
         (addr >> 24) & 0xFF,
@@ -145,9 +157,376 @@ pub fn ipv4_to_string(addr: u32) -> String {
     )
 }
 
+/// 패킷 크기 히스토그램을 가로 막대 차트로 렌더링
+///
+/// `width`는 막대 영역의 최대 폭(문자 수)이며 가장 큰 버킷이 이 폭을 채운다.
+pub fn render_histogram(counts: &[u64], width: usize) -> Vec<String> {
+    let rows: Vec<(String, u64)> = counts.iter().enumerate()
+        .map(|(i, &count)| (size_bucket_label(i), count))
+        .collect();
+    render_bars(&rows, width)
+}
+
+/// 프리픽스 길이별 LPM 항목 수를 가로 막대 차트로 렌더링 (항목이 있는 길이만)
+pub fn render_prefix_histogram(prefix_lengths: &[usize], width: usize) -> Vec<String> {
+    let rows: Vec<(String, u64)> = prefix_lengths.iter().enumerate()
+        .filter(|(_, &count)| count > 0)
+        .map(|(prefix_len, &count)| (format!("/{}", prefix_len), count as u64))
+        .collect();
+    render_bars(&rows, width)
+}
+
+/// 처리 시간 히스토그램을 가로 막대 차트로 렌더링 (버킷 상한은 마이크로초)
+pub fn render_latency_histogram(latency: &LatencySnapshot, width: usize) -> Vec<String> {
+    let rows: Vec<(String, u64)> = latency.counts.iter().enumerate()
+        .map(|(i, &count)| {
+            let label = match latency.bounds_us.get(i) {
+                Some(bound) => format!("<={}us", bound),
+                None => format!(">{}us", latency.bounds_us.last().copied().unwrap_or(0)),
+            };
+            (label, count)
+        })
+        .collect();
+    render_bars(&rows, width)
+}
+
+/// 이름 붙은 값을 가로 막대로 (가장 큰 값이 `width`를 채움, 비율은 합계 기준)
+fn render_bars(rows: &[(String, u64)], width: usize) -> Vec<String> {
+    let max = rows.iter().map(|(_, count)| *count).max().unwrap_or(0);
+    let total: u64 = rows.iter().map(|(_, count)| count).sum();
+
+    rows.iter().map(|(label, count)| {
+        let bar_len = if max == 0 {
+            0
+        } else {
+            ((*count as f64 / max as f64) * width as f64).round() as usize
+        };
+        let percent = if total == 0 { 0.0 } else { *count as f64 * 100.0 / total as f64 };
+
+        format!("{:>7} |{:<width$}| {} ({:.1}%)",
+                label, "#".repeat(bar_len), count, percent, width = width)
+    }).collect()
+}
+
+/// 값 목록을 한 줄 스파크라인으로 (가장 큰 값이 █, 0은 ▁, 0이 아닌 값은 ▁보다 높게)
+pub fn render_sparkline(values: &[u64]) -> String {
+    const LEVELS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+    let max = values.iter().copied().max().unwrap_or(0);
+
+    values.iter().map(|&value| {
+        if value == 0 {
+            return LEVELS[0];
+        }
+        let level = (value as f64 / max as f64 * (LEVELS.len() - 1) as f64).ceil() as usize;
+        LEVELS[level.clamp(1, LEVELS.len() - 1)]
+    }).collect()
+}
+
+/// 기간 옵션을 u32 초로 파싱 (예: `--expire 1h`)
+pub fn parse_duration_u32(s: &str) -> Result<u32> {
+    let secs = parse_duration(s)?;
+    u32::try_from(secs).map_err(|_| anyhow!("Duration too long: {} (at most {}s)", s, u32::MAX))
+}
+
+/// 개수 옵션을 u32로 파싱 (예: `--rate-limit 10k`)
+pub fn parse_count_u32(s: &str) -> Result<u32> {
+    let count = parse_count(s)?;
+    u32::try_from(count).map_err(|_| anyhow!("Count too large: {} (at most {})", s, u32::MAX))
+}
+
+/// 규칙 목록 정렬 기준 (`list-rules --sort`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RuleSort {
+    /// 데몬 기본 순서 (우선순위 내림차순, 같으면 생성 시각 오름차순)
+    Priority,
+    /// 생성 시각 오름차순
+    Created,
+}
+
+/// 정렬 기준 파싱
+pub fn parse_rule_sort(s: &str) -> Result<RuleSort> {
+    match s {
+        "priority" => Ok(RuleSort::Priority),
+        "created" => Ok(RuleSort::Created),
+        _ => Err(anyhow!("Invalid sort key: {} (expected priority or created)", s)),
+    }
+}
+
+/// 주소 체계 필터 파싱 ("4", "6", "ipv4", "ipv6" 등)
+pub fn parse_family(s: &str) -> Result<AddressFamily> {
+    match AddressFamily::parse(s) {
+        Some(family) if family != AddressFamily::Any => Ok(family),
+        _ => Err(anyhow!("Invalid address family: {} (expected 4 or 6)", s)),
+    }
+}
+
+/// 규칙 목록 정렬 (안정 정렬이라 같은 값은 받은 순서를 유지)
+pub fn sort_rules(rules: &mut [RuleInfo], sort: RuleSort) {
+    match sort {
+        RuleSort::Priority => rules.sort_by(|a, b| b.priority.cmp(&a.priority)
+            .then(a.creation_time.cmp(&b.creation_time))),
+        RuleSort::Created => rules.sort_by_key(|rule| rule.creation_time),
+    }
+}
+
+/// 남은 수명이 짧은 순으로 정렬 (만료 없는 규칙은 맨 뒤)
+pub fn sort_rules_by_ttl(rules: &mut [RuleInfo], now: u64) {
+    rules.sort_by_key(|rule| ttl_remaining(rule.creation_time, rule.expire, now).unwrap_or(u64::MAX));
+}
+
+/// 규칙의 남은 수명(TTL)을 문자열로 변환 (None = 만료 없음)
+pub fn format_ttl(ttl_remaining: Option<u64>, expire: u32) -> String {
+    match ttl_remaining {
+        None => "never".to_string(),
+        Some(0) => "expired".to_string(),
+        Some(remaining) => format!("{} remaining (of {})", format_duration(remaining), format_duration(expire.into())),
+    }
+}
+
+/// XDP_ABORTED 누적 수 한 줄 (0이 아니면 대부분 잘못된 리디렉션 대상이므로 경고를 붙임)
+pub fn format_aborted(aborted: u64) -> String {
+    if aborted == 0 {
+        "XDP aborted: 0".to_string()
+    } else {
+        format!("XDP aborted: {} !! packets ended in XDP_ABORTED; check redirect targets (list-rules --stats)", aborted)
+    }
+}
+
+/// 트래픽 기준선 한 줄 (평균 ± 표준편차, 기준선에 반영한 구간 수)
+pub fn format_baseline(baseline: &TrafficBaseline) -> String {
+    format!("Baseline: {:.0} +/- {:.0} pps, {:.2} +/- {:.2} Mbps ({} intervals)",
+        baseline.pps_mean, baseline.pps_stddev, baseline.mbps_mean, baseline.mbps_stddev, baseline.samples)
+}
+
+/// 트래픽 이상 경고 한 줄
+pub fn format_anomaly(baseline: &TrafficBaseline) -> String {
+    format!("ANOMALY !! traffic above baseline mean + {:.1} stddev", baseline.k)
+}
+
+/// 영구/만료 예정 규칙 수와 다음 만료 시각 한 줄 요약
+pub fn format_rule_summary(summary: &RuleExpirySummary) -> String {
+    let next = summary.next_expiry
+        .and_then(|ts| chrono::DateTime::from_timestamp(ts as i64, 0))
+        .map(|t| format!(" (next expiry {} UTC)", t.format("%Y-%m-%d %H:%M:%S")))
+        .unwrap_or_default();
+    
+    format!("Rules: {} permanent, {} expiring{}", summary.permanent, summary.expiring, next)
+}
+
+/// 가동 시간 포맷 (예: "1d 2h 3m", "45s")
+pub fn format_uptime(secs: u64) -> String {
+    let (days, hours, minutes) = (secs / 86400, secs % 86400 / 3600, secs % 3600 / 60);
+    
+    if days > 0 {
+        format!("{}d {}h {}m", days, hours, minutes)
+    } else if hours > 0 {
+        format!("{}h {}m", hours, minutes)
+    } else if minutes > 0 {
+        format!("{}m {}s", minutes, secs % 60)
+    } else {
+        format!("{}s", secs)
+    }
+}
+
+/// 데몬 자체 통계 줄 단위 렌더링
+pub fn format_daemon_stats(daemon: &DaemonSelfStats) -> Vec<String> {
+    vec![
+        format!("CPU:        {:.1}%", daemon.cpu_percent),
+        format!("RSS:        {}", format_size(daemon.rss_bytes)),
+        format!("Open fds:   {}", daemon.open_fds),
+        format!("Tasks:      {}", daemon.tokio_tasks),
+        format!("Uptime:     {}", format_uptime(daemon.uptime_secs)),
+        format!("Event lag:  {}", daemon.event_lag),
+    ]
+}
+
+/// API 서버 요청 통계 한 줄 요약
+pub fn format_api_summary(api: &ApiServerStats) -> String {
+    format!("API: {} requests, {} errors, {} rate limited, {} oversized, {} open connections",
+        api.requests_total, api.errors_total, api.rate_limited_total,
+        api.oversized_frames_total, api.active_connections)
+}
+
+/// 웹훅 전송 통계 한 줄 요약
+pub fn format_webhook_summary(webhooks: &WebhookStats) -> String {
+    format!("Webhooks: {} delivered, {} failed, {} dropped, {} retries, {} queued",
+        webhooks.delivered, webhooks.failed, webhooks.dropped, webhooks.retries, webhooks.queued)
+}
+
+/// XDP 프로그램 매치 기능 한 줄 요약 (이 CLI가 아는 기능 중 빠진 것도 표시)
+pub fn format_xdp_features(features: XdpFeatures) -> String {
+    let missing = features.missing(XdpFeatures::ALL.0);
+    if missing.is_empty() {
+        format!("XDP match features: {}", features)
+    } else {
+        format!("XDP match features: {} (not supported: {})", features, missing)
+    }
+}
+
+/// 마지막 BPF 오브젝트 로드 실패 줄 단위 렌더링 (원인, libbpf 로그)
+pub fn format_load_failure(failure: &BpfLoadFailure) -> Vec<String> {
+    let mut lines = vec![
+        format!("Last BPF load failure at {}: {}", format_time_ms(failure.timestamp.saturating_mul(1000)), failure.object),
+        format!("  {}", failure.error),
+    ];
+    if !failure.log.is_empty() {
+        lines.push("  libbpf log:".to_string());
+        lines.extend(failure.log.iter().map(|line| format!("    {}", line)));
+    }
+    lines
+}
+
+/// 표준 오류에 진행 막대를 그릴지 여부 (터미널일 때)
+pub fn show_progress() -> bool {
+    use std::io::IsTerminal;
+    std::io::stderr().is_terminal()
+}
+
+/// 진행 막대 한 줄 (예: `[########------------] 2000/5000 (40%)`)
+pub fn format_progress(done: usize, total: usize, width: usize) -> String {
+    let done = done.min(total);
+    let (filled, percent) = match total {
+        0 => (width, 100),
+        _ => (done * width / total, done * 100 / total),
+    };
+    format!("[{}{}] {}/{} ({}%)", "#".repeat(filled), "-".repeat(width - filled), done, total, percent)
+}
+
+/// 확인 질문의 답이 동의인지 ("y", "yes", 대소문자 무시)
+pub fn is_affirmative(answer: &str) -> bool {
+    matches!(answer.trim().to_ascii_lowercase().as_str(), "y" | "yes")
+}
+
+/// 표준 입력이 터미널이면 stderr에 질문하고 답을 읽음 (터미널이 아니면 묻지 않고 거절)
+pub fn confirm(question: &str) -> Result<bool> {
+    use std::io::{BufRead, IsTerminal, Write};
+    if !std::io::stdin().is_terminal() {
+        return Ok(false);
+    }
+
+    eprint!("{} [y/N] ", question);
+    std::io::stderr().flush()?;
+    let mut answer = String::new();
+    std::io::stdin().lock().read_line(&mut answer)?;
+    Ok(is_affirmative(&answer))
+}
+
+/// 경고 한 줄 포맷 (color면 노란색)
+pub fn format_warning(message: &str, color: bool) -> String {
+    style::paint(&format!("Warning: {}", message), style::YELLOW, color)
+}
+
+/// 재연결 알림 (예: "Reconnected to API server after 4.2s (3 attempts)")
+pub fn format_reconnected(reconnected: &Reconnected) -> String {
+    let attempts = match reconnected.attempts {
+        1 => "1 attempt".to_string(),
+        n => format!("{} attempts", n),
+    };
+    format!("Reconnected to API server after {:.1}s ({})", reconnected.downtime.as_secs_f64(), attempts)
+}
+
+/// WASM 모듈 로그 한 줄 포맷 (UTC 시각, 밀리초 포함)
+pub fn format_log_line(line: &WasmLogLine) -> String {
+    format!("{} {}", format_time_ms(line.timestamp_ms), line.message)
+}
+
+/// UNIX 밀리초를 UTC 시각으로 포맷 (밀리초 포함)
+fn format_time_ms(timestamp_ms: u64) -> String {
+    chrono::DateTime::from_timestamp_millis(timestamp_ms as i64)
+        .map(|t| t.format("%Y-%m-%d %H:%M:%S%.3f").to_string())
+        .unwrap_or_else(|| "-".to_string())
+}
+
+/// 데몬 이벤트 한 줄 포맷 (순번, UTC 시각, 종류, 종류별 요약)
+pub fn format_event(event: &SequencedEvent) -> String {
+    match &event.event {
+        DaemonEvent::RuleHit(hit) => format!("#{} {} rule_hit {} {} {} {}:{}{} -> {}:{}",
+            event.seq, format_time_ms(hit.timestamp_ns / 1_000_000), hit.label, hit.action, hit.protocol,
+            hit.src_ip, hit.src_port, geo_suffix(&hit.geo), hit.dst_ip, hit.dst_port),
+        DaemonEvent::Mitigation(mitigation) => format!("#{} {} mitigation {} {} {}{} ({} SYN/s){}",
+            event.seq, format_time_ms(mitigation.timestamp.saturating_mul(1000)), mitigation.label,
+            mitigation.action, mitigation.src_ip, geo_suffix(&mitigation.geo), mitigation.syn_pps,
+            if mitigation.dry_run { " [dry-run]" } else { "" }),
+        DaemonEvent::RuleMatched(matched) => format!("#{} {} rule_matched {} {} packets ({} pps, {}/s)",
+            event.seq, format_time_ms(matched.timestamp.saturating_mul(1000)), matched.label,
+            matched.packets, matched.pps, format_size(matched.bps)),
+        DaemonEvent::LinkState(link) => format!("#{} {} link_state {} {}{}{}",
+            event.seq, format_time_ms(link.timestamp.saturating_mul(1000)), link.interface, link.state,
+            link.action.as_ref().map(|action| format!(" ({})", action)).unwrap_or_default(),
+            if link.rules.is_empty() { String::new() } else { format!(" rules: {}", link.rules.join(", ")) }),
+        DaemonEvent::RuleExpiring(expiry) => format_rule_expiry(event.seq, "rule_expiring", "expires", expiry),
+        DaemonEvent::RuleExpired(expiry) => format_rule_expiry(event.seq, "rule_expired", "expired", expiry),
+        DaemonEvent::Anomaly(anomaly) => format!("#{} {} anomaly {} {} pps, {:.2} Mbps (baseline {:.0} pps, {:.2} Mbps)",
+            event.seq, format_time_ms(anomaly.timestamp.saturating_mul(1000)), anomaly.metrics.join(","),
+            anomaly.pps, anomaly.mbps, anomaly.baseline.pps_mean, anomaly.baseline.mbps_mean),
+        DaemonEvent::XdpAborted(aborted) => format!("#{} {} xdp_aborted {} packets over {} intervals ({} total){}",
+            event.seq, format_time_ms(aborted.timestamp.saturating_mul(1000)), aborted.aborted, aborted.intervals,
+            aborted.total,
+            if aborted.rules.is_empty() { String::new() } else { format!(" rules: {}", aborted.rules.join(", ")) }),
+    }
+}
+
+/// 규칙 만료 예고/만료 이벤트 한 줄 (만료 시각과 누적 통계)
+fn format_rule_expiry(seq: u64, kind: &str, verb: &str, expiry: &RuleExpiryEvent) -> String {
+    format!("#{} {} {} {} {} at {} ({} packets, {})",
+        seq, format_time_ms(expiry.timestamp.saturating_mul(1000)), kind, expiry.rule.key(), verb,
+        format_time_ms(expiry.expires_at.saturating_mul(1000)), expiry.rule.stats.packets,
+        format_size(expiry.rule.stats.bytes))
+}
+
+/// 소스 주소 뒤에 붙이는 GeoIP 정보 (" [KR, AS4766]", 없으면 빈 문자열)
+fn geo_suffix(geo: &Option<GeoInfo>) -> String {
+    geo.as_ref().map(|geo| format!(" [{}]", geo)).unwrap_or_default()
+}
+
+/// 기록에서 밀려난 이벤트 구간 안내
+pub fn format_event_gap(gap: &EventGap) -> String {
+    if gap.first_seq == gap.last_seq {
+        format!("missed 1 event (seq {} is no longer in the daemon's history)", gap.first_seq)
+    } else {
+        format!("missed {} events (seq {}-{} are no longer in the daemon's history)",
+            gap.missed(), gap.first_seq, gap.last_seq)
+    }
+}
+
+/// 규칙 변경 한 줄 (monitor --rules, 스냅샷의 규칙은 kind가 "present")
+pub fn format_rule_change(seq: u64, kind: &str, rule: &RuleInfo) -> String {
+    format!("#{} {} {} {} {} {} -> {} priority {}",
+        seq, kind, swift_guard::utils::qualify_label(&rule.namespace, &rule.display_label()), rule.display_action(),
+        rule.protocol, rule.source(), rule.destination(), rule.priority)
+}
+
+/// 커널 기능 점검 결과를 체크리스트로 렌더링 (경고/실패 항목 아래에 해결 방법, color면 상태 표시에 색)
+pub fn render_checklist(checks: &[CapabilityCheck], color: bool) -> Vec<String> {
+    let mut lines = Vec::new();
+
+    for check in checks {
+        let (mark, code) = match check.status {
+            CheckStatus::Ok => ("[ OK ]", Some(style::GREEN)),
+            CheckStatus::Warn => ("[WARN]", Some(style::YELLOW)),
+            CheckStatus::Fail => ("[FAIL]", Some(style::RED)),
+            CheckStatus::Skipped => ("[SKIP]", None),
+        };
+        lines.push(format!("{} {}: {}", style::paint_with(mark, code, color), check.name, check.detail));
+
+        if check.status != CheckStatus::Ok {
+            if let Some(remediation) = &check.remediation {
+                lines.push(format!("       -> {}", remediation));
+            }
+        }
+    }
+
+    let count = |status| checks.iter().filter(|check| check.status == status).count();
+    lines.push(format!("{} ok, {} warnings, {} failed, {} skipped",
+        count(CheckStatus::Ok), count(CheckStatus::Warn), count(CheckStatus::Fail), count(CheckStatus::Skipped)));
+
+    lines
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use swift_guard::utils::parse_ip_prefix;
     
     #[test]
     fn test_parse_port_range() {
@@ -157,7 +536,7 @@ mod tests {
         assert!(parse_port_range("1024-abc").is_err());
         assert!(parse_port_range("2048-1024").is_err());
     }
-    
+
     #[test]
     fn test_parse_ip_prefix() {
         assert_eq!(parse_ip_prefix("192.168.1.1").unwrap(), (0xC0A80101, 32));
@@ -165,4 +544,356 @@ mod tests {
         assert!(parse_ip_prefix("256.168.1.1").is_err());
         assert!(parse_ip_prefix("192.168.1.1/33").is_err());
     }
+
+    #[test]
+    fn test_parse_tcp_flags() {
+        assert_eq!(parse_tcp_flags("SYN").unwrap(), 0x02);
+        assert_eq!(parse_tcp_flags("SYN, ACK").unwrap(), 0x12);
+        assert!(parse_tcp_flags("SYN,XMAS").is_err());
+    }
+
+    #[test]
+    fn test_check_rule_spec() {
+        use swift_guard::types::ActionType;
+
+        assert!(check_rule_spec(&RuleSpec::new("web", ActionType::Drop)).is_ok());
+
+        let err = check_rule_spec(&RuleSpec::new("web", ActionType::Redirect)).unwrap_err();
+        assert_eq!(err.to_string(), "Invalid rule: redirect_if: redirect action requires redirect_if or redirect_cpu");
+
+        // 문제를 모두 한 번에 보고
+        let spec = RuleSpec { counter_group: Some("web".to_string()), quota_action: Some(2), ..RuleSpec::new("bad label", ActionType::Drop) };
+        let err = check_rule_spec(&spec).unwrap_err().to_string();
+        assert!(err.starts_with("Invalid rule (3 issues):\n  - label: "), "{}", err);
+        assert!(err.contains("\n  - counter_group: counter_group requires the count action"));
+        assert!(err.ends_with("\n  - quota_action: quota_action requires quota_bytes or quota_packets"));
+    }
+
+    #[test]
+    fn test_quota_helpers() {
+        let quota = QuotaInfo { bytes: Some(10 << 30), packets: Some(1000), action: "drop".to_string() };
+        assert_eq!(format_quota(&quota), "10.00 GB, 1000 packets -> drop");
+        assert_eq!(format_quota(&QuotaInfo { bytes: None, ..quota }), "1000 packets -> drop");
+    }
+
+    #[test]
+    fn test_render_histogram() {
+        let lines = render_histogram(&[10, 5, 0, 0, 0, 0, 5], 10);
+        assert_eq!(lines.len(), 7);
+        assert_eq!(lines[0], "   <=64 |##########| 10 (50.0%)");
+        assert_eq!(lines[1], "  <=128 |#####     | 5 (25.0%)");
+        assert_eq!(lines[2], "  <=256 |          | 0 (0.0%)");
+        assert_eq!(lines[6], "  >1514 |#####     | 5 (25.0%)");
+
+        let empty = render_histogram(&[0; 7], 4);
+        assert_eq!(empty[0], "   <=64 |    | 0 (0.0%)");
+    }
+
+    #[test]
+    fn test_render_prefix_histogram() {
+        let mut prefix_lengths = vec![0; 33];
+        prefix_lengths[8] = 1;
+        prefix_lengths[24] = 4;
+        prefix_lengths[32] = 3;
+
+        let lines = render_prefix_histogram(&prefix_lengths, 8);
+        assert_eq!(lines, vec![
+            "     /8 |##      | 1 (12.5%)",
+            "    /24 |########| 4 (50.0%)",
+            "    /32 |######  | 3 (37.5%)",
+        ]);
+        assert!(render_prefix_histogram(&[0; 33], 8).is_empty());
+    }
+
+    #[test]
+    fn test_render_latency_histogram() {
+        let latency = LatencySnapshot { bounds_us: vec![1, 10], counts: vec![6, 2, 0], sum_us: 20 };
+        assert_eq!(render_latency_histogram(&latency, 6), vec![
+            "  <=1us |######| 6 (75.0%)",
+            " <=10us |##    | 2 (25.0%)",
+            "  >10us |      | 0 (0.0%)",
+        ]);
+    }
+
+    #[test]
+    fn test_render_sparkline() {
+        assert_eq!(render_sparkline(&[0, 1, 7, 14, 0]), "▁▂▅█▁");
+        // 작은 값도 0과 구분되고, 큰 값에서도 넘치지 않음
+        assert_eq!(render_sparkline(&[1, 1000, u64::MAX]), "▂▂█");
+        assert_eq!(render_sparkline(&[0, 0]), "▁▁");
+        assert_eq!(render_sparkline(&[]), "");
+    }
+
+    #[test]
+    fn test_protocol_stat_name() {
+        let names: Vec<String> = crate::api::ProtocolStats::default().entries().iter()
+            .map(|(name, _)| protocol_stat_name(name))
+            .collect();
+        assert_eq!(names, ["TCP", "UDP", "ICMP", "Other"]);
+    }
+
+    #[test]
+    fn test_parse_u32_options() {
+        assert_eq!(parse_duration_u32("1h").unwrap(), 3600);
+        assert_eq!(parse_duration_u32("3600").unwrap(), 3600);
+        assert!(parse_duration_u32("50000d").is_err());
+        assert_eq!(parse_count_u32("10k").unwrap(), 10_000);
+        assert_eq!(parse_count_u32("1000").unwrap(), 1000);
+        assert!(parse_count_u32("5G").is_err());
+        assert!(format!("{:#}", parse_count_u32("10x").unwrap_err()).contains("k, M or G"));
+    }
+
+    #[test]
+    fn test_format_ttl() {
+        assert_eq!(format_ttl(None, 0), "never");
+        assert_eq!(format_ttl(Some(30), 60), "30s remaining (of 1m)");
+        assert_eq!(format_ttl(Some(5400), 86400), "90m remaining (of 1d)");
+        assert_eq!(format_ttl(Some(0), 60), "expired");
+    }
+
+    #[test]
+    fn test_format_rule_summary() {
+        let summary = RuleExpirySummary { permanent: 4, expiring: 0, next_expiry: None };
+        assert_eq!(format_rule_summary(&summary), "Rules: 4 permanent, 0 expiring");
+
+        let summary = RuleExpirySummary { permanent: 1, expiring: 2, next_expiry: Some(1_700_000_000) };
+        assert_eq!(format_rule_summary(&summary), "Rules: 1 permanent, 2 expiring (next expiry 2023-11-14 22:13:20 UTC)");
+    }
+
+    #[test]
+    fn test_format_daemon_stats() {
+        assert_eq!(format_uptime(45), "45s");
+        assert_eq!(format_uptime(125), "2m 5s");
+        assert_eq!(format_uptime(7380), "2h 3m");
+        assert_eq!(format_uptime(93780), "1d 2h 3m");
+
+        let daemon = DaemonSelfStats {
+            cpu_percent: 3.26,
+            rss_bytes: 50331648,
+            open_fds: 37,
+            tokio_tasks: 12,
+            uptime_secs: 93780,
+            event_lag: 0,
+        };
+        assert_eq!(format_daemon_stats(&daemon), vec![
+            "CPU:        3.3%",
+            "RSS:        48.00 MB",
+            "Open fds:   37",
+            "Tasks:      12",
+            "Uptime:     1d 2h 3m",
+            "Event lag:  0",
+        ]);
+
+        let api = ApiServerStats { requests_total: 120, errors_total: 3, rate_limited_total: 2, active_connections: 1, ..Default::default() };
+        assert_eq!(format_api_summary(&api), "API: 120 requests, 3 errors, 2 rate limited, 0 oversized, 1 open connections");
+
+        let webhooks = WebhookStats { delivered: 96, failed: 2, dropped: 5, retries: 7, queued: 0 };
+        assert_eq!(format_webhook_summary(&webhooks), "Webhooks: 96 delivered, 2 failed, 5 dropped, 7 retries, 0 queued");
+    }
+
+    #[test]
+    fn test_format_load_failure() {
+        let mut failure = BpfLoadFailure {
+            object: "/opt/xdp/vendor.o".to_string(),
+            error: "Failed to load BPF object into the kernel: Permission denied (os error 13)".to_string(),
+            log: Vec::new(),
+            timestamp: 1_700_000_000,
+        };
+        assert_eq!(format_load_failure(&failure), vec![
+            "Last BPF load failure at 2023-11-14 22:13:20.000: /opt/xdp/vendor.o",
+            "  Failed to load BPF object into the kernel: Permission denied (os error 13)",
+        ]);
+
+        failure.log = vec!["R1 invalid mem access 'scalar'".to_string()];
+        assert_eq!(&format_load_failure(&failure)[2..], ["  libbpf log:", "    R1 invalid mem access 'scalar'"]);
+    }
+
+    #[test]
+    fn test_format_xdp_features() {
+        assert_eq!(format_xdp_features(XdpFeatures::ALL), "XDP match features: tcp_flags,port_list,redirect_cpu,rate_limit");
+        assert_eq!(format_xdp_features(XdpFeatures::LEGACY),
+            "XDP match features: tcp_flags (not supported: port_list,redirect_cpu,rate_limit)");
+        assert_eq!(format_xdp_features(XdpFeatures::default()),
+            "XDP match features: none (not supported: tcp_flags,port_list,redirect_cpu,rate_limit)");
+    }
+
+    #[test]
+    fn test_sort_rules() {
+        let rule = |label: &str, priority: u32, creation_time: u64| -> RuleInfo {
+            RuleInfo {
+                label: label.to_string(),
+                protocol: "tcp".to_string(),
+                priority,
+                creation_time,
+                ..RuleInfo::default()
+            }
+        };
+        let labels = |rules: &[RuleInfo]| rules.iter().map(|rule| rule.label.clone()).collect::<Vec<_>>();
+        let mut rules = vec![rule("a", 0, 300), rule("b", 10, 200), rule("c", 10, 100), rule("d", 0, 100)];
+
+        sort_rules(&mut rules, parse_rule_sort("priority").unwrap());
+        assert_eq!(labels(&rules), ["c", "b", "d", "a"]);
+        sort_rules(&mut rules, parse_rule_sort("created").unwrap());
+        assert_eq!(labels(&rules), ["c", "d", "b", "a"]);
+        assert!(parse_rule_sort("label").is_err());
+    }
+
+    #[test]
+    fn test_parse_family() {
+        assert_eq!(parse_family("4").unwrap(), AddressFamily::V4);
+        assert_eq!(parse_family("IPv6").unwrap(), AddressFamily::V6);
+        assert!(parse_family("any").is_err());
+        assert!(parse_family("5").is_err());
+    }
+
+    #[test]
+    fn test_format_warning() {
+        assert_eq!(format_warning("no interfaces", false), "Warning: no interfaces");
+        assert_eq!(format_warning("no interfaces", true), "\x1b[33mWarning: no interfaces\x1b[0m");
+    }
+
+    #[test]
+    fn test_format_event() {
+        let hit: SequencedEvent = serde_json::from_value(serde_json::json!({
+            "seq": 42,
+            "event": {"RuleHit": {
+                "label": "block-web", "rule_key": "192.168.1.0/24", "action": "drop", "protocol": "tcp",
+                "src_ip": "192.168.1.100", "src_port": 40000, "dst_ip": "10.0.0.1", "dst_port": 80,
+                "timestamp_ns": 1_700_000_000_123_456_789u64,
+            }},
+        })).unwrap();
+        assert_eq!(format_event(&hit),
+            "#42 2023-11-14 22:13:20.123 rule_hit block-web drop tcp 192.168.1.100:40000 -> 10.0.0.1:80");
+
+        let mitigation: SequencedEvent = serde_json::from_value(serde_json::json!({
+            "seq": 43,
+            "event": {"Mitigation": {
+                "kind": "synflood", "src_ip": "192.168.1.10", "syn_pps": 5000, "label": "auto-synflood-192.168.1.10",
+                "action": "drop", "expire": 60, "dry_run": true, "timestamp": 1_700_000_000u64,
+                "geo": {"country": "KR", "asn": 4766, "as_org": "Korea Telecom"},
+            }},
+        })).unwrap();
+        assert_eq!(format_event(&mitigation), "#43 2023-11-14 22:13:20.000 mitigation auto-synflood-192.168.1.10 drop \
+            192.168.1.10 [KR, AS4766 (Korea Telecom)] (5000 SYN/s) [dry-run]");
+
+        let matched: SequencedEvent = serde_json::from_value(serde_json::json!({
+            "seq": 44,
+            "event": {"RuleMatched": {
+                "label": "block-web", "packets": 600, "bytes": 61440, "pps": 120, "bps": 12288,
+                "timestamp": 1_700_000_005u64,
+            }},
+        })).unwrap();
+        assert_eq!(format_event(&matched),
+            "#44 2023-11-14 22:13:25.000 rule_matched block-web 600 packets (120 pps, 12.00 KB/s)");
+
+        let link: SequencedEvent = serde_json::from_value(serde_json::json!({
+            "seq": 45,
+            "event": {"LinkState": {
+                "interface": "veth1", "state": "down", "action": "detached", "rules": ["to-ids"],
+                "timestamp": 1_700_000_010u64,
+            }},
+        })).unwrap();
+        assert_eq!(format_event(&link),
+            "#45 2023-11-14 22:13:30.000 link_state veth1 down (detached) rules: to-ids");
+
+        let expiring: SequencedEvent = serde_json::from_value(serde_json::json!({
+            "seq": 46,
+            "event": {"RuleExpiring": {
+                "rule": {
+                    "label": "maintenance", "namespace": "team-a", "action": "pass", "src_ip": "10.0.0.0/8",
+                    "dst_ip": null, "src_port": null, "dst_port": null, "protocol": "any", "tcp_flags": null,
+                    "priority": 0, "redirect_if": null, "rate_limit": 0, "expire": 3600,
+                    "owner_webhook": "http://ops:8080/expiry",
+                    "stats": { "packets": 1200, "bytes": 2048, "last_matched": 0 },
+                },
+                "expires_at": 1_700_000_600u64, "timestamp": 1_700_000_000u64,
+            }},
+        })).unwrap();
+        assert_eq!(format_event(&expiring), "#46 2023-11-14 22:13:20.000 rule_expiring team-a/maintenance \
+            expires at 2023-11-14 22:23:20.000 (1200 packets, 2.00 KB)");
+
+        let aborted: SequencedEvent = serde_json::from_value(serde_json::json!({
+            "seq": 47,
+            "event": {"XdpAborted": {
+                "aborted": 30, "total": 95, "intervals": 3, "rules": ["to-ids", "team-a/mirror"],
+                "timestamp": 1_700_000_030u64,
+            }},
+        })).unwrap();
+        assert_eq!(format_event(&aborted), "#47 2023-11-14 22:13:50.000 xdp_aborted 30 packets over 3 intervals \
+            (95 total) rules: to-ids, team-a/mirror");
+
+        let anomaly: SequencedEvent = serde_json::from_value(serde_json::json!({
+            "seq": 48,
+            "event": {"Anomaly": {
+                "pps": 5200, "mbps": 41.5, "metrics": ["pps", "mbps"],
+                "baseline": {"pps_mean": 1000.4, "pps_stddev": 20.0, "mbps_mean": 8.0, "mbps_stddev": 0.2, "samples": 30, "k": 3.0},
+                "timestamp": 1_700_000_040u64,
+            }},
+        })).unwrap();
+        assert_eq!(format_event(&anomaly), "#48 2023-11-14 22:14:00.000 anomaly pps,mbps 5200 pps, 41.50 Mbps \
+            (baseline 1000 pps, 8.00 Mbps)");
+
+        assert_eq!(format_event_gap(&EventGap { first_seq: 3, last_seq: 3 }),
+            "missed 1 event (seq 3 is no longer in the daemon's history)");
+        assert_eq!(format_event_gap(&EventGap { first_seq: 1, last_seq: 120 }),
+            "missed 120 events (seq 1-120 are no longer in the daemon's history)");
+
+        let rule: RuleInfo = serde_json::from_value(serde_json::json!({
+            "label": "web", "namespace": "team-a", "action": "redirect", "src_ip": "10.0.0.0/8", "dst_ip": null,
+            "src_port": null, "dst_port": "80", "protocol": "tcp", "tcp_flags": null, "priority": 10,
+            "redirect_if": "wasm0", "rate_limit": 0, "expire": 0, "degraded": true,
+            "stats": { "packets": 0, "bytes": 0, "last_matched": 0 },
+        })).unwrap();
+        assert_eq!(format_rule_change(7, "updated", &rule),
+            "#7 updated team-a/web (degraded) redirect:wasm0 tcp 10.0.0.0/8 -> *:80 priority 10");
+    }
+
+    #[test]
+    fn test_render_checklist() {
+        let check = |name: &str, status, remediation: Option<&str>| CapabilityCheck {
+            name: name.to_string(),
+            status,
+            detail: "detail".to_string(),
+            remediation: remediation.map(str::to_string),
+        };
+
+        let checks = [
+            check("kernel_version", CheckStatus::Ok, None),
+            check("btf", CheckStatus::Warn, Some("Enable CONFIG_DEBUG_INFO_BTF")),
+            check("devmap", CheckStatus::Fail, Some("Run as root")),
+            check("xdp_driver_attach", CheckStatus::Skipped, None),
+        ];
+        let lines = render_checklist(&checks, false);
+        assert_eq!(lines, [
+            "[ OK ] kernel_version: detail",
+            "[WARN] btf: detail",
+            "       -> Enable CONFIG_DEBUG_INFO_BTF",
+            "[FAIL] devmap: detail",
+            "       -> Run as root",
+            "[SKIP] xdp_driver_attach: detail",
+            "1 ok, 1 warnings, 1 failed, 1 skipped",
+        ]);
+
+        // 색상은 상태 표시에만
+        let colored = render_checklist(&checks, true);
+        assert_eq!(colored[0], "\x1b[32m[ OK ]\x1b[0m kernel_version: detail");
+        assert_eq!(colored[3], "\x1b[31m[FAIL]\x1b[0m devmap: detail");
+        assert_eq!(colored[5], "[SKIP] xdp_driver_attach: detail");
+    }
+
+    #[test]
+    fn test_format_progress() {
+        assert_eq!(format_progress(2000, 5000, 20), "[########------------] 2000/5000 (40%)");
+        assert_eq!(format_progress(5000, 5000, 10), "[##########] 5000/5000 (100%)");
+        assert_eq!(format_progress(0, 0, 4), "[####] 0/0 (100%)");
+    }
+
+    #[test]
+    fn test_is_affirmative() {
+        assert!(is_affirmative("y\n"));
+        assert!(is_affirmative(" YES "));
+        assert!(!is_affirmative(""));
+        assert!(!is_affirmative("n"));
+        assert!(!is_affirmative("yep"));
+    }
 }
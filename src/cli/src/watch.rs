@@ -0,0 +1,246 @@
+//! 감시 모드 모듈
+//! `list-rules --watch`의 갱신 사이 카운터 변화 계산과 터미널 화면 그리기
+//!
+//! 변화 계산(`WatchState`)과 화면 구성(`render_frame`)은 터미널과 분리되어 있어
+//! 연속된 규칙 목록만으로 시험할 수 있다.
+
+use std::collections::HashMap;
+use std::io::{self, IsTerminal, Read, Write};
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+use tokio::sync::mpsc;
+
+use crate::api::RuleInfo;
+use crate::style::{HIGHLIGHT, RESET};
+use crate::table;
+
+/// 규칙별 직전 표본 (packets, bytes)
+#[derive(Debug, Default)]
+pub struct WatchState {
+    previous: HashMap<String, (u64, u64)>,
+}
+
+impl WatchState {
+    /// 새 표본 반영: 직전 표본과 비교해 규칙별 초당 매치율(pps, bps)을 채우고 패킷 카운터가
+    /// 바뀐 규칙을 표시한다 (반환 값은 `rules`와 같은 순서)
+    ///
+    /// 처음 보는 규칙은 비교할 표본이 없어 매치율 없이 바뀌지 않은 것으로 본다. 카운터가
+    /// 줄었으면(규칙을 다시 만듦) 바뀐 것으로 보되 매치율은 0으로 둔다.
+    pub fn update(&mut self, rules: &mut [RuleInfo], elapsed: Duration) -> Vec<bool> {
+        let secs = elapsed.as_secs_f64();
+        let mut current = HashMap::with_capacity(rules.len());
+
+        let changed = rules.iter_mut().map(|rule| {
+            let key = swift_guard::utils::qualify_label(&rule.namespace, &rule.label);
+            let sample = (rule.stats.packets, rule.stats.bytes);
+            let previous = self.previous.get(&key).copied();
+            (rule.stats.pps, rule.stats.bps) = match previous {
+                Some((packets, bytes)) if secs > 0.0 => {
+                    (Some(rate(sample.0, packets, secs)), Some(rate(sample.1, bytes, secs)))
+                },
+                _ => (None, None),
+            };
+            current.insert(key, sample);
+            matches!(previous, Some((packets, _)) if packets != sample.0)
+        }).collect();
+
+        self.previous = current;
+        changed
+    }
+}
+
+/// 두 표본 사이 초당 증가량 (카운터가 줄었으면 0)
+fn rate(now: u64, before: u64, secs: f64) -> u64 {
+    (now.saturating_sub(before) as f64 / secs).round() as u64
+}
+
+/// 한 화면 구성 (제목 줄, 규칙 테이블)
+///
+/// 행 앞의 `*`는 직전 갱신 이후 패킷 카운터가 바뀐 규칙이며, color면 행 전체를 강조한다.
+pub fn render_frame(title: &str, rules: &[RuleInfo], changed: &[bool], wide: bool, color: bool) -> Vec<String> {
+    let mut lines = vec![title.to_string(), String::new()];
+    if rules.is_empty() {
+        lines.push("No rules found".to_string());
+        return lines;
+    }
+
+    // 테이블 첫 두 줄은 머리글과 구분선
+    for (index, line) in table::rules_table(rules, true, wide).with_color(color).render().into_iter().enumerate() {
+        let hit = matches!(index.checked_sub(2).and_then(|row| changed.get(row)), Some(true));
+        lines.push(match (hit, color) {
+            // 셀 색상이 끝나면 행 강조를 다시 켬
+            (true, true) => format!("{}* {}{}", HIGHLIGHT, line.replace(RESET, &format!("{}{}", RESET, HIGHLIGHT)), RESET),
+            (true, false) => format!("* {}", line),
+            (false, _) => format!("  {}", line),
+        });
+    }
+
+    let hits = changed.iter().filter(|hit| **hit).count();
+    lines.push(String::new());
+    lines.push(format!("{} of {} rules matched since the last refresh", hits, rules.len()));
+    lines
+}
+
+/// 종료 키 (q, 입력 신호가 꺼진 터미널의 Ctrl+C)
+pub fn is_quit_key(key: u8) -> bool {
+    matches!(key, b'q' | b'Q' | 0x03)
+}
+
+/// 감시 화면 터미널 (drop 시 원래 화면과 입력 설정 복원)
+pub struct Terminal {
+    /// 입력 터미널의 이전 stty 설정 (입력이 터미널이 아니면 None)
+    saved: Option<String>,
+}
+
+impl Terminal {
+    /// 대체 화면으로 전환하고 커서를 숨긴다. 입력이 터미널이면 Enter 없이 키를 읽도록
+    /// 정규 모드와 에코를 끈다 (Ctrl+C 신호는 그대로 둠).
+    pub fn enter() -> Self {
+        let saved = if io::stdin().is_terminal() { stty(&["-g"]) } else { None };
+        if saved.is_some() {
+            stty(&["-icanon", "-echo", "min", "1"]);
+        }
+        print!("\x1b[?1049h\x1b[?25l");
+        let _ = io::stdout().flush();
+
+        Self { saved }
+    }
+
+    /// 화면을 지우고 다시 그림
+    pub fn draw(&self, lines: &[String]) -> io::Result<()> {
+        let mut out = io::stdout().lock();
+        write!(out, "\x1b[H\x1b[2J")?;
+        for line in lines {
+            writeln!(out, "{}", line)?;
+        }
+        out.flush()
+    }
+}
+
+impl Drop for Terminal {
+    fn drop(&mut self) {
+        print!("\x1b[?25h\x1b[?1049l");
+        let _ = io::stdout().flush();
+        if let Some(saved) = &self.saved {
+            stty(&[saved]);
+        }
+    }
+}
+
+/// 입력 터미널에 stty 실행 (성공하면 출력)
+fn stty(args: &[&str]) -> Option<String> {
+    let output = Command::new("stty")
+        .args(args)
+        .stdin(Stdio::inherit())
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    output.status.success().then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// 키 입력 수신 시작 (입력이 터미널이 아니면 None)
+///
+/// 블로킹 읽기는 별도 스레드에서 하며, 프로세스가 끝나면 함께 끝난다.
+pub fn spawn_key_reader() -> Option<mpsc::UnboundedReceiver<u8>> {
+    if !io::stdin().is_terminal() {
+        return None;
+    }
+
+    let (sender, receiver) = mpsc::unbounded_channel();
+    std::thread::spawn(move || {
+        let mut byte = [0u8; 1];
+        while let Ok(1) = io::stdin().read(&mut byte) {
+            if sender.send(byte[0]).is_err() {
+                break;
+            }
+        }
+    });
+    Some(receiver)
+}
+
+/// 종료 키를 누를 때까지 대기 (입력이 없거나 닫히면 끝나지 않음)
+pub async fn quit_pressed(keys: &mut Option<mpsc::UnboundedReceiver<u8>>) {
+    while let Some(receiver) = keys {
+        match receiver.recv().await {
+            Some(key) if is_quit_key(key) => return,
+            Some(_) => {},
+            None => *keys = None,
+        }
+    }
+    std::future::pending().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::RuleStats;
+
+    fn rule(label: &str, packets: u64, bytes: u64) -> RuleInfo {
+        RuleInfo {
+            label: label.to_string(),
+            protocol: "tcp".to_string(),
+            stats: RuleStats { packets, bytes, ..RuleStats::default() },
+            ..RuleInfo::default()
+        }
+    }
+
+    #[test]
+    fn test_update_tracks_changes() {
+        let mut state = WatchState::default();
+
+        // 첫 표본은 비교 대상이 없음
+        let mut first = vec![rule("a", 100, 6400), rule("b", 5, 320)];
+        assert_eq!(state.update(&mut first, Duration::ZERO), vec![false, false]);
+        assert!(first.iter().all(|rule| rule.stats.pps.is_none() && rule.stats.bps.is_none()));
+
+        // 2초 뒤: a만 증가, c는 새 규칙
+        let mut second = vec![rule("a", 120, 7680), rule("b", 5, 320), rule("c", 9, 576)];
+        assert_eq!(state.update(&mut second, Duration::from_secs(2)), vec![true, false, false]);
+        assert_eq!((second[0].stats.pps, second[0].stats.bps), (Some(10), Some(640)));
+        assert_eq!(second[1].stats.pps, Some(0));
+        assert_eq!(second[2].stats.pps, None);
+
+        // 다시 만든 규칙은 카운터가 줄어도 음수 매치율 없이 바뀐 것으로 표시, 사라진 규칙은 잊음
+        let mut third = vec![rule("a", 3, 192), rule("c", 9, 576)];
+        assert_eq!(state.update(&mut third, Duration::from_secs(1)), vec![true, false]);
+        assert_eq!(third[0].stats.pps, Some(0));
+        let mut fourth = vec![rule("b", 7, 448)];
+        assert_eq!(state.update(&mut fourth, Duration::from_secs(1)), vec![false]);
+        assert_eq!(fourth[0].stats.pps, None);
+
+        // 같은 레이블도 네임스페이스가 다르면 다른 규칙
+        let mut other = rule("b", 50, 3200);
+        other.namespace = "team-a".to_string();
+        let mut fifth = vec![rule("b", 7, 448), other];
+        assert_eq!(state.update(&mut fifth, Duration::from_secs(1)), vec![false, false]);
+        assert_eq!(fifth[1].stats.pps, None);
+    }
+
+    #[test]
+    fn test_render_frame() {
+        let mut state = WatchState::default();
+        state.update(&mut [rule("a", 100, 6400), rule("b", 5, 320)], Duration::ZERO);
+        let mut rules = vec![rule("a", 120, 7680), rule("b", 5, 320)];
+        let changed = state.update(&mut rules, Duration::from_secs(2));
+
+        let lines = render_frame("Every 2s", &rules, &changed, false, false);
+        assert_eq!(lines[0], "Every 2s");
+        assert!(lines[2].starts_with("  LABEL"), "{}", lines[2]);
+        assert!(lines[2].ends_with("PPS  BPS"), "{}", lines[2]);
+        assert!(lines[4].starts_with("* a "), "{}", lines[4]);
+        assert!(lines[4].contains(" 10 "), "{}", lines[4]);
+        assert!(lines[5].starts_with("  b "), "{}", lines[5]);
+        assert_eq!(lines.last().unwrap(), "1 of 2 rules matched since the last refresh");
+
+        let colored = render_frame("Every 2s", &rules, &changed, false, true);
+        assert!(colored[4].starts_with("\x1b[1;33m* a ") && colored[4].ends_with("\x1b[0m"), "{}", colored[4]);
+        // 액션 셀 색상 뒤에 행 강조가 이어짐
+        assert!(colored[4].contains("\x1b[31mdrop\x1b[0m\x1b[1;33m"), "{}", colored[4]);
+        assert!(colored[5].contains("\x1b[31mdrop\x1b[0m"), "{}", colored[5]);
+        assert_eq!(crate::style::strip(&colored[5]), lines[5]);
+
+        assert_eq!(render_frame("Every 2s", &[], &[], false, true)[2], "No rules found");
+        assert!(is_quit_key(b'q') && is_quit_key(0x03) && !is_quit_key(b'x'));
+    }
+}
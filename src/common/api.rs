@@ -2,6 +2,18 @@
 // CLI와 데몬 간의 통신을 위한 API 정의
 
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::build_info::BuildInfo;
+use crate::error::{ErrorCode, InvalidArgument};
+use crate::histogram::LatencySnapshot;
+use crate::layout::StructLayout;
+use crate::types::{ActionType, ProtocolType, TcpFlags};
+
+pub use crate::client::{
+    ClientError, EventBatch, EventSubscription, ReconnectPolicy, Reconnected, Reconnector, Subscription, SwiftGuardClient,
+    DEFAULT_RECONNECT_WINDOW, DEFAULT_REQUEST_TIMEOUT, EVENTS_BATCH, RULES_PAGE_SIZE, UNIX_ADDR_PREFIX,
+};
 
 /// API 요청
 #[derive(Debug, Serialize, Deserialize)]
@@ -11,6 +23,12 @@ pub enum ApiRequest {
         interface: String,
         mode: u32,
         force: bool,
+        /// XDP 연결 실패 시 대체 백엔드 (없으면 데몬 구성을 따름)
+        #[serde(default)]
+        fallback: Option<FallbackMode>,
+        /// 기본 오브젝트 대신 연결할 BPF 오브젝트 (데몬 구성의 object_dir 아래)
+        #[serde(default)]
+        obj_path: Option<String>,
     },
     
     /// XDP 프로그램 분리
@@ -18,23 +36,8 @@ pub enum ApiRequest {
         interface: String,
     },
     
-    /// 필터 규칙 추가
-    AddRule {
-        src_ip: Option<String>,
-        dst_ip: Option<String>,
-        src_port_min: u16,
-        src_port_max: u16,
-        dst_port_min: u16,
-        dst_port_max: u16,
-        protocol: u8,
-        tcp_flags: u8,
-        action: u8,
-        redirect_if: Option<String>,
-        priority: u32,
-        rate_limit: u32,
-        expire: u32,
-        label: String,
-    },
+    /// 필터 규칙 추가 (요청 전체 크기를 줄이도록 상자에 담음)
+    AddRule(Box<NewRule>),
     
     /// 필터 규칙 삭제
     DeleteRule {
@@ -42,10 +45,117 @@ pub enum ApiRequest {
     },
     
     /// 필터 규칙 목록 조회
+    ///
+    /// 규칙은 우선순위 내림차순, 같으면 생성 시각 오름차순, 그것도 같으면 추가한
+    /// 순서로 반환한다. `expiring_within_secs`를 지정하면 남은 수명 순으로 반환한다.
+    ///
+    /// `cursor`, `offset`, `limit` 중 하나라도 지정하면 페이지 단위로 나누며, 이때는
+    /// 규칙 키(`qualify_label`) 순서로 반환한다. 커서는 이전 페이지 마지막 규칙의 키이고
+    /// 그 키보다 뒤의 규칙부터 돌려주므로, 기준 규칙이 그 사이 삭제되어도 중복이나
+    /// 누락 없이 이어진다. 페이지를 받는 도중 추가된 규칙은 키가 커서보다 뒤일 때만 포함된다.
     ListRules {
         include_stats: bool,
+        /// 이 시간(초) 안에 만료되는 규칙만 남은 수명 순으로 조회
+        #[serde(default)]
+        expiring_within_secs: Option<u64>,
+        /// 텔레메트리가 계산한 규칙별 초당 매치율 포함 (`RuleStats`의 pps, bps)
+        #[serde(default)]
+        include_rates: bool,
+        /// 모든 네임스페이스의 규칙 조회 (관리자 토큰 필요)
+        #[serde(default)]
+        all_namespaces: bool,
+        /// 이 주소 체계의 트래픽에 적용되는 규칙만 조회 (주소 조건이 없는 `Any` 규칙 포함)
+        #[serde(default)]
+        family: Option<AddressFamily>,
+        /// WASM 모듈이 설치한 동적 차단 규칙도 포함 (기본은 제외)
+        #[serde(default)]
+        include_dynamic: bool,
+        /// 이 주체가 설치한 규칙만 조회 (`wasm`은 모든 모듈, `wasm:<모듈>`은 그 모듈, 동적 차단 규칙 포함)
+        #[serde(default)]
+        origin: Option<String>,
+        /// 이전 응답의 `next_cursor` (이 규칙 키보다 뒤의 규칙부터 조회)
+        #[serde(default)]
+        cursor: Option<String>,
+        /// 커서 뒤에서 건너뛸 규칙 수
+        #[serde(default)]
+        offset: u32,
+        /// 한 번에 돌려줄 최대 규칙 수 (0이면 제한 없음)
+        #[serde(default)]
+        limit: u32,
+    },
+    
+    /// 규칙 변경 구독 (`monitor --rules`)
+    ///
+    /// 먼저 현재 규칙 전체를 `seq`가 있는 `Rules` 프레임으로 보내고, 이후 규칙이 추가,
+    /// 갱신, 삭제될 때마다 `RuleChanged` 프레임을 보낸다. 연결을 끊을 때까지 끝나지 않는다.
+    ///
+    /// 순서 보장: 데몬은 규칙 캐시가 바뀔 때마다 순번을 1씩 늘리며, 스냅샷의 `seq`는
+    /// 스냅샷에 반영된 마지막 순번이다. 스냅샷 뒤에는 그보다 큰 순번의 변경만 순번
+    /// 순서대로 빠짐없이 온다 (다른 네임스페이스의 변경은 보내지 않으므로 순번이 건너뛸
+    /// 수 있다). 구독자가 밀려 변경을 놓치면 데몬이 새 스냅샷을 보내므로, 스냅샷마다
+    /// 상태를 통째로 바꾸고 이후 변경을 차례로 적용하면 데몬의 규칙과 같은 상태가 된다
+    /// (`rulemirror::RuleMirror`). 규칙 정보에 통계는 없다.
+    WatchRules {
+        /// 모든 네임스페이스의 규칙 구독 (관리자 토큰 필요)
+        #[serde(default)]
+        all_namespaces: bool,
+    },
+    
+    /// 단일 필터 규칙 상세 조회
+    GetRule {
+        label: String,
+    },
+    
+    /// count 액션 규칙의 카운터 조회 (이름 순)
+    ///
+    /// `counter_group`이 같은 규칙은 하나의 카운터로 합산하고, 그룹이 없는 규칙은 레이블이
+    /// 카운터 이름이 된다. `prefix`가 있으면 이름이 그 문자열로 시작하는 카운터만 돌려준다.
+    GetCounters {
+        #[serde(default)]
+        prefix: Option<String>,
+    },
+    
+    /// 선언형 규칙 집합 적용 (레이블 기준으로 현재 규칙과 비교해 조정)
+    ApplyRuleset {
+        rules: Vec<RuleSpec>,
+        /// 문서에 없는 규칙 삭제 (자동 완화 규칙 제외)
+        prune: bool,
+        /// 적용하지 않고 변경 내용만 계산
+        #[serde(default)]
+        dry_run: bool,
+        /// 맵 쓰기 청크마다 `Progress` 응답 프레임을 먼저 보냄 (마지막 프레임이 결과)
+        #[serde(default)]
+        progress: bool,
     },
     
+    /// 규칙 집합을 적용하면 어떻게 될지 데몬의 맵을 복제해 시뮬레이션 (맵은 바꾸지 않음)
+    ///
+    /// 데몬이 규칙을 정규화하고 맵 용량, 레이블, 리디렉션 대상까지 실제 적용과 같은 검사를
+    /// 거친 결과를 `RulesetApplied`(`dry_run`, `capacity` 포함)로 돌려준다.
+    PlanChanges {
+        rules: Vec<RuleSpec>,
+        /// 문서에 없는 규칙 삭제 (자동 완화 규칙 제외)
+        prune: bool,
+    },
+    
+    /// 프리셋을 규칙 묶음으로 펼쳐 적용 (다시 적용하면 바뀐 규칙만 갱신)
+    ///
+    /// 규칙 레이블은 `<프리셋>.<규칙>`이며 모두 프리셋 이름으로 표시된다. `params`의
+    /// 값은 문자열이고 프리셋마다 정해진 매개변수만 받는다.
+    ApplyPreset {
+        name: String,
+        #[serde(default)]
+        params: BTreeMap<String, String>,
+    },
+    
+    /// 프리셋이 설치한 규칙 모두 삭제
+    RemovePreset {
+        name: String,
+    },
+    
+    /// 프리셋 목록과 적용된 규칙 조회
+    ListPresets {},
+    
     /// 통계 조회
     GetStats {},
     
@@ -53,6 +163,15 @@ pub enum ApiRequest {
     LoadWasmModule {
         name: String,
         file_path: String,
+        /// 검사 오류 정책 (없으면 데몬 구성의 `wasm.on_error`)
+        #[serde(default)]
+        on_error: Option<WasmErrorPolicy>,
+        /// 모듈 설정 (모듈이 `init`에서 `config_len`, `read_config`로 읽음)
+        #[serde(default)]
+        config: Option<String>,
+        /// 판정 적용 방식 (shadow면 검사만 하고 차단하지 않음)
+        #[serde(default)]
+        mode: WasmModuleMode,
     },
     
     /// WASM 모듈 언로드
@@ -60,6 +179,17 @@ pub enum ApiRequest {
         name: String,
     },
     
+    /// 오류로 비활성화된 WASM 모듈 재개
+    ResumeWasmModule {
+        name: String,
+    },
+    
+    /// WASM 모듈 판정 적용 방식 변경 (통계는 유지)
+    SetWasmModuleState {
+        name: String,
+        mode: WasmModuleMode,
+    },
+    
     /// WASM 모듈 목록 조회
     ListWasmModules {},
     
@@ -67,24 +197,448 @@ pub enum ApiRequest {
     WasmModuleStats {
         name: String,
     },
+    
+    /// WASM 모듈 통계 초기화 (카운터와 검사 시간 분포)
+    ResetWasmModuleStats {
+        name: String,
+    },
+    
+    /// WASM 모듈 로그 조회 (최근 `lines`줄, 0이면 보관된 전체)
+    GetWasmModuleLog {
+        name: String,
+        lines: usize,
+        /// 이 순번 이후의 줄만 (follow 폴링용)
+        #[serde(default)]
+        after: Option<u64>,
+    },
+    
+    /// 컴파일된 WASM 모듈 캐시 삭제
+    ClearWasmCache {},
+    
+    /// WASM 모듈이 설치한 동적 차단 규칙 조회 (`module`이 없으면 모든 모듈)
+    ListDynamicBlocks {
+        #[serde(default)]
+        module: Option<String>,
+    },
+    
+    /// WASM 모듈이 설치한 동적 차단 규칙 삭제 (`module`이 없으면 모든 모듈)
+    ///
+    /// 삭제하면 모듈별 동적 차단 한도도 그만큼 비므로 모듈이 다시 차단할 수 있다.
+    ClearDynamicBlocks {
+        #[serde(default)]
+        module: Option<String>,
+    },
+    
+    /// 추적 중인 연결 목록 조회
+    ListConnections {
+        limit: u32,
+        filter: ConnectionFilter,
+    },
+    
+    /// 패킷 캡처 파일 목록 조회
+    ListCaptures {},
+    
+    /// 연결된 인터페이스 목록 조회
+    ListInterfaces {},
+    
+    /// 데몬 상태 및 커널 기능 점검 결과 조회
+    Ping {},
+    
+    /// 데몬 빌드 정보 조회
+    GetVersion {},
+    
+    /// 로드된 BPF 프로그램과 맵 정보 조회
+    GetBpfInfo {},
+    
+    /// 소스 프리픽스가 겹치는 규칙 쌍 조회
+    ExplainConflicts {},
+    
+    /// 설치에 실패했거나 일부만 설치된 규칙 재설치
+    RepairRules {},
+    
+    /// 커널 규칙 맵과 규칙 캐시 비교 (고아 항목과 누락 항목 보고)
+    AuditMaps {},
+    
+    /// LPM 키 공간 통계 조회 (프리픽스 길이 분포, 겹침, 맵 사용률)
+    RuleSpaceStats {},
+    
+    /// 리디렉션 대상(redirect_map 항목)과 참조하는 규칙 조회
+    ListRedirectTargets {},
+    
+    /// 규칙 없이 리디렉션 대상 미리 등록 (해당 인터페이스가 동작 중이어야 함)
+    AddRedirectTarget {
+        interface: String,
+    },
+    
+    /// 직접 등록한 리디렉션 대상 제거 (규칙이 참조하는 동안은 거부)
+    ///
+    /// `interface`는 인터페이스 이름 또는 맵 슬롯 번호다.
+    RemoveRedirectTarget {
+        interface: String,
+    },
+    
+    /// 커널 규칙 맵 감사 후 불일치 수정 (두 옵션이 모두 꺼져 있으면 감사만 함)
+    ReconcileMaps {
+        /// 캐시에 없는 커널 항목 삭제
+        delete_orphans: bool,
+        /// 커널에서 사라진 캐시 규칙 항목 다시 기록
+        reinstall_missing: bool,
+    },
+    
+    /// 이벤트 기록 조회 (재연결한 구독자의 따라잡기용)
+    ///
+    /// `since_seq`가 있으면 그 순번 이후 이벤트를 오래된 것부터, 없으면 최근
+    /// `limit`개를 돌려준다. `kinds`가 비어 있으면 모든 종류를 돌려준다.
+    GetEvents {
+        #[serde(default)]
+        since_seq: Option<u64>,
+        limit: usize,
+        #[serde(default)]
+        kinds: Vec<String>,
+    },
+    
+    /// IP 주소의 GeoIP 정보 조회 (국가 코드, AS)
+    GeoLookup {
+        ip: String,
+    },
+    
+    /// 국가의 프리픽스 집합을 소스로 하는 규칙 설치 (이미 있으면 액션과 집합 갱신)
+    ///
+    /// 프리픽스는 GeoIP 국가 데이터베이스에서, `csv_path`가 있으면 데몬이 읽는
+    /// `<CIDR>,<국가 코드>` 형식 파일에서 얻는다. 규칙 레이블은 `country-<코드>`다.
+    BlockCountry {
+        code: String,
+        action: u8,
+        #[serde(default)]
+        expire: u32,
+        #[serde(default)]
+        csv_path: Option<String>,
+    },
+    
+    /// 국가 차단 규칙의 프리픽스 집합을 원본에서 다시 만듦 (`code`가 없으면 모두)
+    RefreshCountryBlocks {
+        #[serde(default)]
+        code: Option<String>,
+    },
+    
+    /// 국가 차단 규칙과 프리픽스 집합 삭제
+    UnblockCountry {
+        code: String,
+    },
+    
+    /// 인터페이스 데이터 경로 자가 진단
+    ///
+    /// 데몬이 표식 5-튜플에만 맞는 임시 count 규칙을 설치하고, 그 튜플의 UDP 패킷을
+    /// raw 소켓으로 인터페이스에 보낸 뒤 규칙 카운터를 읽고 규칙을 지운다.
+    SelfTest {
+        interface: String,
+    },
+    
+    /// 처리량 벤치마크
+    ///
+    /// 데몬이 veth 쌍을 만들거나(`interface`, `peer`가 없을 때) 지정한 인터페이스 쌍을 쓰고,
+    /// 합성 규칙을 설치한 뒤 모드마다 XDP를 연결해 `peer`에서 `interface`로 트래픽을 보내
+    /// 처리 pps, 차단율, CPU 사용률을 잰다. 끝나면 만든 것을 모두 지운다. 호스트를 바꾸므로
+    /// `confirm`이 true여야 실행하며, 측정하는 동안 초마다 `Progress` 프레임을 보낸다.
+    RunBenchmark {
+        /// XDP를 연결해 측정할 인터페이스
+        #[serde(default)]
+        interface: Option<String>,
+        /// 트래픽을 보낼 인터페이스 (`interface`와 연결된 링크)
+        #[serde(default)]
+        peer: Option<String>,
+        /// 모드별 전송 시간 (초)
+        duration: u32,
+        /// 프레임 크기 (바이트, FCS 제외)
+        pkt_size: u32,
+        /// 합성 규칙 수
+        rules: u32,
+        /// 측정할 XDP 모드 이름 (비어 있으면 driver, generic)
+        #[serde(default)]
+        modes: Vec<String>,
+        /// 호스트 변경에 동의
+        #[serde(default)]
+        confirm: bool,
+    },
+}
+
+impl ApiRequest {
+    /// 요청 종류 이름 (메트릭 레이블)
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Attach { .. } => "attach",
+            Self::Detach { .. } => "detach",
+            Self::AddRule(_) => "add_rule",
+            Self::DeleteRule { .. } => "delete_rule",
+            Self::ListRules { .. } => "list_rules",
+            Self::WatchRules { .. } => "watch_rules",
+            Self::GetRule { .. } => "get_rule",
+            Self::GetCounters { .. } => "get_counters",
+            Self::ApplyRuleset { .. } => "apply_ruleset",
+            Self::PlanChanges { .. } => "plan_changes",
+            Self::ApplyPreset { .. } => "apply_preset",
+            Self::RemovePreset { .. } => "remove_preset",
+            Self::ListPresets {} => "list_presets",
+            Self::GetStats {} => "get_stats",
+            Self::LoadWasmModule { .. } => "load_wasm_module",
+            Self::UnloadWasmModule { .. } => "unload_wasm_module",
+            Self::ResumeWasmModule { .. } => "resume_wasm_module",
+            Self::SetWasmModuleState { .. } => "set_wasm_module_state",
+            Self::ListWasmModules {} => "list_wasm_modules",
+            Self::WasmModuleStats { .. } => "wasm_module_stats",
+            Self::ResetWasmModuleStats { .. } => "reset_wasm_module_stats",
+            Self::GetWasmModuleLog { .. } => "get_wasm_module_log",
+            Self::ClearWasmCache {} => "clear_wasm_cache",
+            Self::ListDynamicBlocks { .. } => "list_dynamic_blocks",
+            Self::ClearDynamicBlocks { .. } => "clear_dynamic_blocks",
+            Self::ListConnections { .. } => "list_connections",
+            Self::ListCaptures {} => "list_captures",
+            Self::ListInterfaces {} => "list_interfaces",
+            Self::Ping {} => "ping",
+            Self::GetVersion {} => "get_version",
+            Self::GetBpfInfo {} => "get_bpf_info",
+            Self::ExplainConflicts {} => "explain_conflicts",
+            Self::RepairRules {} => "repair_rules",
+            Self::AuditMaps {} => "audit_maps",
+            Self::RuleSpaceStats {} => "rule_space_stats",
+            Self::ReconcileMaps { .. } => "reconcile_maps",
+            Self::ListRedirectTargets {} => "list_redirect_targets",
+            Self::AddRedirectTarget { .. } => "add_redirect_target",
+            Self::RemoveRedirectTarget { .. } => "remove_redirect_target",
+            Self::GetEvents { .. } => "get_events",
+            Self::GeoLookup { .. } => "geo_lookup",
+            Self::BlockCountry { .. } => "block_country",
+            Self::RefreshCountryBlocks { .. } => "refresh_country_blocks",
+            Self::UnblockCountry { .. } => "unblock_country",
+            Self::SelfTest { .. } => "self_test",
+            Self::RunBenchmark { .. } => "run_benchmark",
+        }
+    }
+
+    /// 레이블로 규칙을 다루는 요청 (프레임의 네임스페이스 안에서 처리)
+    pub fn is_rule_scoped(&self) -> bool {
+        matches!(self,
+            Self::AddRule(_)
+            | Self::DeleteRule { .. }
+            | Self::ListRules { .. }
+            | Self::WatchRules { .. }
+            | Self::GetRule { .. }
+            | Self::GetCounters { .. }
+            | Self::ApplyRuleset { .. }
+            | Self::PlanChanges { .. }
+            | Self::ApplyPreset { .. }
+            | Self::RemovePreset { .. }
+            | Self::ListPresets {}
+            | Self::BlockCountry { .. }
+            | Self::RefreshCountryBlocks { .. }
+            | Self::UnblockCountry { .. })
+    }
+
+    /// 데몬 상태를 바꾸는 요청 (읽기 전용 토큰은 거부)
+    ///
+    /// 새 요청을 추가할 때 분류를 빠뜨리지 않도록 와일드카드 없이 모든 요청을 나열한다.
+    /// 드라이런 규칙 집합 적용은 변경 내용만 계산하므로 조회로 본다.
+    pub fn is_mutation(&self) -> bool {
+        match self {
+            Self::Attach { .. }
+            | Self::Detach { .. }
+            | Self::AddRule(_)
+            | Self::DeleteRule { .. }
+            | Self::LoadWasmModule { .. }
+            | Self::UnloadWasmModule { .. }
+            | Self::ResumeWasmModule { .. }
+            | Self::SetWasmModuleState { .. }
+            | Self::ResetWasmModuleStats { .. }
+            | Self::ClearWasmCache {}
+            | Self::ClearDynamicBlocks { .. }
+            | Self::ApplyPreset { .. }
+            | Self::RemovePreset { .. }
+            | Self::BlockCountry { .. }
+            | Self::RefreshCountryBlocks { .. }
+            | Self::UnblockCountry { .. }
+            | Self::RepairRules {}
+            | Self::AddRedirectTarget { .. }
+            | Self::RemoveRedirectTarget { .. }
+            | Self::SelfTest { .. }
+            | Self::RunBenchmark { .. } => true,
+            Self::ApplyRuleset { dry_run, .. } => !dry_run,
+            Self::ReconcileMaps { delete_orphans, reinstall_missing } => *delete_orphans || *reinstall_missing,
+            Self::ListRules { .. }
+            | Self::WatchRules { .. }
+            | Self::GetRule { .. }
+            | Self::GetCounters { .. }
+            | Self::PlanChanges { .. }
+            | Self::ListPresets {}
+            | Self::GetStats {}
+            | Self::ListWasmModules {}
+            | Self::WasmModuleStats { .. }
+            | Self::GetWasmModuleLog { .. }
+            | Self::ListDynamicBlocks { .. }
+            | Self::ListConnections { .. }
+            | Self::ListCaptures {}
+            | Self::ListInterfaces {}
+            | Self::Ping {}
+            | Self::GetVersion {}
+            | Self::GetBpfInfo {}
+            | Self::ExplainConflicts {}
+            | Self::AuditMaps {}
+            | Self::RuleSpaceStats {}
+            | Self::ListRedirectTargets {}
+            | Self::GetEvents { .. }
+            | Self::GeoLookup { .. } => false,
+        }
+    }
+}
+
+/// 추가할 필터 규칙 (`ApiRequest::AddRule`)
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NewRule {
+    pub src_ip: Option<String>,
+    pub dst_ip: Option<String>,
+    pub src_port_min: u16,
+    pub src_port_max: u16,
+    pub dst_port_min: u16,
+    pub dst_port_max: u16,
+    pub protocol: u8,
+    pub tcp_flags: u8,
+    pub action: u8,
+    pub redirect_if: Option<String>,
+    #[serde(default)]
+    pub redirect_cpu: Option<u32>,
+    pub priority: u32,
+    pub rate_limit: u32,
+    pub expire: u32,
+    pub label: String,
+    /// 규칙 설명
+    #[serde(default)]
+    pub description: Option<String>,
+    /// 규칙을 추가한 운영자 (없으면 데몬이 채움)
+    #[serde(default)]
+    pub created_by: Option<String>,
+    /// 할당량 기간 동안 허용하는 바이트 수
+    #[serde(default)]
+    pub quota_bytes: Option<u64>,
+    /// 할당량 기간 동안 허용하는 패킷 수
+    #[serde(default)]
+    pub quota_packets: Option<u64>,
+    /// 할당량을 넘으면 적용할 액션 (생략 시 drop)
+    #[serde(default)]
+    pub quota_action: Option<u8>,
+    /// 소스 호스트 이름 (데몬이 A 레코드로 해석, src_ip와 함께 쓸 수 없음)
+    #[serde(default)]
+    pub src_host: Option<String>,
+    /// 목적지 호스트 이름 (XDP 프로그램이 목적지를 비교하지 않으므로 거부됨)
+    #[serde(default)]
+    pub dst_host: Option<String>,
+    /// 카운터 그룹 (count 액션 규칙만, `GetCounters`에서 같은 그룹 규칙의 통계를 합산)
+    #[serde(default)]
+    pub counter_group: Option<String>,
+    /// 소스 포트 목록 (최대 `layout::MAX_RULE_PORTS`개, 소스 포트 범위와 함께 쓸 수 없음)
+    #[serde(default)]
+    pub src_port_list: Vec<u16>,
+    /// 목적지 포트 목록 (최대 `layout::MAX_RULE_PORTS`개, 목적지 포트 범위와 함께 쓸 수 없음)
+    #[serde(default)]
+    pub dst_port_list: Vec<u16>,
+    /// 규칙이 만료되기 전과 만료될 때 알림을 받을 웹훅 URL (만료가 있는 규칙만)
+    #[serde(default)]
+    pub owner_webhook: Option<String>,
+    /// 만료 예고 알림을 보낼 남은 시간 (초, 생략하면 데몬 구성의 `expiry.notify_before`)
+    #[serde(default)]
+    pub notify_before: Option<u32>,
+    /// 로드된 XDP 프로그램이 처리하지 못하는 매치 필드가 있어도 추가 (그 필드는 무시되고 경고로 알림)
+    #[serde(default)]
+    pub force: bool,
 }
 
+/// 요청 프레임 (인증 토큰, 규칙 네임스페이스와 요청)
+///
+/// 요청을 펼쳐서 직렬화하므로 토큰, 네임스페이스, 요청 ID가 없는 프레임은 `ApiRequest`만
+/// 보내던 이전 형식과 같다.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RequestFrame {
+    /// API 토큰 (데몬 구성의 `api.tokens`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+    /// 규칙 요청의 네임스페이스 (없으면 토큰의 네임스페이스, 인증이 없으면 "default")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub namespace: Option<String>,
+    /// 로그와 감사 기록에서 요청을 찾기 위한 ID (없으면 데몬이 생성, 응답에 돌려주지 않음)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+    /// 클라이언트가 받을 수 있는 응답 프레임 최대 크기 (바이트, 없으면 데몬 구성 값)
+    ///
+    /// 데몬은 이 값과 `api.max_response_size` 중 작은 값을 넘는 목록 응답을 `PartialResults`로 줄인다.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_response_len: Option<usize>,
+    #[serde(flatten)]
+    pub request: ApiRequest,
+}
+
+/// 응답 프레임 기본 최대 크기 (바이트)
+pub const DEFAULT_MAX_RESPONSE_LEN: usize = 16 * 1024 * 1024;
+
+/// 응답이 잘렸을 때 `PartialResults`의 조치 안내 (클라이언트는 이 값을 보고 나눠 다시 요청)
+pub const PAGINATION_HINT: &str = "use pagination";
+
 /// API 응답
 #[derive(Debug, Serialize, Deserialize)]
 pub enum ApiResponse {
     /// 성공
     Success {
         message: String,
+        /// 요청은 성공했지만 운영자가 알아야 할 상태 (예: 연결된 인터페이스 없음)
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        warning: Option<String>,
     },
     
     /// 오류
     Error {
         message: String,
+        #[serde(default)]
+        code: ErrorCode,
+    },
+    
+    /// 잘못된 인자 (필드 단위 검증 실패)
+    InvalidArgument {
+        field: String,
+        message: String,
+    },
+    
+    /// 대상 없음
+    NotFound {
+        resource: String,
+        name: String,
+    },
+    
+    /// 요청 빈도 제한 초과
+    RateLimited {
+        /// 다음 요청이 허용될 때까지 남은 시간 (밀리초)
+        retry_after_ms: u64,
+        /// 피어별 초당 허용 요청 수
+        limit: u32,
     },
     
     /// 규칙 목록
     Rules {
         rules: Vec<RuleInfo>,
+        /// 페이지로 나누기 전 조건에 맞는 규칙 수
+        #[serde(default)]
+        total_count: usize,
+        /// 다음 페이지 요청의 커서 (마지막 페이지면 None)
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        next_cursor: Option<String>,
+        /// 규칙 변경 구독의 스냅샷이면 스냅샷에 반영된 마지막 변경 순번
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        seq: Option<u64>,
+    },
+    
+    /// 규칙 변경 (규칙 변경 구독에서 스냅샷 뒤에 오는 프레임)
+    RuleChanged {
+        /// 변경 순번 (데몬 안에서 규칙 변경마다 1씩 증가)
+        seq: u64,
+        kind: RuleChangeKind,
+        /// 변경 후 규칙 (삭제면 삭제 직전 규칙, 통계 없음)
+        rule: RuleInfo,
     },
     
     /// 통계
@@ -92,32 +646,258 @@ pub enum ApiResponse {
         stats: SystemStats,
     },
     
+    /// 규칙 상세 정보
+    RuleDetail {
+        rule: RuleDetail,
+    },
+    
+    /// count 액션 규칙의 카운터 (이름 순)
+    Counters {
+        counters: Vec<CounterInfo>,
+    },
+    
+    /// 규칙 집합 적용 결과
+    RulesetApplied {
+        summary: ApplySummary,
+    },
+    
+    /// 프리셋 목록
+    Presets {
+        presets: Vec<PresetInfo>,
+    },
+    
+    /// 국가 차단 규칙 설치/갱신 결과
+    CountryBlocks {
+        blocks: Vec<CountryBlockInfo>,
+    },
+    
+    /// 자가 진단 결과
+    SelfTest {
+        report: SelfTestReport,
+    },
+    
+    /// 처리량 벤치마크 결과
+    Benchmark {
+        report: BenchReport,
+    },
+    
+    /// 대량 맵 쓰기 진행 상황 (최종 응답 전에 오는 중간 프레임, 변경 수 기준)
+    Progress {
+        done: usize,
+        total: usize,
+    },
+    
     /// WASM 모듈 목록
     WasmModules {
         modules: Vec<WasmModuleInfo>,
     },
     
+    /// WASM 모듈이 설치한 동적 차단 규칙 (남은 수명 순)
+    DynamicBlocks {
+        blocks: Vec<DynamicBlockInfo>,
+    },
+    
+    /// WASM 모듈 로그 (오래된 줄부터)
+    WasmModuleLog {
+        name: String,
+        lines: Vec<WasmLogLine>,
+    },
+    
     /// WASM 모듈 통계
     WasmModuleStats {
         name: String,
         processed_packets: u64,
         blocked_packets: u64,
         avg_processing_time_us: f64,
+        /// 모듈 상태
+        #[serde(default)]
+        state: Option<String>,
+        /// 검사 오류 수
+        #[serde(default)]
+        errors: u64,
+        /// 마지막 검사 오류
+        #[serde(default)]
+        last_error: Option<String>,
+        /// 패킷별 검사 시간 분포
+        #[serde(default)]
+        latency: LatencySnapshot,
+        /// 검사 시간 백분위수 추정 (마이크로초, 검사한 패킷이 없으면 없음)
+        #[serde(default)]
+        p50_us: Option<f64>,
+        #[serde(default)]
+        p95_us: Option<f64>,
+        #[serde(default)]
+        p99_us: Option<f64>,
+        /// 판정 적용 방식
+        #[serde(default)]
+        mode: WasmModuleMode,
+        /// shadow 모드에서 차단 판정을 내렸지만 통과시킨 패킷 수
+        #[serde(default)]
+        would_block_packets: u64,
+    },
+    
+    /// 연결 목록
+    Connections {
+        connections: Vec<ConnectionInfo>,
+        total: u64,
+    },
+    
+    /// GeoIP 조회 결과 (데이터베이스에 없는 필드는 비어 있음)
+    Geo {
+        ip: String,
+        geo: GeoInfo,
+    },
+    
+    /// 패킷 캡처 파일 목록
+    Captures {
+        captures: Vec<CaptureInfo>,
+    },
+    
+    /// 인터페이스 목록
+    Interfaces {
+        interfaces: Vec<InterfaceInfo>,
+    },
+    
+    /// Ping 응답
+    Pong {
+        version: String,
+        /// 시작 시 수행한 커널 기능 점검 결과
+        capabilities: Vec<CapabilityCheck>,
+        /// API 서버 요청 통계 요약
+        #[serde(default)]
+        api: ApiServerStats,
+        /// 웹훅 전송 통계
+        #[serde(default)]
+        webhooks: WebhookStats,
+        /// 데몬 빌드 정보 (이전 데몬은 보내지 않음)
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        build: Option<BuildInfo>,
+        /// 마지막 BPF 오브젝트 로드 실패 (없거나 이전 데몬이면 None)
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        load_failure: Option<BpfLoadFailure>,
+        /// 요청자 자격 증명 (Unix 소켓 연결만, TCP와 이전 데몬은 None)
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        peer: Option<PeerIdentity>,
+        /// 규칙을 설치하는 XDP 프로그램의 매치 기능 비트맵 (`types::XdpFeatures`, 모르거나 이전 데몬이면 None)
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        features: Option<u64>,
+    },
+    
+    /// 데몬 빌드 정보
+    Version {
+        build: BuildInfo,
+    },
+    
+    /// BPF 프로그램과 맵 정보
+    BpfInfo {
+        programs: Vec<BpfProgramInfo>,
+        maps: Vec<BpfMapInfo>,
+        /// 로드 시 BTF로 검증한 맵 값 레이아웃
+        #[serde(default)]
+        layouts: Vec<StructLayout>,
+        /// 규칙을 설치하는 XDP 프로그램의 매치 기능 비트맵 (`types::XdpFeatures`, 모르거나 이전 데몬이면 None)
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        features: Option<u64>,
+    },
+    
+    /// 겹치는 규칙 쌍
+    Conflicts {
+        conflicts: Vec<RuleConflict>,
+    },
+    
+    /// 커널 규칙 맵 감사 결과
+    MapAudit {
+        audit: MapAudit,
     },
+    
+    /// LPM 키 공간 통계
+    RuleSpace {
+        stats: RuleSpaceStats,
+    },
+    
+    /// 커널 규칙 맵 불일치 수정 결과
+    MapsReconciled {
+        summary: ReconcileSummary,
+    },
+    
+    /// 리디렉션 대상 목록
+    RedirectTargets {
+        targets: Vec<RedirectTarget>,
+    },
+    
+    /// 규칙 재설치 결과
+    RulesRepaired {
+        /// 재설치에 성공한 규칙
+        repaired: Vec<String>,
+        /// 여전히 설치되지 않은 규칙
+        errors: Vec<RuleError>,
+    },
+    
+    /// 이벤트 기록 (오래된 것부터)
+    Events {
+        events: Vec<SequencedEvent>,
+        /// 요청한 순번 이후 중 기록에서 밀려나 돌려줄 수 없는 구간
+        #[serde(default)]
+        gap: Option<EventGap>,
+        /// 데몬이 마지막으로 부여한 순번
+        latest_seq: u64,
+    },
+    
+    /// 응답 프레임 크기 제한에 맞춰 앞부분만 담은 목록 응답 (`Rules`, `Events`)
+    ///
+    /// `results`는 원래 응답에서 목록의 앞 `returned`개만 남긴 것이다 (`Rules`의
+    /// `next_cursor`는 비움). `hint`가 `PAGINATION_HINT`면 `limit`을 `returned` 이하로
+    /// 줄여 나눠 요청하면 모두 받을 수 있다.
+    PartialResults {
+        returned: usize,
+        /// 잘리기 전 목록 항목 수
+        total: usize,
+        hint: String,
+        results: Box<ApiResponse>,
+    },
+}
+
+/// 응답 프레임 (요청이 보낸 요청 ID와 응답)
+///
+/// 요청 ID를 보내지 않은 이전 클라이언트에는 `ApiResponse`만 보내던 형식 그대로 응답한다.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ResponseFrame {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+    #[serde(flatten)]
+    pub response: ApiResponse,
 }
 
 /// 필터 규칙 통계
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
 pub struct RuleStats {
     pub packets: u64,
     pub bytes: u64,
     pub last_matched: u64,
+    /// 직전 텔레메트리 수집 구간의 초당 매치 패킷 수 (include_rates, 아직 관측 전이면 None)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pps: Option<u64>,
+    /// 직전 텔레메트리 수집 구간의 초당 매치 바이트 수
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bps: Option<u64>,
+    /// 리디렉션 대상으로 넘긴 패킷 수 (리디렉션 규칙만 해당)
+    #[serde(default)]
+    pub redirected_packets: u64,
+    /// 리디렉션 대상으로 넘기지 못한 패킷 수 (리디렉션 규칙만 해당)
+    #[serde(default)]
+    pub redirect_failures: u64,
+    /// 규칙 액션 대신 XDP_ABORTED로 끝난 패킷 수 (0이 아니면 대부분 잘못된 리디렉션 대상)
+    #[serde(default)]
+    pub aborted_packets: u64,
 }
 
 /// 필터 규칙 정보
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
 pub struct RuleInfo {
     pub label: String,
+    /// 규칙 네임스페이스 (레이블은 네임스페이스 안에서 유일)
+    #[serde(default = "crate::utils::default_namespace")]
+    pub namespace: String,
     pub action: String,
     pub src_ip: Option<String>,
     pub dst_ip: Option<String>,
@@ -127,11 +907,950 @@ pub struct RuleInfo {
     pub tcp_flags: Option<String>,
     pub priority: u32,
     pub redirect_if: Option<String>,
+    #[serde(default)]
+    pub redirect_cpu: Option<u32>,
     pub rate_limit: u32,
     pub expire: u32,
+    #[serde(default)]
+    pub auto: bool,
+    #[serde(default)]
+    pub degraded: bool,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub created_by: Option<String>,
+    /// 생성 시각 (UNIX 초)
+    #[serde(default)]
+    pub creation_time: u64,
+    /// 커널 설치 상태
+    #[serde(default)]
+    pub install_state: InstallState,
+    /// 규칙이 설치되는 커널 맵
+    #[serde(default)]
+    pub map: RuleMap,
+    /// 연결된 인터페이스가 있어 실제로 패킷에 적용되는지 (이전 데몬은 보내지 않음)
+    #[serde(default = "default_effective")]
+    pub effective: bool,
+    /// 사용량 할당량 (없으면 제한 없음)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quota: Option<QuotaInfo>,
+    /// 할당량을 넘어 커널에 `quota.action`이 적용된 상태
+    #[serde(default)]
+    pub quota_exceeded: bool,
+    /// 소스 호스트 이름과 해석한 주소 (호스트 이름 규칙만 해당)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub src_host: Option<HostInfo>,
+    /// 통계를 합산할 카운터 그룹 (count 액션 규칙만 해당)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub counter_group: Option<String>,
+    /// 규칙을 설치한 프리셋 (`ApplyPreset`으로 설치한 규칙만 해당)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preset: Option<String>,
+    /// 규칙을 설치한 주체 (`wasm:<모듈>`은 WASM 모듈이 설치한 동적 차단 규칙)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub origin: Option<String>,
+    /// 소스 프리픽스 집합 (국가 차단 규칙만 해당)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub src_set: Option<PrefixSetInfo>,
+    /// 만료 알림을 받을 웹훅 URL
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner_webhook: Option<String>,
+    /// 만료 예고 알림을 보낼 남은 시간 (초, 규칙에 지정한 값만)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notify_before: Option<u32>,
+    /// 주소 체계 (데몬이 항상 채움, 없는 이전 파일은 `family()`가 주소에서 결정)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address_family: Option<AddressFamily>,
     pub stats: RuleStats,
 }
 
+fn default_effective() -> bool {
+    true
+}
+
+/// 기본 네임스페이스의 빈 drop 규칙 (모든 프로토콜, 설치됨)
+impl Default for RuleInfo {
+    fn default() -> Self {
+        Self {
+            label: String::new(),
+            namespace: crate::utils::default_namespace(),
+            action: "drop".to_string(),
+            src_ip: None,
+            dst_ip: None,
+            src_port: None,
+            dst_port: None,
+            protocol: "any".to_string(),
+            tcp_flags: None,
+            priority: 0,
+            redirect_if: None,
+            redirect_cpu: None,
+            rate_limit: 0,
+            expire: 0,
+            auto: false,
+            degraded: false,
+            description: None,
+            created_by: None,
+            creation_time: 0,
+            install_state: InstallState::default(),
+            map: RuleMap::default(),
+            effective: default_effective(),
+            quota: None,
+            quota_exceeded: false,
+            src_host: None,
+            counter_group: None,
+            preset: None,
+            origin: None,
+            src_set: None,
+            owner_webhook: None,
+            notify_before: None,
+            address_family: None,
+            stats: RuleStats::default(),
+        }
+    }
+}
+
+impl RuleInfo {
+    /// 데몬 전체에서 유일한 규칙 키 (`utils::qualify_label`)
+    pub fn key(&self) -> String {
+        crate::utils::qualify_label(&self.namespace, &self.label)
+    }
+
+    /// 규칙의 주소 체계
+    ///
+    /// 저장된 값이 있으면 그대로 쓰고, 필드가 없는 이전 출력은 주소 조건에서 결정한다.
+    /// 호스트 이름과 프리픽스 집합 소스는 IPv4로 해석되며, 체계가 섞인 주소는 `Any`로 본다.
+    pub fn family(&self) -> AddressFamily {
+        if let Some(family) = self.address_family {
+            return family;
+        }
+        if self.src_ip.is_none() && (self.src_host.is_some() || self.src_set.is_some()) {
+            return AddressFamily::V4;
+        }
+        AddressFamily::of_prefixes(self.src_ip.as_deref(), self.dst_ip.as_deref()).unwrap_or_default()
+    }
+
+    /// WASM 모듈이 설치한 동적 차단 규칙인지
+    pub fn is_dynamic(&self) -> bool {
+        crate::utils::origin_matches(self.origin.as_deref(), crate::utils::WASM_ORIGIN)
+    }
+
+    /// 표시용 레이블 (자동/비활성 표시 포함)
+    pub fn display_label(&self) -> String {
+        let mut label = self.label.clone();
+        if self.auto {
+            label.push_str(" (auto)");
+        }
+        if self.degraded {
+            label.push_str(" (degraded)");
+        }
+        if self.quota_exceeded {
+            label.push_str(" (quota)");
+        }
+        if self.install_state != InstallState::Installed {
+            label.push_str(&format!(" ({})", self.install_state.as_str().to_uppercase()));
+        }
+        label
+    }
+
+    /// 표시용 액션 (리디렉션 대상 포함)
+    pub fn display_action(&self) -> String {
+        match (&self.redirect_if, self.redirect_cpu) {
+            (Some(ifname), _) => format!("{}:{}", self.action, ifname),
+            (None, Some(cpu)) => format!("{}:cpu{}", self.action, cpu),
+            (None, None) => self.action.clone(),
+        }
+    }
+
+    /// 소스 주소 (호스트 이름 규칙은 "이름 (해석한 주소)", 집합 규칙은 "이름 (프리픽스 수)")
+    pub fn source_addr(&self) -> Option<String> {
+        match (&self.src_host, &self.src_set) {
+            (Some(host), _) => Some(format!("{} ({})", host.name, host.addrs.join(", "))),
+            (None, Some(set)) => Some(format!("{} ({} prefixes)", set.name, set.entries)),
+            (None, None) => self.src_ip.clone(),
+        }
+    }
+
+    /// 소스 주소:포트
+    pub fn source(&self) -> String {
+        endpoint(&self.source_addr(), &self.src_port)
+    }
+
+    /// 대상 주소:포트
+    pub fn destination(&self) -> String {
+        endpoint(&self.dst_ip, &self.dst_port)
+    }
+}
+
+fn endpoint(ip: &Option<String>, port: &Option<String>) -> String {
+    match (ip, port) {
+        (Some(ip), Some(port)) => format!("{}:{}", ip, port),
+        (Some(ip), None) => ip.clone(),
+        (None, Some(port)) => format!("*:{}", port),
+        (None, None) => "*".to_string(),
+    }
+}
+
+/// 규칙 변경 종류
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleChangeKind {
+    Added,
+    Updated,
+    Deleted,
+}
+
+impl RuleChangeKind {
+    /// 종류 이름
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Added => "added",
+            Self::Updated => "updated",
+            Self::Deleted => "deleted",
+        }
+    }
+}
+
+/// 규칙 사용량 할당량
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct QuotaInfo {
+    /// 기간 동안 허용하는 바이트 수
+    pub bytes: Option<u64>,
+    /// 기간 동안 허용하는 패킷 수
+    pub packets: Option<u64>,
+    /// 할당량을 넘으면 적용하는 액션 이름
+    pub action: String,
+}
+
+/// 호스트 이름 엔드포인트
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct HostInfo {
+    /// 호스트 이름
+    pub name: String,
+    /// 마지막으로 해석한 주소
+    pub addrs: Vec<String>,
+    /// 마지막 해석 실패 원인 (있으면 이전 주소를 유지하는 중)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// 규칙이 소스로 쓰는 IPv4 프리픽스 집합
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct PrefixSetInfo {
+    /// 집합 이름 (예: country-RU)
+    pub name: String,
+    /// 커널 LPM 항목 수 (정규화한 프리픽스 수)
+    pub entries: usize,
+    /// 집합이 덮는 주소 수
+    pub addresses: u64,
+    /// 프리픽스를 얻은 원본 ("geoip" 또는 CSV 파일 경로)
+    pub source: String,
+    /// 원본 데이터 버전 (데이터베이스 빌드 날짜, 파일 해시)
+    pub version: String,
+}
+
+/// 국가 차단 규칙 하나의 설치/갱신 결과
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct CountryBlockInfo {
+    /// ISO 3166-1 국가 코드 (대문자)
+    pub code: String,
+    /// 규칙 레이블 (country-<코드>)
+    pub label: String,
+    /// 규칙 액션 이름
+    pub action: String,
+    /// 설치된 프리픽스 집합 (갱신에 실패했으면 이전 집합)
+    pub set: PrefixSetInfo,
+    /// 규칙 변경 종류 (바뀐 것이 없으면 None)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub change: Option<RuleChangeKind>,
+    /// 갱신 실패 원인
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// 변경 후 LPM 규칙 맵에서 쓰는 항목 수
+    pub map_entries: usize,
+    /// LPM 규칙 맵 크기 (max_entries)
+    pub map_capacity: usize,
+}
+
+/// 자가 진단 결과
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Default)]
+pub struct SelfTestReport {
+    pub interface: String,
+    /// 보낸 패킷이 모두 임시 규칙 카운터에 잡혔고 오류가 없음
+    pub passed: bool,
+    /// 임시 규칙 레이블
+    pub label: String,
+    /// 표식 5-튜플 ("198.18.12.34:40123 -> 198.19.0.1:9 udp")
+    pub marker: String,
+    /// 보낸 패킷 수
+    pub packets_sent: u32,
+    /// 규칙 카운터에 잡힌 패킷 수 (읽기 전에 실패했으면 None)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub packets_observed: Option<u64>,
+    /// 실패한 단계 ("install", "transmit", "read_counter")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub failed_stage: Option<String>,
+    /// 실패 원인
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// 임시 규칙을 지우지 못한 원인 (규칙은 만료 시간이 지나면 사라짐)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cleanup_error: Option<String>,
+}
+
+impl SelfTestReport {
+    /// 한 줄 요약 ("passed: 5/5 packets counted on lo")
+    pub fn summary(&self) -> String {
+        let observed = self.packets_observed
+            .map(|observed| format!("{}/{} packets counted", observed, self.packets_sent));
+        match (self.passed, &self.failed_stage, &self.error) {
+            (true, ..) => format!("passed: {} on {}", observed.unwrap_or_default(), self.interface),
+            (false, Some(stage), Some(error)) => format!("failed at {} on {}: {}", stage, self.interface, error),
+            (false, ..) => format!("failed: {} on {}", observed.unwrap_or_else(|| "no packets counted".to_string()), self.interface),
+        }
+    }
+}
+
+/// 처리량 벤치마크 결과
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+pub struct BenchReport {
+    /// XDP를 연결해 측정한 인터페이스
+    pub interface: String,
+    /// 트래픽을 보낸 인터페이스
+    pub peer: String,
+    /// 벤치마크가 만들고 지운 veth 쌍인지
+    pub created_pair: bool,
+    /// 합성 규칙 수
+    pub rules: u32,
+    /// 프레임 크기 (바이트)
+    pub pkt_size: u32,
+    /// 모드별 전송 시간 (초)
+    pub duration_secs: u32,
+    /// 측정한 모드별 결과 (요청 순서)
+    pub modes: Vec<BenchModeResult>,
+    /// 측정 전에 실패한 단계 ("create_pair", "install")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub failed_stage: Option<String>,
+    /// 실패 원인
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// 요청자가 떠나 남은 측정을 하지 않음
+    #[serde(default)]
+    pub aborted: bool,
+    /// 되돌리지 못한 항목 (규칙은 만료 시간이 지나면 사라짐)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cleanup_errors: Vec<String>,
+}
+
+impl BenchReport {
+    /// 모든 모드를 측정하고 정리까지 마쳤는지
+    pub fn completed(&self) -> bool {
+        self.failed_stage.is_none()
+            && !self.aborted
+            && self.cleanup_errors.is_empty()
+            && self.modes.iter().all(|mode| mode.error.is_none())
+    }
+
+    /// 한 줄 요약 ("generic 1200000 pps on sgbench0, 1000 rules")
+    pub fn summary(&self) -> String {
+        if let (Some(stage), Some(error)) = (&self.failed_stage, &self.error) {
+            return format!("failed at {} on {}: {}", stage, self.interface, error);
+        }
+        let modes = self.modes.iter()
+            .map(|mode| match &mode.error {
+                Some(error) => format!("{} failed: {}", mode.mode, error),
+                None => format!("{} {:.0} pps", mode.mode, mode.pps()),
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        let aborted = if self.aborted { " (aborted)" } else { "" };
+        format!("{} on {}, {} rules{}", modes, self.interface, self.rules, aborted)
+    }
+}
+
+/// XDP 모드 하나의 벤치마크 결과
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+pub struct BenchModeResult {
+    /// XDP 모드 ("driver", "generic")
+    pub mode: String,
+    /// 보낸 패킷 수
+    pub packets_sent: u64,
+    /// XDP 프로그램이 처리한 패킷 수 (전역 통계 증가분)
+    pub packets_processed: u64,
+    /// 트래픽에 맞는 합성 규칙이 차단한 패킷 수
+    pub packets_dropped: u64,
+    /// 전송 시간 (밀리초)
+    pub elapsed_ms: u64,
+    /// 측정하는 동안 전체 CPU 사용률 (%)
+    pub cpu_percent: f64,
+    /// 측정하는 동안 softirq CPU 사용률 (%, XDP 처리 대부분)
+    pub softirq_percent: f64,
+    /// 측정 실패 원인
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl BenchModeResult {
+    /// 처리 pps
+    pub fn pps(&self) -> f64 {
+        per_second(self.packets_processed, self.elapsed_ms)
+    }
+
+    /// 전송 pps
+    pub fn tx_pps(&self) -> f64 {
+        per_second(self.packets_sent, self.elapsed_ms)
+    }
+
+    /// 처리한 패킷 중 차단한 비율 (%)
+    pub fn drop_rate(&self) -> f64 {
+        if self.packets_processed == 0 {
+            return 0.0;
+        }
+        self.packets_dropped as f64 * 100.0 / self.packets_processed as f64
+    }
+}
+
+fn per_second(count: u64, elapsed_ms: u64) -> f64 {
+    if elapsed_ms == 0 {
+        return 0.0;
+    }
+    count as f64 * 1000.0 / elapsed_ms as f64
+}
+
+/// IP 주소의 GeoIP 정보 (MaxMind 데이터베이스, 모르는 필드는 없음)
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Default)]
+pub struct GeoInfo {
+    /// ISO 3166-1 국가 코드 (예: "KR")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub country: Option<String>,
+    /// AS 번호
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub asn: Option<u32>,
+    /// AS 조직 이름
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub as_org: Option<String>,
+}
+
+impl GeoInfo {
+    /// 아는 필드가 하나도 없는지
+    pub fn is_empty(&self) -> bool {
+        self.country.is_none() && self.asn.is_none() && self.as_org.is_none()
+    }
+}
+
+impl std::fmt::Display for GeoInfo {
+    /// "KR, AS4766 (Korea Telecom)" 형식 (모르는 필드는 생략)
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut parts = Vec::new();
+        if let Some(country) = &self.country {
+            parts.push(country.clone());
+        }
+        match (self.asn, &self.as_org) {
+            (Some(asn), Some(org)) => parts.push(format!("AS{} ({})", asn, org)),
+            (Some(asn), None) => parts.push(format!("AS{}", asn)),
+            (None, Some(org)) => parts.push(org.clone()),
+            (None, None) => {},
+        }
+        write!(f, "{}", parts.join(", "))
+    }
+}
+
+/// 규칙의 커널 설치 상태
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Default)]
+#[serde(tag = "state", rename_all = "lowercase")]
+pub enum InstallState {
+    /// 모든 백엔드에 설치됨
+    #[default]
+    Installed,
+    /// 일부 백엔드나 맵 항목만 남음 (실패한 변경을 되돌리지 못함)
+    Partial { reason: String },
+    /// 캐시에만 있고 커널에 설치되지 않음
+    Failed { reason: String },
+}
+
+impl InstallState {
+    /// 상태 이름
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Installed => "installed",
+            Self::Partial { .. } => "partial",
+            Self::Failed { .. } => "failed",
+        }
+    }
+}
+
+/// 규칙이 설치되는 커널 맵
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleMap {
+    /// 소스 프리픽스로 찾는 filter_rules LPM 트라이
+    #[default]
+    Lpm,
+    /// 소스 IP 없는 규칙을 모두 검사하는 wildcard_rules 배열
+    Wildcard,
+}
+
+impl RuleMap {
+    /// 맵 이름
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Lpm => "lpm",
+            Self::Wildcard => "wildcard",
+        }
+    }
+}
+
+/// 규칙이 적용되는 주소 체계
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum AddressFamily {
+    /// 주소 조건이 없어 IPv4와 IPv6 모두에 적용
+    #[default]
+    Any,
+    /// IPv4 주소 조건
+    V4,
+    /// IPv6 주소 조건
+    V6,
+}
+
+impl AddressFamily {
+    /// 문자열에서 주소 체계 파싱 ("4", "v4", "ipv4", "6", "v6", "ipv6", "any")
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "4" | "v4" | "ipv4" => Some(Self::V4),
+            "6" | "v6" | "ipv6" => Some(Self::V6),
+            "any" => Some(Self::Any),
+            _ => None,
+        }
+    }
+
+    /// 주소 체계 이름
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Any => "any",
+            Self::V4 => "v4",
+            Self::V6 => "v6",
+        }
+    }
+
+    /// 소스와 대상 프리픽스에서 주소 체계 결정
+    ///
+    /// 프리픽스가 없으면 `Any`이고, 둘 다 있으면 체계가 같아야 한다. IPv4 소스와
+    /// IPv6 대상처럼 섞인 규칙은 어떤 패킷에도 매치될 수 없으므로 `dst_ip` 오류로 거부한다.
+    pub fn of_prefixes(src_ip: Option<&str>, dst_ip: Option<&str>) -> Result<Self, InvalidArgument> {
+        let family = |field: &str, value: Option<&str>| -> Result<Option<Self>, InvalidArgument> {
+            let Some(value) = value else {
+                return Ok(None);
+            };
+            let prefix = crate::utils::IpPrefix::parse(value)
+                .map_err(|e| InvalidArgument::new(field, format!("{:#}", e)))?;
+            Ok(Some(if prefix.addr.is_ipv4() { Self::V4 } else { Self::V6 }))
+        };
+
+        match (family("src_ip", src_ip)?, family("dst_ip", dst_ip)?) {
+            (Some(src), Some(dst)) if src != dst => Err(InvalidArgument::new(
+                "dst_ip",
+                format!("address family {} does not match src_ip family {}", dst.as_str(), src.as_str()),
+            )),
+            (Some(family), _) | (None, Some(family)) => Ok(family),
+            (None, None) => Ok(Self::Any),
+        }
+    }
+
+    /// 이 체계의 규칙이 `family` 트래픽에 적용되는지 (`Any` 규칙은 양쪽 모두)
+    pub fn applies_to(&self, family: AddressFamily) -> bool {
+        *self == family || *self == Self::Any
+    }
+}
+
+/// 단일 규칙 상세 정보
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RuleDetail {
+    #[serde(flatten)]
+    pub info: RuleInfo,
+    /// 남은 수명 (초, 만료 없음이면 None)
+    pub ttl_remaining: Option<u64>,
+    /// 커널 LPM 키 (hex, 소스 IP 규칙만 해당)
+    pub lpm_key: Option<String>,
+    /// wildcard_rules 슬롯 (소스 IP 없는 규칙만 해당)
+    #[serde(default)]
+    pub wildcard_slot: Option<u32>,
+    /// 커널 맵에 설치되어 있는지 여부
+    pub installed: bool,
+    /// 규칙이 적용되는 인터페이스 (XDP 프로그램이 연결된 인터페이스)
+    pub interfaces: Vec<String>,
+    /// 규칙과 관련된 인터페이스 중 링크가 내려간 인터페이스 (연결된 인터페이스, 리디렉션 대상)
+    #[serde(default)]
+    pub down_interfaces: Vec<String>,
+    /// 최근 매칭 시각 (이벤트 추적이 켜진 경우, 최대 5개)
+    #[serde(default)]
+    pub recent_matches: Vec<u64>,
+    /// 최근 60분의 분당 매치 패킷 수 (오래된 분부터, 매치된 적이 없으면 비어 있음)
+    #[serde(default)]
+    pub history_1m: Vec<u64>,
+    /// 단일 호스트 소스의 GeoIP 정보 (GeoIP가 구성되고 정보가 있을 때만)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub src_geo: Option<GeoInfo>,
+}
+
+/// 규칙 정의 (AddRule과 같은 필드, 선언형 규칙 집합의 항목)
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct RuleSpec {
+    pub label: String,
+    pub src_ip: Option<String>,
+    pub dst_ip: Option<String>,
+    pub src_port_min: u16,
+    pub src_port_max: u16,
+    pub dst_port_min: u16,
+    pub dst_port_max: u16,
+    pub protocol: u8,
+    pub tcp_flags: u8,
+    pub action: u8,
+    pub redirect_if: Option<String>,
+    #[serde(default)]
+    pub redirect_cpu: Option<u32>,
+    pub priority: u32,
+    pub rate_limit: u32,
+    pub expire: u32,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub quota_bytes: Option<u64>,
+    #[serde(default)]
+    pub quota_packets: Option<u64>,
+    #[serde(default)]
+    pub quota_action: Option<u8>,
+    #[serde(default)]
+    pub counter_group: Option<String>,
+    /// 소스 포트 목록 (비어 있으면 소스 포트 범위로 매치)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub src_port_list: Vec<u16>,
+    /// 목적지 포트 목록 (비어 있으면 목적지 포트 범위로 매치)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dst_port_list: Vec<u16>,
+    /// 만료 알림을 받을 웹훅 URL
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner_webhook: Option<String>,
+    /// 만료 예고 알림을 보낼 남은 시간 (초)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notify_before: Option<u32>,
+}
+
+/// 규칙 정의 빌더
+///
+/// 필드 값의 인코딩(액션, 프로토콜 번호, "모든 포트" 범위 등)을 몰라도 규칙을 만들 수 있다.
+///
+/// ```
+/// use swift_guard::api::RuleSpec;
+/// use swift_guard::types::{ActionType, ProtocolType, TcpFlags};
+///
+/// let spec = RuleSpec::new("block-syn", ActionType::Drop)
+///     .with_src_ip("203.0.113.0/24")
+///     .with_protocol(ProtocolType::Tcp)
+///     .with_dst_ports(1024, 2048)
+///     .with_tcp_flags(TcpFlags(TcpFlags::SYN))
+///     .with_priority(100)
+///     .with_expire(3600);
+///
+/// assert_eq!((spec.action, spec.protocol, spec.tcp_flags), (2, 6, 0x02));
+/// assert_eq!((spec.src_port_min, spec.src_port_max), (0, 65535));
+/// assert_eq!((spec.dst_port_min, spec.dst_port_max), (1024, 2048));
+/// ```
+impl RuleSpec {
+    /// 모든 트래픽에 `action`을 적용하는 규칙 (모든 프로토콜과 포트, 우선순위 0, 만료 없음)
+    ///
+    /// ```
+    /// use swift_guard::api::RuleSpec;
+    /// use swift_guard::types::ActionType;
+    ///
+    /// let spec = RuleSpec::new("allow-all", ActionType::Pass);
+    /// assert_eq!((spec.protocol, spec.priority, spec.expire), (255, 0, 0));
+    /// assert_eq!(spec.src_ip, None);
+    /// ```
+    pub fn new(label: &str, action: ActionType) -> Self {
+        Self {
+            label: label.to_string(),
+            src_ip: None,
+            dst_ip: None,
+            src_port_min: 0,
+            src_port_max: u16::MAX,
+            dst_port_min: 0,
+            dst_port_max: u16::MAX,
+            protocol: ProtocolType::Any as u8,
+            tcp_flags: 0,
+            action: action as u8,
+            redirect_if: None,
+            redirect_cpu: None,
+            priority: 0,
+            rate_limit: 0,
+            expire: 0,
+            description: None,
+            quota_bytes: None,
+            quota_packets: None,
+            quota_action: None,
+            counter_group: None,
+            src_port_list: Vec::new(),
+            dst_port_list: Vec::new(),
+            owner_webhook: None,
+            notify_before: None,
+        }
+    }
+
+    /// 소스 주소 또는 프리픽스 ("10.0.0.1", "10.0.0.0/8")
+    pub fn with_src_ip(mut self, prefix: &str) -> Self {
+        self.src_ip = Some(prefix.to_string());
+        self
+    }
+
+    /// 목적지 주소 또는 프리픽스
+    pub fn with_dst_ip(mut self, prefix: &str) -> Self {
+        self.dst_ip = Some(prefix.to_string());
+        self
+    }
+
+    /// 소스 포트 하나
+    pub fn with_src_port(self, port: u16) -> Self {
+        self.with_src_ports(port, port)
+    }
+
+    /// 소스 포트 범위 (양 끝 포함)
+    pub fn with_src_ports(mut self, min: u16, max: u16) -> Self {
+        self.src_port_min = min;
+        self.src_port_max = max;
+        self
+    }
+
+    /// 소스 포트 목록 (목록의 포트 중 하나와 같으면 매치)
+    ///
+    /// ```
+    /// use swift_guard::api::RuleSpec;
+    /// use swift_guard::types::{ActionType, ProtocolType};
+    ///
+    /// let spec = RuleSpec::new("reflectors", ActionType::Count)
+    ///     .with_protocol(ProtocolType::Udp)
+    ///     .with_src_port_list(&[53, 123, 1900]);
+    /// assert_eq!(spec.src_port_list, vec![53, 123, 1900]);
+    /// assert_eq!((spec.src_port_min, spec.src_port_max), (0, 65535));
+    /// ```
+    pub fn with_src_port_list(mut self, ports: &[u16]) -> Self {
+        self.src_port_list = ports.to_vec();
+        self
+    }
+
+    /// 목적지 포트 하나
+    pub fn with_dst_port(self, port: u16) -> Self {
+        self.with_dst_ports(port, port)
+    }
+
+    /// 목적지 포트 범위 (양 끝 포함)
+    pub fn with_dst_ports(mut self, min: u16, max: u16) -> Self {
+        self.dst_port_min = min;
+        self.dst_port_max = max;
+        self
+    }
+
+    /// 목적지 포트 목록 (목록의 포트 중 하나와 같으면 매치)
+    ///
+    /// ```
+    /// use swift_guard::api::RuleSpec;
+    /// use swift_guard::types::{ActionType, ProtocolType};
+    ///
+    /// let spec = RuleSpec::new("web", ActionType::Pass)
+    ///     .with_protocol(ProtocolType::Tcp)
+    ///     .with_dst_port_list(&[80, 443, 8080]);
+    /// assert_eq!(spec.dst_port_list, vec![80, 443, 8080]);
+    /// assert_eq!((spec.dst_port_min, spec.dst_port_max), (0, 65535));
+    /// ```
+    pub fn with_dst_port_list(mut self, ports: &[u16]) -> Self {
+        self.dst_port_list = ports.to_vec();
+        self
+    }
+
+    /// 프로토콜
+    pub fn with_protocol(mut self, protocol: ProtocolType) -> Self {
+        self.protocol = protocol as u8;
+        self
+    }
+
+    /// 모두 설정되어야 일치하는 TCP 플래그
+    pub fn with_tcp_flags(mut self, flags: TcpFlags) -> Self {
+        self.tcp_flags = flags.0;
+        self
+    }
+
+    /// 리디렉션 대상 인터페이스 (redirect 액션)
+    pub fn with_redirect_if(mut self, interface: &str) -> Self {
+        self.redirect_if = Some(interface.to_string());
+        self
+    }
+
+    /// 리디렉션 대상 CPU (redirect 액션)
+    pub fn with_redirect_cpu(mut self, cpu: u32) -> Self {
+        self.redirect_cpu = Some(cpu);
+        self
+    }
+
+    /// 우선순위 (클수록 먼저 평가)
+    pub fn with_priority(mut self, priority: u32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// 초당 패킷 수 제한
+    pub fn with_rate_limit(mut self, packets_per_sec: u32) -> Self {
+        self.rate_limit = packets_per_sec;
+        self
+    }
+
+    /// 생성 후 만료까지의 시간 (초)
+    pub fn with_expire(mut self, secs: u32) -> Self {
+        self.expire = secs;
+        self
+    }
+
+    /// 규칙 설명
+    pub fn with_description(mut self, description: &str) -> Self {
+        self.description = Some(description.to_string());
+        self
+    }
+
+    /// 하루 할당량 (바이트, 패킷 중 지정한 것)과 넘었을 때 적용할 액션
+    ///
+    /// ```
+    /// use swift_guard::api::RuleSpec;
+    /// use swift_guard::types::ActionType;
+    ///
+    /// let spec = RuleSpec::new("guest", ActionType::Pass)
+    ///     .with_src_ip("192.168.50.0/24")
+    ///     .with_quota(Some(10 << 30), None, ActionType::Drop);
+    /// assert_eq!((spec.quota_bytes, spec.quota_packets, spec.quota_action), (Some(10 << 30), None, Some(2)));
+    /// ```
+    pub fn with_quota(mut self, bytes: Option<u64>, packets: Option<u64>, action: ActionType) -> Self {
+        self.quota_bytes = bytes;
+        self.quota_packets = packets;
+        self.quota_action = Some(action as u8);
+        self
+    }
+
+    /// 통계를 합산할 카운터 그룹 (count 액션)
+    pub fn with_counter_group(mut self, group: &str) -> Self {
+        self.counter_group = Some(group.to_string());
+        self
+    }
+
+    /// 만료 전과 만료 때 알림을 받을 웹훅 (`notify_before`는 만료 예고를 보낼 남은 초)
+    ///
+    /// ```
+    /// use swift_guard::api::RuleSpec;
+    /// use swift_guard::types::ActionType;
+    ///
+    /// let spec = RuleSpec::new("maintenance", ActionType::Pass)
+    ///     .with_expire(7200)
+    ///     .with_owner_webhook("http://ops.example:8080/expiry", Some(600));
+    /// assert_eq!(spec.owner_webhook.as_deref(), Some("http://ops.example:8080/expiry"));
+    /// assert_eq!(spec.notify_before, Some(600));
+    /// ```
+    pub fn with_owner_webhook(mut self, url: &str, notify_before: Option<u32>) -> Self {
+        self.owner_webhook = Some(url.to_string());
+        self.notify_before = notify_before;
+        self
+    }
+
+    /// 규칙 추가 요청으로 변환 (운영자는 데몬이 채움)
+    pub fn into_add_request(self) -> ApiRequest {
+        ApiRequest::AddRule(Box::new(NewRule {
+            src_ip: self.src_ip,
+            dst_ip: self.dst_ip,
+            src_port_min: self.src_port_min,
+            src_port_max: self.src_port_max,
+            dst_port_min: self.dst_port_min,
+            dst_port_max: self.dst_port_max,
+            protocol: self.protocol,
+            tcp_flags: self.tcp_flags,
+            action: self.action,
+            redirect_if: self.redirect_if,
+            redirect_cpu: self.redirect_cpu,
+            priority: self.priority,
+            rate_limit: self.rate_limit,
+            expire: self.expire,
+            label: self.label,
+            description: self.description,
+            created_by: None,
+            quota_bytes: self.quota_bytes,
+            quota_packets: self.quota_packets,
+            quota_action: self.quota_action,
+            src_host: None,
+            dst_host: None,
+            counter_group: self.counter_group,
+            src_port_list: self.src_port_list,
+            dst_port_list: self.dst_port_list,
+            owner_webhook: self.owner_webhook,
+            notify_before: self.notify_before,
+            force: false,
+        }))
+    }
+}
+
+/// 규칙 집합 적용 결과 (각 목록은 레이블)
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ApplySummary {
+    /// 적용하지 않고 계산만 한 결과인지 여부
+    pub dry_run: bool,
+    pub added: Vec<String>,
+    pub updated: Vec<String>,
+    pub deleted: Vec<String>,
+    pub unchanged: Vec<String>,
+    /// 검증 또는 적용에 실패한 규칙
+    pub errors: Vec<RuleError>,
+    /// 갱신 대상 규칙의 항목별 변경 사항
+    #[serde(default)]
+    pub diffs: Vec<RuleDiff>,
+    /// 적용이 중단되어 반영하지 않은 변경의 레이블 (클라이언트 연결 끊김 등)
+    #[serde(default)]
+    pub pending: Vec<String>,
+    /// 변경을 모두 적용한 뒤의 맵 사용량 (`PlanChanges` 결과만)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub capacity: Option<PlanCapacity>,
+}
+
+/// 변경 계획을 적용한 뒤의 규칙 맵 사용량
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct PlanCapacity {
+    /// filter_rules 맵 항목 수
+    pub lpm_entries: usize,
+    /// filter_rules 맵 최대 항목 수
+    pub lpm_capacity: usize,
+    /// filter_rules 맵 사용률 (%)
+    pub fill_percent: f64,
+    /// 와일드카드 맵에 설치된 규칙 수
+    pub wildcard_rules: usize,
+    /// 와일드카드 맵 슬롯 수
+    pub wildcard_capacity: usize,
+}
+
+/// 규칙 하나의 항목별 변경 사항
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct RuleDiff {
+    pub label: String,
+    pub changes: Vec<FieldChange>,
+}
+
+/// 항목 하나의 변경 (값은 사람이 읽는 표기, 예: action "pass" → "drop")
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct FieldChange {
+    pub field: String,
+    pub old: String,
+    pub new: String,
+}
+
+/// 규칙별 오류
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RuleError {
+    pub label: String,
+    pub message: String,
+    #[serde(default)]
+    pub code: ErrorCode,
+}
+
 /// 시스템 통계
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SystemStats {
@@ -139,6 +1858,205 @@ pub struct SystemStats {
     pub total_bytes: u64,
     pub packets_per_sec: u64,
     pub mbps: f64,
+    /// XDP_ABORTED로 끝난 패킷 수 (규칙이 뜻한 액션을 수행하지 못함)
+    #[serde(default)]
+    pub aborted_packets: u64,
+    /// 현재 수집 구간이 트래픽 기준선을 넘었는지
+    #[serde(default)]
+    pub anomaly: bool,
+    /// 트래픽 기준선 (이동 평균과 표준편차)
+    #[serde(default)]
+    pub baseline: TrafficBaseline,
+    #[serde(default)]
+    pub protocols: ProtocolStats,
+    #[serde(default)]
+    pub size_histogram: SizeHistogram,
+    #[serde(default)]
+    pub rules: RuleExpirySummary,
+    #[serde(default)]
+    pub daemon: DaemonSelfStats,
+    #[serde(default)]
+    pub api: ApiServerStats,
+    #[serde(default)]
+    pub webhooks: WebhookStats,
+}
+
+/// 트래픽 기준선
+///
+/// 수집 구간마다 갱신하는 pps, Mbps의 지수 가중 이동 평균과 표준편차다. 평균 + `k`·표준편차를
+/// 넘는 구간을 이상으로 판정하며, `samples`가 예열 구간 수에 이르기 전에는 판정하지 않는다.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq)]
+pub struct TrafficBaseline {
+    pub pps_mean: f64,
+    pub pps_stddev: f64,
+    pub mbps_mean: f64,
+    pub mbps_stddev: f64,
+    /// 기준선에 반영한 수집 구간 수
+    pub samples: u64,
+    /// 이상 판정 배수 (0이면 판정하지 않음)
+    pub k: f64,
+}
+
+/// 규칙 만료 요약
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq)]
+pub struct RuleExpirySummary {
+    /// 만료 없는 규칙 수
+    pub permanent: u64,
+    /// 만료 시간이 있는 규칙 수
+    pub expiring: u64,
+    /// 가장 먼저 만료되는 규칙의 만료 시각 (UNIX 초)
+    pub next_expiry: Option<u64>,
+}
+
+/// 데몬 프로세스 자체 통계 (/proc/self 기준)
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq)]
+pub struct DaemonSelfStats {
+    /// 직전 수집 이후 CPU 사용률 (코어 하나가 100%)
+    pub cpu_percent: f64,
+    /// 상주 메모리 (바이트)
+    pub rss_bytes: u64,
+    /// 열린 파일 디스크립터 수
+    pub open_fds: u64,
+    /// 살아 있는 tokio 작업 수
+    pub tokio_tasks: u64,
+    /// 프로세스 가동 시간 (초)
+    pub uptime_secs: u64,
+    /// 이벤트 채널에서 가장 느린 구독자가 아직 받지 않은 이벤트 수
+    pub event_lag: u64,
+}
+
+/// Unix 소켓 요청자의 자격 증명 (SO_PEERCRED)
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct PeerIdentity {
+    pub uid: u32,
+    pub gid: u32,
+    /// 연결한 프로세스 (커널이 알려주지 않으면 None)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pid: Option<i32>,
+    /// uid의 사용자 이름 (찾지 못하면 None)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+}
+
+impl std::fmt::Display for PeerIdentity {
+    /// "uid 1000 (alice)" 형식 (사용자 이름을 모르면 "uid 1000")
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "uid {}", self.uid)?;
+        if let Some(user) = &self.user {
+            write!(f, " ({})", user)?;
+        }
+        Ok(())
+    }
+}
+
+/// API 서버 자체 통계
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct ApiServerStats {
+    /// 처리한 요청 수
+    pub requests_total: u64,
+    /// 오류 응답 수 (빈도 제한 포함)
+    pub errors_total: u64,
+    /// 빈도 제한으로 거부한 요청 수
+    pub rate_limited_total: u64,
+    /// 크기 제한을 넘어 거부한 요청 프레임 수
+    pub oversized_frames_total: u64,
+    /// 현재 열린 연결 수
+    pub active_connections: u64,
+    /// 요청 종류별 통계 (종류 이름 순)
+    pub requests: Vec<RequestTypeStats>,
+}
+
+/// 웹훅 전송 통계 (데몬 시작 이후 누적, 이벤트 단위)
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WebhookStats {
+    /// 전송에 성공한 이벤트 수
+    pub delivered: u64,
+    /// 재시도 기한 안에 전송하지 못한 이벤트 수
+    pub failed: u64,
+    /// 큐가 가득 차 버린 이벤트 수
+    pub dropped: u64,
+    /// 재시도한 요청 수
+    pub retries: u64,
+    /// 현재 큐에서 대기 중인 이벤트 수
+    pub queued: u64,
+}
+
+/// 요청 종류별 통계
+///
+/// `duration_buckets`는 `types::API_LATENCY_BUCKET_BOUNDS_US` 순서의 버킷별
+/// 요청 수(누적 아님)이며 마지막 원소는 상한을 넘은 요청이다.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct RequestTypeStats {
+    /// 요청 종류 (`ApiRequest::kind`)
+    pub kind: String,
+    pub requests: u64,
+    pub errors: u64,
+    /// 처리 시간 합계 (마이크로초)
+    pub duration_sum_us: u64,
+    pub duration_buckets: Vec<u64>,
+}
+
+/// 패킷 크기 히스토그램
+///
+/// `counts`는 `types::SIZE_BUCKET_BOUNDS` 순서의 버킷별 패킷 수(누적 아님)이며
+/// 마지막 원소는 점보 버킷이다.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct SizeHistogram {
+    pub counts: Vec<u64>,
+    pub sum: u64,
+}
+
+impl SizeHistogram {
+    /// 전체 패킷 수
+    pub fn total(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+}
+
+/// 프로토콜별 패킷 수
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq)]
+pub struct ProtocolStats {
+    pub tcp: u64,
+    pub udp: u64,
+    pub icmp: u64,
+    pub other: u64,
+}
+
+impl ProtocolStats {
+    /// 전체 패킷 수
+    pub fn total(&self) -> u64 {
+        self.tcp + self.udp + self.icmp + self.other
+    }
+
+    /// 전체 대비 비율 (%)
+    pub fn percent(&self, count: u64) -> f64 {
+        let total = self.total();
+        if total == 0 {
+            0.0
+        } else {
+            count as f64 * 100.0 / total as f64
+        }
+    }
+
+    /// (이름, 패킷 수) 목록
+    pub fn entries(&self) -> [(&'static str, u64); 4] {
+        [
+            ("tcp", self.tcp),
+            ("udp", self.udp),
+            ("icmp", self.icmp),
+            ("other", self.other),
+        ]
+    }
+}
+
+/// WASM 모듈 로그 한 줄
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct WasmLogLine {
+    /// 모듈별 순번 (1부터 증가)
+    pub seq: u64,
+    /// 기록 시각 (UNIX 밀리초)
+    pub timestamp_ms: u64,
+    pub message: String,
 }
 
 /// WASM 모듈 정보
@@ -147,4 +2065,907 @@ pub struct WasmModuleInfo {
     pub name: String,
     pub state: String,
     pub loaded_at: u64,
+    /// 판정 적용 방식
+    #[serde(default)]
+    pub mode: WasmModuleMode,
+    /// shadow 모드에서 차단 판정을 내렸지만 통과시킨 패킷 수
+    #[serde(default)]
+    pub would_block_packets: u64,
+}
+
+/// WASM 모듈이 설치한 동적 차단 규칙
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct DynamicBlockInfo {
+    /// 규칙 레이블
+    pub label: String,
+    /// 차단한 소스 주소
+    pub ip: String,
+    /// 규칙을 설치한 모듈
+    pub module: String,
+    /// 모듈이 남긴 차단 사유
+    #[serde(default)]
+    pub reason: Option<String>,
+    /// 남은 수명 (초)
+    pub ttl_remaining: u64,
+    /// 규칙에 매치된 패킷 수
+    pub hits: u64,
+    /// 설치 시각 (UNIX 초)
+    pub creation_time: u64,
+}
+
+/// 연결 추적 조회 필터
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ConnectionFilter {
+    pub src_ip: Option<String>,
+    pub dst_ip: Option<String>,
+    pub src_port: Option<u16>,
+    pub dst_port: Option<u16>,
+    pub protocol: Option<u8>,
+}
+
+/// 추적 중인 연결 정보
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ConnectionInfo {
+    pub src_ip: String,
+    pub src_port: u16,
+    pub dst_ip: String,
+    pub dst_port: u16,
+    pub protocol: String,
+    pub state: String,
+    pub age_secs: u64,
+    pub idle_secs: u64,
+    pub packets: u64,
+    pub bytes: u64,
+    /// 유휴 시간이 TTL을 넘어 커널 LRU에서 곧 제거될 연결
+    pub expired: bool,
+}
+
+/// 패킷 캡처(pcap) 파일 정보
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct CaptureInfo {
+    pub name: String,
+    pub size: u64,
+    pub packets: u64,
+    /// 첫 패킷 시각 (유닉스 초, 패킷이 없으면 None)
+    pub first_packet: Option<u64>,
+    /// 마지막 패킷 시각 (유닉스 초)
+    pub last_packet: Option<u64>,
+}
+
+/// XDP 연결 실패 시 사용할 대체 백엔드
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum FallbackMode {
+    /// 대체하지 않음 (연결 실패)
+    #[default]
+    None,
+    /// nftables 테이블로 규칙 적용
+    Nftables,
+}
+
+/// WASM 모듈 검사 오류 시 처리
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum WasmErrorPolicy {
+    /// 이 모듈의 판정 없이 통과 (다음 모듈은 계속 검사)
+    #[default]
+    Pass,
+    /// 패킷 차단
+    Drop,
+    /// 모듈을 오류 상태로 바꾸고 재개할 때까지 검사에서 제외
+    DisableModule,
+}
+
+impl WasmErrorPolicy {
+    /// 구성 및 명령줄 이름
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WasmErrorPolicy::Pass => "pass",
+            WasmErrorPolicy::Drop => "drop",
+            WasmErrorPolicy::DisableModule => "disable_module",
+        }
+    }
+}
+
+impl std::str::FromStr for WasmErrorPolicy {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "pass" => Ok(WasmErrorPolicy::Pass),
+            "drop" => Ok(WasmErrorPolicy::Drop),
+            "disable_module" => Ok(WasmErrorPolicy::DisableModule),
+            other => Err(anyhow::anyhow!("Invalid WASM error policy: {} (expected pass, drop or disable_module)", other)),
+        }
+    }
+}
+
+/// WASM 모듈 판정 적용 방식
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum WasmModuleMode {
+    /// 차단 판정을 패킷에 적용
+    #[default]
+    Enforce,
+    /// 모든 패킷을 검사하지만 차단하지 않고 차단했을 패킷 수와 사유만 기록
+    Shadow,
+}
+
+impl WasmModuleMode {
+    /// 명령줄 이름
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WasmModuleMode::Enforce => "enforce",
+            WasmModuleMode::Shadow => "shadow",
+        }
+    }
+}
+
+impl std::str::FromStr for WasmModuleMode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "enforce" => Ok(WasmModuleMode::Enforce),
+            "shadow" => Ok(WasmModuleMode::Shadow),
+            other => Err(anyhow::anyhow!("Invalid WASM module mode: {} (expected enforce or shadow)", other)),
+        }
+    }
+}
+
+/// 연결된 인터페이스 정보
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct InterfaceInfo {
+    pub name: String,
+    /// 규칙 설치 백엔드 ("xdp", "nftables")
+    pub backend: String,
+    /// XDP 연결 모드 (nftables면 None)
+    pub mode: Option<String>,
+    /// 대체 BPF 오브젝트 경로 (기본 오브젝트나 nftables면 None)
+    #[serde(default)]
+    pub object: Option<String>,
+    /// 링크 상태 ("up", "down"; 확인하지 못했으면 None)
+    #[serde(default)]
+    pub link: Option<String>,
+    /// 링크가 내려가 XDP 프로그램을 분리해 둔 상태 (링크가 올라오면 다시 연결)
+    #[serde(default)]
+    pub suspended: bool,
+}
+
+/// 커널 기능 점검 상태
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Ok,
+    /// 동작하지만 일부 기능이 제한됨
+    Warn,
+    Fail,
+    /// 점검할 수 없음 (전제 조건 없음)
+    Skipped,
+}
+
+impl CheckStatus {
+    /// 상태 이름
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Ok => "ok",
+            Self::Warn => "warn",
+            Self::Fail => "fail",
+            Self::Skipped => "skipped",
+        }
+    }
+}
+
+/// 커널 기능 점검 결과 (데몬 시작 시 수행)
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct CapabilityCheck {
+    /// 점검 항목 (kernel_version, btf, lpm_trie_map, ...)
+    pub name: String,
+    pub status: CheckStatus,
+    /// 관측 값 또는 오류 내용
+    pub detail: String,
+    /// 경고/실패 시 해결 방법
+    pub remediation: Option<String>,
+}
+
+/// BPF 오브젝트 로드 실패 (데몬이 마지막 실패를 보관)
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Default)]
+pub struct BpfLoadFailure {
+    /// 오브젝트 파일 경로
+    pub object: String,
+    /// 실패 원인
+    pub error: String,
+    /// 로드 동안 libbpf가 남긴 로그 (verifier 로그 포함, 길면 끝부분만)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub log: Vec<String>,
+    /// 실패 시각 (UNIX 초)
+    pub timestamp: u64,
+}
+
+impl std::fmt::Display for BpfLoadFailure {
+    /// 원인 뒤에 libbpf 로그를 한 줄씩 들여 써서 붙임
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "BPF object {} failed to load: {}", self.object, self.error)?;
+        if !self.log.is_empty() {
+            write!(f, "\nlibbpf log:")?;
+            for line in &self.log {
+                write!(f, "\n  {}", line)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 규칙 적중 이벤트
+///
+/// XDP 프로그램이 규칙에 매치된 패킷마다 perf 버퍼로 보고한 레코드를 데몬이
+/// 디코딩한 것이다. 데몬 내부 브로드캐스트 채널을 통해 이벤트 구독(API),
+/// 웹훅, syslog로 전달된다.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct RuleHitEvent {
+    /// 규칙 레이블
+    pub label: String,
+    /// 매치된 규칙 키 (CIDR 표기의 소스 프리픽스)
+    pub rule_key: String,
+    /// 수행한 액션 (pass, drop, redirect, count)
+    pub action: String,
+    /// 프로토콜 이름 (tcp, udp, icmp 또는 번호)
+    pub protocol: String,
+    pub src_ip: String,
+    pub src_port: u16,
+    pub dst_ip: String,
+    pub dst_port: u16,
+    /// 매치 시각 (유닉스 에포크 기준 나노초)
+    pub timestamp_ns: u64,
+    /// 소스 주소의 GeoIP 정보 (GeoIP가 구성되고 정보가 있을 때만)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub geo: Option<GeoInfo>,
+}
+
+/// 자동 완화 이벤트 (로그 및 웹훅 페이로드)
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct MitigationEvent {
+    /// 탐지 종류 (synflood)
+    pub kind: String,
+    pub src_ip: String,
+    pub syn_pps: u64,
+    /// 설치한 자동 규칙 레이블
+    pub label: String,
+    pub action: String,
+    pub expire: u32,
+    pub dry_run: bool,
+    /// 탐지 시각 (UNIX 초)
+    pub timestamp: u64,
+    /// 소스 주소의 GeoIP 정보 (GeoIP가 구성되고 정보가 있을 때만)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub geo: Option<GeoInfo>,
+}
+
+/// 텔레메트리 수집 구간 동안 매치된 규칙 (규칙별 카운터 변화)
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct RuleMatchedEvent {
+    pub label: String,
+    /// 구간 동안 매치된 패킷 수
+    pub packets: u64,
+    /// 구간 동안 매치된 바이트 수
+    pub bytes: u64,
+    /// 초당 매치 패킷 수
+    pub pps: u64,
+    /// 초당 매치 바이트 수
+    pub bps: u64,
+    /// 수집 시각 (UNIX 초)
+    pub timestamp: u64,
+}
+
+/// 인터페이스 링크 상태 변화 (연결된 인터페이스나 리디렉션 대상)
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct LinkStateEvent {
+    pub interface: String,
+    /// 새 링크 상태 ("up", "down")
+    pub state: String,
+    /// XDP 프로그램 처리 ("detached", "reattached"; 없으면 None)
+    pub action: Option<String>,
+    /// 상태 변화로 degraded 여부가 바뀐 규칙
+    pub rules: Vec<String>,
+    /// 감지 시각 (UNIX 초)
+    pub timestamp: u64,
+}
+
+/// 규칙 만료 예고 또는 만료 (만료 알림 웹훅이 있는 규칙은 웹훅으로도 전송)
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct RuleExpiryEvent {
+    /// 알림 시점의 규칙 정보와 통계 (만료 이벤트는 삭제 직전의 최종 통계)
+    pub rule: RuleInfo,
+    /// 만료 시각 (UNIX 초)
+    pub expires_at: u64,
+    /// 감지 시각 (UNIX 초)
+    pub timestamp: u64,
+}
+
+/// 연속한 수집 구간마다 XDP_ABORTED로 끝난 패킷이 늘어남 (대부분 잘못된 구성)
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct XdpAbortedEvent {
+    /// 경보 구간 동안 늘어난 중단 패킷 수
+    pub aborted: u64,
+    /// 누적 중단 패킷 수
+    pub total: u64,
+    /// 중단 패킷이 이어서 관측된 수집 구간 수
+    pub intervals: u32,
+    /// 중단 패킷이 있는 규칙 (많은 순)
+    pub rules: Vec<String>,
+    /// 감지 시각 (UNIX 초)
+    pub timestamp: u64,
+}
+
+/// 수집 구간의 트래픽이 기준선을 넘음 (이상 상태로 바뀔 때 한 번)
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct AnomalyEvent {
+    pub pps: u64,
+    pub mbps: f64,
+    /// 기준선을 넘은 값 ("pps", "mbps")
+    pub metrics: Vec<String>,
+    /// 이 구간을 반영하기 전의 기준선
+    pub baseline: TrafficBaseline,
+    /// 감지 시각 (UNIX 초)
+    pub timestamp: u64,
+}
+
+/// 데몬이 배포하는 이벤트
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub enum DaemonEvent {
+    /// 규칙 적중
+    RuleHit(RuleHitEvent),
+    /// 자동 완화
+    Mitigation(MitigationEvent),
+    /// 수집 구간의 규칙별 매치 요약
+    RuleMatched(RuleMatchedEvent),
+    /// 인터페이스 링크 상태 변화
+    LinkState(LinkStateEvent),
+    /// 규칙 만료 예고 (남은 시간이 알림 기준 이하)
+    RuleExpiring(RuleExpiryEvent),
+    /// 규칙 만료로 삭제됨
+    RuleExpired(RuleExpiryEvent),
+    /// XDP_ABORTED 패킷이 계속 관측됨
+    XdpAborted(XdpAbortedEvent),
+    /// 트래픽이 기준선을 넘음
+    Anomaly(AnomalyEvent),
+}
+
+impl DaemonEvent {
+    /// 이벤트 종류 이름 (GetEvents의 `kinds` 필터 값)
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::RuleHit(_) => "rule_hit",
+            Self::Mitigation(_) => "mitigation",
+            Self::RuleMatched(_) => "rule_matched",
+            Self::LinkState(_) => "link_state",
+            Self::RuleExpiring(_) => "rule_expiring",
+            Self::RuleExpired(_) => "rule_expired",
+            Self::XdpAborted(_) => "xdp_aborted",
+            Self::Anomaly(_) => "anomaly",
+        }
+    }
+}
+
+/// count 액션 규칙의 카운터 (같은 `counter_group` 규칙의 합)
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct CounterInfo {
+    /// 카운터 그룹 이름 (그룹이 없으면 규칙 레이블)
+    pub name: String,
+    /// 합산한 규칙 레이블 (이름 순)
+    pub rules: Vec<String>,
+    pub packets: u64,
+    pub bytes: u64,
+    /// 직전 텔레메트리 수집 구간의 초당 패킷 수 (어느 규칙도 아직 관측 전이면 None)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pps: Option<u64>,
+}
+
+/// 프리셋 정의와 요청 네임스페이스에 적용된 규칙
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct PresetInfo {
+    pub name: String,
+    pub description: String,
+    pub params: Vec<PresetParamInfo>,
+    /// 프리셋이 설치한 규칙 레이블 (적용 전이면 비어 있음)
+    pub rules: Vec<String>,
+}
+
+/// 프리셋 매개변수
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct PresetParamInfo {
+    pub name: String,
+    pub description: String,
+    pub required: bool,
+}
+
+/// 순번이 부여된 이벤트
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SequencedEvent {
+    /// 데몬 시작 후 1부터 증가하는 순번
+    pub seq: u64,
+    pub event: DaemonEvent,
+}
+
+/// 기록에서 밀려나 돌려줄 수 없는 이벤트 순번 구간 (양 끝 포함)
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct EventGap {
+    pub first_seq: u64,
+    pub last_seq: u64,
+}
+
+impl EventGap {
+    /// 놓친 이벤트 수
+    pub fn missed(&self) -> u64 {
+        self.last_seq - self.first_seq + 1
+    }
+}
+
+/// 로드된 BPF 프로그램 정보
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct BpfProgramInfo {
+    pub name: String,
+    /// 프로그램 타입 (xdp 등)
+    pub prog_type: String,
+    /// 커널 프로그램 id (bpftool prog show id N)
+    pub id: u32,
+    /// 잠긴 메모리 (바이트)
+    pub memlock: u64,
+    /// bpffs 고정 경로
+    pub pin_path: Option<String>,
+}
+
+/// 로드된 BPF 맵 정보
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct BpfMapInfo {
+    pub name: String,
+    /// 맵 타입 (lpm_trie, devmap 등)
+    pub map_type: String,
+    /// 커널 맵 id (bpftool map show id N)
+    pub id: u32,
+    pub max_entries: u32,
+    pub key_size: u32,
+    pub value_size: u32,
+    /// 잠긴 메모리 (바이트)
+    pub memlock: u64,
+    /// bpffs 고정 경로
+    pub pin_path: Option<String>,
+}
+
+/// 소스 프리픽스가 겹치는 규칙 쌍
+///
+/// LPM 트라이는 우선순위와 관계없이 가장 긴 프리픽스를 매치하므로, 겹치는 범위에서는
+/// `winner`만 적용된다.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct RuleConflict {
+    /// 넓은 프리픽스 규칙 (같은 프리픽스면 먼저 설치된 규칙)
+    pub broader: String,
+    pub broader_prefix: String,
+    pub broader_priority: u32,
+    /// 좁은 프리픽스 규칙
+    pub narrower: String,
+    pub narrower_prefix: String,
+    pub narrower_priority: u32,
+    /// 겹치는 범위에서 커널이 적용하는 규칙
+    pub winner: String,
+    /// 우선순위가 더 높은 규칙이 적용되지 않음
+    pub priority_ignored: bool,
+    /// 승자 결정 이유
+    pub reason: String,
+}
+
+/// 커널 규칙 맵 감사 결과
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+pub struct MapAudit {
+    /// 캐시의 어떤 규칙에도 속하지 않는 커널 항목
+    pub orphans: Vec<OrphanEntry>,
+    /// 설치되었다고 기록되었지만 커널에 없는 규칙 항목
+    pub missing: Vec<MissingEntry>,
+}
+
+impl MapAudit {
+    /// 불일치 없음
+    pub fn is_clean(&self) -> bool {
+        self.orphans.is_empty() && self.missing.is_empty()
+    }
+}
+
+/// 데몬 캐시에 없는 커널 규칙 항목 (값에서 해석한 필드)
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct OrphanEntry {
+    pub map: RuleMap,
+    /// 대체 BPF 오브젝트 (기본 오브젝트면 None)
+    pub object: Option<String>,
+    /// LPM 키 (16진수, LPM 항목만)
+    pub lpm_key: Option<String>,
+    /// 와일드카드 맵 슬롯 (와일드카드 항목만)
+    pub wildcard_slot: Option<u32>,
+    /// 키의 소스 프리픽스 (LPM 항목만)
+    pub src_ip: Option<String>,
+    /// 값에 기록된 레이블 (네임스페이스 제외)
+    pub label: String,
+    pub action: String,
+    pub protocol: String,
+    pub dst_port: Option<String>,
+    pub priority: u32,
+}
+
+/// 커널에서 사라진 캐시 규칙 항목
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct MissingEntry {
+    pub label: String,
+    pub namespace: String,
+    pub map: RuleMap,
+    /// 대체 BPF 오브젝트 (기본 오브젝트면 None)
+    pub object: Option<String>,
+    /// LPM 키 (16진수, LPM 항목만)
+    pub lpm_key: Option<String>,
+    /// 와일드카드 맵 슬롯 (와일드카드 항목만)
+    pub wildcard_slot: Option<u32>,
+}
+
+/// LPM 키 공간 통계 (filter_rules 용량 계획)
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct RuleSpaceStats {
+    /// 프리픽스 길이별 LPM 항목 수 (인덱스가 프리픽스 길이, 0-32)
+    pub prefix_lengths: Vec<usize>,
+    /// 다른 프리픽스를 가장 많이 포함하는 프리픽스 (최대 10개, 많은 순)
+    pub top_overlaps: Vec<PrefixOverlap>,
+    /// 캐시 기준 LPM 항목 수 (규칙의 소스 프리픽스마다 하나, 같은 프리픽스는 하나)
+    pub lpm_entries: usize,
+    /// 커널 filter_rules 맵을 순회해 센 항목 수 (맵이 없으면 None)
+    pub kernel_entries: Option<usize>,
+    /// 와일드카드 맵에 설치된 규칙 수
+    pub wildcard_rules: usize,
+    /// filter_rules 맵 최대 항목 수
+    pub capacity: usize,
+    /// filter_rules 맵 사용률 (%, 커널 항목 수 기준, 없으면 캐시 기준)
+    pub fill_percent: f64,
+}
+
+impl RuleSpaceStats {
+    /// 커널 항목 수가 캐시와 다름 (`audit-maps`로 확인 필요)
+    pub fn kernel_mismatch(&self) -> bool {
+        self.kernel_entries.is_some_and(|entries| entries != self.lpm_entries)
+    }
+}
+
+/// 다른 프리픽스를 포함하는 프리픽스
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct PrefixOverlap {
+    /// "a.b.c.d/len" 표기
+    pub prefix: String,
+    /// 이 프리픽스 안에 있는 다른 LPM 항목 수
+    pub contains: usize,
+    /// 이 프리픽스를 쓰는 규칙 키 (네임스페이스/레이블)
+    pub labels: Vec<String>,
+}
+
+/// 커널 규칙 맵 불일치 수정 결과
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ReconcileSummary {
+    /// 수정 전 감사 결과
+    pub audit: MapAudit,
+    /// 삭제한 고아 항목 수
+    pub deleted_orphans: usize,
+    /// 다시 기록한 누락 항목 수
+    pub reinstalled: usize,
+    /// 수정하지 못한 항목의 오류
+    pub errors: Vec<String>,
+}
+
+/// redirect_map 항목 (리디렉션 대상 인터페이스)
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct RedirectTarget {
+    /// 맵 키 (규칙의 redirect_ifindex)
+    pub slot: u32,
+    /// 패킷을 보낼 인터페이스 인덱스 (항목 값)
+    pub ifindex: u32,
+    /// 항목에 기록된 인터페이스 이름
+    pub ifname: String,
+    /// 이 대상을 쓰는 규칙 키 (네임스페이스/레이블)
+    pub rules: Vec<String>,
+    /// `AddRedirectTarget`으로 직접 등록한 대상 (규칙이 없어도 유지)
+    pub manual: bool,
+    /// 대체 BPF 오브젝트 (기본 오브젝트면 None)
+    pub object: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn name() -> String {
+        "x".to_string()
+    }
+
+    fn add_rule() -> ApiRequest {
+        serde_json::from_value(serde_json::json!({"AddRule": {
+            "src_ip": null, "dst_ip": null,
+            "src_port_min": 0, "src_port_max": 0, "dst_port_min": 0, "dst_port_max": 0,
+            "protocol": 0, "tcp_flags": 0, "action": 1, "redirect_if": null,
+            "priority": 0, "rate_limit": 0, "expire": 0, "label": "web",
+        }})).unwrap()
+    }
+
+    #[test]
+    fn test_add_rule_wire_format() {
+        // 상자에 담은 규칙도 구조체 변형과 같은 형식으로 직렬화
+        let json = serde_json::to_value(add_rule()).unwrap();
+        assert_eq!((json["AddRule"]["label"].as_str(), json["AddRule"]["action"].as_u64()), (Some("web"), Some(1)));
+        assert_eq!(json["AddRule"]["force"], false);
+    }
+
+    #[test]
+    fn test_response_frame() {
+        let frame = ResponseFrame {
+            request_id: Some("req-1".to_string()),
+            response: ApiResponse::NotFound { resource: "rule".to_string(), name: "web".to_string() },
+        };
+        let json = serde_json::to_value(&frame).unwrap();
+        assert_eq!(json["request_id"], "req-1");
+        assert_eq!(json["NotFound"]["name"], "web");
+
+        let parsed: ResponseFrame = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed.request_id.as_deref(), Some("req-1"));
+        assert!(matches!(parsed.response, ApiResponse::NotFound { .. }));
+
+        // 요청 ID가 없으면 이전 형식 그대로
+        let frame = ResponseFrame {
+            request_id: None,
+            response: ApiResponse::Rules { rules: Vec::new(), total_count: 0, next_cursor: None, seq: None },
+        };
+        assert_eq!(serde_json::to_string(&frame).unwrap(), r#"{"Rules":{"rules":[],"total_count":0}}"#);
+        let parsed: ResponseFrame = serde_json::from_str(r#"{"Rules":{"rules":[]}}"#).unwrap();
+        assert!(parsed.request_id.is_none());
+
+        // 잘린 목록 응답은 남은 앞부분을 원래 응답 형식 그대로 담음
+        let partial = ApiResponse::PartialResults {
+            returned: 0,
+            total: 3,
+            hint: PAGINATION_HINT.to_string(),
+            results: Box::new(ApiResponse::Events { events: Vec::new(), gap: None, latest_seq: 7 }),
+        };
+        let json = serde_json::to_value(&partial).unwrap();
+        assert_eq!(json["PartialResults"]["hint"], "use pagination");
+        assert_eq!(json["PartialResults"]["results"]["Events"]["latest_seq"], 7);
+    }
+
+    #[test]
+    fn test_self_test_summary() {
+        let mut report = SelfTestReport {
+            interface: "lo".to_string(),
+            passed: true,
+            packets_sent: 5,
+            packets_observed: Some(5),
+            ..Default::default()
+        };
+        assert_eq!(report.summary(), "passed: 5/5 packets counted on lo");
+
+        report.passed = false;
+        report.packets_observed = Some(2);
+        assert_eq!(report.summary(), "failed: 2/5 packets counted on lo");
+
+        report.packets_observed = None;
+        report.failed_stage = Some("transmit".to_string());
+        report.error = Some("Operation not permitted".to_string());
+        assert_eq!(report.summary(), "failed at transmit on lo: Operation not permitted");
+
+        // 실패하지 않은 선택 필드는 직렬화하지 않음
+        let json = serde_json::to_value(SelfTestReport::default()).unwrap();
+        assert!(json.get("packets_observed").is_none() && json.get("cleanup_error").is_none());
+    }
+
+    #[test]
+    fn test_bench_mode_rates() {
+        let result = BenchModeResult {
+            mode: "driver".to_string(),
+            packets_sent: 2_000_000,
+            packets_processed: 1_500_000,
+            packets_dropped: 1_200_000,
+            elapsed_ms: 2_000,
+            ..Default::default()
+        };
+        assert_eq!((result.tx_pps(), result.pps(), result.drop_rate()), (1_000_000.0, 750_000.0, 80.0));
+
+        // 측정하지 못한 모드는 0
+        let failed = BenchModeResult { error: Some("attach failed".to_string()), ..Default::default() };
+        assert_eq!((failed.pps(), failed.drop_rate()), (0.0, 0.0));
+
+        let mut report = BenchReport {
+            interface: "sgbench0".to_string(),
+            rules: 1000,
+            modes: vec![result, BenchModeResult { mode: "generic".to_string(), ..failed }],
+            ..Default::default()
+        };
+        assert_eq!(report.summary(), "driver 750000 pps, generic failed: attach failed on sgbench0, 1000 rules");
+        assert!(!report.completed());
+        report.modes.truncate(1);
+        assert!(report.completed());
+
+        report.failed_stage = Some("install".to_string());
+        report.error = Some("map full".to_string());
+        assert_eq!(report.summary(), "failed at install on sgbench0: map full");
+        assert!(!report.completed());
+    }
+
+    #[test]
+    fn test_peer_identity() {
+        let identity = PeerIdentity { uid: 1000, gid: 1000, pid: Some(4242), user: Some("alice".to_string()) };
+        assert_eq!(identity.to_string(), "uid 1000 (alice)");
+        assert_eq!(PeerIdentity { user: None, ..identity.clone() }.to_string(), "uid 1000");
+
+        // 이전 데몬의 Pong에는 peer가 없음
+        let json = r#"{"Pong":{"version":"0.1.0","capabilities":[]}}"#;
+        assert!(matches!(serde_json::from_str(json).unwrap(), ApiResponse::Pong { peer: None, .. }));
+        let pong = ApiResponse::Pong {
+            version: "0.1.0".to_string(),
+            capabilities: Vec::new(),
+            api: ApiServerStats::default(),
+            webhooks: WebhookStats::default(),
+            build: None,
+            load_failure: None,
+            peer: Some(identity.clone()),
+            features: None,
+        };
+        let json = serde_json::to_string(&pong).unwrap();
+        assert!(json.contains(r#""peer":{"uid":1000,"gid":1000,"pid":4242,"user":"alice"}"#));
+        assert!(matches!(serde_json::from_str(&json).unwrap(), ApiResponse::Pong { peer: Some(peer), .. } if peer == identity));
+    }
+
+    #[test]
+    fn test_bpf_load_failure_display() {
+        let mut failure = BpfLoadFailure {
+            object: "/usr/lib/swift-guard/xdp_filter.o".to_string(),
+            error: "Invalid argument (os error 22)".to_string(),
+            log: Vec::new(),
+            timestamp: 1_700_000_000,
+        };
+        assert_eq!(failure.to_string(), "BPF object /usr/lib/swift-guard/xdp_filter.o failed to load: Invalid argument (os error 22)");
+
+        failure.log = vec!["0: (61) r2 = *(u32 *)(r1 +4)".to_string(), "invalid bpf_context access off=4 size=4".to_string()];
+        assert!(failure.to_string().ends_with(
+            "\nlibbpf log:\n  0: (61) r2 = *(u32 *)(r1 +4)\n  invalid bpf_context access off=4 size=4"));
+    }
+
+    #[test]
+    fn test_is_mutation() {
+        let writes = vec![
+            ApiRequest::Attach { interface: name(), mode: 0, force: false, fallback: None, obj_path: None },
+            ApiRequest::Detach { interface: name() },
+            add_rule(),
+            ApiRequest::DeleteRule { label: name() },
+            ApiRequest::ApplyRuleset { rules: Vec::new(), prune: false, dry_run: false, progress: false },
+            ApiRequest::ApplyPreset { name: name(), params: BTreeMap::new() },
+            ApiRequest::RemovePreset { name: name() },
+            ApiRequest::BlockCountry { code: name(), action: 2, expire: 0, csv_path: None },
+            ApiRequest::RefreshCountryBlocks { code: None },
+            ApiRequest::UnblockCountry { code: name() },
+            ApiRequest::LoadWasmModule { name: name(), file_path: name(), on_error: None, config: None, mode: WasmModuleMode::Enforce },
+            ApiRequest::UnloadWasmModule { name: name() },
+            ApiRequest::ResumeWasmModule { name: name() },
+            ApiRequest::SetWasmModuleState { name: name(), mode: WasmModuleMode::Shadow },
+            ApiRequest::ResetWasmModuleStats { name: name() },
+            ApiRequest::ClearWasmCache {},
+            ApiRequest::ClearDynamicBlocks { module: None },
+            ApiRequest::RepairRules {},
+            ApiRequest::ReconcileMaps { delete_orphans: true, reinstall_missing: false },
+            ApiRequest::AddRedirectTarget { interface: name() },
+            ApiRequest::RemoveRedirectTarget { interface: name() },
+            ApiRequest::SelfTest { interface: name() },
+            ApiRequest::RunBenchmark {
+                interface: None,
+                peer: None,
+                duration: 10,
+                pkt_size: 64,
+                rules: 1000,
+                modes: Vec::new(),
+                confirm: true,
+            },
+        ];
+        for request in &writes {
+            assert!(request.is_mutation(), "{} should be a mutation", request.kind());
+        }
+
+        let reads = vec![
+            ApiRequest::ListRules {
+                include_stats: false,
+                expiring_within_secs: None,
+                include_rates: false,
+                all_namespaces: true,
+                family: None,
+                include_dynamic: false,
+                origin: None,
+                cursor: None,
+                offset: 0,
+                limit: 0,
+            },
+            ApiRequest::WatchRules { all_namespaces: false },
+            ApiRequest::GetRule { label: name() },
+            ApiRequest::GetCounters { prefix: None },
+            ApiRequest::ApplyRuleset { rules: Vec::new(), prune: true, dry_run: true, progress: false },
+            ApiRequest::PlanChanges { rules: Vec::new(), prune: true },
+            ApiRequest::ListPresets {},
+            ApiRequest::GetStats {},
+            ApiRequest::ListWasmModules {},
+            ApiRequest::WasmModuleStats { name: name() },
+            ApiRequest::GetWasmModuleLog { name: name(), lines: 0, after: None },
+            ApiRequest::ListDynamicBlocks { module: None },
+            ApiRequest::ListConnections { limit: 0, filter: ConnectionFilter::default() },
+            ApiRequest::ListCaptures {},
+            ApiRequest::ListInterfaces {},
+            ApiRequest::Ping {},
+            ApiRequest::GetVersion {},
+            ApiRequest::GetBpfInfo {},
+            ApiRequest::ExplainConflicts {},
+            ApiRequest::AuditMaps {},
+            ApiRequest::RuleSpaceStats {},
+            ApiRequest::ReconcileMaps { delete_orphans: false, reinstall_missing: false },
+            ApiRequest::ListRedirectTargets {},
+            ApiRequest::GetEvents { since_seq: None, limit: 0, kinds: Vec::new() },
+            ApiRequest::GeoLookup { ip: name() },
+        ];
+        for request in &reads {
+            assert!(!request.is_mutation(), "{} should not be a mutation", request.kind());
+        }
+    }
+
+    #[test]
+    fn test_address_family_of_prefixes() {
+        use AddressFamily::{Any, V4, V6};
+
+        let v4 = Some("10.0.0.0/8");
+        let v6 = Some("2001:db8::/32");
+        let matrix = [
+            (None, None, Some(Any)),
+            (v4, None, Some(V4)),
+            (None, v4, Some(V4)),
+            (v4, v4, Some(V4)),
+            (v6, None, Some(V6)),
+            (None, v6, Some(V6)),
+            (v6, v6, Some(V6)),
+            (v4, v6, None),
+            (v6, v4, None),
+        ];
+        for (src_ip, dst_ip, expected) in matrix {
+            let family = AddressFamily::of_prefixes(src_ip, dst_ip);
+            match expected {
+                Some(expected) => assert_eq!(family.unwrap(), expected, "{:?} -> {:?}", src_ip, dst_ip),
+                None => assert_eq!(family.unwrap_err().field, "dst_ip", "{:?} -> {:?}", src_ip, dst_ip),
+            }
+        }
+
+        let err = AddressFamily::of_prefixes(Some("web.internal"), None).unwrap_err();
+        assert_eq!(err.field, "src_ip");
+        assert!(AddressFamily::V4.applies_to(AddressFamily::V4));
+        assert!(AddressFamily::Any.applies_to(AddressFamily::V6));
+        assert!(!AddressFamily::V6.applies_to(AddressFamily::V4));
+    }
+
+    #[test]
+    fn test_rule_info_family() {
+        let rule = |extra: serde_json::Value| -> RuleInfo {
+            let mut value = serde_json::json!({
+                "label": "web", "action": "drop", "src_ip": null, "dst_ip": null, "src_port": null,
+                "dst_port": null, "protocol": "tcp", "tcp_flags": null, "priority": 0, "redirect_if": null,
+                "rate_limit": 0, "expire": 0, "stats": { "packets": 0, "bytes": 0, "last_matched": 0 },
+            });
+            value.as_object_mut().unwrap().extend(extra.as_object().unwrap().clone());
+            serde_json::from_value(value).unwrap()
+        };
+
+        // 필드가 없는 이전 출력은 주소에서 결정
+        assert_eq!(rule(serde_json::json!({})).family(), AddressFamily::Any);
+        assert_eq!(rule(serde_json::json!({"src_ip": "10.0.0.0/8"})).family(), AddressFamily::V4);
+        assert_eq!(rule(serde_json::json!({"dst_ip": "2001:db8::1"})).family(), AddressFamily::V6);
+        assert_eq!(rule(serde_json::json!({"src_set": {"name": "country-KR", "entries": 3, "addresses": 768, "source": "geoip", "version": "v1"}})).family(), AddressFamily::V4);
+
+        // 저장된 값이 있으면 그대로 쓰고, 다시 직렬화해도 유지
+        let stored = rule(serde_json::json!({"src_ip": "10.0.0.0/8", "address_family": "any"}));
+        assert_eq!(stored.family(), AddressFamily::Any);
+        let json = serde_json::to_value(&stored).unwrap();
+        assert_eq!(json["address_family"], "any");
+        assert_eq!(AddressFamily::parse("6"), Some(AddressFamily::V6));
+        assert_eq!(AddressFamily::parse("7"), None);
+    }
 }
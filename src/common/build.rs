@@ -0,0 +1,97 @@
+// Swift-Guard Common 빌드 스크립트
+// 빌드 정보(build_info 모듈) 환경 변수를 기록하고,
+// grpc 기능이 켜진 경우 proto 정의에서 gRPC 코드 생성
+
+use std::path::Path;
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+fn main() {
+    emit_build_info();
+
+    #[cfg(feature = "grpc")]
+    compile_protos().expect("Failed to compile gRPC protos");
+}
+
+/// 커밋, 빌드 날짜, rustc 버전, 활성 기능을 `SWIFT_GUARD_*` 환경 변수로 기록
+fn emit_build_info() {
+    let manifest_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap_or_default();
+
+    let git_sha = command_output("git", &["-C", &manifest_dir, "rev-parse", "--short=12", "HEAD"])
+        .unwrap_or_else(|| "unknown".to_string());
+    // 커밋이 바뀌면 다시 실행
+    if let Some(git_dir) = command_output("git", &["-C", &manifest_dir, "rev-parse", "--absolute-git-dir"]) {
+        println!("cargo:rerun-if-changed={}", Path::new(&git_dir).join("HEAD").display());
+        println!("cargo:rerun-if-changed={}", Path::new(&git_dir).join("refs").display());
+    }
+
+    // 재현 가능한 빌드는 SOURCE_DATE_EPOCH를 빌드 시각으로 사용
+    let build_secs = std::env::var("SOURCE_DATE_EPOCH").ok()
+        .and_then(|secs| secs.parse::<u64>().ok())
+        .unwrap_or_else(|| SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0));
+
+    let rustc = std::env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+    let rustc_version = command_output(&rustc, &["--version"]).unwrap_or_else(|| "unknown".to_string());
+
+    let mut features: Vec<String> = std::env::vars()
+        .filter_map(|(key, _)| key.strip_prefix("CARGO_FEATURE_").map(|name| name.to_lowercase().replace('_', "-")))
+        .filter(|name| name != "default")
+        .collect();
+    features.sort();
+
+    println!("cargo:rustc-env=SWIFT_GUARD_GIT_SHA={}", git_sha);
+    println!("cargo:rustc-env=SWIFT_GUARD_BUILD_DATE={}", civil_date(build_secs));
+    println!("cargo:rustc-env=SWIFT_GUARD_RUSTC_VERSION={}", rustc_version);
+    println!("cargo:rustc-env=SWIFT_GUARD_FEATURES={}", features.join(","));
+    // --version 출력용 (빈 목록은 "none")
+    let features_display = if features.is_empty() { "none".to_string() } else { features.join(",") };
+    println!("cargo:rustc-env=SWIFT_GUARD_FEATURES_DISPLAY={}", features_display);
+    println!("cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH");
+}
+
+/// 명령의 표준 출력 첫 줄 (실패하거나 비어 있으면 None)
+fn command_output(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let line = String::from_utf8(output.stdout).ok()?.lines().next()?.trim().to_string();
+    (!line.is_empty()).then_some(line)
+}
+
+/// 유닉스 시각의 UTC 날짜 (YYYY-MM-DD)
+fn civil_date(secs: u64) -> String {
+    // Howard Hinnant의 days-from-civil 역변환
+    let days = (secs / 86_400) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+#[cfg(feature = "grpc")]
+fn compile_protos() -> Result<(), Box<dyn std::error::Error>> {
+    use std::path::PathBuf;
+
+    // 루트 패키지도 같은 빌드 스크립트로 공통 라이브러리를 빌드하므로 두 위치를 모두 확인
+    let manifest_dir = PathBuf::from(std::env::var("CARGO_MANIFEST_DIR")?);
+    let proto_dir = [manifest_dir.join("proto"), manifest_dir.join("src/common/proto")]
+        .into_iter()
+        .find(|dir| dir.exists())
+        .ok_or("proto directory not found")?;
+    let proto = proto_dir.join("swift_guard.proto");
+
+    // 시스템 protoc가 없어도 빌드되도록 번들 protoc 사용
+    if std::env::var_os("PROTOC").is_none() {
+        std::env::set_var("PROTOC", protoc_bin_vendored::protoc_bin_path()?);
+    }
+
+    println!("cargo:rerun-if-changed={}", proto.display());
+    tonic_build::configure().compile(&[&proto], &[&proto_dir])?;
+    Ok(())
+}
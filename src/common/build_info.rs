@@ -0,0 +1,118 @@
+//! 빌드 정보 모듈
+//! 빌드 스크립트가 컴파일 시점에 기록한 커밋, 빌드 날짜, rustc 버전, 활성 기능
+//!
+//! 여러 버전이 섞인 배포에서 CLI와 데몬이 같은 빌드인지 확인하는 데 쓴다. git 저장소
+//! 밖에서 빌드하면 커밋은 "unknown"이고, `SOURCE_DATE_EPOCH`가 있으면 빌드 날짜로 쓴다.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::VERSION;
+
+/// 빌드한 커밋 (짧은 SHA)
+pub const GIT_SHA: &str = env!("SWIFT_GUARD_GIT_SHA");
+
+/// 빌드 날짜 (UTC, YYYY-MM-DD)
+pub const BUILD_DATE: &str = env!("SWIFT_GUARD_BUILD_DATE");
+
+/// 빌드에 쓴 컴파일러 (`rustc --version` 출력)
+pub const RUSTC_VERSION: &str = env!("SWIFT_GUARD_RUSTC_VERSION");
+
+/// 켜진 cargo 기능 (쉼표로 구분, 없으면 빈 문자열)
+pub const FEATURES: &str = env!("SWIFT_GUARD_FEATURES");
+
+/// `--version` 출력 (clap의 `long_version`에 쓰도록 컴파일 시점에 조합)
+pub const LONG_VERSION: &str = concat!(
+    env!("CARGO_PKG_VERSION"),
+    "\ncommit:   ", env!("SWIFT_GUARD_GIT_SHA"),
+    "\nbuilt:    ", env!("SWIFT_GUARD_BUILD_DATE"),
+    "\nrustc:    ", env!("SWIFT_GUARD_RUSTC_VERSION"),
+    "\nfeatures: ", env!("SWIFT_GUARD_FEATURES_DISPLAY"),
+);
+
+/// 빌드 메타데이터
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BuildInfo {
+    /// 패키지 버전 (`swift_guard::VERSION`)
+    pub version: String,
+    /// 빌드한 커밋 (짧은 SHA, 알 수 없으면 "unknown")
+    pub git_sha: String,
+    /// 빌드 날짜 (UTC, YYYY-MM-DD)
+    pub build_date: String,
+    /// 빌드에 쓴 컴파일러
+    pub rustc_version: String,
+    /// 켜진 cargo 기능
+    pub features: Vec<String>,
+}
+
+impl BuildInfo {
+    /// 현재 바이너리의 빌드 정보
+    pub fn current() -> Self {
+        Self {
+            version: VERSION.to_string(),
+            git_sha: GIT_SHA.to_string(),
+            build_date: BUILD_DATE.to_string(),
+            rustc_version: RUSTC_VERSION.to_string(),
+            features: FEATURES.split(',').filter(|name| !name.is_empty()).map(str::to_string).collect(),
+        }
+    }
+
+    /// 표시용 기능 목록 (없으면 "none")
+    pub fn features_summary(&self) -> String {
+        if self.features.is_empty() {
+            "none".to_string()
+        } else {
+            self.features.join(",")
+        }
+    }
+}
+
+impl fmt::Display for BuildInfo {
+    /// 한 줄 요약 (예: `0.1.0 (3f2a9c1d0b7e, built 2026-10-16, rustc 1.74.0, features: grpc)`)
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rustc = self.rustc_version.split(' ').take(2).collect::<Vec<_>>().join(" ");
+        write!(f, "{} ({}, built {}, {}, features: {})",
+            self.version, self.git_sha, self.build_date, rustc, self.features_summary())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serializes_all_fields() {
+        let info = BuildInfo::current();
+        let value = serde_json::to_value(&info).unwrap();
+        let fields: Vec<&str> = value.as_object().unwrap().keys().map(String::as_str).collect();
+        assert_eq!(fields.len(), 5);
+        for field in ["version", "git_sha", "build_date", "rustc_version", "features"] {
+            assert!(value.get(field).is_some(), "missing {}", field);
+        }
+        assert_eq!(value["version"], VERSION);
+        assert!(value["rustc_version"].as_str().unwrap().starts_with("rustc "));
+        assert_eq!(serde_json::from_value::<BuildInfo>(value).unwrap(), info);
+
+        // 빈 기능 목록도 필드로 남음
+        let bare = BuildInfo { features: Vec::new(), ..info };
+        assert_eq!(serde_json::to_value(&bare).unwrap()["features"], serde_json::json!([]));
+    }
+
+    #[test]
+    fn test_summary() {
+        let info = BuildInfo {
+            version: "0.1.0".to_string(),
+            git_sha: "3f2a9c1d0b7e".to_string(),
+            build_date: "2026-10-16".to_string(),
+            rustc_version: "rustc 1.74.0 (79e9716c9 2023-11-13)".to_string(),
+            features: vec!["grpc".to_string()],
+        };
+        assert_eq!(info.to_string(), "0.1.0 (3f2a9c1d0b7e, built 2026-10-16, rustc 1.74.0, features: grpc)");
+        assert_eq!(BuildInfo { features: Vec::new(), ..info }.features_summary(), "none");
+
+        // --version 출력은 같은 버전으로 시작
+        assert!(LONG_VERSION.starts_with(VERSION));
+        assert_eq!(BUILD_DATE.len(), 10);
+    }
+}
@@ -0,0 +1,1125 @@
+// Swift-Guard API Client
+// 데몬 API 서버와 통신하는 비동기 클라이언트 (CLI와 규칙을 관리하는 다른 서비스가 함께 사용)
+//
+// 요청과 응답은 4바이트 빅 엔디안 길이가 앞에 붙은 JSON 프레임으로 주고받는다.
+// 서버 주소는 TCP `host:port` 또는 Unix 소켓 `unix:/path/to/socket` 형식이다.
+
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+use rustls::client::ResolvesClientCert;
+use rustls::sign::CertifiedKey;
+use rustls::{ClientConfig, ServerName, SignatureScheme};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::{TcpStream, UnixStream};
+use tokio_rustls::TlsConnector;
+
+use crate::api::{
+    ApiRequest, ApiResponse, EventGap, ResponseFrame, RuleInfo, RuleSpec, SequencedEvent, SystemStats,
+    DEFAULT_MAX_RESPONSE_LEN, PAGINATION_HINT,
+};
+use crate::error::ErrorCode;
+
+/// 기본 요청 시간 제한 (프레임마다 적용)
+pub const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 규칙 목록을 나눠 받을 때의 페이지 크기
+pub const RULES_PAGE_SIZE: u32 = 1000;
+
+/// 이벤트 구독이 한 번에 받는 최대 이벤트 수
+pub const EVENTS_BATCH: usize = 1000;
+
+/// Unix 소켓 서버 주소의 접두사
+pub const UNIX_ADDR_PREFIX: &str = "unix:";
+
+/// 기본 재연결 시도 시간 (업그레이드 중 데몬 재시작을 기다림)
+pub const DEFAULT_RECONNECT_WINDOW: Duration = Duration::from_secs(30);
+
+/// 첫 재연결 시도 전 대기 시간 (시도마다 두 배)
+const RECONNECT_INITIAL_BACKOFF: Duration = Duration::from_millis(250);
+
+/// 재연결 시도 사이 최대 대기 시간
+const RECONNECT_MAX_BACKOFF: Duration = Duration::from_secs(5);
+
+/// 받을 수 있는 응답 프레임 최대 크기 (요청마다 데몬에 알림)
+const MAX_RESPONSE_LEN: usize = DEFAULT_MAX_RESPONSE_LEN;
+
+/// API 클라이언트 오류
+#[derive(Debug, Error)]
+pub enum ClientError {
+    /// 연결 또는 송수신 실패, 시간 초과, TLS 핸드셰이크 실패
+    #[error("{0}")]
+    Unreachable(String),
+
+    /// 소켓 접근 권한 없음
+    #[error("{0}")]
+    PermissionDenied(String),
+
+    /// 잘못된 클라이언트 설정 (TLS 서버 이름 등)
+    #[error("{0}")]
+    InvalidConfig(String),
+
+    /// 요청 직렬화 실패, 해석할 수 없거나 예상하지 못한 응답
+    #[error("{0}")]
+    Protocol(String),
+
+    /// 데몬의 요청 빈도 제한 초과
+    #[error("Rate limited by daemon ({limit} requests/sec), retry after {retry_after_ms} ms")]
+    RateLimited {
+        retry_after_ms: u64,
+        limit: u32,
+    },
+
+    /// 데몬이 돌려준 오류 응답
+    #[error("{message}")]
+    Api {
+        code: ErrorCode,
+        message: String,
+    },
+}
+
+impl ClientError {
+    /// 오류 응답(또는 예상하지 못한 응답)을 클라이언트 오류로 변환
+    pub fn from_response(response: ApiResponse) -> Self {
+        match response {
+            ApiResponse::Error { message, code } => Self::Api { code, message },
+            ApiResponse::InvalidArgument { field, message } => Self::Api {
+                code: ErrorCode::InvalidArgument,
+                message: format!("Invalid {}: {}", field, message),
+            },
+            ApiResponse::NotFound { resource, name } => Self::Api {
+                code: ErrorCode::NotFound,
+                message: format!("{} '{}' not found", resource, name),
+            },
+            ApiResponse::RateLimited { retry_after_ms, limit } => Self::RateLimited { retry_after_ms, limit },
+            ApiResponse::PartialResults { returned, total, hint, .. } => Self::Api {
+                code: ErrorCode::Internal,
+                message: format!("Response exceeded the size limit, daemon returned {} of {} items ({})", returned, total, hint),
+            },
+            _ => Self::Protocol("Unexpected response from server".to_string()),
+        }
+    }
+}
+
+/// 요청 결과
+pub type Result<T> = std::result::Result<T, ClientError>;
+
+/// 서버 주소
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Endpoint {
+    Tcp(String),
+    Unix(PathBuf),
+}
+
+impl Endpoint {
+    /// 서버 주소 해석 (`unix:` 접두사가 있으면 Unix 소켓 경로)
+    fn parse(server_addr: &str) -> Self {
+        match server_addr.strip_prefix(UNIX_ADDR_PREFIX) {
+            Some(path) => Self::Unix(PathBuf::from(path)),
+            None => Self::Tcp(server_addr.to_string()),
+        }
+    }
+}
+
+/// 데몬 API 클라이언트
+///
+/// 요청마다 새로 연결하므로 여러 작업에서 공유해도 된다.
+///
+/// ```no_run
+/// use swift_guard::api::{RuleSpec, SwiftGuardClient};
+/// use swift_guard::types::{ActionType, ProtocolType};
+///
+/// # async fn run() -> Result<(), swift_guard::api::ClientError> {
+/// let client = SwiftGuardClient::new("127.0.0.1:7654")
+///     .with_token(Some("secret".to_string()));
+///
+/// client.add_rule(RuleSpec::new("block-ssh", ActionType::Drop)
+///     .with_protocol(ProtocolType::Tcp)
+///     .with_dst_port(22)).await?;
+///
+/// for rule in client.list_rules().await? {
+///     println!("{} {} packets", rule.label, rule.stats.packets);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct SwiftGuardClient {
+    server_addr: String,
+    endpoint: Endpoint,
+    tls: Option<ClientTls>,
+    /// 요청과 함께 보낼 API 토큰
+    token: Option<String>,
+    /// 규칙 요청의 네임스페이스 (없으면 데몬이 토큰의 네임스페이스 사용)
+    namespace: Option<String>,
+    /// 프레임마다 적용하는 시간 제한
+    timeout: Duration,
+    /// 데몬이 돌려준 요청 ID를 받는 함수 (데몬 로그와 대조용)
+    request_id_hook: Option<RequestIdHook>,
+}
+
+/// 응답의 요청 ID를 받는 함수
+type RequestIdHook = Arc<dyn Fn(&str) + Send + Sync>;
+
+/// 요청 프레임 (요청에 토큰, 네임스페이스, 요청 ID를 덧붙임, `api::RequestFrame`과 같은 형식)
+#[derive(Serialize)]
+struct OutgoingFrame<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    token: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    namespace: Option<&'a str>,
+    request_id: &'a str,
+    max_response_len: usize,
+    #[serde(flatten)]
+    request: &'a ApiRequest,
+}
+
+/// 클라이언트 TLS 설정과 검증할 서버 이름
+struct ClientTls {
+    connector: TlsConnector,
+    server_name: ServerName,
+    /// 클라이언트 인증서가 없을 때 서버의 인증서 요청 기록
+    cert_request: Option<Arc<ClientCertRequest>>,
+}
+
+/// 클라이언트 인증서 없이 연결할 때 서버가 인증서를 요청했는지 기록
+///
+/// TLS 1.3에서는 서버가 클라이언트 인증서 누락을 핸드셰이크가 끝난 뒤에 알리므로
+/// 요청 송신이 경고 대신 연결 끊김으로 실패할 수 있다.
+#[derive(Debug, Default)]
+struct ClientCertRequest(AtomicBool);
+
+impl ClientCertRequest {
+    fn requested(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+impl ResolvesClientCert for ClientCertRequest {
+    fn resolve(&self, _acceptable_issuers: &[&[u8]], _sigschemes: &[SignatureScheme]) -> Option<Arc<CertifiedKey>> {
+        self.0.store(true, Ordering::Relaxed);
+        None
+    }
+
+    fn has_certs(&self) -> bool {
+        false
+    }
+}
+
+impl fmt::Debug for ClientTls {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ClientTls")
+            .field("server_name", &self.server_name)
+            .finish_non_exhaustive()
+    }
+}
+
+impl fmt::Debug for SwiftGuardClient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SwiftGuardClient")
+            .field("server_addr", &self.server_addr)
+            .field("tls", &self.tls)
+            .field("namespace", &self.namespace)
+            .field("timeout", &self.timeout)
+            .finish_non_exhaustive()
+    }
+}
+
+impl SwiftGuardClient {
+    /// 새로운 API 클라이언트 생성 (`host:port` 또는 `unix:/path/to/socket`)
+    pub fn new(server_addr: &str) -> Self {
+        Self {
+            server_addr: server_addr.to_string(),
+            endpoint: Endpoint::parse(server_addr),
+            tls: None,
+            token: None,
+            namespace: None,
+            timeout: DEFAULT_REQUEST_TIMEOUT,
+            request_id_hook: None,
+        }
+    }
+
+    /// 요청에 API 토큰 포함 (데몬 구성의 `api.tokens`)
+    pub fn with_token(mut self, token: Option<String>) -> Self {
+        self.token = token;
+        self
+    }
+
+    /// 규칙 요청의 네임스페이스 지정
+    pub fn with_namespace(mut self, namespace: Option<String>) -> Self {
+        self.namespace = namespace;
+        self
+    }
+
+    /// 연결과 응답 프레임마다 기다리는 시간 (기본 10초)
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// 응답마다 데몬이 돌려준 요청 ID를 `hook`으로 전달 (데몬 로그와 대조용)
+    pub fn with_request_id_hook(mut self, hook: impl Fn(&str) + Send + Sync + 'static) -> Self {
+        self.request_id_hook = Some(Arc::new(hook));
+        self
+    }
+
+    /// TLS 사용 설정 (서버 이름은 API 서버 주소의 호스트 부분, Unix 소켓은 지원하지 않음)
+    pub fn with_tls(mut self, config: Arc<ClientConfig>) -> Result<Self> {
+        if let Endpoint::Unix(_) = self.endpoint {
+            return Err(ClientError::InvalidConfig(format!(
+                "TLS is not supported for Unix socket API server {}", self.server_addr)));
+        }
+
+        let host = server_host(&self.server_addr);
+        let server_name = ServerName::try_from(host)
+            .map_err(|_| ClientError::InvalidConfig(format!("Invalid TLS server name: {}", host)))?;
+
+        // 클라이언트 인증서가 없으면 서버의 인증서 요청 여부를 기록
+        let mut config = (*config).clone();
+        let cert_request = if config.client_auth_cert_resolver.has_certs() {
+            None
+        } else {
+            let cert_request = Arc::new(ClientCertRequest::default());
+            config.client_auth_cert_resolver = cert_request.clone();
+            Some(cert_request)
+        };
+
+        self.tls = Some(ClientTls {
+            connector: TlsConnector::from(Arc::new(config)),
+            server_name,
+            cert_request,
+        });
+        Ok(self)
+    }
+
+    /// 서버 주소
+    pub fn server_addr(&self) -> &str {
+        &self.server_addr
+    }
+
+    /// 규칙 추가 (성공하면 데몬의 경고, 예: 연결된 인터페이스 없음)
+    pub async fn add_rule(&self, spec: RuleSpec) -> Result<Option<String>> {
+        match self.send_request(&spec.into_add_request()).await? {
+            ApiResponse::Success { warning, .. } => Ok(warning),
+            other => Err(ClientError::from_response(other)),
+        }
+    }
+
+    /// 레이블로 규칙 삭제
+    pub async fn delete_rule(&self, label: &str) -> Result<()> {
+        match self.send_request(&ApiRequest::DeleteRule { label: label.to_string() }).await? {
+            ApiResponse::Success { .. } => Ok(()),
+            other => Err(ClientError::from_response(other)),
+        }
+    }
+
+    /// 네임스페이스의 모든 규칙과 통계 조회 (규칙 키 순서, 동적 차단 규칙 포함)
+    pub async fn list_rules(&self) -> Result<Vec<RuleInfo>> {
+        self.list_all_rules(ApiRequest::ListRules {
+            include_stats: true,
+            expiring_within_secs: None,
+            include_rates: false,
+            all_namespaces: false,
+            family: None,
+            include_dynamic: true,
+            origin: None,
+            cursor: None,
+            offset: 0,
+            limit: RULES_PAGE_SIZE,
+        }).await
+    }
+
+    /// 시스템 통계 조회
+    pub async fn get_stats(&self) -> Result<SystemStats> {
+        match self.send_request(&ApiRequest::GetStats {}).await? {
+            ApiResponse::Stats { stats } => Ok(stats),
+            other => Err(ClientError::from_response(other)),
+        }
+    }
+
+    /// 데몬 이벤트 구독 (기본은 구독 이후의 새 이벤트만, `EventSubscription::next`로 차례로 받음)
+    pub fn subscribe_events(&self) -> EventSubscription<'_> {
+        EventSubscription {
+            client: self,
+            since_seq: None,
+            replay: 0,
+            kinds: Vec::new(),
+            interval: Duration::from_secs(1),
+            batch: EVENTS_BATCH,
+            caught_up: false,
+            reconnect: None,
+        }
+    }
+
+    /// 요청 전송 및 응답 수신
+    ///
+    /// 데몬의 오류 응답도 `Ok`로 돌려주므로 호출자가 응답 종류를 확인한다.
+    pub async fn send_request(&self, request: &ApiRequest) -> Result<ApiResponse> {
+        self.send_request_with_progress(request, |_, _| {}).await
+    }
+
+    /// 요청 전송 후 최종 응답 수신 (그 전에 오는 `Progress` 프레임은 `on_progress(done, total)`로 전달)
+    ///
+    /// 시간 제한은 프레임마다 적용되므로 진행 상황을 계속 보내는 긴 작업은 끝까지 기다린다.
+    pub async fn send_request_with_progress<F>(&self, request: &ApiRequest, mut on_progress: F) -> Result<ApiResponse>
+    where
+        F: FnMut(usize, usize),
+    {
+        let request_bytes = self.encode(request)?;
+        let response_bytes = self.exchange(&request_bytes, &mut on_progress).await
+            .map_err(|e| self.request_error(e))?;
+        self.decode(&response_bytes)
+    }
+
+    /// 구독 요청 전송 (응답 프레임은 `Subscription::next`로 차례로 받음)
+    ///
+    /// 연결과 첫 프레임에만 시간 제한을 두고, 이후 프레임은 올 때까지 기다린다.
+    pub async fn subscribe(&self, request: &ApiRequest) -> Result<Subscription<'_>> {
+        let request_bytes = self.encode(request)?;
+        let send = async {
+            let mut stream = self.connect().await?;
+            within_timeout(self.timeout, write_frame(&mut stream, &request_bytes)).await?;
+            Ok(stream)
+        };
+        let stream = send.await.map_err(|e| self.request_error(e))?;
+
+        Ok(Subscription { client: self, stream, received: false })
+    }
+
+    /// `ListRules` 요청의 모든 페이지를 커서로 이어 받아 합침
+    ///
+    /// 페이지는 규칙 키 순서로 오므로 표시 순서는 호출자가 정한다. 페이지를 나누지 않는
+    /// 이전 데몬은 한 번에 모든 규칙을 돌려주므로 첫 응답에서 끝난다.
+    pub async fn list_all_rules(&self, request: ApiRequest) -> Result<Vec<RuleInfo>> {
+        self.collect_rules(request, None).await.map(|(rules, _)| rules)
+    }
+
+    /// `ListRules` 요청의 한 페이지 조회 (규칙과 페이지로 나누기 전 규칙 수)
+    ///
+    /// 페이지가 응답 크기 제한을 넘으면 요청한 규칙 수를 채울 때까지 더 작은 페이지로 이어 받는다.
+    pub async fn list_rules_page(&self, request: ApiRequest) -> Result<(Vec<RuleInfo>, usize)> {
+        let wanted = match &request {
+            ApiRequest::ListRules { limit, .. } if *limit > 0 => Some(*limit as usize),
+            _ => None,
+        };
+        self.collect_rules(request, wanted).await
+    }
+
+    /// `ListRules` 응답을 커서로 이어 받음 (`wanted`개를 채우면 멈춤, None이면 마지막 페이지까지)
+    ///
+    /// 데몬이 응답 크기 제한 때문에 `PartialResults`로 답하면 담을 수 있었던 규칙 수로 페이지
+    /// 크기를 줄여 같은 위치부터 다시 요청한다.
+    async fn collect_rules(&self, mut request: ApiRequest, wanted: Option<usize>) -> Result<(Vec<RuleInfo>, usize)> {
+        let mut rules = Vec::new();
+
+        loop {
+            let (page, total_count, next_cursor) = match self.send_request(&request).await? {
+                ApiResponse::Rules { rules, total_count, next_cursor, .. } => (rules, total_count, next_cursor),
+                ApiResponse::PartialResults { returned, total, hint, .. } if hint == PAGINATION_HINT => {
+                    shrink_page(&mut request, returned, total)?;
+                    continue;
+                },
+                other => return Err(ClientError::from_response(other)),
+            };
+            rules.extend(page);
+            let remaining = wanted.map(|wanted| wanted.saturating_sub(rules.len()));
+
+            match (next_cursor, &mut request) {
+                (Some(next), ApiRequest::ListRules { cursor, offset, limit, .. }) if remaining != Some(0) => {
+                    if cursor.as_deref() == Some(next.as_str()) {
+                        return Err(ClientError::Protocol(format!(
+                            "API server returned the same rule cursor twice ({})", next)));
+                    }
+                    *cursor = Some(next);
+                    *offset = 0;
+                    if let Some(remaining) = remaining {
+                        *limit = (*limit).min(remaining as u32);
+                    }
+                }
+                _ => return Ok((rules, total_count)),
+            }
+        }
+    }
+
+    /// 요청 직렬화 (요청마다 새 요청 ID)
+    fn encode(&self, request: &ApiRequest) -> Result<Vec<u8>> {
+        let request_id = crate::utils::new_request_id();
+        let frame = OutgoingFrame {
+            token: self.token.as_deref(),
+            namespace: self.namespace.as_deref(),
+            request_id: &request_id,
+            max_response_len: MAX_RESPONSE_LEN,
+            request,
+        };
+        serde_json::to_vec(&frame)
+            .map_err(|e| ClientError::Protocol(format!("Failed to serialize request: {}", e)))
+    }
+
+    /// 응답 역직렬화
+    fn decode(&self, response_bytes: &[u8]) -> Result<ApiResponse> {
+        let frame: ResponseFrame = serde_json::from_slice(response_bytes)
+            .map_err(|e| ClientError::Protocol(format!("Failed to deserialize response: {}", e)))?;
+        if let (Some(hook), Some(request_id)) = (&self.request_id_hook, &frame.request_id) {
+            hook(request_id);
+        }
+
+        Ok(frame.response)
+    }
+
+    /// 송수신 오류를 클라이언트 오류로 변환 (시간 초과 포함)
+    fn request_error(&self, error: std::io::Error) -> ClientError {
+        match error.kind() {
+            std::io::ErrorKind::TimedOut => ClientError::Unreachable(format!(
+                "API server {} did not respond within {}s", self.server_addr, self.timeout.as_secs())),
+            _ => self.transport_error(error),
+        }
+    }
+
+    /// 전송 오류를 클라이언트 오류로 변환
+    ///
+    /// TLS 핸드셰이크 실패(만료, 이름 불일치, 클라이언트 인증서 거부 등)는
+    /// 원인과 조치를 설명하는 메시지로 바꾼다.
+    fn transport_error(&self, error: std::io::Error) -> ClientError {
+        let host = server_host(&self.server_addr);
+        if let Some(reason) = crate::tls::describe_handshake_error(&error, host) {
+            return ClientError::Unreachable(format!(
+                "TLS handshake with API server {} failed: {}", self.server_addr, reason));
+        }
+
+        // 인증서 요청을 받고도 제시하지 않은 뒤 연결이 끊긴 경우 (TLS 1.3)
+        let cert_requested = self.tls.as_ref()
+            .and_then(|tls| tls.cert_request.as_ref())
+            .is_some_and(|request| request.requested());
+        let disconnected = matches!(error.kind(),
+            std::io::ErrorKind::BrokenPipe | std::io::ErrorKind::ConnectionReset | std::io::ErrorKind::UnexpectedEof);
+        if cert_requested && disconnected {
+            return ClientError::Unreachable(format!(
+                "TLS handshake with API server {} failed: {}", self.server_addr, crate::tls::CLIENT_CERT_REQUIRED));
+        }
+
+        let message = format!("Failed to communicate with API server {}: {}", self.server_addr, error);
+        if error.kind() == std::io::ErrorKind::PermissionDenied {
+            ClientError::PermissionDenied(message)
+        } else {
+            ClientError::Unreachable(message)
+        }
+    }
+
+    /// 서버에 연결 후 요청 교환
+    async fn exchange(&self, request_bytes: &[u8], on_progress: &mut dyn FnMut(usize, usize)) -> std::io::Result<Vec<u8>> {
+        let stream = self.connect().await?;
+        exchange_framed(stream, request_bytes, self.timeout, on_progress).await
+    }
+
+    /// 서버에 연결 (TLS 설정 시 핸드셰이크 포함)
+    async fn connect(&self) -> std::io::Result<Box<dyn Connection>> {
+        let stream = match &self.endpoint {
+            Endpoint::Tcp(addr) => within_timeout(self.timeout, TcpStream::connect(addr)).await?,
+            Endpoint::Unix(path) => {
+                return Ok(Box::new(within_timeout(self.timeout, UnixStream::connect(path)).await?));
+            },
+        };
+
+        match &self.tls {
+            Some(tls) => Ok(Box::new(within_timeout(self.timeout, tls.connector.connect(tls.server_name.clone(), stream)).await?)),
+            None => Ok(Box::new(stream)),
+        }
+    }
+}
+
+/// 평문 또는 TLS 연결
+trait Connection: AsyncRead + AsyncWrite + Unpin + Send {}
+
+impl<S: AsyncRead + AsyncWrite + Unpin + Send> Connection for S {}
+
+/// 구독 요청의 응답 프레임 수신기
+pub struct Subscription<'c> {
+    client: &'c SwiftGuardClient,
+    stream: Box<dyn Connection>,
+    /// 첫 프레임을 받았는지 (첫 프레임에만 시간 제한 적용)
+    received: bool,
+}
+
+impl Subscription<'_> {
+    /// 다음 응답 프레임 (데몬이 연결을 닫으면 None)
+    pub async fn next(&mut self) -> Result<Option<ApiResponse>> {
+        let read = if self.received {
+            read_frame(&mut self.stream).await
+        } else {
+            within_timeout(self.client.timeout, read_frame(&mut self.stream)).await
+        };
+
+        let response_bytes = match read {
+            Ok(bytes) => bytes,
+            Err(e) if self.received && e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+            Err(e) => return Err(self.client.request_error(e)),
+        };
+        self.received = true;
+        self.client.decode(&response_bytes).map(Some)
+    }
+}
+
+/// 데몬 이벤트 구독 (`SwiftGuardClient::subscribe_events`)
+///
+/// 데몬은 이벤트를 밀어 보내지 않으므로 `GetEvents`를 주기적으로 보내 마지막으로 받은
+/// 순번 뒤의 이벤트를 받는다. 응답이 가득 찼거나 크기 제한으로 잘렸으면 기다리지 않고
+/// 바로 이어서 조회한다.
+#[derive(Debug)]
+pub struct EventSubscription<'c> {
+    client: &'c SwiftGuardClient,
+    /// 다음 조회의 기준 순번 (None이면 아직 조회 전이고 최근 `replay`개부터 받음)
+    since_seq: Option<u64>,
+    replay: usize,
+    kinds: Vec<String>,
+    interval: Duration,
+    /// 한 번에 받을 이벤트 수 (응답 크기 제한에 걸리면 줄어듦)
+    batch: usize,
+    /// 직전 조회가 최신 이벤트까지 받았는지 (다음 조회 전에 `interval`만큼 기다림)
+    caught_up: bool,
+    /// 연결이 끊기면 같은 순번부터 다시 조회할 재연결 상태
+    reconnect: Option<Reconnector>,
+}
+
+/// 이벤트 조회 한 번의 결과
+#[derive(Debug, Clone, PartialEq)]
+pub struct EventBatch {
+    /// 받은 이벤트 (오래된 것부터, 새 이벤트가 없으면 비어 있음)
+    pub events: Vec<SequencedEvent>,
+    /// 기록에서 밀려나 받지 못한 구간
+    pub gap: Option<EventGap>,
+    /// 최신 이벤트까지 받았는지 (아니면 다음 `next`가 기다리지 않고 이어서 조회)
+    pub caught_up: bool,
+    /// 연결이 끊겼다가 다시 연결된 뒤 첫 조회면 재연결 결과 (`events`는 끊긴 동안의 이벤트부터)
+    pub reconnected: Option<Reconnected>,
+}
+
+impl EventSubscription<'_> {
+    /// 이 순번 이후의 이벤트부터 받음 (재연결 시 마지막으로 받은 순번)
+    pub fn with_since_seq(mut self, since_seq: Option<u64>) -> Self {
+        self.since_seq = since_seq;
+        self
+    }
+
+    /// 순번을 지정하지 않았을 때 새 이벤트 전에 받을 최근 이벤트 수
+    pub fn with_replay(mut self, replay: usize) -> Self {
+        self.replay = replay;
+        self
+    }
+
+    /// 받을 이벤트 종류 (`DaemonEvent::kind`, 비어 있으면 모두)
+    pub fn with_kinds(mut self, kinds: Vec<String>) -> Self {
+        self.kinds = kinds;
+        self
+    }
+
+    /// 최신 이벤트까지 받은 뒤 다음 조회까지 기다리는 시간 (기본 1초)
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// 데몬이 재시작되면 정책에 따라 다시 연결해 마지막으로 받은 순번부터 이어서 조회
+    pub fn with_reconnect(mut self, policy: ReconnectPolicy) -> Self {
+        self.reconnect = Some(Reconnector::new(policy));
+        self
+    }
+
+    /// 다음 이벤트 묶음 조회 (최신 이벤트까지 받은 뒤에는 `interval`만큼 기다린 뒤 조회)
+    pub async fn next(&mut self) -> Result<EventBatch> {
+        if self.caught_up {
+            tokio::time::sleep(self.interval).await;
+        }
+
+        let limit = if self.since_seq.is_some() { self.batch } else { self.replay };
+        let request = ApiRequest::GetEvents { since_seq: self.since_seq, limit, kinds: self.kinds.clone() };
+
+        let client = self.client;
+        let (response, reconnected) = match &mut self.reconnect {
+            Some(reconnector) => reconnector.run(|| client.send_request(&request)).await?,
+            None => (client.send_request(&request).await?, None),
+        };
+
+        // 응답 크기 제한으로 잘렸으면 받은 앞부분을 돌려주고 더 작은 묶음으로 이어서 조회
+        let (response, truncated) = match response {
+            ApiResponse::PartialResults { returned, hint, results, .. } if hint == PAGINATION_HINT && returned > 0 => {
+                self.batch = self.batch.min(returned);
+                (*results, true)
+            },
+            other => (other, false),
+        };
+        let (events, gap, latest_seq) = match response {
+            ApiResponse::Events { events, gap, latest_seq } => (events, gap, latest_seq),
+            other => return Err(ClientError::from_response(other)),
+        };
+
+        // 응답이 가득 찼으면 마지막 이벤트 다음부터 바로 이어서 조회
+        let full = truncated || (self.since_seq.is_some() && limit > 0 && events.len() == limit);
+        self.since_seq = Some(match events.last() {
+            Some(last) if full => last.seq,
+            _ => latest_seq,
+        });
+        self.caught_up = !full;
+
+        Ok(EventBatch { events, gap, caught_up: !full, reconnected })
+    }
+}
+
+/// 재연결 정책
+///
+/// 연결이 끊긴 뒤 `window` 동안 `initial_backoff`부터 두 배씩(최대 `max_backoff`) 기다리며
+/// 다시 시도한다. `window`가 0이면 다시 시도하지 않는다.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReconnectPolicy {
+    pub window: Duration,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl ReconnectPolicy {
+    /// 재연결 시도 시간이 `window`인 정책 (대기 시간은 기본값)
+    pub fn new(window: Duration) -> Self {
+        Self { window, initial_backoff: RECONNECT_INITIAL_BACKOFF, max_backoff: RECONNECT_MAX_BACKOFF }
+    }
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self::new(DEFAULT_RECONNECT_WINDOW)
+    }
+}
+
+/// 재연결 결과
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Reconnected {
+    /// 실패한 시도 수 (연결이 끊긴 것을 처음 알아챈 요청 포함)
+    pub attempts: u32,
+    /// 처음 실패한 뒤 다시 연결될 때까지 걸린 시간
+    pub downtime: Duration,
+}
+
+/// 오래 실행되는 요청 흐름(주기적 조회, 구독)의 재연결 상태
+///
+/// 한 번이라도 성공한 뒤 서버에 닿지 못하게 된 경우(`ClientError::Unreachable`)만 다시
+/// 시도한다. 처음부터 연결하지 못했거나 데몬이 오류로 답하면 바로 실패한다. 요청마다 새로
+/// 연결하므로 다시 시도할 때 TLS 핸드셰이크도 새로 한다.
+#[derive(Debug, Clone)]
+pub struct Reconnector {
+    policy: ReconnectPolicy,
+    /// 성공한 요청이 있었는지
+    connected: bool,
+    /// 연결이 끊긴 시각과 그 뒤 실패한 시도 수
+    outage: Option<(Instant, u32)>,
+}
+
+impl Reconnector {
+    pub fn new(policy: ReconnectPolicy) -> Self {
+        Self { policy, connected: false, outage: None }
+    }
+
+    /// 요청 실패 반영 (다시 시도하기 전 기다릴 시간, 다시 시도하지 않으면 None)
+    pub fn on_failure(&mut self, error: &ClientError, now: Instant) -> Option<Duration> {
+        if !self.connected || !matches!(error, ClientError::Unreachable(_)) {
+            return None;
+        }
+
+        let (since, attempts) = self.outage.get_or_insert((now, 0));
+        *attempts += 1;
+        let remaining = self.policy.window.checked_sub(now.saturating_duration_since(*since))
+            .filter(|remaining| !remaining.is_zero())?;
+        let backoff = self.policy.initial_backoff
+            .saturating_mul(1 << (*attempts - 1).min(16))
+            .min(self.policy.max_backoff);
+        Some(backoff.min(remaining))
+    }
+
+    /// 요청 성공 반영 (연결이 끊겼다가 다시 연결됐으면 재연결 결과)
+    pub fn on_success(&mut self, now: Instant) -> Option<Reconnected> {
+        self.connected = true;
+        self.outage.take().map(|(since, attempts)| Reconnected {
+            attempts,
+            downtime: now.saturating_duration_since(since),
+        })
+    }
+
+    /// 성공하거나 재연결을 포기할 때까지 `send`를 다시 시도
+    pub async fn run<T, F, Fut>(&mut self, mut send: F) -> Result<(T, Option<Reconnected>)>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        loop {
+            match send().await {
+                Ok(value) => return Ok((value, self.on_success(Instant::now()))),
+                Err(e) => match self.on_failure(&e, Instant::now()) {
+                    Some(delay) => tokio::time::sleep(delay).await,
+                    None => return Err(e),
+                },
+            }
+        }
+    }
+}
+
+/// 응답 크기 제한에 걸린 `ListRules`의 페이지 크기를 데몬이 담을 수 있었던 규칙 수로 줄임
+///
+/// 규칙 하나도 담지 못했거나 페이지가 줄지 않으면 더 나눌 수 없으므로 오류다.
+fn shrink_page(request: &mut ApiRequest, returned: usize, total: usize) -> Result<()> {
+    let returned = u32::try_from(returned).unwrap_or(u32::MAX);
+    match request {
+        ApiRequest::ListRules { limit, .. } if returned > 0 && (*limit == 0 || returned < *limit) => {
+            *limit = returned;
+            Ok(())
+        },
+        _ => Err(ClientError::Protocol(format!(
+            "API server could not fit the rule list into a response ({} of {} rules)", returned, total))),
+    }
+}
+
+/// 서버 주소에서 호스트 부분 추출 ("[::1]:7654" → "::1")
+fn server_host(server_addr: &str) -> &str {
+    let host = server_addr.rsplit_once(':')
+        .map(|(host, _)| host)
+        .unwrap_or(server_addr);
+    host.trim_start_matches('[').trim_end_matches(']')
+}
+
+/// 시간 제한 안에 끝나지 않으면 `TimedOut` 오류
+async fn within_timeout<T>(timeout: Duration, operation: impl std::future::Future<Output = std::io::Result<T>>) -> std::io::Result<T> {
+    tokio::time::timeout(timeout, operation)
+        .await
+        .unwrap_or_else(|_| Err(std::io::ErrorKind::TimedOut.into()))
+}
+
+/// 진행 상황 프레임 판별용 (다른 응답은 `Progress` 항목이 없음)
+#[derive(Deserialize)]
+struct ProgressFrame {
+    #[serde(rename = "Progress")]
+    progress: Option<Progress>,
+}
+
+#[derive(Deserialize)]
+struct Progress {
+    done: usize,
+    total: usize,
+}
+
+/// 길이 접두 요청 송신 및 응답 수신 (진행 상황 프레임은 `on_progress`로 넘기고 최종 응답 반환)
+async fn exchange_framed<S>(mut stream: S, request_bytes: &[u8], timeout: Duration, on_progress: &mut dyn FnMut(usize, usize))
+    -> std::io::Result<Vec<u8>>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    within_timeout(timeout, write_frame(&mut stream, request_bytes)).await?;
+
+    loop {
+        let response_bytes = within_timeout(timeout, read_frame(&mut stream)).await?;
+        match serde_json::from_slice::<ProgressFrame>(&response_bytes) {
+            Ok(ProgressFrame { progress: Some(Progress { done, total }) }) => on_progress(done, total),
+            _ => return Ok(response_bytes),
+        }
+    }
+}
+
+/// 요청 프레임 송신
+async fn write_frame<S>(stream: &mut S, request_bytes: &[u8]) -> std::io::Result<()>
+where
+    S: AsyncWrite + Unpin,
+{
+    // 요청 길이 전송 (4바이트 빅 엔디안)
+    let len = request_bytes.len() as u32;
+    stream.write_all(&len.to_be_bytes()).await?;
+
+    // 요청 내용 전송
+    stream.write_all(request_bytes).await
+}
+
+/// 응답 프레임 하나 수신
+async fn read_frame<S>(stream: &mut S) -> std::io::Result<Vec<u8>>
+where
+    S: AsyncRead + Unpin,
+{
+    // 응답 길이 수신 (4바이트 빅 엔디안)
+    let mut len_bytes = [0u8; 4];
+    stream.read_exact(&mut len_bytes).await?;
+    let len = u32::from_be_bytes(len_bytes) as usize;
+
+    // 크기 제한을 알리지 않던 이전 데몬의 지나치게 큰 응답은 읽지 않음
+    if len > MAX_RESPONSE_LEN {
+        return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, format!(
+            "response of {} bytes exceeds the {} byte limit", len, MAX_RESPONSE_LEN)));
+    }
+
+    // 응답 내용 수신
+    let mut response_bytes = vec![0u8; len];
+    stream.read_exact(&mut response_bytes).await?;
+
+    Ok(response_bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::{DaemonEvent, LinkStateEvent, RequestFrame};
+    use crate::types::ActionType;
+    use tokio::net::{TcpListener, UnixListener};
+
+    /// 요청 프레임 하나를 받아 `respond`의 응답을 돌려주는 서버 한 번 처리
+    async fn serve_one<S>(mut stream: S, respond: impl FnOnce(RequestFrame) -> ApiResponse)
+    where
+        S: AsyncRead + AsyncWrite + Unpin,
+    {
+        let request = read_frame(&mut stream).await.unwrap();
+        let frame: RequestFrame = serde_json::from_slice(&request).unwrap();
+        let request_id = frame.request_id.clone();
+        let response = ResponseFrame { request_id, response: respond(frame) };
+        write_frame(&mut stream, &serde_json::to_vec(&response).unwrap()).await.unwrap();
+    }
+
+    fn event(seq: u64) -> SequencedEvent {
+        SequencedEvent {
+            seq,
+            event: DaemonEvent::LinkState(LinkStateEvent {
+                interface: "eth0".to_string(),
+                state: "up".to_string(),
+                action: None,
+                rules: Vec::new(),
+                timestamp: seq,
+            }),
+        }
+    }
+
+    #[test]
+    fn test_server_host() {
+        assert_eq!(server_host("127.0.0.1:7654"), "127.0.0.1");
+        assert_eq!(server_host("guard.example.com:7654"), "guard.example.com");
+        assert_eq!(server_host("[::1]:7654"), "::1");
+        assert_eq!(server_host("localhost"), "localhost");
+
+        assert_eq!(Endpoint::parse("unix:/run/swift-guard.sock"), Endpoint::Unix(PathBuf::from("/run/swift-guard.sock")));
+        assert_eq!(Endpoint::parse("127.0.0.1:7654"), Endpoint::Tcp("127.0.0.1:7654".to_string()));
+    }
+
+    #[test]
+    fn test_error_from_response() {
+        let error = ClientError::from_response(ApiResponse::NotFound { resource: "rule".to_string(), name: "web".to_string() });
+        assert!(matches!(&error, ClientError::Api { code: ErrorCode::NotFound, .. }));
+        assert_eq!(error.to_string(), "rule 'web' not found");
+
+        let error = ClientError::from_response(ApiResponse::InvalidArgument { field: "label".to_string(), message: "too long".to_string() });
+        assert!(matches!(&error, ClientError::Api { code: ErrorCode::InvalidArgument, .. }));
+        assert_eq!(error.to_string(), "Invalid label: too long");
+
+        let error = ClientError::from_response(ApiResponse::RateLimited { retry_after_ms: 250, limit: 4 });
+        assert_eq!(error.to_string(), "Rate limited by daemon (4 requests/sec), retry after 250 ms");
+
+        let error = ClientError::from_response(ApiResponse::Success { message: "ok".to_string(), warning: None });
+        assert!(matches!(error, ClientError::Protocol(_)));
+    }
+
+    #[tokio::test]
+    async fn test_add_rule_over_tcp() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            serve_one(stream, |frame| {
+                assert_eq!(frame.token.as_deref(), Some("secret"));
+                assert_eq!(frame.namespace.as_deref(), Some("team-a"));
+                assert_eq!(frame.max_response_len, Some(MAX_RESPONSE_LEN));
+                match frame.request {
+                    ApiRequest::AddRule(rule) => {
+                        assert_eq!((rule.label.as_str(), rule.action, rule.protocol, rule.dst_port_min, rule.dst_port_max),
+                            ("ssh", 2, 6, 22, 22));
+                    },
+                    other => panic!("unexpected request: {:?}", other),
+                }
+                ApiResponse::Success { message: "added".to_string(), warning: Some("no interfaces".to_string()) }
+            }).await;
+        });
+
+        let seen = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let hook_seen = seen.clone();
+        let client = SwiftGuardClient::new(&addr)
+            .with_token(Some("secret".to_string()))
+            .with_namespace(Some("team-a".to_string()))
+            .with_request_id_hook(move |id| hook_seen.lock().unwrap().push(id.to_string()));
+        let spec = RuleSpec::new("ssh", ActionType::Drop)
+            .with_protocol(crate::types::ProtocolType::Tcp)
+            .with_dst_port(22);
+
+        assert_eq!(client.add_rule(spec).await.unwrap().as_deref(), Some("no interfaces"));
+        server.await.unwrap();
+        assert_eq!(seen.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_unix_socket_and_errors() {
+        let dir = std::env::temp_dir().join(format!("swift-guard-client-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("api.sock");
+        let _ = std::fs::remove_file(&path);
+        let listener = UnixListener::bind(&path).unwrap();
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            serve_one(stream, |_| ApiResponse::NotFound { resource: "rule".to_string(), name: "web".to_string() }).await;
+        });
+
+        let client = SwiftGuardClient::new(&format!("unix:{}", path.display()));
+        let error = client.delete_rule("web").await.unwrap_err();
+        assert!(matches!(error, ClientError::Api { code: ErrorCode::NotFound, .. }));
+        server.await.unwrap();
+
+        // 서버가 없으면 연결 실패
+        std::fs::remove_file(&path).unwrap();
+        assert!(matches!(client.get_stats().await.unwrap_err(), ClientError::Unreachable(_)));
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        // Unix 소켓에는 TLS를 쓸 수 없음
+        let config = rustls::ClientConfig::builder()
+            .with_safe_defaults()
+            .with_root_certificates(rustls::RootCertStore::empty())
+            .with_no_client_auth();
+        assert!(matches!(client.with_tls(Arc::new(config)), Err(ClientError::InvalidConfig(_))));
+    }
+
+    #[tokio::test]
+    async fn test_event_subscription_follows() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        let server = tokio::spawn(async move {
+            let mut requests = Vec::new();
+            let responses = [
+                // 최근 2개 재생
+                ApiResponse::Events { events: vec![event(4), event(5)], gap: None, latest_seq: 5 },
+                // 묶음이 가득 참 (batch 2)
+                ApiResponse::PartialResults {
+                    returned: 2,
+                    total: 3,
+                    hint: PAGINATION_HINT.to_string(),
+                    results: Box::new(ApiResponse::Events { events: vec![event(6), event(7)], gap: None, latest_seq: 8 }),
+                },
+                ApiResponse::Events { events: vec![event(8)], gap: None, latest_seq: 8 },
+            ];
+            for response in responses {
+                let (stream, _) = listener.accept().await.unwrap();
+                serve_one(stream, |frame| {
+                    requests.push(frame.request);
+                    response
+                }).await;
+            }
+            requests
+        });
+
+        let client = SwiftGuardClient::new(&addr);
+        let mut events = client.subscribe_events()
+            .with_replay(2)
+            .with_kinds(vec!["link_state".to_string()])
+            .with_interval(Duration::from_millis(10));
+
+        let first = events.next().await.unwrap();
+        assert_eq!((first.events.len(), first.caught_up), (2, true));
+        let second = events.next().await.unwrap();
+        assert_eq!((second.events.last().map(|e| e.seq), second.caught_up), (Some(7), false));
+        let third = events.next().await.unwrap();
+        assert_eq!((third.events[0].seq, third.caught_up), (8, true));
+
+        let requests: Vec<(Option<u64>, usize)> = server.await.unwrap().into_iter()
+            .map(|request| match request {
+                ApiRequest::GetEvents { since_seq, limit, kinds } => {
+                    assert_eq!(kinds, ["link_state"]);
+                    (since_seq, limit)
+                },
+                other => panic!("unexpected request: {:?}", other),
+            })
+            .collect();
+        assert_eq!(requests, [(None, 2), (Some(5), EVENTS_BATCH), (Some(7), 2)]);
+    }
+
+    #[test]
+    fn test_reconnector() {
+        let unreachable = ClientError::Unreachable("connection refused".to_string());
+        let start = Instant::now();
+        let policy = ReconnectPolicy { window: Duration::from_secs(20), ..Default::default() };
+
+        // 처음부터 연결하지 못했거나 데몬의 오류 응답이면 다시 시도하지 않음
+        let mut reconnector = Reconnector::new(policy);
+        assert_eq!(reconnector.on_failure(&unreachable, start), None);
+        assert_eq!(reconnector.on_success(start), None);
+        let denied = ClientError::Api { code: ErrorCode::PermissionDenied, message: "denied".to_string() };
+        assert_eq!(reconnector.on_failure(&denied, start), None);
+
+        // 대기 시간은 두 배씩 늘고 최대값과 남은 시간을 넘지 않음
+        let at = |millis| start + Duration::from_millis(millis);
+        let delays: Vec<Option<Duration>> = [0, 250, 750, 1750, 3750, 7750, 19_900, 20_000].into_iter()
+            .map(|millis| reconnector.on_failure(&unreachable, at(millis)))
+            .collect();
+        assert_eq!(delays, [250, 500, 1000, 2000, 4000, 5000, 100].into_iter()
+            .map(|millis| Some(Duration::from_millis(millis)))
+            .chain([None])
+            .collect::<Vec<_>>());
+
+        // 다시 연결되면 결과를 한 번 돌려주고 다음 끊김은 처음부터 셈
+        assert_eq!(reconnector.on_success(at(20_500)), Some(Reconnected { attempts: 8, downtime: Duration::from_millis(20_500) }));
+        assert_eq!(reconnector.on_success(at(21_000)), None);
+        assert_eq!(reconnector.on_failure(&unreachable, at(22_000)), Some(Duration::from_millis(250)));
+
+        // 시도 시간이 0이면 다시 시도하지 않음
+        let mut disabled = Reconnector::new(ReconnectPolicy::new(Duration::ZERO));
+        disabled.on_success(start);
+        assert_eq!(disabled.on_failure(&unreachable, start), None);
+    }
+
+    #[tokio::test]
+    async fn test_event_subscription_reconnects() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tokio::spawn(async move {
+            let mut requests = Vec::new();
+            let (stream, _) = listener.accept().await.unwrap();
+            serve_one(stream, |frame| {
+                requests.push(frame.request);
+                ApiResponse::Events { events: vec![event(1), event(2)], gap: None, latest_seq: 2 }
+            }).await;
+
+            // 재시작: 다음 요청을 받지 않고 연결을 끊은 뒤 잠시 멈췄다가 같은 주소로 다시 수신
+            let (stream, _) = listener.accept().await.unwrap();
+            drop(stream);
+            drop(listener);
+            tokio::time::sleep(Duration::from_millis(300)).await;
+            let listener = TcpListener::bind(addr).await.unwrap();
+            let (stream, _) = listener.accept().await.unwrap();
+            serve_one(stream, |frame| {
+                requests.push(frame.request);
+                ApiResponse::Events { events: vec![event(1), event(2), event(3)], gap: None, latest_seq: 3 }
+            }).await;
+            requests
+        });
+
+        let client = SwiftGuardClient::new(&addr.to_string());
+        let policy = ReconnectPolicy { initial_backoff: Duration::from_millis(50), ..Default::default() };
+        let mut events = client.subscribe_events()
+            .with_interval(Duration::from_millis(10))
+            .with_reconnect(policy);
+
+        let first = events.next().await.unwrap();
+        assert_eq!((first.events.len(), first.reconnected), (2, None));
+        let second = events.next().await.unwrap();
+        let reconnected = second.reconnected.unwrap();
+        assert!(reconnected.attempts >= 2, "{:?}", reconnected);
+        assert!(reconnected.downtime >= Duration::from_millis(250), "{:?}", reconnected);
+        assert_eq!(second.events.len(), 3);
+
+        // 끊기기 전 마지막으로 받은 순번부터 다시 조회
+        let requests: Vec<Option<u64>> = server.await.unwrap().into_iter()
+            .map(|request| match request {
+                ApiRequest::GetEvents { since_seq, .. } => since_seq,
+                other => panic!("unexpected request: {:?}", other),
+            })
+            .collect();
+        assert_eq!(requests, [None, Some(2)]);
+    }
+}
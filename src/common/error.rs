@@ -0,0 +1,42 @@
+// Swift-Guard Common Errors
+// CLI와 데몬이 공유하는 오류 타입
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// API 오류 코드
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    /// 잘못된 인자
+    InvalidArgument,
+    /// 대상 없음
+    NotFound,
+    /// 이미 존재함
+    AlreadyExists,
+    /// 권한 없음
+    PermissionDenied,
+    /// 데몬 내부 오류
+    #[default]
+    Internal,
+}
+
+/// 잘못된 인자 오류
+#[derive(Debug, Clone, PartialEq, Eq, Error, Serialize, Deserialize)]
+#[error("invalid {field}: {message}")]
+pub struct InvalidArgument {
+    /// 문제가 된 필드 이름
+    pub field: String,
+    /// 오류 설명
+    pub message: String,
+}
+
+impl InvalidArgument {
+    /// 새로운 오류 생성
+    pub fn new(field: &str, message: impl Into<String>) -> Self {
+        Self {
+            field: field.to_string(),
+            message: message.into(),
+        }
+    }
+}
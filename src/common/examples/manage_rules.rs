@@ -0,0 +1,54 @@
+// Swift-Guard 라이브러리 클라이언트 예제
+// 규칙을 추가하고 목록과 통계를 조회한 뒤 새 이벤트를 잠시 따라간다
+//
+// 실행: cargo run -p swift-guard-common --example manage_rules -- [서버 주소] [API 토큰]
+// (서버 주소 기본값 127.0.0.1:7654, Unix 소켓은 unix:/path/to/socket)
+
+use std::time::Duration;
+
+use swift_guard::api::{ClientError, RuleSpec, SwiftGuardClient};
+use swift_guard::types::{ActionType, ProtocolType, TcpFlags};
+
+#[tokio::main]
+async fn main() -> Result<(), ClientError> {
+    let mut args = std::env::args().skip(1);
+    let server_addr = args.next().unwrap_or_else(|| "127.0.0.1:7654".to_string());
+    let client = SwiftGuardClient::new(&server_addr)
+        .with_token(args.next());
+
+    // 외부에서 들어오는 SSH SYN을 1시간 동안 초당 10개로 제한
+    let spec = RuleSpec::new("example-ssh-limit", ActionType::Drop)
+        .with_protocol(ProtocolType::Tcp)
+        .with_dst_port(22)
+        .with_tcp_flags(TcpFlags(TcpFlags::SYN))
+        .with_rate_limit(10)
+        .with_priority(100)
+        .with_expire(3600)
+        .with_description("added by the manage_rules example");
+    if let Some(warning) = client.add_rule(spec).await? {
+        println!("warning: {}", warning);
+    }
+
+    for rule in client.list_rules().await? {
+        println!("{:<24} {:<10} {} -> {} ({} packets)",
+            rule.display_label(), rule.display_action(), rule.source(), rule.destination(), rule.stats.packets);
+    }
+
+    let stats = client.get_stats().await?;
+    println!("total packets: {}, {:.2} Mbps", stats.total_packets, stats.mbps);
+
+    // 새 이벤트를 10초 동안 출력
+    let mut events = client.subscribe_events().with_interval(Duration::from_secs(2));
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(10);
+    while tokio::time::Instant::now() < deadline {
+        let batch = events.next().await?;
+        if let Some(gap) = batch.gap {
+            println!("missed {} events", gap.missed());
+        }
+        for event in batch.events {
+            println!("#{} {}", event.seq, event.event.kind());
+        }
+    }
+
+    client.delete_rule("example-ssh-limit").await
+}
@@ -0,0 +1,424 @@
+// Swift-Guard gRPC
+// proto 정의에서 생성된 gRPC 타입과 API 타입 간 변환
+
+// 변환 오류는 tonic 핸들러가 그대로 반환하는 `Status`로 둔다
+#![allow(clippy::result_large_err)]
+
+use tonic::Status;
+
+use crate::api::{
+    AnomalyEvent, ApiRequest, ApiResponse, DaemonEvent, EventGap, GeoInfo, LinkStateEvent, MitigationEvent, NewRule,
+    RuleExpiryEvent, RuleHitEvent, RuleInfo, RuleMatchedEvent, SequencedEvent, SystemStats, WasmModuleInfo, XdpAbortedEvent,
+};
+use crate::error::ErrorCode;
+
+tonic::include_proto!("swift_guard.v1");
+
+/// 포트 범위 변환 (max가 0이면 min 이상 전체)
+fn port_range(min: u32, max: u32) -> Result<(u16, u16), Status> {
+    let max = if max == 0 { 65535 } else { max };
+    let min = u16::try_from(min).map_err(|_| Status::invalid_argument(format!("Invalid port: {}", min)))?;
+    let max = u16::try_from(max).map_err(|_| Status::invalid_argument(format!("Invalid port: {}", max)))?;
+    Ok((min, max))
+}
+
+/// 포트 목록 변환
+fn port_list(ports: &[u32]) -> Result<Vec<u16>, Status> {
+    ports.iter()
+        .map(|port| u16::try_from(*port).map_err(|_| Status::invalid_argument(format!("Invalid port: {}", port))))
+        .collect()
+}
+
+/// u8 필드 변환
+fn narrow(field: &str, value: u32) -> Result<u8, Status> {
+    u8::try_from(value).map_err(|_| Status::invalid_argument(format!("{}: {} is out of range (0-255)", field, value)))
+}
+
+impl TryFrom<AddRuleRequest> for ApiRequest {
+    type Error = Status;
+
+    fn try_from(request: AddRuleRequest) -> Result<Self, Status> {
+        let (src_port_min, src_port_max) = port_range(request.src_port_min, request.src_port_max)?;
+        let (dst_port_min, dst_port_max) = port_range(request.dst_port_min, request.dst_port_max)?;
+        let src_port_list = port_list(&request.src_port_list)?;
+        let dst_port_list = port_list(&request.dst_port_list)?;
+
+        Ok(ApiRequest::AddRule(Box::new(NewRule {
+            src_ip: request.src_ip,
+            dst_ip: request.dst_ip,
+            src_port_min,
+            src_port_max,
+            dst_port_min,
+            dst_port_max,
+            protocol: narrow("protocol", request.protocol)?,
+            tcp_flags: narrow("tcp_flags", request.tcp_flags)?,
+            action: narrow("action", request.action)?,
+            redirect_if: request.redirect_if,
+            redirect_cpu: request.redirect_cpu,
+            priority: request.priority,
+            rate_limit: request.rate_limit,
+            expire: request.expire,
+            label: request.label,
+            description: request.description,
+            created_by: request.created_by,
+            quota_bytes: request.quota_bytes,
+            quota_packets: request.quota_packets,
+            quota_action: request.quota_action.map(|action| narrow("quota_action", action)).transpose()?,
+            src_host: request.src_host,
+            dst_host: request.dst_host,
+            counter_group: request.counter_group,
+            src_port_list,
+            dst_port_list,
+            owner_webhook: request.owner_webhook,
+            notify_before: request.notify_before,
+            force: request.force,
+        })))
+    }
+}
+
+impl From<RuleInfo> for Rule {
+    fn from(rule: RuleInfo) -> Self {
+        let address_family = rule.family().as_str().to_string();
+        Rule {
+            label: rule.label,
+            action: rule.action,
+            src_ip: rule.src_ip,
+            dst_ip: rule.dst_ip,
+            src_port: rule.src_port,
+            dst_port: rule.dst_port,
+            protocol: rule.protocol,
+            tcp_flags: rule.tcp_flags,
+            priority: rule.priority,
+            redirect_if: rule.redirect_if,
+            redirect_cpu: rule.redirect_cpu,
+            rate_limit: rule.rate_limit,
+            expire: rule.expire,
+            auto: rule.auto,
+            degraded: rule.degraded,
+            description: rule.description,
+            created_by: rule.created_by,
+            creation_time: rule.creation_time,
+            effective: rule.effective,
+            quota_bytes: rule.quota.as_ref().and_then(|quota| quota.bytes),
+            quota_packets: rule.quota.as_ref().and_then(|quota| quota.packets),
+            quota_action: rule.quota.map(|quota| quota.action),
+            quota_exceeded: rule.quota_exceeded,
+            src_host: rule.src_host.as_ref().map(|host| host.name.clone()),
+            src_host_addrs: rule.src_host.as_ref().map(|host| host.addrs.clone()).unwrap_or_default(),
+            src_host_error: rule.src_host.and_then(|host| host.error),
+            namespace: rule.namespace,
+            counter_group: rule.counter_group,
+            preset: rule.preset,
+            origin: rule.origin,
+            src_set: rule.src_set.as_ref().map(|set| set.name.clone()),
+            src_set_entries: rule.src_set.map(|set| set.entries as u64).unwrap_or_default(),
+            owner_webhook: rule.owner_webhook,
+            notify_before: rule.notify_before,
+            address_family,
+            stats: Some(RuleStats {
+                packets: rule.stats.packets,
+                bytes: rule.stats.bytes,
+                last_matched: rule.stats.last_matched,
+                pps: rule.stats.pps,
+                bps: rule.stats.bps,
+                redirected_packets: rule.stats.redirected_packets,
+                redirect_failures: rule.stats.redirect_failures,
+                aborted_packets: rule.stats.aborted_packets,
+            }),
+        }
+    }
+}
+
+impl From<SystemStats> for Stats {
+    fn from(stats: SystemStats) -> Self {
+        Stats {
+            total_packets: stats.total_packets,
+            total_bytes: stats.total_bytes,
+            packets_per_sec: stats.packets_per_sec,
+            mbps: stats.mbps,
+            aborted_packets: stats.aborted_packets,
+            anomaly: stats.anomaly,
+            baseline: Some(stats.baseline.into()),
+            protocols: Some(ProtocolStats {
+                tcp: stats.protocols.tcp,
+                udp: stats.protocols.udp,
+                icmp: stats.protocols.icmp,
+                other: stats.protocols.other,
+            }),
+            size_histogram: Some(SizeHistogram {
+                counts: stats.size_histogram.counts,
+                sum: stats.size_histogram.sum,
+            }),
+        }
+    }
+}
+
+impl From<crate::api::TrafficBaseline> for TrafficBaseline {
+    fn from(baseline: crate::api::TrafficBaseline) -> Self {
+        TrafficBaseline {
+            pps_mean: baseline.pps_mean,
+            pps_stddev: baseline.pps_stddev,
+            mbps_mean: baseline.mbps_mean,
+            mbps_stddev: baseline.mbps_stddev,
+            samples: baseline.samples,
+            k: baseline.k,
+        }
+    }
+}
+
+impl From<WasmModuleInfo> for WasmModule {
+    fn from(module: WasmModuleInfo) -> Self {
+        WasmModule {
+            name: module.name,
+            state: module.state,
+            loaded_at: module.loaded_at,
+            mode: module.mode.as_str().to_string(),
+            would_block_packets: module.would_block_packets,
+        }
+    }
+}
+
+/// GeoIP 필드만 채운 이벤트 (나머지는 기본값)
+fn geo_fields(geo: Option<GeoInfo>) -> Event {
+    let geo = geo.unwrap_or_default();
+    Event {
+        country: geo.country.unwrap_or_default(),
+        asn: geo.asn.unwrap_or_default(),
+        as_org: geo.as_org.unwrap_or_default(),
+        ..Default::default()
+    }
+}
+
+impl From<RuleHitEvent> for Event {
+    fn from(event: RuleHitEvent) -> Self {
+        Event {
+            kind: "rule_hit".to_string(),
+            src_ip: event.src_ip,
+            label: event.label,
+            action: event.action,
+            timestamp: event.timestamp_ns / 1_000_000_000,
+            rule_key: event.rule_key,
+            protocol: event.protocol,
+            src_port: event.src_port as u32,
+            dst_ip: event.dst_ip,
+            dst_port: event.dst_port as u32,
+            ..geo_fields(event.geo)
+        }
+    }
+}
+
+impl From<MitigationEvent> for Event {
+    fn from(event: MitigationEvent) -> Self {
+        Event {
+            kind: event.kind,
+            src_ip: event.src_ip,
+            syn_pps: event.syn_pps,
+            label: event.label,
+            action: event.action,
+            expire: event.expire,
+            dry_run: event.dry_run,
+            timestamp: event.timestamp,
+            ..geo_fields(event.geo)
+        }
+    }
+}
+
+impl From<RuleMatchedEvent> for Event {
+    fn from(event: RuleMatchedEvent) -> Self {
+        Event {
+            kind: "rule_matched".to_string(),
+            label: event.label,
+            timestamp: event.timestamp,
+            packets: event.packets,
+            bytes: event.bytes,
+            pps: event.pps,
+            bps: event.bps,
+            ..Default::default()
+        }
+    }
+}
+
+impl From<LinkStateEvent> for Event {
+    fn from(event: LinkStateEvent) -> Self {
+        Event {
+            kind: "link_state".to_string(),
+            interface: event.interface,
+            state: event.state,
+            action: event.action.unwrap_or_default(),
+            rules: event.rules,
+            timestamp: event.timestamp,
+            ..Default::default()
+        }
+    }
+}
+
+impl From<XdpAbortedEvent> for Event {
+    fn from(event: XdpAbortedEvent) -> Self {
+        Event {
+            kind: "xdp_aborted".to_string(),
+            packets: event.aborted,
+            aborted_total: event.total,
+            intervals: event.intervals,
+            rules: event.rules,
+            timestamp: event.timestamp,
+            ..Default::default()
+        }
+    }
+}
+
+impl From<AnomalyEvent> for Event {
+    fn from(event: AnomalyEvent) -> Self {
+        Event {
+            kind: "anomaly".to_string(),
+            pps: event.pps,
+            mbps: event.mbps,
+            metrics: event.metrics,
+            baseline: Some(event.baseline.into()),
+            timestamp: event.timestamp,
+            ..Default::default()
+        }
+    }
+}
+
+/// 규칙 만료 예고/만료 이벤트 (`kind`는 "rule_expiring" 또는 "rule_expired")
+fn expiry_event(kind: &str, event: RuleExpiryEvent) -> Event {
+    Event {
+        kind: kind.to_string(),
+        label: event.rule.key(),
+        action: event.rule.action,
+        expire: event.rule.expire,
+        timestamp: event.timestamp,
+        packets: event.rule.stats.packets,
+        bytes: event.rule.stats.bytes,
+        expires_at: event.expires_at,
+        ..Default::default()
+    }
+}
+
+impl From<SequencedEvent> for Event {
+    fn from(event: SequencedEvent) -> Self {
+        let converted = match event.event {
+            DaemonEvent::RuleHit(hit) => Event::from(hit),
+            DaemonEvent::Mitigation(mitigation) => Event::from(mitigation),
+            DaemonEvent::RuleMatched(matched) => Event::from(matched),
+            DaemonEvent::LinkState(link) => Event::from(link),
+            DaemonEvent::RuleExpiring(expiry) => expiry_event("rule_expiring", expiry),
+            DaemonEvent::RuleExpired(expiry) => expiry_event("rule_expired", expiry),
+            DaemonEvent::XdpAborted(aborted) => Event::from(aborted),
+            DaemonEvent::Anomaly(anomaly) => Event::from(anomaly),
+        };
+        Event { seq: event.seq, ..converted }
+    }
+}
+
+/// 재생할 수 없는 구간은 kind "gap" 이벤트로 알림 (seq는 구간의 마지막 순번)
+impl From<EventGap> for Event {
+    fn from(gap: EventGap) -> Self {
+        Event {
+            kind: "gap".to_string(),
+            seq: gap.last_seq,
+            missed: gap.missed(),
+            ..Default::default()
+        }
+    }
+}
+
+/// 오류 코드를 gRPC 상태 코드로 변환
+pub fn status_code(code: ErrorCode) -> tonic::Code {
+    match code {
+        ErrorCode::InvalidArgument => tonic::Code::InvalidArgument,
+        ErrorCode::NotFound => tonic::Code::NotFound,
+        ErrorCode::AlreadyExists => tonic::Code::AlreadyExists,
+        ErrorCode::PermissionDenied => tonic::Code::PermissionDenied,
+        ErrorCode::Internal => tonic::Code::Internal,
+    }
+}
+
+/// 오류 응답(또는 예상하지 못한 응답)을 gRPC 상태로 변환
+pub fn response_status(response: ApiResponse) -> Status {
+    match response {
+        ApiResponse::Error { message, code } => Status::new(status_code(code), message),
+        ApiResponse::InvalidArgument { field, message } => {
+            Status::invalid_argument(format!("Invalid {}: {}", field, message))
+        },
+        ApiResponse::NotFound { resource, name } => {
+            Status::not_found(format!("{} '{}' not found", resource, name))
+        },
+        ApiResponse::RateLimited { retry_after_ms, limit } => Status::resource_exhausted(
+            format!("Rate limited ({} requests/sec), retry after {} ms", limit, retry_after_ms)),
+        other => Status::internal(format!("Unexpected response: {:?}", other)),
+    }
+}
+
+/// 성공 메시지 응답 변환
+pub fn status_reply(response: ApiResponse) -> Result<StatusReply, Status> {
+    match response {
+        ApiResponse::Success { message, warning } => Ok(StatusReply { message, warning }),
+        other => Err(response_status(other)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add_rule_port_defaults() {
+        let request = AddRuleRequest {
+            label: "web".to_string(),
+            dst_port_min: 80,
+            dst_port_max: 80,
+            protocol: 6,
+            action: 2,
+            ..Default::default()
+        };
+
+        match ApiRequest::try_from(request).unwrap() {
+            ApiRequest::AddRule(rule) => {
+                assert_eq!((rule.src_port_min, rule.src_port_max), (0, 65535));
+                assert_eq!((rule.dst_port_min, rule.dst_port_max), (80, 80));
+                assert_eq!(rule.protocol, 6);
+            },
+            other => panic!("unexpected request: {:?}", other),
+        }
+
+        let request = AddRuleRequest { protocol: 17, src_port_list: vec![53, 123], ..Default::default() };
+        match ApiRequest::try_from(request).unwrap() {
+            ApiRequest::AddRule(rule) => assert_eq!(rule.src_port_list, vec![53, 123]),
+            other => panic!("unexpected request: {:?}", other),
+        }
+        let request = AddRuleRequest { src_port_list: vec![70000], ..Default::default() };
+        assert_eq!(ApiRequest::try_from(request).unwrap_err().code(), tonic::Code::InvalidArgument);
+        let request = AddRuleRequest { protocol: 6, dst_port_list: vec![80, 443, 8080], ..Default::default() };
+        match ApiRequest::try_from(request).unwrap() {
+            ApiRequest::AddRule(rule) => assert_eq!(rule.dst_port_list, vec![80, 443, 8080]),
+            other => panic!("unexpected request: {:?}", other),
+        }
+
+        let request = AddRuleRequest { protocol: 256, ..Default::default() };
+        let status = ApiRequest::try_from(request).unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+
+        let request = AddRuleRequest { quota_bytes: Some(1 << 30), quota_action: Some(300), ..Default::default() };
+        let status = ApiRequest::try_from(request).unwrap_err();
+        assert!(status.message().contains("quota_action"));
+    }
+
+    #[test]
+    fn test_response_status() {
+        let status = response_status(ApiResponse::NotFound { resource: "rule".to_string(), name: "web".to_string() });
+        assert_eq!(status.code(), tonic::Code::NotFound);
+
+        let status = response_status(ApiResponse::Error {
+            message: "exists".to_string(),
+            code: ErrorCode::AlreadyExists,
+        });
+        assert_eq!(status.code(), tonic::Code::AlreadyExists);
+
+        let status = response_status(ApiResponse::RateLimited { retry_after_ms: 100, limit: 10 });
+        assert_eq!(status.code(), tonic::Code::ResourceExhausted);
+
+        let reply = status_reply(ApiResponse::Success { message: "ok".to_string(), warning: None }).unwrap();
+        assert_eq!(reply.message, "ok");
+    }
+}
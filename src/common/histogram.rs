@@ -0,0 +1,201 @@
+//! 지연 시간 히스토그램 모듈
+//! 고정 상한 버킷별 처리 시간 집계와 백분위수 추정 (API 요청, WASM 패킷 검사)
+//!
+//! 기록 경로는 원자 연산만 쓰므로 여러 스레드가 잠금 없이 같은 히스토그램에 기록할 수 있다.
+//! 조회는 `snapshot`으로 복사한 `LatencySnapshot`을 쓰며, 백분위수는 해당 버킷 안에서
+//! 값이 고르게 퍼져 있다고 보고 선형 보간한 추정값이다 (Prometheus `histogram_quantile`과 같은 방식).
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+/// 값에 해당하는 버킷 인덱스 (상한 이하인 첫 버킷, 모든 상한을 넘으면 마지막 버킷)
+pub fn bucket_index(bounds_us: &[u64], value_us: u64) -> usize {
+    bounds_us.iter()
+        .position(|&bound| value_us <= bound)
+        .unwrap_or(bounds_us.len())
+}
+
+/// 원자 카운터 기반 지연 시간 히스토그램
+#[derive(Debug)]
+pub struct LatencyHistogram {
+    /// 버킷 상한 (마이크로초, 이하, 오름차순)
+    bounds_us: &'static [u64],
+    /// 버킷별 기록 수 (마지막은 상한 초과)
+    counts: Box<[AtomicU64]>,
+    /// 기록한 시간 합계 (마이크로초)
+    sum_us: AtomicU64,
+}
+
+impl LatencyHistogram {
+    /// 주어진 상한으로 빈 히스토그램 생성
+    pub fn new(bounds_us: &'static [u64]) -> Self {
+        Self {
+            bounds_us,
+            counts: (0..=bounds_us.len()).map(|_| AtomicU64::new(0)).collect(),
+            sum_us: AtomicU64::new(0),
+        }
+    }
+
+    /// 처리 시간 기록
+    pub fn record(&self, duration: Duration) {
+        self.record_us(u64::try_from(duration.as_micros()).unwrap_or(u64::MAX));
+    }
+
+    /// 처리 시간 기록 (마이크로초)
+    pub fn record_us(&self, value_us: u64) {
+        self.counts[bucket_index(self.bounds_us, value_us)].fetch_add(1, Ordering::Relaxed);
+        self.sum_us.fetch_add(value_us, Ordering::Relaxed);
+    }
+
+    /// 현재 값 복사
+    pub fn snapshot(&self) -> LatencySnapshot {
+        LatencySnapshot {
+            bounds_us: self.bounds_us.to_vec(),
+            counts: self.counts.iter().map(|count| count.load(Ordering::Relaxed)).collect(),
+            sum_us: self.sum_us.load(Ordering::Relaxed),
+        }
+    }
+
+    /// 모든 버킷과 합계를 0으로
+    pub fn reset(&self) {
+        for count in self.counts.iter() {
+            count.store(0, Ordering::Relaxed);
+        }
+        self.sum_us.store(0, Ordering::Relaxed);
+    }
+}
+
+/// 히스토그램 값
+///
+/// `counts`는 `bounds_us` 순서의 버킷별 기록 수(누적 아님)이며 마지막 원소는 상한 초과 버킷이다.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct LatencySnapshot {
+    pub bounds_us: Vec<u64>,
+    pub counts: Vec<u64>,
+    /// 기록한 시간 합계 (마이크로초)
+    pub sum_us: u64,
+}
+
+impl LatencySnapshot {
+    /// 전체 기록 수
+    pub fn total(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+
+    /// 같은 상한을 쓰는 다른 히스토그램 더하기 (비어 있으면 상한도 가져옴)
+    pub fn merge(&mut self, other: &LatencySnapshot) {
+        if self.counts.is_empty() {
+            *self = other.clone();
+            return;
+        }
+        for (count, added) in self.counts.iter_mut().zip(&other.counts) {
+            *count += added;
+        }
+        self.sum_us += other.sum_us;
+    }
+
+    /// 백분위수 추정 (`quantile`은 0.0~1.0, 기록이 없으면 None)
+    ///
+    /// 상한 초과 버킷에 해당하면 마지막 상한을 돌려준다.
+    pub fn percentile(&self, quantile: f64) -> Option<f64> {
+        let total = self.total();
+        if total == 0 {
+            return None;
+        }
+
+        let rank = quantile.clamp(0.0, 1.0) * total as f64;
+        let mut cumulative = 0;
+        for (index, &count) in self.counts.iter().enumerate() {
+            let below = cumulative;
+            cumulative += count;
+            if count == 0 || (cumulative as f64) < rank {
+                continue;
+            }
+            let lower = if index == 0 { 0 } else { self.bounds_us[index - 1] };
+            let Some(&upper) = self.bounds_us.get(index) else {
+                return Some(lower as f64);
+            };
+            let fraction = (rank - below as f64) / count as f64;
+            return Some(lower as f64 + (upper - lower) as f64 * fraction);
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BOUNDS: [u64; 4] = [10, 100, 1_000, 10_000];
+
+    #[test]
+    fn test_bucket_index() {
+        assert_eq!(bucket_index(&BOUNDS, 0), 0);
+        assert_eq!(bucket_index(&BOUNDS, 10), 0);
+        assert_eq!(bucket_index(&BOUNDS, 11), 1);
+        assert_eq!(bucket_index(&BOUNDS, 10_000), 3);
+        assert_eq!(bucket_index(&BOUNDS, 10_001), 4);
+        assert_eq!(bucket_index(&[], 5), 0);
+    }
+
+    #[test]
+    fn test_record_and_reset() {
+        let histogram = LatencyHistogram::new(&BOUNDS);
+        histogram.record(Duration::from_micros(5));
+        histogram.record(Duration::from_nanos(900));
+        histogram.record(Duration::from_micros(150));
+        histogram.record(Duration::from_secs(1));
+
+        let snapshot = histogram.snapshot();
+        assert_eq!(snapshot.bounds_us, BOUNDS);
+        assert_eq!(snapshot.counts, [2, 0, 1, 0, 1]);
+        assert_eq!(snapshot.sum_us, 1_000_155);
+        assert_eq!(snapshot.total(), 4);
+
+        histogram.reset();
+        let snapshot = histogram.snapshot();
+        assert_eq!(snapshot.counts, [0; 5]);
+        assert_eq!(snapshot.sum_us, 0);
+    }
+
+    #[test]
+    fn test_percentile() {
+        assert_eq!(LatencySnapshot::default().percentile(0.5), None);
+
+        // 첫 버킷은 0부터 상한까지 보간
+        let snapshot = LatencySnapshot { bounds_us: BOUNDS.to_vec(), counts: vec![10, 0, 0, 0, 0], sum_us: 50 };
+        assert_eq!(snapshot.percentile(0.5), Some(5.0));
+        assert_eq!(snapshot.percentile(1.0), Some(10.0));
+
+        // 90개는 10µs 이하, 10개는 100~1000µs
+        let snapshot = LatencySnapshot { bounds_us: BOUNDS.to_vec(), counts: vec![90, 0, 10, 0, 0], sum_us: 0 };
+        assert_eq!(snapshot.percentile(0.5), Some(50.0 / 90.0 * 10.0));
+        assert_eq!(snapshot.percentile(0.9), Some(10.0));
+        assert_eq!(snapshot.percentile(0.95), Some(100.0 + 900.0 * 0.5));
+        assert_eq!(snapshot.percentile(0.99), Some(100.0 + 900.0 * 0.9));
+
+        // 빈 버킷은 건너뛰고, 상한 초과 버킷은 마지막 상한
+        let snapshot = LatencySnapshot { bounds_us: BOUNDS.to_vec(), counts: vec![0, 0, 0, 1, 3], sum_us: 0 };
+        assert_eq!(snapshot.percentile(0.0), Some(1_000.0));
+        assert_eq!(snapshot.percentile(0.25), Some(10_000.0));
+        assert_eq!(snapshot.percentile(0.99), Some(10_000.0));
+    }
+
+    #[test]
+    fn test_merge() {
+        let first = LatencyHistogram::new(&BOUNDS);
+        let second = LatencyHistogram::new(&BOUNDS);
+        first.record_us(5);
+        second.record_us(50);
+        second.record_us(50_000);
+
+        let mut merged = LatencySnapshot::default();
+        merged.merge(&first.snapshot());
+        merged.merge(&second.snapshot());
+        assert_eq!(merged.bounds_us, BOUNDS);
+        assert_eq!(merged.counts, [1, 1, 0, 0, 1]);
+        assert_eq!(merged.sum_us, 50_055);
+    }
+}
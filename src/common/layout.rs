@@ -0,0 +1,759 @@
+//! 커널 값 레이아웃 모듈
+//! BPF 프로그램의 맵 값 구조체(struct filter_rule, filter_stats, global_stats)의 필드 위치
+//!
+//! 데몬은 여기 정의한 위치로 규칙 값을 기록하고 통계를 읽는다. 오브젝트를 로드할 때
+//! BTF에서 읽은 구조체와 비교해(`verify_btf`) 하나라도 다르면 시작하지 않는다.
+
+use std::ops::Range;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::api::ProtocolStats;
+use crate::utils::MAX_LABEL_LEN;
+
+/// 필드 정의 (이름, 위치, 크기)
+type FieldDef = (&'static str, usize, usize);
+
+/// struct filter_rule에서 action 위치 (0이면 빈 와일드카드 슬롯)
+pub const RULE_ACTION_OFFSET: usize = 4;
+
+/// struct filter_rule에서 레이블(char[32]) 위치
+pub const RULE_LABEL_OFFSET: usize = 32;
+
+/// struct filter_rule에서 소스 포트 목록(uint16_t[MAX_RULE_PORTS]) 위치
+pub const RULE_SRC_PORTS_OFFSET: usize = 64;
+
+/// struct filter_rule에서 목적지 포트 목록(uint16_t[MAX_RULE_PORTS]) 위치
+pub const RULE_DST_PORTS_OFFSET: usize = RULE_SRC_PORTS_OFFSET + MAX_RULE_PORTS * 2;
+
+/// 규칙 하나가 담는 포트 목록의 최대 길이 (커널 값은 0으로 끝남)
+pub const MAX_RULE_PORTS: usize = 8;
+
+/// struct filter_rule에서 통계 영역(struct filter_stats) 위치
+pub const RULE_STATS_OFFSET: usize = RULE_DST_PORTS_OFFSET + MAX_RULE_PORTS * 2;
+
+/// struct filter_rule 크기
+pub const RULE_VALUE_SIZE: usize = RULE_STATS_OFFSET + FilterStats::SIZE;
+
+/// struct filter_rule 필드 (tcp_flags 뒤 1바이트는 정렬 패딩)
+const FILTER_RULE_FIELDS: &[FieldDef] = &[
+    ("priority", 0, 4),
+    ("action", RULE_ACTION_OFFSET, 1),
+    ("protocol", 5, 1),
+    ("src_port_min", 6, 2),
+    ("src_port_max", 8, 2),
+    ("dst_port_min", 10, 2),
+    ("dst_port_max", 12, 2),
+    ("tcp_flags", 14, 1),
+    ("redirect_ifindex", 16, 4),
+    ("rate_limit", 20, 4),
+    ("expire", 24, 4),
+    ("redirect_cpu", 28, 4),
+    ("label", RULE_LABEL_OFFSET, MAX_LABEL_LEN + 1),
+    ("src_ports", RULE_SRC_PORTS_OFFSET, MAX_RULE_PORTS * 2),
+    ("dst_ports", RULE_DST_PORTS_OFFSET, MAX_RULE_PORTS * 2),
+    ("stats", RULE_STATS_OFFSET, FilterStats::SIZE),
+];
+
+/// struct filter_stats 필드
+const FILTER_STATS_FIELDS: &[FieldDef] = &[
+    ("packets", 0, 8),
+    ("bytes", 8, 8),
+    ("last_matched", 16, 8),
+    ("redirected_packets", 24, 8),
+    ("redirect_failures", 32, 8),
+    ("aborted_packets", 40, 8),
+];
+
+/// struct global_stats 필드
+const GLOBAL_STATS_FIELDS: &[FieldDef] = &[
+    ("packets", 0, 8),
+    ("bytes", 8, 8),
+    ("last_matched", 16, 8),
+    ("tcp_packets", 24, 8),
+    ("udp_packets", 32, 8),
+    ("icmp_packets", 40, 8),
+    ("other_packets", 48, 8),
+    ("syn_packets", 56, 8),
+    ("aborted_packets", 64, 8),
+];
+
+/// 데몬이 기대하는 구조체 (이름, 크기, 필드)
+const EXPECTED: &[(&str, usize, &[FieldDef])] = &[
+    ("filter_rule", RULE_VALUE_SIZE, FILTER_RULE_FIELDS),
+    ("filter_stats", FilterStats::SIZE, FILTER_STATS_FIELDS),
+    ("global_stats", GlobalStats::SIZE, GLOBAL_STATS_FIELDS),
+];
+
+/// struct filter_rule 필드의 바이트 범위
+pub fn rule_field(name: &str) -> Option<Range<usize>> {
+    FILTER_RULE_FIELDS.iter()
+        .find(|(field, ..)| *field == name)
+        .map(|(_, offset, size)| *offset..*offset + *size)
+}
+
+/// 포트 목록을 src_ports, dst_ports 필드 바이트로 변환 (리틀 엔디안, 남는 칸은 0)
+///
+/// 0번 포트는 목록의 끝을 뜻하므로 담을 수 없다. 포트가 `MAX_RULE_PORTS`개보다
+/// 많거나 0이 있으면 None.
+pub fn encode_port_list(ports: &[u16]) -> Option<[u8; MAX_RULE_PORTS * 2]> {
+    if ports.len() > MAX_RULE_PORTS || ports.contains(&0) {
+        return None;
+    }
+    let mut bytes = [0u8; MAX_RULE_PORTS * 2];
+    for (slot, port) in bytes.chunks_exact_mut(2).zip(ports) {
+        slot.copy_from_slice(&port.to_le_bytes());
+    }
+    Some(bytes)
+}
+
+/// src_ports, dst_ports 필드 바이트에서 포트 목록 읽기 (첫 0에서 끝남)
+pub fn decode_port_list(bytes: &[u8]) -> Vec<u16> {
+    bytes.chunks_exact(2)
+        .take(MAX_RULE_PORTS)
+        .map(|b| u16::from_le_bytes([b[0], b[1]]))
+        .take_while(|port| *port != 0)
+        .collect()
+}
+
+/// 맵 값에서 u64 필드 읽기 (리틀 엔디안)
+fn read_u64(value: &[u8], offset: usize) -> u64 {
+    value.get(offset..offset + 8)
+        .map(|b| u64::from_le_bytes([b[0], b[1], b[2], b[3], b[4], b[5], b[6], b[7]]))
+        .unwrap_or(0)
+}
+
+/// 필드 정의 위치에서 u64 필드 읽기 (값이 짧으면 0)
+fn read_field(value: &[u8], fields: &[FieldDef], name: &str) -> u64 {
+    fields.iter()
+        .find(|(field, ..)| *field == name)
+        .map_or(0, |(_, offset, _)| read_u64(value, *offset))
+}
+
+/// 전역 통계 맵 값 레이아웃 (struct global_stats와 일치)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GlobalStats {
+    /// 처리된 패킷 수
+    pub packets: u64,
+    /// 처리된 바이트 수
+    pub bytes: u64,
+    /// 마지막 매치 타임스탬프
+    pub last_matched: u64,
+    /// TCP 패킷 수
+    pub tcp_packets: u64,
+    /// UDP 패킷 수
+    pub udp_packets: u64,
+    /// ICMP 패킷 수
+    pub icmp_packets: u64,
+    /// 기타 프로토콜 패킷 수
+    pub other_packets: u64,
+    /// TCP SYN(ACK 없음) 패킷 수
+    pub syn_packets: u64,
+    /// XDP_ABORTED로 끝난 패킷 수 (규칙이 뜻한 액션을 수행하지 못함)
+    pub aborted_packets: u64,
+}
+
+impl GlobalStats {
+    /// 기본 카운터(packets, bytes) 크기
+    pub const MIN_SIZE: usize = 16;
+    /// 전체 구조체 크기
+    pub const SIZE: usize = 72;
+
+    /// 맵 값 바이트에서 파싱
+    ///
+    /// 프로토콜 카운터나 중단 카운터가 없는 이전 레이아웃의 값은 해당 필드를 0으로 채운다.
+    pub fn from_bytes(value: &[u8]) -> Option<Self> {
+        if value.len() < Self::MIN_SIZE {
+            return None;
+        }
+
+        let field = |name| read_field(value, GLOBAL_STATS_FIELDS, name);
+        Some(Self {
+            packets: field("packets"),
+            bytes: field("bytes"),
+            last_matched: field("last_matched"),
+            tcp_packets: field("tcp_packets"),
+            udp_packets: field("udp_packets"),
+            icmp_packets: field("icmp_packets"),
+            other_packets: field("other_packets"),
+            syn_packets: field("syn_packets"),
+            aborted_packets: field("aborted_packets"),
+        })
+    }
+
+    /// CPU별 값 합산
+    pub fn merge(&mut self, other: &Self) {
+        self.packets += other.packets;
+        self.bytes += other.bytes;
+        self.last_matched = self.last_matched.max(other.last_matched);
+        self.tcp_packets += other.tcp_packets;
+        self.udp_packets += other.udp_packets;
+        self.icmp_packets += other.icmp_packets;
+        self.other_packets += other.other_packets;
+        self.syn_packets += other.syn_packets;
+        self.aborted_packets += other.aborted_packets;
+    }
+
+    /// 프로토콜별 통계로 변환
+    pub fn protocol_stats(&self) -> ProtocolStats {
+        ProtocolStats {
+            tcp: self.tcp_packets,
+            udp: self.udp_packets,
+            icmp: self.icmp_packets,
+            other: self.other_packets,
+        }
+    }
+}
+
+/// 규칙 값 끝의 통계 영역 레이아웃 (struct filter_stats와 일치)
+///
+/// 리디렉션 카운터는 리디렉션 규칙에서만 증가하고, 매치 카운터(packets)는
+/// 리디렉션 성공 여부와 관계없이 증가한다. 헬퍼가 XDP_ABORTED를 돌려준 리디렉션은
+/// `redirect_failures`와 `aborted_packets`에 함께 센다.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FilterStats {
+    /// 매치된 패킷 수
+    pub packets: u64,
+    /// 매치된 바이트 수
+    pub bytes: u64,
+    /// 마지막 매치 타임스탬프 (bpf_ktime_get_ns, 부팅 후 나노초)
+    pub last_matched: u64,
+    /// 리디렉션 대상으로 넘긴 패킷 수
+    pub redirected_packets: u64,
+    /// 리디렉션 대상이 없거나 커널이 거부해 넘기지 못한 패킷 수
+    pub redirect_failures: u64,
+    /// 규칙 액션 대신 XDP_ABORTED로 끝난 패킷 수
+    pub aborted_packets: u64,
+}
+
+const _: () = assert!(std::mem::size_of::<FilterStats>() == FilterStats::SIZE);
+
+impl FilterStats {
+    /// 리디렉션 카운터가 없는 이전 레이아웃 크기
+    pub const LEGACY_SIZE: usize = 24;
+    /// 전체 구조체 크기
+    pub const SIZE: usize = 48;
+
+    /// 통계 영역 바이트에서 파싱
+    ///
+    /// 고정(pin)된 맵에 남은 이전 레이아웃의 값은 없는 카운터(리디렉션, 중단)를 0으로 채운다.
+    pub fn from_bytes(value: &[u8]) -> Option<Self> {
+        if value.len() < Self::LEGACY_SIZE {
+            return None;
+        }
+
+        let field = |name| read_field(value, FILTER_STATS_FIELDS, name);
+        Some(Self {
+            packets: field("packets"),
+            bytes: field("bytes"),
+            last_matched: field("last_matched"),
+            redirected_packets: field("redirected_packets"),
+            redirect_failures: field("redirect_failures"),
+            aborted_packets: field("aborted_packets"),
+        })
+    }
+
+    /// 맵 값 바이트로 변환 (리틀 엔디안)
+    pub fn to_bytes(&self) -> [u8; Self::SIZE] {
+        let mut bytes = [0u8; Self::SIZE];
+        let fields = [self.packets, self.bytes, self.last_matched, self.redirected_packets, self.redirect_failures,
+            self.aborted_packets];
+        for ((_, offset, size), field) in FILTER_STATS_FIELDS.iter().zip(fields) {
+            bytes[*offset..*offset + *size].copy_from_slice(&field.to_le_bytes());
+        }
+        bytes
+    }
+
+    /// 같은 규칙의 다른 항목(호스트 이름 주소, 대체 오브젝트) 통계 합산
+    pub fn merge(&mut self, other: &Self) {
+        self.packets += other.packets;
+        self.bytes += other.bytes;
+        self.last_matched = self.last_matched.max(other.last_matched);
+        self.redirected_packets += other.redirected_packets;
+        self.redirect_failures += other.redirect_failures;
+        self.aborted_packets += other.aborted_packets;
+    }
+}
+
+/// 구조체 필드 위치
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FieldLayout {
+    pub name: String,
+    /// 구조체 시작에서의 바이트 위치
+    pub offset: usize,
+    /// 바이트 크기 (BTF에서 크기를 알 수 없으면 0)
+    pub size: usize,
+}
+
+/// 구조체 레이아웃 (bpf-info 출력)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StructLayout {
+    /// 구조체 이름 (`struct` 제외)
+    pub name: String,
+    /// 구조체 크기 (바이트)
+    pub size: usize,
+    pub fields: Vec<FieldLayout>,
+}
+
+impl StructLayout {
+    fn from_defs(name: &str, size: usize, fields: &[FieldDef]) -> Self {
+        Self {
+            name: name.to_string(),
+            size,
+            fields: fields.iter()
+                .map(|(name, offset, size)| FieldLayout { name: name.to_string(), offset: *offset, size: *size })
+                .collect(),
+        }
+    }
+
+    /// 이름으로 필드 찾기
+    pub fn field(&self, name: &str) -> Option<&FieldLayout> {
+        self.fields.iter().find(|field| field.name == name)
+    }
+}
+
+/// 데몬이 기대하는 맵 값 구조체
+pub fn expected() -> Vec<StructLayout> {
+    EXPECTED.iter()
+        .map(|(name, size, fields)| StructLayout::from_defs(name, *size, fields))
+        .collect()
+}
+
+/// 레이아웃 검증 오류
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum LayoutError {
+    #[error("invalid BTF data: {0}")]
+    InvalidBtf(String),
+    #[error("struct {0} is not defined in the BTF")]
+    MissingStruct(String),
+    #[error("struct {structure} has no field {field}")]
+    MissingField { structure: String, field: String },
+    #[error("struct {structure} field {field} is at offset {actual_offset} ({actual_size} bytes), expected offset {expected_offset} ({expected_size} bytes)")]
+    FieldMismatch {
+        structure: String,
+        field: String,
+        expected_offset: usize,
+        expected_size: usize,
+        actual_offset: usize,
+        actual_size: usize,
+    },
+    #[error("struct {structure} is {actual} bytes, expected {expected} bytes")]
+    SizeMismatch { structure: String, expected: usize, actual: usize },
+}
+
+/// BTF 헤더 매직 (리틀 엔디안 오브젝트)
+const BTF_MAGIC: u16 = 0xEB9F;
+
+/// BTF 타입 종류 (linux/btf.h)
+const BTF_KIND_INT: u32 = 1;
+const BTF_KIND_PTR: u32 = 2;
+const BTF_KIND_ARRAY: u32 = 3;
+const BTF_KIND_STRUCT: u32 = 4;
+const BTF_KIND_UNION: u32 = 5;
+const BTF_KIND_ENUM: u32 = 6;
+const BTF_KIND_FWD: u32 = 7;
+const BTF_KIND_TYPEDEF: u32 = 8;
+const BTF_KIND_VOLATILE: u32 = 9;
+const BTF_KIND_CONST: u32 = 10;
+const BTF_KIND_RESTRICT: u32 = 11;
+const BTF_KIND_FUNC: u32 = 12;
+const BTF_KIND_FUNC_PROTO: u32 = 13;
+const BTF_KIND_VAR: u32 = 14;
+const BTF_KIND_DATASEC: u32 = 15;
+const BTF_KIND_FLOAT: u32 = 16;
+const BTF_KIND_DECL_TAG: u32 = 17;
+const BTF_KIND_TYPE_TAG: u32 = 18;
+const BTF_KIND_ENUM64: u32 = 19;
+
+/// 수식자/배열 체인 최대 깊이 (잘못된 BTF의 순환 방지)
+const MAX_TYPE_DEPTH: usize = 32;
+
+/// 필드 크기 계산에 필요한 BTF 타입 정보
+enum BtfType {
+    /// 크기가 정해진 타입 (int, enum, struct, union, float)
+    Sized(usize),
+    /// 다른 타입을 가리키는 수식자 (typedef, const, volatile, restrict, type_tag)
+    Alias(u32),
+    /// 배열 (원소 타입, 원소 수)
+    Array(u32, usize),
+    Pointer,
+    /// 크기가 없는 타입 (func, var, datasec 등)
+    Other,
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> Option<u32> {
+    bytes.get(offset..offset + 4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+/// 타입 크기 (수식자와 배열을 따라감)
+fn type_size(types: &[BtfType], id: u32, depth: usize) -> Option<usize> {
+    if depth > MAX_TYPE_DEPTH {
+        return None;
+    }
+    match types.get(id as usize)? {
+        BtfType::Sized(size) => Some(*size),
+        BtfType::Alias(target) => type_size(types, *target, depth + 1),
+        BtfType::Array(element, count) => type_size(types, *element, depth + 1).map(|size| size * count),
+        BtfType::Pointer => Some(8),
+        BtfType::Other => None,
+    }
+}
+
+/// 원시 BTF(.BTF 섹션)에서 이름 있는 구조체 레이아웃 추출
+pub fn parse_btf(data: &[u8]) -> Result<Vec<StructLayout>, LayoutError> {
+    let invalid = |reason: &str| LayoutError::InvalidBtf(reason.to_string());
+    if data.get(..2) != Some(&BTF_MAGIC.to_le_bytes()[..]) {
+        return Err(invalid("bad magic (expected a little-endian .BTF section)"));
+    }
+
+    let header = |offset| read_u32(data, offset).map(|value| value as usize).ok_or_else(|| invalid("truncated header"));
+    let (header_len, type_off, type_len, str_off, str_len) = (header(4)?, header(8)?, header(12)?, header(16)?, header(20)?);
+    let types = data.get(header_len + type_off..header_len + type_off + type_len)
+        .ok_or_else(|| invalid("type section is out of bounds"))?;
+    let strings = data.get(header_len + str_off..header_len + str_off + str_len)
+        .ok_or_else(|| invalid("string section is out of bounds"))?;
+    let name_at = |offset: u32| strings.get(offset as usize..)
+        .and_then(|rest| rest.split(|b| *b == 0).next())
+        .map(|name| String::from_utf8_lossy(name).into_owned())
+        .unwrap_or_default();
+
+    // 타입 id 0은 void
+    let mut kinds = vec![BtfType::Other];
+    let mut structs = Vec::new();
+    let mut pos = 0;
+    while pos < types.len() {
+        let (Some(name_off), Some(info), Some(size_or_type)) =
+            (read_u32(types, pos), read_u32(types, pos + 4), read_u32(types, pos + 8)) else {
+            return Err(LayoutError::InvalidBtf(format!("type {} is truncated", kinds.len())));
+        };
+        let kind = (info >> 24) & 0x1f;
+        let vlen = (info & 0xffff) as usize;
+        let data_start = pos + 12;
+        let extra = match kind {
+            BTF_KIND_INT | BTF_KIND_VAR | BTF_KIND_DECL_TAG => 4,
+            BTF_KIND_ARRAY => 12,
+            BTF_KIND_STRUCT | BTF_KIND_UNION | BTF_KIND_DATASEC | BTF_KIND_ENUM64 => 12 * vlen,
+            BTF_KIND_ENUM | BTF_KIND_FUNC_PROTO => 8 * vlen,
+            BTF_KIND_PTR | BTF_KIND_FWD | BTF_KIND_TYPEDEF | BTF_KIND_VOLATILE | BTF_KIND_CONST
+            | BTF_KIND_RESTRICT | BTF_KIND_FUNC | BTF_KIND_FLOAT | BTF_KIND_TYPE_TAG => 0,
+            other => return Err(LayoutError::InvalidBtf(format!("type {} has unknown kind {}", kinds.len(), other))),
+        };
+        if types.len() < data_start + extra {
+            return Err(LayoutError::InvalidBtf(format!("type {} is truncated", kinds.len())));
+        }
+
+        let word = |offset| read_u32(types, offset).unwrap_or(0);
+        kinds.push(match kind {
+            BTF_KIND_STRUCT => {
+                let members: Vec<(String, u32, usize)> = (0..vlen).map(|index| {
+                    let member = data_start + index * 12;
+                    // kind_flag가 있으면 하위 24비트가 비트 위치 (상위 8비트는 비트필드 크기)
+                    let bits = if info >> 31 == 1 { word(member + 8) & 0x00ff_ffff } else { word(member + 8) };
+                    (name_at(word(member)), word(member + 4), bits as usize / 8)
+                }).collect();
+                structs.push((name_at(name_off), size_or_type as usize, members));
+                BtfType::Sized(size_or_type as usize)
+            },
+            BTF_KIND_INT | BTF_KIND_UNION | BTF_KIND_ENUM | BTF_KIND_ENUM64 | BTF_KIND_FLOAT => {
+                BtfType::Sized(size_or_type as usize)
+            },
+            BTF_KIND_TYPEDEF | BTF_KIND_VOLATILE | BTF_KIND_CONST | BTF_KIND_RESTRICT | BTF_KIND_TYPE_TAG => {
+                BtfType::Alias(size_or_type)
+            },
+            BTF_KIND_ARRAY => BtfType::Array(word(data_start), word(data_start + 8) as usize),
+            BTF_KIND_PTR => BtfType::Pointer,
+            _ => BtfType::Other,
+        });
+        pos = data_start + extra;
+    }
+
+    Ok(structs.into_iter()
+        .filter(|(name, ..)| !name.is_empty())
+        .map(|(name, size, members)| StructLayout {
+            name,
+            size,
+            fields: members.into_iter()
+                .filter(|(name, ..)| !name.is_empty())
+                .map(|(name, ty, offset)| FieldLayout { name, offset, size: type_size(&kinds, ty, 0).unwrap_or(0) })
+                .collect(),
+        })
+        .collect())
+}
+
+/// BTF 구조체를 데몬이 기대하는 레이아웃과 비교해 검증된 레이아웃 반환
+///
+/// 기대하는 필드는 모두 같은 위치와 크기여야 한다. BTF에만 있는 필드는 구조체 크기가
+/// 같으면 무시한다.
+pub fn verify(actual: &[StructLayout]) -> Result<Vec<StructLayout>, LayoutError> {
+    let expected = expected();
+    for structure in &expected {
+        let found = actual.iter()
+            .find(|candidate| candidate.name == structure.name)
+            .ok_or_else(|| LayoutError::MissingStruct(structure.name.clone()))?;
+
+        for field in &structure.fields {
+            let other = found.field(&field.name).ok_or_else(|| LayoutError::MissingField {
+                structure: structure.name.clone(),
+                field: field.name.clone(),
+            })?;
+            if other.offset != field.offset || other.size != field.size {
+                return Err(LayoutError::FieldMismatch {
+                    structure: structure.name.clone(),
+                    field: field.name.clone(),
+                    expected_offset: field.offset,
+                    expected_size: field.size,
+                    actual_offset: other.offset,
+                    actual_size: other.size,
+                });
+            }
+        }
+
+        if found.size != structure.size {
+            return Err(LayoutError::SizeMismatch {
+                structure: structure.name.clone(),
+                expected: structure.size,
+                actual: found.size,
+            });
+        }
+    }
+
+    Ok(expected)
+}
+
+/// 원시 BTF를 파싱해 검증 (`parse_btf` 후 `verify`)
+pub fn verify_btf(data: &[u8]) -> Result<Vec<StructLayout>, LayoutError> {
+    verify(&parse_btf(data)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats_bytes(fields: &[u64]) -> Vec<u8> {
+        fields.iter().flat_map(|v| v.to_le_bytes()).collect()
+    }
+
+    /// 테스트용 BTF 작성기
+    #[derive(Default)]
+    struct BtfFixture {
+        types: Vec<u8>,
+        strings: Vec<u8>,
+        count: u32,
+    }
+
+    impl BtfFixture {
+        fn new() -> Self {
+            Self { strings: vec![0], ..Self::default() }
+        }
+
+        fn name(&mut self, name: &str) -> u32 {
+            if name.is_empty() {
+                return 0;
+            }
+            let offset = self.strings.len() as u32;
+            self.strings.extend_from_slice(name.as_bytes());
+            self.strings.push(0);
+            offset
+        }
+
+        fn push(&mut self, name: &str, kind: u32, vlen: usize, size_or_type: u32, extra: &[u32]) -> u32 {
+            let name = self.name(name);
+            for word in [name, kind << 24 | vlen as u32, size_or_type].iter().chain(extra) {
+                self.types.extend_from_slice(&word.to_le_bytes());
+            }
+            self.count += 1;
+            self.count
+        }
+
+        fn int(&mut self, name: &str, size: u32) -> u32 {
+            self.push(name, BTF_KIND_INT, 0, size, &[size * 8])
+        }
+
+        fn structure(&mut self, name: &str, size: usize, members: &[(&str, u32, usize)]) -> u32 {
+            let extra: Vec<u32> = members.iter()
+                .flat_map(|(member, ty, offset)| [self.name(member), *ty, *offset as u32 * 8])
+                .collect();
+            self.push(name, BTF_KIND_STRUCT, members.len(), size as u32, &extra)
+        }
+
+        fn finish(self) -> Vec<u8> {
+            let mut data = BTF_MAGIC.to_le_bytes().to_vec();
+            data.extend_from_slice(&[1, 0]);
+            for word in [24, 0, self.types.len() as u32, self.types.len() as u32, self.strings.len() as u32] {
+                data.extend_from_slice(&word.to_le_bytes());
+            }
+            data.extend_from_slice(&self.types);
+            data.extend_from_slice(&self.strings);
+            data
+        }
+    }
+
+    /// xdp_filter.c 구조체를 담은 BTF (struct filter_rule 크기와 필드 위치 지정)
+    fn xdp_filter_btf(rule_size: usize, rule_fields: &[FieldDef]) -> Vec<u8> {
+        let mut btf = BtfFixture::new();
+        let u8_t = btf.int("unsigned char", 1);
+        let u16_t = btf.int("unsigned short", 2);
+        let u32_t = btf.int("unsigned int", 4);
+        let u64_t = btf.int("unsigned long long", 8);
+        let uint64_t = btf.push("uint64_t", BTF_KIND_TYPEDEF, 0, u64_t, &[]);
+        let char_t = btf.int("char", 1);
+        let label = btf.push("", BTF_KIND_ARRAY, 0, 0, &[char_t, u32_t, 32]);
+        let ports = btf.push("", BTF_KIND_ARRAY, 0, 0, &[u16_t, u32_t, MAX_RULE_PORTS as u32]);
+
+        let members = |fields: &[FieldDef]| fields.iter().map(|(name, offset, _)| (*name, uint64_t, *offset)).collect::<Vec<_>>();
+        let stats = btf.structure("filter_stats", FilterStats::SIZE, &members(FILTER_STATS_FIELDS));
+        btf.structure("global_stats", GlobalStats::SIZE, &members(GLOBAL_STATS_FIELDS));
+
+        let rule: Vec<_> = rule_fields.iter().map(|(name, offset, size)| {
+            let ty = match (*name, size) {
+                ("label", _) => label,
+                ("src_ports" | "dst_ports", _) => ports,
+                ("stats", _) => stats,
+                (_, 1) => u8_t,
+                (_, 2) => u16_t,
+                _ => u32_t,
+            };
+            (*name, ty, *offset)
+        }).collect();
+        btf.structure("filter_rule", rule_size, &rule);
+        btf.finish()
+    }
+
+    #[test]
+    fn test_global_stats_from_bytes() {
+        let value = stats_bytes(&[100, 6400, 0, 60, 30, 10, 0]);
+        let stats = GlobalStats::from_bytes(&value).unwrap();
+        assert_eq!(stats.packets, 100);
+        assert_eq!(stats.bytes, 6400);
+        assert_eq!(stats.tcp_packets, 60);
+        assert_eq!(stats.other_packets, 0);
+        assert_eq!(stats.aborted_packets, 0);
+
+        let stats = GlobalStats::from_bytes(&stats_bytes(&[100, 6400, 0, 60, 30, 10, 0, 5, 3])).unwrap();
+        assert_eq!((stats.syn_packets, stats.aborted_packets), (5, 3));
+
+        // 프로토콜 카운터가 없는 이전 레이아웃
+        let legacy = GlobalStats::from_bytes(&stats_bytes(&[5, 320])).unwrap();
+        assert_eq!(legacy.packets, 5);
+        assert_eq!(legacy.tcp_packets, 0);
+
+        assert!(GlobalStats::from_bytes(&[0u8; 8]).is_none());
+    }
+
+    #[test]
+    fn test_filter_stats_layout() {
+        let stats = FilterStats {
+            packets: 10,
+            bytes: 640,
+            last_matched: 12_500_000_000,
+            redirected_packets: 8,
+            redirect_failures: 2,
+            aborted_packets: 1,
+        };
+        let bytes = stats.to_bytes();
+        assert_eq!(bytes.len(), std::mem::size_of::<FilterStats>());
+        assert_eq!(bytes[16..24], 12_500_000_000u64.to_le_bytes());
+        assert_eq!(bytes[24..32], 8u64.to_le_bytes());
+        assert_eq!(bytes[40..48], 1u64.to_le_bytes());
+        assert_eq!(FilterStats::from_bytes(&bytes), Some(stats));
+
+        // 중단 카운터가 없는 이전 레이아웃
+        let previous = FilterStats::from_bytes(&bytes[..40]).unwrap();
+        assert_eq!((previous.redirect_failures, previous.aborted_packets), (2, 0));
+
+        // 리디렉션 카운터가 없는 이전 레이아웃
+        let legacy = FilterStats::from_bytes(&bytes[..FilterStats::LEGACY_SIZE]).unwrap();
+        assert_eq!((legacy.packets, legacy.last_matched, legacy.redirected_packets), (10, 12_500_000_000, 0));
+        assert!(FilterStats::from_bytes(&bytes[..16]).is_none());
+
+        let mut total = legacy;
+        total.merge(&stats);
+        assert_eq!((total.packets, total.last_matched, total.redirect_failures, total.aborted_packets), (20, 12_500_000_000, 2, 1));
+    }
+
+    #[test]
+    fn test_protocol_percentages() {
+        let mut total = GlobalStats::from_bytes(&stats_bytes(&[0, 0, 0, 50, 20, 5, 0])).unwrap();
+        let cpu1 = GlobalStats::from_bytes(&stats_bytes(&[0, 0, 0, 25, 0, 0, 0])).unwrap();
+        total.merge(&cpu1);
+
+        let protocols = total.protocol_stats();
+        assert_eq!(protocols.total(), 100);
+        assert_eq!(protocols.percent(protocols.tcp), 75.0);
+        assert_eq!(protocols.percent(protocols.udp), 20.0);
+        assert_eq!(protocols.percent(protocols.icmp), 5.0);
+        assert_eq!(protocols.percent(protocols.other), 0.0);
+
+        assert_eq!(ProtocolStats::default().percent(0), 0.0);
+    }
+
+    #[test]
+    fn test_rule_layout() {
+        // C 구조체와 같은 정렬: tcp_flags 뒤 패딩, 통계 영역은 8바이트 정렬
+        assert_eq!(rule_field("redirect_ifindex"), Some(16..20));
+        assert_eq!(rule_field("label"), Some(RULE_LABEL_OFFSET..RULE_SRC_PORTS_OFFSET));
+        assert_eq!(rule_field("src_ports"), Some(64..80));
+        assert_eq!(rule_field("dst_ports"), Some(80..96));
+        assert_eq!(rule_field("stats"), Some(RULE_STATS_OFFSET..RULE_VALUE_SIZE));
+        assert_eq!(RULE_STATS_OFFSET % 8, 0);
+        assert_eq!(rule_field("padding"), None);
+
+        let rule = &expected()[0];
+        assert_eq!((rule.name.as_str(), rule.size), ("filter_rule", 144));
+        assert_eq!(rule.field("stats").map(|field| field.offset), Some(96));
+    }
+
+    #[test]
+    fn test_port_list_encoding() {
+        let bytes = encode_port_list(&[19, 53, 11211]).unwrap();
+        assert_eq!(bytes[..6], [19, 0, 53, 0, 0xcb, 0x2b]);
+        assert!(bytes[6..].iter().all(|b| *b == 0));
+        assert_eq!(decode_port_list(&bytes), vec![19, 53, 11211]);
+
+        // 빈 목록은 모두 0 (포트 범위로 매치)
+        assert_eq!(encode_port_list(&[]), Some([0u8; MAX_RULE_PORTS * 2]));
+        assert!(decode_port_list(&[0u8; MAX_RULE_PORTS * 2]).is_empty());
+
+        let full: Vec<u16> = (1..=MAX_RULE_PORTS as u16).collect();
+        assert_eq!(decode_port_list(&encode_port_list(&full).unwrap()), full);
+        assert_eq!(encode_port_list(&[1, 2, 3, 4, 5, 6, 7, 8, 9]), None);
+        assert_eq!(encode_port_list(&[53, 0]), None);
+    }
+
+    #[test]
+    fn test_verify_btf() {
+        let btf = xdp_filter_btf(RULE_VALUE_SIZE, FILTER_RULE_FIELDS);
+        let parsed = parse_btf(&btf).unwrap();
+        let stats = parsed.iter().find(|layout| layout.name == "filter_stats").unwrap();
+        assert_eq!(stats.field("last_matched"), Some(&FieldLayout { name: "last_matched".to_string(), offset: 16, size: 8 }));
+        let rule = parsed.iter().find(|layout| layout.name == "filter_rule").unwrap();
+        assert_eq!(rule.field("label").map(|field| field.size), Some(32));
+        assert_eq!(rule.field("src_ports").map(|field| field.size), Some(16));
+        assert_eq!(rule.field("dst_ports").map(|field| (field.offset, field.size)), Some((80, 16)));
+        assert_eq!(verify_btf(&btf).unwrap(), expected());
+
+        // 패딩 없이 기록하던 이전 데몬의 레이아웃
+        let packed: Vec<FieldDef> = FILTER_RULE_FIELDS.iter()
+            .map(|(name, offset, size)| (*name, if *offset > 14 { offset - 1 } else { *offset }, *size))
+            .collect();
+        let err = verify_btf(&xdp_filter_btf(RULE_VALUE_SIZE - 1, &packed)).unwrap_err();
+        assert_eq!(err.to_string(),
+            "struct filter_rule field redirect_ifindex is at offset 15 (4 bytes), expected offset 16 (4 bytes)");
+
+        // 필드는 같지만 끝에 필드가 추가된 구조체
+        let err = verify_btf(&xdp_filter_btf(RULE_VALUE_SIZE + 8, FILTER_RULE_FIELDS)).unwrap_err();
+        assert_eq!(err, LayoutError::SizeMismatch { structure: "filter_rule".to_string(), expected: 144, actual: 152 });
+
+        let err = verify_btf(&xdp_filter_btf(RULE_VALUE_SIZE, &FILTER_RULE_FIELDS[..15])).unwrap_err();
+        assert_eq!(err.to_string(), "struct filter_rule has no field stats");
+
+        let mut empty = BtfFixture::new();
+        empty.int("int", 4);
+        assert_eq!(verify_btf(&empty.finish()).unwrap_err(), LayoutError::MissingStruct("filter_rule".to_string()));
+
+        assert!(matches!(parse_btf(b"\x7fELF"), Err(LayoutError::InvalidBtf(_))));
+        assert!(matches!(parse_btf(&btf[..btf.len() / 2]), Err(LayoutError::InvalidBtf(_))));
+    }
+}
@@ -2,8 +2,20 @@
 // 이 모듈은 CLI와 데몬 간의 공유 코드를 포함합니다
 
 pub mod api;
+pub mod build_info;
+mod client;
+pub mod error;
+#[cfg(feature = "grpc")]
+pub mod grpc;
+pub mod histogram;
+pub mod layout;
+pub mod rulediff;
+pub mod rulemirror;
+pub mod tls;
 pub mod types;
 pub mod utils;
+pub mod validation;
+pub mod xdp;
 
 /// Swift-Guard 버전 정보
 pub const VERSION: &str = "0.1.0";
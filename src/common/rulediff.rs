@@ -0,0 +1,305 @@
+//! 규칙 비교 모듈
+//! 두 규칙 정의를 정규화한 뒤 항목별 변경 사항 계산 (apply --dry-run 미리보기)
+//!
+//! 정규화는 표기만 다른 같은 규칙이 변경으로 보이지 않게 한다: 프리픽스의 호스트
+//! 비트 제거(10.0.0.5/8 → 10.0.0.0/8), 뒤집힌 포트 범위와 포트 목록 정렬, 설명, 인터페이스 이름과
+//! 카운터 그룹, 만료 알림 웹훅의 앞뒤 공백 제거, 할당량이 있는 규칙의 생략된 할당량 액션을 drop으로 채움.
+
+use crate::api::{FieldChange, RuleSpec};
+use crate::utils::{self, IpPrefix};
+
+/// 규칙 정의 정규화
+pub fn normalize(spec: &RuleSpec) -> RuleSpec {
+    let (src_port_min, src_port_max) = ordered(spec.src_port_min, spec.src_port_max);
+    let (dst_port_min, dst_port_max) = ordered(spec.dst_port_min, spec.dst_port_max);
+
+    RuleSpec {
+        label: spec.label.clone(),
+        src_ip: spec.src_ip.as_deref().map(normalize_prefix),
+        dst_ip: spec.dst_ip.as_deref().map(normalize_prefix),
+        src_port_min,
+        src_port_max,
+        dst_port_min,
+        dst_port_max,
+        protocol: spec.protocol,
+        tcp_flags: spec.tcp_flags,
+        action: spec.action,
+        redirect_if: non_empty(spec.redirect_if.as_deref()),
+        redirect_cpu: spec.redirect_cpu,
+        priority: spec.priority,
+        rate_limit: spec.rate_limit,
+        expire: spec.expire,
+        description: non_empty(spec.description.as_deref()),
+        quota_bytes: spec.quota_bytes,
+        quota_packets: spec.quota_packets,
+        // 할당량이 있으면 생략한 액션은 drop
+        quota_action: spec.quota_action
+            .or_else(|| (spec.quota_bytes.is_some() || spec.quota_packets.is_some()).then_some(2)),
+        counter_group: non_empty(spec.counter_group.as_deref()),
+        src_port_list: sorted(&spec.src_port_list),
+        dst_port_list: sorted(&spec.dst_port_list),
+        owner_webhook: non_empty(spec.owner_webhook.as_deref()),
+        notify_before: spec.notify_before,
+    }
+}
+
+/// 프리픽스 정규화 (해석할 수 없는 값은 공백만 제거)
+fn normalize_prefix(prefix: &str) -> String {
+    match IpPrefix::parse(prefix) {
+        Ok(prefix) => prefix.to_string(),
+        Err(_) => prefix.trim().to_string(),
+    }
+}
+
+fn ordered(a: u16, b: u16) -> (u16, u16) {
+    (a.min(b), a.max(b))
+}
+
+fn sorted(ports: &[u16]) -> Vec<u16> {
+    let mut ports = ports.to_vec();
+    ports.sort_unstable();
+    ports.dedup();
+    ports
+}
+
+fn non_empty(value: Option<&str>) -> Option<String> {
+    value.map(str::trim).filter(|value| !value.is_empty()).map(str::to_string)
+}
+
+/// 두 규칙 정의의 항목별 차이 (정규화 후 비교, 레이블은 비교하지 않음)
+pub fn diff_specs(old: &RuleSpec, new: &RuleSpec) -> Vec<FieldChange> {
+    let (old, new) = (normalize(old), normalize(new));
+    let mut changes = Vec::new();
+
+    let mut field = |name: &str, changed: bool, render: &dyn Fn(&RuleSpec) -> String| {
+        if changed {
+            changes.push(FieldChange { field: name.to_string(), old: render(&old), new: render(&new) });
+        }
+    };
+
+    field("src_ip", old.src_ip != new.src_ip, &|spec| or_any(spec.src_ip.clone()));
+    field("dst_ip", old.dst_ip != new.dst_ip, &|spec| or_any(spec.dst_ip.clone()));
+    field("src_port", (old.src_port_min, old.src_port_max, &old.src_port_list) != (new.src_port_min, new.src_port_max, &new.src_port_list),
+        &|spec| ports(spec.src_port_min, spec.src_port_max, &spec.src_port_list));
+    field("dst_port", (old.dst_port_min, old.dst_port_max, &old.dst_port_list) != (new.dst_port_min, new.dst_port_max, &new.dst_port_list),
+        &|spec| ports(spec.dst_port_min, spec.dst_port_max, &spec.dst_port_list));
+    field("protocol", old.protocol != new.protocol, &|spec| utils::protocol_num_to_name(spec.protocol));
+    field("tcp_flags", old.tcp_flags != new.tcp_flags, &|spec| match spec.tcp_flags {
+        0 => "-".to_string(),
+        flags => utils::tcp_flags_to_string(flags),
+    });
+    field("action", old.action != new.action, &|spec| utils::action_num_to_name(spec.action));
+    field("redirect_if", old.redirect_if != new.redirect_if, &|spec| or_none(spec.redirect_if.clone()));
+    field("redirect_cpu", old.redirect_cpu != new.redirect_cpu, &|spec| or_none(spec.redirect_cpu.map(|cpu| cpu.to_string())));
+    field("priority", old.priority != new.priority, &|spec| spec.priority.to_string());
+    field("rate_limit", old.rate_limit != new.rate_limit, &|spec| spec.rate_limit.to_string());
+    field("expire", old.expire != new.expire, &|spec| spec.expire.to_string());
+    field("description", old.description != new.description, &|spec| match &spec.description {
+        Some(description) => format!("{:?}", description),
+        None => "-".to_string(),
+    });
+    field("quota_bytes", old.quota_bytes != new.quota_bytes, &|spec| or_none(spec.quota_bytes.map(utils::format_size)));
+    field("quota_packets", old.quota_packets != new.quota_packets, &|spec| or_none(spec.quota_packets.map(|packets| packets.to_string())));
+    field("quota_action", old.quota_action != new.quota_action, &|spec| or_none(spec.quota_action.map(utils::action_num_to_name)));
+    field("counter_group", old.counter_group != new.counter_group, &|spec| or_none(spec.counter_group.clone()));
+    field("owner_webhook", old.owner_webhook != new.owner_webhook, &|spec| or_none(spec.owner_webhook.clone()));
+    field("notify_before", old.notify_before != new.notify_before, &|spec| or_none(spec.notify_before.map(|secs| secs.to_string())));
+
+    changes
+}
+
+/// 포트 조건 표시 (목록이 있으면 쉼표 목록, 없으면 범위)
+fn ports(min: u16, max: u16, list: &[u16]) -> String {
+    if list.is_empty() {
+        or_any(utils::port_range_to_string(min, max))
+    } else {
+        utils::port_list_to_string(list)
+    }
+}
+
+fn or_any(value: Option<String>) -> String {
+    value.unwrap_or_else(|| "any".to_string())
+}
+
+fn or_none(value: Option<String>) -> String {
+    value.unwrap_or_else(|| "-".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn spec() -> RuleSpec {
+        RuleSpec {
+            label: "web".to_string(),
+            src_ip: Some("192.168.1.0/24".to_string()),
+            dst_ip: None,
+            src_port_min: 0,
+            src_port_max: 65535,
+            dst_port_min: 80,
+            dst_port_max: 80,
+            protocol: 6,
+            tcp_flags: 0,
+            action: 1,
+            redirect_if: None,
+            redirect_cpu: None,
+            priority: 0,
+            rate_limit: 0,
+            expire: 0,
+            description: None,
+            quota_bytes: None,
+            quota_packets: None,
+            quota_action: None,
+            counter_group: None,
+            src_port_list: Vec::new(),
+            dst_port_list: Vec::new(),
+            owner_webhook: None,
+            notify_before: None,
+        }
+    }
+
+    fn change(field: &str, old: &str, new: &str) -> FieldChange {
+        FieldChange { field: field.to_string(), old: old.to_string(), new: new.to_string() }
+    }
+
+    #[test]
+    fn test_diff_specs() {
+        let new = RuleSpec {
+            src_ip: Some("10.0.0.1".to_string()),
+            dst_port_min: 8000,
+            dst_port_max: 8080,
+            tcp_flags: 0x12,
+            action: 2,
+            priority: 10,
+            description: Some("web servers".to_string()),
+            ..spec()
+        };
+
+        assert_eq!(diff_specs(&spec(), &new), [
+            change("src_ip", "192.168.1.0/24", "10.0.0.1"),
+            change("dst_port", "80", "8000-8080"),
+            change("tcp_flags", "-", "SYN,ACK"),
+            change("action", "pass", "drop"),
+            change("priority", "0", "10"),
+            change("description", "-", "\"web servers\""),
+        ]);
+        assert_eq!(diff_specs(&new, &spec())[0], change("src_ip", "10.0.0.1", "192.168.1.0/24"));
+
+        // 할당량이 있으면 생략한 액션은 drop과 같음
+        let quota = RuleSpec { quota_bytes: Some(10 << 30), ..spec() };
+        assert_eq!(diff_specs(&spec(), &quota), [
+            change("quota_bytes", "-", "10.00 GB"),
+            change("quota_action", "-", "drop"),
+        ]);
+        assert!(diff_specs(&quota, &RuleSpec { quota_action: Some(2), ..quota.clone() }).is_empty());
+        assert_eq!(diff_specs(&quota, &RuleSpec { quota_action: Some(4), ..quota.clone() }),
+            [change("quota_action", "drop", "count")]);
+
+        let grouped = RuleSpec { action: 4, counter_group: Some("web".to_string()), ..spec() };
+        assert_eq!(diff_specs(&RuleSpec { action: 4, ..spec() }, &grouped), [change("counter_group", "-", "web")]);
+
+        // 포트 목록은 순서와 관계없이 비교
+        let listed = RuleSpec { src_port_list: vec![123, 53], ..spec() };
+        assert_eq!(diff_specs(&spec(), &listed), [change("src_port", "any", "53,123")]);
+        assert!(diff_specs(&listed, &RuleSpec { src_port_list: vec![53, 123], ..spec() }).is_empty());
+        let web = RuleSpec { dst_port_min: 0, dst_port_max: 65535, dst_port_list: vec![443, 80, 8080], ..spec() };
+        assert_eq!(diff_specs(&spec(), &web), [change("dst_port", "80", "80,443,8080")]);
+
+        let owned = RuleSpec { expire: 3600, ..spec() }.with_owner_webhook("http://ops:8080/expiry", Some(600));
+        assert_eq!(diff_specs(&RuleSpec { expire: 3600, ..spec() }, &owned), [
+            change("owner_webhook", "-", "http://ops:8080/expiry"),
+            change("notify_before", "-", "600"),
+        ]);
+    }
+
+    #[test]
+    fn test_diff_ignores_cosmetic_differences() {
+        let cosmetic = RuleSpec {
+            label: "renamed".to_string(),
+            src_ip: Some(" 192.168.1.77/24".to_string()),
+            dst_port_min: 80,
+            dst_port_max: 80,
+            description: Some("  ".to_string()),
+            counter_group: Some(" ".to_string()),
+            ..spec()
+        };
+        assert!(diff_specs(&spec(), &cosmetic).is_empty());
+
+        let host = RuleSpec { src_ip: Some("10.0.0.1/32".to_string()), ..spec() };
+        assert!(diff_specs(&host, &RuleSpec { src_ip: Some("10.0.0.1".to_string()), ..spec() }).is_empty());
+        assert_eq!(diff_specs(&host, &RuleSpec { src_ip: None, ..spec() }),
+            [change("src_ip", "10.0.0.1", "any")]);
+    }
+
+    fn prefix() -> impl Strategy<Value = Option<String>> {
+        prop::option::of((any::<u32>(), 0u32..=32).prop_map(|(ip, prefix_len)| {
+            format!("{}/{}", utils::ipv4_to_string(ip), prefix_len)
+        }))
+    }
+
+    fn text() -> impl Strategy<Value = Option<String>> {
+        prop::option::of("[ a-z0-9-]{0,12}")
+    }
+
+    prop_compose! {
+        fn rule_spec()(
+            (src_ip, dst_ip) in (prefix(), prefix()),
+            (src_port_min, src_port_max, dst_port_min, dst_port_max) in any::<(u16, u16, u16, u16)>(),
+            (protocol, tcp_flags, action) in any::<(u8, u8, u8)>(),
+            (redirect_if, redirect_cpu) in (text(), any::<Option<u32>>()),
+            (priority, rate_limit, expire) in any::<(u32, u32, u32)>(),
+            (description, counter_group) in (text(), text()),
+            (quota_bytes, quota_packets, quota_action) in any::<(Option<u64>, Option<u64>, Option<u8>)>(),
+            (src_port_list, dst_port_list) in (prop::collection::vec(1u16.., 0..=8), prop::collection::vec(1u16.., 0..=8)),
+            (owner_webhook, notify_before) in (text(), any::<Option<u32>>()),
+        ) -> RuleSpec {
+            RuleSpec {
+                label: "rule".to_string(),
+                src_ip, dst_ip, src_port_min, src_port_max, dst_port_min, dst_port_max,
+                protocol, tcp_flags, action, redirect_if, redirect_cpu, priority, rate_limit, expire,
+                description, quota_bytes, quota_packets, quota_action, counter_group, src_port_list, dst_port_list,
+                owner_webhook, notify_before,
+            }
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn prop_diff_with_self_is_empty(spec in rule_spec()) {
+            prop_assert!(diff_specs(&spec, &spec).is_empty());
+            prop_assert!(diff_specs(&spec, &normalize(&spec)).is_empty());
+        }
+
+        #[test]
+        fn prop_normalize_is_idempotent(spec in rule_spec()) {
+            let once = normalize(&spec);
+            prop_assert_eq!(normalize(&once), once);
+        }
+
+        #[test]
+        fn prop_cosmetic_changes_are_ignored(spec in rule_spec(), host_bits in any::<u32>()) {
+            // 같은 네트워크의 다른 주소, 뒤집은 포트 범위와 포트 목록, 공백을 덧붙인 문자열
+            let rewrite = |prefix: &Option<String>| prefix.as_ref().map(|prefix| {
+                let (ip, prefix_len) = utils::parse_ip_prefix(prefix).unwrap();
+                let host_mask = if prefix_len == 0 { u32::MAX } else { u32::MAX.checked_shr(prefix_len).unwrap_or(0) };
+                format!("{}/{}", utils::ipv4_to_string(ip | (host_bits & host_mask)), prefix_len)
+            });
+            let pad = |text: &Option<String>| text.as_ref().map(|text| format!(" {} ", text));
+            let cosmetic = RuleSpec {
+                src_ip: rewrite(&spec.src_ip),
+                dst_ip: rewrite(&spec.dst_ip),
+                src_port_min: spec.src_port_max,
+                src_port_max: spec.src_port_min,
+                src_port_list: spec.src_port_list.iter().rev().copied().collect(),
+                dst_port_list: spec.dst_port_list.iter().rev().copied().collect(),
+                redirect_if: pad(&spec.redirect_if),
+                description: pad(&spec.description),
+                counter_group: pad(&spec.counter_group),
+                owner_webhook: pad(&spec.owner_webhook),
+                ..spec.clone()
+            };
+            prop_assert_eq!(diff_specs(&spec, &cosmetic), Vec::new());
+        }
+    }
+}
@@ -0,0 +1,140 @@
+//! 규칙 미러 모듈
+//! `WatchRules` 구독 프레임으로 데몬의 규칙 집합을 재구성 (자동화 도구용)
+//!
+//! 스냅샷(`seq`가 있는 `Rules` 프레임)은 상태를 통째로 바꾸고, 변경(`RuleChanged`)은
+//! 마지막으로 적용한 순번보다 큰 순번만 받는다. 순서가 어긋나거나 상태와 맞지 않는
+//! 변경은 오류로 돌려주므로, 소비자는 오류가 나면 다시 구독해 새 스냅샷부터 받는다.
+
+use std::collections::BTreeMap;
+
+use thiserror::Error;
+
+use crate::api::{ApiResponse, RuleChangeKind, RuleInfo};
+
+/// 구독 프레임 적용 오류
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum MirrorError {
+    /// 스냅샷 전에 받은 변경
+    #[error("rule change {seq} arrived before a snapshot")]
+    NoSnapshot { seq: u64 },
+    /// 마지막으로 적용한 순번보다 크지 않은 변경
+    #[error("rule change {seq} is out of order (last applied {last})")]
+    OutOfOrder { seq: u64, last: u64 },
+    /// 있는 규칙의 추가, 없는 규칙의 갱신이나 삭제
+    #[error("rule change {seq} ({kind}) does not match the mirrored state of {key}")]
+    Inconsistent { seq: u64, kind: &'static str, key: String },
+    /// 구독 프레임이 아닌 응답
+    #[error("unexpected response in rule watch stream")]
+    UnexpectedFrame,
+}
+
+/// 구독 프레임으로 재구성한 규칙 집합 (규칙 키 순서)
+#[derive(Debug, Default)]
+pub struct RuleMirror {
+    /// 마지막으로 적용한 순번 (스냅샷 전이면 None)
+    seq: Option<u64>,
+    rules: BTreeMap<String, RuleInfo>,
+}
+
+impl RuleMirror {
+    /// 구독 프레임 하나 적용
+    pub fn apply(&mut self, frame: ApiResponse) -> Result<(), MirrorError> {
+        match frame {
+            ApiResponse::Rules { rules, seq: Some(seq), .. } => {
+                self.rules = rules.into_iter().map(|rule| (rule.key(), rule)).collect();
+                self.seq = Some(seq);
+                Ok(())
+            },
+            ApiResponse::RuleChanged { seq, kind, rule } => self.apply_change(seq, kind, rule),
+            _ => Err(MirrorError::UnexpectedFrame),
+        }
+    }
+
+    fn apply_change(&mut self, seq: u64, kind: RuleChangeKind, rule: RuleInfo) -> Result<(), MirrorError> {
+        let last = self.seq.ok_or(MirrorError::NoSnapshot { seq })?;
+        if seq <= last {
+            return Err(MirrorError::OutOfOrder { seq, last });
+        }
+
+        let key = rule.key();
+        // 추가는 없는 규칙에만, 갱신과 삭제는 있는 규칙에만 적용
+        if self.rules.contains_key(&key) == (kind == RuleChangeKind::Added) {
+            return Err(MirrorError::Inconsistent { seq, kind: kind.as_str(), key });
+        }
+        match kind {
+            RuleChangeKind::Added | RuleChangeKind::Updated => self.rules.insert(key, rule),
+            RuleChangeKind::Deleted => self.rules.remove(&key),
+        };
+
+        self.seq = Some(seq);
+        Ok(())
+    }
+
+    /// 마지막으로 적용한 순번
+    pub fn seq(&self) -> Option<u64> {
+        self.seq
+    }
+
+    /// 재구성한 규칙 (규칙 키 순서)
+    pub fn rules(&self) -> impl Iterator<Item = &RuleInfo> + '_ {
+        self.rules.values()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(namespace: &str, label: &str, priority: u32) -> RuleInfo {
+        RuleInfo {
+            label: label.to_string(),
+            namespace: namespace.to_string(),
+            protocol: "tcp".to_string(),
+            priority,
+            ..RuleInfo::default()
+        }
+    }
+
+    fn snapshot(seq: u64, rules: Vec<RuleInfo>) -> ApiResponse {
+        ApiResponse::Rules { total_count: rules.len(), rules, next_cursor: None, seq: Some(seq) }
+    }
+
+    fn change(seq: u64, kind: RuleChangeKind, rule: RuleInfo) -> ApiResponse {
+        ApiResponse::RuleChanged { seq, kind, rule }
+    }
+
+    fn labels(mirror: &RuleMirror) -> Vec<(String, u32)> {
+        mirror.rules().map(|rule| (rule.key(), rule.priority)).collect()
+    }
+
+    #[test]
+    fn test_apply_snapshot_and_changes() {
+        let mut mirror = RuleMirror::default();
+        assert_eq!(mirror.apply(change(1, RuleChangeKind::Added, rule("default", "a", 0))),
+            Err(MirrorError::NoSnapshot { seq: 1 }));
+
+        mirror.apply(snapshot(4, vec![rule("default", "b", 0), rule("team", "a", 0)])).unwrap();
+        mirror.apply(change(5, RuleChangeKind::Added, rule("default", "a", 1))).unwrap();
+        // 다른 네임스페이스 변경은 오지 않으므로 순번이 건너뛸 수 있음
+        mirror.apply(change(8, RuleChangeKind::Updated, rule("default", "b", 9))).unwrap();
+        mirror.apply(change(9, RuleChangeKind::Deleted, rule("team", "a", 0))).unwrap();
+        assert_eq!(mirror.seq(), Some(9));
+        assert_eq!(labels(&mirror), [("a".to_string(), 1), ("b".to_string(), 9)]);
+
+        // 순서가 어긋나거나 상태와 맞지 않는 변경은 적용하지 않음
+        assert_eq!(mirror.apply(change(9, RuleChangeKind::Deleted, rule("default", "a", 1))),
+            Err(MirrorError::OutOfOrder { seq: 9, last: 9 }));
+        assert!(matches!(mirror.apply(change(10, RuleChangeKind::Added, rule("default", "a", 2))),
+            Err(MirrorError::Inconsistent { seq: 10, kind: "added", .. })));
+        assert!(matches!(mirror.apply(change(11, RuleChangeKind::Updated, rule("default", "z", 0))),
+            Err(MirrorError::Inconsistent { kind: "updated", .. })));
+        assert_eq!(mirror.apply(ApiResponse::Rules { rules: Vec::new(), total_count: 0, next_cursor: None, seq: None }),
+            Err(MirrorError::UnexpectedFrame));
+        assert_eq!(labels(&mirror), [("a".to_string(), 1), ("b".to_string(), 9)]);
+
+        // 새 스냅샷은 상태를 통째로 바꿈
+        mirror.apply(snapshot(20, vec![rule("default", "c", 0)])).unwrap();
+        assert_eq!(mirror.seq(), Some(20));
+        assert_eq!(labels(&mirror), [("c".to_string(), 0)]);
+    }
+}
@@ -0,0 +1,151 @@
+// Swift-Guard TLS
+// API 전송 계층의 TLS 설정 생성 및 핸드셰이크 오류 해석
+
+use std::fs::File;
+use std::io::{self, BufReader};
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Context, Result};
+use rustls::server::AllowAnyAuthenticatedClient;
+use rustls::{AlertDescription, Certificate, CertificateError, ClientConfig, PrivateKey, RootCertStore, ServerConfig};
+
+/// PEM 파일에서 인증서 체인 로드
+pub fn load_certs(path: &Path) -> Result<Vec<Certificate>> {
+    let file = File::open(path)
+        .with_context(|| format!("Failed to open certificate file: {}", path.display()))?;
+    let certs = rustls_pemfile::certs(&mut BufReader::new(file))
+        .with_context(|| format!("Failed to parse certificate file: {}", path.display()))?;
+
+    if certs.is_empty() {
+        return Err(anyhow!("No certificates found in {}", path.display()));
+    }
+
+    Ok(certs.into_iter().map(Certificate).collect())
+}
+
+/// PEM 파일에서 개인 키 로드 (PKCS#8, PKCS#1, SEC1)
+pub fn load_private_key(path: &Path) -> Result<PrivateKey> {
+    let file = File::open(path)
+        .with_context(|| format!("Failed to open key file: {}", path.display()))?;
+    let mut reader = BufReader::new(file);
+
+    loop {
+        match rustls_pemfile::read_one(&mut reader)
+            .with_context(|| format!("Failed to parse key file: {}", path.display()))? {
+            Some(rustls_pemfile::Item::PKCS8Key(key))
+            | Some(rustls_pemfile::Item::RSAKey(key))
+            | Some(rustls_pemfile::Item::ECKey(key)) => return Ok(PrivateKey(key)),
+            Some(_) => continue,
+            None => return Err(anyhow!("No private key found in {}", path.display())),
+        }
+    }
+}
+
+/// PEM 파일의 CA 인증서로 신뢰 저장소 생성
+fn load_roots(path: &Path) -> Result<RootCertStore> {
+    let mut roots = RootCertStore::empty();
+    for cert in load_certs(path)? {
+        roots.add(&cert)
+            .with_context(|| format!("Invalid CA certificate in {}", path.display()))?;
+    }
+    Ok(roots)
+}
+
+/// 서버 TLS 설정 생성 (client_ca가 있으면 클라이언트 인증서 필수)
+pub fn server_config(cert_path: &Path, key_path: &Path, client_ca_path: Option<&Path>) -> Result<Arc<ServerConfig>> {
+    let certs = load_certs(cert_path)?;
+    let key = load_private_key(key_path)?;
+    let builder = ServerConfig::builder().with_safe_defaults();
+
+    let config = match client_ca_path {
+        Some(path) => builder
+            .with_client_cert_verifier(AllowAnyAuthenticatedClient::new(load_roots(path)?).boxed())
+            .with_single_cert(certs, key),
+        None => builder
+            .with_no_client_auth()
+            .with_single_cert(certs, key),
+    }.context("Invalid server certificate or key")?;
+
+    Ok(Arc::new(config))
+}
+
+/// 클라이언트 TLS 설정 생성 (identity = (인증서, 키) 경로, 상호 TLS용)
+pub fn client_config(ca_path: &Path, identity: Option<(&Path, &Path)>) -> Result<Arc<ClientConfig>> {
+    let builder = ClientConfig::builder()
+        .with_safe_defaults()
+        .with_root_certificates(load_roots(ca_path)?);
+
+    let config = match identity {
+        Some((cert_path, key_path)) => builder
+            .with_client_auth_cert(load_certs(cert_path)?, load_private_key(key_path)?)
+            .context("Invalid client certificate or key")?,
+        None => builder.with_no_client_auth(),
+    };
+
+    Ok(Arc::new(config))
+}
+
+/// 클라이언트 인증서가 필요한 서버에 인증서 없이 연결했을 때의 설명
+pub const CLIENT_CERT_REQUIRED: &str = "server requires a client certificate; pass --client-cert and --client-key";
+
+/// TLS 핸드셰이크 오류를 조치 가능한 설명으로 변환 (TLS 오류가 아니면 None)
+pub fn describe_handshake_error(error: &io::Error, server_name: &str) -> Option<String> {
+    let error = error.get_ref()?.downcast_ref::<rustls::Error>()?;
+
+    let message = match error {
+        rustls::Error::InvalidCertificate(CertificateError::Expired) => {
+            "server certificate has expired; renew the daemon certificate".to_string()
+        },
+        rustls::Error::InvalidCertificate(CertificateError::NotValidYet) => {
+            "server certificate is not valid yet; check the clock on both hosts".to_string()
+        },
+        rustls::Error::InvalidCertificate(CertificateError::NotValidForName) => {
+            format!("server certificate is not valid for '{}'; connect using a name listed in the certificate \
+                     or reissue it with that name", server_name)
+        },
+        rustls::Error::InvalidCertificate(CertificateError::UnknownIssuer) => {
+            "server certificate is not signed by the CA given with --ca-cert".to_string()
+        },
+        rustls::Error::AlertReceived(AlertDescription::CertificateRequired)
+        | rustls::Error::AlertReceived(AlertDescription::HandshakeFailure) => {
+            CLIENT_CERT_REQUIRED.to_string()
+        },
+        rustls::Error::AlertReceived(AlertDescription::BadCertificate)
+        | rustls::Error::AlertReceived(AlertDescription::UnknownCA) => {
+            "server rejected the client certificate; it must be signed by the daemon's client CA".to_string()
+        },
+        rustls::Error::AlertReceived(AlertDescription::CertificateExpired) => {
+            "server rejected the client certificate as expired".to_string()
+        },
+        other => format!("TLS error: {}", other),
+    };
+
+    Some(message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_describe_handshake_error() {
+        let expired = io::Error::new(io::ErrorKind::InvalidData,
+            rustls::Error::InvalidCertificate(CertificateError::Expired));
+        assert!(describe_handshake_error(&expired, "host").unwrap().contains("expired"));
+
+        let mismatch = io::Error::new(io::ErrorKind::InvalidData,
+            rustls::Error::InvalidCertificate(CertificateError::NotValidForName));
+        assert!(describe_handshake_error(&mismatch, "10.0.0.1").unwrap().contains("'10.0.0.1'"));
+
+        let refused = io::Error::from(io::ErrorKind::ConnectionRefused);
+        assert!(describe_handshake_error(&refused, "host").is_none());
+    }
+
+    #[test]
+    fn test_load_missing_files() {
+        let missing = Path::new("/nonexistent/swift-guard.pem");
+        assert!(load_certs(missing).is_err());
+        assert!(load_private_key(missing).is_err());
+    }
+}
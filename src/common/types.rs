@@ -14,6 +14,7 @@ pub enum XdpMode {
 
 impl XdpMode {
     /// 문자열에서 XDP 모드 파싱
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(s: &str) -> Option<Self> {
         match s.to_lowercase().as_str() {
             "driver" => Some(Self::Driver),
@@ -59,6 +60,7 @@ impl ActionType {
     }
     
     /// 문자열에서 액션 타입 파싱
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(s: &str) -> Option<Self> {
         match s.to_lowercase().as_str() {
             "pass" => Some(Self::Pass),
@@ -85,50 +87,64 @@ impl ActionType {
 pub enum ProtocolType {
     /// ICMP
     Icmp = 1,
+    /// IP-in-IP
+    Ipip = 4,
     /// TCP
     Tcp = 6,
     /// UDP
     Udp = 17,
+    /// GRE
+    Gre = 47,
+    /// IPsec ESP
+    Esp = 50,
+    /// IPsec AH
+    Ah = 51,
+    /// ICMPv6
+    Icmpv6 = 58,
+    /// SCTP
+    Sctp = 132,
     /// 모든 프로토콜
     Any = 255,
 }
 
 impl ProtocolType {
+    /// 이름이 있는 모든 프로토콜
+    pub const ALL: [Self; 10] = [
+        Self::Icmp, Self::Ipip, Self::Tcp, Self::Udp, Self::Gre,
+        Self::Esp, Self::Ah, Self::Icmpv6, Self::Sctp, Self::Any,
+    ];
+    
     /// 숫자에서 프로토콜 타입 변환
     pub fn from_u8(value: u8) -> Option<Self> {
-        match value {
-            1 => Some(Self::Icmp),
-            6 => Some(Self::Tcp),
-            17 => Some(Self::Udp),
-            255 => Some(Self::Any),
-            _ => None,
-        }
+        Self::ALL.iter().copied().find(|p| *p as u8 == value)
     }
     
     /// 문자열에서 프로토콜 타입 파싱
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(s: &str) -> Option<Self> {
-        match s.to_lowercase().as_str() {
-            "icmp" => Some(Self::Icmp),
-            "tcp" => Some(Self::Tcp),
-            "udp" => Some(Self::Udp),
-            "any" => Some(Self::Any),
-            _ => None,
-        }
+        let s = s.to_lowercase();
+        Self::ALL.iter().copied().find(|p| p.to_str() == s)
     }
     
     /// 프로토콜 타입을 문자열로 변환
     pub fn to_str(&self) -> &'static str {
         match self {
             Self::Icmp => "icmp",
+            Self::Ipip => "ipip",
             Self::Tcp => "tcp",
             Self::Udp => "udp",
+            Self::Gre => "gre",
+            Self::Esp => "esp",
+            Self::Ah => "ah",
+            Self::Icmpv6 => "icmpv6",
+            Self::Sctp => "sctp",
             Self::Any => "any",
         }
     }
 }
 
 /// TCP 플래그
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub struct TcpFlags(pub u8);
 
 impl TcpFlags {
@@ -155,6 +171,7 @@ impl TcpFlags {
     }
     
     /// 문자열에서 TCP 플래그 파싱
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(s: &str) -> Self {
         let mut flags = Self::new();
         
@@ -192,6 +209,83 @@ impl TcpFlags {
     }
 }
 
+/// XDP 프로그램이 처리하는 매치 기능
+///
+/// BPF 오브젝트의 `features` 섹션에 u64 비트맵으로 기록되며, 데몬은 로드할 때 읽어
+/// 프로그램이 처리하지 못하는 매치 필드를 쓰는 규칙을 거부한다 (`--force`면 경고만).
+/// 비트 값은 src/bpf/xdp_filter.c의 `FEATURE_*`와 같아야 한다.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct XdpFeatures(pub u64);
+
+impl XdpFeatures {
+    /// TCP 플래그 매치 (tcp_flags)
+    pub const TCP_FLAGS: u64 = 1 << 0;
+    /// 포트 목록 매치 (src_port_list, dst_port_list)
+    pub const PORT_LIST: u64 = 1 << 1;
+    /// CPU 리디렉션 (redirect_cpu)
+    pub const REDIRECT_CPU: u64 = 1 << 2;
+    /// 레이트 리밋 (rate_limit, 한도를 넘은 패킷은 버림)
+    pub const RATE_LIMIT: u64 = 1 << 3;
+    /// 목적지 주소 매치 (dst_ip, 아직 어떤 XDP 프로그램도 비교하지 않음)
+    pub const DST_IP: u64 = 1 << 4;
+
+    /// 기능 이름 (bpf-info, doctor, 오류 메시지)
+    const NAMES: [(u64, &'static str); 5] = [
+        (Self::TCP_FLAGS, "tcp_flags"),
+        (Self::PORT_LIST, "port_list"),
+        (Self::REDIRECT_CPU, "redirect_cpu"),
+        (Self::RATE_LIMIT, "rate_limit"),
+        (Self::DST_IP, "dst_ip"),
+    ];
+
+    /// 이 버전의 XDP 프로그램이 처리하는 모든 기능 (`DST_IP`는 비교할 때까지 빠짐)
+    pub const ALL: Self = Self(Self::TCP_FLAGS | Self::PORT_LIST | Self::REDIRECT_CPU | Self::RATE_LIMIT);
+
+    /// `features` 섹션이 없는 (비트맵 도입 전에 빌드된) 오브젝트가 처리한다고 보는 기능
+    pub const LEGACY: Self = Self(Self::TCP_FLAGS);
+
+    /// 기능 확인
+    pub fn has(&self, flag: u64) -> bool {
+        (self.0 & flag) == flag
+    }
+
+    /// 둘 다 처리하는 기능 (여러 오브젝트에 같은 규칙을 설치할 때)
+    pub fn intersect(self, other: Self) -> Self {
+        Self(self.0 & other.0)
+    }
+
+    /// `required` 중 처리하지 못하는 기능
+    pub fn missing(&self, required: u64) -> Self {
+        Self(required & !self.0)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// 기능 이름 목록 (이 버전이 모르는 비트는 "bit5"처럼 표시)
+    pub fn names(&self) -> Vec<String> {
+        (0..64)
+            .map(|bit| 1u64 << bit)
+            .filter(|flag| self.0 & flag != 0)
+            .map(|flag| match Self::NAMES.iter().find(|(known, _)| *known == flag) {
+                Some((_, name)) => name.to_string(),
+                None => format!("bit{}", flag.trailing_zeros()),
+            })
+            .collect()
+    }
+}
+
+impl std::fmt::Display for XdpFeatures {
+    /// 쉼표로 구분한 기능 이름 (없으면 "none")
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_empty() {
+            return f.write_str("none");
+        }
+        f.write_str(&self.names().join(","))
+    }
+}
+
 /// WASM 모듈 상태
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum WasmModuleState {
@@ -219,3 +313,84 @@ impl WasmModuleState {
         }
     }
 }
+
+/// 패킷 크기 히스토그램 버킷 상한 (바이트, 이하)
+///
+/// 마지막 상한을 넘는 패킷은 점보 버킷으로 집계된다. BPF 프로그램의
+/// size_hist_map 인덱스와 CLI, 내보내기 모두 이 정의를 따른다.
+pub const SIZE_BUCKET_BOUNDS: [u64; 6] = [64, 128, 256, 512, 1024, 1514];
+
+/// 히스토그램 버킷 수 (점보 버킷 포함)
+pub const SIZE_BUCKET_COUNT: usize = SIZE_BUCKET_BOUNDS.len() + 1;
+
+/// size_hist_map에서 바이트 합계가 저장되는 인덱스
+pub const SIZE_HIST_SUM_INDEX: u32 = SIZE_BUCKET_COUNT as u32;
+
+/// 패킷 길이에 해당하는 버킷 인덱스
+pub fn size_bucket_index(len: u64) -> usize {
+    SIZE_BUCKET_BOUNDS.iter()
+        .position(|&bound| len <= bound)
+        .unwrap_or(SIZE_BUCKET_BOUNDS.len())
+}
+
+/// 버킷 레이블 (예: "<=64", ">1514")
+pub fn size_bucket_label(index: usize) -> String {
+    match SIZE_BUCKET_BOUNDS.get(index) {
+        Some(bound) => format!("<={}", bound),
+        None => format!(">{}", SIZE_BUCKET_BOUNDS[SIZE_BUCKET_BOUNDS.len() - 1]),
+    }
+}
+
+/// API 요청 처리 시간 히스토그램 버킷 상한 (마이크로초, 이하)
+pub const API_LATENCY_BUCKET_BOUNDS_US: [u64; 8] = [500, 1_000, 5_000, 10_000, 50_000, 100_000, 500_000, 1_000_000];
+
+/// 처리 시간에 해당하는 버킷 인덱스 (상한을 넘으면 마지막 버킷)
+pub fn latency_bucket_index(duration_us: u64) -> usize {
+    crate::histogram::bucket_index(&API_LATENCY_BUCKET_BOUNDS_US, duration_us)
+}
+
+/// WASM 패킷 검사 시간 히스토그램 버킷 상한 (마이크로초, 이하)
+pub const WASM_LATENCY_BUCKET_BOUNDS_US: [u64; 8] = [1, 5, 10, 50, 100, 500, 1_000, 5_000];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_size_buckets() {
+        assert_eq!(size_bucket_index(40), 0);
+        assert_eq!(size_bucket_index(64), 0);
+        assert_eq!(size_bucket_index(65), 1);
+        assert_eq!(size_bucket_index(1514), 5);
+        assert_eq!(size_bucket_index(9000), 6);
+        assert_eq!(size_bucket_label(0), "<=64");
+        assert_eq!(size_bucket_label(SIZE_BUCKET_COUNT - 1), ">1514");
+    }
+
+    #[test]
+    fn test_latency_buckets() {
+        assert_eq!(latency_bucket_index(0), 0);
+        assert_eq!(latency_bucket_index(500), 0);
+        assert_eq!(latency_bucket_index(501), 1);
+        assert_eq!(latency_bucket_index(1_000_000), 7);
+        assert_eq!(latency_bucket_index(u64::MAX), API_LATENCY_BUCKET_BOUNDS_US.len());
+    }
+
+    #[test]
+    fn test_xdp_features() {
+        let legacy = XdpFeatures::LEGACY;
+        assert!(legacy.has(XdpFeatures::TCP_FLAGS));
+        assert!(!legacy.has(XdpFeatures::TCP_FLAGS | XdpFeatures::PORT_LIST));
+        assert_eq!(legacy.missing(XdpFeatures::PORT_LIST | XdpFeatures::TCP_FLAGS), XdpFeatures(XdpFeatures::PORT_LIST));
+        assert!(XdpFeatures::ALL.missing(XdpFeatures::ALL.0).is_empty());
+        assert_eq!(XdpFeatures::ALL.intersect(legacy), legacy);
+
+        assert_eq!(XdpFeatures::ALL.to_string(), "tcp_flags,port_list,redirect_cpu,rate_limit");
+        assert!(XdpFeatures::ALL.has(XdpFeatures::RATE_LIMIT));
+        assert!(!XdpFeatures::ALL.has(XdpFeatures::DST_IP));
+        assert_eq!(XdpFeatures(XdpFeatures::RATE_LIMIT).to_string(), "rate_limit");
+        assert_eq!(XdpFeatures::default().to_string(), "none");
+        // 더 새로운 오브젝트의 모르는 비트도 표시
+        assert_eq!(XdpFeatures(XdpFeatures::PORT_LIST | 1 << 5).names(), ["port_list", "bit5"]);
+    }
+}
@@ -1,8 +1,20 @@
 // Swift-Guard Common Utilities
 // 공통 유틸리티 함수
 
+use std::fmt;
 use std::net::{IpAddr, Ipv4Addr};
 use anyhow::{anyhow, Result};
+use ipnet::IpNet;
+
+use crate::error::InvalidArgument;
+use crate::layout::MAX_RULE_PORTS;
+use crate::types::ProtocolType;
+
+/// 규칙 레이블 최대 길이 (바이트, 커널 char[32]의 NUL 종료 제외)
+pub const MAX_LABEL_LEN: usize = 31;
+
+/// 네임스페이스를 지정하지 않은 규칙과 요청의 네임스페이스
+pub const DEFAULT_NAMESPACE: &str = "default";
 
 /// 포트 범위 문자열 파싱 (예: "80" 또는 "1024-2048")
 pub fn parse_port_range(s: &str) -> Result<(u16, u16)> {
@@ -31,39 +43,133 @@ pub fn parse_port_range(s: &str) -> Result<(u16, u16)> {
     }
 }
 
-/// IP 주소 문자열에서 IP 주소와 프리픽스 길이 추출
-pub fn parse_ip_prefix(s: &str) -> Result<(u32, u32)> {
-    let parts: Vec<&str> = s.split('/').collect();
-    
-    let ip_str = parts[0].trim();
-    let octets: Vec<&str> = ip_str.split('.').collect();
-    if octets.len() != 4 {
-        return Err(anyhow!("Invalid IP address format: {}", ip_str));
+/// 쉼표로 구분한 포트 목록 파싱 (예: "80,443,8080", `check_port_list`로 검증)
+///
+/// 목록 안에는 범위("80-90")를 쓸 수 없다.
+pub fn parse_port_list(s: &str) -> Result<Vec<u16>> {
+    let ports = s.split(',')
+        .map(|part| {
+            if part.contains('-') {
+                return Err(anyhow!("Port ranges cannot be used in a port list: {}", part.trim()));
+            }
+            part.trim().parse::<u16>().map_err(|_| anyhow!("Invalid port number: {}", part))
+        })
+        .collect::<Result<Vec<u16>>>()?;
+    check_port_list(&ports)
+}
+
+/// 포트 목록 검증 (중복을 뺀 목록)
+///
+/// 규칙 하나는 0이 아닌 포트를 `MAX_RULE_PORTS`개까지 담는다.
+pub fn check_port_list(ports: &[u16]) -> Result<Vec<u16>> {
+    if ports.contains(&0) {
+        return Err(anyhow!("Port 0 cannot be used in a port list"));
     }
-    
-    let mut ip: u32 = 0;
-    for (i, octet) in octets.iter().enumerate() {
-        let value = octet.parse::<u8>()
-            .map_err(|_| anyhow!("Invalid IP address octet: {}", octet))?;
-        
-        ip |= (value as u32) << (8 * (3 - i));
+
+    let mut unique: Vec<u16> = Vec::with_capacity(ports.len());
+    for port in ports {
+        if !unique.contains(port) {
+            unique.push(*port);
+        }
     }
-    
-    let prefix_len = if parts.len() > 1 {
-        parts[1].trim().parse::<u32>()
-            .map_err(|_| anyhow!("Invalid prefix length: {}", parts[1]))?
-    } else {
-        32 // 프리픽스가 지정되지 않은 경우 32(정확한 IP 매치)
-    };
-    
-    if prefix_len > 32 {
-        return Err(anyhow!("Invalid prefix length: {}", prefix_len));
+    if unique.len() > MAX_RULE_PORTS {
+        return Err(anyhow!("Too many ports in list: {} (at most {})", unique.len(), MAX_RULE_PORTS));
+    }
+    Ok(unique)
+}
+
+/// 포트 목록을 문자열로 변환 ("80,443,8080")
+pub fn port_list_to_string(ports: &[u16]) -> String {
+    ports.iter().map(|port| port.to_string()).collect::<Vec<_>>().join(",")
+}
+
+/// 네트워크 주소로 정규화된 IP 프리픽스
+///
+/// IPv4와 IPv6를 모두 표현하지만 커널 맵은 아직 IPv4만 받으므로, 맵에 기록할 값은
+/// `to_ipv4`로 얻는다.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct IpPrefix {
+    /// 네트워크 주소 (호스트 비트는 0)
+    pub addr: IpAddr,
+    /// 프리픽스 길이
+    pub len: u8,
+}
+
+impl IpPrefix {
+    /// 프리픽스 문자열 파싱 (예: "10.0.0.0/8", " 192.168.1.5 ", "2001:db8::/32")
+    ///
+    /// 길이를 생략하면 호스트 프리픽스(/32, /128)이며, 호스트 비트는 제거한다.
+    /// 앞자리 0이 있는 옥텟("010.0.0.1")과 DNS 이름은 거부한다.
+    pub fn parse(s: &str) -> Result<Self> {
+        let text = s.trim();
+        let (addr_text, len_text) = match text.split_once('/') {
+            Some((addr, len)) => (addr.trim(), Some(len.trim())),
+            None => (text, None),
+        };
+
+        let addr: IpAddr = addr_text.parse().map_err(|_| {
+            // 문자로 시작하고 호스트 이름에 쓰는 문자만 있으면 DNS 이름으로 봄
+            let hostname = addr_text.starts_with(|c: char| c.is_ascii_alphabetic())
+                && addr_text.chars().all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-');
+            if hostname {
+                anyhow!("Invalid IP address: {} (DNS names are not supported)", addr_text)
+            } else {
+                anyhow!("Invalid IP address: {}", addr_text)
+            }
+        })?;
+
+        let len = match len_text {
+            Some(len) => len.parse::<u8>().map_err(|_| anyhow!("Invalid prefix length: {}", len))?,
+            None if addr.is_ipv4() => 32,
+            None => 128,
+        };
+
+        let network = IpNet::new(addr, len)
+            .map_err(|_| anyhow!("Invalid prefix length: {}", len))?
+            .trunc();
+
+        Ok(Self { addr: network.addr(), len: network.prefix_len() })
+    }
+
+    /// IPv4 프리픽스를 (호스트 순서 주소, 길이)로 변환 (IPv6는 오류)
+    pub fn to_ipv4(self) -> Result<(u32, u32)> {
+        match self.addr {
+            IpAddr::V4(addr) => Ok((ipv4_to_u32(&addr), u32::from(self.len))),
+            IpAddr::V6(_) => Err(anyhow!("IPv6 prefixes are not supported yet: {}", self)),
+        }
     }
-    
-    Ok((ip, prefix_len))
 }
 
-/// IPv4 주소를 문자열로 변환
+impl fmt::Display for IpPrefix {
+    /// 호스트 프리픽스는 주소만, 나머지는 "주소/길이"
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.addr, self.len) {
+            (IpAddr::V4(_), 32) | (IpAddr::V6(_), 128) => write!(f, "{}", self.addr),
+            _ => write!(f, "{}/{}", self.addr, self.len),
+        }
+    }
+}
+
+/// IPv4 프리픽스 문자열에서 네트워크 주소와 프리픽스 길이 추출
+///
+/// 주소는 첫 옥텟이 최상위 바이트인 호스트 순서 값이다 (10.0.0.0 → 0x0A000000).
+/// 커널 맵에 기록할 때는 `to_be_bytes`로 네트워크 순서 바이트를 만든다.
+pub fn parse_ip_prefix(s: &str) -> Result<(u32, u32)> {
+    IpPrefix::parse(s)?.to_ipv4()
+}
+
+/// 프리픽스 길이 밖의 호스트 비트 제거 (10.0.0.5/8 → 10.0.0.0)
+pub fn mask_ipv4(addr: u32, prefix_len: u32) -> u32 {
+    match prefix_len {
+        0 => 0,
+        1..=31 => addr & (u32::MAX << (32 - prefix_len)),
+        _ => addr,
+    }
+}
+
+/// IPv4 주소를 문자열로 변환 (`parse_ip_prefix`와 같은 호스트 순서 값)
+///
+/// 커널 맵에서 읽은 주소는 `u32::from_be_bytes`로 변환한 뒤 넘긴다.
 pub fn ipv4_to_string(addr: u32) -> String {
     format!("{}.{}.{}.{}", 
         (addr >> 24) & 0xFF,
@@ -73,7 +179,7 @@ pub fn ipv4_to_string(addr: u32) -> String {
     )
 }
 
-/// IPv4 주소를 네트워크 순서(빅 엔디안) u32로 변환
+/// IPv4 주소를 호스트 순서 u32로 변환 (첫 옥텟이 최상위 바이트)
 pub fn ipv4_to_u32(addr: &Ipv4Addr) -> u32 {
     let octets = addr.octets();
     ((octets[0] as u32) << 24) |
@@ -82,7 +188,7 @@ pub fn ipv4_to_u32(addr: &Ipv4Addr) -> u32 {
     (octets[3] as u32)
 }
 
-/// u32 네트워크 순서(빅 엔디안)에서 IPv4 주소로 변환
+/// 호스트 순서 u32(첫 옥텟이 최상위 바이트)에서 IPv4 주소로 변환
 pub fn u32_to_ipv4(addr: u32) -> Ipv4Addr {
     Ipv4Addr::new(
         ((addr >> 24) & 0xFF) as u8,
@@ -100,6 +206,19 @@ pub fn current_time_secs() -> u64 {
         .as_secs()
 }
 
+/// 규칙의 남은 수명 (초, expire 0 = 만료 없음으로 None)
+///
+/// 만료 처리와 목록 표시가 모두 이 함수를 쓴다. 만료 시각이 지났거나 같으면 0이며,
+/// 시계가 생성 시각보다 뒤로 간 경우에도 `expire`를 넘지 않는다.
+pub fn ttl_remaining(creation_time: u64, expire: u32, now: u64) -> Option<u64> {
+    if expire == 0 {
+        return None;
+    }
+    
+    let expires_at = creation_time.saturating_add(expire as u64);
+    Some(expires_at.saturating_sub(now).min(expire as u64))
+}
+
 /// 포맷된 크기 문자열 반환 (바이트, KB, MB, GB)
 pub fn format_size(bytes: u64) -> String {
     const KB: u64 = 1024;
@@ -117,6 +236,234 @@ pub fn format_size(bytes: u64) -> String {
     }
 }
 
+/// 사람이 읽는 크기 문자열 파싱 (예: "512", "64K", "10G", "1.5TiB")
+///
+/// 접미사 K, M, G, T는 1024의 거듭제곱이며 대소문자를 구분하지 않고 뒤에 B나
+/// iB를 붙여도 된다. 소수는 바이트 단위로 내림한다.
+pub fn parse_size(s: &str) -> Result<u64> {
+    let text = s.trim();
+    let split = text.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(text.len());
+    let (number, suffix) = text.split_at(split);
+
+    let shift = match suffix.trim_start().to_ascii_uppercase().as_str() {
+        "" | "B" => 0,
+        "K" | "KB" | "KIB" => 10,
+        "M" | "MB" | "MIB" => 20,
+        "G" | "GB" | "GIB" => 30,
+        "T" | "TB" | "TIB" => 40,
+        _ => return Err(anyhow!("Invalid size: {} (expected a number with an optional K, M, G or T suffix)", s)),
+    };
+
+    let size = if number.contains('.') {
+        let value: f64 = number.parse().map_err(|_| anyhow!("Invalid size: {}", s))?;
+        let bytes = value * (1u64 << shift) as f64;
+        if !bytes.is_finite() || bytes >= u64::MAX as f64 {
+            return Err(anyhow!("Size out of range: {}", s));
+        }
+        bytes as u64
+    } else {
+        let value: u64 = number.parse().map_err(|_| anyhow!("Invalid size: {}", s))?;
+        value.checked_mul(1u64 << shift).ok_or_else(|| anyhow!("Size out of range: {}", s))?
+    };
+
+    Ok(size)
+}
+
+/// 기간 문자열을 초로 변환 (예: "90", "90s", "15m", "2h", "1d")
+///
+/// 접미사가 없으면 초로 본다. 정수만 받는다.
+pub fn parse_duration(s: &str) -> Result<u64> {
+    let text = s.trim();
+    let split = text.find(|c: char| !c.is_ascii_digit()).unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+    let invalid = || anyhow!("Invalid duration: {:?} (expected a whole number with an optional s, m, h or d suffix)", s);
+
+    let multiplier = match unit {
+        "" | "s" => 1,
+        "m" => 60,
+        "h" => 3600,
+        "d" => 86400,
+        _ => return Err(invalid()),
+    };
+
+    number.parse::<u64>().ok()
+        .and_then(|n| n.checked_mul(multiplier))
+        .ok_or_else(invalid)
+}
+
+/// 초를 기간 문자열로 변환 (나누어떨어지는 가장 큰 단위, `parse_duration`의 역)
+pub fn format_duration(secs: u64) -> String {
+    match secs {
+        0 => "0s".to_string(),
+        _ if secs.is_multiple_of(86400) => format!("{}d", secs / 86400),
+        _ if secs.is_multiple_of(3600) => format!("{}h", secs / 3600),
+        _ if secs.is_multiple_of(60) => format!("{}m", secs / 60),
+        _ => format!("{}s", secs),
+    }
+}
+
+/// 개수 문자열 파싱 (예: "1000", "10k", "1.5M", "2G")
+///
+/// 접미사 k, M, G는 1000의 거듭제곱이며 대소문자를 구분하지 않는다. 소수는 내림한다.
+pub fn parse_count(s: &str) -> Result<u64> {
+    let text = s.trim();
+    let split = text.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(text.len());
+    let (number, suffix) = text.split_at(split);
+    let invalid = || anyhow!("Invalid count: {:?} (expected a number with an optional k, M or G suffix)", s);
+
+    let multiplier: u64 = match suffix.to_ascii_lowercase().as_str() {
+        "" => 1,
+        "k" => 1_000,
+        "m" => 1_000_000,
+        "g" => 1_000_000_000,
+        _ => return Err(invalid()),
+    };
+
+    // 소수는 부동소수점 오차 없이 자릿수별로 더함
+    let (whole, fraction) = number.split_once('.').unwrap_or((number, ""));
+    if whole.is_empty() || fraction.contains('.') {
+        return Err(invalid());
+    }
+
+    let mut count = whole.parse::<u64>().map_err(|_| invalid())?
+        .checked_mul(multiplier)
+        .ok_or_else(|| anyhow!("Count out of range: {}", s))?;
+    let mut scale = multiplier;
+    for digit in fraction.chars() {
+        scale /= 10;
+        count = count.checked_add(u64::from(digit.to_digit(10).ok_or_else(invalid)?) * scale)
+            .ok_or_else(|| anyhow!("Count out of range: {}", s))?;
+    }
+    Ok(count)
+}
+
+/// 개수를 짧은 문자열로 변환 (`parse_count`의 역, 예: 10000 → "10k", 1500000 → "1.5M")
+///
+/// 소수 둘째 자리까지 정확히 나타낼 수 있을 때만 접미사를 쓴다.
+pub fn format_count(count: u64) -> String {
+    for (unit, suffix) in [(1_000_000_000, "G"), (1_000_000, "M"), (1_000, "k")] {
+        if count >= unit && count.is_multiple_of(unit / 100) {
+            let whole = count / unit;
+            let cents = count % unit / (unit / 100);
+            return match cents {
+                0 => format!("{}{}", whole, suffix),
+                _ if cents.is_multiple_of(10) => format!("{}.{}{}", whole, cents / 10, suffix),
+                _ => format!("{}.{:02}{}", whole, cents, suffix),
+            };
+        }
+    }
+    count.to_string()
+}
+
+/// 규칙 레이블 검증
+///
+/// 1~31바이트의 영숫자, '-', '_', '.'만 허용한다. 잘라내지 않고 거부하므로
+/// 캐시의 레이블과 커널 맵의 레이블이 항상 같다.
+pub fn validate_label(label: &str) -> std::result::Result<(), InvalidArgument> {
+    validate_name("label", label)
+}
+
+/// 네임스페이스 이름 검증 (레이블과 같은 규칙)
+pub fn validate_namespace(namespace: &str) -> std::result::Result<(), InvalidArgument> {
+    validate_name("namespace", namespace)
+}
+
+fn validate_name(field: &str, name: &str) -> std::result::Result<(), InvalidArgument> {
+    if name.is_empty() {
+        return Err(InvalidArgument::new(field, "must not be empty"));
+    }
+
+    if let Some(c) = name.chars().find(|c| !(c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))) {
+        return Err(InvalidArgument::new(field, format!(
+            "character {:?} is not allowed (use letters, digits, '-', '_' or '.')", c)));
+    }
+
+    if name.len() > MAX_LABEL_LEN {
+        return Err(InvalidArgument::new(field, format!(
+            "{} bytes exceeds the maximum of {} bytes", name.len(), MAX_LABEL_LEN)));
+    }
+
+    Ok(())
+}
+
+/// serde 기본값용 기본 네임스페이스
+pub fn default_namespace() -> String {
+    DEFAULT_NAMESPACE.to_string()
+}
+
+/// 네임스페이스 안의 레이블을 데몬 전체에서 유일한 규칙 키로 변환
+///
+/// 기본 네임스페이스의 규칙 키는 레이블 그대로이고, 다른 네임스페이스는 "네임스페이스/레이블"이다.
+/// 레이블과 네임스페이스에는 '/'를 쓸 수 없으므로 키는 항상 한 가지로 나뉜다.
+pub fn qualify_label(namespace: &str, label: &str) -> String {
+    if namespace == DEFAULT_NAMESPACE {
+        label.to_string()
+    } else {
+        format!("{}/{}", namespace, label)
+    }
+}
+
+/// 규칙 키를 (네임스페이스, 레이블)로 분리 (`qualify_label`의 역)
+pub fn split_label(key: &str) -> (&str, &str) {
+    key.split_once('/').unwrap_or((DEFAULT_NAMESPACE, key))
+}
+
+/// 규칙 키(`qualify_label`의 결과) 검증 (네임스페이스와 레이블 각각)
+pub fn validate_rule_key(key: &str) -> std::result::Result<(), InvalidArgument> {
+    let (namespace, label) = split_label(key);
+    validate_namespace(namespace)?;
+    validate_label(label)
+}
+
+/// WASM 모듈이 설치한 규칙의 설치 주체 접두사 (`wasm:<모듈>`)
+pub const WASM_ORIGIN: &str = "wasm";
+
+/// WASM 모듈의 설치 주체 이름
+pub fn wasm_origin(module: &str) -> String {
+    format!("{}:{}", WASM_ORIGIN, module)
+}
+
+/// 설치 주체가 조회 조건에 맞는지 (`wasm`은 모든 `wasm:<모듈>`, `wasm:<모듈>`은 그 모듈만)
+pub fn origin_matches(origin: Option<&str>, filter: &str) -> bool {
+    let Some(origin) = origin else {
+        return false;
+    };
+    origin == filter || origin.strip_prefix(filter).is_some_and(|rest| rest.starts_with(':'))
+}
+
+/// 검증된 레이블을 커널 레이블 바이트(char[32], NUL 종료)로 변환
+pub fn label_to_kernel_bytes(label: &str) -> std::result::Result<[u8; MAX_LABEL_LEN + 1], InvalidArgument> {
+    validate_label(label)?;
+
+    let mut bytes = [0u8; MAX_LABEL_LEN + 1];
+    bytes[..label.len()].copy_from_slice(label.as_bytes());
+    Ok(bytes)
+}
+
+/// 요청 ID 최대 길이 (바이트)
+pub const MAX_REQUEST_ID_LEN: usize = 64;
+
+/// 새 요청 ID (UUID v4)
+pub fn new_request_id() -> String {
+    uuid::Uuid::new_v4().to_string()
+}
+
+/// 클라이언트가 보낸 요청 ID 검증
+///
+/// 로그 줄과 syslog 구조화 데이터에 그대로 들어가므로 영숫자, '-', '_', '.', ':'만 허용한다.
+pub fn validate_request_id(id: &str) -> std::result::Result<(), InvalidArgument> {
+    if id.is_empty() || id.len() > MAX_REQUEST_ID_LEN {
+        return Err(InvalidArgument::new("request_id", format!(
+            "must be 1 to {} bytes long", MAX_REQUEST_ID_LEN)));
+    }
+
+    if let Some(c) = id.chars().find(|c| !(c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | ':'))) {
+        return Err(InvalidArgument::new("request_id", format!("character {:?} is not allowed", c)));
+    }
+
+    Ok(())
+}
+
 // src/common/utils.rs에 추가할 함수들
 
 /// 액션 번호를 액션 이름으로 변환
@@ -130,15 +477,26 @@ pub fn action_num_to_name(num: u8) -> String {
     }
 }
 
-/// 프로토콜 번호를 프로토콜 이름으로 변환
-pub fn protocol_num_to_name(num: u8) -> String {
-    match num {
-        1 => "icmp".to_string(),
-        6 => "tcp".to_string(),
-        17 => "udp".to_string(),
-        255 => "any".to_string(),
-        _ => format!("{}", num),
+/// 프로토콜 이름 또는 번호(0~255)를 프로토콜 번호로 변환
+pub fn protocol_name_to_num(name: &str) -> Result<u8> {
+    let name = name.trim();
+    if let Some(protocol) = ProtocolType::from_str(name) {
+        return Ok(protocol as u8);
     }
+    
+    if !name.is_empty() && name.chars().all(|c| c.is_ascii_digit()) {
+        return name.parse::<u8>()
+            .map_err(|_| anyhow!("Protocol number out of range (0-255): {}", name));
+    }
+    
+    Err(anyhow!("Unknown protocol: {}", name))
+}
+
+/// 프로토콜 번호를 프로토콜 이름으로 변환 (이름이 없으면 번호)
+pub fn protocol_num_to_name(num: u8) -> String {
+    ProtocolType::from_u8(num)
+        .map(|p| p.to_str().to_string())
+        .unwrap_or_else(|| num.to_string())
 }
 
 /// TCP 플래그 비트맵을 문자열로 변환
@@ -174,6 +532,23 @@ pub fn port_range_to_string(min: u16, max: u16) -> Option<String> {
 mod tests {
     use super::*;
     
+    #[test]
+    fn test_ttl_remaining() {
+        // 만료 없음
+        assert_eq!(ttl_remaining(1000, 0, 500), None);
+        assert_eq!(ttl_remaining(1000, 0, 5000), None);
+
+        assert_eq!(ttl_remaining(1000, 60, 1000), Some(60));
+        assert_eq!(ttl_remaining(1000, 60, 1059), Some(1));
+        // 만료 시각과 같거나 지난 경우
+        assert_eq!(ttl_remaining(1000, 60, 1060), Some(0));
+        assert_eq!(ttl_remaining(1000, 60, 9999), Some(0));
+        // 시계가 생성 시각보다 뒤로 간 경우
+        assert_eq!(ttl_remaining(1000, 60, 0), Some(60));
+        // 생성 시각이 비정상적으로 큰 경우
+        assert_eq!(ttl_remaining(u64::MAX, 60, 1000), Some(60));
+    }
+    
     #[test]
     fn test_parse_port_range() {
         assert_eq!(parse_port_range("80").unwrap(), (80, 80));
@@ -182,15 +557,274 @@ mod tests {
         assert!(parse_port_range("1024-abc").is_err());
         assert!(parse_port_range("2048-1024").is_err());
     }
+
+    #[test]
+    fn test_parse_port_list() {
+        assert_eq!(parse_port_list("80, 443,8080").unwrap(), vec![80, 443, 8080]);
+        assert_eq!(port_list_to_string(&[19, 53, 123]), "19,53,123");
+        assert!(parse_port_list("53,dns").is_err());
+        assert!(parse_port_list("53,0").is_err());
+        assert!(parse_port_list("53,").is_err());
+
+        // 중복은 처음 나온 순서대로 한 번만 남기고 개수에서도 빠짐
+        assert_eq!(parse_port_list("19, 53,123,53,19").unwrap(), vec![19, 53, 123]);
+        assert_eq!(parse_port_list("1,2,3,4,5,6,7,8,8,1").unwrap().len(), 8);
+        assert_eq!(check_port_list(&[1, 2, 3, 4, 5, 6, 7, 8, 8]).unwrap().len(), 8);
+
+        // 8개를 넘는 목록
+        assert_eq!(parse_port_list("1,2,3,4,5,6,7,8,9").unwrap_err().to_string(),
+            "Too many ports in list: 9 (at most 8)");
+        assert!(check_port_list(&[1, 2, 3, 4, 5, 6, 7, 8, 9, 10]).is_err());
+
+        // 범위와 섞은 목록
+        assert_eq!(parse_port_list("80,8000-8080").unwrap_err().to_string(),
+            "Port ranges cannot be used in a port list: 8000-8080");
+        assert!(parse_port_list("80-90").is_err());
+        assert!(check_port_list(&[]).unwrap().is_empty());
+    }
     
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("512").unwrap(), 512);
+        assert_eq!(parse_size("512B").unwrap(), 512);
+        assert_eq!(parse_size("64K").unwrap(), 64 * 1024);
+        assert_eq!(parse_size("64kb").unwrap(), 64 * 1024);
+        assert_eq!(parse_size("20M").unwrap(), 20 << 20);
+        assert_eq!(parse_size("20 MiB").unwrap(), 20 << 20);
+        assert_eq!(parse_size("10G").unwrap(), 10 << 30);
+        assert_eq!(parse_size("10GB").unwrap(), 10 << 30);
+        assert_eq!(parse_size("2T").unwrap(), 2 << 40);
+        assert_eq!(parse_size("1.5tib").unwrap(), 3 << 39);
+        assert_eq!(parse_size("0.5K").unwrap(), 512);
+
+        assert!(parse_size("").is_err());
+        assert!(parse_size("G").is_err());
+        assert!(parse_size("10X").is_err());
+        assert!(parse_size("10GG").is_err());
+        assert!(parse_size("-1G").is_err());
+        assert!(parse_size("1.2.3M").is_err());
+        assert!(parse_size("16777216T").is_err());
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("90").unwrap(), 90);
+        assert_eq!(parse_duration("90s").unwrap(), 90);
+        assert_eq!(parse_duration("15m").unwrap(), 900);
+        assert_eq!(parse_duration("2h").unwrap(), 7200);
+        assert_eq!(parse_duration("1d").unwrap(), 86400);
+        assert_eq!(parse_duration(" 0 ").unwrap(), 0);
+
+        assert!(parse_duration("").is_err());
+        assert!(parse_duration("m").is_err());
+        assert!(parse_duration("1.5h").is_err());
+        assert!(parse_duration("-1s").is_err());
+        assert!(parse_duration("18446744073709551615d").is_err());
+        let err = parse_duration("10w").unwrap_err().to_string();
+        assert!(err.contains("\"10w\"") && err.contains("s, m, h or d"), "{}", err);
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(0), "0s");
+        assert_eq!(format_duration(45), "45s");
+        assert_eq!(format_duration(90), "90s");
+        assert_eq!(format_duration(900), "15m");
+        assert_eq!(format_duration(3600), "1h");
+        assert_eq!(format_duration(5400), "90m");
+        assert_eq!(format_duration(172800), "2d");
+
+        for secs in [1, 59, 60, 61, 3599, 3600, 86399, 86400, 90000] {
+            assert_eq!(parse_duration(&format_duration(secs)).unwrap(), secs);
+        }
+    }
+
+    #[test]
+    fn test_parse_count() {
+        assert_eq!(parse_count("1000").unwrap(), 1000);
+        assert_eq!(parse_count("10k").unwrap(), 10_000);
+        assert_eq!(parse_count("10K").unwrap(), 10_000);
+        assert_eq!(parse_count("1.5M").unwrap(), 1_500_000);
+        assert_eq!(parse_count("2g").unwrap(), 2_000_000_000);
+        assert_eq!(parse_count("0.5k").unwrap(), 500);
+        assert_eq!(parse_count("2.05G").unwrap(), 2_050_000_000);
+        assert_eq!(parse_count("1.2345k").unwrap(), 1234);
+
+        assert!(parse_count("").is_err());
+        assert!(parse_count("k").is_err());
+        assert!(parse_count("-5").is_err());
+        assert!(parse_count("1.2.3k").is_err());
+        assert!(parse_count(".5k").is_err());
+        assert!(parse_count("18446744073709551615k").is_err());
+        let err = parse_count("10x").unwrap_err().to_string();
+        assert!(err.contains("\"10x\"") && err.contains("k, M or G"), "{}", err);
+    }
+
+    #[test]
+    fn test_format_count() {
+        assert_eq!(format_count(0), "0");
+        assert_eq!(format_count(999), "999");
+        assert_eq!(format_count(1000), "1k");
+        assert_eq!(format_count(1234), "1234");
+        assert_eq!(format_count(1230), "1.23k");
+        assert_eq!(format_count(1_500_000), "1.5M");
+        assert_eq!(format_count(2_050_000_000), "2.05G");
+        assert_eq!(format_count(1_000_001), "1000001");
+
+        for count in [1, 1000, 1230, 10_000, 1_500_000, 1_000_001, 2_050_000_000] {
+            assert_eq!(parse_count(&format_count(count)).unwrap(), count);
+        }
+    }
+
     #[test]
     fn test_parse_ip_prefix() {
         assert_eq!(parse_ip_prefix("192.168.1.1").unwrap(), (0xC0A80101, 32));
         assert_eq!(parse_ip_prefix("10.0.0.0/8").unwrap(), (0x0A000000, 8));
         assert!(parse_ip_prefix("256.168.1.1").is_err());
         assert!(parse_ip_prefix("192.168.1.1/33").is_err());
+
+        // 경계 길이, 공백, 호스트 비트 정규화
+        assert_eq!(parse_ip_prefix("0.0.0.0/0").unwrap(), (0, 0));
+        assert_eq!(parse_ip_prefix("10.1.2.3/0").unwrap(), (0, 0));
+        assert_eq!(parse_ip_prefix("10.1.2.3/32").unwrap(), (0x0A010203, 32));
+        assert_eq!(parse_ip_prefix(" 10.1.2.3/8 ").unwrap(), (0x0A000000, 8));
+        assert_eq!(parse_ip_prefix("10.1.2.3 / 16").unwrap(), (0x0A010000, 16));
+
+        for bad in ["", "10.0.0", "10.0.0.0.1", "010.0.0.1", "10.0.0.1/", "10.0.0.1/-1", "10.0.0.1/8/8", "1.2.3.4 5"] {
+            assert!(parse_ip_prefix(bad).is_err(), "{:?}", bad);
+        }
+
+        let err = parse_ip_prefix("2001:db8::/32").unwrap_err().to_string();
+        assert!(err.contains("IPv6 prefixes are not supported"), "{}", err);
+        let err = parse_ip_prefix("example.com").unwrap_err().to_string();
+        assert!(err.contains("DNS names are not supported"), "{}", err);
+        let err = parse_ip_prefix("gateway/24").unwrap_err().to_string();
+        assert!(err.contains("DNS names are not supported"), "{}", err);
+        assert!(!parse_ip_prefix("10.0.0.x").unwrap_err().to_string().contains("DNS"));
+    }
+
+    #[test]
+    fn test_ip_prefix() {
+        let prefix = IpPrefix::parse("2001:db8::1/32").unwrap();
+        assert_eq!(prefix, IpPrefix { addr: "2001:db8::".parse().unwrap(), len: 32 });
+        assert_eq!(prefix.to_string(), "2001:db8::/32");
+        assert_eq!(IpPrefix::parse("::1").unwrap().len, 128);
+        assert!(IpPrefix::parse("::1/129").is_err());
+        assert!(IpPrefix::parse("2001:db8:::1").is_err());
+
+        assert_eq!(IpPrefix::parse("192.168.1.77/24").unwrap().to_string(), "192.168.1.0/24");
+        assert_eq!(IpPrefix::parse("192.168.1.77").unwrap().to_string(), "192.168.1.77");
+        assert_eq!(IpPrefix::parse("0.0.0.0/0").unwrap().to_string(), "0.0.0.0/0");
     }
     
+    #[test]
+    fn test_mask_ipv4() {
+        assert_eq!(mask_ipv4(0x0A000005, 8), 0x0A000000);
+        assert_eq!(mask_ipv4(0xC0A80101, 24), 0xC0A80100);
+        assert_eq!(mask_ipv4(0xC0A80101, 32), 0xC0A80101);
+        assert_eq!(mask_ipv4(0xC0A80101, 0), 0);
+    }
+
+    #[test]
+    fn test_protocol_name_to_num() {
+        assert_eq!(protocol_name_to_num("TCP").unwrap(), 6);
+        assert_eq!(protocol_name_to_num("gre").unwrap(), 47);
+        assert_eq!(protocol_name_to_num("0").unwrap(), 0);
+        assert_eq!(protocol_name_to_num("253").unwrap(), 253);
+        assert!(protocol_name_to_num("ospf").is_err());
+        assert!(protocol_name_to_num("").is_err());
+        assert!(protocol_name_to_num("-1").is_err());
+
+        let err = protocol_name_to_num("256").unwrap_err();
+        assert!(err.to_string().contains("out of range"));
+    }
+
+    #[test]
+    fn test_protocol_round_trip() {
+        for protocol in ProtocolType::ALL {
+            let num = protocol_name_to_num(protocol.to_str()).unwrap();
+            assert_eq!(num, protocol as u8);
+            assert_eq!(protocol_num_to_name(num), protocol.to_str());
+            assert_eq!(protocol_name_to_num(&num.to_string()).unwrap(), num);
+        }
+        assert_eq!(protocol_num_to_name(89), "89");
+    }
+
+    #[test]
+    fn test_validate_label() {
+        assert!(validate_label("block-ssh_v1.2").is_ok());
+        assert!(validate_label(&"a".repeat(31)).is_ok());
+
+        let err = validate_label("").unwrap_err();
+        assert_eq!(err.field, "label");
+
+        let err = validate_label("block external ssh").unwrap_err();
+        assert!(err.message.contains("' '"));
+    }
+
+    #[test]
+    fn test_validate_label_too_long() {
+        let label = "x".repeat(40);
+        let err = validate_label(&label).unwrap_err();
+        assert_eq!(err.field, "label");
+        assert!(err.message.contains("40 bytes"));
+        assert!(label_to_kernel_bytes(&label).is_err());
+    }
+
+    #[test]
+    fn test_validate_label_emoji() {
+        let err = validate_label("block-🔥").unwrap_err();
+        assert!(err.message.contains("'🔥'"));
+        assert!(label_to_kernel_bytes("block-🔥").is_err());
+    }
+
+    #[test]
+    fn test_namespace_labels() {
+        assert!(validate_namespace("team-a").is_ok());
+        assert_eq!(validate_namespace("team/a").unwrap_err().field, "namespace");
+
+        assert_eq!(qualify_label("default", "web"), "web");
+        assert_eq!(qualify_label("team-a", "web"), "team-a/web");
+        assert_eq!(split_label("web"), ("default", "web"));
+        assert_eq!(split_label("team-a/web"), ("team-a", "web"));
+        assert!(validate_rule_key("team-a/web").is_ok());
+        assert!(validate_rule_key("team-a/web/x").is_err());
+    }
+
+    #[test]
+    fn test_origin_matches() {
+        let origin = wasm_origin("scanner");
+        assert_eq!(origin, "wasm:scanner");
+        assert!(origin_matches(Some(&origin), "wasm"));
+        assert!(origin_matches(Some(&origin), "wasm:scanner"));
+        assert!(!origin_matches(Some(&origin), "wasm:scan"));
+        assert!(!origin_matches(Some("wasmer:x"), "wasm"));
+        assert!(!origin_matches(None, "wasm"));
+    }
+
+    #[test]
+    fn test_label_to_kernel_bytes() {
+        let bytes = label_to_kernel_bytes("web").unwrap();
+        assert_eq!(&bytes[..4], b"web\0");
+        assert_eq!(bytes.len(), 32);
+
+        let full = label_to_kernel_bytes(&"a".repeat(31)).unwrap();
+        assert_eq!(full[31], 0);
+    }
+
+    #[test]
+    fn test_request_ids() {
+        let id = new_request_id();
+        assert_eq!(id.len(), 36);
+        assert_ne!(id, new_request_id());
+        assert!(validate_request_id(&id).is_ok());
+        assert!(validate_request_id("deploy-42:step.1").is_ok());
+
+        assert!(validate_request_id("").is_err());
+        assert!(validate_request_id(&"a".repeat(MAX_REQUEST_ID_LEN + 1)).is_err());
+        assert_eq!(validate_request_id("a b\nforged").unwrap_err().field, "request_id");
+    }
+
     #[test]
     fn test_ipv4_conversions() {
         let addr = Ipv4Addr::new(192, 168, 1, 1);
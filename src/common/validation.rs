@@ -0,0 +1,365 @@
+//! 규칙 정의 검증 모듈
+//! CLI 사전 검증, 데몬 규칙 추가, 규칙 집합 계획이 함께 쓰는 규칙 정의 검증
+//!
+//! 데몬 상태 없이 정의만 보고 알 수 있는 문제를 모두 모아 돌려준다. 리디렉션 인터페이스와
+//! 온라인 CPU, 웹훅 URL 형식, 소스 호스트나 프리픽스 집합과의 조합은 데몬이 따로 확인한다.
+//! 로드된 XDP 프로그램이 매치 필드를 처리하는지는 프로그램의 기능 비트맵으로 `check_features`가 확인한다.
+
+use std::fmt;
+
+use crate::api::{AddressFamily, RuleSpec};
+use crate::error::InvalidArgument;
+use crate::layout;
+use crate::types::XdpFeatures;
+use crate::utils::{self, IpPrefix};
+
+/// 리디렉션 액션 (struct filter_rule.action)
+const ACTION_REDIRECT: u8 = 3;
+
+/// count 액션
+const ACTION_COUNT: u8 = 4;
+
+/// 규칙 정의의 문제
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationIssue {
+    /// 문제가 된 필드 (RuleSpec 필드 이름)
+    pub field: String,
+    pub message: String,
+}
+
+impl ValidationIssue {
+    fn new(field: &str, message: impl Into<String>) -> Self {
+        Self { field: field.to_string(), message: message.into() }
+    }
+}
+
+impl fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.field, self.message)
+    }
+}
+
+impl From<InvalidArgument> for ValidationIssue {
+    fn from(e: InvalidArgument) -> Self {
+        Self { field: e.field, message: e.message }
+    }
+}
+
+impl From<ValidationIssue> for InvalidArgument {
+    fn from(issue: ValidationIssue) -> Self {
+        Self { field: issue.field, message: issue.message }
+    }
+}
+
+/// 규칙 정의 검증 (문제가 없으면 빈 목록, 필드 순서대로)
+pub fn validate_rule_spec(spec: &RuleSpec) -> Vec<ValidationIssue> {
+    let mut issues = Vec::new();
+
+    if let Err(e) = utils::validate_label(&spec.label) {
+        issues.push(e.into());
+    }
+
+    if utils::action_num_to_name(spec.action) == "unknown" {
+        issues.push(ValidationIssue::new("action",
+            format!("Invalid action {} (expected pass, drop, redirect or count)", spec.action)));
+    }
+
+    check_redirect(spec, &mut issues);
+    check_prefixes(spec.src_ip.as_deref(), spec.dst_ip.as_deref(), &mut issues);
+    check_port_list("src_port", (spec.src_port_min, spec.src_port_max), &spec.src_port_list, spec.protocol, &mut issues);
+    check_port_list("dst_port", (spec.dst_port_min, spec.dst_port_max), &spec.dst_port_list, spec.protocol, &mut issues);
+
+    // 카운터 그룹 (통계를 합산하는 count 액션 규칙만)
+    if let Some(group) = spec.counter_group.as_deref().filter(|group| !group.trim().is_empty()) {
+        if spec.action != ACTION_COUNT {
+            issues.push(ValidationIssue::new("counter_group", "counter_group requires the count action"));
+        }
+        if let Err(e) = utils::validate_label(group) {
+            issues.push(ValidationIssue::new("counter_group", e.message));
+        }
+    }
+
+    // 만료 알림 (만료가 있는 규칙만)
+    if spec.expire == 0 {
+        if spec.owner_webhook.as_deref().is_some_and(|url| !url.trim().is_empty()) {
+            issues.push(ValidationIssue::new("owner_webhook", "owner_webhook requires an expire time"));
+        }
+        if spec.notify_before.is_some() {
+            issues.push(ValidationIssue::new("notify_before", "notify_before requires an expire time"));
+        }
+    }
+
+    check_quota(spec, &mut issues);
+    issues
+}
+
+/// 리디렉션 대상 (리디렉션 액션에만, 인터페이스와 CPU 중 하나)
+fn check_redirect(spec: &RuleSpec, issues: &mut Vec<ValidationIssue>) {
+    match (spec.redirect_if.is_some(), spec.redirect_cpu.is_some()) {
+        (true, true) => issues.push(ValidationIssue::new("redirect_cpu",
+            "redirect_if and redirect_cpu cannot be used together")),
+        (false, false) if spec.action == ACTION_REDIRECT => issues.push(ValidationIssue::new("redirect_if",
+            "redirect action requires redirect_if or redirect_cpu")),
+        (has_if, has_cpu) if spec.action != ACTION_REDIRECT => {
+            for (field, present) in [("redirect_if", has_if), ("redirect_cpu", has_cpu)] {
+                if present {
+                    issues.push(ValidationIssue::new(field, format!("{} requires the redirect action", field)));
+                }
+            }
+        },
+        _ => {},
+    }
+}
+
+/// 소스, 대상 프리픽스 (주소 체계가 섞이면 체계 문제만, 같으면 커널 맵 제약까지)
+fn check_prefixes(src_ip: Option<&str>, dst_ip: Option<&str>, issues: &mut Vec<ValidationIssue>) {
+    let mut parse = |field: &str, value: Option<&str>| -> Option<IpPrefix> {
+        match IpPrefix::parse(value?) {
+            Ok(prefix) => Some(prefix),
+            Err(e) => {
+                issues.push(ValidationIssue::new(field, format!("{:#}", e)));
+                None
+            },
+        }
+    };
+    let src = parse("src_ip", src_ip);
+    let dst = parse("dst_ip", dst_ip);
+
+    if let (Some(src), Some(dst)) = (&src, &dst) {
+        if src.addr.is_ipv4() != dst.addr.is_ipv4() {
+            if let Err(e) = AddressFamily::of_prefixes(src_ip, dst_ip) {
+                issues.push(e.into());
+            }
+            return;
+        }
+    }
+
+    for (field, prefix) in [("src_ip", src), ("dst_ip", dst)] {
+        if let Some(Err(e)) = prefix.map(|prefix| prefix.to_ipv4()) {
+            issues.push(ValidationIssue::new(field, e.to_string()));
+        }
+    }
+}
+
+/// 포트 목록 (`port`는 "src_port" 또는 "dst_port", 문제 필드는 `<port>_list`)
+fn check_port_list(port: &str, range: (u16, u16), list: &[u16], protocol: u8, issues: &mut Vec<ValidationIssue>) {
+    if list.is_empty() {
+        return;
+    }
+
+    let field = format!("{}_list", port);
+    if range != (0, u16::MAX) {
+        issues.push(ValidationIssue::new(&field, format!("{} and a {} range cannot be used together", field, port)));
+    }
+    if !matches!(protocol, 6 | 17) {
+        issues.push(ValidationIssue::new(&field, format!("{} requires the tcp or udp protocol", field)));
+    }
+    if layout::encode_port_list(list).is_none() {
+        issues.push(ValidationIssue::new(&field,
+            format!("must hold at most {} ports, none of them 0", layout::MAX_RULE_PORTS)));
+    }
+}
+
+/// 규칙 정의가 쓰는 매치 필드와 그 필드에 필요한 XDP 프로그램 기능 (필드 순서대로)
+pub fn required_features(spec: &RuleSpec) -> Vec<(&'static str, u64)> {
+    let mut required = Vec::new();
+    if spec.dst_ip.is_some() {
+        required.push(("dst_ip", XdpFeatures::DST_IP));
+    }
+    if spec.tcp_flags != 0 {
+        required.push(("tcp_flags", XdpFeatures::TCP_FLAGS));
+    }
+    if spec.redirect_cpu.is_some() {
+        required.push(("redirect_cpu", XdpFeatures::REDIRECT_CPU));
+    }
+    if !spec.src_port_list.is_empty() {
+        required.push(("src_port_list", XdpFeatures::PORT_LIST));
+    }
+    if !spec.dst_port_list.is_empty() {
+        required.push(("dst_port_list", XdpFeatures::PORT_LIST));
+    }
+    if spec.rate_limit != 0 {
+        required.push(("rate_limit", XdpFeatures::RATE_LIMIT));
+    }
+    required
+}
+
+/// 로드된 XDP 프로그램이 처리하지 못하는 매치 필드 (필드마다 빠진 기능을 밝힘)
+pub fn check_features(spec: &RuleSpec, supported: XdpFeatures) -> Vec<ValidationIssue> {
+    required_features(spec).into_iter()
+        .filter(|(_, feature)| !supported.has(*feature))
+        .map(|(field, feature)| ValidationIssue::new(field, format!(
+            "{} requires the {} feature, which the loaded XDP program does not support (supported: {})",
+            field, XdpFeatures(feature), supported)))
+        .collect()
+}
+
+/// 할당량 (한도가 있어야 액션을 지정할 수 있고, 한도는 0보다 크며 리디렉션으로 바꿀 수 없음)
+fn check_quota(spec: &RuleSpec, issues: &mut Vec<ValidationIssue>) {
+    if spec.quota_bytes.is_none() && spec.quota_packets.is_none() {
+        if spec.quota_action.is_some() {
+            issues.push(ValidationIssue::new("quota_action", "quota_action requires quota_bytes or quota_packets"));
+        }
+        return;
+    }
+
+    for (field, quota) in [("quota_bytes", spec.quota_bytes), ("quota_packets", spec.quota_packets)] {
+        if quota == Some(0) {
+            issues.push(ValidationIssue::new(field, "Quota must be greater than zero"));
+        }
+    }
+    if let Some(action) = spec.quota_action {
+        if action == ACTION_REDIRECT || utils::action_num_to_name(action) == "unknown" {
+            issues.push(ValidationIssue::new("quota_action",
+                format!("Invalid quota action {} (expected pass, drop or count)", action)));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{ActionType, ProtocolType};
+
+    fn spec() -> RuleSpec {
+        RuleSpec::new("web", ActionType::Drop).with_src_ip("10.0.0.0/8")
+    }
+
+    fn fields(spec: &RuleSpec) -> Vec<String> {
+        validate_rule_spec(spec).into_iter().map(|issue| issue.field).collect()
+    }
+
+    #[test]
+    fn test_valid_specs() {
+        let valid = [
+            spec(),
+            RuleSpec::new("any", ActionType::Pass),
+            spec().with_dst_ip("192.168.1.1"),
+            RuleSpec::new("v6-any", ActionType::Drop).with_dst_port(22),
+            spec().with_protocol(ProtocolType::Tcp).with_dst_port_list(&[80, 443]),
+            RuleSpec { action: 3, redirect_if: Some("veth0".to_string()), ..spec() },
+            RuleSpec { action: 3, redirect_cpu: Some(2), ..spec() },
+            RuleSpec::new("hits", ActionType::Count).with_counter_group("web-total"),
+            spec().with_expire(600).with_owner_webhook("https://ops.example.com/expiry", Some(60)),
+            spec().with_quota(Some(1 << 30), Some(1000), ActionType::Pass),
+            RuleSpec { quota_packets: Some(10), ..spec() },
+            // 공백뿐인 카운터 그룹과 웹훅은 지정하지 않은 것으로 봄
+            RuleSpec { counter_group: Some(" ".to_string()), owner_webhook: Some("".to_string()), ..spec() },
+        ];
+
+        for spec in valid {
+            assert_eq!(validate_rule_spec(&spec), Vec::new(), "{:?}", spec);
+        }
+    }
+
+    #[test]
+    fn test_issue_table() {
+        let v6 = || Some("2001:db8::/32".to_string());
+        let cases: Vec<(RuleSpec, &[&str])> = vec![
+            (RuleSpec { label: "bad label".to_string(), ..spec() }, &["label"]),
+            (RuleSpec { label: "x".repeat(32), ..spec() }, &["label"]),
+            (RuleSpec { label: String::new(), ..spec() }, &["label"]),
+            (RuleSpec { action: 0, ..spec() }, &["action"]),
+            (RuleSpec { action: 9, ..spec() }, &["action"]),
+            (RuleSpec { action: 3, ..spec() }, &["redirect_if"]),
+            (RuleSpec { redirect_if: Some("veth0".to_string()), ..spec() }, &["redirect_if"]),
+            (RuleSpec { redirect_cpu: Some(0), ..spec() }, &["redirect_cpu"]),
+            (RuleSpec { action: 3, redirect_if: Some("veth0".to_string()), redirect_cpu: Some(0), ..spec() }, &["redirect_cpu"]),
+            (RuleSpec { src_ip: Some("300.0.0.1".to_string()), ..spec() }, &["src_ip"]),
+            (RuleSpec { src_ip: Some("10.0.0.0/33".to_string()), ..spec() }, &["src_ip"]),
+            (RuleSpec { dst_ip: Some("db.internal".to_string()), ..spec() }, &["dst_ip"]),
+            (RuleSpec { src_ip: v6(), ..spec() }, &["src_ip"]),
+            (RuleSpec { src_ip: v6(), dst_ip: v6(), ..spec() }, &["src_ip", "dst_ip"]),
+            (RuleSpec { dst_ip: v6(), ..spec() }, &["dst_ip"]),
+            (RuleSpec { src_ip: Some("bad".to_string()), dst_ip: Some("worse".to_string()), ..spec() }, &["src_ip", "dst_ip"]),
+            (RuleSpec { src_port_list: vec![53], ..spec() }, &["src_port_list"]),
+            (spec().with_protocol(ProtocolType::Tcp).with_dst_port_list(&[80]).with_dst_ports(80, 90), &["dst_port_list"]),
+            (spec().with_protocol(ProtocolType::Udp).with_dst_port_list(&(1..=9).collect::<Vec<_>>()), &["dst_port_list"]),
+            (spec().with_protocol(ProtocolType::Udp).with_src_port_list(&[0]), &["src_port_list"]),
+            (spec().with_counter_group("web"), &["counter_group"]),
+            (RuleSpec::new("hits", ActionType::Count).with_counter_group("web hits"), &["counter_group"]),
+            (spec().with_owner_webhook("https://ops.example.com/expiry", None), &["owner_webhook"]),
+            (RuleSpec { notify_before: Some(60), ..spec() }, &["notify_before"]),
+            (RuleSpec { quota_action: Some(2), ..spec() }, &["quota_action"]),
+            (RuleSpec { quota_bytes: Some(0), quota_packets: Some(0), ..spec() }, &["quota_bytes", "quota_packets"]),
+            (RuleSpec { quota_bytes: Some(1), quota_action: Some(3), ..spec() }, &["quota_action"]),
+            (RuleSpec { quota_bytes: Some(1), quota_action: Some(7), ..spec() }, &["quota_action"]),
+        ];
+
+        for (spec, expected) in cases {
+            assert_eq!(fields(&spec), expected, "{:?}", spec);
+        }
+    }
+
+    #[test]
+    fn test_all_issues_reported() {
+        // 한 정의의 문제를 필드 순서대로 모두 보고
+        let broken = RuleSpec {
+            label: "bad label".to_string(),
+            action: 2,
+            redirect_if: Some("veth0".to_string()),
+            dst_ip: Some("10.0.0.0/40".to_string()),
+            dst_port_list: vec![80, 443],
+            counter_group: Some("web".to_string()),
+            notify_before: Some(60),
+            quota_action: Some(1),
+            ..spec()
+        };
+        assert_eq!(fields(&broken), [
+            "label", "redirect_if", "dst_ip", "dst_port_list", "counter_group", "notify_before", "quota_action",
+        ]);
+
+        // 포트 목록 하나에 문제가 여럿이면 모두
+        let ports = RuleSpec { dst_port_min: 80, dst_port_max: 80, dst_port_list: vec![0], ..spec() };
+        let issues = validate_rule_spec(&ports);
+        assert_eq!(issues.len(), 3);
+        assert_eq!(issues[1].to_string(), "dst_port_list: dst_port_list requires the tcp or udp protocol");
+
+        // 주소 체계가 섞이면 체계 문제만 (IPv6 미지원은 보고하지 않음)
+        let mixed = RuleSpec { dst_ip: Some("2001:db8::1".to_string()), ..spec() };
+        let issues = validate_rule_spec(&mixed);
+        assert_eq!(issues.len(), 1);
+        assert!(issues[0].message.contains("address family v6 does not match src_ip family v4"), "{}", issues[0]);
+
+        let invalid: InvalidArgument = issues[0].clone().into();
+        assert_eq!(invalid.field, "dst_ip");
+    }
+
+    #[test]
+    fn test_check_features() {
+        let ports = spec().with_protocol(ProtocolType::Tcp).with_src_port_list(&[1024]).with_dst_port_list(&[80, 443]);
+        let full = RuleSpec { tcp_flags: 0x02, action: 3, redirect_cpu: Some(1), ..ports.clone() };
+        assert_eq!(required_features(&full), [
+            ("tcp_flags", XdpFeatures::TCP_FLAGS),
+            ("redirect_cpu", XdpFeatures::REDIRECT_CPU),
+            ("src_port_list", XdpFeatures::PORT_LIST),
+            ("dst_port_list", XdpFeatures::PORT_LIST),
+        ]);
+        assert!(required_features(&spec()).is_empty());
+
+        let missing = |spec: &RuleSpec, supported: u64| -> Vec<String> {
+            check_features(spec, XdpFeatures(supported)).into_iter().map(|issue| issue.field).collect()
+        };
+        assert!(missing(&full, XdpFeatures::ALL.0).is_empty());
+        assert!(missing(&spec(), 0).is_empty());
+        assert_eq!(missing(&full, XdpFeatures::LEGACY.0), ["redirect_cpu", "src_port_list", "dst_port_list"]);
+        assert_eq!(missing(&full, XdpFeatures::PORT_LIST), ["tcp_flags", "redirect_cpu"]);
+        assert_eq!(missing(&ports, XdpFeatures::TCP_FLAGS | XdpFeatures::REDIRECT_CPU), ["src_port_list", "dst_port_list"]);
+
+        // 레이트 리밋은 rate_limit 비트가 없는 (이전에 빌드된) 프로그램에서 빠진 기능
+        let limited = RuleSpec { rate_limit: 100, ..spec() };
+        assert_eq!(required_features(&limited), [("rate_limit", XdpFeatures::RATE_LIMIT)]);
+        assert!(missing(&limited, XdpFeatures::ALL.0).is_empty());
+        assert_eq!(missing(&limited, XdpFeatures::ALL.0 & !XdpFeatures::RATE_LIMIT), ["rate_limit"]);
+
+        // 목적지 주소는 지금 프로그램이 비교하지 않으므로 항상 빠진 기능
+        let to_host = RuleSpec { dst_ip: Some("192.168.1.1".to_string()), ..spec() };
+        assert_eq!(required_features(&to_host), [("dst_ip", XdpFeatures::DST_IP)]);
+        assert_eq!(missing(&to_host, XdpFeatures::ALL.0), ["dst_ip"]);
+
+        // 빠진 기능과 프로그램이 처리하는 기능을 밝힘
+        let issues = check_features(&ports, XdpFeatures::LEGACY);
+        assert_eq!(issues[1].to_string(),
+            "dst_port_list: dst_port_list requires the port_list feature, which the loaded XDP program does not support (supported: tcp_flags)");
+    }
+}
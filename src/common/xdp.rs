@@ -0,0 +1,263 @@
+//! XDP 직접 제어 모듈
+//! 데몬 없이 XDP 프로그램을 연결하고 필터 규칙을 기록하며 전역 통계를 읽는 함수
+//!
+//! 맵은 `XdpMap`으로 접근한다. 고정(pin)된 맵이나 로드한 오브젝트의 맵을 여는 일은
+//! 호출자가 맡는다 (데몬은 libbpf 맵을, 테스트는 메모리 맵을 넘긴다).
+
+use anyhow::{anyhow, bail, Context, Result};
+use std::path::Path;
+use std::process::Command;
+
+use crate::layout::{self, FilterStats, GlobalStats};
+use crate::types::{ActionType, XdpMode};
+use crate::utils;
+
+/// struct filter_rule의 redirect_cpu에서 CPU 리디렉션 없음
+const REDIRECT_CPU_NONE: u32 = u32::MAX;
+
+/// 키/값 바이트 기반 BPF 맵
+pub trait XdpMap {
+    /// CPU별 값 조회 (PERCPU 맵이 아니면 단일 값)
+    fn lookup_percpu(&self, key: &[u8]) -> Result<Option<Vec<Vec<u8>>>>;
+    /// 키/값 갱신
+    fn update(&self, key: &[u8], value: &[u8]) -> Result<()>;
+}
+
+/// 직접 설치할 필터 규칙 (소스 프리픽스 규칙, 리디렉션 제외)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct XdpRule {
+    /// 소스 주소와 프리픽스 길이 (`utils::parse_ip_prefix`와 같은 호스트 순서 값)
+    pub src: (u32, u32),
+    /// 프로토콜 번호 (0이면 모든 프로토콜)
+    pub protocol: u8,
+    pub src_port_min: u16,
+    pub src_port_max: u16,
+    pub dst_port_min: u16,
+    pub dst_port_max: u16,
+    pub tcp_flags: u8,
+    pub action: ActionType,
+    pub priority: u32,
+    /// 초당 최대 패킷 수 (0이면 제한 없음)
+    pub rate_limit: u32,
+    pub expire: u32,
+    pub label: String,
+}
+
+impl XdpRule {
+    /// 모든 프로토콜과 포트에 매치하는 규칙
+    pub fn new(label: &str, src: (u32, u32), action: ActionType) -> Self {
+        Self {
+            src,
+            protocol: 0,
+            src_port_min: 0,
+            src_port_max: u16::MAX,
+            dst_port_min: 0,
+            dst_port_max: u16::MAX,
+            tcp_flags: 0,
+            action,
+            priority: 0,
+            rate_limit: 0,
+            expire: 0,
+            label: label.to_string(),
+        }
+    }
+}
+
+/// 인터페이스에 XDP 프로그램 연결 (`ip link set dev <인터페이스> <모드> obj <오브젝트> sec xdp`)
+pub fn attach_interface(obj_path: &Path, interface: &str, mode: XdpMode) -> Result<()> {
+    if !obj_path.exists() {
+        bail!("BPF object {} does not exist", obj_path.display());
+    }
+
+    let output = attach_command(obj_path, interface, mode)
+        .output()
+        .with_context(|| format!("Failed to run ip for interface {}", interface))?;
+    if !output.status.success() {
+        bail!("Failed to attach XDP program to {} in {} mode: {}",
+            interface, mode.to_str(), String::from_utf8_lossy(&output.stderr).trim());
+    }
+
+    Ok(())
+}
+
+/// XDP 연결 명령
+fn attach_command(obj_path: &Path, interface: &str, mode: XdpMode) -> Command {
+    let keyword = match mode {
+        XdpMode::Driver => "xdpdrv",
+        XdpMode::Generic => "xdpgeneric",
+        XdpMode::Offload => "xdpoffload",
+    };
+
+    let mut command = Command::new("ip");
+    command.args(["link", "set", "dev", interface, keyword, "obj"])
+        .arg(obj_path)
+        .args(["sec", "xdp"]);
+    command
+}
+
+/// 프리픽스 키 (struct prefix_key)
+///
+/// `addr`는 호스트 순서 값이다. LPM 트라이는 주소 바이트를 앞에서부터 비교하므로
+/// 주소는 네트워크 순서로 기록한다. 10.0.0.0/8 → `08 00 00 00 0a 00 00 00`
+pub fn prefix_key(addr: u32, prefix_len: u32) -> [u8; 8] {
+    let mut key = [0u8; 8];
+    key[..4].copy_from_slice(&prefix_len.to_le_bytes());
+    key[4..].copy_from_slice(&addr.to_be_bytes());
+    key
+}
+
+/// filter_rules 맵에 규칙 기록 (같은 프리픽스의 규칙은 덮어씀, 통계는 0부터)
+pub fn install_rule(filter_rules: &dyn XdpMap, rule: &XdpRule) -> Result<()> {
+    let (addr, prefix_len) = rule.src;
+    if prefix_len > 32 {
+        bail!("Invalid prefix length /{} for rule '{}'", prefix_len, rule.label);
+    }
+    if rule.action == ActionType::Redirect {
+        bail!("Rule '{}' redirects; redirect targets are managed by the daemon", rule.label);
+    }
+    let label = utils::label_to_kernel_bytes(&rule.label)?;
+    // 포트 목록은 비워 두고 범위로만 매치
+    let ports = [0u8; layout::MAX_RULE_PORTS * 2];
+
+    let fields: [(&str, &[u8]); 16] = [
+        ("priority", &rule.priority.to_le_bytes()),
+        ("action", &[rule.action as u8]),
+        ("protocol", &[rule.protocol]),
+        ("src_port_min", &rule.src_port_min.to_le_bytes()),
+        ("src_port_max", &rule.src_port_max.to_le_bytes()),
+        ("dst_port_min", &rule.dst_port_min.to_le_bytes()),
+        ("dst_port_max", &rule.dst_port_max.to_le_bytes()),
+        ("tcp_flags", &[rule.tcp_flags]),
+        ("redirect_ifindex", &0u32.to_le_bytes()),
+        ("rate_limit", &rule.rate_limit.to_le_bytes()),
+        ("expire", &rule.expire.to_le_bytes()),
+        ("redirect_cpu", &REDIRECT_CPU_NONE.to_le_bytes()),
+        ("label", &label),
+        ("src_ports", &ports),
+        ("dst_ports", &ports),
+        ("stats", &FilterStats::default().to_bytes()),
+    ];
+
+    let mut value = vec![0u8; layout::RULE_VALUE_SIZE];
+    for (name, bytes) in fields {
+        let range = layout::rule_field(name)
+            .ok_or_else(|| anyhow!("struct filter_rule has no field {}", name))?;
+        value[range].copy_from_slice(bytes);
+    }
+
+    filter_rules.update(&prefix_key(addr, prefix_len), &value)
+        .with_context(|| format!("Failed to write rule '{}' to filter_rules", rule.label))
+}
+
+/// stats_map의 전역 통계 읽기 (CPU별 값 합산)
+pub fn read_stats(stats_map: &dyn XdpMap) -> Result<GlobalStats> {
+    let values = stats_map.lookup_percpu(&0u32.to_le_bytes())?
+        .ok_or_else(|| anyhow!("stats_map has no entry"))?;
+
+    let mut total: Option<GlobalStats> = None;
+    for stats in values.iter().filter_map(|value| GlobalStats::from_bytes(value)) {
+        total.get_or_insert_with(GlobalStats::default).merge(&stats);
+    }
+    total.ok_or_else(|| anyhow!("stats_map value is too short"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::collections::HashMap;
+
+    /// 메모리 맵 (CPU별 값은 `percpu`에 미리 채움)
+    #[derive(Default)]
+    struct FakeMap {
+        entries: RefCell<HashMap<Vec<u8>, Vec<u8>>>,
+        percpu: HashMap<Vec<u8>, Vec<Vec<u8>>>,
+    }
+
+    impl XdpMap for FakeMap {
+        fn lookup_percpu(&self, key: &[u8]) -> Result<Option<Vec<Vec<u8>>>> {
+            Ok(self.percpu.get(key).cloned().or_else(|| self.entries.borrow().get(key).map(|value| vec![value.clone()])))
+        }
+
+        fn update(&self, key: &[u8], value: &[u8]) -> Result<()> {
+            self.entries.borrow_mut().insert(key.to_vec(), value.to_vec());
+            Ok(())
+        }
+    }
+
+    fn field(value: &[u8], name: &str) -> Vec<u8> {
+        value[layout::rule_field(name).unwrap()].to_vec()
+    }
+
+    #[test]
+    fn test_prefix_key() {
+        assert_eq!(prefix_key(0x0A000000, 8), [8, 0, 0, 0, 0x0a, 0, 0, 0]);
+        assert_eq!(prefix_key(0xC0A80101, 32), [32, 0, 0, 0, 192, 168, 1, 1]);
+    }
+
+    #[test]
+    fn test_install_rule() {
+        let map = FakeMap::default();
+        let rule = XdpRule {
+            protocol: 6,
+            dst_port_min: 22,
+            dst_port_max: 22,
+            priority: 10,
+            ..XdpRule::new("block-ssh", utils::parse_ip_prefix("10.0.0.0/8").unwrap(), ActionType::Drop)
+        };
+        install_rule(&map, &rule).unwrap();
+
+        let entries = map.entries.borrow();
+        let value = &entries[prefix_key(0x0A000000, 8).as_slice()];
+        assert_eq!(value.len(), layout::RULE_VALUE_SIZE);
+        assert_eq!(field(value, "action"), [ActionType::Drop as u8]);
+        assert_eq!(field(value, "protocol"), [6]);
+        assert_eq!(field(value, "priority"), 10u32.to_le_bytes());
+        assert_eq!(field(value, "dst_port_min"), 22u16.to_le_bytes());
+        assert_eq!(field(value, "src_port_max"), u16::MAX.to_le_bytes());
+        assert_eq!(field(value, "redirect_cpu"), REDIRECT_CPU_NONE.to_le_bytes());
+        assert_eq!(&field(value, "label")[..10], b"block-ssh\0");
+        assert_eq!(FilterStats::from_bytes(&field(value, "stats")), Some(FilterStats::default()));
+    }
+
+    #[test]
+    fn test_install_rule_rejects() {
+        let map = FakeMap::default();
+        assert!(install_rule(&map, &XdpRule::new("web", (0x0A000000, 33), ActionType::Drop)).is_err());
+        assert!(install_rule(&map, &XdpRule::new("web", (0x0A000000, 8), ActionType::Redirect)).is_err());
+        assert!(install_rule(&map, &XdpRule::new("bad label", (0x0A000000, 8), ActionType::Drop)).is_err());
+        assert!(map.entries.borrow().is_empty());
+    }
+
+    #[test]
+    fn test_read_stats() {
+        let cpu = |packets: u64, bytes: u64, tcp: u64| -> Vec<u8> {
+            [packets, bytes, 0, tcp, 0, 0, packets - tcp, 0, 0].iter()
+                .flat_map(|value| value.to_le_bytes())
+                .collect()
+        };
+        let mut map = FakeMap::default();
+        map.percpu.insert(0u32.to_le_bytes().to_vec(), vec![cpu(10, 1000, 4), cpu(5, 300, 5)]);
+
+        let stats = read_stats(&map).unwrap();
+        assert_eq!((stats.packets, stats.bytes), (15, 1300));
+        assert_eq!((stats.tcp_packets, stats.other_packets), (9, 6));
+
+        // 항목이 없거나 값이 짧으면 오류
+        assert!(read_stats(&FakeMap::default()).is_err());
+        map.percpu.insert(0u32.to_le_bytes().to_vec(), vec![vec![0; 8]]);
+        assert!(read_stats(&map).is_err());
+    }
+
+    #[test]
+    fn test_attach_interface() {
+        let command = attach_command(Path::new("/opt/xdp_filter.o"), "eth0", XdpMode::Generic);
+        let args: Vec<_> = command.get_args().map(|arg| arg.to_str().unwrap()).collect();
+        assert_eq!(command.get_program(), "ip");
+        assert_eq!(args, ["link", "set", "dev", "eth0", "xdpgeneric", "obj", "/opt/xdp_filter.o", "sec", "xdp"]);
+
+        // 오브젝트가 없으면 ip를 실행하지 않음
+        let missing = attach_interface(Path::new("/nonexistent/xdp_filter.o"), "eth0", XdpMode::Driver).unwrap_err();
+        assert!(missing.to_string().contains("does not exist"));
+    }
+}
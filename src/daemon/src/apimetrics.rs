@@ -0,0 +1,222 @@
+//! API 서버 메트릭 모듈
+//! 요청 종류별 처리 수, 오류 수, 처리 시간 히스토그램과 연결 상태 집계
+//!
+//! 요청 경로에서는 원자 연산만 쓰고, 종류별 항목은 처음 보는 종류일 때만 쓰기
+//! 잠금을 잡아 추가한다. 텔레메트리와 Prometheus 내보내기는 `snapshot`을 읽는다.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use swift_guard::api::{ApiResponse, ApiServerStats, RequestTypeStats};
+use swift_guard::histogram::LatencyHistogram;
+use swift_guard::types::API_LATENCY_BUCKET_BOUNDS_US;
+
+/// 요청 종류별 카운터
+#[derive(Debug)]
+struct KindMetrics {
+    requests: AtomicU64,
+    errors: AtomicU64,
+    duration: LatencyHistogram,
+}
+
+impl Default for KindMetrics {
+    fn default() -> Self {
+        Self {
+            requests: AtomicU64::new(0),
+            errors: AtomicU64::new(0),
+            duration: LatencyHistogram::new(&API_LATENCY_BUCKET_BOUNDS_US),
+        }
+    }
+}
+
+/// API 서버 메트릭
+#[derive(Debug, Default)]
+pub struct ApiMetrics {
+    requests_total: AtomicU64,
+    errors_total: AtomicU64,
+    rate_limited_total: AtomicU64,
+    oversized_frames_total: AtomicU64,
+    active_connections: AtomicU64,
+    kinds: RwLock<HashMap<&'static str, Arc<KindMetrics>>>,
+}
+
+/// 열린 연결 표시 (해제되면 연결 수 감소)
+#[derive(Debug)]
+pub struct ConnectionGuard<'a> {
+    metrics: &'a ApiMetrics,
+}
+
+impl Drop for ConnectionGuard<'_> {
+    fn drop(&mut self) {
+        self.metrics.active_connections.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+impl ApiMetrics {
+    /// 새로운 메트릭 생성
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 처리한 요청 기록
+    pub fn record(&self, kind: &'static str, duration: Duration, error: bool) {
+        self.requests_total.fetch_add(1, Ordering::Relaxed);
+        if error {
+            self.errors_total.fetch_add(1, Ordering::Relaxed);
+        }
+
+        let metrics = self.kind(kind);
+        metrics.requests.fetch_add(1, Ordering::Relaxed);
+        if error {
+            metrics.errors.fetch_add(1, Ordering::Relaxed);
+        }
+        metrics.duration.record(duration);
+    }
+
+    /// 빈도 제한으로 거부한 요청 기록 (`record`와 별도로 호출)
+    pub fn rate_limited(&self) {
+        self.rate_limited_total.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// 크기 제한을 넘은 요청 프레임 기록
+    pub fn oversized(&self) {
+        self.oversized_frames_total.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// 연결 시작 (반환값이 살아 있는 동안 열린 연결로 집계)
+    pub fn connection(&self) -> ConnectionGuard<'_> {
+        self.active_connections.fetch_add(1, Ordering::Relaxed);
+        ConnectionGuard { metrics: self }
+    }
+
+    /// 요청 종류 항목 (없으면 추가)
+    fn kind(&self, kind: &'static str) -> Arc<KindMetrics> {
+        if let Some(metrics) = self.kinds.read().ok().and_then(|kinds| kinds.get(kind).cloned()) {
+            return metrics;
+        }
+        match self.kinds.write() {
+            Ok(mut kinds) => kinds.entry(kind).or_default().clone(),
+            // 잠금이 깨졌으면 종류별 집계만 버림
+            Err(_) => Arc::default(),
+        }
+    }
+
+    /// 현재 값 복사
+    pub fn snapshot(&self) -> ApiServerStats {
+        let mut requests: Vec<RequestTypeStats> = self.kinds.read()
+            .map(|kinds| kinds.iter().map(|(kind, metrics)| {
+                let duration = metrics.duration.snapshot();
+                RequestTypeStats {
+                    kind: kind.to_string(),
+                    requests: metrics.requests.load(Ordering::Relaxed),
+                    errors: metrics.errors.load(Ordering::Relaxed),
+                    duration_sum_us: duration.sum_us,
+                    duration_buckets: duration.counts,
+                }
+            }).collect())
+            .unwrap_or_default();
+        requests.sort_by(|a, b| a.kind.cmp(&b.kind));
+
+        ApiServerStats {
+            requests_total: self.requests_total.load(Ordering::Relaxed),
+            errors_total: self.errors_total.load(Ordering::Relaxed),
+            rate_limited_total: self.rate_limited_total.load(Ordering::Relaxed),
+            oversized_frames_total: self.oversized_frames_total.load(Ordering::Relaxed),
+            active_connections: self.active_connections.load(Ordering::Relaxed),
+            requests,
+        }
+    }
+}
+
+/// 오류 응답 여부
+pub fn is_error(response: &ApiResponse) -> bool {
+    matches!(response,
+        ApiResponse::Error { .. }
+        | ApiResponse::InvalidArgument { .. }
+        | ApiResponse::NotFound { .. }
+        | ApiResponse::RateLimited { .. })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use swift_guard::error::ErrorCode;
+
+    #[test]
+    fn test_record_requests() {
+        let metrics = ApiMetrics::new();
+        metrics.record("ping", Duration::from_micros(200), false);
+        metrics.record("ping", Duration::from_micros(700), false);
+        metrics.record("add_rule", Duration::from_millis(3), true);
+        metrics.record("add_rule", Duration::from_secs(5), false);
+        metrics.rate_limited();
+        metrics.oversized();
+
+        let stats = metrics.snapshot();
+        assert_eq!(stats.requests_total, 4);
+        assert_eq!(stats.errors_total, 1);
+        assert_eq!(stats.rate_limited_total, 1);
+        assert_eq!(stats.oversized_frames_total, 1);
+
+        // 종류 이름 순
+        assert_eq!(stats.requests, [
+            RequestTypeStats {
+                kind: "add_rule".to_string(),
+                requests: 2,
+                errors: 1,
+                duration_sum_us: 5_003_000,
+                duration_buckets: vec![0, 0, 1, 0, 0, 0, 0, 0, 1],
+            },
+            RequestTypeStats {
+                kind: "ping".to_string(),
+                requests: 2,
+                errors: 0,
+                duration_sum_us: 900,
+                duration_buckets: vec![1, 1, 0, 0, 0, 0, 0, 0, 0],
+            },
+        ]);
+    }
+
+    #[test]
+    fn test_connection_guard() {
+        let metrics = ApiMetrics::new();
+        let first = metrics.connection();
+        {
+            let _second = metrics.connection();
+            assert_eq!(metrics.snapshot().active_connections, 2);
+        }
+        assert_eq!(metrics.snapshot().active_connections, 1);
+        drop(first);
+        assert_eq!(metrics.snapshot().active_connections, 0);
+    }
+
+    #[test]
+    fn test_concurrent_record() {
+        let metrics = Arc::new(ApiMetrics::new());
+        let handles: Vec<_> = (0..8).map(|i| {
+            let metrics = metrics.clone();
+            std::thread::spawn(move || {
+                let kind = if i % 2 == 0 { "ping" } else { "get_stats" };
+                for _ in 0..1000 {
+                    metrics.record(kind, Duration::from_micros(10), false);
+                }
+            })
+        }).collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        let stats = metrics.snapshot();
+        assert_eq!(stats.requests_total, 8000);
+        assert!(stats.requests.iter().all(|kind| kind.requests == 4000));
+    }
+
+    #[test]
+    fn test_is_error() {
+        assert!(is_error(&ApiResponse::Error { message: "x".to_string(), code: ErrorCode::Internal }));
+        assert!(is_error(&ApiResponse::RateLimited { retry_after_ms: 1, limit: 1 }));
+        assert!(!is_error(&ApiResponse::Success { message: "ok".to_string(), warning: None }));
+    }
+}
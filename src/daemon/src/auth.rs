@@ -0,0 +1,333 @@
+//! API 인증 모듈
+//! 구성된 API 토큰으로 요청자를 식별하고 규칙 네임스페이스 접근 권한 검사
+//!
+//! 토큰이 하나도 없으면 인증하지 않고 모든 요청을 기본 네임스페이스의 관리자
+//! 요청으로 처리한다. 토큰이 있으면 모든 요청에 토큰이 필요하고, 규칙 요청은
+//! 토큰의 네임스페이스로 제한된다. 다른 네임스페이스와 데몬 전역 변경 요청
+//! (attach, WASM 모듈 등)은 관리자 토큰만 허용한다.
+
+use anyhow::{bail, Result};
+
+use swift_guard::api::{ApiRequest, ApiResponse};
+use swift_guard::error::{ErrorCode, InvalidArgument};
+use swift_guard::utils;
+
+use crate::config::ApiToken;
+
+/// 인증된 요청자
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Caller {
+    /// 요청이 네임스페이스를 생략하면 쓰는 네임스페이스
+    pub namespace: String,
+    /// 조회 요청만 허용
+    pub readonly: bool,
+    /// 모든 네임스페이스와 데몬 전역 요청 허용
+    pub admin: bool,
+}
+
+impl Caller {
+    /// 인증을 쓰지 않을 때의 요청자 (기본 네임스페이스의 관리자)
+    fn unrestricted() -> Self {
+        Self {
+            namespace: utils::default_namespace(),
+            readonly: false,
+            admin: true,
+        }
+    }
+}
+
+/// 인증 또는 권한 오류
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AuthError {
+    /// 토큰이 없거나 잘못되었거나 요청 권한이 없음
+    Denied(String),
+    /// 잘못된 네임스페이스 이름
+    InvalidNamespace(InvalidArgument),
+}
+
+impl From<AuthError> for ApiResponse {
+    fn from(error: AuthError) -> Self {
+        match error {
+            AuthError::Denied(message) => ApiResponse::Error {
+                message,
+                code: ErrorCode::PermissionDenied,
+            },
+            AuthError::InvalidNamespace(e) => ApiResponse::InvalidArgument {
+                field: e.field,
+                message: e.message,
+            },
+        }
+    }
+}
+
+/// API 토큰 목록
+#[derive(Default)]
+pub struct TokenTable {
+    tokens: Vec<ApiToken>,
+}
+
+impl std::fmt::Debug for TokenTable {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // 토큰 문자열은 로그에 남기지 않음
+        f.debug_struct("TokenTable").field("tokens", &self.tokens.len()).finish()
+    }
+}
+
+impl TokenTable {
+    /// 구성의 `api.tokens`로 토큰 목록 생성
+    pub fn new(tokens: Vec<ApiToken>) -> Result<Self> {
+        for (i, entry) in tokens.iter().enumerate() {
+            if entry.token.is_empty() {
+                bail!("API token #{} is empty", i + 1);
+            }
+            if let Err(e) = utils::validate_namespace(&entry.namespace) {
+                bail!("API token #{}: {}", i + 1, e);
+            }
+            if tokens[..i].iter().any(|other| other.token == entry.token) {
+                bail!("API token #{} is configured more than once", i + 1);
+            }
+        }
+
+        Ok(Self { tokens })
+    }
+
+    /// 인증 사용 여부
+    pub fn is_enabled(&self) -> bool {
+        !self.tokens.is_empty()
+    }
+
+    /// 요청의 토큰으로 요청자 식별
+    pub fn authenticate(&self, token: Option<&str>) -> Result<Caller, AuthError> {
+        if !self.is_enabled() {
+            return Ok(Caller::unrestricted());
+        }
+
+        let token = token.ok_or_else(|| denied("API token required"))?;
+
+        // 일치하는 항목을 찾은 뒤에도 모두 비교해 응답 시간으로 위치가 드러나지 않게 함
+        let mut found = None;
+        for entry in &self.tokens {
+            if constant_time_eq(entry.token.as_bytes(), token.as_bytes()) && found.is_none() {
+                found = Some(entry);
+            }
+        }
+
+        found
+            .map(|entry| Caller {
+                namespace: entry.namespace.clone(),
+                readonly: entry.readonly,
+                admin: entry.admin,
+            })
+            .ok_or_else(|| denied("Invalid API token"))
+    }
+}
+
+/// 요청 권한 검사 후 규칙 요청이 쓸 네임스페이스 결정
+///
+/// 프레임에 네임스페이스가 없으면 요청자의 네임스페이스를 쓴다. 규칙 요청이 아니면
+/// 네임스페이스는 검사하지 않는다.
+pub fn authorize(caller: &Caller, request: &ApiRequest, namespace: Option<&str>) -> Result<String, AuthError> {
+    let kind = request.kind();
+    let mutating = request.is_mutation();
+
+    if mutating && caller.readonly {
+        return Err(denied(format!("Token is read-only and cannot send {} requests", kind)));
+    }
+
+    if !request.is_rule_scoped() {
+        // 맵 감사, 키 공간 통계, 리디렉션 대상 목록과 규칙 충돌은 모든 네임스페이스의 항목을 보여 줌
+        // 이벤트, 연결, 캡처, 동적 차단, WASM 로그와 통계도 네임스페이스로 나뉘지 않은 데몬 전체 정보임
+        let cross_namespace = matches!(request,
+            ApiRequest::AuditMaps {}
+            | ApiRequest::ReconcileMaps { .. }
+            | ApiRequest::RuleSpaceStats {}
+            | ApiRequest::ListRedirectTargets {}
+            | ApiRequest::ExplainConflicts {}
+            | ApiRequest::GetEvents { .. }
+            | ApiRequest::ListConnections { .. }
+            | ApiRequest::ListCaptures {}
+            | ApiRequest::ListDynamicBlocks { .. }
+            | ApiRequest::GetWasmModuleLog { .. }
+            | ApiRequest::GetStats {});
+        if (mutating || cross_namespace) && !caller.admin {
+            return Err(denied(format!("{} requires an admin token", kind)));
+        }
+        return Ok(caller.namespace.clone());
+    }
+
+    if let ApiRequest::ListRules { all_namespaces: true, .. } | ApiRequest::WatchRules { all_namespaces: true } = request {
+        if !caller.admin {
+            return Err(denied("Listing rules of all namespaces requires an admin token"));
+        }
+    }
+
+    let namespace = namespace.unwrap_or(&caller.namespace);
+    utils::validate_namespace(namespace).map_err(AuthError::InvalidNamespace)?;
+    if namespace != caller.namespace && !caller.admin {
+        return Err(denied(format!("Token is not allowed to access namespace '{}'", namespace)));
+    }
+
+    Ok(namespace.to_string())
+}
+
+fn denied(message: impl Into<String>) -> AuthError {
+    AuthError::Denied(message.into())
+}
+
+/// 길이가 같으면 내용과 무관하게 같은 시간이 걸리는 비교
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use swift_guard::api::ConnectionFilter;
+
+    fn token(token: &str, namespace: &str, readonly: bool, admin: bool) -> ApiToken {
+        ApiToken {
+            token: token.to_string(),
+            namespace: namespace.to_string(),
+            readonly,
+            admin,
+        }
+    }
+
+    fn table() -> TokenTable {
+        TokenTable::new(vec![
+            token("team-a-secret", "team-a", false, false),
+            token("viewer-secret", "team-a", true, false),
+            token("root-secret", "default", false, true),
+        ]).unwrap()
+    }
+
+    fn delete() -> ApiRequest {
+        ApiRequest::DeleteRule { label: "web".to_string() }
+    }
+
+    fn list(all_namespaces: bool) -> ApiRequest {
+        ApiRequest::ListRules {
+            include_stats: false,
+            expiring_within_secs: None,
+            include_rates: false,
+            all_namespaces,
+            family: None,
+            include_dynamic: false,
+            origin: None,
+            cursor: None,
+            offset: 0,
+            limit: 0,
+        }
+    }
+
+    fn is_denied(result: Result<String, AuthError>) -> bool {
+        matches!(result, Err(AuthError::Denied(_)))
+    }
+
+    #[test]
+    fn test_authenticate() {
+        let table = table();
+        assert_eq!(table.authenticate(Some("team-a-secret")).unwrap().namespace, "team-a");
+        assert!(table.authenticate(Some("root-secret")).unwrap().admin);
+        assert!(matches!(table.authenticate(None), Err(AuthError::Denied(_))));
+        assert!(table.authenticate(Some("team-a-secre")).is_err());
+
+        // 토큰이 없으면 인증하지 않음
+        let caller = TokenTable::default().authenticate(None).unwrap();
+        assert_eq!(caller, Caller::unrestricted());
+
+        assert!(TokenTable::new(vec![token("", "default", false, false)]).is_err());
+        assert!(TokenTable::new(vec![token("x", "team/a", false, false)]).is_err());
+        assert!(TokenTable::new(vec![token("x", "a", false, false), token("x", "b", false, false)]).is_err());
+    }
+
+    #[test]
+    fn test_namespace_defaults_to_token() {
+        let caller = table().authenticate(Some("team-a-secret")).unwrap();
+        assert_eq!(authorize(&caller, &delete(), None).unwrap(), "team-a");
+        assert_eq!(authorize(&caller, &delete(), Some("team-a")).unwrap(), "team-a");
+
+        // 인증을 쓰지 않으면 기본 네임스페이스
+        let caller = TokenTable::default().authenticate(None).unwrap();
+        assert_eq!(authorize(&caller, &delete(), None).unwrap(), "default");
+    }
+
+    #[test]
+    fn test_foreign_namespace_requires_admin() {
+        let table = table();
+        let tenant = table.authenticate(Some("team-a-secret")).unwrap();
+        assert!(is_denied(authorize(&tenant, &delete(), Some("team-b"))));
+        assert!(is_denied(authorize(&tenant, &delete(), Some("default"))));
+        assert!(is_denied(authorize(&tenant, &list(true), None)));
+        assert!(authorize(&tenant, &list(false), None).is_ok());
+
+        let admin = table.authenticate(Some("root-secret")).unwrap();
+        assert_eq!(authorize(&admin, &delete(), Some("team-b")).unwrap(), "team-b");
+        assert!(authorize(&admin, &list(true), None).is_ok());
+
+        let invalid = authorize(&admin, &delete(), Some("team/b")).unwrap_err();
+        assert!(matches!(ApiResponse::from(invalid), ApiResponse::InvalidArgument { ref field, .. } if field == "namespace"));
+    }
+
+    #[test]
+    fn test_readonly_token() {
+        let viewer = table().authenticate(Some("viewer-secret")).unwrap();
+        assert!(is_denied(authorize(&viewer, &delete(), None)));
+        assert!(authorize(&viewer, &list(false), None).is_ok());
+        assert!(authorize(&viewer, &ApiRequest::GetRule { label: "web".to_string() }, None).is_ok());
+
+        // 드라이런은 상태를 바꾸지 않으므로 허용
+        let dry_run = ApiRequest::ApplyRuleset { rules: Vec::new(), prune: true, dry_run: true, progress: false };
+        assert!(authorize(&viewer, &dry_run, None).is_ok());
+
+        let denied = authorize(&viewer, &delete(), None).unwrap_err();
+        assert!(matches!(ApiResponse::from(denied), ApiResponse::Error { code: ErrorCode::PermissionDenied, .. }));
+    }
+
+    #[test]
+    fn test_daemon_wide_requests_require_admin() {
+        let table = table();
+        let tenant = table.authenticate(Some("team-a-secret")).unwrap();
+        let detach = ApiRequest::Detach { interface: "eth0".to_string() };
+        assert!(is_denied(authorize(&tenant, &detach, None)));
+        assert!(is_denied(authorize(&tenant, &ApiRequest::RepairRules {}, None)));
+        assert!(authorize(&tenant, &ApiRequest::ListInterfaces {}, None).is_ok());
+        assert!(is_denied(authorize(&tenant, &ApiRequest::AuditMaps {}, None)));
+        assert!(is_denied(authorize(&tenant, &ApiRequest::RuleSpaceStats {}, None)));
+        assert!(is_denied(authorize(&tenant, &ApiRequest::ListRedirectTargets {}, None)));
+        assert!(is_denied(authorize(&tenant, &ApiRequest::ExplainConflicts {}, None)));
+
+        // 규칙 요청이 아니면 네임스페이스는 보지 않음
+        assert!(authorize(&tenant, &ApiRequest::ListInterfaces {}, Some("team-b")).is_ok());
+
+        let admin = table.authenticate(Some("root-secret")).unwrap();
+        assert!(authorize(&admin, &detach, None).is_ok());
+        assert!(authorize(&admin, &ApiRequest::ExplainConflicts {}, None).is_ok());
+    }
+
+    #[test]
+    fn test_daemon_wide_reads_require_admin() {
+        let table = table();
+        let requests = [
+            ApiRequest::GetEvents { since_seq: None, limit: 100, kinds: Vec::new() },
+            ApiRequest::ListConnections { limit: 100, filter: ConnectionFilter::default() },
+            ApiRequest::ListCaptures {},
+            ApiRequest::ListDynamicBlocks { module: None },
+            ApiRequest::GetWasmModuleLog { name: "stub".to_string(), lines: 0, after: None },
+            ApiRequest::GetStats {},
+        ];
+
+        // 다른 네임스페이스의 이벤트와 연결이 섞여 있으므로 네임스페이스 토큰은 볼 수 없음
+        let tenant = table.authenticate(Some("team-a-secret")).unwrap();
+        let viewer = table.authenticate(Some("viewer-secret")).unwrap();
+        let admin = table.authenticate(Some("root-secret")).unwrap();
+        for request in &requests {
+            assert!(is_denied(authorize(&tenant, request, None)), "{}", request.kind());
+            assert!(is_denied(authorize(&viewer, request, Some("team-a"))), "{}", request.kind());
+            assert!(authorize(&admin, request, None).is_ok(), "{}", request.kind());
+        }
+    }
+}
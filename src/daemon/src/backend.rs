@@ -0,0 +1,242 @@
+//! 맵 백엔드 모듈
+//! BPF 맵 접근과 규칙 설치 방식을 추상화하여 커널 없이도 맵 관리 로직을 테스트할 수 있게 함
+
+use anyhow::{anyhow, Context, Result};
+use libbpf_rs::{Map, MapFlags, MapType, Object};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, PoisonError};
+
+use swift_guard::api::RuleStats;
+
+use crate::maps::FilterRule;
+use crate::rulecache::RuleCache;
+
+/// 키/값 바이트 기반 맵 백엔드
+pub trait MapBackend {
+    /// 키 조회
+    fn lookup(&self, key: &[u8]) -> Result<Option<Vec<u8>>>;
+    /// CPU별 값 조회 (PERCPU 맵이 아니면 단일 값)
+    fn lookup_percpu(&self, key: &[u8]) -> Result<Option<Vec<Vec<u8>>>> {
+        Ok(self.lookup(key)?.map(|value| vec![value]))
+    }
+    /// 키/값 갱신
+    fn update(&self, key: &[u8], value: &[u8]) -> Result<()>;
+    /// 키 삭제
+    fn delete(&self, key: &[u8]) -> Result<()>;
+    /// 전체 키 목록
+    fn keys(&self) -> Vec<Vec<u8>>;
+}
+
+/// 로드된 오브젝트의 커널 맵
+///
+/// libbpf-rs의 `Map::update`/`Map::delete`는 `&mut Map`을 요구하므로 오브젝트를 잠금 뒤에
+/// 두고 접근할 때마다 가변으로 빌린다. 같은 오브젝트의 맵은 잠금 하나를 공유한다.
+/// 맵 정보(fd, 타입, 크기)는 로드 시점에 복사해 두어 잠금 없이 읽는다.
+pub struct KernelMap {
+    obj: Arc<Mutex<Object>>,
+    name: String,
+    fd: i32,
+    map_type: MapType,
+    key_size: u32,
+    value_size: u32,
+}
+
+impl KernelMap {
+    /// 오브젝트의 모든 맵
+    pub fn all(obj: &Arc<Mutex<Object>>) -> Vec<Self> {
+        let locked = obj.lock().unwrap_or_else(PoisonError::into_inner);
+        locked.maps_iter()
+            .map(|map| Self {
+                obj: Arc::clone(obj),
+                name: map.name().to_string(),
+                fd: map.fd(),
+                map_type: map.map_type(),
+                key_size: map.key_size(),
+                value_size: map.value_size(),
+            })
+            .collect()
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn fd(&self) -> i32 {
+        self.fd
+    }
+
+    pub fn map_type(&self) -> MapType {
+        self.map_type.clone()
+    }
+
+    pub fn key_size(&self) -> u32 {
+        self.key_size
+    }
+
+    pub fn value_size(&self) -> u32 {
+        self.value_size
+    }
+
+    /// 오브젝트 잠금을 잡고 맵에 접근
+    pub fn with<T>(&self, f: impl FnOnce(&mut Map) -> Result<T>) -> Result<T> {
+        let mut obj = self.obj.lock().unwrap_or_else(PoisonError::into_inner);
+        let map = obj.map_mut(&self.name)
+            .ok_or_else(|| anyhow!("BPF map {} not found in object", self.name))?;
+        f(map)
+    }
+}
+
+impl MapBackend for KernelMap {
+    fn lookup(&self, key: &[u8]) -> Result<Option<Vec<u8>>> {
+        self.with(|map| map.lookup(key, MapFlags::empty())
+            .context("Failed to lookup map entry"))
+    }
+
+    fn lookup_percpu(&self, key: &[u8]) -> Result<Option<Vec<Vec<u8>>>> {
+        self.with(|map| map.lookup_percpu(key, MapFlags::empty())
+            .context("Failed to lookup per-CPU map entry"))
+    }
+
+    fn update(&self, key: &[u8], value: &[u8]) -> Result<()> {
+        self.with(|map| map.update(key, value, MapFlags::ANY)
+            .context("Failed to update map entry"))
+    }
+
+    fn delete(&self, key: &[u8]) -> Result<()> {
+        self.with(|map| map.delete(key)
+            .context("Failed to delete map entry"))
+    }
+
+    fn keys(&self) -> Vec<Vec<u8>> {
+        self.with(|map| Ok(map.keys().collect())).unwrap_or_default()
+    }
+}
+
+/// 규칙 설치 백엔드 종류
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BackendKind {
+    /// XDP 프로그램의 BPF 맵
+    Xdp,
+    /// 데몬이 관리하는 nftables 테이블 (XDP 연결 실패 시 대체)
+    Nftables,
+}
+
+impl BackendKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Xdp => "xdp",
+            Self::Nftables => "nftables",
+        }
+    }
+}
+
+/// 필터 규칙 설치 백엔드
+///
+/// 각 메서드의 `rules`는 변경이 반영된 뒤의 전체 규칙 목록이다. 공유 자원
+/// (리디렉션 맵 항목 등)의 정리나 전체 규칙 집합 재생성에 사용한다.
+pub trait RuleBackend {
+    /// 백엔드 종류
+    fn kind(&self) -> BackendKind;
+    /// 규칙 설치
+    fn install(&mut self, rule: &FilterRule, rules: &RuleCache) -> Result<()>;
+    /// 규칙 제거
+    fn remove(&mut self, rule: &FilterRule, rules: &RuleCache) -> Result<()>;
+    /// 같은 레이블의 규칙 교체
+    fn replace(&mut self, old: &FilterRule, new: &FilterRule, rules: &RuleCache) -> Result<()> {
+        self.remove(old, rules)?;
+        self.install(new, rules)
+    }
+    /// 레이블별 규칙 통계
+    fn stats(&self, rules: &RuleCache) -> Result<HashMap<String, RuleStats>>;
+}
+
+/// 인메모리 맵 (테스트와 변경 계획 시뮬레이션용)
+#[derive(Debug, Default)]
+pub struct MemoryMap {
+    entries: std::sync::Mutex<std::collections::BTreeMap<Vec<u8>, Vec<u8>>>,
+    /// 최대 항목 수 (None이면 제한 없음, 커널 맵의 max_entries 흉내)
+    capacity: Option<usize>,
+    /// 설정하면 update가 실패함 (오류 주입용)
+    fail_updates: std::sync::atomic::AtomicBool,
+    /// 설정하면 delete가 실패함 (오류 주입용)
+    fail_deletes: std::sync::atomic::AtomicBool,
+}
+
+impl MemoryMap {
+    /// 다른 맵의 항목을 복사해 생성 (값을 읽지 못한 키는 빈 값으로 둠)
+    ///
+    /// CPUMAP처럼 사용자 공간에서 값을 읽을 수 없는 맵도 키가 있는지는 유지되므로
+    /// 항목 삭제를 시뮬레이션할 수 있다.
+    pub fn copy_of(map: &dyn MapBackend) -> Self {
+        let entries = map.keys().into_iter()
+            .map(|key| {
+                let value = map.lookup(&key).ok().flatten().unwrap_or_default();
+                (key, value)
+            })
+            .collect();
+
+        Self {
+            entries: std::sync::Mutex::new(entries),
+            ..Self::default()
+        }
+    }
+
+    /// 최대 항목 수 지정 (가득 차면 새 키의 update가 실패)
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = Some(capacity);
+        self
+    }
+}
+
+#[cfg(test)]
+impl MemoryMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+
+    pub fn fail_updates(&self, fail: bool) {
+        self.fail_updates.store(fail, std::sync::atomic::Ordering::SeqCst);
+    }
+
+    pub fn fail_deletes(&self, fail: bool) {
+        self.fail_deletes.store(fail, std::sync::atomic::Ordering::SeqCst);
+    }
+}
+
+impl MapBackend for MemoryMap {
+    fn lookup(&self, key: &[u8]) -> Result<Option<Vec<u8>>> {
+        Ok(self.entries.lock().unwrap().get(key).cloned())
+    }
+
+    fn update(&self, key: &[u8], value: &[u8]) -> Result<()> {
+        if self.fail_updates.load(std::sync::atomic::Ordering::SeqCst) {
+            return Err(anyhow::anyhow!("No space left in map"));
+        }
+        let mut entries = self.entries.lock().unwrap();
+        if let Some(capacity) = self.capacity {
+            if entries.len() >= capacity && !entries.contains_key(key) {
+                return Err(anyhow::anyhow!("No space left in map ({} entries)", capacity));
+            }
+        }
+        entries.insert(key.to_vec(), value.to_vec());
+        Ok(())
+    }
+
+    fn delete(&self, key: &[u8]) -> Result<()> {
+        if self.fail_deletes.load(std::sync::atomic::Ordering::SeqCst) {
+            return Err(anyhow::anyhow!("Map is busy"));
+        }
+        self.entries.lock().unwrap()
+            .remove(key)
+            .map(|_| ())
+            .ok_or_else(|| anyhow::anyhow!("No such map entry"))
+    }
+
+    fn keys(&self) -> Vec<Vec<u8>> {
+        self.entries.lock().unwrap().keys().cloned().collect()
+    }
+}
@@ -0,0 +1,222 @@
+//! 트래픽 기준선 모듈
+//! 수집 구간마다 pps, Mbps의 지수 가중 이동 평균과 분산을 갱신하고 평균 + k·표준편차를 넘는 구간을 이상으로 판정
+//!
+//! 통계 맵은 연결된 모든 인터페이스의 카운터를 함께 세므로 기준선도 인터페이스 전체의
+//! 트래픽 하나다. 각 구간은 기준선에 반영하기 전에 판정하므로 급증한 구간이 자기 자신의
+//! 기준선을 올리지 않는다. 이상 구간도 반영하므로 트래픽 수준이 계속 바뀌면 기준선이 따라간다.
+//! 처음 `WARMUP_SAMPLES` 구간은 기준선을 쌓기만 하고 판정하지 않는다 (콜드 스타트).
+
+use swift_guard::api::TrafficBaseline;
+
+/// 새 구간의 가중치
+pub const DEFAULT_ALPHA: f64 = 0.1;
+
+/// 판정 전에 쌓을 구간 수
+pub const WARMUP_SAMPLES: u64 = 10;
+
+/// 표준편차 하한 (평균 대비 비율)
+///
+/// 일정한 트래픽은 분산이 0에 가까워 작은 흔들림도 이상이 되므로 하한을 둔다.
+const MIN_RELATIVE_STDDEV: f64 = 0.1;
+
+/// pps 표준편차 하한 (유휴 인터페이스에 패킷 몇 개가 들어온 것은 이상이 아님)
+const MIN_PPS_STDDEV: f64 = 10.0;
+
+/// Mbps 표준편차 하한
+const MIN_MBPS_STDDEV: f64 = 0.1;
+
+/// 지수 가중 이동 평균과 분산
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ewma {
+    alpha: f64,
+    mean: f64,
+    variance: f64,
+    samples: u64,
+}
+
+impl Ewma {
+    /// 새 구간의 가중치로 생성 (0.0~1.0)
+    pub fn new(alpha: f64) -> Self {
+        Self { alpha: alpha.clamp(0.0, 1.0), mean: 0.0, variance: 0.0, samples: 0 }
+    }
+
+    /// 값 반영 (첫 값은 그대로 평균)
+    pub fn update(&mut self, value: f64) {
+        if self.samples == 0 {
+            self.mean = value;
+            self.variance = 0.0;
+        } else {
+            let diff = value - self.mean;
+            let increment = self.alpha * diff;
+            self.mean += increment;
+            self.variance = (1.0 - self.alpha) * (self.variance + diff * increment);
+        }
+        self.samples += 1;
+    }
+
+    pub fn mean(&self) -> f64 {
+        self.mean
+    }
+
+    pub fn stddev(&self) -> f64 {
+        self.variance.sqrt()
+    }
+
+    pub fn samples(&self) -> u64 {
+        self.samples
+    }
+
+    /// 판정 기준 (평균 + k·표준편차, 표준편차는 평균의 `MIN_RELATIVE_STDDEV`와 `min_stddev` 이상으로 봄)
+    pub fn threshold(&self, k: f64, min_stddev: f64) -> f64 {
+        let stddev = self.stddev()
+            .max(self.mean * MIN_RELATIVE_STDDEV)
+            .max(min_stddev);
+        self.mean + k * stddev
+    }
+}
+
+/// 인터페이스 트래픽 기준선과 이상 판정
+#[derive(Debug, Clone)]
+pub struct BaselineTracker {
+    pps: Ewma,
+    mbps: Ewma,
+    /// 판정 배수 (0 이하이면 판정하지 않음)
+    k: f64,
+}
+
+impl BaselineTracker {
+    /// 판정 배수로 생성
+    pub fn new(k: f64) -> Self {
+        Self { pps: Ewma::new(DEFAULT_ALPHA), mbps: Ewma::new(DEFAULT_ALPHA), k }
+    }
+
+    /// 한 구간 반영 (기준선을 넘은 값의 이름, 예열 중이거나 넘지 않으면 빈 목록)
+    pub fn observe(&mut self, pps: f64, mbps: f64) -> Vec<&'static str> {
+        let mut exceeded = Vec::new();
+        if self.k > 0.0 && self.pps.samples() >= WARMUP_SAMPLES {
+            if pps > self.pps.threshold(self.k, MIN_PPS_STDDEV) {
+                exceeded.push("pps");
+            }
+            if mbps > self.mbps.threshold(self.k, MIN_MBPS_STDDEV) {
+                exceeded.push("mbps");
+            }
+        }
+
+        self.pps.update(pps);
+        self.mbps.update(mbps);
+        exceeded
+    }
+
+    /// 현재 기준선
+    pub fn snapshot(&self) -> TrafficBaseline {
+        TrafficBaseline {
+            pps_mean: self.pps.mean(),
+            pps_stddev: self.pps.stddev(),
+            mbps_mean: self.mbps.mean(),
+            mbps_stddev: self.mbps.stddev(),
+            samples: self.pps.samples(),
+            k: self.k.max(0.0),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(actual: f64, expected: f64) -> bool {
+        (actual - expected).abs() < 1e-9
+    }
+
+    #[test]
+    fn test_ewma() {
+        let mut ewma = Ewma::new(0.5);
+        assert_eq!((ewma.mean(), ewma.stddev(), ewma.samples()), (0.0, 0.0, 0));
+
+        // 첫 값은 그대로 평균, 분산 0
+        ewma.update(100.0);
+        assert_eq!((ewma.mean(), ewma.stddev()), (100.0, 0.0));
+
+        // diff 100, 평균 150, 분산 0.5·(0 + 100·50) = 2500
+        ewma.update(200.0);
+        assert!(close(ewma.mean(), 150.0));
+        assert!(close(ewma.stddev(), 50.0));
+
+        // 일정한 값이 이어지면 평균은 수렴하고 분산은 줄어듦
+        for _ in 0..50 {
+            ewma.update(150.0);
+        }
+        assert!(close(ewma.mean(), 150.0));
+        assert!(ewma.stddev() < 1e-3);
+        assert_eq!(ewma.samples(), 52);
+    }
+
+    #[test]
+    fn test_threshold_floor() {
+        let mut ewma = Ewma::new(DEFAULT_ALPHA);
+        ewma.update(1_000.0);
+
+        // 분산이 0이면 평균의 10%를 표준편차로 봄
+        assert!(close(ewma.threshold(3.0, 0.0), 1_300.0));
+        // 절대 하한이 더 크면 그쪽
+        assert!(close(ewma.threshold(3.0, 500.0), 2_500.0));
+    }
+
+    #[test]
+    fn test_cold_start() {
+        let mut tracker = BaselineTracker::new(3.0);
+
+        // 예열 중에는 급증도 판정하지 않음
+        for pps in [100.0, 100.0, 100.0, 50_000.0] {
+            assert!(tracker.observe(pps, 1.0).is_empty());
+        }
+        for _ in 4..WARMUP_SAMPLES {
+            assert!(tracker.observe(100.0, 1.0).is_empty());
+        }
+        assert_eq!(tracker.snapshot().samples, WARMUP_SAMPLES);
+
+        // 예열 뒤에는 판정 (예열 중 급증으로 분산이 커서 적당한 증가는 통과)
+        assert!(tracker.observe(500.0, 1.0).is_empty());
+        assert_eq!(tracker.observe(1_000_000.0, 1.0), ["pps"]);
+    }
+
+    #[test]
+    fn test_spike_detection() {
+        let mut tracker = BaselineTracker::new(3.0);
+        for i in 0..30 {
+            // 1000±20 pps, 8±0.2 Mbps
+            let jitter = if i % 2 == 0 { 1.0 } else { -1.0 };
+            assert!(tracker.observe(1_000.0 + 20.0 * jitter, 8.0 + 0.2 * jitter).is_empty());
+        }
+
+        let before = tracker.snapshot();
+        assert!((before.pps_mean - 1_000.0).abs() < 5.0);
+        assert_eq!(before.k, 3.0);
+
+        // pps만 급증, 둘 다 급증
+        assert_eq!(tracker.observe(5_000.0, 8.0), ["pps"]);
+        assert_eq!(tracker.observe(10_000.0, 80.0), ["pps", "mbps"]);
+
+        // 판정한 구간도 반영하므로 기준선이 올라감
+        assert!(tracker.snapshot().pps_mean > before.pps_mean);
+    }
+
+    #[test]
+    fn test_idle_and_disabled() {
+        // 유휴 인터페이스에 패킷 몇 개는 이상이 아님
+        let mut tracker = BaselineTracker::new(3.0);
+        for _ in 0..WARMUP_SAMPLES {
+            tracker.observe(0.0, 0.0);
+        }
+        assert!(tracker.observe(25.0, 0.01).is_empty());
+        assert_eq!(tracker.observe(100.0, 0.5), ["pps", "mbps"]);
+
+        // k가 0이면 판정하지 않지만 기준선은 쌓음
+        let mut disabled = BaselineTracker::new(0.0);
+        for _ in 0..WARMUP_SAMPLES {
+            disabled.observe(10.0, 0.1);
+        }
+        assert!(disabled.observe(1e9, 1e6).is_empty());
+        assert_eq!(disabled.snapshot().samples, WARMUP_SAMPLES + 1);
+    }
+}
@@ -0,0 +1,801 @@
+//! 처리량 벤치마크 모듈
+//! 합성 규칙을 설치하고 XDP 모드마다 트래픽을 보내 처리 pps, 차단율, CPU 사용률 측정
+//!
+//! 진행 단계는 준비(veth 쌍 생성 → 규칙 설치) → 모드마다(연결 → 카운터 표본 → 전송 → 대기 →
+//! 카운터 표본 → 분리) → 정리다. 준비한 것은 어느 단계에서 실패해도, 요청이 중간에 취소되어도
+//! 역순으로 되돌린다. 그래도 남은 규칙(데몬 종료 등)은 측정 시간이 지나면 만료된다.
+//! 한 모드가 실패해도(드라이버가 driver 모드를 지원하지 않는 등) 다음 모드는 측정한다.
+
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Context, Result};
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::bpf::{self, XdpMode};
+use crate::maps::{FilterRule, MapManager};
+use crate::netif;
+use crate::packet::UdpFrame;
+use crate::ruleset;
+use swift_guard::api::{ApiResponse, BenchModeResult, BenchReport, FallbackMode, RuleSpec};
+use swift_guard::error::InvalidArgument;
+use swift_guard::types::{ActionType, ProtocolType};
+use swift_guard::utils;
+
+/// 인터페이스를 지정하지 않았을 때 만드는 veth 쌍 (XDP 연결, 트래픽 전송)
+pub const DEFAULT_PAIR: (&str, &str) = ("sgbench0", "sgbench1");
+
+/// 모드별 최대 전송 시간 (초)
+pub const MAX_DURATION: u32 = 300;
+
+/// 프레임 크기 범위 (바이트, FCS 제외 최소 이더넷 프레임부터 MTU 1500 프레임까지)
+pub const MIN_PKT_SIZE: u32 = 60;
+pub const MAX_PKT_SIZE: u32 = 1514;
+
+/// 최대 합성 규칙 수 (소스 대역 198.18.0.0/16 안에서 만듦)
+pub const MAX_RULES: u32 = 65_000;
+
+/// 한 번에 전송하는 시간 (진행 상황 프레임 간격)
+const TRANSMIT_SLICE: Duration = Duration::from_secs(1);
+
+/// 전송 후 카운터를 읽기 전까지 기다리는 시간
+pub const SETTLE_WAIT: Duration = Duration::from_millis(200);
+
+/// 합성 규칙 만료 여유 (초, 측정 시간에 더함)
+const EXPIRE_MARGIN: u32 = 60;
+
+/// 합성 규칙 소스 대역 (RFC 2544 벤치마크용 198.18.0.0/16)
+const BENCH_SRC_NET: u32 = 0xC612_0000;
+
+/// 트래픽 목적지 (198.19.0.2:9, discard 포트)
+const BENCH_DST_IP: u32 = 0xC613_0002;
+const BENCH_DST_PORT: u16 = 9;
+const BENCH_SRC_PORT: u16 = 49152;
+
+/// 벤치마크 페이로드 앞부분 (패킷 캡처에서 벤치마크 트래픽을 알아보기 위함)
+const BENCH_PAYLOAD: &[u8] = b"swift-guard bench";
+
+/// 이더넷, IPv4, UDP 헤더 길이
+const HEADER_LEN: usize = 42;
+
+/// 요청 값 검증 후 측정할 모드 반환 (비어 있으면 driver, generic 순)
+pub fn validate(duration: u32, pkt_size: u32, rules: u32, modes: &[String]) -> Result<Vec<XdpMode>, InvalidArgument> {
+    if duration == 0 || duration > MAX_DURATION {
+        return Err(InvalidArgument::new("duration", format!("Duration must be 1-{} seconds", MAX_DURATION)));
+    }
+    if !(MIN_PKT_SIZE..=MAX_PKT_SIZE).contains(&pkt_size) {
+        return Err(InvalidArgument::new("pkt_size", format!("Packet size must be {}-{} bytes", MIN_PKT_SIZE, MAX_PKT_SIZE)));
+    }
+    if rules == 0 || rules > MAX_RULES {
+        return Err(InvalidArgument::new("rules", format!("Rule count must be 1-{}", MAX_RULES)));
+    }
+    if modes.is_empty() {
+        return Ok(vec![XdpMode::Driver, XdpMode::Generic]);
+    }
+
+    let mut parsed = Vec::new();
+    for name in modes {
+        let mode = match XdpMode::from_name(name) {
+            Some(mode @ (XdpMode::Driver | XdpMode::Generic)) => mode,
+            _ => return Err(InvalidArgument::new("modes", format!("Invalid benchmark mode {} (expected driver or generic)", name))),
+        };
+        if parsed.contains(&mode) {
+            return Err(InvalidArgument::new("modes", format!("Mode {} is listed twice", name)));
+        }
+        parsed.push(mode);
+    }
+    Ok(parsed)
+}
+
+/// 측정 인터페이스와 전송 인터페이스, veth 쌍을 만들어야 하는지 (둘 다 주거나 둘 다 생략)
+pub fn endpoints(interface: Option<String>, peer: Option<String>) -> Result<(String, String, bool), InvalidArgument> {
+    match (interface, peer) {
+        (None, None) => Ok((DEFAULT_PAIR.0.to_string(), DEFAULT_PAIR.1.to_string(), true)),
+        (Some(interface), Some(peer)) if interface == peer => {
+            Err(InvalidArgument::new("peer", "Peer must differ from the measured interface"))
+        },
+        (Some(interface), Some(peer)) => Ok((interface, peer, false)),
+        (Some(_), None) => Err(InvalidArgument::new("peer", "Peer is required when an interface is given")),
+        (None, Some(_)) => Err(InvalidArgument::new("interface", "Interface is required when a peer is given")),
+    }
+}
+
+/// 합성 규칙 만료 시간 (초, 모든 모드의 측정 시간 + 여유)
+pub fn rule_expire(duration: u32, modes: usize) -> u32 {
+    duration.saturating_mul(modes as u32).saturating_add(EXPIRE_MARGIN)
+}
+
+/// `index`번째 합성 규칙의 소스 주소 (198.18.0.1부터)
+fn rule_source(index: u32) -> u32 {
+    BENCH_SRC_NET + 1 + index
+}
+
+/// 합성 drop 규칙 `count`개 (레이블 "bench-<번호>", 소스 /32마다 하나, 네임스페이스를 포함한 키)
+pub fn synthetic_rules(count: u32, namespace: &str, created_by: &str, now: u64, expire: u32) -> Result<Vec<FilterRule>, InvalidArgument> {
+    (0..count)
+        .map(|index| {
+            let spec = RuleSpec::new(&format!("bench-{}", index), ActionType::Drop)
+                .with_src_ip(&utils::ipv4_to_string(rule_source(index)))
+                .with_protocol(ProtocolType::Udp)
+                .with_dst_port(BENCH_DST_PORT)
+                .with_expire(expire)
+                .with_description("temporary benchmark rule");
+            let mut rule = ruleset::build_rule(spec, Some(created_by.to_string()), now, |_| Ok(0))?;
+            rule.label = utils::qualify_label(namespace, &rule.label);
+            Ok(rule)
+        })
+        .collect()
+}
+
+/// 측정 트래픽 프레임 (마지막 합성 규칙의 소스, `pkt_size` 바이트)
+pub fn frame(pkt_size: u32, rules: u32) -> Vec<u8> {
+    let mut payload = BENCH_PAYLOAD.to_vec();
+    payload.resize((pkt_size as usize).saturating_sub(HEADER_LEN).max(BENCH_PAYLOAD.len()), 0);
+    UdpFrame::new(rule_source(rules.saturating_sub(1)), BENCH_DST_IP, BENCH_SRC_PORT, BENCH_DST_PORT)
+        .with_payload(&payload)
+        .build()
+}
+
+/// 전체 CPU 누적 시간 (클록 틱, /proc/stat의 cpu 줄)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CpuTimes {
+    /// user부터 steal까지 합 (guest는 user에 포함)
+    pub total: u64,
+    /// idle + iowait
+    pub idle: u64,
+    pub softirq: u64,
+}
+
+/// /proc/stat 파싱 (첫 줄의 전체 CPU 합계)
+pub fn parse_cpu_times(content: &str) -> Result<CpuTimes> {
+    let line = content.lines().find(|line| line.starts_with("cpu "))
+        .ok_or_else(|| anyhow!("Malformed /proc/stat: no cpu line"))?;
+    let fields = line.split_whitespace()
+        .skip(1)
+        .take(8)
+        .map(|field| field.parse::<u64>())
+        .collect::<Result<Vec<_>, _>>()
+        .context("Malformed /proc/stat cpu line")?;
+    if fields.len() < 7 {
+        return Err(anyhow!("Malformed /proc/stat: {} cpu fields", fields.len()));
+    }
+
+    Ok(CpuTimes {
+        total: fields.iter().sum(),
+        idle: fields[3] + fields[4],
+        softirq: fields[6],
+    })
+}
+
+/// 한 시점의 카운터
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Sample {
+    /// XDP 프로그램이 처리한 패킷 수 (전역 통계)
+    pub processed: u64,
+    /// 트래픽에 맞는 합성 규칙의 패킷 수
+    pub dropped: u64,
+    pub cpu: CpuTimes,
+}
+
+/// 두 표본 사이의 모드 결과 (카운터가 줄었으면 0으로 봄)
+pub fn aggregate(mode: XdpMode, sent: u64, before: &Sample, after: &Sample, elapsed: Duration) -> BenchModeResult {
+    let total = after.cpu.total.saturating_sub(before.cpu.total);
+    let percent = |ticks: u64| if total == 0 { 0.0 } else { ticks as f64 * 100.0 / total as f64 };
+    let idle = after.cpu.idle.saturating_sub(before.cpu.idle);
+
+    BenchModeResult {
+        mode: mode.name().to_string(),
+        packets_sent: sent,
+        packets_processed: after.processed.saturating_sub(before.processed),
+        packets_dropped: after.dropped.saturating_sub(before.dropped),
+        elapsed_ms: elapsed.as_millis() as u64,
+        cpu_percent: percent(total.saturating_sub(idle)),
+        softirq_percent: percent(after.cpu.softirq.saturating_sub(before.cpu.softirq)),
+        error: None,
+    }
+}
+
+/// 벤치마크가 쓰는 호스트 조작
+pub trait Testbed {
+    /// veth 쌍 생성
+    fn create_pair(&mut self, interface: &str, peer: &str) -> Result<()>;
+    /// veth 쌍 삭제
+    fn delete_pair(&mut self, interface: &str) -> Result<()>;
+    /// 합성 규칙 설치
+    fn install(&mut self, rule: FilterRule) -> Result<()>;
+    /// 합성 규칙 삭제 (`label`은 네임스페이스를 포함한 규칙 키)
+    fn remove(&mut self, label: &str) -> Result<()>;
+    /// 인터페이스에 XDP 프로그램 연결
+    fn attach(&mut self, interface: &str, mode: XdpMode) -> Result<()>;
+    /// 인터페이스에서 XDP 프로그램 분리
+    fn detach(&mut self, interface: &str) -> Result<()>;
+    /// 현재 카운터 (`label`은 트래픽에 맞는 규칙 키)
+    fn sample(&mut self, label: &str) -> Result<Sample>;
+    /// 프레임을 인터페이스로 `duration` 동안 보내고 보낸 수 반환
+    fn transmit(&mut self, interface: &str, frame: &[u8], duration: Duration) -> Result<u64>;
+}
+
+/// 벤치마크 계획
+#[derive(Debug, Clone)]
+pub struct Plan {
+    pub interface: String,
+    pub peer: String,
+    /// veth 쌍을 만들고 끝나면 지움
+    pub create_pair: bool,
+    /// 합성 규칙 (`synthetic_rules`, 트래픽은 마지막 규칙에 맞음)
+    pub rules: Vec<FilterRule>,
+    pub modes: Vec<XdpMode>,
+    /// 모드별 전송 시간 (초)
+    pub duration_secs: u32,
+    pub pkt_size: u32,
+    pub settle: Duration,
+}
+
+/// 처리량 벤치마크
+///
+/// `run`이 준비, 모드별 측정, 정리를 차례로 실행한다. 준비한 것은 `run` 끝이나, 그 전에
+/// 값이 버려지면 `Drop`에서 되돌린다.
+pub struct Benchmark<'t, T: Testbed> {
+    testbed: &'t mut T,
+    plan: Plan,
+    /// 만든 veth 쌍이 있어 지워야 함
+    created_pair: bool,
+    /// 설치한 규칙 키 (설치 순서)
+    installed: Vec<String>,
+    /// XDP 프로그램이 연결되어 있어 분리해야 함
+    attached: bool,
+    results: Vec<BenchModeResult>,
+    /// 측정 전에 실패한 단계와 원인
+    failure: Option<(&'static str, String)>,
+    aborted: bool,
+    cleanup_errors: Vec<String>,
+}
+
+impl<'t, T: Testbed> Benchmark<'t, T> {
+    pub fn new(testbed: &'t mut T, plan: Plan) -> Self {
+        Self {
+            testbed,
+            plan,
+            created_pair: false,
+            installed: Vec::new(),
+            attached: false,
+            results: Vec::new(),
+            failure: None,
+            aborted: false,
+            cleanup_errors: Vec::new(),
+        }
+    }
+
+    /// veth 쌍 생성과 규칙 설치 (실패하면 원인을 기록하고 false)
+    fn setup(&mut self) -> bool {
+        if self.plan.create_pair {
+            if let Err(e) = self.testbed.create_pair(&self.plan.interface, &self.plan.peer) {
+                self.failure = Some(("create_pair", format!("{:#}", e)));
+                return false;
+            }
+            self.created_pair = true;
+        }
+
+        for rule in &self.plan.rules {
+            let label = rule.label.clone();
+            if let Err(e) = self.testbed.install(rule.clone()) {
+                self.failure = Some(("install", format!("rule {}: {:#}", utils::split_label(&label).1, e)));
+                return false;
+            }
+            self.installed.push(label);
+        }
+        true
+    }
+
+    /// 모드 하나 측정 (연결부터 분리까지)
+    async fn measure(&mut self, mode: XdpMode, progress: Option<&UnboundedSender<ApiResponse>>, done: &mut usize) -> BenchModeResult {
+        if let Err(e) = self.testbed.attach(&self.plan.interface, mode) {
+            return failed_mode(mode, e.context("attach"));
+        }
+        self.attached = true;
+
+        let mut result = match self.transmit(mode, progress, done).await {
+            Ok(result) => result,
+            Err(e) => failed_mode(mode, e),
+        };
+
+        match self.testbed.detach(&self.plan.interface) {
+            Ok(()) => self.attached = false,
+            // 정리할 때 다시 시도
+            Err(e) => {
+                result.error.get_or_insert_with(|| format!("detach: {:#}", e));
+            },
+        }
+        result
+    }
+
+    /// 연결된 상태에서 트래픽을 보내고 전후 카운터로 결과 계산
+    async fn transmit(&mut self, mode: XdpMode, progress: Option<&UnboundedSender<ApiResponse>>, done: &mut usize) -> Result<BenchModeResult> {
+        let label = self.plan.rules.last().map(|rule| rule.label.clone()).unwrap_or_default();
+        let frame = frame(self.plan.pkt_size, self.plan.rules.len() as u32);
+        let total = self.plan.duration_secs as usize * self.plan.modes.len();
+
+        let before = self.testbed.sample(&label).context("read counters")?;
+        let started = Instant::now();
+        let mut sent = 0;
+        let mut error = None;
+        for _ in 0..self.plan.duration_secs {
+            // 요청자가 떠났으면 남은 측정은 하지 않음
+            if progress.is_some_and(|sender| sender.is_closed()) {
+                self.aborted = true;
+                break;
+            }
+            match self.testbed.transmit(&self.plan.peer, &frame, TRANSMIT_SLICE) {
+                Ok(count) => sent += count,
+                Err(e) => {
+                    error = Some(format!("transmit: {:#}", e));
+                    break;
+                },
+            }
+            *done += 1;
+            if let Some(sender) = progress {
+                let _ = sender.send(ApiResponse::Progress { done: *done, total });
+            }
+        }
+        let elapsed = started.elapsed();
+
+        tokio::time::sleep(self.plan.settle).await;
+        let after = self.testbed.sample(&label).context("read counters")?;
+
+        let mut result = aggregate(mode, sent, &before, &after, elapsed);
+        result.error = error;
+        Ok(result)
+    }
+
+    /// 준비한 것을 역순으로 되돌림 (한 번만)
+    fn cleanup(&mut self) {
+        if self.attached {
+            self.attached = false;
+            if let Err(e) = self.testbed.detach(&self.plan.interface) {
+                self.cleanup_errors.push(format!("detach {}: {:#}", self.plan.interface, e));
+            }
+        }
+
+        let installed = std::mem::take(&mut self.installed);
+        let mut failed = 0;
+        let mut first_error = None;
+        for label in installed.iter().rev() {
+            if let Err(e) = self.testbed.remove(label) {
+                failed += 1;
+                first_error.get_or_insert_with(|| format!("{:#}", e));
+            }
+        }
+        if let Some(error) = first_error {
+            self.cleanup_errors.push(format!("remove {} of {} rules: {}", failed, installed.len(), error));
+        }
+
+        if self.created_pair {
+            self.created_pair = false;
+            if let Err(e) = self.testbed.delete_pair(&self.plan.interface) {
+                self.cleanup_errors.push(format!("delete {}: {:#}", self.plan.interface, e));
+            }
+        }
+    }
+
+    /// 끝까지 진행하고 결과 반환 (`progress`가 있으면 전송한 초마다 진행 상황 프레임)
+    pub async fn run(mut self, progress: Option<&UnboundedSender<ApiResponse>>) -> BenchReport {
+        if self.setup() {
+            let mut done = 0;
+            for mode in self.plan.modes.clone() {
+                if self.aborted {
+                    break;
+                }
+                let result = self.measure(mode, progress, &mut done).await;
+                self.results.push(result);
+            }
+        }
+        self.cleanup();
+        self.report()
+    }
+
+    /// 지금까지의 결과
+    pub fn report(&self) -> BenchReport {
+        BenchReport {
+            interface: self.plan.interface.clone(),
+            peer: self.plan.peer.clone(),
+            created_pair: self.plan.create_pair,
+            rules: self.plan.rules.len() as u32,
+            pkt_size: self.plan.pkt_size,
+            duration_secs: self.plan.duration_secs,
+            modes: self.results.clone(),
+            failed_stage: self.failure.as_ref().map(|(stage, _)| stage.to_string()),
+            error: self.failure.as_ref().map(|(_, error)| error.clone()),
+            aborted: self.aborted,
+            cleanup_errors: self.cleanup_errors.clone(),
+        }
+    }
+}
+
+impl<T: Testbed> Drop for Benchmark<'_, T> {
+    /// 요청이 취소되어 중간에 버려져도 준비한 것을 되돌림
+    fn drop(&mut self) {
+        self.cleanup();
+    }
+}
+
+fn failed_mode(mode: XdpMode, error: anyhow::Error) -> BenchModeResult {
+    BenchModeResult {
+        mode: mode.name().to_string(),
+        error: Some(format!("{:#}", error)),
+        ..Default::default()
+    }
+}
+
+/// 맵 관리자, ip 명령, raw 소켓을 쓰는 실제 호스트
+pub struct LiveTestbed<'m, 'a> {
+    map_manager: &'m Mutex<MapManager<'a>>,
+    obj_path: PathBuf,
+}
+
+impl<'m, 'a> LiveTestbed<'m, 'a> {
+    pub fn new(map_manager: &'m Mutex<MapManager<'a>>, obj_path: PathBuf) -> Self {
+        Self { map_manager, obj_path }
+    }
+
+    fn lock(&self) -> Result<std::sync::MutexGuard<'m, MapManager<'a>>> {
+        self.map_manager.lock().map_err(|_| anyhow!("Failed to lock map_manager"))
+    }
+}
+
+impl Testbed for LiveTestbed<'_, '_> {
+    fn create_pair(&mut self, interface: &str, peer: &str) -> Result<()> {
+        netif::create_veth_pair(interface, peer)
+    }
+
+    fn delete_pair(&mut self, interface: &str) -> Result<()> {
+        netif::delete_link(interface)
+    }
+
+    fn install(&mut self, rule: FilterRule) -> Result<()> {
+        Ok(self.lock()?.add_rule(rule)?)
+    }
+
+    fn remove(&mut self, label: &str) -> Result<()> {
+        Ok(self.lock()?.delete_rule(label)?)
+    }
+
+    fn attach(&mut self, interface: &str, mode: XdpMode) -> Result<()> {
+        let obj_path = self.obj_path.clone();
+        self.lock()?.attach_interface(interface, mode.name(), None, FallbackMode::None, |interface| {
+            bpf::attach_xdp_program(&obj_path, interface, mode)
+        })?;
+        Ok(())
+    }
+
+    fn detach(&mut self, interface: &str) -> Result<()> {
+        self.lock()?.detach_interface(interface, bpf::unload_xdp_program)?;
+        Ok(())
+    }
+
+    fn sample(&mut self, label: &str) -> Result<Sample> {
+        let (processed, rules) = {
+            let map_manager = self.lock()?;
+            (map_manager.get_stats()?.0, map_manager.list_rules(true)?)
+        };
+        let dropped = rules.iter()
+            .find(|rule| utils::qualify_label(&rule.namespace, &rule.label) == label)
+            .map(|rule| rule.stats.packets)
+            .ok_or_else(|| anyhow!("Rule '{}' disappeared during the benchmark", label))?;
+        let cpu = parse_cpu_times(&std::fs::read_to_string("/proc/stat").context("Failed to read /proc/stat")?)?;
+        Ok(Sample { processed, dropped, cpu })
+    }
+
+    fn transmit(&mut self, interface: &str, frame: &[u8], duration: Duration) -> Result<u64> {
+        // 전송은 초 단위로 스레드를 붙잡으므로 런타임의 다른 작업이 밀리지 않게 함
+        tokio::task::block_in_place(|| netif::send_frames_for(interface, frame, duration))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::sync::mpsc;
+
+    /// 호출을 기록하고 카운터를 흉내 내는 호스트
+    #[derive(Default)]
+    struct FakeTestbed {
+        calls: Vec<String>,
+        /// 이 순번의 규칙 설치가 실패
+        fail_install_at: Option<usize>,
+        fail_attach: Option<XdpMode>,
+        fail_remove: bool,
+        fail_delete_pair: bool,
+        /// 프레임마다 이 수만큼 보낸 것으로 셈
+        per_slice: u64,
+        /// 보낸 패킷 중 XDP가 처리하지 못한 수 (슬라이스마다)
+        lost: u64,
+        processed: u64,
+        installs: usize,
+        ticks: u64,
+    }
+
+    impl Testbed for FakeTestbed {
+        fn create_pair(&mut self, interface: &str, peer: &str) -> Result<()> {
+            self.calls.push(format!("create {} {}", interface, peer));
+            Ok(())
+        }
+
+        fn delete_pair(&mut self, interface: &str) -> Result<()> {
+            self.calls.push(format!("delete {}", interface));
+            if self.fail_delete_pair {
+                return Err(anyhow!("link busy"));
+            }
+            Ok(())
+        }
+
+        fn install(&mut self, rule: FilterRule) -> Result<()> {
+            if self.fail_install_at == Some(self.installs) {
+                return Err(anyhow!("map full"));
+            }
+            self.installs += 1;
+            self.calls.push(format!("install {}", rule.label));
+            Ok(())
+        }
+
+        fn remove(&mut self, label: &str) -> Result<()> {
+            self.calls.push(format!("remove {}", label));
+            if self.fail_remove {
+                return Err(anyhow!("map delete failed"));
+            }
+            Ok(())
+        }
+
+        fn attach(&mut self, interface: &str, mode: XdpMode) -> Result<()> {
+            self.calls.push(format!("attach {} {}", interface, mode.name()));
+            if self.fail_attach == Some(mode) {
+                return Err(anyhow!("driver mode not supported"));
+            }
+            Ok(())
+        }
+
+        fn detach(&mut self, interface: &str) -> Result<()> {
+            self.calls.push(format!("detach {}", interface));
+            Ok(())
+        }
+
+        fn sample(&mut self, _label: &str) -> Result<Sample> {
+            // 표본마다 CPU 100틱 중 40틱 사용, 그중 30틱 softirq
+            self.ticks += 1;
+            let cpu = CpuTimes { total: self.ticks * 100, idle: self.ticks * 60, softirq: self.ticks * 30 };
+            Ok(Sample { processed: self.processed, dropped: self.processed, cpu })
+        }
+
+        fn transmit(&mut self, interface: &str, frame: &[u8], _duration: Duration) -> Result<u64> {
+            self.calls.push(format!("transmit {} {}", interface, frame.len()));
+            self.processed += self.per_slice - self.lost;
+            Ok(self.per_slice)
+        }
+    }
+
+    fn plan(rules: u32, modes: Vec<XdpMode>) -> Plan {
+        Plan {
+            interface: DEFAULT_PAIR.0.to_string(),
+            peer: DEFAULT_PAIR.1.to_string(),
+            create_pair: true,
+            rules: synthetic_rules(rules, "default", "test", 1000, 120).unwrap(),
+            modes,
+            duration_secs: 2,
+            pkt_size: 64,
+            settle: Duration::ZERO,
+        }
+    }
+
+    #[test]
+    fn test_validate() {
+        let modes = |names: &[&str]| names.iter().map(|name| name.to_string()).collect::<Vec<_>>();
+
+        assert_eq!(validate(10, 64, 1000, &[]).unwrap(), [XdpMode::Driver, XdpMode::Generic]);
+        assert_eq!(validate(1, 1514, 1, &modes(&["generic"])).unwrap(), [XdpMode::Generic]);
+
+        assert_eq!(validate(0, 64, 1000, &[]).unwrap_err().field, "duration");
+        assert_eq!(validate(MAX_DURATION + 1, 64, 1000, &[]).unwrap_err().field, "duration");
+        assert_eq!(validate(10, 59, 1000, &[]).unwrap_err().field, "pkt_size");
+        assert_eq!(validate(10, 64, 0, &[]).unwrap_err().field, "rules");
+        assert_eq!(validate(10, 64, MAX_RULES + 1, &[]).unwrap_err().field, "rules");
+        // offload는 veth에서 쓸 수 없고, 같은 모드를 두 번 잴 이유가 없음
+        assert_eq!(validate(10, 64, 1000, &modes(&["offload"])).unwrap_err().field, "modes");
+        assert_eq!(validate(10, 64, 1000, &modes(&["driver", "driver"])).unwrap_err().field, "modes");
+    }
+
+    #[test]
+    fn test_endpoints() {
+        assert_eq!(endpoints(None, None).unwrap(), ("sgbench0".to_string(), "sgbench1".to_string(), true));
+        assert_eq!(endpoints(Some("veth0".into()), Some("veth1".into())).unwrap(), ("veth0".to_string(), "veth1".to_string(), false));
+        assert_eq!(endpoints(Some("veth0".into()), None).unwrap_err().field, "peer");
+        assert_eq!(endpoints(None, Some("veth1".into())).unwrap_err().field, "interface");
+        assert_eq!(endpoints(Some("veth0".into()), Some("veth0".into())).unwrap_err().field, "peer");
+    }
+
+    #[test]
+    fn test_synthetic_rules_and_frame() {
+        let rules = synthetic_rules(300, "lab", "test", 1000, rule_expire(10, 2)).unwrap();
+        assert_eq!(rules.len(), 300);
+        assert_eq!(rules[0].label, utils::qualify_label("lab", "bench-0"));
+        assert_eq!(rules[299].label, utils::qualify_label("lab", "bench-299"));
+        assert_eq!(rules[0].src_ip, Some((0xC612_0001, 32)));
+        // 198.18.0.1부터 차례로, 256개를 넘으면 다음 옥텟
+        assert_eq!(rules[299].src_ip, Some((0xC612_012C, 32)));
+        assert!(rules.iter().all(|rule| rule.action == ActionType::Drop as u8 && rule.protocol == 17));
+        assert_eq!(rules[0].expire, 80);
+
+        // 트래픽은 마지막 규칙에 맞고, 크기는 요청한 프레임 크기
+        let frame = frame(64, 300);
+        assert_eq!(frame.len(), 64);
+        assert_eq!(&frame[26..30], &[198, 18, 1, 44]);
+        assert_eq!(&frame[36..38], &BENCH_DST_PORT.to_be_bytes());
+        assert!(frame[HEADER_LEN..].starts_with(BENCH_PAYLOAD));
+        assert_eq!(super::frame(1514, 1).len(), 1514);
+    }
+
+    #[test]
+    fn test_parse_cpu_times() {
+        let stat = "cpu  4705 356 584 3699 23 23 17 5 0 0\ncpu0 1393 180 290 3685 21 23 17 0 0 0\nintr 0\n";
+        let cpu = parse_cpu_times(stat).unwrap();
+        assert_eq!(cpu, CpuTimes { total: 4705 + 356 + 584 + 3699 + 23 + 23 + 17 + 5, idle: 3722, softirq: 17 });
+
+        // 오래된 커널은 필드가 7개
+        assert_eq!(parse_cpu_times("cpu 1 2 3 4 5 6 7\n").unwrap().total, 28);
+        assert!(parse_cpu_times("cpu 1 2 3\n").is_err());
+        assert!(parse_cpu_times("cpu0 1 2 3 4 5 6 7\n").is_err());
+        assert!(parse_cpu_times("cpu 1 x 3 4 5 6 7\n").is_err());
+    }
+
+    #[test]
+    fn test_aggregate() {
+        let before = Sample { processed: 1_000, dropped: 500, cpu: CpuTimes { total: 10_000, idle: 8_000, softirq: 100 } };
+        let after = Sample { processed: 2_001_000, dropped: 1_900_500, cpu: CpuTimes { total: 12_000, idle: 9_000, softirq: 600 } };
+
+        let result = aggregate(XdpMode::Generic, 2_500_000, &before, &after, Duration::from_secs(2));
+        assert_eq!(result.mode, "generic");
+        assert_eq!((result.packets_sent, result.packets_processed, result.packets_dropped), (2_500_000, 2_000_000, 1_900_000));
+        assert_eq!(result.pps(), 1_000_000.0);
+        assert_eq!(result.tx_pps(), 1_250_000.0);
+        assert_eq!(result.drop_rate(), 95.0);
+        assert_eq!((result.cpu_percent, result.softirq_percent), (50.0, 25.0));
+
+        // 카운터가 줄면(맵 재생성 등) 0, CPU 시간이 그대로면 0%
+        let result = aggregate(XdpMode::Driver, 0, &after, &before, Duration::ZERO);
+        assert_eq!((result.packets_processed, result.packets_dropped), (0, 0));
+        assert_eq!((result.cpu_percent, result.pps()), (0.0, 0.0));
+    }
+
+    #[tokio::test]
+    async fn test_run_each_mode() {
+        let mut testbed = FakeTestbed { per_slice: 1_000, lost: 10, ..Default::default() };
+        let (progress, mut frames) = mpsc::unbounded_channel();
+
+        let report = Benchmark::new(&mut testbed, plan(2, vec![XdpMode::Driver, XdpMode::Generic]))
+            .run(Some(&progress))
+            .await;
+
+        assert_eq!((report.failed_stage.as_deref(), report.aborted), (None, false));
+        assert!(report.cleanup_errors.is_empty());
+        assert_eq!((report.rules, report.pkt_size, report.duration_secs), (2, 64, 2));
+        assert_eq!(report.modes.iter().map(|mode| mode.mode.as_str()).collect::<Vec<_>>(), ["driver", "generic"]);
+        for mode in &report.modes {
+            assert_eq!((mode.packets_sent, mode.packets_processed, mode.packets_dropped), (2_000, 1_980, 1_980));
+            assert_eq!((mode.cpu_percent, mode.softirq_percent), (40.0, 30.0));
+            assert_eq!(mode.error, None);
+        }
+
+        let key = |label: &str| utils::qualify_label("default", label);
+        assert_eq!(testbed.calls, [
+            "create sgbench0 sgbench1".to_string(),
+            format!("install {}", key("bench-0")),
+            format!("install {}", key("bench-1")),
+            "attach sgbench0 driver".to_string(),
+            "transmit sgbench1 64".to_string(),
+            "transmit sgbench1 64".to_string(),
+            "detach sgbench0".to_string(),
+            "attach sgbench0 generic".to_string(),
+            "transmit sgbench1 64".to_string(),
+            "transmit sgbench1 64".to_string(),
+            "detach sgbench0".to_string(),
+            format!("remove {}", key("bench-1")),
+            format!("remove {}", key("bench-0")),
+            "delete sgbench0".to_string(),
+        ]);
+
+        // 전송한 초마다 진행 상황 (전체는 모드 수 × 측정 시간)
+        let mut done = Vec::new();
+        while let Ok(ApiResponse::Progress { done: d, total }) = frames.try_recv() {
+            assert_eq!(total, 4);
+            done.push(d);
+        }
+        assert_eq!(done, [1, 2, 3, 4]);
+    }
+
+    #[tokio::test]
+    async fn test_mode_failure_continues() {
+        let mut testbed = FakeTestbed { per_slice: 100, fail_attach: Some(XdpMode::Driver), ..Default::default() };
+        let report = Benchmark::new(&mut testbed, plan(1, vec![XdpMode::Driver, XdpMode::Generic]))
+            .run(None)
+            .await;
+
+        // driver 연결 실패는 그 모드의 결과에만 남고 generic은 측정
+        assert_eq!(report.failed_stage, None);
+        assert_eq!(report.modes[0].error.as_deref(), Some("attach: driver mode not supported"));
+        assert_eq!(report.modes[0].packets_sent, 0);
+        assert_eq!((report.modes[1].error.as_deref(), report.modes[1].packets_sent), (None, 200));
+        // 연결하지 못한 모드는 분리하지 않음
+        assert_eq!(testbed.calls.iter().filter(|call| call.starts_with("detach")).count(), 1);
+        assert_eq!(testbed.calls.last().unwrap(), "delete sgbench0");
+    }
+
+    #[tokio::test]
+    async fn test_install_failure_cleans_up() {
+        let mut testbed = FakeTestbed { fail_install_at: Some(2), ..Default::default() };
+        let report = Benchmark::new(&mut testbed, plan(5, vec![XdpMode::Generic])).run(None).await;
+
+        assert_eq!(report.failed_stage.as_deref(), Some("install"));
+        assert_eq!(report.error.as_deref(), Some("rule bench-2: map full"));
+        assert!(report.modes.is_empty());
+
+        // 설치한 두 규칙만 역순으로 지우고 veth 쌍 삭제, 연결은 하지 않음
+        let key = |label: &str| utils::qualify_label("default", label);
+        assert_eq!(testbed.calls[3..], [
+            format!("remove {}", key("bench-1")),
+            format!("remove {}", key("bench-0")),
+            "delete sgbench0".to_string(),
+        ]);
+        assert!(!testbed.calls.iter().any(|call| call.starts_with("attach")));
+    }
+
+    #[tokio::test]
+    async fn test_cleanup_errors_reported() {
+        let mut testbed = FakeTestbed { fail_remove: true, fail_delete_pair: true, ..Default::default() };
+        let mut plan = plan(3, vec![XdpMode::Generic]);
+        plan.duration_secs = 1;
+        let report = Benchmark::new(&mut testbed, plan).run(None).await;
+
+        // 규칙 삭제 실패는 한 줄로 요약하고, 실패해도 나머지 정리는 계속
+        assert_eq!(report.cleanup_errors, [
+            "remove 3 of 3 rules: map delete failed".to_string(),
+            "delete sgbench0: link busy".to_string(),
+        ]);
+        assert_eq!(report.modes.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_abort_when_requester_leaves() {
+        let mut testbed = FakeTestbed { per_slice: 100, ..Default::default() };
+        let (progress, frames) = mpsc::unbounded_channel();
+        drop(frames);
+
+        let report = Benchmark::new(&mut testbed, plan(1, vec![XdpMode::Driver, XdpMode::Generic]))
+            .run(Some(&progress))
+            .await;
+
+        // 첫 모드에서 멈추고 남은 모드는 측정하지 않지만 정리는 함
+        assert!(report.aborted);
+        assert_eq!(report.modes.len(), 1);
+        assert_eq!(report.modes[0].packets_sent, 0);
+        assert!(!testbed.calls.iter().any(|call| call.starts_with("transmit")));
+        assert_eq!(testbed.calls.last().unwrap(), "delete sgbench0");
+    }
+
+    #[test]
+    fn test_drop_cleans_up() {
+        let mut testbed = FakeTestbed::default();
+        {
+            // 준비 후 요청이 취소되어 값이 버려짐
+            let mut benchmark = Benchmark::new(&mut testbed, plan(1, vec![XdpMode::Generic]));
+            assert!(benchmark.setup());
+            benchmark.testbed.attach(DEFAULT_PAIR.0, XdpMode::Generic).unwrap();
+            benchmark.attached = true;
+        }
+
+        let key = utils::qualify_label("default", "bench-0");
+        assert_eq!(testbed.calls[3..], [
+            "detach sgbench0".to_string(),
+            format!("remove {}", key),
+            "delete sgbench0".to_string(),
+        ]);
+    }
+}
@@ -1,12 +1,118 @@
 // src/daemon/src/bpf.rs
 use anyhow::{anyhow, Context, Result};
-use libbpf_rs::{Map, Object, ObjectBuilder, Program};
-use log::{debug, error, info};
-use std::path::Path;
+use libbpf_rs::{Object, ObjectBuilder, PrintLevel, Program};
+use log::{debug, error, info, warn};
+use std::collections::HashMap;
+use std::ffi::CString;
+use std::fs;
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+use swift_guard::api::{BpfLoadFailure, BpfMapInfo, BpfProgramInfo};
+use swift_guard::error::InvalidArgument;
+use swift_guard::layout::{self, StructLayout};
+use swift_guard::types::XdpFeatures;
+
+use crate::backend::KernelMap;
+use crate::error::DaemonError;
+
+/// libbpf가 이름 기준으로 고정(pin)하는 기본 bpffs 경로
+const BPF_PIN_ROOT: &str = "/sys/fs/bpf";
+
+/// 규칙 관리에 필요한 맵 (대체 오브젝트도 모두 정의해야 함)
+const REQUIRED_MAPS: &[&str] = &["filter_rules", "redirect_map", "cpu_map", "stats_map"];
+
+/// bpf(2) 명령 번호 (linux/bpf.h)
+const BPF_OBJ_GET: libc::c_long = 7;
+
+/// 로드 실패에 붙이는 libbpf 로그 최대 줄 수 (verifier 로그는 수천 줄일 수 있어 끝부분만 보관)
+const MAX_LOAD_LOG_LINES: usize = 200;
+
+/// libbpf의 info/debug 메시지도 데몬 로그에 남길지 (--bpf-verbose)
+static LIBBPF_VERBOSE: AtomicBool = AtomicBool::new(false);
+
+/// 진행 중인 오브젝트 로드가 수집하는 libbpf 로그 (로드 중이 아니면 None)
+///
+/// libbpf 출력 콜백은 함수 포인터라 상태를 가질 수 없으므로 전역으로 둔다.
+static LOAD_LOG: Mutex<Option<Vec<String>>> = Mutex::new(None);
+
+/// 수집 구간이 겹치지 않도록 오브젝트 로드를 직렬화
+static LOAD_LOCK: Mutex<()> = Mutex::new(());
+
+/// 마지막 오브젝트 로드 실패 (Ping 응답, `xdp-filter doctor`)
+static LAST_LOAD_FAILURE: Mutex<Option<BpfLoadFailure>> = Mutex::new(None);
+
+/// libbpf 출력 콜백 설치 (`verbose`면 info/debug 메시지도 데몬 로그에 남김)
+///
+/// 경고는 항상 남긴다. 로드 중에는 수준과 관계없이 모든 메시지를 수집해 실패 오류에 붙인다.
+pub fn init_libbpf_logging(verbose: bool) {
+    set_libbpf_verbose(verbose);
+    libbpf_rs::set_print(Some((PrintLevel::Debug, libbpf_print)));
+}
+
+/// 실행 중 libbpf 메시지 기록 수준 변경
+pub fn set_libbpf_verbose(verbose: bool) {
+    LIBBPF_VERBOSE.store(verbose, Ordering::Relaxed);
+}
+
+/// libbpf 출력 콜백
+fn libbpf_print(level: PrintLevel, message: String) {
+    let message = message.trim_end();
+    if let Some(lines) = LOAD_LOG.lock().unwrap_or_else(PoisonError::into_inner).as_mut() {
+        lines.extend(message.lines().map(str::to_string));
+        let excess = lines.len().saturating_sub(MAX_LOAD_LOG_LINES);
+        lines.drain(..excess);
+    }
+
+    match (level, LIBBPF_VERBOSE.load(Ordering::Relaxed)) {
+        (PrintLevel::Warn, _) => warn!("libbpf: {}", message),
+        (_, true) => info!("libbpf: {}", message),
+        (PrintLevel::Info, false) => debug!("libbpf: {}", message),
+        (PrintLevel::Debug, false) => {},
+    }
+}
+
+/// 마지막 오브젝트 로드 실패
+pub fn last_load_failure() -> Option<BpfLoadFailure> {
+    LAST_LOAD_FAILURE.lock().unwrap_or_else(PoisonError::into_inner).clone()
+}
+
+/// libbpf 로그를 수집하며 오브젝트 로드 실행
+///
+/// 실패하면 수집한 로그를 붙인 `DaemonError::BpfLoad`로 바꾸고 마지막 실패로 보관한다.
+fn capture_load<T>(object: &Path, load: impl FnOnce() -> Result<T, DaemonError>) -> Result<T, DaemonError> {
+    let _serialized = LOAD_LOCK.lock().unwrap_or_else(PoisonError::into_inner);
+    *LOAD_LOG.lock().unwrap_or_else(PoisonError::into_inner) = Some(Vec::new());
+    let result = load();
+    let log = LOAD_LOG.lock().unwrap_or_else(PoisonError::into_inner).take().unwrap_or_default();
+
+    result.map_err(|e| {
+        let failure = BpfLoadFailure {
+            object: object.display().to_string(),
+            error: e.to_string(),
+            log,
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|elapsed| elapsed.as_secs())
+                .unwrap_or_default(),
+        };
+        error!("BPF 오브젝트 로드 실패: {} ({}), libbpf 로그 {}줄", failure.object, failure.error, failure.log.len());
+        *LAST_LOAD_FAILURE.lock().unwrap_or_else(PoisonError::into_inner) = Some(failure.clone());
+        DaemonError::BpfLoad(failure)
+    })
+}
 
 pub struct XdpFilterSkel {
-    pub obj: Object,
+    /// 로드된 오브젝트 (맵 갱신에 가변 참조가 필요하므로 잠금 뒤에 둠)
+    obj: Arc<Mutex<Object>>,
+    /// 오브젝트의 맵
+    maps: Vec<KernelMap>,
+    /// 로드 전에 BTF로 검증한 맵 값 레이아웃
+    layouts: Vec<StructLayout>,
+    /// 오브젝트의 `features` 섹션에서 읽은 매치 기능
+    features: XdpFeatures,
 }
 
 impl XdpFilterSkel {
@@ -16,16 +122,66 @@ impl XdpFilterSkel {
         }
     }
 
-    pub fn maps(&self) -> XdpFilterMaps {
+    pub fn maps(&self) -> XdpFilterMaps<'_> {
         XdpFilterMaps {
-            obj: &self.obj,
+            maps: &self.maps,
         }
     }
 
-    pub fn progs(&self) -> XdpFilterProgs {
-        XdpFilterProgs {
-            obj: &self.obj,
+    /// 규칙 관리에 필요한 맵이 모두 있는지 확인 (없으면 찾은 맵을 오류에 나열)
+    pub fn check_maps(&self) -> Result<(), DaemonError> {
+        let found: Vec<String> = self.maps().iter().map(|map| map.name().to_string()).collect();
+        let missing = missing_maps(&found);
+        if missing.is_empty() {
+            return Ok(());
         }
+
+        Err(DaemonError::Bpf(anyhow!("BPF object is missing required maps: {} (found: {})",
+            missing.join(", "),
+            if found.is_empty() { "none".to_string() } else { found.join(", ") })))
+    }
+
+    /// 로드된 프로그램과 맵의 커널 정보 (bpftool prog/map show에 해당)
+    pub fn info(&self) -> (Vec<BpfProgramInfo>, Vec<BpfMapInfo>) {
+        let obj = self.obj.lock().unwrap_or_else(PoisonError::into_inner);
+        let programs = XdpFilterProgs { obj: &obj }.iter().map(|prog| {
+            let fdinfo = read_fdinfo(prog.fd());
+            let id = fdinfo_value(&fdinfo, "prog_id");
+            BpfProgramInfo {
+                name: prog.name().to_string(),
+                prog_type: type_name(&prog.prog_type()),
+                id: id as u32,
+                memlock: fdinfo_value(&fdinfo, "memlock"),
+                pin_path: pin_path(prog.name(), "prog_id", id),
+            }
+        }).collect();
+
+        let maps = self.maps().iter().map(|map| {
+            let fdinfo = read_fdinfo(map.fd());
+            let id = fdinfo_value(&fdinfo, "map_id");
+            BpfMapInfo {
+                name: map.name().to_string(),
+                map_type: type_name(&map.map_type()),
+                id: id as u32,
+                max_entries: fdinfo_value(&fdinfo, "max_entries") as u32,
+                key_size: map.key_size(),
+                value_size: map.value_size(),
+                memlock: fdinfo_value(&fdinfo, "memlock"),
+                pin_path: pin_path(map.name(), "map_id", id),
+            }
+        }).collect();
+
+        (programs, maps)
+    }
+
+    /// BTF로 검증한 맵 값 레이아웃 (bpf-info 출력)
+    pub fn layouts(&self) -> &[StructLayout] {
+        &self.layouts
+    }
+
+    /// 프로그램이 처리하는 매치 기능 (규칙 검증, bpf-info 출력)
+    pub fn features(&self) -> XdpFeatures {
+        self.features
     }
 }
 
@@ -39,32 +195,86 @@ impl XdpFilterSkelBuilder {
         self
     }
 
-    pub fn open(self) -> Result<XdpFilterSkel> {
-        let mut builder = ObjectBuilder::default();
-        let path = self.obj_path.ok_or_else(|| anyhow!("No Object file path provided"))?;
-        let object = builder.open_file(path)?;
+    /// 오브젝트를 열어 커널에 로드 (실패하면 libbpf 로그를 담은 `DaemonError::BpfLoad`)
+    pub fn open(self) -> Result<XdpFilterSkel, DaemonError> {
+        let path = self.obj_path
+            .ok_or_else(|| DaemonError::Config("No Object file path provided".to_string()))?;
+        let path = Path::new(&path);
+
+        capture_load(path, || {
+            // 커널에 올리기 전에 맵 값 레이아웃과 매치 기능 확인
+            let layouts = check_layout(path)?;
+            let features = read_features(path)?;
+            let object = ObjectBuilder::default().open_file(path)
+                .context("Failed to open BPF object")
+                .map_err(DaemonError::Bpf)?;
+
+            #[allow(clippy::arc_with_non_send_sync)]
+            let obj = Arc::new(Mutex::new(object.load()
+                .context("Failed to load BPF object into the kernel")
+                .map_err(DaemonError::Bpf)?));
 
-        Ok(XdpFilterSkel {
-            obj: object.load().expect("Failed to load object"),
+            Ok(XdpFilterSkel {
+                maps: KernelMap::all(&obj),
+                obj,
+                layouts,
+                features,
+            })
         })
     }
 }
 
 pub struct XdpFilterMaps<'a> {
-    obj: &'a Object,
+    maps: &'a [KernelMap],
 }
 
 impl<'a> XdpFilterMaps<'a> {
-    pub fn filter_rules(&self) -> Option<&Map> {
-        self.obj.map("filter_rules")
+    /// 오브젝트의 모든 맵
+    pub fn iter(&self) -> impl Iterator<Item = &'a KernelMap> {
+        self.maps.iter()
+    }
+
+    /// 이름으로 맵 찾기
+    fn map(&self, name: &str) -> Option<&'a KernelMap> {
+        self.maps.iter().find(|map| map.name() == name)
+    }
+    
+    pub fn filter_rules(&self) -> Option<&'a KernelMap> {
+        self.map("filter_rules")
+    }
+    
+    /// 소스 IP 없는 규칙 배열 (이전 오브젝트에는 없음)
+    pub fn wildcard_rules(&self) -> Option<&'a KernelMap> {
+        self.map("wildcard_rules")
+    }
+    
+    pub fn redirect_map(&self) -> Option<&'a KernelMap> {
+        self.map("redirect_map")
+    }
+    
+    pub fn cpu_map(&self) -> Option<&'a KernelMap> {
+        self.map("cpu_map")
     }
     
-    pub fn redirect_map(&self) -> Option<&Map> {
-        self.obj.map("redirect_map")
+    pub fn stats_map(&self) -> Option<&'a KernelMap> {
+        self.map("stats_map")
     }
     
-    pub fn stats_map(&self) -> Option<&Map> {
-        self.obj.map("stats_map")
+    pub fn size_histogram(&self) -> Option<&'a KernelMap> {
+        self.map("size_hist_map")
+    }
+    
+    pub fn syn_sources(&self) -> Option<&'a KernelMap> {
+        self.map("syn_src_map")
+    }
+    
+    pub fn conntrack(&self) -> Option<&'a KernelMap> {
+        self.map("conntrack_map")
+    }
+    
+    /// 규칙 적중 이벤트 perf 버퍼 (events::EventDispatcher::run로 수신)
+    pub fn rule_events(&self) -> Option<&'a KernelMap> {
+        self.map("rule_events")
     }
 }
 
@@ -73,24 +283,183 @@ pub struct XdpFilterProgs<'a> {
 }
 
 impl<'a> XdpFilterProgs<'a> {
-    pub fn xdp_filter_func(&self) -> Option<&Program> {
+    /// 오브젝트의 모든 프로그램
+    pub fn iter(&self) -> impl Iterator<Item = &'a Program> {
+        self.obj.progs_iter()
+    }
+    
+    #[allow(dead_code)]
+    pub fn xdp_filter_func(&self) -> Option<&'a Program> {
         self.obj.prog("xdp_filter_func")
     }
 }
 
+/// 대체 BPF 오브젝트 로드
+///
+/// 인터페이스에 연결된 오브젝트의 맵은 데몬이 끝날 때까지 규칙 관리에 사용되므로
+/// 해제하지 않고 'static 참조로 반환한다.
+pub fn load_object(path: &Path) -> Result<&'static XdpFilterSkel, DaemonError> {
+    // 로드 실패는 오브젝트 경로와 libbpf 로그를 담고 있으므로 그대로 반환
+    let skel = XdpFilterSkel::builder()
+        .obj_path(path)
+        .open()?;
+    skel.check_maps()
+        .map_err(|e| DaemonError::Bpf(anyhow::Error::new(e)
+            .context(format!("Invalid BPF object {}", path.display()))))?;
+
+    info!("대체 BPF 오브젝트 로드됨: {}", path.display());
+    Ok(Box::leak(Box::new(skel)))
+}
+
+/// attach 요청의 오브젝트 경로 확인 (허용 디렉토리 아래의 파일만 허용)
+///
+/// 심볼릭 링크나 `..`로 디렉토리를 벗어나지 못하도록 정규화한 경로를 비교한다.
+pub fn resolve_object_path(requested: &str, object_dir: Option<&Path>) -> Result<PathBuf, DaemonError> {
+    let object_dir = object_dir.ok_or_else(|| {
+        DaemonError::Config("alternate BPF objects are disabled (set general.object_dir)".to_string())
+    })?;
+    let object_dir = object_dir.canonicalize()
+        .map_err(|e| DaemonError::io(format!("object directory {} is not accessible", object_dir.display()), e))?;
+
+    // 상대 경로는 허용 디렉토리 기준
+    let path = object_dir.join(requested).canonicalize()
+        .map_err(|e| InvalidArgument::new("obj_path", format!("{} does not exist: {}", requested, e)))?;
+
+    if !path.starts_with(&object_dir) {
+        return Err(InvalidArgument::new("obj_path",
+            format!("{} is outside the object directory {}", requested, object_dir.display())).into());
+    }
+    if !path.is_file() {
+        return Err(InvalidArgument::new("obj_path", format!("{} is not a file", requested)).into());
+    }
+
+    Ok(path)
+}
+
+/// 오브젝트의 BTF로 맵 값 레이아웃 검증
+///
+/// libbpf-rs 0.19는 오브젝트의 BTF를 노출하지 않으므로 ELF의 .BTF 섹션을 직접 읽는다.
+/// 데몬이 기록하고 읽는 위치와 다르면 규칙 필드와 통계를 엉뚱한 곳에서 읽으므로 거부한다.
+pub fn check_layout(path: &Path) -> Result<Vec<StructLayout>, DaemonError> {
+    let elf = fs::read(path)
+        .map_err(|e| DaemonError::io(format!("failed to read BPF object {}", path.display()), e))?;
+    let btf = elf_section(&elf, ".BTF").ok_or_else(|| DaemonError::Bpf(anyhow!(
+        "BPF object {} has no .BTF section to verify the map value layout (build it with clang -g)",
+        path.display())))?;
+
+    layout::verify_btf(btf).map_err(|e| DaemonError::Bpf(anyhow!(
+        "BPF object {} does not match the map value layout this daemon was built for: {} \
+        (rebuild the object from this release's src/bpf/xdp_filter.c)", path.display(), e)))
+}
+
+/// 오브젝트가 처리하는 매치 기능 읽기
+///
+/// 기능 비트맵은 `features` 섹션의 u64 하나다. 섹션이 없으면 비트맵 도입 전에 빌드된
+/// 오브젝트로 보고 `XdpFeatures::LEGACY`를 쓴다.
+pub fn read_features(path: &Path) -> Result<XdpFeatures, DaemonError> {
+    let elf = fs::read(path)
+        .map_err(|e| DaemonError::io(format!("failed to read BPF object {}", path.display()), e))?;
+    parse_features(&elf).map_err(|e| DaemonError::Bpf(e.context(format!("BPF object {}", path.display()))))
+}
+
+/// ELF 오브젝트의 `features` 섹션 해석
+fn parse_features(elf: &[u8]) -> Result<XdpFeatures> {
+    let Some(section) = elf_section(elf, "features") else {
+        return Ok(XdpFeatures::LEGACY);
+    };
+    let bitmap: [u8; 8] = section.try_into()
+        .map_err(|_| anyhow!("features section is {} bytes, expected 8", section.len()))?;
+    Ok(XdpFeatures(u64::from_le_bytes(bitmap)))
+}
+
+/// ELF64 리틀 엔디안 오브젝트에서 이름으로 섹션 내용 찾기
+fn elf_section<'a>(elf: &'a [u8], name: &str) -> Option<&'a [u8]> {
+    // e_ident: 매직, ELFCLASS64, ELFDATA2LSB
+    if elf.get(..6)? != b"\x7fELF\x02\x01" {
+        return None;
+    }
+    let u16_at = |offset: usize| elf.get(offset..offset + 2).map(|b| u16::from_le_bytes([b[0], b[1]]) as usize);
+    let u32_at = |offset: usize| elf.get(offset..offset + 4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]) as usize);
+    let u64_at = |offset: usize| elf.get(offset..offset + 8)
+        .map(|b| u64::from_le_bytes([b[0], b[1], b[2], b[3], b[4], b[5], b[6], b[7]]) as usize);
+
+    let (shoff, shentsize, shnum, shstrndx) = (u64_at(0x28)?, u16_at(0x3a)?, u16_at(0x3c)?, u16_at(0x3e)?);
+    // 섹션 헤더: sh_name(0x00), sh_offset(0x18), sh_size(0x20)
+    let section = |index: usize| {
+        let header = index.checked_mul(shentsize)?.checked_add(shoff)?;
+        let (offset, size) = (u64_at(header + 0x18)?, u64_at(header + 0x20)?);
+        Some((u32_at(header)?, elf.get(offset..offset.checked_add(size)?)?))
+    };
+
+    let (_, names) = section(shstrndx)?;
+    (0..shnum)
+        .filter_map(section)
+        .find(|(name_off, _)| names.get(*name_off..)
+            .and_then(|rest| rest.split(|b| *b == 0).next()) == Some(name.as_bytes()))
+        .map(|(_, data)| data)
+}
+
+/// 필요한 맵 중 오브젝트에 없는 맵
+fn missing_maps(found: &[String]) -> Vec<&'static str> {
+    REQUIRED_MAPS.iter()
+        .copied()
+        .filter(|name| !found.iter().any(|found| found == name))
+        .collect()
+}
+
 /// XDP 모드 열거형
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum XdpMode {
     Driver = 0,  // 드라이버/네이티브 모드
     Generic = 1, // SKB 기반 제네릭 모드
     Offload = 2, // 하드웨어 오프로드 모드
 }
 
+impl XdpMode {
+    /// API 요청의 모드 번호 변환
+    pub fn from_num(num: u32) -> Option<Self> {
+        match num {
+            0 => Some(Self::Driver),
+            1 => Some(Self::Generic),
+            2 => Some(Self::Offload),
+            _ => None,
+        }
+    }
+
+    /// 모드 이름 변환 (`name`의 역)
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "driver" => Some(Self::Driver),
+            "generic" => Some(Self::Generic),
+            "offload" => Some(Self::Offload),
+            _ => None,
+        }
+    }
+
+    /// 모드 이름
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Driver => "driver",
+            Self::Generic => "generic",
+            Self::Offload => "offload",
+        }
+    }
+
+    /// `ip link set` 모드 키워드
+    fn ip_keyword(&self) -> &'static str {
+        match self {
+            Self::Driver => "xdpdrv",
+            Self::Generic => "xdpgeneric",
+            Self::Offload => "xdpoffload",
+        }
+    }
+}
+
 /// XDP 프로그램 로드
-pub fn load_xdp_program(obj_path: &Path, interface: &str) -> Result<()> {
+pub fn load_xdp_program(obj_path: &Path, interface: &str) -> Result<(), DaemonError> {
     // BPF 오브젝트 파일 존재 확인
     if !obj_path.exists() {
-        return Err(anyhow!("BPF 오브젝트 파일이 존재하지 않습니다: {}", obj_path.display()));
+        return Err(DaemonError::Bpf(anyhow!("BPF 오브젝트 파일이 존재하지 않습니다: {}", obj_path.display())));
     }
 
     // 인터페이스 존재 확인
@@ -98,32 +467,74 @@ pub fn load_xdp_program(obj_path: &Path, interface: &str) -> Result<()> {
 
     // ip 명령으로 XDP 프로그램 로드
     let status = Command::new("ip")
-        .args(&["link", "set", "dev", interface, "xdp", "obj", 
+        .args(["link", "set", "dev", interface, "xdp", "obj", 
                obj_path.to_str().unwrap(), "sec", "xdp"])
         .status()
-        .context(format!("인터페이스 {}에 XDP 프로그램 로드 실패", interface))?;
+        .map_err(|e| DaemonError::io(format!("인터페이스 {}에 XDP 프로그램 로드 실패", interface), e))?;
 
     if !status.success() {
-        return Err(anyhow!("인터페이스 {}에 XDP 프로그램 로드 실패", interface));
+        return Err(DaemonError::Bpf(anyhow!("인터페이스 {}에 XDP 프로그램 로드 실패", interface)));
     }
 
     info!("인터페이스 {}에 XDP 프로그램이 로드되었습니다", interface);
     Ok(())
 }
 
+/// 지정한 모드로 XDP 프로그램 연결
+pub fn attach_xdp_program(obj_path: &Path, interface: &str, mode: XdpMode) -> Result<(), DaemonError> {
+    // BPF 오브젝트 파일 존재 확인
+    if !obj_path.exists() {
+        return Err(DaemonError::Bpf(anyhow!("BPF 오브젝트 파일이 존재하지 않습니다: {}", obj_path.display())));
+    }
+
+    // 인터페이스 존재 확인
+    check_interface_exists(interface)?;
+
+    // 드라이버가 모드를 지원하지 않으면 ip 명령이 실패함
+    let output = Command::new("ip")
+        .args(["link", "set", "dev", interface, mode.ip_keyword(), "obj"])
+        .arg(obj_path)
+        .args(["sec", "xdp"])
+        .output()
+        .map_err(|e| DaemonError::io(format!("인터페이스 {}에 XDP 프로그램 연결 실패", interface), e))?;
+
+    if !output.status.success() {
+        return Err(DaemonError::Bpf(anyhow!("인터페이스 {}에 {} 모드 XDP 연결 실패: {}",
+            interface, mode.name(), String::from_utf8_lossy(&output.stderr).trim())));
+    }
+
+    info!("인터페이스 {}에 {} 모드로 XDP 프로그램이 연결되었습니다", interface, mode.name());
+    Ok(())
+}
+
+/// 지정 모드 연결 시험 (연결 직후 해제)
+pub fn dry_attach_xdp_program(obj_path: &Path, interface: &str, mode: XdpMode) -> Result<(), DaemonError> {
+    attach_xdp_program(obj_path, interface, mode)?;
+
+    let status = Command::new("ip")
+        .args(["link", "set", "dev", interface, mode.ip_keyword(), "off"])
+        .status()
+        .map_err(|e| DaemonError::io(format!("인터페이스 {}에서 시험 연결 해제 실패", interface), e))?;
+
+    if !status.success() {
+        return Err(DaemonError::Bpf(anyhow!("인터페이스 {}에서 시험 연결 해제 실패", interface)));
+    }
+    Ok(())
+}
+
 /// XDP 프로그램 언로드
-pub fn unload_xdp_program(interface: &str) -> Result<()> {
+pub fn unload_xdp_program(interface: &str) -> Result<(), DaemonError> {
     // 인터페이스 존재 확인
     check_interface_exists(interface)?;
 
     // ip 명령으로 XDP 프로그램 언로드
     let status = Command::new("ip")
-        .args(&["link", "set", "dev", interface, "xdp", "off"])
+        .args(["link", "set", "dev", interface, "xdp", "off"])
         .status()
-        .context(format!("인터페이스 {}에서 XDP 프로그램 언로드 실패", interface))?;
+        .map_err(|e| DaemonError::io(format!("인터페이스 {}에서 XDP 프로그램 언로드 실패", interface), e))?;
 
     if !status.success() {
-        return Err(anyhow!("인터페이스 {}에서 XDP 프로그램 언로드 실패", interface));
+        return Err(DaemonError::Bpf(anyhow!("인터페이스 {}에서 XDP 프로그램 언로드 실패", interface)));
     }
 
     info!("인터페이스 {}에서 XDP 프로그램이 언로드되었습니다", interface);
@@ -131,15 +542,281 @@ pub fn unload_xdp_program(interface: &str) -> Result<()> {
 }
 
 /// 인터페이스 존재 확인
-fn check_interface_exists(interface: &str) -> Result<()> {
+fn check_interface_exists(interface: &str) -> Result<(), DaemonError> {
     let output = Command::new("ip")
-        .args(&["link", "show", "dev", interface])
+        .args(["link", "show", "dev", interface])
         .output()
-        .context(format!("인터페이스 {} 확인 실패", interface))?;
+        .map_err(|e| DaemonError::io(format!("인터페이스 {} 확인 실패", interface), e))?;
 
     if !output.status.success() {
-        return Err(anyhow!("인터페이스 {}가 존재하지 않습니다", interface));
+        return Err(InvalidArgument::new("interface", format!("인터페이스 {}가 존재하지 않습니다", interface)).into());
     }
 
     Ok(())
-}
\ No newline at end of file
+}
+/// /proc/self/fdinfo/<fd> 읽기 (id, max_entries, memlock 등 libbpf-rs가 노출하지 않는 값)
+fn read_fdinfo(fd: i32) -> HashMap<String, String> {
+    fs::read_to_string(format!("/proc/self/fdinfo/{}", fd))
+        .map(|text| parse_fdinfo(&text))
+        .unwrap_or_default()
+}
+
+/// fdinfo의 `key:\tvalue` 줄 파싱
+fn parse_fdinfo(text: &str) -> HashMap<String, String> {
+    text.lines()
+        .filter_map(|line| line.split_once(':'))
+        .map(|(key, value)| (key.trim().to_string(), value.trim().to_string()))
+        .collect()
+}
+
+/// fdinfo 숫자 값 (없으면 0)
+fn fdinfo_value(fdinfo: &HashMap<String, String>, key: &str) -> u64 {
+    fdinfo.get(key).and_then(|value| value.parse().ok()).unwrap_or(0)
+}
+
+/// libbpf 타입 열거형 이름 (LpmTrie → lpm_trie)
+fn type_name<T: std::fmt::Debug>(ty: &T) -> String {
+    let mut name = String::new();
+    for (i, c) in format!("{:?}", ty).chars().enumerate() {
+        if c.is_ascii_uppercase() {
+            if i > 0 {
+                name.push('_');
+            }
+            name.push(c.to_ascii_lowercase());
+        } else {
+            name.push(c);
+        }
+    }
+    name
+}
+
+/// 기본 bpffs 경로에 같은 객체가 고정되어 있으면 그 경로
+///
+/// 같은 이름의 다른 객체일 수 있으므로 고정된 객체를 열어 id를 비교한다.
+fn pin_path(name: &str, id_key: &str, id: u64) -> Option<String> {
+    let path = PathBuf::from(BPF_PIN_ROOT).join(name);
+    if id == 0 || !path.exists() {
+        return None;
+    }
+
+    let fd = obj_get(&path)?;
+    let pinned_id = fdinfo_value(&read_fdinfo(fd), id_key);
+    // SAFETY: obj_get이 연 fd이며 다른 곳에서 사용하지 않는다
+    unsafe { libc::close(fd) };
+
+    (pinned_id == id).then(|| path.display().to_string())
+}
+
+/// union bpf_attr (BPF_OBJ_GET)
+#[repr(C)]
+struct ObjGetAttr {
+    pathname: u64,
+    bpf_fd: u32,
+    file_flags: u32,
+}
+
+/// 고정된 BPF 객체 열기
+fn obj_get(path: &Path) -> Option<i32> {
+    let pathname = CString::new(path.as_os_str().as_bytes()).ok()?;
+    let attr = ObjGetAttr { pathname: pathname.as_ptr() as u64, bpf_fd: 0, file_flags: 0 };
+
+    // SAFETY: attr와 pathname은 호출 동안 유효하며 크기를 함께 전달한다
+    let fd = unsafe {
+        libc::syscall(libc::SYS_bpf, BPF_OBJ_GET, &attr as *const ObjGetAttr,
+            std::mem::size_of::<ObjGetAttr>() as libc::c_uint)
+    };
+    (fd >= 0).then_some(fd as i32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use libbpf_rs::MapType;
+    use swift_guard::api::ApiResponse;
+
+    #[test]
+    fn test_parse_fdinfo() {
+        let fdinfo = parse_fdinfo("pos:\t0\nflags:\t02000002\nmnt_id:\t15\nmap_type:\t11\n\
+            key_size:\t8\nvalue_size:\t32\nmax_entries:\t1024\nmap_flags:\t0x1\n\
+            memlock:\t73728\nmap_id:\t42\nfrozen:\t0\n");
+
+        assert_eq!(fdinfo_value(&fdinfo, "max_entries"), 1024);
+        assert_eq!(fdinfo_value(&fdinfo, "memlock"), 73728);
+        assert_eq!(fdinfo_value(&fdinfo, "map_id"), 42);
+        assert_eq!(fdinfo_value(&fdinfo, "prog_id"), 0);
+    }
+
+    #[test]
+    fn test_map_type_name() {
+        // KernelMap::map_type가 돌려주는 libbpf 타입이 bpf-info 이름으로 변환되는지
+        assert_eq!(type_name(&MapType::LpmTrie), "lpm_trie");
+        assert_eq!(type_name(&MapType::PercpuArray), "percpu_array");
+    }
+
+    #[test]
+    fn test_missing_maps() {
+        let found: Vec<String> = ["filter_rules", "redirect_map", "cpu_map", "stats_map", "conntrack_map"]
+            .iter().map(|name| name.to_string()).collect();
+        assert!(missing_maps(&found).is_empty());
+
+        let found = vec!["filter_rules".to_string(), "stats_map".to_string()];
+        assert_eq!(missing_maps(&found), vec!["redirect_map", "cpu_map"]);
+    }
+
+    #[test]
+    fn test_resolve_object_path() {
+        let root = std::env::temp_dir().join(format!("swift-guard-objects-{}", std::process::id()));
+        let dir = root.join("objects");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("vendor.o"), b"").unwrap();
+        fs::write(root.join("outside.o"), b"").unwrap();
+
+        let resolved = resolve_object_path("vendor.o", Some(&dir)).unwrap();
+        assert_eq!(resolved, dir.canonicalize().unwrap().join("vendor.o"));
+        let absolute = dir.join("vendor.o");
+        assert!(resolve_object_path(absolute.to_str().unwrap(), Some(&dir)).is_ok());
+
+        let err = resolve_object_path("../outside.o", Some(&dir)).unwrap_err();
+        assert!(matches!(&err, DaemonError::Validation(e) if e.field == "obj_path"));
+        assert!(err.to_string().contains("outside the object directory"));
+        assert!(matches!(resolve_object_path("missing.o", Some(&dir)), Err(DaemonError::Validation(_))));
+        let err = resolve_object_path("vendor.o", None).unwrap_err();
+        assert!(matches!(&err, DaemonError::Config(message) if message.contains("disabled")));
+        assert!(matches!(resolve_object_path("vendor.o", Some(&root.join("gone"))), Err(DaemonError::Io { .. })));
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_corrupt_object_load_error() {
+        let path = std::env::temp_dir().join(format!("swift-guard-corrupt-{}.o", std::process::id()));
+        fs::write(&path, b"\x7fELF not really an object").unwrap();
+
+        // 패닉하지 않고 오브젝트와 원인을 담은 구조화된 오류를 반환
+        let err = XdpFilterSkel::builder().obj_path(&path).open().err().unwrap();
+        let DaemonError::BpfLoad(failure) = &err else { panic!("unexpected error: {}", err) };
+        assert_eq!(failure.object, path.display().to_string());
+        assert!(failure.error.contains("no .BTF section"), "{}", failure.error);
+        assert!(failure.timestamp > 0);
+        assert!(last_load_failure().is_some());
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_capture_load_log() {
+        // 로드 밖의 메시지는 수집하지 않음
+        libbpf_print(PrintLevel::Warn, "libbpf: unrelated warning\n".to_string());
+
+        let err = capture_load(Path::new("/tmp/xdp_filter.o"), || -> Result<(), DaemonError> {
+            libbpf_print(PrintLevel::Debug, "libbpf: loading object 'xdp_filter'\n".to_string());
+            libbpf_print(PrintLevel::Warn, "0: (61) r2 = *(u32 *)(r1 +4)\ninvalid bpf_context access off=4 size=4\n".to_string());
+            Err(DaemonError::Bpf(anyhow!("Permission denied (os error 13)")))
+        }).unwrap_err();
+        let DaemonError::BpfLoad(failure) = &err else { panic!("unexpected error: {}", err) };
+        assert_eq!(failure.log, [
+            "libbpf: loading object 'xdp_filter'",
+            "0: (61) r2 = *(u32 *)(r1 +4)",
+            "invalid bpf_context access off=4 size=4",
+        ]);
+        assert!(err.to_string().contains("Permission denied (os error 13)\nlibbpf log:\n  libbpf: loading object"));
+        assert!(matches!(ApiResponse::from(err), ApiResponse::Error { ref message, .. } if message.contains("invalid bpf_context access")));
+
+        // 긴 verifier 로그는 끝부분만 보관
+        let err = capture_load(Path::new("/tmp/xdp_filter.o"), || -> Result<(), DaemonError> {
+            for i in 0..MAX_LOAD_LOG_LINES + 50 {
+                libbpf_print(PrintLevel::Info, format!("{}: (b7) r0 = 2", i));
+            }
+            Err(DaemonError::Bpf(anyhow!("Argument list too long")))
+        }).unwrap_err();
+        let DaemonError::BpfLoad(failure) = err else { panic!("unexpected error") };
+        assert_eq!(failure.log.len(), MAX_LOAD_LOG_LINES);
+        assert_eq!(failure.log.last().map(String::as_str), Some("249: (b7) r0 = 2"));
+
+        // 성공하면 결과 그대로
+        assert_eq!(capture_load(Path::new("/tmp/xdp_filter.o"), || Ok(7)).unwrap(), 7);
+    }
+
+    /// 섹션 하나를 가진 최소 ELF64 오브젝트 (0 = null, 1 = .shstrtab, 2 = `name`)
+    fn elf_with_section(name: &str, data: &[u8]) -> Vec<u8> {
+        let names = format!("\0.shstrtab\0{}\0", name).into_bytes();
+        let mut elf = vec![0u8; 64];
+        elf[..6].copy_from_slice(b"\x7fELF\x02\x01");
+        let names_offset = elf.len();
+        elf.extend_from_slice(&names);
+        let data_offset = elf.len();
+        elf.extend_from_slice(data);
+
+        let shoff = elf.len() as u64;
+        elf[0x28..0x30].copy_from_slice(&shoff.to_le_bytes());
+        elf[0x3a..0x3c].copy_from_slice(&64u16.to_le_bytes());
+        elf[0x3c..0x3e].copy_from_slice(&3u16.to_le_bytes());
+        elf[0x3e..0x40].copy_from_slice(&1u16.to_le_bytes());
+        for (name_off, offset, size) in [(0u32, 0, 0), (1, names_offset, names.len()), (11, data_offset, data.len())] {
+            let mut header = [0u8; 64];
+            header[..4].copy_from_slice(&name_off.to_le_bytes());
+            header[0x18..0x20].copy_from_slice(&(offset as u64).to_le_bytes());
+            header[0x20..0x28].copy_from_slice(&(size as u64).to_le_bytes());
+            elf.extend_from_slice(&header);
+        }
+        elf
+    }
+
+    #[test]
+    fn test_check_layout() {
+        let elf = elf_with_section(".BTF", b"btf");
+        assert_eq!(elf_section(&elf, ".BTF"), Some(&b"btf"[..]));
+        assert_eq!(elf_section(&elf, ".maps"), None);
+        assert_eq!(elf_section(b"not an object", ".BTF"), None);
+
+        let dir = std::env::temp_dir().join(format!("swift-guard-btf-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("xdp_filter.o");
+
+        // 타입이 없는 BTF: 헤더(매직, 버전 1, 헤더 24바이트, 빈 타입 영역, 문자열 1바이트)
+        let mut btf = vec![0x9f, 0xeb, 1, 0];
+        for word in [24u32, 0, 0, 0, 1] {
+            btf.extend_from_slice(&word.to_le_bytes());
+        }
+        btf.push(0);
+        fs::write(&path, elf_with_section(".BTF", &btf)).unwrap();
+        let err = check_layout(&path).unwrap_err().to_string();
+        assert!(err.contains("struct filter_rule is not defined in the BTF"), "{}", err);
+        assert!(err.contains("rebuild the object"), "{}", err);
+
+        fs::write(&path, elf_with_section(".text", &btf)).unwrap();
+        let err = check_layout(&path).unwrap_err().to_string();
+        assert!(err.contains("has no .BTF section"), "{}", err);
+        assert!(matches!(check_layout(&dir.join("missing.o")), Err(DaemonError::Io { .. })));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_read_features() {
+        let bitmap = XdpFeatures::TCP_FLAGS | XdpFeatures::PORT_LIST;
+        let elf = elf_with_section("features", &bitmap.to_le_bytes());
+        assert_eq!(parse_features(&elf).unwrap(), XdpFeatures(bitmap));
+
+        // 섹션이 없는 이전 오브젝트
+        assert_eq!(parse_features(&elf_with_section(".BTF", b"btf")).unwrap(), XdpFeatures::LEGACY);
+        let err = parse_features(&elf_with_section("features", &[1, 0, 0, 0])).unwrap_err();
+        assert_eq!(err.to_string(), "features section is 4 bytes, expected 8");
+
+        let path = std::env::temp_dir().join(format!("swift-guard-features-{}.o", std::process::id()));
+        fs::write(&path, &elf).unwrap();
+        assert_eq!(read_features(&path).unwrap(), XdpFeatures(bitmap));
+        fs::remove_file(&path).unwrap();
+        assert!(matches!(read_features(&path), Err(DaemonError::Io { .. })));
+    }
+
+    #[test]
+    fn test_type_name() {
+        #[derive(Debug)]
+        enum Kind { LpmTrie, Xdp, PerfEventArray }
+
+        assert_eq!(type_name(&Kind::LpmTrie), "lpm_trie");
+        assert_eq!(type_name(&Kind::Xdp), "xdp");
+        assert_eq!(type_name(&Kind::PerfEventArray), "perf_event_array");
+    }
+}
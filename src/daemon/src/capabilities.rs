@@ -0,0 +1,419 @@
+//! 커널 기능 점검 모듈
+//! 데몬 시작 시 XDP/BPF 관련 커널 기능을 점검하고 해결 방법을 제시
+//!
+//! 각 점검은 독립적으로 실패할 수 있으며, 실패해도 데몬 시작을 막지 않는다.
+//! 결과는 시작 로그, `ApiRequest::Ping` 응답, `xdp-filter doctor`로 확인한다.
+
+use anyhow::{anyhow, Context, Result};
+use log::{error, info, warn};
+use nix::errno::Errno;
+use nix::sys::resource::{getrlimit, setrlimit, Resource, RLIM_INFINITY};
+use std::path::Path;
+use std::process::Command;
+
+use swift_guard::api::{CapabilityCheck, CheckStatus};
+
+/// 지원하는 최소 커널 버전
+const MIN_KERNEL: (u32, u32) = (5, 10);
+
+/// 권장 커널 버전
+const RECOMMENDED_KERNEL: (u32, u32) = (5, 15);
+
+/// 커널 BTF 위치
+const BTF_PATH: &str = "/sys/kernel/btf/vmlinux";
+
+/// 필요한 최소 memlock 한도 (5.11 이전 커널은 BPF 맵 메모리를 memlock으로 계산)
+const MIN_MEMLOCK_BYTES: u64 = 64 << 20;
+
+/// XDP 연결 점검용 veth 쌍 이름
+const PROBE_VETH: &str = "sgprobe0";
+const PROBE_VETH_PEER: &str = "sgprobe1";
+
+/// bpf(2) 명령 및 맵 유형 번호 (include/uapi/linux/bpf.h)
+const BPF_MAP_CREATE: libc::c_long = 0;
+const BPF_MAP_TYPE_LPM_TRIE: u32 = 11;
+const BPF_MAP_TYPE_DEVMAP: u32 = 14;
+const BPF_MAP_TYPE_CPUMAP: u32 = 16;
+const BPF_F_NO_PREALLOC: u32 = 1;
+
+fn check(name: &str, status: CheckStatus, detail: impl Into<String>, remediation: Option<&str>) -> CapabilityCheck {
+    CapabilityCheck {
+        name: name.to_string(),
+        status,
+        detail: detail.into(),
+        remediation: remediation.map(str::to_string),
+    }
+}
+
+fn passed(name: &str, detail: impl Into<String>) -> CapabilityCheck {
+    check(name, CheckStatus::Ok, detail, None)
+}
+
+fn warning(name: &str, detail: impl Into<String>, remediation: &str) -> CapabilityCheck {
+    check(name, CheckStatus::Warn, detail, Some(remediation))
+}
+
+fn failed(name: &str, detail: impl Into<String>, remediation: &str) -> CapabilityCheck {
+    check(name, CheckStatus::Fail, detail, Some(remediation))
+}
+
+fn skipped(name: &str, detail: impl Into<String>, remediation: &str) -> CapabilityCheck {
+    check(name, CheckStatus::Skipped, detail, Some(remediation))
+}
+
+/// 점검 실행 (오류는 해당 항목의 실패로 기록)
+fn run_probe(name: &str, remediation: &str, probe: impl FnOnce() -> Result<CapabilityCheck>) -> CapabilityCheck {
+    probe().unwrap_or_else(|e| failed(name, format!("{:#}", e), remediation))
+}
+
+/// 모든 점검 수행
+///
+/// `bpf_obj`가 없으면 XDP 연결 점검은 건너뛴다.
+pub fn probe_all(bpf_obj: Option<&Path>) -> Vec<CapabilityCheck> {
+    let mut checks = vec![
+        run_probe("kernel_version", "Check `uname -r`", || {
+            let uts = nix::sys::utsname::uname().context("uname failed")?;
+            Ok(check_kernel_version(&uts.release().to_string_lossy()))
+        }),
+        check_btf(Path::new(BTF_PATH)),
+        // 맵 생성 점검 전에 한도를 올림
+        run_probe("memlock_rlimit", "Run the daemon with CAP_SYS_RESOURCE or set LimitMEMLOCK=infinity", || {
+            let (soft, hard) = getrlimit(Resource::RLIMIT_MEMLOCK).context("getrlimit failed")?;
+            Ok(check_memlock(soft, hard, |soft, hard| {
+                setrlimit(Resource::RLIMIT_MEMLOCK, soft, hard).map_err(|e| anyhow!("setrlimit failed: {}", e))
+            }))
+        }),
+        probe_map("lpm_trie_map", BPF_MAP_TYPE_LPM_TRIE, 8, 4, 1, BPF_F_NO_PREALLOC),
+        probe_map("devmap", BPF_MAP_TYPE_DEVMAP, 4, 4, 1, 0),
+        probe_map("cpumap", BPF_MAP_TYPE_CPUMAP, 4, 4, 1, 0),
+    ];
+
+    match bpf_obj {
+        Some(obj) if obj.exists() => checks.extend(probe_xdp_attach(&IpCommand, obj)),
+        _ => {
+            let detail = match bpf_obj {
+                Some(obj) => format!("BPF object not found: {}", obj.display()),
+                None => "No BPF object configured".to_string(),
+            };
+            for name in ["xdp_generic_attach", "xdp_driver_attach"] {
+                checks.push(skipped(name, detail.clone(),
+                    "Build src/bpf (make) and pass --bpf-obj"));
+            }
+        },
+    }
+
+    checks
+}
+
+/// 점검 결과를 시작 로그에 기록
+pub fn log_checks(checks: &[CapabilityCheck]) {
+    for check in checks {
+        let hint = check.remediation.as_deref().map(|h| format!(" (hint: {})", h)).unwrap_or_default();
+        match check.status {
+            CheckStatus::Ok => info!("Capability {}: {}", check.name, check.detail),
+            CheckStatus::Warn | CheckStatus::Skipped => {
+                warn!("Capability {} {}: {}{}", check.name, check.status.as_str(), check.detail, hint)
+            },
+            CheckStatus::Fail => error!("Capability {} failed: {}{}", check.name, check.detail, hint),
+        }
+    }
+}
+
+/// 커널 릴리스 문자열에서 버전 추출 ("5.15.0-91-generic" → (5, 15, 0))
+pub fn parse_kernel_version(release: &str) -> Option<(u32, u32, u32)> {
+    let numeric = release.split(|c: char| !c.is_ascii_digit() && c != '.').next()?;
+    let mut parts = numeric.split('.').map(|part| part.parse::<u32>().ok());
+
+    let major = parts.next()??;
+    let minor = parts.next()??;
+    let patch = parts.next().flatten().unwrap_or(0);
+
+    Some((major, minor, patch))
+}
+
+/// 커널 버전 점검
+pub fn check_kernel_version(release: &str) -> CapabilityCheck {
+    const NAME: &str = "kernel_version";
+
+    let Some((major, minor, _)) = parse_kernel_version(release) else {
+        return warning(NAME, format!("Unrecognized kernel release: {}", release),
+            "Make sure the kernel is 5.10 or newer");
+    };
+
+    if (major, minor) < MIN_KERNEL {
+        failed(NAME, format!("{} is older than {}.{}", release, MIN_KERNEL.0, MIN_KERNEL.1),
+            "Upgrade to kernel 5.10 or newer (5.15+ recommended); use --fallback nftables meanwhile")
+    } else if (major, minor) < RECOMMENDED_KERNEL {
+        warning(NAME, release.to_string(),
+            "Kernel 5.15+ is recommended for best XDP support")
+    } else {
+        passed(NAME, release.to_string())
+    }
+}
+
+/// BTF 점검
+fn check_btf(path: &Path) -> CapabilityCheck {
+    if path.exists() {
+        passed("btf", format!("{} present", path.display()))
+    } else {
+        warning("btf", format!("{} not found", path.display()),
+            "Enable CONFIG_DEBUG_INFO_BTF in the kernel config")
+    }
+}
+
+/// bpf(BPF_MAP_CREATE) 속성 (union bpf_attr의 앞부분)
+#[repr(C)]
+struct MapCreateAttr {
+    map_type: u32,
+    key_size: u32,
+    value_size: u32,
+    max_entries: u32,
+    map_flags: u32,
+}
+
+/// 맵을 만들었다가 바로 닫음
+fn create_map(map_type: u32, key_size: u32, value_size: u32, max_entries: u32, map_flags: u32) -> std::result::Result<(), Errno> {
+    let attr = MapCreateAttr { map_type, key_size, value_size, max_entries, map_flags };
+
+    // SAFETY: attr는 호출 동안 유효하며 크기를 함께 전달한다
+    let fd = unsafe {
+        libc::syscall(libc::SYS_bpf, BPF_MAP_CREATE, &attr as *const MapCreateAttr,
+            std::mem::size_of::<MapCreateAttr>() as libc::c_uint)
+    };
+    if fd < 0 {
+        return Err(Errno::last());
+    }
+
+    // SAFETY: 방금 만든 fd이며 다른 곳에서 사용하지 않는다
+    unsafe { libc::close(fd as libc::c_int) };
+    Ok(())
+}
+
+/// 맵 생성 점검
+fn probe_map(name: &str, map_type: u32, key_size: u32, value_size: u32, max_entries: u32, map_flags: u32) -> CapabilityCheck {
+    match create_map(map_type, key_size, value_size, max_entries, map_flags) {
+        Ok(()) => passed(name, "creatable"),
+        Err(errno) => failed(name, format!("Map creation failed: {}", errno), map_error_hint(errno)),
+    }
+}
+
+/// 맵 생성 오류의 해결 방법
+fn map_error_hint(errno: Errno) -> &'static str {
+    match errno {
+        Errno::EPERM | Errno::EACCES => "Run the daemon as root or grant CAP_BPF and CAP_NET_ADMIN",
+        Errno::EINVAL => "The kernel does not support this map type; upgrade to 5.10 or newer",
+        Errno::ENOMEM => "Raise the memlock rlimit (LimitMEMLOCK=infinity)",
+        Errno::ENOSYS => "The kernel was built without CONFIG_BPF_SYSCALL",
+        _ => "Check `dmesg` for BPF errors",
+    }
+}
+
+/// memlock 한도 점검 (부족하면 `raise(soft, hard)`로 올림)
+pub fn check_memlock(soft: u64, hard: u64, raise: impl FnOnce(u64, u64) -> Result<()>) -> CapabilityCheck {
+    const NAME: &str = "memlock_rlimit";
+    const HINT: &str = "Run the daemon with CAP_SYS_RESOURCE or set LimitMEMLOCK=infinity";
+
+    if soft == RLIM_INFINITY || soft >= MIN_MEMLOCK_BYTES {
+        return passed(NAME, format_limit(soft));
+    }
+
+    // 하드 한도가 충분하면 소프트 한도만 올리고, 아니면 무제한을 시도 (CAP_SYS_RESOURCE 필요)
+    let (new_soft, new_hard) = if hard == RLIM_INFINITY || hard >= MIN_MEMLOCK_BYTES {
+        (hard, hard)
+    } else {
+        (RLIM_INFINITY, RLIM_INFINITY)
+    };
+
+    match raise(new_soft, new_hard) {
+        Ok(()) => passed(NAME, format!("raised from {} to {}", format_limit(soft), format_limit(new_soft))),
+        Err(e) => warning(NAME,
+            format!("{} is below {} and could not be raised: {:#}", format_limit(soft), format_limit(MIN_MEMLOCK_BYTES), e),
+            HINT),
+    }
+}
+
+fn format_limit(limit: u64) -> String {
+    if limit == RLIM_INFINITY {
+        "unlimited".to_string()
+    } else {
+        swift_guard::utils::format_size(limit)
+    }
+}
+
+/// ip 명령 실행 (테스트에서 대체 가능)
+pub trait LinkRunner {
+    fn ip(&self, args: &[&str]) -> Result<()>;
+}
+
+/// 시스템 ip 명령
+pub struct IpCommand;
+
+impl LinkRunner for IpCommand {
+    fn ip(&self, args: &[&str]) -> Result<()> {
+        let output = Command::new("ip").args(args).output().context("Failed to run ip")?;
+
+        if !output.status.success() {
+            return Err(anyhow!("{}", String::from_utf8_lossy(&output.stderr).trim()));
+        }
+
+        Ok(())
+    }
+}
+
+/// 점검용 veth 쌍 (드롭 시 삭제)
+struct ProbeVeth<'r> {
+    runner: &'r dyn LinkRunner,
+}
+
+impl<'r> ProbeVeth<'r> {
+    fn create(runner: &'r dyn LinkRunner) -> Result<Self> {
+        // 이전 실행이 남긴 쌍 정리
+        let _ = runner.ip(&["link", "del", PROBE_VETH]);
+
+        runner.ip(&["link", "add", PROBE_VETH, "type", "veth", "peer", "name", PROBE_VETH_PEER])?;
+        let veth = Self { runner };
+        runner.ip(&["link", "set", "dev", PROBE_VETH, "up"])?;
+        runner.ip(&["link", "set", "dev", PROBE_VETH_PEER, "up"])?;
+
+        Ok(veth)
+    }
+}
+
+impl Drop for ProbeVeth<'_> {
+    fn drop(&mut self) {
+        // 한쪽을 삭제하면 상대도 함께 삭제됨
+        if let Err(e) = self.runner.ip(&["link", "del", PROBE_VETH]) {
+            warn!("Failed to remove probe interface {}: {}", PROBE_VETH, e);
+        }
+    }
+}
+
+/// veth 쌍을 만들어 generic/driver 모드 XDP 연결 점검
+pub fn probe_xdp_attach(runner: &dyn LinkRunner, bpf_obj: &Path) -> Vec<CapabilityCheck> {
+    let modes = [("xdp_generic_attach", "xdpgeneric"), ("xdp_driver_attach", "xdpdrv")];
+
+    let veth = match ProbeVeth::create(runner) {
+        Ok(veth) => veth,
+        Err(e) => {
+            let detail = format!("Failed to create probe veth pair: {:#}", e);
+            return modes.iter().map(|(name, _)| skipped(name, detail.clone(),
+                "Run the daemon as root (CAP_NET_ADMIN) with veth support (CONFIG_VETH)")).collect();
+        },
+    };
+
+    let obj = bpf_obj.to_string_lossy();
+    let checks = modes.iter().map(|(name, keyword)| {
+        match runner.ip(&["link", "set", "dev", PROBE_VETH, keyword, "obj", &obj, "sec", "xdp"]) {
+            Ok(()) => {
+                let _ = runner.ip(&["link", "set", "dev", PROBE_VETH, keyword, "off"]);
+                passed(name, format!("{} attach works", keyword))
+            },
+            Err(e) => failed(name, format!("{} attach failed: {:#}", keyword, e),
+                "Use --mode generic, or --fallback nftables on this kernel"),
+        }
+    }).collect();
+
+    drop(veth);
+    checks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    /// 실행한 명령을 기록하고 지정한 명령을 실패시키는 ip 대역
+    struct RecordingIp {
+        commands: RefCell<Vec<String>>,
+        fail_on: Vec<&'static str>,
+    }
+
+    impl RecordingIp {
+        fn new(fail_on: Vec<&'static str>) -> Self {
+            Self { commands: RefCell::new(Vec::new()), fail_on }
+        }
+    }
+
+    impl LinkRunner for RecordingIp {
+        fn ip(&self, args: &[&str]) -> Result<()> {
+            let command = args.join(" ");
+            self.commands.borrow_mut().push(command.clone());
+            if self.fail_on.iter().any(|pattern| command.contains(pattern)) {
+                return Err(anyhow!("RTNETLINK answers: Operation not supported"));
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_parse_kernel_version() {
+        assert_eq!(parse_kernel_version("5.15.0-91-generic"), Some((5, 15, 0)));
+        assert_eq!(parse_kernel_version("6.1"), Some((6, 1, 0)));
+        assert_eq!(parse_kernel_version("4.19.0+"), Some((4, 19, 0)));
+        assert_eq!(parse_kernel_version("linux"), None);
+
+        assert_eq!(check_kernel_version("4.19.0-25-amd64").status, CheckStatus::Fail);
+        assert_eq!(check_kernel_version("5.10.0-28-amd64").status, CheckStatus::Warn);
+        assert_eq!(check_kernel_version("6.8.0").status, CheckStatus::Ok);
+        assert_eq!(check_kernel_version("weird").status, CheckStatus::Warn);
+    }
+
+    #[test]
+    fn test_check_memlock() {
+        let mib = 1 << 20;
+
+        let check = check_memlock(RLIM_INFINITY, RLIM_INFINITY, |_, _| panic!("should not raise"));
+        assert_eq!(check.status, CheckStatus::Ok);
+
+        // 하드 한도까지 올림
+        let mut raised = None;
+        let check = check_memlock(8 * mib, 128 * mib, |soft, hard| { raised = Some((soft, hard)); Ok(()) });
+        assert_eq!(raised, Some((128 * mib, 128 * mib)));
+        assert_eq!(check.status, CheckStatus::Ok);
+        assert!(check.detail.contains("raised"));
+
+        // 하드 한도도 낮으면 무제한 시도, 실패하면 경고
+        let check = check_memlock(8 * mib, 8 * mib, |soft, _| {
+            assert_eq!(soft, RLIM_INFINITY);
+            Err(anyhow!("EPERM"))
+        });
+        assert_eq!(check.status, CheckStatus::Warn);
+        assert!(check.remediation.unwrap().contains("LimitMEMLOCK"));
+    }
+
+    #[test]
+    fn test_probe_xdp_attach() {
+        let runner = RecordingIp::new(vec!["xdpdrv obj"]);
+        let checks = probe_xdp_attach(&runner, Path::new("/tmp/xdp_filter.o"));
+
+        assert_eq!(checks.len(), 2);
+        assert_eq!(checks[0].status, CheckStatus::Ok);
+        assert_eq!(checks[1].status, CheckStatus::Fail);
+        assert!(checks[1].detail.contains("Operation not supported"));
+
+        let commands = runner.commands.borrow();
+        assert!(commands.contains(&"link set dev sgprobe0 xdpgeneric off".to_string()));
+        assert_eq!(commands.last().unwrap(), "link del sgprobe0");
+    }
+
+    #[test]
+    fn test_probe_veth_cleanup_on_failure() {
+        // 생성 후 설정 단계에서 실패해도 veth 삭제
+        let runner = RecordingIp::new(vec!["sgprobe1 up"]);
+        let checks = probe_xdp_attach(&runner, Path::new("/tmp/xdp_filter.o"));
+
+        assert!(checks.iter().all(|check| check.status == CheckStatus::Skipped));
+        assert_eq!(runner.commands.borrow().last().unwrap(), "link del sgprobe0");
+
+        // 생성 자체가 실패하면 정리할 것이 없음
+        let runner = RecordingIp::new(vec!["type veth"]);
+        probe_xdp_attach(&runner, Path::new("/tmp/xdp_filter.o"));
+        assert_eq!(runner.commands.borrow().iter().filter(|c| *c == "link del sgprobe0").count(), 1);
+    }
+
+    #[test]
+    fn test_map_error_hint() {
+        assert!(map_error_hint(Errno::EPERM).contains("CAP_BPF"));
+        assert!(map_error_hint(Errno::EINVAL).contains("map type"));
+    }
+}
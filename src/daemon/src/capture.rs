@@ -0,0 +1,494 @@
+//! 패킷 캡처 모듈
+//! 차단되거나 샘플링된 패킷을 pcap 파일로 저장 (파일 교체 및 전체 크기 제한)
+
+use anyhow::{anyhow, Context, Result};
+use log::{debug, info, warn};
+use std::fs::{self, File, OpenOptions};
+use std::io::{ErrorKind, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, SyncSender, TrySendError};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::config::{CaptureConfig, CaptureFilter};
+use swift_guard::api::CaptureInfo;
+
+/// pcap 매직 넘버 (마이크로초 타임스탬프)
+const PCAP_MAGIC: u32 = 0xa1b2c3d4;
+
+/// 링크 타입: 이더넷
+const LINKTYPE_ETHERNET: u32 = 1;
+
+/// 전역 헤더 길이
+const GLOBAL_HEADER_LEN: usize = 24;
+
+/// 패킷 레코드 헤더 길이
+const RECORD_HEADER_LEN: usize = 16;
+
+/// 캡처 파일 이름 접두사/접미사
+const FILE_PREFIX: &str = "swift-guard-";
+const FILE_SUFFIX: &str = ".pcap";
+
+/// 기록 대기열 크기 (가득 차면 패킷을 버림)
+const QUEUE_CAPACITY: usize = 1024;
+
+/// 캡처 사유
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CaptureReason {
+    /// 검사 모듈이 차단
+    Blocked,
+    /// 샘플링
+    Sampled,
+}
+
+impl CaptureFilter {
+    /// 이 사유의 패킷을 캡처하는지 여부
+    pub fn accepts(&self, reason: CaptureReason) -> bool {
+        match self {
+            CaptureFilter::All => true,
+            CaptureFilter::Blocked => reason == CaptureReason::Blocked,
+            CaptureFilter::Sampled => reason == CaptureReason::Sampled,
+        }
+    }
+}
+
+/// pcap 전역 헤더 (리틀 엔디안, 버전 2.4, 이더넷)
+pub fn global_header(snaplen: u32) -> [u8; GLOBAL_HEADER_LEN] {
+    let mut header = [0u8; GLOBAL_HEADER_LEN];
+    header[0..4].copy_from_slice(&PCAP_MAGIC.to_le_bytes());
+    header[4..6].copy_from_slice(&2u16.to_le_bytes());
+    header[6..8].copy_from_slice(&4u16.to_le_bytes());
+    // thiszone, sigfigs = 0
+    header[16..20].copy_from_slice(&snaplen.to_le_bytes());
+    header[20..24].copy_from_slice(&LINKTYPE_ETHERNET.to_le_bytes());
+    header
+}
+
+/// 패킷 레코드 (헤더 + snaplen으로 자른 데이터)
+pub fn packet_record(timestamp: Duration, data: &[u8], snaplen: u32) -> Vec<u8> {
+    let captured = &data[..data.len().min(snaplen as usize)];
+
+    let mut record = Vec::with_capacity(RECORD_HEADER_LEN + captured.len());
+    record.extend_from_slice(&(timestamp.as_secs() as u32).to_le_bytes());
+    record.extend_from_slice(&timestamp.subsec_micros().to_le_bytes());
+    record.extend_from_slice(&(captured.len() as u32).to_le_bytes());
+    record.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    record.extend_from_slice(captured);
+    record
+}
+
+/// 캡처 파일 요약 (패킷 수, 첫/마지막 패킷 시각)
+///
+/// 마지막 레코드가 잘려 있으면(기록 중인 파일) 그 앞까지만 센다.
+pub fn summarize<R: Read + Seek>(mut reader: R) -> Result<(u64, Option<u64>, Option<u64>)> {
+    let mut header = [0u8; GLOBAL_HEADER_LEN];
+    reader.read_exact(&mut header).context("Missing pcap header")?;
+
+    let magic = u32::from_le_bytes(header[0..4].try_into().unwrap());
+    let read_u32 = match magic {
+        PCAP_MAGIC => u32::from_le_bytes,
+        m if m.swap_bytes() == PCAP_MAGIC => u32::from_be_bytes,
+        _ => return Err(anyhow!("Not a pcap file (magic {:#010x})", magic)),
+    };
+
+    let (mut packets, mut first, mut last) = (0u64, None, None);
+    let mut record = [0u8; RECORD_HEADER_LEN];
+
+    loop {
+        match reader.read_exact(&mut record) {
+            Ok(()) => {},
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof => break,
+            Err(e) => return Err(e.into()),
+        }
+
+        let ts = read_u32(record[0..4].try_into().unwrap()) as u64;
+        let incl_len = read_u32(record[8..12].try_into().unwrap()) as u64;
+
+        let pos = reader.stream_position()?;
+        let end = reader.seek(SeekFrom::End(0))?;
+        if pos + incl_len > end {
+            break;
+        }
+        reader.seek(SeekFrom::Start(pos + incl_len))?;
+
+        packets += 1;
+        first.get_or_insert(ts);
+        last = Some(ts);
+    }
+
+    Ok((packets, first, last))
+}
+
+/// 디렉토리의 캡처 파일 (이름 순 = 생성 순)
+fn capture_files(dir: &Path) -> Result<Vec<(PathBuf, u64)>> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e).context(format!("Failed to read capture directory: {}", dir.display())),
+    };
+
+    let mut files = Vec::new();
+    for entry in entries {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if name.starts_with(FILE_PREFIX) && name.ends_with(FILE_SUFFIX) {
+            files.push((entry.path(), entry.metadata()?.len()));
+        }
+    }
+
+    files.sort();
+    Ok(files)
+}
+
+/// 캡처 파일 목록 (읽을 수 없는 파일은 건너뜀)
+pub fn list_captures(dir: &Path) -> Result<Vec<CaptureInfo>> {
+    let mut captures = Vec::new();
+
+    for (path, size) in capture_files(dir)? {
+        let summary = File::open(&path)
+            .map_err(anyhow::Error::from)
+            .and_then(summarize);
+
+        match summary {
+            Ok((packets, first_packet, last_packet)) => captures.push(CaptureInfo {
+                name: path.file_name().unwrap_or_default().to_string_lossy().into_owned(),
+                size,
+                packets,
+                first_packet,
+                last_packet,
+            }),
+            Err(e) => warn!("Skipping capture file {}: {:#}", path.display(), e),
+        }
+    }
+
+    Ok(captures)
+}
+
+/// 현재 기록 중인 파일
+#[derive(Debug)]
+struct CurrentFile {
+    file: File,
+    path: PathBuf,
+    size: u64,
+}
+
+/// pcap 파일 기록기
+///
+/// 파일이 `max_file_bytes`를 넘기 전에 새 파일로 교체하고, 교체할 때마다
+/// 파일 수와 전체 크기 제한을 넘는 오래된 파일을 삭제한다.
+#[derive(Debug)]
+pub struct CaptureWriter {
+    dir: PathBuf,
+    snaplen: u32,
+    max_file_bytes: u64,
+    max_files: usize,
+    max_total_bytes: u64,
+    current: Option<CurrentFile>,
+}
+
+impl CaptureWriter {
+    /// 새로운 기록기 생성
+    pub fn new(config: &CaptureConfig) -> Result<Self> {
+        if config.max_files == 0 || config.max_file_mb == 0 || config.snaplen == 0 {
+            return Err(anyhow!("capture.max_files, capture.max_file_mb and capture.snaplen must be positive"));
+        }
+
+        if config.max_total_mb < config.max_file_mb {
+            return Err(anyhow!("capture.max_total_mb ({}) must be at least capture.max_file_mb ({})",
+                config.max_total_mb, config.max_file_mb));
+        }
+
+        Ok(Self {
+            dir: PathBuf::from(&config.dir),
+            snaplen: config.snaplen,
+            max_file_bytes: config.max_file_mb * 1024 * 1024,
+            max_files: config.max_files,
+            max_total_bytes: config.max_total_mb * 1024 * 1024,
+            current: None,
+        })
+    }
+
+    /// 패킷 기록
+    pub fn write(&mut self, data: &[u8], timestamp: Duration) -> Result<()> {
+        let record = packet_record(timestamp, data, self.snaplen);
+
+        let rotate = self.current.as_ref()
+            .is_none_or(|current| current.size + record.len() as u64 > self.max_file_bytes);
+        if rotate {
+            self.rotate(timestamp)?;
+        }
+
+        let current = self.current.as_mut().expect("capture file opened by rotate");
+        current.file.write_all(&record)
+            .context(format!("Failed to write {}", current.path.display()))?;
+        current.size += record.len() as u64;
+
+        Ok(())
+    }
+
+    /// 새 파일 열기 및 오래된 파일 정리
+    fn rotate(&mut self, timestamp: Duration) -> Result<()> {
+        fs::create_dir_all(&self.dir)
+            .context(format!("Failed to create capture directory: {}", self.dir.display()))?;
+
+        let stamp = chrono::DateTime::from_timestamp(timestamp.as_secs() as i64, 0)
+            .map(|t| t.format("%Y%m%dT%H%M%S").to_string())
+            .unwrap_or_default();
+
+        // 같은 초에 여러 번 교체하면 일련번호로 구분
+        let mut seq = 0;
+        let (mut file, path) = loop {
+            let path = self.dir.join(format!("{}{}-{:04}{}", FILE_PREFIX, stamp, seq, FILE_SUFFIX));
+            match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(file) => break (file, path),
+                Err(e) if e.kind() == ErrorKind::AlreadyExists => seq += 1,
+                Err(e) => return Err(e).context(format!("Failed to create {}", path.display())),
+            }
+        };
+
+        file.write_all(&global_header(self.snaplen))
+            .context(format!("Failed to write {}", path.display()))?;
+
+        debug!("Capturing packets to {}", path.display());
+        self.current = Some(CurrentFile { file, path: path.clone(), size: GLOBAL_HEADER_LEN as u64 });
+
+        self.enforce_budget(&path)
+    }
+
+    /// 파일 수와 전체 크기 제한을 넘는 오래된 파일 삭제 (현재 파일 제외)
+    fn enforce_budget(&self, current: &Path) -> Result<()> {
+        let files: Vec<_> = capture_files(&self.dir)?
+            .into_iter()
+            .filter(|(path, _)| path != current)
+            .collect();
+
+        // 현재 파일은 최대 크기까지 자랄 수 있으므로 그만큼 예약
+        let mut count = files.len() + 1;
+        let mut total = files.iter().map(|(_, size)| size).sum::<u64>() + self.max_file_bytes;
+
+        for (path, size) in files {
+            if count <= self.max_files && total <= self.max_total_bytes {
+                break;
+            }
+
+            fs::remove_file(&path)
+                .context(format!("Failed to remove {}", path.display()))?;
+            info!("Removed old capture file {}", path.display());
+            count -= 1;
+            total -= size;
+        }
+
+        Ok(())
+    }
+}
+
+/// 캡처 대기열 항목
+type CaptureItem = (Vec<u8>, Duration);
+
+/// 패킷 캡처 싱크
+///
+/// 파일 기록은 전용 스레드에서 처리한다. 대기열이 가득 차면 패킷을 버리므로
+/// `record`는 검사 경로를 막지 않는다.
+#[derive(Debug, Clone)]
+pub struct CaptureSink {
+    tx: SyncSender<CaptureItem>,
+    filter: CaptureFilter,
+}
+
+impl CaptureSink {
+    /// 구성에 따라 캡처 시작 (비활성화면 None)
+    pub fn start(config: &CaptureConfig) -> Result<Option<Self>> {
+        if !config.enabled {
+            return Ok(None);
+        }
+
+        let mut writer = CaptureWriter::new(config)?;
+        let (tx, rx) = mpsc::sync_channel::<CaptureItem>(QUEUE_CAPACITY);
+
+        std::thread::Builder::new()
+            .name("pcap-writer".to_string())
+            .spawn(move || {
+                let mut failing = false;
+                for (data, timestamp) in rx {
+                    match writer.write(&data, timestamp) {
+                        Ok(()) => failing = false,
+                        Err(e) if !failing => {
+                            warn!("Packet capture failed: {:#}", e);
+                            failing = true;
+                        },
+                        Err(_) => {},
+                    }
+                }
+            })
+            .context("Failed to start capture writer thread")?;
+
+        info!("Capturing {:?} packets to {}", config.filter, config.dir);
+        Ok(Some(Self { tx, filter: config.filter }))
+    }
+
+    /// 패킷 캡처 요청 (대기하지 않음)
+    pub fn record(&self, reason: CaptureReason, packet: &[u8]) {
+        if !self.filter.accepts(reason) {
+            return;
+        }
+
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+        match self.tx.try_send((packet.to_vec(), timestamp)) {
+            Ok(()) => {},
+            Err(TrySendError::Full(_)) => debug!("Capture queue full, dropping packet"),
+            Err(TrySendError::Disconnected(_)) => debug!("Capture writer stopped, dropping packet"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static NEXT_DIR: AtomicUsize = AtomicUsize::new(0);
+
+    /// 테스트별 임시 디렉토리
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new() -> Self {
+            let path = std::env::temp_dir().join(format!(
+                "swift-guard-capture-{}-{}", std::process::id(), NEXT_DIR.fetch_add(1, Ordering::SeqCst)));
+            Self(path)
+        }
+
+        fn names(&self) -> Vec<String> {
+            capture_files(&self.0).unwrap().iter()
+                .map(|(path, _)| path.file_name().unwrap().to_string_lossy().into_owned())
+                .collect()
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn config(dir: &TempDir) -> CaptureConfig {
+        CaptureConfig {
+            enabled: true,
+            dir: dir.0.to_string_lossy().into_owned(),
+            max_file_mb: 1,
+            max_files: 3,
+            max_total_mb: 16,
+            snaplen: 65535,
+            filter: CaptureFilter::All,
+        }
+    }
+
+    #[test]
+    fn test_global_header_fixture() {
+        assert_eq!(global_header(65535), [
+            0xd4, 0xc3, 0xb2, 0xa1, // magic
+            0x02, 0x00, 0x04, 0x00, // version 2.4
+            0x00, 0x00, 0x00, 0x00, // thiszone
+            0x00, 0x00, 0x00, 0x00, // sigfigs
+            0xff, 0xff, 0x00, 0x00, // snaplen
+            0x01, 0x00, 0x00, 0x00, // LINKTYPE_ETHERNET
+        ]);
+    }
+
+    #[test]
+    fn test_packet_record_fixture() {
+        let timestamp = Duration::new(1_700_000_000, 123_456_789);
+        let record = packet_record(timestamp, &[0xaa, 0xbb, 0xcc], 65535);
+        assert_eq!(record, [
+            0x00, 0xf1, 0x53, 0x65, // ts_sec 1700000000
+            0x40, 0xe2, 0x01, 0x00, // ts_usec 123456
+            0x03, 0x00, 0x00, 0x00, // incl_len
+            0x03, 0x00, 0x00, 0x00, // orig_len
+            0xaa, 0xbb, 0xcc,
+        ]);
+
+        // snaplen보다 긴 패킷은 잘리고 원래 길이는 유지
+        let record = packet_record(timestamp, &[0u8; 100], 64);
+        assert_eq!(record.len(), RECORD_HEADER_LEN + 64);
+        assert_eq!(&record[8..16], &[64, 0, 0, 0, 100, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_summarize() {
+        let mut bytes = global_header(65535).to_vec();
+        bytes.extend(packet_record(Duration::from_secs(100), &[1, 2, 3], 65535));
+        bytes.extend(packet_record(Duration::from_secs(160), &[4; 60], 65535));
+        assert_eq!(summarize(Cursor::new(&bytes)).unwrap(), (2, Some(100), Some(160)));
+
+        // 기록 중에 잘린 마지막 레코드는 무시
+        bytes.extend(&packet_record(Duration::from_secs(200), &[5; 40], 65535)[..20]);
+        assert_eq!(summarize(Cursor::new(&bytes)).unwrap(), (2, Some(100), Some(160)));
+
+        assert_eq!(summarize(Cursor::new(global_header(65535))).unwrap(), (0, None, None));
+        assert!(summarize(Cursor::new(vec![0u8; 24])).is_err());
+    }
+
+    #[test]
+    fn test_writer_rotates_and_enforces_file_count() {
+        let dir = TempDir::new();
+        let mut writer = CaptureWriter::new(&config(&dir)).unwrap();
+
+        // 파일당 1MB: 1000바이트 패킷은 약 1030개마다 교체
+        let packet = vec![0u8; 1000];
+        for i in 0..5000u64 {
+            writer.write(&packet, Duration::from_secs(1_700_000_000 + i / 1000)).unwrap();
+        }
+
+        let names = dir.names();
+        assert_eq!(names.len(), 3);
+        assert!(names.iter().all(|name| name.starts_with("swift-guard-20231114T2213")));
+
+        let captures = list_captures(&dir.0).unwrap();
+        assert!(captures.iter().all(|c| c.size <= 1024 * 1024));
+        // 최신 파일이 마지막
+        let last = captures.last().unwrap();
+        assert_eq!(last.last_packet, Some(1_700_000_004));
+        assert!(captures.iter().map(|c| c.packets).sum::<u64>() < 5000);
+    }
+
+    #[test]
+    fn test_writer_enforces_total_budget() {
+        let dir = TempDir::new();
+        let mut config = config(&dir);
+        config.max_files = 100;
+        config.max_total_mb = 2;
+        let mut writer = CaptureWriter::new(&config).unwrap();
+
+        let packet = vec![0u8; 60_000];
+        for i in 0..100u64 {
+            writer.write(&packet, Duration::from_secs(1_700_000_000 + i)).unwrap();
+        }
+
+        // 현재 파일을 위해 1MB를 예약하므로 이전 파일은 하나만 남음
+        let captures = list_captures(&dir.0).unwrap();
+        assert_eq!(captures.len(), 2);
+        assert!(captures.iter().map(|c| c.size).sum::<u64>() <= 2 * 1024 * 1024);
+    }
+
+    #[test]
+    fn test_invalid_config() {
+        let dir = TempDir::new();
+        let mut config = config(&dir);
+        config.max_total_mb = 0;
+        assert!(CaptureWriter::new(&config).is_err());
+    }
+
+    #[test]
+    fn test_filter() {
+        assert!(CaptureFilter::Blocked.accepts(CaptureReason::Blocked));
+        assert!(!CaptureFilter::Blocked.accepts(CaptureReason::Sampled));
+        assert!(CaptureFilter::Sampled.accepts(CaptureReason::Sampled));
+        assert!(CaptureFilter::All.accepts(CaptureReason::Blocked));
+    }
+
+    #[test]
+    fn test_list_missing_dir() {
+        let dir = TempDir::new();
+        assert!(list_captures(&dir.0).unwrap().is_empty());
+    }
+}
@@ -1,12 +1,20 @@
 //! 구성 모듈
 //! 데몬 구성 로드 및 관리
 
-use anyhow::{anyhow, Context, Result};
-use log::{debug, error, info, warn};
+use anyhow::{Context, Result};
+use log::{info, warn};
 use serde::{Deserialize, Serialize};
 use std::fs::File;
 use std::io::Read;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio_rustls::rustls::ServerConfig;
+
+use swift_guard::api::{FallbackMode, WasmErrorPolicy};
+
+use crate::maps::WriteSchedule;
+use crate::statslog::StatsLogFormat;
 
 /// 데몬 구성
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -17,6 +25,390 @@ pub struct DaemonConfig {
     pub telemetry: TelemetryConfig,
     /// WASM 구성
     pub wasm: WasmConfig,
+    /// 자동 완화 구성
+    #[serde(default, deserialize_with = "default_if_null")]
+    pub mitigation: MitigationConfig,
+    /// API 서버 구성
+    #[serde(default, deserialize_with = "default_if_null")]
+    pub api: ApiConfig,
+    /// 로그 출력 구성
+    #[serde(default, deserialize_with = "default_if_null")]
+    pub logging: LoggingConfig,
+    /// 패킷 캡처 구성
+    #[serde(default, deserialize_with = "default_if_null")]
+    pub capture: CaptureConfig,
+    /// 규칙 적중 이벤트 구성
+    #[serde(default, deserialize_with = "default_if_null")]
+    pub events: EventsConfig,
+    /// 규칙 할당량 구성
+    #[serde(default, deserialize_with = "default_if_null")]
+    pub quota: QuotaConfig,
+    /// 규칙 만료 알림 구성
+    #[serde(default, deserialize_with = "default_if_null")]
+    pub expiry: ExpiryConfig,
+    /// 호스트 이름 규칙 구성
+    #[serde(default, deserialize_with = "default_if_null")]
+    pub dns: DnsConfig,
+    /// 링크 상태 감시 구성
+    #[serde(default, deserialize_with = "default_if_null")]
+    pub link: LinkConfig,
+    /// GeoIP 조회 구성
+    #[serde(default, deserialize_with = "default_if_null")]
+    pub geoip: GeoIpConfig,
+    /// 커널 맵 대량 쓰기 구성
+    #[serde(default, deserialize_with = "default_if_null")]
+    pub map_writes: MapWriteConfig,
+    /// 인터페이스별 구성
+    #[serde(default, deserialize_with = "default_if_null")]
+    pub interfaces: Vec<InterfaceConfig>,
+}
+
+/// 인터페이스 구성
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct InterfaceConfig {
+    /// 인터페이스 이름
+    pub name: String,
+    /// XDP 연결 모드 (driver, generic, offload)
+    #[serde(default = "default_interface_mode")]
+    pub mode: String,
+    /// 시작 시 자동 연결
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// XDP 연결 실패 시 대체 백엔드 (none, nftables)
+    #[serde(default)]
+    pub fallback: FallbackMode,
+}
+
+fn default_interface_mode() -> String {
+    "driver".to_string()
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// 캡처 대상 패킷
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CaptureFilter {
+    /// WASM 모듈이 차단한 패킷
+    Blocked,
+    /// 샘플링된 패킷
+    Sampled,
+    /// 모두
+    All,
+}
+
+/// 패킷 캡처(pcap) 구성
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct CaptureConfig {
+    /// 캡처 활성화
+    pub enabled: bool,
+    /// 캡처 파일 디렉토리
+    pub dir: String,
+    /// 파일 하나의 최대 크기 (MB, 넘으면 새 파일로 교체)
+    pub max_file_mb: u64,
+    /// 보관할 최대 파일 수
+    pub max_files: usize,
+    /// 모든 캡처 파일의 최대 크기 합 (MB)
+    pub max_total_mb: u64,
+    /// 패킷당 저장할 최대 바이트 수
+    pub snaplen: u32,
+    /// 캡처 대상
+    pub filter: CaptureFilter,
+}
+
+impl Default for CaptureConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            dir: "/var/lib/swift-guard/captures".to_string(),
+            max_file_mb: 16,
+            max_files: 8,
+            max_total_mb: 128,
+            snaplen: 65535,
+            filter: CaptureFilter::Blocked,
+        }
+    }
+}
+
+/// 규칙 적중 이벤트 구성
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct EventsConfig {
+    /// XDP 프로그램의 규칙 적중 이벤트 수신
+    pub enabled: bool,
+    /// 이벤트를 syslog로도 전달 (logging.syslog 활성화 필요)
+    pub syslog: bool,
+    /// 이벤트 웹훅 URL
+    pub webhook_url: Option<String>,
+    /// GetEvents로 조회할 수 있게 보관하는 최근 이벤트 수
+    pub history_size: usize,
+}
+
+impl Default for EventsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            syslog: false,
+            webhook_url: None,
+            history_size: crate::events::DEFAULT_HISTORY_SIZE,
+        }
+    }
+}
+
+/// 규칙 할당량 구성
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct QuotaConfig {
+    /// 매일 사용량을 초기화하는 시각 (UTC 기준 시, 0~23)
+    pub reset_hour: u32,
+    /// 사용량 상태 파일 (생략하면 `work_dir/quota-state.json`)
+    pub state_file: Option<String>,
+}
+
+impl QuotaConfig {
+    /// 사용량 상태 파일 경로
+    pub fn state_path(&self, work_dir: &str) -> PathBuf {
+        match &self.state_file {
+            Some(path) => PathBuf::from(path),
+            None => Path::new(work_dir).join("quota-state.json"),
+        }
+    }
+}
+
+/// 규칙 만료 알림(add-rule --owner-webhook) 구성
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ExpiryConfig {
+    /// 만료 예고 알림을 보낼 남은 시간 (초, "5m"처럼 쓸 수 있음, 0 = 만료 알림만)
+    #[serde(deserialize_with = "deserialize_duration")]
+    pub notify_before: u64,
+    /// 알림 상태 파일 (생략하면 `work_dir/expiry-state.json`)
+    pub state_file: Option<String>,
+}
+
+impl Default for ExpiryConfig {
+    fn default() -> Self {
+        Self {
+            notify_before: 300,
+            state_file: None,
+        }
+    }
+}
+
+impl ExpiryConfig {
+    /// 알림 상태 파일 경로
+    pub fn state_path(&self, work_dir: &str) -> PathBuf {
+        match &self.state_file {
+            Some(path) => PathBuf::from(path),
+            None => Path::new(work_dir).join("expiry-state.json"),
+        }
+    }
+}
+
+/// 호스트 이름 규칙(add-rule --src-host) 구성
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct DnsConfig {
+    /// 호스트 이름 규칙 허용 (시스템 resolv.conf로 해석)
+    pub enabled: bool,
+    /// 호스트 이름 재해석 간격 (초, "5m"처럼 쓸 수 있음)
+    #[serde(deserialize_with = "deserialize_duration")]
+    pub refresh_interval: u64,
+}
+
+impl Default for DnsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            refresh_interval: 300,
+        }
+    }
+}
+
+/// GeoIP(MaxMind mmdb) 조회 구성
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct GeoIpConfig {
+    /// 국가(또는 도시) 데이터베이스 경로 (GeoLite2-Country.mmdb 등, 없으면 조회하지 않음)
+    pub mmdb_path: Option<String>,
+    /// ASN 데이터베이스 경로 (GeoLite2-ASN.mmdb 등)
+    pub asn_mmdb_path: Option<String>,
+}
+
+/// 링크가 내려갔을 때 XDP 프로그램 처리
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LinkDownPolicy {
+    /// 연결을 유지 (링크가 올라오면 그대로 동작)
+    #[default]
+    Keep,
+    /// 프로그램을 분리하고 링크가 올라오면 다시 연결
+    Detach,
+}
+
+/// 링크 상태 감시 구성
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct LinkConfig {
+    /// 연결된 인터페이스와 리디렉션 대상의 operstate 감시
+    pub enabled: bool,
+    /// 점검 간격 (초, "5s"처럼 쓸 수 있음)
+    #[serde(deserialize_with = "deserialize_duration")]
+    pub poll_interval: u64,
+    /// 링크가 내려갔을 때 XDP 프로그램 처리 (keep, detach)
+    pub on_link_down: LinkDownPolicy,
+}
+
+impl Default for LinkConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            poll_interval: 2,
+            on_link_down: LinkDownPolicy::Keep,
+        }
+    }
+}
+
+/// 커널 맵 대량 쓰기 구성 (규칙 집합 적용)
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct MapWriteConfig {
+    /// 맵 관리자 잠금을 놓기 전까지 적용할 규칙 변경 수 ("1k"처럼 쓸 수 있음)
+    #[serde(deserialize_with = "deserialize_count")]
+    pub chunk_size: usize,
+    /// 청크 사이 대기 시간 (밀리초)
+    pub pause_ms: u64,
+}
+
+impl MapWriteConfig {
+    /// 맵 관리자의 대량 쓰기 일정
+    pub fn schedule(&self) -> WriteSchedule {
+        WriteSchedule {
+            chunk_size: self.chunk_size,
+            pause: Duration::from_millis(self.pause_ms),
+        }
+    }
+}
+
+impl Default for MapWriteConfig {
+    fn default() -> Self {
+        let schedule = WriteSchedule::default();
+        Self {
+            chunk_size: schedule.chunk_size,
+            pause_ms: schedule.pause.as_millis() as u64,
+        }
+    }
+}
+
+/// 로그 출력 구성
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct LoggingConfig {
+    /// 보안 이벤트 syslog 출력
+    #[serde(default)]
+    pub syslog: SyslogConfig,
+}
+
+/// syslog 출력 구성
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct SyslogConfig {
+    /// syslog 출력 활성화
+    pub enabled: bool,
+    /// facility 이름 (daemon, auth, local0-local7 등)
+    pub facility: String,
+    /// 전송 대상 (/dev/log, udp://host:port, tcp://host:port)
+    pub address: String,
+    /// 이 초당 매치 수 이상인 규칙을 보고 (0 = 보고 안 함, "1k"처럼 쓸 수 있음)
+    #[serde(deserialize_with = "deserialize_count")]
+    pub rule_match_pps_threshold: u64,
+}
+
+impl Default for SyslogConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            facility: "local0".to_string(),
+            address: "/dev/log".to_string(),
+            rule_match_pps_threshold: 1000,
+        }
+    }
+}
+
+/// API 서버 구성
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ApiConfig {
+    /// TLS 구성 (없으면 평문 TCP)
+    #[serde(default)]
+    pub tls: Option<TlsConfig>,
+    /// 피어별 초당 허용 요청 수 (없으면 제한하지 않음)
+    #[serde(default)]
+    pub max_requests_per_sec: Option<u32>,
+    /// API 토큰 (비어 있으면 인증하지 않음)
+    #[serde(default)]
+    pub tokens: Vec<ApiToken>,
+    /// 응답 프레임 최대 크기 (바이트, "16M"처럼 쓸 수 있음)
+    ///
+    /// 넘는 규칙/이벤트 목록 응답은 앞부분만 담은 `PartialResults`로 보낸다.
+    #[serde(default = "default_max_response_size", deserialize_with = "deserialize_size")]
+    pub max_response_size: usize,
+    /// TCP와 함께 요청을 받을 Unix 소켓 경로 (요청자는 연결한 프로세스의 uid로 식별)
+    #[serde(default)]
+    pub unix_socket: Option<PathBuf>,
+}
+
+impl Default for ApiConfig {
+    fn default() -> Self {
+        Self {
+            tls: None,
+            max_requests_per_sec: None,
+            tokens: Vec::new(),
+            max_response_size: default_max_response_size(),
+            unix_socket: None,
+        }
+    }
+}
+
+fn default_max_response_size() -> usize {
+    swift_guard::api::DEFAULT_MAX_RESPONSE_LEN
+}
+
+/// API 토큰과 권한
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ApiToken {
+    /// 요청에 포함할 토큰 문자열
+    pub token: String,
+    /// 토큰으로 다룰 수 있는 규칙 네임스페이스
+    #[serde(default = "swift_guard::utils::default_namespace")]
+    pub namespace: String,
+    /// 조회 요청만 허용
+    #[serde(default)]
+    pub readonly: bool,
+    /// 모든 네임스페이스와 데몬 전역 요청(attach, WASM 등) 허용
+    #[serde(default)]
+    pub admin: bool,
+}
+
+/// API 서버 TLS 구성
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TlsConfig {
+    /// 서버 인증서 체인 (PEM)
+    pub cert_path: PathBuf,
+    /// 서버 개인 키 (PEM)
+    pub key_path: PathBuf,
+    /// 클라이언트 인증서를 검증할 CA (PEM, 지정하면 상호 TLS 필수)
+    #[serde(default)]
+    pub client_ca_path: Option<PathBuf>,
+}
+
+impl TlsConfig {
+    /// 인증서와 키를 읽어 서버 TLS 설정 생성
+    pub fn server_config(&self) -> Result<Arc<ServerConfig>> {
+        swift_guard::tls::server_config(&self.cert_path, &self.key_path, self.client_ca_path.as_deref())
+            .context("Failed to load API TLS configuration")
+    }
 }
 
 /// 일반 구성
@@ -28,19 +420,51 @@ pub struct GeneralConfig {
     pub work_dir: String,
     /// PID 파일 경로
     pub pid_file: String,
+    /// attach 요청이 지정할 수 있는 대체 BPF 오브젝트 디렉토리 (없으면 기본 오브젝트만 사용)
+    #[serde(default)]
+    pub object_dir: Option<String>,
+    /// 규칙을 추가할 때 이전 버전이 고정 맵에 남긴 같은 프리픽스 키를 네트워크 순서로 이전
+    #[serde(default)]
+    pub migrate_prefix_keys: bool,
+    /// 초기화 후 전환할 사용자 (없으면 root로 계속 실행)
+    #[serde(default)]
+    pub run_as_user: Option<String>,
+    /// 초기화 후 전환할 그룹 (없으면 사용자의 기본 그룹)
+    #[serde(default)]
+    pub run_as_group: Option<String>,
+    /// 사용자 전환 후에도 남길 capability (예: CAP_NET_ADMIN, CAP_BPF)
+    #[serde(default)]
+    pub keep_capabilities: Vec<String>,
 }
 
 /// 텔레메트리 구성
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct TelemetryConfig {
-    /// 통계 로깅 활성화
+    /// 통계 로깅 활성화 (수집 주기마다 `stats_log_format` 한 줄을 info 수준으로 기록)
     pub log_stats: bool,
+    /// 통계 로그 줄 형식 (`{pps}`, `{mbps:.2}`, `{dropped}` 등, 알 수 없는 키는 구성 오류)
+    #[serde(default)]
+    pub stats_log_format: StatsLogFormat,
     /// 통계 수집 간격 (초)
     pub interval: u64,
-    /// 텔레메트리 내보내기 활성화
+    /// Prometheus 스크레이프 엔드포인트 활성화
     pub export_enabled: bool,
-    /// 내보내기 URL
+    /// 스크레이프 엔드포인트 URL (`http://주소:포트/경로`, 없으면 `metrics::DEFAULT_EXPORT_URL`)
     pub export_url: Option<String>,
+    /// XDP_ABORTED 경보까지 필요한 연속 수집 구간 수 (0이면 경보 없음)
+    #[serde(default = "default_abort_alert_intervals")]
+    pub abort_alert_intervals: u32,
+    /// 트래픽 이상 판정 배수 (pps나 Mbps가 기준선 평균 + k·표준편차를 넘으면 이상, 0이면 판정 안 함)
+    #[serde(default = "default_anomaly_k")]
+    pub anomaly_k: f64,
+}
+
+fn default_abort_alert_intervals() -> u32 {
+    3
+}
+
+fn default_anomaly_k() -> f64 {
+    3.0
 }
 
 /// WASM 구성
@@ -52,6 +476,157 @@ pub struct WasmConfig {
     pub auto_load: bool,
     /// 자동 로드 모듈 목록
     pub auto_load_modules: Vec<String>,
+    /// 검사 워커 수 (없으면 물리 코어 수)
+    #[serde(default)]
+    pub workers: Option<usize>,
+    /// 워커 앞 검사 대기열 크기 (패킷 수)
+    #[serde(default = "default_wasm_queue_size")]
+    pub queue_size: usize,
+    /// 대기열이 가득 찼을 때 패킷 처리 (pass, drop)
+    #[serde(default)]
+    pub overload_policy: OverloadPolicy,
+    /// 컴파일된 모듈을 `work_dir/wasm-cache`에 캐시
+    #[serde(default = "default_true")]
+    pub cache: bool,
+    /// 모듈이 검사 중 오류를 낼 때 처리 (pass, drop, disable_module, 로드 시 모듈별로 변경 가능)
+    #[serde(default)]
+    pub on_error: WasmErrorPolicy,
+    /// 모듈별로 보관할 최근 로그 줄 수
+    #[serde(default = "default_wasm_log_lines")]
+    pub log_lines: usize,
+    /// 모듈마다 동시에 둘 수 있는 동적 차단 규칙 수 (0 = 동적 차단 안 함, "1k"처럼 쓸 수 있음)
+    #[serde(default = "default_max_dynamic_blocks", deserialize_with = "deserialize_count")]
+    pub max_dynamic_blocks: usize,
+    /// 동적 차단 규칙의 수명 (초, "10m"처럼 쓸 수 있음)
+    #[serde(default = "default_dynamic_block_ttl", deserialize_with = "deserialize_duration")]
+    pub dynamic_block_ttl: u32,
+    /// 검사할 프레임을 읽을 인터페이스 (redirect 규칙 대상의 수신 쪽, 없으면 패킷 검사 안 함)
+    #[serde(default)]
+    pub interface: Option<String>,
+}
+
+fn default_wasm_queue_size() -> usize {
+    crate::wasm::DEFAULT_QUEUE_SIZE
+}
+
+fn default_wasm_log_lines() -> usize {
+    crate::wasm::DEFAULT_LOG_LINES
+}
+
+fn default_max_dynamic_blocks() -> usize {
+    crate::dynblock::DEFAULT_MAX_PER_MODULE
+}
+
+fn default_dynamic_block_ttl() -> u32 {
+    crate::dynblock::DEFAULT_TTL
+}
+
+/// 숫자 또는 단위가 붙은 문자열 ("10k", "5m")
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Quantity {
+    Number(u64),
+    Text(String),
+}
+
+/// 숫자나 문자열 값을 `parse`로 해석해 대상 정수형으로 변환
+fn deserialize_quantity<'de, D, T>(deserializer: D, parse: fn(&str) -> Result<u64>) -> Result<T, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: TryFrom<u64>,
+{
+    let value = match Quantity::deserialize(deserializer)? {
+        Quantity::Number(value) => value,
+        Quantity::Text(text) => parse(&text).map_err(|e| serde::de::Error::custom(format!("{:#}", e)))?,
+    };
+    T::try_from(value).map_err(|_| serde::de::Error::custom(format!("{} is out of range", value)))
+}
+
+/// 개수 항목 (1000, "10k", "1.5M")
+fn deserialize_count<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: TryFrom<u64>,
+{
+    deserialize_quantity(deserializer, swift_guard::utils::parse_count)
+}
+
+/// 크기 항목 (바이트, 65536, "64K", "16M")
+fn deserialize_size<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: TryFrom<u64>,
+{
+    deserialize_quantity(deserializer, swift_guard::utils::parse_size)
+}
+
+/// 구성 섹션 (하위 항목이 모두 주석인 `api:`처럼 비어 있으면 기본값)
+fn default_if_null<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: Deserialize<'de> + Default,
+{
+    Ok(Option::<T>::deserialize(deserializer)?.unwrap_or_default())
+}
+
+/// 기간 항목 (초, 300, "5m", "1h")
+fn deserialize_duration<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: TryFrom<u64>,
+{
+    deserialize_quantity(deserializer, swift_guard::utils::parse_duration)
+}
+
+/// 검사 대기열 과부하 시 패킷 처리
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OverloadPolicy {
+    /// 검사하지 않고 통과
+    #[default]
+    Pass,
+    /// 검사하지 않고 차단
+    Drop,
+}
+
+/// 자동 완화 구성
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct MitigationConfig {
+    /// SYN 플러드 자동 완화 활성화
+    pub enabled: bool,
+    /// 단일 소스(/32)의 SYN 초당 패킷 수 임계값 ("10k"처럼 쓸 수 있음)
+    #[serde(deserialize_with = "deserialize_count")]
+    pub syn_pps_threshold: u64,
+    /// 임계값을 연속으로 초과해야 하는 수집 간격 수
+    pub consecutive_intervals: u32,
+    /// 완화 액션 (drop, rate-limit)
+    pub action: String,
+    /// rate-limit 액션의 초당 패킷 수
+    #[serde(deserialize_with = "deserialize_count")]
+    pub rate_limit: u32,
+    /// 자동 규칙 만료 시간 (초, "5m"처럼 쓸 수 있음)
+    #[serde(deserialize_with = "deserialize_duration")]
+    pub expire: u32,
+    /// 규칙을 설치하지 않고 로그만 기록
+    pub dry_run: bool,
+    /// 완화 이벤트 웹훅 URL
+    pub webhook_url: Option<String>,
+}
+
+impl Default for MitigationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            syn_pps_threshold: 10_000,
+            consecutive_intervals: 3,
+            action: "drop".to_string(),
+            rate_limit: 100,
+            expire: 300,
+            dry_run: false,
+            webhook_url: None,
+        }
+    }
 }
 
 impl Default for DaemonConfig {
@@ -61,18 +636,47 @@ impl Default for DaemonConfig {
                 log_level: "info".to_string(),
                 work_dir: "/var/lib/swift-guard".to_string(),
                 pid_file: "/var/run/swift-guard.pid".to_string(),
+                object_dir: None,
+                migrate_prefix_keys: false,
+                run_as_user: None,
+                run_as_group: None,
+                keep_capabilities: Vec::new(),
             },
             telemetry: TelemetryConfig {
                 log_stats: true,
+                stats_log_format: StatsLogFormat::default(),
                 interval: 10,
                 export_enabled: false,
                 export_url: None,
+                abort_alert_intervals: default_abort_alert_intervals(),
+                anomaly_k: default_anomaly_k(),
             },
             wasm: WasmConfig {
                 modules_dir: "/usr/local/lib/swift-guard/wasm".to_string(),
                 auto_load: false,
                 auto_load_modules: Vec::new(),
+                workers: None,
+                queue_size: default_wasm_queue_size(),
+                overload_policy: OverloadPolicy::Pass,
+                cache: true,
+                on_error: WasmErrorPolicy::Pass,
+                log_lines: default_wasm_log_lines(),
+                max_dynamic_blocks: default_max_dynamic_blocks(),
+                dynamic_block_ttl: default_dynamic_block_ttl(),
+                interface: None,
             },
+            mitigation: MitigationConfig::default(),
+            api: ApiConfig::default(),
+            logging: LoggingConfig::default(),
+            capture: CaptureConfig::default(),
+            events: EventsConfig::default(),
+            quota: QuotaConfig::default(),
+            expiry: ExpiryConfig::default(),
+            dns: DnsConfig::default(),
+            geoip: GeoIpConfig::default(),
+            link: LinkConfig::default(),
+            map_writes: MapWriteConfig::default(),
+            interfaces: Vec::new(),
         }
     }
 }
@@ -103,6 +707,7 @@ pub fn load_config(path: &Path) -> Result<DaemonConfig> {
 }
 
 /// 구성 파일 저장
+#[allow(dead_code)]
 pub fn save_config(config: &DaemonConfig, path: &Path) -> Result<()> {
     // YAML 직렬화
     let yaml = serde_yaml::to_string(config)
@@ -124,6 +729,7 @@ pub fn save_config(config: &DaemonConfig, path: &Path) -> Result<()> {
 }
 
 /// 구성 예시 생성
+#[allow(dead_code)]
 pub fn create_example_config() -> DaemonConfig {
     let mut config = DaemonConfig::default();
     
@@ -135,3 +741,58 @@ pub fn create_example_config() -> DaemonConfig {
     
     config
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_alerting_values_accept_units() {
+        let config: MitigationConfig = serde_yaml::from_str(
+            "syn_pps_threshold: 10k\nrate_limit: 1.5k\nexpire: 5m\n").unwrap();
+        assert_eq!((config.syn_pps_threshold, config.rate_limit, config.expire), (10_000, 1500, 300));
+        assert_eq!(config.consecutive_intervals, 3);
+
+        // 숫자는 그대로
+        let config: MitigationConfig = serde_yaml::from_str("syn_pps_threshold: 5000\nexpire: 60\n").unwrap();
+        assert_eq!((config.syn_pps_threshold, config.expire), (5000, 60));
+
+        let config: SyslogConfig = serde_yaml::from_str("rule_match_pps_threshold: 2k\n").unwrap();
+        assert_eq!(config.rule_match_pps_threshold, 2000);
+
+        let config: ExpiryConfig = serde_yaml::from_str("notify_before: 15m\n").unwrap();
+        assert_eq!((config.notify_before, config.state_path("/var/lib/swift-guard")),
+            (900, PathBuf::from("/var/lib/swift-guard/expiry-state.json")));
+
+        let config: DnsConfig = serde_yaml::from_str("enabled: true\nrefresh_interval: 2m\n").unwrap();
+        assert_eq!(config.refresh_interval, 120);
+
+        let config: LinkConfig = serde_yaml::from_str("poll_interval: 5s\non_link_down: detach\n").unwrap();
+        assert_eq!((config.enabled, config.poll_interval, config.on_link_down), (true, 5, LinkDownPolicy::Detach));
+        assert!(serde_yaml::from_str::<LinkConfig>("on_link_down: unplug\n").is_err());
+
+        let config: ApiConfig = serde_yaml::from_str("max_response_size: 4M\n").unwrap();
+        assert_eq!(config.max_response_size, 4 * 1024 * 1024);
+        let config: ApiConfig = serde_yaml::from_str("max_requests_per_sec: 50\n").unwrap();
+        assert_eq!(config.max_response_size, 16 * 1024 * 1024);
+
+        let config: WasmConfig = serde_yaml::from_str(
+            "modules_dir: /tmp\nauto_load: false\nauto_load_modules: []\nmax_dynamic_blocks: 2k\ndynamic_block_ttl: 15m\n").unwrap();
+        assert_eq!((config.max_dynamic_blocks, config.dynamic_block_ttl), (2000, 900));
+
+        let config: MapWriteConfig = serde_yaml::from_str("chunk_size: 2k\n").unwrap();
+        assert_eq!(config.schedule(), WriteSchedule { chunk_size: 2000, pause: Duration::from_millis(5) });
+
+        let err = serde_yaml::from_str::<MitigationConfig>("expire: 2w\n").unwrap_err();
+        assert!(err.to_string().contains("s, m, h or d"), "{}", err);
+        assert!(serde_yaml::from_str::<MitigationConfig>("rate_limit: 5G\n").is_err());
+    }
+
+    #[test]
+    fn test_sample_config_parses() {
+        let config: DaemonConfig = serde_yaml::from_str(include_str!("../../../config/swift-guard.yaml")).unwrap();
+        // 하위 항목이 모두 주석인 api 섹션은 기본값
+        assert_eq!(config.api.max_response_size, 16 * 1024 * 1024);
+        assert!(config.api.tokens.is_empty() && config.api.tls.is_none());
+    }
+}
@@ -0,0 +1,242 @@
+//! 규칙 겹침 분석 모듈
+//! 소스 프리픽스가 겹치는 규칙 쌍과 커널이 실제로 적용하는 규칙 계산
+//!
+//! filter_rules는 LPM 트라이이므로 겹치는 범위에서는 우선순위와 관계없이 가장 긴
+//! 프리픽스의 규칙이 매치된다. 같은 프리픽스는 키가 같아 마지막에 설치된 규칙만
+//! 남는다. 우선순위는 "높을수록 우선"으로 문서화되어 있으므로, 넓은 프리픽스의
+//! 우선순위가 더 높은 쌍은 문서와 다르게 동작한다.
+
+use swift_guard::api::RuleConflict;
+use swift_guard::utils;
+
+use crate::maps::FilterRule;
+
+/// `outer` 프리픽스가 `inner` 프리픽스를 포함하는지 (같은 프리픽스 포함)
+pub fn prefix_contains(outer: (u32, u32), inner: (u32, u32)) -> bool {
+    let (outer_addr, outer_len) = outer;
+    let (inner_addr, inner_len) = inner;
+
+    outer_len <= inner_len
+        && utils::mask_ipv4(outer_addr, outer_len) == utils::mask_ipv4(inner_addr, outer_len)
+}
+
+/// 두 규칙의 겹침 분석 (`later`는 나중에 설치된 규칙, 겹치지 않으면 None)
+fn conflict(earlier: &FilterRule, later: &FilterRule) -> Option<RuleConflict> {
+    let earlier_prefix = earlier.src_ip?;
+    let later_prefix = later.src_ip?;
+
+    let (broader, narrower) = if prefix_contains(earlier_prefix, later_prefix) {
+        (earlier, later)
+    } else if prefix_contains(later_prefix, earlier_prefix) {
+        (later, earlier)
+    } else {
+        return None;
+    };
+
+    let same_prefix = earlier_prefix.1 == later_prefix.1;
+    let (winner, reason) = if same_prefix {
+        (later, "same prefix: one kernel entry per prefix, the most recently installed rule replaces the other")
+    } else {
+        (narrower, "longest prefix match: the more specific prefix wins regardless of priority")
+    };
+    let loser = if std::ptr::eq(winner, broader) { narrower } else { broader };
+
+    Some(RuleConflict {
+        broader: broader.label.clone(),
+        broader_prefix: format_prefix(broader.src_ip?),
+        broader_priority: broader.priority,
+        narrower: narrower.label.clone(),
+        narrower_prefix: format_prefix(narrower.src_ip?),
+        narrower_priority: narrower.priority,
+        winner: winner.label.clone(),
+        priority_ignored: loser.priority > winner.priority,
+        reason: reason.to_string(),
+    })
+}
+
+/// 소스 프리픽스가 겹치는 모든 규칙 쌍 (규칙 순서는 설치 순서)
+pub fn find_conflicts<'r>(rules: impl IntoIterator<Item = &'r FilterRule>) -> Vec<RuleConflict> {
+    let rules: Vec<&FilterRule> = rules.into_iter().collect();
+
+    let mut conflicts = Vec::new();
+    for (i, earlier) in rules.iter().enumerate() {
+        for later in &rules[i + 1..] {
+            conflicts.extend(conflict(earlier, later));
+        }
+    }
+    conflicts
+}
+
+/// 새 규칙이 기존 규칙과 겹쳐 우선순위가 무시되는 경우
+pub fn priority_conflicts<'r>(
+    rule: &FilterRule,
+    existing: impl IntoIterator<Item = &'r FilterRule>,
+) -> Vec<RuleConflict> {
+    existing.into_iter()
+        .filter(|other| other.label != rule.label)
+        .filter_map(|other| conflict(other, rule))
+        .filter(|conflict| conflict.priority_ignored)
+        .collect()
+}
+
+/// 프리픽스마다 그 안에 있는 다른 프리픽스 수 (같은 프리픽스는 하나로 셈, 주소 순)
+///
+/// 주소 순으로, 같은 주소면 짧은 프리픽스부터 정렬하면 포함하는 프리픽스가 항상 먼저
+/// 나오고 포함 관계는 서로 중첩된다. 현재 프리픽스를 포함하는 프리픽스만 스택에 남기므로
+/// 프리픽스마다 스택 깊이(최대 33)만큼만 센다.
+pub fn containment_counts(prefixes: impl IntoIterator<Item = (u32, u32)>) -> Vec<((u32, u32), usize)> {
+    let mut sorted: Vec<(u32, u32)> = prefixes.into_iter()
+        .map(|(addr, prefix_len)| (utils::mask_ipv4(addr, prefix_len), prefix_len))
+        .collect();
+    sorted.sort_unstable();
+    sorted.dedup();
+
+    let mut counts = vec![0; sorted.len()];
+    let mut enclosing: Vec<usize> = Vec::new();
+    for (i, &prefix) in sorted.iter().enumerate() {
+        while let Some(&outer) = enclosing.last() {
+            if prefix_contains(sorted[outer], prefix) {
+                break;
+            }
+            enclosing.pop();
+        }
+        for &outer in &enclosing {
+            counts[outer] += 1;
+        }
+        enclosing.push(i);
+    }
+
+    sorted.into_iter().zip(counts).collect()
+}
+
+/// "a.b.c.d/len" 표기 (호스트 비트는 지움)
+pub fn format_prefix((addr, prefix_len): (u32, u32)) -> String {
+    format!("{}/{}", utils::ipv4_to_string(utils::mask_ipv4(addr, prefix_len)), prefix_len)
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    /// 소스 프리픽스만 지정한 drop 규칙
+    pub fn rule(label: &str, prefix: &str, priority: u32) -> FilterRule {
+        FilterRule {
+            src_ip: Some(utils::parse_ip_prefix(prefix).unwrap()),
+            src_port_max: 0,
+            dst_port_max: 0,
+            protocol: 0,
+            action: 2,
+            priority,
+            label: label.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_prefix_contains() {
+        let p = |s: &str| utils::parse_ip_prefix(s).unwrap();
+
+        assert!(prefix_contains(p("10.0.0.0/8"), p("10.1.0.0/16")));
+        assert!(prefix_contains(p("10.0.0.0/8"), p("10.255.255.255")));
+        assert!(!prefix_contains(p("10.1.0.0/16"), p("10.0.0.0/8")));
+        assert!(!prefix_contains(p("10.0.0.0/8"), p("11.0.0.0/16")));
+        assert!(prefix_contains(p("0.0.0.0/0"), p("192.168.1.1")));
+        assert!(prefix_contains(p("192.168.1.0/24"), p("192.168.1.0/24")));
+        // 호스트 비트가 있어도 프리픽스 기준으로 비교
+        assert!(prefix_contains(p("10.0.0.5/8"), p("10.2.0.0/16")));
+    }
+
+    #[test]
+    fn test_containment_counts() {
+        let p = |s: &str| utils::parse_ip_prefix(s).unwrap();
+        let count = |prefixes: &[&str]| -> Vec<(String, usize)> {
+            containment_counts(prefixes.iter().map(|s| p(s)))
+                .into_iter()
+                .map(|(prefix, contains)| (format_prefix(prefix), contains))
+                .collect()
+        };
+
+        assert!(count(&[]).is_empty());
+        assert_eq!(count(&["10.0.0.0/8", "172.16.0.0/12"]), vec![
+            ("10.0.0.0/8".to_string(), 0),
+            ("172.16.0.0/12".to_string(), 0),
+        ]);
+
+        // 중첩된 포함 관계와 형제 프리픽스 (스택에서 빠진 뒤에도 바깥 프리픽스는 계속 셈)
+        assert_eq!(count(&["10.1.2.3", "10.2.0.0/16", "10.0.0.0/8", "10.1.0.0/16", "11.0.0.0/8"]), vec![
+            ("10.0.0.0/8".to_string(), 3),
+            ("10.1.0.0/16".to_string(), 1),
+            ("10.1.2.3/32".to_string(), 0),
+            ("10.2.0.0/16".to_string(), 0),
+            ("11.0.0.0/8".to_string(), 0),
+        ]);
+
+        // 같은 프리픽스와 호스트 비트만 다른 프리픽스는 하나로 셈, 0.0.0.0/0은 모두 포함
+        assert_eq!(count(&["10.0.0.5/8", "10.0.0.0/8", "0.0.0.0/0", "192.168.1.1"]), vec![
+            ("0.0.0.0/0".to_string(), 2),
+            ("10.0.0.0/8".to_string(), 0),
+            ("192.168.1.1/32".to_string(), 0),
+        ]);
+    }
+
+    #[test]
+    fn test_containment_counts_match_pairwise() {
+        // 스택 계산이 모든 쌍을 비교한 결과와 같은지
+        let prefixes: Vec<(u32, u32)> = (0..200u32)
+            .map(|i| (i.wrapping_mul(0x9E37_79B9), 8 + i % 25))
+            .collect();
+        let counts = containment_counts(prefixes.iter().copied());
+        for &(outer, contains) in &counts {
+            let expected = counts.iter()
+                .filter(|(inner, _)| *inner != outer && prefix_contains(outer, *inner))
+                .count();
+            assert_eq!(contains, expected, "{}", format_prefix(outer));
+        }
+    }
+
+    #[test]
+    fn test_longest_prefix_beats_priority() {
+        let rules = vec![rule("drop-all-10", "10.0.0.0/8", 1), rule("pass-10-1", "10.1.0.0/16", 5)];
+        let conflicts = find_conflicts(&rules);
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].broader, "drop-all-10");
+        assert_eq!(conflicts[0].narrower_prefix, "10.1.0.0/16");
+        assert_eq!(conflicts[0].winner, "pass-10-1");
+        assert!(!conflicts[0].priority_ignored);
+
+        // 넓은 프리픽스의 우선순위가 높으면 문서와 다르게 동작
+        let rules = vec![rule("drop-all-10", "10.0.0.0/8", 9), rule("pass-10-1", "10.1.0.0/16", 5)];
+        let conflicts = find_conflicts(&rules);
+        assert_eq!(conflicts[0].winner, "pass-10-1");
+        assert!(conflicts[0].priority_ignored);
+    }
+
+    #[test]
+    fn test_same_prefix_later_rule_wins() {
+        let rules = vec![rule("first", "192.168.0.0/16", 10), rule("second", "192.168.0.0/16", 1)];
+        let conflicts = find_conflicts(&rules);
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].winner, "second");
+        assert!(conflicts[0].priority_ignored);
+        assert!(conflicts[0].reason.starts_with("same prefix"));
+    }
+
+    #[test]
+    fn test_disjoint_and_unkeyed_rules() {
+        let mut any = rule("any-source", "0.0.0.0/0", 0);
+        any.src_ip = None;
+        let rules = vec![rule("a", "10.0.0.0/8", 1), rule("b", "172.16.0.0/12", 1), any];
+        assert!(find_conflicts(&rules).is_empty());
+    }
+
+    #[test]
+    fn test_priority_conflicts_for_new_rule() {
+        let existing = vec![rule("broad", "10.0.0.0/8", 50), rule("other", "10.2.0.0/16", 0)];
+        let new = rule("narrow", "10.1.0.0/16", 5);
+
+        let conflicts = priority_conflicts(&new, &existing);
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].broader, "broad");
+        assert!(priority_conflicts(&rule("narrow", "10.1.0.0/16", 60), &existing).is_empty());
+    }
+}
@@ -0,0 +1,372 @@
+//! 연결 추적 모듈
+//! conntrack_map(LRU 해시)을 읽어 추적 중인 연결 목록을 제공
+
+use anyhow::{anyhow, Result};
+use nix::time::{clock_gettime, ClockId};
+
+use crate::backend::MapBackend;
+
+use swift_guard::api::{ConnectionFilter, ConnectionInfo};
+use swift_guard::utils;
+
+/// TCP ESTABLISHED 연결 유휴 TTL (초)
+pub const TCP_ESTABLISHED_TTL: u64 = 300;
+/// 그 외 TCP 상태 유휴 TTL (초)
+pub const TCP_TRANSIENT_TTL: u64 = 60;
+/// 상태 없는 프로토콜 유휴 TTL (초)
+pub const STATELESS_TTL: u64 = 30;
+
+const NS_PER_SEC: u64 = 1_000_000_000;
+
+/// 연결 상태 (struct ct_entry.state와 일치)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnState {
+    None = 0,
+    SynSent = 1,
+    Established = 2,
+    FinWait = 3,
+    Closed = 4,
+}
+
+impl ConnState {
+    /// 숫자에서 상태 변환
+    pub fn from_u8(value: u8) -> Self {
+        match value {
+            1 => Self::SynSent,
+            2 => Self::Established,
+            3 => Self::FinWait,
+            4 => Self::Closed,
+            _ => Self::None,
+        }
+    }
+
+    /// 상태를 문자열로 변환
+    pub fn to_str(self) -> &'static str {
+        match self {
+            Self::None => "-",
+            Self::SynSent => "syn_sent",
+            Self::Established => "established",
+            Self::FinWait => "fin_wait",
+            Self::Closed => "closed",
+        }
+    }
+
+    /// 유휴 TTL (초)
+    pub fn ttl(&self) -> u64 {
+        match self {
+            Self::None => STATELESS_TTL,
+            Self::Established => TCP_ESTABLISHED_TTL,
+            _ => TCP_TRANSIENT_TTL,
+        }
+    }
+}
+
+/// 연결 키 (struct ct_key와 일치)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConnKey {
+    /// 소스 IPv4 주소 (호스트 순서)
+    pub saddr: u32,
+    /// 대상 IPv4 주소 (호스트 순서)
+    pub daddr: u32,
+    pub sport: u16,
+    pub dport: u16,
+    pub protocol: u8,
+}
+
+impl ConnKey {
+    pub const SIZE: usize = 16;
+
+    /// 맵 키 바이트에서 파싱
+    pub fn from_bytes(key: &[u8]) -> Option<Self> {
+        if key.len() < Self::SIZE {
+            return None;
+        }
+
+        Some(Self {
+            saddr: u32::from_be_bytes([key[0], key[1], key[2], key[3]]),
+            daddr: u32::from_be_bytes([key[4], key[5], key[6], key[7]]),
+            sport: u16::from_le_bytes([key[8], key[9]]),
+            dport: u16::from_le_bytes([key[10], key[11]]),
+            protocol: key[12],
+        })
+    }
+}
+
+/// 연결 값 (struct ct_entry와 일치)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConnEntry {
+    pub state: ConnState,
+    pub packets: u64,
+    pub bytes: u64,
+    /// 최초 관측 시각 (단조 시계, ns)
+    pub first_seen: u64,
+    /// 마지막 관측 시각 (단조 시계, ns)
+    pub last_seen: u64,
+}
+
+impl ConnEntry {
+    pub const SIZE: usize = 40;
+
+    /// 맵 값 바이트에서 파싱
+    pub fn from_bytes(value: &[u8]) -> Option<Self> {
+        if value.len() < Self::SIZE {
+            return None;
+        }
+
+        let read = |offset: usize| {
+            let mut buf = [0u8; 8];
+            buf.copy_from_slice(&value[offset..offset + 8]);
+            u64::from_le_bytes(buf)
+        };
+
+        Some(Self {
+            state: ConnState::from_u8(value[0]),
+            packets: read(8),
+            bytes: read(16),
+            first_seen: read(24),
+            last_seen: read(32),
+        })
+    }
+}
+
+/// 파싱된 연결 필터
+#[derive(Debug, Default)]
+struct ParsedFilter {
+    src_ip: Option<(u32, u32)>,
+    dst_ip: Option<(u32, u32)>,
+    src_port: Option<u16>,
+    dst_port: Option<u16>,
+    protocol: Option<u8>,
+}
+
+impl ParsedFilter {
+    fn parse(filter: &ConnectionFilter) -> Result<Self> {
+        Ok(Self {
+            src_ip: filter.src_ip.as_deref().map(utils::parse_ip_prefix).transpose()?,
+            dst_ip: filter.dst_ip.as_deref().map(utils::parse_ip_prefix).transpose()?,
+            src_port: filter.src_port,
+            dst_port: filter.dst_port,
+            protocol: filter.protocol.filter(|&p| p != 255),
+        })
+    }
+
+    fn matches(&self, key: &ConnKey) -> bool {
+        prefix_matches(self.src_ip, key.saddr)
+            && prefix_matches(self.dst_ip, key.daddr)
+            && self.src_port.is_none_or(|p| p == key.sport)
+            && self.dst_port.is_none_or(|p| p == key.dport)
+            && self.protocol.is_none_or(|p| p == key.protocol)
+    }
+}
+
+/// 프리픽스 매치 확인
+fn prefix_matches(prefix: Option<(u32, u32)>, addr: u32) -> bool {
+    match prefix {
+        None => true,
+        Some((_, 0)) => true,
+        Some((net, len)) => {
+            let mask = u32::MAX << (32 - len);
+            (addr & mask) == (net & mask)
+        }
+    }
+}
+
+/// 현재 단조 시계 (ns, bpf_ktime_get_ns와 같은 기준)
+pub fn monotonic_ns() -> Result<u64> {
+    let ts = clock_gettime(ClockId::CLOCK_MONOTONIC)
+        .map_err(|e| anyhow!("Failed to read monotonic clock: {}", e))?;
+
+    Ok(ts.tv_sec() as u64 * NS_PER_SEC + ts.tv_nsec() as u64)
+}
+
+/// 연결 추적 테이블
+pub struct ConntrackTable<'a> {
+    map: &'a dyn MapBackend,
+}
+
+impl<'a> ConntrackTable<'a> {
+    /// 새로운 연결 추적 테이블 생성
+    pub fn new(map: &'a dyn MapBackend) -> Self {
+        Self { map }
+    }
+
+    /// 연결 목록 조회
+    ///
+    /// 마지막 관측 시각이 최근인 순서로 최대 `limit`개(0 = 무제한)를 반환하며,
+    /// 두 번째 값은 필터에 일치하는 전체 연결 수다.
+    pub fn list(
+        &self,
+        limit: u32,
+        filter: &ConnectionFilter,
+        now_ns: u64,
+    ) -> Result<(Vec<ConnectionInfo>, u64)> {
+        let filter = ParsedFilter::parse(filter)?;
+        let mut flows = Vec::new();
+
+        for key_bytes in self.map.keys() {
+            let Some(key) = ConnKey::from_bytes(&key_bytes) else {
+                continue;
+            };
+
+            if !filter.matches(&key) {
+                continue;
+            }
+
+            // 순회 중 LRU에서 제거된 항목은 건너뜀
+            if let Some(entry) = self.map.lookup(&key_bytes)?
+                .as_deref()
+                .and_then(ConnEntry::from_bytes)
+            {
+                flows.push((key, entry));
+            }
+        }
+
+        let total = flows.len() as u64;
+        flows.sort_by_key(|flow| std::cmp::Reverse(flow.1.last_seen));
+        if limit > 0 {
+            flows.truncate(limit as usize);
+        }
+
+        let connections = flows.iter()
+            .map(|(key, entry)| to_connection_info(key, entry, now_ns))
+            .collect();
+
+        Ok((connections, total))
+    }
+}
+
+/// API 연결 정보로 변환
+fn to_connection_info(key: &ConnKey, entry: &ConnEntry, now_ns: u64) -> ConnectionInfo {
+    let idle_secs = now_ns.saturating_sub(entry.last_seen) / NS_PER_SEC;
+
+    ConnectionInfo {
+        src_ip: utils::ipv4_to_string(key.saddr),
+        src_port: key.sport,
+        dst_ip: utils::ipv4_to_string(key.daddr),
+        dst_port: key.dport,
+        protocol: utils::protocol_num_to_name(key.protocol),
+        state: entry.state.to_str().to_string(),
+        age_secs: now_ns.saturating_sub(entry.first_seen) / NS_PER_SEC,
+        idle_secs,
+        packets: entry.packets,
+        bytes: entry.bytes,
+        expired: idle_secs > entry.state.ttl(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::MemoryMap;
+
+    const SEC: u64 = NS_PER_SEC;
+
+    /// 커널이 쓰는 struct ct_key 바이트 (fixture용)
+    fn key_bytes(key: &ConnKey) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(ConnKey::SIZE);
+        bytes.extend_from_slice(&key.saddr.to_be_bytes());
+        bytes.extend_from_slice(&key.daddr.to_be_bytes());
+        bytes.extend_from_slice(&key.sport.to_le_bytes());
+        bytes.extend_from_slice(&key.dport.to_le_bytes());
+        bytes.push(key.protocol);
+        bytes.extend_from_slice(&[0u8; 3]);
+        bytes
+    }
+
+    /// 커널이 쓰는 struct ct_entry 바이트 (fixture용)
+    fn entry_bytes(entry: &ConnEntry) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(ConnEntry::SIZE);
+        bytes.push(entry.state as u8);
+        bytes.extend_from_slice(&[0u8; 7]);
+        bytes.extend_from_slice(&entry.packets.to_le_bytes());
+        bytes.extend_from_slice(&entry.bytes.to_le_bytes());
+        bytes.extend_from_slice(&entry.first_seen.to_le_bytes());
+        bytes.extend_from_slice(&entry.last_seen.to_le_bytes());
+        bytes
+    }
+
+    fn insert(map: &MemoryMap, saddr: u32, dport: u16, protocol: u8, state: ConnState, last_seen: u64) {
+        let key = ConnKey {
+            saddr,
+            daddr: 0x0A000001,
+            sport: 40000,
+            dport,
+            protocol,
+        };
+        let entry = ConnEntry {
+            state,
+            packets: 10,
+            bytes: 1500,
+            first_seen: 100 * SEC,
+            last_seen,
+        };
+        map.update(&key_bytes(&key), &entry_bytes(&entry)).unwrap();
+    }
+
+    fn table() -> MemoryMap {
+        let map = MemoryMap::new();
+        insert(&map, 0xC0A80101, 443, 6, ConnState::Established, 1000 * SEC);
+        insert(&map, 0xC0A80102, 443, 6, ConnState::SynSent, 900 * SEC);
+        insert(&map, 0xC0A80201, 53, 17, ConnState::None, 990 * SEC);
+        map
+    }
+
+    #[test]
+    fn test_key_entry_roundtrip() {
+        let key = ConnKey { saddr: 0xC0A80101, daddr: 0x0A000001, sport: 1234, dport: 80, protocol: 6 };
+        let bytes = key_bytes(&key);
+        assert_eq!(bytes.len(), ConnKey::SIZE);
+        assert_eq!(&bytes[0..4], &[192, 168, 1, 1]);
+        assert_eq!(ConnKey::from_bytes(&bytes), Some(key));
+
+        let entry = ConnEntry {
+            state: ConnState::FinWait,
+            packets: 3,
+            bytes: 180,
+            first_seen: 5,
+            last_seen: 9,
+        };
+        assert_eq!(ConnEntry::from_bytes(&entry_bytes(&entry)), Some(entry));
+        assert!(ConnEntry::from_bytes(&[0u8; 16]).is_none());
+    }
+
+    #[test]
+    fn test_list_filter_and_order() {
+        let map = table();
+        let conntrack = ConntrackTable::new(&map);
+
+        let (all, total) = conntrack.list(0, &ConnectionFilter::default(), 1000 * SEC).unwrap();
+        assert_eq!(total, 3);
+        assert_eq!(all[0].src_ip, "192.168.1.1");
+        assert_eq!(all[1].src_ip, "192.168.2.1");
+
+        let filter = ConnectionFilter { dst_port: Some(443), ..Default::default() };
+        let (https, total) = conntrack.list(0, &filter, 1000 * SEC).unwrap();
+        assert_eq!(total, 2);
+        assert!(https.iter().all(|c| c.dst_port == 443 && c.protocol == "tcp"));
+
+        let filter = ConnectionFilter { src_ip: Some("192.168.1.0/24".to_string()), ..Default::default() };
+        let (limited, total) = conntrack.list(1, &filter, 1000 * SEC).unwrap();
+        assert_eq!(total, 2);
+        assert_eq!(limited.len(), 1);
+        assert_eq!(limited[0].state, "established");
+    }
+
+    #[test]
+    fn test_ttl_expiry_display() {
+        let map = table();
+        let conntrack = ConntrackTable::new(&map);
+
+        let (connections, _) = conntrack.list(0, &ConnectionFilter::default(), 1000 * SEC).unwrap();
+        let syn_sent = connections.iter().find(|c| c.state == "syn_sent").unwrap();
+        assert_eq!(syn_sent.idle_secs, 100);
+        assert!(syn_sent.expired);
+
+        let established = connections.iter().find(|c| c.state == "established").unwrap();
+        assert_eq!(established.age_secs, 900);
+        assert!(!established.expired);
+
+        let udp = connections.iter().find(|c| c.protocol == "udp").unwrap();
+        assert!(!udp.expired);
+    }
+}
@@ -0,0 +1,108 @@
+//! 명명된 카운터 모듈
+//! count 액션 규칙의 통계를 카운터 이름별로 합산 (`GetCounters`)
+//!
+//! 카운터 이름은 규칙의 `counter_group`이고, 그룹이 없는 규칙은 레이블이 곧 카운터
+//! 이름이다. 같은 그룹의 규칙은 패킷, 바이트, 초당 패킷 수를 더해 하나로 보여준다.
+
+use std::collections::BTreeMap;
+
+use swift_guard::api::{CounterInfo, RuleInfo};
+
+/// count 액션 규칙을 카운터 이름별로 합산 (이름 순)
+///
+/// `prefix`가 있으면 이름이 그 문자열로 시작하는 카운터만 돌려준다.
+pub fn aggregate(rules: &[RuleInfo], prefix: Option<&str>) -> Vec<CounterInfo> {
+    let mut counters: BTreeMap<&str, CounterInfo> = BTreeMap::new();
+
+    for rule in rules.iter().filter(|rule| rule.action == "count") {
+        let name = rule.counter_group.as_deref().unwrap_or(&rule.label);
+        if prefix.is_some_and(|prefix| !name.starts_with(prefix)) {
+            continue;
+        }
+
+        let counter = counters.entry(name).or_insert_with(|| CounterInfo {
+            name: name.to_string(),
+            rules: Vec::new(),
+            packets: 0,
+            bytes: 0,
+            pps: None,
+        });
+        counter.rules.push(rule.label.clone());
+        counter.packets = counter.packets.saturating_add(rule.stats.packets);
+        counter.bytes = counter.bytes.saturating_add(rule.stats.bytes);
+        if let Some(pps) = rule.stats.pps {
+            counter.pps = Some(counter.pps.unwrap_or(0).saturating_add(pps));
+        }
+    }
+
+    counters.into_values()
+        .map(|mut counter| {
+            counter.rules.sort();
+            counter
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use swift_guard::api::RuleStats;
+
+    fn rule(label: &str, action: &str, group: Option<&str>, packets: u64, pps: Option<u64>) -> RuleInfo {
+        RuleInfo {
+            label: label.to_string(),
+            action: action.to_string(),
+            src_ip: Some("10.0.0.0/8".to_string()),
+            dst_port: Some("80".to_string()),
+            protocol: "tcp".to_string(),
+            counter_group: group.map(str::to_string),
+            stats: RuleStats { packets, bytes: packets * 100, pps, ..RuleStats::default() },
+            ..RuleInfo::default()
+        }
+    }
+
+    fn rules() -> Vec<RuleInfo> {
+        vec![
+            rule("web-https", "count", Some("web-total"), 30, Some(3)),
+            rule("db", "count", None, 5, None),
+            rule("web-http", "count", Some("web-total"), 10, None),
+            rule("web-api", "count", None, 7, Some(1)),
+            rule("web-block", "drop", Some("web-total"), 1000, Some(100)),
+            rule("api-legacy", "count", Some("web-total"), 2, Some(2)),
+        ]
+    }
+
+    #[test]
+    fn test_aggregate_groups_count_rules() {
+        let counters = aggregate(&rules(), None);
+        let names: Vec<&str> = counters.iter().map(|counter| counter.name.as_str()).collect();
+        assert_eq!(names, ["db", "web-api", "web-total"]);
+
+        // 그룹 규칙은 합산하고 count가 아닌 규칙은 그룹이 있어도 제외
+        let total = &counters[2];
+        assert_eq!(total.rules, ["api-legacy", "web-http", "web-https"]);
+        assert_eq!((total.packets, total.bytes), (42, 4200));
+        assert_eq!(total.pps, Some(5));
+
+        // 그룹이 없는 규칙은 레이블이 카운터 이름이고, 관측 전이면 pps 없음
+        assert_eq!(counters[0], CounterInfo {
+            name: "db".to_string(),
+            rules: vec!["db".to_string()],
+            packets: 5,
+            bytes: 500,
+            pps: None,
+        });
+    }
+
+    #[test]
+    fn test_aggregate_filters_by_prefix() {
+        // 접두사는 규칙 레이블이 아니라 카운터 이름에 적용
+        let counters = aggregate(&rules(), Some("web-"));
+        let names: Vec<&str> = counters.iter().map(|counter| counter.name.as_str()).collect();
+        assert_eq!(names, ["web-api", "web-total"]);
+        assert_eq!(counters[1].rules.len(), 3);
+
+        assert!(aggregate(&rules(), Some("mail")).is_empty());
+        assert!(aggregate(&[], None).is_empty());
+    }
+}
@@ -0,0 +1,530 @@
+//! 국가 차단 모듈
+//! 국가의 IPv4 프리픽스 집합을 소스로 하는 규칙 설치, 갱신, 삭제 (`BlockCountry`)
+//!
+//! 프리픽스는 GeoIP 국가 데이터베이스나 운영자가 준 `<CIDR>,<국가 코드>` CSV 파일에서
+//! 얻는다. 집합과 규칙은 모두 `country-<코드>`라는 이름을 쓰고, 집합은 규칙에 붙어 있으므로
+//! 규칙을 지우면 집합도 사라진다. 집합에는 원본과 데이터 버전을 남겨 두어, 갱신할 때 같은
+//! 원본에서 다시 만들고 바뀐 것이 없으면 커널 맵을 건드리지 않는다.
+
+use anyhow::{anyhow, Context, Result};
+use log::info;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeSet;
+use std::fs;
+
+use swift_guard::api::{BpfMapInfo, CountryBlockInfo, RuleChangeKind, RuleSpec};
+use swift_guard::error::InvalidArgument;
+use swift_guard::utils;
+
+use crate::error::DaemonError;
+use crate::geoip::GeoResolver;
+use crate::ipset::PrefixSet;
+use crate::maps::{FilterRule, MapManager};
+use crate::ruleset;
+
+/// 국가 집합과 규칙 이름의 접두사
+const SET_PREFIX: &str = "country-";
+
+/// 프리픽스를 GeoIP 국가 데이터베이스에서 얻은 집합의 원본 이름
+pub const GEOIP_SOURCE: &str = "geoip";
+
+/// 국가 차단 규칙의 우선순위
+const COUNTRY_PRIORITY: u32 = 100;
+
+/// LPM 규칙 맵 크기 (BPF 정보가 없을 때, xdp_filter.c의 MAX_FILTER_RULES)
+const DEFAULT_MAP_CAPACITY: usize = 10240;
+
+/// 국가 차단 요청 (검증한 국가 코드와 만든 집합)
+#[derive(Debug, Clone)]
+pub struct Block {
+    pub code: String,
+    pub action: u8,
+    pub expire: u32,
+    pub set: PrefixSet,
+}
+
+/// 국가 코드 검증 (ISO 3166-1 alpha-2, 대문자로 바꿔 돌려줌)
+pub fn parse_code(code: &str) -> Result<String, InvalidArgument> {
+    let code = code.trim();
+    if code.len() != 2 || !code.chars().all(|c| c.is_ascii_alphabetic()) {
+        return Err(InvalidArgument::new("code", format!("'{}' is not a two-letter ISO 3166-1 country code", code)));
+    }
+    Ok(code.to_ascii_uppercase())
+}
+
+/// 액션 검증 (pass, drop, count만 허용)
+pub fn validate_action(action: u8) -> Result<(), InvalidArgument> {
+    match action {
+        1 | 2 | 4 => Ok(()),
+        _ => Err(InvalidArgument::new("action", format!(
+            "Country rules support pass, drop and count (got {})", utils::action_num_to_name(action)))),
+    }
+}
+
+/// 국가의 집합/규칙 이름
+pub fn set_name(code: &str) -> String {
+    format!("{}{}", SET_PREFIX, code)
+}
+
+/// 규칙이 국가 차단 규칙이면 국가 코드 (레이블과 집합 이름이 같은 규칙)
+fn country_code(rule: &FilterRule) -> Option<&str> {
+    let set = rule.src_set.as_ref()?;
+    let code = set.name.strip_prefix(SET_PREFIX)?;
+    (utils::split_label(&rule.label).1 == set.name).then_some(code)
+}
+
+/// 원본에서 국가의 프리픽스 집합 생성 (`source`는 `GEOIP_SOURCE` 또는 CSV 파일 경로)
+///
+/// 데이터베이스 전체를 훑거나 파일을 읽으므로 잠금 밖에서 호출한다.
+pub fn derive_set(code: &str, source: &str, geo: Option<&dyn GeoResolver>) -> Result<PrefixSet> {
+    let (prefixes, version) = if source == GEOIP_SOURCE {
+        let geo = geo.ok_or_else(|| DaemonError::Config("GeoIP lookups are not enabled (geoip.mmdb_path)".to_string()))?;
+        geo.country_prefixes(code)?
+    } else {
+        let content = fs::read_to_string(source)
+            .map_err(|e| DaemonError::io(format!("Failed to read {}", source), e))?;
+        let prefixes = parse_csv(&content, code)
+            .map_err(|e| DaemonError::Validation(InvalidArgument::new("csv_path", format!("{}: {:#}", source, e))))?;
+        (prefixes, content_version(&content))
+    };
+
+    if prefixes.is_empty() {
+        return Err(DaemonError::Validation(InvalidArgument::new("code",
+            format!("No IPv4 prefixes found for country {} in {}", code, source))).into());
+    }
+    Ok(PrefixSet::new(&set_name(code), prefixes, source, &version))
+}
+
+/// `<CIDR>,<국가 코드>[,...]` 형식에서 국가의 IPv4 프리픽스 추출
+///
+/// 빈 줄, `#` 주석, 첫 줄의 머리글(`network,...`)과 IPv6 프리픽스는 건너뛴다.
+fn parse_csv(content: &str, code: &str) -> Result<Vec<(u32, u32)>> {
+    let mut prefixes = Vec::new();
+    for (index, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || (index == 0 && line.starts_with("network")) {
+            continue;
+        }
+
+        let mut fields = line.split(',').map(str::trim);
+        let (prefix, country) = match (fields.next(), fields.next()) {
+            (Some(prefix), Some(country)) if !prefix.is_empty() => (prefix, country),
+            _ => return Err(anyhow!("line {}: expected <cidr>,<country code>", index + 1)),
+        };
+        if prefix.contains(':') || !country.eq_ignore_ascii_case(code) {
+            continue;
+        }
+
+        let prefix = utils::parse_ip_prefix(prefix)
+            .with_context(|| format!("line {}: invalid prefix '{}'", index + 1, prefix))?;
+        prefixes.push(prefix);
+    }
+    Ok(prefixes)
+}
+
+/// 파일 내용 버전 (SHA-256 앞 12자리)
+fn content_version(content: &str) -> String {
+    let hex: String = Sha256::digest(content.as_bytes()).iter()
+        .take(6)
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+    format!("sha256:{}", hex)
+}
+
+/// LPM 규칙 맵 크기 (로드한 BPF 오브젝트의 filter_rules 맵)
+pub fn map_capacity(maps: Option<&[BpfMapInfo]>) -> usize {
+    maps.and_then(|maps| maps.iter().find(|map| map.name == "filter_rules"))
+        .map(|map| map.max_entries as usize)
+        .unwrap_or(DEFAULT_MAP_CAPACITY)
+}
+
+/// `key` 규칙의 소스를 `set`으로 바꿀 때 LPM 맵 항목 수 (교체 중 최대, 교체 후)
+///
+/// 규칙 교체는 새 항목을 먼저 기록하고 이전 항목을 지우므로, 교체하는 동안에는 이전
+/// 집합에만 있는 항목도 맵에 남아 있다. 여러 규칙이 같은 프리픽스를 쓰면 항목 하나로 센다.
+fn map_usage(map_manager: &MapManager, key: &str, set: &PrefixSet) -> (usize, usize) {
+    let mut others = BTreeSet::new();
+    let mut replaced = BTreeSet::new();
+    for rule in map_manager.rules().iter() {
+        let prefixes = rule.src_prefixes().into_iter()
+            .map(|(addr, prefix_len)| (utils::mask_ipv4(addr, prefix_len), prefix_len));
+        if rule.label == key {
+            replaced.extend(prefixes);
+        } else {
+            others.extend(prefixes);
+        }
+    }
+
+    others.extend(set.prefixes.iter().copied());
+    let peak = others.len() + replaced.difference(&others).count();
+    (peak, others.len())
+}
+
+/// 규칙 설치 또는 교체 (맵 크기를 넘으면 바꾸지 않음)
+fn install(map_manager: &mut MapManager, rule: FilterRule, capacity: usize) -> Result<CountryBlockInfo, DaemonError> {
+    let (Some(code), Some(set)) = (country_code(&rule).map(str::to_string), rule.src_set.clone()) else {
+        return Err(DaemonError::Validation(InvalidArgument::new("src_set", format!("{} is not a country rule", rule.label))));
+    };
+    let (peak, after) = map_usage(map_manager, &rule.label, &set);
+    if peak > capacity {
+        return Err(DaemonError::Map(anyhow!(
+            "Country {} needs {} filter_rules entries in total but the map holds {}", code, peak, capacity)));
+    }
+
+    let change = match map_manager.rules().get(&rule.label) {
+        Some(current) if unchanged(current, &rule) => None,
+        Some(_) => {
+            map_manager.update_rule(rule.clone())?;
+            Some(RuleChangeKind::Updated)
+        },
+        None => {
+            map_manager.add_rule(rule.clone())?;
+            Some(RuleChangeKind::Added)
+        },
+    };
+
+    if let Some(kind) = change {
+        info!("Country rule {} {}: {} prefixes ({} addresses) from {} {}, {}/{} filter_rules entries",
+            rule.label, kind.as_str(), set.prefixes.len(), set.address_count(), set.source, set.version,
+            after, capacity);
+    }
+    Ok(block_info(code, &rule, &set, after, capacity, change))
+}
+
+/// 결과 정보 (`map_entries`는 변경 후 LPM 맵 항목 수)
+fn block_info(
+    code: String,
+    rule: &FilterRule,
+    set: &PrefixSet,
+    map_entries: usize,
+    map_capacity: usize,
+    change: Option<RuleChangeKind>,
+) -> CountryBlockInfo {
+    CountryBlockInfo {
+        code,
+        label: utils::split_label(&rule.label).1.to_string(),
+        action: utils::action_num_to_name(rule.action),
+        set: set.to_info(),
+        change,
+        error: None,
+        map_entries,
+        map_capacity,
+    }
+}
+
+/// 규칙 정의와 집합이 모두 같은지
+fn unchanged(current: &FilterRule, desired: &FilterRule) -> bool {
+    ruleset::same_definition(current, desired) && current.src_set == desired.src_set
+}
+
+/// 국가 차단 규칙 설치 (같은 국가의 규칙이 있으면 액션, 만료, 집합 갱신)
+///
+/// 같은 레이블의 규칙이 국가 차단 규칙이 아니면 덮어쓰지 않는다.
+pub fn block(
+    map_manager: &mut MapManager,
+    block: Block,
+    namespace: &str,
+    created_by: &str,
+    now: u64,
+    capacity: usize,
+) -> Result<CountryBlockInfo, DaemonError> {
+    validate_action(block.action)?;
+
+    let name = set_name(&block.code);
+    let spec = RuleSpec {
+        label: name.clone(),
+        src_ip: None,
+        dst_ip: None,
+        src_port_min: 0,
+        src_port_max: 65535,
+        dst_port_min: 0,
+        dst_port_max: 65535,
+        protocol: 0,
+        tcp_flags: 0,
+        action: block.action,
+        redirect_if: None,
+        redirect_cpu: None,
+        priority: COUNTRY_PRIORITY,
+        rate_limit: 0,
+        expire: block.expire,
+        description: Some(format!("Traffic from country {} ({})", block.code, block.set.source)),
+        quota_bytes: None,
+        quota_packets: None,
+        quota_action: None,
+        counter_group: None,
+        src_port_list: Vec::new(),
+        dst_port_list: Vec::new(),
+        owner_webhook: None,
+        notify_before: None,
+    };
+    let mut rule = ruleset::build_set_rule(spec, block.set, Some(created_by.to_string()), now)?;
+    rule.label = utils::qualify_label(namespace, &name);
+
+    if map_manager.rules().get(&rule.label).is_some_and(|existing| country_code(existing).is_none()) {
+        return Err(DaemonError::DuplicateLabel(rule.label));
+    }
+    install(map_manager, rule, capacity)
+}
+
+/// 이 네임스페이스의 국가 차단 규칙 (국가 코드, 집합 원본), `code`가 있으면 그 국가만
+pub fn sources(map_manager: &MapManager, namespace: &str, code: Option<&str>) -> Vec<(String, String)> {
+    let mut sources: Vec<(String, String)> = map_manager.rules().iter()
+        .filter(|rule| utils::split_label(&rule.label).0 == namespace)
+        .filter_map(|rule| Some((country_code(rule)?.to_string(), rule.src_set.as_ref()?.source.clone())))
+        .filter(|(found, _)| code.is_none() || code == Some(found.as_str()))
+        .collect();
+    sources.sort();
+    sources
+}
+
+/// 다시 만든 집합으로 국가 차단 규칙 갱신 (`derived`는 `sources` 순서의 결과)
+///
+/// 원본 버전과 프리픽스가 같으면 그대로 두고, 집합을 만들지 못했거나 설치에 실패한
+/// 규칙은 이전 집합을 유지한 채 오류를 결과에 남긴다.
+pub fn refresh(
+    map_manager: &mut MapManager,
+    derived: Vec<(String, Result<PrefixSet>)>,
+    namespace: &str,
+    capacity: usize,
+) -> Vec<CountryBlockInfo> {
+    derived.into_iter()
+        .filter_map(|(code, set)| {
+            let key = utils::qualify_label(namespace, &set_name(&code));
+            let current = map_manager.rules().get(&key).filter(|rule| country_code(rule).is_some())?.clone();
+            let current_set = current.src_set.clone()?;
+
+            let result = set.and_then(|set| {
+                install(map_manager, FilterRule { src_set: Some(set), ..current.clone() }, capacity)
+                    .map_err(anyhow::Error::from)
+            });
+            Some(result.unwrap_or_else(|e| {
+                let (_, entries) = map_usage(map_manager, &key, &current_set);
+                CountryBlockInfo {
+                    error: Some(format!("{:#}", e)),
+                    ..block_info(code, &current, &current_set, entries, capacity, None)
+                }
+            }))
+        })
+        .collect()
+}
+
+/// 국가 차단 규칙과 집합 삭제
+pub fn remove(map_manager: &mut MapManager, code: &str, namespace: &str) -> Result<(), DaemonError> {
+    let key = utils::qualify_label(namespace, &set_name(code));
+    if map_manager.rules().get(&key).and_then(country_code).is_none() {
+        return Err(DaemonError::RuleNotFound(key));
+    }
+
+    map_manager.delete_rule(&key)?;
+    info!("Country rule {} removed", key);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::MemoryMap;
+    use crate::geoip::tests::stub;
+    use crate::maps::MapBackends;
+
+    const CSV: &str = "\
+network,country_iso_code
+# 테스트 데이터
+10.1.0.0/24,RU
+10.1.1.0/24,ru,Russia
+10.1.0.128/25,RU
+2001:db8::/32,RU
+192.0.2.0/24,KR
+";
+
+    fn manager<'a>(filter_rules: &'a MemoryMap, wildcard_rules: &'a MemoryMap) -> MapManager<'a> {
+        MapManager::with_backends(MapBackends {
+            filter_rules: Some(filter_rules),
+            wildcard: Some(wildcard_rules),
+            ..Default::default()
+        })
+    }
+
+    fn set(code: &str, prefixes: &[(u32, u32)], version: &str) -> PrefixSet {
+        PrefixSet::new(&set_name(code), prefixes.to_vec(), GEOIP_SOURCE, version)
+    }
+
+    fn request(code: &str, set: PrefixSet) -> Block {
+        Block { code: code.to_string(), action: 2, expire: 0, set }
+    }
+
+    #[test]
+    fn test_parse_code_and_action() {
+        assert_eq!(parse_code(" ru ").unwrap(), "RU");
+        for code in ["", "R", "RUS", "R1", "한국"] {
+            assert_eq!(parse_code(code).unwrap_err().field, "code", "{}", code);
+        }
+        assert!(validate_action(2).is_ok() && validate_action(4).is_ok());
+        assert_eq!(validate_action(3).unwrap_err().field, "action");
+    }
+
+    #[test]
+    fn test_parse_csv() {
+        // 대소문자 구분 없는 코드 비교, 머리글/주석/IPv6 건너뜀, 중복은 집합에서 정규화
+        let prefixes = parse_csv(CSV, "RU").unwrap();
+        assert_eq!(prefixes, [(0x0A010000, 24), (0x0A010100, 24), (0x0A010080, 25)]);
+        assert_eq!(PrefixSet::new("country-RU", prefixes, "x", "v").prefixes, [(0x0A010000, 23)]);
+        assert_eq!(parse_csv(CSV, "KR").unwrap(), [(0xC0000200, 24)]);
+        assert!(parse_csv(CSV, "DE").unwrap().is_empty());
+
+        // 오류는 줄 번호와 함께
+        let err = parse_csv("10.0.0.0/8,RU\n10.0.0.0/33,RU\n", "RU").unwrap_err();
+        assert!(format!("{:#}", err).starts_with("line 2: invalid prefix"), "{:#}", err);
+        let err = parse_csv("10.0.0.0/8\n", "RU").unwrap_err();
+        assert_eq!(err.to_string(), "line 1: expected <cidr>,<country code>");
+    }
+
+    #[test]
+    fn test_derive_set() {
+        let dir = std::env::temp_dir().join(format!("swift-guard-country-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("ru.csv");
+        fs::write(&path, CSV).unwrap();
+        let path = path.to_string_lossy().into_owned();
+
+        let set = derive_set("RU", &path, None).unwrap();
+        assert_eq!((set.name.as_str(), set.prefixes.len(), set.source.as_str()), ("country-RU", 1, path.as_str()));
+        assert_eq!(set.version, content_version(CSV));
+        assert!(set.version.starts_with("sha256:") && set.version.len() == 19);
+
+        // 원본에 국가가 없거나 GeoIP가 꺼져 있으면 오류
+        assert!(derive_set("DE", &path, None).unwrap_err().to_string().contains("No IPv4 prefixes found for country DE"));
+        assert!(derive_set("RU", GEOIP_SOURCE, None).unwrap_err().to_string().contains("not enabled"));
+
+        let geo = stub(&[("198.51.100.7", "RU", 1), ("198.51.100.6", "RU", 1), ("203.0.113.1", "KR", 2)]);
+        let set = derive_set("RU", GEOIP_SOURCE, Some(&geo)).unwrap();
+        assert_eq!((set.prefixes.as_slice(), set.version.as_str()), (&[(0xC6336406, 31)][..], "stub"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_block_update_and_remove() {
+        let (filter_rules, wildcard_rules) = (MemoryMap::new(), MemoryMap::new());
+        let mut manager = manager(&filter_rules, &wildcard_rules);
+
+        let info = block(&mut manager, request("RU", set("RU", &[(0x0A000000, 24), (0x0A000100, 24)], "v1")),
+            "default", "ops", 1000, 100).unwrap();
+        assert_eq!((info.label.as_str(), info.action.as_str(), info.change), ("country-RU", "drop", Some(RuleChangeKind::Added)));
+        assert_eq!((info.set.entries, info.set.addresses, info.map_entries, info.map_capacity), (1, 512, 1, 100));
+        assert_eq!(filter_rules.len(), 1);
+
+        // 같은 요청은 그대로, 액션이나 집합이 바뀌면 제자리 갱신
+        let again = block(&mut manager, request("RU", set("RU", &[(0x0A000000, 23)], "v1")), "default", "ops", 2000, 100).unwrap();
+        assert_eq!(again.change, None);
+        let count = Block { action: 4, ..request("RU", set("RU", &[(0x0A000000, 24), (0x0B000000, 24)], "v2")) };
+        let updated = block(&mut manager, count, "default", "ops", 3000, 100).unwrap();
+        assert_eq!((updated.change, updated.action.as_str(), updated.set.version.as_str()),
+            (Some(RuleChangeKind::Updated), "count", "v2"));
+        assert_eq!(manager.rules().get("country-RU").unwrap().creation_time, 1000);
+        assert_eq!(filter_rules.len(), 2);
+
+        // 삭제하면 규칙과 집합의 커널 항목이 모두 사라짐
+        assert!(matches!(remove(&mut manager, "KR", "default"), Err(DaemonError::RuleNotFound(_))));
+        remove(&mut manager, "RU", "default").unwrap();
+        assert!(manager.rules().iter().next().is_none());
+        assert_eq!(filter_rules.len(), 0);
+    }
+
+    #[test]
+    fn test_block_checks_capacity_and_foreign_labels() {
+        let (filter_rules, wildcard_rules) = (MemoryMap::new(), MemoryMap::new());
+        let mut manager = manager(&filter_rules, &wildcard_rules);
+
+        // 다른 규칙의 항목도 셈
+        let other = ruleset::build_rule(RuleSpec {
+            label: "web".to_string(),
+            src_ip: Some("10.9.0.0/24".to_string()),
+            ..request_spec()
+        }, None, 0, |_| Ok(0)).unwrap();
+        manager.add_rule(other).unwrap();
+
+        let three = set("RU", &[(0x0A000000, 24), (0x0B000000, 24), (0x0C000000, 24)], "v1");
+        let err = block(&mut manager, request("RU", three.clone()), "default", "ops", 0, 2).unwrap_err();
+        assert!(err.to_string().contains("needs 4 filter_rules entries in total but the map holds 2"), "{}", err);
+        assert!(!manager.has_rule("country-RU"));
+        assert_eq!(block(&mut manager, request("RU", three), "default", "ops", 0, 4).unwrap().map_entries, 4);
+
+        // 다른 규칙이 쓰는 프리픽스는 덮어쓰지 않음
+        let taken = set("CN", &[(0x0A090000, 24)], "v1");
+        assert!(matches!(block(&mut manager, request("CN", taken), "default", "ops", 0, 100),
+            Err(DaemonError::PrefixConflict(_))));
+
+        // 교체 중에는 이전 집합의 항목도 남아 있음
+        let moved = set("RU", &[(0x0D000000, 24), (0x0E000000, 24)], "v2");
+        assert!(block(&mut manager, request("RU", moved.clone()), "default", "ops", 0, 5).is_err());
+        let info = block(&mut manager, request("RU", moved), "default", "ops", 0, 6).unwrap();
+        assert_eq!(info.map_entries, 3);
+
+        // 국가 차단 규칙이 아닌 같은 레이블의 규칙은 덮어쓰지 않음
+        let own = ruleset::build_rule(RuleSpec { label: "country-KR".to_string(), ..request_spec() }, None, 0, |_| Ok(0)).unwrap();
+        manager.add_rule(own).unwrap();
+        let err = block(&mut manager, request("KR", set("KR", &[(0xC0000200, 24)], "v1")), "default", "ops", 0, 100).unwrap_err();
+        assert!(matches!(err, DaemonError::DuplicateLabel(_)));
+        assert!(matches!(remove(&mut manager, "KR", "default"), Err(DaemonError::RuleNotFound(_))));
+    }
+
+    #[test]
+    fn test_refresh() {
+        let (filter_rules, wildcard_rules) = (MemoryMap::new(), MemoryMap::new());
+        let mut manager = manager(&filter_rules, &wildcard_rules);
+        block(&mut manager, request("RU", set("RU", &[(0x0A000000, 24)], "v1")), "default", "ops", 0, 100).unwrap();
+        block(&mut manager, request("KR", set("KR", &[(0xC0000200, 24)], "v1")), "default", "ops", 0, 100).unwrap();
+        block(&mut manager, request("CN", set("CN", &[(0x01000000, 24)], "v1")), "team", "ops", 0, 100).unwrap();
+
+        assert_eq!(sources(&manager, "default", None),
+            [("KR".to_string(), GEOIP_SOURCE.to_string()), ("RU".to_string(), GEOIP_SOURCE.to_string())]);
+        assert_eq!(sources(&manager, "default", Some("RU")).len(), 1);
+        assert!(sources(&manager, "team", Some("RU")).is_empty());
+
+        // 바뀐 집합만 갱신하고, 만들지 못한 집합은 이전 집합을 유지한 채 오류 보고
+        let results = refresh(&mut manager, vec![
+            ("KR".to_string(), Err(anyhow!("database unavailable"))),
+            ("RU".to_string(), Ok(set("RU", &[(0x0A000000, 24), (0x0A000100, 24)], "v2"))),
+        ], "default", 100);
+        assert_eq!(results.len(), 2);
+        assert_eq!((results[0].change, results[0].error.as_deref()), (None, Some("database unavailable")));
+        assert_eq!(results[0].set.version, "v1");
+        assert_eq!((results[1].change, results[1].set.entries), (Some(RuleChangeKind::Updated), 1));
+        assert_eq!(manager.rules().get("country-RU").unwrap().src_prefixes(), [(0x0A000000, 23)]);
+
+        let results = refresh(&mut manager, vec![
+            ("RU".to_string(), Ok(set("RU", &[(0x0A000000, 23)], "v2"))),
+        ], "default", 100);
+        assert_eq!(results[0].change, None);
+    }
+
+    fn request_spec() -> RuleSpec {
+        RuleSpec {
+            label: String::new(),
+            src_ip: None,
+            dst_ip: None,
+            src_port_min: 0,
+            src_port_max: 65535,
+            dst_port_min: 0,
+            dst_port_max: 65535,
+            protocol: 6,
+            tcp_flags: 0,
+            action: 2,
+            redirect_if: None,
+            redirect_cpu: None,
+            priority: 0,
+            rate_limit: 0,
+            expire: 0,
+            description: None,
+            quota_bytes: None,
+            quota_packets: None,
+            quota_action: None,
+            counter_group: None,
+            src_port_list: Vec::new(),
+            dst_port_list: Vec::new(),
+            owner_webhook: None,
+            notify_before: None,
+        }
+    }
+}
@@ -0,0 +1,236 @@
+//! DNS 해석 모듈
+//! 호스트 이름으로 지정한 규칙 엔드포인트를 IPv4 주소 집합으로 해석하고 주기적으로 갱신
+//!
+//! 호스트 이름 규칙은 A 레코드마다 /32 LPM 항목을 하나씩 같은 레이블로 설치한다.
+//! 갱신 주기마다 다시 해석해 바뀐 주소만 항목을 추가하거나 삭제하며, 해석에
+//! 실패하면 마지막으로 해석한 항목을 그대로 두고 규칙을 degraded로 표시한다.
+//! 해석은 맵 관리자 잠금 밖에서 하고, 결과 반영만 잠금 안에서 한다.
+
+use anyhow::{anyhow, Context, Result};
+use hickory_resolver::TokioAsyncResolver;
+use log::{debug, warn};
+use std::collections::{BTreeSet, HashMap};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::maps::MapManager;
+
+use swift_guard::api::HostInfo;
+use swift_guard::utils;
+
+/// 호스트 이름 최대 길이 (RFC 1035)
+const MAX_HOSTNAME_LEN: usize = 253;
+
+/// 호스트 이름으로 지정한 엔드포인트
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HostEndpoint {
+    /// 호스트 이름
+    pub name: String,
+    /// 마지막으로 해석한 IPv4 주소 (호스트 순서, 정렬·중복 제거)
+    pub addrs: Vec<u32>,
+    /// 마지막 해석 실패 원인 (있으면 이전 주소를 유지하는 중)
+    pub error: Option<String>,
+}
+
+impl HostEndpoint {
+    /// 해석 결과로 생성 (주소는 정렬하고 중복 제거)
+    pub fn new(name: &str, addrs: Vec<u32>) -> Self {
+        Self {
+            name: name.to_string(),
+            addrs: normalize_addrs(addrs),
+            error: None,
+        }
+    }
+
+    /// 주소별 /32 프리픽스
+    pub fn prefixes(&self) -> Vec<(u32, u32)> {
+        self.addrs.iter().map(|&addr| (addr, 32)).collect()
+    }
+
+    /// 해석에 실패해 마지막으로 해석한 주소를 쓰는 중인지
+    pub fn is_stale(&self) -> bool {
+        self.error.is_some()
+    }
+
+    /// API 호스트 정보로 변환
+    pub fn to_info(&self) -> HostInfo {
+        HostInfo {
+            name: self.name.clone(),
+            addrs: self.addrs.iter().map(|&addr| utils::ipv4_to_string(addr)).collect(),
+            error: self.error.clone(),
+        }
+    }
+}
+
+/// 해석한 주소 집합의 변경
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AddrDiff {
+    /// 새로 나타난 주소
+    pub added: Vec<u32>,
+    /// 사라진 주소
+    pub removed: Vec<u32>,
+}
+
+impl AddrDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// 이전 주소 집합과 새 주소 집합의 차이
+pub fn diff_addrs(old: &[u32], new: &[u32]) -> AddrDiff {
+    let old: BTreeSet<u32> = old.iter().copied().collect();
+    let new: BTreeSet<u32> = new.iter().copied().collect();
+
+    AddrDiff {
+        added: new.difference(&old).copied().collect(),
+        removed: old.difference(&new).copied().collect(),
+    }
+}
+
+/// 주소 정렬 및 중복 제거
+pub fn normalize_addrs(addrs: Vec<u32>) -> Vec<u32> {
+    addrs.into_iter().collect::<BTreeSet<u32>>().into_iter().collect()
+}
+
+/// 주소 목록 표기 ("10.0.0.1, 10.0.0.2")
+pub fn format_addrs(addrs: &[u32]) -> String {
+    addrs.iter().map(|&addr| utils::ipv4_to_string(addr)).collect::<Vec<_>>().join(", ")
+}
+
+/// 호스트 이름 검증 (IP 주소는 src_ip로 지정해야 함)
+pub fn validate_hostname(name: &str) -> Result<()> {
+    if name.is_empty() {
+        return Err(anyhow!("Hostname cannot be empty"));
+    }
+    if name.parse::<std::net::IpAddr>().is_ok() {
+        return Err(anyhow!("{} is an IP address; use src_ip instead", name));
+    }
+    if name.len() > MAX_HOSTNAME_LEN {
+        return Err(anyhow!("Hostname is longer than {} characters", MAX_HOSTNAME_LEN));
+    }
+
+    let valid_label = |label: &str| !label.is_empty()
+        && label.len() <= 63
+        && !label.starts_with('-')
+        && !label.ends_with('-')
+        && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !name.trim_end_matches('.').split('.').all(valid_label) {
+        return Err(anyhow!("Invalid hostname: {}", name));
+    }
+
+    Ok(())
+}
+
+/// 호스트 이름 해석기
+#[derive(Clone)]
+pub struct HostResolver {
+    resolver: TokioAsyncResolver,
+}
+
+impl std::fmt::Debug for HostResolver {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HostResolver").finish()
+    }
+}
+
+impl HostResolver {
+    /// 시스템 설정(/etc/resolv.conf)을 쓰는 해석기
+    pub fn system() -> Result<Self> {
+        let resolver = TokioAsyncResolver::tokio_from_system_conf()
+            .context("Failed to load system DNS configuration")?;
+        Ok(Self { resolver })
+    }
+
+    /// 호스트 이름의 A 레코드 (정렬·중복 제거, 레코드가 없으면 오류)
+    pub async fn resolve(&self, name: &str) -> Result<Vec<u32>> {
+        let lookup = self.resolver.ipv4_lookup(name).await
+            .with_context(|| format!("Failed to resolve {}", name))?;
+        let addrs = normalize_addrs(lookup.iter().map(|record| u32::from(record.0)).collect());
+
+        if addrs.is_empty() {
+            return Err(anyhow!("{} has no A records", name));
+        }
+        Ok(addrs)
+    }
+}
+
+/// 호스트 이름 규칙을 모두 다시 해석해 맵 관리자에 반영
+///
+/// 같은 호스트 이름은 한 번만 해석한다. 항목이나 상태가 바뀐 규칙의 레이블을 반환한다.
+pub async fn refresh_hosts(resolver: &HostResolver, map_manager: &Mutex<MapManager<'_>>) -> Result<Vec<String>> {
+    let names = map_manager.lock()
+        .map_err(|_| anyhow!("Failed to lock map_manager"))?
+        .host_names();
+
+    let mut results: HashMap<String, Result<Vec<u32>, String>> = HashMap::new();
+    for name in names {
+        let result = resolver.resolve(&name).await.map_err(|e| format!("{:#}", e));
+        results.insert(name, result);
+    }
+
+    let mut map_manager = map_manager.lock()
+        .map_err(|_| anyhow!("Failed to lock map_manager"))?;
+    let changed = map_manager.refresh_host_endpoints(|name| match results.get(name) {
+        Some(Ok(addrs)) => Ok(addrs.clone()),
+        Some(Err(e)) => Err(anyhow!("{}", e)),
+        // 해석한 뒤에 추가된 규칙은 다음 갱신에서 해석
+        None => Err(anyhow!("{} was not resolved yet", name)),
+    })?;
+    Ok(changed)
+}
+
+/// `interval`마다 호스트 이름 규칙 갱신 (반환하지 않음)
+pub async fn run_refresher(resolver: HostResolver, map_manager: Arc<Mutex<MapManager<'_>>>, interval: Duration) {
+    let mut ticker = tokio::time::interval(interval);
+    ticker.tick().await; // 첫 틱은 즉시 완료
+    loop {
+        ticker.tick().await;
+        match refresh_hosts(&resolver, &map_manager).await {
+            Ok(changed) if !changed.is_empty() => debug!("Refreshed hostname rules: {}", changed.join(", ")),
+            Ok(_) => {}
+            Err(e) => warn!("Failed to refresh hostname rules: {:#}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_addrs() {
+        assert!(diff_addrs(&[1, 2], &[2, 1]).is_empty());
+        assert_eq!(diff_addrs(&[1, 2, 3], &[2, 3, 4, 5]), AddrDiff { added: vec![4, 5], removed: vec![1] });
+        assert_eq!(diff_addrs(&[], &[7]), AddrDiff { added: vec![7], removed: vec![] });
+        assert_eq!(diff_addrs(&[7, 7], &[]), AddrDiff { added: vec![], removed: vec![7] });
+    }
+
+    #[test]
+    fn test_host_endpoint() {
+        let host = HostEndpoint::new("api.example.com", vec![0x0A000002, 0x0A000001, 0x0A000002]);
+        assert_eq!(host.addrs, [0x0A000001, 0x0A000002]);
+        assert_eq!(host.prefixes(), [(0x0A000001, 32), (0x0A000002, 32)]);
+        assert!(!host.is_stale());
+        assert_eq!(host.to_info(), HostInfo {
+            name: "api.example.com".to_string(),
+            addrs: vec!["10.0.0.1".to_string(), "10.0.0.2".to_string()],
+            error: None,
+        });
+        assert_eq!(format_addrs(&host.addrs), "10.0.0.1, 10.0.0.2");
+    }
+
+    #[test]
+    fn test_validate_hostname() {
+        assert!(validate_hostname("api.example.com").is_ok());
+        assert!(validate_hostname("api.example.com.").is_ok());
+        assert!(validate_hostname("db-1").is_ok());
+
+        assert!(validate_hostname("").is_err());
+        assert!(validate_hostname("10.0.0.1").unwrap_err().to_string().contains("use src_ip"));
+        assert!(validate_hostname("-bad.example.com").is_err());
+        assert!(validate_hostname("a..b").is_err());
+        assert!(validate_hostname("web server").is_err());
+        assert!(validate_hostname(&"a".repeat(64)).is_err());
+    }
+}
@@ -0,0 +1,394 @@
+//! 동적 차단 규칙 모듈
+//! WASM 모듈의 판정으로 설치하는 임시 차단 규칙의 설치, 조회, 정리 (`origin: wasm:<모듈>`)
+//!
+//! 동적 차단 규칙은 기본 네임스페이스에 `wasm-<주소>` 레이블의 /32 drop 규칙으로 설치되고,
+//! 수명이 지나면 다른 규칙처럼 만료 처리로 삭제된다. 주소마다 규칙은 하나이므로 이미 차단한
+//! 주소를 다시 차단하면 새 규칙을 만들지 않고 수명만 연장한다. 모듈마다 동시에 둘 수 있는
+//! 규칙 수를 제한해 모듈이 규칙 맵을 채우지 못하게 한다. 자동 완화 규칙처럼 영구 저장하지
+//! 않으므로 데몬을 다시 시작하면 사라진다.
+
+use log::info;
+use std::collections::HashSet;
+
+use swift_guard::api::{DynamicBlockInfo, InstallState, RuleInfo};
+use swift_guard::types::{ActionType, ProtocolType};
+use swift_guard::utils;
+
+use crate::config::WasmConfig;
+use crate::error::DaemonError;
+use crate::logthrottle;
+use crate::maps::{FilterRule, MapManager};
+use crate::wasm::BlockVerdict;
+
+/// 동적 차단 규칙 레이블 접두사
+pub const LABEL_PREFIX: &str = "wasm-";
+
+/// 모듈마다 동시에 둘 수 있는 기본 규칙 수
+pub const DEFAULT_MAX_PER_MODULE: usize = 1000;
+
+/// 기본 규칙 수명 (초)
+pub const DEFAULT_TTL: u32 = 600;
+
+/// 동적 차단 한도와 수명
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DynamicBlockLimits {
+    /// 모듈마다 동시에 둘 수 있는 규칙 수 (0이면 동적 차단 안 함)
+    pub max_per_module: usize,
+    /// 규칙 수명 (초)
+    pub ttl: u32,
+}
+
+impl DynamicBlockLimits {
+    pub fn from_config(config: &WasmConfig) -> Self {
+        Self {
+            max_per_module: config.max_dynamic_blocks,
+            ttl: config.dynamic_block_ttl,
+        }
+    }
+}
+
+/// 동적 차단 결과
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BlockOutcome {
+    /// 새 규칙 설치 (규칙 키)
+    Installed(String),
+    /// 이미 차단 중인 주소의 수명 연장 (규칙 키)
+    Extended(String),
+}
+
+/// 주소의 동적 차단 규칙 레이블
+pub fn label(src_ip: u32) -> String {
+    format!("{}{}", LABEL_PREFIX, utils::ipv4_to_string(src_ip))
+}
+
+/// 동적 차단 규칙인지 (설치 주체가 `wasm:<모듈>`)
+pub fn is_dynamic(rule: &FilterRule) -> bool {
+    utils::origin_matches(rule.origin.as_deref(), utils::WASM_ORIGIN)
+}
+
+/// 모듈이 둔 동적 차단 규칙 수 (수명이 지났지만 아직 삭제되지 않은 규칙 제외)
+pub fn active(map_manager: &MapManager, module: &str, now: u64) -> usize {
+    let origin = utils::wasm_origin(module);
+    map_manager.rules().iter()
+        .filter(|rule| rule.origin.as_deref() == Some(origin.as_str()) && !rule.is_expired(now))
+        .count()
+}
+
+/// 모듈의 판정으로 소스 주소 차단
+///
+/// 이미 동적 차단 중인 주소는 설치한 모듈과 관계없이 수명만 연장하며 한도를 쓰지 않는다.
+/// 모듈의 규칙 수가 한도에 이르렀거나 같은 레이블의 운영자 규칙이 있으면 실패한다.
+pub fn block(
+    map_manager: &mut MapManager,
+    module: &str,
+    src_ip: u32,
+    reason: Option<&str>,
+    limits: DynamicBlockLimits,
+    now: u64,
+) -> Result<BlockOutcome, DaemonError> {
+    let label = label(src_ip);
+
+    if let Some(existing) = map_manager.rules().get(&label) {
+        if !is_dynamic(existing) {
+            return Err(DaemonError::DuplicateLabel(label));
+        }
+        let elapsed = now.saturating_sub(existing.creation_time);
+        let rule = FilterRule {
+            expire: u32::try_from(elapsed + limits.ttl as u64).unwrap_or(u32::MAX),
+            description: reason.map(str::to_string).or_else(|| existing.description.clone()),
+            ..existing.clone()
+        };
+        map_manager.update_rule(rule)?;
+        return Ok(BlockOutcome::Extended(label));
+    }
+
+    if limits.max_per_module == 0 {
+        return Err(DaemonError::Config("Dynamic blocks are disabled (wasm.max_dynamic_blocks is 0)".to_string()));
+    }
+    let count = active(map_manager, module, now);
+    if count >= limits.max_per_module {
+        return Err(DaemonError::Config(format!(
+            "WASM module {} already has {} dynamic blocks (wasm.max_dynamic_blocks)", module, count)));
+    }
+
+    map_manager.add_rule(build_rule(module, src_ip, reason, limits.ttl, now))?;
+    info!("WASM module {} blocked {} for {}s", module, utils::ipv4_to_string(src_ip), limits.ttl);
+    Ok(BlockOutcome::Installed(label))
+}
+
+/// 모듈의 차단 판정을 동적 차단 규칙으로 적용하고 적용한 판정 수 반환
+///
+/// 같은 주소의 판정이 여러 개면 첫 판정만 적용한다. 한도에 걸렸거나 운영자 규칙과
+/// 레이블이 겹친 판정은 기록만 하고 건너뛴다.
+pub fn apply(map_manager: &mut MapManager, verdicts: Vec<BlockVerdict>, limits: DynamicBlockLimits, now: u64) -> usize {
+    let mut seen = HashSet::new();
+    let mut applied = 0;
+    for verdict in verdicts {
+        if !seen.insert(verdict.src_ip) {
+            continue;
+        }
+        match block(map_manager, &verdict.module, verdict.src_ip, Some(&verdict.reason), limits, now) {
+            Ok(_) => applied += 1,
+            Err(e) => throttled_warn!(&format!("dynblock:{}", verdict.module), logthrottle::HOT_PATH_INTERVAL,
+                "Failed to block {} for WASM module {}: {}", utils::ipv4_to_string(verdict.src_ip), verdict.module, e),
+        }
+    }
+    applied
+}
+
+fn build_rule(module: &str, src_ip: u32, reason: Option<&str>, ttl: u32, now: u64) -> FilterRule {
+    FilterRule {
+        src_ip: Some((src_ip, 32)),
+        dst_ip: None,
+        src_port_min: 0,
+        src_port_max: 65535,
+        src_port_list: Vec::new(),
+        dst_port_min: 0,
+        dst_port_max: 65535,
+        dst_port_list: Vec::new(),
+        protocol: ProtocolType::Any as u8,
+        tcp_flags: 0,
+        action: ActionType::Drop as u8,
+        redirect_ifindex: 0,
+        redirect_ifname: None,
+        redirect_cpu: None,
+        priority: u32::MAX,
+        rate_limit: 0,
+        expire: ttl,
+        label: label(src_ip),
+        description: reason.map(str::to_string),
+        created_by: Some(utils::wasm_origin(module)),
+        creation_time: now,
+        auto_generated: true,
+        degraded: false,
+        install_state: InstallState::Installed,
+        quota: None,
+        quota_exceeded: false,
+        src_host: None,
+        counter_group: None,
+        preset: None,
+        origin: Some(utils::wasm_origin(module)),
+        src_set: None,
+        owner_webhook: None,
+        notify_before: None,
+    }
+}
+
+/// 규칙 목록 조회 조건 적용 (`ListRules`)
+///
+/// `origin`이 있으면 그 주체가 설치한 규칙만 남기고, 없으면 `include_dynamic`일 때만
+/// 동적 차단 규칙을 남긴다.
+pub fn retain_listed(rules: &mut Vec<RuleInfo>, include_dynamic: bool, origin: Option<&str>) {
+    match origin {
+        Some(origin) => rules.retain(|rule| utils::origin_matches(rule.origin.as_deref(), origin)),
+        None if !include_dynamic => rules.retain(|rule| !rule.is_dynamic()),
+        None => {},
+    }
+}
+
+/// 동적 차단 규칙 목록 (`module`이 없으면 모든 모듈, 남은 수명이 짧은 순)
+pub fn list(map_manager: &MapManager, module: Option<&str>, now: u64) -> Result<Vec<DynamicBlockInfo>, DaemonError> {
+    let filter = module.map_or_else(|| utils::WASM_ORIGIN.to_string(), utils::wasm_origin);
+
+    let mut blocks: Vec<DynamicBlockInfo> = map_manager.list_rules(true)?.into_iter()
+        .filter(|rule| utils::origin_matches(rule.origin.as_deref(), &filter))
+        .map(|rule| DynamicBlockInfo {
+            ip: rule.src_ip.clone().unwrap_or_default(),
+            module: rule.origin.as_deref()
+                .and_then(|origin| origin.split_once(':'))
+                .map(|(_, module)| module.to_string())
+                .unwrap_or_default(),
+            reason: rule.description.clone(),
+            ttl_remaining: utils::ttl_remaining(rule.creation_time, rule.expire, now).unwrap_or(0),
+            hits: rule.stats.packets,
+            creation_time: rule.creation_time,
+            label: rule.label,
+        })
+        .collect();
+    blocks.sort_by(|a, b| a.ttl_remaining.cmp(&b.ttl_remaining).then_with(|| a.label.cmp(&b.label)));
+    Ok(blocks)
+}
+
+/// 동적 차단 규칙 삭제 (`module`이 없으면 모든 모듈, 삭제한 레이블)
+pub fn clear(map_manager: &mut MapManager, module: Option<&str>) -> Result<Vec<String>, DaemonError> {
+    let filter = module.map_or_else(|| utils::WASM_ORIGIN.to_string(), utils::wasm_origin);
+    let mut keys: Vec<String> = map_manager.rules().iter()
+        .filter(|rule| utils::origin_matches(rule.origin.as_deref(), &filter))
+        .map(|rule| rule.label.clone())
+        .collect();
+    keys.sort();
+
+    for key in &keys {
+        map_manager.delete_rule(key)?;
+    }
+
+    if !keys.is_empty() {
+        info!("Cleared {} dynamic blocks ({})", keys.len(), filter);
+    }
+    Ok(keys)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::MemoryMap;
+    use crate::maps::MapBackends;
+    use swift_guard::api::FallbackMode;
+
+    const LIMITS: DynamicBlockLimits = DynamicBlockLimits { max_per_module: 2, ttl: 60 };
+
+    fn manager<'a>(filter_rules: &'a MemoryMap, wildcard_rules: &'a MemoryMap) -> MapManager<'a> {
+        let mut manager = MapManager::with_backends(MapBackends {
+            filter_rules: Some(filter_rules),
+            wildcard: Some(wildcard_rules),
+            ..Default::default()
+        });
+        manager.attach_interface("eth0", "generic", None, FallbackMode::None, |_| Ok(())).unwrap();
+        manager
+    }
+
+    fn ip(last: u8) -> u32 {
+        u32::from_be_bytes([203, 0, 113, last])
+    }
+
+    /// 운영자가 직접 추가한 규칙
+    fn operator_rule(label: &str, src_ip: u32) -> FilterRule {
+        FilterRule {
+            src_ip: Some((src_ip, 32)),
+            action: ActionType::Drop as u8,
+            priority: u32::MAX,
+            label: label.to_string(),
+            created_by: Some("ops".to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_block_and_extend() {
+        let (filter_rules, wildcard_rules) = (MemoryMap::new(), MemoryMap::new());
+        let mut manager = manager(&filter_rules, &wildcard_rules);
+
+        let outcome = block(&mut manager, "scanner", ip(7), Some("port scan"), LIMITS, 1000).unwrap();
+        assert_eq!(outcome, BlockOutcome::Installed("wasm-203.0.113.7".to_string()));
+        let rule = manager.rules().get("wasm-203.0.113.7").unwrap();
+        assert_eq!((rule.action, rule.expire, rule.origin.as_deref()), (2, 60, Some("wasm:scanner")));
+        assert!(rule.auto_generated);
+
+        // 다시 차단하면 수명만 연장하고 사유는 새 값으로
+        let outcome = block(&mut manager, "scanner", ip(7), Some("still scanning"), LIMITS, 1030).unwrap();
+        assert_eq!(outcome, BlockOutcome::Extended("wasm-203.0.113.7".to_string()));
+        let rule = manager.rules().get("wasm-203.0.113.7").unwrap();
+        assert_eq!((rule.creation_time, rule.ttl_remaining(1030)), (1000, Some(60)));
+        assert_eq!(rule.description.as_deref(), Some("still scanning"));
+        assert_eq!(active(&manager, "scanner", 1030), 1);
+
+        // 운영자 규칙이 쓰는 레이블은 덮어쓰지 않음
+        manager.add_rule(operator_rule(&label(ip(9)), ip(9))).unwrap();
+        assert!(matches!(block(&mut manager, "scanner", ip(9), None, LIMITS, 1030), Err(DaemonError::DuplicateLabel(_))));
+    }
+
+    #[test]
+    fn test_quota_per_module() {
+        let (filter_rules, wildcard_rules) = (MemoryMap::new(), MemoryMap::new());
+        let mut manager = manager(&filter_rules, &wildcard_rules);
+
+        block(&mut manager, "scanner", ip(1), None, LIMITS, 1000).unwrap();
+        block(&mut manager, "scanner", ip(2), None, LIMITS, 1000).unwrap();
+        let err = block(&mut manager, "scanner", ip(3), None, LIMITS, 1000).unwrap_err();
+        assert!(err.to_string().contains("already has 2 dynamic blocks"), "{}", err);
+
+        // 연장은 한도를 쓰지 않고, 다른 모듈의 한도는 따로
+        assert!(block(&mut manager, "scanner", ip(1), None, LIMITS, 1010).is_ok());
+        assert!(block(&mut manager, "bruteforce", ip(3), None, LIMITS, 1010).is_ok());
+        // 다른 모듈이 이미 차단한 주소는 연장만 하므로 한도와 무관
+        assert_eq!(block(&mut manager, "bruteforce", ip(2), None, LIMITS, 1010).unwrap(),
+            BlockOutcome::Extended("wasm-203.0.113.2".to_string()));
+        assert_eq!((active(&manager, "scanner", 1010), active(&manager, "bruteforce", 1010)), (2, 1));
+
+        // 수명이 지난 규칙은 삭제 전이라도 한도에서 빠짐
+        assert_eq!(active(&manager, "scanner", 1070), 0);
+        assert!(block(&mut manager, "scanner", ip(4), None, LIMITS, 1070).is_ok());
+
+        // 한도가 0이면 동적 차단 안 함
+        let disabled = DynamicBlockLimits { max_per_module: 0, ..LIMITS };
+        assert!(matches!(block(&mut manager, "other", ip(5), None, disabled, 1070), Err(DaemonError::Config(_))));
+    }
+
+    #[test]
+    fn test_apply_verdicts() {
+        let (filter_rules, wildcard_rules) = (MemoryMap::new(), MemoryMap::new());
+        let mut manager = manager(&filter_rules, &wildcard_rules);
+        manager.add_rule(operator_rule("web", ip(100))).unwrap();
+
+        let verdict = |module: &str, last: u8, reason: &str| BlockVerdict {
+            module: module.to_string(),
+            src_ip: ip(last),
+            reason: reason.to_string(),
+        };
+        // 같은 주소는 첫 판정만, 한도를 넘거나 운영자 규칙과 겹치는 판정은 건너뜀
+        let verdicts = vec![
+            verdict("scanner", 1, "port scan"),
+            verdict("scanner", 1, "port scan again"),
+            verdict("scanner", 2, "port scan"),
+            verdict("scanner", 3, "port scan"),
+            verdict("bruteforce", 100, "ssh"),
+        ];
+        assert_eq!(apply(&mut manager, verdicts, LIMITS, 1000), 2);
+        assert_eq!(active(&manager, "scanner", 1000), 2);
+        let rule = manager.rules().get("wasm-203.0.113.1").unwrap();
+        assert_eq!(rule.description.as_deref(), Some("port scan"));
+        assert!(!manager.has_rule("wasm-203.0.113.100"));
+    }
+
+    #[test]
+    fn test_clear_frees_quota() {
+        let (filter_rules, wildcard_rules) = (MemoryMap::new(), MemoryMap::new());
+        let mut manager = manager(&filter_rules, &wildcard_rules);
+        manager.add_rule(operator_rule("web", ip(100))).unwrap();
+
+        block(&mut manager, "scanner", ip(1), None, LIMITS, 1000).unwrap();
+        block(&mut manager, "scanner", ip(2), None, LIMITS, 1000).unwrap();
+        block(&mut manager, "bruteforce", ip(3), None, LIMITS, 1000).unwrap();
+        assert!(block(&mut manager, "scanner", ip(4), None, LIMITS, 1000).is_err());
+
+        // 모듈을 지정하면 그 모듈의 규칙만 지우고 한도가 비워짐
+        assert_eq!(clear(&mut manager, Some("scanner")).unwrap(), ["wasm-203.0.113.1", "wasm-203.0.113.2"]);
+        assert_eq!(active(&manager, "scanner", 1000), 0);
+        assert_eq!(active(&manager, "bruteforce", 1000), 1);
+        assert!(block(&mut manager, "scanner", ip(4), None, LIMITS, 1000).is_ok());
+        assert!(clear(&mut manager, Some("missing")).unwrap().is_empty());
+
+        // 모듈이 없으면 모든 동적 차단 규칙, 운영자 규칙은 그대로
+        assert_eq!(clear(&mut manager, None).unwrap(), ["wasm-203.0.113.3", "wasm-203.0.113.4"]);
+        let labels: Vec<&str> = manager.rules().iter().map(|rule| rule.label.as_str()).collect();
+        assert_eq!(labels, ["web"]);
+    }
+
+    #[test]
+    fn test_list_by_origin() {
+        let (filter_rules, wildcard_rules) = (MemoryMap::new(), MemoryMap::new());
+        let mut manager = manager(&filter_rules, &wildcard_rules);
+        manager.add_rule(operator_rule("web", ip(100))).unwrap();
+
+        block(&mut manager, "scanner", ip(1), Some("port scan"), LIMITS, 1000).unwrap();
+        block(&mut manager, "bruteforce", ip(2), None, LIMITS, 1020).unwrap();
+
+        let blocks = list(&manager, None, 1030).unwrap();
+        assert_eq!(blocks.iter().map(|block| (block.ip.as_str(), block.module.as_str(), block.ttl_remaining)).collect::<Vec<_>>(),
+            [("203.0.113.1", "scanner", 30), ("203.0.113.2", "bruteforce", 50)]);
+        assert_eq!(blocks[0].reason.as_deref(), Some("port scan"));
+        assert_eq!(list(&manager, Some("bruteforce"), 1030).unwrap().len(), 1);
+        assert!(list(&manager, Some("scan"), 1030).unwrap().is_empty());
+
+        // 기본 목록은 동적 차단 규칙을 숨기고, --all이나 --origin이면 포함
+        let listed = |include_dynamic: bool, origin: Option<&str>| {
+            let mut rules = manager.list_rules(false).unwrap();
+            retain_listed(&mut rules, include_dynamic, origin);
+            rules.into_iter().map(|rule| rule.label).collect::<Vec<_>>()
+        };
+        assert_eq!(listed(false, None), ["web"]);
+        assert_eq!(listed(true, None).len(), 3);
+        assert_eq!(listed(false, Some("wasm")), ["wasm-203.0.113.1", "wasm-203.0.113.2"]);
+        assert_eq!(listed(false, Some("wasm:bruteforce")), ["wasm-203.0.113.2"]);
+    }
+}
@@ -0,0 +1,219 @@
+//! 데몬 오류 모듈
+//! 맵 관리자, WASM 관리자, BPF 로더가 반환하는 오류와 API 응답 변환
+//!
+//! 규칙 백엔드와 WASM 런타임 내부는 anyhow 오류를 쓰고, 공개 함수가 이를 변형으로
+//! 감싸 반환한다. API 서버는 `From<DaemonError> for ApiResponse`로 오류 코드를 정한다.
+
+use std::io;
+
+use thiserror::Error;
+
+use swift_guard::api::{ApiResponse, BpfLoadFailure};
+use swift_guard::error::{ErrorCode, InvalidArgument};
+
+/// 데몬 핵심 구성 요소의 오류
+#[derive(Debug, Error)]
+pub enum DaemonError {
+    /// 규칙 백엔드(BPF 맵, nftables) 작업 실패
+    #[error("{0:#}")]
+    Map(anyhow::Error),
+    /// 레이블(규칙 키)에 해당하는 규칙 없음
+    #[error("Rule '{0}' not found")]
+    RuleNotFound(String),
+    /// 같은 레이블(규칙 키)의 규칙이 이미 있음
+    #[error("Rule '{0}' already exists")]
+    DuplicateLabel(String),
+    /// 다른 규칙이 같은 소스 프리픽스(커널 LPM 키)를 사용 중
+    #[error("Prefix {0} is already used by another rule")]
+    PrefixConflict(String),
+    /// 잘못된 인자
+    #[error(transparent)]
+    Validation(#[from] InvalidArgument),
+    /// WASM 모듈 로드 또는 검사 실패
+    #[error("{0:#}")]
+    Wasm(anyhow::Error),
+    /// 로드되지 않은 WASM 모듈
+    #[error("WASM module {0} is not loaded")]
+    ModuleNotFound(String),
+    /// 같은 이름의 WASM 모듈이 이미 로드됨
+    #[error("WASM module {0} is already loaded")]
+    DuplicateModule(String),
+    /// redirect_map에 없는 리디렉션 대상
+    #[error("Redirect target {0} not found")]
+    TargetNotFound(String),
+    /// 이미 직접 등록된 리디렉션 대상
+    #[error("Redirect target {0} is already provisioned")]
+    DuplicateTarget(String),
+    /// BPF 오브젝트 로드 또는 XDP 연결/분리 실패
+    #[error("{0:#}")]
+    Bpf(anyhow::Error),
+    /// BPF 오브젝트를 커널에 올리지 못함 (libbpf 로그 포함)
+    #[error("{0}")]
+    BpfLoad(BpfLoadFailure),
+    /// 입출력 실패 (`context`는 하려던 작업)
+    #[error("{context}: {source}")]
+    Io {
+        context: String,
+        #[source]
+        source: io::Error,
+    },
+    /// 데몬 구성이 요청을 허용하지 않음
+    #[error("{0}")]
+    Config(String),
+    /// 권한을 낮춘 데몬에 요청에 필요한 capability가 없음
+    #[error("{0}")]
+    PermissionDenied(String),
+}
+
+impl DaemonError {
+    /// 작업을 설명하는 입출력 오류
+    pub fn io(context: impl Into<String>, source: io::Error) -> Self {
+        Self::Io { context: context.into(), source }
+    }
+
+    /// API 오류 코드
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Self::RuleNotFound(_) | Self::ModuleNotFound(_) | Self::TargetNotFound(_) => ErrorCode::NotFound,
+            Self::DuplicateLabel(_) | Self::PrefixConflict(_) | Self::DuplicateModule(_) | Self::DuplicateTarget(_) => ErrorCode::AlreadyExists,
+            Self::Validation(_) => ErrorCode::InvalidArgument,
+            Self::PermissionDenied(_) => ErrorCode::PermissionDenied,
+            Self::Map(_) | Self::Wasm(_) | Self::Bpf(_) | Self::BpfLoad(_) | Self::Io { .. } | Self::Config(_) => ErrorCode::Internal,
+        }
+    }
+
+    /// 규칙 변경이 일부만 반영된 채 끝났는지 (`repair-rules`로 재시도 가능)
+    pub fn is_partial(&self) -> bool {
+        matches!(self, Self::Map(e) if crate::maps::is_partial(e))
+    }
+}
+
+impl From<DaemonError> for ApiResponse {
+    fn from(error: DaemonError) -> Self {
+        let code = error.code();
+        match error {
+            DaemonError::Validation(e) => ApiResponse::InvalidArgument {
+                field: e.field,
+                message: e.message,
+            },
+            DaemonError::RuleNotFound(label) => ApiResponse::NotFound {
+                resource: "rule".to_string(),
+                name: label,
+            },
+            DaemonError::TargetNotFound(interface) => ApiResponse::NotFound {
+                resource: "redirect target".to_string(),
+                name: interface,
+            },
+            error @ (DaemonError::Map(_)
+            | DaemonError::DuplicateLabel(_)
+            | DaemonError::PrefixConflict(_)
+            | DaemonError::Wasm(_)
+            | DaemonError::ModuleNotFound(_)
+            | DaemonError::DuplicateModule(_)
+            | DaemonError::DuplicateTarget(_)
+            | DaemonError::Bpf(_)
+            | DaemonError::BpfLoad(_)
+            | DaemonError::Io { .. }
+            | DaemonError::Config(_)
+            | DaemonError::PermissionDenied(_)) => ApiResponse::Error {
+                message: error.to_string(),
+                code,
+            },
+        }
+    }
+}
+
+/// 처리 오류를 API 응답으로 변환 (`DaemonError`가 아니면 내부 오류)
+///
+/// 오류 체인 안의 `DaemonError`도 찾으므로 호출자가 덧붙인 맥락과 관계없이 코드가 정해진다.
+pub fn to_response(error: anyhow::Error) -> ApiResponse {
+    match error.downcast::<DaemonError>() {
+        Ok(error) => ApiResponse::from(error),
+        Err(error) => ApiResponse::Error {
+            message: error.to_string(),
+            code: ErrorCode::Internal,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::{anyhow, Context};
+
+    fn all_variants() -> Vec<DaemonError> {
+        vec![
+            DaemonError::Map(anyhow!("map update failed")),
+            DaemonError::RuleNotFound("web".to_string()),
+            DaemonError::DuplicateLabel("web".to_string()),
+            DaemonError::PrefixConflict("10.0.0.0/8".to_string()),
+            DaemonError::Validation(InvalidArgument::new("label", "must not be empty")),
+            DaemonError::Wasm(anyhow!("trap")),
+            DaemonError::ModuleNotFound("ids".to_string()),
+            DaemonError::DuplicateModule("ids".to_string()),
+            DaemonError::TargetNotFound("eth9".to_string()),
+            DaemonError::DuplicateTarget("eth1".to_string()),
+            DaemonError::Bpf(anyhow!("attach failed")),
+            DaemonError::BpfLoad(BpfLoadFailure { object: "xdp_filter.o".to_string(), ..Default::default() }),
+            DaemonError::io("read object", io::Error::from(io::ErrorKind::NotFound)),
+            DaemonError::Config("alternate BPF objects are disabled".to_string()),
+            DaemonError::PermissionDenied("attach needs CAP_NET_ADMIN".to_string()),
+        ]
+    }
+
+    #[test]
+    fn test_response_codes() {
+        let expected = vec![
+            ErrorCode::Internal,
+            ErrorCode::NotFound,
+            ErrorCode::AlreadyExists,
+            ErrorCode::AlreadyExists,
+            ErrorCode::InvalidArgument,
+            ErrorCode::Internal,
+            ErrorCode::NotFound,
+            ErrorCode::AlreadyExists,
+            ErrorCode::NotFound,
+            ErrorCode::AlreadyExists,
+            ErrorCode::Internal,
+            ErrorCode::Internal,
+            ErrorCode::Internal,
+            ErrorCode::Internal,
+            ErrorCode::PermissionDenied,
+        ];
+        assert_eq!(all_variants().iter().map(DaemonError::code).collect::<Vec<_>>(), expected);
+
+        // 구조화된 응답도 같은 코드로 분류됨
+        let codes: Vec<ErrorCode> = all_variants().into_iter()
+            .map(|error| match ApiResponse::from(error) {
+                ApiResponse::Error { code, .. } => code,
+                ApiResponse::InvalidArgument { .. } => ErrorCode::InvalidArgument,
+                ApiResponse::NotFound { .. } => ErrorCode::NotFound,
+                other => panic!("unexpected response: {:?}", other),
+            })
+            .collect();
+        assert_eq!(codes, expected);
+    }
+
+    #[test]
+    fn test_response_details() {
+        let response = ApiResponse::from(DaemonError::Validation(InvalidArgument::new("label", "too long")));
+        assert!(matches!(response, ApiResponse::InvalidArgument { ref field, ref message }
+            if field == "label" && message == "too long"));
+
+        let response = ApiResponse::from(DaemonError::RuleNotFound("team-a/web".to_string()));
+        assert!(matches!(response, ApiResponse::NotFound { ref resource, ref name }
+            if resource == "rule" && name == "team-a/web"));
+
+        let response = ApiResponse::from(DaemonError::DuplicateLabel("web".to_string()));
+        assert!(matches!(response, ApiResponse::Error { ref message, code: ErrorCode::AlreadyExists }
+            if message == "Rule 'web' already exists"));
+
+        // 맥락이 붙은 anyhow 오류 안의 변형도 찾음
+        let error = Err::<(), _>(DaemonError::ModuleNotFound("ids".to_string()))
+            .context("Failed to unload")
+            .unwrap_err();
+        assert!(matches!(to_response(error), ApiResponse::Error { ref message, code: ErrorCode::NotFound }
+            if message == "WASM module ids is not loaded"));
+        assert!(matches!(to_response(anyhow!("boom")), ApiResponse::Error { code: ErrorCode::Internal, .. }));
+    }
+}
@@ -0,0 +1,221 @@
+//! 테스트 패킷 평가 모듈
+//! 커널 XDP 프로그램과 같은 순서로 캐시된 규칙 중 테스트 패킷에 적용될 규칙 선택
+//!
+//! 커널은 소스 IP로 LPM 맵을 먼저 조회하고, 가장 긴 프리픽스 규칙의 조건이 맞으면
+//! 그 규칙을 적용한다. 프리픽스 규칙이 없거나 조건이 맞지 않을 때만 와일드카드 맵에서
+//! 조건이 맞는 규칙 중 우선순위가 가장 높은 규칙(같으면 앞 슬롯)을 찾는다. 따라서
+//! 소스 프리픽스 규칙은 우선순위와 관계없이 와일드카드 규칙보다 먼저 적용된다.
+
+use std::collections::HashMap;
+
+use crate::maps::FilterRule;
+use swift_guard::api::RuleMap;
+use swift_guard::utils;
+
+/// 모든 프로토콜 (struct filter_rule.protocol)
+const PROTOCOL_ANY: u8 = 255;
+
+/// TCP 프로토콜 번호
+const PROTOCOL_TCP: u8 = 6;
+
+/// 평가할 테스트 패킷
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TestPacket {
+    /// 소스 IPv4 주소 (호스트 순서)
+    pub src_ip: u32,
+    pub protocol: u8,
+    pub src_port: u16,
+    pub dst_port: u16,
+    /// TCP 플래그 (TCP가 아니면 무시)
+    pub tcp_flags: u8,
+}
+
+impl TestPacket {
+    /// 이더넷 프레임의 평가용 헤더 (IPv4가 아니거나 IP 헤더가 잘못되었으면 None, L4 헤더가 없으면 포트 0)
+    pub fn from_frame(frame: &[u8]) -> Option<Self> {
+        let headers = crate::packet::parse_headers(frame)?;
+        let src_ip = match headers.src_ip {
+            std::net::IpAddr::V4(addr) => u32::from(addr),
+            std::net::IpAddr::V6(_) => return None,
+        };
+        let transport = headers.transport;
+
+        Some(Self {
+            src_ip,
+            protocol: headers.protocol,
+            src_port: transport.map(|t| t.src_port).unwrap_or(0),
+            dst_port: transport.map(|t| t.dst_port).unwrap_or(0),
+            tcp_flags: transport.and_then(|t| t.tcp_flags).unwrap_or(0),
+        })
+    }
+}
+
+/// 규칙의 프로토콜, 포트, TCP 플래그 조건 확인 (커널 rule_matches와 동일)
+pub fn rule_matches(rule: &FilterRule, packet: &TestPacket) -> bool {
+    (rule.protocol == PROTOCOL_ANY || rule.protocol == packet.protocol)
+        && (rule.src_port_min..=rule.src_port_max).contains(&packet.src_port)
+        && (rule.src_port_list.is_empty() || rule.src_port_list.contains(&packet.src_port))
+        && (rule.dst_port_min..=rule.dst_port_max).contains(&packet.dst_port)
+        && (rule.dst_port_list.is_empty() || rule.dst_port_list.contains(&packet.dst_port))
+        && (packet.protocol != PROTOCOL_TCP || rule.tcp_flags & packet.tcp_flags == rule.tcp_flags)
+}
+
+/// 테스트 패킷에 적용될 규칙 (없으면 기본 동작인 통과)
+///
+/// `wildcard_slots`는 소스 IP가 없는 규칙의 레이블별 와일드카드 맵 슬롯이다.
+/// 슬롯이 없는 규칙은 커널에 설치되지 않은 것으로 보고 건너뛴다.
+pub fn evaluate<'r, I>(rules: I, wildcard_slots: &HashMap<String, u32>, packet: &TestPacket) -> Option<&'r FilterRule>
+where
+    I: IntoIterator<Item = &'r FilterRule>,
+{
+    let mut prefix: Option<(u32, &FilterRule)> = None;
+    let mut wildcard: Option<(u32, &FilterRule)> = None;
+
+    for rule in rules {
+        match rule.rule_map() {
+            RuleMap::Lpm => {
+                // LPM 맵에는 프리픽스당 항목이 하나뿐이므로 나중에 기록한 규칙이 남음
+                for (src_ip, prefix_len) in rule.src_prefixes() {
+                    let covers = utils::mask_ipv4(packet.src_ip, prefix_len) == utils::mask_ipv4(src_ip, prefix_len);
+                    if covers && prefix.is_none_or(|(best, _)| prefix_len >= best) {
+                        prefix = Some((prefix_len, rule));
+                    }
+                }
+            }
+            RuleMap::Wildcard => {
+                let slot = match wildcard_slots.get(&rule.label) {
+                    Some(&slot) => slot,
+                    None => continue,
+                };
+                if !rule_matches(rule, packet) {
+                    continue;
+                }
+                let better = wildcard.is_none_or(|(best_slot, best)| {
+                    rule.priority > best.priority || (rule.priority == best.priority && slot < best_slot)
+                });
+                if better {
+                    wildcard = Some((slot, rule));
+                }
+            }
+        }
+    }
+
+    match prefix {
+        Some((_, rule)) if rule_matches(rule, packet) => Some(rule),
+        _ => wildcard.map(|(_, rule)| rule),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dns::HostEndpoint;
+
+    fn rule(label: &str, src_ip: Option<(u32, u32)>, dst_port: u16, priority: u32) -> FilterRule {
+        FilterRule {
+            src_ip,
+            dst_port_min: dst_port,
+            dst_port_max: dst_port,
+            protocol: PROTOCOL_TCP,
+            action: 2,
+            priority,
+            label: label.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn packet(src_ip: u32, dst_port: u16) -> TestPacket {
+        TestPacket { src_ip, protocol: PROTOCOL_TCP, src_port: 40000, dst_port, tcp_flags: 0x02 }
+    }
+
+    fn label(rule: Option<&FilterRule>) -> Option<&str> {
+        rule.map(|rule| rule.label.as_str())
+    }
+
+    #[test]
+    fn test_wildcard_priority_and_slot_order() {
+        let rules = [
+            rule("telnet-low", None, 23, 5),
+            rule("telnet-high", None, 23, 50),
+            rule("telnet-tie", None, 23, 50),
+            rule("uninstalled", None, 23, 100),
+        ];
+        let slots = HashMap::from([
+            ("telnet-low".to_string(), 0),
+            ("telnet-high".to_string(), 2),
+            ("telnet-tie".to_string(), 1),
+        ]);
+
+        // 우선순위가 같으면 앞 슬롯, 슬롯이 없는 규칙은 무시
+        assert_eq!(label(evaluate(&rules, &slots, &packet(0x0A000001, 23))), Some("telnet-tie"));
+        assert_eq!(label(evaluate(&rules, &slots, &packet(0x0A000001, 22))), None);
+
+        // UDP 패킷은 TCP 규칙과 일치하지 않음
+        let udp = TestPacket { protocol: 17, ..packet(0x0A000001, 23) };
+        assert_eq!(label(evaluate(&rules, &slots, &udp)), None);
+    }
+
+    #[test]
+    fn test_prefix_rule_takes_precedence() {
+        let rules = [
+            rule("telnet", None, 23, 1000),
+            rule("lab", Some((0x0A000000, 8)), 23, 0),
+            rule("lab-host", Some((0x0A000005, 32)), 22, 0),
+        ];
+        let slots = HashMap::from([("telnet".to_string(), 0)]);
+
+        // 소스 프리픽스 규칙은 우선순위와 관계없이 먼저 적용
+        assert_eq!(label(evaluate(&rules, &slots, &packet(0x0A000001, 23))), Some("lab"));
+        assert_eq!(label(evaluate(&rules, &slots, &packet(0xC0A80001, 23))), Some("telnet"));
+
+        // 가장 긴 프리픽스 규칙의 조건이 맞지 않으면 짧은 프리픽스가 아니라 와일드카드로 넘어감
+        assert_eq!(label(evaluate(&rules, &slots, &packet(0x0A000005, 23))), Some("telnet"));
+        assert_eq!(label(evaluate(&rules, &slots, &packet(0x0A000005, 22))), Some("lab-host"));
+    }
+
+    #[test]
+    fn test_host_rule_matches_each_address() {
+        let host = FilterRule {
+            src_host: Some(HostEndpoint::new("api.example.com", vec![0xC0A80001, 0xC0A80002])),
+            ..rule("api", None, 443, 0)
+        };
+        let rules = [rule("https", None, 443, 10), host];
+        let slots = HashMap::from([("https".to_string(), 0)]);
+
+        assert_eq!(label(evaluate(&rules, &slots, &packet(0xC0A80001, 443))), Some("api"));
+        assert_eq!(label(evaluate(&rules, &slots, &packet(0xC0A80002, 443))), Some("api"));
+        assert_eq!(label(evaluate(&rules, &slots, &packet(0xC0A80003, 443))), Some("https"));
+    }
+
+    #[test]
+    fn test_src_port_list() {
+        let reflectors = FilterRule {
+            protocol: 17,
+            dst_port_min: 0,
+            dst_port_max: 65535,
+            src_port_list: vec![53, 123, 1900],
+            ..rule("reflectors", None, 0, 0)
+        };
+        let slots = HashMap::from([("reflectors".to_string(), 0)]);
+        let udp = |src_port| TestPacket { protocol: 17, src_port, ..packet(0xC0A80001, 40000) };
+
+        assert_eq!(label(evaluate([&reflectors], &slots, &udp(123))), Some("reflectors"));
+        assert_eq!(label(evaluate([&reflectors], &slots, &udp(1900))), Some("reflectors"));
+        assert_eq!(label(evaluate([&reflectors], &slots, &udp(124))), None);
+    }
+
+    #[test]
+    fn test_dst_port_list() {
+        let web = FilterRule {
+            dst_port_min: 0,
+            dst_port_max: 65535,
+            dst_port_list: vec![80, 443, 8080],
+            ..rule("web", None, 0, 0)
+        };
+        let slots = HashMap::from([("web".to_string(), 0)]);
+
+        assert_eq!(label(evaluate([&web], &slots, &packet(0xC0A80001, 443))), Some("web"));
+        assert_eq!(label(evaluate([&web], &slots, &packet(0xC0A80001, 8080))), Some("web"));
+        assert_eq!(label(evaluate([&web], &slots, &packet(0xC0A80001, 81))), None);
+    }
+}
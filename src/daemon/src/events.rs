@@ -0,0 +1,763 @@
+//! 규칙 적중 이벤트 모듈
+//! XDP 프로그램이 perf 버퍼로 보고한 규칙 매치 레코드를 디코딩하여 구독자에게 배포
+//!
+//! 이벤트는 하나의 브로드캐스트 채널로 발행되고 API 이벤트 구독, 웹훅, syslog가
+//! 각자 수신한다. 커널 버퍼가 넘쳐 유실된 레코드와 느린 구독자가 건너뛴
+//! 이벤트는 따로 집계하여 주기적으로 보고한다.
+//!
+//! 규칙 적중과 자동 완화 이벤트는 순번을 붙여 제한된 크기의 기록에도 남긴다.
+//! 재연결한 구독자는 마지막으로 받은 순번을 보내 그 사이 이벤트를 따라잡고,
+//! 이미 기록에서 밀려난 구간은 빈 구간(gap)으로 보고받는다.
+
+use anyhow::{anyhow, Result};
+use libbpf_rs::PerfBufferBuilder;
+use log::{debug, warn};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::io::unix::AsyncFd;
+use tokio::io::Interest;
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+use tokio::time;
+
+use crate::backend::KernelMap;
+use crate::config::EventsConfig;
+use crate::conntrack;
+use crate::geoip::{self, GeoResolver};
+use crate::logthrottle;
+use crate::syslog::{SecurityEvent, SyslogSink};
+use crate::webhook::WebhookSender;
+
+use swift_guard::api::{
+    AnomalyEvent, DaemonEvent, EventGap, LinkStateEvent, MitigationEvent, RuleExpiryEvent, RuleHitEvent, RuleMatchedEvent, SequencedEvent,
+    XdpAbortedEvent,
+};
+use swift_guard::utils;
+
+/// 구독자별 버퍼 크기 (초과하면 오래된 이벤트부터 건너뜀)
+const EVENT_CHANNEL_CAPACITY: usize = 4096;
+
+/// CPU별 perf 버퍼 크기 (페이지 수, 2의 거듭제곱)
+const PERF_BUFFER_PAGES: usize = 64;
+
+/// 유실 이벤트 보고 간격
+const DROP_REPORT_INTERVAL: Duration = Duration::from_secs(10);
+
+/// 기본 이벤트 기록 크기 (events.history_size)
+pub const DEFAULT_HISTORY_SIZE: usize = 10_000;
+
+/// GetEvents 응답 하나에 담는 최대 이벤트 수
+pub const MAX_EVENTS_PER_QUERY: usize = 1000;
+
+/// 규칙 적중 레코드 (struct rule_event와 일치)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleEventRecord {
+    /// 매치 시각 (단조 시계, ns)
+    pub timestamp: u64,
+    /// 규칙 키 프리픽스 길이
+    pub prefix_len: u32,
+    /// 규칙 키 주소 (호스트 순서)
+    pub rule_addr: u32,
+    /// 소스 IPv4 주소 (호스트 순서)
+    pub saddr: u32,
+    /// 대상 IPv4 주소 (호스트 순서)
+    pub daddr: u32,
+    pub sport: u16,
+    pub dport: u16,
+    pub action: u8,
+    pub protocol: u8,
+    pub label: String,
+}
+
+impl RuleEventRecord {
+    pub const SIZE: usize = 64;
+
+    /// perf 샘플 바이트에서 파싱 (샘플 끝의 정렬 패딩은 무시)
+    pub fn from_bytes(data: &[u8]) -> Option<Self> {
+        if data.len() < Self::SIZE {
+            return None;
+        }
+
+        let be32 = |offset: usize| u32::from_be_bytes([data[offset], data[offset + 1], data[offset + 2], data[offset + 3]]);
+        let le16 = |offset: usize| u16::from_le_bytes([data[offset], data[offset + 1]]);
+
+        let mut timestamp = [0u8; 8];
+        timestamp.copy_from_slice(&data[0..8]);
+
+        let label = &data[32..Self::SIZE];
+        let end = label.iter().position(|&b| b == 0).unwrap_or(label.len());
+
+        Some(Self {
+            timestamp: u64::from_le_bytes(timestamp),
+            prefix_len: u32::from_le_bytes([data[8], data[9], data[10], data[11]]),
+            rule_addr: be32(12),
+            saddr: be32(16),
+            daddr: be32(20),
+            sport: le16(24),
+            dport: le16(26),
+            action: data[28],
+            protocol: data[29],
+            label: String::from_utf8_lossy(&label[..end]).into_owned(),
+        })
+    }
+
+    /// API 이벤트로 변환 (`clock_offset_ns`는 실시간 시계와 단조 시계의 차이)
+    pub fn to_event(&self, clock_offset_ns: u64) -> RuleHitEvent {
+        RuleHitEvent {
+            label: self.label.clone(),
+            rule_key: format!("{}/{}", utils::ipv4_to_string(self.rule_addr), self.prefix_len),
+            action: utils::action_num_to_name(self.action),
+            protocol: utils::protocol_num_to_name(self.protocol),
+            src_ip: utils::ipv4_to_string(self.saddr),
+            src_port: self.sport,
+            dst_ip: utils::ipv4_to_string(self.daddr),
+            dst_port: self.dport,
+            timestamp_ns: self.timestamp.saturating_add(clock_offset_ns),
+            geo: None,
+        }
+    }
+}
+
+/// 배포 카운터 스냅샷
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DispatchCounts {
+    /// 디코딩하여 발행한 이벤트 수
+    pub dispatched: u64,
+    /// 형식이 맞지 않아 버린 레코드 수
+    pub malformed: u64,
+    /// 커널 perf 버퍼가 가득 차 유실된 레코드 수
+    pub lost: u64,
+    /// 느린 구독자가 건너뛴 이벤트 수 (구독자별 합계)
+    pub lagged: u64,
+}
+
+impl DispatchCounts {
+    /// 유실된 이벤트 합계
+    pub fn dropped(&self) -> u64 {
+        self.malformed + self.lost + self.lagged
+    }
+}
+
+#[derive(Debug, Default)]
+struct Counters {
+    dispatched: AtomicU64,
+    malformed: AtomicU64,
+    lost: AtomicU64,
+    lagged: AtomicU64,
+}
+
+/// 이벤트 기록 조회 결과
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EventPage {
+    /// 조건에 맞는 이벤트 (오래된 것부터)
+    pub events: Vec<SequencedEvent>,
+    /// 요청한 순번 이후 중 이미 밀려난 구간
+    pub gap: Option<EventGap>,
+    /// 마지막으로 부여한 순번
+    pub latest_seq: u64,
+}
+
+/// 순번이 붙은 최근 이벤트 기록 (링 버퍼)
+///
+/// 순번은 데몬 시작 후 1부터 증가하며 기록 크기와 관계없이 모든 이벤트에
+/// 부여된다. 기록이 가득 차면 가장 오래된 이벤트부터 버린다. 새 이벤트는
+/// 기록과 같은 잠금 안에서 브로드캐스트되므로 `subscribe_since`의 재생 목록과
+/// 실시간 수신 사이에 빠지거나 겹치는 이벤트가 없다.
+#[derive(Debug)]
+pub struct EventHistory {
+    capacity: usize,
+    /// 보관 중인 이벤트와 마지막으로 부여한 순번
+    inner: Mutex<(VecDeque<SequencedEvent>, u64)>,
+    sender: broadcast::Sender<SequencedEvent>,
+}
+
+impl EventHistory {
+    /// 최대 `capacity`개를 보관하는 기록 생성 (0이면 보관하지 않음)
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            inner: Mutex::new((VecDeque::with_capacity(capacity.min(EVENT_CHANNEL_CAPACITY)), 0)),
+            sender: broadcast::channel(EVENT_CHANNEL_CAPACITY).0,
+        }
+    }
+
+    /// 이벤트 기록 (부여한 순번 반환)
+    pub fn record(&self, event: DaemonEvent) -> u64 {
+        let mut inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        let (events, latest) = &mut *inner;
+        *latest += 1;
+
+        let event = SequencedEvent { seq: *latest, event };
+        if self.capacity > 0 {
+            if events.len() == self.capacity {
+                events.pop_front();
+            }
+            events.push_back(event.clone());
+        }
+
+        // 구독자가 없으면 전송 실패는 무시
+        let _ = self.sender.send(event);
+        *latest
+    }
+
+    /// 이벤트 조회
+    ///
+    /// `since_seq`가 있으면 그 순번 이후 이벤트를 오래된 것부터 최대 `limit`개,
+    /// 없으면 최근 `limit`개를 돌려준다. `kinds`가 비어 있지 않으면 그 종류만
+    /// 돌려준다. 마지막 순번보다 큰 `since_seq`는 데몬이 재시작된 것으로 보고
+    /// 처음부터 조회한다.
+    pub fn query(&self, since_seq: Option<u64>, limit: usize, kinds: &[String]) -> EventPage {
+        let inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        page(&inner.0, inner.1, since_seq, limit, kinds)
+    }
+
+    /// `since_seq` 이후 기록을 재생 목록으로 받고 이후 이벤트를 구독 (gRPC StreamEvents)
+    #[cfg(feature = "grpc")]
+    pub fn subscribe_since(&self, since_seq: u64) -> (EventPage, broadcast::Receiver<SequencedEvent>) {
+        let inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        let receiver = self.sender.subscribe();
+        (page(&inner.0, inner.1, Some(since_seq), usize::MAX, &[]), receiver)
+    }
+}
+
+/// 잠근 기록에서 조회 (`EventHistory::query` 참고)
+fn page(events: &VecDeque<SequencedEvent>, latest_seq: u64, since_seq: Option<u64>, limit: usize, kinds: &[String]) -> EventPage {
+    let wanted = |event: &&SequencedEvent| kinds.is_empty() || kinds.iter().any(|kind| kind == event.event.kind());
+
+    match since_seq {
+        Some(since) => {
+            let since = if since > latest_seq { 0 } else { since };
+            EventPage {
+                events: events.iter()
+                    .filter(|event| event.seq > since)
+                    .filter(wanted)
+                    .take(limit)
+                    .cloned()
+                    .collect(),
+                gap: gap_after(events, since, latest_seq),
+                latest_seq,
+            }
+        },
+        None => {
+            let mut recent: Vec<SequencedEvent> = events.iter().rev().filter(wanted).take(limit).cloned().collect();
+            recent.reverse();
+            EventPage { events: recent, gap: None, latest_seq }
+        },
+    }
+}
+
+/// `since` 이후 순번 중 기록에서 밀려난 구간
+fn gap_after(events: &VecDeque<SequencedEvent>, since: u64, latest_seq: u64) -> Option<EventGap> {
+    let oldest = events.front().map(|event| event.seq).unwrap_or(latest_seq + 1);
+    (since + 1 < oldest).then(|| EventGap { first_seq: since + 1, last_seq: oldest - 1 })
+}
+
+/// 규칙 적중 이벤트 배포기
+///
+/// 복제본은 같은 채널, 카운터, 이벤트 기록을 공유한다.
+#[derive(Debug, Clone)]
+pub struct EventDispatcher {
+    sender: broadcast::Sender<RuleHitEvent>,
+    counters: Arc<Counters>,
+    history: Arc<EventHistory>,
+    clock_offset_ns: u64,
+    /// 소스 주소 GeoIP 해석기 (없으면 이벤트에 geo를 붙이지 않음)
+    geo: Option<Arc<dyn GeoResolver>>,
+}
+
+impl EventDispatcher {
+    /// 새로운 배포기 생성
+    pub fn new() -> Result<Self> {
+        let realtime = SystemTime::now().duration_since(UNIX_EPOCH)
+            .map_err(|e| anyhow!("System clock is before the Unix epoch: {}", e))?
+            .as_nanos() as u64;
+
+        Ok(Self::with_clock_offset(realtime.saturating_sub(conntrack::monotonic_ns()?)))
+    }
+
+    /// 시계 차이를 지정하여 생성
+    pub fn with_clock_offset(clock_offset_ns: u64) -> Self {
+        Self {
+            sender: broadcast::channel(EVENT_CHANNEL_CAPACITY).0,
+            counters: Arc::new(Counters::default()),
+            history: Arc::new(EventHistory::new(DEFAULT_HISTORY_SIZE)),
+            clock_offset_ns,
+            geo: None,
+        }
+    }
+
+    /// 이벤트 기록 크기 지정 (복제하기 전에 호출)
+    pub fn with_history_size(mut self, size: usize) -> Self {
+        self.history = Arc::new(EventHistory::new(size));
+        self
+    }
+
+    /// 이벤트 소스 주소에 GeoIP 정보 추가 (복제하기 전에 호출)
+    pub fn with_geo(mut self, resolver: Arc<dyn GeoResolver>) -> Self {
+        self.geo = Some(resolver);
+        self
+    }
+
+    /// 순번이 붙은 이벤트 기록
+    pub fn history(&self) -> &EventHistory {
+        &self.history
+    }
+
+    /// 가장 느린 구독자가 아직 받지 않은 이벤트 수
+    pub fn backlog(&self) -> u64 {
+        self.sender.len() as u64
+    }
+
+    /// 이벤트 구독
+    pub fn subscribe(&self) -> broadcast::Receiver<RuleHitEvent> {
+        self.sender.subscribe()
+    }
+
+    /// perf 샘플 하나 처리
+    pub fn handle_sample(&self, data: &[u8]) {
+        let Some(record) = RuleEventRecord::from_bytes(data) else {
+            self.counters.malformed.fetch_add(1, Ordering::Relaxed);
+            debug!("Discarding malformed rule event ({} bytes)", data.len());
+            return;
+        };
+
+        self.counters.dispatched.fetch_add(1, Ordering::Relaxed);
+
+        let mut event = record.to_event(self.clock_offset_ns);
+        event.geo = geoip::enrich(self.geo.as_deref(), &event.src_ip);
+        self.history.record(DaemonEvent::RuleHit(event.clone()));
+
+        // 구독자가 없으면 전송 실패는 무시
+        let _ = self.sender.send(event);
+    }
+
+    /// 자동 완화 이벤트를 기록에 추가
+    pub fn record_mitigation(&self, event: MitigationEvent) {
+        self.history.record(DaemonEvent::Mitigation(event));
+    }
+
+    /// 텔레메트리 수집 구간의 규칙 매치 요약을 기록에 추가
+    pub fn record_rule_matched(&self, event: RuleMatchedEvent) {
+        self.history.record(DaemonEvent::RuleMatched(event));
+    }
+
+    /// 인터페이스 링크 상태 변화를 기록에 추가
+    pub fn record_link_state(&self, event: LinkStateEvent) {
+        self.history.record(DaemonEvent::LinkState(event));
+    }
+
+    /// 규칙 만료 예고를 기록에 추가
+    pub fn record_rule_expiring(&self, event: RuleExpiryEvent) {
+        self.history.record(DaemonEvent::RuleExpiring(event));
+    }
+
+    /// 규칙 만료(삭제)를 기록에 추가
+    pub fn record_rule_expired(&self, event: RuleExpiryEvent) {
+        self.history.record(DaemonEvent::RuleExpired(event));
+    }
+
+    /// XDP_ABORTED 경보를 기록에 추가
+    pub fn record_xdp_aborted(&self, event: XdpAbortedEvent) {
+        self.history.record(DaemonEvent::XdpAborted(event));
+    }
+
+    /// 트래픽 이상 감지를 기록에 추가
+    pub fn record_anomaly(&self, event: AnomalyEvent) {
+        self.history.record(DaemonEvent::Anomaly(event));
+    }
+
+    /// 완화 이벤트 채널을 구독하여 기록에 추가하는 작업 시작
+    pub fn spawn_mitigation_recorder(&self, mut receiver: broadcast::Receiver<MitigationEvent>) -> JoinHandle<()> {
+        let dispatcher = self.clone();
+
+        tokio::spawn(async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(event) => dispatcher.record_mitigation(event),
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        dispatcher.record_lagged(skipped);
+                        warn!("Event history lagged behind mitigation events, skipped {}", skipped);
+                    },
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        })
+    }
+
+    /// 커널 버퍼 유실 기록
+    pub fn record_lost(&self, count: u64) {
+        self.counters.lost.fetch_add(count, Ordering::Relaxed);
+    }
+
+    /// 구독자 지연으로 건너뛴 이벤트 기록
+    pub fn record_lagged(&self, count: u64) {
+        self.counters.lagged.fetch_add(count, Ordering::Relaxed);
+    }
+
+    /// 현재 카운터
+    pub fn counts(&self) -> DispatchCounts {
+        DispatchCounts {
+            dispatched: self.counters.dispatched.load(Ordering::Relaxed),
+            malformed: self.counters.malformed.load(Ordering::Relaxed),
+            lost: self.counters.lost.load(Ordering::Relaxed),
+            lagged: self.counters.lagged.load(Ordering::Relaxed),
+        }
+    }
+
+    /// 구독자 작업 시작
+    ///
+    /// 구독자가 밀리면 건너뛴 이벤트 수를 기록하고 최신 이벤트부터 계속 처리한다.
+    pub fn spawn_consumer<F>(&self, name: &'static str, mut handler: F) -> JoinHandle<()>
+    where
+        F: FnMut(RuleHitEvent) + Send + 'static,
+    {
+        let mut receiver = self.subscribe();
+        let dispatcher = self.clone();
+
+        tokio::spawn(async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(event) => handler(event),
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        dispatcher.record_lagged(skipped);
+                        throttled_warn!(&format!("events:lagged:{}", name), logthrottle::HOT_PATH_INTERVAL,
+                            "Rule event consumer '{}' lagged, skipped {} events", name, skipped);
+                    },
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        })
+    }
+
+    /// 구성에 따라 웹훅 및 syslog 구독자 시작
+    ///
+    /// `webhook`은 호출자가 `config.webhook_url`로 시작한 전송기이다. 이벤트 기록
+    /// 크기는 `with_history_size(config.history_size)`로 따로 지정한다.
+    pub fn start_consumers(&self, config: &EventsConfig, syslog: Option<SyslogSink>,
+                           webhook: Option<WebhookSender>) -> Vec<JoinHandle<()>> {
+        let mut consumers = Vec::new();
+
+        if let Some(webhook) = webhook {
+            consumers.push(self.spawn_consumer("webhook", move |event| webhook.send(DaemonEvent::RuleHit(event))));
+        }
+
+        if let Some(sink) = syslog.filter(|_| config.syslog) {
+            consumers.push(self.spawn_consumer("syslog", move |event| sink.send(&SecurityEvent::from(&event))));
+        }
+
+        consumers
+    }
+
+    /// perf 버퍼 수신 (오류가 나기 전에는 반환하지 않음)
+    ///
+    /// 맵은 Send가 아니므로 별도 스레드 대신 perf 버퍼의 epoll fd를 런타임에
+    /// 등록하고 읽을 레코드가 있을 때만 처리한다.
+    pub async fn run(&self, map: &KernelMap) -> Result<()> {
+        let perf = map.with(|map| PerfBufferBuilder::new(map)
+            .sample_cb(|_cpu: i32, data: &[u8]| self.handle_sample(data))
+            .lost_cb(|cpu: i32, count: u64| {
+                debug!("Lost {} rule events on CPU {}", count, cpu);
+                self.record_lost(count);
+            })
+            .pages(PERF_BUFFER_PAGES)
+            .build()
+            .map_err(|e| anyhow!("Failed to open rule event perf buffer: {}", e)))?;
+
+        let ready = AsyncFd::with_interest(perf.epoll_fd(), Interest::READABLE)
+            .map_err(|e| anyhow!("Failed to watch rule event perf buffer: {}", e))?;
+        let mut report = time::interval(DROP_REPORT_INTERVAL);
+        report.tick().await; // 첫 틱은 즉시 완료
+        let mut reported = self.counts();
+
+        loop {
+            tokio::select! {
+                guard = ready.readable() => {
+                    let mut guard = guard
+                        .map_err(|e| anyhow!("Failed to wait for rule event perf buffer: {}", e))?;
+                    perf.consume()
+                        .map_err(|e| anyhow!("Failed to read rule event perf buffer: {}", e))?;
+                    guard.clear_ready();
+                },
+                _ = report.tick() => {
+                    let counts = self.counts();
+                    report_drops(&reported, &counts);
+                    reported = counts;
+                },
+            }
+        }
+    }
+}
+
+/// 직전 보고 이후 유실이 있으면 경고
+fn report_drops(previous: &DispatchCounts, current: &DispatchCounts) {
+    let dropped = current.dropped() - previous.dropped();
+    if dropped == 0 {
+        return;
+    }
+
+    warn!("Dropped {} rule events in the last {}s (lost in kernel: {}, lagging consumers: {}, malformed: {})",
+        dropped, DROP_REPORT_INTERVAL.as_secs(),
+        current.lost - previous.lost,
+        current.lagged - previous.lagged,
+        current.malformed - previous.malformed);
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    /// struct rule_event 레이아웃대로 레코드 생성
+    pub fn record_bytes(label: &str, action: u8) -> Vec<u8> {
+        let mut data = Vec::with_capacity(RuleEventRecord::SIZE);
+        data.extend_from_slice(&5_000_000_000u64.to_le_bytes()); // timestamp
+        data.extend_from_slice(&32u32.to_le_bytes());             // key.prefix_len
+        data.extend_from_slice(&[192, 168, 1, 100]);              // key.addr
+        data.extend_from_slice(&[192, 168, 1, 100]);              // saddr
+        data.extend_from_slice(&[10, 0, 0, 1]);                   // daddr
+        data.extend_from_slice(&40000u16.to_le_bytes());          // sport
+        data.extend_from_slice(&80u16.to_le_bytes());             // dport
+        data.push(action);
+        data.push(6);                                             // protocol
+        data.extend_from_slice(&[0, 0]);
+        let mut label_bytes = [0u8; 32];
+        label_bytes[..label.len()].copy_from_slice(label.as_bytes());
+        data.extend_from_slice(&label_bytes);
+        data
+    }
+
+    #[test]
+    fn test_decode_record() {
+        let record = RuleEventRecord::from_bytes(&record_bytes("block-web", 2)).unwrap();
+        assert_eq!(record, RuleEventRecord {
+            timestamp: 5_000_000_000,
+            prefix_len: 32,
+            rule_addr: 0xC0A80164,
+            saddr: 0xC0A80164,
+            daddr: 0x0A000001,
+            sport: 40000,
+            dport: 80,
+            action: 2,
+            protocol: 6,
+            label: "block-web".to_string(),
+        });
+
+        let event = record.to_event(1_700_000_000_000_000_000);
+        assert_eq!(event, RuleHitEvent {
+            label: "block-web".to_string(),
+            rule_key: "192.168.1.100/32".to_string(),
+            action: "drop".to_string(),
+            protocol: "tcp".to_string(),
+            src_ip: "192.168.1.100".to_string(),
+            src_port: 40000,
+            dst_ip: "10.0.0.1".to_string(),
+            dst_port: 80,
+            timestamp_ns: 1_700_000_005_000_000_000,
+            geo: None,
+        });
+    }
+
+    #[test]
+    fn test_decode_edge_cases() {
+        // perf 샘플은 8바이트 정렬로 패딩될 수 있음
+        let mut padded = record_bytes("x", 3);
+        padded.extend_from_slice(&[0xAA; 4]);
+        assert_eq!(RuleEventRecord::from_bytes(&padded).unwrap().label, "x");
+
+        // NUL 없이 가득 찬 레이블
+        let full = "a".repeat(32);
+        assert_eq!(RuleEventRecord::from_bytes(&record_bytes(&full, 1)).unwrap().label, full);
+
+        assert!(RuleEventRecord::from_bytes(&record_bytes("short", 2)[..63]).is_none());
+        assert_eq!(RuleEventRecord::from_bytes(&record_bytes("odd", 9)).unwrap().to_event(0).action, "unknown");
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_and_lag_accounting() {
+        let dispatcher = EventDispatcher::with_clock_offset(0);
+        let mut fast = dispatcher.subscribe();
+        let mut slow = dispatcher.subscribe();
+
+        dispatcher.handle_sample(&record_bytes("a", 2));
+        dispatcher.handle_sample(&[0u8; 10]);
+        dispatcher.record_lost(7);
+
+        assert_eq!(fast.recv().await.unwrap().label, "a");
+
+        // 채널 용량을 넘기면 느린 구독자는 건너뛴 수를 보고받음
+        for _ in 0..EVENT_CHANNEL_CAPACITY {
+            dispatcher.handle_sample(&record_bytes("b", 2));
+        }
+        match slow.recv().await {
+            Err(broadcast::error::RecvError::Lagged(skipped)) => dispatcher.record_lagged(skipped),
+            other => panic!("expected lag, got {:?}", other),
+        }
+
+        let counts = dispatcher.counts();
+        assert_eq!(counts.dispatched, EVENT_CHANNEL_CAPACITY as u64 + 1);
+        assert_eq!(counts.malformed, 1);
+        assert_eq!(counts.lost, 7);
+        assert_eq!(counts.lagged, 1);
+        assert_eq!(counts.dropped(), 9);
+    }
+
+    #[tokio::test]
+    async fn test_consumer_receives_events() {
+        let dispatcher = EventDispatcher::with_clock_offset(0);
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let consumer = dispatcher.spawn_consumer("test", move |event| {
+            let _ = tx.send(event.label);
+        });
+
+        dispatcher.handle_sample(&record_bytes("seen", 1));
+        assert_eq!(rx.recv().await.unwrap(), "seen");
+
+        consumer.abort();
+    }
+
+    #[tokio::test]
+    async fn test_events_carry_geo() {
+        let resolver = geoip::tests::stub(&[("192.168.1.100", "KR", 4766)]);
+        let dispatcher = EventDispatcher::with_clock_offset(0).with_geo(Arc::new(resolver));
+        let mut events = dispatcher.subscribe();
+
+        dispatcher.handle_sample(&record_bytes("a", 2));
+        let event = events.recv().await.unwrap();
+        assert_eq!(event.geo.unwrap().to_string(), "KR, AS4766");
+
+        // 해석기가 없으면 geo 없음
+        let dispatcher = EventDispatcher::with_clock_offset(0);
+        let mut events = dispatcher.subscribe();
+        dispatcher.handle_sample(&record_bytes("a", 2));
+        assert_eq!(events.recv().await.unwrap().geo, None);
+    }
+
+    fn hit(label: &str) -> DaemonEvent {
+        DaemonEvent::RuleHit(RuleEventRecord::from_bytes(&record_bytes(label, 2)).unwrap().to_event(0))
+    }
+
+    fn mitigation(src_ip: &str) -> DaemonEvent {
+        DaemonEvent::Mitigation(MitigationEvent {
+            kind: "synflood".to_string(),
+            src_ip: src_ip.to_string(),
+            syn_pps: 5000,
+            label: format!("auto-synflood-{}", src_ip),
+            action: "drop".to_string(),
+            expire: 60,
+            dry_run: false,
+            timestamp: 100,
+            geo: None,
+        })
+    }
+
+    fn seqs(page: &EventPage) -> Vec<u64> {
+        page.events.iter().map(|event| event.seq).collect()
+    }
+
+    #[test]
+    fn test_history_ring_eviction_and_gap() {
+        let history = EventHistory::new(3);
+        for n in 1..=5 {
+            assert_eq!(history.record(hit(&format!("e{}", n))), n);
+        }
+
+        // 1, 2는 밀려났으므로 빈 구간으로 보고
+        let page = history.query(Some(0), 10, &[]);
+        assert_eq!(seqs(&page), [3, 4, 5]);
+        assert_eq!(page.gap, Some(EventGap { first_seq: 1, last_seq: 2 }));
+        assert_eq!(page.gap.unwrap().missed(), 2);
+        assert_eq!(page.latest_seq, 5);
+
+        let page = history.query(Some(1), 10, &[]);
+        assert_eq!(page.gap, Some(EventGap { first_seq: 2, last_seq: 2 }));
+
+        // 남아 있는 구간부터는 빈 구간 없음
+        let page = history.query(Some(2), 10, &[]);
+        assert_eq!((seqs(&page), page.gap), (vec![3, 4, 5], None));
+
+        // 최신 순번까지 받은 구독자에게는 새 이벤트 없음
+        let page = history.query(Some(5), 10, &[]);
+        assert_eq!((seqs(&page), page.gap), (vec![], None));
+
+        // 마지막 순번보다 큰 순번은 재시작 전 순번으로 보고 처음부터
+        let page = history.query(Some(9), 10, &[]);
+        assert_eq!((seqs(&page), page.gap), (vec![3, 4, 5], Some(EventGap { first_seq: 1, last_seq: 2 })));
+    }
+
+    #[test]
+    fn test_history_limit_and_kinds() {
+        let history = EventHistory::new(10);
+        history.record(hit("a"));
+        history.record(mitigation("192.168.1.10"));
+        history.record(hit("b"));
+        history.record(mitigation("192.168.1.20"));
+        history.record(hit("c"));
+
+        // 순번 지정은 오래된 것부터, 미지정은 최근 것
+        assert_eq!(seqs(&history.query(Some(1), 2, &[])), [2, 3]);
+        assert_eq!(seqs(&history.query(None, 2, &[])), [4, 5]);
+        assert_eq!(seqs(&history.query(None, 100, &[])), [1, 2, 3, 4, 5]);
+
+        let page = history.query(Some(0), 0, &[]);
+        assert!(page.events.is_empty());
+        assert_eq!(page.latest_seq, 5);
+
+        let kinds = ["mitigation".to_string()];
+        assert_eq!(seqs(&history.query(Some(0), 10, &kinds)), [2, 4]);
+        assert_eq!(seqs(&history.query(None, 1, &kinds)), [4]);
+        assert!(history.query(Some(0), 10, &["unknown".to_string()]).events.is_empty());
+    }
+
+    #[test]
+    fn test_history_without_capacity() {
+        // 기록하지 않아도 순번은 부여되고 요청한 구간 전체가 빈 구간
+        let history = EventHistory::new(0);
+        history.record(hit("a"));
+        history.record(hit("b"));
+
+        let page = history.query(Some(0), 10, &[]);
+        assert!(page.events.is_empty());
+        assert_eq!(page.gap, Some(EventGap { first_seq: 1, last_seq: 2 }));
+        assert_eq!(history.query(Some(2), 10, &[]).gap, None);
+    }
+
+    #[cfg(feature = "grpc")]
+    #[tokio::test]
+    async fn test_subscribe_since_replays_then_follows() {
+        let history = EventHistory::new(2);
+        for label in ["a", "b", "c"] {
+            history.record(hit(label));
+        }
+
+        let (page, mut receiver) = history.subscribe_since(0);
+        assert_eq!(seqs(&page), [2, 3]);
+        assert_eq!(page.gap, Some(EventGap { first_seq: 1, last_seq: 1 }));
+
+        // 재생 목록 이후 이벤트만 실시간으로 수신
+        history.record(mitigation("192.168.1.10"));
+        let event = receiver.recv().await.unwrap();
+        assert_eq!((event.seq, event.event.kind()), (4, "mitigation"));
+        assert!(receiver.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_dispatcher_records_history() {
+        let dispatcher = EventDispatcher::with_clock_offset(0).with_history_size(2);
+        let shared = dispatcher.clone();
+
+        dispatcher.handle_sample(&record_bytes("a", 2));
+        dispatcher.handle_sample(&[0u8; 10]);
+        shared.record_mitigation(match mitigation("192.168.1.10") {
+            DaemonEvent::Mitigation(event) => event,
+            other => panic!("unexpected event: {:?}", other),
+        });
+        dispatcher.handle_sample(&record_bytes("b", 2));
+
+        // 형식이 맞지 않는 레코드는 순번을 받지 않고, 복제본은 같은 기록을 공유
+        let page = shared.history().query(Some(0), 10, &[]);
+        assert_eq!(seqs(&page), [2, 3]);
+        assert_eq!(page.events[0].event.kind(), "mitigation");
+        assert_eq!(page.gap, Some(EventGap { first_seq: 1, last_seq: 1 }));
+    }
+}
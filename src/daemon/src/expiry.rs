@@ -0,0 +1,441 @@
+//! 규칙 만료 알림 모듈
+//! 만료가 가까운 규칙과 만료된 규칙을 이벤트 버스와 규칙 소유자 웹훅으로 알림
+//!
+//! 텔레메트리 루프가 주기마다 `ExpiryNotifier::tick`을 호출한다. 남은 수명이 알림
+//! 기준(규칙의 `notify_before`, 없으면 `expiry.notify_before`) 이하가 된 규칙은
+//! RuleExpiring을, 만료된 규칙은 삭제 직전의 규칙 정보와 최종 통계를 담은 RuleExpired를
+//! 한 번씩 보낸 뒤 만료 규칙을 삭제한다. 보낸 알림은 규칙 키와 생성 시각으로 상태
+//! 파일에 기록하므로 데몬을 다시 시작해도 같은 규칙에 같은 알림을 다시 보내지 않는다.
+//! 자동 완화 규칙은 완화 이벤트로 알리므로 제외한다.
+
+use anyhow::{Context, Result};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use crate::error::DaemonError;
+use crate::maps::MapManager;
+use crate::webhook::{WebhookMetrics, WebhookOptions, WebhookSender};
+
+use swift_guard::api::{DaemonEvent, RuleExpiryEvent, RuleInfo};
+use swift_guard::utils;
+
+/// 만료 알림을 보낸 뒤 상태를 남겨 두는 시간 (초, 재시작 직후 규칙이 돌아오기 전에 잊지 않도록)
+const STATE_RETENTION_SECS: u64 = 86_400;
+
+/// 규칙 하나에 보낸 알림
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+struct Notified {
+    /// 규칙 생성 시각 (같은 레이블로 다시 추가한 규칙과 구분)
+    creation_time: u64,
+    /// 만료 시각 (UNIX 초)
+    expires_at: u64,
+    /// 만료 예고를 보냄
+    expiring: bool,
+    /// 만료 알림을 보냄
+    expired: bool,
+}
+
+/// 상태 파일 형식
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+struct ExpiryState {
+    /// 규칙 키별 보낸 알림
+    rules: BTreeMap<String, Notified>,
+}
+
+/// 보낼 만료 알림
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExpiryNotice {
+    /// 규칙 소유자 웹훅 (없으면 이벤트 버스에만 기록)
+    pub webhook: Option<String>,
+    /// RuleExpiring 또는 RuleExpired 이벤트
+    pub event: DaemonEvent,
+}
+
+/// 규칙 만료 알림기
+#[derive(Debug)]
+pub struct ExpiryNotifier {
+    /// 규칙에 알림 기준이 없을 때 만료 예고를 보낼 남은 시간 (초, 0이면 예고하지 않음)
+    notify_before: u64,
+    state: ExpiryState,
+    /// 상태 파일 (없으면 저장하지 않음)
+    path: Option<PathBuf>,
+}
+
+impl ExpiryNotifier {
+    /// 저장하지 않는 알림기 생성
+    pub fn new(notify_before: u64) -> Self {
+        Self {
+            notify_before,
+            state: ExpiryState::default(),
+            path: None,
+        }
+    }
+
+    /// 상태 파일에서 알림기 복원 (파일이 없으면 빈 상태)
+    pub fn load(path: impl Into<PathBuf>, notify_before: u64) -> Result<Self> {
+        let path = path.into();
+        let mut notifier = Self::new(notify_before);
+
+        match fs::read_to_string(&path) {
+            Ok(content) => {
+                notifier.state = serde_json::from_str(&content)
+                    .with_context(|| format!("Invalid expiry state file {}", path.display()))?;
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+        }
+
+        notifier.path = Some(path);
+        Ok(notifier)
+    }
+
+    /// 상태 파일 저장 (임시 파일에 쓴 뒤 교체)
+    pub fn save(&self) -> Result<()> {
+        let path = match &self.path {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+        }
+
+        let content = serde_json::to_vec_pretty(&self.state)
+            .context("Failed to serialize expiry state")?;
+        let tmp = path.with_extension("tmp");
+        fs::write(&tmp, content)
+            .with_context(|| format!("Failed to write {}", tmp.display()))?;
+        fs::rename(&tmp, path)
+            .with_context(|| format!("Failed to replace {}", path.display()))
+    }
+
+    /// 규칙 목록 관측 (`rules`는 통계를 포함한 전체 규칙)
+    ///
+    /// 아직 보내지 않은 알림을 규칙 키 순서로 돌려주고 보낸 것으로 기록한다. 데몬이
+    /// 멈춰 있는 동안 만료된 규칙은 예고 없이 만료 알림만 보낸다.
+    pub fn observe(&mut self, rules: &[RuleInfo], now: u64) -> Vec<ExpiryNotice> {
+        let mut notices: Vec<(String, ExpiryNotice)> = Vec::new();
+        let mut current = HashSet::new();
+
+        for rule in rules.iter().filter(|rule| !rule.auto) {
+            let Some(ttl) = utils::ttl_remaining(rule.creation_time, rule.expire, now) else {
+                continue;
+            };
+            let key = rule.key();
+            current.insert(key.clone());
+
+            let expires_at = rule.creation_time + u64::from(rule.expire);
+            let notified = self.state.rules.entry(key.clone())
+                .and_modify(|notified| {
+                    // 같은 레이블로 다시 추가한 규칙은 처음부터
+                    if notified.creation_time != rule.creation_time {
+                        *notified = Notified::default();
+                    }
+                })
+                .or_default();
+            notified.creation_time = rule.creation_time;
+            notified.expires_at = expires_at;
+
+            let lead = rule.notify_before.map(u64::from).unwrap_or(self.notify_before);
+            let event = RuleExpiryEvent { rule: rule.clone(), expires_at, timestamp: now };
+            let event = if ttl == 0 && !notified.expired {
+                notified.expired = true;
+                notified.expiring = true;
+                DaemonEvent::RuleExpired(event)
+            } else if ttl > 0 && ttl <= lead && !notified.expiring {
+                notified.expiring = true;
+                DaemonEvent::RuleExpiring(event)
+            } else {
+                continue;
+            };
+
+            notices.push((key, ExpiryNotice { webhook: rule.owner_webhook.clone(), event }));
+        }
+
+        // 삭제된 규칙의 기록은 만료 시각에서 보존 기간이 지나면 잊음
+        self.state.rules.retain(|key, notified| {
+            current.contains(key) || notified.expires_at + STATE_RETENTION_SECS > now
+        });
+
+        notices.sort_by(|a, b| a.0.cmp(&b.0));
+        notices.into_iter().map(|(_, notice)| notice).collect()
+    }
+
+    /// 만료 처리 (텔레메트리 수집 주기마다 호출)
+    ///
+    /// 알림을 정한 뒤 만료 규칙을 삭제하고 상태 파일을 저장한다. 만료 규칙 삭제를 함께
+    /// 하므로 다른 만료 처리(`MapManager::remove_expired_rules`)보다 먼저 호출해야
+    /// 최종 통계를 놓치지 않는다.
+    pub fn tick(&mut self, map_manager: &mut MapManager, now: u64) -> Result<Vec<ExpiryNotice>, DaemonError> {
+        let rules = map_manager.list_rules(true)?;
+        let notices = self.observe(&rules, now);
+        let removed = map_manager.remove_expired_rules(now);
+
+        if let Err(e) = self.save() {
+            throttled_warn!("expiry:state", crate::logthrottle::HOT_PATH_INTERVAL,
+                "Failed to save expiry state: {:#}", e);
+        }
+
+        removed?;
+        Ok(notices)
+    }
+}
+
+/// 규칙 소유자 웹훅 전송기 모음 (URL마다 전송 작업 하나)
+///
+/// 전송기는 처음 알림을 보낼 때 만들고, `retain`으로 더 이상 어느 규칙도 쓰지 않는
+/// URL의 전송기를 해제한다. 해제한 전송 작업은 큐에 남은 알림을 보낸 뒤 끝난다.
+#[derive(Debug)]
+pub struct OwnerWebhooks {
+    options: WebhookOptions,
+    metrics: Arc<WebhookMetrics>,
+    senders: HashMap<String, WebhookSender>,
+}
+
+impl OwnerWebhooks {
+    /// 전송기 모음 생성 (`metrics`는 다른 웹훅과 공유하는 카운터)
+    pub fn new(options: WebhookOptions, metrics: Arc<WebhookMetrics>) -> Self {
+        Self {
+            options,
+            metrics,
+            senders: HashMap::new(),
+        }
+    }
+
+    /// 알림 전송 (웹훅이 없는 알림은 무시, 기다리지 않음)
+    pub fn send(&mut self, notice: &ExpiryNotice) {
+        let Some(url) = &notice.webhook else {
+            return;
+        };
+
+        if !self.senders.contains_key(url) {
+            match WebhookSender::spawn(url, self.options.clone(), self.metrics.clone()) {
+                Ok((sender, _task)) => {
+                    info!("Started expiry webhook sender for {}", url);
+                    self.senders.insert(url.clone(), sender);
+                }
+                Err(e) => {
+                    warn!("Failed to start expiry webhook for {}: {:#}", url, e);
+                    return;
+                }
+            }
+        }
+
+        self.senders[url].send(notice.event.clone());
+    }
+
+    /// `urls`에 없는 웹훅의 전송기 해제
+    pub fn retain(&mut self, urls: &HashSet<&str>) {
+        self.senders.retain(|url, _| urls.contains(url.as_str()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::MemoryMap;
+    use crate::maps::MapBackends;
+    use crate::ruleset;
+    use anyhow::anyhow;
+    use swift_guard::api::{RuleSpec, RuleStats};
+    use swift_guard::types::ActionType;
+
+    /// 규칙 생성 시각
+    const START: u64 = 1_700_000_000;
+
+    fn rule(label: &str, expire: u32, notify_before: Option<u32>, webhook: Option<&str>) -> RuleInfo {
+        RuleInfo {
+            label: label.to_string(),
+            action: "pass".to_string(),
+            src_ip: Some("10.0.0.1".to_string()),
+            expire,
+            creation_time: START,
+            notify_before,
+            owner_webhook: webhook.map(str::to_string),
+            stats: RuleStats { packets: 10, bytes: 1000, ..RuleStats::default() },
+            ..RuleInfo::default()
+        }
+    }
+
+    fn expiry(notice: &ExpiryNotice) -> &RuleExpiryEvent {
+        match &notice.event {
+            DaemonEvent::RuleExpiring(event) | DaemonEvent::RuleExpired(event) => event,
+            other => panic!("unexpected event: {:?}", other),
+        }
+    }
+
+    /// 알림 요약 (종류, 규칙 키, 웹훅)
+    fn summary(notices: &[ExpiryNotice]) -> Vec<(&'static str, String, Option<String>)> {
+        notices.iter()
+            .map(|notice| (notice.event.kind(), expiry(notice).rule.key(), notice.webhook.clone()))
+            .collect()
+    }
+
+    fn kinds(notices: &[ExpiryNotice]) -> Vec<(&'static str, String)> {
+        notices.iter().map(|notice| (notice.event.kind(), expiry(notice).rule.label.clone())).collect()
+    }
+
+    #[test]
+    fn test_clock_progression_with_mixed_lead_times() {
+        let owner = Some("http://ops:8080/expiry");
+        let rules = [
+            // 구성 기본값(300초) 사용
+            rule("default-lead", 3600, None, owner),
+            // 규칙마다 다른 예고 시간
+            rule("long-lead", 3600, Some(1800), owner),
+            rule("short-lead", 1200, Some(60), None),
+            // 예고 없이 만료 알림만
+            rule("no-lead", 600, Some(0), owner),
+            // 수명이 예고 시간보다 짧으면 처음 관측에서 바로 예고
+            rule("short-lived", 120, None, None),
+            rule("permanent", 0, None, owner),
+        ];
+        let mut notifier = ExpiryNotifier::new(300);
+
+        let mut timeline = Vec::new();
+        for now in (START..=START + 3700).step_by(10) {
+            // 만료된 규칙은 삭제되므로 다음 관측에서 빠짐
+            let alive: Vec<RuleInfo> = rules.iter()
+                .filter(|rule| !timeline.contains(&("rule_expired", rule.label.clone())))
+                .cloned()
+                .collect();
+            for (kind, label) in kinds(&notifier.observe(&alive, now)) {
+                timeline.push((kind, label.clone()));
+                let at = now - START;
+                let expected = match (kind, label.as_str()) {
+                    ("rule_expiring", "short-lived") => at == 0,
+                    ("rule_expired", "short-lived") => at == 120,
+                    ("rule_expired", "no-lead") => at == 600,
+                    ("rule_expiring", "short-lead") => at == 1140,
+                    ("rule_expired", "short-lead") => at == 1200,
+                    ("rule_expiring", "long-lead") => at == 1800,
+                    ("rule_expiring", "default-lead") => at == 3300,
+                    ("rule_expired", "long-lead" | "default-lead") => at == 3600,
+                    _ => false,
+                };
+                assert!(expected, "unexpected {} for {} at +{}s", kind, label, at);
+            }
+        }
+
+        // 모든 알림은 한 번씩만
+        assert_eq!(timeline.len(), 9);
+        let unique: HashSet<_> = timeline.iter().collect();
+        assert_eq!(unique.len(), timeline.len());
+        assert!(!timeline.iter().any(|(_, label)| label == "permanent"));
+    }
+
+    #[test]
+    fn test_notice_carries_rule_and_webhook() {
+        let mut notifier = ExpiryNotifier::new(300);
+        let rules = vec![
+            rule("b-web", 600, None, Some("http://ops:8080/expiry")),
+            rule("a-db", 600, None, None),
+        ];
+
+        let notices = notifier.observe(&rules, START + 600);
+        assert_eq!(summary(&notices), [
+            ("rule_expired", "a-db".to_string(), None),
+            ("rule_expired", "b-web".to_string(), Some("http://ops:8080/expiry".to_string())),
+        ]);
+        let event = expiry(&notices[1]);
+        assert_eq!((event.expires_at, event.timestamp), (START + 600, START + 600));
+        assert_eq!(event.rule, rules[0]);
+        assert_eq!(event.rule.stats.packets, 10);
+
+        // 자동 완화 규칙은 완화 이벤트로 알림
+        let auto = RuleInfo { auto: true, ..rule("auto-synflood-10.0.0.9", 60, None, None) };
+        assert!(notifier.observe(&[auto], START + 60).is_empty());
+
+        // 같은 레이블로 다시 추가한 규칙은 새 규칙으로 알림
+        let readded = RuleInfo { creation_time: START + 700, ..rules[1].clone() };
+        assert_eq!(kinds(&notifier.observe(&[readded], START + 1000)), [("rule_expiring", "a-db".to_string())]);
+    }
+
+    #[test]
+    fn test_state_survives_restart() {
+        let dir = std::env::temp_dir().join(format!("swift-guard-expiry-{}", std::process::id()));
+        let path = dir.join("expiry-state.json");
+        let rules = vec![rule("maintenance", 3600, Some(600), Some("http://ops:8080/expiry"))];
+
+        let mut notifier = ExpiryNotifier::load(&path, 300).unwrap();
+        assert_eq!(kinds(&notifier.observe(&rules, START + 3000)), [("rule_expiring", "maintenance".to_string())]);
+        notifier.save().unwrap();
+        assert!(!path.with_extension("tmp").exists());
+
+        // 재시작 후 예고를 다시 보내지 않고 만료 알림만
+        let mut restarted = ExpiryNotifier::load(&path, 300).unwrap();
+        assert!(restarted.observe(&rules, START + 3010).is_empty());
+        assert_eq!(kinds(&restarted.observe(&rules, START + 3600)), [("rule_expired", "maintenance".to_string())]);
+        restarted.save().unwrap();
+
+        // 삭제가 끝나기 전에 재시작해도 만료 알림을 다시 보내지 않음
+        let mut again = ExpiryNotifier::load(&path, 300).unwrap();
+        assert!(again.observe(&rules, START + 3620).is_empty());
+
+        // 삭제된 규칙의 기록은 보존 기간이 지나면 잊음
+        again.observe(&[], START + 3600 + STATE_RETENTION_SECS);
+        assert!(again.state.rules.is_empty());
+
+        fs::write(&path, "not json").unwrap();
+        assert!(ExpiryNotifier::load(&path, 300).is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_tick_removes_expired_rules() {
+        let filter_rules = MemoryMap::new();
+        let mut manager = MapManager::with_backends(MapBackends {
+            filter_rules: Some(&filter_rules),
+            ..Default::default()
+        });
+        let no_redirect = |_: &str| Err(anyhow!("no redirect"));
+        let temporary = RuleSpec::new("temporary", ActionType::Drop)
+            .with_src_ip("192.0.2.0/24")
+            .with_expire(600)
+            .with_owner_webhook("http://ops:8080/expiry", Some(120));
+        manager.add_rule(ruleset::build_rule(temporary, None, START, no_redirect).unwrap()).unwrap();
+        let permanent = RuleSpec::new("permanent", ActionType::Pass).with_src_ip("198.51.100.0/24");
+        manager.add_rule(ruleset::build_rule(permanent, None, START, no_redirect).unwrap()).unwrap();
+
+        let mut notifier = ExpiryNotifier::new(300);
+        assert!(notifier.tick(&mut manager, START + 100).unwrap().is_empty());
+        assert_eq!(kinds(&notifier.tick(&mut manager, START + 500).unwrap()), [("rule_expiring", "temporary".to_string())]);
+        assert!(manager.has_rule("temporary"));
+
+        let notices = notifier.tick(&mut manager, START + 610).unwrap();
+        assert_eq!(summary(&notices), [
+            ("rule_expired", "temporary".to_string(), Some("http://ops:8080/expiry".to_string())),
+        ]);
+        let event = expiry(&notices[0]);
+        assert_eq!((event.rule.notify_before, event.expires_at), (Some(120), START + 600));
+        assert!(!manager.has_rule("temporary"));
+        assert!(manager.has_rule("permanent"));
+        assert!(notifier.tick(&mut manager, START + 620).unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_owner_webhooks_share_sender_per_url() {
+        let mut webhooks = OwnerWebhooks::new(WebhookOptions::default(), Arc::new(WebhookMetrics::new()));
+        let mut notifier = ExpiryNotifier::new(300);
+        let rules = vec![
+            rule("a", 600, None, Some("http://127.0.0.1:9/a")),
+            rule("b", 600, None, Some("http://127.0.0.1:9/a")),
+            rule("c", 600, None, Some("http://127.0.0.1:9/c")),
+            rule("d", 600, None, None),
+        ];
+
+        for notice in notifier.observe(&rules, START + 400) {
+            webhooks.send(&notice);
+        }
+        assert_eq!(webhooks.senders.len(), 2);
+
+        webhooks.retain(&HashSet::from(["http://127.0.0.1:9/c"]));
+        assert_eq!(webhooks.senders.len(), 1);
+    }
+}
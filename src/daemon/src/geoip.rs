@@ -0,0 +1,225 @@
+//! GeoIP 조회 모듈
+//! MaxMind mmdb 데이터베이스로 IP 주소의 국가와 ASN 조회
+//!
+//! 데이터베이스는 메모리 매핑으로 열고, 구성되지 않았거나 열 수 없으면 경고만 남기고
+//! 빈 결과를 돌려준다. 조회하는 쪽은 `GeoResolver` 트레이트만 알기 때문에 테스트에서는
+//! 고정된 결과를 돌려주는 해석기로 바꿔 쓸 수 있다.
+//!
+//! 국가 차단 규칙은 국가 데이터베이스 전체를 훑어 국가 코드가 같은 IPv4 네트워크를 모은다.
+
+use anyhow::{anyhow, Context, Result};
+use ipnetwork::{IpNetwork, Ipv4Network};
+use log::{info, warn};
+use maxminddb::{geoip2, Metadata, Mmap, Reader};
+use serde::Deserialize;
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr};
+use std::path::Path;
+
+use swift_guard::api::GeoInfo;
+
+use crate::config::GeoIpConfig;
+
+/// IP 주소 → 국가/ASN 해석기
+pub trait GeoResolver: Send + Sync + fmt::Debug {
+    /// 주소의 GeoIP 정보 (데이터베이스에 없으면 빈 값)
+    fn lookup(&self, ip: IpAddr) -> GeoInfo;
+
+    /// 국가 코드(대문자)에 속한 IPv4 프리픽스와 데이터 버전
+    fn country_prefixes(&self, _code: &str) -> Result<(Vec<(u32, u32)>, String)> {
+        Err(anyhow!("No GeoIP country database is loaded (geoip.mmdb_path)"))
+    }
+}
+
+/// 문자열 주소의 GeoIP 정보 (해석기가 없거나 결과가 비면 None)
+pub fn enrich(resolver: Option<&dyn GeoResolver>, ip: &str) -> Option<GeoInfo> {
+    let ip = ip.parse().ok()?;
+    Some(resolver?.lookup(ip)).filter(|geo| !geo.is_empty())
+}
+
+/// MaxMind mmdb 해석기 (국가, ASN 데이터베이스)
+pub struct MmdbResolver {
+    country: Option<Reader<Mmap>>,
+    asn: Option<Reader<Mmap>>,
+    /// 국가 데이터베이스 경로 (국가 집합은 매번 다시 열어 갱신된 파일을 읽음)
+    country_path: Option<String>,
+}
+
+impl fmt::Debug for MmdbResolver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MmdbResolver")
+            .field("country", &self.country.is_some())
+            .field("asn", &self.asn.is_some())
+            .finish()
+    }
+}
+
+impl MmdbResolver {
+    /// 구성된 데이터베이스 열기 (하나도 열지 못하면 None)
+    pub fn open(config: &GeoIpConfig) -> Option<Self> {
+        let resolver = Self {
+            country: config.mmdb_path.as_deref().and_then(open_reader),
+            asn: config.asn_mmdb_path.as_deref().and_then(open_reader),
+            country_path: config.mmdb_path.clone(),
+        };
+
+        if resolver.country.is_none() && resolver.asn.is_none() {
+            return None;
+        }
+        Some(resolver)
+    }
+}
+
+/// 데이터베이스 하나를 메모리 매핑으로 열기 (실패하면 경고 후 None)
+fn open_reader(path: &str) -> Option<Reader<Mmap>> {
+    match Reader::open_mmap(Path::new(path)) {
+        Ok(reader) => {
+            info!("Opened GeoIP database {} ({})", path, reader.metadata.database_type);
+            Some(reader)
+        },
+        Err(e) => {
+            warn!("GeoIP database {} is unavailable, lookups from it are skipped: {}", path, e);
+            None
+        },
+    }
+}
+
+/// 데이터베이스 버전 문자열 (종류와 빌드 날짜, 예: "GeoLite2-Country 2024-05-03")
+fn database_version(metadata: &Metadata) -> String {
+    let built = chrono::DateTime::from_timestamp(metadata.build_epoch as i64, 0)
+        .map(|t| t.format("%Y-%m-%d").to_string())
+        .unwrap_or_else(|| metadata.build_epoch.to_string());
+    format!("{} {}", metadata.database_type, built)
+}
+
+/// 국가 코드만 읽는 레코드 (전체 순회에서 이름 표 디코딩을 피함)
+#[derive(Deserialize)]
+struct CountryCode<'a> {
+    #[serde(borrow)]
+    country: Option<IsoCode<'a>>,
+    #[serde(borrow)]
+    registered_country: Option<IsoCode<'a>>,
+}
+
+#[derive(Deserialize)]
+struct IsoCode<'a> {
+    iso_code: Option<&'a str>,
+}
+
+impl CountryCode<'_> {
+    /// 조회와 같은 규칙의 국가 코드 (국가가 없으면 등록 국가)
+    fn code(&self) -> Option<&str> {
+        self.country.as_ref().and_then(|country| country.iso_code)
+            .or_else(|| self.registered_country.as_ref().and_then(|country| country.iso_code))
+    }
+}
+
+impl GeoResolver for MmdbResolver {
+    fn lookup(&self, ip: IpAddr) -> GeoInfo {
+        let mut geo = GeoInfo::default();
+
+        // 주소가 없으면 AddressNotFoundError이므로 오류는 빈 값으로 취급
+        if let Some(Ok(record)) = self.country.as_ref().map(|reader| reader.lookup::<geoip2::Country>(ip)) {
+            geo.country = record.country
+                .and_then(|country| country.iso_code)
+                .or_else(|| record.registered_country.and_then(|country| country.iso_code))
+                .map(str::to_string);
+        }
+
+        if let Some(Ok(record)) = self.asn.as_ref().map(|reader| reader.lookup::<geoip2::Asn>(ip)) {
+            geo.asn = record.autonomous_system_number;
+            geo.as_org = record.autonomous_system_organization.map(str::to_string);
+        }
+
+        geo
+    }
+
+    /// 데몬이 시작할 때 매핑한 파일이 아니라 지금 경로의 파일을 읽으므로, 데이터베이스를
+    /// 바꾼 뒤 `refresh-countries`를 실행하면 새 데이터로 집합을 만든다.
+    fn country_prefixes(&self, code: &str) -> Result<(Vec<(u32, u32)>, String)> {
+        let path = self.country_path.as_deref()
+            .ok_or_else(|| anyhow!("No GeoIP country database is loaded (geoip.mmdb_path)"))?;
+        let reader = Reader::open_mmap(Path::new(path))
+            .with_context(|| format!("Failed to open GeoIP country database {}", path))?;
+
+        let everything = IpNetwork::V4(Ipv4Network::new(Ipv4Addr::UNSPECIFIED, 0)?);
+        let mut prefixes = Vec::new();
+        for item in reader.within::<CountryCode>(everything).context("Failed to walk GeoIP country database")? {
+            let item = item.context("Failed to read GeoIP country database")?;
+            if let (IpNetwork::V4(network), Some(found)) = (item.ip_net, item.info.code()) {
+                if found == code {
+                    prefixes.push((u32::from(network.network()), u32::from(network.prefix())));
+                }
+            }
+        }
+
+        Ok((prefixes, database_version(&reader.metadata)))
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::io::Write;
+
+    /// 고정된 결과를 돌려주는 해석기
+    #[derive(Debug, Default)]
+    pub struct StubResolver(pub HashMap<IpAddr, GeoInfo>);
+
+    impl GeoResolver for StubResolver {
+        fn lookup(&self, ip: IpAddr) -> GeoInfo {
+            self.0.get(&ip).cloned().unwrap_or_default()
+        }
+
+        /// 국가가 같은 IPv4 주소마다 /32 (버전은 "stub")
+        fn country_prefixes(&self, code: &str) -> Result<(Vec<(u32, u32)>, String)> {
+            let prefixes = self.0.iter()
+                .filter(|(_, geo)| geo.country.as_deref() == Some(code))
+                .filter_map(|(ip, _)| match ip {
+                    IpAddr::V4(ip) => Some((u32::from(*ip), 32)),
+                    IpAddr::V6(_) => None,
+                })
+                .collect();
+            Ok((prefixes, "stub".to_string()))
+        }
+    }
+
+    pub fn stub(entries: &[(&str, &str, u32)]) -> StubResolver {
+        StubResolver(entries.iter().map(|(ip, country, asn)| (ip.parse().unwrap(), GeoInfo {
+            country: Some(country.to_string()),
+            asn: Some(*asn),
+            as_org: None,
+        })).collect())
+    }
+
+    #[test]
+    fn test_enrich() {
+        let resolver = stub(&[("203.0.113.5", "KR", 4766)]);
+
+        let geo = enrich(Some(&resolver), "203.0.113.5").unwrap();
+        assert_eq!((geo.country.as_deref(), geo.asn), (Some("KR"), Some(4766)));
+
+        // 데이터베이스에 없는 주소, 잘못된 주소, 해석기 없음
+        assert_eq!(enrich(Some(&resolver), "198.51.100.1"), None);
+        assert_eq!(enrich(Some(&resolver), "not-an-ip"), None);
+        assert_eq!(enrich(None, "203.0.113.5"), None);
+    }
+
+    #[test]
+    fn test_missing_database_degrades() {
+        let dir = std::env::temp_dir().join(format!("swift-guard-geoip-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let garbage = dir.join("garbage.mmdb");
+        std::fs::File::create(&garbage).unwrap().write_all(b"not a maxmind database").unwrap();
+
+        // 구성하지 않음, 없는 파일, 형식이 틀린 파일 모두 해석기 없이 동작
+        assert!(MmdbResolver::open(&GeoIpConfig::default()).is_none());
+        assert!(MmdbResolver::open(&GeoIpConfig {
+            mmdb_path: Some(dir.join("missing.mmdb").to_string_lossy().into_owned()),
+            asn_mmdb_path: Some(garbage.to_string_lossy().into_owned()),
+        }).is_none());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
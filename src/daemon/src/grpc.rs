@@ -0,0 +1,429 @@
+//! gRPC 서버 모듈
+//! tonic 기반 gRPC 관리 인터페이스 (grpc 기능)
+//!
+//! 각 RPC는 `ApiRequest`로 변환되어 `ApiServer::serve_forwarded`로 전달되므로
+//! TCP API와 같은 `process_request` 로직을 거친다.
+
+use anyhow::{Context, Result};
+use log::{info, warn};
+use std::net::SocketAddr;
+use std::pin::Pin;
+use tokio::sync::{broadcast, mpsc, oneshot};
+use tokio_stream::wrappers::{errors::BroadcastStreamRecvError, BroadcastStream};
+use tokio_stream::{Stream, StreamExt};
+use tonic::{Request, Response, Status};
+
+use crate::events::EventDispatcher;
+use crate::mitigation::MitigationEvent;
+use crate::server::ForwardedRequest;
+
+use swift_guard::api::{AddressFamily, ApiRequest, ApiResponse, EventGap, WasmModuleMode};
+use swift_guard::grpc::{self as pb, swift_guard_server::{SwiftGuard, SwiftGuardServer}};
+
+type ResponseStream<T> = Pin<Box<dyn Stream<Item = Result<T, Status>> + Send>>;
+
+/// gRPC 서비스
+#[derive(Debug, Clone)]
+pub struct GrpcService {
+    /// API 서버로 요청 전달
+    requests: mpsc::Sender<ForwardedRequest>,
+    /// 완화 이벤트 발행 채널
+    events: broadcast::Sender<MitigationEvent>,
+    /// 규칙 적중 이벤트 배포기
+    hits: EventDispatcher,
+}
+
+impl GrpcService {
+    /// 새로운 gRPC 서비스 생성
+    pub fn new(
+        requests: mpsc::Sender<ForwardedRequest>,
+        events: broadcast::Sender<MitigationEvent>,
+        hits: EventDispatcher,
+    ) -> Self {
+        Self { requests, events, hits }
+    }
+
+    /// 서비스 실행
+    pub async fn serve(self, addr: SocketAddr) -> Result<()> {
+        info!("gRPC server listening on {}", addr);
+
+        tonic::transport::Server::builder()
+            .add_service(SwiftGuardServer::new(self))
+            .serve(addr)
+            .await
+            .context(format!("gRPC server on {} failed", addr))
+    }
+
+    /// 이벤트 기록에서 `since_seq` 이후를 재생한 뒤 실시간 이벤트로 이어가는 스트림
+    ///
+    /// 기록에서 밀려난 구간과 느린 구독자가 건너뛴 이벤트는 kind "gap" 이벤트로 알린다.
+    fn replay_events(&self, since_seq: u64) -> ResponseStream<pb::Event> {
+        let (page, receiver) = self.hits.history().subscribe_since(since_seq);
+        let mut last_seq = page.events.last().map(|event| event.seq).unwrap_or(page.latest_seq);
+
+        let replay = page.gap.map(pb::Event::from).into_iter()
+            .chain(page.events.into_iter().map(pb::Event::from))
+            .map(Ok);
+
+        let live = BroadcastStream::new(receiver).filter_map(move |event| match event {
+            Ok(event) => {
+                last_seq = event.seq;
+                Some(Ok(pb::Event::from(event)))
+            },
+            Err(BroadcastStreamRecvError::Lagged(skipped)) => {
+                warn!("gRPC event replay subscriber lagged, skipped {} events", skipped);
+                let gap = EventGap { first_seq: last_seq + 1, last_seq: last_seq + skipped };
+                last_seq = gap.last_seq;
+                Some(Ok(pb::Event::from(gap)))
+            },
+        });
+
+        Box::pin(tokio_stream::iter(replay).chain(live))
+    }
+
+    /// 요청을 API 서버로 전달하고 응답 대기
+    async fn call<T>(&self, request: &Request<T>, api_request: ApiRequest) -> Result<ApiResponse, Status> {
+        self.call_in(request, api_request, None).await
+    }
+
+    /// 규칙 요청을 네임스페이스와 함께 API 서버로 전달하고 응답 대기
+    ///
+    /// API 토큰은 `authorization: Bearer <토큰>`, 요청 ID는 `x-request-id` 메타데이터로 받는다.
+    async fn call_in<T>(
+        &self,
+        request: &Request<T>,
+        api_request: ApiRequest,
+        namespace: Option<String>,
+    ) -> Result<ApiResponse, Status> {
+        let peer = request.remote_addr()
+            .map(|addr| format!("peer:{}", addr.ip()))
+            .unwrap_or_else(|| "peer:unknown".to_string());
+        let token = request.metadata().get("authorization")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .map(str::to_string);
+        let request_id = request.metadata().get("x-request-id")
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
+
+        let (reply, response) = oneshot::channel();
+        self.requests.send(ForwardedRequest { request: api_request, token, namespace, request_id, peer, reply })
+            .await
+            .map_err(|_| Status::unavailable("API server is not running"))?;
+
+        response.await.map_err(|_| Status::internal("API server dropped the request"))
+    }
+}
+
+#[tonic::async_trait]
+impl SwiftGuard for GrpcService {
+    type ListRulesStream = ResponseStream<pb::Rule>;
+    type StreamEventsStream = ResponseStream<pb::Event>;
+
+    async fn attach_interface(&self, request: Request<pb::AttachInterfaceRequest>) -> Result<Response<pb::StatusReply>, Status> {
+        let api_request = ApiRequest::Attach {
+            interface: request.get_ref().interface.clone(),
+            mode: request.get_ref().mode,
+            force: request.get_ref().force,
+            fallback: None,
+            obj_path: None,
+        };
+        let response = self.call(&request, api_request).await?;
+        pb::status_reply(response).map(Response::new)
+    }
+
+    async fn detach_interface(&self, request: Request<pb::DetachInterfaceRequest>) -> Result<Response<pb::StatusReply>, Status> {
+        let api_request = ApiRequest::Detach { interface: request.get_ref().interface.clone() };
+        let response = self.call(&request, api_request).await?;
+        pb::status_reply(response).map(Response::new)
+    }
+
+    async fn add_rule(&self, request: Request<pb::AddRuleRequest>) -> Result<Response<pb::StatusReply>, Status> {
+        let api_request = ApiRequest::try_from(request.get_ref().clone())?;
+        let namespace = request.get_ref().namespace.clone();
+        let response = self.call_in(&request, api_request, namespace).await?;
+        pb::status_reply(response).map(Response::new)
+    }
+
+    async fn delete_rule(&self, request: Request<pb::DeleteRuleRequest>) -> Result<Response<pb::StatusReply>, Status> {
+        let api_request = ApiRequest::DeleteRule { label: request.get_ref().label.clone() };
+        let namespace = request.get_ref().namespace.clone();
+        let response = self.call_in(&request, api_request, namespace).await?;
+        pb::status_reply(response).map(Response::new)
+    }
+
+    async fn list_rules(&self, request: Request<pb::ListRulesRequest>) -> Result<Response<Self::ListRulesStream>, Status> {
+        let api_request = ApiRequest::ListRules {
+            include_stats: request.get_ref().include_stats,
+            expiring_within_secs: None,
+            include_rates: request.get_ref().include_rates,
+            all_namespaces: request.get_ref().all_namespaces,
+            family: match request.get_ref().family.as_deref() {
+                Some(family) => Some(AddressFamily::parse(family)
+                    .ok_or_else(|| Status::invalid_argument(format!("Invalid family: {}", family)))?),
+                None => None,
+            },
+            include_dynamic: request.get_ref().include_dynamic,
+            origin: request.get_ref().origin.clone(),
+            // 스트림 응답은 프레임 크기 제한이 없으므로 나누지 않음
+            cursor: None,
+            offset: 0,
+            limit: 0,
+        };
+        let namespace = request.get_ref().namespace.clone();
+        match self.call_in(&request, api_request, namespace).await? {
+            ApiResponse::Rules { rules, .. } => {
+                let stream = tokio_stream::iter(rules.into_iter().map(pb::Rule::from).map(Ok));
+                Ok(Response::new(Box::pin(stream)))
+            },
+            other => Err(pb::response_status(other)),
+        }
+    }
+
+    async fn get_stats(&self, request: Request<pb::GetStatsRequest>) -> Result<Response<pb::Stats>, Status> {
+        match self.call(&request, ApiRequest::GetStats {}).await? {
+            ApiResponse::Stats { stats } => Ok(Response::new(stats.into())),
+            other => Err(pb::response_status(other)),
+        }
+    }
+
+    async fn load_wasm_module(&self, request: Request<pb::LoadWasmModuleRequest>) -> Result<Response<pb::StatusReply>, Status> {
+        let on_error = match request.get_ref().on_error.as_str() {
+            "" => None,
+            policy => Some(policy.parse().map_err(|e| Status::invalid_argument(format!("{:#}", e)))?),
+        };
+        let mode = match request.get_ref().mode.as_str() {
+            "" => WasmModuleMode::Enforce,
+            mode => mode.parse().map_err(|e| Status::invalid_argument(format!("{:#}", e)))?,
+        };
+        let api_request = ApiRequest::LoadWasmModule {
+            name: request.get_ref().name.clone(),
+            file_path: request.get_ref().file_path.clone(),
+            on_error,
+            config: Some(request.get_ref().config.clone()).filter(|config| !config.is_empty()),
+            mode,
+        };
+        let response = self.call(&request, api_request).await?;
+        pb::status_reply(response).map(Response::new)
+    }
+
+    async fn unload_wasm_module(&self, request: Request<pb::UnloadWasmModuleRequest>) -> Result<Response<pb::StatusReply>, Status> {
+        let api_request = ApiRequest::UnloadWasmModule { name: request.get_ref().name.clone() };
+        let response = self.call(&request, api_request).await?;
+        pb::status_reply(response).map(Response::new)
+    }
+
+    async fn list_wasm_modules(&self, request: Request<pb::ListWasmModulesRequest>) -> Result<Response<pb::ListWasmModulesReply>, Status> {
+        match self.call(&request, ApiRequest::ListWasmModules {}).await? {
+            ApiResponse::WasmModules { modules } => Ok(Response::new(pb::ListWasmModulesReply {
+                modules: modules.into_iter().map(pb::WasmModule::from).collect(),
+            })),
+            other => Err(pb::response_status(other)),
+        }
+    }
+
+    async fn get_wasm_module_stats(&self, request: Request<pb::WasmModuleStatsRequest>) -> Result<Response<pb::WasmModuleStats>, Status> {
+        let api_request = ApiRequest::WasmModuleStats { name: request.get_ref().name.clone() };
+        match self.call(&request, api_request).await? {
+            ApiResponse::WasmModuleStats { name, processed_packets, blocked_packets, avg_processing_time_us,
+                                           state, errors, last_error, latency, p50_us, p95_us, p99_us,
+                                           mode, would_block_packets } => {
+                Ok(Response::new(pb::WasmModuleStats {
+                    name,
+                    processed_packets,
+                    blocked_packets,
+                    avg_processing_time_us,
+                    state: state.unwrap_or_default(),
+                    errors,
+                    last_error: last_error.unwrap_or_default(),
+                    latency_bounds_us: latency.bounds_us,
+                    latency_counts: latency.counts,
+                    p50_us,
+                    p95_us,
+                    p99_us,
+                    mode: mode.as_str().to_string(),
+                    would_block_packets,
+                }))
+            },
+            other => Err(pb::response_status(other)),
+        }
+    }
+
+    async fn stream_events(&self, request: Request<pb::StreamEventsRequest>) -> Result<Response<Self::StreamEventsStream>, Status> {
+        if let Some(since_seq) = request.get_ref().since_seq {
+            return Ok(Response::new(self.replay_events(since_seq)));
+        }
+
+        // 느린 구독자는 밀린 이벤트를 건너뛰고 계속 받는다
+        let mitigations = BroadcastStream::new(self.events.subscribe()).filter_map(|event| match event {
+            Ok(event) => Some(Ok(pb::Event::from(event))),
+            Err(e) => {
+                warn!("gRPC event subscriber lagged: {}", e);
+                None
+            }
+        });
+
+        let hits = self.hits.clone();
+        let rule_hits = BroadcastStream::new(self.hits.subscribe()).filter_map(move |event| match event {
+            Ok(event) => Some(Ok(pb::Event::from(event))),
+            Err(BroadcastStreamRecvError::Lagged(skipped)) => {
+                hits.record_lagged(skipped);
+                warn!("gRPC rule event subscriber lagged, skipped {} events", skipped);
+                None
+            }
+        });
+
+        Ok(Response::new(Box::pin(mitigations.merge(rule_hits))))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::tests::record_bytes;
+    use std::sync::{Arc, Mutex};
+    use swift_guard::api::{GeoInfo, RuleInfo};
+    use swift_guard::grpc::swift_guard_client::SwiftGuardClient;
+    use tokio::net::TcpListener;
+    use tokio_stream::wrappers::TcpListenerStream;
+
+    fn rule(label: &str) -> RuleInfo {
+        RuleInfo {
+            label: label.to_string(),
+            src_ip: Some("192.168.1.100".to_string()),
+            dst_port: Some("80".to_string()),
+            protocol: "tcp".to_string(),
+            priority: 10,
+            ..RuleInfo::default()
+        }
+    }
+
+    /// API 서버 대역이 받은 요청 (요청, 피어, 토큰, 네임스페이스, 요청 ID)
+    type Forwarded = (ApiRequest, String, Option<String>, Option<String>, Option<String>);
+
+    /// 전달된 요청에 고정 응답을 돌려주는 API 서버 대역
+    fn spawn_api(mut requests: mpsc::Receiver<ForwardedRequest>) -> Arc<Mutex<Vec<Forwarded>>> {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let recorded = seen.clone();
+        tokio::spawn(async move {
+            while let Some(forwarded) = requests.recv().await {
+                let response = match &forwarded.request {
+                    ApiRequest::AddRule(rule) => ApiResponse::Success { message: format!("Rule '{}' added", rule.label), warning: None },
+                    ApiRequest::DeleteRule { label } => ApiResponse::NotFound { resource: "rule".to_string(), name: label.clone() },
+                    ApiRequest::ListRules { .. } => ApiResponse::Rules {
+                        rules: vec![rule("a"), rule("b"), rule("c")],
+                        total_count: 3,
+                        next_cursor: None,
+                        seq: None,
+                    },
+                    _ => ApiResponse::Error { message: "unsupported".to_string(), code: Default::default() },
+                };
+                recorded.lock().unwrap().push((forwarded.request, forwarded.peer, forwarded.token, forwarded.namespace, forwarded.request_id));
+                let _ = forwarded.reply.send(response);
+            }
+        });
+        seen
+    }
+
+    #[tokio::test]
+    async fn test_grpc_round_trip() {
+        let (request_tx, request_rx) = mpsc::channel(8);
+        let (event_tx, _) = broadcast::channel(8);
+        let api = spawn_api(request_rx);
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let hits = EventDispatcher::with_clock_offset(0);
+        let service = GrpcService::new(request_tx, event_tx.clone(), hits.clone());
+        let server = tokio::spawn(tonic::transport::Server::builder()
+            .add_service(SwiftGuardServer::new(service))
+            .serve_with_incoming(TcpListenerStream::new(listener)));
+
+        let mut client = SwiftGuardClient::connect(format!("http://{}", addr)).await.unwrap();
+
+        // 단항 RPC
+        let reply = client.add_rule(pb::AddRuleRequest {
+            label: "web".to_string(),
+            dst_port_min: 80,
+            dst_port_max: 80,
+            protocol: 6,
+            action: 2,
+            ..Default::default()
+        }).await.unwrap();
+        assert_eq!(reply.into_inner().message, "Rule 'web' added");
+
+        // 오류 응답은 gRPC 상태 코드로 변환
+        let mut delete = Request::new(pb::DeleteRuleRequest { label: "missing".to_string(), namespace: Some("team-a".to_string()) });
+        delete.metadata_mut().insert("authorization", "Bearer team-a-secret".parse().unwrap());
+        delete.metadata_mut().insert("x-request-id", "req-7".parse().unwrap());
+        let status = client.delete_rule(delete).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::NotFound);
+
+        // 서버 스트리밍
+        let mut rules = client.list_rules(pb::ListRulesRequest { include_stats: true, ..Default::default() }).await.unwrap().into_inner();
+        let mut labels = Vec::new();
+        while let Some(rule) = rules.message().await.unwrap() {
+            assert_eq!(rule.address_family, "v4");
+            labels.push(rule.label);
+        }
+        assert_eq!(labels, ["a", "b", "c"]);
+        let family = pb::ListRulesRequest { family: Some("5".to_string()), ..Default::default() };
+        assert_eq!(client.list_rules(family).await.unwrap_err().code(), tonic::Code::InvalidArgument);
+
+        // 이벤트 스트리밍 (구독 등록 후 발행)
+        let mut events = client.stream_events(pb::StreamEventsRequest::default()).await.unwrap().into_inner();
+        while event_tx.receiver_count() == 0 {
+            tokio::task::yield_now().await;
+        }
+        event_tx.send(MitigationEvent {
+            kind: "synflood".to_string(),
+            src_ip: "192.168.1.10".to_string(),
+            syn_pps: 5000,
+            label: "auto-synflood-192.168.1.10".to_string(),
+            action: "drop".to_string(),
+            expire: 60,
+            dry_run: false,
+            timestamp: 100,
+            geo: Some(GeoInfo { country: Some("KR".to_string()), asn: Some(4766), as_org: None }),
+        }).unwrap();
+        let event = events.message().await.unwrap().unwrap();
+        assert_eq!(event.label, "auto-synflood-192.168.1.10");
+        assert_eq!(event.syn_pps, 5000);
+        assert_eq!((event.country.as_str(), event.asn, event.as_org.as_str()), ("KR", 4766, ""));
+
+        // 규칙 적중 이벤트도 같은 스트림으로 전달
+        hits.handle_sample(&record_bytes("block-web", 2));
+        let event = events.message().await.unwrap().unwrap();
+        assert_eq!(event.kind, "rule_hit");
+        assert_eq!(event.label, "block-web");
+        assert_eq!((event.src_port, event.dst_ip.as_str(), event.dst_port), (40000, "10.0.0.1", 80));
+
+        // 순번을 지정하면 기록을 재생한 뒤 실시간 이벤트로 이어감
+        let mut replay = client.stream_events(pb::StreamEventsRequest { since_seq: Some(0) }).await.unwrap().into_inner();
+        let event = replay.message().await.unwrap().unwrap();
+        assert_eq!((event.seq, event.kind.as_str(), event.label.as_str()), (1, "rule_hit", "block-web"));
+        hits.handle_sample(&record_bytes("live", 1));
+        let event = replay.message().await.unwrap().unwrap();
+        assert_eq!((event.seq, event.label.as_str()), (2, "live"));
+
+        server.abort();
+
+        let seen = api.lock().unwrap();
+        assert_eq!(seen.len(), 3);
+        match &seen[0].0 {
+            ApiRequest::AddRule(rule) => {
+                assert_eq!(rule.label, "web");
+                assert_eq!((rule.dst_port_min, rule.dst_port_max), (80, 80));
+                assert_eq!(rule.src_port_max, 65535);
+            },
+            other => panic!("unexpected request: {:?}", other),
+        }
+        assert_eq!(seen[0].1, "peer:127.0.0.1");
+        assert_eq!((seen[0].2.as_deref(), seen[0].3.as_deref()), (None, None));
+
+        // 토큰과 요청 ID는 메타데이터로, 네임스페이스는 요청 필드로 전달
+        assert!(matches!(&seen[1].0, ApiRequest::DeleteRule { label } if label == "missing"));
+        assert_eq!((seen[1].2.as_deref(), seen[1].3.as_deref()), (Some("team-a-secret"), Some("team-a")));
+        assert_eq!((seen[0].4.as_deref(), seen[1].4.as_deref()), (None, Some("req-7")));
+    }
+}
@@ -0,0 +1,200 @@
+//! 규칙 매치 기록 모듈
+//! 규칙별 최근 1시간의 분당 매치 패킷 수 (show-rule의 `history_1m`)
+//!
+//! 텔레메트리가 규칙 통계를 수집할 때마다 구간 변화(`RuleRateTracker`)를 관측 시각이 속한
+//! 분(벽시계 기준, 유닉스 시간 / 60)의 칸에 더한다. 기록은 한 번이라도 매치된 규칙에만
+//! 만들며 규칙마다 60칸으로 고정된다. 목록에서 사라진 규칙의 기록은 다음 수집 때 해제한다.
+
+use std::collections::HashMap;
+
+/// 보관하는 분 수
+pub const HISTORY_MINUTES: usize = 60;
+
+/// 관측 시각이 속한 분
+fn minute_of(now: u64) -> u64 {
+    now / 60
+}
+
+/// 분 단위 링 버퍼
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct MinuteRing {
+    /// 가장 최근 칸의 분
+    latest: u64,
+    /// 분 `m`의 칸은 `m % HISTORY_MINUTES`
+    buckets: Box<[u64; HISTORY_MINUTES]>,
+}
+
+impl MinuteRing {
+    fn new(minute: u64) -> Self {
+        Self { latest: minute, buckets: Box::new([0; HISTORY_MINUTES]) }
+    }
+
+    /// 칸 위치
+    fn slot(minute: u64) -> usize {
+        (minute % HISTORY_MINUTES as u64) as usize
+    }
+
+    /// 창 안의 분인지 (가장 최근 칸 기준 60분)
+    fn holds(&self, minute: u64) -> bool {
+        minute <= self.latest && self.latest - minute < HISTORY_MINUTES as u64
+    }
+
+    /// 분 `minute`에 패킷 수 더하기
+    ///
+    /// 더 나중 분이면 그 사이 칸을 비우며 앞으로 옮긴다. 시계가 되돌아가 창보다 오래된
+    /// 분이면 버린다.
+    fn add(&mut self, minute: u64, packets: u64) {
+        if minute > self.latest {
+            let gap = minute - self.latest;
+            if gap >= HISTORY_MINUTES as u64 {
+                self.buckets.fill(0);
+            } else {
+                for skipped in self.latest + 1..=minute {
+                    self.buckets[Self::slot(skipped)] = 0;
+                }
+            }
+            self.latest = minute;
+        }
+        if self.holds(minute) {
+            let bucket = &mut self.buckets[Self::slot(minute)];
+            *bucket = bucket.saturating_add(packets);
+        }
+    }
+
+    /// `minute`까지 60분의 값 (오래된 분부터, 기록이 없는 분은 0)
+    fn window(&self, minute: u64) -> Vec<u64> {
+        (0..HISTORY_MINUTES as u64)
+            .map(|age| minute.checked_sub(HISTORY_MINUTES as u64 - 1 - age))
+            .map(|m| match m {
+                Some(m) if self.holds(m) => self.buckets[Self::slot(m)],
+                _ => 0,
+            })
+            .collect()
+    }
+}
+
+/// 규칙별 분당 매치 기록
+#[derive(Debug, Default)]
+pub struct HitHistory {
+    /// 규칙 키(`RuleInfo::key`)별 기록 (매치된 적이 있는 규칙만)
+    rings: HashMap<String, MinuteRing>,
+}
+
+impl HitHistory {
+    /// 수집 구간 동안 매치된 패킷 수를 관측 시각(`now`, 유닉스 초)의 분에 기록
+    pub fn record(&mut self, key: &str, packets: u64, now: u64) {
+        if packets == 0 {
+            return;
+        }
+        let minute = minute_of(now);
+        self.rings.entry(key.to_string())
+            .or_insert_with(|| MinuteRing::new(minute))
+            .add(minute, packets);
+    }
+
+    /// `keep`이 거짓인 규칙(삭제된 규칙)의 기록 해제
+    pub fn retain(&mut self, keep: impl Fn(&str) -> bool) {
+        self.rings.retain(|key, _| keep(key));
+    }
+
+    /// 규칙의 최근 60분 분당 매치 패킷 수 (오래된 분부터 `now`의 분까지, 매치된 적이 없으면 빈 목록)
+    pub fn history(&self, key: &str, now: u64) -> Vec<u64> {
+        self.rings.get(key)
+            .map(|ring| ring.window(minute_of(now)))
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 분 경계에서 시작하는 가짜 시계 (유닉스 초)
+    struct Clock(u64);
+
+    impl Clock {
+        fn at(minute: u64, second: u64) -> Self {
+            Self(minute * 60 + second)
+        }
+
+        fn advance(&mut self, secs: u64) -> u64 {
+            self.0 += secs;
+            self.0
+        }
+    }
+
+    /// 창의 마지막 `n`분
+    fn tail(history: &[u64], n: usize) -> &[u64] {
+        &history[history.len() - n..]
+    }
+
+    #[test]
+    fn test_wall_clock_buckets() {
+        let mut history = HitHistory::default();
+        let mut clock = Clock::at(28_000_000, 55);
+
+        // 1초 차이라도 분 경계를 넘으면 다른 칸
+        history.record("web", 10, clock.0);
+        history.record("web", 5, clock.advance(4));
+        history.record("web", 7, clock.advance(1));
+        let window = history.history("web", clock.0);
+        assert_eq!(window.len(), HISTORY_MINUTES);
+        assert_eq!(tail(&window, 3), [0, 15, 7]);
+
+        // 매치가 없던 분은 0, 조회 시각까지 창이 흐름
+        history.record("web", 3, clock.advance(180));
+        assert_eq!(tail(&history.history("web", clock.0), 5), [15, 7, 0, 0, 3]);
+        assert_eq!(tail(&history.history("web", clock.0 + 120), 5), [0, 0, 3, 0, 0]);
+        assert_eq!(history.history("web", clock.0 + 120).iter().sum::<u64>(), 25);
+    }
+
+    #[test]
+    fn test_window_expiry() {
+        let mut history = HitHistory::default();
+        let mut clock = Clock::at(1_000, 0);
+        history.record("dns", 4, clock.0);
+
+        // 60분이 지나면 창에서 빠짐
+        assert_eq!(history.history("dns", clock.0 + 59 * 60)[0], 4);
+        assert!(history.history("dns", clock.0 + 60 * 60).iter().all(|&packets| packets == 0));
+
+        // 긴 공백 뒤 기록은 이전 칸을 모두 비움 (같은 칸 위치를 다시 써도 섞이지 않음)
+        history.record("dns", 9, clock.advance(120 * 60));
+        assert_eq!(history.history("dns", clock.0).iter().sum::<u64>(), 9);
+        history.record("dns", 1, clock.advance(30 * 60));
+        assert_eq!(history.history("dns", clock.0).iter().sum::<u64>(), 10);
+
+        // 시계가 창보다 더 되돌아간 관측은 버리고, 창 안이면 그 분에 더함
+        history.record("dns", 100, clock.0 - 90 * 60);
+        history.record("dns", 2, clock.0 - 30 * 60);
+        let window = history.history("dns", clock.0);
+        assert_eq!((window[29], window[59], window.iter().sum::<u64>()), (11, 1, 12));
+
+        // 시작 시각 근처(유닉스 시간 0)도 처리
+        let mut early = HitHistory::default();
+        early.record("boot", 1, 30);
+        assert_eq!(tail(&early.history("boot", 30), 1), [1]);
+    }
+
+    #[test]
+    fn test_lazy_allocation_and_release() {
+        let mut history = HitHistory::default();
+        let now = Clock::at(5_000, 10).0;
+
+        // 매치된 적이 없는 규칙은 기록을 만들지 않음
+        history.record("idle", 0, now);
+        assert_eq!(history.rings.len(), 0);
+        assert!(history.history("idle", now).is_empty());
+
+        history.record("web", 1, now);
+        history.record("ns1/dns", 2, now);
+        assert_eq!(history.rings.len(), 2);
+        assert_eq!(std::mem::size_of_val(&*history.rings["web"].buckets), HISTORY_MINUTES * 8);
+
+        // 창 중간에 삭제된 규칙은 해제
+        history.retain(|key| key == "ns1/dns");
+        assert_eq!(history.rings.len(), 1);
+        assert!(history.history("web", now).is_empty());
+        assert_eq!(tail(&history.history("ns1/dns", now), 1), [2]);
+    }
+}
@@ -0,0 +1,118 @@
+//! 검사 문맥 모듈
+//! WASM 모듈의 `inspect_packet_v2`에 넘기는 패킷 문맥 (패킷에 적용될 규칙의 레이블과 액션)
+//!
+//! XDP 프로그램은 프레임에 규칙 메타데이터를 붙이지 않으므로, 캐시된 규칙으로 커널과 같은
+//! 순서로 다시 찾는다 (`MapManager::packet_context`). 규칙을 찾지 못하면 레이블이 비고
+//! 출처가 `Unavailable`인 문맥을 넘긴다.
+
+use crate::evaluate::TestPacket;
+use crate::maps::FilterRule;
+use swift_guard::utils::{self, MAX_LABEL_LEN};
+
+/// 게스트 메모리에 쓰는 문맥 크기
+///
+/// `label[32]`(NUL로 채움), `action: u8`, `source: u8`, 예약 6바이트(0).
+pub const CONTEXT_SIZE: usize = 40;
+
+/// 문맥의 레이블 필드 크기
+const LABEL_FIELD_LEN: usize = MAX_LABEL_LEN + 1;
+
+/// 문맥의 규칙 정보 출처
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[repr(u8)]
+pub enum ContextSource {
+    /// 규칙 정보 없음 (일치하는 규칙이 없거나 다시 찾을 수 없는 프레임)
+    #[default]
+    Unavailable = 0,
+    /// 사용자 공간에서 다시 찾은 규칙
+    Rematch = 1,
+}
+
+/// 검사할 패킷의 규칙 문맥
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PacketContext {
+    /// 일치한 규칙 레이블 (커널 filter_rule.label과 같은 네임스페이스 없는 레이블, 규칙 정보가 없으면 빈 문자열)
+    pub label: String,
+    /// 일치한 규칙 액션 (struct filter_rule.action, 규칙 정보가 없으면 0)
+    pub action: u8,
+    pub source: ContextSource,
+}
+
+impl PacketContext {
+    /// 규칙으로 문맥 생성 (레이블은 커널에 기록되는 형태)
+    fn matched(rule: &FilterRule, source: ContextSource) -> Self {
+        let label = utils::split_label(&rule.label).1;
+        Self { label: label.to_string(), action: rule.action, source }
+    }
+
+    /// `rematch`로 프레임에 적용될 규칙을 찾아 문맥 생성
+    ///
+    /// 다시 찾을 수 있는 것은 헤더가 온전한 IPv4 프레임뿐이다 (커널 규칙 맵은 IPv4 전용).
+    pub fn resolve<'r, F>(frame: &[u8], rematch: F) -> Self
+    where
+        F: FnOnce(&TestPacket) -> Option<&'r FilterRule>,
+    {
+        TestPacket::from_frame(frame)
+            .and_then(|packet| rematch(&packet))
+            .map(|rule| Self::matched(rule, ContextSource::Rematch))
+            .unwrap_or_default()
+    }
+
+    /// 게스트 메모리에 쓸 바이트열
+    pub fn to_bytes(&self) -> [u8; CONTEXT_SIZE] {
+        let mut bytes = [0u8; CONTEXT_SIZE];
+        let label = self.label.as_bytes();
+        let len = label.len().min(MAX_LABEL_LEN);
+        bytes[..len].copy_from_slice(&label[..len]);
+        bytes[LABEL_FIELD_LEN] = self.action;
+        bytes[LABEL_FIELD_LEN + 1] = self.source as u8;
+        bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::packet::UdpFrame;
+
+    #[test]
+    fn test_to_bytes() {
+        let context = PacketContext { label: "web".to_string(), action: 2, source: ContextSource::Rematch };
+        let bytes = context.to_bytes();
+        assert_eq!(&bytes[..4], b"web\0");
+        assert_eq!((bytes[32], bytes[33]), (2, 1));
+        assert!(bytes[34..].iter().all(|&b| b == 0));
+
+        // 가장 긴 레이블도 NUL 자리가 남음
+        let long = PacketContext { label: "x".repeat(MAX_LABEL_LEN), ..Default::default() };
+        assert_eq!(long.to_bytes()[MAX_LABEL_LEN - 1], b'x');
+        assert_eq!(long.to_bytes()[MAX_LABEL_LEN], 0);
+        assert_eq!(PacketContext::default().to_bytes(), [0u8; CONTEXT_SIZE]);
+    }
+
+    #[test]
+    fn test_resolve() {
+        let frame = UdpFrame::new(0x0A000001, 0x0A000002, 40000, 53).build();
+        let rule = FilterRule { action: 4, ..crate::conflicts::tests::rule("dns", "10.0.0.0/8", 0) };
+
+        // 프레임 헤더로 다시 찾음
+        let mut seen = None;
+        let context = PacketContext::resolve(&frame, |packet| {
+            seen = Some(*packet);
+            Some(&rule)
+        });
+        assert_eq!(context, PacketContext { label: "dns".to_string(), action: 4, source: ContextSource::Rematch });
+        assert_eq!(seen, Some(TestPacket { src_ip: 0x0A000001, protocol: 17, src_port: 40000, dst_port: 53, tcp_flags: 0 }));
+
+        // 네임스페이스의 규칙도 커널과 같은 레이블
+        let label = format!("team-a/{}", "x".repeat(MAX_LABEL_LEN));
+        let scoped = FilterRule { label, ..rule.clone() };
+        let context = PacketContext::resolve(&frame, |_| Some(&scoped));
+        assert_eq!(context.label, "x".repeat(MAX_LABEL_LEN));
+        assert_eq!(&context.to_bytes()[..MAX_LABEL_LEN], "x".repeat(MAX_LABEL_LEN).as_bytes());
+
+        // 일치하는 규칙이 없거나 다시 찾을 수 없는 프레임
+        assert_eq!(PacketContext::resolve(&frame, |_| None), PacketContext::default());
+        assert_eq!(PacketContext::resolve(&frame[..20], |_| Some(&rule)), PacketContext::default());
+    }
+}
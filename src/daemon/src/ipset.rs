@@ -0,0 +1,175 @@
+//! IP 집합 모듈
+//! 규칙이 소스로 참조하는 이름 붙은 IPv4 프리픽스 집합
+//!
+//! 집합은 참조하는 규칙에 붙어 있고, 프리픽스마다 규칙과 같은 레이블의 LPM 항목으로
+//! 설치된다. 규칙을 삭제하면 집합도 함께 사라진다. 집합을 만들 때 프리픽스를 정규화하여
+//! (호스트 비트 제거, 다른 프리픽스에 포함된 프리픽스 제거, 이웃한 두 프리픽스 병합)
+//! 같은 주소 범위를 가장 적은 항목으로 덮는다.
+
+use std::collections::BTreeSet;
+
+use swift_guard::api::PrefixSetInfo;
+use swift_guard::utils;
+
+/// 이름 붙은 IPv4 프리픽스 집합
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrefixSet {
+    /// 집합 이름 (예: country-RU)
+    pub name: String,
+    /// 정규화한 프리픽스 (호스트 순서 주소, 주소 순 정렬)
+    pub prefixes: Vec<(u32, u32)>,
+    /// 프리픽스를 얻은 원본 (예: "geoip", CSV 파일 경로)
+    pub source: String,
+    /// 원본 데이터 버전 (데이터베이스 빌드 날짜, 파일 해시 등)
+    pub version: String,
+}
+
+impl PrefixSet {
+    /// 프리픽스를 정규화하여 생성
+    pub fn new(name: &str, prefixes: Vec<(u32, u32)>, source: &str, version: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            prefixes: normalize(prefixes),
+            source: source.to_string(),
+            version: version.to_string(),
+        }
+    }
+
+    /// 집합이 덮는 주소 수
+    pub fn address_count(&self) -> u64 {
+        self.prefixes.iter().map(|&(_, prefix_len)| 1u64 << (32 - prefix_len)).sum()
+    }
+
+    /// API 집합 정보로 변환
+    pub fn to_info(&self) -> PrefixSetInfo {
+        PrefixSetInfo {
+            name: self.name.clone(),
+            entries: self.prefixes.len(),
+            addresses: self.address_count(),
+            source: self.source.clone(),
+            version: self.version.clone(),
+        }
+    }
+}
+
+/// 프리픽스 정규화
+///
+/// 호스트 비트를 지우고 주소 순으로 정렬한 뒤, 앞선 프리픽스에 포함되는 프리픽스를
+/// 버리고 한 단계 짧은 프리픽스로 합칠 수 있는 이웃 쌍을 반복해서 합친다.
+/// 결과는 서로 겹치지 않으며 입력과 정확히 같은 주소 범위를 덮는다.
+pub fn normalize(prefixes: Vec<(u32, u32)>) -> Vec<(u32, u32)> {
+    let sorted: BTreeSet<(u32, u32)> = prefixes.into_iter()
+        .map(|(addr, prefix_len)| {
+            let prefix_len = prefix_len.min(32);
+            (utils::mask_ipv4(addr, prefix_len), prefix_len)
+        })
+        .collect();
+
+    let mut merged: Vec<(u32, u32)> = Vec::with_capacity(sorted.len());
+    for prefix in sorted {
+        // 같은 주소에서는 짧은 프리픽스가 먼저 오므로 포함 관계는 바로 앞 항목만 보면 됨
+        if merged.last().is_some_and(|&last| covers(last, prefix)) {
+            continue;
+        }
+
+        merged.push(prefix);
+        while let [.., low, high] = merged[..] {
+            match sibling_parent(low, high) {
+                Some(parent) => {
+                    merged.truncate(merged.len() - 2);
+                    merged.push(parent);
+                },
+                None => break,
+            }
+        }
+    }
+
+    merged
+}
+
+/// `outer`가 `inner`의 주소를 모두 포함하는지
+fn covers((addr, prefix_len): (u32, u32), (inner, inner_len): (u32, u32)) -> bool {
+    prefix_len <= inner_len && utils::mask_ipv4(inner, prefix_len) == addr
+}
+
+/// 두 프리픽스가 한 프리픽스의 두 절반이면 그 프리픽스
+fn sibling_parent((low, low_len): (u32, u32), (high, high_len): (u32, u32)) -> Option<(u32, u32)> {
+    if low_len != high_len || low_len == 0 {
+        return None;
+    }
+
+    let half = 1u32 << (32 - low_len);
+    (low & half == 0 && high == low | half).then_some((low, low_len - 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn prefixes(list: &[&str]) -> Vec<(u32, u32)> {
+        list.iter().map(|s| utils::parse_ip_prefix(s).unwrap()).collect()
+    }
+
+    fn normalized(list: &[&str]) -> Vec<String> {
+        normalize(prefixes(list)).into_iter()
+            .map(|(addr, prefix_len)| format!("{}/{}", utils::ipv4_to_string(addr), prefix_len))
+            .collect()
+    }
+
+    #[test]
+    fn test_normalize_dedups_and_drops_covered() {
+        assert_eq!(normalized(&[
+            "10.1.2.0/24", "10.0.0.0/8", "10.1.2.3/32", "192.168.1.7/24", "192.168.1.0/24", "172.16.0.0/12",
+        ]), ["10.0.0.0/8", "172.16.0.0/12", "192.168.1.0/24"]);
+
+        // 포함하는 프리픽스가 형제 병합으로 만들어지는 경우
+        assert_eq!(normalized(&["10.0.0.0/25", "10.0.0.128/25", "10.0.0.200/32"]), ["10.0.0.0/24"]);
+        assert_eq!(normalized(&["10.0.0.0/25", "10.0.0.128/25", "10.0.0.64/26"]), ["10.0.0.0/24"]);
+        assert_eq!(normalized(&[]), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_normalize_merges_siblings() {
+        // 연쇄 병합: /24 네 개 → /22
+        assert_eq!(normalized(&["10.0.3.0/24", "10.0.1.0/24", "10.0.0.0/24", "10.0.2.0/24"]), ["10.0.0.0/22"]);
+
+        // 이웃하지만 같은 부모가 아니면 합치지 않음
+        assert_eq!(normalized(&["10.0.1.0/24", "10.0.2.0/24"]), ["10.0.1.0/24", "10.0.2.0/24"]);
+
+        // 크기가 다른 이웃
+        assert_eq!(normalized(&["10.0.0.0/24", "10.0.1.0/25", "10.0.1.128/25"]), ["10.0.0.0/23"]);
+
+        // 주소 공간 전체
+        assert_eq!(normalized(&["0.0.0.0/1", "128.0.0.0/1"]), ["0.0.0.0/0"]);
+        assert_eq!(normalized(&["0.0.0.0/0", "10.0.0.0/8"]), ["0.0.0.0/0"]);
+    }
+
+    #[test]
+    fn test_normalize_preserves_coverage() {
+        // 정규화 전후에 덮는 /32 주소가 같은지 작은 범위에서 전수 비교
+        let input = prefixes(&[
+            "10.0.0.0/30", "10.0.0.4/31", "10.0.0.6/32", "10.0.0.7/32", "10.0.0.9/32", "10.0.0.8/29",
+            "10.0.0.32/28", "10.0.0.48/29", "10.0.0.60/30",
+        ]);
+        let output = normalize(input.clone());
+
+        let covered = |set: &[(u32, u32)], addr: u32| set.iter().any(|&prefix| covers(prefix, (addr, 32)));
+        for addr in 0x0A000000..0x0A000100 {
+            assert_eq!(covered(&input, addr), covered(&output, addr), "{}", utils::ipv4_to_string(addr));
+        }
+        assert_eq!(output, prefixes(&["10.0.0.0/28", "10.0.0.32/28", "10.0.0.48/29", "10.0.0.60/30"]));
+
+        // 겹치지 않음
+        for pair in output.windows(2) {
+            assert!(!covers(pair[0], pair[1]) && !covers(pair[1], pair[0]));
+        }
+    }
+
+    #[test]
+    fn test_set_info() {
+        let set = PrefixSet::new("country-KR", prefixes(&["1.0.0.0/24", "1.0.1.0/24", "2.0.0.1/32"]), "geoip", "v1");
+        let info = set.to_info();
+        assert_eq!((info.entries, info.addresses), (2, 513));
+        assert_eq!((info.source.as_str(), info.version.as_str()), ("geoip", "v1"));
+    }
+}
@@ -0,0 +1,346 @@
+//! 링크 상태 감시 모듈
+//! 연결된 인터페이스와 리디렉션 대상 인터페이스의 operstate 변화를 맵 관리자에 반영
+//!
+//! 링크가 내려가면 해당 인터페이스를 down으로 기록하고, 그 인터페이스로 리디렉션하는
+//! 규칙을 degraded로 표시한다. `link.on_link_down: detach`이면 XDP 프로그램도 분리해 둔다.
+//! 링크가 올라오면 분리해 둔 프로그램을 다시 연결하고 리디렉션 대상을 다시 확인한다.
+//! 상태 기계는 `LinkOps`로 시스템 접근을 주입받으므로 실제 인터페이스 없이 시험할 수 있다.
+
+use anyhow::Result;
+use log::{info, warn};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::bpf::{self, XdpMode};
+use crate::config::LinkDownPolicy;
+use crate::error::DaemonError;
+use crate::events::EventDispatcher;
+use crate::maps::MapManager;
+use crate::netif;
+
+use swift_guard::api::LinkStateEvent;
+use swift_guard::error::InvalidArgument;
+use swift_guard::utils;
+
+/// 링크 상태 확인과 XDP 연결에 쓰는 시스템 접근
+pub trait LinkOps {
+    /// 링크가 올라와 있는지
+    fn is_up(&self, interface: &str) -> bool;
+    /// 리디렉션 대상 ifindex (사라졌거나 내려갔으면 None)
+    fn redirect_target(&self, interface: &str) -> Option<u32>;
+    /// XDP 프로그램 분리
+    fn detach_xdp(&self, interface: &str) -> Result<(), DaemonError>;
+    /// XDP 프로그램 다시 연결 (`object`가 None이면 기본 오브젝트)
+    fn attach_xdp(&self, interface: &str, mode: &str, object: Option<&str>) -> Result<(), DaemonError>;
+}
+
+/// /sys/class/net과 ip 명령을 쓰는 시스템 구현
+#[derive(Debug, Clone)]
+pub struct SystemLinks {
+    /// 기본 BPF 오브젝트 경로
+    bpf_obj: Option<PathBuf>,
+}
+
+impl SystemLinks {
+    pub fn new(bpf_obj: Option<PathBuf>) -> Self {
+        Self { bpf_obj }
+    }
+}
+
+impl LinkOps for SystemLinks {
+    fn is_up(&self, interface: &str) -> bool {
+        netif::is_up(interface)
+    }
+
+    fn redirect_target(&self, interface: &str) -> Option<u32> {
+        netif::current_target(interface)
+    }
+
+    fn detach_xdp(&self, interface: &str) -> Result<(), DaemonError> {
+        bpf::unload_xdp_program(interface)
+    }
+
+    fn attach_xdp(&self, interface: &str, mode: &str, object: Option<&str>) -> Result<(), DaemonError> {
+        let mode = XdpMode::from_name(mode)
+            .ok_or_else(|| InvalidArgument::new("mode", format!("Invalid XDP mode: {}", mode)))?;
+        let obj_path = object.map(PathBuf::from).or_else(|| self.bpf_obj.clone())
+            .ok_or_else(|| DaemonError::Config("BPF object path is not configured".to_string()))?;
+        bpf::attach_xdp_program(&obj_path, interface, mode)
+    }
+}
+
+/// 링크 상태 기계
+///
+/// 인터페이스별 마지막 관찰 상태를 기억하고, 상태가 바뀐 관찰만 맵 관리자에 반영한다.
+/// 처음 관찰하는 인터페이스는 올라와 있던 것으로 본다.
+#[derive(Debug)]
+pub struct LinkWatcher {
+    policy: LinkDownPolicy,
+    /// 인터페이스별 링크 상태 (true면 up)
+    states: BTreeMap<String, bool>,
+}
+
+impl LinkWatcher {
+    pub fn new(policy: LinkDownPolicy) -> Self {
+        Self { policy, states: BTreeMap::new() }
+    }
+
+    /// 링크 관찰 결과 반영 (상태가 바뀌었으면 이벤트 반환)
+    ///
+    /// 분리해 둔 XDP 프로그램을 다시 연결하지 못하면 오류를 반환하고 down 상태로 남아
+    /// 다음 관찰에서 재시도한다.
+    pub fn observe(
+        &mut self,
+        map_manager: &mut MapManager<'_>,
+        ops: &dyn LinkOps,
+        interface: &str,
+        up: bool,
+        now: u64,
+    ) -> Result<Option<LinkStateEvent>> {
+        let was_up = self.states.get(interface).copied().unwrap_or(true);
+        if was_up == up {
+            self.states.insert(interface.to_string(), up);
+            return Ok(None);
+        }
+
+        let action = if up {
+            map_manager.link_up(interface, |interface, mode, object| ops.attach_xdp(interface, mode, object))?
+                .then_some("reattached")
+        } else {
+            let detach = (self.policy == LinkDownPolicy::Detach).then_some(|interface: &str| ops.detach_xdp(interface));
+            let detached = match map_manager.link_down(interface, detach) {
+                Ok(detached) => detached,
+                // 분리에 실패해도 링크 상태는 반영
+                Err(e) => {
+                    warn!("{:#}", e);
+                    false
+                }
+            };
+            detached.then_some("detached")
+        };
+        self.states.insert(interface.to_string(), up);
+
+        // 리디렉션 대상 다시 확인 (내려간 대상은 degraded, 올라온 대상은 복구)
+        let rules = map_manager.refresh_redirect_targets(|name| {
+            if name == interface && !up { None } else { ops.redirect_target(name) }
+        })?;
+
+        let state = if up { "up" } else { "down" };
+        if up {
+            info!("Link on {} is up", interface);
+        } else {
+            warn!("Link on {} is down{}", interface,
+                if rules.is_empty() { String::new() } else { format!("; degraded rules: {}", rules.join(", ")) });
+        }
+
+        Ok(Some(LinkStateEvent {
+            interface: interface.to_string(),
+            state: state.to_string(),
+            action: action.map(str::to_string),
+            rules,
+            timestamp: now,
+        }))
+    }
+
+    /// 감시 대상 인터페이스를 한 번 점검해 바뀐 상태를 반영
+    ///
+    /// 감시 대상에서 빠진 인터페이스(분리된 인터페이스, 삭제된 리디렉션 규칙의 대상)는 잊는다.
+    pub fn poll(&mut self, map_manager: &mut MapManager<'_>, ops: &dyn LinkOps, now: u64) -> Vec<LinkStateEvent> {
+        let watched = map_manager.watched_interfaces();
+        self.states.retain(|name, _| watched.contains(name));
+        map_manager.forget_links(&watched);
+
+        let mut events = Vec::new();
+        for interface in &watched {
+            let up = ops.is_up(interface);
+            match self.observe(map_manager, ops, interface, up, now) {
+                Ok(Some(event)) => events.push(event),
+                Ok(None) => {}
+                Err(e) => throttled_warn!(&format!("linkwatch:{}", interface), Duration::from_secs(60), "{:#}", e),
+            }
+        }
+        events
+    }
+}
+
+/// `interval`마다 링크 상태를 점검해 변화를 이벤트 기록에 추가 (반환하지 않음)
+pub async fn run_watcher(
+    mut watcher: LinkWatcher,
+    ops: SystemLinks,
+    map_manager: Arc<Mutex<MapManager<'_>>>,
+    events: EventDispatcher,
+    interval: Duration,
+) {
+    let mut ticker = tokio::time::interval(interval);
+    loop {
+        ticker.tick().await;
+        let changes = match map_manager.lock() {
+            Ok(mut map_manager) => watcher.poll(&mut map_manager, &ops, utils::current_time_secs()),
+            Err(_) => {
+                warn!("Failed to lock map_manager for link watch");
+                continue;
+            }
+        };
+        for change in changes {
+            events.record_link_state(change);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+    use crate::backend::MemoryMap;
+    use crate::maps::{FilterRule, MapBackends};
+    use std::cell::RefCell;
+    use std::collections::BTreeSet;
+    use swift_guard::api::FallbackMode;
+
+    /// 주입한 링크 상태로 동작하는 시험용 시스템
+    #[derive(Default)]
+    struct FakeLinks {
+        down: RefCell<BTreeSet<String>>,
+        calls: RefCell<Vec<String>>,
+        fail_attach: RefCell<bool>,
+    }
+
+    impl FakeLinks {
+        fn set(&self, interface: &str, up: bool) {
+            if up {
+                self.down.borrow_mut().remove(interface);
+            } else {
+                self.down.borrow_mut().insert(interface.to_string());
+            }
+        }
+    }
+
+    impl LinkOps for FakeLinks {
+        fn is_up(&self, interface: &str) -> bool {
+            !self.down.borrow().contains(interface)
+        }
+
+        fn redirect_target(&self, interface: &str) -> Option<u32> {
+            self.is_up(interface).then_some(7)
+        }
+
+        fn detach_xdp(&self, interface: &str) -> Result<(), DaemonError> {
+            self.calls.borrow_mut().push(format!("detach {}", interface));
+            Ok(())
+        }
+
+        fn attach_xdp(&self, interface: &str, mode: &str, object: Option<&str>) -> Result<(), DaemonError> {
+            if *self.fail_attach.borrow() {
+                return Err(DaemonError::Bpf(anyhow!("link not ready")));
+            }
+            self.calls.borrow_mut().push(format!("attach {} {} {}", interface, mode, object.unwrap_or("default")));
+            Ok(())
+        }
+    }
+
+    fn redirect_rule(label: &str, src: u32, ifname: &str) -> FilterRule {
+        FilterRule {
+            src_ip: Some((src, 32)),
+            action: 3,
+            redirect_ifindex: 7,
+            redirect_ifname: Some(ifname.to_string()),
+            label: label.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_redirect_target_down_and_up() {
+        let filter_rules = MemoryMap::new();
+        let redirect = MemoryMap::new();
+        let mut manager = MapManager::with_backends(MapBackends {
+            filter_rules: Some(&filter_rules),
+            redirect: Some(&redirect),
+            ..Default::default()
+        });
+        manager.add_rule(redirect_rule("to-ids", 0xC0A80101, "veth1")).unwrap();
+        manager.attach_interface("eth0", "driver", None, FallbackMode::None, |_| Ok(())).unwrap();
+
+        let links = FakeLinks::default();
+        let mut watcher = LinkWatcher::new(LinkDownPolicy::Keep);
+
+        // 처음 관찰은 올라와 있던 것으로 보므로 변화 없음
+        assert!(watcher.poll(&mut manager, &links, 100).is_empty());
+        assert_eq!(manager.watched_interfaces(), ["eth0", "veth1"]);
+
+        links.set("veth1", false);
+        let events = watcher.poll(&mut manager, &links, 110);
+        assert_eq!(events, vec![LinkStateEvent {
+            interface: "veth1".to_string(),
+            state: "down".to_string(),
+            action: None,
+            rules: vec!["to-ids".to_string()],
+            timestamp: 110,
+        }]);
+        assert!(manager.list_rules(false).unwrap()[0].degraded);
+        assert_eq!(redirect.len(), 0);
+        assert_eq!(manager.down_interfaces().collect::<Vec<_>>(), ["veth1"]);
+
+        // 상태가 그대로면 다시 알리지 않음
+        assert!(watcher.poll(&mut manager, &links, 120).is_empty());
+
+        links.set("veth1", true);
+        let events = watcher.poll(&mut manager, &links, 130);
+        assert_eq!((events[0].state.as_str(), events[0].rules.as_slice()), ("up", ["to-ids".to_string()].as_slice()));
+        assert!(!manager.list_rules(false).unwrap()[0].degraded);
+        assert_eq!(redirect.len(), 1);
+        assert_eq!(manager.down_interfaces().count(), 0);
+
+        // keep 정책은 XDP 프로그램을 건드리지 않음
+        links.set("eth0", false);
+        let events = watcher.poll(&mut manager, &links, 140);
+        assert_eq!((events[0].interface.as_str(), events[0].action.as_deref()), ("eth0", None));
+        assert!(links.calls.borrow().is_empty());
+        let interfaces = manager.list_interfaces();
+        assert_eq!((interfaces[0].link.as_deref(), interfaces[0].suspended), (Some("down"), false));
+    }
+
+    #[test]
+    fn test_detach_policy_reattaches_on_up() {
+        let filter_rules = MemoryMap::new();
+        let mut manager = MapManager::with_backends(MapBackends {
+            filter_rules: Some(&filter_rules),
+            ..Default::default()
+        });
+        manager.attach_interface("eth0", "generic", None, FallbackMode::None, |_| Ok(())).unwrap();
+
+        let links = FakeLinks::default();
+        let mut watcher = LinkWatcher::new(LinkDownPolicy::Detach);
+
+        links.set("eth0", false);
+        let events = watcher.poll(&mut manager, &links, 100);
+        assert_eq!(events[0].action.as_deref(), Some("detached"));
+        assert_eq!(*links.calls.borrow(), ["detach eth0"]);
+        assert!(manager.list_interfaces()[0].suspended);
+
+        // 다시 연결하지 못하면 down으로 남아 다음 점검에서 재시도
+        links.set("eth0", true);
+        *links.fail_attach.borrow_mut() = true;
+        assert!(watcher.poll(&mut manager, &links, 110).is_empty());
+        assert_eq!(manager.list_interfaces()[0].link.as_deref(), Some("down"));
+
+        *links.fail_attach.borrow_mut() = false;
+        let events = watcher.poll(&mut manager, &links, 120);
+        assert_eq!((events[0].state.as_str(), events[0].action.as_deref()), ("up", Some("reattached")));
+        assert_eq!(*links.calls.borrow(), ["detach eth0", "attach eth0 generic default"]);
+        let interfaces = manager.list_interfaces();
+        assert_eq!((interfaces[0].link.as_deref(), interfaces[0].suspended), (Some("up"), false));
+
+        // 분리해 둔 인터페이스를 detach하면 프로그램 분리 없이 연결 정보만 제거
+        links.set("eth0", false);
+        watcher.poll(&mut manager, &links, 130);
+        manager.detach_interface("eth0", |_| panic!("XDP program is already detached")).unwrap();
+        assert!(manager.list_interfaces().is_empty());
+
+        // 감시 대상에서 빠진 인터페이스는 잊음
+        assert!(watcher.poll(&mut manager, &links, 140).is_empty());
+        assert_eq!(manager.down_interfaces().count(), 0);
+    }
+}
@@ -0,0 +1,288 @@
+//! 로그 제한 모듈
+//! 패킷 경로처럼 같은 오류가 폭주할 수 있는 곳의 로그를 키별로 제한하고
+//! 억제된 횟수를 주기적으로 요약
+
+use log::Level;
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tokio::task::JoinHandle;
+
+/// 억제 요약 보고 간격
+pub const FLUSH_INTERVAL: Duration = Duration::from_secs(30);
+
+/// 패킷 경로 오류의 기본 출력 간격
+pub const HOT_PATH_INTERVAL: Duration = Duration::from_secs(10);
+
+/// 시각 공급자 (테스트에서 교체)
+pub trait Clock: Send + Sync {
+    fn now(&self) -> Instant;
+}
+
+/// 시스템 단조 시계
+#[derive(Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// 키별 상태
+#[derive(Debug)]
+struct Entry {
+    /// 마지막 출력 시각
+    last_emit: Instant,
+    /// 최소 출력 간격
+    interval: Duration,
+    /// 마지막 출력 이후 억제된 횟수
+    suppressed: u64,
+    /// 원래 메시지의 로그 레벨
+    level: Level,
+}
+
+/// 억제 요약 (주기 보고용)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Suppressed {
+    pub key: String,
+    pub level: Level,
+    pub count: u64,
+}
+
+/// 키별 로그 제한기
+///
+/// 같은 키의 메시지는 `interval`마다 한 번만 출력하고 나머지는 횟수만 센다.
+/// 간격이 지난 뒤의 첫 메시지는 그동안 억제된 횟수와 함께 출력된다. 메시지가
+/// 더 오지 않으면 `flush`가 남은 횟수를 돌려준다.
+pub struct LogThrottle {
+    clock: Box<dyn Clock>,
+    entries: Mutex<HashMap<String, Entry>>,
+}
+
+impl std::fmt::Debug for LogThrottle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LogThrottle").field("entries", &self.entries).finish()
+    }
+}
+
+impl Default for LogThrottle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LogThrottle {
+    /// 시스템 시계로 제한기 생성
+    pub fn new() -> Self {
+        Self::with_clock(SystemClock)
+    }
+
+    /// 주어진 시계로 제한기 생성
+    pub fn with_clock(clock: impl Clock + 'static) -> Self {
+        Self {
+            clock: Box::new(clock),
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// 메시지 출력 여부 판단
+    ///
+    /// 출력해야 하면 직전 출력 이후 억제된 횟수를, 억제해야 하면 None을 반환한다.
+    pub fn check(&self, key: &str, interval: Duration, level: Level) -> Option<u64> {
+        let now = self.clock.now();
+        let mut entries = match self.entries.lock() {
+            Ok(entries) => entries,
+            Err(_) => return Some(0), // 잠금이 깨졌으면 제한하지 않음
+        };
+
+        match entries.get_mut(key) {
+            Some(entry) if now.saturating_duration_since(entry.last_emit) < entry.interval => {
+                entry.suppressed += 1;
+                None
+            }
+            Some(entry) => {
+                let suppressed = std::mem::take(&mut entry.suppressed);
+                entry.last_emit = now;
+                entry.interval = interval;
+                entry.level = level;
+                Some(suppressed)
+            }
+            None => {
+                entries.insert(key.to_string(), Entry { last_emit: now, interval, suppressed: 0, level });
+                Some(0)
+            }
+        }
+    }
+
+    /// 간격이 지난 키의 억제 횟수 수거
+    ///
+    /// 억제 없이 간격이 지난 키는 지워서 일회성 키가 쌓이지 않게 한다.
+    pub fn flush(&self) -> Vec<Suppressed> {
+        let now = self.clock.now();
+        let mut entries = match self.entries.lock() {
+            Ok(entries) => entries,
+            Err(_) => return Vec::new(),
+        };
+
+        let mut flushed = Vec::new();
+        entries.retain(|key, entry| {
+            if now.saturating_duration_since(entry.last_emit) < entry.interval {
+                return true;
+            }
+            if entry.suppressed == 0 {
+                return false;
+            }
+            flushed.push(Suppressed {
+                key: key.clone(),
+                level: entry.level,
+                count: std::mem::take(&mut entry.suppressed),
+            });
+            entry.last_emit = now;
+            true
+        });
+
+        flushed.sort_by(|a, b| a.key.cmp(&b.key));
+        flushed
+    }
+}
+
+/// 데몬 전역 제한기
+pub fn global() -> &'static LogThrottle {
+    static THROTTLE: OnceLock<LogThrottle> = OnceLock::new();
+    THROTTLE.get_or_init(LogThrottle::new)
+}
+
+/// 전역 제한기의 억제 횟수 출력
+pub fn flush_global() {
+    for suppressed in global().flush() {
+        log::log!(suppressed.level, "{}: previous message repeated {} times",
+            suppressed.key, suppressed.count);
+    }
+}
+
+/// 억제 요약을 주기적으로 출력하는 작업 시작
+pub fn spawn_flusher(interval: Duration) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.tick().await; // 첫 틱은 즉시 완료
+        loop {
+            ticker.tick().await;
+            flush_global();
+        }
+    })
+}
+
+/// 제한된 로그 출력 (`throttled_warn!`, `throttled_error!`의 공통 구현)
+macro_rules! throttled_log {
+    ($level:expr, $key:expr, $interval:expr, $($arg:tt)+) => {{
+        let level = $level;
+        match $crate::logthrottle::global().check($key, $interval, level) {
+            Some(0) => log::log!(level, $($arg)+),
+            Some(suppressed) => log::log!(level, "{} (previous message repeated {} times)",
+                format_args!($($arg)+), suppressed),
+            None => {}
+        }
+    }};
+}
+
+/// 키별로 `interval`마다 한 번만 출력하는 warn!
+macro_rules! throttled_warn {
+    ($key:expr, $interval:expr, $($arg:tt)+) => {
+        throttled_log!(log::Level::Warn, $key, $interval, $($arg)+)
+    };
+}
+
+/// 키별로 `interval`마다 한 번만 출력하는 error!
+macro_rules! throttled_error {
+    ($key:expr, $interval:expr, $($arg:tt)+) => {
+        throttled_log!(log::Level::Error, $key, $interval, $($arg)+)
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    /// 수동으로 진행하는 시계
+    #[derive(Clone)]
+    struct MockClock {
+        start: Instant,
+        offset: Arc<Mutex<Duration>>,
+    }
+
+    impl MockClock {
+        fn new() -> Self {
+            Self { start: Instant::now(), offset: Arc::new(Mutex::new(Duration::ZERO)) }
+        }
+
+        fn advance(&self, by: Duration) {
+            *self.offset.lock().unwrap() += by;
+        }
+    }
+
+    impl Clock for MockClock {
+        fn now(&self) -> Instant {
+            self.start + *self.offset.lock().unwrap()
+        }
+    }
+
+    const SECOND: Duration = Duration::from_secs(1);
+
+    #[test]
+    fn test_throttle_suppresses_within_interval() {
+        let clock = MockClock::new();
+        let throttle = LogThrottle::with_clock(clock.clone());
+
+        assert_eq!(throttle.check("map", 10 * SECOND, Level::Warn), Some(0));
+        for _ in 0..4831 {
+            assert_eq!(throttle.check("map", 10 * SECOND, Level::Warn), None);
+        }
+
+        // 다른 키는 독립적으로 제한
+        assert_eq!(throttle.check("wasm", 10 * SECOND, Level::Warn), Some(0));
+
+        clock.advance(9 * SECOND);
+        assert_eq!(throttle.check("map", 10 * SECOND, Level::Warn), None);
+
+        // 간격이 지나면 억제된 횟수와 함께 출력
+        clock.advance(SECOND);
+        assert_eq!(throttle.check("map", 10 * SECOND, Level::Warn), Some(4832));
+        assert_eq!(throttle.check("map", 10 * SECOND, Level::Warn), None);
+    }
+
+    #[test]
+    fn test_throttle_flush() {
+        let clock = MockClock::new();
+        let throttle = LogThrottle::with_clock(clock.clone());
+
+        throttle.check("map", 10 * SECOND, Level::Warn);
+        throttle.check("map", 10 * SECOND, Level::Warn);
+        throttle.check("map", 10 * SECOND, Level::Warn);
+        throttle.check("quiet", 10 * SECOND, Level::Error);
+        throttle.check("trap", 60 * SECOND, Level::Error);
+        throttle.check("trap", 60 * SECOND, Level::Error);
+
+        // 간격 안에서는 보고하지 않음
+        assert!(throttle.flush().is_empty());
+
+        clock.advance(10 * SECOND);
+        assert_eq!(throttle.flush(), vec![
+            Suppressed { key: "map".to_string(), level: Level::Warn, count: 2 },
+        ]);
+
+        // 보고한 횟수는 초기화되고, 억제가 없던 키는 정리됨
+        clock.advance(10 * SECOND);
+        assert!(throttle.flush().is_empty());
+        assert!(!throttle.entries.lock().unwrap().contains_key("quiet"));
+
+        clock.advance(40 * SECOND);
+        assert_eq!(throttle.flush(), vec![
+            Suppressed { key: "trap".to_string(), level: Level::Error, count: 1 },
+        ]);
+
+        // 보고 뒤 새 메시지는 간격 동안 다시 억제
+        assert_eq!(throttle.check("trap", 60 * SECOND, Level::Error), None);
+    }
+}
@@ -2,19 +2,92 @@
 use anyhow::{Context, Result};
 use clap::Parser;
 use log::{debug, error, info, warn};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use tokio::signal;
+use tracing_subscriber::EnvFilter;
 
+// 다른 모듈에서 throttled_warn! 등을 쓰므로 먼저 선언
+#[macro_use]
+mod logthrottle;
+
+mod apimetrics;
+mod auth;
+mod backend;
+mod baseline;
+mod bench;
 mod bpf;
+mod capabilities;
+mod capture;
 mod config;
+mod conflicts;
+mod conntrack;
+mod counters;
+mod country;
+mod dns;
+mod dynblock;
+mod error;
+mod evaluate;
+mod events;
+mod expiry;
+mod geoip;
+mod hithistory;
+mod inspectctx;
+#[cfg(feature = "grpc")]
+mod grpc;
+mod ipset;
+mod linkwatch;
 mod maps;
+mod metrics;
+mod mitigation;
+mod netif;
+mod nftables;
+mod packet;
+mod peercred;
+mod preflight;
+mod presets;
+mod privdrop;
+mod procstat;
+mod quota;
+mod ratelimit;
+mod responselimit;
+mod rulecache;
+mod rulewatch;
+mod ruleset;
+mod selftest;
 mod server;
+mod statslog;
+mod syslog;
 mod telemetry;
 mod wasm;
+mod wasmtap;
+mod webhook;
 
+use crate::apimetrics::ApiMetrics;
+use crate::auth::TokenTable;
+use crate::bpf::XdpFilterSkel;
+use crate::capture::CaptureSink;
+use crate::dns::HostResolver;
+use crate::dynblock::DynamicBlockLimits;
+use crate::events::EventDispatcher;
+use crate::expiry::{ExpiryNotifier, OwnerWebhooks};
+use crate::geoip::{GeoResolver, MmdbResolver};
+use crate::linkwatch::{LinkWatcher, SystemLinks};
 use crate::maps::MapManager;
+use crate::mitigation::SynFloodDetector;
+use crate::quota::QuotaTracker;
+use crate::server::ApiServer;
+use crate::syslog::SyslogSink;
 use crate::telemetry::TelemetryCollector;
+use crate::wasm::WasmManager;
+use crate::wasmtap::WasmTap;
+use crate::webhook::{WebhookMetrics, WebhookOptions, WebhookSender};
+
+/// gRPC에서 API 서버로 전달을 기다리는 요청 수
+#[cfg(feature = "grpc")]
+const GRPC_QUEUE_SIZE: usize = 64;
 
 #[derive(Parser, Debug)]
 #[clap(name = "swift-guard-daemon", about = "Swift-Guard Daemon")]
@@ -35,27 +108,188 @@ struct Args {
     #[clap(long, default_value = "127.0.0.1:7654")]
     api_addr: String,
 
+    /// gRPC 서버 바인드 주소 (grpc 기능)
+    #[cfg(feature = "grpc")]
+    #[clap(long, default_value = "127.0.0.1:7655")]
+    grpc_addr: std::net::SocketAddr,
+
     /// 상세 로깅
     #[clap(short, long)]
     verbose: bool,
+
+    /// libbpf의 info/debug 메시지도 기록 (BPF 오브젝트 로드 문제 진단)
+    #[clap(long)]
+    bpf_verbose: bool,
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    // 로깅 초기화
-    env_logger::init();
-
     // 명령줄 인수 파싱
     let args = Args::parse();
 
-    // 로깅 레벨 설정
-    if args.verbose {
-        std::env::set_var("RUST_LOG", "debug");
-    } else {
-        std::env::set_var("RUST_LOG", "info");
+    // 로깅 초기화 (RUST_LOG가 없으면 --verbose에 따라 debug 또는 info)
+    // log 매크로로 남긴 로그도 tracing으로 전달되어 API 요청 스팬의 요청 ID를 가진다.
+    let default_level = if args.verbose { "debug" } else { "info" };
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(default_level));
+    tracing_subscriber::fmt().with_env_filter(filter).init();
+
+    info!("Swift-Guard 데몬 시작 중... ({})", swift_guard::build_info::BuildInfo::current());
+
+    // libbpf 메시지를 데몬 로그로 전달 (로드 실패 시 verifier 로그 수집)
+    bpf::init_libbpf_logging(args.bpf_verbose);
+
+    // 제한된 로그의 억제 횟수 주기 보고
+    logthrottle::spawn_flusher(logthrottle::FLUSH_INTERVAL);
+
+    // 구성 로드 (권한 전환 대상은 BPF 로드 전에 확인)
+    let config = config::load_config(&args.config)?;
+    let run_as = privdrop::Target::from_config(&config.general)
+        .context("Invalid privilege drop settings")?;
+
+    // 커널 기능 점검 (실패해도 계속 진행)
+    let capabilities = capabilities::probe_all(Some(&args.bpf_obj));
+    capabilities::log_checks(&capabilities);
+
+    // BPF 오브젝트 로드 (맵은 데몬이 끝날 때까지 규칙 관리와 통계 수집에 사용)
+    // API 서버의 연결 작업이 맵 관리자를 빌리므로 프로세스가 끝날 때까지 유지한다.
+    let skel: &'static XdpFilterSkel = Box::leak(Box::new(XdpFilterSkel::builder()
+        .obj_path(&args.bpf_obj)
+        .open()?));
+    skel.check_maps()?;
+    let (programs, maps) = skel.info();
+    debug!("BPF 오브젝트 로드됨: {} (프로그램 {}개, 맵 {}개)", args.bpf_obj.display(), programs.len(), maps.len());
+
+    let mut map_manager = MapManager::new(skel)
+        .with_write_schedule(config.map_writes.schedule());
+    if config.general.migrate_prefix_keys {
+        map_manager = map_manager.with_legacy_key_migration();
     }
+    #[allow(clippy::arc_with_non_send_sync)]
+    let map_manager = Arc::new(Mutex::new(map_manager));
+    // GeoIP 데이터베이스 (열지 못하면 geo 정보 없이 동작)
+    let geo = MmdbResolver::open(&config.geoip).map(|resolver| Arc::new(resolver) as Arc<dyn GeoResolver>);
+    let mut events = EventDispatcher::new()?
+        .with_history_size(config.events.history_size);
+    if let Some(resolver) = &geo {
+        events = events.with_geo(Arc::clone(resolver));
+    }
+    let syslog = SyslogSink::start(&config.logging.syslog).context("Invalid syslog settings")?;
+    // 캡처 파일은 WASM 검사 경로가 기록하고 ListCaptures가 나열한다
+    let capture = CaptureSink::start(&config.capture).context("Failed to start packet capture")?;
+
+    // WASM 검사 모듈 (API 요청으로 관리하고, wasm.interface의 프레임을 검사)
+    let mut wasm = WasmManager::from_config(&config.wasm, Path::new(&config.general.work_dir));
+    if let Some(sink) = &syslog {
+        wasm = wasm.with_syslog(sink.clone());
+    }
+    if let Some(sink) = &capture {
+        wasm = wasm.with_capture(sink.clone());
+    }
+    let wasm = Arc::new(wasm);
+    wasm.auto_load(&config.wasm);
+
+    // API 서버가 기록한 요청 메트릭은 Prometheus 내보내기에도 쓰인다
+    let api_metrics = Arc::new(ApiMetrics::new());
+    // 웹훅 전송기들이 함께 쓰는 카운터 (Ping 응답과 Prometheus 내보내기)
+    let webhook_metrics = Arc::new(WebhookMetrics::new());
+    let start_webhook = |url: &str| {
+        WebhookSender::spawn(url, WebhookOptions::default(), Arc::clone(&webhook_metrics))
+            .map(|(sender, _task)| sender)
+    };
+
+    let mut telemetry = TelemetryCollector::new(skel, &config)?
+        .with_events(events.clone())
+        .with_api_metrics(Arc::clone(&api_metrics))
+        .with_webhook_metrics(Arc::clone(&webhook_metrics))
+        .with_wasm(Arc::clone(&wasm));
+    let mut detector = SynFloodDetector::new(&config.mitigation);
+    if let Some(sink) = &syslog {
+        telemetry = telemetry.with_syslog(sink.clone());
+        detector = detector.with_syslog(sink.clone());
+    }
+    if let Some(resolver) = &geo {
+        detector = detector.with_geo(Arc::clone(resolver));
+    }
+    if let Some(url) = &config.mitigation.webhook_url {
+        detector = detector.with_webhook(start_webhook(url).context("Invalid mitigation.webhook_url")?);
+    }
+    #[cfg(feature = "grpc")]
+    let mitigation_events = detector.event_sender();
+    events.spawn_mitigation_recorder(detector.event_sender().subscribe());
+    // 할당량 사용량은 재시작해도 이어서 센다
+    let quota = QuotaTracker::load(config.quota.state_path(&config.general.work_dir), config.quota.reset_hour)
+        .context("Failed to load quota state")?;
+    telemetry = telemetry.with_quota(quota);
+    // 보낸 만료 알림은 재시작해도 다시 보내지 않는다
+    let expiry = ExpiryNotifier::load(config.expiry.state_path(&config.general.work_dir), config.expiry.notify_before)
+        .context("Failed to load expiry state")?;
+    telemetry = telemetry.with_expiry(expiry, OwnerWebhooks::new(WebhookOptions::default(), Arc::clone(&webhook_metrics)));
+    if config.mitigation.enabled {
+        telemetry = telemetry.with_mitigation(detector);
+    }
+    #[allow(clippy::arc_with_non_send_sync)]
+    let telemetry = Arc::new(telemetry);
 
-    info!("Swift-Guard 데몬 시작 중...");
+    let mut server = ApiServer::new(&args.api_addr, Arc::clone(&map_manager), Arc::clone(&telemetry))?
+        .with_bpf_obj(args.bpf_obj.clone())
+        .with_interfaces(config.interfaces.clone())
+        .with_capabilities(capabilities)
+        .with_bpf_info(programs, maps, skel.layouts().to_vec())
+        .with_tokens(TokenTable::new(config.api.tokens.clone()).context("Invalid API tokens")?)
+        .with_max_response_len(config.api.max_response_size)
+        .with_metrics(api_metrics)
+        .with_webhook_metrics(Arc::clone(&webhook_metrics))
+        .with_wasm(Arc::clone(&wasm));
+    if let Some(sink) = &syslog {
+        server = server.with_syslog(sink.clone());
+    }
+    server = server.with_events(events.clone());
+    if let Some(resolver) = geo {
+        server = server.with_geo(resolver);
+    }
+
+    // XDP 프로그램이 perf 버퍼로 보내는 규칙 적중 이벤트 (events.enabled)
+    let rule_events = match skel.maps().rule_events() {
+        Some(map) if config.events.enabled => {
+            let webhook = config.events.webhook_url.as_deref()
+                .map(start_webhook)
+                .transpose()
+                .context("Invalid events.webhook_url")?;
+            events.start_consumers(&config.events, syslog.clone(), webhook);
+            Some(map)
+        },
+        Some(_) => None,
+        None => {
+            if config.events.enabled {
+                warn!("BPF 오브젝트에 rule_events 맵이 없어 규칙 적중 이벤트를 받지 않음");
+            }
+            None
+        },
+    };
+    if capture.is_some() {
+        server = server.with_capture_dir(PathBuf::from(&config.capture.dir));
+    }
+    if let Some(tls) = &config.api.tls {
+        server = server.with_tls(tls.server_config()?);
+    }
+    if let Some(max_requests_per_sec) = config.api.max_requests_per_sec {
+        server = server.with_rate_limit(max_requests_per_sec);
+    }
+    if let Some(path) = &config.api.unix_socket {
+        server = server.with_unix_socket(path.clone());
+    }
+    if let Some(dir) = &config.general.object_dir {
+        server = server.with_object_dir(PathBuf::from(dir));
+    }
+    // 호스트 이름 규칙은 dns.enabled일 때만 받고 refresh_interval마다 다시 해석한다
+    let resolver = if config.dns.enabled {
+        Some(HostResolver::system()?)
+    } else {
+        None
+    };
+    if let Some(resolver) = &resolver {
+        server = server.with_resolver(resolver.clone());
+    }
 
     // 특정 인터페이스에 XDP 프로그램 로드
     if let Some(interface) = &args.interface {
@@ -65,9 +299,97 @@ async fn main() -> Result<()> {
         }
     }
 
-    // Ctrl+C 대기
+    // 검사 인터페이스 수신 소켓은 CAP_NET_RAW가 필요하므로 권한을 낮추기 전에 연다
+    let wasm_tap = config.wasm.interface.as_deref()
+        .map(WasmTap::open)
+        .transpose()
+        .context("Failed to open WASM inspection interface")?;
+
+    // 초기화가 끝났으므로 권한 낮추기 (실패하면 시작하지 않음)
+    if let Some(target) = &run_as {
+        privdrop::drop_privileges(&mut privdrop::SystemCredentials, target)
+            .with_context(|| format!("Failed to switch to user {}", target.user))?;
+        let privileges = privdrop::DroppedPrivileges::detect(&target.user)?;
+        info!("사용자 {}(uid {}, gid {})로 전환, 남은 capability: [{}]", target.user, target.uid, target.gid,
+            privileges.effective.iter().map(|cap| cap.name()).collect::<Vec<_>>().join(", "));
+        if args.interface.is_some() {
+            if let Err(e) = privileges.require("detach on shutdown", &[&[privdrop::Capability::NetAdmin]]) {
+                warn!("{}", e);
+            }
+        }
+        server = server.with_dropped_privileges(privileges);
+    }
+
+    // 연결 작업과 gRPC 전달 처리가 함께 쓰는 서버
+    let server = Rc::new(server);
+
+    // Prometheus 스크레이프 주소 (내보내기가 꺼져 있으면 열지 않음)
+    let export_url = config.telemetry.export_url.as_deref().unwrap_or(metrics::DEFAULT_EXPORT_URL);
+
+    // gRPC 요청은 API 서버로 전달되어 같은 요청 처리를 거친다 (grpc 기능)
+    #[cfg(feature = "grpc")]
+    let grpc = {
+        let (requests, forwarded) = tokio::sync::mpsc::channel(GRPC_QUEUE_SIZE);
+        let service = grpc::GrpcService::new(requests, mitigation_events, events.clone());
+        let server = &server;
+        async move { tokio::join!(server.serve_forwarded(forwarded), service.serve(args.grpc_addr)).1 }
+    };
+    #[cfg(not(feature = "grpc"))]
+    let grpc = std::future::pending::<Result<()>>();
+
+    let rule_events = async {
+        match rule_events {
+            Some(map) => events.run(map).await,
+            None => std::future::pending().await,
+        }
+    };
+
+    let wasm_tap = async {
+        match &wasm_tap {
+            Some(tap) => {
+                info!("인터페이스 {}의 프레임을 WASM 모듈로 검사", tap.interface());
+                tap.run(&wasm, &map_manager, DynamicBlockLimits::from_config(&config.wasm)).await
+            },
+            None => std::future::pending().await,
+        }
+    };
+
+    let dns_refresh = async {
+        match resolver {
+            Some(resolver) => {
+                let interval = Duration::from_secs(config.dns.refresh_interval.max(1));
+                dns::run_refresher(resolver, Arc::clone(&map_manager), interval).await
+            },
+            None => std::future::pending().await,
+        }
+    };
+
+    // 연결된 인터페이스와 리디렉션 대상의 링크 상태 감시 (link.enabled)
+    let link_watch = async {
+        if config.link.enabled {
+            let watcher = LinkWatcher::new(config.link.on_link_down);
+            let ops = SystemLinks::new(Some(args.bpf_obj.clone()));
+            let interval = Duration::from_secs(config.link.poll_interval.max(1));
+            linkwatch::run_watcher(watcher, ops, Arc::clone(&map_manager), events.clone(), interval).await
+        } else {
+            std::future::pending().await
+        }
+    };
+
+    // API 서버와 주기 작업 실행, Ctrl+C 대기
+    // 맵 관리자와 수집기는 Send가 아니므로 주기 작업도 같은 작업 안에서 함께 폴링한다.
     info!("데몬 실행 중... Ctrl+C로 종료");
-    tokio::signal::ctrl_c().await?;
+    tokio::select! {
+        result = Rc::clone(&server).run() => result?,
+        result = grpc => result?,
+        result = rule_events => result?,
+        result = wasm_tap => result?,
+        _ = telemetry.run(&map_manager) => {},
+        _ = dns_refresh => {},
+        _ = link_watch => {},
+        result = metrics::serve(export_url, &telemetry, &map_manager), if config.telemetry.export_enabled => result?,
+        result = signal::ctrl_c() => result?,
+    }
     
     // 종료 처리
     if let Some(interface) = &args.interface {
@@ -2,18 +2,41 @@
 //! BPF 맵을 관리하는 기능 제공
 
 use anyhow::{anyhow, Context, Result};
-use libbpf_rs::Map;
-use log::{debug, error, info, warn};
-use std::net::{IpAddr, Ipv4Addr};
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::{debug, error, info, warn};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+use std::time::Duration;
+use tokio::sync::broadcast;
 
+use crate::backend::{BackendKind, MapBackend, MemoryMap, RuleBackend};
 use crate::bpf::XdpFilterSkel;
+use crate::conflicts;
+use crate::dns::{self, HostEndpoint};
+use crate::error::DaemonError;
+use crate::evaluate::{self, TestPacket};
+use crate::inspectctx::PacketContext;
+use crate::ipset::PrefixSet;
+use crate::logthrottle;
+use crate::nftables::NftablesBackend;
+use crate::quota::RuleQuota;
+use crate::rulecache::RuleCache;
+use crate::rulewatch::{RuleChange, RuleChangeLog};
+use crate::telemetry::read_global_stats;
 //use crate::api::{RuleInfo, RuleStats};
 //use crate::utils;
 
-use swift_guard::api::{RuleInfo, RuleStats};
+use crate::conntrack::{self, ConntrackTable};
+
+use swift_guard::api::{
+    AddressFamily, ConnectionFilter, ConnectionInfo, FallbackMode, InstallState, InterfaceInfo, MapAudit, MissingEntry, OrphanEntry,
+    PrefixOverlap, ReconcileSummary, RedirectTarget, RuleChangeKind, RuleDetail, RuleExpirySummary, RuleInfo, RuleMap,
+    RuleSpaceStats, RuleSpec, RuleStats,
+};
+use swift_guard::error::InvalidArgument;
+use swift_guard::layout::{self, FilterStats, RULE_ACTION_OFFSET, RULE_LABEL_OFFSET, RULE_SRC_PORTS_OFFSET, RULE_STATS_OFFSET};
+use swift_guard::types::{ActionType, ProtocolType, XdpFeatures};
 use swift_guard::utils;
-use libbpf_rs::MapFlags;
+use swift_guard::xdp;
 
 /// 필터 규칙 정보
 #[derive(Debug, Clone)]
@@ -22,24 +45,103 @@ pub struct FilterRule {
     pub dst_ip: Option<(u32, u32)>,  // (IP, 프리픽스 길이)
     pub src_port_min: u16,
     pub src_port_max: u16,
+    /// 소스 포트 목록 (비어 있지 않으면 소스 포트가 이 중 하나여야 매치)
+    pub src_port_list: Vec<u16>,
     pub dst_port_min: u16,
     pub dst_port_max: u16,
+    /// 목적지 포트 목록 (비어 있지 않으면 목적지 포트가 이 중 하나여야 매치)
+    pub dst_port_list: Vec<u16>,
     pub protocol: u8,
     pub tcp_flags: u8,
     pub action: u8,
     pub redirect_ifindex: u32,
+    /// 리디렉션 인터페이스 이름 (ifindex 재확인용)
+    pub redirect_ifname: Option<String>,
+    /// CPU 맵 리디렉션 대상 (redirect_ifindex와 동시에 사용할 수 없음)
+    pub redirect_cpu: Option<u32>,
     pub priority: u32,
     pub rate_limit: u32,
     pub expire: u32,
     pub label: String,
+    /// 규칙 설명 (캐시 전용, 커널에 기록하지 않음)
+    pub description: Option<String>,
+    /// 규칙을 추가한 운영자 (캐시 전용)
+    pub created_by: Option<String>,
     pub creation_time: u64,
+    /// 자동 완화로 설치된 규칙 (영구 저장 대상 아님)
+    pub auto_generated: bool,
+    /// 리디렉션 대상 인터페이스가 사라져 동작하지 않는 규칙
+    pub degraded: bool,
+    /// 커널 설치 상태 (캐시 전용)
+    pub install_state: InstallState,
+    /// 사용량 할당량
+    pub quota: Option<RuleQuota>,
+    /// 할당량을 넘어 커널에 할당량 액션을 기록한 상태 (캐시 전용)
+    pub quota_exceeded: bool,
+    /// 소스 호스트 이름 (있으면 src_ip 대신 해석한 주소마다 /32 항목 설치)
+    pub src_host: Option<HostEndpoint>,
+    /// 통계를 합산해 보여줄 카운터 이름 (count 액션 규칙만, 캐시 전용)
+    pub counter_group: Option<String>,
+    /// 규칙을 설치한 프리셋 (같은 프리셋 규칙을 함께 갱신하고 삭제하는 표시, 캐시 전용)
+    pub preset: Option<String>,
+    /// 규칙을 설치한 주체 (`wasm:<모듈>`은 WASM 모듈의 동적 차단 규칙, 캐시 전용)
+    pub origin: Option<String>,
+    /// 소스 프리픽스 집합 (있으면 src_ip 대신 집합의 프리픽스마다 항목 설치)
+    pub src_set: Option<PrefixSet>,
+    /// 만료 알림 웹훅 URL (캐시 전용)
+    pub owner_webhook: Option<String>,
+    /// 만료 예고 알림을 보낼 남은 시간 (초, 없으면 데몬 구성 값, 캐시 전용)
+    pub notify_before: Option<u32>,
+}
+
+impl Default for FilterRule {
+    /// 모든 주소, 포트, 프로토콜에 일치하는 통과 규칙
+    fn default() -> Self {
+        Self {
+            src_ip: None,
+            dst_ip: None,
+            src_port_min: 0,
+            src_port_max: 65535,
+            src_port_list: Vec::new(),
+            dst_port_min: 0,
+            dst_port_max: 65535,
+            dst_port_list: Vec::new(),
+            protocol: ProtocolType::Any as u8,
+            tcp_flags: 0,
+            action: ActionType::Pass as u8,
+            redirect_ifindex: 0,
+            redirect_ifname: None,
+            redirect_cpu: None,
+            priority: 0,
+            rate_limit: 0,
+            expire: 0,
+            label: String::new(),
+            description: None,
+            created_by: None,
+            creation_time: 0,
+            auto_generated: false,
+            degraded: false,
+            install_state: InstallState::default(),
+            quota: None,
+            quota_exceeded: false,
+            src_host: None,
+            counter_group: None,
+            preset: None,
+            origin: None,
+            src_set: None,
+            owner_webhook: None,
+            notify_before: None,
+        }
+    }
 }
 
 impl FilterRule {
-    /// API 룰 정보로 변환
-    pub fn to_rule_info(&self, stats: RuleStats) -> RuleInfo {
+    /// API 룰 정보로 변환 (`attached`는 규칙을 적용할 인터페이스가 연결되어 있는지)
+    pub fn to_rule_info(&self, stats: RuleStats, attached: bool) -> RuleInfo {
+        let (namespace, label) = utils::split_label(&self.label);
         RuleInfo {
-            label: self.label.clone(),
+            label: label.to_string(),
+            namespace: namespace.to_string(),
             action: utils::action_num_to_name(self.action),
             src_ip: self.src_ip.map(|(ip, prefix)| {
                 if prefix == 32 {
@@ -55,8 +157,8 @@ impl FilterRule {
                     format!("{}/{}", utils::ipv4_to_string(ip), prefix)
                 }
             }),
-            src_port: utils::port_range_to_string(self.src_port_min, self.src_port_max),
-            dst_port: utils::port_range_to_string(self.dst_port_min, self.dst_port_max),
+            src_port: ports_to_string(self.src_port_min, self.src_port_max, &self.src_port_list),
+            dst_port: ports_to_string(self.dst_port_min, self.dst_port_max, &self.dst_port_list),
             protocol: utils::protocol_num_to_name(self.protocol),
             tcp_flags: if self.tcp_flags == 0 {
                 None
@@ -65,24 +167,368 @@ impl FilterRule {
             },
             priority: self.priority,
             redirect_if: if self.action == 3 && self.redirect_ifindex != 0 {
-                Some(format!("if{}", self.redirect_ifindex))
+                Some(self.redirect_ifname.clone()
+                    .unwrap_or_else(|| format!("if{}", self.redirect_ifindex)))
             } else {
                 None
             },
+            redirect_cpu: if self.action == 3 { self.redirect_cpu } else { None },
             rate_limit: self.rate_limit,
             expire: self.expire,
+            auto: self.auto_generated,
+            // 호스트 이름을 해석하지 못하면 마지막으로 해석한 주소로 동작
+            degraded: self.degraded || self.src_host.as_ref().is_some_and(HostEndpoint::is_stale),
+            description: self.description.clone(),
+            created_by: self.created_by.clone(),
+            creation_time: self.creation_time,
+            install_state: self.install_state.clone(),
+            map: self.rule_map(),
+            effective: attached && self.is_enforceable(),
+            quota: self.quota.map(|quota| quota.to_info()),
+            quota_exceeded: self.quota_exceeded,
+            src_host: self.src_host.as_ref().map(HostEndpoint::to_info),
+            counter_group: self.counter_group.clone(),
+            preset: self.preset.clone(),
+            origin: self.origin.clone(),
+            src_set: self.src_set.as_ref().map(PrefixSet::to_info),
+            owner_webhook: self.owner_webhook.clone(),
+            notify_before: self.notify_before,
+            address_family: Some(self.address_family()),
             stats,
         }
     }
+
+    /// 규칙의 주소 체계 (커널 맵은 IPv4 주소만 저장하므로 주소 조건이 있으면 IPv4)
+    pub fn address_family(&self) -> AddressFamily {
+        if self.src_ip.is_some() || self.dst_ip.is_some() || self.src_host.is_some() || self.src_set.is_some() {
+            AddressFamily::V4
+        } else {
+            AddressFamily::Any
+        }
+    }
+    
+    /// 커널 맵에 기록하는 액션 (할당량을 넘었으면 할당량 액션)
+    pub fn kernel_action(&self) -> u8 {
+        match self.quota {
+            Some(quota) if self.quota_exceeded => quota.action,
+            _ => self.action,
+        }
+    }
+
+    /// 인터페이스가 연결되면 패킷에 적용될 규칙 (완전히 설치되었고 리디렉션 대상이 있음)
+    pub fn is_enforceable(&self) -> bool {
+        self.install_state == InstallState::Installed && !self.degraded
+    }
+    
+    /// 규칙이 설치되는 커널 맵 (소스 IP, 호스트 이름, 프리픽스 집합이 없으면 와일드카드 맵)
+    pub fn rule_map(&self) -> RuleMap {
+        if self.src_ip.is_some() || self.src_host.is_some() || self.src_set.is_some() {
+            RuleMap::Lpm
+        } else {
+            RuleMap::Wildcard
+        }
+    }
+    
+    /// LPM 맵에 기록하는 소스 프리픽스 (호스트 이름 규칙은 해석한 주소마다 /32)
+    pub fn src_prefixes(&self) -> Vec<(u32, u32)> {
+        match (&self.src_host, &self.src_set) {
+            (Some(host), _) => host.prefixes(),
+            (None, Some(set)) => set.prefixes.clone(),
+            (None, None) => self.src_ip.into_iter().collect(),
+        }
+    }
+    
+    /// 규칙 정의로 변환 (규칙 비교용, 프리픽스는 "a.b.c.d/len" 표기)
+    pub fn to_spec(&self) -> RuleSpec {
+        let prefix = |prefix: Option<(u32, u32)>| prefix
+            .map(|(ip, prefix_len)| format!("{}/{}", utils::ipv4_to_string(ip), prefix_len));
+        
+        RuleSpec {
+            label: self.label.clone(),
+            src_ip: prefix(self.src_ip),
+            dst_ip: prefix(self.dst_ip),
+            src_port_min: self.src_port_min,
+            src_port_max: self.src_port_max,
+            dst_port_min: self.dst_port_min,
+            dst_port_max: self.dst_port_max,
+            protocol: self.protocol,
+            tcp_flags: self.tcp_flags,
+            action: self.action,
+            redirect_if: self.redirect_ifname.clone(),
+            redirect_cpu: self.redirect_cpu,
+            priority: self.priority,
+            rate_limit: self.rate_limit,
+            expire: self.expire,
+            description: self.description.clone(),
+            quota_bytes: self.quota.and_then(|quota| quota.bytes),
+            quota_packets: self.quota.and_then(|quota| quota.packets),
+            quota_action: self.quota.map(|quota| quota.action),
+            counter_group: self.counter_group.clone(),
+            src_port_list: self.src_port_list.clone(),
+            dst_port_list: self.dst_port_list.clone(),
+            owner_webhook: self.owner_webhook.clone(),
+            notify_before: self.notify_before,
+        }
+    }
+    
+    /// 남은 수명 (초, 만료 없음은 None)
+    pub fn ttl_remaining(&self, now: u64) -> Option<u64> {
+        utils::ttl_remaining(self.creation_time, self.expire, now)
+    }
+    
+    /// 만료 여부 확인 (expire 0 = 만료 없음)
+    pub fn is_expired(&self, now: u64) -> bool {
+        self.ttl_remaining(now) == Some(0)
+    }
+}
+
+/// 규칙 정보의 포트 조건 (목록이 있으면 쉼표 목록, 모든 포트는 None)
+fn ports_to_string(min: u16, max: u16, list: &[u16]) -> Option<String> {
+    if list.is_empty() {
+        utils::port_range_to_string(min, max)
+    } else {
+        Some(utils::port_list_to_string(list))
+    }
+}
+
+/// 규칙 목록 순서 (우선순위 내림차순, 같으면 생성 시각 오름차순)
+///
+/// 정렬은 안정 정렬로 하므로 두 값이 모두 같은 규칙은 추가한 순서를 유지한다.
+pub fn list_order(a: &RuleInfo, b: &RuleInfo) -> Ordering {
+    b.priority.cmp(&a.priority)
+        .then(a.creation_time.cmp(&b.creation_time))
 }
 
-/// 리디렉션 인터페이스 정보
+/// `within`초 안에 만료되는 규칙만 남은 수명이 짧은 순서로 (이미 만료된 규칙 포함)
+pub fn expiring_within(rules: Vec<RuleInfo>, within: u64, now: u64) -> Vec<RuleInfo> {
+    let mut expiring: Vec<(u64, RuleInfo)> = rules.into_iter()
+        .filter_map(|rule| {
+            let ttl = utils::ttl_remaining(rule.creation_time, rule.expire, now)?;
+            (ttl <= within).then_some((ttl, rule))
+        })
+        .collect();
+    
+    expiring.sort_by_key(|(ttl, _)| *ttl);
+    expiring.into_iter().map(|(_, rule)| rule).collect()
+}
+
+/// 규칙 목록의 한 페이지
 #[derive(Debug, Clone)]
+pub struct RulePage {
+    pub rules: Vec<RuleInfo>,
+    /// 페이지로 나누기 전 규칙 수
+    pub total_count: usize,
+    /// 다음 페이지의 커서 (이 페이지 마지막 규칙의 키, 마지막 페이지면 None)
+    pub next_cursor: Option<String>,
+}
+
+/// 규칙 키 순서로 `cursor` 뒤에서 `offset`개를 건너뛰고 최대 `limit`개 (0이면 전부)
+///
+/// 커서와 같은 키의 규칙이 있는지는 보지 않고 키를 비교만 하므로, 기준 규칙이 삭제되어도
+/// 다음 페이지는 그 뒤부터 이어진다.
+pub fn paginate(rules: Vec<RuleInfo>, cursor: Option<&str>, offset: usize, limit: usize) -> RulePage {
+    let total_count = rules.len();
+    let mut keyed: Vec<(String, RuleInfo)> = rules.into_iter()
+        .map(|rule| (utils::qualify_label(&rule.namespace, &rule.label), rule))
+        .filter(|(key, _)| match cursor {
+            Some(cursor) => key.as_str() > cursor,
+            None => true,
+        })
+        .collect();
+    keyed.sort_by(|(a, _), (b, _)| a.cmp(b));
+    
+    let mut page: Vec<(String, RuleInfo)> = keyed.into_iter().skip(offset).collect();
+    let more = limit > 0 && page.len() > limit;
+    if limit > 0 {
+        page.truncate(limit);
+    }
+    
+    RulePage {
+        next_cursor: if more { page.last().map(|(key, _)| key.clone()) } else { None },
+        rules: page.into_iter().map(|(_, rule)| rule).collect(),
+        total_count,
+    }
+}
+
+/// 커널 규칙 값 끝의 통계 영역 크기 (struct filter_stats)
+const RULE_STATS_SIZE: usize = FilterStats::SIZE;
+
+/// 커널 규칙 값의 통계 영역 파싱 (로드 시 BTF로 검증한 `layout` 위치)
+///
+/// 고정(pin)된 맵에 남은 이전 레이아웃의 값은 없는 카운터(리디렉션, 중단)를 0으로 읽는다.
+fn parse_rule_stats(value: &[u8]) -> RuleStats {
+    let stats = value.get(RULE_STATS_OFFSET..)
+        .and_then(FilterStats::from_bytes)
+        .unwrap_or_default();
+    
+    RuleStats {
+        packets: stats.packets,
+        bytes: stats.bytes,
+        last_matched: stats.last_matched,
+        redirected_packets: stats.redirected_packets,
+        redirect_failures: stats.redirect_failures,
+        aborted_packets: stats.aborted_packets,
+        ..RuleStats::default()
+    }
+}
+
+/// 와일드카드 맵 슬롯 수 (MAX_WILDCARD_RULES)
+pub const MAX_WILDCARD_RULES: u32 = 64;
+
+/// 키 공간 통계에 보고하는 겹침 프리픽스 수
+const TOP_OVERLAPS: usize = 10;
+
+/// 맵 키를 16진수 문자열로
+fn hex_key(key: &[u8]) -> String {
+    key.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// 커널 맵 감사에서 찾은 불일치
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Divergence {
+    /// 캐시의 어떤 규칙에도 속하지 않는 커널 항목 (`key`는 LPM 키 또는 슬롯 번호)
+    Orphan { map: RuleMap, key: Vec<u8>, value: Vec<u8> },
+    /// 설치되었다고 기록되었지만 커널에 없는 규칙 항목
+    Missing { map: RuleMap, label: String, key: Vec<u8> },
+}
+
+impl Divergence {
+    /// 고아 항목 보고 (키와 값에서 필드 해석, 값이 짧으면 빈 값)
+    fn to_orphan_entry(&self, object: Option<&str>) -> Option<OrphanEntry> {
+        let Self::Orphan { map, key, value } = self else {
+            return None;
+        };
+        let u16_at = |offset: usize| value.get(offset..offset + 2)
+            .map(|b| u16::from_le_bytes([b[0], b[1]]))
+            .unwrap_or(0);
+        let label = value.get(RULE_LABEL_OFFSET..RULE_SRC_PORTS_OFFSET).unwrap_or_default();
+        let label = label.split(|b| *b == 0).next().unwrap_or_default();
+        
+        Some(OrphanEntry {
+            map: *map,
+            object: object.map(str::to_string),
+            lpm_key: (*map == RuleMap::Lpm).then(|| hex_key(key)),
+            wildcard_slot: (*map == RuleMap::Wildcard).then(|| slot_of(key)),
+            // struct prefix_key: 프리픽스 길이(호스트 순서), 주소(네트워크 순서)
+            src_ip: match (map, key.get(..4), key.get(4..8)) {
+                (RuleMap::Lpm, Some(len), Some(addr)) => Some(format!("{}/{}",
+                    utils::ipv4_to_string(u32::from_be_bytes([addr[0], addr[1], addr[2], addr[3]])),
+                    u32::from_le_bytes([len[0], len[1], len[2], len[3]]))),
+                _ => None,
+            },
+            label: String::from_utf8_lossy(label).into_owned(),
+            action: utils::action_num_to_name(value.get(RULE_ACTION_OFFSET).copied().unwrap_or(0)),
+            protocol: utils::protocol_num_to_name(value.get(5).copied().unwrap_or(0)),
+            dst_port: utils::port_range_to_string(u16_at(10), u16_at(12)),
+            priority: value.get(..4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]])).unwrap_or(0),
+        })
+    }
+    
+    /// 누락 항목 보고
+    fn to_missing_entry(&self, object: Option<&str>) -> Option<MissingEntry> {
+        let Self::Missing { map, label, key } = self else {
+            return None;
+        };
+        let (namespace, label) = utils::split_label(label);
+        
+        Some(MissingEntry {
+            label: label.to_string(),
+            namespace: namespace.to_string(),
+            map: *map,
+            object: object.map(str::to_string),
+            lpm_key: (*map == RuleMap::Lpm).then(|| hex_key(key)),
+            wildcard_slot: (*map == RuleMap::Wildcard).then(|| slot_of(key)),
+        })
+    }
+}
+
+/// 와일드카드 맵 키의 슬롯 번호
+fn slot_of(key: &[u8]) -> u32 {
+    key.get(..4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]])).unwrap_or(0)
+}
+
+/// 잠금 밖에서 상세 정보를 조립하기 위한 규칙 스냅샷
+pub struct RuleSnapshot<'a> {
+    pub rule: FilterRule,
+    /// 커널 LPM 키 (소스 IP 규칙만 해당)
+    pub key: Option<Vec<u8>>,
+    /// 와일드카드 맵 슬롯 (소스 IP가 없는 규칙만 해당)
+    pub wildcard_slot: Option<u32>,
+    /// 스냅샷 시점에 연결된 인터페이스가 있었는지
+    pub attached: bool,
+    filter_rules: Option<&'a dyn MapBackend>,
+    wildcard_rules: Option<&'a dyn MapBackend>,
+}
+
+impl<'a> RuleSnapshot<'a> {
+    /// 커널 맵을 조회해 상세 정보 생성
+    pub fn into_detail(self, interfaces: Vec<String>, down_interfaces: Vec<String>, now: u64) -> RuleDetail {
+        let value = match (&self.key, self.filter_rules, self.wildcard_slot, self.wildcard_rules) {
+            (Some(key), Some(map), _, _) => map.lookup(key).ok().flatten(),
+            (None, _, Some(slot), Some(map)) => map.lookup(&slot.to_le_bytes()).ok().flatten()
+                .filter(|value| value.get(RULE_ACTION_OFFSET).is_some_and(|action| *action != 0)),
+            _ => None,
+        };
+        
+        let stats = value.as_deref().map(parse_rule_stats).unwrap_or_default();
+        let ttl_remaining = self.rule.ttl_remaining(now);
+        
+        RuleDetail {
+            info: self.rule.to_rule_info(stats, self.attached),
+            ttl_remaining,
+            lpm_key: self.key.as_deref().map(hex_key),
+            wildcard_slot: self.wildcard_slot,
+            installed: value.is_some(),
+            interfaces,
+            down_interfaces,
+            recent_matches: Vec::new(),
+            history_1m: Vec::new(),
+            src_geo: None,
+        }
+    }
+}
+
+/// CPU 리디렉션 미사용 표시 (struct filter_rule.redirect_cpu)
+pub const REDIRECT_CPU_NONE: u32 = u32::MAX;
+
+/// CPU 맵 항목의 큐 크기
+const CPUMAP_QUEUE_SIZE: u32 = 2048;
+
+/// 온라인 CPU 수
+pub fn online_cpu_count() -> u32 {
+    let count = unsafe { libc::sysconf(libc::_SC_NPROCESSORS_ONLN) };
+    if count > 0 { count as u32 } else { 1 }
+}
+
+/// 리디렉션 인터페이스 정보 (redirect_map 값, struct if_redirect와 일치)
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct RedirectIf {
     pub ifindex: u32,
     pub ifname: String,
 }
 
+impl RedirectIf {
+    /// 인터페이스 이름 영역 크기 (char[16], IFNAMSIZ)
+    const NAME_LEN: usize = 16;
+    
+    /// 맵 값 바이트에서 파싱 (이름은 첫 NUL 앞까지)
+    pub fn from_bytes(value: &[u8]) -> Option<Self> {
+        let ifindex = u32::from_le_bytes(value.get(0..4)?.try_into().ok()?);
+        let name = value.get(4..4 + Self::NAME_LEN)?;
+        let end = name.iter().position(|b| *b == 0).unwrap_or(name.len());
+        
+        Some(Self {
+            ifindex,
+            ifname: String::from_utf8_lossy(&name[..end]).into_owned(),
+        })
+    }
+}
+
+/// 규칙이 인터페이스 리디렉션 대상 슬롯을 쓰는지 (대상이 사라진 규칙은 제외)
+fn references_redirect(rule: &FilterRule, slot: u32) -> bool {
+    rule.action == 3 && rule.redirect_ifindex == slot && !rule.degraded
+}
+
 /*
 /// 맵 관리자
 #[derive(Debug)]
@@ -98,317 +544,3260 @@ pub struct MapManager {
 }
 */
 
-pub struct MapManager<'a> {
-    // XdpFilterSkel에 대한 참조만 유지
-//    skel: &'a XdpFilterSkel,
-    filter_rules_map: Option<&'a Map>,
-    redirect_map: Option<&'a Map>,
-    stats_map: Option<&'a Map>,
-    rules: Vec<FilterRule>,
+/// 맵 관리자가 사용하는 맵 백엔드 묶음
+#[derive(Default)]
+pub struct MapBackends<'a> {
+    pub filter_rules: Option<&'a dyn MapBackend>,
+    /// 소스 IP가 없는 규칙용 배열 맵 (이전 오브젝트에는 없음)
+    pub wildcard: Option<&'a dyn MapBackend>,
+    pub redirect: Option<&'a dyn MapBackend>,
+    pub stats: Option<&'a dyn MapBackend>,
+    pub conntrack: Option<&'a dyn MapBackend>,
+    pub cpu: Option<&'a dyn MapBackend>,
+    /// 오브젝트가 처리하는 매치 기능 (None이면 알 수 없음, 테스트용 인메모리 맵 등)
+    pub features: Option<XdpFeatures>,
 }
 
-impl<'a> std::fmt::Debug for MapManager<'a> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_struct("MapManager")
-            .field("rules", &self.rules)
-            // Map은 Debug할 수 없으므로 포함하지 않음
-            .finish()
+impl<'a> MapBackends<'a> {
+    /// 로드된 오브젝트의 맵
+    pub fn from_skel(skel: &'a XdpFilterSkel) -> Self {
+        let maps = skel.maps();
+        
+        Self {
+            filter_rules: maps.filter_rules().map(|m| m as &dyn MapBackend),
+            wildcard: maps.wildcard_rules().map(|m| m as &dyn MapBackend),
+            redirect: maps.redirect_map().map(|m| m as &dyn MapBackend),
+            stats: maps.stats_map().map(|m| m as &dyn MapBackend),
+            conntrack: maps.conntrack().map(|m| m as &dyn MapBackend),
+            cpu: maps.cpu_map().map(|m| m as &dyn MapBackend),
+            features: Some(skel.features()),
+        }
     }
 }
 
-impl<'a> MapManager<'a> {
-    pub fn new(skel: &'a XdpFilterSkel) -> Self {
+/// 규칙 변경이 일부만 반영되고 되돌리지 못한 상태로 끝났음을 나타내는 오류
+#[derive(Debug, thiserror::Error)]
+#[error("rule is only partially installed")]
+pub struct PartialInstall;
+
+/// 오류가 규칙을 일부만 설치된 상태로 남겼는지 확인
+pub fn is_partial(e: &anyhow::Error) -> bool {
+    e.downcast_ref::<PartialInstall>().is_some()
+}
+
+/// 일부만 설치된 규칙의 설치 상태
+fn partial_state(e: &anyhow::Error) -> InstallState {
+    InstallState::Partial { reason: format!("{:#}", e) }
+}
+
+/// 규칙별로 커널에 기록된 항목
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct Written {
+    /// redirect_map 참조
+    redirect: bool,
+    /// cpu_map 항목
+    cpu: bool,
+    /// filter_rules LPM 항목
+    lpm: bool,
+    /// wildcard_rules 슬롯
+    wildcard: bool,
+}
+
+impl Written {
+    /// 규칙 설치에 필요한 항목
+    fn required(rule: &FilterRule) -> Self {
         Self {
-//            skel,
-            filter_rules_map: skel.maps().filter_rules(),
-            redirect_map: skel.maps().redirect_map(),
-            stats_map: skel.maps().stats_map(),
-            rules: Vec::new(),
+            redirect: rule.action == 3 && rule.redirect_ifindex != 0,
+            cpu: rule.action == 3 && rule.redirect_cpu.is_some(),
+            lpm: rule.rule_map() == RuleMap::Lpm,
+            wildcard: rule.rule_map() == RuleMap::Wildcard,
         }
     }
-    
-    // 필요할 때마다 skel에서 맵을 가져오는 헬퍼 메서드
-    fn filter_rules_map(&self) -> Option<&Map> {
-//        self.skel.maps().filter_rules()
-//        let maps = &self.skel.maps();
-//        maps.filter_rules()
-        self.filter_rules_map
+}
+
+/// XDP 프로그램의 BPF 맵에 규칙을 설치하는 백엔드
+pub struct XdpBackend<'a> {
+    filter_rules_map: Option<&'a dyn MapBackend>,
+    wildcard_map: Option<&'a dyn MapBackend>,
+    redirect_map: Option<&'a dyn MapBackend>,
+    cpu_map: Option<&'a dyn MapBackend>,
+    /// 오브젝트가 처리하는 매치 기능
+    features: Option<XdpFeatures>,
+    /// 레이블별 와일드카드 맵 슬롯
+    wildcard_slots: HashMap<String, u32>,
+    /// 리디렉션 인터페이스별 참조 규칙 수
+    redirect_refs: HashMap<u32, usize>,
+    /// CPU별 참조 규칙 수
+    cpu_refs: HashMap<u32, usize>,
+    /// 레이블별로 기록된 커널 항목 (재시도 시 빠진 항목만 기록)
+    written: HashMap<String, Written>,
+}
+
+impl<'a> XdpBackend<'a> {
+    fn new(backends: &MapBackends<'a>) -> Self {
+        Self {
+            filter_rules_map: backends.filter_rules,
+            wildcard_map: backends.wildcard,
+            redirect_map: backends.redirect,
+            cpu_map: backends.cpu,
+            features: backends.features,
+            wildcard_slots: HashMap::new(),
+            redirect_refs: HashMap::new(),
+            cpu_refs: HashMap::new(),
+            written: HashMap::new(),
+        }
     }
     
-    fn redirect_map(&self) -> Option<&Map> {
-//        self.skel.maps().redirect_map()
-//        let maps = &self.skel.maps();
-//        maps.redirect_map()
-        self.redirect_map
+    /// 슬롯 할당과 참조 수, 기록 상태를 그대로 가진 채 다른 맵에 기록하는 백엔드
+    fn simulated<'b>(&self, backends: &MapBackends<'b>) -> XdpBackend<'b> {
+        XdpBackend {
+            wildcard_slots: self.wildcard_slots.clone(),
+            redirect_refs: self.redirect_refs.clone(),
+            cpu_refs: self.cpu_refs.clone(),
+            written: self.written.clone(),
+            ..XdpBackend::new(backends)
+        }
     }
     
-    fn stats_map(&self) -> Option<&Map> {
-//        self.skel.maps().stats_map()
-//        let maps = &self.skel.maps();
-//        maps.stats_map()
-        self.stats_map
+    /// 규칙에 대해 기록된 커널 항목
+    fn written_for(&self, label: &str) -> Written {
+        self.written.get(label).copied().unwrap_or_default()
     }
-
-    /// 규칙 추가
-    pub fn add_rule(&mut self, rule: FilterRule) -> Result<()> {
-        debug!("Adding rule: {}", rule.label);
+    
+    /// 빠진 커널 항목 기록
+    ///
+    /// 대상 맵 항목을 먼저 기록하고 LPM 또는 와일드카드 항목을 마지막에 기록하므로,
+    /// 규칙은 모든 대상이 준비된 뒤에야 패킷에 적용된다.
+    fn write_steps(&mut self, rule: &FilterRule, written: &mut Written) -> Result<()> {
+        let required = Written::required(rule);
         
-        // 소스 IP 규칙 추가 (있는 경우)
-        if let Some((src_ip, prefix_len)) = rule.src_ip {
-            let key = self.create_prefix_key(src_ip, prefix_len);
-            let value = self.create_filter_rule(&rule)?;
-            
-            if let Some(map) = self.filter_rules_map() {
-                map.update(&key, &value, libbpf_rs::MapFlags::ANY)
-                    .context("Failed to update filter_rules map")?;
-            } else {
-                return Err(anyhow!("Failed to update filter_rules map"));
-            }
+        if required.redirect && !written.redirect {
+            self.acquire_rule_redirect(rule)?;
+            written.redirect = true;
         }
         
-        // 리디렉션 인터페이스 설정 (필요한 경우)
-        if rule.action == 3 && rule.redirect_ifindex != 0 {
-            let key = rule.redirect_ifindex.to_le_bytes();
-            let if_redirect = self.create_if_redirect(rule.redirect_ifindex, &format!("if{}", rule.redirect_ifindex))?;
-            
-            if let Some(map) = self.redirect_map() {
-                map.update(&key, &if_redirect, libbpf_rs::MapFlags::ANY)
-                    .context("Failed to update redirect_map")?;
-            } else {
-                return Err(anyhow!("Failed to update redirect_map"));
-            }
+        if let (true, false, Some(cpu)) = (required.cpu, written.cpu, rule.redirect_cpu) {
+            self.acquire_cpu_redirect(cpu)?;
+            written.cpu = true;
+        }
+        
+        if required.lpm && !written.lpm {
+            self.write_rule(rule)?;
+            written.lpm = true;
         }
         
-        // 로컬 캐시 업데이트
-        self.rules.push(rule);
+        if required.wildcard && !written.wildcard {
+            self.write_wildcard(rule)?;
+            written.wildcard = true;
+        }
         
         Ok(())
     }
     
-    /// 규칙 삭제
-    pub fn delete_rule(&mut self, label: &str) -> Result<bool> {
-        debug!("Deleting rule: {}", label);
+    /// 완전히 설치된 규칙을 새 규칙으로 교체
+    ///
+    /// 새 규칙의 대상 맵 항목과 LPM 항목을 먼저 기록한 뒤 이전 항목을 정리한다.
+    /// 기록한 새 항목은 `written`에, 커널 상태를 바꾸기 시작했는지는 `changed`에 남긴다.
+    fn replace_steps(
+        &mut self,
+        old: &FilterRule,
+        rule: &FilterRule,
+        written: &mut Written,
+        changed: &mut bool,
+    ) -> Result<()> {
+        let required = Written::required(rule);
+        let old_keys = self.prefix_keys(old);
+        let new_keys = self.prefix_keys(rule);
+        
+        // 리디렉션 인터페이스: 새 참조를 먼저 얻어 같은 대상의 항목이 잠시 삭제되지 않게 함
+        if required.redirect {
+            self.acquire_rule_redirect(rule)?;
+            written.redirect = true;
+            *changed = true;
+        }
         
-        let rule_index = self.rules.iter().position(|r| r.label == label);
+        // CPU 리디렉션 (새 항목 기록 후 더 이상 참조되지 않는 이전 항목 정리)
+        if let (true, Some(cpu)) = (required.cpu, rule.redirect_cpu) {
+            self.acquire_cpu_redirect(cpu)?;
+            written.cpu = true;
+            *changed = true;
+        }
         
-        if let Some(index) = rule_index {
-            let rule = &self.rules[index];
+        // 새 소스 IP 항목 기록 (같은 키면 통계를 이어받아 덮어씀)
+        for key in &new_keys {
+            let mut value = self.create_filter_rule(rule)?;
+            let map = self.filter_rules_map
+                .ok_or_else(|| anyhow!("Failed to update filter_rules map"))?;
             
-            // 소스 IP 규칙 삭제 (있는 경우)
-            if let Some((src_ip, prefix_len)) = rule.src_ip {
-                let key = self.create_prefix_key(src_ip, prefix_len);
-                
-                if let Some(map) = self.filter_rules_map() {
-                    map.delete(&key)
-                        .context("Failed to delete from filter_rules map")?;
-                } else {
-                    return Err(anyhow!("Failed to get filter_rules map"));
+            if old_keys.contains(key) {
+                if let Ok(Some(current)) = map.lookup(key) {
+                    if current.len() == value.len() {
+                        let stats = value.len() - RULE_STATS_SIZE;
+                        value[stats..].copy_from_slice(&current[stats..]);
+                    }
                 }
             }
             
-            // 로컬 캐시 업데이트
-            self.rules.remove(index);
-            
-            Ok(true)
-        } else {
-            Ok(false)
+            map.update(key, &value)
+                .context("Failed to update filter_rules map")?;
+            written.lpm = true;
+            *changed = true;
         }
-    }
-    
-    /// 규칙 목록 조회
-    pub fn list_rules(&self, include_stats: bool) -> Result<Vec<RuleInfo>> {
-        let mut result = Vec::new();
-        
-        for rule in &self.rules {
-            let stats = if include_stats {
-                // 규칙 통계 조회
-                if let Some((src_ip, prefix_len)) = rule.src_ip {
-                    let key = self.create_prefix_key(src_ip, prefix_len);
-                    
-//                    if let Ok(value) = self.filter_rules_map.lookup(&key, 0) {
-                    if let Some(map) = self.filter_rules_map() {
-                        if let Ok(Some(value)) = map.lookup(&key, MapFlags::empty()) {
-                            if value.len() >= std::mem::size_of::<RuleStats>() {
-                                let stats_offset = value.len() - std::mem::size_of::<RuleStats>();
-                                let stats_bytes = &value[stats_offset..];
-                            
-                                // 통계 데이터 파싱
-                                let packets = u64::from_le_bytes([
-                                    stats_bytes[0], stats_bytes[1], stats_bytes[2], stats_bytes[3],
-                                    stats_bytes[4], stats_bytes[5], stats_bytes[6], stats_bytes[7],
-                                ]);
-                            
-                                let bytes = u64::from_le_bytes([
-                                    stats_bytes[8], stats_bytes[9], stats_bytes[10], stats_bytes[11],
-                                    stats_bytes[12], stats_bytes[13], stats_bytes[14], stats_bytes[15],
-                                ]);
-                            
-                                let last_matched = u64::from_le_bytes([
-                                    stats_bytes[16], stats_bytes[17], stats_bytes[18], stats_bytes[19],
-                                    stats_bytes[20], stats_bytes[21], stats_bytes[22], stats_bytes[23],
-                                ]);
-                            
-                                RuleStats {
-                                    packets,
-                                    bytes,
-                                    last_matched,
-                                
-                                }
-                            } else {
-                                 RuleStats {
-                                    packets: 0,
-                                    bytes: 0,
-                                    last_matched: 0,
-                                }
-                            }
-                        } else {
-                            RuleStats {
-                                packets: 0,
-                                bytes: 0,
-                                last_matched: 0,
-                            }
-                        }
-                    } else {
-                        RuleStats {
-                            packets: 0,
-                            bytes: 0,
-                            last_matched: 0,
-                        }
-                    }
-                } else {
-                    RuleStats {
-                        packets: 0,
-                        bytes: 0,
-                        last_matched: 0,
-                    }
-                }
-            } else {
-                RuleStats {
-                    packets: 0,
-                    bytes: 0,
-                    last_matched: 0,
+        
+        // 와일드카드 규칙은 이전 슬롯을 그대로 덮어써 통계를 이어받음
+        if required.wildcard {
+            self.write_wildcard(rule)?;
+            written.wildcard = true;
+            *changed = true;
+        } else if old.rule_map() == RuleMap::Wildcard {
+            self.erase_wildcard(old)?;
+            *changed = true;
+        }
+        
+        // 새 규칙에 없는 이전 항목 삭제
+        for key in &old_keys {
+            if !new_keys.contains(key) {
+                if let Some(map) = self.filter_rules_map {
+                    map.delete(key)
+                        .context("Failed to delete from filter_rules map")?;
                 }
-            };
-            
-            
-            result.push(rule.to_rule_info(stats));
+                *changed = true;
+            }
+        }
+        
+        self.release_rule_redirect(old)?;
+        if let (3, Some(cpu)) = (old.action, old.redirect_cpu) {
+            self.release_cpu_redirect(cpu)?;
         }
         
-        Ok(result)
+        Ok(())
     }
     
-    /// 전체 통계 조회
-    pub fn get_stats(&self) -> Result<(u64, u64)> {
-        let key = 0u32.to_le_bytes();
-        
-//        if let Ok(value) = self.stats_map.lookup(&key, 0) {
-        if let Some(map) = self.stats_map() {
-            if let Ok(Some(value)) = map.lookup(&key, MapFlags::empty()) {
-                if value.len() >= 16 {
-                    // 통계 데이터 파싱
-                    let packets = u64::from_le_bytes([
-                        value[0], value[1], value[2], value[3],
-                        value[4], value[5], value[6], value[7],
-                    ]);
-                
-                    let bytes = u64::from_le_bytes([
-                        value[8], value[9], value[10], value[11],
-                        value[12], value[13], value[14], value[15],
-                    ]);
-                
-                    Ok((packets, bytes))
+    /// 기록된 커널 항목 삭제 (LPM 항목부터, 삭제한 항목은 `written`에서 지움)
+    fn erase_steps(&mut self, rule: &FilterRule, written: &mut Written) -> Result<()> {
+        if written.lpm {
+            for key in self.prefix_keys(rule) {
+                if let Some(map) = self.filter_rules_map {
+                    map.delete(&key)
+                        .context("Failed to delete from filter_rules map")?;
                 } else {
-                    Ok((0, 0))
+                    return Err(anyhow!("Failed to get filter_rules map"));
                 }
-            } else {
-                Ok((0, 0))
             }
-        } else {
-            Ok((0, 0))
+            written.lpm = false;
         }
-    }
-    
-    /// 프리픽스 키 생성
-    fn create_prefix_key(&self, addr: u32, prefix_len: u32) -> Vec<u8> {
-        let mut key = Vec::with_capacity(8);
         
-        // 프리픽스 길이 (u32)
-        key.extend_from_slice(&prefix_len.to_le_bytes());
+        if written.wildcard {
+            self.erase_wildcard(rule)?;
+            written.wildcard = false;
+        }
         
-        // IPv4 주소 (u32)
-        key.extend_from_slice(&addr.to_le_bytes());
+        // 리디렉션 인터페이스 참조 해제
+        if written.redirect {
+            self.release_rule_redirect(rule)?;
+            written.redirect = false;
+        }
         
-        key
+        // CPU 리디렉션 참조 해제
+        if let (true, Some(cpu)) = (written.cpu, rule.redirect_cpu) {
+            self.release_cpu_redirect(cpu)?;
+        }
+        written.cpu = false;
+        
+        Ok(())
     }
     
-    /// 필터 규칙 생성
-    fn create_filter_rule(&self, rule: &FilterRule) -> Result<Vec<u8>> {
-        let mut value = Vec::new();
-        
-        // priority (u32)
-        value.extend_from_slice(&rule.priority.to_le_bytes());
+    /// CPU 리디렉션 참조 추가 (첫 참조 시 cpu_map 항목 생성)
+    fn acquire_cpu_redirect(&mut self, cpu: u32) -> Result<()> {
+        if !self.cpu_refs.contains_key(&cpu) {
+            self.write_cpu_redirect(cpu)?;
+        }
         
-        // action (u8)
-        value.push(rule.action);
+        *self.cpu_refs.entry(cpu).or_insert(0) += 1;
+        Ok(())
+    }
+    
+    /// CPU 리디렉션 참조 해제 (마지막 참조 시 cpu_map 항목 삭제)
+    fn release_cpu_redirect(&mut self, cpu: u32) -> Result<()> {
+        let count = match self.cpu_refs.get(&cpu) {
+            Some(count) => *count,
+            None => return Ok(()),
+        };
         
-        // protocol (u8)
-        value.push(rule.protocol);
+        if count > 1 {
+            self.cpu_refs.insert(cpu, count - 1);
+            return Ok(());
+        }
         
-        // src_port_min (u16)
-        value.extend_from_slice(&rule.src_port_min.to_le_bytes());
+        if let Some(map) = self.cpu_map {
+            map.delete(&cpu.to_le_bytes())
+                .context("Failed to delete from cpu_map")?;
+        }
+        self.cpu_refs.remove(&cpu);
         
-        // src_port_max (u16)
-        value.extend_from_slice(&rule.src_port_max.to_le_bytes());
+        Ok(())
+    }
+    
+    /// CPU 리디렉션 항목 기록
+    fn write_cpu_redirect(&self, cpu: u32) -> Result<()> {
+        let cpu_redirect = self.create_cpu_redirect(CPUMAP_QUEUE_SIZE)?;
         
-        // dst_port_min (u16)
-        value.extend_from_slice(&rule.dst_port_min.to_le_bytes());
+        if let Some(map) = self.cpu_map {
+            map.update(&cpu.to_le_bytes(), &cpu_redirect)
+                .context("Failed to update cpu_map")
+        } else {
+            Err(anyhow!("Failed to update cpu_map"))
+        }
+    }
+    
+    /// 리디렉션 인터페이스 참조 추가 (첫 참조 시 redirect_map 항목 생성)
+    fn acquire_redirect(&mut self, ifindex: u32, ifname: &str) -> Result<()> {
+        if !self.redirect_refs.contains_key(&ifindex) {
+            let key = ifindex.to_le_bytes();
+            let if_redirect = self.create_if_redirect(ifindex, ifname)?;
+            
+            if let Some(map) = self.redirect_map {
+                map.update(&key, &if_redirect)
+                    .context("Failed to update redirect_map")?;
+            } else {
+                return Err(anyhow!("Failed to update redirect_map"));
+            }
+        }
         
-        // dst_port_max (u16)
-        value.extend_from_slice(&rule.dst_port_max.to_le_bytes());
+        *self.redirect_refs.entry(ifindex).or_insert(0) += 1;
+        Ok(())
+    }
+    
+    /// 리디렉션 인터페이스 참조 해제 (마지막 참조 시 redirect_map 항목 삭제)
+    ///
+    /// 항목 삭제에 실패하면 참조 수를 그대로 두어 다시 시도할 수 있게 한다.
+    fn release_redirect(&mut self, ifindex: u32) -> Result<()> {
+        let count = match self.redirect_refs.get(&ifindex) {
+            Some(count) => *count,
+            None => return Ok(()),
+        };
         
-        // tcp_flags (u8)
-        value.push(rule.tcp_flags);
+        if count > 1 {
+            self.redirect_refs.insert(ifindex, count - 1);
+            return Ok(());
+        }
         
-        // redirect_ifindex (u32)
-        value.extend_from_slice(&rule.redirect_ifindex.to_le_bytes());
+        if let Some(map) = self.redirect_map {
+            map.delete(&ifindex.to_le_bytes())
+                .context("Failed to delete from redirect_map")?;
+        }
+        self.redirect_refs.remove(&ifindex);
         
-        // rate_limit (u32)
-        value.extend_from_slice(&rule.rate_limit.to_le_bytes());
+        Ok(())
+    }
+    
+    /// 리디렉션 인터페이스 참조 추가 (인터페이스 리디렉션 규칙만 해당)
+    fn acquire_rule_redirect(&mut self, rule: &FilterRule) -> Result<()> {
+        if rule.action == 3 && rule.redirect_ifindex != 0 && !rule.degraded {
+            let ifname = rule.redirect_ifname.clone()
+                .unwrap_or_else(|| format!("if{}", rule.redirect_ifindex));
+            self.acquire_redirect(rule.redirect_ifindex, &ifname)?;
+        }
         
-        // expire (u32)
-        value.extend_from_slice(&rule.expire.to_le_bytes());
+        Ok(())
+    }
+    
+    /// redirect_map의 모든 항목 (슬롯 순, 값을 해석하지 못한 항목은 제외)
+    fn redirect_entries(&self) -> Vec<(u32, RedirectIf)> {
+        let Some(map) = self.redirect_map else {
+            return Vec::new();
+        };
         
-        // label (char[32])
-        let mut label_bytes = [0u8; 32];
-        for (i, b) in rule.label.as_bytes().iter().enumerate() {
-            if i < 31 {
-                label_bytes[i] = *b;
+        let mut entries: Vec<(u32, RedirectIf)> = map.keys().into_iter()
+            .filter_map(|key| {
+                let slot = u32::from_le_bytes(key.get(0..4)?.try_into().ok()?);
+                let value = map.lookup(&key).ok().flatten()?;
+                Some((slot, RedirectIf::from_bytes(&value)?))
+            })
+            .collect();
+        entries.sort_by_key(|(slot, _)| *slot);
+        entries
+    }
+    
+    /// 참조 수와 관계없이 redirect_map 항목 삭제 (참조하는 규칙이 없는 항목 정리용)
+    fn delete_redirect_entry(&mut self, slot: u32) -> Result<()> {
+        if let Some(map) = self.redirect_map {
+            if matches!(map.lookup(&slot.to_le_bytes()), Ok(Some(_))) {
+                map.delete(&slot.to_le_bytes())
+                    .context("Failed to delete from redirect_map")?;
             }
         }
-        value.extend_from_slice(&label_bytes);
+        self.redirect_refs.remove(&slot);
         
-        // stats (구조체)
-        value.extend_from_slice(&[0u8; 24]); // packets, bytes, last_matched (u64 * 3)
-        
-        Ok(value)
+        Ok(())
     }
     
-    /// 리디렉션 인터페이스 생성
-    fn create_if_redirect(&self, ifindex: u32, ifname: &str) -> Result<Vec<u8>> {
-        let mut value = Vec::new();
-        
-        // ifindex (u32)
-        value.extend_from_slice(&ifindex.to_le_bytes());
-        
-        // ifname (char[16])
-        let mut ifname_bytes = [0u8; 16];
-        for (i, b) in ifname.as_bytes().iter().enumerate() {
-            if i < 15 {
-                ifname_bytes[i] = *b;
-            }
+    /// 리디렉션 인터페이스 참조 해제 (인터페이스 리디렉션 규칙만 해당)
+    fn release_rule_redirect(&mut self, rule: &FilterRule) -> Result<()> {
+        if rule.action == 3 && rule.redirect_ifindex != 0 && !rule.degraded {
+            self.release_redirect(rule.redirect_ifindex)?;
         }
-        value.extend_from_slice(&ifname_bytes);
         
-        Ok(value)
+        Ok(())
+    }
+    
+    /// 소스 IP 규칙의 커널 항목 기록 (소스 IP가 없으면 아무것도 하지 않음)
+    fn write_rule(&self, rule: &FilterRule) -> Result<()> {
+        for key in self.prefix_keys(rule) {
+            let value = self.create_filter_rule(rule)?;
+            
+            if let Some(map) = self.filter_rules_map {
+                map.update(&key, &value)
+                    .context("Failed to update filter_rules map")?;
+            } else {
+                return Err(anyhow!("Failed to update filter_rules map"));
+            }
+        }
+        
+        Ok(())
+    }
+    
+    /// 소스 IP가 없는 규칙의 와일드카드 슬롯 기록
+    ///
+    /// 이미 슬롯이 있는 레이블은 같은 슬롯을 덮어쓰며 패킷 통계를 이어받고,
+    /// 없으면 비어 있는 가장 낮은 슬롯을 사용한다.
+    fn write_wildcard(&mut self, rule: &FilterRule) -> Result<()> {
+        let map = self.wildcard_map
+            .ok_or_else(|| anyhow!("BPF object has no wildcard_rules map; rules without src_ip cannot be enforced"))?;
+        let mut value = self.create_filter_rule(rule)?;
+        
+        let slot = match self.wildcard_slots.get(&rule.label) {
+            Some(&slot) => {
+                if let Ok(Some(current)) = map.lookup(&slot.to_le_bytes()) {
+                    if current.len() == value.len() {
+                        let stats = value.len() - RULE_STATS_SIZE;
+                        value[stats..].copy_from_slice(&current[stats..]);
+                    }
+                }
+                slot
+            }
+            None => (0..MAX_WILDCARD_RULES)
+                .find(|slot| !self.wildcard_slots.values().any(|used| used == slot))
+                .ok_or_else(|| anyhow!("wildcard_rules map is full ({} rules without src_ip)", MAX_WILDCARD_RULES))?,
+        };
+        
+        map.update(&slot.to_le_bytes(), &value)
+            .context("Failed to update wildcard_rules map")?;
+        self.wildcard_slots.insert(rule.label.clone(), slot);
+        
+        Ok(())
+    }
+    
+    /// 와일드카드 슬롯 비우기 (배열 맵은 항목을 삭제할 수 없으므로 0으로 덮어씀)
+    fn erase_wildcard(&mut self, rule: &FilterRule) -> Result<()> {
+        let slot = match self.wildcard_slots.get(&rule.label) {
+            Some(&slot) => slot,
+            None => return Ok(()),
+        };
+        let map = self.wildcard_map
+            .ok_or_else(|| anyhow!("Failed to get wildcard_rules map"))?;
+        
+        let empty = vec![0u8; self.create_filter_rule(rule)?.len()];
+        map.update(&slot.to_le_bytes(), &empty)
+            .context("Failed to clear wildcard_rules slot")?;
+        self.wildcard_slots.remove(&rule.label);
+        
+        Ok(())
+    }
+    
+    /// 규칙 통계 조회 (커널 항목이 없으면 None, 호스트 이름 규칙은 주소별 항목 합산)
+    fn rule_stats(&self, rule: &FilterRule) -> Option<RuleStats> {
+        if rule.rule_map() == RuleMap::Wildcard {
+            let slot = self.wildcard_slots.get(&rule.label)?;
+            return match self.wildcard_map?.lookup(&slot.to_le_bytes()) {
+                Ok(Some(value)) => Some(parse_rule_stats(&value)),
+                _ => None,
+            };
+        }
+        
+        let map = self.filter_rules_map?;
+        self.prefix_keys(rule).iter()
+            .filter_map(|key| match map.lookup(key) {
+                Ok(Some(value)) => Some(parse_rule_stats(&value)),
+                _ => None,
+            })
+            .reduce(|mut total, stats| {
+                total.packets += stats.packets;
+                total.bytes += stats.bytes;
+                total.last_matched = total.last_matched.max(stats.last_matched);
+                total.redirected_packets += stats.redirected_packets;
+                total.redirect_failures += stats.redirect_failures;
+                total.aborted_packets += stats.aborted_packets;
+                total
+            })
+    }
+    
+    /// 규칙의 LPM 키 (소스 프리픽스마다 하나)
+    fn prefix_keys(&self, rule: &FilterRule) -> Vec<Vec<u8>> {
+        rule.src_prefixes().into_iter()
+            .map(|(src_ip, prefix_len)| self.create_prefix_key(src_ip, prefix_len))
+            .collect()
+    }
+    
+    /// 프리픽스 키 생성 (struct prefix_key)
+    ///
+    /// `addr`는 `parse_ip_prefix`가 만든 호스트 순서 값이다. LPM 트라이는 주소
+    /// 바이트를 앞에서부터 비교하므로 주소는 네트워크 순서로 기록해야 한다.
+    /// 10.0.0.0/8 → `08 00 00 00 0a 00 00 00`
+    fn create_prefix_key(&self, addr: u32, prefix_len: u32) -> Vec<u8> {
+        xdp::prefix_key(addr, prefix_len).to_vec()
+    }
+    
+    /// 이전 버전이 주소를 리틀 엔디안으로 기록한 키 (마이그레이션용)
+    fn legacy_prefix_key(&self, addr: u32, prefix_len: u32) -> Vec<u8> {
+        let mut key = Vec::with_capacity(8);
+        key.extend_from_slice(&prefix_len.to_le_bytes());
+        key.extend_from_slice(&addr.to_le_bytes());
+        key
+    }
+    
+    /// 이전 형식 키의 항목을 현재 형식 키로 옮김 (통계 유지)
+    ///
+    /// 현재 형식 키에 이미 항목이 있으면 통계만 이어받는다. 옮긴 규칙 수를 반환한다.
+    fn migrate_legacy_keys<'r>(&self, rules: impl IntoIterator<Item = &'r FilterRule>) -> Result<usize> {
+        let map = match self.filter_rules_map {
+            Some(map) => map,
+            None => return Ok(0),
+        };
+        
+        let mut migrated = 0;
+        for (src_ip, prefix_len) in rules.into_iter().flat_map(FilterRule::src_prefixes) {
+            let legacy = self.legacy_prefix_key(src_ip, prefix_len);
+            let key = self.create_prefix_key(src_ip, prefix_len);
+            if legacy == key {
+                continue;
+            }
+            
+            let old = match map.lookup(&legacy)? {
+                Some(old) => old,
+                None => continue,
+            };
+            let value = match map.lookup(&key)? {
+                Some(mut current) if current.len() == old.len() && old.len() >= RULE_STATS_SIZE => {
+                    let stats = old.len() - RULE_STATS_SIZE;
+                    current[stats..].copy_from_slice(&old[stats..]);
+                    current
+                }
+                _ => old,
+            };
+            
+            map.update(&key, &value)
+                .context("Failed to update filter_rules map")?;
+            map.delete(&legacy)
+                .context("Failed to delete from filter_rules map")?;
+            migrated += 1;
+        }
+        
+        Ok(migrated)
+    }
+    
+    /// 커널 규칙 맵과 캐시 비교
+    ///
+    /// 캐시의 어떤 규칙에도 속하지 않는 LPM 항목과 채워진 와일드카드 슬롯은 고아로,
+    /// 기록했다고 남긴 규칙 항목이 커널에 없으면 누락으로 분류한다. 설치에 실패한
+    /// 규칙은 설치 상태로 이미 드러나므로 누락으로 보지 않는다.
+    fn audit(&self, rules: &RuleCache) -> Vec<Divergence> {
+        let mut found = Vec::new();
+        
+        if let Some(map) = self.filter_rules_map {
+            let known: BTreeSet<Vec<u8>> = rules.iter()
+                .flat_map(|rule| self.prefix_keys(rule))
+                .collect();
+            
+            for key in map.keys() {
+                if known.contains(&key) {
+                    continue;
+                }
+                let value = map.lookup(&key).ok().flatten().unwrap_or_default();
+                found.push(Divergence::Orphan { map: RuleMap::Lpm, key, value });
+            }
+            
+            for rule in rules.iter().filter(|rule| self.written_for(&rule.label).lpm) {
+                for key in self.prefix_keys(rule) {
+                    if matches!(map.lookup(&key), Ok(None)) {
+                        found.push(Divergence::Missing { map: RuleMap::Lpm, label: rule.label.clone(), key });
+                    }
+                }
+            }
+        }
+        
+        if let Some(map) = self.wildcard_map {
+            let filled = |slot: u32| map.lookup(&slot.to_le_bytes()).ok().flatten()
+                .filter(|value| value.get(RULE_ACTION_OFFSET).is_some_and(|action| *action != 0));
+            
+            for slot in 0..MAX_WILDCARD_RULES {
+                if self.wildcard_slots.values().any(|used| *used == slot) {
+                    continue;
+                }
+                if let Some(value) = filled(slot) {
+                    found.push(Divergence::Orphan { map: RuleMap::Wildcard, key: slot.to_le_bytes().to_vec(), value });
+                }
+            }
+            
+            for rule in rules.iter().filter(|rule| self.written_for(&rule.label).wildcard) {
+                if let Some(&slot) = self.wildcard_slots.get(&rule.label) {
+                    if filled(slot).is_none() {
+                        found.push(Divergence::Missing {
+                            map: RuleMap::Wildcard,
+                            label: rule.label.clone(),
+                            key: slot.to_le_bytes().to_vec(),
+                        });
+                    }
+                }
+            }
+        }
+        
+        found
+    }
+    
+    /// 고아 항목 삭제 (와일드카드 슬롯은 0으로 덮어씀)
+    fn delete_orphan(&self, map: RuleMap, key: &[u8], value: &[u8]) -> Result<()> {
+        match map {
+            RuleMap::Lpm => self.filter_rules_map
+                .ok_or_else(|| anyhow!("Failed to get filter_rules map"))?
+                .delete(key)
+                .context("Failed to delete from filter_rules map"),
+            RuleMap::Wildcard => self.wildcard_map
+                .ok_or_else(|| anyhow!("Failed to get wildcard_rules map"))?
+                .update(key, &vec![0u8; value.len()])
+                .context("Failed to clear wildcard_rules slot"),
+        }
+    }
+    
+    /// 누락 항목 다시 기록 (통계는 0부터)
+    fn reinstall_missing(&mut self, map: RuleMap, key: &[u8], rule: &FilterRule) -> Result<()> {
+        match map {
+            RuleMap::Lpm => {
+                let value = self.create_filter_rule(rule)?;
+                self.filter_rules_map
+                    .ok_or_else(|| anyhow!("Failed to update filter_rules map"))?
+                    .update(key, &value)
+                    .context("Failed to update filter_rules map")
+            }
+            RuleMap::Wildcard => self.write_wildcard(rule),
+        }
+    }
+    
+    /// 필터 규칙 생성 (struct filter_rule, 정렬 패딩은 0)
+    fn create_filter_rule(&self, rule: &FilterRule) -> Result<Vec<u8>> {
+        // label (char[32]) - 검증된 레이블에서만 생성 (잘라내지 않음, 네임스페이스 제외)
+        let label_bytes = utils::label_to_kernel_bytes(utils::split_label(&rule.label).1)?;
+        let src_ports = layout::encode_port_list(&rule.src_port_list)
+            .ok_or_else(|| anyhow!("Rule '{}' has more than {} source ports or port 0", rule.label, layout::MAX_RULE_PORTS))?;
+        let dst_ports = layout::encode_port_list(&rule.dst_port_list)
+            .ok_or_else(|| anyhow!("Rule '{}' has more than {} destination ports or port 0", rule.label, layout::MAX_RULE_PORTS))?;
+        
+        let fields: [(&str, &[u8]); 16] = [
+            ("priority", &rule.priority.to_le_bytes()),
+            // 할당량을 넘었으면 할당량 액션
+            ("action", &[rule.kernel_action()]),
+            ("protocol", &[rule.protocol]),
+            ("src_port_min", &rule.src_port_min.to_le_bytes()),
+            ("src_port_max", &rule.src_port_max.to_le_bytes()),
+            ("dst_port_min", &rule.dst_port_min.to_le_bytes()),
+            ("dst_port_max", &rule.dst_port_max.to_le_bytes()),
+            ("tcp_flags", &[rule.tcp_flags]),
+            ("redirect_ifindex", &rule.redirect_ifindex.to_le_bytes()),
+            ("rate_limit", &rule.rate_limit.to_le_bytes()),
+            ("expire", &rule.expire.to_le_bytes()),
+            ("redirect_cpu", &rule.redirect_cpu.unwrap_or(REDIRECT_CPU_NONE).to_le_bytes()),
+            ("label", &label_bytes),
+            ("src_ports", &src_ports),
+            ("dst_ports", &dst_ports),
+            ("stats", &FilterStats::default().to_bytes()),
+        ];
+        
+        let mut value = vec![0u8; layout::RULE_VALUE_SIZE];
+        for (name, bytes) in fields {
+            let range = layout::rule_field(name)
+                .ok_or_else(|| anyhow!("struct filter_rule has no field {}", name))?;
+            value[range].copy_from_slice(bytes);
+        }
+        
+        Ok(value)
+    }
+    
+    /// CPU 리디렉션 값 생성 (struct bpf_cpumap_val)
+    fn create_cpu_redirect(&self, qsize: u32) -> Result<Vec<u8>> {
+        let mut value = Vec::new();
+        
+        // qsize (u32)
+        value.extend_from_slice(&qsize.to_le_bytes());
+        
+        // bpf_prog.fd (i32, 0 = 추가 프로그램 없음)
+        value.extend_from_slice(&0i32.to_le_bytes());
+        
+        Ok(value)
+    }
+    
+    /// 리디렉션 인터페이스 생성
+    fn create_if_redirect(&self, ifindex: u32, ifname: &str) -> Result<Vec<u8>> {
+        let mut value = Vec::new();
+        
+        // ifindex (u32)
+        value.extend_from_slice(&ifindex.to_le_bytes());
+        
+        // ifname (char[16])
+        let mut ifname_bytes = [0u8; 16];
+        for (i, b) in ifname.as_bytes().iter().enumerate() {
+            if i < 15 {
+                ifname_bytes[i] = *b;
+            }
+        }
+        value.extend_from_slice(&ifname_bytes);
+        
+        Ok(value)
+    }
+}
+
+impl<'a> RuleBackend for XdpBackend<'a> {
+    fn kind(&self) -> BackendKind {
+        BackendKind::Xdp
+    }
+    
+    /// 중간 단계가 실패하면 이미 기록한 항목을 지운다. 지우지 못하면
+    /// `PartialInstall` 오류를 반환하고, 남은 항목은 다음 설치 시도에서 이어받는다.
+    fn install(&mut self, rule: &FilterRule, _rules: &RuleCache) -> Result<()> {
+        let mut written = self.written.remove(&rule.label).unwrap_or_default();
+        
+        let result = self.write_steps(rule, &mut written);
+        if let Err(e) = result {
+            if let Err(undo_err) = self.erase_steps(rule, &mut written) {
+                error!("Failed to roll back partial install of rule {}: {:#}", rule.label, undo_err);
+                self.written.insert(rule.label.clone(), written);
+                return Err(e.context(PartialInstall));
+            }
+            return Err(e);
+        }
+        
+        self.written.insert(rule.label.clone(), written);
+        Ok(())
+    }
+    
+    /// 설치되지 않은 규칙이면 아무것도 하지 않는다. 일부 항목만 지우고 실패하면
+    /// `PartialInstall` 오류를 반환한다.
+    fn remove(&mut self, rule: &FilterRule, _rules: &RuleCache) -> Result<()> {
+        let before = match self.written.remove(&rule.label) {
+            Some(written) => written,
+            None => return Ok(()),
+        };
+        
+        let mut written = before;
+        let result = self.erase_steps(rule, &mut written);
+        if written != Written::default() {
+            self.written.insert(rule.label.clone(), written);
+        }
+        
+        match result {
+            Err(e) if written != before => Err(e.context(PartialInstall)),
+            result => result,
+        }
+    }
+    
+    /// 새 커널 항목을 먼저 기록하고 이전 항목을 정리하므로 교체 중에 규칙이
+    /// 사라지는 구간이 없다. LPM 키가 같으면 패킷 통계를 이어받는다.
+    /// 이전 규칙이 일부만 설치된 상태였다면 남은 항목을 지우고 새로 설치한다.
+    fn replace(&mut self, old: &FilterRule, rule: &FilterRule, rules: &RuleCache) -> Result<()> {
+        let before = self.written.get(&old.label).copied().unwrap_or_default();
+        if before != Written::required(old) {
+            self.remove(old, rules)?;
+            return self.install(rule, rules);
+        }
+        
+        let mut written = Written::default();
+        let mut changed = false;
+        let result = self.replace_steps(old, rule, &mut written, &mut changed);
+        
+        match result {
+            Ok(()) => {
+                self.written.insert(rule.label.clone(), written);
+                Ok(())
+            }
+            // 커널 항목이 바뀌기 전에 실패하면 이전 규칙이 그대로 남음
+            Err(e) if !changed => Err(e),
+            Err(e) => {
+                self.written.insert(rule.label.clone(), written);
+                Err(e.context(PartialInstall))
+            }
+        }
+    }
+    
+    fn stats(&self, rules: &RuleCache) -> Result<HashMap<String, RuleStats>> {
+        Ok(rules.iter()
+            .filter_map(|rule| Some((rule.label.clone(), self.rule_stats(rule)?)))
+            .collect())
+    }
+}
+
+/// 대량 맵 쓰기 기본 청크 크기 (규칙 변경 수)
+pub const DEFAULT_WRITE_CHUNK_SIZE: usize = 500;
+
+/// 대량 맵 쓰기 청크 사이 기본 대기 시간
+pub const DEFAULT_WRITE_PAUSE: Duration = Duration::from_millis(5);
+
+/// 대량 맵 쓰기 일정
+///
+/// 규칙 집합 적용처럼 변경이 많은 작업은 청크 단위로 나눠 쓰고, 호출자는 청크 사이에
+/// 맵 관리자 잠금을 놓고 `pause`만큼 쉰다. 한 번에 수만 번의 맵 갱신을 몰아 하면
+/// 일부 커널에서 패킷 경로의 맵 조회가 밀리기 때문이다.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WriteSchedule {
+    /// 청크 하나에 적용할 규칙 변경 수 (0이면 1로 봄)
+    pub chunk_size: usize,
+    /// 청크 사이 대기 시간
+    pub pause: Duration,
+}
+
+impl Default for WriteSchedule {
+    fn default() -> Self {
+        Self {
+            chunk_size: DEFAULT_WRITE_CHUNK_SIZE,
+            pause: DEFAULT_WRITE_PAUSE,
+        }
+    }
+}
+
+/// 대량 규칙 변경 종류
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BulkKind {
+    Delete,
+    Update,
+    Add,
+}
+
+/// 대량 규칙 변경 하나
+#[derive(Debug)]
+pub enum BulkOp {
+    /// 레이블(네임스페이스 포함)의 규칙 삭제
+    Delete(String),
+    Update(FilterRule),
+    Add(FilterRule),
+}
+
+impl BulkOp {
+    pub fn kind(&self) -> BulkKind {
+        match self {
+            Self::Delete(_) => BulkKind::Delete,
+            Self::Update(_) => BulkKind::Update,
+            Self::Add(_) => BulkKind::Add,
+        }
+    }
+    
+    pub fn label(&self) -> &str {
+        match self {
+            Self::Delete(label) => label,
+            Self::Update(rule) | Self::Add(rule) => &rule.label,
+        }
+    }
+}
+
+/// 청크 단위로 나눠 적용하는 대량 규칙 변경 (넣은 순서대로 적용)
+#[derive(Debug, Default)]
+pub struct BulkWrite {
+    pending: VecDeque<BulkOp>,
+    total: usize,
+}
+
+impl BulkWrite {
+    pub fn new(ops: Vec<BulkOp>) -> Self {
+        Self {
+            total: ops.len(),
+            pending: ops.into(),
+        }
+    }
+    
+    /// 전체 변경 수
+    pub fn total(&self) -> usize {
+        self.total
+    }
+    
+    /// 적용을 시도한 변경 수 (실패 포함)
+    pub fn done(&self) -> usize {
+        self.total - self.pending.len()
+    }
+    
+    pub fn is_finished(&self) -> bool {
+        self.pending.is_empty()
+    }
+    
+    /// 적용하지 않고 남은 변경 (중단할 때 보고용)
+    pub fn into_pending(self) -> Vec<BulkOp> {
+        self.pending.into()
+    }
+}
+
+/// 대량 규칙 변경 계획 (`MapManager::plan`)
+#[derive(Debug)]
+pub struct Plan {
+    /// 변경별 결과 (넣은 순서, 실제 적용과 같은 오류)
+    pub outcomes: Vec<(BulkKind, String, Result<(), DaemonError>)>,
+    /// 모든 변경을 적용한 뒤의 LPM 키 공간
+    pub space: RuleSpaceStats,
+}
+
+/// 인터페이스에 연결된 규칙 설치 백엔드
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InterfaceBinding {
+    pub backend: BackendKind,
+    /// XDP 연결 모드 (nftables 대체 시 None)
+    pub mode: Option<String>,
+    /// 대체 BPF 오브젝트 (기본 오브젝트면 None)
+    pub object: Option<String>,
+    /// 링크가 내려가 XDP 프로그램을 분리해 둔 상태
+    pub suspended: bool,
+}
+
+pub struct MapManager<'a> {
+    // XdpFilterSkel에 대한 참조만 유지
+//    skel: &'a XdpFilterSkel,
+    xdp: XdpBackend<'a>,
+    /// attach 요청으로 로드한 대체 오브젝트별 백엔드 (오브젝트 경로 기준)
+    objects: BTreeMap<String, XdpBackend<'a>>,
+    /// XDP를 연결할 수 없는 인터페이스용 nftables 백엔드 (처음 대체할 때 생성)
+    nftables: Option<NftablesBackend>,
+    stats_map: Option<&'a dyn MapBackend>,
+    conntrack_map: Option<&'a dyn MapBackend>,
+    rules: RuleCache,
+    /// attach 요청으로 연결된 인터페이스
+    interfaces: BTreeMap<String, InterfaceBinding>,
+    /// 링크가 내려간 인터페이스 (연결된 인터페이스와 리디렉션 대상, 링크 감시가 갱신)
+    links_down: BTreeSet<String>,
+    /// 직접 등록한 리디렉션 대상 (슬롯별 인터페이스 이름, 규칙처럼 참조 하나를 가짐)
+    manual_redirects: BTreeMap<u32, String>,
+    /// 대량 맵 쓰기 일정
+    write_schedule: WriteSchedule,
+    /// 추가하는 규칙의 이전 형식 키 항목 이전 (`general.migrate_prefix_keys`)
+    migrate_legacy_keys: bool,
+    /// 규칙 변경 발행 (`WatchRules` 구독)
+    changes: RuleChangeLog,
+}
+
+impl<'a> std::fmt::Debug for MapManager<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MapManager")
+            .field("rules", &self.rules)
+            .field("interfaces", &self.interfaces)
+            // Map은 Debug할 수 없으므로 포함하지 않음
+            .finish()
+    }
+}
+
+impl<'a> MapManager<'a> {
+    pub fn new(skel: &'a XdpFilterSkel) -> Self {
+        Self::with_backends(MapBackends::from_skel(skel))
+    }
+    
+    /// 임의의 맵 백엔드로 생성 (테스트용 인메모리 맵 등)
+    pub fn with_backends(backends: MapBackends<'a>) -> Self {
+        Self {
+            xdp: XdpBackend::new(&backends),
+            objects: BTreeMap::new(),
+            nftables: None,
+            stats_map: backends.stats,
+            conntrack_map: backends.conntrack,
+            rules: RuleCache::new(),
+            interfaces: BTreeMap::new(),
+            links_down: BTreeSet::new(),
+            manual_redirects: BTreeMap::new(),
+            write_schedule: WriteSchedule::default(),
+            migrate_legacy_keys: false,
+            changes: RuleChangeLog::default(),
+        }
+    }
+    
+    /// 규칙을 추가할 때 이전 버전이 남긴 같은 프리픽스 항목을 통계와 함께 옮김
+    pub fn with_legacy_key_migration(mut self) -> Self {
+        self.migrate_legacy_keys = true;
+        self
+    }
+    
+    /// 대량 맵 쓰기 일정 지정 (구성의 `map_writes`)
+    pub fn with_write_schedule(mut self, schedule: WriteSchedule) -> Self {
+        self.write_schedule = schedule;
+        self
+    }
+    
+    pub fn write_schedule(&self) -> WriteSchedule {
+        self.write_schedule
+    }
+    
+    fn stats_map(&self) -> Option<&'a dyn MapBackend> {
+//        self.skel.maps().stats_map()
+//        let maps = &self.skel.maps();
+//        maps.stats_map()
+        self.stats_map
+    }
+    
+    /// 모든 규칙 설치 백엔드에 변경 반영
+    ///
+    /// 규칙 캐시는 이미 변경된 상태여야 한다. 한 백엔드가 실패하면 `restore`로
+    /// 캐시를 되돌린 뒤 앞서 반영한 백엔드에 `undo`를 적용하고 오류를 반환한다.
+    /// 실패한 백엔드나 `undo`가 변경을 일부만 되돌렸으면 `PartialInstall` 오류를 반환한다.
+    fn apply_to_backends<Op, Restore, Undo>(&mut self, op: Op, restore: Restore, undo: Undo) -> Result<()>
+    where
+        Op: Fn(&mut dyn RuleBackend, &RuleCache) -> Result<()>,
+        Restore: FnOnce(&mut RuleCache),
+        Undo: Fn(&mut dyn RuleBackend, &RuleCache) -> Result<()>,
+    {
+        let mut backends: Vec<&mut dyn RuleBackend> = vec![&mut self.xdp];
+        for object in self.objects.values_mut() {
+            backends.push(object);
+        }
+        if let Some(nftables) = self.nftables.as_mut() {
+            backends.push(nftables);
+        }
+        
+        for index in 0..backends.len() {
+            if let Err(e) = op(&mut *backends[index], &self.rules) {
+                restore(&mut self.rules);
+                
+                let mut partial = is_partial(&e);
+                for backend in backends[..index].iter_mut() {
+                    if let Err(undo_err) = undo(&mut **backend, &self.rules) {
+                        error!("Failed to roll back {} backend: {:#}", backend.kind().as_str(), undo_err);
+                        partial = true;
+                    }
+                }
+                
+                if partial && !is_partial(&e) {
+                    return Err(e.context(PartialInstall));
+                }
+                return Err(e);
+            }
+        }
+        
+        Ok(())
+    }
+
+    /// 규칙 추가
+    pub fn add_rule(&mut self, rule: FilterRule) -> Result<(), DaemonError> {
+        debug!("Adding rule: {}", rule.label);
+        
+        // 커널에 기록될 레이블과 캐시의 레이블이 같도록 먼저 검증
+        utils::validate_rule_key(&rule.label)?;
+        if self.rules.contains(&rule.label) {
+            return Err(DaemonError::DuplicateLabel(rule.label));
+        }
+        if let Some(prefix) = self.taken_prefix(&rule) {
+            return Err(DaemonError::PrefixConflict(conflicts::format_prefix(prefix)));
+        }
+        
+        // 겹치는 범위에서는 우선순위가 아니라 가장 긴 프리픽스가 적용됨
+        let overlapping = rule.src_ip.map(|prefix| self.rules.overlapping(prefix)).unwrap_or_default();
+        for conflict in conflicts::priority_conflicts(&rule, overlapping) {
+            warn!("Rule {} ({}) overlaps rule {} ({}); {} wins despite lower priority",
+                conflict.narrower, conflict.narrower_prefix, conflict.broader, conflict.broader_prefix,
+                conflict.winner);
+        }
+        
+        // 로컬 캐시 업데이트 후 각 백엔드에 설치
+        let label = rule.label.clone();
+        self.rules.push(rule.clone());
+        let result = self.apply_to_backends(
+            |backend, rules| backend.install(&rule, rules),
+            |rules| { rules.remove(&rule.label); },
+            |backend, rules| backend.remove(&rule, rules),
+        );
+        
+        // 되돌리지 못한 항목이 남았으면 repair_rules로 재시도할 수 있도록 캐시에 유지
+        match &result {
+            Ok(()) if self.migrate_legacy_keys => self.migrate_rule_keys(&rule),
+            Err(e) if is_partial(e) => {
+                self.rules.push(FilterRule { install_state: partial_state(e), ..rule });
+            },
+            _ => {},
+        }
+        
+        self.publish_change(&label, None);
+        result.map_err(DaemonError::Map)
+    }
+    
+    /// 규칙 삭제
+    pub fn delete_rule(&mut self, label: &str) -> Result<(), DaemonError> {
+        debug!("Deleting rule: {}", label);
+        
+        // 로컬 캐시 업데이트 후 규칙을 가진 모든 백엔드에서 제거
+        let before = self.rule_view(label);
+        let entry = match self.rules.remove(label) {
+            Some(entry) => entry,
+            None => return Err(DaemonError::RuleNotFound(label.to_string())),
+        };
+        let rule = entry.rule.clone();
+        let result = self.apply_to_backends(
+            |backend, rules| backend.remove(&rule, rules),
+            |rules| rules.restore(entry),
+            |backend, rules| backend.install(&rule, rules),
+        );
+        self.mark_partial(label, &result);
+        self.publish_change(label, before);
+        result.map_err(DaemonError::Map)
+    }
+    
+    /// 규칙 교체 (같은 레이블의 기존 규칙을 제자리에서 갱신)
+    ///
+    /// 새 커널 항목을 먼저 기록하고 이전 항목을 정리하므로 갱신 중에 규칙이
+    /// 사라지는 구간이 없다. LPM 키가 같으면 패킷 통계를 유지하며, 생성 시각과
+    /// 작성자, 할당량 초과 상태는 기존 규칙의 값을 유지한다.
+    pub fn update_rule(&mut self, mut rule: FilterRule) -> Result<(), DaemonError> {
+        debug!("Updating rule: {}", rule.label);
+        
+        utils::validate_rule_key(&rule.label)?;
+        
+        let old = match self.rules.get(&rule.label) {
+            Some(old) => old.clone(),
+            None => return Err(DaemonError::RuleNotFound(rule.label)),
+        };
+        if let Some(prefix) = self.taken_prefix(&rule) {
+            return Err(DaemonError::PrefixConflict(conflicts::format_prefix(prefix)));
+        }
+        rule.creation_time = old.creation_time;
+        rule.created_by = old.created_by.clone();
+        rule.quota_exceeded = old.quota_exceeded && rule.quota.is_some();
+        
+        self.replace_rule(old, rule).map_err(DaemonError::Map)
+    }
+
+    /// 대량 규칙 변경의 다음 청크 적용 (`write_schedule`의 청크 크기만큼)
+    ///
+    /// 변경 하나의 실패는 나머지 적용을 막지 않고 결과로 돌려준다. 청크 사이의 대기와
+    /// 잠금 해제는 호출자가 맡는다.
+    pub fn write_chunk(&mut self, write: &mut BulkWrite) -> Vec<(BulkKind, String, Result<(), DaemonError>)> {
+        let count = self.write_schedule.chunk_size.max(1).min(write.pending.len());
+
+        write.pending.drain(..count).collect::<Vec<_>>().into_iter()
+            .map(|op| {
+                let (kind, label) = (op.kind(), op.label().to_string());
+                let result = match op {
+                    BulkOp::Delete(label) => self.delete_rule(&label),
+                    BulkOp::Update(rule) => self.update_rule(rule),
+                    BulkOp::Add(rule) => self.add_rule(rule),
+                };
+                (kind, label, result)
+            })
+            .collect()
+    }
+
+    /// 대량 규칙 변경 계획 (맵과 규칙 캐시는 바꾸지 않음)
+    ///
+    /// 기본 오브젝트의 규칙 맵을 인메모리 맵으로 복사하고 규칙 캐시와 백엔드 상태를
+    /// 복제한 맵 관리자에 `write_chunk`로 변경을 적용하므로, 레이블 중복, 리디렉션 대상,
+    /// 와일드카드 슬롯과 filter_rules 맵 용량(`capacity`) 검사가 실제 적용과 같다.
+    /// 대체 오브젝트와 nftables 백엔드는 시뮬레이션하지 않는다.
+    pub fn plan(&self, ops: Vec<BulkOp>, capacity: usize) -> Plan {
+        let copy = |map: Option<&dyn MapBackend>| map.map(MemoryMap::copy_of);
+        let filter_rules = copy(self.xdp.filter_rules_map).map(|map| map.with_capacity(capacity));
+        let wildcard = copy(self.xdp.wildcard_map);
+        let redirect = copy(self.xdp.redirect_map);
+        let cpu = copy(self.xdp.cpu_map);
+        let backends = MapBackends {
+            filter_rules: filter_rules.as_ref().map(|map| map as &dyn MapBackend),
+            wildcard: wildcard.as_ref().map(|map| map as &dyn MapBackend),
+            redirect: redirect.as_ref().map(|map| map as &dyn MapBackend),
+            cpu: cpu.as_ref().map(|map| map as &dyn MapBackend),
+            ..MapBackends::default()
+        };
+        
+        let mut simulation = MapManager {
+            xdp: self.xdp.simulated(&backends),
+            rules: self.rules.clone(),
+            interfaces: self.interfaces.clone(),
+            links_down: self.links_down.clone(),
+            manual_redirects: self.manual_redirects.clone(),
+            ..MapManager::with_backends(MapBackends::default())
+        };
+        
+        let mut write = BulkWrite::new(ops);
+        let mut outcomes = Vec::new();
+        while !write.is_finished() {
+            outcomes.extend(simulation.write_chunk(&mut write));
+        }
+        
+        Plan { outcomes, space: simulation.rule_space_stats(capacity) }
+    }
+
+    /// 할당량 초과 상태 변경 (커널에는 바뀐 액션만 다시 기록)
+    ///
+    /// 할당량이 없는 규칙이나 없는 레이블이면 false를 반환한다.
+    pub fn set_quota_exceeded(&mut self, label: &str, exceeded: bool) -> Result<bool, DaemonError> {
+        let old = match self.rules.get(label) {
+            Some(old) if old.quota.is_some() => old.clone(),
+            _ => return Ok(false),
+        };
+        if old.quota_exceeded == exceeded {
+            return Ok(true);
+        }
+        
+        let rule = FilterRule { quota_exceeded: exceeded, ..old.clone() };
+        self.replace_rule(old, rule).map_err(DaemonError::Map)?;
+        Ok(true)
+    }
+    
+    /// 다른 규칙이 이미 커널 LPM 키로 쓰는 `rule`의 소스 프리픽스
+    ///
+    /// filter_rules 맵에는 프리픽스마다 항목이 하나뿐이라 같은 키를 기록하면 네임스페이스와
+    /// 관계없이 다른 규칙을 덮어쓴다. 같은 프리픽스의 규칙을 바꾸려면 그 규칙을 갱신한다.
+    fn taken_prefix(&self, rule: &FilterRule) -> Option<(u32, u32)> {
+        let foreign = |other: &FilterRule| other.label != rule.label;
+        let masked = |(addr, prefix_len): (u32, u32)| (utils::mask_ipv4(addr, prefix_len), prefix_len);
+        let prefixes: BTreeSet<(u32, u32)> = rule.src_prefixes().into_iter().map(masked).collect();
+        if prefixes.is_empty() {
+            return None;
+        }
+        
+        // 캐시는 src_ip만 색인하므로 호스트와 프리픽스 집합 규칙은 훑어서 확인
+        let listed = |other: &FilterRule| other.src_host.is_some() || other.src_set.is_some();
+        let indexed = prefixes.iter()
+            .find(|&&prefix| self.rules.with_prefix(prefix).any(|other| !listed(other) && foreign(other)));
+        indexed.copied().or_else(|| self.rules.iter()
+            .filter(|other| listed(other) && foreign(other))
+            .flat_map(|other| other.src_prefixes())
+            .map(masked)
+            .find(|prefix| prefixes.contains(prefix)))
+    }
+    
+    /// 캐시와 모든 백엔드에서 규칙 교체 (실패하면 이전 규칙으로 복원)
+    fn replace_rule(&mut self, old: FilterRule, rule: FilterRule) -> Result<()> {
+        let before = self.rule_view(&old.label);
+        self.rules.replace(rule.clone());
+        let restored = old.clone();
+        let result = self.apply_to_backends(
+            |backend, rules| backend.replace(&old, &rule, rules),
+            |rules| { rules.replace(restored); },
+            |backend, rules| backend.replace(&rule, &old, rules),
+        );
+        self.mark_partial(&rule.label, &result);
+        self.publish_change(&rule.label, before);
+        result
+    }
+    
+    /// 규칙 캐시의 규칙을 구독자에게 보낼 규칙 정보로 변환 (통계 제외)
+    fn rule_view(&self, label: &str) -> Option<RuleInfo> {
+        self.rules.get(label)
+            .map(|rule| rule.to_rule_info(RuleStats::default(), self.has_attached_interfaces()))
+    }
+    
+    /// 변경 전 규칙 정보와 현재 캐시를 비교해 규칙 변경 발행 (달라진 것이 없으면 발행하지 않음)
+    ///
+    /// 규칙 캐시를 바꾸는 모든 경로가 변경을 마친 뒤 호출하므로, 실패해 되돌린 변경은
+    /// 발행되지 않는다.
+    fn publish_change(&mut self, label: &str, before: Option<RuleInfo>) {
+        match (before, self.rule_view(label)) {
+            (None, Some(rule)) => self.changes.publish(RuleChangeKind::Added, rule),
+            (Some(before), Some(rule)) if before != rule => self.changes.publish(RuleChangeKind::Updated, rule),
+            (Some(before), None) => self.changes.publish(RuleChangeKind::Deleted, before),
+            _ => {},
+        }
+    }
+    
+    /// 규칙 하나의 캐시 항목 수정 후 변경 발행
+    fn modify_rule<F>(&mut self, label: &str, f: F)
+    where
+        F: FnOnce(&mut FilterRule),
+    {
+        let before = self.rule_view(label);
+        self.rules.modify(label, f);
+        self.publish_change(label, before);
+    }
+    
+    /// 연결된 인터페이스가 처음 생기거나 모두 사라졌으면 적용 여부(`effective`)가 바뀐 규칙 발행
+    fn publish_attachment(&mut self, was_attached: bool) {
+        if was_attached == self.has_attached_interfaces() {
+            return;
+        }
+        
+        let labels: Vec<String> = self.rules.iter()
+            .filter(|rule| rule.is_enforceable())
+            .map(|rule| rule.label.clone())
+            .collect();
+        for label in labels {
+            if let Some(rule) = self.rule_view(&label) {
+                self.changes.publish(RuleChangeKind::Updated, rule);
+            }
+        }
+    }
+    
+    /// 규칙 변경 구독 시작
+    ///
+    /// 현재 규칙(통계 제외, `list_order` 순서)과 그 상태에 반영된 마지막 변경 순번, 이후
+    /// 변경 수신기를 한 번에 돌려주므로 스냅샷과 변경 사이에 빠지거나 겹치는 변경이 없다.
+    pub fn watch_rules(&self) -> Result<(u64, Vec<RuleInfo>, broadcast::Receiver<RuleChange>), DaemonError> {
+        Ok((self.changes.seq(), self.list_rules(false)?, self.changes.subscribe()))
+    }
+    
+    /// 변경을 되돌리지 못했으면 복원된 규칙을 partial로 표시
+    fn mark_partial(&mut self, label: &str, result: &Result<()>) {
+        if let Err(e) = result {
+            if is_partial(e) {
+                self.rules.modify(label, |rule| rule.install_state = partial_state(e));
+            }
+        }
+    }
+    
+    /// 설치 상태가 installed가 아닌 규칙을 모든 백엔드에 다시 설치
+    ///
+    /// 이미 기록된 항목은 건너뛰고 빠진 항목만 기록한다. 다시 실패한 규칙은
+    /// 설치한 항목을 되돌리고 failed로, 되돌리지도 못하면 partial로 표시한다.
+    /// 복구된 규칙의 레이블과 실패한 규칙의 오류를 반환한다.
+    pub fn repair_rules(&mut self) -> (Vec<String>, Vec<(String, DaemonError)>) {
+        let mut repaired = Vec::new();
+        let mut errors = Vec::new();
+        
+        let pending: Vec<FilterRule> = self.rules.iter()
+            .filter(|rule| rule.install_state != InstallState::Installed)
+            .cloned()
+            .collect();
+        
+        for rule in pending {
+            let result = self.apply_to_backends(
+                |backend, rules| backend.install(&rule, rules),
+                |_| {},
+                |backend, rules| backend.remove(&rule, rules),
+            );
+            
+            let state = match &result {
+                Ok(()) => InstallState::Installed,
+                Err(e) if is_partial(e) => partial_state(e),
+                Err(e) => InstallState::Failed { reason: format!("{:#}", e) },
+            };
+            self.modify_rule(&rule.label, |cached| cached.install_state = state);
+            
+            match result {
+                Ok(()) => {
+                    info!("Repaired rule {}", rule.label);
+                    repaired.push(rule.label);
+                }
+                Err(e) => {
+                    throttled_warn!(&format!("maps:repair:{}", rule.label), logthrottle::HOT_PATH_INTERVAL,
+                        "Failed to repair rule {}: {:#}", rule.label, e);
+                    errors.push((rule.label, DaemonError::Map(e)));
+                }
+            }
+        }
+        
+        (repaired, errors)
+    }
+    
+    /// LPM 키 공간 통계 (`capacity`는 filter_rules 맵 최대 항목 수)
+    ///
+    /// 분포와 겹침은 캐시로 계산하고, 기본 오브젝트의 filter_rules를 순회한 항목 수를
+    /// 함께 보고해 캐시와 커널이 어긋났는지 확인할 수 있게 한다.
+    pub fn rule_space_stats(&self, capacity: usize) -> RuleSpaceStats {
+        let mut labels: BTreeMap<(u32, u32), Vec<String>> = BTreeMap::new();
+        for rule in self.rules.iter().filter(|rule| rule.rule_map() == RuleMap::Lpm) {
+            for (addr, prefix_len) in rule.src_prefixes() {
+                labels.entry((utils::mask_ipv4(addr, prefix_len), prefix_len))
+                    .or_default()
+                    .push(rule.label.clone());
+            }
+        }
+        
+        let mut prefix_lengths = vec![0; 33];
+        for &(_, prefix_len) in labels.keys() {
+            prefix_lengths[prefix_len.min(32) as usize] += 1;
+        }
+        
+        let mut overlaps = conflicts::containment_counts(labels.keys().copied());
+        overlaps.retain(|(_, contains)| *contains > 0);
+        overlaps.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        let top_overlaps = overlaps.into_iter()
+            .take(TOP_OVERLAPS)
+            .map(|(prefix, contains)| PrefixOverlap {
+                prefix: conflicts::format_prefix(prefix),
+                contains,
+                labels: labels.get(&prefix).cloned().unwrap_or_default(),
+            })
+            .collect();
+        
+        let kernel_entries = self.xdp.filter_rules_map.map(|map| map.keys().len());
+        let used = kernel_entries.unwrap_or(labels.len());
+        let fill_percent = if capacity == 0 { 0.0 } else { used as f64 * 100.0 / capacity as f64 };
+        
+        RuleSpaceStats {
+            prefix_lengths,
+            top_overlaps,
+            lpm_entries: labels.len(),
+            kernel_entries,
+            wildcard_rules: self.xdp.wildcard_slots.len(),
+            capacity,
+            fill_percent,
+        }
+    }
+    
+    /// 커널 규칙 맵과 규칙 캐시 비교 (기본 오브젝트와 대체 오브젝트의 맵)
+    pub fn audit_maps(&self) -> MapAudit {
+        let mut audit = MapAudit::default();
+        
+        let backends = std::iter::once((None, &self.xdp))
+            .chain(self.objects.iter().map(|(path, xdp)| (Some(path.as_str()), xdp)));
+        for (object, xdp) in backends {
+            for divergence in xdp.audit(&self.rules) {
+                audit.orphans.extend(divergence.to_orphan_entry(object));
+                audit.missing.extend(divergence.to_missing_entry(object));
+            }
+        }
+        
+        audit
+    }
+    
+    /// 커널 규칙 맵 감사 후 불일치 수정
+    ///
+    /// `delete_orphans`이면 고아 항목을 삭제하고, `reinstall_missing`이면 누락 항목을
+    /// 캐시의 규칙으로 다시 기록한다. 실패한 항목은 건너뛰고 오류로 보고한다.
+    pub fn reconcile_maps(&mut self, delete_orphans: bool, reinstall_missing: bool) -> ReconcileSummary {
+        let mut summary = ReconcileSummary { audit: self.audit_maps(), ..ReconcileSummary::default() };
+        if !delete_orphans && !reinstall_missing {
+            return summary;
+        }
+        
+        let rules = &self.rules;
+        let backends = std::iter::once((None, &mut self.xdp))
+            .chain(self.objects.iter_mut().map(|(path, xdp)| (Some(path.as_str()), xdp)));
+        for (object, xdp) in backends {
+            let object = object.map(|path| format!(" ({})", path)).unwrap_or_default();
+            
+            for divergence in xdp.audit(rules) {
+                match divergence {
+                    Divergence::Orphan { map, key, value } if delete_orphans => {
+                        match xdp.delete_orphan(map, &key, &value) {
+                            Ok(()) => {
+                                info!("Deleted orphaned {} entry {}{}", map.as_str(), hex_key(&key), object);
+                                summary.deleted_orphans += 1;
+                            }
+                            Err(e) => summary.errors.push(
+                                format!("{} entry {}{}: {:#}", map.as_str(), hex_key(&key), object, e)),
+                        }
+                    }
+                    Divergence::Missing { map, label, key } if reinstall_missing => {
+                        let result = match rules.get(&label) {
+                            Some(rule) => xdp.reinstall_missing(map, &key, rule),
+                            None => continue,
+                        };
+                        match result {
+                            Ok(()) => {
+                                info!("Reinstalled missing {} entry of rule {}{}", map.as_str(), label, object);
+                                summary.reinstalled += 1;
+                            }
+                            Err(e) => summary.errors.push(format!("rule {}{}: {:#}", label, object, e)),
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+        
+        summary
+    }
+    
+    /// 리디렉션 대상(redirect_map 항목)과 참조하는 규칙
+    ///
+    /// 기본 오브젝트와 대체 오브젝트의 맵을 모두 읽는다. 규칙은 해당 오브젝트에
+    /// 리디렉션 참조를 기록한 것만 포함한다.
+    pub fn list_redirect_targets(&self) -> Vec<RedirectTarget> {
+        let mut targets = Vec::new();
+        
+        let backends = std::iter::once((None, &self.xdp))
+            .chain(self.objects.iter().map(|(path, xdp)| (Some(path.as_str()), xdp)));
+        for (object, xdp) in backends {
+            for (slot, value) in xdp.redirect_entries() {
+                let rules = self.rules.iter()
+                    .filter(|rule| references_redirect(rule, slot) && xdp.written_for(&rule.label).redirect)
+                    .map(|rule| rule.label.clone())
+                    .collect();
+                targets.push(RedirectTarget {
+                    slot,
+                    ifindex: value.ifindex,
+                    ifname: value.ifname,
+                    rules,
+                    manual: self.manual_redirects.contains_key(&slot),
+                    object: object.map(str::to_string),
+                });
+            }
+        }
+        
+        targets
+    }
+    
+    /// 규칙 없이 리디렉션 대상 등록 (모든 XDP 오브젝트의 redirect_map에 기록)
+    ///
+    /// 등록한 대상은 규칙 하나처럼 참조를 가지므로, 같은 대상을 쓰던 규칙이 모두
+    /// 삭제되어도 `remove_redirect_target`까지 맵에 남는다.
+    pub fn add_redirect_target(&mut self, ifname: &str, ifindex: u32) -> Result<(), DaemonError> {
+        if self.manual_redirects.contains_key(&ifindex) {
+            return Err(DaemonError::DuplicateTarget(ifname.to_string()));
+        }
+        
+        let mut backends: Vec<&mut XdpBackend<'a>> = std::iter::once(&mut self.xdp)
+            .chain(self.objects.values_mut())
+            .collect();
+        for index in 0..backends.len() {
+            if let Err(e) = backends[index].acquire_redirect(ifindex, ifname) {
+                for backend in backends[..index].iter_mut() {
+                    if let Err(undo_err) = backend.release_redirect(ifindex) {
+                        error!("Failed to roll back redirect target {}: {:#}", ifname, undo_err);
+                    }
+                }
+                return Err(DaemonError::Map(e.context(format!("Failed to add redirect target {}", ifname))));
+            }
+        }
+        
+        self.manual_redirects.insert(ifindex, ifname.to_string());
+        info!("Added redirect target {} (ifindex {})", ifname, ifindex);
+        Ok(())
+    }
+    
+    /// 리디렉션 대상 제거 (`interface`는 인터페이스 이름 또는 맵 슬롯 번호)
+    ///
+    /// 규칙이 참조하는 대상은 거부한다. 직접 등록한 대상은 그 참조를 해제하고,
+    /// 참조하는 규칙이 없는데 남아 있는 항목(이전 데몬이 남긴 항목 등)은 바로 삭제한다.
+    pub fn remove_redirect_target(&mut self, interface: &str) -> Result<(), DaemonError> {
+        let slot = self.find_redirect_slot(interface)
+            .ok_or_else(|| DaemonError::TargetNotFound(interface.to_string()))?;
+        
+        let users: Vec<&str> = self.rules.iter()
+            .filter(|rule| references_redirect(rule, slot))
+            .map(|rule| rule.label.as_str())
+            .collect();
+        if !users.is_empty() {
+            return Err(InvalidArgument::new("interface",
+                format!("redirect target {} is used by rules: {}", interface, users.join(", "))).into());
+        }
+        
+        let manual = self.manual_redirects.contains_key(&slot);
+        for xdp in std::iter::once(&mut self.xdp).chain(self.objects.values_mut()) {
+            let result = if manual { xdp.release_redirect(slot) } else { xdp.delete_redirect_entry(slot) };
+            result.with_context(|| format!("Failed to remove redirect target {}", interface))
+                .map_err(DaemonError::Map)?;
+        }
+        
+        self.manual_redirects.remove(&slot);
+        info!("Removed redirect target {} (slot {})", interface, slot);
+        Ok(())
+    }
+    
+    /// 인터페이스 이름 또는 슬롯 번호에 해당하는 redirect_map 슬롯
+    fn find_redirect_slot(&self, interface: &str) -> Option<u32> {
+        let entries: Vec<(u32, String)> = self.manual_redirects.iter()
+            .map(|(slot, ifname)| (*slot, ifname.clone()))
+            .chain(self.list_redirect_targets().into_iter().map(|target| (target.slot, target.ifname)))
+            .collect();
+        
+        match interface.parse::<u32>() {
+            Ok(slot) => entries.iter().find(|(known, _)| *known == slot).map(|(slot, _)| *slot),
+            Err(_) => entries.iter().find(|(_, ifname)| ifname == interface).map(|(slot, _)| *slot),
+        }
+    }
+    
+    /// 리디렉션 대상 인터페이스 재확인
+    ///
+    /// `resolve`는 인터페이스 이름을 현재 ifindex로 변환하며, 인터페이스가 없거나
+    /// 내려간 경우 None을 반환한다. 사라진 대상을 참조하는 규칙은 degraded로
+    /// 표시하고, 다시 나타난 대상은 새 ifindex로 규칙을 갱신한다.
+    /// 상태가 바뀐 규칙의 레이블을 반환한다.
+    pub fn refresh_redirect_targets<F>(&mut self, resolve: F) -> Result<Vec<String>, DaemonError>
+    where
+        F: Fn(&str) -> Option<u32>,
+    {
+        let mut changed = Vec::new();
+        
+        let targets: Vec<(String, String, u32, bool)> = self.rules.iter()
+            .filter(|rule| rule.action == 3)
+            .filter_map(|rule| Some((rule.label.clone(), rule.redirect_ifname.clone()?,
+                rule.redirect_ifindex, rule.degraded)))
+            .collect();
+        
+        for (label, ifname, old_ifindex, was_degraded) in targets {
+            match resolve(&ifname) {
+                None if !was_degraded => {
+                    warn!("Redirect target {} for rule {} is gone", ifname, label);
+                    for xdp in std::iter::once(&mut self.xdp).chain(self.objects.values_mut()) {
+                        if xdp.written_for(&label).redirect {
+                            xdp.release_redirect(old_ifindex).map_err(DaemonError::Map)?;
+                        }
+                    }
+                    self.modify_rule(&label, |rule| rule.degraded = true);
+                }
+                Some(ifindex) if was_degraded || ifindex != old_ifindex => {
+                    for xdp in std::iter::once(&mut self.xdp).chain(self.objects.values_mut()) {
+                        if !xdp.written_for(&label).redirect {
+                            continue;
+                        }
+                        if !was_degraded {
+                            xdp.release_redirect(old_ifindex).map_err(DaemonError::Map)?;
+                        }
+                        xdp.acquire_redirect(ifindex, &ifname).map_err(DaemonError::Map)?;
+                    }
+                    
+                    self.modify_rule(&label, |rule| {
+                        rule.redirect_ifindex = ifindex;
+                        rule.degraded = false;
+                    });
+                    info!("Redirect target {} for rule {} resolved to ifindex {}", ifname, label, ifindex);
+                    
+                    // 커널 규칙의 ifindex 갱신
+                    if let Some(rule) = self.rules.get(&label) {
+                        for xdp in std::iter::once(&self.xdp).chain(self.objects.values()) {
+                            if xdp.written_for(&label).lpm {
+                                xdp.write_rule(rule).map_err(DaemonError::Map)?;
+                            }
+                        }
+                    }
+                }
+                _ => continue,
+            }
+            
+            changed.push(label);
+        }
+        
+        // nftables는 인터페이스 이름으로 리디렉션하므로 degraded 상태만 반영
+        if !changed.is_empty() {
+            if let Some(nftables) = self.nftables.as_mut() {
+                nftables.sync(&self.rules).map_err(DaemonError::Map)?;
+            }
+        }
+        
+        Ok(changed)
+    }
+    
+    /// 호스트 이름 규칙이 쓰는 호스트 이름 (중복 제거)
+    pub fn host_names(&self) -> Vec<String> {
+        let names: std::collections::BTreeSet<&str> = self.rules.iter()
+            .filter_map(|rule| rule.src_host.as_ref())
+            .map(|host| host.name.as_str())
+            .collect();
+        names.into_iter().map(str::to_string).collect()
+    }
+    
+    /// 호스트 이름 규칙의 주소 재해석
+    ///
+    /// `resolve`는 호스트 이름을 A 레코드 주소로 변환한다. 주소 집합이 바뀐 규칙은
+    /// 새로 나타난 주소의 항목을 먼저 기록하고 사라진 주소의 항목을 삭제한다.
+    /// 해석에 실패하거나 주소가 없으면 마지막으로 해석한 항목을 그대로 두고
+    /// 규칙을 degraded로 표시한다. 상태가 바뀐 규칙의 레이블을 반환한다.
+    pub fn refresh_host_endpoints<F>(&mut self, resolve: F) -> Result<Vec<String>, DaemonError>
+    where
+        F: Fn(&str) -> Result<Vec<u32>>,
+    {
+        let mut changed = Vec::new();
+        
+        let hosts: Vec<(String, HostEndpoint)> = self.rules.iter()
+            .filter_map(|rule| Some((rule.label.clone(), rule.src_host.clone()?)))
+            .collect();
+        
+        for (label, host) in hosts {
+            let addrs = resolve(&host.name)
+                .map(dns::normalize_addrs)
+                .and_then(|addrs| if addrs.is_empty() {
+                    Err(anyhow!("{} has no A records", host.name))
+                } else {
+                    Ok(addrs)
+                });
+            
+            match addrs {
+                Err(e) => {
+                    if !host.is_stale() {
+                        warn!("Failed to resolve {} for rule {}; keeping last known addresses {}: {:#}",
+                            host.name, label, dns::format_addrs(&host.addrs), e);
+                        changed.push(label.clone());
+                    }
+                    self.modify_rule(&label, |rule| {
+                        if let Some(host) = rule.src_host.as_mut() {
+                            host.error = Some(format!("{:#}", e));
+                        }
+                    });
+                }
+                Ok(addrs) if dns::diff_addrs(&host.addrs, &addrs).is_empty() => {
+                    if host.is_stale() {
+                        info!("Resolved {} for rule {} again", host.name, label);
+                        self.modify_rule(&label, |rule| {
+                            if let Some(host) = rule.src_host.as_mut() {
+                                host.error = None;
+                            }
+                        });
+                        changed.push(label);
+                    }
+                }
+                Ok(addrs) => {
+                    let diff = dns::diff_addrs(&host.addrs, &addrs);
+                    info!("Host {} for rule {} changed: added [{}], removed [{}]",
+                        host.name, label, dns::format_addrs(&diff.added), dns::format_addrs(&diff.removed));
+                    
+                    let old = match self.rules.get(&label) {
+                        Some(old) => old.clone(),
+                        None => continue,
+                    };
+                    let rule = FilterRule { src_host: Some(HostEndpoint::new(&host.name, addrs)), ..old.clone() };
+                    self.replace_rule(old, rule).map_err(DaemonError::Map)?;
+                    changed.push(label);
+                }
+            }
+        }
+        
+        Ok(changed)
+    }
+    
+    /// 대체 BPF 오브젝트의 맵 등록 (현재 규칙을 모두 설치)
+    ///
+    /// 이미 등록된 오브젝트면 아무것도 하지 않는다.
+    pub fn add_object(&mut self, name: &str, backends: MapBackends<'a>) -> Result<(), DaemonError> {
+        if self.objects.contains_key(name) {
+            return Ok(());
+        }
+        
+        let mut backend = XdpBackend::new(&backends);
+        for (slot, ifname) in &self.manual_redirects {
+            backend.acquire_redirect(*slot, ifname)
+                .with_context(|| format!("Failed to add redirect target {} to BPF object {}", ifname, name))
+                .map_err(DaemonError::Map)?;
+        }
+        for rule in self.rules.iter() {
+            backend.install(rule, &self.rules)
+                .with_context(|| format!("Failed to install rule {} into BPF object {}", rule.label, name))
+                .map_err(DaemonError::Map)?;
+        }
+        
+        self.objects.insert(name.to_string(), backend);
+        Ok(())
+    }
+    
+    /// 규칙을 설치하는 모든 XDP 오브젝트가 처리하는 매치 기능
+    ///
+    /// 규칙은 대체 오브젝트에도 모두 설치되므로 기능을 아는 오브젝트들의 공통 기능이다.
+    /// 기능을 아는 오브젝트가 없으면 (인메모리 맵 등) None.
+    pub fn features(&self) -> Option<XdpFeatures> {
+        std::iter::once(&self.xdp)
+            .chain(self.objects.values())
+            .filter_map(|xdp| xdp.features)
+            .reduce(XdpFeatures::intersect)
+    }
+    
+    /// 규칙 검증에 쓰는 매치 기능
+    ///
+    /// 기능을 아는 오브젝트가 없으면 (인메모리 맵 등) 이 버전의 XDP 프로그램이 처리하는
+    /// 기능으로 본다. 확인을 건너뛰면 적용되지 않는 필드가 그대로 설치되기 때문이다.
+    pub fn rule_features(&self) -> XdpFeatures {
+        self.features().unwrap_or(XdpFeatures::ALL)
+    }
+    
+    /// 추가한 규칙의 이전 형식 키 항목 이전 (실패해도 규칙 추가는 유지)
+    fn migrate_rule_keys(&self, rule: &FilterRule) {
+        for xdp in std::iter::once(&self.xdp).chain(self.objects.values()) {
+            match xdp.migrate_legacy_keys([rule]) {
+                Ok(0) => {},
+                Ok(_) => info!("Migrated legacy filter rule key of {} to network byte order", rule.label),
+                Err(e) => warn!("Failed to migrate legacy filter rule key of {}: {:#}", rule.label, e),
+            }
+        }
+    }
+    
+    /// 대체 BPF 오브젝트 등록 여부
+    pub fn has_object(&self, name: &str) -> bool {
+        self.objects.contains_key(name)
+    }
+    
+    /// 인터페이스 연결
+    ///
+    /// `attach_xdp`로 XDP 프로그램 연결을 시도하고, 실패하면 `fallback`이
+    /// nftables인 경우 현재 규칙을 nftables 테이블로 설치한다.
+    /// `object`는 `add_object`로 등록한 대체 오브젝트 이름이다 (None이면 기본 오브젝트).
+    /// 인터페이스에 사용된 백엔드를 반환한다.
+    pub fn attach_interface<F>(
+        &mut self,
+        interface: &str,
+        mode: &str,
+        object: Option<&str>,
+        fallback: FallbackMode,
+        attach_xdp: F,
+    ) -> Result<BackendKind, DaemonError>
+    where
+        F: FnOnce(&str) -> Result<(), DaemonError>,
+    {
+        if let Some(object) = object {
+            if !self.objects.contains_key(object) {
+                return Err(DaemonError::Bpf(anyhow!("BPF object {} is not loaded", object)));
+            }
+        }
+        
+        // 첫 인터페이스가 연결되면 그동안 적용되지 않던 규칙이 적용되기 시작함
+        let was_attached = self.has_attached_interfaces();
+        let dormant = if !was_attached {
+            self.rules.iter().filter(|rule| rule.is_enforceable()).count()
+        } else {
+            0
+        };
+        
+        let backend = match attach_xdp(interface) {
+            Ok(()) => {
+                // nftables로 대체 중이던 인터페이스라면 체인 제거
+                if let Some(nftables) = self.nftables.as_mut() {
+                    nftables.remove_interface(interface, &self.rules).map_err(DaemonError::Map)?;
+                }
+                
+                self.interfaces.insert(interface.to_string(), InterfaceBinding {
+                    backend: BackendKind::Xdp,
+                    mode: Some(mode.to_string()),
+                    object: object.map(str::to_string),
+                    suspended: false,
+                });
+                BackendKind::Xdp
+            }
+            Err(e) if fallback == FallbackMode::Nftables => {
+                warn!("XDP attach to {} failed, falling back to nftables: {:#}", interface, e);
+                
+                self.nftables.get_or_insert_with(NftablesBackend::new)
+                    .add_interface(interface, &self.rules)
+                    .with_context(|| format!("Failed to install nftables fallback on {}", interface))
+                    .map_err(DaemonError::Map)?;
+                
+                self.interfaces.insert(interface.to_string(), InterfaceBinding {
+                    backend: BackendKind::Nftables,
+                    mode: None,
+                    object: None,
+                    suspended: false,
+                });
+                BackendKind::Nftables
+            }
+            Err(DaemonError::Bpf(e)) => return Err(DaemonError::Bpf(e.context(format!(
+                "Failed to attach XDP program to {} (use --fallback nftables to enforce rules with nftables)",
+                interface)))),
+            Err(e) => return Err(e),
+        };
+        
+        if dormant > 0 {
+            info!("Interface {} attached; {} dormant rule(s) are now effective", interface, dormant);
+        }
+        self.publish_attachment(was_attached);
+        
+        Ok(backend)
+    }
+    
+    /// 인터페이스 분리 (연결에 사용된 백엔드에서 제거)
+    ///
+    /// attach 요청으로 연결되지 않은 인터페이스는 XDP 프로그램 분리를 시도한다.
+    /// 링크가 내려가 이미 분리해 둔 인터페이스는 연결 정보만 제거한다.
+    pub fn detach_interface<F>(&mut self, interface: &str, detach_xdp: F) -> Result<BackendKind, DaemonError>
+    where
+        F: FnOnce(&str) -> Result<(), DaemonError>,
+    {
+        let (backend, suspended) = self.interfaces.get(interface)
+            .map(|binding| (binding.backend, binding.suspended))
+            .unwrap_or((BackendKind::Xdp, false));
+        
+        match (backend, self.nftables.as_mut()) {
+            (BackendKind::Nftables, Some(nftables)) => {
+                nftables.remove_interface(interface, &self.rules).map_err(DaemonError::Map)?;
+            }
+            _ if suspended => {}
+            _ => detach_xdp(interface)?,
+        }
+        
+        let was_attached = self.has_attached_interfaces();
+        self.interfaces.remove(interface);
+        self.publish_attachment(was_attached);
+        Ok(backend)
+    }
+    
+    /// 링크가 내려간 인터페이스 기록
+    ///
+    /// `detach_xdp`가 주어지고 XDP로 연결된 인터페이스이면 프로그램을 분리해 두고
+    /// (`link_up`에서 다시 연결) true를 반환한다. nftables 인터페이스는 분리하지 않는다.
+    pub fn link_down<F>(&mut self, interface: &str, detach_xdp: Option<F>) -> Result<bool, DaemonError>
+    where
+        F: FnOnce(&str) -> Result<(), DaemonError>,
+    {
+        self.links_down.insert(interface.to_string());
+        
+        let binding = match self.interfaces.get_mut(interface) {
+            Some(binding) if binding.backend == BackendKind::Xdp && !binding.suspended => binding,
+            _ => return Ok(false),
+        };
+        let Some(detach_xdp) = detach_xdp else {
+            return Ok(false);
+        };
+        
+        detach_xdp(interface)
+            .map_err(|e| DaemonError::Bpf(anyhow::Error::new(e)
+                .context(format!("Failed to detach XDP program from {} after link down", interface))))?;
+        binding.suspended = true;
+        info!("Link on {} is down; XDP program detached until it comes back", interface);
+        Ok(true)
+    }
+    
+    /// 링크가 다시 올라온 인터페이스 기록
+    ///
+    /// 링크가 내려가 분리해 둔 인터페이스는 `attach_xdp(interface, mode, object)`로 다시
+    /// 연결하고 true를 반환한다. 다시 연결하지 못하면 링크가 내려간 상태로 남겨
+    /// 다음 점검에서 재시도한다.
+    pub fn link_up<F>(&mut self, interface: &str, attach_xdp: F) -> Result<bool, DaemonError>
+    where
+        F: FnOnce(&str, &str, Option<&str>) -> Result<(), DaemonError>,
+    {
+        let reattached = match self.interfaces.get_mut(interface) {
+            Some(binding) if binding.suspended => {
+                let mode = binding.mode.as_deref().unwrap_or("driver");
+                attach_xdp(interface, mode, binding.object.as_deref())
+                    .map_err(|e| DaemonError::Bpf(anyhow::Error::new(e)
+                        .context(format!("Failed to reattach XDP program to {}", interface))))?;
+                binding.suspended = false;
+                info!("Link on {} is up; XDP program reattached", interface);
+                true
+            }
+            _ => false,
+        };
+        
+        self.links_down.remove(interface);
+        Ok(reattached)
+    }
+    
+    /// 링크 상태를 감시할 인터페이스 (연결된 인터페이스와 리디렉션 대상, 이름순)
+    pub fn watched_interfaces(&self) -> Vec<String> {
+        let mut names: BTreeSet<String> = self.interfaces.keys().cloned().collect();
+        names.extend(self.rules.iter().filter_map(|rule| rule.redirect_ifname.clone()));
+        names.into_iter().collect()
+    }
+    
+    /// 링크가 내려간 것으로 기록된 인터페이스
+    pub fn down_interfaces(&self) -> impl Iterator<Item = &String> {
+        self.links_down.iter()
+    }
+    
+    /// 감시 대상에서 빠진 인터페이스의 링크 상태 기록 제거
+    pub fn forget_links(&mut self, keep: &[String]) {
+        self.links_down.retain(|name| keep.contains(name));
+    }
+    
+    /// 링크가 내려가 XDP 프로그램을 분리해 둔 인터페이스
+    pub fn suspended_interfaces(&self) -> impl Iterator<Item = &String> {
+        self.interfaces.iter()
+            .filter(|(_, binding)| binding.suspended)
+            .map(|(name, _)| name)
+    }
+    
+    /// 규칙을 적용할 인터페이스가 하나라도 연결되어 있는지
+    pub fn has_attached_interfaces(&self) -> bool {
+        !self.interfaces.is_empty()
+    }
+    
+    /// attach 요청으로 연결된 인터페이스 목록
+    pub fn list_interfaces(&self) -> Vec<InterfaceInfo> {
+        self.interfaces.iter()
+            .map(|(name, binding)| InterfaceInfo {
+                name: name.clone(),
+                backend: binding.backend.as_str().to_string(),
+                mode: binding.mode.clone(),
+                object: binding.object.clone(),
+                link: Some(if self.links_down.contains(name) { "down" } else { "up" }.to_string()),
+                suspended: binding.suspended,
+            })
+            .collect()
+    }
+    
+    /// nftables로 규칙을 적용 중인 인터페이스
+    pub fn nftables_interfaces(&self) -> Vec<String> {
+        self.nftables.iter()
+            .flat_map(|nftables| nftables.interfaces().cloned())
+            .collect()
+    }
+    
+    /// 캐시된 전체 규칙
+    pub fn rules(&self) -> &RuleCache {
+        &self.rules
+    }
+    
+    /// 테스트 패킷에 적용될 규칙 (기본 오브젝트의 LPM 맵과 와일드카드 맵 기준)
+    pub fn evaluate(&self, packet: &TestPacket) -> Option<&FilterRule> {
+        evaluate::evaluate(self.rules.iter(), &self.xdp.wildcard_slots, packet)
+    }
+    
+    /// WASM 검사로 넘길 프레임의 규칙 문맥 (`evaluate`로 다시 찾은 규칙)
+    pub fn packet_context(&self, frame: &[u8]) -> PacketContext {
+        PacketContext::resolve(frame, |packet| self.evaluate(packet))
+    }
+    
+    /// 레이블로 규칙 존재 여부 확인
+    pub fn has_rule(&self, label: &str) -> bool {
+        self.rules.contains(label)
+    }
+    
+    /// 만료된 규칙 삭제
+    pub fn remove_expired_rules(&mut self, now: u64) -> Result<Vec<String>, DaemonError> {
+        let expired: Vec<String> = self.rules.iter()
+            .filter(|r| r.is_expired(now))
+            .map(|r| r.label.clone())
+            .collect();
+        
+        for label in &expired {
+            self.delete_rule(label)?;
+            info!("Rule expired: {}", label);
+        }
+        
+        Ok(expired)
+    }
+    
+    /// 영구 규칙과 만료 예정 규칙 수, 가장 이른 만료 시각 (만료 시각이 지난 규칙은 `now`)
+    pub fn expiry_summary(&self, now: u64) -> RuleExpirySummary {
+        let mut summary = RuleExpirySummary::default();
+        for rule in self.rules.iter() {
+            match rule.ttl_remaining(now) {
+                None => summary.permanent += 1,
+                Some(ttl) => {
+                    summary.expiring += 1;
+                    let expires_at = now + ttl;
+                    summary.next_expiry = Some(summary.next_expiry.map_or(expires_at, |next| next.min(expires_at)));
+                },
+            }
+        }
+        
+        summary
+    }
+    
+    /// 규칙 스냅샷 (맵 조회 없이 캐시만 복사하므로 잠금을 짧게 유지)
+    pub fn snapshot_rule(&self, label: &str) -> Option<RuleSnapshot<'a>> {
+        let rule = self.rules.get(label)?.clone();
+        let key = self.xdp.prefix_keys(&rule).into_iter().next();
+        let wildcard_slot = self.xdp.wildcard_slots.get(label).copied();
+        
+        Some(RuleSnapshot {
+            rule,
+            key,
+            wildcard_slot,
+            attached: self.has_attached_interfaces(),
+            filter_rules: self.xdp.filter_rules_map,
+            wildcard_rules: self.xdp.wildcard_map,
+        })
+    }
+    
+    /// 규칙 목록 조회
+    ///
+    /// 통계는 오브젝트별 XDP 맵과 nftables 카운터를 레이블 기준으로 합산한다.
+    /// 순서는 `list_order`를 따른다.
+    pub fn list_rules(&self, include_stats: bool) -> Result<Vec<RuleInfo>, DaemonError> {
+        let mut stats = HashMap::new();
+        
+        if include_stats {
+            stats = self.xdp.stats(&self.rules).map_err(DaemonError::Map)?;
+            
+            for object in self.objects.values() {
+                for (label, counter) in object.stats(&self.rules).map_err(DaemonError::Map)? {
+                    let entry: &mut RuleStats = stats.entry(label).or_default();
+                    entry.packets += counter.packets;
+                    entry.bytes += counter.bytes;
+                    entry.last_matched = entry.last_matched.max(counter.last_matched);
+                    entry.redirected_packets += counter.redirected_packets;
+                    entry.redirect_failures += counter.redirect_failures;
+                    entry.aborted_packets += counter.aborted_packets;
+                }
+            }
+            
+            if let Some(nftables) = &self.nftables {
+                match nftables.stats(&self.rules) {
+                    Ok(counters) => {
+                        for (label, counter) in counters {
+                            let entry: &mut RuleStats = stats.entry(label).or_default();
+                            entry.packets += counter.packets;
+                            entry.bytes += counter.bytes;
+                        }
+                    }
+                    Err(e) => throttled_warn!("maps:nftables-counters", logthrottle::HOT_PATH_INTERVAL,
+                        "Failed to read nftables counters: {:#}", e),
+                }
+            }
+        }
+        
+        let attached = self.has_attached_interfaces();
+        let mut rules: Vec<RuleInfo> = self.rules.iter()
+            .map(|rule| rule.to_rule_info(stats.remove(&rule.label).unwrap_or_default(), attached))
+            .collect();
+        rules.sort_by(list_order);
+        
+        Ok(rules)
+    }
+    
+    /// 전체 통계 조회
+    pub fn get_stats(&self) -> Result<(u64, u64), DaemonError> {
+        let global = self.stats_map()
+            .and_then(read_global_stats)
+            .unwrap_or_default();
+
+        Ok((global.packets, global.bytes))
+    }
+    
+    /// 추적 중인 연결 목록 조회
+    pub fn list_connections(
+        &self,
+        limit: u32,
+        filter: &ConnectionFilter,
+    ) -> Result<(Vec<ConnectionInfo>, u64), DaemonError> {
+        let map = self.conntrack_map
+            .ok_or_else(|| DaemonError::Map(anyhow!("Failed to get conntrack_map")))?;
+
+        let now = conntrack::monotonic_ns().map_err(DaemonError::Map)?;
+        ConntrackTable::new(map).list(limit, filter, now).map_err(DaemonError::Map)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nftables::tests::{RecordingNft, LISTING};
+
+    fn redirect_rule(label: &str, redirect_cpu: Option<u32>) -> FilterRule {
+        FilterRule {
+            src_ip: Some((0xC0A80101, 32)),
+            action: 3,
+            redirect_cpu,
+            label: label.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn if_rule(label: &str, src_ip: u32, ifname: &str, ifindex: u32) -> FilterRule {
+        FilterRule {
+            src_ip: Some((src_ip, 32)),
+            redirect_ifindex: ifindex,
+            redirect_ifname: Some(ifname.to_string()),
+            ..redirect_rule(label, None)
+        }
+    }
+
+    #[test]
+    fn test_snapshot_rule_detail() {
+        let filter_rules = MemoryMap::new();
+        let mut manager = MapManager::with_backends(MapBackends {
+            filter_rules: Some(&filter_rules),
+            ..Default::default()
+        });
+
+        let rule = FilterRule { action: 2, expire: 60, creation_time: 1000, ..redirect_rule("web", None) };
+        manager.add_rule(rule).unwrap();
+        assert!(manager.snapshot_rule("missing").is_none());
+
+        let snapshot = manager.snapshot_rule("web").unwrap();
+        let detail = snapshot.into_detail(vec!["eth0".to_string()], Vec::new(), 1030);
+        assert!(detail.installed);
+        assert_eq!(detail.ttl_remaining, Some(30));
+        assert_eq!(detail.lpm_key.as_deref(), Some("20000000c0a80101"));
+        assert_eq!(detail.interfaces, vec!["eth0"]);
+        assert_eq!(detail.info.stats.packets, 0);
+
+        // 커널 맵에서 사라진 규칙은 미설치로 표시
+        filter_rules.delete(&hex_key("20000000c0a80101")).unwrap();
+        let detail = manager.snapshot_rule("web").unwrap().into_detail(Vec::new(), Vec::new(), 2000);
+        assert!(!detail.installed);
+        assert_eq!(detail.ttl_remaining, Some(0));
+    }
+
+    fn hex_key(hex: &str) -> Vec<u8> {
+        (0..hex.len()).step_by(2).map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap()).collect()
+    }
+
+    #[test]
+    fn test_add_rule_rejects_invalid_label() {
+        let filter_rules = MemoryMap::new();
+        let mut manager = MapManager::with_backends(MapBackends {
+            filter_rules: Some(&filter_rules),
+            ..Default::default()
+        });
+
+        let long = FilterRule { action: 2, ..redirect_rule(&"x".repeat(40), None) };
+        assert!(matches!(manager.add_rule(long), Err(DaemonError::Validation(_))));
+
+        let emoji = FilterRule { action: 2, ..redirect_rule("drop-🔥", None) };
+        assert!(matches!(manager.add_rule(emoji), Err(DaemonError::Validation(ref e)) if e.field == "label"));
+
+        assert_eq!(filter_rules.len(), 0);
+        assert!(manager.list_rules(false).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_rule_stats_layout() {
+        let manager = MapManager::with_backends(MapBackends::default());
+        let mut value = manager.xdp.create_filter_rule(&redirect_rule("to-ids", None)).unwrap();
+        assert_eq!(value.len(), RULE_STATS_OFFSET + RULE_STATS_SIZE);
+
+        // 커널이 기록한 리디렉션, 중단 카운터 흉내
+        let stats = FilterStats {
+            packets: 10, bytes: 640, last_matched: 12_500_000_000, redirected_packets: 8, redirect_failures: 2, aborted_packets: 2,
+        };
+        value[RULE_STATS_OFFSET..].copy_from_slice(&stats.to_bytes());
+        let parsed = parse_rule_stats(&value);
+        assert_eq!((parsed.packets, parsed.redirected_packets, parsed.redirect_failures, parsed.aborted_packets), (10, 8, 2, 2));
+        assert_eq!(parsed.last_matched, 12_500_000_000);
+
+        // 이전 레이아웃으로 고정된 맵의 값은 리디렉션 카운터가 0
+        let parsed = parse_rule_stats(&value[..RULE_STATS_OFFSET + FilterStats::LEGACY_SIZE]);
+        assert_eq!((parsed.packets, parsed.bytes, parsed.redirected_packets, parsed.redirect_failures), (10, 640, 0, 0));
+        assert_eq!(parse_rule_stats(&value[..RULE_STATS_OFFSET + 40]).aborted_packets, 0);
+        assert_eq!(parse_rule_stats(&value[..RULE_STATS_OFFSET]).packets, 0);
+    }
+
+    #[test]
+    fn test_rule_error_variants() {
+        let filter_rules = MemoryMap::new();
+        let mut manager = MapManager::with_backends(MapBackends {
+            filter_rules: Some(&filter_rules),
+            ..Default::default()
+        });
+        let web = FilterRule { action: 2, ..redirect_rule("web", None) };
+        manager.add_rule(web.clone()).unwrap();
+
+        // 같은 레이블은 기존 규칙을 덮어쓰지 않음
+        let err = manager.add_rule(FilterRule { priority: 9, ..web.clone() }).unwrap_err();
+        assert!(matches!(err, DaemonError::DuplicateLabel(ref label) if label == "web"));
+        assert_eq!(manager.rules().get("web").unwrap().priority, 0);
+
+        let err = manager.delete_rule("missing").unwrap_err();
+        assert!(matches!(err, DaemonError::RuleNotFound(ref label) if label == "missing"));
+        assert!(matches!(manager.update_rule(FilterRule { label: "bad label".to_string(), ..web }),
+            Err(DaemonError::Validation(_))));
+        assert!(matches!(manager.list_connections(10, &ConnectionFilter::default()), Err(DaemonError::Map(_))));
+    }
+
+    #[test]
+    fn test_prefix_conflict() {
+        let filter_rules = MemoryMap::new();
+        let mut manager = MapManager::with_backends(MapBackends {
+            filter_rules: Some(&filter_rules),
+            ..Default::default()
+        });
+        let rule = |label: &str, src_ip| FilterRule { label: label.to_string(), src_ip, ..redirect_rule("", None) };
+        manager.add_rule(rule("team-a/web", Some((0xC0A80101, 32)))).unwrap();
+
+        // 다른 네임스페이스는 같은 LPM 키를 덮어쓸 수 없음
+        let err = manager.add_rule(rule("team-b/web", Some((0xC0A80101, 32)))).unwrap_err();
+        assert!(matches!(err, DaemonError::PrefixConflict(ref prefix) if prefix == "192.168.1.1/32"));
+        assert!(!manager.rules().contains("team-b/web"));
+        assert_eq!(filter_rules.len(), 1);
+
+        // 호스트 비트만 다른 키와 프리픽스 집합도 같은 키
+        manager.add_rule(rule("team-a/lan", Some((0xC0A80200, 24)))).unwrap();
+        assert!(matches!(manager.add_rule(rule("team-b/lan", Some((0xC0A80263, 24)))),
+            Err(DaemonError::PrefixConflict(_))));
+        let set = PrefixSet::new("blocklist", vec![(0x0A000000, 8), (0xC0A80200, 24)], "test", "1");
+        assert!(matches!(manager.add_rule(FilterRule { src_set: Some(set), ..rule("team-b/set", None) }),
+            Err(DaemonError::PrefixConflict(ref prefix)) if prefix == "192.168.2.0/24"));
+
+        // 같은 네임스페이스의 같은 프리픽스도 덮어쓸 수 없음
+        assert!(matches!(manager.add_rule(rule("team-a/web2", Some((0xC0A80101, 32)))),
+            Err(DaemonError::PrefixConflict(_))));
+        assert_eq!(manager.rules().get("team-a/web").unwrap().label, "team-a/web");
+        assert_eq!(filter_rules.len(), 2);
+
+        // 겹치기만 하는 다른 프리픽스는 허용
+        manager.add_rule(rule("team-b/wide", Some((0xC0A80000, 16)))).unwrap();
+
+        // 갱신으로 다른 네임스페이스의 키로 옮길 수도 없음
+        assert!(matches!(manager.update_rule(rule("team-b/wide", Some((0xC0A80200, 24)))),
+            Err(DaemonError::PrefixConflict(_))));
+        assert_eq!(manager.rules().get("team-b/wide").unwrap().src_ip, Some((0xC0A80000, 16)));
+    }
+
+    #[test]
+    fn test_redirect_refcount_add_add_delete_delete() {
+        let filter_rules = MemoryMap::new();
+        let redirect = MemoryMap::new();
+        let mut manager = MapManager::with_backends(MapBackends {
+            filter_rules: Some(&filter_rules),
+            redirect: Some(&redirect),
+            ..Default::default()
+        });
+
+        manager.add_rule(if_rule("a", 0xC0A80101, "veth0", 7)).unwrap();
+        manager.add_rule(if_rule("b", 0xC0A80102, "veth0", 7)).unwrap();
+        assert_eq!(redirect.len(), 1);
+
+        let value = redirect.lookup(&7u32.to_le_bytes()).unwrap().unwrap();
+        assert_eq!(&value[4..9], b"veth0");
+
+        manager.delete_rule("a").unwrap();
+        assert_eq!(redirect.len(), 1);
+        manager.delete_rule("b").unwrap();
+        assert_eq!(redirect.len(), 0);
+        assert_eq!(filter_rules.len(), 0);
+    }
+
+    #[test]
+    fn test_manage_redirect_targets() {
+        let filter_rules = MemoryMap::new();
+        let redirect = MemoryMap::new();
+        let mut manager = MapManager::with_backends(MapBackends {
+            filter_rules: Some(&filter_rules),
+            redirect: Some(&redirect),
+            ..Default::default()
+        });
+
+        manager.add_rule(if_rule("a", 0xC0A80101, "veth0", 7)).unwrap();
+        manager.add_rule(if_rule("b", 0xC0A80102, "veth0", 7)).unwrap();
+        manager.add_redirect_target("veth1", 9).unwrap();
+        assert!(matches!(manager.add_redirect_target("veth1", 9), Err(DaemonError::DuplicateTarget(_))));
+
+        let targets = manager.list_redirect_targets();
+        assert_eq!(targets.len(), 2);
+        assert_eq!((targets[0].slot, targets[0].ifindex, targets[0].ifname.as_str()), (7, 7, "veth0"));
+        assert_eq!(targets[0].rules, vec!["a".to_string(), "b".to_string()]);
+        assert!(!targets[0].manual);
+        assert_eq!((targets[1].slot, targets[1].ifname.as_str()), (9, "veth1"));
+        assert!(targets[1].rules.is_empty() && targets[1].manual);
+        assert!(targets.iter().all(|target| target.object.is_none()));
+
+        // 규칙이 참조하는 대상은 제거 거부
+        let err = manager.remove_redirect_target("veth0").unwrap_err();
+        assert!(matches!(err, DaemonError::Validation(ref e) if e.message.contains("a, b")));
+        assert!(matches!(manager.remove_redirect_target("eth9"), Err(DaemonError::TargetNotFound(_))));
+
+        // 직접 등록한 대상은 규칙이 같은 슬롯을 쓰다 삭제되어도 남음
+        manager.add_rule(if_rule("c", 0xC0A80103, "veth1", 9)).unwrap();
+        manager.delete_rule("c").unwrap();
+        assert!(redirect.lookup(&9u32.to_le_bytes()).unwrap().is_some());
+        manager.remove_redirect_target("9").unwrap();
+        assert!(redirect.lookup(&9u32.to_le_bytes()).unwrap().is_none());
+
+        // 참조하는 규칙이 없는 남은 항목은 바로 삭제
+        manager.delete_rule("a").unwrap();
+        manager.delete_rule("b").unwrap();
+        let mut stale = 5u32.to_le_bytes().to_vec();
+        stale.extend_from_slice(b"old0\0\0\0\0\0\0\0\0\0\0\0\0");
+        redirect.update(&5u32.to_le_bytes(), &stale).unwrap();
+        assert_eq!(manager.list_redirect_targets()[0].ifname, "old0");
+        manager.remove_redirect_target("old0").unwrap();
+        assert_eq!(redirect.len(), 0);
+    }
+
+    #[test]
+    fn test_redirect_targets_degrade_and_recover() {
+        let filter_rules = MemoryMap::new();
+        let redirect = MemoryMap::new();
+        let mut manager = MapManager::with_backends(MapBackends {
+            filter_rules: Some(&filter_rules),
+            redirect: Some(&redirect),
+            ..Default::default()
+        });
+
+        manager.add_rule(if_rule("a", 0xC0A80101, "veth0", 7)).unwrap();
+        manager.add_rule(if_rule("b", 0xC0A80102, "veth0", 7)).unwrap();
+
+        // 인터페이스 사라짐
+        let changed = manager.refresh_redirect_targets(|_| None).unwrap();
+        assert_eq!(changed, vec!["a".to_string(), "b".to_string()]);
+        assert_eq!(redirect.len(), 0);
+        assert!(manager.list_rules(false).unwrap().iter().all(|r| r.degraded));
+
+        // 변화 없으면 아무것도 바뀌지 않음
+        assert!(manager.refresh_redirect_targets(|_| None).unwrap().is_empty());
+
+        // 새 ifindex로 다시 나타남
+        let changed = manager.refresh_redirect_targets(|_| Some(9)).unwrap();
+        assert_eq!(changed.len(), 2);
+        assert_eq!(redirect.len(), 1);
+        assert!(redirect.lookup(&9u32.to_le_bytes()).unwrap().is_some());
+        assert!(manager.list_rules(false).unwrap().iter().all(|r| !r.degraded));
+
+        // degraded 상태에서 삭제해도 참조 수가 어긋나지 않음
+        manager.refresh_redirect_targets(|_| None).unwrap();
+        manager.delete_rule("a").unwrap();
+        manager.refresh_redirect_targets(|_| Some(9)).unwrap();
+        assert_eq!(redirect.len(), 1);
+        manager.delete_rule("b").unwrap();
+        assert_eq!(redirect.len(), 0);
+    }
+
+    #[test]
+    fn test_cpu_redirect_add_delete() {
+        let filter_rules = MemoryMap::new();
+        let cpu = MemoryMap::new();
+        let mut manager = MapManager::with_backends(MapBackends {
+            filter_rules: Some(&filter_rules),
+            cpu: Some(&cpu),
+            ..Default::default()
+        });
+
+        manager.add_rule(redirect_rule("cpu-a", Some(2))).unwrap();
+        let mut second = redirect_rule("cpu-b", Some(2));
+        second.src_ip = Some((0xC0A80102, 32));
+        manager.add_rule(second).unwrap();
+        assert_eq!(cpu.len(), 1);
+        assert_eq!(cpu.lookup(&2u32.to_le_bytes()).unwrap().unwrap()[..4], CPUMAP_QUEUE_SIZE.to_le_bytes());
+
+        let info = manager.list_rules(false).unwrap();
+        assert_eq!(info[0].redirect_cpu, Some(2));
+        assert_eq!(info[0].redirect_if, None);
+
+        // 다른 규칙이 같은 CPU를 참조하는 동안 항목 유지
+        manager.delete_rule("cpu-a").unwrap();
+        assert_eq!(cpu.len(), 1);
+        manager.delete_rule("cpu-b").unwrap();
+        assert_eq!(cpu.len(), 0);
+    }
+
+    #[test]
+    fn test_add_rule_rolls_back_failed_step() {
+        let filter_rules = MemoryMap::new();
+        let redirect = MemoryMap::new();
+        let cpu = MemoryMap::new();
+        let mut manager = MapManager::with_backends(MapBackends {
+            filter_rules: Some(&filter_rules),
+            redirect: Some(&redirect),
+            cpu: Some(&cpu),
+            ..Default::default()
+        });
+
+        // 각 단계가 실패하면 앞서 기록한 항목을 지우고 규칙을 캐시하지 않음
+        let steps: [(&MemoryMap, FilterRule); 4] = [
+            (&redirect, if_rule("a", 0xC0A80101, "veth0", 7)),
+            (&filter_rules, if_rule("a", 0xC0A80101, "veth0", 7)),
+            (&cpu, redirect_rule("a", Some(1))),
+            (&filter_rules, redirect_rule("a", Some(1))),
+        ];
+        for (map, rule) in steps {
+            map.fail_updates(true);
+            let err = manager.add_rule(rule).unwrap_err();
+            map.fail_updates(false);
+
+            assert!(matches!(err, DaemonError::Map(_)));
+            assert!(!err.is_partial());
+            assert!(!manager.has_rule("a"));
+            assert_eq!((filter_rules.len(), redirect.len(), cpu.len()), (0, 0, 0));
+        }
+
+        // 실패한 시도가 참조 수를 남기지 않음
+        manager.add_rule(if_rule("a", 0xC0A80101, "veth0", 7)).unwrap();
+        manager.delete_rule("a").unwrap();
+        assert_eq!(redirect.len(), 0);
+    }
+
+    #[test]
+    fn test_partial_install_and_repair() {
+        let filter_rules = MemoryMap::new();
+        let redirect = MemoryMap::new();
+        let mut manager = MapManager::with_backends(MapBackends {
+            filter_rules: Some(&filter_rules),
+            redirect: Some(&redirect),
+            ..Default::default()
+        });
+        let state = |manager: &MapManager| manager.list_rules(false).unwrap()[0].install_state.as_str();
+
+        // LPM 기록 실패 후 redirect_map 항목도 지우지 못함
+        filter_rules.fail_updates(true);
+        redirect.fail_deletes(true);
+        let err = manager.add_rule(if_rule("a", 0xC0A80101, "veth0", 7)).unwrap_err();
+        assert!(err.is_partial());
+        assert!(manager.has_rule("a"));
+        assert_eq!(state(&manager), "partial");
+        assert_eq!(redirect.len(), 1);
+
+        // 여전히 실패하면 partial 유지
+        let (repaired, errors) = manager.repair_rules();
+        assert!(repaired.is_empty());
+        assert_eq!(errors.len(), 1);
+        assert_eq!(state(&manager), "partial");
+
+        // 되돌리기에 성공하면 failed
+        redirect.fail_deletes(false);
+        let (_, errors) = manager.repair_rules();
+        assert_eq!(errors[0].0, "a");
+        assert_eq!(state(&manager), "failed");
+        assert_eq!(redirect.len(), 0);
+
+        // 맵이 복구되면 설치
+        filter_rules.fail_updates(false);
+        let (repaired, errors) = manager.repair_rules();
+        assert_eq!(repaired, vec!["a".to_string()]);
+        assert!(errors.is_empty());
+        assert_eq!(state(&manager), "installed");
+        assert_eq!((filter_rules.len(), redirect.len()), (1, 1));
+        assert!(manager.repair_rules().0.is_empty());
+
+        manager.delete_rule("a").unwrap();
+        assert_eq!((filter_rules.len(), redirect.len()), (0, 0));
+    }
+
+    #[test]
+    fn test_list_order() {
+        let filter_rules = MemoryMap::new();
+        let mut manager = MapManager::with_backends(MapBackends {
+            filter_rules: Some(&filter_rules),
+            ..Default::default()
+        });
+        let labels = |manager: &MapManager| -> Vec<String> {
+            manager.list_rules(false).unwrap().into_iter().map(|r| r.label).collect()
+        };
+
+        for (i, label) in ["e", "a", "d", "b", "c"].iter().enumerate() {
+            let rule = FilterRule { action: 2, src_ip: Some((0x0A000001 + i as u32, 32)), ..redirect_rule(label, None) };
+            manager.add_rule(rule).unwrap();
+        }
+
+        // 우선순위와 생성 시각이 같으면 추가한 순서, 갱신한 규칙과 삭제에 실패해
+        // 복원된 규칙은 자리를 유지
+        let updated = FilterRule { description: Some("edited".to_string()), ..manager.rules().get("d").unwrap().clone() };
+        manager.update_rule(updated).unwrap();
+        filter_rules.fail_deletes(true);
+        assert!(manager.delete_rule("a").is_err());
+        filter_rules.fail_deletes(false);
+        assert_eq!(labels(&manager), ["e", "a", "d", "b", "c"]);
+
+        manager.delete_rule("e").unwrap();
+        manager.add_rule(FilterRule { action: 2, ..redirect_rule("e", None) }).unwrap();
+        assert_eq!(labels(&manager), ["a", "d", "b", "c", "e"]);
+
+        // 우선순위가 높은 규칙이 먼저, 같으면 먼저 만든 규칙이 먼저
+        let updated = FilterRule { priority: 99, ..manager.rules().get("c").unwrap().clone() };
+        manager.update_rule(updated).unwrap();
+        manager.add_rule(FilterRule { action: 2, creation_time: 500, priority: 99, src_ip: Some((0x0A000010, 32)), ..redirect_rule("old", None) }).unwrap();
+        assert_eq!(labels(&manager), ["c", "old", "a", "d", "b", "e"]);
+    }
+
+    #[test]
+    fn test_list_order_comparator() {
+        let info = |priority, creation_time| RuleInfo {
+            priority,
+            creation_time,
+            ..redirect_rule("r", None).to_rule_info(RuleStats::default(), false)
+        };
+
+        assert_eq!(list_order(&info(10, 100), &info(5, 0)), Ordering::Less);
+        assert_eq!(list_order(&info(5, 0), &info(10, 100)), Ordering::Greater);
+        assert_eq!(list_order(&info(10, 100), &info(10, 200)), Ordering::Less);
+        assert_eq!(list_order(&info(10, 100), &info(10, 100)), Ordering::Equal);
+    }
+
+    #[test]
+    fn test_expiring_rules_and_summary() {
+        let filter_rules = MemoryMap::new();
+        let mut manager = MapManager::with_backends(MapBackends {
+            filter_rules: Some(&filter_rules),
+            ..Default::default()
+        });
+
+        let now = 10_000;
+        for (i, (label, creation_time, expire)) in [
+            ("permanent", now - 500, 0),
+            ("in-10m", now - 100, 700),
+            ("in-1m", now - 60, 120),
+            ("overdue", now - 600, 300),
+            ("in-1h", now, 3600),
+        ].into_iter().enumerate() {
+            let rule = FilterRule {
+                action: 2,
+                src_ip: Some((0x0A000001 + i as u32, 32)),
+                creation_time,
+                expire,
+                ..redirect_rule(label, None)
+            };
+            manager.add_rule(rule).unwrap();
+        }
+
+        // 남은 수명 순으로, 이미 만료됐지만 아직 삭제되지 않은 규칙 포함
+        let rules = manager.list_rules(false).unwrap();
+        let expiring: Vec<String> = expiring_within(rules.clone(), 600, now).into_iter().map(|r| r.label).collect();
+        assert_eq!(expiring, ["overdue", "in-1m", "in-10m"]);
+        assert!(expiring_within(rules, 0, now).iter().all(|r| r.label == "overdue"));
+
+        assert_eq!(manager.expiry_summary(now), RuleExpirySummary {
+            permanent: 1,
+            expiring: 4,
+            next_expiry: Some(now),
+        });
+
+        // 만료 처리도 같은 기준 사용
+        assert_eq!(manager.remove_expired_rules(now).unwrap(), ["overdue"]);
+        assert_eq!(manager.expiry_summary(now).next_expiry, Some(now + 60));
+    }
+
+    /// 규칙 10만 개에서 레이블 조회·삭제 시간을 선형 탐색과 비교
+    ///
+    /// `cargo test --release -- --ignored test_label_index_scaling --nocapture`
+    #[test]
+    #[ignore]  // 실제 실행시 --ignored 플래그로 실행
+    fn test_label_index_scaling() {
+        const RULES: u32 = 100_000;
+        const OPS: u32 = 2_000;
+
+        let rules: Vec<FilterRule> = (0..RULES)
+            .map(|i| FilterRule { action: 2, src_ip: Some((0x0A000000 + i, 32)), ..redirect_rule(&format!("r{}", i), None) })
+            .collect();
+        // 캐시 끝쪽 규칙부터 조회·삭제 (선형 탐색의 최악에 가까운 경우)
+        let targets: Vec<String> = (0..OPS).map(|i| format!("r{}", RULES - 1 - i * (RULES / OPS))).collect();
+
+        let filter_rules = MemoryMap::new();
+        let mut manager = MapManager::with_backends(MapBackends {
+            filter_rules: Some(&filter_rules),
+            ..Default::default()
+        });
+        let started = std::time::Instant::now();
+        for rule in rules.iter().cloned() {
+            manager.add_rule(rule).unwrap();
+        }
+        let add_time = started.elapsed();
+
+        let started = std::time::Instant::now();
+        for label in &targets {
+            assert!(manager.snapshot_rule(label).is_some());
+            manager.delete_rule(label).unwrap();
+        }
+        let indexed = started.elapsed();
+
+        // 이전 Vec 캐시의 탐색과 삭제
+        let mut linear = rules.clone();
+        let started = std::time::Instant::now();
+        for label in &targets {
+            assert!(linear.iter().find(|r| &r.label == label).is_some());
+            let index = linear.iter().position(|r| &r.label == label).unwrap();
+            linear.remove(index);
+        }
+        let scanned = started.elapsed();
+
+        println!("{} rules: add {:?}, {} lookups+deletes indexed {:?}, linear scan {:?}",
+            RULES, add_time, OPS, indexed, scanned);
+        assert_eq!(manager.rules().iter().count(), linear.len());
+        assert!(manager.rules().iter().map(|r| &r.label).eq(linear.iter().map(|r| &r.label)));
+        assert!(indexed < scanned);
+    }
+
+    #[test]
+    fn test_update_rule_same_key_keeps_stats() {
+        let filter_rules = MemoryMap::new();
+        let mut manager = MapManager::with_backends(MapBackends {
+            filter_rules: Some(&filter_rules),
+            ..Default::default()
+        });
+
+        let rule = FilterRule {
+            action: 2,
+            creation_time: 1000,
+            created_by: Some("alice".to_string()),
+            ..redirect_rule("web", None)
+        };
+        manager.add_rule(rule.clone()).unwrap();
+
+        // 커널이 기록한 통계 흉내
+        let key = manager.xdp.create_prefix_key(0xC0A80101, 32);
+        let mut value = filter_rules.lookup(&key).unwrap().unwrap();
+        let stats = value.len() - RULE_STATS_SIZE;
+        value[stats..stats + 8].copy_from_slice(&42u64.to_le_bytes());
+        filter_rules.update(&key, &value).unwrap();
+
+        let updated = FilterRule { priority: 50, creation_time: 2000, created_by: Some("bob".to_string()), ..rule };
+        manager.update_rule(updated).unwrap();
+        let err = manager.update_rule(redirect_rule("missing", None)).unwrap_err();
+        assert!(matches!(err, DaemonError::RuleNotFound(ref label) if label == "missing"));
+
+        let value = filter_rules.lookup(&key).unwrap().unwrap();
+        assert_eq!(value[..4], 50u32.to_le_bytes());
+        assert_eq!(parse_rule_stats(&value).packets, 42);
+        assert_eq!(filter_rules.len(), 1);
+
+        let cached = manager.rules().get("web").unwrap();
+        assert_eq!(cached.priority, 50);
+        assert_eq!(cached.creation_time, 1000);
+        assert_eq!(cached.created_by.as_deref(), Some("alice"));
+    }
+
+    #[test]
+    fn test_host_rule_refresh_diffs_addresses() {
+        let filter_rules = MemoryMap::new();
+        let mut manager = MapManager::with_backends(MapBackends {
+            filter_rules: Some(&filter_rules),
+            ..Default::default()
+        });
+        let (a, b, c) = (0xC0000201, 0xC0000202, 0xC0000203);
+
+        let rule = FilterRule {
+            src_ip: None,
+            action: 2,
+            src_host: Some(HostEndpoint::new("api.example.com", vec![b, a])),
+            ..redirect_rule("api", None)
+        };
+        manager.add_rule(rule).unwrap();
+        assert_eq!(filter_rules.len(), 2);
+        assert_eq!(manager.host_names(), ["api.example.com"]);
+
+        // 커널이 주소별 항목에 기록한 통계 흉내 (규칙 통계는 합산)
+        for (addr, packets) in [(a, 5u64), (b, 42)] {
+            let key = manager.xdp.create_prefix_key(addr, 32);
+            let mut value = filter_rules.lookup(&key).unwrap().unwrap();
+            let stats = value.len() - RULE_STATS_SIZE;
+            value[stats..stats + 8].copy_from_slice(&packets.to_le_bytes());
+            filter_rules.update(&key, &value).unwrap();
+        }
+        let info = &manager.list_rules(true).unwrap()[0];
+        assert_eq!(info.stats.packets, 47);
+        assert_eq!(info.map, RuleMap::Lpm);
+        assert_eq!(info.src_host.as_ref().unwrap().addrs, ["192.0.2.1", "192.0.2.2"]);
+
+        // 주소가 바뀌면 사라진 주소만 삭제하고 남은 주소의 통계는 유지
+        assert_eq!(manager.refresh_host_endpoints(|_| Ok(vec![c, b])).unwrap(), ["api"]);
+        assert_eq!(filter_rules.len(), 2);
+        assert!(filter_rules.lookup(&manager.xdp.create_prefix_key(a, 32)).unwrap().is_none());
+        assert!(filter_rules.lookup(&manager.xdp.create_prefix_key(c, 32)).unwrap().is_some());
+        assert_eq!(manager.list_rules(true).unwrap()[0].stats.packets, 42);
+        assert!(manager.refresh_host_endpoints(|_| Ok(vec![b, c])).unwrap().is_empty());
+
+        // 해석에 실패하거나 레코드가 없으면 마지막 항목을 유지하고 degraded로 표시
+        assert_eq!(manager.refresh_host_endpoints(|_| Err(anyhow!("SERVFAIL"))).unwrap(), ["api"]);
+        assert!(manager.refresh_host_endpoints(|_| Ok(vec![])).unwrap().is_empty());
+        assert_eq!(filter_rules.len(), 2);
+        let info = &manager.list_rules(false).unwrap()[0];
+        assert!(info.degraded);
+        assert!(info.src_host.as_ref().unwrap().error.as_deref().unwrap().contains("no A records"));
+
+        // 다시 해석되면 복구
+        assert_eq!(manager.refresh_host_endpoints(|_| Ok(vec![b, c])).unwrap(), ["api"]);
+        assert!(!manager.list_rules(false).unwrap()[0].degraded);
+
+        manager.delete_rule("api").unwrap();
+        assert_eq!(filter_rules.len(), 0);
+    }
+
+    #[test]
+    fn test_quota_exceeded_swaps_kernel_action() {
+        let filter_rules = MemoryMap::new();
+        let mut manager = MapManager::with_backends(MapBackends {
+            filter_rules: Some(&filter_rules),
+            ..Default::default()
+        });
+
+        let quota = RuleQuota { bytes: Some(1000), packets: None, action: 2 };
+        manager.add_rule(FilterRule { action: 1, quota: Some(quota), ..redirect_rule("web", None) }).unwrap();
+        manager.add_rule(FilterRule { action: 1, src_ip: Some((0xC0A80102, 32)), ..redirect_rule("plain", None) }).unwrap();
+
+        let key = manager.xdp.create_prefix_key(0xC0A80101, 32);
+        let mut value = filter_rules.lookup(&key).unwrap().unwrap();
+        assert_eq!(value[4], 1);
+        let stats = value.len() - RULE_STATS_SIZE;
+        value[stats..stats + 8].copy_from_slice(&42u64.to_le_bytes());
+        filter_rules.update(&key, &value).unwrap();
+
+        // 초과하면 할당량 액션, 통계는 유지
+        assert!(manager.set_quota_exceeded("web", true).unwrap());
+        let value = filter_rules.lookup(&key).unwrap().unwrap();
+        assert_eq!(value[4], 2);
+        assert_eq!(parse_rule_stats(&value).packets, 42);
+
+        let rules = manager.list_rules(false).unwrap();
+        let info = |label: &str| rules.iter().find(|rule| rule.label == label).unwrap();
+        assert_eq!((info("web").action.as_str(), info("web").quota_exceeded), ("pass", true));
+        assert_eq!(info("web").quota.as_ref().unwrap().action, "drop");
+        assert!(!info("plain").quota_exceeded && info("plain").quota.is_none());
+
+        // 정의를 바꿔도 기간이 끝날 때까지 초과 상태 유지
+        manager.update_rule(FilterRule { action: 1, priority: 5, quota: Some(quota), ..redirect_rule("web", None) }).unwrap();
+        assert_eq!(filter_rules.lookup(&key).unwrap().unwrap()[4], 2);
+
+        assert!(manager.set_quota_exceeded("web", false).unwrap());
+        assert_eq!(filter_rules.lookup(&key).unwrap().unwrap()[4], 1);
+
+        // 할당량이 없는 규칙이나 없는 규칙은 무시
+        assert!(!manager.set_quota_exceeded("plain", true).unwrap());
+        assert!(!manager.set_quota_exceeded("missing", true).unwrap());
+    }
+
+    #[test]
+    fn test_update_rule_moves_key_and_redirect() {
+        let filter_rules = MemoryMap::new();
+        let redirect = MemoryMap::new();
+        let cpu = MemoryMap::new();
+        let mut manager = MapManager::with_backends(MapBackends {
+            filter_rules: Some(&filter_rules),
+            redirect: Some(&redirect),
+            cpu: Some(&cpu),
+            ..Default::default()
+        });
+
+        manager.add_rule(if_rule("a", 0xC0A80101, "veth0", 7)).unwrap();
+
+        // 같은 인터페이스로 소스만 변경: 리디렉션 항목 유지
+        manager.update_rule(if_rule("a", 0xC0A80102, "veth0", 7)).unwrap();
+        assert_eq!(filter_rules.len(), 1);
+        assert!(filter_rules.lookup(&manager.xdp.create_prefix_key(0xC0A80102, 32)).unwrap().is_some());
+        assert_eq!(redirect.len(), 1);
+
+        // 인터페이스 리디렉션에서 CPU 리디렉션으로 변경
+        let to_cpu = FilterRule { src_ip: Some((0xC0A80102, 32)), ..redirect_rule("a", Some(1)) };
+        manager.update_rule(to_cpu).unwrap();
+        assert_eq!(redirect.len(), 0);
+        assert_eq!(cpu.len(), 1);
+
+        // 리디렉션 해제
+        let to_drop = FilterRule { action: 2, src_ip: Some((0xC0A80102, 32)), ..redirect_rule("a", None) };
+        manager.update_rule(to_drop).unwrap();
+        assert_eq!(cpu.len(), 0);
+        assert_eq!(filter_rules.len(), 1);
+    }
+
+    fn wildcard_rule(label: &str, dst_port: u16, priority: u32) -> FilterRule {
+        FilterRule {
+            src_ip: None,
+            protocol: 6,
+            dst_port_min: dst_port,
+            dst_port_max: dst_port,
+            action: 2,
+            priority,
+            ..redirect_rule(label, None)
+        }
+    }
+
+    #[test]
+    fn test_wildcard_rules_use_slots() {
+        let filter_rules = MemoryMap::new();
+        let wildcard_rules = MemoryMap::new();
+        let mut manager = MapManager::with_backends(MapBackends {
+            filter_rules: Some(&filter_rules),
+            wildcard: Some(&wildcard_rules),
+            ..Default::default()
+        });
+
+        manager.add_rule(wildcard_rule("telnet", 23, 10)).unwrap();
+        manager.add_rule(wildcard_rule("ssh", 22, 10)).unwrap();
+        assert_eq!(filter_rules.len(), 0);
+        assert_eq!(wildcard_rules.lookup(&1u32.to_le_bytes()).unwrap().unwrap()[..4], 10u32.to_le_bytes());
+
+        let listed = manager.list_rules(false).unwrap();
+        assert_eq!(listed[0].map, RuleMap::Wildcard);
+        let detail = manager.snapshot_rule("ssh").unwrap().into_detail(Vec::new(), Vec::new(), 0);
+        assert!(detail.installed);
+        assert_eq!(detail.wildcard_slot, Some(1));
+        assert_eq!(detail.lpm_key, None);
+
+        // 삭제한 슬롯은 0으로 비우고 다음 규칙이 다시 사용
+        manager.delete_rule("telnet").unwrap();
+        let cleared = wildcard_rules.lookup(&0u32.to_le_bytes()).unwrap().unwrap();
+        assert!(cleared.iter().all(|b| *b == 0));
+        manager.add_rule(wildcard_rule("smtp", 25, 5)).unwrap();
+        assert_eq!(manager.snapshot_rule("smtp").unwrap().wildcard_slot, Some(0));
+
+        // 평가기는 프리픽스 규칙을 먼저, 그다음 와일드카드 규칙을 확인
+        manager.add_rule(FilterRule { action: 1, ..if_rule("lab", 0x0A000001, "veth0", 0) }).unwrap();
+        let packet = |src_ip, dst_port| TestPacket { src_ip, protocol: 6, src_port: 40000, dst_port, tcp_flags: 0 };
+        assert_eq!(manager.evaluate(&packet(0x0A000001, 22)).map(|rule| rule.label.as_str()), Some("lab"));
+        assert_eq!(manager.evaluate(&packet(0x0A000002, 22)).map(|rule| rule.label.as_str()), Some("ssh"));
+        assert!(manager.evaluate(&packet(0x0A000002, 80)).is_none());
+
+        // WASM 검사 문맥도 같은 규칙
+        let frame = crate::packet::UdpFrame::new(0x0A000001, 0x0A000002, 40000, 22).build();
+        let context = manager.packet_context(&frame);
+        assert_eq!((context.label.as_str(), context.action), ("lab", 1));
+        let frame = crate::packet::UdpFrame::new(0x0A000002, 0x0A000001, 40000, 22).build();
+        assert_eq!(manager.packet_context(&frame), PacketContext::default());
+    }
+
+    #[test]
+    fn test_wildcard_rules_refused_without_map() {
+        let filter_rules = MemoryMap::new();
+        let mut manager = MapManager::with_backends(MapBackends {
+            filter_rules: Some(&filter_rules),
+            ..Default::default()
+        });
+
+        let err = manager.add_rule(wildcard_rule("telnet", 23, 0)).unwrap_err();
+        assert!(format!("{:#}", err).contains("no wildcard_rules map"));
+        assert!(!manager.has_rule("telnet"));
+    }
+
+    #[test]
+    fn test_wildcard_rules_full() {
+        let wildcard_rules = MemoryMap::new();
+        let mut manager = MapManager::with_backends(MapBackends {
+            wildcard: Some(&wildcard_rules),
+            ..Default::default()
+        });
+
+        for port in 0..MAX_WILDCARD_RULES {
+            manager.add_rule(wildcard_rule(&format!("port-{}", port), port as u16, 0)).unwrap();
+        }
+        let err = manager.add_rule(wildcard_rule("one-too-many", 9999, 0)).unwrap_err();
+        assert!(format!("{:#}", err).contains("wildcard_rules map is full"));
+    }
+
+    #[test]
+    fn test_update_rule_moves_between_lpm_and_wildcard() {
+        let filter_rules = MemoryMap::new();
+        let wildcard_rules = MemoryMap::new();
+        let mut manager = MapManager::with_backends(MapBackends {
+            filter_rules: Some(&filter_rules),
+            wildcard: Some(&wildcard_rules),
+            ..Default::default()
+        });
+
+        manager.add_rule(wildcard_rule("web", 80, 0)).unwrap();
+
+        // 같은 슬롯을 덮어쓰면 통계를 이어받음
+        let slot = 0u32.to_le_bytes();
+        let mut value = wildcard_rules.lookup(&slot).unwrap().unwrap();
+        let stats = value.len() - RULE_STATS_SIZE;
+        value[stats..stats + 8].copy_from_slice(&7u64.to_le_bytes());
+        wildcard_rules.update(&slot, &value).unwrap();
+
+        manager.update_rule(wildcard_rule("web", 8080, 20)).unwrap();
+        assert_eq!(parse_rule_stats(&wildcard_rules.lookup(&slot).unwrap().unwrap()).packets, 7);
+        assert_eq!(manager.list_rules(true).unwrap()[0].stats.packets, 7);
+
+        // 소스 IP를 추가하면 LPM 맵으로 옮기고 슬롯을 비움
+        manager.update_rule(FilterRule { src_ip: Some((0x0A000000, 8)), ..wildcard_rule("web", 80, 0) }).unwrap();
+        assert_eq!(filter_rules.len(), 1);
+        assert_eq!(wildcard_rules.lookup(&slot).unwrap().unwrap()[RULE_ACTION_OFFSET], 0);
+        assert_eq!(manager.list_rules(false).unwrap()[0].map, RuleMap::Lpm);
+
+        // 소스 IP를 빼면 다시 와일드카드 슬롯으로
+        manager.update_rule(wildcard_rule("web", 80, 0)).unwrap();
+        assert_eq!(filter_rules.len(), 0);
+        assert_eq!(manager.snapshot_rule("web").unwrap().wildcard_slot, Some(0));
+    }
+
+    #[test]
+    fn test_filter_rule_blob_carries_redirect_cpu() {
+        let manager = MapManager::with_backends(MapBackends::default());
+
+        let with_cpu = manager.xdp.create_filter_rule(&redirect_rule("cpu", Some(5))).unwrap();
+        let without = manager.xdp.create_filter_rule(&redirect_rule("dev", None)).unwrap();
+
+        // redirect_cpu는 expire 바로 뒤 (label 32바이트 앞)
+        let offset = RULE_LABEL_OFFSET - 4;
+        assert_eq!(with_cpu[offset..offset + 4], 5u32.to_le_bytes());
+        assert_eq!(without[offset..offset + 4], REDIRECT_CPU_NONE.to_le_bytes());
+    }
+
+    #[test]
+    fn test_rules_effective_after_attach() {
+        let filter_rules = MemoryMap::new();
+        let mut manager = MapManager::with_backends(MapBackends {
+            filter_rules: Some(&filter_rules),
+            ..Default::default()
+        });
+
+        // 연결된 인터페이스가 없으면 설치만 되고 적용되지 않음
+        manager.add_rule(FilterRule { action: 2, ..redirect_rule("web", None) }).unwrap();
+        assert!(!manager.has_attached_interfaces());
+        assert!(!manager.list_rules(false).unwrap()[0].effective);
+        assert!(!manager.snapshot_rule("web").unwrap().into_detail(Vec::new(), Vec::new(), 0).info.effective);
+
+        manager.attach_interface("eth0", "generic", None, FallbackMode::None, |_| Ok(())).unwrap();
+        assert!(manager.list_rules(false).unwrap()[0].effective);
+
+        // 리디렉션 대상이 사라진 규칙은 연결되어 있어도 적용되지 않음
+        manager.add_rule(FilterRule { degraded: true, src_ip: Some((0xC0A80102, 32)), ..redirect_rule("ids", None) }).unwrap();
+        assert!(!manager.list_rules(false).unwrap()[1].effective);
+
+        manager.detach_interface("eth0", |_| Ok(())).unwrap();
+        assert!(!manager.list_rules(false).unwrap()[0].effective);
+    }
+
+    #[test]
+    fn test_nftables_fallback_attach_and_rules() {
+        let filter_rules = MemoryMap::new();
+        let nft = RecordingNft::default();
+        let mut manager = MapManager::with_backends(MapBackends {
+            filter_rules: Some(&filter_rules),
+            ..Default::default()
+        });
+        manager.nftables = Some(NftablesBackend::with_runner(Box::new(nft.clone())));
+
+        let web = FilterRule { action: 2, ..redirect_rule("web", None) };
+        manager.add_rule(web.clone()).unwrap();
+
+        // 대체 백엔드 없이 XDP 연결이 실패하면 오류
+        let unsupported = |_: &str| Err(DaemonError::Bpf(anyhow!("not supported")));
+        let err = manager.attach_interface("eth1", "driver", None, FallbackMode::None, unsupported).unwrap_err();
+        assert!(matches!(err, DaemonError::Bpf(_)));
+        assert!(err.to_string().contains("--fallback nftables"));
+        assert!(manager.list_interfaces().is_empty());
+
+        let backend = manager.attach_interface("eth1", "driver", None, FallbackMode::Nftables, unsupported).unwrap();
+        assert_eq!(backend, BackendKind::Nftables);
+        assert_eq!(manager.attach_interface("eth0", "generic", None, FallbackMode::Nftables, |_| Ok(())).unwrap(), BackendKind::Xdp);
+        assert_eq!(manager.list_interfaces(), vec![
+            InterfaceInfo { name: "eth0".to_string(), backend: "xdp".to_string(), mode: Some("generic".to_string()), object: None,
+                link: Some("up".to_string()), suspended: false },
+            InterfaceInfo { name: "eth1".to_string(), backend: "nftables".to_string(), mode: None, object: None,
+                link: Some("up".to_string()), suspended: false },
+        ]);
+        assert!(nft.scripts.lock().unwrap()[0].contains("comment \"swift-guard:web\""));
+
+        // 규칙 변경은 두 백엔드에 모두 반영
+        let mut ssh = FilterRule { action: 2, ..redirect_rule("ssh", None) };
+        ssh.src_ip = Some((0xC0A80102, 32));
+        manager.add_rule(ssh).unwrap();
+        assert_eq!(filter_rules.len(), 2);
+        assert!(nft.scripts.lock().unwrap().last().unwrap().contains("swift-guard:ssh"));
+
+        // 목록 통계에 nftables 카운터 합산
+        *nft.listing.lock().unwrap() = LISTING.to_string();
+        let rules = manager.list_rules(true).unwrap();
+        assert_eq!(rules.iter().find(|r| r.label == "web").unwrap().stats.packets, 10);
+
+        manager.delete_rule("ssh").unwrap();
+        assert_eq!(filter_rules.len(), 1);
+        assert!(!nft.scripts.lock().unwrap().last().unwrap().contains("swift-guard:ssh"));
+
+        // nftables 적용 실패 시 규칙 추가를 되돌림
+        *nft.fail.lock().unwrap() = true;
+        let mut dns = FilterRule { action: 2, ..redirect_rule("dns", None) };
+        dns.src_ip = Some((0xC0A80103, 32));
+        assert!(manager.add_rule(dns).is_err());
+        assert!(!manager.has_rule("dns"));
+        assert_eq!(filter_rules.len(), 1);
+        *nft.fail.lock().unwrap() = false;
+
+        // 분리는 인터페이스를 가진 백엔드에서 수행
+        assert_eq!(manager.detach_interface("eth1", |_| panic!("XDP detach on nftables interface")).unwrap(),
+            BackendKind::Nftables);
+        assert!(nft.scripts.lock().unwrap().last().unwrap().ends_with("delete table netdev swift_guard\n"));
+        assert_eq!(manager.detach_interface("eth0", |_| Ok(())).unwrap(), BackendKind::Xdp);
+        assert!(manager.list_interfaces().is_empty());
+    }
+
+    #[test]
+    fn test_alternate_object_receives_rules() {
+        let default_rules = MemoryMap::new();
+        let vendor_rules = MemoryMap::new();
+        let mut manager = MapManager::with_backends(MapBackends {
+            filter_rules: Some(&default_rules),
+            features: Some(XdpFeatures::ALL),
+            ..Default::default()
+        });
+        assert_eq!(manager.features(), Some(XdpFeatures::ALL));
+        assert_eq!(MapManager::with_backends(MapBackends::default()).features(), None);
+        assert_eq!(MapManager::with_backends(MapBackends::default()).rule_features(), XdpFeatures::ALL);
+
+        let mut ssh = FilterRule { action: 2, ..redirect_rule("ssh", None) };
+        ssh.src_ip = Some((0xC0A80102, 32));
+        manager.add_rule(ssh).unwrap();
+
+        // 등록되지 않은 오브젝트로는 연결하지 않음
+        let err = manager.attach_interface("eth1", "driver", Some("/objects/vendor.o"), FallbackMode::None, |_| Ok(()))
+            .unwrap_err();
+        assert!(err.to_string().contains("not loaded"));
+
+        // 등록 시 기존 규칙 설치
+        manager.add_object("/objects/vendor.o", MapBackends {
+            filter_rules: Some(&vendor_rules),
+            features: Some(XdpFeatures::LEGACY),
+            ..Default::default()
+        }).unwrap();
+        assert_eq!(vendor_rules.len(), 1);
+        // 규칙 검증은 두 오브젝트가 모두 처리하는 기능만 허용
+        assert_eq!(manager.features(), Some(XdpFeatures::LEGACY));
+        assert_eq!(manager.rule_features(), XdpFeatures::LEGACY);
+
+        manager.attach_interface("eth1", "driver", Some("/objects/vendor.o"), FallbackMode::None, |_| Ok(())).unwrap();
+        manager.attach_interface("eth0", "driver", None, FallbackMode::None, |_| Ok(())).unwrap();
+        assert_eq!(manager.list_interfaces(), vec![
+            InterfaceInfo { name: "eth0".to_string(), backend: "xdp".to_string(), mode: Some("driver".to_string()), object: None,
+                link: Some("up".to_string()), suspended: false },
+            InterfaceInfo {
+                name: "eth1".to_string(),
+                backend: "xdp".to_string(),
+                mode: Some("driver".to_string()),
+                object: Some("/objects/vendor.o".to_string()),
+                link: Some("up".to_string()),
+                suspended: false,
+            },
+        ]);
+
+        // 이후 규칙 변경은 모든 오브젝트에 반영
+        let mut web = FilterRule { action: 2, ..redirect_rule("web", None) };
+        web.src_ip = Some((0xC0A80103, 32));
+        manager.add_rule(web).unwrap();
+        assert_eq!(default_rules.len(), 2);
+        assert_eq!(vendor_rules.len(), 2);
+
+        manager.delete_rule("ssh").unwrap();
+        assert_eq!(default_rules.len(), 1);
+        assert_eq!(vendor_rules.len(), 1);
+    }
+
+    #[test]
+    fn test_prefix_key_golden_bytes() {
+        let manager = MapManager::with_backends(MapBackends::default());
+        let key = |prefix: &str| {
+            let (addr, prefix_len) = utils::parse_ip_prefix(prefix).unwrap();
+            manager.xdp.create_prefix_key(addr, prefix_len)
+        };
+
+        // struct prefix_key { u32 prefix_len (호스트 순서); u32 addr (네트워크 순서); }
+        assert_eq!(key("10.0.0.0/8"), [0x08, 0x00, 0x00, 0x00, 0x0a, 0x00, 0x00, 0x00]);
+        assert_eq!(key("192.168.1.0/24"), [0x18, 0x00, 0x00, 0x00, 0xc0, 0xa8, 0x01, 0x00]);
+        assert_eq!(key("172.16.5.4"), [0x20, 0x00, 0x00, 0x00, 0xac, 0x10, 0x05, 0x04]);
+        assert_eq!(key("0.0.0.0/0"), [0x00; 8]);
+    }
+
+    #[test]
+    fn test_add_rule_migrates_legacy_key() {
+        let filter_rules = MemoryMap::new();
+        let mut manager = MapManager::with_backends(MapBackends {
+            filter_rules: Some(&filter_rules),
+            ..Default::default()
+        }).with_legacy_key_migration();
+
+        // 이전 버전이 고정된 맵에 남긴 항목 (통계 포함)
+        let key = manager.xdp.create_prefix_key(0x0A000000, 8);
+        let legacy = manager.xdp.legacy_prefix_key(0x0A000000, 8);
+        let mut old = manager.xdp.create_filter_rule(&redirect_rule("lan", None)).unwrap();
+        let stats = old.len() - RULE_STATS_SIZE;
+        old[stats..stats + 8].copy_from_slice(&7u64.to_le_bytes());
+        filter_rules.update(&legacy, &old).unwrap();
+
+        let mut rule = FilterRule { action: 2, ..redirect_rule("lan", None) };
+        rule.src_ip = Some((0x0A000000, 8));
+        manager.add_rule(rule).unwrap();
+        assert!(filter_rules.lookup(&legacy).unwrap().is_none());
+        assert_eq!(parse_rule_stats(&filter_rules.lookup(&key).unwrap().unwrap()).packets, 7);
+        assert_eq!(filter_rules.len(), 1);
+    }
+
+    #[test]
+    fn test_audit_and_reconcile_maps() {
+        let filter_rules = MemoryMap::new();
+        let wildcard_rules = MemoryMap::new();
+        let mut manager = MapManager::with_backends(MapBackends {
+            filter_rules: Some(&filter_rules),
+            wildcard: Some(&wildcard_rules),
+            ..Default::default()
+        });
+        let web = FilterRule { action: 1, dst_port_min: 80, dst_port_max: 80, ..redirect_rule("web", None) };
+        manager.add_rule(web.clone()).unwrap();
+        manager.add_rule(wildcard_rule("ssh", 22, 10)).unwrap();
+        assert!(manager.audit_maps().is_clean());
+
+        // 캐시에 없는 LPM 항목과 와일드카드 슬롯, 커널에서 사라진 규칙 항목
+        let stale = FilterRule { src_ip: Some((0xC0A80900, 24)), ..wildcard_rule("stale", 443, 5) };
+        let stale_key = manager.xdp.create_prefix_key(0xC0A80900, 24);
+        filter_rules.update(&stale_key, &manager.xdp.create_filter_rule(&stale).unwrap()).unwrap();
+        let ghost = manager.xdp.create_filter_rule(&wildcard_rule("ghost", 23, 0)).unwrap();
+        wildcard_rules.update(&5u32.to_le_bytes(), &ghost).unwrap();
+        filter_rules.delete(&manager.xdp.create_prefix_key(0xC0A80101, 32)).unwrap();
+
+        let audit = manager.audit_maps();
+        assert_eq!(audit.orphans.len(), 2);
+        let orphan = &audit.orphans[0];
+        assert_eq!((orphan.map, orphan.label.as_str(), orphan.action.as_str()), (RuleMap::Lpm, "stale", "drop"));
+        assert_eq!(orphan.src_ip.as_deref(), Some("192.168.9.0/24"));
+        assert_eq!(orphan.lpm_key.as_deref(), Some("18000000c0a80900"));
+        assert_eq!((orphan.dst_port.as_deref(), orphan.priority), (Some("443"), 5));
+        assert_eq!((audit.orphans[1].wildcard_slot, audit.orphans[1].label.as_str()), (Some(5), "ghost"));
+        assert_eq!(audit.missing.len(), 1);
+        assert_eq!((audit.missing[0].label.as_str(), audit.missing[0].map), ("web", RuleMap::Lpm));
+
+        // 옵션 없이는 감사만 함
+        let summary = manager.reconcile_maps(false, false);
+        assert_eq!(summary.audit, audit);
+        assert_eq!((summary.deleted_orphans, summary.reinstalled), (0, 0));
+        assert_eq!(filter_rules.len(), 1);
+
+        let summary = manager.reconcile_maps(true, true);
+        assert_eq!((summary.deleted_orphans, summary.reinstalled), (2, 1));
+        assert!(summary.errors.is_empty());
+        assert!(manager.audit_maps().is_clean());
+        assert_eq!(filter_rules.len(), 1);
+        assert_eq!(wildcard_rules.lookup(&5u32.to_le_bytes()).unwrap().unwrap()[RULE_ACTION_OFFSET], 0);
+
+        // 삭제에 실패한 항목은 오류로 보고
+        filter_rules.update(&stale_key, &manager.xdp.create_filter_rule(&stale).unwrap()).unwrap();
+        filter_rules.fail_deletes(true);
+        let summary = manager.reconcile_maps(true, false);
+        assert_eq!(summary.deleted_orphans, 0);
+        assert_eq!(summary.errors.len(), 1);
+        assert_eq!(manager.audit_maps().orphans.len(), 1);
+    }
+
+    #[test]
+    fn test_rule_space_stats() {
+        let filter_rules = MemoryMap::new();
+        let wildcard_rules = MemoryMap::new();
+        let mut manager = MapManager::with_backends(MapBackends {
+            filter_rules: Some(&filter_rules),
+            wildcard: Some(&wildcard_rules),
+            ..Default::default()
+        });
+        let rule = |label: &str, prefix: &str| FilterRule {
+            src_ip: Some(utils::parse_ip_prefix(prefix).unwrap()),
+            ..wildcard_rule(label, 80, 0)
+        };
+        manager.add_rule(rule("all-10", "10.0.0.0/8")).unwrap();
+        manager.add_rule(rule("lab", "10.1.0.0/16")).unwrap();
+        manager.add_rule(rule("lab-host", "10.1.2.3")).unwrap();
+        manager.add_rule(rule("office", "192.168.0.0/16")).unwrap();
+        manager.add_rule(wildcard_rule("ssh", 22, 10)).unwrap();
+
+        let stats = manager.rule_space_stats(8);
+        assert_eq!(stats.prefix_lengths.len(), 33);
+        assert_eq!((stats.prefix_lengths[8], stats.prefix_lengths[16], stats.prefix_lengths[32]), (1, 2, 1));
+        assert_eq!(stats.prefix_lengths.iter().sum::<usize>(), 4);
+        assert_eq!((stats.lpm_entries, stats.kernel_entries, stats.wildcard_rules), (4, Some(4), 1));
+        assert_eq!(stats.fill_percent, 50.0);
+        assert!(!stats.kernel_mismatch());
+
+        // 겹침이 없는 프리픽스는 보고하지 않음
+        let overlaps: Vec<(&str, usize)> = stats.top_overlaps.iter()
+            .map(|overlap| (overlap.prefix.as_str(), overlap.contains))
+            .collect();
+        assert_eq!(overlaps, vec![("10.0.0.0/8", 2), ("10.1.0.0/16", 1)]);
+        assert_eq!(stats.top_overlaps[1].labels, vec!["lab".to_string()]);
+
+        // 커널에서 사라진 항목은 사용률과 불일치로 드러남
+        filter_rules.delete(&manager.xdp.create_prefix_key(0xC0A80000, 16)).unwrap();
+        let stats = manager.rule_space_stats(8);
+        assert_eq!((stats.lpm_entries, stats.kernel_entries), (4, Some(3)));
+        assert_eq!(stats.fill_percent, 37.5);
+        assert!(stats.kernel_mismatch());
+    }
+
+    #[test]
+    fn test_paginate_rules_while_changing() {
+        let filter_rules = MemoryMap::new();
+        let mut manager = MapManager::with_backends(MapBackends {
+            filter_rules: Some(&filter_rules),
+            ..Default::default()
+        });
+        let rule = |label: String, host: u32| FilterRule {
+            src_ip: Some((0x0A000000 + host, 32)),
+            action: 1,
+            ..redirect_rule(&label, None)
+        };
+        for i in 0..3000 {
+            manager.add_rule(rule(format!("r{:04}", i), i)).unwrap();
+        }
+
+        // 페이지 사이에 기준 규칙을 지우고 커서 앞뒤에 규칙을 추가
+        let mut seen: Vec<String> = Vec::new();
+        let mut cursor: Option<String> = None;
+        let mut pages = 0;
+        loop {
+            let page = paginate(manager.list_rules(false).unwrap(), cursor.as_deref(), 0, 250);
+            assert!(page.rules.len() <= 250);
+            seen.extend(page.rules.iter().map(|rule| rule.label.clone()));
+            pages += 1;
+            let Some(next) = page.next_cursor else {
+                break;
+            };
+            assert_eq!(Some(&next), seen.last());
+            manager.delete_rule(&next).unwrap();
+            manager.add_rule(rule(format!("a{:02}", pages), 10000 + pages)).unwrap();
+            manager.add_rule(rule(format!("z{:02}", pages), 20000 + pages)).unwrap();
+            cursor = Some(next);
+        }
+
+        // 처음부터 있던 규칙은 빠짐없이 한 번씩, 커서 앞에 추가된 규칙은 보이지 않음
+        let originals: Vec<String> = seen.iter().filter(|label| label.starts_with('r')).cloned().collect();
+        assert_eq!(originals, (0..3000).map(|i| format!("r{:04}", i)).collect::<Vec<_>>());
+        assert!(seen.iter().all(|label| !label.starts_with('a')));
+        let mut sorted = seen.clone();
+        sorted.sort();
+        sorted.dedup();
+        assert_eq!(sorted, seen);
+        assert!(seen.iter().any(|label| label.starts_with('z')));
+
+        // 오프셋은 커서 뒤 규칙에 적용하고 마지막 페이지에는 커서가 없음
+        let page = paginate(manager.list_rules(false).unwrap(), Some("r2990"), 2, 5);
+        assert_eq!(page.total_count, manager.list_rules(false).unwrap().len());
+        assert_eq!(page.rules.iter().map(|rule| rule.label.as_str()).collect::<Vec<_>>(),
+            ["r2993", "r2994", "r2995", "r2996", "r2997"]);
+        assert_eq!(page.next_cursor.as_deref(), Some("r2997"));
+        let page = paginate(manager.list_rules(false).unwrap(), Some("z"), 0, 1000);
+        assert_eq!(page.next_cursor, None);
     }
 }
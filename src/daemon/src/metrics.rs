@@ -0,0 +1,565 @@
+//! 메트릭 내보내기 모듈
+//! 수집된 통계를 Prometheus 텍스트 형식으로 변환하고 스크레이프 엔드포인트로 제공
+
+use anyhow::{anyhow, Context, Result};
+use log::{debug, info};
+use std::fmt::Write;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::time;
+
+use crate::maps::MapManager;
+use crate::telemetry::TelemetryCollector;
+
+use swift_guard::api::{ApiServerStats, DaemonSelfStats, RuleInfo, SizeHistogram, SystemStats, WebhookStats};
+use swift_guard::histogram::LatencySnapshot;
+use swift_guard::types::{API_LATENCY_BUCKET_BOUNDS_US, SIZE_BUCKET_BOUNDS};
+
+/// 메트릭 헤더(HELP, TYPE) 기록
+fn write_header(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+}
+
+/// 시스템 통계와 규칙별 카운터를 Prometheus 텍스트 형식으로 렌더링
+pub fn render_prometheus(stats: &SystemStats, rules: &[RuleInfo]) -> String {
+    let mut out = String::new();
+
+    write_header(&mut out, "swift_guard_packets_total", "counter", "Total packets processed");
+    let _ = writeln!(out, "swift_guard_packets_total {}", stats.total_packets);
+
+    write_header(&mut out, "swift_guard_bytes_total", "counter", "Total bytes processed");
+    let _ = writeln!(out, "swift_guard_bytes_total {}", stats.total_bytes);
+
+    write_header(&mut out, "swift_guard_packets_per_second", "gauge", "Current packet rate");
+    let _ = writeln!(out, "swift_guard_packets_per_second {}", stats.packets_per_sec);
+
+    write_header(&mut out, "swift_guard_throughput_mbps", "gauge", "Current throughput in Mbps");
+    let _ = writeln!(out, "swift_guard_throughput_mbps {:.3}", stats.mbps);
+
+    write_header(&mut out, "swift_guard_xdp_aborted_total", "counter", "Packets that ended in XDP_ABORTED (nonzero usually means an invalid redirect target)");
+    let _ = writeln!(out, "swift_guard_xdp_aborted_total {}", stats.aborted_packets);
+
+    write_header(&mut out, "swift_guard_protocol_packets_total", "counter", "Packets seen per IP protocol");
+    for (name, count) in stats.protocols.entries() {
+        let _ = writeln!(out, "swift_guard_protocol_packets_total{{protocol=\"{}\"}} {}", name, count);
+    }
+
+    if !stats.size_histogram.counts.is_empty() {
+        write_size_histogram(&mut out, &stats.size_histogram);
+    }
+
+    write_daemon_stats(&mut out, &stats.daemon);
+    write_api_stats(&mut out, &stats.api);
+    write_webhook_stats(&mut out, &stats.webhooks);
+    write_redirect_stats(&mut out, rules);
+
+    out
+}
+
+/// 리디렉션 규칙별 리디렉션 성공/실패/중단 수 기록 (리디렉션 규칙이 없으면 생략)
+fn write_redirect_stats(out: &mut String, rules: &[RuleInfo]) {
+    let redirects: Vec<&RuleInfo> = rules.iter().filter(|rule| rule.action == "redirect").collect();
+    if redirects.is_empty() {
+        return;
+    }
+
+    write_header(out, "swift_guard_rule_redirected_packets_total", "counter", "Packets handed to the redirect target per rule");
+    for rule in &redirects {
+        let _ = writeln!(out, "swift_guard_rule_redirected_packets_total{{namespace=\"{}\",rule=\"{}\"}} {}",
+            rule.namespace, rule.label, rule.stats.redirected_packets);
+    }
+
+    write_header(out, "swift_guard_rule_redirect_failures_total", "counter", "Packets the redirect helper failed to redirect per rule");
+    for rule in &redirects {
+        let _ = writeln!(out, "swift_guard_rule_redirect_failures_total{{namespace=\"{}\",rule=\"{}\"}} {}",
+            rule.namespace, rule.label, rule.stats.redirect_failures);
+    }
+
+    write_header(out, "swift_guard_rule_xdp_aborted_total", "counter", "Packets that ended in XDP_ABORTED per rule");
+    for rule in &redirects {
+        let _ = writeln!(out, "swift_guard_rule_xdp_aborted_total{{namespace=\"{}\",rule=\"{}\"}} {}",
+            rule.namespace, rule.label, rule.stats.aborted_packets);
+    }
+}
+
+/// API 서버 통계 기록 (처리 시간은 초 단위 히스토그램)
+fn write_api_stats(out: &mut String, api: &ApiServerStats) {
+    write_header(out, "swift_guard_api_requests_total", "counter", "API requests handled per request type");
+    for kind in &api.requests {
+        let _ = writeln!(out, "swift_guard_api_requests_total{{kind=\"{}\"}} {}", kind.kind, kind.requests);
+    }
+
+    write_header(out, "swift_guard_api_errors_total", "counter", "API requests answered with an error per request type");
+    for kind in &api.requests {
+        let _ = writeln!(out, "swift_guard_api_errors_total{{kind=\"{}\"}} {}", kind.kind, kind.errors);
+    }
+
+    let name = "swift_guard_api_request_duration_seconds";
+    write_header(out, name, "histogram", "API request handling time per request type");
+    for kind in &api.requests {
+        let mut cumulative = 0;
+        for (bound, count) in API_LATENCY_BUCKET_BOUNDS_US.iter().zip(kind.duration_buckets.iter()) {
+            cumulative += count;
+            let _ = writeln!(out, "{}_bucket{{kind=\"{}\",le=\"{}\"}} {}",
+                name, kind.kind, *bound as f64 / 1e6, cumulative);
+        }
+        let _ = writeln!(out, "{}_bucket{{kind=\"{}\",le=\"+Inf\"}} {}", name, kind.kind, kind.requests);
+        let _ = writeln!(out, "{}_sum{{kind=\"{}\"}} {}", name, kind.kind, kind.duration_sum_us as f64 / 1e6);
+        let _ = writeln!(out, "{}_count{{kind=\"{}\"}} {}", name, kind.kind, kind.requests);
+    }
+
+    write_header(out, "swift_guard_api_rate_limited_total", "counter", "API requests rejected by the per-peer rate limit");
+    let _ = writeln!(out, "swift_guard_api_rate_limited_total {}", api.rate_limited_total);
+
+    write_header(out, "swift_guard_api_oversized_frames_total", "counter", "API request frames rejected for exceeding the size limit");
+    let _ = writeln!(out, "swift_guard_api_oversized_frames_total {}", api.oversized_frames_total);
+
+    write_header(out, "swift_guard_api_active_connections", "gauge", "Open API connections");
+    let _ = writeln!(out, "swift_guard_api_active_connections {}", api.active_connections);
+}
+
+/// 웹훅 전송 통계 기록
+fn write_webhook_stats(out: &mut String, webhooks: &WebhookStats) {
+    write_header(out, "swift_guard_webhook_events_total", "counter", "Webhook events by delivery outcome");
+    let _ = writeln!(out, "swift_guard_webhook_events_total{{outcome=\"delivered\"}} {}", webhooks.delivered);
+    let _ = writeln!(out, "swift_guard_webhook_events_total{{outcome=\"failed\"}} {}", webhooks.failed);
+    let _ = writeln!(out, "swift_guard_webhook_events_total{{outcome=\"dropped\"}} {}", webhooks.dropped);
+
+    write_header(out, "swift_guard_webhook_retries_total", "counter", "Webhook requests retried after a failure");
+    let _ = writeln!(out, "swift_guard_webhook_retries_total {}", webhooks.retries);
+
+    write_header(out, "swift_guard_webhook_queued_events", "gauge", "Webhook events waiting in the send queue");
+    let _ = writeln!(out, "swift_guard_webhook_queued_events {}", webhooks.queued);
+}
+
+/// 데몬 자체 통계 기록
+fn write_daemon_stats(out: &mut String, daemon: &DaemonSelfStats) {
+    write_header(out, "swift_guard_daemon_cpu_percent", "gauge", "Daemon CPU usage in percent of one core");
+    let _ = writeln!(out, "swift_guard_daemon_cpu_percent {:.2}", daemon.cpu_percent);
+
+    write_header(out, "swift_guard_daemon_resident_memory_bytes", "gauge", "Daemon resident memory size");
+    let _ = writeln!(out, "swift_guard_daemon_resident_memory_bytes {}", daemon.rss_bytes);
+
+    write_header(out, "swift_guard_daemon_open_fds", "gauge", "Open file descriptors in the daemon");
+    let _ = writeln!(out, "swift_guard_daemon_open_fds {}", daemon.open_fds);
+
+    write_header(out, "swift_guard_daemon_tokio_tasks", "gauge", "Alive tokio tasks in the daemon");
+    let _ = writeln!(out, "swift_guard_daemon_tokio_tasks {}", daemon.tokio_tasks);
+
+    write_header(out, "swift_guard_daemon_uptime_seconds", "gauge", "Daemon process uptime");
+    let _ = writeln!(out, "swift_guard_daemon_uptime_seconds {}", daemon.uptime_secs);
+
+    write_header(out, "swift_guard_daemon_event_lag", "gauge", "Rule events not yet received by the slowest consumer");
+    let _ = writeln!(out, "swift_guard_daemon_event_lag {}", daemon.event_lag);
+}
+
+/// 패킷 크기 히스토그램 기록 (누적 버킷)
+fn write_size_histogram(out: &mut String, histogram: &SizeHistogram) {
+    let name = "swift_guard_packet_size_bytes";
+    write_header(out, name, "histogram", "Distribution of packet sizes");
+
+    let mut cumulative = 0;
+    for (bound, count) in SIZE_BUCKET_BOUNDS.iter().zip(histogram.counts.iter()) {
+        cumulative += count;
+        let _ = writeln!(out, "{}_bucket{{le=\"{}\"}} {}", name, bound, cumulative);
+    }
+
+    let total = histogram.total();
+    let _ = writeln!(out, "{}_bucket{{le=\"+Inf\"}} {}", name, total);
+    let _ = writeln!(out, "{}_sum {}", name, histogram.sum);
+    let _ = writeln!(out, "{}_count {}", name, total);
+}
+
+/// WASM 모듈별 패킷 검사 시간을 Prometheus 텍스트 형식으로 렌더링 (모듈이 없으면 빈 문자열)
+///
+/// 히스토그램과 함께 데몬이 추정한 p50/p95/p99를 게이지로 내보낸다.
+pub fn render_wasm_latency(modules: &[(String, LatencySnapshot)]) -> String {
+    let mut out = String::new();
+    if modules.is_empty() {
+        return out;
+    }
+
+    let name = "swift_guard_wasm_inspect_duration_seconds";
+    write_header(&mut out, name, "histogram", "WASM packet inspection time per module");
+    for (module, latency) in modules {
+        let mut cumulative = 0;
+        for (bound, count) in latency.bounds_us.iter().zip(latency.counts.iter()) {
+            cumulative += count;
+            let _ = writeln!(out, "{}_bucket{{module=\"{}\",le=\"{}\"}} {}",
+                name, module, *bound as f64 / 1e6, cumulative);
+        }
+        let total = latency.total();
+        let _ = writeln!(out, "{}_bucket{{module=\"{}\",le=\"+Inf\"}} {}", name, module, total);
+        let _ = writeln!(out, "{}_sum{{module=\"{}\"}} {}", name, module, latency.sum_us as f64 / 1e6);
+        let _ = writeln!(out, "{}_count{{module=\"{}\"}} {}", name, module, total);
+    }
+
+    let name = "swift_guard_wasm_inspect_duration_quantile_seconds";
+    write_header(&mut out, name, "gauge", "Estimated WASM packet inspection time percentiles per module");
+    for (module, latency) in modules {
+        for quantile in [0.5, 0.95, 0.99] {
+            if let Some(value_us) = latency.percentile(quantile) {
+                let _ = writeln!(out, "{}{{module=\"{}\",quantile=\"{}\"}} {}", name, module, quantile, value_us / 1e6);
+            }
+        }
+    }
+
+    out
+}
+
+/// WASM 검사 대기열이 가득 차 과부하 정책으로 처리한 패킷 수를 Prometheus 텍스트 형식으로 렌더링
+pub fn render_wasm_overloads(count: u64) -> String {
+    let mut out = String::new();
+    write_header(&mut out, "swift_guard_wasm_queue_overloads_total", "counter", "Packets handled by the overload policy because the WASM inspection queue was full");
+    let _ = writeln!(out, "swift_guard_wasm_queue_overloads_total {}", count);
+    out
+}
+
+/// `telemetry.export_url`이 없을 때의 스크레이프 주소
+pub const DEFAULT_EXPORT_URL: &str = "http://127.0.0.1:9464/metrics";
+
+/// 요청 하나를 읽고 응답하는 최대 시간 (느린 클라이언트가 다음 스크레이프를 막지 않게)
+const SCRAPE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// 요청 머리 최대 크기
+const MAX_REQUEST_HEAD: usize = 8192;
+
+/// 스크레이프 엔드포인트 URL 파싱 (`http://주소:포트[/경로]`, 경로가 없으면 /metrics)
+pub fn parse_export_url(url: &str) -> Result<(String, String)> {
+    let rest = url.strip_prefix("http://")
+        .ok_or_else(|| anyhow!("Unsupported telemetry export URL (http only): {}", url))?;
+
+    let (authority, path) = match rest.find('/') {
+        Some(pos) => (&rest[..pos], &rest[pos..]),
+        None => (rest, "/metrics"),
+    };
+    match authority.rsplit_once(':') {
+        Some((host, port)) if !host.is_empty() && port.parse::<u16>().is_ok() => {},
+        _ => return Err(anyhow!("Telemetry export URL needs a host and port: {}", url)),
+    }
+
+    Ok((authority.to_string(), path.to_string()))
+}
+
+/// 요청 머리에 대한 HTTP 응답 (`path`로 온 GET이면 `render`가 만든 메트릭)
+pub fn scrape_response(head: &str, path: &str, render: impl FnOnce() -> Result<String>) -> Vec<u8> {
+    let mut request_line = head.lines().next().unwrap_or("").split_whitespace();
+    let method = request_line.next().unwrap_or("");
+    let target = request_line.next().unwrap_or("");
+    let target = target.split('?').next().unwrap_or(target);
+
+    let (status, body) = if target != path {
+        ("404 Not Found", "Not found\n".to_string())
+    } else if method != "GET" {
+        ("405 Method Not Allowed", "Only GET is supported\n".to_string())
+    } else {
+        match render() {
+            Ok(text) => ("200 OK", text),
+            Err(e) => ("500 Internal Server Error", format!("{:#}\n", e)),
+        }
+    };
+
+    format!("HTTP/1.1 {}\r\nContent-Type: text/plain; version=0.0.4; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status, body.len(), body).into_bytes()
+}
+
+/// Prometheus 스크레이프 엔드포인트 실행 (연결을 차례로 처리, 바인드에 실패할 때만 반환)
+pub async fn serve(url: &str, telemetry: &TelemetryCollector<'_>, map_manager: &Mutex<MapManager<'_>>) -> Result<()> {
+    let (addr, path) = parse_export_url(url)?;
+    let listener = TcpListener::bind(&addr)
+        .await
+        .with_context(|| format!("Failed to bind metrics endpoint to {}", addr))?;
+    info!("Prometheus metrics available at http://{}{}", addr, path);
+
+    loop {
+        let (mut stream, peer) = match listener.accept().await {
+            Ok(accepted) => accepted,
+            Err(e) => {
+                debug!("Failed to accept metrics connection: {}", e);
+                continue;
+            }
+        };
+
+        let scrape = async {
+            let head = read_head(&mut stream).await?;
+            let response = scrape_response(&head, &path, || {
+                let map_manager = map_manager.lock()
+                    .map_err(|_| anyhow!("Failed to lock map_manager"))?;
+                telemetry.prometheus_metrics(&map_manager)
+            });
+            stream.write_all(&response).await
+        };
+        match time::timeout(SCRAPE_TIMEOUT, scrape).await {
+            Ok(Ok(())) => {},
+            Ok(Err(e)) => debug!("Metrics request from {} failed: {}", peer, e),
+            Err(_) => debug!("Metrics request from {} timed out", peer),
+        }
+    }
+}
+
+/// 빈 줄까지 요청 머리 읽기 (본문은 읽지 않음)
+async fn read_head(stream: &mut TcpStream) -> std::io::Result<String> {
+    let mut head = Vec::new();
+    let mut buf = [0u8; 1024];
+    while !head.windows(4).any(|window| window == b"\r\n\r\n") && head.len() < MAX_REQUEST_HEAD {
+        let n = stream.read(&mut buf).await?;
+        if n == 0 {
+            break;
+        }
+        head.extend_from_slice(&buf[..n]);
+    }
+    Ok(String::from_utf8_lossy(&head).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use swift_guard::api::{ProtocolStats, RequestTypeStats, RuleExpirySummary, RuleStats, TrafficBaseline};
+
+    #[test]
+    fn test_render_protocol_metrics() {
+        let stats = SystemStats {
+            total_packets: 100,
+            total_bytes: 6400,
+            packets_per_sec: 10,
+            mbps: 0.5,
+            aborted_packets: 3,
+            anomaly: false,
+            baseline: TrafficBaseline::default(),
+            protocols: ProtocolStats { tcp: 70, udp: 20, icmp: 10, other: 0 },
+            size_histogram: SizeHistogram::default(),
+            rules: RuleExpirySummary::default(),
+            daemon: DaemonSelfStats::default(),
+            api: ApiServerStats::default(),
+            webhooks: WebhookStats::default(),
+        };
+
+        let text = render_prometheus(&stats, &[]);
+        assert!(text.contains("swift_guard_packets_total 100\n"));
+        assert!(text.contains("# TYPE swift_guard_protocol_packets_total counter\n"));
+        assert!(text.contains("swift_guard_protocol_packets_total{protocol=\"tcp\"} 70\n"));
+        assert!(text.contains("swift_guard_protocol_packets_total{protocol=\"other\"} 0\n"));
+        assert!(!text.contains("swift_guard_packet_size_bytes"));
+        assert!(text.contains("# TYPE swift_guard_xdp_aborted_total counter\n"));
+        assert!(text.contains("swift_guard_xdp_aborted_total 3\n"));
+    }
+
+    #[test]
+    fn test_render_daemon_stats() {
+        let stats = SystemStats {
+            total_packets: 0,
+            total_bytes: 0,
+            packets_per_sec: 0,
+            mbps: 0.0,
+            aborted_packets: 0,
+            anomaly: false,
+            baseline: TrafficBaseline::default(),
+            protocols: ProtocolStats::default(),
+            size_histogram: SizeHistogram::default(),
+            rules: RuleExpirySummary::default(),
+            daemon: DaemonSelfStats {
+                cpu_percent: 12.5,
+                rss_bytes: 50331648,
+                open_fds: 37,
+                tokio_tasks: 12,
+                uptime_secs: 3600,
+                event_lag: 4,
+            },
+            api: ApiServerStats::default(),
+            webhooks: WebhookStats::default(),
+        };
+
+        let text = render_prometheus(&stats, &[]);
+        assert!(text.contains("# TYPE swift_guard_daemon_cpu_percent gauge\n"));
+        assert!(text.contains("swift_guard_daemon_cpu_percent 12.50\n"));
+        assert!(text.contains("swift_guard_daemon_resident_memory_bytes 50331648\n"));
+        assert!(text.contains("swift_guard_daemon_open_fds 37\n"));
+        assert!(text.contains("swift_guard_daemon_tokio_tasks 12\n"));
+        assert!(text.contains("swift_guard_daemon_uptime_seconds 3600\n"));
+        assert!(text.contains("swift_guard_daemon_event_lag 4\n"));
+    }
+
+    #[test]
+    fn test_render_size_histogram() {
+        let stats = SystemStats {
+            total_packets: 0,
+            total_bytes: 0,
+            packets_per_sec: 0,
+            mbps: 0.0,
+            aborted_packets: 0,
+            anomaly: false,
+            baseline: TrafficBaseline::default(),
+            protocols: ProtocolStats::default(),
+            size_histogram: SizeHistogram {
+                counts: vec![10, 0, 5, 0, 0, 3, 2],
+                sum: 20000,
+            },
+            rules: RuleExpirySummary::default(),
+            daemon: DaemonSelfStats::default(),
+            api: ApiServerStats::default(),
+            webhooks: WebhookStats::default(),
+        };
+
+        let text = render_prometheus(&stats, &[]);
+        assert!(text.contains("# TYPE swift_guard_packet_size_bytes histogram\n"));
+        assert!(text.contains("swift_guard_packet_size_bytes_bucket{le=\"64\"} 10\n"));
+        assert!(text.contains("swift_guard_packet_size_bytes_bucket{le=\"256\"} 15\n"));
+        assert!(text.contains("swift_guard_packet_size_bytes_bucket{le=\"1514\"} 18\n"));
+        assert!(text.contains("swift_guard_packet_size_bytes_bucket{le=\"+Inf\"} 20\n"));
+        assert!(text.contains("swift_guard_packet_size_bytes_sum 20000\n"));
+        assert!(text.contains("swift_guard_packet_size_bytes_count 20\n"));
+    }
+
+    #[test]
+    fn test_render_api_stats() {
+        let stats = SystemStats {
+            total_packets: 0,
+            total_bytes: 0,
+            packets_per_sec: 0,
+            mbps: 0.0,
+            aborted_packets: 0,
+            anomaly: false,
+            baseline: TrafficBaseline::default(),
+            protocols: ProtocolStats::default(),
+            size_histogram: SizeHistogram::default(),
+            rules: RuleExpirySummary::default(),
+            daemon: DaemonSelfStats::default(),
+            api: ApiServerStats {
+                requests_total: 3,
+                errors_total: 1,
+                rate_limited_total: 1,
+                oversized_frames_total: 2,
+                active_connections: 1,
+                requests: vec![RequestTypeStats {
+                    kind: "ping".to_string(),
+                    requests: 3,
+                    errors: 1,
+                    duration_sum_us: 1500,
+                    duration_buckets: vec![2, 0, 1, 0, 0, 0, 0, 0, 0],
+                }],
+            },
+            webhooks: WebhookStats { delivered: 40, failed: 3, dropped: 7, retries: 5, queued: 2 },
+        };
+
+        let text = render_prometheus(&stats, &[]);
+        assert!(text.contains("swift_guard_api_requests_total{kind=\"ping\"} 3\n"));
+        assert!(text.contains("swift_guard_api_errors_total{kind=\"ping\"} 1\n"));
+        assert!(text.contains("# TYPE swift_guard_api_request_duration_seconds histogram\n"));
+        assert!(text.contains("swift_guard_api_request_duration_seconds_bucket{kind=\"ping\",le=\"0.0005\"} 2\n"));
+        assert!(text.contains("swift_guard_api_request_duration_seconds_bucket{kind=\"ping\",le=\"0.005\"} 3\n"));
+        assert!(text.contains("swift_guard_api_request_duration_seconds_bucket{kind=\"ping\",le=\"+Inf\"} 3\n"));
+        assert!(text.contains("swift_guard_api_request_duration_seconds_sum{kind=\"ping\"} 0.0015\n"));
+        assert!(text.contains("swift_guard_api_rate_limited_total 1\n"));
+        assert!(text.contains("swift_guard_api_oversized_frames_total 2\n"));
+        assert!(text.contains("swift_guard_api_active_connections 1\n"));
+        assert!(text.contains("swift_guard_webhook_events_total{outcome=\"delivered\"} 40\n"));
+        assert!(text.contains("swift_guard_webhook_events_total{outcome=\"failed\"} 3\n"));
+        assert!(text.contains("swift_guard_webhook_events_total{outcome=\"dropped\"} 7\n"));
+        assert!(text.contains("swift_guard_webhook_retries_total 5\n"));
+        assert!(text.contains("swift_guard_webhook_queued_events 2\n"));
+    }
+
+    fn rule(label: &str, action: &str, redirected_packets: u64, redirect_failures: u64) -> RuleInfo {
+        RuleInfo {
+            label: label.to_string(),
+            action: action.to_string(),
+            protocol: "tcp".to_string(),
+            redirect_if: (action == "redirect").then(|| "veth1".to_string()),
+            stats: RuleStats { redirected_packets, redirect_failures, aborted_packets: redirect_failures, ..RuleStats::default() },
+            ..RuleInfo::default()
+        }
+    }
+
+    #[test]
+    fn test_render_redirect_stats() {
+        let stats = SystemStats {
+            total_packets: 0,
+            total_bytes: 0,
+            packets_per_sec: 0,
+            mbps: 0.0,
+            aborted_packets: 0,
+            anomaly: false,
+            baseline: TrafficBaseline::default(),
+            protocols: ProtocolStats::default(),
+            size_histogram: SizeHistogram::default(),
+            rules: RuleExpirySummary::default(),
+            daemon: DaemonSelfStats::default(),
+            api: ApiServerStats::default(),
+            webhooks: WebhookStats::default(),
+        };
+
+        let rules = vec![rule("block-ssh", "drop", 0, 0), rule("to-ids", "redirect", 40, 2)];
+        let text = render_prometheus(&stats, &rules);
+        assert!(text.contains("# TYPE swift_guard_rule_redirected_packets_total counter\n"));
+        assert!(text.contains("swift_guard_rule_redirected_packets_total{namespace=\"default\",rule=\"to-ids\"} 40\n"));
+        assert!(text.contains("swift_guard_rule_redirect_failures_total{namespace=\"default\",rule=\"to-ids\"} 2\n"));
+        assert!(text.contains("swift_guard_rule_xdp_aborted_total{namespace=\"default\",rule=\"to-ids\"} 2\n"));
+        assert!(!text.contains("rule=\"block-ssh\""));
+
+        // 리디렉션 규칙이 없으면 메트릭 자체를 생략
+        let text = render_prometheus(&stats, &rules[..1]);
+        assert!(!text.contains("swift_guard_rule_redirect"));
+    }
+
+    #[test]
+    fn test_render_wasm_latency() {
+        assert_eq!(render_wasm_latency(&[]), "");
+
+        let latency = LatencySnapshot { bounds_us: vec![1, 10, 100], counts: vec![6, 3, 0, 1], sum_us: 250 };
+        let text = render_wasm_latency(&[("ids".to_string(), latency), ("idle".to_string(), LatencySnapshot::default())]);
+        assert!(text.contains("# TYPE swift_guard_wasm_inspect_duration_seconds histogram\n"));
+        assert!(text.contains("swift_guard_wasm_inspect_duration_seconds_bucket{module=\"ids\",le=\"0.000001\"} 6\n"));
+        assert!(text.contains("swift_guard_wasm_inspect_duration_seconds_bucket{module=\"ids\",le=\"0.0001\"} 9\n"));
+        assert!(text.contains("swift_guard_wasm_inspect_duration_seconds_bucket{module=\"ids\",le=\"+Inf\"} 10\n"));
+        assert!(text.contains("swift_guard_wasm_inspect_duration_seconds_sum{module=\"ids\"} 0.00025\n"));
+        assert!(text.contains("swift_guard_wasm_inspect_duration_seconds_count{module=\"ids\"} 10\n"));
+        assert!(text.contains("swift_guard_wasm_inspect_duration_seconds_count{module=\"idle\"} 0\n"));
+
+        // 백분위수는 검사한 패킷이 있는 모듈만
+        assert!(text.contains("swift_guard_wasm_inspect_duration_quantile_seconds{module=\"ids\",quantile=\"0.99\"} 0.0001\n"));
+        assert!(!text.contains("quantile_seconds{module=\"idle\""));
+    }
+
+    #[test]
+    fn test_render_wasm_overloads() {
+        let text = render_wasm_overloads(3);
+        assert!(text.contains("# TYPE swift_guard_wasm_queue_overloads_total counter\n"));
+        assert!(text.ends_with("swift_guard_wasm_queue_overloads_total 3\n"));
+    }
+
+    #[test]
+    fn test_parse_export_url() {
+        assert_eq!(parse_export_url("http://127.0.0.1:9464/metrics").unwrap(),
+            ("127.0.0.1:9464".to_string(), "/metrics".to_string()));
+        assert_eq!(parse_export_url("http://0.0.0.0:9100").unwrap(),
+            ("0.0.0.0:9100".to_string(), "/metrics".to_string()));
+        assert!(parse_export_url("https://127.0.0.1:9464/metrics").is_err());
+        assert!(parse_export_url("http://127.0.0.1/metrics").is_err());
+        assert!(parse_export_url("http://:9464/metrics").is_err());
+    }
+
+    #[test]
+    fn test_scrape_response() {
+        let ok = || Ok("swift_guard_packets_total 1\n".to_string());
+
+        let response = String::from_utf8(scrape_response("GET /metrics HTTP/1.1\r\nHost: x\r\n\r\n", "/metrics", ok)).unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(response.contains("Content-Length: 28\r\n"));
+        assert!(response.ends_with("\r\n\r\nswift_guard_packets_total 1\n"));
+
+        // 쿼리 문자열은 무시
+        let response = String::from_utf8(scrape_response("GET /metrics?x=1 HTTP/1.1\r\n\r\n", "/metrics", ok)).unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+
+        let response = String::from_utf8(scrape_response("GET / HTTP/1.1\r\n\r\n", "/metrics", ok)).unwrap();
+        assert!(response.starts_with("HTTP/1.1 404 Not Found\r\n"));
+        let response = String::from_utf8(scrape_response("POST /metrics HTTP/1.1\r\n\r\n", "/metrics", ok)).unwrap();
+        assert!(response.starts_with("HTTP/1.1 405 Method Not Allowed\r\n"));
+        let response = String::from_utf8(scrape_response("GET /metrics HTTP/1.1\r\n\r\n", "/metrics",
+            || Err(anyhow!("Failed to lock stats")))).unwrap();
+        assert!(response.starts_with("HTTP/1.1 500 Internal Server Error\r\n"));
+    }
+}
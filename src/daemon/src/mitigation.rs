@@ -0,0 +1,397 @@
+//! 자동 완화 모듈
+//! 텔레메트리 카운터를 기반으로 SYN 플러드를 탐지하고 차단 규칙을 설치
+
+use anyhow::Result;
+use log::{info, warn};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::broadcast;
+
+use crate::config::MitigationConfig;
+use crate::geoip::{self, GeoResolver};
+use crate::maps::{FilterRule, MapManager};
+use crate::syslog::{SecurityEvent, SyslogSink};
+use crate::telemetry::TelemetryCollector;
+use crate::webhook::WebhookSender;
+
+use swift_guard::api::{DaemonEvent, InstallState};
+use swift_guard::types::{ActionType, ProtocolType, TcpFlags};
+use swift_guard::utils;
+
+pub use swift_guard::api::MitigationEvent;
+
+/// 이벤트 구독자별 버퍼 크기 (초과하면 오래된 이벤트부터 유실)
+const EVENT_CHANNEL_CAPACITY: usize = 256;
+
+/// 자동 규칙 레이블 접두사
+pub const SYNFLOOD_LABEL_PREFIX: &str = "auto-synflood-";
+
+/// 탐지 결과
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Detection {
+    /// 소스 IPv4 주소 (호스트 순서)
+    pub src_ip: u32,
+    /// 관측된 초당 SYN 수
+    pub syn_pps: u64,
+}
+
+impl Detection {
+    /// 자동 규칙 레이블
+    pub fn label(&self) -> String {
+        format!("{}{}", SYNFLOOD_LABEL_PREFIX, utils::ipv4_to_string(self.src_ip))
+    }
+}
+
+/// SYN 플러드 탐지 상태 머신
+///
+/// 소스별 누적 SYN 카운터를 수집 간격마다 입력받아 초당 SYN 수를 계산하고,
+/// 임계값을 `consecutive_intervals`번 연속으로 초과한 소스를 보고한다.
+/// 이미 완화 중인 소스는 규칙이 만료될 때까지 다시 보고하지 않는다.
+#[derive(Debug)]
+pub struct SynFloodDetector {
+    config: MitigationConfig,
+    /// 이전 누적 카운터
+    prev_counts: HashMap<u32, u64>,
+    /// 연속 초과 횟수
+    streaks: HashMap<u32, u32>,
+    /// 완화 중인 소스와 만료 시각
+    active: HashMap<u32, u64>,
+    /// 완화 이벤트 발행 채널
+    events: broadcast::Sender<MitigationEvent>,
+    /// 보안 이벤트 syslog 싱크
+    syslog: Option<SyslogSink>,
+    /// 완화 이벤트 웹훅 전송기
+    webhook: Option<WebhookSender>,
+    /// 소스 주소 GeoIP 해석기
+    geo: Option<Arc<dyn GeoResolver>>,
+}
+
+impl SynFloodDetector {
+    /// 새로운 탐지기 생성
+    pub fn new(config: &MitigationConfig) -> Self {
+        Self {
+            config: config.clone(),
+            prev_counts: HashMap::new(),
+            streaks: HashMap::new(),
+            active: HashMap::new(),
+            events: broadcast::channel(EVENT_CHANNEL_CAPACITY).0,
+            syslog: None,
+            webhook: None,
+            geo: None,
+        }
+    }
+
+    /// 완화 이벤트를 syslog로도 전달
+    pub fn with_syslog(mut self, sink: SyslogSink) -> Self {
+        self.syslog = Some(sink);
+        self
+    }
+
+    /// 완화 이벤트를 웹훅으로도 전달 (`webhook_url`로 시작한 전송기)
+    pub fn with_webhook(mut self, sender: WebhookSender) -> Self {
+        self.webhook = Some(sender);
+        self
+    }
+
+    /// 완화 이벤트 소스 주소에 GeoIP 정보 추가
+    pub fn with_geo(mut self, resolver: Arc<dyn GeoResolver>) -> Self {
+        self.geo = Some(resolver);
+        self
+    }
+
+    /// 완화 이벤트 발행 채널 (구독은 `subscribe()`로)
+    pub fn event_sender(&self) -> broadcast::Sender<MitigationEvent> {
+        self.events.clone()
+    }
+
+    /// 수집 간격의 카운터 관측
+    ///
+    /// `global_syn_pps`가 임계값보다 낮으면 어떤 단일 소스도 임계값을 넘을 수
+    /// 없으므로 소스별 연속 기록을 초기화한다.
+    pub fn observe(
+        &mut self,
+        global_syn_pps: u64,
+        syn_counts: &HashMap<u32, u64>,
+        elapsed_secs: f64,
+        now: u64,
+    ) -> Vec<Detection> {
+        self.active.retain(|_, until| *until > now);
+
+        let mut detections = Vec::new();
+        let threshold = self.config.syn_pps_threshold;
+        let mut over = HashMap::new();
+
+        if global_syn_pps >= threshold && elapsed_secs > 0.0 {
+            for (&ip, &count) in syn_counts {
+                // 처음 보는 소스는 기준값만 기록
+                let Some(&prev) = self.prev_counts.get(&ip) else {
+                    continue;
+                };
+
+                let pps = (count.saturating_sub(prev) as f64 / elapsed_secs) as u64;
+                if pps >= threshold {
+                    over.insert(ip, pps);
+                }
+            }
+        }
+
+        self.streaks.retain(|ip, _| over.contains_key(ip));
+        for (ip, pps) in over {
+            let streak = self.streaks.entry(ip).or_insert(0);
+            *streak += 1;
+
+            if *streak >= self.config.consecutive_intervals && !self.active.contains_key(&ip) {
+                self.active.insert(ip, now + self.config.expire as u64);
+                self.streaks.remove(&ip);
+                detections.push(Detection { src_ip: ip, syn_pps: pps });
+            }
+        }
+
+        self.prev_counts = syn_counts.clone();
+        detections.sort_by_key(|d| d.src_ip);
+        detections
+    }
+
+    /// 탐지 결과에 대한 자동 규칙 생성
+    pub fn build_rule(&self, detection: &Detection, now: u64) -> FilterRule {
+        let rate_limit = self.config.action == "rate-limit";
+
+        FilterRule {
+            src_ip: Some((detection.src_ip, 32)),
+            dst_ip: None,
+            src_port_min: 0,
+            src_port_max: 65535,
+            src_port_list: Vec::new(),
+            dst_port_min: 0,
+            dst_port_max: 65535,
+            dst_port_list: Vec::new(),
+            protocol: ProtocolType::Tcp as u8,
+            tcp_flags: TcpFlags::SYN,
+            action: if rate_limit { ActionType::Pass as u8 } else { ActionType::Drop as u8 },
+            redirect_ifindex: 0,
+            redirect_ifname: None,
+            redirect_cpu: None,
+            priority: u32::MAX,
+            rate_limit: if rate_limit { self.config.rate_limit } else { 0 },
+            expire: self.config.expire,
+            label: detection.label(),
+            description: Some(format!("SYN flood from {}", utils::ipv4_to_string(detection.src_ip))),
+            created_by: Some("mitigation".to_string()),
+            creation_time: now,
+            auto_generated: true,
+            degraded: false,
+            install_state: InstallState::Installed,
+            quota: None,
+            quota_exceeded: false,
+            src_host: None,
+            counter_group: None,
+            preset: None,
+            origin: None,
+            src_set: None,
+            owner_webhook: None,
+            notify_before: None,
+        }
+    }
+
+    /// 텔레메트리 수집 주기마다 호출: 만료 규칙 정리, 탐지 및 적용
+    pub fn tick(
+        &mut self,
+        telemetry: &TelemetryCollector,
+        map_manager: &mut MapManager,
+        elapsed_secs: f64,
+        now: u64,
+    ) -> Result<Vec<MitigationEvent>> {
+        if !self.config.enabled {
+            return Ok(Vec::new());
+        }
+
+        map_manager.remove_expired_rules(now)?;
+
+        let detections = self.observe(
+            telemetry.syn_packets_per_sec()?,
+            &telemetry.syn_source_counts(),
+            elapsed_secs,
+            now,
+        );
+
+        self.apply(&detections, map_manager, now)
+    }
+
+    /// 탐지 결과 적용 (규칙 설치 또는 드라이런 로그) 및 이벤트 전송
+    pub fn apply(
+        &self,
+        detections: &[Detection],
+        map_manager: &mut MapManager,
+        now: u64,
+    ) -> Result<Vec<MitigationEvent>> {
+        let mut events = Vec::new();
+
+        for detection in detections {
+            let rule = self.build_rule(detection, now);
+
+            if self.config.dry_run {
+                info!("[dry-run] Would install {} ({} SYN/s from {})",
+                    rule.label, detection.syn_pps, utils::ipv4_to_string(detection.src_ip));
+            } else if map_manager.has_rule(&rule.label) {
+                continue;
+            } else {
+                warn!("SYN flood from {} ({} SYN/s), installing {}",
+                    utils::ipv4_to_string(detection.src_ip), detection.syn_pps, rule.label);
+                // 같은 주소의 규칙이 이미 있으면 그 규칙을 유지
+                if let Err(e) = map_manager.add_rule(rule.clone()) {
+                    warn!("Failed to install {}: {}", rule.label, e);
+                    continue;
+                }
+            }
+
+            let src_ip = utils::ipv4_to_string(detection.src_ip);
+            let geo = geoip::enrich(self.geo.as_deref(), &src_ip);
+            let event = MitigationEvent {
+                kind: "synflood".to_string(),
+                src_ip,
+                syn_pps: detection.syn_pps,
+                label: rule.label.clone(),
+                action: self.config.action.clone(),
+                expire: self.config.expire,
+                dry_run: self.config.dry_run,
+                timestamp: now,
+                geo,
+            };
+
+            if let Some(webhook) = &self.webhook {
+                webhook.send(DaemonEvent::Mitigation(event.clone()));
+            }
+
+            if let Some(sink) = &self.syslog {
+                sink.send(&SecurityEvent::from(&event));
+            }
+
+            // 구독자가 없으면 전송 실패는 무시
+            let _ = self.events.send(event.clone());
+
+            events.push(event);
+        }
+
+        Ok(events)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ATTACKER: u32 = 0xC0A8010A; // 192.168.1.10
+    const CLIENT: u32 = 0xC0A80114; // 192.168.1.20
+
+    fn config() -> MitigationConfig {
+        MitigationConfig {
+            enabled: true,
+            syn_pps_threshold: 1000,
+            consecutive_intervals: 3,
+            expire: 60,
+            ..MitigationConfig::default()
+        }
+    }
+
+    fn counts(entries: &[(u32, u64)]) -> HashMap<u32, u64> {
+        entries.iter().copied().collect()
+    }
+
+    #[test]
+    fn test_detects_after_consecutive_intervals() {
+        let mut detector = SynFloodDetector::new(&config());
+
+        // 기준값
+        assert!(detector.observe(0, &counts(&[(ATTACKER, 0), (CLIENT, 0)]), 1.0, 0).is_empty());
+        // 2번 연속 초과: 아직 탐지 안 됨
+        assert!(detector.observe(5000, &counts(&[(ATTACKER, 5000), (CLIENT, 10)]), 1.0, 1).is_empty());
+        assert!(detector.observe(5000, &counts(&[(ATTACKER, 10000), (CLIENT, 20)]), 1.0, 2).is_empty());
+        // 3번째 초과에서 탐지
+        let detections = detector.observe(5000, &counts(&[(ATTACKER, 15000), (CLIENT, 30)]), 1.0, 3);
+        assert_eq!(detections, vec![Detection { src_ip: ATTACKER, syn_pps: 5000 }]);
+        assert_eq!(detections[0].label(), "auto-synflood-192.168.1.10");
+
+        // 완화 중에는 다시 보고하지 않음
+        for t in 4..10 {
+            let count = 15000 + (t - 3) * 5000;
+            assert!(detector.observe(5000, &counts(&[(ATTACKER, count)]), 1.0, t).is_empty());
+        }
+    }
+
+    #[test]
+    fn test_streak_resets_when_rate_drops() {
+        let mut detector = SynFloodDetector::new(&config());
+
+        detector.observe(0, &counts(&[(ATTACKER, 0)]), 1.0, 0);
+        detector.observe(2000, &counts(&[(ATTACKER, 2000)]), 1.0, 1);
+        detector.observe(2000, &counts(&[(ATTACKER, 4000)]), 1.0, 2);
+        // 임계값 아래로 떨어지면 연속 기록 초기화
+        detector.observe(100, &counts(&[(ATTACKER, 4100)]), 1.0, 3);
+        assert!(detector.observe(2000, &counts(&[(ATTACKER, 6100)]), 1.0, 4).is_empty());
+        assert!(detector.observe(2000, &counts(&[(ATTACKER, 8100)]), 1.0, 5).is_empty());
+        assert_eq!(detector.observe(2000, &counts(&[(ATTACKER, 10100)]), 1.0, 6).len(), 1);
+    }
+
+    #[test]
+    fn test_global_rate_gates_detection() {
+        let mut detector = SynFloodDetector::new(&config());
+
+        detector.observe(0, &counts(&[(ATTACKER, 0)]), 1.0, 0);
+        for t in 1..5 {
+            // 전역 SYN 속도가 임계값 미만이면 소스별 계산을 하지 않음
+            assert!(detector.observe(500, &counts(&[(ATTACKER, t * 2000)]), 1.0, t).is_empty());
+        }
+    }
+
+    #[test]
+    fn test_redetects_after_expire() {
+        let mut detector = SynFloodDetector::new(&MitigationConfig {
+            consecutive_intervals: 1,
+            ..config()
+        });
+
+        detector.observe(0, &counts(&[(ATTACKER, 0)]), 1.0, 0);
+        assert_eq!(detector.observe(3000, &counts(&[(ATTACKER, 3000)]), 1.0, 1).len(), 1);
+        assert!(detector.observe(3000, &counts(&[(ATTACKER, 6000)]), 1.0, 30).is_empty());
+        // expire(60초) 이후 다시 탐지
+        assert_eq!(detector.observe(3000, &counts(&[(ATTACKER, 9000)]), 1.0, 61).len(), 1);
+    }
+
+    #[test]
+    fn test_apply_publishes_events() {
+        let detector = SynFloodDetector::new(&MitigationConfig {
+            dry_run: true,
+            ..config()
+        }).with_geo(Arc::new(geoip::tests::stub(&[("192.168.1.10", "KR", 4766)])));
+        let mut events = detector.event_sender().subscribe();
+        let mut map_manager = MapManager::with_backends(Default::default());
+
+        let detection = Detection { src_ip: ATTACKER, syn_pps: 5000 };
+        detector.apply(&[detection], &mut map_manager, 100).unwrap();
+
+        let event = events.try_recv().unwrap();
+        assert_eq!(event.label, "auto-synflood-192.168.1.10");
+        assert!(event.dry_run);
+        assert_eq!(event.geo.unwrap().country.as_deref(), Some("KR"));
+        assert!(events.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_build_rule() {
+        let detector = SynFloodDetector::new(&MitigationConfig {
+            action: "rate-limit".to_string(),
+            rate_limit: 50,
+            ..config()
+        });
+
+        let rule = detector.build_rule(&Detection { src_ip: ATTACKER, syn_pps: 5000 }, 100);
+        assert_eq!(rule.src_ip, Some((ATTACKER, 32)));
+        assert_eq!(rule.tcp_flags, TcpFlags::SYN);
+        assert_eq!(rule.action, ActionType::Pass as u8);
+        assert_eq!(rule.rate_limit, 50);
+        assert_eq!(rule.expire, 60);
+        assert!(rule.auto_generated);
+        assert!(rule.is_expired(160));
+        assert!(!rule.is_expired(159));
+    }
+}
@@ -0,0 +1,283 @@
+//! 네트워크 인터페이스 모듈
+//! 인터페이스 이름 해석, 링크 상태 및 드라이버 확인, 프레임 전송
+
+use anyhow::{anyhow, Context, Result};
+use nix::net::if_::if_nametoindex;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+/// ethtool ioctl 요청 번호 (linux/sockios.h, linux/ethtool.h)
+const SIOCETHTOOL: libc::c_ulong = 0x8946;
+const ETHTOOL_GDRVINFO: u32 = 0x03;
+
+/// 인터페이스 ifindex 조회
+pub fn if_index(name: &str) -> Result<u32> {
+    if_nametoindex(name)
+        .map_err(|_| anyhow!("Interface {} does not exist", name))
+}
+
+/// 인터페이스 operstate 조회 (/sys/class/net/<name>/operstate)
+pub fn operstate(name: &str) -> Option<String> {
+    fs::read_to_string(format!("/sys/class/net/{}/operstate", name))
+        .ok()
+        .map(|s| s.trim().to_string())
+}
+
+/// 인터페이스가 동작 중인지 확인
+///
+/// 루프백이나 일부 가상 인터페이스는 operstate가 "unknown"이므로 동작 중으로 본다.
+pub fn is_up(name: &str) -> bool {
+    matches!(operstate(name).as_deref(), Some("up") | Some("unknown"))
+}
+
+/// 리디렉션 대상 인터페이스 검증 및 ifindex 반환
+pub fn resolve_redirect_target(name: &str) -> Result<u32> {
+    let ifindex = if_index(name)?;
+
+    if !is_up(name) {
+        return Err(anyhow!("Redirect interface {} is not up", name));
+    }
+
+    Ok(ifindex)
+}
+
+/// 현재 동작 중인 인터페이스의 ifindex (사라졌거나 내려갔으면 None)
+pub fn current_target(name: &str) -> Option<u32> {
+    resolve_redirect_target(name).ok()
+}
+
+/// 인터페이스 드라이버 이름
+///
+/// 물리 장치는 /sys/class/net/<name>/device/driver 링크에서, 장치 디렉토리가 없는
+/// 가상 인터페이스(veth, tun 등)는 ethtool ioctl(ETHTOOL_GDRVINFO)로 확인한다.
+pub fn driver_name(name: &str) -> Option<String> {
+    driver_from_sysfs(Path::new(&format!("/sys/class/net/{}/device/driver", name)))
+        .or_else(|| ethtool_driver(name))
+}
+
+/// 드라이버 링크 대상의 마지막 경로 요소 (../../bus/pci/drivers/ixgbe → ixgbe)
+fn driver_from_sysfs(link: &Path) -> Option<String> {
+    let target = fs::read_link(link).ok()?;
+    target.file_name().map(|name| name.to_string_lossy().into_owned())
+}
+
+/// struct ethtool_drvinfo
+#[repr(C)]
+struct EthtoolDrvinfo {
+    cmd: u32,
+    driver: [u8; 32],
+    version: [u8; 32],
+    fw_version: [u8; 32],
+    bus_info: [u8; 32],
+    erom_version: [u8; 32],
+    reserved2: [u8; 12],
+    n_priv_flags: u32,
+    n_stats: u32,
+    testinfo_len: u32,
+    eedump_len: u32,
+    regdump_len: u32,
+}
+
+/// struct ifreq (ifr_data 형태)
+#[repr(C)]
+struct IfreqData {
+    ifr_name: [u8; libc::IFNAMSIZ],
+    ifr_data: *mut libc::c_void,
+    _pad: [u8; 16],
+}
+
+/// ethtool ioctl로 드라이버 이름 조회
+fn ethtool_driver(name: &str) -> Option<String> {
+    if name.is_empty() || name.len() >= libc::IFNAMSIZ {
+        return None;
+    }
+
+    let mut info = EthtoolDrvinfo {
+        cmd: ETHTOOL_GDRVINFO,
+        driver: [0; 32],
+        version: [0; 32],
+        fw_version: [0; 32],
+        bus_info: [0; 32],
+        erom_version: [0; 32],
+        reserved2: [0; 12],
+        n_priv_flags: 0,
+        n_stats: 0,
+        testinfo_len: 0,
+        eedump_len: 0,
+        regdump_len: 0,
+    };
+    let mut request = IfreqData {
+        ifr_name: [0; libc::IFNAMSIZ],
+        ifr_data: &mut info as *mut EthtoolDrvinfo as *mut libc::c_void,
+        _pad: [0; 16],
+    };
+    request.ifr_name[..name.len()].copy_from_slice(name.as_bytes());
+
+    // SAFETY: request와 info는 호출 동안 유효하며 소켓은 이 함수 안에서만 사용한다
+    let result = unsafe {
+        let fd = libc::socket(libc::AF_INET, libc::SOCK_DGRAM, 0);
+        if fd < 0 {
+            return None;
+        }
+        let result = libc::ioctl(fd, SIOCETHTOOL as _, &mut request as *mut IfreqData);
+        libc::close(fd);
+        result
+    };
+    if result < 0 {
+        return None;
+    }
+
+    let end = info.driver.iter().position(|&b| b == 0).unwrap_or(info.driver.len());
+    let driver = String::from_utf8_lossy(&info.driver[..end]).into_owned();
+    (!driver.is_empty()).then_some(driver)
+}
+
+/// XDP 프로그램이 연결된 인터페이스 목록 (`ip -o link show` 기준)
+pub fn xdp_interfaces() -> Vec<String> {
+    match Command::new("ip").args(["-o", "link", "show"]).output() {
+        Ok(output) if output.status.success() => {
+            parse_xdp_interfaces(&String::from_utf8_lossy(&output.stdout))
+        },
+        _ => Vec::new(),
+    }
+}
+
+/// `ip -o link show` 출력에서 xdp/xdpgeneric/xdpdrv/xdpoffload가 표시된 인터페이스 추출
+fn parse_xdp_interfaces(output: &str) -> Vec<String> {
+    output.lines().filter_map(|line| {
+        let mut fields = line.splitn(3, ": ");
+        let _index = fields.next()?;
+        let name = fields.next()?;
+        let rest = fields.next()?;
+
+        if rest.split_whitespace().any(|token| token.starts_with("xdp")) {
+            Some(name.split('@').next().unwrap_or(name).to_string())
+        } else {
+            None
+        }
+    }).collect()
+}
+
+/// 이더넷 프레임을 인터페이스로 `count`번 전송하고 보낸 수 반환 (AF_PACKET raw 소켓, CAP_NET_RAW 필요)
+///
+/// 루프백으로 보낸 프레임은 수신 경로로 돌아오므로 루프백에 연결된 XDP 프로그램이 처리한다.
+/// 물리 인터페이스로 보낸 프레임은 호스트를 떠나므로 그 인터페이스의 XDP 프로그램을 거치지 않는다.
+pub fn send_frames(name: &str, frame: &[u8], count: u32) -> Result<u32> {
+    let (sent, result) = send_while(name, frame, false, |sent| sent < u64::from(count))?;
+    result.with_context(|| format!("Failed to send frame on {} ({} of {} sent)", name, sent, count))?;
+    Ok(sent as u32)
+}
+
+/// 이더넷 프레임을 `duration` 동안 최대한 빠르게 전송하고 보낸 수 반환 (벤치마크 트래픽)
+///
+/// 송신 큐가 가득 차 ENOBUFS로 실패한 전송은 세지 않고 다시 시도한다.
+pub fn send_frames_for(name: &str, frame: &[u8], duration: Duration) -> Result<u64> {
+    let deadline = Instant::now() + duration;
+    // 시계는 256개마다 확인
+    let (sent, result) = send_while(name, frame, true, |sent| sent % 256 != 0 || Instant::now() < deadline)?;
+    result.with_context(|| format!("Failed to send traffic on {} ({} sent)", name, sent))?;
+    Ok(sent)
+}
+
+/// `keep_going(보낸 수)`가 true인 동안 프레임 전송 (`retry_full`이면 ENOBUFS는 다시 시도)
+///
+/// 소켓을 열지 못하면 오류, 전송 중 실패하면 그때까지 보낸 수와 전송 오류를 반환한다.
+fn send_while<F>(name: &str, frame: &[u8], retry_full: bool, mut keep_going: F) -> Result<(u64, std::io::Result<()>)>
+where
+    F: FnMut(u64) -> bool,
+{
+    let ifindex = if_index(name)?;
+    if frame.len() < 14 {
+        return Err(anyhow!("Frame too short: {} bytes", frame.len()));
+    }
+
+    // SAFETY: sockaddr_ll은 모든 필드가 0이어도 유효한 C 구조체다
+    let mut addr: libc::sockaddr_ll = unsafe { std::mem::zeroed() };
+    addr.sll_family = libc::AF_PACKET as u16;
+    addr.sll_protocol = (libc::ETH_P_IP as u16).to_be();
+    addr.sll_ifindex = ifindex as i32;
+    addr.sll_halen = 6;
+    addr.sll_addr[..6].copy_from_slice(&frame[..6]);
+
+    // SAFETY: 소켓은 이 함수 안에서만 쓰고 닫으며, frame과 addr은 호출 동안 유효하다
+    unsafe {
+        let fd = libc::socket(libc::AF_PACKET, libc::SOCK_RAW | libc::SOCK_CLOEXEC, 0);
+        if fd < 0 {
+            return Err(std::io::Error::last_os_error()).context("Failed to open raw packet socket");
+        }
+
+        let mut sent = 0;
+        let mut result = Ok(());
+        while keep_going(sent) {
+            let written = libc::sendto(fd, frame.as_ptr() as *const libc::c_void, frame.len(), 0,
+                &addr as *const libc::sockaddr_ll as *const libc::sockaddr,
+                std::mem::size_of::<libc::sockaddr_ll>() as libc::socklen_t);
+            if written < 0 {
+                let error = std::io::Error::last_os_error();
+                if retry_full && error.raw_os_error() == Some(libc::ENOBUFS) {
+                    std::thread::yield_now();
+                    continue;
+                }
+                result = Err(error);
+                break;
+            }
+            sent += 1;
+        }
+        libc::close(fd);
+
+        Ok((sent, result))
+    }
+}
+
+/// veth 쌍 생성 후 두 링크를 올림 (`ip link add`, CAP_NET_ADMIN 필요)
+pub fn create_veth_pair(name: &str, peer: &str) -> Result<()> {
+    run_ip(&["link", "add", name, "type", "veth", "peer", "name", peer])?;
+    run_ip(&["link", "set", name, "up"])?;
+    run_ip(&["link", "set", peer, "up"])
+}
+
+/// 링크 삭제 (veth는 짝도 함께 사라짐)
+pub fn delete_link(name: &str) -> Result<()> {
+    run_ip(&["link", "del", name])
+}
+
+fn run_ip(args: &[&str]) -> Result<()> {
+    let output = Command::new("ip").args(args).output()
+        .context("Failed to run ip")?;
+    if !output.status.success() {
+        return Err(anyhow!("ip {} failed: {}", args.join(" "), String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_xdp_interfaces() {
+        let output = "\
+1: lo: <LOOPBACK,UP,LOWER_UP> mtu 65536 qdisc noqueue state UNKNOWN mode DEFAULT group default qlen 1000\\    link/loopback 00:00:00:00:00:00 brd 00:00:00:00:00:00
+2: eth0: <BROADCAST,MULTICAST,UP,LOWER_UP> mtu 1500 xdpgeneric qdisc fq_codel state UP mode DEFAULT group default qlen 1000\\    link/ether 52:54:00:12:34:56 brd ff:ff:ff:ff:ff:ff
+5: veth0@if4: <BROADCAST,MULTICAST,UP,LOWER_UP> mtu 1500 xdp qdisc noqueue state UP mode DEFAULT group default qlen 1000\\    link/ether aa:bb:cc:dd:ee:ff brd ff:ff:ff:ff:ff:ff link-netnsid 0
+";
+        assert_eq!(parse_xdp_interfaces(output), vec!["eth0", "veth0"]);
+        assert!(parse_xdp_interfaces("").is_empty());
+    }
+
+    #[test]
+    fn test_driver_from_sysfs() {
+        let dir = std::env::temp_dir().join(format!("swift-guard-netif-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let link = dir.join("driver");
+        let _ = fs::remove_file(&link);
+        std::os::unix::fs::symlink("../../../bus/pci/drivers/ixgbe", &link).unwrap();
+
+        assert_eq!(driver_from_sysfs(&link).as_deref(), Some("ixgbe"));
+        assert_eq!(driver_from_sysfs(&dir.join("missing")), None);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,570 @@
+//! nftables 백엔드 모듈
+//! XDP 프로그램을 연결할 수 없는 인터페이스의 필터 규칙을 nftables 테이블로 설치
+
+use anyhow::{anyhow, Context, Result};
+use log::{debug, warn};
+use serde_json::Value;
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::Write as _;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use swift_guard::api::RuleStats;
+use swift_guard::utils;
+
+use crate::backend::{BackendKind, RuleBackend};
+use crate::maps::FilterRule;
+use crate::rulecache::RuleCache;
+
+/// 데몬이 관리하는 테이블 (netdev 패밀리, 인터페이스별 ingress 체인)
+const TABLE: &str = "netdev swift_guard";
+
+/// 규칙 주석 접두사 (주석의 레이블로 카운터를 규칙에 대응시킴)
+const COMMENT_PREFIX: &str = "swift-guard:";
+
+/// ingress 훅 우선순위
+const HOOK_PRIORITY: i32 = -500;
+
+/// 체인별 레이블 카운터 ((체인, 레이블) -> (packets, bytes))
+pub type Counters = HashMap<(String, String), (u64, u64)>;
+
+/// nft 명령 실행 (테스트에서 대체 가능)
+pub trait NftRunner {
+    /// 규칙 스크립트를 하나의 트랜잭션으로 적용 (`nft -f -`)
+    fn apply(&self, script: &str) -> Result<()>;
+    /// 관리 테이블을 JSON으로 조회
+    fn list_table(&self) -> Result<String>;
+}
+
+/// 시스템 nft 명령
+pub struct NftCommand;
+
+impl NftRunner for NftCommand {
+    fn apply(&self, script: &str) -> Result<()> {
+        let mut child = Command::new("nft")
+            .args(["-f", "-"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .context("Failed to run nft")?;
+
+        child.stdin.take()
+            .ok_or_else(|| anyhow!("Failed to open nft stdin"))?
+            .write_all(script.as_bytes())
+            .context("Failed to write nft script")?;
+
+        let output = child.wait_with_output().context("Failed to wait for nft")?;
+        if !output.status.success() {
+            return Err(anyhow!("nft rejected the ruleset: {}", String::from_utf8_lossy(&output.stderr).trim()));
+        }
+
+        Ok(())
+    }
+
+    fn list_table(&self) -> Result<String> {
+        let output = Command::new("nft")
+            .args(["-j", "list", "table"])
+            .args(TABLE.split(' '))
+            .output()
+            .context("Failed to run nft")?;
+
+        if !output.status.success() {
+            return Err(anyhow!("nft list failed: {}", String::from_utf8_lossy(&output.stderr).trim()));
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+}
+
+/// 인터페이스 이름 검증 (nft 스크립트에 따옴표로 들어가므로 안전한 문자만 허용)
+fn validate_ifname(name: &str) -> Result<()> {
+    let valid = !name.is_empty() && name.len() < 16
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+
+    if valid {
+        Ok(())
+    } else {
+        Err(anyhow!("Invalid interface name for nftables: {:?}", name))
+    }
+}
+
+/// 인터페이스의 ingress 체인 이름
+fn chain_name(interface: &str) -> String {
+    let name: String = interface.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    format!("ingress_{}", name)
+}
+
+/// 주소/프리픽스를 nft 표기로 변환 (호스트 비트는 지움)
+fn prefix_expr((addr, prefix): (u32, u32)) -> Option<String> {
+    match prefix {
+        0 => None,
+        prefix if prefix >= 32 => Some(utils::ipv4_to_string(addr)),
+        _ => {
+            let network = addr & (u32::MAX << (32 - prefix));
+            Some(format!("{}/{}", utils::ipv4_to_string(network), prefix))
+        }
+    }
+}
+
+/// 포트 범위나 목록을 nft 표기로 변환 (목록은 익명 집합, 전체 범위면 None)
+fn port_expr(min: u16, max: u16, list: &[u16]) -> Option<String> {
+    match (list, min, max) {
+        ([], 0, 65535) => None,
+        ([], min, max) if min == max => Some(min.to_string()),
+        ([], min, max) => Some(format!("{}-{}", min, max)),
+        ([port], ..) => Some(port.to_string()),
+        (ports, ..) => Some(format!("{{ {} }}", ports.iter().map(|port| port.to_string()).collect::<Vec<_>>().join(", "))),
+    }
+}
+
+/// TCP 플래그 비트맵을 nft 플래그 목록으로 변환
+fn tcp_flags_expr(flags: u8) -> String {
+    const NAMES: [(u8, &str); 6] = [
+        (0x01, "fin"), (0x02, "syn"), (0x04, "rst"),
+        (0x08, "psh"), (0x10, "ack"), (0x20, "urg"),
+    ];
+
+    NAMES.iter()
+        .filter(|(bit, _)| flags & bit != 0)
+        .map(|(_, name)| *name)
+        .collect::<Vec<_>>()
+        .join(" | ")
+}
+
+/// 필터 규칙을 nft 규칙 문장으로 변환
+///
+/// XDP 프로그램과 같이 TCP 플래그는 지정한 플래그가 모두 설정된 TCP 패킷에만
+/// 적용한다. count 액션과 CPU 리디렉션, 대상이 사라진 리디렉션은 카운터만 남긴다.
+fn rule_statement(rule: &FilterRule, counter: (u64, u64)) -> String {
+    let mut parts = Vec::new();
+
+    // 호스트 이름 규칙은 해석한 주소 집합으로 매치
+    let sources: Vec<String> = rule.src_prefixes().into_iter().filter_map(prefix_expr).collect();
+    match sources.as_slice() {
+        [] => {}
+        [source] => parts.push(format!("ip saddr {}", source)),
+        sources => parts.push(format!("ip saddr {{ {} }}", sources.join(", "))),
+    }
+    if let Some(expr) = rule.dst_ip.and_then(prefix_expr) {
+        parts.push(format!("ip daddr {}", expr));
+    }
+
+    let sport = port_expr(rule.src_port_min, rule.src_port_max, &rule.src_port_list);
+    let dport = port_expr(rule.dst_port_min, rule.dst_port_max, &rule.dst_port_list);
+    let has_ports = sport.is_some() || dport.is_some();
+
+    let transport = match rule.protocol {
+        6 => "tcp",
+        17 => "udp",
+        255 if has_ports => {
+            parts.push("meta l4proto { tcp, udp }".to_string());
+            "th"
+        }
+        255 => "",
+        other => {
+            parts.push(format!("meta l4proto {}", other));
+            "th"
+        }
+    };
+
+    // 포트나 TCP 플래그 매치가 없으면 프로토콜을 직접 지정
+    let matches_flags = rule.protocol == 6 && rule.tcp_flags != 0;
+    if (rule.protocol == 6 || rule.protocol == 17) && !has_ports && !matches_flags {
+        parts.push(format!("meta l4proto {}", transport));
+    }
+    if let Some(port) = sport {
+        parts.push(format!("{} sport {}", transport, port));
+    }
+    if let Some(port) = dport {
+        parts.push(format!("{} dport {}", transport, port));
+    }
+
+    if matches_flags {
+        let flags = tcp_flags_expr(rule.tcp_flags);
+        parts.push(format!("tcp flags & ({}) == {}", flags, flags));
+    }
+
+    parts.push(format!("counter packets {} bytes {}", counter.0, counter.1));
+
+    match rule.kernel_action() {
+        1 => parts.push("accept".to_string()),
+        2 => parts.push("drop".to_string()),
+        3 if rule.redirect_cpu.is_none() && !rule.degraded => {
+            match rule.redirect_ifname.as_deref().filter(|name| validate_ifname(name).is_ok()) {
+                Some(ifname) => parts.push(format!("fwd to \"{}\"", ifname)),
+                None => warn!("Rule {} has no usable redirect target for nftables", rule.label),
+            }
+        }
+        _ => {}
+    }
+
+    parts.push(format!("comment \"{}{}\"", COMMENT_PREFIX, rule.label));
+    parts.join(" ")
+}
+
+/// 관리 테이블 전체를 다시 만드는 nft 스크립트 생성
+///
+/// 테이블 선언 후 삭제하고 다시 정의하므로 `nft -f`로 적용하면 기존 테이블
+/// 유무와 관계없이 하나의 트랜잭션으로 교체된다. 인터페이스가 없으면 테이블만
+/// 삭제한다. 규칙은 우선순위가 높은 순서로 배치한다.
+pub fn render_ruleset(interfaces: &BTreeSet<String>, rules: &RuleCache, counters: &Counters) -> String {
+    let mut script = format!("table {}\ndelete table {}\n", TABLE, TABLE);
+    if interfaces.is_empty() {
+        return script;
+    }
+
+    let _ = writeln!(script, "table {} {{", TABLE);
+    for interface in interfaces {
+        let chain = chain_name(interface);
+        let _ = writeln!(script, "\tchain {} {{", chain);
+        let _ = writeln!(script, "\t\ttype filter hook ingress device \"{}\" priority {}; policy accept;",
+            interface, HOOK_PRIORITY);
+
+        for rule in rules.by_priority() {
+            let counter = counters.get(&(chain.clone(), rule.label.clone()))
+                .copied()
+                .unwrap_or_default();
+            let _ = writeln!(script, "\t\t{}", rule_statement(rule, counter));
+        }
+
+        script.push_str("\t}\n");
+    }
+    script.push_str("}\n");
+
+    script
+}
+
+/// `nft -j list table` 출력에서 레이블 주석이 달린 규칙의 카운터 추출
+pub fn parse_counters(json: &str) -> Result<Counters> {
+    let document: Value = serde_json::from_str(json)
+        .context("Invalid nft JSON output")?;
+    let items = document.get("nftables")
+        .and_then(Value::as_array)
+        .ok_or_else(|| anyhow!("Missing 'nftables' array in nft output"))?;
+
+    let mut counters = Counters::new();
+    for rule in items.iter().filter_map(|item| item.get("rule")) {
+        let chain = rule.get("chain").and_then(Value::as_str);
+        let label = rule.get("comment")
+            .and_then(Value::as_str)
+            .and_then(|comment| comment.strip_prefix(COMMENT_PREFIX));
+        let counter = rule.get("expr")
+            .and_then(Value::as_array)
+            .and_then(|exprs| exprs.iter().find_map(|expr| expr.get("counter")));
+
+        if let (Some(chain), Some(label), Some(counter)) = (chain, label, counter) {
+            let field = |name: &str| counter.get(name).and_then(Value::as_u64).unwrap_or(0);
+            counters.insert((chain.to_string(), label.to_string()), (field("packets"), field("bytes")));
+        }
+    }
+
+    Ok(counters)
+}
+
+/// nftables 규칙 설치 백엔드
+///
+/// 규칙이 바뀔 때마다 관리 테이블 전체를 다시 생성해 원자적으로 교체하며,
+/// 교체 직전의 카운터를 새 규칙에 이어붙인다. 조회와 교체 사이에 매치된
+/// 패킷은 카운터에서 빠질 수 있다.
+pub struct NftablesBackend {
+    /// 대체 백엔드로 동작 중인 인터페이스
+    interfaces: BTreeSet<String>,
+    runner: Box<dyn NftRunner + Send>,
+    /// 관리 테이블이 설치되어 있는지 (카운터 조회 가능 여부)
+    applied: bool,
+}
+
+impl NftablesBackend {
+    pub fn new() -> Self {
+        Self::with_runner(Box::new(NftCommand))
+    }
+
+    /// 임의의 nft 실행기로 생성 (테스트용)
+    pub fn with_runner(runner: Box<dyn NftRunner + Send>) -> Self {
+        Self {
+            interfaces: BTreeSet::new(),
+            runner,
+            applied: false,
+        }
+    }
+
+    /// 대체 백엔드로 동작 중인 인터페이스
+    pub fn interfaces(&self) -> impl Iterator<Item = &String> {
+        self.interfaces.iter()
+    }
+
+    /// 인터페이스에 규칙 설치 (실패하면 인터페이스를 추가하지 않음)
+    pub fn add_interface(&mut self, interface: &str, rules: &RuleCache) -> Result<()> {
+        validate_ifname(interface)?;
+
+        if self.interfaces.insert(interface.to_string()) {
+            if let Err(e) = self.sync(rules) {
+                self.interfaces.remove(interface);
+                return Err(e);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 인터페이스의 체인 제거 (관리 중이 아니면 false)
+    pub fn remove_interface(&mut self, interface: &str, rules: &RuleCache) -> Result<bool> {
+        if !self.interfaces.remove(interface) {
+            return Ok(false);
+        }
+
+        if let Err(e) = self.sync(rules) {
+            self.interfaces.insert(interface.to_string());
+            return Err(e);
+        }
+
+        Ok(true)
+    }
+
+    /// 현재 카운터 조회
+    fn counters(&self) -> Result<Counters> {
+        if !self.applied {
+            return Ok(Counters::new());
+        }
+
+        parse_counters(&self.runner.list_table()?)
+    }
+
+    /// 관리 테이블을 규칙 목록에 맞게 다시 생성
+    pub fn sync(&mut self, rules: &RuleCache) -> Result<()> {
+        if self.interfaces.is_empty() && !self.applied {
+            return Ok(());
+        }
+
+        let counters = self.counters().unwrap_or_else(|e| {
+            warn!("Failed to read nftables counters, counters will restart: {:#}", e);
+            Counters::new()
+        });
+
+        let script = render_ruleset(&self.interfaces, rules, &counters);
+        debug!("Applying nftables ruleset:\n{}", script);
+
+        self.runner.apply(&script)
+            .context("Failed to apply nftables ruleset")?;
+        self.applied = !self.interfaces.is_empty();
+
+        Ok(())
+    }
+}
+
+impl RuleBackend for NftablesBackend {
+    fn kind(&self) -> BackendKind {
+        BackendKind::Nftables
+    }
+
+    fn install(&mut self, _rule: &FilterRule, rules: &RuleCache) -> Result<()> {
+        self.sync(rules)
+    }
+
+    fn remove(&mut self, _rule: &FilterRule, rules: &RuleCache) -> Result<()> {
+        self.sync(rules)
+    }
+
+    fn replace(&mut self, _old: &FilterRule, _new: &FilterRule, rules: &RuleCache) -> Result<()> {
+        self.sync(rules)
+    }
+
+    fn stats(&self, _rules: &RuleCache) -> Result<HashMap<String, RuleStats>> {
+        let mut stats: HashMap<String, RuleStats> = HashMap::new();
+
+        // 같은 규칙이 인터페이스 체인마다 있으므로 합산
+        for ((_, label), (packets, bytes)) in self.counters()? {
+            let entry = stats.entry(label).or_default();
+            entry.packets += packets;
+            entry.bytes += bytes;
+        }
+
+        Ok(stats)
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::dns::HostEndpoint;
+    use std::sync::{Arc, Mutex};
+
+    /// 적용된 스크립트를 기록하고 고정된 목록을 돌려주는 nft 실행기
+    #[derive(Clone, Default)]
+    pub struct RecordingNft {
+        pub scripts: Arc<Mutex<Vec<String>>>,
+        pub listing: Arc<Mutex<String>>,
+        pub fail: Arc<Mutex<bool>>,
+    }
+
+    impl NftRunner for RecordingNft {
+        fn apply(&self, script: &str) -> Result<()> {
+            if *self.fail.lock().unwrap() {
+                return Err(anyhow!("nft rejected the ruleset"));
+            }
+            self.scripts.lock().unwrap().push(script.to_string());
+            Ok(())
+        }
+
+        fn list_table(&self) -> Result<String> {
+            Ok(self.listing.lock().unwrap().clone())
+        }
+    }
+
+    fn rule(label: &str) -> FilterRule {
+        FilterRule {
+            action: 2,
+            label: label.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_rule_statement() {
+        let ssh = FilterRule {
+            src_ip: Some((0x0A010203, 8)),
+            protocol: 6,
+            dst_port_min: 22,
+            dst_port_max: 22,
+            tcp_flags: 0x12,
+            ..rule("block-ssh")
+        };
+        assert_eq!(rule_statement(&ssh, (3, 180)),
+            "ip saddr 10.0.0.0/8 tcp dport 22 tcp flags & (syn | ack) == syn | ack \
+             counter packets 3 bytes 180 drop comment \"swift-guard:block-ssh\"");
+
+        let any_port = FilterRule { dst_port_min: 8000, dst_port_max: 8080, action: 1, ..rule("web") };
+        assert_eq!(rule_statement(&any_port, (0, 0)),
+            "meta l4proto { tcp, udp } th dport 8000-8080 counter packets 0 bytes 0 accept comment \"swift-guard:web\"");
+
+        let udp = FilterRule { protocol: 17, dst_ip: Some((0xC0A80101, 32)), action: 4, ..rule("dns") };
+        assert_eq!(rule_statement(&udp, (0, 0)),
+            "ip daddr 192.168.1.1 meta l4proto udp counter packets 0 bytes 0 comment \"swift-guard:dns\"");
+
+        let reflectors = FilterRule { protocol: 17, src_port_list: vec![19, 53, 11211], action: 4, ..rule("reflectors") };
+        assert_eq!(rule_statement(&reflectors, (0, 0)),
+            "udp sport { 19, 53, 11211 } counter packets 0 bytes 0 comment \"swift-guard:reflectors\"");
+
+        let web = FilterRule { protocol: 6, dst_port_list: vec![80, 443, 8080], action: 1, ..rule("web") };
+        assert_eq!(rule_statement(&web, (0, 0)),
+            "tcp dport { 80, 443, 8080 } counter packets 0 bytes 0 accept comment \"swift-guard:web\"");
+
+        let redirect = FilterRule {
+            action: 3,
+            redirect_ifindex: 7,
+            redirect_ifname: Some("wasm0".to_string()),
+            ..rule("inspect")
+        };
+        assert!(rule_statement(&redirect, (0, 0)).contains("fwd to \"wasm0\""));
+
+        // 대상이 사라진 리디렉션과 CPU 리디렉션은 카운터만 남김
+        let degraded = FilterRule { degraded: true, ..redirect.clone() };
+        assert!(!rule_statement(&degraded, (0, 0)).contains("fwd"));
+        let cpu = FilterRule { redirect_cpu: Some(1), redirect_ifname: None, ..redirect };
+        assert!(!rule_statement(&cpu, (0, 0)).contains("fwd"));
+
+        // 호스트 이름 규칙은 해석한 주소 집합
+        let host = FilterRule {
+            src_host: Some(HostEndpoint::new("api.example.com", vec![0xC0A80002, 0xC0A80001])),
+            ..rule("api")
+        };
+        assert!(rule_statement(&host, (0, 0)).starts_with("ip saddr { 192.168.0.1, 192.168.0.2 } counter"));
+    }
+
+    #[test]
+    fn test_render_ruleset() {
+        let interfaces: BTreeSet<String> = ["eth0", "veth-1"].iter().map(|s| s.to_string()).collect();
+        let rules: RuleCache = vec![
+            FilterRule { priority: 10, ..rule("low") },
+            FilterRule { priority: 100, ..rule("high") },
+        ].into_iter().collect();
+        let mut counters = Counters::new();
+        counters.insert(("ingress_veth_1".to_string(), "low".to_string()), (5, 300));
+
+        let script = render_ruleset(&interfaces, &rules, &counters);
+        assert_eq!(script, "\
+table netdev swift_guard
+delete table netdev swift_guard
+table netdev swift_guard {
+\tchain ingress_eth0 {
+\t\ttype filter hook ingress device \"eth0\" priority -500; policy accept;
+\t\tcounter packets 0 bytes 0 drop comment \"swift-guard:high\"
+\t\tcounter packets 0 bytes 0 drop comment \"swift-guard:low\"
+\t}
+\tchain ingress_veth_1 {
+\t\ttype filter hook ingress device \"veth-1\" priority -500; policy accept;
+\t\tcounter packets 0 bytes 0 drop comment \"swift-guard:high\"
+\t\tcounter packets 5 bytes 300 drop comment \"swift-guard:low\"
+\t}
+}
+");
+
+        // 인터페이스가 없으면 테이블만 삭제
+        assert_eq!(render_ruleset(&BTreeSet::new(), &rules, &counters),
+            "table netdev swift_guard\ndelete table netdev swift_guard\n");
+    }
+
+    pub const LISTING: &str = r#"{"nftables": [
+        {"metainfo": {"version": "1.0.6", "json_schema_version": 1}},
+        {"table": {"family": "netdev", "name": "swift_guard", "handle": 3}},
+        {"chain": {"family": "netdev", "table": "swift_guard", "name": "ingress_eth0", "handle": 1}},
+        {"rule": {"family": "netdev", "table": "swift_guard", "chain": "ingress_eth0", "handle": 2,
+                  "comment": "swift-guard:web",
+                  "expr": [{"counter": {"packets": 7, "bytes": 420}}, {"drop": null}]}},
+        {"rule": {"family": "netdev", "table": "swift_guard", "chain": "ingress_eth1", "handle": 3,
+                  "comment": "swift-guard:web",
+                  "expr": [{"counter": {"packets": 3, "bytes": 180}}, {"drop": null}]}},
+        {"rule": {"family": "netdev", "table": "swift_guard", "chain": "ingress_eth0", "handle": 4,
+                  "comment": "someone else",
+                  "expr": [{"counter": {"packets": 1, "bytes": 60}}]}}
+    ]}"#;
+
+    #[test]
+    fn test_parse_counters() {
+        let counters = parse_counters(LISTING).unwrap();
+        assert_eq!(counters.len(), 2);
+        assert_eq!(counters[&("ingress_eth0".to_string(), "web".to_string())], (7, 420));
+        assert_eq!(counters[&("ingress_eth1".to_string(), "web".to_string())], (3, 180));
+
+        assert!(parse_counters("not json").is_err());
+        assert!(parse_counters("{}").is_err());
+    }
+
+    #[test]
+    fn test_backend_sync_and_stats() {
+        let nft = RecordingNft::default();
+        let mut backend = NftablesBackend::with_runner(Box::new(nft.clone()));
+        let rules: RuleCache = vec![rule("web")].into_iter().collect();
+        let web = rules.get("web").unwrap();
+
+        // 인터페이스가 없으면 아무것도 적용하지 않음
+        backend.install(web, &rules).unwrap();
+        assert!(nft.scripts.lock().unwrap().is_empty());
+        assert!(backend.stats(&rules).unwrap().is_empty());
+
+        assert!(backend.add_interface("bad\"name", &rules).is_err());
+        backend.add_interface("eth0", &rules).unwrap();
+        assert_eq!(nft.scripts.lock().unwrap().len(), 1);
+
+        // 카운터는 인터페이스 체인을 합산하고 재생성 시 이어받음
+        *nft.listing.lock().unwrap() = LISTING.to_string();
+        assert_eq!(backend.stats(&rules).unwrap()["web"].packets, 10);
+        backend.remove(web, &rules).unwrap();
+        assert!(nft.scripts.lock().unwrap()[1].contains("counter packets 7 bytes 420"));
+
+        // 적용 실패 시 인터페이스를 추가하지 않음
+        *nft.fail.lock().unwrap() = true;
+        assert!(backend.add_interface("eth1", &rules).is_err());
+        assert_eq!(backend.interfaces().count(), 1);
+        *nft.fail.lock().unwrap() = false;
+
+        // 마지막 인터페이스를 제거하면 테이블 삭제
+        assert!(backend.remove_interface("eth0", &rules).unwrap());
+        assert!(!backend.remove_interface("eth0", &rules).unwrap());
+        assert!(nft.scripts.lock().unwrap().last().unwrap().ends_with("delete table netdev swift_guard\n"));
+        assert!(backend.stats(&rules).unwrap().is_empty());
+    }
+}
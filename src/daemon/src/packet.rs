@@ -0,0 +1,437 @@
+//! 패킷 생성 및 파싱 모듈
+//! 자가 진단과 테스트에 쓰는 이더넷/IPv4/UDP 프레임 생성과 사용자 공간 헤더 파싱
+//!
+//! 파서는 WASM 검사 경로에서 커널이 넘긴 임의의 바이트를 다루므로 모든 헤더 접근을
+//! 길이 확인 뒤에 하고, 잘렸거나 헤더 길이, 옵션이 맞지 않으면 `None`을 돌려준다.
+
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+/// 이더넷 헤더 길이
+pub const ETH_HEADER_LEN: usize = 14;
+
+/// 옵션 없는 IPv4 헤더 길이
+pub const IPV4_HEADER_LEN: usize = 20;
+
+/// UDP 헤더 길이
+pub const UDP_HEADER_LEN: usize = 8;
+
+/// IPv6 고정 헤더 길이
+pub const IPV6_HEADER_LEN: usize = 40;
+
+/// 옵션 없는 TCP 헤더 길이
+pub const TCP_HEADER_LEN: usize = 20;
+
+const ETHERTYPE_IPV4: u16 = 0x0800;
+const ETHERTYPE_IPV6: u16 = 0x86dd;
+const ETHERTYPE_VLAN: u16 = 0x8100;
+const ETHERTYPE_QINQ: u16 = 0x88a8;
+const IPPROTO_TCP: u8 = 6;
+const IPPROTO_UDP: u8 = 17;
+
+/// UDP 프레임 (이더넷 + IPv4 + UDP)
+///
+/// 주소는 `utils::parse_ip_prefix`와 같은 호스트 바이트 순서 u32다 (0xC0A80101 = 192.168.1.1).
+/// MAC 주소는 기본값 0으로, 루프백 인터페이스의 주소와 같아 수신 경로에서 자기 호스트 패킷으로 처리된다.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UdpFrame {
+    pub src_mac: [u8; 6],
+    pub dst_mac: [u8; 6],
+    pub src_ip: u32,
+    pub dst_ip: u32,
+    pub src_port: u16,
+    pub dst_port: u16,
+    pub ttl: u8,
+    pub payload: Vec<u8>,
+}
+
+impl UdpFrame {
+    /// 새 프레임 (MAC 0, TTL 64, 빈 페이로드)
+    pub fn new(src_ip: u32, dst_ip: u32, src_port: u16, dst_port: u16) -> Self {
+        Self {
+            src_mac: [0; 6],
+            dst_mac: [0; 6],
+            src_ip,
+            dst_ip,
+            src_port,
+            dst_port,
+            ttl: 64,
+            payload: Vec::new(),
+        }
+    }
+
+    /// 페이로드
+    pub fn with_payload(mut self, payload: &[u8]) -> Self {
+        self.payload = payload.to_vec();
+        self
+    }
+
+    /// 프레임 바이트 생성 (IPv4 헤더와 UDP 체크섬 포함)
+    pub fn build(&self) -> Vec<u8> {
+        let udp_len = UDP_HEADER_LEN + self.payload.len();
+        let ip_len = IPV4_HEADER_LEN + udp_len;
+        let mut frame = Vec::with_capacity(ETH_HEADER_LEN + ip_len);
+
+        // 이더넷
+        frame.extend_from_slice(&self.dst_mac);
+        frame.extend_from_slice(&self.src_mac);
+        frame.extend_from_slice(&ETHERTYPE_IPV4.to_be_bytes());
+
+        // IPv4 (DF, 식별자 0)
+        let mut ip = [0u8; IPV4_HEADER_LEN];
+        ip[0] = 0x45;
+        ip[2..4].copy_from_slice(&(ip_len as u16).to_be_bytes());
+        ip[6..8].copy_from_slice(&0x4000u16.to_be_bytes());
+        ip[8] = self.ttl;
+        ip[9] = IPPROTO_UDP;
+        ip[12..16].copy_from_slice(&self.src_ip.to_be_bytes());
+        ip[16..20].copy_from_slice(&self.dst_ip.to_be_bytes());
+        let ip_checksum = checksum(&ip);
+        ip[10..12].copy_from_slice(&ip_checksum.to_be_bytes());
+        frame.extend_from_slice(&ip);
+
+        // UDP (체크섬은 의사 헤더 포함, 결과가 0이면 0xffff)
+        let mut udp = Vec::with_capacity(udp_len);
+        udp.extend_from_slice(&self.src_port.to_be_bytes());
+        udp.extend_from_slice(&self.dst_port.to_be_bytes());
+        udp.extend_from_slice(&(udp_len as u16).to_be_bytes());
+        udp.extend_from_slice(&[0, 0]);
+        udp.extend_from_slice(&self.payload);
+
+        let mut pseudo = Vec::with_capacity(12 + udp.len());
+        pseudo.extend_from_slice(&self.src_ip.to_be_bytes());
+        pseudo.extend_from_slice(&self.dst_ip.to_be_bytes());
+        pseudo.extend_from_slice(&[0, IPPROTO_UDP]);
+        pseudo.extend_from_slice(&(udp_len as u16).to_be_bytes());
+        pseudo.extend_from_slice(&udp);
+        let udp_checksum = match checksum(&pseudo) {
+            0 => 0xffff,
+            sum => sum,
+        };
+        udp[6..8].copy_from_slice(&udp_checksum.to_be_bytes());
+        frame.extend_from_slice(&udp);
+
+        frame
+    }
+}
+
+/// 파싱한 패킷 헤더
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PacketHeaders {
+    pub src_ip: IpAddr,
+    pub dst_ip: IpAddr,
+    /// IP 프로토콜 번호 (IPv6는 고정 헤더의 다음 헤더)
+    pub protocol: u8,
+    /// TCP/UDP 헤더 (다른 프로토콜, 첫 조각이 아닌 조각, 잘렸거나 잘못된 L4 헤더면 없음)
+    pub transport: Option<Transport>,
+}
+
+/// TCP/UDP 헤더
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Transport {
+    pub src_port: u16,
+    pub dst_port: u16,
+    /// TCP 플래그 (UDP는 없음)
+    pub tcp_flags: Option<u8>,
+    /// 프레임 안에서 L4 페이로드가 시작하는 위치
+    pub payload_offset: usize,
+    /// L4 페이로드 길이 (이더넷 패딩 제외, 잘린 프레임이면 남은 만큼)
+    pub payload_len: usize,
+}
+
+fn be16(data: &[u8], offset: usize) -> Option<u16> {
+    let bytes = data.get(offset..offset.checked_add(2)?)?;
+    Some(u16::from_be_bytes([bytes[0], bytes[1]]))
+}
+
+/// IP/TCP 옵션 영역이 온전한지 (EOL과 NOP 외의 옵션은 길이가 2 이상이고 영역 안에서 끝남)
+fn options_valid(options: &[u8]) -> bool {
+    let mut offset = 0;
+    while let Some(&kind) = options.get(offset) {
+        match kind {
+            0 => return true,
+            1 => offset += 1,
+            _ => match options.get(offset + 1) {
+                Some(&len) if len >= 2 && offset + len as usize <= options.len() => offset += len as usize,
+                _ => return false,
+            },
+        }
+    }
+    true
+}
+
+/// 이더넷 프레임의 IP, TCP/UDP 헤더 파싱 (VLAN 태그 하나까지, IPv6 확장 헤더는 따라가지 않음)
+///
+/// IP 헤더가 잘렸거나 잘못되었으면 `None`, L4 헤더만 문제면 `transport`가 없다.
+pub fn parse_headers(frame: &[u8]) -> Option<PacketHeaders> {
+    let mut ether_type = be16(frame, 12)?;
+    let mut offset = ETH_HEADER_LEN;
+    if ether_type == ETHERTYPE_VLAN || ether_type == ETHERTYPE_QINQ {
+        ether_type = be16(frame, 16)?;
+        offset += 4;
+    }
+    let packet = frame.get(offset..)?;
+
+    let (src_ip, dst_ip, protocol, l4_start, end, first_fragment) = match ether_type {
+        ETHERTYPE_IPV4 => {
+            let version_ihl = *packet.first()?;
+            let header_len = (version_ihl & 0x0f) as usize * 4;
+            let total_len = be16(packet, 2)? as usize;
+            if version_ihl >> 4 != 4 || header_len < IPV4_HEADER_LEN || total_len < header_len
+                || !options_valid(packet.get(IPV4_HEADER_LEN..header_len)?) {
+                return None;
+            }
+            let address = |at: usize| packet.get(at..at + 4)
+                .map(|bytes| IpAddr::V4(Ipv4Addr::new(bytes[0], bytes[1], bytes[2], bytes[3])));
+            (address(12)?, address(16)?, *packet.get(9)?, header_len, total_len, be16(packet, 6)? & 0x1fff == 0)
+        },
+        ETHERTYPE_IPV6 => {
+            if *packet.first()? >> 4 != 6 {
+                return None;
+            }
+            let address = |at: usize| packet.get(at..at + 16)
+                .and_then(|bytes| <[u8; 16]>::try_from(bytes).ok())
+                .map(|bytes| IpAddr::V6(Ipv6Addr::from(bytes)));
+            (address(8)?, address(24)?, *packet.get(6)?, IPV6_HEADER_LEN,
+                IPV6_HEADER_LEN + be16(packet, 4)? as usize, true)
+        },
+        _ => return None,
+    };
+
+    // 이더넷 패딩은 빼고, 잘린 프레임이면 있는 만큼만
+    let transport = if first_fragment {
+        packet.get(l4_start..end.min(packet.len()))
+            .and_then(|l4| parse_transport(protocol, l4))
+            .map(|transport| Transport {
+                payload_offset: offset + l4_start + transport.payload_offset,
+                ..transport
+            })
+    } else {
+        None
+    };
+
+    Some(PacketHeaders { src_ip, dst_ip, protocol, transport })
+}
+
+/// L4 헤더 파싱 (`payload_offset`은 L4 헤더 기준)
+fn parse_transport(protocol: u8, l4: &[u8]) -> Option<Transport> {
+    let (header_len, tcp_flags) = match protocol {
+        IPPROTO_TCP => {
+            let header_len = (*l4.get(12)? >> 4) as usize * 4;
+            if header_len < TCP_HEADER_LEN || !options_valid(l4.get(TCP_HEADER_LEN..header_len)?) {
+                return None;
+            }
+            (header_len, Some(*l4.get(13)?))
+        },
+        IPPROTO_UDP => (UDP_HEADER_LEN, None),
+        _ => return None,
+    };
+
+    Some(Transport {
+        src_port: be16(l4, 0)?,
+        dst_port: be16(l4, 2)?,
+        tcp_flags,
+        payload_offset: header_len,
+        payload_len: l4.get(header_len..)?.len(),
+    })
+}
+
+/// 인터넷 체크섬 (RFC 1071, 홀수 길이는 끝에 0을 붙여 계산)
+pub fn checksum(data: &[u8]) -> u16 {
+    let mut sum: u32 = data.chunks(2)
+        .map(|chunk| u32::from(u16::from_be_bytes([chunk[0], chunk.get(1).copied().unwrap_or(0)])))
+        .sum();
+    while sum > 0xffff {
+        sum = (sum & 0xffff) + (sum >> 16);
+    }
+    !(sum as u16)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_udp_frame() {
+        let frame = UdpFrame {
+            src_mac: [2, 0, 0, 0, 0, 1],
+            dst_mac: [2, 0, 0, 0, 0, 2],
+            ..UdpFrame::new(0xC6120A0B, 0xC6130001, 40000, 9)
+        }
+        .with_payload(b"swift-guard")
+        .build();
+
+        assert_eq!(frame.len(), ETH_HEADER_LEN + IPV4_HEADER_LEN + UDP_HEADER_LEN + 11);
+        assert_eq!(&frame[..6], &[2, 0, 0, 0, 0, 2]);
+        assert_eq!(&frame[6..12], &[2, 0, 0, 0, 0, 1]);
+        assert_eq!(&frame[12..14], &[0x08, 0x00]);
+
+        let ip = &frame[ETH_HEADER_LEN..ETH_HEADER_LEN + IPV4_HEADER_LEN];
+        assert_eq!((ip[0], ip[8], ip[9]), (0x45, 64, 17));
+        assert_eq!(u16::from_be_bytes([ip[2], ip[3]]), 39);
+        assert_eq!(&ip[12..16], &[198, 18, 10, 11]);
+        assert_eq!(&ip[16..20], &[198, 19, 0, 1]);
+        // 체크섬을 포함한 헤더의 합은 0
+        assert_eq!(checksum(ip), 0);
+
+        let udp = &frame[ETH_HEADER_LEN + IPV4_HEADER_LEN..];
+        assert_eq!(u16::from_be_bytes([udp[0], udp[1]]), 40000);
+        assert_eq!(u16::from_be_bytes([udp[2], udp[3]]), 9);
+        assert_eq!(u16::from_be_bytes([udp[4], udp[5]]), 19);
+        assert_eq!(&udp[8..], b"swift-guard");
+
+        let mut pseudo = vec![198, 18, 10, 11, 198, 19, 0, 1, 0, 17, 0, 19];
+        pseudo.extend_from_slice(udp);
+        assert_eq!(checksum(&pseudo), 0);
+    }
+
+    #[test]
+    fn test_checksum() {
+        // RFC 1071 예제
+        assert_eq!(checksum(&[0x00, 0x01, 0xf2, 0x03, 0xf4, 0xf5, 0xf6, 0xf7]), !0xddf2);
+        assert_eq!(checksum(&[0x01]), !0x0100);
+        assert_eq!(checksum(&[]), 0xffff);
+    }
+
+    /// 이더넷 + IPv4 + TCP 프레임 (IP 옵션 `ip_options`, TCP 옵션 `tcp_options`)
+    fn tcp_frame(ip_options: &[u8], tcp_options: &[u8], payload: &[u8]) -> Vec<u8> {
+        let ip_len = IPV4_HEADER_LEN + ip_options.len();
+        let tcp_len = TCP_HEADER_LEN + tcp_options.len();
+
+        let mut frame = vec![0u8; 12];
+        frame.extend_from_slice(&ETHERTYPE_IPV4.to_be_bytes());
+        frame.extend_from_slice(&[0x40 | (ip_len / 4) as u8, 0]);
+        frame.extend_from_slice(&((ip_len + tcp_len + payload.len()) as u16).to_be_bytes());
+        frame.extend_from_slice(&[0, 0, 0x40, 0, 64, IPPROTO_TCP, 0, 0, 10, 0, 0, 1, 10, 0, 0, 2]);
+        frame.extend_from_slice(ip_options);
+        frame.extend_from_slice(&[0x9c, 0x40, 0, 80, 0, 0, 0, 0, 0, 0, 0, 0]);
+        frame.extend_from_slice(&[((tcp_len / 4) as u8) << 4, 0x18, 0xff, 0xff, 0, 0, 0, 0]);
+        frame.extend_from_slice(tcp_options);
+        frame.extend_from_slice(payload);
+        frame
+    }
+
+    /// 결정적인 의사 난수 (xorshift64)
+    struct Rng(u64);
+
+    impl Rng {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        fn below(&mut self, bound: usize) -> usize {
+            (self.next() % bound as u64) as usize
+        }
+    }
+
+    #[test]
+    fn test_parse_headers() {
+        let mut frame = UdpFrame::new(0xC0A8010A, 0xC0A80101, 40000, 53).with_payload(b"query").build();
+        frame.extend_from_slice(&[0xee; 8]);
+        let headers = parse_headers(&frame).unwrap();
+        assert_eq!(headers.src_ip, "192.168.1.10".parse::<IpAddr>().unwrap());
+        assert_eq!(headers.dst_ip, "192.168.1.1".parse::<IpAddr>().unwrap());
+        assert_eq!(headers.protocol, IPPROTO_UDP);
+        // 이더넷 패딩은 페이로드가 아님
+        assert_eq!(headers.transport, Some(Transport {
+            src_port: 40000,
+            dst_port: 53,
+            tcp_flags: None,
+            payload_offset: 42,
+            payload_len: 5,
+        }));
+
+        // 옵션이 있는 TCP
+        let frame = tcp_frame(&[1, 1, 1, 0], &[2, 4, 0x05, 0xb4, 1, 1, 4, 2], b"GET /");
+        let transport = parse_headers(&frame).unwrap().transport.unwrap();
+        assert_eq!((transport.src_port, transport.dst_port, transport.tcp_flags), (40000, 80, Some(0x18)));
+        assert_eq!(&frame[transport.payload_offset..][..transport.payload_len], b"GET /");
+
+        // VLAN 태그
+        let mut tagged = frame[..12].to_vec();
+        tagged.extend_from_slice(&[0x81, 0x00, 0x00, 0x64]);
+        tagged.extend_from_slice(&frame[12..]);
+        let transport = parse_headers(&tagged).unwrap().transport.unwrap();
+        assert_eq!(&tagged[transport.payload_offset..][..transport.payload_len], b"GET /");
+
+        // IPv6 UDP
+        let mut v6 = vec![0u8; 12];
+        v6.extend_from_slice(&ETHERTYPE_IPV6.to_be_bytes());
+        v6.extend_from_slice(&[0x60, 0, 0, 0, 0, 10, IPPROTO_UDP, 64]);
+        v6.extend_from_slice(&"2001:db8::1".parse::<Ipv6Addr>().unwrap().octets());
+        v6.extend_from_slice(&"2001:db8::2".parse::<Ipv6Addr>().unwrap().octets());
+        v6.extend_from_slice(&[0x13, 0x88, 0, 53, 0, 10, 0, 0, b'h', b'i']);
+        let headers = parse_headers(&v6).unwrap();
+        assert_eq!(headers.src_ip, "2001:db8::1".parse::<IpAddr>().unwrap());
+        assert_eq!(headers.transport.map(|transport| (transport.src_port, transport.payload_len)), Some((5000, 2)));
+    }
+
+    #[test]
+    fn test_parse_malformed_headers() {
+        let frame = tcp_frame(&[1, 1, 1, 0], &[2, 4, 0x05, 0xb4], b"GET / HTTP/1.1");
+
+        // 잘린 프레임: IP 헤더가 다 있어야 헤더를 돌려주고, TCP 헤더가 다 있어야 포트를 돌려줌
+        for len in 0..frame.len() {
+            let headers = parse_headers(&frame[..len]);
+            assert_eq!(headers.is_some(), len >= ETH_HEADER_LEN + 24, "{}", len);
+            assert_eq!(headers.and_then(|headers| headers.transport).is_some(), len >= ETH_HEADER_LEN + 24 + 24, "{}", len);
+        }
+
+        // IP 헤더 길이가 최소보다 작거나 전체 길이보다 큼
+        let mut short_ihl = frame.clone();
+        short_ihl[14] = 0x44;
+        assert_eq!(parse_headers(&short_ihl), None);
+        let mut short_total = frame.clone();
+        short_total[16..18].copy_from_slice(&10u16.to_be_bytes());
+        assert_eq!(parse_headers(&short_total), None);
+
+        // IP 옵션 길이 오류
+        for bad in [[7, 0, 0, 0], [7, 9, 0, 0], [1, 1, 1, 0x44]] {
+            assert_eq!(parse_headers(&tcp_frame(&bad, &[], b"")), None, "{:?}", bad);
+        }
+
+        // TCP 데이터 오프셋이나 옵션 길이 오류는 L4 정보만 없음
+        let mut short_tcp = frame.clone();
+        short_tcp[ETH_HEADER_LEN + 24 + 12] = 0x40;
+        assert_eq!(parse_headers(&short_tcp).unwrap().transport, None);
+        let headers = parse_headers(&tcp_frame(&[], &[2, 1, 0, 0], b"")).unwrap();
+        assert_eq!((headers.protocol, headers.transport), (IPPROTO_TCP, None));
+
+        // 첫 조각이 아닌 조각
+        let mut fragment = frame.clone();
+        fragment[20..22].copy_from_slice(&0x2001u16.to_be_bytes());
+        assert_eq!(parse_headers(&fragment).unwrap().transport, None);
+
+        // 이더넷 유형과 IP 버전이 다름
+        let mut mismatched = frame.clone();
+        mismatched[12..14].copy_from_slice(&ETHERTYPE_IPV6.to_be_bytes());
+        assert_eq!(parse_headers(&mismatched), None);
+    }
+
+    #[test]
+    fn test_parse_random_frames() {
+        let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+        let base = tcp_frame(&[1, 1, 1, 0], &[2, 4, 0x05, 0xb4], b"GET / HTTP/1.1");
+
+        for round in 0..40_000 {
+            let frame: Vec<u8> = if round % 2 == 0 {
+                (0..rng.below(128)).map(|_| rng.next() as u8).collect()
+            } else {
+                let mut mutated = base.clone();
+                for _ in 0..1 + rng.below(4) {
+                    let at = rng.below(mutated.len());
+                    mutated[at] = rng.next() as u8;
+                }
+                mutated.truncate(rng.below(mutated.len() + 1));
+                mutated
+            };
+
+            // 파싱한 위치는 항상 프레임 안
+            if let Some(transport) = parse_headers(&frame).and_then(|headers| headers.transport) {
+                assert!(transport.payload_offset + transport.payload_len <= frame.len());
+            }
+        }
+    }
+}
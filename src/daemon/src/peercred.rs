@@ -0,0 +1,284 @@
+//! 요청자 식별 모듈
+//! API 요청자 식별자와 Unix 소켓 연결의 자격 증명(SO_PEERCRED), uid의 사용자 이름 조회
+//!
+//! TCP 요청자는 피어 주소로, Unix 소켓 요청자는 커널이 알려주는 uid, gid, pid로 식별한다.
+//! 사용자 이름은 /etc/passwd에서 찾아 uid마다 캐시한다. 파일을 읽지 못해도 요청은 uid로
+//! 식별해 처리하며, 읽기 실패는 캐시하지 않아 다음 연결에서 다시 찾는다.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::net::IpAddr;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use tokio::net::UnixStream;
+
+use swift_guard::api::PeerIdentity;
+
+/// 사용자 이름을 찾는 passwd 파일
+pub const PASSWD_PATH: &str = "/etc/passwd";
+
+/// API 요청자
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Peer {
+    /// 요청 빈도 제한과 로그에 쓰는 식별자 ("peer:IP", "uid:1000")
+    id: String,
+    /// Unix 소켓 요청자의 자격 증명
+    identity: Option<PeerIdentity>,
+}
+
+impl Peer {
+    /// TCP 요청자 ("peer:IP")
+    pub fn remote(addr: IpAddr) -> Self {
+        Self::named(format!("peer:{}", addr))
+    }
+
+    /// 다른 전송 계층이 정한 식별자의 요청자 (gRPC)
+    pub fn named(id: impl Into<String>) -> Self {
+        Self { id: id.into(), identity: None }
+    }
+
+    /// Unix 소켓 요청자 ("uid:1000", 같은 사용자의 프로세스는 한 요청자로 봄)
+    pub fn local(identity: PeerIdentity) -> Self {
+        Self { id: format!("uid:{}", identity.uid), identity: Some(identity) }
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn identity(&self) -> Option<&PeerIdentity> {
+        self.identity.as_ref()
+    }
+
+    /// 규칙 작성자 기본값 (Unix 소켓 요청자는 "uid:1000(alice)")
+    pub fn creator(&self) -> String {
+        match self.identity.as_ref().and_then(|identity| identity.user.as_ref()) {
+            Some(user) => format!("{}({})", self.id, user),
+            None => self.id.clone(),
+        }
+    }
+
+    /// 감사 이벤트 행위자 (Unix 소켓 요청자는 "uid:1000(alice) pid:4242")
+    pub fn actor(&self) -> String {
+        match self.identity.as_ref().and_then(|identity| identity.pid) {
+            Some(pid) => format!("{} pid:{}", self.creator(), pid),
+            None => self.creator(),
+        }
+    }
+}
+
+impl fmt::Display for Peer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.id)
+    }
+}
+
+/// uid의 사용자 이름 조회
+pub trait UserLookup: fmt::Debug + Send + Sync {
+    /// 사용자 이름 (uid가 없으면 `Ok(None)`, 조회 자체가 실패하면 오류)
+    fn user_name(&self, uid: u32) -> std::io::Result<Option<String>>;
+}
+
+/// passwd 파일에서 조회
+#[derive(Debug, Clone)]
+pub struct PasswdFile {
+    path: PathBuf,
+}
+
+impl PasswdFile {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+impl Default for PasswdFile {
+    fn default() -> Self {
+        Self::new(PASSWD_PATH)
+    }
+}
+
+impl UserLookup for PasswdFile {
+    fn user_name(&self, uid: u32) -> std::io::Result<Option<String>> {
+        Ok(parse_passwd(&std::fs::read_to_string(&self.path)?, uid))
+    }
+}
+
+/// passwd 내용에서 uid의 사용자 이름 (`name:password:uid:gid:...`, 형식이 맞지 않는 줄은 건너뜀)
+pub fn parse_passwd(content: &str, uid: u32) -> Option<String> {
+    content.lines()
+        .filter(|line| !line.starts_with('#'))
+        .find_map(|line| {
+            let mut fields = line.split(':');
+            let name = fields.next().filter(|name| !name.is_empty())?;
+            let entry_uid = fields.nth(1)?.parse::<u32>().ok()?;
+            (entry_uid == uid).then(|| name.to_string())
+        })
+}
+
+/// uid별 사용자 이름 캐시
+///
+/// passwd에 없는 uid도 캐시한다. 조회가 실패하면 이름 없이 진행하고 캐시하지 않는다.
+#[derive(Debug)]
+pub struct UserCache {
+    lookup: Box<dyn UserLookup>,
+    names: Mutex<HashMap<u32, Option<String>>>,
+}
+
+impl UserCache {
+    pub fn new(lookup: Box<dyn UserLookup>) -> Self {
+        Self { lookup, names: Mutex::new(HashMap::new()) }
+    }
+
+    /// uid의 사용자 이름 (모르면 None)
+    pub fn name(&self, uid: u32) -> Option<String> {
+        if let Some(cached) = self.names.lock().ok().and_then(|names| names.get(&uid).cloned()) {
+            return cached;
+        }
+
+        match self.lookup.user_name(uid) {
+            Ok(name) => {
+                if let Ok(mut names) = self.names.lock() {
+                    names.insert(uid, name.clone());
+                }
+                name
+            },
+            Err(e) => {
+                throttled_warn!("peercred-user-lookup", crate::logthrottle::HOT_PATH_INTERVAL,
+                    "Failed to look up the user name of uid {}: {}", uid, e);
+                None
+            },
+        }
+    }
+}
+
+impl Default for UserCache {
+    /// /etc/passwd에서 찾는 캐시
+    fn default() -> Self {
+        Self::new(Box::<PasswdFile>::default())
+    }
+}
+
+/// Unix 소켓 연결의 자격 증명 (연결한 순간의 uid, gid, pid)
+pub fn identify(stream: &UnixStream, users: &UserCache) -> std::io::Result<PeerIdentity> {
+    let cred = stream.peer_cred()?;
+    Ok(PeerIdentity {
+        uid: cred.uid(),
+        gid: cred.gid(),
+        pid: cred.pid(),
+        user: users.name(cred.uid()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+    /// 고정된 이름을 돌려주고 조회 횟수를 세는 조회기
+    #[derive(Debug, Default)]
+    struct FakeLookup {
+        names: HashMap<u32, String>,
+        calls: Arc<AtomicUsize>,
+        failing: Arc<AtomicBool>,
+    }
+
+    impl UserLookup for FakeLookup {
+        fn user_name(&self, uid: u32) -> std::io::Result<Option<String>> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            if self.failing.load(Ordering::SeqCst) {
+                return Err(std::io::Error::new(std::io::ErrorKind::PermissionDenied, "passwd unreadable"));
+            }
+            Ok(self.names.get(&uid).cloned())
+        }
+    }
+
+    fn identity(user: Option<&str>, pid: Option<i32>) -> PeerIdentity {
+        PeerIdentity { uid: 1000, gid: 100, pid, user: user.map(str::to_string) }
+    }
+
+    #[test]
+    fn test_parse_passwd() {
+        let passwd = "\
+root:x:0:0:root:/root:/bin/bash
+# comment:x:1000:1000
+broken line
+nouid:x:abc:1000::/:/bin/false
+alice:x:1000:1000:Alice,,,:/home/alice:/bin/bash
+:x:1001:1001::/:/bin/false
+";
+        assert_eq!(parse_passwd(passwd, 0).as_deref(), Some("root"));
+        assert_eq!(parse_passwd(passwd, 1000).as_deref(), Some("alice"));
+        assert_eq!(parse_passwd(passwd, 1001), None);
+        assert_eq!(parse_passwd(passwd, 4242), None);
+        assert_eq!(parse_passwd("", 0), None);
+    }
+
+    #[test]
+    fn test_user_cache() {
+        let lookup = FakeLookup { names: HashMap::from([(1000, "alice".to_string())]), ..Default::default() };
+        let (calls, failing) = (lookup.calls.clone(), lookup.failing.clone());
+        let cache = UserCache::new(Box::new(lookup));
+
+        // 실패는 캐시하지 않고 다음 조회에서 다시 찾음
+        failing.store(true, Ordering::SeqCst);
+        assert_eq!(cache.name(1000), None);
+        failing.store(false, Ordering::SeqCst);
+        assert_eq!(cache.name(1000).as_deref(), Some("alice"));
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        // 찾은 이름과 없는 uid는 캐시
+        assert_eq!(cache.name(1000).as_deref(), Some("alice"));
+        assert_eq!(cache.name(4242), None);
+        assert_eq!(cache.name(4242), None);
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[test]
+    fn test_passwd_file() {
+        let path = std::env::temp_dir().join(format!("swift-guard-passwd-{}", std::process::id()));
+        std::fs::write(&path, "alice:x:1000:1000::/home/alice:/bin/sh\n").unwrap();
+
+        assert_eq!(PasswdFile::new(&path).user_name(1000).unwrap().as_deref(), Some("alice"));
+        std::fs::remove_file(&path).unwrap();
+        assert!(PasswdFile::new(&path).user_name(1000).is_err());
+    }
+
+    #[test]
+    fn test_peer() {
+        let remote = Peer::remote("10.0.0.1".parse().unwrap());
+        assert_eq!((remote.id(), remote.creator(), remote.actor()), ("peer:10.0.0.1", "peer:10.0.0.1".to_string(), "peer:10.0.0.1".to_string()));
+        assert_eq!(remote.identity(), None);
+        assert_eq!(Peer::named("peer:[::1]").to_string(), "peer:[::1]");
+
+        let local = Peer::local(identity(Some("alice"), Some(4242)));
+        assert_eq!(local.id(), "uid:1000");
+        assert_eq!(local.creator(), "uid:1000(alice)");
+        assert_eq!(local.actor(), "uid:1000(alice) pid:4242");
+        assert_eq!(local.identity().map(|identity| identity.gid), Some(100));
+
+        // 이름이나 pid를 모르면 생략
+        let unnamed = Peer::local(identity(None, None));
+        assert_eq!((unnamed.creator(), unnamed.actor()), ("uid:1000".to_string(), "uid:1000".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_identify_socketpair() {
+        let (client, server) = UnixStream::pair().unwrap();
+        let uid = nix::unistd::getuid().as_raw();
+        let lookup = FakeLookup { names: HashMap::from([(uid, "tester".to_string())]), ..Default::default() };
+        let users = UserCache::new(Box::new(lookup));
+
+        // 같은 프로세스가 연 쌍이므로 양쪽 모두 이 프로세스의 자격 증명
+        let identity = identify(&server, &users).unwrap();
+        assert_eq!(identity.uid, uid);
+        assert_eq!(identity.gid, nix::unistd::getgid().as_raw());
+        assert_eq!(identity.pid, Some(std::process::id() as i32));
+        assert_eq!(identity.user.as_deref(), Some("tester"));
+        assert_eq!(identify(&client, &users).unwrap(), identity);
+
+        let peer = Peer::local(identity);
+        assert_eq!(peer.actor(), format!("uid:{}(tester) pid:{}", uid, std::process::id()));
+    }
+}
@@ -0,0 +1,160 @@
+//! XDP 연결 사전 점검 모듈
+//! 드라이버별 네이티브 XDP 지원 여부를 확인하고 연결 진행 여부를 결정
+
+use anyhow::{anyhow, Result};
+use log::{debug, warn};
+
+use crate::bpf::XdpMode;
+
+/// 네이티브(드라이버) XDP를 지원하는 것으로 확인된 드라이버
+const NATIVE_XDP_DRIVERS: &[&str] = &[
+    "bnxt_en",
+    "dpaa2-eth",
+    "ena",
+    "hv_netvsc",
+    "i40e",
+    "ice",
+    "igb",
+    "igc",
+    "ixgbe",
+    "ixgbevf",
+    "mlx4_core",
+    "mlx4_en",
+    "mlx5_core",
+    "mvneta",
+    "mvpp2",
+    "nfp",
+    "qede",
+    "sfc",
+    "stmmac",
+    "tun",
+    "veth",
+    "virtio_net",
+    "xen-netfront",
+];
+
+/// 하드웨어 오프로드 XDP를 지원하는 드라이버
+const OFFLOAD_XDP_DRIVERS: &[&str] = &["nfp"];
+
+/// 드라이버의 모드 지원 여부
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModeSupport {
+    Supported,
+    Unsupported,
+    Unknown,
+}
+
+/// 드라이버 이름과 모드로 지원 여부 판단
+pub fn mode_support(driver: Option<&str>, mode: XdpMode) -> ModeSupport {
+    let table = match mode {
+        // 제네릭 모드는 모든 드라이버에서 동작
+        XdpMode::Generic => return ModeSupport::Supported,
+        XdpMode::Driver => NATIVE_XDP_DRIVERS,
+        XdpMode::Offload => OFFLOAD_XDP_DRIVERS,
+    };
+
+    match driver {
+        Some(driver) if table.contains(&driver) => ModeSupport::Supported,
+        Some(_) => ModeSupport::Unsupported,
+        None => ModeSupport::Unknown,
+    }
+}
+
+/// 연결 진행 여부 결정
+///
+/// 표에 없는 드라이버는 `dry_attach`로 실제 연결을 시험하고, 실패하면 감지한 드라이버를
+/// 명시해 거부한다. `force`가 설정되면 점검 결과와 관계없이 진행한다.
+pub fn decide<F>(
+    interface: &str,
+    driver: Option<&str>,
+    mode: XdpMode,
+    force: bool,
+    dry_attach: F,
+) -> Result<()>
+where
+    F: FnOnce() -> Result<()>,
+{
+    let driver_label = driver.unwrap_or("unknown");
+
+    if force {
+        debug!("Skipping XDP support check for {} (driver {})", interface, driver_label);
+        return Ok(());
+    }
+
+    if mode_support(driver, mode) == ModeSupport::Supported {
+        return Ok(());
+    }
+
+    match dry_attach() {
+        Ok(()) => {
+            warn!("Driver {} on {} is not in the {} mode support table, but a dry attach succeeded",
+                driver_label, interface, mode.name());
+            Ok(())
+        }
+        Err(e) => Err(anyhow!(
+            "Interface {} uses driver {}, which does not support {} mode XDP ({:#}); \
+             retry with --mode generic, or pass --force to attach anyway",
+            interface, driver_label, mode.name(), e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    #[test]
+    fn test_mode_support_table() {
+        assert_eq!(mode_support(Some("ixgbe"), XdpMode::Driver), ModeSupport::Supported);
+        assert_eq!(mode_support(Some("mlx5_core"), XdpMode::Driver), ModeSupport::Supported);
+        assert_eq!(mode_support(Some("e1000"), XdpMode::Driver), ModeSupport::Unsupported);
+        assert_eq!(mode_support(None, XdpMode::Driver), ModeSupport::Unknown);
+
+        assert_eq!(mode_support(Some("nfp"), XdpMode::Offload), ModeSupport::Supported);
+        assert_eq!(mode_support(Some("ixgbe"), XdpMode::Offload), ModeSupport::Unsupported);
+
+        assert_eq!(mode_support(Some("e1000"), XdpMode::Generic), ModeSupport::Supported);
+        assert_eq!(mode_support(None, XdpMode::Generic), ModeSupport::Supported);
+    }
+
+    #[test]
+    fn test_decide_supported_driver_skips_dry_attach() {
+        let called = Cell::new(false);
+        decide("eth0", Some("i40e"), XdpMode::Driver, false, || {
+            called.set(true);
+            Ok(())
+        }).unwrap();
+        assert!(!called.get());
+    }
+
+    #[test]
+    fn test_decide_unknown_driver_uses_dry_attach() {
+        let called = Cell::new(false);
+        decide("eth0", Some("r8169"), XdpMode::Driver, false, || {
+            called.set(true);
+            Ok(())
+        }).unwrap();
+        assert!(called.get());
+    }
+
+    #[test]
+    fn test_decide_refuses_with_driver_name() {
+        let err = decide("eth0", Some("e1000"), XdpMode::Driver, false, || {
+            Err(anyhow!("Operation not supported"))
+        }).unwrap_err().to_string();
+
+        assert!(err.contains("e1000"));
+        assert!(err.contains("--mode generic"));
+        assert!(err.contains("Operation not supported"));
+    }
+
+    #[test]
+    fn test_decide_force_proceeds() {
+        let called = Cell::new(false);
+        decide("eth0", Some("e1000"), XdpMode::Driver, true, || {
+            called.set(true);
+            Err(anyhow!("Operation not supported"))
+        }).unwrap();
+        assert!(!called.get());
+    }
+}
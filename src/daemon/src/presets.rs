@@ -0,0 +1,577 @@
+//! 규칙 프리셋 모듈
+//! 자주 쓰는 보호 설정을 검증된 규칙 묶음으로 펼쳐 적용하고 되돌림 (`ApplyPreset`)
+//!
+//! 프리셋은 설정 파일이 아니라 이 모듈의 표로 정의한다. 펼친 규칙의 레이블은
+//! `<프리셋>.<규칙>`이고 모두 프리셋 이름으로 표시되므로, 다시 적용하면 바뀐 규칙만
+//! 갱신되고 정의에서 빠진 규칙은 삭제되며, 되돌릴 때는 표시된 규칙만 지운다.
+
+use anyhow::anyhow;
+use log::info;
+use std::collections::{BTreeMap, HashSet};
+
+use swift_guard::api::{ApplySummary, PresetInfo, PresetParamInfo, RuleDiff, RuleError, RuleSpec};
+use swift_guard::error::InvalidArgument;
+use swift_guard::{rulediff, utils, validation};
+
+use crate::error::DaemonError;
+use crate::maps::{FilterRule, MapManager};
+use crate::ruleset;
+
+/// 프리셋 규칙의 우선순위
+const PRESET_PRIORITY: u32 = 100;
+
+/// 증폭 공격에 자주 쓰이는 UDP 서비스 포트 (chargen, DNS, NTP, SNMP, SSDP, memcached)
+const AMPLIFICATION_PORTS: [u16; 6] = [19, 53, 123, 161, 1900, 11211];
+
+/// `action=drop`에서 임계값을 주지 않았을 때 증폭 포트에서 허용할 초당 패킷 수
+const DEFAULT_AMPLIFICATION_THRESHOLD: u32 = 1000;
+
+/// 매개변수 값 종류
+#[derive(Debug, Clone, Copy)]
+enum ParamKind {
+    /// 1 이상의 개수 ("100", "1k")
+    Count,
+    /// 연결된 인터페이스 이름
+    Interface,
+    /// 규칙 액션 ("count", "drop")
+    Action,
+}
+
+/// 검증한 매개변수 값
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Value {
+    Count(u32),
+    Interface(String),
+    Action(u8),
+}
+
+type Values = BTreeMap<&'static str, Value>;
+
+/// 프리셋 매개변수 정의
+struct Param {
+    name: &'static str,
+    description: &'static str,
+    kind: ParamKind,
+    required: bool,
+}
+
+/// 프리셋 정의
+struct Preset {
+    name: &'static str,
+    description: &'static str,
+    params: &'static [Param],
+    /// 검증한 매개변수로 규칙 정의 생성 (레이블은 프리셋 안의 규칙 이름)
+    expand: fn(&Values) -> Vec<RuleSpec>,
+}
+
+/// 데몬이 제공하는 프리셋
+///
+/// XDP 프로그램이 매치할 수 있는 필드(주소, 프로토콜, 포트, TCP 플래그)로만 규칙을
+/// 만든다. 단편화나 패킷 길이 조건은 매치할 수 없어 포함하지 않는다.
+const PRESETS: &[Preset] = &[
+    Preset {
+        name: "icmp-protect",
+        description: "Rate-limit ICMP to protect against ping floods (packets over the limit are dropped)",
+        params: &[
+            Param {
+                name: "limit",
+                description: "ICMP packets per second to allow (e.g. 100, 1k)",
+                kind: ParamKind::Count,
+                required: true,
+            },
+            Param {
+                name: "interface",
+                description: "Interface to protect (must be attached; rules apply on every attached interface)",
+                kind: ParamKind::Interface,
+                required: false,
+            },
+        ],
+        expand: icmp_protect,
+    },
+    Preset {
+        name: "amplification-protect",
+        description: "Count UDP from amplification ports (chargen, DNS, NTP, SNMP, SSDP, memcached), optionally dropping it above a threshold",
+        params: &[
+            Param {
+                name: "action",
+                description: "count (default) to watch the traffic, drop to drop packets above the threshold",
+                kind: ParamKind::Action,
+                required: false,
+            },
+            Param {
+                name: "threshold",
+                description: "UDP packets per second from these ports to allow with action=drop (default 1k)",
+                kind: ParamKind::Count,
+                required: false,
+            },
+            Param {
+                name: "interface",
+                description: "Interface to protect (must be attached; rules apply on every attached interface)",
+                kind: ParamKind::Interface,
+                required: false,
+            },
+        ],
+        expand: amplification_protect,
+    },
+];
+
+fn icmp_protect(values: &Values) -> Vec<RuleSpec> {
+    let limit = match values.get("limit") {
+        Some(Value::Count(limit)) => *limit,
+        _ => 0,
+    };
+    let target = match values.get("interface") {
+        Some(Value::Interface(name)) => format!(" on {}", name),
+        _ => String::new(),
+    };
+
+    vec![RuleSpec {
+        rate_limit: limit,
+        description: Some(format!("ICMP limited to {} pps{} (preset icmp-protect)", limit, target)),
+        ..spec("echo", 1, 1)
+    }]
+}
+
+/// 증폭 공격 포트에서 오는 UDP 규칙
+///
+/// 기본(`action=count`)은 count 규칙으로 트래픽을 지켜보기만 하고, `action=drop`으로 다시
+/// 적용하면 같은 규칙이 임계값까지 통과시키고 넘는 패킷은 버리는 레이트 리밋 규칙으로
+/// 제자리 갱신된다 (통계는 이어짐). 호스트가 보낸 질의의 응답도 이 포트에서 오므로 평소
+/// 트래픽은 임계값 아래에서 그대로 통과한다. 임계값은 규칙 전체에 대한 한도이다.
+fn amplification_protect(values: &Values) -> Vec<RuleSpec> {
+    let target = match values.get("interface") {
+        Some(Value::Interface(name)) => format!(" on {}", name),
+        _ => String::new(),
+    };
+    let threshold = match values.get("threshold") {
+        Some(Value::Count(threshold)) => *threshold,
+        _ => DEFAULT_AMPLIFICATION_THRESHOLD,
+    };
+
+    let udp = RuleSpec { src_port_list: AMPLIFICATION_PORTS.to_vec(), ..spec("udp", 17, 4) };
+    match values.get("action") {
+        Some(Value::Action(2)) => vec![RuleSpec {
+            action: 1,
+            rate_limit: threshold,
+            description: Some(format!("UDP from amplification ports limited to {} pps{} (preset amplification-protect)",
+                threshold, target)),
+            ..udp
+        }],
+        _ => vec![RuleSpec {
+            description: Some(format!("UDP from amplification ports counted{} (preset amplification-protect)", target)),
+            ..udp
+        }],
+    }
+}
+
+/// 모든 주소와 포트에 매치하는 규칙 정의
+fn spec(label: &str, protocol: u8, action: u8) -> RuleSpec {
+    RuleSpec {
+        label: label.to_string(),
+        src_ip: None,
+        dst_ip: None,
+        src_port_min: 0,
+        src_port_max: 65535,
+        dst_port_min: 0,
+        dst_port_max: 65535,
+        protocol,
+        tcp_flags: 0,
+        action,
+        redirect_if: None,
+        redirect_cpu: None,
+        priority: PRESET_PRIORITY,
+        rate_limit: 0,
+        expire: 0,
+        description: None,
+        quota_bytes: None,
+        quota_packets: None,
+        quota_action: None,
+        counter_group: None,
+        src_port_list: Vec::new(),
+        dst_port_list: Vec::new(),
+        owner_webhook: None,
+        notify_before: None,
+    }
+}
+
+fn find(name: &str) -> Result<&'static Preset, InvalidArgument> {
+    PRESETS.iter().find(|preset| preset.name == name).ok_or_else(|| {
+        let names: Vec<&str> = PRESETS.iter().map(|preset| preset.name).collect();
+        InvalidArgument::new("name", format!("Unknown preset '{}' (available: {})", name, names.join(", ")))
+    })
+}
+
+/// 매개변수 검증 (`attached`는 연결된 인터페이스 이름)
+fn validate(preset: &Preset, params: &BTreeMap<String, String>, attached: &[String]) -> Result<Values, InvalidArgument> {
+    if let Some(unknown) = params.keys().find(|key| !preset.params.iter().any(|param| param.name == key.as_str())) {
+        let names: Vec<&str> = preset.params.iter().map(|param| param.name).collect();
+        return Err(InvalidArgument::new(unknown, format!(
+            "Unknown parameter for preset '{}' (expected {})", preset.name, names.join(", "))));
+    }
+
+    let mut values = Values::new();
+    for param in preset.params {
+        let Some(raw) = params.get(param.name) else {
+            if param.required {
+                return Err(InvalidArgument::new(param.name, format!("required by preset '{}'", preset.name)));
+            }
+            continue;
+        };
+
+        let value = match param.kind {
+            ParamKind::Count => {
+                let count = utils::parse_count(raw)
+                    .map_err(|e| InvalidArgument::new(param.name, e.to_string()))?;
+                match u32::try_from(count) {
+                    Ok(count) if count > 0 => Value::Count(count),
+                    _ => return Err(InvalidArgument::new(param.name,
+                        format!("must be between 1 and {}", u32::MAX))),
+                }
+            },
+            ParamKind::Interface => {
+                if !attached.iter().any(|name| name == raw) {
+                    return Err(InvalidArgument::new(param.name, format!("Interface '{}' is not attached", raw)));
+                }
+                Value::Interface(raw.clone())
+            },
+            ParamKind::Action => match raw.as_str() {
+                "count" => Value::Action(4),
+                "drop" => Value::Action(2),
+                _ => return Err(InvalidArgument::new(param.name, "must be count or drop")),
+            },
+        };
+        values.insert(param.name, value);
+    }
+
+    Ok(values)
+}
+
+/// 규칙이 이 네임스페이스에서 프리셋이 설치한 규칙인지
+fn installed_by(rule: &FilterRule, name: &str, namespace: &str) -> bool {
+    rule.preset.as_deref() == Some(name) && utils::split_label(&rule.label).0 == namespace
+}
+
+/// 프리셋 적용 (다시 적용하면 바뀐 규칙만 갱신하고 정의에서 빠진 규칙은 삭제)
+///
+/// 매개변수가 잘못되었거나, 로드된 XDP 프로그램이 규칙을 적용하지 못하거나(레이트 리밋 등),
+/// 프리셋이 쓸 레이블을 다른 규칙이 쓰고 있으면 아무것도 바꾸지 않고 오류를 돌려준다.
+/// 규칙 하나의 적용 실패는 결과의 `errors`로 보고한다.
+pub fn apply(
+    map_manager: &mut MapManager,
+    name: &str,
+    params: &BTreeMap<String, String>,
+    namespace: &str,
+    created_by: &str,
+    now: u64,
+) -> Result<ApplySummary, InvalidArgument> {
+    let preset = find(name)?;
+    let attached: Vec<String> = map_manager.list_interfaces().into_iter().map(|interface| interface.name).collect();
+    let values = validate(preset, params, &attached)?;
+    let supported = map_manager.rule_features();
+
+    let mut desired = Vec::new();
+    for spec in (preset.expand)(&values) {
+        // 적용되지 않는 필드를 가진 규칙은 보호하는 것처럼 보이기만 하므로 설치하지 않음
+        if let Some(issue) = validation::check_features(&spec, supported).into_iter().next() {
+            return Err(InvalidArgument::new("name", format!(
+                "Preset '{}' cannot be applied: {}", preset.name, issue.message)));
+        }
+        let label = format!("{}.{}", preset.name, spec.label);
+        let mut rule = ruleset::build_rule(RuleSpec { label: label.clone(), ..spec }, Some(created_by.to_string()), now,
+            |_| Err(anyhow!("presets do not redirect")))?;
+        rule.label = utils::qualify_label(namespace, &label);
+        rule.preset = Some(preset.name.to_string());
+
+        if matches!(map_manager.rules().get(&rule.label), Some(existing) if existing.preset != rule.preset) {
+            return Err(InvalidArgument::new("name", format!(
+                "Rule '{}' already exists and was not installed by preset '{}'", label, preset.name)));
+        }
+        desired.push(rule);
+    }
+
+    // 이 프리셋이 설치한 규칙만 비교하고 정리
+    let keep: HashSet<String> = map_manager.rules().iter()
+        .filter(|rule| !installed_by(rule, preset.name, namespace))
+        .map(|rule| rule.label.clone())
+        .collect();
+    let diff = ruleset::diff(map_manager.rules(), desired, true, &keep);
+
+    let bare = |key: &str| utils::split_label(key).1.to_string();
+    let mut summary = ApplySummary {
+        unchanged: diff.unchanged.iter().map(|key| bare(key)).collect(),
+        diffs: diff.update.iter()
+            .filter_map(|rule| map_manager.rules().get(&rule.label).map(|current| RuleDiff {
+                label: bare(&rule.label),
+                changes: rulediff::diff_specs(&current.to_spec(), &rule.to_spec()),
+            }))
+            .collect(),
+        ..Default::default()
+    };
+
+    for key in diff.delete {
+        match map_manager.delete_rule(&key) {
+            Ok(()) => summary.deleted.push(bare(&key)),
+            Err(e) => summary.errors.push(rule_error(&bare(&key), e)),
+        }
+    }
+    for rule in diff.update {
+        let label = bare(&rule.label);
+        match map_manager.update_rule(rule) {
+            Ok(()) => summary.updated.push(label),
+            Err(e) => summary.errors.push(rule_error(&label, e)),
+        }
+    }
+    for rule in diff.add {
+        let label = bare(&rule.label);
+        match map_manager.add_rule(rule) {
+            Ok(()) => summary.added.push(label),
+            Err(e) => summary.errors.push(rule_error(&label, e)),
+        }
+    }
+
+    info!("Preset '{}' applied: {} added, {} updated, {} deleted, {} unchanged, {} errors", preset.name,
+        summary.added.len(), summary.updated.len(), summary.deleted.len(), summary.unchanged.len(), summary.errors.len());
+    Ok(summary)
+}
+
+fn rule_error(label: &str, e: DaemonError) -> RuleError {
+    RuleError {
+        label: label.to_string(),
+        message: e.to_string(),
+        code: e.code(),
+    }
+}
+
+/// 이 네임스페이스에서 프리셋이 설치한 규칙 삭제 (삭제한 레이블, 없으면 빈 목록)
+pub fn remove(map_manager: &mut MapManager, name: &str, namespace: &str) -> Result<Vec<String>, DaemonError> {
+    let keys: Vec<String> = map_manager.rules().iter()
+        .filter(|rule| installed_by(rule, name, namespace))
+        .map(|rule| rule.label.clone())
+        .collect();
+
+    let mut removed = Vec::with_capacity(keys.len());
+    for key in keys {
+        map_manager.delete_rule(&key)?;
+        removed.push(utils::split_label(&key).1.to_string());
+    }
+
+    if !removed.is_empty() {
+        info!("Preset '{}' removed: {} rules deleted", name, removed.len());
+    }
+    Ok(removed)
+}
+
+/// 프리셋 목록과 이 네임스페이스에 적용된 규칙 (규칙 레이블 순)
+pub fn list(map_manager: &MapManager, namespace: &str) -> Vec<PresetInfo> {
+    PRESETS.iter().map(|preset| {
+        let mut rules: Vec<String> = map_manager.rules().iter()
+            .filter(|rule| installed_by(rule, preset.name, namespace))
+            .map(|rule| utils::split_label(&rule.label).1.to_string())
+            .collect();
+        rules.sort();
+
+        PresetInfo {
+            name: preset.name.to_string(),
+            description: preset.description.to_string(),
+            params: preset.params.iter().map(|param| PresetParamInfo {
+                name: param.name.to_string(),
+                description: param.description.to_string(),
+                required: param.required,
+            }).collect(),
+            rules,
+        }
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::{MapBackend, MemoryMap};
+    use crate::maps::MapBackends;
+    use swift_guard::api::FallbackMode;
+    use swift_guard::types::XdpFeatures;
+
+    fn params(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+        pairs.iter().map(|(key, value)| (key.to_string(), value.to_string())).collect()
+    }
+
+    /// 규칙 맵 두 개 (LPM, 와일드카드)와 eth0이 연결된 관리자
+    fn manager<'a>(filter_rules: &'a MemoryMap, wildcard_rules: &'a MemoryMap) -> MapManager<'a> {
+        let mut manager = MapManager::with_backends(MapBackends {
+            filter_rules: Some(filter_rules),
+            wildcard: Some(wildcard_rules),
+            ..Default::default()
+        });
+        manager.attach_interface("eth0", "generic", None, FallbackMode::None, |_| Ok(())).unwrap();
+        manager
+    }
+
+    #[test]
+    fn test_expand_icmp_protect() {
+        let preset = find("icmp-protect").unwrap();
+        let values = validate(preset, &params(&[("limit", "1k"), ("interface", "eth0")]), &["eth0".to_string()]).unwrap();
+        let specs = (preset.expand)(&values);
+
+        assert_eq!(specs.len(), 1);
+        assert_eq!((specs[0].label.as_str(), specs[0].protocol, specs[0].action), ("echo", 1, 1));
+        assert_eq!(specs[0].rate_limit, 1000);
+        assert_eq!(specs[0].description.as_deref(), Some("ICMP limited to 1000 pps on eth0 (preset icmp-protect)"));
+
+        // 매개변수 검증 (오류 필드는 매개변수 이름)
+        let cases = [
+            (params(&[]), "limit"),
+            (params(&[("limit", "0")]), "limit"),
+            (params(&[("limit", "5G")]), "limit"),
+            (params(&[("limit", "lots")]), "limit"),
+            (params(&[("limit", "100"), ("interface", "eth9")]), "interface"),
+            (params(&[("limit", "100"), ("burst", "5")]), "burst"),
+        ];
+        for (params, field) in cases {
+            let err = validate(preset, &params, &["eth0".to_string()]).unwrap_err();
+            assert_eq!(err.field, field, "{:?}", params);
+        }
+        assert!(matches!(find("syn-protect"), Err(e) if e.field == "name"));
+    }
+
+    #[test]
+    fn test_expand_amplification_protect() {
+        let preset = find("amplification-protect").unwrap();
+        let specs = (preset.expand)(&validate(preset, &params(&[]), &[]).unwrap());
+
+        assert_eq!(specs.len(), 1);
+        assert_eq!((specs[0].label.as_str(), specs[0].protocol, specs[0].action), ("udp", 17, 4));
+        assert_eq!(specs[0].src_port_list, [19, 53, 123, 161, 1900, 11211]);
+        assert_eq!((specs[0].src_port_min, specs[0].src_port_max, specs[0].rate_limit), (0, 65535, 0));
+        assert_eq!(specs[0].description.as_deref(), Some("UDP from amplification ports counted (preset amplification-protect)"));
+
+        // 드롭은 임계값까지 통과시키는 레이트 리밋 (응답 트래픽은 임계값 아래에서 통과)
+        let values = validate(preset, &params(&[("action", "drop"), ("interface", "eth0")]), &["eth0".to_string()]).unwrap();
+        let specs = (preset.expand)(&values);
+        assert_eq!((specs[0].action, specs[0].rate_limit), (1, 1000));
+        assert_eq!(specs[0].description.as_deref(),
+            Some("UDP from amplification ports limited to 1000 pps on eth0 (preset amplification-protect)"));
+        let values = validate(preset, &params(&[("action", "drop"), ("threshold", "5k")]), &[]).unwrap();
+        assert_eq!((preset.expand)(&values)[0].rate_limit, 5000);
+        assert_eq!(validate(preset, &params(&[("threshold", "0")]), &[]).unwrap_err().field, "threshold");
+
+        let values = validate(preset, &params(&[("action", "count")]), &[]).unwrap();
+        assert_eq!((preset.expand)(&values)[0].action, 4);
+        assert_eq!(validate(preset, &params(&[("action", "pass")]), &[]).unwrap_err().field, "action");
+        assert_eq!(validate(preset, &params(&[("limit", "10k")]), &[]).unwrap_err().field, "limit");
+    }
+
+    #[test]
+    fn test_amplification_protect_counts_then_drops() {
+        let (filter_rules, wildcard_rules) = (MemoryMap::new(), MemoryMap::new());
+        let mut manager = manager(&filter_rules, &wildcard_rules);
+
+        let summary = apply(&mut manager, "amplification-protect", &params(&[]), "default", "ops", 1000).unwrap();
+        assert_eq!(summary.added, ["amplification-protect.udp"]);
+        let value = wildcard_rules.lookup(&0u32.to_le_bytes()).unwrap().unwrap();
+        let ports = swift_guard::layout::rule_field("src_ports").unwrap();
+        assert_eq!(swift_guard::layout::decode_port_list(&value[ports]), [19, 53, 123, 161, 1900, 11211]);
+        assert_eq!(manager.rules().get("amplification-protect.udp").unwrap().to_rule_info(Default::default(), true).src_port.as_deref(),
+            Some("19,53,123,161,1900,11211"));
+
+        // action=drop으로 다시 적용하면 같은 규칙을 제자리 갱신
+        let dropping = apply(&mut manager, "amplification-protect", &params(&[("action", "drop")]), "default", "ops", 2000).unwrap();
+        assert_eq!(dropping.updated, ["amplification-protect.udp"]);
+        assert_eq!(dropping.diffs[0].changes.iter().map(|change| change.field.as_str()).collect::<Vec<_>>(),
+            ["action", "rate_limit", "description"]);
+        let rule = manager.rules().get("amplification-protect.udp").unwrap();
+        assert_eq!((rule.action, rule.rate_limit, rule.creation_time), (1, 1000, 1000));
+    }
+
+    #[test]
+    fn test_apply_is_idempotent() {
+        let (filter_rules, wildcard_rules) = (MemoryMap::new(), MemoryMap::new());
+        let mut manager = manager(&filter_rules, &wildcard_rules);
+
+        let summary = apply(&mut manager, "icmp-protect", &params(&[("limit", "100")]), "default", "ops", 1000).unwrap();
+        assert_eq!(summary.added, ["icmp-protect.echo"]);
+        let rule = manager.rules().get("icmp-protect.echo").unwrap();
+        assert_eq!((rule.preset.as_deref(), rule.rate_limit, rule.created_by.as_deref()), (Some("icmp-protect"), 100, Some("ops")));
+
+        // 같은 매개변수로 다시 적용하면 바뀌는 것이 없고, 바뀐 매개변수는 제자리 갱신
+        let again = apply(&mut manager, "icmp-protect", &params(&[("limit", "100")]), "default", "ops", 2000).unwrap();
+        assert_eq!(again.unchanged, ["icmp-protect.echo"]);
+        assert!(again.added.is_empty() && again.updated.is_empty() && again.deleted.is_empty());
+        assert_eq!(manager.rules().get("icmp-protect.echo").unwrap().creation_time, 1000);
+
+        let changed = apply(&mut manager, "icmp-protect", &params(&[("limit", "500")]), "default", "ops", 3000).unwrap();
+        assert_eq!(changed.updated, ["icmp-protect.echo"]);
+        assert_eq!(changed.diffs[0].changes.iter().map(|change| change.field.as_str()).collect::<Vec<_>>(),
+            ["rate_limit", "description"]);
+        assert_eq!(manager.rules().get("icmp-protect.echo").unwrap().rate_limit, 500);
+        assert_eq!(manager.rules().iter().count(), 1);
+
+        // 잘못된 매개변수는 아무것도 바꾸지 않음
+        assert!(apply(&mut manager, "icmp-protect", &params(&[("limit", "0")]), "default", "ops", 4000).is_err());
+        assert_eq!(manager.rules().get("icmp-protect.echo").unwrap().rate_limit, 500);
+    }
+
+    #[test]
+    fn test_apply_refuses_unenforced_rate_limit() {
+        let (filter_rules, wildcard_rules) = (MemoryMap::new(), MemoryMap::new());
+        let mut manager = MapManager::with_backends(MapBackends {
+            filter_rules: Some(&filter_rules),
+            wildcard: Some(&wildcard_rules),
+            features: Some(XdpFeatures(XdpFeatures::ALL.0 & !XdpFeatures::RATE_LIMIT)),
+            ..Default::default()
+        });
+
+        // rate_limit을 적용하지 않는 (이전에 빌드된) XDP 프로그램에는 icmp-protect를 설치하지 않음
+        let err = apply(&mut manager, "icmp-protect", &params(&[("limit", "100")]), "default", "ops", 1000).unwrap_err();
+        assert!(err.message.contains("rate_limit requires the rate_limit feature"), "{}", err.message);
+        assert!(manager.rules().iter().next().is_none());
+        assert!(wildcard_rules.keys().is_empty());
+
+        // 프로그램이 처리하는 필드만 쓰는 프리셋은 그대로 적용
+        let summary = apply(&mut manager, "amplification-protect", &params(&[]), "default", "ops", 1000).unwrap();
+        assert_eq!(summary.added, ["amplification-protect.udp"]);
+    }
+
+    #[test]
+    fn test_apply_refuses_foreign_label() {
+        let (filter_rules, wildcard_rules) = (MemoryMap::new(), MemoryMap::new());
+        let mut manager = manager(&filter_rules, &wildcard_rules);
+
+        // 프리셋이 쓸 레이블의 규칙을 운영자가 직접 만들었으면 덮어쓰지 않음
+        let own = ruleset::build_rule(RuleSpec { label: "icmp-protect.echo".to_string(), ..spec("x", 1, 2) }, None, 0,
+            |_| Ok(0)).unwrap();
+        manager.add_rule(own).unwrap();
+
+        let err = apply(&mut manager, "icmp-protect", &params(&[("limit", "100")]), "default", "ops", 1000).unwrap_err();
+        assert!(err.message.contains("not installed by preset"), "{}", err.message);
+        assert_eq!(manager.rules().get("icmp-protect.echo").unwrap().action, 2);
+        assert!(remove(&mut manager, "icmp-protect", "default").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_remove_and_list() {
+        let (filter_rules, wildcard_rules) = (MemoryMap::new(), MemoryMap::new());
+        let mut manager = manager(&filter_rules, &wildcard_rules);
+        let other = ruleset::build_rule(spec("web", 6, 2), None, 0, |_| Ok(0)).unwrap();
+        manager.add_rule(other).unwrap();
+
+        apply(&mut manager, "icmp-protect", &params(&[("limit", "100")]), "default", "ops", 1000).unwrap();
+        apply(&mut manager, "icmp-protect", &params(&[("limit", "50")]), "team", "ops", 1000).unwrap();
+
+        let presets = list(&manager, "team");
+        assert_eq!(presets.len(), 2);
+        assert_eq!(presets[0].name, "icmp-protect");
+        assert_eq!(presets[0].rules, ["icmp-protect.echo"]);
+        assert_eq!(presets[0].params.iter().map(|param| (param.name.as_str(), param.required)).collect::<Vec<_>>(),
+            [("limit", true), ("interface", false)]);
+
+        // 네임스페이스 안의 프리셋 규칙만 지우고 다른 규칙은 그대로
+        assert_eq!(remove(&mut manager, "icmp-protect", "team").unwrap(), ["icmp-protect.echo"]);
+        assert!(list(&manager, "team")[0].rules.is_empty());
+        assert_eq!(list(&manager, "default")[0].rules, ["icmp-protect.echo"]);
+        assert!(remove(&mut manager, "icmp-protect", "team").unwrap().is_empty());
+
+        remove(&mut manager, "icmp-protect", "default").unwrap();
+        let labels: Vec<&str> = manager.rules().iter().map(|rule| rule.label.as_str()).collect();
+        assert_eq!(labels, ["web"]);
+    }
+}
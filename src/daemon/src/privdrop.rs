@@ -0,0 +1,450 @@
+//! 권한 낮추기 모듈
+//! 초기화가 끝난 뒤 데몬을 구성한 사용자/그룹으로 전환하고 필요한 capability만 남김
+//!
+//! 전환 순서가 중요하다. 보조 그룹과 gid는 root일 때만 바꿀 수 있으므로 uid보다 먼저 바꾼다.
+//! uid를 바꾸면 capability가 모두 사라지므로 그 전에 PR_SET_KEEPCAPS로 허용 집합을 유지하고,
+//! 전환 후 남길 capability만 다시 설정해 ambient 집합에 올린다(자식 프로세스인 ip, nft도 사용).
+//! 마지막으로 root로 돌아갈 수 없는지 확인한다. 어느 단계든 실패하면 데몬은 시작하지 않는다.
+
+use anyhow::{anyhow, Context, Result};
+use nix::errno::Errno;
+use nix::unistd::{self, Gid, Group, Uid, User};
+use std::ffi::CString;
+
+use crate::config::GeneralConfig;
+use crate::error::DaemonError;
+
+/// capget/capset 헤더 버전 (64비트 집합, linux/capability.h)
+const LINUX_CAPABILITY_VERSION_3: u32 = 0x2008_0522;
+
+/// 인터페이스 연결에 필요한 capability (안쪽 목록은 그중 하나면 충분)
+pub const ATTACH_CAPABILITIES: &[&[Capability]] = &[
+    &[Capability::NetAdmin],
+    &[Capability::Bpf, Capability::SysAdmin],
+];
+
+/// 벤치마크에 필요한 capability (veth 생성과 XDP 연결, raw 소켓 전송)
+pub const BENCH_CAPABILITIES: &[&[Capability]] = &[
+    &[Capability::NetAdmin],
+    &[Capability::Bpf, Capability::SysAdmin],
+    &[Capability::NetRaw],
+];
+
+/// 권한을 낮춘 뒤에도 남길 수 있는 capability (linux/capability.h 번호)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Capability {
+    NetAdmin = 12,
+    NetRaw = 13,
+    SysAdmin = 21,
+    SysResource = 24,
+    Perfmon = 38,
+    Bpf = 39,
+}
+
+impl Capability {
+    const ALL: [Capability; 6] = [
+        Capability::NetAdmin,
+        Capability::NetRaw,
+        Capability::SysAdmin,
+        Capability::SysResource,
+        Capability::Perfmon,
+        Capability::Bpf,
+    ];
+
+    /// 이름 해석 ("CAP_NET_ADMIN", "net_admin", 대소문자 무시)
+    pub fn parse(name: &str) -> Option<Self> {
+        let name = name.trim().to_ascii_uppercase();
+        let name = name.strip_prefix("CAP_").unwrap_or(&name);
+        Self::ALL.into_iter().find(|cap| &cap.name()[4..] == name)
+    }
+
+    /// capability 이름
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::NetAdmin => "CAP_NET_ADMIN",
+            Self::NetRaw => "CAP_NET_RAW",
+            Self::SysAdmin => "CAP_SYS_ADMIN",
+            Self::SysResource => "CAP_SYS_RESOURCE",
+            Self::Perfmon => "CAP_PERFMON",
+            Self::Bpf => "CAP_BPF",
+        }
+    }
+
+    fn bit(&self) -> u64 {
+        1 << (*self as u64)
+    }
+
+    /// capability 집합 비트마스크
+    pub fn mask(caps: &[Capability]) -> u64 {
+        caps.iter().fold(0, |mask, cap| mask | cap.bit())
+    }
+
+    /// 비트마스크에 포함된 capability (알려진 것만)
+    pub fn from_mask(mask: u64) -> Vec<Capability> {
+        Self::ALL.into_iter().filter(|cap| mask & cap.bit() != 0).collect()
+    }
+}
+
+/// 권한 전환 대상
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Target {
+    pub user: String,
+    pub uid: Uid,
+    pub gid: Gid,
+    /// 보조 그룹 (사용자가 속한 그룹과 대상 gid)
+    pub groups: Vec<Gid>,
+    /// 전환 후에도 남길 capability
+    pub keep: Vec<Capability>,
+}
+
+impl Target {
+    /// 구성에서 전환 대상 해석 (`run_as_user`가 없으면 None)
+    pub fn from_config(general: &GeneralConfig) -> Result<Option<Self>> {
+        let keep = parse_capabilities(&general.keep_capabilities)?;
+        let Some(name) = &general.run_as_user else {
+            if general.run_as_group.is_some() {
+                return Err(anyhow!("general.run_as_group requires general.run_as_user"));
+            }
+            if !keep.is_empty() {
+                return Err(anyhow!("general.keep_capabilities requires general.run_as_user"));
+            }
+            return Ok(None);
+        };
+
+        let user = User::from_name(name)
+            .with_context(|| format!("Failed to look up user {}", name))?
+            .ok_or_else(|| anyhow!("general.run_as_user: unknown user {}", name))?;
+        if user.uid.is_root() {
+            return Err(anyhow!("general.run_as_user must not be root"));
+        }
+        let gid = match &general.run_as_group {
+            Some(group) => Group::from_name(group)
+                .with_context(|| format!("Failed to look up group {}", group))?
+                .ok_or_else(|| anyhow!("general.run_as_group: unknown group {}", group))?
+                .gid,
+            None => user.gid,
+        };
+
+        let c_name = CString::new(name.as_str()).context("general.run_as_user contains a NUL byte")?;
+        let groups = unistd::getgrouplist(&c_name, gid)
+            .with_context(|| format!("Failed to read the groups of {}", name))?;
+
+        Ok(Some(Self { user: name.clone(), uid: user.uid, gid, groups, keep }))
+    }
+}
+
+/// capability 이름 목록 해석 (중복 제거, 번호 순)
+pub fn parse_capabilities(names: &[String]) -> Result<Vec<Capability>> {
+    let mut caps = names.iter()
+        .map(|name| Capability::parse(name).ok_or_else(|| anyhow!(
+            "general.keep_capabilities: unsupported capability {} (expected one of {})", name,
+            Capability::ALL.iter().map(Capability::name).collect::<Vec<_>>().join(", "))))
+        .collect::<Result<Vec<_>>>()?;
+    caps.sort();
+    caps.dedup();
+    Ok(caps)
+}
+
+/// 권한 전환 단계
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Step {
+    /// uid를 바꿔도 허용 capability 유지 (PR_SET_KEEPCAPS)
+    KeepCaps(bool),
+    SetGroups(Vec<Gid>),
+    SetGid(Gid),
+    SetUid(Uid),
+    /// 허용/유효/상속 집합을 이 capability로 설정
+    SetCapabilities(Vec<Capability>),
+    /// ambient 집합에 올림 (execve 후에도 유지)
+    RaiseAmbient(Capability),
+    /// root로 돌아갈 수 없는지 확인
+    VerifyNoRoot,
+}
+
+impl Step {
+    /// 오류 메시지용 설명
+    fn describe(&self) -> String {
+        match self {
+            Step::KeepCaps(keep) => format!("{} PR_SET_KEEPCAPS", if *keep { "set" } else { "clear" }),
+            Step::SetGroups(groups) => format!("set {} supplementary groups", groups.len()),
+            Step::SetGid(gid) => format!("set group id {}", gid),
+            Step::SetUid(uid) => format!("set user id {}", uid),
+            Step::SetCapabilities(caps) => format!("set capabilities {}", names(caps)),
+            Step::RaiseAmbient(cap) => format!("raise ambient {}", cap.name()),
+            Step::VerifyNoRoot => "verify that root cannot be regained".to_string(),
+        }
+    }
+}
+
+/// 전환 단계 (그룹 → gid → uid 순서, 남길 capability는 uid 전환 뒤 다시 설정)
+pub fn plan(target: &Target) -> Vec<Step> {
+    let keep = !target.keep.is_empty();
+    let mut steps = Vec::new();
+    if keep {
+        steps.push(Step::KeepCaps(true));
+    }
+    steps.push(Step::SetGroups(target.groups.clone()));
+    steps.push(Step::SetGid(target.gid));
+    steps.push(Step::SetUid(target.uid));
+    if keep {
+        steps.push(Step::SetCapabilities(target.keep.clone()));
+        steps.extend(target.keep.iter().copied().map(Step::RaiseAmbient));
+        steps.push(Step::KeepCaps(false));
+    }
+    steps.push(Step::VerifyNoRoot);
+    steps
+}
+
+/// 프로세스 자격 증명 변경 (테스트에서 대체 가능)
+pub trait Credentials {
+    fn apply(&mut self, step: &Step) -> Result<()>;
+}
+
+/// 실제 시스템 호출
+pub struct SystemCredentials;
+
+impl Credentials for SystemCredentials {
+    fn apply(&mut self, step: &Step) -> Result<()> {
+        match step {
+            Step::KeepCaps(keep) => prctl(libc::PR_SET_KEEPCAPS, libc::c_ulong::from(*keep), 0)?,
+            Step::SetGroups(groups) => unistd::setgroups(groups)?,
+            Step::SetGid(gid) => unistd::setresgid(*gid, *gid, *gid)?,
+            Step::SetUid(uid) => unistd::setresuid(*uid, *uid, *uid)?,
+            Step::SetCapabilities(caps) => capset(Capability::mask(caps))?,
+            Step::RaiseAmbient(cap) => prctl(libc::PR_CAP_AMBIENT, libc::PR_CAP_AMBIENT_RAISE as libc::c_ulong, *cap as libc::c_ulong)?,
+            Step::VerifyNoRoot => {
+                if unistd::setresuid(Uid::from_raw(0), Uid::from_raw(0), Uid::from_raw(0)).is_ok() {
+                    return Err(anyhow!("the process could switch back to root"));
+                }
+            },
+        }
+        Ok(())
+    }
+}
+
+/// 단계를 순서대로 적용 (실패하면 그 단계를 밝힌 오류로 중단)
+pub fn drop_privileges(credentials: &mut impl Credentials, target: &Target) -> Result<()> {
+    for step in plan(target) {
+        credentials.apply(&step).with_context(|| format!("Failed to {}", step.describe()))?;
+    }
+    Ok(())
+}
+
+/// 권한을 낮춘 뒤의 상태 (권한이 필요한 요청 확인)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DroppedPrivileges {
+    pub user: String,
+    /// 전환 후 확인한 유효 capability
+    pub effective: Vec<Capability>,
+}
+
+impl DroppedPrivileges {
+    /// 전환 후 현재 유효 capability 확인
+    pub fn detect(user: &str) -> Result<Self> {
+        let effective = Capability::from_mask(capget().context("Failed to read the capability set")?);
+        Ok(Self { user: user.to_string(), effective })
+    }
+
+    /// `operation`에 필요한 capability가 남아 있는지 (`required`의 안쪽 목록은 그중 하나면 충분)
+    pub fn require(&self, operation: &str, required: &[&[Capability]]) -> Result<(), DaemonError> {
+        let missing: Vec<String> = required.iter()
+            .filter(|any_of| !any_of.iter().any(|cap| self.effective.contains(cap)))
+            .map(|any_of| names(any_of).replace(", ", " or "))
+            .collect();
+        if missing.is_empty() {
+            return Ok(());
+        }
+
+        Err(DaemonError::PermissionDenied(format!(
+            "{} needs {}, which the daemon no longer has after switching to user {} \
+            (add it to general.keep_capabilities and restart the daemon)",
+            operation, missing.join(" and "), self.user)))
+    }
+}
+
+fn names(caps: &[Capability]) -> String {
+    caps.iter().map(Capability::name).collect::<Vec<_>>().join(", ")
+}
+
+fn prctl(option: libc::c_int, arg2: libc::c_ulong, arg3: libc::c_ulong) -> Result<(), Errno> {
+    // SAFETY: 정수 인자만 전달하는 prctl 호출
+    let ret = unsafe { libc::prctl(option, arg2, arg3, 0 as libc::c_ulong, 0 as libc::c_ulong) };
+    if ret < 0 {
+        return Err(Errno::last());
+    }
+    Ok(())
+}
+
+/// capget/capset 헤더
+#[repr(C)]
+struct CapHeader {
+    version: u32,
+    pid: libc::c_int,
+}
+
+/// capget/capset 데이터 (버전 3은 하위/상위 32비트 두 항목)
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+struct CapData {
+    effective: u32,
+    permitted: u32,
+    inheritable: u32,
+}
+
+/// 현재 스레드의 허용/유효/상속 집합을 `mask`로 설정
+fn capset(mask: u64) -> Result<(), Errno> {
+    let header = CapHeader { version: LINUX_CAPABILITY_VERSION_3, pid: 0 };
+    let data = [mask as u32, (mask >> 32) as u32]
+        .map(|word| CapData { effective: word, permitted: word, inheritable: word });
+
+    // SAFETY: 헤더와 두 항목 데이터는 호출 동안 유효하다
+    let ret = unsafe { libc::syscall(libc::SYS_capset, &header as *const CapHeader, data.as_ptr()) };
+    if ret < 0 {
+        return Err(Errno::last());
+    }
+    Ok(())
+}
+
+/// 현재 스레드의 유효 집합
+fn capget() -> Result<u64, Errno> {
+    let mut header = CapHeader { version: LINUX_CAPABILITY_VERSION_3, pid: 0 };
+    let mut data = [CapData::default(); 2];
+
+    // SAFETY: 헤더와 두 항목 버퍼는 호출 동안 유효하다
+    let ret = unsafe { libc::syscall(libc::SYS_capget, &mut header as *mut CapHeader, data.as_mut_ptr()) };
+    if ret < 0 {
+        return Err(Errno::last());
+    }
+    Ok(u64::from(data[0].effective) | (u64::from(data[1].effective) << 32))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 적용한 단계를 기록하고 지정한 단계에서 실패하는 자격 증명
+    #[derive(Default)]
+    struct RecordingCredentials {
+        applied: Vec<Step>,
+        fail_on: Option<Step>,
+    }
+
+    impl Credentials for RecordingCredentials {
+        fn apply(&mut self, step: &Step) -> Result<()> {
+            if self.fail_on.as_ref() == Some(step) {
+                return Err(Errno::EPERM.into());
+            }
+            self.applied.push(step.clone());
+            Ok(())
+        }
+    }
+
+    fn target(keep: Vec<Capability>) -> Target {
+        Target {
+            user: "swift-guard".to_string(),
+            uid: Uid::from_raw(990),
+            gid: Gid::from_raw(990),
+            groups: vec![Gid::from_raw(990), Gid::from_raw(44)],
+            keep,
+        }
+    }
+
+    fn general(user: Option<&str>, group: Option<&str>, keep: &[&str]) -> GeneralConfig {
+        let mut general = crate::config::DaemonConfig::default().general;
+        general.run_as_user = user.map(str::to_string);
+        general.run_as_group = group.map(str::to_string);
+        general.keep_capabilities = keep.iter().map(|name| name.to_string()).collect();
+        general
+    }
+
+    #[test]
+    fn test_parse_capabilities() {
+        assert_eq!(Capability::parse("CAP_NET_ADMIN"), Some(Capability::NetAdmin));
+        assert_eq!(Capability::parse("bpf"), Some(Capability::Bpf));
+        assert_eq!(Capability::parse("cap_sys_admin"), Some(Capability::SysAdmin));
+        assert_eq!(Capability::parse("CAP_SETUID"), None);
+
+        let names = ["CAP_BPF", "net_admin", "CAP_NET_ADMIN"].map(str::to_string);
+        assert_eq!(parse_capabilities(&names).unwrap(), vec![Capability::NetAdmin, Capability::Bpf]);
+        let err = parse_capabilities(&["CAP_SETUID".to_string()]).unwrap_err();
+        assert!(err.to_string().contains("unsupported capability CAP_SETUID"), "{}", err);
+
+        let mask = Capability::mask(&[Capability::NetAdmin, Capability::Bpf]);
+        assert_eq!(mask, (1 << 12) | (1 << 39));
+        assert_eq!(Capability::from_mask(mask | (1 << 7)), vec![Capability::NetAdmin, Capability::Bpf]);
+    }
+
+    #[test]
+    fn test_plan_order() {
+        // 그룹과 gid는 root일 때만 바꿀 수 있으므로 uid보다 먼저
+        let steps = plan(&target(vec![Capability::NetAdmin, Capability::Bpf]));
+        assert_eq!(steps, vec![
+            Step::KeepCaps(true),
+            Step::SetGroups(vec![Gid::from_raw(990), Gid::from_raw(44)]),
+            Step::SetGid(Gid::from_raw(990)),
+            Step::SetUid(Uid::from_raw(990)),
+            Step::SetCapabilities(vec![Capability::NetAdmin, Capability::Bpf]),
+            Step::RaiseAmbient(Capability::NetAdmin),
+            Step::RaiseAmbient(Capability::Bpf),
+            Step::KeepCaps(false),
+            Step::VerifyNoRoot,
+        ]);
+
+        // 남길 capability가 없으면 모두 버림
+        let steps = plan(&target(Vec::new()));
+        assert_eq!(steps, vec![
+            Step::SetGroups(vec![Gid::from_raw(990), Gid::from_raw(44)]),
+            Step::SetGid(Gid::from_raw(990)),
+            Step::SetUid(Uid::from_raw(990)),
+            Step::VerifyNoRoot,
+        ]);
+    }
+
+    #[test]
+    fn test_drop_stops_at_failure() {
+        let mut credentials = RecordingCredentials::default();
+        drop_privileges(&mut credentials, &target(vec![Capability::NetAdmin])).unwrap();
+        assert_eq!(credentials.applied.last(), Some(&Step::VerifyNoRoot));
+
+        // gid 전환에 실패하면 uid는 바꾸지 않음
+        let mut credentials = RecordingCredentials { fail_on: Some(Step::SetGid(Gid::from_raw(990))), ..Default::default() };
+        let err = drop_privileges(&mut credentials, &target(vec![Capability::NetAdmin])).unwrap_err();
+        assert_eq!(format!("{:#}", err), "Failed to set group id 990: EPERM: Operation not permitted");
+        assert!(!credentials.applied.iter().any(|step| matches!(step, Step::SetUid(_))));
+
+        let mut credentials = RecordingCredentials { fail_on: Some(Step::VerifyNoRoot), ..Default::default() };
+        let err = drop_privileges(&mut credentials, &target(Vec::new())).unwrap_err();
+        assert!(err.to_string().contains("root cannot be regained"));
+    }
+
+    #[test]
+    fn test_target_from_config() {
+        assert_eq!(Target::from_config(&general(None, None, &[])).unwrap(), None);
+
+        let err = Target::from_config(&general(None, Some("daemon"), &[])).unwrap_err();
+        assert!(err.to_string().contains("requires general.run_as_user"));
+        let err = Target::from_config(&general(None, None, &["CAP_NET_ADMIN"])).unwrap_err();
+        assert!(err.to_string().contains("requires general.run_as_user"));
+
+        let err = Target::from_config(&general(Some("no-such-user-swift-guard"), None, &[])).unwrap_err();
+        assert!(err.to_string().contains("unknown user no-such-user-swift-guard"));
+        let err = Target::from_config(&general(Some("root"), None, &[])).unwrap_err();
+        assert!(err.to_string().contains("must not be root"));
+    }
+
+    #[test]
+    fn test_require_capabilities() {
+        let privileges = DroppedPrivileges { user: "swift-guard".to_string(), effective: vec![Capability::NetAdmin] };
+        let err = privileges.require("attach", ATTACH_CAPABILITIES).unwrap_err();
+        assert_eq!(err.code(), swift_guard::error::ErrorCode::PermissionDenied);
+        assert_eq!(err.to_string(), "attach needs CAP_BPF or CAP_SYS_ADMIN, which the daemon no longer has after \
+            switching to user swift-guard (add it to general.keep_capabilities and restart the daemon)");
+
+        let privileges = DroppedPrivileges { effective: Vec::new(), ..privileges };
+        assert!(privileges.require("attach", ATTACH_CAPABILITIES).unwrap_err().to_string()
+            .starts_with("attach needs CAP_NET_ADMIN and CAP_BPF or CAP_SYS_ADMIN,"));
+
+        let privileges = DroppedPrivileges { effective: vec![Capability::NetAdmin, Capability::SysAdmin], ..privileges };
+        assert!(privileges.require("attach", ATTACH_CAPABILITIES).is_ok());
+    }
+}
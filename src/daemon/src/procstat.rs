@@ -0,0 +1,245 @@
+//! 프로세스 자체 통계 모듈
+//! /proc/self에서 데몬 프로세스의 CPU, 메모리, 파일 디스크립터 사용량 수집
+//!
+//! 파싱 함수는 파일 내용만 받으므로 커널 버전별로 캡처한 /proc 파일로 검증한다.
+//! stat 필드 수는 커널마다 다르므로(2.6.32는 44개, 5.x 이후는 52개) 앞쪽
+//! 필드만 쓰고, comm은 공백이나 괄호를 포함할 수 있으므로 마지막 ')'에서 자른다.
+
+use anyhow::{anyhow, Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use swift_guard::api::DaemonSelfStats;
+
+/// /proc/[pid]/stat에서 쓰는 필드
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProcStat {
+    /// 사용자 모드 CPU 시간 (클록 틱)
+    pub utime: u64,
+    /// 커널 모드 CPU 시간 (클록 틱)
+    pub stime: u64,
+    /// 스레드 수
+    pub num_threads: u64,
+    /// 부팅 후 프로세스 시작 시각 (클록 틱)
+    pub starttime: u64,
+    /// 상주 페이지 수
+    pub rss_pages: u64,
+}
+
+impl ProcStat {
+    /// 누적 CPU 시간 (클록 틱)
+    pub fn cpu_ticks(&self) -> u64 {
+        self.utime + self.stime
+    }
+}
+
+/// /proc/[pid]/stat 파싱
+pub fn parse_stat(content: &str) -> Result<ProcStat> {
+    let end = content.rfind(')')
+        .ok_or_else(|| anyhow!("Malformed /proc stat: no command name"))?;
+    // fields[0]은 3번째 필드(state)
+    let fields: Vec<&str> = content[end + 1..].split_whitespace().collect();
+
+    let field = |number: usize| -> Result<u64> {
+        let value = fields.get(number - 3)
+            .ok_or_else(|| anyhow!("Malformed /proc stat: missing field {}", number))?;
+        value.parse()
+            .with_context(|| format!("Malformed /proc stat: invalid field {} '{}'", number, value))
+    };
+
+    Ok(ProcStat {
+        utime: field(14)?,
+        stime: field(15)?,
+        num_threads: field(20)?,
+        starttime: field(22)?,
+        rss_pages: field(24)?,
+    })
+}
+
+/// /proc/[pid]/status의 VmRSS (바이트, 커널 스레드 등 항목이 없으면 None)
+pub fn parse_status_rss(content: &str) -> Option<u64> {
+    content.lines()
+        .find_map(|line| line.strip_prefix("VmRSS:"))
+        .and_then(|value| value.split_whitespace().next())
+        .and_then(|kb| kb.parse::<u64>().ok())
+        .map(|kb| kb * 1024)
+}
+
+/// /proc/uptime의 시스템 가동 시간 (초)
+pub fn parse_uptime(content: &str) -> Result<f64> {
+    let value = content.split_whitespace().next()
+        .ok_or_else(|| anyhow!("Malformed /proc/uptime: empty"))?;
+    value.parse()
+        .with_context(|| format!("Malformed /proc/uptime: invalid uptime '{}'", value))
+}
+
+/// 열린 파일 디스크립터 수 (/proc/[pid]/fd 항목 수)
+pub fn count_fds(fd_dir: &Path) -> Result<u64> {
+    let entries = fs::read_dir(fd_dir)
+        .with_context(|| format!("Failed to read {}", fd_dir.display()))?;
+    Ok(entries.filter_map(|entry| entry.ok()).count() as u64)
+}
+
+/// 데몬 자체 통계 수집기
+///
+/// CPU 사용률은 직전 수집과의 CPU 시간 차이로 계산하며, 첫 수집에서는 프로세스
+/// 시작 이후 평균을 쓴다. tokio 작업 수와 이벤트 채널 지연은 호출자가 채운다.
+#[derive(Debug)]
+pub struct SelfSampler {
+    /// 프로세스 디렉터리 (/proc/self)
+    proc_dir: PathBuf,
+    /// 시스템 가동 시간 파일 (/proc/uptime)
+    uptime_path: PathBuf,
+    /// 초당 클록 틱
+    clock_ticks: u64,
+    /// 페이지 크기 (VmRSS가 없을 때 사용)
+    page_size: u64,
+    /// 직전 수집의 (시스템 가동 시간, 누적 CPU 틱)
+    previous: Option<(f64, u64)>,
+}
+
+impl Default for SelfSampler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SelfSampler {
+    /// 현재 프로세스용 수집기 생성
+    pub fn new() -> Self {
+        let clock_ticks = unsafe { libc::sysconf(libc::_SC_CLK_TCK) };
+        let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+
+        Self::with_paths("/proc/self", "/proc/uptime",
+            if clock_ticks > 0 { clock_ticks as u64 } else { 100 },
+            if page_size > 0 { page_size as u64 } else { 4096 })
+    }
+
+    /// 경로와 시스템 상수를 지정해 수집기 생성
+    pub fn with_paths(proc_dir: impl Into<PathBuf>, uptime_path: impl Into<PathBuf>,
+                      clock_ticks: u64, page_size: u64) -> Self {
+        Self {
+            proc_dir: proc_dir.into(),
+            uptime_path: uptime_path.into(),
+            clock_ticks,
+            page_size,
+            previous: None,
+        }
+    }
+
+    /// 통계 수집
+    pub fn sample(&mut self) -> Result<DaemonSelfStats> {
+        let stat = parse_stat(&read(&self.proc_dir.join("stat"))?)?;
+        let uptime = parse_uptime(&read(&self.uptime_path)?)?;
+        let rss_bytes = fs::read_to_string(self.proc_dir.join("status")).ok()
+            .and_then(|status| parse_status_rss(&status))
+            .unwrap_or(stat.rss_pages * self.page_size);
+        let open_fds = count_fds(&self.proc_dir.join("fd"))?;
+
+        let started = stat.starttime as f64 / self.clock_ticks as f64;
+        let (since, base_ticks) = self.previous.unwrap_or((started, 0));
+        let elapsed = uptime - since;
+        let cpu_secs = stat.cpu_ticks().saturating_sub(base_ticks) as f64 / self.clock_ticks as f64;
+        self.previous = Some((uptime, stat.cpu_ticks()));
+
+        Ok(DaemonSelfStats {
+            cpu_percent: if elapsed > 0.0 { cpu_secs / elapsed * 100.0 } else { 0.0 },
+            rss_bytes,
+            open_fds,
+            tokio_tasks: 0,
+            uptime_secs: (uptime - started).max(0.0) as u64,
+            event_lag: 0,
+        })
+    }
+}
+
+/// 파일 읽기 (경로를 오류에 포함)
+fn read(path: &Path) -> Result<String> {
+    fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STAT_2_6_32: &str = include_str!("../testdata/proc/linux-2.6.32/stat");
+    const STATUS_2_6_32: &str = include_str!("../testdata/proc/linux-2.6.32/status");
+    const STAT_6_1: &str = include_str!("../testdata/proc/linux-6.1/stat");
+    const STATUS_6_1: &str = include_str!("../testdata/proc/linux-6.1/status");
+    const UPTIME_6_1: &str = include_str!("../testdata/proc/linux-6.1/uptime");
+
+    #[test]
+    fn test_parse_stat_fixtures() {
+        // 44개 필드, 공백이 있는 comm
+        assert_eq!(parse_stat(STAT_2_6_32).unwrap(), ProcStat {
+            utime: 125,
+            stime: 37,
+            num_threads: 6,
+            starttime: 8813,
+            rss_pages: 3097,
+        });
+
+        // 52개 필드, 괄호가 있는 comm
+        let stat = parse_stat(STAT_6_1).unwrap();
+        assert_eq!(stat, ProcStat {
+            utime: 4521,
+            stime: 1893,
+            num_threads: 14,
+            starttime: 1523400,
+            rss_pages: 12288,
+        });
+        assert_eq!(stat.cpu_ticks(), 6414);
+
+        assert!(parse_stat("").is_err());
+        assert!(parse_stat("1 (init) S 1 1").is_err());
+        assert!(parse_stat(&STAT_6_1.replace(" 4521 ", " x ")).is_err());
+    }
+
+    #[test]
+    fn test_parse_status_and_uptime() {
+        assert_eq!(parse_status_rss(STATUS_2_6_32), Some(12388 * 1024));
+        assert_eq!(parse_status_rss(STATUS_6_1), Some(49152 * 1024));
+        // 커널 스레드에는 VmRSS가 없음
+        assert_eq!(parse_status_rss("Name:\tkworker/0:1\nState:\tI (idle)\n"), None);
+
+        assert_eq!(parse_uptime(UPTIME_6_1).unwrap(), 20000.5);
+        assert!(parse_uptime("").is_err());
+        assert!(parse_uptime("abc 1.0").is_err());
+    }
+
+    #[test]
+    fn test_self_sampler() {
+        let dir = std::env::temp_dir().join(format!("swift-guard-procstat-{}", std::process::id()));
+        let proc_dir = dir.join("self");
+        fs::create_dir_all(proc_dir.join("fd")).unwrap();
+        for fd in 0..3 {
+            fs::write(proc_dir.join("fd").join(fd.to_string()), "").unwrap();
+        }
+        fs::write(proc_dir.join("stat"), STAT_6_1).unwrap();
+        fs::write(dir.join("uptime"), UPTIME_6_1).unwrap();
+
+        let mut sampler = SelfSampler::with_paths(&proc_dir, dir.join("uptime"), 100, 4096);
+
+        // status가 없으면 stat의 상주 페이지 수 사용, CPU는 시작 이후 평균
+        let first = sampler.sample().unwrap();
+        assert_eq!(first.rss_bytes, 12288 * 4096);
+        assert_eq!(first.open_fds, 3);
+        assert_eq!(first.uptime_secs, 4766); // 20000.5 - 15234.0
+        assert!((first.cpu_percent - 64.14 / 4766.5 * 100.0).abs() < 1e-9);
+
+        // 10초 동안 CPU 5초 사용
+        fs::write(proc_dir.join("status"), STATUS_6_1).unwrap();
+        fs::write(proc_dir.join("stat"), STAT_6_1.replace(" 4521 1893 ", " 4821 2093 ")).unwrap();
+        fs::write(dir.join("uptime"), "20010.50 150080.00\n").unwrap();
+
+        let second = sampler.sample().unwrap();
+        assert_eq!(second.rss_bytes, 49152 * 1024);
+        assert_eq!(second.uptime_secs, 4776);
+        assert!((second.cpu_percent - 50.0).abs() < 1e-9);
+
+        fs::remove_file(proc_dir.join("stat")).unwrap();
+        assert!(sampler.sample().is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,344 @@
+//! 규칙 할당량 모듈
+//! 규칙별 누적 바이트·패킷 사용량을 할당량과 비교해 넘은 규칙의 커널 액션을 바꿈
+//!
+//! 사용량은 텔레메트리 루프가 규칙 통계 카운터의 증가분으로 누적한다. 매일
+//! `quota.reset_hour`(UTC) 시각에 새 기간이 시작되면 사용량이 0으로 돌아가고
+//! 바뀌었던 액션도 원래대로 돌아간다. 기간 시작 시각과 레이블별 사용량은 상태
+//! 파일에 저장하므로 데몬을 다시 시작해도 같은 기간의 사용량이 이어진다.
+
+use anyhow::{anyhow, Context, Result};
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::PathBuf;
+
+use crate::telemetry::counter_delta;
+
+use swift_guard::api::{QuotaInfo, RuleInfo};
+use swift_guard::error::InvalidArgument;
+use swift_guard::utils;
+
+/// 하루 (초)
+const DAY_SECS: u64 = 86_400;
+
+/// 할당량을 넘었을 때 기본 액션 (drop)
+pub const DEFAULT_QUOTA_ACTION: u8 = 2;
+
+/// 리디렉션 액션 (대상이 필요하므로 할당량 액션으로 쓸 수 없음)
+const ACTION_REDIRECT: u8 = 3;
+
+/// 규칙 사용량 할당량
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RuleQuota {
+    /// 기간 동안 허용하는 바이트 수
+    pub bytes: Option<u64>,
+    /// 기간 동안 허용하는 패킷 수
+    pub packets: Option<u64>,
+    /// 할당량을 넘으면 커널에 기록할 액션
+    pub action: u8,
+}
+
+impl RuleQuota {
+    /// 규칙 정의의 할당량 항목 검증 (할당량이 없으면 None)
+    pub fn from_spec(bytes: Option<u64>, packets: Option<u64>, action: Option<u8>) -> Result<Option<Self>, InvalidArgument> {
+        if bytes.is_none() && packets.is_none() {
+            return match action {
+                Some(_) => Err(InvalidArgument::new("quota_action", "quota_action requires quota_bytes or quota_packets")),
+                None => Ok(None),
+            };
+        }
+
+        if bytes == Some(0) {
+            return Err(InvalidArgument::new("quota_bytes", "Quota must be greater than zero"));
+        }
+        if packets == Some(0) {
+            return Err(InvalidArgument::new("quota_packets", "Quota must be greater than zero"));
+        }
+
+        let action = action.unwrap_or(DEFAULT_QUOTA_ACTION);
+        if action == ACTION_REDIRECT || utils::action_num_to_name(action) == "unknown" {
+            return Err(InvalidArgument::new("quota_action",
+                format!("Invalid quota action {} (expected pass, drop or count)", action)));
+        }
+
+        Ok(Some(Self { bytes, packets, action }))
+    }
+
+    /// API 할당량 정보로 변환
+    pub fn to_info(self) -> QuotaInfo {
+        QuotaInfo {
+            bytes: self.bytes,
+            packets: self.packets,
+            action: utils::action_num_to_name(self.action),
+        }
+    }
+}
+
+/// 현재 기간의 규칙 사용량
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuotaUsage {
+    pub bytes: u64,
+    pub packets: u64,
+}
+
+impl QuotaUsage {
+    /// 바이트 또는 패킷 할당량을 넘었는지 확인
+    pub fn exceeds(&self, bytes: Option<u64>, packets: Option<u64>) -> bool {
+        bytes.is_some_and(|limit| self.bytes > limit) || packets.is_some_and(|limit| self.packets > limit)
+    }
+}
+
+/// 할당량 초과 상태 변경
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuotaChange {
+    /// 규칙 키 (`RuleInfo::key`)
+    pub label: String,
+    /// true면 할당량 액션으로, false면 원래 액션으로 되돌림
+    pub exceeded: bool,
+    pub usage: QuotaUsage,
+}
+
+/// 상태 파일 형식
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+struct QuotaState {
+    /// 현재 기간 시작 시각 (UNIX 초)
+    period_start: u64,
+    /// 레이블별 사용량
+    usage: BTreeMap<String, QuotaUsage>,
+}
+
+/// `now`가 속한 기간의 시작 시각 (매일 `reset_hour`시 UTC)
+pub fn period_start(now: u64, reset_hour: u32) -> u64 {
+    let reset = now - now % DAY_SECS + u64::from(reset_hour) * 3600;
+    if reset <= now { reset } else { reset.saturating_sub(DAY_SECS) }
+}
+
+/// 규칙별 할당량 사용량 추적기
+///
+/// 삭제된 규칙의 사용량도 기간이 끝날 때까지 남겨 둔다. 데몬 시작 직후에는 규칙이
+/// 아직 다시 추가되지 않았을 수 있으므로, 같은 레이블의 규칙이 돌아오면 사용량을
+/// 이어서 센다.
+#[derive(Debug)]
+pub struct QuotaTracker {
+    reset_hour: u32,
+    state: QuotaState,
+    /// 직전 관측의 규칙별 누적 카운터 (패킷, 바이트)
+    previous: HashMap<String, (u64, u64)>,
+    /// 상태 파일 (없으면 저장하지 않음)
+    path: Option<PathBuf>,
+}
+
+impl QuotaTracker {
+    /// 저장하지 않는 추적기 생성
+    pub fn new(reset_hour: u32) -> Result<Self> {
+        if reset_hour > 23 {
+            return Err(anyhow!("Invalid quota reset_hour {} (expected 0-23)", reset_hour));
+        }
+
+        Ok(Self {
+            reset_hour,
+            state: QuotaState::default(),
+            previous: HashMap::new(),
+            path: None,
+        })
+    }
+
+    /// 상태 파일에서 추적기 복원 (파일이 없으면 빈 상태)
+    pub fn load(path: impl Into<PathBuf>, reset_hour: u32) -> Result<Self> {
+        let path = path.into();
+        let mut tracker = Self::new(reset_hour)?;
+
+        match fs::read_to_string(&path) {
+            Ok(content) => {
+                tracker.state = serde_json::from_str(&content)
+                    .with_context(|| format!("Invalid quota state file {}", path.display()))?;
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+        }
+
+        tracker.path = Some(path);
+        Ok(tracker)
+    }
+
+    /// 상태 파일 저장 (임시 파일에 쓴 뒤 교체)
+    pub fn save(&self) -> Result<()> {
+        let path = match &self.path {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+        }
+
+        let content = serde_json::to_vec_pretty(&self.state)
+            .context("Failed to serialize quota state")?;
+        let tmp = path.with_extension("tmp");
+        fs::write(&tmp, content)
+            .with_context(|| format!("Failed to write {}", tmp.display()))?;
+        fs::rename(&tmp, path)
+            .with_context(|| format!("Failed to replace {}", path.display()))
+    }
+
+    /// 규칙 통계 관측 (텔레메트리 수집 주기마다 호출)
+    ///
+    /// 할당량이 있는 규칙의 카운터 증가분을 사용량에 더하고, 초과 여부가
+    /// `RuleInfo::quota_exceeded`와 달라진 규칙을 돌려준다. 처음 보는 규칙은
+    /// 카운터 전체를 증가분으로 센다.
+    pub fn observe(&mut self, rules: &[RuleInfo], now: u64) -> Vec<QuotaChange> {
+        let period = period_start(now, self.reset_hour);
+        if period > self.state.period_start {
+            if !self.state.usage.is_empty() {
+                info!("Quota period started, resetting usage of {} rules", self.state.usage.len());
+            }
+            self.state = QuotaState { period_start: period, usage: BTreeMap::new() };
+        }
+
+        let mut changes = Vec::new();
+        for rule in rules {
+            let key = rule.key();
+            let current = (rule.stats.packets, rule.stats.bytes);
+            let previous = self.previous.insert(key.clone(), current).unwrap_or((0, 0));
+
+            let quota = match &rule.quota {
+                Some(quota) => quota,
+                None => continue,
+            };
+
+            let usage = self.state.usage.entry(key.clone()).or_default();
+            usage.packets += counter_delta(previous.0, current.0);
+            usage.bytes += counter_delta(previous.1, current.1);
+
+            let exceeded = usage.exceeds(quota.bytes, quota.packets);
+            if exceeded != rule.quota_exceeded {
+                changes.push(QuotaChange { label: key, exceeded, usage: *usage });
+            }
+        }
+
+        let keys: HashSet<String> = rules.iter().map(RuleInfo::key).collect();
+        self.previous.retain(|key, _| keys.contains(key));
+        changes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use swift_guard::api::RuleStats;
+
+    /// 2023-11-14 00:00:00 UTC
+    const MIDNIGHT: u64 = 1_699_920_000;
+
+    fn rule(label: &str, quota: Option<QuotaInfo>, packets: u64, bytes: u64, exceeded: bool) -> RuleInfo {
+        RuleInfo {
+            label: label.to_string(),
+            action: "pass".to_string(),
+            src_ip: Some("10.0.0.1".to_string()),
+            quota,
+            quota_exceeded: exceeded,
+            stats: RuleStats { packets, bytes, ..RuleStats::default() },
+            ..RuleInfo::default()
+        }
+    }
+
+    /// 레이블의 현재 기간 사용량
+    fn usage(tracker: &QuotaTracker, label: &str) -> QuotaUsage {
+        tracker.state.usage.get(label).copied().unwrap_or_default()
+    }
+
+    fn gigabytes(limit: u64) -> Option<QuotaInfo> {
+        Some(QuotaInfo { bytes: Some(limit << 30), packets: None, action: "drop".to_string() })
+    }
+
+    #[test]
+    fn test_quota_from_spec() {
+        assert_eq!(RuleQuota::from_spec(None, None, None).unwrap(), None);
+        assert_eq!(RuleQuota::from_spec(Some(10 << 30), None, None).unwrap(),
+            Some(RuleQuota { bytes: Some(10 << 30), packets: None, action: 2 }));
+        assert_eq!(RuleQuota::from_spec(None, Some(1000), Some(4)).unwrap().unwrap().action, 4);
+
+        assert_eq!(RuleQuota::from_spec(None, None, Some(2)).unwrap_err().field, "quota_action");
+        assert_eq!(RuleQuota::from_spec(Some(0), None, None).unwrap_err().field, "quota_bytes");
+        assert_eq!(RuleQuota::from_spec(None, Some(0), None).unwrap_err().field, "quota_packets");
+        assert_eq!(RuleQuota::from_spec(Some(1), None, Some(3)).unwrap_err().field, "quota_action");
+        assert_eq!(RuleQuota::from_spec(Some(1), None, Some(9)).unwrap_err().field, "quota_action");
+    }
+
+    #[test]
+    fn test_period_start() {
+        assert_eq!(period_start(MIDNIGHT + 5, 0), MIDNIGHT);
+        assert_eq!(period_start(MIDNIGHT + 23 * 3600, 6), MIDNIGHT + 6 * 3600);
+        // 초기화 시각 전이면 전날 초기화 시각부터
+        assert_eq!(period_start(MIDNIGHT + 3600, 6), MIDNIGHT - 18 * 3600);
+        assert_eq!(period_start(MIDNIGHT + 6 * 3600, 6), MIDNIGHT + 6 * 3600);
+        assert!(QuotaTracker::new(24).is_err());
+    }
+
+    #[test]
+    fn test_quota_exceeded_and_daily_reset() {
+        let mut tracker = QuotaTracker::new(6).unwrap();
+        let now = MIDNIGHT + 12 * 3600;
+
+        // 할당량이 없는 규칙은 추적하지 않음
+        let changes = tracker.observe(&[
+            rule("host", gigabytes(10), 1_000, 4 << 30, false),
+            rule("other", None, 5_000, 20 << 30, false),
+        ], now);
+        assert!(changes.is_empty());
+        assert_eq!(usage(&tracker, "host"), QuotaUsage { bytes: 4 << 30, packets: 1_000 });
+        assert_eq!(usage(&tracker, "other"), QuotaUsage::default());
+
+        // 카운터 증가분만 더함, 10 GB를 넘으면 초과
+        assert!(tracker.observe(&[rule("host", gigabytes(10), 2_000, 10 << 30, false)], now + 10).is_empty());
+        let changes = tracker.observe(&[rule("host", gigabytes(10), 2_001, (10 << 30) + 1, false)], now + 20);
+        assert_eq!(changes, vec![QuotaChange {
+            label: "host".to_string(),
+            exceeded: true,
+            usage: QuotaUsage { bytes: (10 << 30) + 1, packets: 2_001 },
+        }]);
+
+        // 이미 표시된 규칙은 다시 보고하지 않음, 카운터가 줄면 다시 센 것으로 봄
+        assert!(tracker.observe(&[rule("host", gigabytes(10), 10, 100, true)], now + 30).is_empty());
+        assert_eq!(usage(&tracker, "host").bytes, (10 << 30) + 101);
+
+        // 다음 날 06:00에 초기화되어 원래 액션으로 복원
+        let changes = tracker.observe(&[rule("host", gigabytes(10), 20, 200, true)], MIDNIGHT + 30 * 3600);
+        assert_eq!(changes, vec![QuotaChange {
+            label: "host".to_string(),
+            exceeded: false,
+            usage: QuotaUsage { bytes: 100, packets: 10 },
+        }]);
+    }
+
+    #[test]
+    fn test_quota_state_persists() {
+        let dir = std::env::temp_dir().join(format!("swift-guard-quota-{}", std::process::id()));
+        let path = dir.join("quota-state.json");
+        let now = MIDNIGHT + 3600;
+
+        let mut tracker = QuotaTracker::load(&path, 0).unwrap();
+        tracker.observe(&[rule("host", gigabytes(10), 100, 6 << 30, false)], now);
+        tracker.save().unwrap();
+        assert!(!path.with_extension("tmp").exists());
+
+        // 재시작 후 커널 카운터는 0부터 다시 시작
+        let mut restarted = QuotaTracker::load(&path, 0).unwrap();
+        assert_eq!(usage(&restarted, "host"), QuotaUsage { bytes: 6 << 30, packets: 100 });
+        let changes = restarted.observe(&[rule("host", gigabytes(10), 50, 5 << 30, false)], now + 60);
+        assert_eq!(changes.len(), 1);
+        assert!(changes[0].exceeded);
+
+        // 저장한 기간이 지났으면 사용량을 버림
+        let mut next_day = QuotaTracker::load(&path, 0).unwrap();
+        assert!(next_day.observe(&[rule("host", gigabytes(10), 1, 1, false)], now + DAY_SECS).is_empty());
+        assert_eq!(usage(&next_day, "host"), QuotaUsage { bytes: 1, packets: 1 });
+
+        fs::write(&path, "not json").unwrap();
+        assert!(QuotaTracker::load(&path, 0).is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
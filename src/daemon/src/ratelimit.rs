@@ -0,0 +1,201 @@
+//! 요청 빈도 제한 모듈
+//! 피어별 토큰 버킷으로 API 요청 빈도 제한
+//!
+//! 버킷 용량은 초당 허용 요청 수와 같아서 1초 분량의 요청은 한 번에 몰려도
+//! 통과한다. 가득 찬 버킷은 없는 버킷과 같으므로 주기적으로 지워 일회성 피어가
+//! 쌓이지 않게 한다.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::logthrottle::{Clock, SystemClock};
+
+/// 가득 찬 버킷 정리 간격
+const SWEEP_INTERVAL: Duration = Duration::from_secs(60);
+
+/// 피어별 버킷
+#[derive(Debug)]
+struct Bucket {
+    /// 남은 토큰 수
+    tokens: f64,
+    /// 마지막 충전 시각
+    updated: Instant,
+}
+
+/// 피어별 토큰 버킷 빈도 제한기
+pub struct RateLimiter {
+    /// 초당 허용 요청 수 (버킷 용량)
+    limit: u32,
+    clock: Box<dyn Clock>,
+    /// (피어별 버킷, 마지막 정리 시각)
+    state: Mutex<(HashMap<String, Bucket>, Instant)>,
+}
+
+impl std::fmt::Debug for RateLimiter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RateLimiter").field("limit", &self.limit).finish()
+    }
+}
+
+impl RateLimiter {
+    /// 시스템 시계로 제한기 생성 (`limit`은 1 이상으로 보정)
+    pub fn new(limit: u32) -> Self {
+        Self::with_clock(limit, SystemClock)
+    }
+
+    /// 주어진 시계로 제한기 생성
+    pub fn with_clock(limit: u32, clock: impl Clock + 'static) -> Self {
+        let now = clock.now();
+        Self {
+            limit: limit.max(1),
+            clock: Box::new(clock),
+            state: Mutex::new((HashMap::new(), now)),
+        }
+    }
+
+    /// 초당 허용 요청 수
+    pub fn limit(&self) -> u32 {
+        self.limit
+    }
+
+    /// 요청 허용 여부 확인 (거부하면 다음 토큰까지 남은 시간 반환)
+    pub fn check(&self, peer: &str) -> Result<(), Duration> {
+        let now = self.clock.now();
+        let rate = self.limit as f64;
+        let mut state = match self.state.lock() {
+            Ok(state) => state,
+            Err(_) => return Ok(()), // 잠금이 깨졌으면 제한하지 않음
+        };
+        let (buckets, last_sweep) = &mut *state;
+
+        if now.saturating_duration_since(*last_sweep) >= SWEEP_INTERVAL {
+            buckets.retain(|_, bucket| refill(bucket, now, rate) < rate);
+            *last_sweep = now;
+        }
+
+        let bucket = buckets.entry(peer.to_string())
+            .or_insert(Bucket { tokens: rate, updated: now });
+        if refill(bucket, now, rate) >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - bucket.tokens) / rate))
+        }
+    }
+}
+
+/// 경과 시간만큼 토큰 충전 후 남은 토큰 수 반환
+fn refill(bucket: &mut Bucket, now: Instant, rate: f64) -> f64 {
+    let elapsed = now.saturating_duration_since(bucket.updated).as_secs_f64();
+    bucket.tokens = (bucket.tokens + elapsed * rate).min(rate);
+    bucket.updated = now;
+    bucket.tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    /// 수동으로 진행하는 시계
+    #[derive(Clone)]
+    struct MockClock {
+        start: Instant,
+        offset: Arc<Mutex<Duration>>,
+    }
+
+    impl MockClock {
+        fn new() -> Self {
+            Self { start: Instant::now(), offset: Arc::new(Mutex::new(Duration::ZERO)) }
+        }
+
+        fn advance(&self, by: Duration) {
+            *self.offset.lock().unwrap() += by;
+        }
+    }
+
+    impl Clock for MockClock {
+        fn now(&self) -> Instant {
+            self.start + *self.offset.lock().unwrap()
+        }
+    }
+
+    /// 거부된 요청의 대기 시간 (밀리초, 반올림)
+    fn wait_ms(limiter: &RateLimiter, peer: &str) -> Option<u64> {
+        limiter.check(peer).err().map(|wait| (wait.as_secs_f64() * 1000.0).round() as u64)
+    }
+
+    #[test]
+    fn test_burst_then_refill() {
+        let clock = MockClock::new();
+        let limiter = RateLimiter::with_clock(4, clock.clone());
+
+        // 용량만큼 한 번에 허용
+        for _ in 0..4 {
+            assert_eq!(limiter.check("peer:10.0.0.1"), Ok(()));
+        }
+        assert_eq!(wait_ms(&limiter, "peer:10.0.0.1"), Some(250));
+
+        // 다른 피어는 독립적으로 제한
+        assert_eq!(limiter.check("peer:10.0.0.2"), Ok(()));
+
+        clock.advance(Duration::from_millis(100));
+        assert_eq!(wait_ms(&limiter, "peer:10.0.0.1"), Some(150));
+
+        clock.advance(Duration::from_millis(160));
+        assert_eq!(limiter.check("peer:10.0.0.1"), Ok(()));
+        assert!(limiter.check("peer:10.0.0.1").is_err());
+
+        // 오래 쉬어도 용량 이상으로 쌓이지 않음
+        clock.advance(Duration::from_secs(10));
+        for _ in 0..4 {
+            assert_eq!(limiter.check("peer:10.0.0.1"), Ok(()));
+        }
+        assert!(limiter.check("peer:10.0.0.1").is_err());
+    }
+
+    #[test]
+    fn test_sustained_rate() {
+        let clock = MockClock::new();
+        let limiter = RateLimiter::with_clock(10, clock.clone());
+
+        // 10초 동안 1ms마다 요청하면 초기 용량 + 초당 10개만 통과
+        let mut allowed = 0;
+        for _ in 0..10_000 {
+            if limiter.check("peer:10.0.0.1").is_ok() {
+                allowed += 1;
+            }
+            clock.advance(Duration::from_millis(1));
+        }
+        assert!((109..=111).contains(&allowed), "allowed {}", allowed);
+    }
+
+    /// 추적 중인 피어 수
+    fn tracked_peers(limiter: &RateLimiter) -> usize {
+        limiter.state.lock().unwrap().0.len()
+    }
+
+    #[test]
+    fn test_idle_peers_are_evicted() {
+        let clock = MockClock::new();
+        let limiter = RateLimiter::with_clock(2, clock.clone());
+
+        limiter.check("peer:10.0.0.1").unwrap();
+        limiter.check("peer:10.0.0.2").unwrap();
+        assert_eq!(tracked_peers(&limiter), 2);
+
+        // 정리 간격 직전까지 한 피어만 계속 요청
+        for _ in 0..59 {
+            clock.advance(Duration::from_secs(1));
+            limiter.check("peer:10.0.0.2").unwrap();
+            limiter.check("peer:10.0.0.2").unwrap();
+        }
+        clock.advance(Duration::from_secs(1));
+        limiter.check("peer:10.0.0.2").unwrap();
+        assert_eq!(tracked_peers(&limiter), 1);
+
+        // 0으로 설정해도 최소 1개는 허용
+        assert_eq!(RateLimiter::new(0).limit(), 1);
+    }
+}
@@ -0,0 +1,239 @@
+//! 응답 크기 제한 모듈
+//! 클라이언트가 받을 수 있는 프레임 크기를 넘는 응답을 보내기 전에 줄임
+//!
+//! 직렬화 크기는 버퍼를 만들지 않고 바이트 수만 세어 구한다. 목록 항목은 JSON 배열 안에
+//! 쉼표로 이어지므로, 빈 목록 응답의 크기에 항목 크기와 쉼표를 더하면 프레임 크기가 되고
+//! 같은 응답에는 항상 같은 잘림 위치를 고른다.
+
+use std::io;
+
+use serde::Serialize;
+
+use swift_guard::api::{ApiResponse, ResponseFrame, PAGINATION_HINT};
+use swift_guard::error::ErrorCode;
+
+/// 쓴 바이트 수만 세는 쓰기 대상
+struct ByteCounter(usize);
+
+impl io::Write for ByteCounter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0 += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// JSON 직렬화 크기 (바이트, 직렬화할 수 없으면 usize::MAX)
+pub fn serialized_len<T: Serialize + ?Sized>(value: &T) -> usize {
+    let mut counter = ByteCounter(0);
+    match serde_json::to_writer(&mut counter, value) {
+        Ok(()) => counter.0,
+        Err(_) => usize::MAX,
+    }
+}
+
+/// 크기가 `base`인 빈 목록 응답에 크기가 `items`인 항목을 앞에서부터 몇 개까지 담아도
+/// `limit`을 넘지 않는지 (둘째 항목부터는 쉼표 1바이트가 더 듦)
+pub fn fit_count(base: usize, items: &[usize], limit: usize) -> usize {
+    let mut size = base;
+    for (count, len) in items.iter().enumerate() {
+        size = size.saturating_add(*len).saturating_add((count > 0) as usize);
+        if size > limit {
+            return count;
+        }
+    }
+    items.len()
+}
+
+/// 직렬화한 프레임이 `limit`을 넘으면 줄인 프레임, 아니면 그대로
+///
+/// `Rules`와 `Events`는 목록 앞부분만 담은 `PartialResults`로 바꾸고, 목록이 아닌 응답은
+/// 크기 초과 오류로 바꾼다.
+pub fn guard(frame: ResponseFrame, limit: usize) -> ResponseFrame {
+    let len = serialized_len(&frame);
+    if len <= limit {
+        return frame;
+    }
+
+    let ResponseFrame { request_id, response } = frame;
+    let response = match response {
+        ApiResponse::Rules { rules, total_count, seq, .. } => {
+            truncate(&request_id, rules, limit, |rules| ApiResponse::Rules { rules, total_count, next_cursor: None, seq })
+        },
+        ApiResponse::Events { events, gap, latest_seq } => {
+            truncate(&request_id, events, limit, |events| ApiResponse::Events { events, gap, latest_seq })
+        },
+        _ => ApiResponse::Error {
+            message: format!("Response too large: {} bytes (max {})", len, limit),
+            code: ErrorCode::Internal,
+        },
+    };
+
+    ResponseFrame { request_id, response }
+}
+
+/// 목록을 `limit` 안에 들어가는 앞부분으로 자른 `PartialResults`
+///
+/// 바깥 크기는 `returned` 자리에 `total`을 넣어 재므로 (자릿수가 같거나 많음) 잘린
+/// 프레임은 항상 `limit` 안에 들어간다.
+fn truncate<T, F>(request_id: &Option<String>, mut items: Vec<T>, limit: usize, rebuild: F) -> ApiResponse
+where
+    T: Serialize,
+    F: Fn(Vec<T>) -> ApiResponse,
+{
+    let total = items.len();
+    let lens: Vec<usize> = items.iter().map(serialized_len).collect();
+    let empty = ResponseFrame {
+        request_id: request_id.clone(),
+        response: partial(total, total, rebuild(Vec::new())),
+    };
+
+    let returned = fit_count(serialized_len(&empty), &lens, limit);
+    items.truncate(returned);
+    partial(returned, total, rebuild(items))
+}
+
+fn partial(returned: usize, total: usize, results: ApiResponse) -> ApiResponse {
+    ApiResponse::PartialResults {
+        returned,
+        total,
+        hint: PAGINATION_HINT.to_string(),
+        results: Box::new(results),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use swift_guard::api::{DaemonEvent, EventGap, LinkStateEvent, RuleInfo, SequencedEvent};
+
+    fn rule(label: &str) -> RuleInfo {
+        RuleInfo {
+            label: label.to_string(),
+            src_ip: Some("10.0.0.0/8".to_string()),
+            dst_port: Some("80".to_string()),
+            protocol: "tcp".to_string(),
+            ..RuleInfo::default()
+        }
+    }
+
+    fn rules_frame(count: usize) -> ResponseFrame {
+        let rules: Vec<RuleInfo> = (0..count).map(|i| rule(&format!("rule-{:03}", i))).collect();
+        ResponseFrame {
+            request_id: Some("req-1".to_string()),
+            response: ApiResponse::Rules { total_count: 500, rules, next_cursor: Some("rule-099".to_string()), seq: None },
+        }
+    }
+
+    #[test]
+    fn test_fit_count() {
+        assert_eq!(fit_count(10, &[5, 5, 5], 100), 3);
+        // 10 + 5, + 쉼표 + 5 = 21
+        assert_eq!(fit_count(10, &[5, 5, 5], 21), 2);
+        assert_eq!(fit_count(10, &[5, 5, 5], 20), 1);
+        assert_eq!(fit_count(10, &[5, 5, 5], 14), 0);
+        assert_eq!(fit_count(10, &[], 0), 0);
+        assert_eq!(fit_count(10, &[usize::MAX, 1], 100), 0);
+
+        // 빈 목록 크기에 항목과 쉼표를 더하면 실제 직렬화 크기
+        let items = vec![rule("a"), rule("bb"), rule("ccc")];
+        let lens: Vec<usize> = items.iter().map(serialized_len).collect();
+        let exact = serialized_len(&items);
+        assert_eq!(fit_count(serialized_len(&Vec::<RuleInfo>::new()), &lens, exact), 3);
+        assert_eq!(fit_count(serialized_len(&Vec::<RuleInfo>::new()), &lens, exact - 1), 2);
+    }
+
+    #[test]
+    fn test_guard_truncates_rules() {
+        let frame = rules_frame(100);
+        let full = serialized_len(&frame);
+        assert!(matches!(guard(rules_frame(100), full).response, ApiResponse::Rules { .. }));
+
+        let limit = full / 3;
+        let guarded = guard(frame, limit);
+        assert_eq!(guarded.request_id.as_deref(), Some("req-1"));
+        assert!(serialized_len(&guarded) <= limit);
+
+        let (returned, total, results) = match guarded.response {
+            ApiResponse::PartialResults { returned, total, hint, results } => {
+                assert_eq!(hint, PAGINATION_HINT);
+                (returned, total, results)
+            },
+            other => panic!("unexpected response: {:?}", other),
+        };
+        assert_eq!(total, 100);
+        assert!(returned > 0 && returned <= 33, "{}", returned);
+        match *results {
+            ApiResponse::Rules { rules, total_count, next_cursor, .. } => {
+                assert_eq!(rules.len(), returned);
+                assert_eq!(rules.last().unwrap().label, format!("rule-{:03}", returned - 1));
+                assert_eq!(total_count, 500);
+                assert!(next_cursor.is_none());
+            },
+            other => panic!("unexpected results: {:?}", other),
+        }
+
+        // 같은 응답과 제한이면 같은 위치에서 자르고, 한 항목만 더 넣어도 제한을 넘음
+        let again = guard(rules_frame(100), limit);
+        assert!(matches!(again.response, ApiResponse::PartialResults { returned: r, .. } if r == returned));
+        let mut one_more = guard(rules_frame(100), usize::MAX);
+        if let ApiResponse::Rules { rules, next_cursor, .. } = &mut one_more.response {
+            rules.truncate(returned + 1);
+            *next_cursor = None;
+        }
+        let one_more = ResponseFrame {
+            request_id: one_more.request_id,
+            response: partial(returned + 1, 100, one_more.response),
+        };
+        assert!(serialized_len(&one_more) > limit);
+    }
+
+    #[test]
+    fn test_guard_truncates_events_and_rejects_others() {
+        let events: Vec<SequencedEvent> = (10..60).map(|seq| SequencedEvent {
+            seq,
+            event: DaemonEvent::LinkState(LinkStateEvent {
+                interface: format!("eth{}", seq),
+                state: "down".to_string(),
+                action: None,
+                rules: Vec::new(),
+                timestamp: 1000,
+            }),
+        }).collect();
+        let gap = Some(EventGap { first_seq: 1, last_seq: 9 });
+        let frame = ResponseFrame {
+            request_id: None,
+            response: ApiResponse::Events { events, gap, latest_seq: 60 },
+        };
+        let limit = serialized_len(&frame) / 2;
+
+        let guarded = guard(frame, limit);
+        assert!(serialized_len(&guarded) <= limit);
+        match guarded.response {
+            ApiResponse::PartialResults { returned, total: 50, results, .. } => match *results {
+                // 오래된 것부터 남기므로 마지막 이벤트 다음 순번부터 이어서 조회할 수 있음
+                ApiResponse::Events { events, gap: Some(kept), latest_seq: 60 } => {
+                    assert_eq!(events.len(), returned);
+                    assert_eq!(events.last().unwrap().seq, 9 + returned as u64);
+                    assert_eq!(kept, EventGap { first_seq: 1, last_seq: 9 });
+                },
+                other => panic!("unexpected results: {:?}", other),
+            },
+            other => panic!("unexpected response: {:?}", other),
+        }
+
+        let frame = ResponseFrame {
+            request_id: None,
+            response: ApiResponse::Success { message: "x".repeat(100), warning: None },
+        };
+        match guard(frame, 50).response {
+            ApiResponse::Error { message, code: ErrorCode::Internal } => {
+                assert_eq!(message, "Response too large: 126 bytes (max 50)");
+            },
+            other => panic!("unexpected response: {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,262 @@
+//! 규칙 캐시 모듈
+//! 레이블로 색인한 규칙 캐시와 추가 순서·우선순위·소스 프리픽스 보조 색인
+
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap};
+
+use swift_guard::utils;
+
+use crate::maps::FilterRule;
+
+/// 캐시 항목
+#[derive(Debug, Clone)]
+pub struct RuleEntry {
+    pub rule: FilterRule,
+    /// 추가 순번 (목록 순서)
+    seq: u64,
+}
+
+/// 레이블로 색인한 규칙 캐시
+///
+/// 레이블 조회·추가·삭제는 규칙 수와 관계없이 빠르다. `iter`는 추가 순서로,
+/// `by_priority`는 우선순위가 높은 순서(같으면 추가 순서)로 순회한다.
+/// 교체한 규칙은 원래 자리를 유지한다.
+#[derive(Default, Clone)]
+pub struct RuleCache {
+    entries: HashMap<String, RuleEntry>,
+    /// 추가 순번 → 레이블
+    order: BTreeMap<u64, String>,
+    /// (우선순위 역순, 추가 순번) → 레이블
+    by_priority: BTreeMap<(Reverse<u32>, u64), String>,
+    /// (프리픽스 길이, 마스크된 소스 주소, 추가 순번) → 레이블
+    by_prefix: BTreeMap<(u32, u32, u64), String>,
+    next_seq: u64,
+}
+
+impl RuleCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 레이블로 규칙 존재 여부 확인
+    pub fn contains(&self, label: &str) -> bool {
+        self.entries.contains_key(label)
+    }
+
+    /// 레이블로 규칙 조회
+    pub fn get(&self, label: &str) -> Option<&FilterRule> {
+        self.entries.get(label).map(|entry| &entry.rule)
+    }
+
+    /// 규칙을 맨 뒤에 추가 (같은 레이블의 규칙이 있으면 제거하고 반환)
+    pub fn push(&mut self, rule: FilterRule) -> Option<FilterRule> {
+        let previous = self.remove(&rule.label).map(|entry| entry.rule);
+
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        self.index(RuleEntry { rule, seq });
+
+        previous
+    }
+
+    /// 규칙 삭제 (`restore`로 원래 자리에 되돌릴 수 있는 항목 반환)
+    pub fn remove(&mut self, label: &str) -> Option<RuleEntry> {
+        let entry = self.entries.remove(label)?;
+        self.order.remove(&entry.seq);
+        self.by_priority.remove(&(Reverse(entry.rule.priority), entry.seq));
+        if let Some(key) = prefix_key(&entry.rule, entry.seq) {
+            self.by_prefix.remove(&key);
+        }
+
+        Some(entry)
+    }
+
+    /// 삭제한 항목을 원래 자리에 복원
+    pub fn restore(&mut self, entry: RuleEntry) {
+        self.remove(&entry.rule.label);
+        self.index(entry);
+    }
+
+    /// 같은 레이블의 규칙을 제자리에서 교체 (없으면 아무것도 하지 않고 None)
+    pub fn replace(&mut self, rule: FilterRule) -> Option<FilterRule> {
+        let old = self.remove(&rule.label)?;
+        self.index(RuleEntry { rule, seq: old.seq });
+
+        Some(old.rule)
+    }
+
+    /// 규칙 수정 (레이블은 바꿀 수 없음, 규칙이 없으면 false)
+    pub fn modify<F>(&mut self, label: &str, f: F) -> bool
+    where
+        F: FnOnce(&mut FilterRule),
+    {
+        let mut entry = match self.remove(label) {
+            Some(entry) => entry,
+            None => return false,
+        };
+        f(&mut entry.rule);
+        debug_assert_eq!(entry.rule.label, label);
+        self.index(entry);
+
+        true
+    }
+
+    /// 추가 순서로 순회
+    pub fn iter(&self) -> impl Iterator<Item = &FilterRule> + '_ {
+        self.order.values().map(move |label| &self.entries[label].rule)
+    }
+
+    /// 우선순위가 높은 순서로 순회 (같으면 추가 순서)
+    pub fn by_priority(&self) -> impl Iterator<Item = &FilterRule> + '_ {
+        self.by_priority.values().map(move |label| &self.entries[label].rule)
+    }
+
+    /// 소스 프리픽스(`src_ip`)가 정확히 `prefix`인 규칙 (추가 순서)
+    pub fn with_prefix(&self, (addr, prefix_len): (u32, u32)) -> impl Iterator<Item = &FilterRule> + '_ {
+        let masked = utils::mask_ipv4(addr, prefix_len);
+        self.by_prefix.range((prefix_len, masked, 0)..=(prefix_len, masked, u64::MAX))
+            .map(move |(_, label)| &self.entries[label].rule)
+    }
+
+    /// 소스 프리픽스가 `prefix`를 포함하거나 `prefix`에 포함되는 규칙 (추가 순서)
+    pub fn overlapping(&self, (addr, prefix_len): (u32, u32)) -> Vec<&FilterRule> {
+        let mut found: Vec<(u64, &str)> = Vec::new();
+
+        // 더 넓거나 같은 프리픽스: 길이별로 키 하나
+        for len in 0..=prefix_len.min(32) {
+            let masked = utils::mask_ipv4(addr, len);
+            found.extend(self.by_prefix.range((len, masked, 0)..=(len, masked, u64::MAX))
+                .map(|(&(_, _, seq), label)| (seq, label.as_str())));
+        }
+
+        // 더 좁은 프리픽스: 길이별로 주소 범위
+        let start = utils::mask_ipv4(addr, prefix_len);
+        let end = start | !utils::mask_ipv4(u32::MAX, prefix_len);
+        for len in prefix_len + 1..=32 {
+            found.extend(self.by_prefix.range((len, start, 0)..=(len, end, u64::MAX))
+                .map(|(&(_, _, seq), label)| (seq, label.as_str())));
+        }
+
+        found.sort_unstable_by_key(|&(seq, _)| seq);
+        found.into_iter()
+            .map(|(_, label)| &self.entries[label].rule)
+            .collect()
+    }
+
+    fn index(&mut self, entry: RuleEntry) {
+        let label = entry.rule.label.clone();
+        self.order.insert(entry.seq, label.clone());
+        self.by_priority.insert((Reverse(entry.rule.priority), entry.seq), label.clone());
+        if let Some(key) = prefix_key(&entry.rule, entry.seq) {
+            self.by_prefix.insert(key, label.clone());
+        }
+        self.entries.insert(label, entry);
+    }
+}
+
+impl FromIterator<FilterRule> for RuleCache {
+    fn from_iter<I: IntoIterator<Item = FilterRule>>(rules: I) -> Self {
+        let mut cache = Self::new();
+        for rule in rules {
+            cache.push(rule);
+        }
+        cache
+    }
+}
+
+impl std::fmt::Debug for RuleCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+fn prefix_key(rule: &FilterRule, seq: u64) -> Option<(u32, u32, u64)> {
+    let (addr, prefix_len) = rule.src_ip?;
+    Some((prefix_len, utils::mask_ipv4(addr, prefix_len), seq))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conflicts::{prefix_contains, tests::rule};
+
+    fn labels<'r>(rules: impl Iterator<Item = &'r FilterRule>) -> Vec<&'r str> {
+        rules.map(|r| r.label.as_str()).collect()
+    }
+
+    #[test]
+    fn test_order_survives_replace_and_restore() {
+        let mut cache: RuleCache = ["a", "b", "c", "d"].iter()
+            .map(|label| rule(label, "10.0.0.1/32", 0))
+            .collect();
+
+        // 교체와 수정은 자리를 유지
+        assert!(cache.replace(rule("b", "10.0.0.2/32", 7)).is_some());
+        assert!(cache.replace(rule("missing", "10.0.0.2/32", 7)).is_none());
+        assert!(cache.modify("c", |r| r.priority = 9));
+        assert_eq!(labels(cache.iter()), ["a", "b", "c", "d"]);
+
+        // 삭제 후 복원하면 원래 자리로
+        let entry = cache.remove("b").unwrap();
+        assert!(!cache.contains("b"));
+        assert_eq!(labels(cache.iter()), ["a", "c", "d"]);
+        cache.restore(entry);
+        assert_eq!(labels(cache.iter()), ["a", "b", "c", "d"]);
+        assert_eq!(cache.get("b").unwrap().priority, 7);
+
+        // 같은 레이블을 다시 추가하면 맨 뒤로
+        assert!(cache.push(rule("a", "10.0.0.1/32", 0)).is_some());
+        assert_eq!(labels(cache.iter()), ["b", "c", "d", "a"]);
+        assert_eq!(cache.iter().count(), 4);
+    }
+
+    #[test]
+    fn test_by_priority_matches_stable_sort() {
+        let rules: Vec<FilterRule> = (0..200u32)
+            .map(|i| rule(&format!("r{}", i), "10.0.0.1/32", (i * 7919) % 13))
+            .collect();
+        let mut cache: RuleCache = rules.iter().cloned().collect();
+        cache.modify("r5", |r| r.priority = 100);
+
+        let mut expected = rules.clone();
+        expected[5].priority = 100;
+        expected.sort_by_key(|rule| std::cmp::Reverse(rule.priority));
+
+        assert_eq!(labels(cache.by_priority()), labels(expected.iter()));
+    }
+
+    #[test]
+    fn test_overlapping_matches_scan() {
+        let prefixes = [
+            "0.0.0.0/0", "10.0.0.0/8", "10.1.0.0/16", "10.1.2.0/24", "10.1.2.3/32",
+            "10.2.0.0/16", "192.168.0.0/16", "192.168.1.1/32", "10.1.2.0/24",
+        ];
+        let rules: Vec<FilterRule> = prefixes.iter().enumerate()
+            .map(|(i, prefix)| rule(&format!("r{}", i), prefix, 0))
+            .collect();
+        let mut cache: RuleCache = rules.iter().cloned().collect();
+        cache.push(FilterRule { src_ip: None, ..rule("any", "0.0.0.0/0", 0) });
+
+        for prefix in prefixes.iter().chain(&["10.1.0.0/17", "172.16.0.1/32"]) {
+            let prefix = utils::parse_ip_prefix(prefix).unwrap();
+            let expected: Vec<&FilterRule> = rules.iter()
+                .filter(|r| {
+                    let other = r.src_ip.unwrap();
+                    prefix_contains(other, prefix) || prefix_contains(prefix, other)
+                })
+                .collect();
+
+            assert_eq!(labels(cache.overlapping(prefix).into_iter()), labels(expected.into_iter()));
+        }
+
+        // 같은 프리픽스만 (호스트 비트는 무시)
+        let exact = cache.with_prefix(utils::parse_ip_prefix("10.1.2.0/24").unwrap()).collect::<Vec<_>>();
+        assert_eq!(labels(exact.into_iter()), ["r3", "r8"]);
+        assert_eq!(cache.with_prefix((0x0A010209, 24)).count(), 2);
+
+        // 삭제한 규칙은 색인에서도 제거
+        cache.remove("r1");
+        let found = cache.overlapping(utils::parse_ip_prefix("10.1.0.0/16").unwrap());
+        assert_eq!(labels(found.into_iter()), ["r0", "r2", "r3", "r4", "r8"]);
+    }
+}
@@ -0,0 +1,1135 @@
+//! 규칙 집합 모듈
+//! 규칙 정의 검증 및 선언형 규칙 집합 조정 (desired state → MapManager)
+
+use anyhow::{anyhow, Result};
+use log::{info, warn};
+use std::collections::HashSet;
+use std::sync::{Mutex, MutexGuard};
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::dns::HostEndpoint;
+use crate::error::DaemonError;
+use crate::ipset::PrefixSet;
+use crate::maps::{self, BulkKind, BulkOp, BulkWrite, FilterRule, MapManager};
+use crate::quota::RuleQuota;
+use crate::rulecache::RuleCache;
+use crate::webhook::WebhookTarget;
+
+use swift_guard::api::{AddressFamily, ApiResponse, ApplySummary, InstallState, PlanCapacity, RuleDiff, RuleError, RuleSpec};
+use swift_guard::error::{ErrorCode, InvalidArgument};
+use swift_guard::types::XdpFeatures;
+use swift_guard::{rulediff, utils, validation};
+
+/// 규칙 정의를 검증해 필터 규칙 생성
+///
+/// IP 프리픽스는 호스트 비트를 제거해 저장하고, 리디렉션 인터페이스는
+/// `resolve_ifindex`로 현재 ifindex를 찾는다.
+pub fn build_rule<F>(
+    spec: RuleSpec,
+    created_by: Option<String>,
+    now: u64,
+    resolve_ifindex: F,
+) -> Result<FilterRule, InvalidArgument>
+where
+    F: Fn(&str) -> Result<u32>,
+{
+    build(spec, None, created_by, now, resolve_ifindex)
+}
+
+/// 소스를 호스트 이름으로 지정한 규칙 정의를 검증해 필터 규칙 생성
+///
+/// `host`는 호출자가 미리 해석한 주소 집합이며, 규칙은 주소마다 /32 항목으로 설치된다.
+pub fn build_host_rule<F>(
+    spec: RuleSpec,
+    host: HostEndpoint,
+    created_by: Option<String>,
+    now: u64,
+    resolve_ifindex: F,
+) -> Result<FilterRule, InvalidArgument>
+where
+    F: Fn(&str) -> Result<u32>,
+{
+    if spec.src_ip.is_some() {
+        return Err(InvalidArgument::new("src_host", "src_ip and src_host cannot be used together"));
+    }
+    if host.addrs.is_empty() {
+        return Err(InvalidArgument::new("src_host", format!("{} has no A records", host.name)));
+    }
+    check_ipv4_source("src_host", spec.dst_ip.as_deref())?;
+
+    build(spec, Some(host), created_by, now, resolve_ifindex)
+}
+
+/// 소스를 프리픽스 집합으로 지정한 규칙 정의를 검증해 필터 규칙 생성
+///
+/// 규칙은 집합의 프리픽스마다 LPM 항목으로 설치된다. 집합 규칙은 리디렉션하지 않는다.
+pub fn build_set_rule(
+    spec: RuleSpec,
+    set: PrefixSet,
+    created_by: Option<String>,
+    now: u64,
+) -> Result<FilterRule, InvalidArgument> {
+    if spec.src_ip.is_some() {
+        return Err(InvalidArgument::new("src_ip", format!("src_ip and prefix set {} cannot be used together", set.name)));
+    }
+    if set.prefixes.is_empty() {
+        return Err(InvalidArgument::new("src_set", format!("Prefix set {} is empty", set.name)));
+    }
+    check_ipv4_source("src_set", spec.dst_ip.as_deref())?;
+
+    let rule = build(spec, None, created_by, now, |_| Err(anyhow!("prefix set rules do not redirect")))?;
+    Ok(FilterRule { src_set: Some(set), ..rule })
+}
+
+fn build<F>(
+    spec: RuleSpec,
+    src_host: Option<HostEndpoint>,
+    created_by: Option<String>,
+    now: u64,
+    resolve_ifindex: F,
+) -> Result<FilterRule, InvalidArgument>
+where
+    F: Fn(&str) -> Result<u32>,
+{
+    // 데몬 상태 없이 확인할 수 있는 항목 (CLI 사전 검증과 같은 검증, 첫 문제로 거부)
+    if let Some(issue) = validation::validate_rule_spec(&spec).into_iter().next() {
+        return Err(issue.into());
+    }
+
+    // 리디렉션 CPU 검증
+    if let Some(cpu) = spec.redirect_cpu {
+        let online = maps::online_cpu_count();
+        if cpu >= online {
+            return Err(InvalidArgument::new("redirect_cpu",
+                format!("Invalid redirect CPU {} (online CPUs: {})", cpu, online)));
+        }
+    }
+
+    // IP 주소 파싱
+    let src_ip = parse_prefix("src_ip", spec.src_ip.as_deref())?;
+    let dst_ip = parse_prefix("dst_ip", spec.dst_ip.as_deref())?;
+
+    // 소스 IP가 없는 규칙은 와일드카드 맵에 설치되는데, XDP 프로그램은 목적지
+    // 주소를 비교하지 않으므로 목적지만 지정한 규칙은 모든 목적지에 적용되어 버린다
+    if dst_ip.is_some() && src_ip.is_none() && src_host.is_none() {
+        return Err(InvalidArgument::new("dst_ip",
+            "Destination-only rules cannot be enforced by the XDP program; add src_ip"));
+    }
+
+    let counter_group = spec.counter_group.filter(|group| !group.trim().is_empty());
+
+    // 만료 알림 웹훅 URL 검증
+    let owner_webhook = spec.owner_webhook.filter(|url| !url.trim().is_empty());
+    if let Some(url) = &owner_webhook {
+        WebhookTarget::parse(url).map_err(|e| InvalidArgument::new("owner_webhook", e.to_string()))?;
+    }
+
+    // 할당량
+    let quota = RuleQuota::from_spec(spec.quota_bytes, spec.quota_packets, spec.quota_action)?;
+
+    // 리디렉션 인터페이스 검증 및 인덱스 획득
+    let redirect_ifindex = match &spec.redirect_if {
+        Some(ifname) => resolve_ifindex(ifname)
+            .map_err(|e| InvalidArgument::new("redirect_if", e.to_string()))?,
+        None => 0,
+    };
+
+    Ok(FilterRule {
+        src_ip,
+        dst_ip,
+        src_port_min: spec.src_port_min,
+        src_port_max: spec.src_port_max,
+        src_port_list: spec.src_port_list,
+        dst_port_min: spec.dst_port_min,
+        dst_port_max: spec.dst_port_max,
+        dst_port_list: spec.dst_port_list,
+        protocol: spec.protocol,
+        tcp_flags: spec.tcp_flags,
+        action: spec.action,
+        redirect_ifindex,
+        redirect_ifname: spec.redirect_if,
+        redirect_cpu: spec.redirect_cpu,
+        priority: spec.priority,
+        rate_limit: spec.rate_limit,
+        expire: spec.expire,
+        label: spec.label,
+        description: spec.description,
+        created_by,
+        creation_time: now,
+        auto_generated: false,
+        degraded: false,
+        install_state: InstallState::Installed,
+        quota,
+        quota_exceeded: false,
+        src_host,
+        counter_group,
+        preset: None,
+        origin: None,
+        src_set: None,
+        owner_webhook,
+        notify_before: spec.notify_before,
+    })
+}
+
+/// 로드된 XDP 프로그램이 규칙의 매치 필드를 처리하는지 확인
+///
+/// 처리하지 못하는 필드가 있으면 첫 필드로 거부하고, `force`면 규칙을 받아들이되 무시될
+/// 필드를 경고로 돌려준다 (`supported`는 `MapManager::rule_features`).
+pub fn negotiate_features(
+    rule: &FilterRule,
+    supported: XdpFeatures,
+    force: bool,
+) -> Result<Option<String>, InvalidArgument> {
+    let spec = rule.to_spec();
+    let issues = validation::check_features(&spec, supported);
+    if !force {
+        return match issues.into_iter().next() {
+            Some(issue) => Err(issue.into()),
+            None => Ok(None),
+        };
+    }
+    if issues.is_empty() {
+        return Ok(None);
+    }
+
+    let required = validation::required_features(&spec).iter().fold(0, |all, (_, feature)| all | feature);
+    let fields: Vec<&str> = issues.iter().map(|issue| issue.field.as_str()).collect();
+    Ok(Some(format!("the loaded XDP program does not support {}; {} will be ignored",
+        supported.missing(required), fields.join(", "))))
+}
+
+/// 프리픽스 파싱 (호스트 비트는 파싱할 때 제거됨)
+fn parse_prefix(field: &str, value: Option<&str>) -> Result<Option<(u32, u32)>, InvalidArgument> {
+    value
+        .map(|s| utils::parse_ip_prefix(s).map_err(|e| InvalidArgument::new(field, e.to_string())))
+        .transpose()
+}
+
+/// IPv4 주소로만 해석되는 소스(호스트 이름, 프리픽스 집합)와 대상 주소 체계 비교
+fn check_ipv4_source(source: &str, dst_ip: Option<&str>) -> Result<(), InvalidArgument> {
+    if AddressFamily::of_prefixes(None, dst_ip)? == AddressFamily::V6 {
+        return Err(InvalidArgument::new("dst_ip", format!("address family v6 does not match {} family v4", source)));
+    }
+    Ok(())
+}
+
+/// 마스크된 프리픽스
+fn masked(prefix: Option<(u32, u32)>) -> Option<(u32, u32)> {
+    prefix.map(|(ip, prefix_len)| (utils::mask_ipv4(ip, prefix_len), prefix_len))
+}
+
+/// 두 규칙의 정의가 같은지 비교
+///
+/// 서버가 정하는 값(생성 시각, 작성자, 자동/비활성/할당량 초과 상태)은 무시하고, 프리픽스는
+/// 마스크한 값으로, 리디렉션 인터페이스는 ifindex가 아닌 이름으로 비교한다
+/// (인터페이스가 다시 만들어지면 ifindex만 바뀔 수 있음).
+pub fn same_definition(current: &FilterRule, desired: &FilterRule) -> bool {
+    let same_redirect_if = match (&current.redirect_ifname, &desired.redirect_ifname) {
+        (Some(a), Some(b)) => a == b,
+        (None, None) => current.redirect_ifindex == desired.redirect_ifindex,
+        _ => false,
+    };
+
+    same_redirect_if
+        && masked(current.src_ip) == masked(desired.src_ip)
+        && masked(current.dst_ip) == masked(desired.dst_ip)
+        && current.src_host.as_ref().map(|host| &host.name) == desired.src_host.as_ref().map(|host| &host.name)
+        && current.src_set.as_ref().map(|set| &set.name) == desired.src_set.as_ref().map(|set| &set.name)
+        && current.src_port_min == desired.src_port_min
+        && current.src_port_max == desired.src_port_max
+        && current.src_port_list == desired.src_port_list
+        && current.dst_port_min == desired.dst_port_min
+        && current.dst_port_max == desired.dst_port_max
+        && current.dst_port_list == desired.dst_port_list
+        && current.protocol == desired.protocol
+        && current.tcp_flags == desired.tcp_flags
+        && current.action == desired.action
+        && current.redirect_cpu == desired.redirect_cpu
+        && current.priority == desired.priority
+        && current.rate_limit == desired.rate_limit
+        && current.expire == desired.expire
+        && current.description == desired.description
+        && current.quota == desired.quota
+        && current.counter_group == desired.counter_group
+        && current.owner_webhook == desired.owner_webhook
+        && current.notify_before == desired.notify_before
+}
+
+/// 현재 규칙과 원하는 규칙의 차이 (레이블 기준)
+#[derive(Debug, Default)]
+pub struct RulesetDiff {
+    pub add: Vec<FilterRule>,
+    pub update: Vec<FilterRule>,
+    /// 삭제할 레이블 (prune일 때만)
+    pub delete: Vec<String>,
+    pub unchanged: Vec<String>,
+}
+
+/// 규칙 집합 비교
+///
+/// `desired`의 레이블은 중복되지 않아야 한다. prune이면 문서에 없는 규칙을
+/// 삭제 대상으로 포함하되, 자동 완화 규칙과 `keep`에 있는 레이블은 제외한다.
+pub fn diff(current: &RuleCache, desired: Vec<FilterRule>, prune: bool, keep: &HashSet<String>) -> RulesetDiff {
+    let mut result = RulesetDiff::default();
+
+    let wanted: HashSet<String> = desired.iter().map(|r| r.label.clone()).collect();
+
+    for rule in desired {
+        match current.get(&rule.label) {
+            None => result.add.push(rule),
+            Some(existing) if same_definition(existing, &rule) => result.unchanged.push(rule.label),
+            Some(_) => result.update.push(rule),
+        }
+    }
+
+    if prune {
+        result.delete = current.iter()
+            .filter(|r| !r.auto_generated && !wanted.contains(&r.label) && !keep.contains(&r.label))
+            .map(|r| r.label.clone())
+            .collect();
+    }
+
+    result
+}
+
+/// 규칙 집합 적용 방식
+#[derive(Debug, Clone, Copy)]
+pub struct ApplyOptions<'o> {
+    /// 규칙 집합의 네임스페이스 (prune도 이 네임스페이스의 규칙만 삭제)
+    pub namespace: &'o str,
+    /// 문서에 없는 규칙 삭제 (자동 완화 규칙 제외)
+    pub prune: bool,
+    /// 적용하지 않고 변경 내용만 계산
+    pub dry_run: bool,
+}
+
+/// 검증과 비교를 마치고 맵 쓰기만 남은 규칙 집합 적용
+///
+/// 맵 쓰기는 `run_chunk`로 나눠 수행하므로 호출자가 청크 사이에 맵 관리자 잠금을
+/// 놓고 쉬거나 진행 상황을 보고할 수 있다.
+#[derive(Debug)]
+pub struct PendingApply {
+    summary: ApplySummary,
+    writes: BulkWrite,
+}
+
+impl PendingApply {
+    /// 전체 맵 쓰기 변경 수
+    pub fn total(&self) -> usize {
+        self.writes.total()
+    }
+    
+    /// 적용을 시도한 변경 수
+    pub fn done(&self) -> usize {
+        self.writes.done()
+    }
+    
+    pub fn is_finished(&self) -> bool {
+        self.writes.is_finished()
+    }
+    
+    /// 다음 청크 적용 (결과는 요약에 누적, 레이블은 네임스페이스를 뺀 레이블)
+    pub fn run_chunk(&mut self, map_manager: &mut MapManager) {
+        let outcomes = map_manager.write_chunk(&mut self.writes);
+        record(&mut self.summary, outcomes);
+    }
+    
+    /// 청크마다 맵 관리자 잠금을 놓고 `write_schedule`의 대기 시간만큼 쉬며 적용
+    ///
+    /// `report`이면 청크마다 `Progress` 프레임을 `progress`로 보낸다. `progress`의 수신
+    /// 측이 닫히면(요청자가 떠남) 다음 청크를 시작하지 않고 멈추며, 남은 변경은 결과의
+    /// `pending`으로 보고한다.
+    pub async fn run(
+        mut self,
+        map_manager: &Mutex<MapManager<'_>>,
+        progress: Option<&UnboundedSender<ApiResponse>>,
+        report: bool,
+    ) -> Result<ApplySummary> {
+        let pause = lock(map_manager)?.write_schedule().pause;
+        
+        while !self.is_finished() {
+            if matches!(progress, Some(sender) if sender.is_closed()) {
+                warn!("Requester left, stopping ruleset apply at a chunk boundary");
+                break;
+            }
+            
+            self.run_chunk(&mut *lock(map_manager)?);
+            
+            if let (true, Some(sender)) = (report, progress) {
+                let _ = sender.send(ApiResponse::Progress { done: self.done(), total: self.total() });
+            }
+            if !self.is_finished() {
+                tokio::time::sleep(pause).await;
+            }
+        }
+        
+        Ok(self.finish())
+    }
+    
+    /// 적용 결과 (끝나기 전에 부르면 남은 변경은 적용하지 않고 `pending`으로 보고)
+    pub fn finish(self) -> ApplySummary {
+        let PendingApply { mut summary, writes } = self;
+        
+        if summary.dry_run {
+            return summary;
+        }
+        
+        if !writes.is_finished() {
+            warn!("Ruleset apply stopped after {} of {} changes", writes.done(), writes.total());
+            summary.pending = writes.into_pending().iter().map(|op| bare(op.label())).collect();
+        }
+        
+        info!("Ruleset applied: {} added, {} updated, {} deleted, {} unchanged, {} errors",
+            summary.added.len(), summary.updated.len(), summary.deleted.len(),
+            summary.unchanged.len(), summary.errors.len());
+        
+        summary
+    }
+}
+
+/// 규칙 집합 검증 및 비교 (맵은 바꾸지 않음)
+///
+/// 잘못된 규칙은 오류로 보고하고 건너뛴다(prune 대상에서도 제외). 맵 쓰기는 삭제 →
+/// 갱신 → 추가 순서로 예약해 LPM 키를 옮기는 규칙이 삭제될 규칙의 키와 겹쳐도 안전하다.
+/// 드라이런이면 예약하지 않고 변경 내용만 요약에 담는다.
+pub fn prepare<F>(
+    map_manager: &MapManager,
+    specs: Vec<RuleSpec>,
+    options: ApplyOptions,
+    created_by: &str,
+    now: u64,
+    resolve_ifindex: F,
+) -> PendingApply
+where
+    F: Fn(&str) -> Result<u32>,
+{
+    let ApplyOptions { namespace, prune, dry_run } = options;
+    let mut summary = ApplySummary { dry_run, ..Default::default() };
+    let mut desired = Vec::new();
+    let mut seen = HashSet::new();
+    let features = map_manager.rule_features();
+    
+    // 다른 네임스페이스의 규칙은 prune 대상에서 제외
+    let mut keep: HashSet<String> = map_manager.rules().iter()
+        .filter(|rule| utils::split_label(&rule.label).0 != namespace)
+        .map(|rule| rule.label.clone())
+        .collect();
+
+    for spec in specs {
+        let label = spec.label.clone();
+
+        if !seen.insert(label.clone()) {
+            summary.errors.push(rule_error(&label, "duplicate label in ruleset".to_string(), ErrorCode::InvalidArgument));
+            continue;
+        }
+
+        // 규칙 집합에는 --force가 없으므로 처리하지 못하는 매치 필드는 오류
+        let built = build_rule(spec, Some(created_by.to_string()), now, &resolve_ifindex)
+            .and_then(|rule| negotiate_features(&rule, features, false).map(|_| rule));
+        match built {
+            Ok(mut rule) => {
+                rule.label = utils::qualify_label(namespace, &label);
+                desired.push(rule);
+            },
+            Err(e) => {
+                summary.errors.push(rule_error(&label, format!("Invalid {}: {}", e.field, e.message), ErrorCode::InvalidArgument));
+                keep.insert(utils::qualify_label(namespace, &label));
+            }
+        }
+    }
+
+    let diff = diff(map_manager.rules(), desired, prune, &keep);
+    summary.unchanged = diff.unchanged.iter().map(|key| bare(key)).collect();
+    summary.diffs = diff.update.iter()
+        .filter_map(|rule| map_manager.rules().get(&rule.label).map(|current| RuleDiff {
+            label: bare(&rule.label),
+            changes: rulediff::diff_specs(&current.to_spec(), &rule.to_spec()),
+        }))
+        .collect();
+
+    if dry_run {
+        summary.deleted = diff.delete.iter().map(|key| bare(key)).collect();
+        summary.updated = diff.update.iter().map(|r| bare(&r.label)).collect();
+        summary.added = diff.add.iter().map(|r| bare(&r.label)).collect();
+        return PendingApply { summary, writes: BulkWrite::default() };
+    }
+
+    let ops = diff.delete.into_iter().map(BulkOp::Delete)
+        .chain(diff.update.into_iter().map(BulkOp::Update))
+        .chain(diff.add.into_iter().map(BulkOp::Add))
+        .collect();
+    
+    PendingApply { summary, writes: BulkWrite::new(ops) }
+}
+
+/// 규칙 집합 적용 계획 (맵과 규칙 캐시는 바꾸지 않음, `PlanChanges`)
+///
+/// `prepare`로 검증과 비교를 마친 맵 쓰기를 `MapManager::plan`으로 시뮬레이션하므로,
+/// 검증은 통과해도 맵 용량이나 리디렉션 대상 때문에 실패할 변경까지 적용할 때와 같은
+/// 오류로 보고한다. 결과는 드라이런으로 표시하고 적용 뒤 맵 사용량을 담는다.
+/// `capacity`는 filter_rules 맵 최대 항목 수이며 `options.dry_run`은 무시한다.
+pub fn plan<F>(
+    map_manager: &MapManager,
+    specs: Vec<RuleSpec>,
+    options: ApplyOptions,
+    created_by: &str,
+    now: u64,
+    resolve_ifindex: F,
+    capacity: usize,
+) -> ApplySummary
+where
+    F: Fn(&str) -> Result<u32>,
+{
+    let options = ApplyOptions { dry_run: false, ..options };
+    let PendingApply { mut summary, writes } = prepare(map_manager, specs, options, created_by, now, resolve_ifindex);
+    
+    let plan = map_manager.plan(writes.into_pending(), capacity);
+    record(&mut summary, plan.outcomes);
+    
+    let space = plan.space;
+    summary.dry_run = true;
+    summary.capacity = Some(PlanCapacity {
+        lpm_entries: space.kernel_entries.unwrap_or(space.lpm_entries),
+        lpm_capacity: space.capacity,
+        fill_percent: space.fill_percent,
+        wildcard_rules: space.wildcard_rules,
+        wildcard_capacity: maps::MAX_WILDCARD_RULES as usize,
+    });
+    summary
+}
+
+/// 맵 쓰기 결과를 요약에 누적 (적용과 계획이 같은 분류를 쓰도록 공유)
+fn record(summary: &mut ApplySummary, outcomes: Vec<(BulkKind, String, Result<(), DaemonError>)>) {
+    for (kind, key, result) in outcomes {
+        let label = bare(&key);
+        match (result, kind) {
+            (Ok(()), BulkKind::Delete) => summary.deleted.push(label),
+            (Ok(()), BulkKind::Update) => summary.updated.push(label),
+            (Ok(()), BulkKind::Add) => summary.added.push(label),
+            (Err(e), _) => summary.errors.push(rule_error(&label, e.to_string(), e.code())),
+        }
+    }
+}
+
+/// 네임스페이스를 뺀 레이블
+fn bare(key: &str) -> String {
+    utils::split_label(key).1.to_string()
+}
+
+fn lock<'m, 'a>(map_manager: &'m Mutex<MapManager<'a>>) -> Result<MutexGuard<'m, MapManager<'a>>> {
+    map_manager.lock().map_err(|_| anyhow!("Failed to lock map_manager"))
+}
+
+fn rule_error(label: &str, message: String, code: ErrorCode) -> RuleError {
+    warn!("Ruleset rule '{}' failed: {}", label, message);
+    RuleError {
+        label: label.to_string(),
+        message,
+        code,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::MemoryMap;
+    use crate::maps::{MapBackends, WriteSchedule};
+    use std::time::Duration;
+
+    /// 규칙 집합 적용 (청크 사이에 쉬지 않고 한 번에)
+    fn apply<F>(
+        map_manager: &mut MapManager,
+        specs: Vec<RuleSpec>,
+        options: ApplyOptions,
+        created_by: &str,
+        now: u64,
+        resolve_ifindex: F,
+    ) -> ApplySummary
+    where
+        F: Fn(&str) -> Result<u32>,
+    {
+        let mut pending = prepare(map_manager, specs, options, created_by, now, resolve_ifindex);
+        while !pending.is_finished() {
+            pending.run_chunk(map_manager);
+        }
+        pending.finish()
+    }
+
+    fn spec(label: &str) -> RuleSpec {
+        RuleSpec {
+            label: label.to_string(),
+            src_ip: Some("192.168.1.0/24".to_string()),
+            dst_ip: None,
+            src_port_min: 0,
+            src_port_max: 65535,
+            dst_port_min: 80,
+            dst_port_max: 80,
+            protocol: 6,
+            tcp_flags: 0,
+            action: 2,
+            redirect_if: None,
+            redirect_cpu: None,
+            priority: 10,
+            rate_limit: 0,
+            expire: 0,
+            description: None,
+            quota_bytes: None,
+            quota_packets: None,
+            quota_action: None,
+            counter_group: None,
+            src_port_list: Vec::new(),
+            dst_port_list: Vec::new(),
+            owner_webhook: None,
+            notify_before: None,
+        }
+    }
+
+    fn options(prune: bool, dry_run: bool) -> ApplyOptions<'static> {
+        ApplyOptions { namespace: "default", prune, dry_run }
+    }
+
+    fn resolve(name: &str) -> Result<u32> {
+        match name {
+            "veth0" => Ok(7),
+            "veth1" => Ok(8),
+            _ => Err(anyhow!("Interface {} does not exist", name)),
+        }
+    }
+
+    fn rule(spec: RuleSpec) -> FilterRule {
+        build_rule(spec, Some("test".to_string()), 1000, resolve).unwrap()
+    }
+
+    #[test]
+    fn test_build_rule_normalizes() {
+        let rule = rule(RuleSpec {
+            src_ip: Some("10.1.2.3/8".to_string()),
+            dst_ip: Some("192.168.1.1".to_string()),
+            action: 3,
+            redirect_if: Some("veth0".to_string()),
+            ..spec("web")
+        });
+
+        assert_eq!(rule.src_ip, Some((0x0A000000, 8)));
+        assert_eq!(rule.dst_ip, Some((0xC0A80101, 32)));
+        assert_eq!(rule.redirect_ifindex, 7);
+        assert_eq!(rule.created_by.as_deref(), Some("test"));
+        assert_eq!(rule.creation_time, 1000);
+    }
+
+    #[test]
+    fn test_build_rule_rejects_invalid_fields() {
+        let cases = [
+            (RuleSpec { label: "bad label".to_string(), ..spec("x") }, "label"),
+            (RuleSpec { src_ip: Some("300.0.0.1".to_string()), ..spec("x") }, "src_ip"),
+            (RuleSpec { src_ip: Some("2001:db8::/32".to_string()), ..spec("x") }, "src_ip"),
+            (RuleSpec { dst_ip: Some("db.internal".to_string()), ..spec("x") }, "dst_ip"),
+            (RuleSpec { dst_ip: Some("10.0.0.0/40".to_string()), ..spec("x") }, "dst_ip"),
+            (RuleSpec { src_ip: None, dst_ip: Some("10.0.0.1".to_string()), ..spec("x") }, "dst_ip"),
+            (RuleSpec { action: 3, redirect_if: Some("missing0".to_string()), ..spec("x") }, "redirect_if"),
+            (RuleSpec { action: 3, redirect_if: Some("veth0".to_string()), redirect_cpu: Some(0), ..spec("x") }, "redirect_cpu"),
+            (RuleSpec { action: 3, redirect_cpu: Some(u32::MAX), ..spec("x") }, "redirect_cpu"),
+            (RuleSpec { quota_bytes: Some(1 << 30), quota_action: Some(3), ..spec("x") }, "quota_action"),
+            (RuleSpec { counter_group: Some("web".to_string()), ..spec("x") }, "counter_group"),
+            (RuleSpec { action: 4, counter_group: Some("web hits".to_string()), ..spec("x") }, "counter_group"),
+            (RuleSpec { src_port_min: 1024, src_port_list: vec![53], ..spec("x") }, "src_port_list"),
+            (RuleSpec { protocol: 1, src_port_list: vec![53], ..spec("x") }, "src_port_list"),
+            (RuleSpec { dst_port_list: vec![80, 443], ..spec("x") }, "dst_port_list"),
+            (RuleSpec { dst_port_min: 0, dst_port_max: 65535, protocol: 0, dst_port_list: vec![80], ..spec("x") }, "dst_port_list"),
+            (RuleSpec { dst_port_min: 0, dst_port_max: 65535, dst_port_list: (1..=9).collect(), ..spec("x") }, "dst_port_list"),
+            (RuleSpec { dst_port_min: 0, dst_port_max: 65535, dst_port_list: vec![0], ..spec("x") }, "dst_port_list"),
+            (spec("x").with_owner_webhook("http://ops:8080/expiry", None), "owner_webhook"),
+            (spec("x").with_expire(60).with_owner_webhook("https://ops/expiry", None), "owner_webhook"),
+            (RuleSpec { notify_before: Some(60), ..spec("x") }, "notify_before"),
+        ];
+
+        for (spec, field) in cases {
+            let err = build_rule(spec, None, 0, resolve).unwrap_err();
+            assert_eq!(err.field, field);
+        }
+    }
+
+    #[test]
+    fn test_build_rule_rejects_mixed_families() {
+        let v4 = || Some("10.0.0.0/8".to_string());
+        let v6 = || Some("2001:db8::/32".to_string());
+
+        for (src_ip, dst_ip) in [(v4(), v6()), (v6(), v4())] {
+            let err = build_rule(RuleSpec { src_ip, dst_ip, ..spec("x") }, None, 0, resolve).unwrap_err();
+            assert_eq!(err.field, "dst_ip");
+            assert!(err.message.contains("address family"), "{}", err.message);
+        }
+
+        // 같은 체계의 IPv6 규칙은 체계 검증을 통과하고 커널 맵 제약에서 거부됨
+        let err = build_rule(RuleSpec { src_ip: v6(), dst_ip: v6(), ..spec("x") }, None, 0, resolve).unwrap_err();
+        assert_eq!(err.field, "src_ip");
+        assert!(!err.message.contains("address family"), "{}", err.message);
+
+        // 호스트 이름과 프리픽스 집합 소스는 IPv4로 해석됨
+        let host = HostEndpoint::new("api.example.com", vec![0x0A000001]);
+        let err = build_host_rule(RuleSpec { src_ip: None, dst_ip: v6(), ..spec("x") }, host, None, 0, resolve).unwrap_err();
+        assert_eq!(err.field, "dst_ip");
+        let set = PrefixSet::new("country-KR", vec![(0x0A000000, 24)], "geoip", "v1");
+        let err = build_set_rule(RuleSpec { src_ip: None, dst_ip: v6(), ..spec("x") }, set, None, 0).unwrap_err();
+        assert_eq!(err.field, "dst_ip");
+    }
+
+    #[test]
+    fn test_rule_address_family() {
+        let v4 = rule(RuleSpec { dst_ip: Some("192.168.1.1".to_string()), ..spec("web") });
+        assert_eq!(v4.to_rule_info(Default::default(), true).address_family, Some(AddressFamily::V4));
+
+        let any = rule(RuleSpec { src_ip: None, ..spec("web") });
+        assert_eq!(any.to_rule_info(Default::default(), true).address_family, Some(AddressFamily::Any));
+
+        let host = HostEndpoint::new("api.example.com", vec![0x0A000001]);
+        let host = build_host_rule(RuleSpec { src_ip: None, ..spec("api") }, host, None, 0, resolve).unwrap();
+        assert_eq!(host.address_family(), AddressFamily::V4);
+    }
+
+    #[test]
+    fn test_build_host_rule() {
+        let host = HostEndpoint::new("api.example.com", vec![0x0A000002, 0x0A000001]);
+        let dst = RuleSpec { src_ip: None, dst_ip: Some("192.168.1.1".to_string()), ..spec("api") };
+        let rule = build_host_rule(dst, host.clone(), None, 0, resolve).unwrap();
+        assert_eq!(rule.src_ip, None);
+        assert_eq!(rule.src_prefixes(), [(0x0A000001, 32), (0x0A000002, 32)]);
+        assert_eq!(rule.rule_map(), swift_guard::api::RuleMap::Lpm);
+
+        let err = build_host_rule(spec("api"), host, None, 0, resolve).unwrap_err();
+        assert_eq!(err.field, "src_host");
+        let empty = HostEndpoint::new("api.example.com", vec![]);
+        let err = build_host_rule(RuleSpec { src_ip: None, ..spec("api") }, empty, None, 0, resolve).unwrap_err();
+        assert_eq!(err.field, "src_host");
+    }
+
+    #[test]
+    fn test_build_set_rule() {
+        let set = PrefixSet::new("country-KR", vec![(0x0A000100, 24), (0x0A000000, 24)], "geoip", "v1");
+        let rule = build_set_rule(RuleSpec { src_ip: None, ..spec("country-KR") }, set.clone(), None, 0).unwrap();
+        assert_eq!(rule.src_prefixes(), [(0x0A000000, 23)]);
+        assert_eq!(rule.rule_map(), swift_guard::api::RuleMap::Lpm);
+        assert_eq!(rule.to_rule_info(Default::default(), true).src_set.unwrap().entries, 1);
+
+        let err = build_set_rule(spec("country-KR"), set, None, 0).unwrap_err();
+        assert_eq!(err.field, "src_ip");
+        let empty = PrefixSet::new("country-KR", Vec::new(), "geoip", "v1");
+        let err = build_set_rule(RuleSpec { src_ip: None, ..spec("country-KR") }, empty, None, 0).unwrap_err();
+        assert_eq!(err.field, "src_set");
+    }
+
+    #[test]
+    fn test_same_definition_ignores_server_fields() {
+        let current = rule(RuleSpec { action: 3, redirect_if: Some("veth0".to_string()), ..spec("web") });
+
+        // 서버가 채우거나 바꾸는 값은 비교하지 않음
+        let desired = FilterRule {
+            creation_time: 5000,
+            created_by: Some("someone-else".to_string()),
+            redirect_ifindex: 42,
+            degraded: true,
+            auto_generated: true,
+            install_state: InstallState::Failed { reason: "map full".to_string() },
+            ..current.clone()
+        };
+        assert!(same_definition(&current, &desired));
+        assert!(rulediff::diff_specs(&current.to_spec(), &desired.to_spec()).is_empty());
+
+        // 마스크되지 않은 기존 프리픽스 (마스킹 도입 전에 추가된 규칙)
+        let unmasked = FilterRule { src_ip: Some((0xC0A80117, 24)), ..current.clone() };
+        assert!(same_definition(&unmasked, &current));
+        assert!(!same_definition(&FilterRule { src_ip: Some((0xC0A80117, 32)), ..current.clone() }, &current));
+    }
+
+    #[test]
+    fn test_same_definition_detects_changes() {
+        let base = rule(spec("web"));
+        let changed = [
+            FilterRule { src_ip: Some((0xC0A80200, 24)), ..base.clone() },
+            FilterRule { src_ip: None, ..base.clone() },
+            FilterRule { dst_ip: Some((0x0A000000, 8)), ..base.clone() },
+            FilterRule { src_port_min: 1024, ..base.clone() },
+            FilterRule { src_port_max: 2048, ..base.clone() },
+            FilterRule { dst_port_min: 443, ..base.clone() },
+            FilterRule { dst_port_max: 443, ..base.clone() },
+            FilterRule { protocol: 17, ..base.clone() },
+            FilterRule { tcp_flags: 0x02, ..base.clone() },
+            FilterRule { action: 1, ..base.clone() },
+            FilterRule { redirect_cpu: Some(1), ..base.clone() },
+            FilterRule { redirect_ifname: Some("veth1".to_string()), redirect_ifindex: 8, ..base.clone() },
+            FilterRule { priority: 11, ..base.clone() },
+            FilterRule { rate_limit: 100, ..base.clone() },
+            FilterRule { expire: 60, ..base.clone() },
+            FilterRule { description: Some("changed".to_string()), ..base.clone() },
+            FilterRule { counter_group: Some("web-total".to_string()), ..base.clone() },
+        ];
+
+        for (i, rule) in changed.iter().enumerate() {
+            assert!(!same_definition(&base, rule), "change #{} not detected", i);
+            assert_eq!(rulediff::diff_specs(&base.to_spec(), &rule.to_spec()).len(), 1, "change #{} not reported", i);
+        }
+    }
+
+    #[test]
+    fn test_same_definition_redirect_by_name() {
+        let current = rule(RuleSpec { action: 3, redirect_if: Some("veth0".to_string()), ..spec("web") });
+
+        // 인터페이스가 다시 만들어져 ifindex만 바뀐 경우는 같은 정의
+        let recreated = FilterRule { redirect_ifindex: 99, ..current.clone() };
+        assert!(same_definition(&current, &recreated));
+
+        // 이름이 없는 이전 규칙은 ifindex로 비교
+        let legacy = FilterRule { redirect_ifname: None, ..current.clone() };
+        assert!(!same_definition(&legacy, &current));
+        assert!(same_definition(&legacy, &legacy.clone()));
+    }
+
+    #[test]
+    fn test_diff() {
+        let current: RuleCache = vec![
+            rule(spec("same")),
+            rule(spec("changed")),
+            rule(spec("extra")),
+            FilterRule { auto_generated: true, ..rule(spec("auto-synflood-10.0.0.1")) },
+        ].into_iter().collect();
+        let desired = vec![
+            rule(spec("same")),
+            rule(RuleSpec { priority: 99, ..spec("changed") }),
+            rule(spec("new")),
+        ];
+
+        let result = diff(&current, desired.clone(), false, &HashSet::new());
+        assert_eq!(result.unchanged, ["same"]);
+        assert_eq!(result.update.iter().map(|r| r.label.as_str()).collect::<Vec<_>>(), ["changed"]);
+        assert_eq!(result.add.iter().map(|r| r.label.as_str()).collect::<Vec<_>>(), ["new"]);
+        assert!(result.delete.is_empty());
+
+        // prune: 자동 완화 규칙과 keep 목록은 삭제하지 않음
+        let result = diff(&current, desired.clone(), true, &HashSet::new());
+        assert_eq!(result.delete, ["extra"]);
+
+        let keep: HashSet<String> = ["extra".to_string()].into_iter().collect();
+        assert!(diff(&current, desired, true, &keep).delete.is_empty());
+    }
+
+    #[test]
+    fn test_diff_unmasked_submission_is_unchanged() {
+        let current: RuleCache = vec![rule(spec("web"))].into_iter().collect();
+        // 같은 네트워크를 호스트 비트가 있는 형태로 제출
+        let desired = vec![rule(RuleSpec { src_ip: Some("192.168.1.77/24".to_string()), ..spec("web") })];
+
+        let result = diff(&current, desired, true, &HashSet::new());
+        assert_eq!(result.unchanged, ["web"]);
+        assert!(result.update.is_empty() && result.add.is_empty() && result.delete.is_empty());
+    }
+
+    #[test]
+    fn test_apply() {
+        let filter_rules = MemoryMap::new();
+        let mut manager = MapManager::with_backends(MapBackends {
+            filter_rules: Some(&filter_rules),
+            ..Default::default()
+        });
+        manager.add_rule(rule(spec("same"))).unwrap();
+        manager.add_rule(rule(RuleSpec { src_ip: Some("10.0.0.0/8".to_string()), ..spec("changed") })).unwrap();
+        manager.add_rule(rule(RuleSpec { src_ip: Some("172.16.0.0/12".to_string()), ..spec("extra") })).unwrap();
+        manager.add_rule(rule(RuleSpec { src_ip: Some("172.20.0.0/16".to_string()), ..spec("broken") })).unwrap();
+
+        let specs = vec![
+            spec("same"),
+            RuleSpec { src_ip: Some("10.0.0.0/8".to_string()), priority: 99, ..spec("changed") },
+            RuleSpec { src_ip: Some("192.168.2.0/24".to_string()), ..spec("new") },
+            RuleSpec { src_ip: Some("not-an-ip".to_string()), ..spec("broken") },
+            RuleSpec { priority: 1, ..spec("new") },
+        ];
+
+        // dry-run: 변경 없음
+        let summary = apply(&mut manager, specs.clone(), options(true, true), "peer:127.0.0.1", 2000, resolve);
+        assert!(summary.dry_run);
+        assert_eq!(summary.added, ["new"]);
+        assert_eq!(summary.updated, ["changed"]);
+        assert_eq!(summary.deleted, ["extra"]);
+        assert_eq!(summary.unchanged, ["same"]);
+        assert_eq!(summary.diffs.len(), 1);
+        assert_eq!(summary.diffs[0].label, "changed");
+        let changes: Vec<(&str, &str, &str)> = summary.diffs[0].changes.iter()
+            .map(|c| (c.field.as_str(), c.old.as_str(), c.new.as_str()))
+            .collect();
+        assert_eq!(changes, [("priority", "10", "99")]);
+        assert_eq!(manager.rules().iter().count(), 4);
+        assert_eq!(filter_rules.len(), 4);
+
+        let summary = apply(&mut manager, specs, options(true, false), "peer:127.0.0.1", 2000, resolve);
+        assert!(!summary.dry_run);
+        assert_eq!(summary.added, ["new"]);
+        assert_eq!(summary.updated, ["changed"]);
+        assert_eq!(summary.deleted, ["extra"]);
+        assert_eq!(summary.unchanged, ["same"]);
+
+        // 잘못된 규칙과 중복 레이블은 오류, 잘못된 규칙의 기존 버전은 유지
+        let errors: Vec<(&str, ErrorCode)> = summary.errors.iter().map(|e| (e.label.as_str(), e.code)).collect();
+        assert_eq!(errors, [("broken", ErrorCode::InvalidArgument), ("new", ErrorCode::InvalidArgument)]);
+        assert!(summary.errors[0].message.contains("src_ip"));
+        assert!(summary.errors[1].message.contains("duplicate"));
+
+        let labels: Vec<&str> = manager.rules().iter().map(|r| r.label.as_str()).collect();
+        assert_eq!(labels, ["same", "changed", "broken", "new"]);
+        let rules: Vec<&FilterRule> = manager.rules().iter().collect();
+        assert_eq!(rules[1].priority, 99);
+        assert_eq!(rules[1].creation_time, 1000);
+        assert_eq!(rules[3].created_by.as_deref(), Some("peer:127.0.0.1"));
+        assert_eq!(filter_rules.len(), 4);
+
+        // 다시 적용하면 모두 unchanged
+        let summary = apply(&mut manager, vec![
+            spec("same"),
+            RuleSpec { src_ip: Some("10.0.0.0/8".to_string()), priority: 99, ..spec("changed") },
+            RuleSpec { src_ip: Some("192.168.2.0/24".to_string()), ..spec("new") },
+        ], options(false, false), "peer:127.0.0.1", 3000, resolve);
+        assert_eq!(summary.unchanged, ["same", "changed", "new"]);
+        assert!(summary.added.is_empty() && summary.updated.is_empty() && summary.deleted.is_empty());
+    }
+
+    #[test]
+    fn test_negotiate_features() {
+        let plain = rule(spec("plain"));
+        let ports = rule(RuleSpec { dst_port_min: 0, dst_port_max: 65535, dst_port_list: vec![80, 443], ..spec("ports") });
+        let flagged = rule(RuleSpec { tcp_flags: 0x02, ..ports.to_spec() });
+        let legacy = XdpFeatures::LEGACY;
+
+        // 거부하면 문제 필드, 받아들이면 경고
+        let negotiate = |rule: &FilterRule, supported: XdpFeatures, force: bool| -> Result<Option<String>, String> {
+            negotiate_features(rule, supported, force).map_err(|e| e.field)
+        };
+        assert_eq!(negotiate(&plain, legacy, false), Ok(None));
+        assert_eq!(negotiate(&plain, XdpFeatures::default(), true), Ok(None));
+        assert_eq!(negotiate(&ports, XdpFeatures::ALL, false), Ok(None));
+        assert_eq!(negotiate(&ports, legacy, false), Err("dst_port_list".to_string()));
+        assert_eq!(negotiate(&ports, legacy, true),
+            Ok(Some("the loaded XDP program does not support port_list; dst_port_list will be ignored".to_string())));
+        assert_eq!(negotiate(&flagged, XdpFeatures(XdpFeatures::PORT_LIST), false), Err("tcp_flags".to_string()));
+        assert_eq!(negotiate(&flagged, XdpFeatures::default(), true), Ok(Some(
+            "the loaded XDP program does not support tcp_flags,port_list; tcp_flags, dst_port_list will be ignored".to_string())));
+        let err = negotiate_features(&ports, legacy, false).unwrap_err();
+        assert!(err.message.contains("requires the port_list feature"), "{}", err.message);
+        // 목적지 주소는 이 버전의 프로그램도 비교하지 않음
+        let to_host = rule(RuleSpec { dst_ip: Some("192.168.1.1".to_string()), ..spec("to-host") });
+        assert_eq!(negotiate(&to_host, XdpFeatures::ALL, false), Err("dst_ip".to_string()));
+        assert_eq!(negotiate(&to_host, XdpFeatures::ALL, true),
+            Ok(Some("the loaded XDP program does not support dst_ip; dst_ip will be ignored".to_string())));
+
+        // 규칙 집합은 처리하지 못하는 규칙을 오류로 보고하고 나머지는 적용
+        let filter_rules = MemoryMap::new();
+        let mut manager = MapManager::with_backends(MapBackends {
+            filter_rules: Some(&filter_rules),
+            features: Some(legacy),
+            ..Default::default()
+        });
+        let summary = apply(&mut manager, vec![plain.to_spec(), ports.to_spec()], options(false, false), "test", 2000, resolve);
+        assert_eq!(summary.added, ["plain"]);
+        assert_eq!(summary.errors.len(), 1);
+        assert_eq!((summary.errors[0].label.as_str(), summary.errors[0].code), ("ports", ErrorCode::InvalidArgument));
+        assert!(summary.errors[0].message.starts_with("Invalid dst_port_list: "), "{}", summary.errors[0].message);
+    }
+
+    #[test]
+    fn test_apply_namespace() {
+        let filter_rules = MemoryMap::new();
+        let mut manager = MapManager::with_backends(MapBackends {
+            filter_rules: Some(&filter_rules),
+            ..Default::default()
+        });
+        manager.add_rule(rule(spec("web"))).unwrap();
+        let foreign = rule(RuleSpec { src_ip: Some("10.0.0.0/8".to_string()), ..spec("web") });
+        manager.add_rule(FilterRule { label: "team-b/web".to_string(), ..foreign }).unwrap();
+
+        // 같은 레이블이라도 네임스페이스가 다르면 별개의 규칙이고, prune은 네임스페이스 안에서만
+        let team_a = ApplyOptions { namespace: "team-a", prune: true, dry_run: false };
+        let specs = vec![RuleSpec { src_ip: Some("172.16.0.0/12".to_string()), ..spec("web") }];
+        let summary = apply(&mut manager, specs, team_a, "peer:127.0.0.1", 2000, resolve);
+        assert_eq!(summary.added, ["web"]);
+        assert!(summary.deleted.is_empty() && summary.errors.is_empty());
+
+        let keys: Vec<&str> = manager.rules().iter().map(|r| r.label.as_str()).collect();
+        assert_eq!(keys, ["web", "team-b/web", "team-a/web"]);
+
+        let summary = apply(&mut manager, Vec::new(), team_a, "peer:127.0.0.1", 3000, resolve);
+        assert_eq!(summary.deleted, ["web"]);
+        let keys: Vec<&str> = manager.rules().iter().map(|r| r.label.as_str()).collect();
+        assert_eq!(keys, ["web", "team-b/web"]);
+    }
+
+    #[test]
+    fn test_plan_reports_map_failures() {
+        let (filter_rules, wildcard, redirect) = (MemoryMap::new().with_capacity(3), MemoryMap::new(), MemoryMap::new());
+        let mut manager = MapManager::with_backends(MapBackends {
+            filter_rules: Some(&filter_rules),
+            wildcard: Some(&wildcard),
+            redirect: Some(&redirect),
+            ..Default::default()
+        });
+        manager.add_rule(rule(spec("web"))).unwrap();
+        manager.add_rule(rule(RuleSpec { src_ip: Some("10.0.0.0/8".to_string()), ..spec("old") })).unwrap();
+
+        // old를 지워 생긴 자리와 남은 한 자리에 두 규칙이 들어가고, 세 번째는 맵이 가득 참
+        let specs = vec![
+            spec("web"),
+            RuleSpec { src_ip: Some("172.16.0.0/12".to_string()), ..spec("a") },
+            RuleSpec { src_ip: Some("172.17.0.0/16".to_string()), action: 3, redirect_if: Some("veth0".to_string()), ..spec("b") },
+            RuleSpec { src_ip: Some("172.18.0.0/16".to_string()), ..spec("c") },
+            RuleSpec { src_ip: None, dst_port_min: 53, dst_port_max: 53, ..spec("any") },
+        ];
+        let summary = plan(&manager, specs, options(true, false), "peer:127.0.0.1", 2000, resolve, 3);
+
+        assert!(summary.dry_run);
+        assert_eq!(summary.deleted, ["old"]);
+        assert_eq!(summary.added, ["a", "b", "any"]);
+        assert_eq!(summary.unchanged, ["web"]);
+        let errors: Vec<(&str, ErrorCode)> = summary.errors.iter().map(|e| (e.label.as_str(), e.code)).collect();
+        assert_eq!(errors, [("c", ErrorCode::Internal)]);
+        assert!(summary.errors[0].message.contains("filter_rules"), "{}", summary.errors[0].message);
+
+        let capacity = summary.capacity.unwrap();
+        assert_eq!((capacity.lpm_entries, capacity.lpm_capacity, capacity.fill_percent), (3, 3, 100.0));
+        assert_eq!((capacity.wildcard_rules, capacity.wildcard_capacity), (1, maps::MAX_WILDCARD_RULES as usize));
+
+        // 맵과 규칙 캐시는 그대로
+        let labels: Vec<&str> = manager.rules().iter().map(|r| r.label.as_str()).collect();
+        assert_eq!(labels, ["web", "old"]);
+        assert_eq!((filter_rules.len(), wildcard.len(), redirect.len()), (2, 0, 0));
+    }
+
+    /// 결정적인 의사 난수 (xorshift)
+    struct Rng(u64);
+
+    impl Rng {
+        fn below(&mut self, n: usize) -> usize {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            (self.0 % n as u64) as usize
+        }
+    }
+
+    fn random_spec(rng: &mut Rng) -> RuleSpec {
+        const LABELS: [&str; 6] = ["a", "b", "c", "d", "e", "f"];
+        const SOURCES: [Option<&str>; 5] = [Some("10.0.0.0/8"), Some("10.1.0.0/16"), Some("192.168.1.0/24"), Some("172.16.0.0/12"), None];
+        const REDIRECTS: [Option<&str>; 4] = [None, None, Some("veth0"), Some("veth1")];
+
+        let redirect_if = REDIRECTS[rng.below(REDIRECTS.len())].map(str::to_string);
+        RuleSpec {
+            src_ip: SOURCES[rng.below(SOURCES.len())].map(str::to_string),
+            priority: rng.below(3) as u32,
+            action: if redirect_if.is_some() { 3 } else { 2 },
+            redirect_if,
+            ..spec(LABELS[rng.below(LABELS.len())])
+        }
+    }
+
+    #[test]
+    fn test_plan_matches_apply() {
+        let outcome = |summary: &ApplySummary| {
+            let errors: Vec<(String, String, ErrorCode)> = summary.errors.iter()
+                .map(|e| (e.label.clone(), e.message.clone(), e.code))
+                .collect();
+            (summary.added.clone(), summary.updated.clone(), summary.deleted.clone(), summary.unchanged.clone(), errors)
+        };
+
+        for seed in 1..=200u64 {
+            let mut rng = Rng(seed.wrapping_mul(0x9E37_79B9_7F4A_7C15));
+            let capacity = 2 + rng.below(4);
+            let (filter_rules, wildcard, redirect) = (MemoryMap::new().with_capacity(capacity), MemoryMap::new(), MemoryMap::new());
+            let mut manager = MapManager::with_backends(MapBackends {
+                filter_rules: Some(&filter_rules),
+                wildcard: Some(&wildcard),
+                redirect: Some(&redirect),
+                ..Default::default()
+            });
+            for _ in 0..rng.below(5) {
+                let _ = manager.add_rule(rule(random_spec(&mut rng)));
+            }
+
+            let specs: Vec<RuleSpec> = (0..rng.below(7)).map(|_| random_spec(&mut rng)).collect();
+            let prune = rng.below(2) == 0;
+
+            let planned = plan(&manager, specs.clone(), options(prune, false), "peer", 2000, resolve, capacity);
+            let applied = apply(&mut manager, specs, options(prune, false), "peer", 2000, resolve);
+            assert_eq!(outcome(&planned), outcome(&applied), "seed {}", seed);
+
+            let space = manager.rule_space_stats(capacity);
+            let planned = planned.capacity.unwrap();
+            assert_eq!(planned.lpm_entries, filter_rules.len(), "seed {}", seed);
+            assert_eq!(planned.wildcard_rules, space.wildcard_rules, "seed {}", seed);
+        }
+    }
+
+    fn chunked_manager(filter_rules: &MemoryMap) -> Mutex<MapManager<'_>> {
+        let schedule = WriteSchedule { chunk_size: 2, pause: Duration::ZERO };
+        Mutex::new(MapManager::with_backends(MapBackends {
+            filter_rules: Some(filter_rules),
+            ..Default::default()
+        }).with_write_schedule(schedule))
+    }
+
+    fn subnet_specs(count: u8) -> Vec<RuleSpec> {
+        (0..count)
+            .map(|i| RuleSpec { src_ip: Some(format!("10.{}.0.0/16", i)), ..spec(&format!("r{}", i)) })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_apply_in_chunks_reports_progress() {
+        let filter_rules = MemoryMap::new();
+        let manager = chunked_manager(&filter_rules);
+        let (sender, mut frames) = tokio::sync::mpsc::unbounded_channel();
+
+        let pending = prepare(&manager.lock().unwrap(), subnet_specs(5), options(false, false),
+            "peer:127.0.0.1", 2000, resolve);
+        assert_eq!((pending.done(), pending.total()), (0, 5));
+        let summary = pending.run(&manager, Some(&sender), true).await.unwrap();
+        assert_eq!(summary.added, ["r0", "r1", "r2", "r3", "r4"]);
+        assert!(summary.pending.is_empty());
+        assert_eq!(filter_rules.len(), 5);
+
+        // 청크마다 진행 상황 프레임 하나
+        let mut progress = Vec::new();
+        while let Ok(frame) = frames.try_recv() {
+            match frame {
+                ApiResponse::Progress { done, total } => progress.push((done, total)),
+                other => panic!("unexpected frame {:?}", other),
+            }
+        }
+        assert_eq!(progress, [(2, 5), (4, 5), (5, 5)]);
+
+        // 보고를 요청하지 않으면 프레임 없음, 드라이런은 맵 쓰기 없음
+        let pending = prepare(&manager.lock().unwrap(), Vec::new(), options(true, false),
+            "peer:127.0.0.1", 3000, resolve);
+        assert_eq!(pending.run(&manager, Some(&sender), false).await.unwrap().deleted.len(), 5);
+        assert!(frames.try_recv().is_err());
+        let pending = prepare(&manager.lock().unwrap(), subnet_specs(3), options(false, true),
+            "peer:127.0.0.1", 3000, resolve);
+        assert_eq!(pending.total(), 0);
+        assert_eq!(pending.run(&manager, Some(&sender), true).await.unwrap().added.len(), 3);
+        assert!(frames.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_apply_stops_when_requester_leaves() {
+        let filter_rules = MemoryMap::new();
+        let manager = chunked_manager(&filter_rules);
+        let (sender, mut frames) = tokio::sync::mpsc::unbounded_channel();
+
+        let mut pending = prepare(&manager.lock().unwrap(), subnet_specs(5), options(false, false),
+            "peer:127.0.0.1", 2000, resolve);
+        pending.run_chunk(&mut manager.lock().unwrap());
+        assert_eq!(pending.done(), 2);
+
+        // 연결이 끊기면 다음 청크를 시작하지 않음
+        frames.close();
+        let summary = pending.run(&manager, Some(&sender), true).await.unwrap();
+        assert_eq!(summary.added, ["r0", "r1"]);
+        assert_eq!(summary.pending, ["r2", "r3", "r4"]);
+        assert_eq!(manager.lock().unwrap().rules().iter().count(), 2);
+        assert_eq!(filter_rules.len(), 2);
+    }
+}
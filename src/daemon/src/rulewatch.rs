@@ -0,0 +1,242 @@
+//! 규칙 변경 구독 모듈
+//! 맵 관리자의 규칙 변경에 순번을 붙여 발행하고 `WatchRules` 구독자에게 스트리밍
+//!
+//! 순번은 규칙 캐시가 바뀔 때마다 맵 관리자 잠금 안에서 1씩 늘어나고 같은 잠금 안에서
+//! 발행된다. 구독자는 같은 잠금 안에서 스냅샷을 찍고 수신기를 만들므로, 스냅샷 순번
+//! 뒤의 변경을 빠짐없이 순서대로 받는다. 느린 구독자가 변경을 건너뛰면 수신기를 새로
+//! 만들고 새 스냅샷을 보낸다.
+
+use anyhow::{anyhow, Result};
+use log::warn;
+use std::sync::Mutex;
+use tokio::sync::broadcast;
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::maps::MapManager;
+
+use swift_guard::api::{ApiResponse, RuleChangeKind, RuleInfo};
+
+/// 구독자별 버퍼 크기 (초과하면 새 스냅샷을 보냄)
+const CHANGE_CHANNEL_CAPACITY: usize = 4096;
+
+/// 순번을 붙인 규칙 변경
+#[derive(Debug, Clone)]
+pub struct RuleChange {
+    pub seq: u64,
+    pub kind: RuleChangeKind,
+    /// 변경 후 규칙 (삭제면 삭제 직전 규칙, 통계 없음)
+    pub rule: RuleInfo,
+}
+
+/// 규칙 변경 발행기 (맵 관리자가 소유)
+#[derive(Debug)]
+pub struct RuleChangeLog {
+    /// 마지막으로 부여한 순번
+    seq: u64,
+    sender: broadcast::Sender<RuleChange>,
+}
+
+impl Default for RuleChangeLog {
+    fn default() -> Self {
+        Self {
+            seq: 0,
+            sender: broadcast::channel(CHANGE_CHANNEL_CAPACITY).0,
+        }
+    }
+}
+
+impl RuleChangeLog {
+    /// 마지막으로 부여한 순번
+    pub fn seq(&self) -> u64 {
+        self.seq
+    }
+
+    /// 다음 변경부터 수신
+    pub fn subscribe(&self) -> broadcast::Receiver<RuleChange> {
+        self.sender.subscribe()
+    }
+
+    /// 다음 순번을 붙여 발행 (구독자가 없어도 순번은 늘어남)
+    pub fn publish(&mut self, kind: RuleChangeKind, rule: RuleInfo) {
+        self.seq += 1;
+        let _ = self.sender.send(RuleChange { seq: self.seq, kind, rule });
+    }
+}
+
+/// 규칙 스냅샷과 이후 변경을 `frames`로 스트리밍 (요청자가 떠나면 끝남)
+///
+/// `namespace`가 있으면 그 네임스페이스의 규칙만 보낸다.
+pub async fn stream(
+    map_manager: &Mutex<MapManager<'_>>,
+    namespace: Option<&str>,
+    frames: &UnboundedSender<ApiResponse>,
+) -> Result<()> {
+    let visible = |rule: &RuleInfo| namespace.is_none() || namespace == Some(rule.namespace.as_str());
+
+    loop {
+        let (seq, mut rules, mut changes) = map_manager.lock()
+            .map_err(|_| anyhow!("Failed to lock map_manager"))?
+            .watch_rules()?;
+        rules.retain(|rule| visible(rule));
+        let snapshot = ApiResponse::Rules { total_count: rules.len(), rules, next_cursor: None, seq: Some(seq) };
+        if frames.send(snapshot).is_err() {
+            return Ok(());
+        }
+
+        loop {
+            let change = tokio::select! {
+                change = changes.recv() => change,
+                _ = frames.closed() => return Ok(()),
+            };
+
+            match change {
+                Ok(RuleChange { seq, kind, rule }) if visible(&rule) => {
+                    if frames.send(ApiResponse::RuleChanged { seq, kind, rule }).is_err() {
+                        return Ok(());
+                    }
+                },
+                Ok(_) => {},
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    warn!("Rule watch subscriber lagged, skipped {} changes; sending a new snapshot", skipped);
+                    break;
+                },
+                Err(broadcast::error::RecvError::Closed) => return Ok(()),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::MemoryMap;
+    use crate::maps::{FilterRule, MapBackends};
+    use swift_guard::api::FallbackMode;
+    use swift_guard::rulemirror::RuleMirror;
+    use tokio::sync::mpsc::{self, UnboundedReceiver};
+
+    fn rule(label: &str, src_ip: u32) -> FilterRule {
+        FilterRule {
+            src_ip: Some((src_ip, 32)),
+            protocol: 6,
+            action: 2,
+            label: label.to_string(),
+            creation_time: 1000,
+            ..Default::default()
+        }
+    }
+
+    /// 재구성한 상태가 `seq`까지 따라잡을 때까지 프레임 적용 후 수신 종료
+    async fn follow(mirror: &mut RuleMirror, frames: &mut UnboundedReceiver<ApiResponse>, seq: u64) -> usize {
+        let mut snapshots = 0;
+        while mirror.seq() < Some(seq) {
+            let frame = frames.recv().await.unwrap();
+            snapshots += matches!(frame, ApiResponse::Rules { .. }) as usize;
+            mirror.apply(frame).unwrap();
+        }
+        frames.close();
+        snapshots
+    }
+
+    fn reconstructed(mirror: &RuleMirror) -> Vec<RuleInfo> {
+        mirror.rules().cloned().collect()
+    }
+
+    /// 관리자의 현재 규칙 (규칙 키 순서, 네임스페이스 필터)
+    fn current(manager: &Mutex<MapManager<'_>>, namespace: Option<&str>) -> Vec<RuleInfo> {
+        let mut rules = manager.lock().unwrap().list_rules(false).unwrap();
+        rules.retain(|rule| namespace.is_none() || namespace == Some(rule.namespace.as_str()));
+        rules.sort_by_key(RuleInfo::key);
+        rules
+    }
+
+    fn manager(filter_rules: &MemoryMap) -> Mutex<MapManager<'_>> {
+        Mutex::new(MapManager::with_backends(MapBackends {
+            filter_rules: Some(filter_rules),
+            ..Default::default()
+        }))
+    }
+
+    #[tokio::test]
+    async fn test_stream_reconstructs_state() {
+        let filter_rules = MemoryMap::new();
+        let manager = manager(&filter_rules);
+        {
+            let mut manager = manager.lock().unwrap();
+            manager.add_rule(rule("existing", 0x0A000001)).unwrap();
+            manager.add_rule(rule("team/existing", 0x0A000002)).unwrap();
+        }
+
+        let (all_sender, mut all_frames) = mpsc::unbounded_channel();
+        let (team_sender, mut team_frames) = mpsc::unbounded_channel();
+        let mut all = RuleMirror::default();
+        let mut team = RuleMirror::default();
+
+        let script = async {
+            // 구독자가 스냅샷을 찍은 뒤 변경 시작
+            tokio::task::yield_now().await;
+            let seq = {
+                let mut manager = manager.lock().unwrap();
+                manager.add_rule(rule("web", 0x0A000003)).unwrap();
+                manager.add_rule(rule("team/db", 0x0A000004)).unwrap();
+                // 실패한 변경은 발행하지 않음
+                assert!(manager.add_rule(rule("web", 0x0A000005)).is_err());
+                manager.update_rule(FilterRule { priority: 10, ..rule("web", 0x0A000003) }).unwrap();
+                manager.update_rule(FilterRule { action: 1, ..rule("team/existing", 0x0A000002) }).unwrap();
+                manager.delete_rule("existing").unwrap();
+                manager.add_rule(FilterRule { expire: 30, ..rule("temp", 0x0A000006) }).unwrap();
+                assert_eq!(manager.remove_expired_rules(1030).unwrap(), ["temp"]);
+                // 첫 인터페이스가 연결되면 모든 규칙의 effective가 바뀜
+                manager.attach_interface("eth0", "generic", None, FallbackMode::None, |_| Ok(())).unwrap();
+                manager.add_rule(rule("team/last", 0x0A000007)).unwrap();
+                manager.watch_rules().unwrap().0
+            };
+
+            let all_snapshots = follow(&mut all, &mut all_frames, seq).await;
+            let team_snapshots = follow(&mut team, &mut team_frames, seq).await;
+            (seq, all_snapshots, team_snapshots)
+        };
+
+        let (all_result, team_result, (seq, all_snapshots, team_snapshots)) = tokio::join!(
+            stream(&manager, None, &all_sender),
+            stream(&manager, Some("team"), &team_sender),
+            script,
+        );
+        all_result.unwrap();
+        team_result.unwrap();
+
+        // 스냅샷 뒤 변경 11개 (실패한 추가 제외, 연결로 규칙 3개 갱신)
+        assert_eq!(seq, 13);
+        assert_eq!((all_snapshots, team_snapshots), (1, 1));
+        assert_eq!(reconstructed(&all), current(&manager, None));
+        assert_eq!(reconstructed(&team), current(&manager, Some("team")));
+        assert_eq!(reconstructed(&team).len(), 3);
+        assert!(reconstructed(&all).iter().all(|rule| rule.effective));
+    }
+
+    #[tokio::test]
+    async fn test_stream_resyncs_after_lag() {
+        let filter_rules = MemoryMap::new();
+        let manager = manager(&filter_rules);
+        let (sender, mut frames) = mpsc::unbounded_channel();
+        let mut mirror = RuleMirror::default();
+
+        // 구독자가 받기 전에 버퍼보다 많은 변경이 쌓이면 새 스냅샷으로 따라잡음
+        let script = async {
+            tokio::task::yield_now().await;
+            let seq = {
+                let mut manager = manager.lock().unwrap();
+                for i in 0..CHANGE_CHANNEL_CAPACITY as u32 + 10 {
+                    manager.add_rule(rule(&format!("r{}", i), 0x0A000000 + i)).unwrap();
+                }
+                manager.watch_rules().unwrap().0
+            };
+            follow(&mut mirror, &mut frames, seq).await
+        };
+
+        let (result, snapshots) = tokio::join!(stream(&manager, None, &sender), script);
+        result.unwrap();
+        assert_eq!(snapshots, 2);
+        assert_eq!(reconstructed(&mirror), current(&manager, None));
+    }
+}
@@ -0,0 +1,529 @@
+//! 자가 진단 모듈
+//! 표식 5-튜플의 임시 count 규칙을 설치하고 그 튜플의 패킷을 보내 데이터 경로가 규칙을 적용하는지 확인
+//!
+//! 카운터는 XDP 프로그램이 매치된 규칙 항목의 `stats`에 직접 더하는 패킷 수이며, 임시 규칙은
+//! 새로 설치되므로 0에서 시작한다.
+//!
+//! 진행 단계는 규칙 설치 → 패킷 전송 → 대기 → 카운터 읽기 → 규칙 삭제다. 규칙을 설치한 뒤에는
+//! 어느 단계에서 실패해도, 요청이 중간에 취소되어도 규칙을 지운다. 그래도 남은 규칙(데몬 종료 등)은
+//! `SELFTEST_EXPIRE`초 뒤 만료된다.
+
+use std::fmt;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+
+use crate::maps::{FilterRule, MapManager};
+use crate::netif;
+use crate::packet::UdpFrame;
+use swift_guard::api::{RuleSpec, SelfTestReport};
+use swift_guard::error::InvalidArgument;
+use swift_guard::types::{ActionType, ProtocolType};
+use swift_guard::utils;
+
+/// 보낼 표식 패킷 수
+pub const SELFTEST_PACKETS: u32 = 5;
+
+/// 전송 후 카운터를 읽기 전까지 기다리는 시간
+pub const SELFTEST_WAIT: Duration = Duration::from_millis(200);
+
+/// 임시 규칙 만료 시간 (초, 삭제하지 못했을 때의 안전장치)
+pub const SELFTEST_EXPIRE: u32 = 60;
+
+/// 표식 소스 주소 대역 (RFC 2544 벤치마크용 198.18.0.0/16)
+const MARKER_SRC_NET: u32 = 0xC612_0000;
+
+/// 표식 목적지 (198.19.0.1:9, discard 포트)
+const MARKER_DST_IP: u32 = 0xC613_0001;
+const MARKER_DST_PORT: u16 = 9;
+
+/// 표식 페이로드 (패킷 캡처에서 자가 진단 패킷을 알아보기 위함)
+const MARKER_PAYLOAD: &[u8] = b"swift-guard selftest";
+
+/// 자가 진단이 쓰는 데이터 경로 조작
+pub trait Datapath {
+    /// 임시 규칙 설치
+    fn install(&mut self, rule: FilterRule) -> Result<()>;
+    /// 프레임을 인터페이스로 `count`번 보내고 보낸 수 반환
+    fn transmit(&mut self, interface: &str, frame: &[u8], count: u32) -> Result<u32>;
+    /// 규칙 카운터의 패킷 수 (`label`은 네임스페이스를 포함한 규칙 키, 커널 규칙 항목의 `stats.packets`)
+    fn packets(&mut self, label: &str) -> Result<u64>;
+    /// 임시 규칙 삭제
+    fn remove(&mut self, label: &str) -> Result<()>;
+}
+
+/// 다른 트래픽과 겹치지 않는 표식 5-튜플 (UDP)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Marker {
+    pub src_ip: u32,
+    pub src_port: u16,
+    pub dst_ip: u32,
+    pub dst_port: u16,
+}
+
+impl Marker {
+    /// 임의 값에서 표식 생성 (소스는 198.18.0.1-198.18.255.255와 임시 포트 49152-65535)
+    pub fn from_nonce(nonce: u64) -> Self {
+        Self {
+            src_ip: MARKER_SRC_NET | (nonce as u32 & 0xffff).max(1),
+            src_port: 49152 + ((nonce >> 16) % 16384) as u16,
+            dst_ip: MARKER_DST_IP,
+            dst_port: MARKER_DST_PORT,
+        }
+    }
+
+    /// 현재 시각과 프로세스 ID로 만든 표식
+    pub fn generate() -> Self {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_nanos() as u64)
+            .unwrap_or_default();
+        Self::from_nonce(nanos ^ (u64::from(std::process::id()) << 32))
+    }
+
+    /// 임시 규칙 레이블 ("selftest-<소스 주소 하위 16비트><소스 포트>")
+    pub fn label(&self) -> String {
+        format!("selftest-{:04x}{:04x}", self.src_ip & 0xffff, self.src_port)
+    }
+
+    /// 표식 튜플에만 맞는 임시 count 규칙 정의 (가장 높은 우선순위, `SELFTEST_EXPIRE`초 후 만료)
+    pub fn spec(&self) -> RuleSpec {
+        RuleSpec::new(&self.label(), ActionType::Count)
+            .with_src_ip(&utils::ipv4_to_string(self.src_ip))
+            .with_protocol(ProtocolType::Udp)
+            .with_src_port(self.src_port)
+            .with_dst_port(self.dst_port)
+            .with_priority(u32::MAX)
+            .with_expire(SELFTEST_EXPIRE)
+            .with_description("temporary self-test rule")
+    }
+
+    /// 표식 튜플의 UDP 프레임
+    pub fn frame(&self) -> Vec<u8> {
+        UdpFrame::new(self.src_ip, self.dst_ip, self.src_port, self.dst_port)
+            .with_payload(MARKER_PAYLOAD)
+            .build()
+    }
+}
+
+impl fmt::Display for Marker {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{} -> {}:{} udp", utils::ipv4_to_string(self.src_ip), self.src_port,
+            utils::ipv4_to_string(self.dst_ip), self.dst_port)
+    }
+}
+
+/// 자가 진단 계획
+#[derive(Debug, Clone)]
+pub struct Plan {
+    pub interface: String,
+    pub marker: Marker,
+    /// 표식의 임시 규칙 (`Marker::spec`으로 만든 규칙, 레이블은 규칙 키)
+    pub rule: FilterRule,
+    pub packets: u32,
+    pub wait: Duration,
+}
+
+impl Plan {
+    /// 기본 패킷 수와 대기 시간의 계획
+    pub fn new(interface: &str, marker: Marker, rule: FilterRule) -> Self {
+        Self { interface: interface.to_string(), marker, rule, packets: SELFTEST_PACKETS, wait: SELFTEST_WAIT }
+    }
+}
+
+/// 자가 진단 단계
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stage {
+    Install,
+    Transmit,
+    Wait,
+    ReadCounter,
+    Cleanup,
+    Done,
+}
+
+impl Stage {
+    /// 결과에 쓰는 단계 이름
+    pub fn name(&self) -> &'static str {
+        match self {
+            Stage::Install => "install",
+            Stage::Transmit => "transmit",
+            Stage::Wait => "wait",
+            Stage::ReadCounter => "read_counter",
+            Stage::Cleanup => "cleanup",
+            Stage::Done => "done",
+        }
+    }
+}
+
+/// 자가 진단 진행 상태
+///
+/// `advance`가 현재 단계를 실행하고 다음 단계로 넘어간다. 설치한 임시 규칙은 `Cleanup`
+/// 단계나, 그 전에 값이 버려지면 `Drop`에서 지운다.
+pub struct SelfTest<'d, D: Datapath> {
+    datapath: &'d mut D,
+    plan: Plan,
+    stage: Stage,
+    /// 임시 규칙이 설치되어 있어 지워야 함
+    installed: bool,
+    sent: u32,
+    observed: Option<u64>,
+    /// 실패한 단계와 원인
+    failure: Option<(Stage, String)>,
+    cleanup_error: Option<String>,
+}
+
+impl<'d, D: Datapath> SelfTest<'d, D> {
+    /// 설치 단계에서 시작하는 자가 진단
+    pub fn new(datapath: &'d mut D, plan: Plan) -> Self {
+        Self {
+            datapath,
+            plan,
+            stage: Stage::Install,
+            installed: false,
+            sent: 0,
+            observed: None,
+            failure: None,
+            cleanup_error: None,
+        }
+    }
+
+    /// 현재 단계를 실행하고 다음 단계 반환 (`Wait`는 호출자가 기다린 뒤 넘김)
+    pub fn advance(&mut self) -> Stage {
+        self.stage = match self.stage {
+            Stage::Install => match self.datapath.install(self.plan.rule.clone()) {
+                Ok(()) => {
+                    self.installed = true;
+                    Stage::Transmit
+                }
+                Err(e) => self.fail(Stage::Install, e),
+            },
+            Stage::Transmit => {
+                let frame = self.plan.marker.frame();
+                match self.datapath.transmit(&self.plan.interface, &frame, self.plan.packets) {
+                    Ok(sent) => {
+                        self.sent = sent;
+                        Stage::Wait
+                    }
+                    Err(e) => self.fail(Stage::Transmit, e),
+                }
+            },
+            Stage::Wait => Stage::ReadCounter,
+            Stage::ReadCounter => match self.datapath.packets(&self.plan.rule.label) {
+                Ok(packets) => {
+                    self.observed = Some(packets);
+                    Stage::Cleanup
+                }
+                Err(e) => self.fail(Stage::ReadCounter, e),
+            },
+            Stage::Cleanup => {
+                self.cleanup();
+                Stage::Done
+            },
+            Stage::Done => Stage::Done,
+        };
+        self.stage
+    }
+
+    /// 실패 기록 (설치한 규칙이 있으면 삭제 단계로)
+    fn fail(&mut self, stage: Stage, error: anyhow::Error) -> Stage {
+        self.failure = Some((stage, format!("{:#}", error)));
+        if self.installed { Stage::Cleanup } else { Stage::Done }
+    }
+
+    /// 설치한 임시 규칙 삭제 (한 번만)
+    fn cleanup(&mut self) {
+        if !self.installed {
+            return;
+        }
+        self.installed = false;
+        if let Err(e) = self.datapath.remove(&self.plan.rule.label) {
+            self.cleanup_error = Some(format!("{:#}", e));
+        }
+    }
+
+    /// 끝까지 진행하고 결과 반환
+    pub async fn run(mut self) -> SelfTestReport {
+        loop {
+            match self.stage {
+                Stage::Done => return self.report(),
+                Stage::Wait => tokio::time::sleep(self.plan.wait).await,
+                _ => {},
+            }
+            self.advance();
+        }
+    }
+
+    /// 지금까지의 결과 (보낸 패킷이 모두 카운터에 잡혀야 통과)
+    pub fn report(&self) -> SelfTestReport {
+        let counted = self.observed.is_some_and(|observed| observed >= u64::from(self.sent));
+        SelfTestReport {
+            interface: self.plan.interface.clone(),
+            passed: self.failure.is_none() && self.sent == self.plan.packets && self.sent > 0 && counted,
+            label: utils::split_label(&self.plan.rule.label).1.to_string(),
+            marker: self.plan.marker.to_string(),
+            packets_sent: self.sent,
+            packets_observed: self.observed,
+            failed_stage: self.failure.as_ref().map(|(stage, _)| stage.name().to_string()),
+            error: self.failure.as_ref().map(|(_, error)| error.clone()),
+            cleanup_error: self.cleanup_error.clone(),
+        }
+    }
+}
+
+impl<D: Datapath> Drop for SelfTest<'_, D> {
+    /// 요청이 취소되어 중간에 버려져도 임시 규칙 삭제
+    fn drop(&mut self) {
+        self.cleanup();
+    }
+}
+
+/// 자가 진단할 수 있는 인터페이스인지 (attach 요청으로 연결되어 동작 중)
+pub fn check_interface(map_manager: &MapManager, interface: &str) -> Result<(), InvalidArgument> {
+    match map_manager.list_interfaces().into_iter().find(|info| info.name == interface) {
+        None => Err(InvalidArgument::new("interface", format!("Interface {} is not attached", interface))),
+        Some(info) if info.suspended => {
+            Err(InvalidArgument::new("interface", format!("Interface {} is down and detached", interface)))
+        },
+        Some(_) => Ok(()),
+    }
+}
+
+/// 맵 관리자와 raw 소켓을 쓰는 실제 데이터 경로
+pub struct LiveDatapath<'m, 'a> {
+    map_manager: &'m Mutex<MapManager<'a>>,
+}
+
+impl<'m, 'a> LiveDatapath<'m, 'a> {
+    pub fn new(map_manager: &'m Mutex<MapManager<'a>>) -> Self {
+        Self { map_manager }
+    }
+
+    fn lock(&self) -> Result<std::sync::MutexGuard<'m, MapManager<'a>>> {
+        self.map_manager.lock().map_err(|_| anyhow!("Failed to lock map_manager"))
+    }
+}
+
+impl Datapath for LiveDatapath<'_, '_> {
+    fn install(&mut self, rule: FilterRule) -> Result<()> {
+        Ok(self.lock()?.add_rule(rule)?)
+    }
+
+    fn transmit(&mut self, interface: &str, frame: &[u8], count: u32) -> Result<u32> {
+        netif::send_frames(interface, frame, count)
+    }
+
+    fn packets(&mut self, label: &str) -> Result<u64> {
+        let rules = self.lock()?.list_rules(true)?;
+        rules.iter()
+            .find(|rule| utils::qualify_label(&rule.namespace, &rule.label) == label)
+            .map(|rule| rule.stats.packets)
+            .ok_or_else(|| anyhow!("Rule '{}' disappeared before its counter was read", label))
+    }
+
+    fn remove(&mut self, label: &str) -> Result<()> {
+        Ok(self.lock()?.delete_rule(label)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::{MapBackend, MemoryMap};
+    use crate::maps::MapBackends;
+    use crate::ruleset;
+    use swift_guard::api::FallbackMode;
+    use swift_guard::layout::{FilterStats, RULE_STATS_OFFSET};
+
+    /// 호출을 기록하고 지정한 단계에서 실패하는 데이터 경로
+    #[derive(Default)]
+    struct FakeDatapath {
+        calls: Vec<String>,
+        fail_at: Option<Stage>,
+        fail_remove: bool,
+        /// 보낸 패킷 중 카운터에 잡히는 수
+        counted: Option<u64>,
+        sent: u64,
+    }
+
+    impl FakeDatapath {
+        fn failing(stage: Stage) -> Self {
+            Self { fail_at: Some(stage), ..Default::default() }
+        }
+
+        fn check(&self, stage: Stage) -> Result<()> {
+            match self.fail_at {
+                Some(fail) if fail == stage => Err(anyhow!("{} failed", stage.name())),
+                _ => Ok(()),
+            }
+        }
+    }
+
+    impl Datapath for FakeDatapath {
+        fn install(&mut self, rule: FilterRule) -> Result<()> {
+            self.calls.push(format!("install {}", rule.label));
+            self.check(Stage::Install)
+        }
+
+        fn transmit(&mut self, interface: &str, frame: &[u8], count: u32) -> Result<u32> {
+            self.calls.push(format!("transmit {} {}x{}", interface, count, frame.len()));
+            self.check(Stage::Transmit)?;
+            self.sent = u64::from(count);
+            Ok(count)
+        }
+
+        fn packets(&mut self, label: &str) -> Result<u64> {
+            self.calls.push(format!("packets {}", label));
+            self.check(Stage::ReadCounter)?;
+            Ok(self.counted.unwrap_or(self.sent))
+        }
+
+        fn remove(&mut self, label: &str) -> Result<()> {
+            self.calls.push(format!("remove {}", label));
+            if self.fail_remove {
+                return Err(anyhow!("map delete failed"));
+            }
+            Ok(())
+        }
+    }
+
+    fn marker() -> Marker {
+        Marker::from_nonce(0x0123_0a0b)
+    }
+
+    fn plan() -> Plan {
+        let rule = ruleset::build_rule(marker().spec(), None, 1000, |_| Ok(0)).unwrap();
+        Plan { wait: Duration::ZERO, ..Plan::new("lo", marker(), rule) }
+    }
+
+    #[test]
+    fn test_marker() {
+        let marker = marker();
+        assert_eq!(marker.to_string(), "198.18.10.11:49443 -> 198.19.0.1:9 udp");
+        assert_eq!(marker.label(), "selftest-0a0bc123");
+        assert_eq!(Marker::from_nonce(0).src_ip, 0xC612_0001);
+
+        let spec = marker.spec();
+        assert_eq!(spec.src_ip.as_deref(), Some("198.18.10.11"));
+        assert_eq!((spec.protocol, spec.action, spec.priority, spec.expire), (17, 4, u32::MAX, SELFTEST_EXPIRE));
+        assert_eq!((spec.src_port_min, spec.src_port_max, spec.dst_port_min, spec.dst_port_max), (49443, 49443, 9, 9));
+
+        // 표식 프레임은 임시 규칙에 매치
+        let frame = marker.frame();
+        assert_eq!(&frame[26..30], &[198, 18, 10, 11]);
+        assert_eq!(&frame[34..36], &49443u16.to_be_bytes());
+        assert!(frame.ends_with(MARKER_PAYLOAD));
+    }
+
+    #[tokio::test]
+    async fn test_run_passes() {
+        let mut datapath = FakeDatapath::default();
+        let report = SelfTest::new(&mut datapath, plan()).run().await;
+
+        assert!(report.passed, "{:?}", report);
+        assert_eq!((report.packets_sent, report.packets_observed), (5, Some(5)));
+        assert_eq!(report.label, "selftest-0a0bc123");
+        assert_eq!(report.summary(), "passed: 5/5 packets counted on lo");
+        assert_eq!(datapath.calls, [
+            "install selftest-0a0bc123",
+            "transmit lo 5x62",
+            "packets selftest-0a0bc123",
+            "remove selftest-0a0bc123",
+        ]);
+    }
+
+    #[test]
+    fn test_stages_advance_in_order() {
+        let mut datapath = FakeDatapath::default();
+        let mut test = SelfTest::new(&mut datapath, plan());
+        let stages: Vec<Stage> = std::iter::from_fn(|| (test.stage != Stage::Done).then(|| test.advance())).collect();
+        assert_eq!(stages, [Stage::Transmit, Stage::Wait, Stage::ReadCounter, Stage::Cleanup, Stage::Done]);
+        assert_eq!(test.advance(), Stage::Done);
+    }
+
+    #[tokio::test]
+    async fn test_missing_packets_fail() {
+        let mut datapath = FakeDatapath { counted: Some(0), ..Default::default() };
+        let report = SelfTest::new(&mut datapath, plan()).run().await;
+
+        assert!(!report.passed);
+        assert_eq!((report.failed_stage.as_deref(), report.packets_observed), (None, Some(0)));
+        assert_eq!(report.summary(), "failed: 0/5 packets counted on lo");
+        assert_eq!(datapath.calls.last().map(String::as_str), Some("remove selftest-0a0bc123"));
+    }
+
+    #[tokio::test]
+    async fn test_failures_clean_up() {
+        // 설치에 실패하면 지울 규칙이 없음
+        let mut datapath = FakeDatapath::failing(Stage::Install);
+        let report = SelfTest::new(&mut datapath, plan()).run().await;
+        assert_eq!(report.failed_stage.as_deref(), Some("install"));
+        assert_eq!(report.summary(), "failed at install on lo: install failed");
+        assert_eq!(datapath.calls.len(), 1);
+
+        // 설치 뒤 실패는 모두 규칙을 지움
+        for (stage, calls) in [(Stage::Transmit, 3), (Stage::ReadCounter, 4)] {
+            let mut datapath = FakeDatapath::failing(stage);
+            let report = SelfTest::new(&mut datapath, plan()).run().await;
+            assert!(!report.passed);
+            assert_eq!(report.failed_stage.as_deref(), Some(stage.name()));
+            assert_eq!(report.error, Some(format!("{} failed", stage.name())));
+            assert_eq!(datapath.calls.len(), calls);
+            assert_eq!(datapath.calls.last().map(String::as_str), Some("remove selftest-0a0bc123"));
+        }
+
+        // 삭제 실패는 따로 보고
+        let mut datapath = FakeDatapath { fail_remove: true, ..Default::default() };
+        let report = SelfTest::new(&mut datapath, plan()).run().await;
+        assert!(report.passed);
+        assert_eq!(report.cleanup_error.as_deref(), Some("map delete failed"));
+    }
+
+    #[test]
+    fn test_drop_removes_installed_rule() {
+        let mut datapath = FakeDatapath::default();
+        let mut test = SelfTest::new(&mut datapath, plan());
+        test.advance();
+        test.advance();
+        assert_eq!(test.stage, Stage::Wait);
+        drop(test);
+        assert_eq!(datapath.calls.last().map(String::as_str), Some("remove selftest-0a0bc123"));
+
+        // 설치 전에 버리면 아무것도 하지 않음
+        let mut datapath = FakeDatapath::default();
+        drop(SelfTest::new(&mut datapath, plan()));
+        assert!(datapath.calls.is_empty());
+    }
+
+    #[test]
+    fn test_live_datapath_counts_and_removes() {
+        let (filter_rules, wildcard_rules) = (MemoryMap::new(), MemoryMap::new());
+        let mut manager = MapManager::with_backends(MapBackends {
+            filter_rules: Some(&filter_rules),
+            wildcard: Some(&wildcard_rules),
+            ..Default::default()
+        });
+        assert_eq!(check_interface(&manager, "eth0").unwrap_err().field, "interface");
+        manager.attach_interface("eth0", "generic", None, FallbackMode::None, |_| Ok(())).unwrap();
+        assert!(check_interface(&manager, "eth0").is_ok());
+
+        let manager = Mutex::new(manager);
+        let mut datapath = LiveDatapath::new(&manager);
+        let rule = plan().rule;
+        let label = rule.label.clone();
+        datapath.install(rule).unwrap();
+
+        // 커널이 센 것처럼 통계 영역 갱신
+        let key = manager.lock().unwrap().snapshot_rule(&label).unwrap().key.unwrap();
+        let mut value = filter_rules.lookup(&key).unwrap().unwrap();
+        value[RULE_STATS_OFFSET..].copy_from_slice(&FilterStats { packets: 5, bytes: 310, ..Default::default() }.to_bytes());
+        filter_rules.update(&key, &value).unwrap();
+        assert_eq!(datapath.packets(&label).unwrap(), 5);
+
+        datapath.remove(&label).unwrap();
+        assert!(!manager.lock().unwrap().has_rule(&label));
+        assert!(filter_rules.keys().is_empty());
+        assert!(datapath.packets(&label).is_err());
+    }
+}
@@ -2,22 +2,87 @@
 //! CLI 도구와 통신하기 위한 API 서버 구현
 
 use anyhow::{anyhow, Context, Result};
-use log::{debug, error, info, warn};
-use serde_json::{self, json};
+use tracing::{debug, error, info, warn, Instrument};
 use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
 use std::sync::{Arc, Mutex};
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
-use tokio::net::{TcpListener, TcpStream};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::{TcpListener, UnixListener, UnixStream};
 use tokio::sync::mpsc;
+use tokio::task::LocalSet;
+use tokio_rustls::rustls::ServerConfig;
+use tokio_rustls::TlsAcceptor;
 
 //use crate::api::{ApiRequest, ApiResponse};
-use crate::maps::{FilterRule, MapManager};
+use crate::apimetrics::{self, ApiMetrics};
+use crate::auth::{self, TokenTable};
+use crate::backend::BackendKind;
+use crate::bench;
+use crate::bpf::{self, XdpMode};
+use crate::capture;
+use crate::config::InterfaceConfig;
+use crate::conflicts;
+use crate::counters;
+use crate::country;
+use crate::dns::{self, HostEndpoint, HostResolver};
+use crate::dynblock;
+use crate::error::{self, DaemonError};
+use crate::events::{self, EventDispatcher};
+use crate::geoip::{self, GeoResolver};
+use crate::ipset::PrefixSet;
+use crate::maps::{self, MapBackends, MapManager};
+use crate::netif;
+use crate::peercred::{self, Peer, UserCache};
+use crate::presets;
+use crate::preflight;
+use crate::privdrop::{self, DroppedPrivileges};
+use crate::ratelimit::RateLimiter;
+use crate::responselimit;
+use crate::ruleset;
+use crate::selftest;
+use crate::rulewatch;
+use crate::syslog::{audit_subject, SecurityEvent, SyslogSink};
 use crate::telemetry::TelemetryCollector;
+use crate::wasm::WasmManager;
+use crate::webhook::WebhookMetrics;
 //use crate::utils;
 
-use swift_guard::api::{RuleInfo, RuleStats, ApiRequest, ApiResponse, SystemStats};
+use swift_guard::api::{BpfMapInfo, BpfProgramInfo, CapabilityCheck, FallbackMode, InterfaceInfo, NewRule, RuleError, RuleSpec, ApiRequest, ApiResponse, RequestFrame, ResponseFrame, WasmModuleMode, DEFAULT_MAX_RESPONSE_LEN};
+use swift_guard::build_info::BuildInfo;
+use swift_guard::error::ErrorCode;
+use swift_guard::layout::StructLayout;
 use swift_guard::utils;
 
+/// 요청 프레임 최대 크기 (바이트)
+const MAX_REQUEST_LEN: usize = 16 * 1024 * 1024;
+
+/// TLS 핸드셰이크 제한 시간 (넘기면 연결을 닫음)
+const TLS_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 최종 응답 전에 보낼 중간 응답 프레임 채널 (대량 맵 쓰기 진행 상황)
+type ProgressSender = mpsc::UnboundedSender<ApiResponse>;
+
+/// 다른 전송 계층(gRPC 등)에서 전달된 요청
+///
+/// 모든 전송 계층이 같은 `process_request`를 거치도록 요청을 API 서버로 넘기고
+/// 응답을 `reply`로 돌려받는다.
+#[cfg(feature = "grpc")]
+#[derive(Debug)]
+pub struct ForwardedRequest {
+    pub request: ApiRequest,
+    /// 요청과 함께 받은 API 토큰
+    pub token: Option<String>,
+    /// 규칙 요청의 네임스페이스
+    pub namespace: Option<String>,
+    /// 요청과 함께 받은 요청 ID
+    pub request_id: Option<String>,
+    /// 요청자 식별자 ("peer:IP")
+    pub peer: String,
+    pub reply: tokio::sync::oneshot::Sender<ApiResponse>,
+}
+
 /// API 서버
 #[derive(Debug)]
 pub struct ApiServer<'a> {
@@ -27,6 +92,63 @@ pub struct ApiServer<'a> {
     map_manager: Arc<Mutex<MapManager<'a>>>,
     /// 텔레메트리 수집기
     telemetry: Arc<TelemetryCollector<'a>>,
+    /// TLS 설정 (없으면 평문 TCP)
+    tls: Option<Arc<ServerConfig>>,
+    /// 함께 받을 Unix 소켓 경로 (TLS 없이 요청자 자격 증명으로 식별)
+    unix_socket: Option<PathBuf>,
+    /// Unix 소켓 요청자의 사용자 이름 캐시
+    users: UserCache,
+    /// 감사 이벤트 syslog 싱크
+    syslog: Option<SyslogSink>,
+    /// 요청 처리 설정
+    settings: ServerSettings,
+}
+
+/// 요청 처리에 필요한 데몬 설정
+#[derive(Debug, Default)]
+struct ServerSettings {
+    /// 패킷 캡처 디렉토리 (ListCaptures 조회 대상)
+    capture_dir: Option<PathBuf>,
+    /// attach 요청에 사용할 BPF 오브젝트
+    bpf_obj: Option<PathBuf>,
+    /// attach 요청이 지정할 수 있는 대체 BPF 오브젝트 디렉토리
+    object_dir: Option<PathBuf>,
+    /// 인터페이스별 구성 (XDP 연결 실패 시 대체 백엔드)
+    interfaces: Vec<InterfaceConfig>,
+    /// 시작 시 수행한 커널 기능 점검 결과 (Ping 응답)
+    capabilities: Vec<CapabilityCheck>,
+    /// 로드 시 수집한 BPF 프로그램/맵 정보와 검증된 맵 값 레이아웃 (GetBpfInfo 응답)
+    bpf_info: Option<(Vec<BpfProgramInfo>, Vec<BpfMapInfo>, Vec<StructLayout>)>,
+    /// WASM 검사 모듈 관리자 (없으면 WASM 요청 거부)
+    wasm: Option<Arc<WasmManager>>,
+    /// API 서버 메트릭 (텔레메트리와 공유)
+    metrics: Arc<ApiMetrics>,
+    /// 피어별 요청 빈도 제한기 (없으면 제한하지 않음)
+    rate_limiter: Option<RateLimiter>,
+    /// 이벤트 배포기 (GetEvents 조회 대상, 없으면 거부)
+    events: Option<EventDispatcher>,
+    /// 웹훅 전송 카운터 (Ping 응답, 없으면 0)
+    webhook_metrics: Option<Arc<WebhookMetrics>>,
+    /// 호스트 이름 해석기 (없으면 호스트 이름 규칙 거부)
+    resolver: Option<HostResolver>,
+    /// GeoIP 해석기 (없으면 GeoLookup 거부, show-rule에 geo 없음)
+    geo: Option<Arc<dyn GeoResolver>>,
+    /// API 토큰 (비어 있으면 인증하지 않음)
+    tokens: TokenTable,
+    /// 응답 프레임 최대 크기 (없으면 `DEFAULT_MAX_RESPONSE_LEN`)
+    max_response_len: Option<usize>,
+    /// 낮춘 권한 (없으면 root로 실행 중, attach 전에 capability 확인)
+    privileges: Option<DroppedPrivileges>,
+}
+
+impl ServerSettings {
+    /// 인터페이스의 구성된 대체 백엔드
+    fn fallback_for(&self, interface: &str) -> FallbackMode {
+        self.interfaces.iter()
+            .find(|config| config.name == interface)
+            .map(|config| config.fallback)
+            .unwrap_or_default()
+    }
 }
 
 impl<'a> ApiServer<'a> {
@@ -34,46 +156,240 @@ impl<'a> ApiServer<'a> {
     pub fn new(
         addr: &str,
         map_manager: Arc<Mutex<MapManager<'a>>>,
-        telemetry: Arc<TelemetryCollector>,
+        telemetry: Arc<TelemetryCollector<'a>>,
     ) -> Result<Self> {
         Ok(Self {
             addr: addr.to_string(),
             map_manager,
             telemetry,
+            tls: None,
+            unix_socket: None,
+            users: UserCache::default(),
+            syslog: None,
+            settings: ServerSettings::default(),
         })
     }
     
+    /// TLS 종료 사용 (길이 접두 프로토콜은 TLS 스트림 위에서 그대로 사용)
+    pub fn with_tls(mut self, config: Arc<ServerConfig>) -> Self {
+        self.tls = Some(config);
+        self
+    }
+    
+    /// TCP와 함께 Unix 소켓에서도 요청을 받음 (요청자는 SO_PEERCRED의 uid로 식별)
+    pub fn with_unix_socket(mut self, path: PathBuf) -> Self {
+        self.unix_socket = Some(path);
+        self
+    }
+    
+    /// 상태를 변경하는 요청을 syslog 감사 이벤트로 기록
+    pub fn with_syslog(mut self, sink: SyslogSink) -> Self {
+        self.syslog = Some(sink);
+        self
+    }
+    
+    /// 캡처 파일 목록을 조회할 디렉토리 지정
+    pub fn with_capture_dir(mut self, dir: PathBuf) -> Self {
+        self.settings.capture_dir = Some(dir);
+        self
+    }
+    
+    /// attach 요청에 사용할 BPF 오브젝트 지정
+    pub fn with_bpf_obj(mut self, path: PathBuf) -> Self {
+        self.settings.bpf_obj = Some(path);
+        self
+    }
+    
+    /// attach 요청이 대체 BPF 오브젝트를 불러올 수 있는 디렉토리 지정
+    pub fn with_object_dir(mut self, dir: PathBuf) -> Self {
+        self.settings.object_dir = Some(dir);
+        self
+    }
+    
+    /// 인터페이스별 구성 지정 (attach 요청에 대체 백엔드가 없을 때 사용)
+    pub fn with_interfaces(mut self, interfaces: Vec<InterfaceConfig>) -> Self {
+        self.settings.interfaces = interfaces;
+        self
+    }
+    
+    /// Ping 응답에 포함할 커널 기능 점검 결과 설정
+    pub fn with_capabilities(mut self, capabilities: Vec<CapabilityCheck>) -> Self {
+        self.settings.capabilities = capabilities;
+        self
+    }
+    
+    /// GetBpfInfo 응답에 포함할 BPF 프로그램/맵 정보 설정 (`XdpFilterSkel::info`, `XdpFilterSkel::layouts`)
+    pub fn with_bpf_info(mut self, programs: Vec<BpfProgramInfo>, maps: Vec<BpfMapInfo>, layouts: Vec<StructLayout>) -> Self {
+        self.settings.bpf_info = Some((programs, maps, layouts));
+        self
+    }
+    
+    /// WASM 모듈 요청을 처리할 관리자 지정
+    pub fn with_wasm(mut self, manager: Arc<WasmManager>) -> Self {
+        self.settings.wasm = Some(manager);
+        self
+    }
+    
+    /// 요청 메트릭을 기록할 대상 지정 (`TelemetryCollector::with_api_metrics`와 공유)
+    pub fn with_metrics(mut self, metrics: Arc<ApiMetrics>) -> Self {
+        self.settings.metrics = metrics;
+        self
+    }
+    
+    /// 피어별 초당 요청 수 제한
+    pub fn with_rate_limit(mut self, max_requests_per_sec: u32) -> Self {
+        self.settings.rate_limiter = Some(RateLimiter::new(max_requests_per_sec));
+        self
+    }
+    
+    /// GetEvents 요청이 조회할 이벤트 기록 지정
+    pub fn with_events(mut self, dispatcher: EventDispatcher) -> Self {
+        self.settings.events = Some(dispatcher);
+        self
+    }
+    
+    /// Ping 응답에 포함할 웹훅 전송 카운터 (`TelemetryCollector::with_webhook_metrics`와 공유)
+    pub fn with_webhook_metrics(mut self, metrics: Arc<WebhookMetrics>) -> Self {
+        self.settings.webhook_metrics = Some(metrics);
+        self
+    }
+    
+    /// 호스트 이름 규칙 사용 (src_host를 이 해석기로 해석)
+    pub fn with_resolver(mut self, resolver: HostResolver) -> Self {
+        self.settings.resolver = Some(resolver);
+        self
+    }
+    
+    /// GeoIP 조회 사용 (GeoLookup, 단일 호스트 소스 규칙의 show-rule)
+    pub fn with_geo(mut self, resolver: Arc<dyn GeoResolver>) -> Self {
+        self.settings.geo = Some(resolver);
+        self
+    }
+    
+    /// API 토큰 인증 사용 (규칙 요청을 토큰의 네임스페이스로 제한)
+    pub fn with_tokens(mut self, tokens: TokenTable) -> Self {
+        self.settings.tokens = tokens;
+        self
+    }
+    
+    /// 응답 프레임 최대 크기 지정 (클라이언트가 더 작은 값을 보내면 그 값을 따름)
+    pub fn with_max_response_len(mut self, max_response_len: usize) -> Self {
+        self.settings.max_response_len = Some(max_response_len);
+        self
+    }
+    
+    /// 권한을 낮춘 상태 지정 (권한이 필요한 요청을 처리 전에 확인)
+    pub fn with_dropped_privileges(mut self, privileges: DroppedPrivileges) -> Self {
+        self.settings.privileges = Some(privileges);
+        self
+    }
+    
+    /// 전달된 요청 처리 (송신 측이 모두 닫히면 종료)
+    #[cfg(feature = "grpc")]
+    pub async fn serve_forwarded(&self, mut requests: mpsc::Receiver<ForwardedRequest>) {
+        while let Some(forwarded) = requests.recv().await {
+            debug!("Processing forwarded request from {}: {:?}", forwarded.peer, forwarded.request);
+            let frame = RequestFrame {
+                token: forwarded.token,
+                namespace: forwarded.namespace,
+                request_id: forwarded.request_id,
+                max_response_len: None,
+                request: forwarded.request,
+            };
+            let response = respond(frame, &Peer::named(forwarded.peer), self.syslog.as_ref(),
+                &self.settings, self.map_manager.clone(), self.telemetry.clone(), None).await;
+            
+            // 요청자가 이미 떠났으면 응답은 버림
+            let _ = forwarded.reply.send(response.response);
+        }
+    }
+}
+
+impl ApiServer<'static> {
     /// 서버 실행
-    pub async fn run(&self) -> Result<()> {
+    ///
+    /// 연결마다 작업을 만들어 처리하므로 끝나지 않는 요청(WatchRules)이 다른 연결을 막지 않는다.
+    /// 맵 관리자와 수집기는 Send가 아니므로 연결 작업은 이 스레드의 `LocalSet`에서 실행한다.
+    pub async fn run(self: Rc<Self>) -> Result<()> {
         // TCP 리스너 생성
         let listener = TcpListener::bind(&self.addr)
             .await
             .context(format!("Failed to bind to {}", self.addr))?;
         
-        info!("API server listening on {}", self.addr);
+        info!("API server listening on {}{}", self.addr,
+            if self.tls.is_some() { " (TLS)" } else { "" });
         
-        // 연결 수락 루프
+        let unix_listener = match &self.unix_socket {
+            Some(path) => {
+                let listener = bind_unix_socket(path)?;
+                info!("API server listening on {}", path.display());
+                Some(listener)
+            },
+            None => None,
+        };
+        
+        LocalSet::new().run_until(self.accept_loop(listener, unix_listener)).await
+    }
+    
+    /// 연결 수락 루프 (연결마다 로컬 작업 생성)
+    async fn accept_loop(self: Rc<Self>, listener: TcpListener, unix_listener: Option<UnixListener>) -> Result<()> {
         loop {
-            match listener.accept().await {
-                Ok((stream, addr)) => {
+            let accepted = tokio::select! {
+                accepted = listener.accept() => accepted.map(Accepted::Tcp),
+                accepted = accept_unix(unix_listener.as_ref()) => accepted.map(Accepted::Unix),
+            };
+            
+            match accepted {
+                Ok(Accepted::Tcp((stream, addr))) => {
                     debug!("Accepted connection from {}", addr);
                     
-                    // 요청 처리 작업 생성
-                    let map_manager = self.map_manager.clone();
-                    let telemetry = self.telemetry.clone();
-/*                    
-                    tokio::spawn(async move {
-//                        if let Err(e) = handle_connection(stream, map_manager, telemetry).await {
-                        if let Err(e) = handle_connection(stream, map_manager.clone(), telemetry.clone()).await {
+                    // 요청자 식별 (인증 수단이 없으므로 피어 주소 사용)
+                    let peer = Peer::remote(addr.ip());
+                    let server = Rc::clone(&self);
+                    tokio::task::spawn_local(async move {
+                        let _connection = server.settings.metrics.connection();
+                        let result = match &server.tls {
+                            Some(config) => {
+                                let handshake = TlsAcceptor::from(config.clone()).accept(stream);
+                                match tokio::time::timeout(TLS_HANDSHAKE_TIMEOUT, handshake).await {
+                                    Ok(Ok(stream)) => server.handle(stream, &peer).await,
+                                    Ok(Err(e)) => {
+                                        warn!("TLS handshake with {} failed: {}", addr, e);
+                                        return;
+                                    }
+                                    Err(_) => {
+                                        warn!("TLS handshake with {} timed out after {:?}", addr, TLS_HANDSHAKE_TIMEOUT);
+                                        return;
+                                    }
+                                }
+                            },
+                            None => server.handle(stream, &peer).await,
+                        };
+                        
+                        if let Err(e) = result {
                             error!("Connection error: {}", e);
                         }
                     });
                 }
-*/
-                    // 직접 요청 처리
-                    if let Err(e) = handle_connection(stream, map_manager.clone(), telemetry.clone()).await {
-                        error!("Connection error: {}", e);
-                    }
+                
+                Ok(Accepted::Unix(stream)) => {
+                    // 로컬 연결은 커널이 알려주는 자격 증명으로 식별
+                    let peer = match peercred::identify(&stream, &self.users) {
+                        Ok(identity) => Peer::local(identity),
+                        Err(e) => {
+                            warn!("Failed to read Unix socket peer credentials: {}", e);
+                            continue;
+                        }
+                    };
+                    debug!("Accepted Unix socket connection from {}", peer.actor());
+                    
+                    let server = Rc::clone(&self);
+                    tokio::task::spawn_local(async move {
+                        let _connection = server.settings.metrics.connection();
+                        if let Err(e) = server.handle(stream, &peer).await {
+                            error!("Connection error: {}", e);
+                        }
+                    });
                 }
 
                 Err(e) => {
@@ -82,14 +398,53 @@ impl<'a> ApiServer<'a> {
             }
         }
     }
+    
+    /// 연결 하나의 요청 처리
+    async fn handle<S>(&self, stream: S, peer: &Peer) -> Result<()>
+    where
+        S: AsyncRead + AsyncWrite + Unpin,
+    {
+        handle_connection(stream, peer, self.syslog.as_ref(), &self.settings,
+            self.map_manager.clone(), self.telemetry.clone()).await
+    }
+}
+
+/// 수락한 연결
+enum Accepted {
+    Tcp((tokio::net::TcpStream, SocketAddr)),
+    Unix(UnixStream),
+}
+
+/// Unix 소켓 연결 수락 (소켓이 없으면 끝나지 않음)
+async fn accept_unix(listener: Option<&UnixListener>) -> std::io::Result<UnixStream> {
+    match listener {
+        Some(listener) => listener.accept().await.map(|(stream, _)| stream),
+        None => std::future::pending().await,
+    }
+}
+
+/// Unix 소켓 바인드 (이전 실행이 남긴 소켓 파일은 지움)
+fn bind_unix_socket(path: &Path) -> Result<UnixListener> {
+    use std::os::unix::fs::FileTypeExt;
+    if std::fs::symlink_metadata(path).is_ok_and(|metadata| metadata.file_type().is_socket()) {
+        std::fs::remove_file(path)
+            .with_context(|| format!("Failed to remove stale socket {}", path.display()))?;
+    }
+    UnixListener::bind(path).with_context(|| format!("Failed to bind to {}", path.display()))
 }
 
 /// 클라이언트 연결 처리
-async fn handle_connection<'a>(
-    mut stream: TcpStream,
+async fn handle_connection<'a, S>(
+    mut stream: S,
+    peer: &Peer,
+    syslog: Option<&SyslogSink>,
+    settings: &ServerSettings,
     map_manager: Arc<Mutex<MapManager<'a>>>,
     telemetry: Arc<TelemetryCollector<'a>>,
-) -> Result<()> {
+) -> Result<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
     // 요청 길이 수신 (4바이트 빅 엔디안)
     let mut len_bytes = [0u8; 4];
     stream.read_exact(&mut len_bytes)
@@ -97,6 +452,18 @@ async fn handle_connection<'a>(
         .context("Failed to read request length")?;
     let len = u32::from_be_bytes(len_bytes) as usize;
     
+    // 크기 제한을 넘는 요청은 읽지 않고 거부
+    if len > MAX_REQUEST_LEN {
+        settings.metrics.oversized();
+        throttled_warn!("api-oversized", crate::logthrottle::HOT_PATH_INTERVAL,
+            "Rejected oversized request from {}: {} bytes", peer, len);
+        let response = ApiResponse::InvalidArgument {
+            field: "request".to_string(),
+            message: format!("Request too large: {} bytes (max {})", len, MAX_REQUEST_LEN),
+        };
+        return write_response(&mut stream, &ResponseFrame { request_id: None, response }).await;
+    }
+    
     // 요청 내용 수신
     let mut request_bytes = vec![0u8; len];
     stream.read_exact(&mut request_bytes)
@@ -104,15 +471,57 @@ async fn handle_connection<'a>(
         .context("Failed to read request")?;
     
     // 요청 역직렬화
-    let request: ApiRequest = serde_json::from_slice(&request_bytes)
+    let frame: RequestFrame = serde_json::from_slice(&request_bytes)
         .context("Failed to deserialize request")?;
     
     // 요청 처리
-    debug!("Processing request: {:?}", request);
-    let response = process_request(request, map_manager, telemetry).await?;
+    debug!("Processing request from {}: {:?}", peer, frame.request);
+    let request_id = frame.request_id.clone();
+    let max_response_len = settings.max_response_len.unwrap_or(DEFAULT_MAX_RESPONSE_LEN);
+    let max_response_len = frame.max_response_len.map_or(max_response_len, |len| len.min(max_response_len));
+    let (progress, mut progress_frames) = mpsc::unbounded_channel();
+    let (mut reader, mut writer) = tokio::io::split(stream);
+    let work = respond(frame, peer, syslog, settings, map_manager, telemetry, Some(&progress));
+    tokio::pin!(work);
+    
+    // 처리 중에는 진행 상황 프레임을 보내고, 연결이 끊기면 수신 측을 닫아 처리를 중단시킴
+    let mut connected = true;
+    let mut probe = [0u8; 1];
+    let response = loop {
+        tokio::select! {
+            response = &mut work => break response,
+            Some(response) = progress_frames.recv() => {
+                let frame = ResponseFrame { request_id: request_id.clone(), response };
+                if write_response(&mut writer, &frame).await.is_err() {
+                    progress_frames.close();
+                }
+            },
+            // 클라이언트는 응답을 기다리는 동안 보내는 것이 없으므로 읽기가 끝나면 연결이 끊긴 것
+            read = reader.read(&mut probe), if connected => {
+                if matches!(read, Ok(0) | Err(_)) {
+                    connected = false;
+                    progress_frames.close();
+                }
+            },
+        }
+    };
+    
+    // 클라이언트가 받을 수 없는 크기의 목록 응답은 앞부분만 담아 보냄
+    let response = responselimit::guard(response, max_response_len);
+    if let ApiResponse::PartialResults { returned, total, .. } = &response.response {
+        debug!("Truncated response to {} to {} of {} items ({} byte limit)", peer, returned, total, max_response_len);
+    }
     
+    write_response(&mut writer, &response).await
+}
+
+/// 응답 전송
+async fn write_response<S>(stream: &mut S, response: &ResponseFrame) -> Result<()>
+where
+    S: AsyncWrite + Unpin,
+{
     // 응답 직렬화
-    let response_bytes = serde_json::to_vec(&response)
+    let response_bytes = serde_json::to_vec(response)
         .context("Failed to serialize response")?;
     
     // 응답 길이 전송 (4바이트 빅 엔디안)
@@ -130,71 +539,235 @@ async fn handle_connection<'a>(
     Ok(())
 }
 
-/// 요청 처리
+/// 요청 ID를 정한 뒤 `request` 스팬 안에서 요청 처리
+///
+/// 요청 처리 중 남는 로그 줄(`log` 매크로 포함)은 모두 요청 ID를 가진다. 요청 ID가
+/// 없으면 새로 만들고, 클라이언트가 보낸 요청 ID만 응답에 돌려준다. `progress`는
+/// 중간 응답 프레임을 보낼 채널이며, 수신 측이 닫히면 요청자가 떠난 것으로 본다.
+async fn respond<'a>(
+    mut frame: RequestFrame,
+    peer: &Peer,
+    syslog: Option<&SyslogSink>,
+    settings: &ServerSettings,
+    map_manager: Arc<Mutex<MapManager<'a>>>,
+    telemetry: Arc<TelemetryCollector<'a>>,
+    progress: Option<&ProgressSender>,
+) -> ResponseFrame {
+    let (request_id, echo) = match frame.request_id.take() {
+        Some(id) => {
+            if let Err(e) = utils::validate_request_id(&id) {
+                settings.metrics.record(frame.request.kind(), Duration::ZERO, true);
+                let response = ApiResponse::InvalidArgument { field: e.field, message: e.message };
+                return ResponseFrame { request_id: None, response };
+            }
+            (id, true)
+        },
+        None => (utils::new_request_id(), false),
+    };
+    
+    let span = tracing::info_span!("request", id = %request_id, kind = frame.request.kind(), peer = peer.id());
+    frame.request_id = Some(request_id.clone());
+    let response = respond_in_span(frame, peer, syslog, settings, map_manager, telemetry, progress)
+        .instrument(span)
+        .await;
+    
+    ResponseFrame {
+        request_id: echo.then_some(request_id),
+        response,
+    }
+}
+
+/// 요청 처리 후 응답 생성
+///
+/// 빈도 제한을 넘거나 토큰 권한이 없는 요청은 처리하지 않는다. 처리 오류는 내부
+/// 오류 응답으로 바꾸고, 상태 변경 요청은 감사 기록하며, 모든 요청을 종류별 메트릭에
+/// 집계한다. 프레임의 요청 ID는 `respond`가 정한 값이다.
+async fn respond_in_span<'a>(
+    frame: RequestFrame,
+    peer: &Peer,
+    syslog: Option<&SyslogSink>,
+    settings: &ServerSettings,
+    map_manager: Arc<Mutex<MapManager<'a>>>,
+    telemetry: Arc<TelemetryCollector<'a>>,
+    progress: Option<&ProgressSender>,
+) -> ApiResponse {
+    let started = Instant::now();
+    let RequestFrame { token, namespace, request_id, request, .. } = frame;
+    let kind = request.kind();
+    let authorized = settings.tokens.authenticate(token.as_deref())
+        .and_then(|caller| auth::authorize(&caller, &request, namespace.as_deref()));
+    let audit = syslog.and_then(|sink| audit_subject(&request).map(|mut subject| {
+        if request.is_rule_scoped() {
+            subject.namespace = authorized.as_ref().ok().cloned().or(namespace);
+        }
+        subject.request_id = request_id;
+        (sink, subject)
+    }));
+    
+    let limited = settings.rate_limiter.as_ref()
+        .and_then(|limiter| limiter.check(peer.id()).err().map(|wait| (limiter.limit(), wait)));
+    
+    let response = match (limited, authorized) {
+        (Some((limit, wait)), _) => {
+            settings.metrics.rate_limited();
+            throttled_warn!(&format!("api-rate-limit:{}", peer), crate::logthrottle::HOT_PATH_INTERVAL,
+                "Rate limited {} request from {}", kind, peer);
+            ApiResponse::RateLimited {
+                retry_after_ms: wait.as_millis().max(1) as u64,
+                limit,
+            }
+        },
+        (None, Err(e)) => {
+            throttled_warn!(&format!("api-denied:{}", peer), crate::logthrottle::HOT_PATH_INTERVAL,
+                "Denied {} request from {}: {:?}", kind, peer, e);
+            ApiResponse::from(e)
+        },
+        (None, Ok(namespace)) => match process_request(request, &namespace, peer, settings, map_manager, telemetry, progress).await {
+            Ok(response) => response,
+            Err(e) => {
+                error!("Failed to process request: {:#}", e);
+                error::to_response(e)
+            }
+        },
+    };
+    
+    settings.metrics.record(kind, started.elapsed(), apimetrics::is_error(&response));
+    
+    if let Some((sink, subject)) = audit {
+        sink.send(&SecurityEvent::audit(subject, &peer.actor(), &response));
+    }
+    
+    response
+}
+
+/// 요청 처리 (`namespace`는 규칙 요청의 인가된 네임스페이스)
 async fn process_request<'a>(
     request: ApiRequest,
+    namespace: &str,
+    peer: &Peer,
+    settings: &ServerSettings,
     map_manager: Arc<Mutex<MapManager<'a>>>,
     telemetry: Arc<TelemetryCollector<'a>>,
+    progress: Option<&ProgressSender>,
 ) -> Result<ApiResponse> {
+    // 작성자를 지정하지 않은 규칙의 작성자
+    let creator = peer.creator();
+    
     match request {
-        ApiRequest::Attach { interface, mode, force } => {
-            // XDP 프로그램 연결 로직
-            // 실제 구현에서는 특정 인터페이스에 XDP 프로그램을 로드하는 로직 추가
+        ApiRequest::Attach { interface, mode, force, fallback, obj_path } => {
+            if let Some(privileges) = &settings.privileges {
+                if let Err(e) = privileges.require("attach", privdrop::ATTACH_CAPABILITIES) {
+                    return Ok(ApiResponse::from(e));
+                }
+            }
+            let mode = match XdpMode::from_num(mode) {
+                Some(mode) => mode,
+                None => return Ok(ApiResponse::InvalidArgument {
+                    field: "mode".to_string(),
+                    message: format!("Invalid XDP mode: {}", mode),
+                }),
+            };
+            let object = match obj_path {
+                Some(requested) => match bpf::resolve_object_path(&requested, settings.object_dir.as_deref()) {
+                    Ok(path) => Some(path),
+                    Err(e) => return Ok(ApiResponse::from(e)),
+                },
+                None => None,
+            };
+            let object_name = object.as_ref().map(|path| path.display().to_string());
+            let fallback = fallback.unwrap_or_else(|| settings.fallback_for(&interface));
             
-            Ok(ApiResponse::Success {
-                message: format!("XDP program attached to {} in mode {}", interface, mode),
-            })
+            let mut map_manager = map_manager.lock()
+                .map_err(|_| anyhow!("Failed to lock map_manager"))?;
+            
+            // 처음 쓰는 대체 오브젝트는 로드해 현재 규칙을 설치
+            if let (Some(path), Some(name)) = (&object, &object_name) {
+                if !map_manager.has_object(name) {
+                    let skel = bpf::load_object(path)?;
+                    map_manager.add_object(name, MapBackends::from_skel(skel))?;
+                }
+            }
+            
+            let backend = map_manager.attach_interface(&interface, mode.name(), object_name.as_deref(), fallback, |interface| {
+                let obj_path = object.as_ref().or(settings.bpf_obj.as_ref())
+                    .ok_or_else(|| DaemonError::Config("BPF object path is not configured".to_string()))?;
+                let driver = netif::driver_name(interface);
+                preflight::decide(interface, driver.as_deref(), mode, force, || {
+                    Ok(bpf::dry_attach_xdp_program(obj_path, interface, mode)?)
+                }).map_err(DaemonError::Bpf)?;
+                bpf::attach_xdp_program(obj_path, interface, mode)
+            })?;
+            
+            let message = match backend {
+                BackendKind::Xdp => format!("XDP program attached to {} in {} mode", interface, mode.name()),
+                BackendKind::Nftables => format!("XDP attach to {} failed; rules are enforced with nftables", interface),
+            };
+            Ok(ApiResponse::Success { message, warning: None })
         },
         
         ApiRequest::Detach { interface } => {
-            // XDP 프로그램 분리 로직
-            // 실제 구현에서는 특정 인터페이스에서 XDP 프로그램을 언로드하는 로직 추가
+            let mut map_manager = map_manager.lock()
+                .map_err(|_| anyhow!("Failed to lock map_manager"))?;
             
-            Ok(ApiResponse::Success {
-                message: format!("XDP program detached from {}", interface),
-            })
+            let backend = map_manager.detach_interface(&interface, bpf::unload_xdp_program)?;
+            
+            let message = match backend {
+                BackendKind::Xdp => format!("XDP program detached from {}", interface),
+                BackendKind::Nftables => format!("nftables rules removed from {}", interface),
+            };
+            Ok(ApiResponse::Success { message, warning: None })
         },
         
-        ApiRequest::AddRule {
-            src_ip,
-            dst_ip,
-            src_port_min,
-            src_port_max,
-            dst_port_min,
-            dst_port_max,
-            protocol,
-            tcp_flags,
-            action,
-            redirect_if,
-            priority,
-            rate_limit,
-            expire,
-            label,
-        } => {
-            // IP 주소 파싱
-            let src_ip_parsed = if let Some(ip_str) = src_ip {
-                Some(utils::parse_ip_prefix(&ip_str)?)
-            } else {
-                None
-            };
-            
-            let dst_ip_parsed = if let Some(ip_str) = dst_ip {
-                Some(utils::parse_ip_prefix(&ip_str)?)
-            } else {
-                None
-            };
-            
-            // 리디렉션 인터페이스 인덱스 획득
-            let redirect_ifindex = if let Some(ifname) = redirect_if {
-                // 여기서는 간단히 하기 위해 "if<number>" 형식을 파싱
-                if ifname.starts_with("if") {
-                    ifname[2..].parse::<u32>()
-                        .map_err(|_| anyhow!("Invalid interface format: {}", ifname))?
-                } else {
-                    0
-                }
-            } else {
-                0
+        ApiRequest::AddRule(rule) => {
+            let NewRule {
+                src_ip,
+                dst_ip,
+                src_port_min,
+                src_port_max,
+                dst_port_min,
+                dst_port_max,
+                protocol,
+                tcp_flags,
+                action,
+                redirect_if,
+                redirect_cpu,
+                priority,
+                rate_limit,
+                expire,
+                label,
+                description,
+                created_by,
+                quota_bytes,
+                quota_packets,
+                quota_action,
+                src_host,
+                dst_host,
+                counter_group,
+                src_port_list,
+                dst_port_list,
+                owner_webhook,
+                notify_before,
+                force,
+            } = *rule;
+            
+            // XDP 프로그램은 목적지 주소를 비교하지 않으므로 목적지 호스트 이름은 적용할 수 없음
+            if dst_host.is_some() {
+                return Ok(ApiResponse::InvalidArgument {
+                    field: "dst_host".to_string(),
+                    message: "Destination hostnames cannot be enforced by the XDP program; use src_host".to_string(),
+                });
+            }
+            
+            // 소스 호스트 이름 해석 (맵 관리자 잠금 전에 수행)
+            let host = match src_host {
+                Some(name) => match resolve_src_host(settings, &name).await {
+                    Ok(host) => Some(host),
+                    Err(e) => return Ok(ApiResponse::InvalidArgument {
+                        field: "src_host".to_string(),
+                        message: format!("{:#}", e),
+                    }),
+                },
+                None => None,
             };
             
             // 현재 시간
@@ -203,10 +776,10 @@ async fn process_request<'a>(
                 .map_err(|_| anyhow!("Failed to get system time"))?
                 .as_secs();
             
-            // 필터 규칙 생성
-            let rule = FilterRule {
-                src_ip: src_ip_parsed,
-                dst_ip: dst_ip_parsed,
+            let spec = RuleSpec {
+                label: label.clone(),
+                src_ip,
+                dst_ip,
                 src_port_min,
                 src_port_max,
                 dst_port_min,
@@ -214,22 +787,66 @@ async fn process_request<'a>(
                 protocol,
                 tcp_flags,
                 action,
-                redirect_ifindex,
+                redirect_if,
+                redirect_cpu,
                 priority,
                 rate_limit,
                 expire,
-                label: label.clone(),
-                creation_time: now,
+                description,
+                quota_bytes,
+                quota_packets,
+                quota_action,
+                counter_group,
+                src_port_list,
+                dst_port_list,
+                owner_webhook,
+                notify_before,
+            };
+            
+            // 필터 규칙 생성 (필드 검증, 리디렉션 인터페이스 확인)
+            let created_by = created_by.or_else(|| Some(creator.clone()));
+            let built = match host {
+                Some(host) => ruleset::build_host_rule(spec, host, created_by, now, netif::resolve_redirect_target),
+                None => ruleset::build_rule(spec, created_by, now, netif::resolve_redirect_target),
             };
+            let mut rule = match built {
+                Ok(rule) => rule,
+                Err(e) => {
+                    return Ok(ApiResponse::InvalidArgument { field: e.field, message: e.message });
+                }
+            };
+            
+            // 레이블은 네임스페이스 안에서만 유일
+            rule.label = utils::qualify_label(namespace, &label);
             
             // 맵 관리자에 규칙 추가
             let mut map_manager = map_manager.lock()
                 .map_err(|_| anyhow!("Failed to lock map_manager"))?;
             
-            map_manager.add_rule(rule)?;
+            // 로드된 XDP 프로그램이 처리하지 못하는 매치 필드 (force면 경고만)
+            let unsupported = match ruleset::negotiate_features(&rule, map_manager.rule_features(), force) {
+                Ok(warning) => warning,
+                Err(e) => return Ok(ApiResponse::InvalidArgument { field: e.field, message: e.message }),
+            };
+            
+            match map_manager.add_rule(rule) {
+                Ok(()) => {}
+                // 응답에는 네임스페이스를 뺀 레이블을 씀
+                Err(DaemonError::DuplicateLabel(_)) => return Ok(ApiResponse::from(DaemonError::DuplicateLabel(label))),
+                Err(e) => return Err(e.into()),
+            }
+            
+            // 인터페이스를 연결하기 전에 추가한 규칙은 아직 패킷에 적용되지 않음
+            let detached = (!map_manager.has_attached_interfaces())
+                .then(|| "rule installed but no interfaces are attached".to_string());
+            let warning = match (unsupported, detached) {
+                (Some(unsupported), Some(detached)) => Some(format!("{}; {}", unsupported, detached)),
+                (unsupported, detached) => unsupported.or(detached),
+            };
             
             Ok(ApiResponse::Success {
                 message: format!("Rule '{}' added successfully", label),
+                warning,
             })
         },
         
@@ -238,66 +855,882 @@ async fn process_request<'a>(
             let mut map_manager = map_manager.lock()
                 .map_err(|_| anyhow!("Failed to lock map_manager"))?;
             
-            let deleted = map_manager.delete_rule(&label)?;
-            
-            if deleted {
-                Ok(ApiResponse::Success {
+            match map_manager.delete_rule(&utils::qualify_label(namespace, &label)) {
+                Ok(()) => Ok(ApiResponse::Success {
                     message: format!("Rule '{}' deleted successfully", label),
-                })
-            } else {
-                Ok(ApiResponse::Error {
-                    message: format!("Rule '{}' not found", label),
-                })
+                    warning: None,
+                }),
+                Err(DaemonError::RuleNotFound(_)) => Ok(ApiResponse::from(DaemonError::RuleNotFound(label))),
+                Err(e) => Err(e.into()),
             }
         },
         
-        ApiRequest::ListRules { include_stats } => {
+        ApiRequest::ListRules {
+            include_stats,
+            expiring_within_secs,
+            include_rates,
+            all_namespaces,
+            family,
+            include_dynamic,
+            origin,
+            cursor,
+            offset,
+            limit,
+        } => {
             // 맵 관리자에서 규칙 목록 조회
+            let mut map_manager = map_manager.lock()
+                .map_err(|_| anyhow!("Failed to lock map_manager"))?;
+            
+            // 리디렉션 대상 인터페이스 상태 반영
+            map_manager.refresh_redirect_targets(netif::current_target)?;
+            
+            let mut rules = map_manager.list_rules(include_stats)?;
+            if !all_namespaces {
+                rules.retain(|rule| rule.namespace == namespace);
+            }
+            if let Some(family) = family {
+                rules.retain(|rule| rule.family().applies_to(family));
+            }
+            dynblock::retain_listed(&mut rules, include_dynamic, origin.as_deref());
+            if include_rates {
+                telemetry.fill_rule_rates(&mut rules)?;
+            }
+            if let Some(within) = expiring_within_secs {
+                rules = maps::expiring_within(rules, within, utils::current_time_secs());
+            }
+            
+            // 페이지를 나누지 않으면 위의 순서 그대로
+            if cursor.is_none() && offset == 0 && limit == 0 {
+                let total_count = rules.len();
+                return Ok(ApiResponse::Rules { rules, total_count, next_cursor: None, seq: None });
+            }
+            
+            let page = maps::paginate(rules, cursor.as_deref(), offset as usize, limit as usize);
+            Ok(ApiResponse::Rules {
+                rules: page.rules,
+                total_count: page.total_count,
+                next_cursor: page.next_cursor,
+                seq: None,
+            })
+        },
+        
+        ApiRequest::GetCounters { prefix } => {
+            let mut rules = map_manager.lock()
+                .map_err(|_| anyhow!("Failed to lock map_manager"))?
+                .list_rules(true)?;
+            rules.retain(|rule| rule.namespace == namespace);
+            telemetry.fill_rule_rates(&mut rules)?;
+            
+            Ok(ApiResponse::Counters { counters: counters::aggregate(&rules, prefix.as_deref()) })
+        },
+        
+        ApiRequest::WatchRules { all_namespaces } => {
+            // 변경 프레임은 진행 상황 채널로 보내므로 직접 연결한 요청만 구독할 수 있음
+            let Some(frames) = progress else {
+                return Ok(ApiResponse::InvalidArgument {
+                    field: "request".to_string(),
+                    message: "Rule watch streams frames and requires a direct API connection".to_string(),
+                });
+            };
+            
+            rulewatch::stream(&map_manager, (!all_namespaces).then_some(namespace), frames).await?;
+            Ok(ApiResponse::Success { message: "Rule watch ended".to_string(), warning: None })
+        },
+        
+        ApiRequest::GetRule { label } => {
+            let key = utils::qualify_label(namespace, &label);
+            
+            // 잠금은 캐시 스냅샷을 복사하는 동안만 유지
+            let (snapshot, nftables_interfaces, suspended, links_down) = {
+                let map_manager = map_manager.lock()
+                    .map_err(|_| anyhow!("Failed to lock map_manager"))?;
+                (
+                    map_manager.snapshot_rule(&key),
+                    map_manager.nftables_interfaces(),
+                    map_manager.suspended_interfaces().cloned().collect::<Vec<_>>(),
+                    map_manager.down_interfaces().cloned().collect::<Vec<_>>(),
+                )
+            };
+            
+            let snapshot = match snapshot {
+                Some(snapshot) => snapshot,
+                None => {
+                    return Ok(ApiResponse::NotFound {
+                        resource: "rule".to_string(),
+                        name: label,
+                    });
+                }
+            };
+            
+            let now = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map_err(|_| anyhow!("Failed to get system time"))?
+                .as_secs();
+            
+            // 링크가 내려가 분리해 둔 인터페이스도 규칙이 적용될 인터페이스로 표시
+            let mut interfaces = netif::xdp_interfaces();
+            interfaces.extend(nftables_interfaces);
+            interfaces.extend(suspended);
+            
+            let down_interfaces = links_down.into_iter()
+                .filter(|name| interfaces.contains(name) || snapshot.rule.redirect_ifname.as_ref() == Some(name))
+                .collect();
+            // 단일 호스트 소스만 GeoIP 정보 표시
+            let src_geo = match snapshot.rule.src_ip {
+                Some((addr, 32)) => geoip::enrich(settings.geo.as_deref(), &utils::ipv4_to_string(addr)),
+                _ => None,
+            };
+            let mut rule = snapshot.into_detail(interfaces, down_interfaces, now);
+            rule.src_geo = src_geo;
+            rule.history_1m = telemetry.rule_history(&key, now)?;
+            
+            Ok(ApiResponse::RuleDetail { rule })
+        },
+        
+        ApiRequest::ApplyRuleset { rules, prune, dry_run, progress: report } => {
+            let now = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map_err(|_| anyhow!("Failed to get system time"))?
+                .as_secs();
+            
+            let options = ruleset::ApplyOptions { namespace, prune, dry_run };
+            let pending = ruleset::prepare(&*map_manager.lock().map_err(|_| anyhow!("Failed to lock map_manager"))?,
+                rules, options, &creator, now, netif::resolve_redirect_target);
+            
+            // 맵 쓰기는 청크마다 잠금을 놓으며 진행
+            let summary = pending.run(&map_manager, progress, report).await?;
+            Ok(ApiResponse::RulesetApplied { summary })
+        },
+        
+        ApiRequest::PlanChanges { rules, prune } => {
+            let map_manager = map_manager.lock()
+                .map_err(|_| anyhow!("Failed to lock map_manager"))?;
+            
+            let options = ruleset::ApplyOptions { namespace, prune, dry_run: true };
+            let summary = ruleset::plan(&map_manager, rules, options, &creator, utils::current_time_secs(),
+                netif::resolve_redirect_target, lpm_map_capacity(settings));
+            Ok(ApiResponse::RulesetApplied { summary })
+        },
+        
+        ApiRequest::ApplyPreset { name, params } => {
+            let mut map_manager = map_manager.lock()
+                .map_err(|_| anyhow!("Failed to lock map_manager"))?;
+            
+            match presets::apply(&mut map_manager, &name, &params, namespace, &creator, utils::current_time_secs()) {
+                Ok(summary) => Ok(ApiResponse::RulesetApplied { summary }),
+                Err(e) => Ok(ApiResponse::InvalidArgument { field: e.field, message: e.message }),
+            }
+        },
+        
+        ApiRequest::RemovePreset { name } => {
+            let mut map_manager = map_manager.lock()
+                .map_err(|_| anyhow!("Failed to lock map_manager"))?;
+            
+            let removed = presets::remove(&mut map_manager, &name, namespace)?;
+            if removed.is_empty() {
+                return Ok(ApiResponse::NotFound { resource: "preset".to_string(), name });
+            }
+            Ok(ApiResponse::Success {
+                message: format!("Preset '{}' removed ({} rules deleted)", name, removed.len()),
+                warning: None,
+            })
+        },
+        
+        ApiRequest::ListPresets {} => {
             let map_manager = map_manager.lock()
                 .map_err(|_| anyhow!("Failed to lock map_manager"))?;
             
-            let rules = map_manager.list_rules(include_stats)?;
+            Ok(ApiResponse::Presets { presets: presets::list(&map_manager, namespace) })
+        },
+        
+        ApiRequest::BlockCountry { code, action, expire, csv_path } => {
+            let code = match country::parse_code(&code) {
+                Ok(code) => code,
+                Err(e) => return Ok(ApiResponse::InvalidArgument { field: e.field, message: e.message }),
+            };
+            if let Err(e) = country::validate_action(action) {
+                return Ok(ApiResponse::InvalidArgument { field: e.field, message: e.message });
+            }
+            
+            let source = csv_path.unwrap_or_else(|| country::GEOIP_SOURCE.to_string());
+            let set = match derive_country_set(settings, &code, source).await? {
+                Ok(set) => set,
+                Err(e) => return Ok(error::to_response(e)),
+            };
+            
+            let mut map_manager = map_manager.lock()
+                .map_err(|_| anyhow!("Failed to lock map_manager"))?;
+            
+            let block = country::Block { code, action, expire, set };
+            match country::block(&mut map_manager, block, namespace, &creator, utils::current_time_secs(), lpm_map_capacity(settings)) {
+                Ok(info) => Ok(ApiResponse::CountryBlocks { blocks: vec![info] }),
+                Err(e) => Ok(ApiResponse::from(e)),
+            }
+        },
+        
+        ApiRequest::RefreshCountryBlocks { code } => {
+            let code = match code.as_deref().map(country::parse_code).transpose() {
+                Ok(code) => code,
+                Err(e) => return Ok(ApiResponse::InvalidArgument { field: e.field, message: e.message }),
+            };
+            
+            // 원본은 잠금 밖에서 다시 읽음
+            let sources = {
+                let map_manager = map_manager.lock()
+                    .map_err(|_| anyhow!("Failed to lock map_manager"))?;
+                country::sources(&map_manager, namespace, code.as_deref())
+            };
+            if let (Some(code), true) = (&code, sources.is_empty()) {
+                return Ok(ApiResponse::NotFound {
+                    resource: "country block".to_string(),
+                    name: code.clone(),
+                });
+            }
+            
+            let mut derived = Vec::with_capacity(sources.len());
+            for (code, source) in sources {
+                let set = derive_country_set(settings, &code, source).await?;
+                derived.push((code, set));
+            }
+            
+            let mut map_manager = map_manager.lock()
+                .map_err(|_| anyhow!("Failed to lock map_manager"))?;
+            
+            let blocks = country::refresh(&mut map_manager, derived, namespace, lpm_map_capacity(settings));
+            Ok(ApiResponse::CountryBlocks { blocks })
+        },
+        
+        ApiRequest::UnblockCountry { code } => {
+            let code = match country::parse_code(&code) {
+                Ok(code) => code,
+                Err(e) => return Ok(ApiResponse::InvalidArgument { field: e.field, message: e.message }),
+            };
+            
+            let mut map_manager = map_manager.lock()
+                .map_err(|_| anyhow!("Failed to lock map_manager"))?;
             
-            Ok(ApiResponse::Rules { rules })
+            match country::remove(&mut map_manager, &code, namespace) {
+                Ok(()) => Ok(ApiResponse::Success {
+                    message: format!("Country {} unblocked (rule and prefix set {} deleted)", code, country::set_name(&code)),
+                    warning: None,
+                }),
+                Err(DaemonError::RuleNotFound(_)) => Ok(ApiResponse::NotFound {
+                    resource: "country block".to_string(),
+                    name: code,
+                }),
+                Err(e) => Ok(ApiResponse::from(e)),
+            }
+        },
+        
+        ApiRequest::RepairRules {} => {
+            let mut map_manager = map_manager.lock()
+                .map_err(|_| anyhow!("Failed to lock map_manager"))?;
+            
+            let (repaired, failed) = map_manager.repair_rules();
+            let errors = failed.into_iter()
+                .map(|(label, e)| RuleError {
+                    label,
+                    message: e.to_string(),
+                    code: e.code(),
+                })
+                .collect();
+            
+            Ok(ApiResponse::RulesRepaired { repaired, errors })
+        },
+        
+        ApiRequest::AuditMaps {} => {
+            let map_manager = map_manager.lock()
+                .map_err(|_| anyhow!("Failed to lock map_manager"))?;
+            
+            let audit = map_manager.audit_maps();
+            if !audit.is_clean() {
+                warn!("Kernel maps diverge from the rule cache: {} orphaned, {} missing entries",
+                    audit.orphans.len(), audit.missing.len());
+            }
+            
+            Ok(ApiResponse::MapAudit { audit })
+        },
+        
+        ApiRequest::RuleSpaceStats {} => {
+            let map_manager = map_manager.lock()
+                .map_err(|_| anyhow!("Failed to lock map_manager"))?;
+            
+            let stats = map_manager.rule_space_stats(lpm_map_capacity(settings));
+            if stats.kernel_mismatch() {
+                warn!("filter_rules holds {} entries but the rule cache expects {}",
+                    stats.kernel_entries.unwrap_or_default(), stats.lpm_entries);
+            }
+            
+            Ok(ApiResponse::RuleSpace { stats })
+        },
+        
+        ApiRequest::ReconcileMaps { delete_orphans, reinstall_missing } => {
+            let mut map_manager = map_manager.lock()
+                .map_err(|_| anyhow!("Failed to lock map_manager"))?;
+            
+            let summary = map_manager.reconcile_maps(delete_orphans, reinstall_missing);
+            Ok(ApiResponse::MapsReconciled { summary })
+        },
+        
+        ApiRequest::ListRedirectTargets {} => {
+            let map_manager = map_manager.lock()
+                .map_err(|_| anyhow!("Failed to lock map_manager"))?;
+            
+            Ok(ApiResponse::RedirectTargets { targets: map_manager.list_redirect_targets() })
+        },
+        
+        ApiRequest::AddRedirectTarget { interface } => {
+            let ifindex = match netif::resolve_redirect_target(&interface) {
+                Ok(ifindex) => ifindex,
+                Err(e) => return Ok(ApiResponse::InvalidArgument {
+                    field: "interface".to_string(),
+                    message: format!("{:#}", e),
+                }),
+            };
+            
+            let mut map_manager = map_manager.lock()
+                .map_err(|_| anyhow!("Failed to lock map_manager"))?;
+            map_manager.add_redirect_target(&interface, ifindex)?;
+            
+            let message = format!("Redirect target {} added (ifindex {})", interface, ifindex);
+            Ok(ApiResponse::Success { message, warning: None })
+        },
+        
+        ApiRequest::RemoveRedirectTarget { interface } => {
+            let mut map_manager = map_manager.lock()
+                .map_err(|_| anyhow!("Failed to lock map_manager"))?;
+            map_manager.remove_redirect_target(&interface)?;
+            
+            let message = format!("Redirect target {} removed", interface);
+            Ok(ApiResponse::Success { message, warning: None })
         },
         
         ApiRequest::GetStats {} => {
             // 텔레메트리 수집기에서 통계 조회
-            let stats = telemetry.get_stats()?;
+            let mut stats = telemetry.get_stats()?;
+            
+            // 규칙 만료 요약은 규칙 캐시에서
+            stats.rules = map_manager.lock()
+                .map_err(|_| anyhow!("Failed to lock map_manager"))?
+                .expiry_summary(utils::current_time_secs());
+            
+            // API 서버 통계는 서버 메트릭에서
+            stats.api = settings.metrics.snapshot();
             
             Ok(ApiResponse::Stats { stats })
         },
 
-        ApiRequest::LoadWasmModule { name, file_path } => {
-            // WASM 모듈 로드 로직
-
-            Ok(ApiResponse::Error {
-                message: "WASM module loading not implemented yet".to_string(),
-            })
+        ApiRequest::LoadWasmModule { name, file_path, on_error, config, mode } => {
+            let manager = match &settings.wasm {
+                Some(manager) => Arc::clone(manager),
+                None => return Ok(wasm_disabled()),
+            };
+            
+            if manager.module_stats(&name).is_ok() {
+                return Ok(ApiResponse::from(DaemonError::DuplicateModule(name)));
+            }
+            
+            // 컴파일은 오래 걸릴 수 있으므로 블로킹 스레드에서 수행
+            let id = name.clone();
+            let config = config.map(String::into_bytes).unwrap_or_default();
+            let result = tokio::task::spawn_blocking(move || manager.load_module_with_policy(&id, Path::new(&file_path), on_error, config, mode))
+                .await
+                .context("WASM module load task failed")?;
+            
+            match result {
+                Ok(report) => Ok(ApiResponse::Success {
+                    message: match mode {
+                        WasmModuleMode::Enforce => format!("WASM module {} loaded in {}", name, report),
+                        WasmModuleMode::Shadow => format!("WASM module {} loaded in {} (shadow mode, verdicts are not enforced)", name, report),
+                    },
+                    warning: None,
+                }),
+                Err(e @ DaemonError::Wasm(_)) => Ok(ApiResponse::Error {
+                    message: format!("Failed to load WASM module {}: {}", name, e),
+                    code: e.code(),
+                }),
+                Err(e) => Ok(ApiResponse::from(e)),
+            }
         },
 
         ApiRequest::UnloadWasmModule { name } => {
-            // WASM 모듈 언로드 로직
+            let manager = match &settings.wasm {
+                Some(manager) => manager,
+                None => return Ok(wasm_disabled()),
+            };
+            
+            match manager.unload_module(&name) {
+                Ok(()) => Ok(ApiResponse::Success {
+                    message: format!("WASM module {} unloaded", name),
+                    warning: None,
+                }),
+                Err(e) => Ok(ApiResponse::from(e)),
+            }
+        },
 
-            Ok(ApiResponse::Error {
-                message: "WASM module unloading not implemented yet".to_string(),
-            })
+        ApiRequest::ResumeWasmModule { name } => {
+            let manager = match &settings.wasm {
+                Some(manager) => manager,
+                None => return Ok(wasm_disabled()),
+            };
+            
+            match manager.resume_module(&name) {
+                Ok(()) => Ok(ApiResponse::Success {
+                    message: format!("WASM module {} resumed", name),
+                    warning: None,
+                }),
+                Err(e) => Ok(ApiResponse::from(e)),
+            }
+        },
+
+        ApiRequest::SetWasmModuleState { name, mode } => {
+            let manager = match &settings.wasm {
+                Some(manager) => manager,
+                None => return Ok(wasm_disabled()),
+            };
+            
+            match manager.set_module_mode(&name, mode) {
+                Ok(()) => Ok(ApiResponse::Success {
+                    message: format!("WASM module {} is in {} mode", name, mode.as_str()),
+                    warning: None,
+                }),
+                Err(e) => Ok(ApiResponse::from(e)),
+            }
         },
 
         ApiRequest::ListWasmModules { } => {
-            // WASM 모듈 목록 조회
+            let manager = match &settings.wasm {
+                Some(manager) => manager,
+                None => return Ok(wasm_disabled()),
+            };
             
-            Ok(ApiResponse::Error {
-                message: "WASM module listing not implemented yet".to_string(),
-            })
+            Ok(ApiResponse::WasmModules { modules: manager.list_modules()? })
         },
 
         ApiRequest::WasmModuleStats { name } => {
-            // WASM 모듈 통계
+            let manager = match &settings.wasm {
+                Some(manager) => manager,
+                None => return Ok(wasm_disabled()),
+            };
+            
+            match manager.module_stats(&name) {
+                Ok(stats) => Ok(ApiResponse::WasmModuleStats {
+                    name,
+                    processed_packets: stats.processed_packets,
+                    blocked_packets: stats.blocked_packets,
+                    avg_processing_time_us: stats.avg_processing_time_us,
+                    state: Some(stats.state.as_str().to_string()),
+                    errors: stats.errors,
+                    last_error: stats.last_error,
+                    p50_us: stats.latency.percentile(0.50),
+                    p95_us: stats.latency.percentile(0.95),
+                    p99_us: stats.latency.percentile(0.99),
+                    latency: stats.latency,
+                    mode: stats.mode,
+                    would_block_packets: stats.would_block_packets,
+                }),
+                Err(e) => Ok(ApiResponse::from(e)),
+            }
+        },
 
-            Ok(ApiResponse::Error {
-                message: "WASM module statistics not implemented yet".to_string(),
+        ApiRequest::ResetWasmModuleStats { name } => {
+            let manager = match &settings.wasm {
+                Some(manager) => manager,
+                None => return Ok(wasm_disabled()),
+            };
+            
+            match manager.reset_module_stats(&name) {
+                Ok(()) => Ok(ApiResponse::Success {
+                    message: format!("WASM module {} stats reset", name),
+                    warning: None,
+                }),
+                Err(e) => Ok(ApiResponse::from(e)),
+            }
+        },
+
+        ApiRequest::GetWasmModuleLog { name, lines, after } => {
+            let manager = match &settings.wasm {
+                Some(manager) => manager,
+                None => return Ok(wasm_disabled()),
+            };
+            
+            match manager.module_log(&name, lines, after) {
+                Ok(lines) => Ok(ApiResponse::WasmModuleLog { name, lines }),
+                Err(e) => Ok(ApiResponse::from(e)),
+            }
+        },
+        
+        ApiRequest::ClearWasmCache {} => {
+            let cache = match settings.wasm.as_ref().and_then(|manager| manager.cache()) {
+                Some(cache) => cache,
+                None => return Ok(ApiResponse::Error {
+                    message: "WASM module cache is disabled".to_string(),
+                    code: ErrorCode::Internal,
+                }),
+            };
+            
+            let removed = cache.clear()?;
+            Ok(ApiResponse::Success {
+                message: format!("Removed {} cached WASM modules from {}", removed, cache.dir().display()),
+                warning: None,
             })
         },
+
+        ApiRequest::ListDynamicBlocks { module } => {
+            let map_manager = map_manager.lock()
+                .map_err(|_| anyhow!("Failed to lock map_manager"))?;
+            
+            let blocks = dynblock::list(&map_manager, module.as_deref(), utils::current_time_secs())?;
+            Ok(ApiResponse::DynamicBlocks { blocks })
+        },
+
+        ApiRequest::ClearDynamicBlocks { module } => {
+            let mut map_manager = map_manager.lock()
+                .map_err(|_| anyhow!("Failed to lock map_manager"))?;
+            
+            let removed = dynblock::clear(&mut map_manager, module.as_deref())?;
+            let scope = match &module {
+                Some(module) => format!("WASM module {}", module),
+                None => "all WASM modules".to_string(),
+            };
+            Ok(ApiResponse::Success {
+                message: format!("Cleared {} dynamic blocks of {}", removed.len(), scope),
+                warning: None,
+            })
+        },
+
+        ApiRequest::ListConnections { limit, filter } => {
+            // 연결 추적 테이블 조회
+            let map_manager = map_manager.lock()
+                .map_err(|_| anyhow!("Failed to lock map_manager"))?;
+            
+            let (connections, total) = map_manager.list_connections(limit, &filter)?;
+            
+            Ok(ApiResponse::Connections { connections, total })
+        },
+        
+        ApiRequest::ListCaptures {} => {
+            // 캡처 디렉토리가 없으면 빈 목록
+            let captures = match &settings.capture_dir {
+                Some(dir) => capture::list_captures(dir)?,
+                None => Vec::new(),
+            };
+            
+            Ok(ApiResponse::Captures { captures })
+        },
+        
+        ApiRequest::ListInterfaces {} => {
+            let mut interfaces = {
+                let map_manager = map_manager.lock()
+                    .map_err(|_| anyhow!("Failed to lock map_manager"))?;
+                map_manager.list_interfaces()
+            };
+            
+            // attach 요청 없이 XDP가 연결된 인터페이스 (데몬 시작 시 연결 등)
+            for name in netif::xdp_interfaces() {
+                if !interfaces.iter().any(|info| info.name == name) {
+                    let link = if netif::is_up(&name) { "up" } else { "down" };
+                    interfaces.push(InterfaceInfo {
+                        name,
+                        backend: BackendKind::Xdp.as_str().to_string(),
+                        mode: None,
+                        object: None,
+                        link: Some(link.to_string()),
+                        suspended: false,
+                    });
+                }
+            }
+            interfaces.sort_by(|a, b| a.name.cmp(&b.name));
+            
+            Ok(ApiResponse::Interfaces { interfaces })
+        },
+        
+        ApiRequest::Ping {} => {
+            Ok(ApiResponse::Pong {
+                version: swift_guard::VERSION.to_string(),
+                capabilities: settings.capabilities.clone(),
+                api: settings.metrics.snapshot(),
+                webhooks: settings.webhook_metrics.as_ref().map(|metrics| metrics.snapshot()).unwrap_or_default(),
+                build: Some(BuildInfo::current()),
+                load_failure: bpf::last_load_failure(),
+                peer: peer.identity().cloned(),
+                features: map_manager.lock()
+                    .map_err(|_| anyhow!("Failed to lock map_manager"))?
+                    .features()
+                    .map(|features| features.0),
+            })
+        },
+        
+        ApiRequest::GetVersion {} => {
+            Ok(ApiResponse::Version { build: BuildInfo::current() })
+        },
+        
+        ApiRequest::ExplainConflicts {} => {
+            let map_manager = map_manager.lock()
+                .map_err(|_| anyhow!("Failed to lock map_manager"))?;
+            
+            Ok(ApiResponse::Conflicts { conflicts: conflicts::find_conflicts(map_manager.rules().iter()) })
+        },
+        
+        ApiRequest::GetBpfInfo {} => {
+            match &settings.bpf_info {
+                Some((programs, maps, layouts)) => Ok(ApiResponse::BpfInfo {
+                    programs: programs.clone(),
+                    maps: maps.clone(),
+                    layouts: layouts.clone(),
+                    features: map_manager.lock()
+                        .map_err(|_| anyhow!("Failed to lock map_manager"))?
+                        .features()
+                        .map(|features| features.0),
+                }),
+                None => Ok(ApiResponse::Error {
+                    message: "BPF object is not loaded".to_string(),
+                    code: ErrorCode::NotFound,
+                }),
+            }
+        },
+
+        ApiRequest::GetEvents { since_seq, limit, kinds } => {
+            let history = match &settings.events {
+                Some(dispatcher) => dispatcher.history(),
+                None => return Ok(ApiResponse::Error {
+                    message: "Event history is not enabled".to_string(),
+                    code: ErrorCode::Internal,
+                }),
+            };
+
+            let page = history.query(since_seq, limit.min(events::MAX_EVENTS_PER_QUERY), &kinds);
+            Ok(ApiResponse::Events { events: page.events, gap: page.gap, latest_seq: page.latest_seq })
+        },
+
+        ApiRequest::GeoLookup { ip } => {
+            let Ok(addr) = ip.parse() else {
+                return Ok(ApiResponse::InvalidArgument {
+                    field: "ip".to_string(),
+                    message: format!("Invalid IP address: {}", ip),
+                });
+            };
+            let Some(resolver) = &settings.geo else {
+                return Ok(ApiResponse::Error {
+                    message: "GeoIP lookups are not enabled (geoip.mmdb_path)".to_string(),
+                    code: ErrorCode::Internal,
+                });
+            };
+
+            Ok(ApiResponse::Geo { geo: resolver.lookup(addr), ip })
+        },
+        ApiRequest::SelfTest { interface } => {
+            {
+                let map_manager = map_manager.lock()
+                    .map_err(|_| anyhow!("Failed to lock map_manager"))?;
+                if let Err(e) = selftest::check_interface(&map_manager, &interface) {
+                    return Ok(ApiResponse::InvalidArgument { field: e.field, message: e.message });
+                }
+            }
+
+            let now = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map_err(|_| anyhow!("Failed to get system time"))?
+                .as_secs();
+
+            // 표식 튜플의 임시 규칙 (레이블은 네임스페이스 안에서만 유일)
+            let marker = selftest::Marker::generate();
+            let mut rule = match ruleset::build_rule(marker.spec(), Some(creator.clone()), now, netif::resolve_redirect_target) {
+                Ok(rule) => rule,
+                Err(e) => return Ok(ApiResponse::InvalidArgument { field: e.field, message: e.message }),
+            };
+            rule.label = utils::qualify_label(namespace, &rule.label);
+
+            let mut datapath = selftest::LiveDatapath::new(&map_manager);
+            let report = selftest::SelfTest::new(&mut datapath, selftest::Plan::new(&interface, marker, rule))
+                .run()
+                .await;
+
+            Ok(ApiResponse::SelfTest { report })
+        },
+        ApiRequest::RunBenchmark { interface, peer: link_peer, duration, pkt_size, rules, modes, confirm } => {
+            // veth 생성, XDP 연결, 트래픽 전송으로 호스트를 바꾸므로 명시적인 동의가 필요
+            if !confirm {
+                return Ok(ApiResponse::InvalidArgument {
+                    field: "confirm".to_string(),
+                    message: "Benchmark perturbs the host and requires confirmation".to_string(),
+                });
+            }
+            if let Some(privileges) = &settings.privileges {
+                if let Err(e) = privileges.require("run_benchmark", privdrop::BENCH_CAPABILITIES) {
+                    return Ok(ApiResponse::from(e));
+                }
+            }
+            let checked = bench::validate(duration, pkt_size, rules, &modes)
+                .and_then(|modes| bench::endpoints(interface, link_peer).map(|endpoints| (modes, endpoints)));
+            let (modes, (interface, link_peer, create_pair)) = match checked {
+                Ok(checked) => checked,
+                Err(e) => return Ok(ApiResponse::InvalidArgument { field: e.field, message: e.message }),
+            };
+            let Some(obj_path) = settings.bpf_obj.clone() else {
+                return Ok(ApiResponse::Error {
+                    message: "BPF object path is not configured".to_string(),
+                    code: ErrorCode::Internal,
+                });
+            };
+
+            // 운영 중인 연결을 벤치마크가 분리하지 않도록 이미 연결된 인터페이스는 거부
+            {
+                let map_manager = map_manager.lock()
+                    .map_err(|_| anyhow!("Failed to lock map_manager"))?;
+                if let Some(name) = [&interface, &link_peer].into_iter()
+                    .find(|name| map_manager.list_interfaces().iter().any(|info| &info.name == *name))
+                {
+                    return Ok(ApiResponse::InvalidArgument {
+                        field: "interface".to_string(),
+                        message: format!("Interface {} is attached; detach it before benchmarking", name),
+                    });
+                }
+            }
+
+            let now = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map_err(|_| anyhow!("Failed to get system time"))?
+                .as_secs();
+            let expire = bench::rule_expire(duration, modes.len());
+            let rules = match bench::synthetic_rules(rules, namespace, &creator, now, expire) {
+                Ok(rules) => rules,
+                Err(e) => return Ok(ApiResponse::InvalidArgument { field: e.field, message: e.message }),
+            };
+
+            let plan = bench::Plan {
+                interface,
+                peer: link_peer,
+                create_pair,
+                rules,
+                modes,
+                duration_secs: duration,
+                pkt_size,
+                settle: bench::SETTLE_WAIT,
+            };
+            let mut testbed = bench::LiveTestbed::new(&map_manager, obj_path);
+            let report = bench::Benchmark::new(&mut testbed, plan).run(progress).await;
+
+            Ok(ApiResponse::Benchmark { report })
+        },
+    }
+}
+
+/// 국가의 프리픽스 집합을 블로킹 스레드에서 생성 (데이터베이스 순회, 파일 읽기)
+async fn derive_country_set(settings: &ServerSettings, code: &str, source: String) -> Result<Result<PrefixSet>> {
+    let geo = settings.geo.clone();
+    let code = code.to_string();
+    tokio::task::spawn_blocking(move || country::derive_set(&code, &source, geo.as_deref()))
+        .await
+        .context("Country prefix set task failed")
+}
+
+/// LPM 규칙 맵 크기 (로드한 BPF 오브젝트 기준)
+fn lpm_map_capacity(settings: &ServerSettings) -> usize {
+    country::map_capacity(settings.bpf_info.as_ref().map(|(_, maps, _)| maps.as_slice()))
+}
+
+/// 소스 호스트 이름 검증 및 해석
+async fn resolve_src_host(settings: &ServerSettings, name: &str) -> Result<HostEndpoint> {
+    dns::validate_hostname(name)?;
+    let resolver = settings.resolver.as_ref()
+        .ok_or_else(|| anyhow!("DNS resolution is disabled (dns.enabled)"))?;
+    
+    Ok(HostEndpoint::new(name, resolver.resolve(name).await?))
+}
+
+/// WASM 런타임이 꺼져 있을 때의 응답
+fn wasm_disabled() -> ApiResponse {
+    ApiResponse::Error {
+        message: "WASM runtime is not enabled".to_string(),
+        code: ErrorCode::Internal,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::MemoryMap;
+    use crate::config::DaemonConfig;
+    use crate::maps::FilterRule;
+    use swift_guard::api::RuleChangeKind;
+
+    /// 길이 접두 요청 전송
+    async fn send(stream: &mut UnixStream, request: ApiRequest) {
+        let frame = RequestFrame { token: None, namespace: None, request_id: None, max_response_len: None, request };
+        let bytes = serde_json::to_vec(&frame).unwrap();
+        stream.write_all(&(bytes.len() as u32).to_be_bytes()).await.unwrap();
+        stream.write_all(&bytes).await.unwrap();
+    }
+
+    /// 응답 프레임 하나 수신
+    async fn recv(stream: &mut UnixStream) -> ApiResponse {
+        let mut len = [0u8; 4];
+        stream.read_exact(&mut len).await.unwrap();
+        let mut bytes = vec![0u8; u32::from_be_bytes(len) as usize];
+        stream.read_exact(&mut bytes).await.unwrap();
+        serde_json::from_slice::<ResponseFrame>(&bytes).unwrap().response
+    }
+
+    #[tokio::test]
+    async fn test_watcher_sees_changes_from_other_connections() {
+        // 연결 작업은 'static이어야 하므로 테스트 맵은 테스트가 끝날 때까지 유지
+        let filter_rules: &'static MemoryMap = Box::leak(Box::new(MemoryMap::new()));
+        let stats_map: &'static MemoryMap = Box::leak(Box::new(MemoryMap::new()));
+        let mut manager = MapManager::with_backends(MapBackends {
+            filter_rules: Some(filter_rules),
+            ..Default::default()
+        });
+        manager.add_rule(FilterRule {
+            src_ip: Some((0x0A000001, 32)),
+            protocol: 6,
+            action: 2,
+            label: "web".to_string(),
+            ..Default::default()
+        }).unwrap();
+        #[allow(clippy::arc_with_non_send_sync)]
+        let map_manager = Arc::new(Mutex::new(manager));
+        #[allow(clippy::arc_with_non_send_sync)]
+        let telemetry = Arc::new(TelemetryCollector::from_maps(stats_map, None, None, &DaemonConfig::default()));
+
+        let socket = std::env::temp_dir().join(format!("swift-guard-server-{}.sock", std::process::id()));
+        let server = Rc::new(ApiServer::new("127.0.0.1:0", map_manager, telemetry)
+            .unwrap()
+            .with_unix_socket(socket.clone()));
+
+        let script = async {
+            // 서버가 소켓을 바인드할 때까지 재시도
+            let connect = || async {
+                loop {
+                    match UnixStream::connect(&socket).await {
+                        Ok(stream) => return stream,
+                        Err(_) => tokio::time::sleep(Duration::from_millis(10)).await,
+                    }
+                }
+            };
+
+            let mut watcher = connect().await;
+            send(&mut watcher, ApiRequest::WatchRules { all_namespaces: false }).await;
+            assert!(matches!(recv(&mut watcher).await, ApiResponse::Rules { total_count: 1, .. }));
+
+            // 구독이 끝나지 않아도 다른 연결의 요청은 처리됨
+            let mut client = connect().await;
+            send(&mut client, ApiRequest::DeleteRule { label: "web".to_string() }).await;
+            assert!(matches!(recv(&mut client).await, ApiResponse::Success { .. }));
+
+            match recv(&mut watcher).await {
+                ApiResponse::RuleChanged { kind, rule, .. } => {
+                    assert_eq!(kind, RuleChangeKind::Deleted);
+                    assert_eq!(rule.label, "web");
+                },
+                other => panic!("unexpected frame: {:?}", other),
+            }
+        };
+
+        tokio::select! {
+            result = Rc::clone(&server).run() => panic!("server stopped: {:?}", result),
+            result = tokio::time::timeout(Duration::from_secs(10), script) => result.expect("watcher blocked the server"),
+        }
+        let _ = std::fs::remove_file(&socket);
     }
 }
@@ -0,0 +1,359 @@
+//! 통계 로그 형식 모듈
+//! `telemetry.stats_log_format` 템플릿을 구성 로드 시 검사하고 수집 주기마다 한 줄로 렌더링
+//!
+//! 템플릿은 일반 문자와 `{키}` 또는 `{키:.N}`(소수점 아래 N자리) 자리 표시자로 이루어진다.
+//! `{{`와 `}}`는 중괄호 문자 자체다. 알 수 없는 키, 닫히지 않은 중괄호, 정수 키의 자릿수
+//! 지정은 구성 오류이므로 실행 중에는 렌더링이 실패하지 않는다.
+
+use std::fmt::Write;
+
+use serde::{Deserialize, Serialize};
+
+use swift_guard::api::RuleInfo;
+use swift_guard::error::InvalidArgument;
+use swift_guard::types::ActionType;
+
+/// 기본 통계 로그 형식
+pub const DEFAULT_STATS_LOG_FORMAT: &str = "packets={packets} bytes={bytes} pps={pps} mbps={mbps:.2} dropped={dropped}";
+
+/// 구성 오류에 표시할 필드 이름
+const FIELD: &str = "telemetry.stats_log_format";
+
+/// 템플릿에 쓸 수 있는 통계 키
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum StatsKey {
+    Packets,
+    Bytes,
+    Pps,
+    Mbps,
+    SynPps,
+    Tcp,
+    Udp,
+    Icmp,
+    Other,
+    Aborted,
+    Dropped,
+    Passed,
+    Redirected,
+    RedirectFailures,
+}
+
+impl StatsKey {
+    const ALL: [StatsKey; 14] = [
+        Self::Packets, Self::Bytes, Self::Pps, Self::Mbps, Self::SynPps,
+        Self::Tcp, Self::Udp, Self::Icmp, Self::Other, Self::Aborted,
+        Self::Dropped, Self::Passed, Self::Redirected, Self::RedirectFailures,
+    ];
+
+    /// 템플릿에서 쓰는 이름
+    fn name(self) -> &'static str {
+        match self {
+            Self::Packets => "packets",
+            Self::Bytes => "bytes",
+            Self::Pps => "pps",
+            Self::Mbps => "mbps",
+            Self::SynPps => "syn_pps",
+            Self::Tcp => "tcp",
+            Self::Udp => "udp",
+            Self::Icmp => "icmp",
+            Self::Other => "other",
+            Self::Aborted => "aborted",
+            Self::Dropped => "dropped",
+            Self::Passed => "passed",
+            Self::Redirected => "redirected",
+            Self::RedirectFailures => "redirect_failures",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|key| key.name() == name)
+    }
+
+    /// 소수 값인지 (자릿수 지정 가능)
+    fn is_fractional(self) -> bool {
+        matches!(self, Self::Mbps)
+    }
+}
+
+/// 규칙 액션별 누적 패킷 수 (규칙 카운터 합계)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ActionTotals {
+    /// drop 규칙이 매치한 패킷 수
+    pub dropped: u64,
+    /// pass 규칙이 매치한 패킷 수
+    pub passed: u64,
+    /// 리디렉션 대상으로 넘긴 패킷 수
+    pub redirected: u64,
+    /// 리디렉션 대상으로 넘기지 못한 패킷 수
+    pub redirect_failures: u64,
+}
+
+impl ActionTotals {
+    /// 통계를 포함한 규칙 목록에서 합산
+    pub fn from_rules(rules: &[RuleInfo]) -> Self {
+        let mut totals = Self::default();
+        for rule in rules {
+            match ActionType::from_str(&rule.action) {
+                Some(ActionType::Drop) => totals.dropped += rule.stats.packets,
+                Some(ActionType::Pass) => totals.passed += rule.stats.packets,
+                _ => {},
+            }
+            totals.redirected += rule.stats.redirected_packets;
+            totals.redirect_failures += rule.stats.redirect_failures;
+        }
+        totals
+    }
+}
+
+/// 통계 로그 한 줄에 쓰는 값
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct StatsLine {
+    pub packets: u64,
+    pub bytes: u64,
+    pub pps: u64,
+    pub mbps: f64,
+    pub syn_pps: u64,
+    pub tcp: u64,
+    pub udp: u64,
+    pub icmp: u64,
+    pub other: u64,
+    /// XDP_ABORTED로 끝난 누적 패킷 수
+    pub aborted: u64,
+    pub actions: ActionTotals,
+}
+
+/// 자리 표시자 값
+enum Value {
+    Count(u64),
+    Fraction(f64),
+}
+
+impl StatsLine {
+    fn value(&self, key: StatsKey) -> Value {
+        match key {
+            StatsKey::Packets => Value::Count(self.packets),
+            StatsKey::Bytes => Value::Count(self.bytes),
+            StatsKey::Pps => Value::Count(self.pps),
+            StatsKey::Mbps => Value::Fraction(self.mbps),
+            StatsKey::SynPps => Value::Count(self.syn_pps),
+            StatsKey::Tcp => Value::Count(self.tcp),
+            StatsKey::Udp => Value::Count(self.udp),
+            StatsKey::Icmp => Value::Count(self.icmp),
+            StatsKey::Other => Value::Count(self.other),
+            StatsKey::Aborted => Value::Count(self.aborted),
+            StatsKey::Dropped => Value::Count(self.actions.dropped),
+            StatsKey::Passed => Value::Count(self.actions.passed),
+            StatsKey::Redirected => Value::Count(self.actions.redirected),
+            StatsKey::RedirectFailures => Value::Count(self.actions.redirect_failures),
+        }
+    }
+}
+
+/// 템플릿 조각
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Text(String),
+    Field { key: StatsKey, precision: Option<usize> },
+}
+
+/// 검사를 마친 통계 로그 형식
+///
+/// 구성 파일에는 템플릿 문자열로 읽고 쓴다.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct StatsLogFormat {
+    template: String,
+    segments: Vec<Segment>,
+}
+
+impl StatsLogFormat {
+    /// 템플릿 파싱
+    pub fn parse(template: &str) -> Result<Self, InvalidArgument> {
+        let mut segments = Vec::new();
+        let mut text = String::new();
+        let mut chars = template.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    text.push('{');
+                },
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    text.push('}');
+                },
+                '{' => {
+                    let mut spec = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => spec.push(c),
+                            None => return Err(InvalidArgument::new(FIELD, format!("unclosed '{{' in \"{}\"", template))),
+                        }
+                    }
+                    if !text.is_empty() {
+                        segments.push(Segment::Text(std::mem::take(&mut text)));
+                    }
+                    segments.push(parse_field(&spec)?);
+                },
+                '}' => return Err(InvalidArgument::new(FIELD, format!("unmatched '}}' in \"{}\" (use '}}}}')", template))),
+                c => text.push(c),
+            }
+        }
+        if !text.is_empty() {
+            segments.push(Segment::Text(text));
+        }
+
+        Ok(Self { template: template.to_string(), segments })
+    }
+
+    /// 템플릿 원문
+    pub fn template(&self) -> &str {
+        &self.template
+    }
+
+    /// 값을 채운 로그 줄
+    pub fn render(&self, line: &StatsLine) -> String {
+        let mut out = String::with_capacity(self.template.len() + 32);
+        for segment in &self.segments {
+            // String에 쓰기는 실패하지 않음
+            let _ = match segment {
+                Segment::Text(text) => out.write_str(text),
+                Segment::Field { key, precision } => match (line.value(*key), precision) {
+                    (Value::Fraction(value), Some(precision)) => write!(out, "{:.*}", precision, value),
+                    (Value::Fraction(value), None) => write!(out, "{}", value),
+                    (Value::Count(value), _) => write!(out, "{}", value),
+                },
+            };
+        }
+        out
+    }
+}
+
+/// 자리 표시자 안쪽(`키` 또는 `키:.N`) 파싱
+fn parse_field(spec: &str) -> Result<Segment, InvalidArgument> {
+    let (name, format) = match spec.split_once(':') {
+        Some((name, format)) => (name.trim(), Some(format)),
+        None => (spec.trim(), None),
+    };
+
+    let key = StatsKey::from_name(name).ok_or_else(|| {
+        let known: Vec<&str> = StatsKey::ALL.iter().map(|key| key.name()).collect();
+        InvalidArgument::new(FIELD, format!("unknown key '{}' (known keys: {})", name, known.join(", ")))
+    })?;
+
+    let precision = match format {
+        None => None,
+        Some(format) => {
+            let precision = format.strip_prefix('.')
+                .and_then(|digits| digits.parse::<usize>().ok())
+                .filter(|precision| *precision <= 9)
+                .ok_or_else(|| InvalidArgument::new(FIELD,
+                    format!("invalid format '{}' for '{}' (expected '.N' with N from 0 to 9)", format, name)))?;
+            if !key.is_fractional() {
+                return Err(InvalidArgument::new(FIELD, format!("'{}' is an integer and takes no precision", name)));
+            }
+            Some(precision)
+        },
+    };
+
+    Ok(Segment::Field { key, precision })
+}
+
+impl Default for StatsLogFormat {
+    fn default() -> Self {
+        Self::parse(DEFAULT_STATS_LOG_FORMAT).expect("default stats log format is valid")
+    }
+}
+
+impl TryFrom<String> for StatsLogFormat {
+    type Error = InvalidArgument;
+
+    fn try_from(template: String) -> Result<Self, Self::Error> {
+        Self::parse(&template)
+    }
+}
+
+impl From<StatsLogFormat> for String {
+    fn from(format: StatsLogFormat) -> Self {
+        format.template
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use swift_guard::api::RuleStats;
+
+    fn line() -> StatsLine {
+        StatsLine {
+            packets: 1_000,
+            bytes: 64_000,
+            pps: 120,
+            mbps: 1.23456,
+            syn_pps: 7,
+            tcp: 900,
+            udp: 90,
+            icmp: 10,
+            other: 0,
+            aborted: 2,
+            actions: ActionTotals { dropped: 42, passed: 3, redirected: 5, redirect_failures: 1 },
+        }
+    }
+
+    #[test]
+    fn test_render() {
+        let format = StatsLogFormat::parse("pps={pps} mbps={mbps:.2} drops={dropped}").unwrap();
+        assert_eq!(format.render(&line()), "pps=120 mbps=1.23 drops=42");
+
+        let format = StatsLogFormat::parse("{{tcp}}={tcp} {mbps} {mbps:.0}/{ syn_pps } redir={redirected}/{redirect_failures}").unwrap();
+        assert_eq!(format.render(&line()), "{tcp}=900 1.23456 1/7 redir=5/1");
+
+        let format = StatsLogFormat::parse("aborted={aborted}").unwrap();
+        assert_eq!(format.render(&line()), "aborted=2");
+
+        assert_eq!(StatsLogFormat::default().render(&line()), "packets=1000 bytes=64000 pps=120 mbps=1.23 dropped=42");
+        assert_eq!(StatsLogFormat::parse("").unwrap().render(&line()), "");
+    }
+
+    #[test]
+    fn test_invalid_templates() {
+        let error = StatsLogFormat::parse("pps={pps} x={nope}").unwrap_err();
+        assert_eq!(error.field, "telemetry.stats_log_format");
+        assert!(error.message.starts_with("unknown key 'nope' (known keys: packets, bytes"), "{}", error.message);
+
+        for template in ["{pps", "pps}", "{mbps:2}", "{mbps:.x}", "{mbps:.12}", "{pps:.2}", "{}"] {
+            assert!(StatsLogFormat::parse(template).is_err(), "{} should be rejected", template);
+        }
+    }
+
+    #[test]
+    fn test_config_round_trip() {
+        #[derive(Debug, Deserialize, Serialize)]
+        struct Telemetry {
+            stats_log_format: StatsLogFormat,
+        }
+
+        let config: Telemetry = serde_yaml::from_str("stats_log_format: \"pps={pps} drops={dropped}\"\n").unwrap();
+        assert_eq!(config.stats_log_format.template(), "pps={pps} drops={dropped}");
+        assert_eq!(serde_yaml::to_string(&config).unwrap(), "stats_log_format: pps={pps} drops={dropped}\n");
+
+        // 알 수 없는 키는 구성 로드 오류
+        let error = serde_yaml::from_str::<Telemetry>("stats_log_format: \"{drops}\"\n").unwrap_err();
+        assert!(error.to_string().contains("unknown key 'drops'"), "{}", error);
+    }
+
+    #[test]
+    fn test_action_totals() {
+        let rule = |action: &str, packets: u64, redirected_packets: u64| RuleInfo {
+            label: action.to_string(),
+            action: action.to_string(),
+            stats: RuleStats { packets, redirected_packets, redirect_failures: 1, ..Default::default() },
+            ..RuleInfo::default()
+        };
+        let rules = vec![rule("drop", 10, 0), rule("drop", 5, 0), rule("pass", 3, 0), rule("redirect", 8, 7), rule("count", 4, 0)];
+        assert_eq!(ActionTotals::from_rules(&rules),
+            ActionTotals { dropped: 15, passed: 3, redirected: 7, redirect_failures: 5 });
+    }
+}
@@ -0,0 +1,856 @@
+//! Syslog 모듈
+//! 보안 이벤트를 RFC 5424 형식으로 syslog에 전달
+
+use anyhow::{anyhow, Context, Result};
+use log::{info, warn};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use tokio::io::AsyncWriteExt;
+use tokio::net::{TcpStream, UdpSocket, UnixDatagram};
+use tokio::sync::mpsc;
+
+use crate::config::SyslogConfig;
+use crate::logthrottle;
+use crate::mitigation::MitigationEvent;
+use swift_guard::api::{ApiRequest, ApiResponse, RuleHitEvent, RuleInfo};
+use swift_guard::utils;
+
+/// 구조화 데이터 ID (32473은 문서용으로 예약된 IANA 기업 번호)
+const SD_ID: &str = "swiftguard@32473";
+
+/// APP-NAME 필드
+const APP_NAME: &str = "swift-guard";
+
+/// 전송 대기열 크기 (가득 차면 새 이벤트를 버림)
+const QUEUE_CAPACITY: usize = 1024;
+
+/// 재연결 대기 시간 상한
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// syslog 심각도 (RFC 5424)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Warning = 4,
+    Notice = 5,
+    Informational = 6,
+}
+
+/// 보안 관련 이벤트
+#[derive(Debug, Clone, PartialEq)]
+pub enum SecurityEvent {
+    /// 규칙 매치율이 임계값 초과
+    RuleMatch {
+        label: String,
+        action: String,
+        src_ip: Option<String>,
+        packets_per_sec: u64,
+    },
+    /// XDP 프로그램이 보고한 개별 규칙 적중
+    RuleHit {
+        label: String,
+        action: String,
+        protocol: String,
+        src: String,
+        dst: String,
+    },
+    /// WASM 모듈이 패킷 차단
+    WasmBlock {
+        module: String,
+        reason: String,
+        src_ip: Option<String>,
+    },
+    /// 자동 완화 규칙 설치
+    Mitigation {
+        label: String,
+        action: String,
+        src_ip: String,
+        syn_pps: u64,
+        dry_run: bool,
+    },
+    /// 상태를 변경하는 API 요청
+    Audit {
+        actor: String,
+        operation: String,
+        target: String,
+        /// 규칙 요청의 네임스페이스
+        namespace: Option<String>,
+        /// 요청 ID (데몬 로그의 `request` 스팬과 같은 값)
+        request_id: Option<String>,
+        outcome: String,
+        success: bool,
+    },
+}
+
+impl SecurityEvent {
+    /// MSGID 필드
+    pub fn msg_id(&self) -> &'static str {
+        match self {
+            SecurityEvent::RuleMatch { .. } => "RULE_MATCH",
+            SecurityEvent::RuleHit { .. } => "RULE_HIT",
+            SecurityEvent::WasmBlock { .. } => "WASM_BLOCK",
+            SecurityEvent::Mitigation { .. } => "MITIGATION",
+            SecurityEvent::Audit { .. } => "AUDIT",
+        }
+    }
+
+    /// 이벤트 심각도
+    pub fn severity(&self) -> Severity {
+        match self {
+            SecurityEvent::RuleMatch { .. } => Severity::Notice,
+            SecurityEvent::RuleHit { .. } => Severity::Informational,
+            SecurityEvent::WasmBlock { .. } | SecurityEvent::Mitigation { .. } => Severity::Warning,
+            SecurityEvent::Audit { success: true, .. } => Severity::Informational,
+            SecurityEvent::Audit { success: false, .. } => Severity::Notice,
+        }
+    }
+
+    /// 구조화 데이터 매개변수
+    fn params(&self) -> Vec<(&'static str, String)> {
+        let mut params = Vec::new();
+
+        match self {
+            SecurityEvent::RuleMatch { label, action, src_ip, packets_per_sec } => {
+                params.push(("label", label.clone()));
+                if let Some(src_ip) = src_ip {
+                    params.push(("src_ip", src_ip.clone()));
+                }
+                params.push(("action", action.clone()));
+                params.push(("pps", packets_per_sec.to_string()));
+            },
+            SecurityEvent::RuleHit { label, action, protocol, src, dst } => {
+                params.push(("label", label.clone()));
+                params.push(("src", src.clone()));
+                params.push(("dst", dst.clone()));
+                params.push(("protocol", protocol.clone()));
+                params.push(("action", action.clone()));
+            },
+            SecurityEvent::WasmBlock { module, reason, src_ip } => {
+                params.push(("module", module.clone()));
+                if let Some(src_ip) = src_ip {
+                    params.push(("src_ip", src_ip.clone()));
+                }
+                params.push(("action", "drop".to_string()));
+                params.push(("reason", reason.clone()));
+            },
+            SecurityEvent::Mitigation { label, action, src_ip, syn_pps, dry_run } => {
+                params.push(("label", label.clone()));
+                params.push(("src_ip", src_ip.clone()));
+                params.push(("action", action.clone()));
+                params.push(("syn_pps", syn_pps.to_string()));
+                params.push(("dry_run", dry_run.to_string()));
+            },
+            SecurityEvent::Audit { actor, operation, target, namespace, request_id, outcome, .. } => {
+                params.push(("actor", actor.clone()));
+                params.push(("operation", operation.clone()));
+                params.push(("target", target.clone()));
+                if let Some(namespace) = namespace {
+                    params.push(("namespace", namespace.clone()));
+                }
+                if let Some(request_id) = request_id {
+                    params.push(("request_id", request_id.clone()));
+                }
+                params.push(("outcome", outcome.clone()));
+            },
+        }
+
+        params
+    }
+
+    /// 사람이 읽는 메시지
+    fn message(&self) -> String {
+        match self {
+            SecurityEvent::RuleMatch { label, packets_per_sec, .. } => {
+                format!("Rule '{}' matched {} packets/s", label, packets_per_sec)
+            },
+            SecurityEvent::RuleHit { label, action, protocol, src, dst } => {
+                format!("Rule '{}' matched {} {} -> {} ({})", label, protocol, src, dst, action)
+            },
+            SecurityEvent::WasmBlock { module, reason, .. } => {
+                format!("WASM module '{}' blocked a packet: {}", module, reason)
+            },
+            SecurityEvent::Mitigation { label, src_ip, syn_pps, dry_run: false, .. } => {
+                format!("SYN flood from {} ({} SYN/s), installed {}", src_ip, syn_pps, label)
+            },
+            SecurityEvent::Mitigation { label, src_ip, syn_pps, dry_run: true, .. } => {
+                format!("[dry-run] SYN flood from {} ({} SYN/s), would install {}", src_ip, syn_pps, label)
+            },
+            SecurityEvent::Audit { actor, operation, target, outcome, .. } => {
+                format!("{} {} {}: {}", actor, operation, target, outcome)
+            },
+        }
+    }
+
+    /// 감사 이벤트 (`subject`는 `audit_subject`의 결과)
+    pub fn audit(subject: AuditSubject, actor: &str, response: &ApiResponse) -> Self {
+        let (outcome, success) = match response {
+            ApiResponse::Success { .. } => ("success".to_string(), true),
+            ApiResponse::RulesetApplied { summary } => (format!(
+                "{} added, {} updated, {} deleted, {} failed, {} not applied",
+                summary.added.len(), summary.updated.len(), summary.deleted.len(), summary.errors.len(),
+                summary.pending.len()),
+                summary.errors.is_empty() && summary.pending.is_empty()),
+            ApiResponse::MapsReconciled { summary } => (format!(
+                "{} orphaned entries deleted, {} missing entries reinstalled, {} failed",
+                summary.deleted_orphans, summary.reinstalled, summary.errors.len()),
+                summary.errors.is_empty()),
+            ApiResponse::SelfTest { report } => (report.summary(), report.passed),
+            ApiResponse::Benchmark { report } => (report.summary(), report.completed()),
+            ApiResponse::Error { message, .. } => (format!("failed: {}", message), false),
+            ApiResponse::InvalidArgument { field, message } => {
+                (format!("failed: invalid {}: {}", field, message), false)
+            },
+            ApiResponse::NotFound { resource, name } => {
+                (format!("failed: {} '{}' not found", resource, name), false)
+            },
+            ApiResponse::RateLimited { limit, .. } => {
+                (format!("failed: rate limited ({} requests/sec)", limit), false)
+            },
+            _ => ("failed: unexpected response".to_string(), false),
+        };
+
+        SecurityEvent::Audit {
+            actor: actor.to_string(),
+            operation: subject.operation.to_string(),
+            target: subject.target,
+            namespace: subject.namespace,
+            request_id: subject.request_id,
+            outcome,
+            success,
+        }
+    }
+}
+
+/// 감사 대상 요청의 작업과 대상
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditSubject {
+    pub operation: &'static str,
+    pub target: String,
+    /// 규칙 요청의 네임스페이스 (다른 요청은 None)
+    pub namespace: Option<String>,
+    /// 요청 ID
+    pub request_id: Option<String>,
+}
+
+/// 상태를 변경하는 요청의 감사 대상 (조회 요청과 드라이런은 None)
+///
+/// 요청은 처리 중에 소비되므로 처리 전에 호출한다. 규칙 요청의 네임스페이스는
+/// 요청 ID와 함께 요청 프레임에 있으므로 호출자가 채운다.
+pub fn audit_subject(request: &ApiRequest) -> Option<AuditSubject> {
+    let (operation, target) = match request {
+        ApiRequest::Attach { interface, .. } => ("attach", interface.clone()),
+        ApiRequest::Detach { interface } => ("detach", interface.clone()),
+        ApiRequest::AddRule(rule) => ("add_rule", rule.label.clone()),
+        ApiRequest::DeleteRule { label } => ("delete_rule", label.clone()),
+        ApiRequest::ApplyRuleset { dry_run: true, .. } => return None,
+        ApiRequest::ApplyRuleset { rules, .. } => ("apply_ruleset", format!("{} rules", rules.len())),
+        ApiRequest::ApplyPreset { name, .. } => ("apply_preset", name.clone()),
+        ApiRequest::RemovePreset { name } => ("remove_preset", name.clone()),
+        ApiRequest::BlockCountry { code, .. } => ("block_country", code.clone()),
+        ApiRequest::RefreshCountryBlocks { code } => ("refresh_country_blocks", code.clone().unwrap_or_else(|| "all".to_string())),
+        ApiRequest::UnblockCountry { code } => ("unblock_country", code.clone()),
+        ApiRequest::LoadWasmModule { name, .. } => ("load_wasm_module", name.clone()),
+        ApiRequest::UnloadWasmModule { name } => ("unload_wasm_module", name.clone()),
+        ApiRequest::ResumeWasmModule { name } => ("resume_wasm_module", name.clone()),
+        ApiRequest::SetWasmModuleState { name, .. } => ("set_wasm_module_state", name.clone()),
+        ApiRequest::ResetWasmModuleStats { name } => ("reset_wasm_module_stats", name.clone()),
+        ApiRequest::ClearWasmCache {} => ("clear_wasm_cache", "wasm-cache".to_string()),
+        ApiRequest::ClearDynamicBlocks { module } => ("clear_dynamic_blocks", module.clone().unwrap_or_else(|| "all".to_string())),
+        ApiRequest::RepairRules {} => ("repair_rules", "uninstalled rules".to_string()),
+        ApiRequest::ReconcileMaps { delete_orphans: false, reinstall_missing: false } => return None,
+        ApiRequest::ReconcileMaps { .. } => ("reconcile_maps", "kernel maps".to_string()),
+        ApiRequest::AddRedirectTarget { interface } => ("add_redirect_target", interface.clone()),
+        ApiRequest::RemoveRedirectTarget { interface } => ("remove_redirect_target", interface.clone()),
+        ApiRequest::SelfTest { interface } => ("self_test", interface.clone()),
+        ApiRequest::RunBenchmark { interface, .. } => {
+            ("run_benchmark", interface.clone().unwrap_or_else(|| "veth pair".to_string()))
+        },
+        ApiRequest::ListRules { .. }
+        | ApiRequest::WatchRules { .. }
+        | ApiRequest::GetRule { .. }
+        | ApiRequest::GetCounters { .. }
+        | ApiRequest::PlanChanges { .. }
+        | ApiRequest::ListPresets {}
+        | ApiRequest::GetStats {}
+        | ApiRequest::ListWasmModules {}
+        | ApiRequest::WasmModuleStats { .. }
+        | ApiRequest::GetWasmModuleLog { .. }
+        | ApiRequest::ListDynamicBlocks { .. }
+        | ApiRequest::ListConnections { .. }
+        | ApiRequest::ListCaptures {}
+        | ApiRequest::ListInterfaces {}
+        | ApiRequest::Ping {}
+        | ApiRequest::GetVersion {}
+        | ApiRequest::GetBpfInfo {}
+        | ApiRequest::ExplainConflicts {}
+        | ApiRequest::AuditMaps {}
+        | ApiRequest::RuleSpaceStats {}
+        | ApiRequest::ListRedirectTargets {}
+        | ApiRequest::GetEvents { .. }
+        | ApiRequest::GeoLookup { .. } => return None,
+    };
+
+    Some(AuditSubject { operation, target, namespace: None, request_id: None })
+}
+
+impl From<&MitigationEvent> for SecurityEvent {
+    fn from(event: &MitigationEvent) -> Self {
+        SecurityEvent::Mitigation {
+            label: event.label.clone(),
+            action: event.action.clone(),
+            src_ip: event.src_ip.clone(),
+            syn_pps: event.syn_pps,
+            dry_run: event.dry_run,
+        }
+    }
+}
+
+impl From<&RuleHitEvent> for SecurityEvent {
+    fn from(event: &RuleHitEvent) -> Self {
+        SecurityEvent::RuleHit {
+            label: event.label.clone(),
+            action: event.action.clone(),
+            protocol: event.protocol.clone(),
+            src: format!("{}:{}", event.src_ip, event.src_port),
+            dst: format!("{}:{}", event.dst_ip, event.dst_port),
+        }
+    }
+}
+
+/// facility 이름을 번호로 변환
+pub fn parse_facility(name: &str) -> Result<u8> {
+    let facility = match name.to_lowercase().as_str() {
+        "kern" => 0,
+        "user" => 1,
+        "mail" => 2,
+        "daemon" => 3,
+        "auth" => 4,
+        "syslog" => 5,
+        "lpr" => 6,
+        "news" => 7,
+        "uucp" => 8,
+        "cron" => 9,
+        "authpriv" => 10,
+        "ftp" => 11,
+        "local0" => 16,
+        "local1" => 17,
+        "local2" => 18,
+        "local3" => 19,
+        "local4" => 20,
+        "local5" => 21,
+        "local6" => 22,
+        "local7" => 23,
+        _ => return Err(anyhow!("Unknown syslog facility: {}", name)),
+    };
+
+    Ok(facility)
+}
+
+/// SD-PARAM 값 이스케이프 (", \, ])
+fn escape_param(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, '"' | '\\' | ']') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// 헤더 필드 정리 (출력 가능한 ASCII만, 비어 있으면 NILVALUE)
+fn header_field(value: &str, max_len: usize) -> String {
+    let field: String = value.chars()
+        .filter(|c| c.is_ascii_graphic())
+        .take(max_len)
+        .collect();
+
+    if field.is_empty() { "-".to_string() } else { field }
+}
+
+/// RFC 5424 메시지 생성
+pub fn format_message(event: &SecurityEvent, facility: u8, hostname: &str, timestamp: u64, pid: u32) -> String {
+    let pri = facility as u32 * 8 + event.severity() as u32;
+
+    let timestamp = chrono::DateTime::from_timestamp(timestamp as i64, 0)
+        .map(|t| t.format("%Y-%m-%dT%H:%M:%SZ").to_string())
+        .unwrap_or_else(|| "-".to_string());
+
+    let params: Vec<String> = event.params().iter()
+        .map(|(name, value)| format!("{}=\"{}\"", name, escape_param(value)))
+        .collect();
+
+    format!("<{}>1 {} {} {} {} {} [{} {}] {}",
+        pri, timestamp, header_field(hostname, 255), APP_NAME, pid, event.msg_id(),
+        SD_ID, params.join(" "), event.message())
+}
+
+/// syslog 전송 대상
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SyslogTarget {
+    /// 로컬 유닉스 소켓 (/dev/log)
+    Unix(PathBuf),
+    /// 원격 UDP (host:port)
+    Udp(String),
+    /// 원격 TCP (host:port, 옥텟 카운팅 프레이밍)
+    Tcp(String),
+}
+
+impl SyslogTarget {
+    /// 주소 파싱 (/path, udp://host:port, tcp://host:port)
+    pub fn parse(address: &str) -> Result<Self> {
+        if let Some(addr) = address.strip_prefix("udp://") {
+            Ok(SyslogTarget::Udp(with_default_port(addr)?))
+        } else if let Some(addr) = address.strip_prefix("tcp://") {
+            Ok(SyslogTarget::Tcp(with_default_port(addr)?))
+        } else if address.starts_with('/') {
+            Ok(SyslogTarget::Unix(PathBuf::from(address)))
+        } else {
+            Err(anyhow!("Unsupported syslog address (expected /path, udp://host:port or tcp://host:port): {}", address))
+        }
+    }
+}
+
+/// 포트가 없으면 514 사용
+fn with_default_port(addr: &str) -> Result<String> {
+    if addr.is_empty() {
+        return Err(anyhow!("Missing syslog host"));
+    }
+
+    match addr.rsplit_once(':') {
+        Some((host, port)) if !host.ends_with(':') && !port.contains(']') => {
+            port.parse::<u16>().map_err(|_| anyhow!("Invalid syslog port: {}", port))?;
+            Ok(addr.to_string())
+        },
+        _ => Ok(format!("{}:514", addr)),
+    }
+}
+
+/// 연결된 전송 소켓
+enum Connection {
+    Unix(UnixDatagram),
+    Udp(UdpSocket),
+    Tcp(TcpStream),
+}
+
+impl Connection {
+    async fn open(target: &SyslogTarget) -> Result<Self> {
+        match target {
+            SyslogTarget::Unix(path) => {
+                let socket = UnixDatagram::unbound()?;
+                socket.connect(path)
+                    .context(format!("Failed to connect to {}", path.display()))?;
+                Ok(Connection::Unix(socket))
+            },
+            SyslogTarget::Udp(addr) => {
+                let remote = tokio::net::lookup_host(addr.as_str()).await?
+                    .next()
+                    .ok_or_else(|| anyhow!("Failed to resolve {}", addr))?;
+                let local = if remote.is_ipv4() { "0.0.0.0:0" } else { "[::]:0" };
+                let socket = UdpSocket::bind(local).await?;
+                socket.connect(remote).await
+                    .context(format!("Failed to connect to {}", addr))?;
+                Ok(Connection::Udp(socket))
+            },
+            SyslogTarget::Tcp(addr) => {
+                let stream = TcpStream::connect(addr.as_str()).await
+                    .context(format!("Failed to connect to {}", addr))?;
+                Ok(Connection::Tcp(stream))
+            },
+        }
+    }
+
+    async fn send(&mut self, message: &str) -> Result<()> {
+        match self {
+            Connection::Unix(socket) => { socket.send(message.as_bytes()).await?; },
+            Connection::Udp(socket) => { socket.send(message.as_bytes()).await?; },
+            Connection::Tcp(stream) => {
+                // RFC 6587 옥텟 카운팅
+                let frame = format!("{} {}", message.len(), message);
+                stream.write_all(frame.as_bytes()).await?;
+            },
+        }
+        Ok(())
+    }
+}
+
+/// syslog 전송 작업: 실패하면 연결을 다시 맺고, 재연결 대기 중 도착한 메시지는 버린다
+async fn deliver(target: SyslogTarget, mut messages: mpsc::Receiver<String>) {
+    let mut connection: Option<Connection> = None;
+    let mut backoff = Duration::from_secs(1);
+    let mut retry_at: Option<Instant> = None;
+    let mut dropped = 0u64;
+
+    while let Some(message) = messages.recv().await {
+        if connection.is_none() {
+            if retry_at.is_some_and(|at| Instant::now() < at) {
+                dropped += 1;
+                continue;
+            }
+
+            match Connection::open(&target).await {
+                Ok(opened) => {
+                    if dropped > 0 {
+                        info!("Syslog connection to {:?} restored ({} events dropped)", target, dropped);
+                    }
+                    connection = Some(opened);
+                    backoff = Duration::from_secs(1);
+                    retry_at = None;
+                    dropped = 0;
+                },
+                Err(e) => {
+                    warn!("Syslog connection to {:?} failed, retrying in {:?}: {:#}", target, backoff, e);
+                    retry_at = Some(Instant::now() + backoff);
+                    backoff = (backoff * 2).min(MAX_BACKOFF);
+                    dropped += 1;
+                    continue;
+                }
+            }
+        }
+
+        if let Some(conn) = connection.as_mut() {
+            if let Err(e) = conn.send(&message).await {
+                warn!("Syslog delivery to {:?} failed: {}", target, e);
+                connection = None;
+                dropped += 1;
+            }
+        }
+    }
+}
+
+/// 보안 이벤트 syslog 싱크
+///
+/// 포맷은 호출자에서, 전송은 백그라운드 작업에서 처리한다. 대기열이 가득 차면
+/// 이벤트를 버리므로 `send`는 패킷 경로나 API 핸들러를 막지 않는다.
+#[derive(Debug, Clone)]
+pub struct SyslogSink {
+    tx: mpsc::Sender<String>,
+    facility: u8,
+    hostname: String,
+}
+
+impl SyslogSink {
+    /// 구성에 따라 싱크 시작 (비활성화면 None)
+    pub fn start(config: &SyslogConfig) -> Result<Option<Self>> {
+        if !config.enabled {
+            return Ok(None);
+        }
+
+        let facility = parse_facility(&config.facility)?;
+        let target = SyslogTarget::parse(&config.address)?;
+
+        let hostname = nix::unistd::gethostname()
+            .ok()
+            .and_then(|name| name.into_string().ok())
+            .unwrap_or_default();
+
+        let (tx, rx) = mpsc::channel(QUEUE_CAPACITY);
+        info!("Sending security events to syslog at {:?}", target);
+        tokio::spawn(deliver(target, rx));
+
+        Ok(Some(Self { tx, facility, hostname }))
+    }
+
+    /// 이벤트 전송 (대기하지 않음)
+    pub fn send(&self, event: &SecurityEvent) {
+        let message = format_message(event, self.facility, &self.hostname,
+            utils::current_time_secs(), std::process::id());
+
+        if self.tx.try_send(message).is_err() {
+            throttled_warn!("syslog:queue", logthrottle::HOT_PATH_INTERVAL,
+                "Syslog queue full or closed, dropping {} event", event.msg_id());
+        }
+    }
+}
+
+/// 규칙별 매치율 감시 (수집 간격마다 누적 패킷 카운터 입력)
+#[derive(Debug)]
+pub struct RuleMatchMonitor {
+    threshold_pps: u64,
+    prev_packets: HashMap<String, u64>,
+}
+
+impl RuleMatchMonitor {
+    /// 새로운 감시기 생성 (임계값 0이면 보고하지 않음)
+    pub fn new(threshold_pps: u64) -> Self {
+        Self {
+            threshold_pps,
+            prev_packets: HashMap::new(),
+        }
+    }
+
+    /// 이전 관측 이후 초당 매치 수가 임계값 이상인 규칙 보고
+    pub fn observe(&mut self, rules: &[RuleInfo], elapsed_secs: f64) -> Vec<SecurityEvent> {
+        let mut events = Vec::new();
+        let mut current = HashMap::with_capacity(rules.len());
+
+        for rule in rules {
+            let key = rule.key();
+            let packets = rule.stats.packets;
+            current.insert(key.clone(), packets);
+
+            let prev = match self.prev_packets.get(&key) {
+                Some(prev) => *prev,
+                None => continue,
+            };
+
+            if self.threshold_pps == 0 || elapsed_secs <= 0.0 {
+                continue;
+            }
+
+            let pps = (packets.saturating_sub(prev) as f64 / elapsed_secs) as u64;
+            if pps >= self.threshold_pps {
+                events.push(SecurityEvent::RuleMatch {
+                    label: key,
+                    action: rule.action.clone(),
+                    src_ip: rule.src_ip.clone(),
+                    packets_per_sec: pps,
+                });
+            }
+        }
+
+        // 삭제된 규칙은 잊음
+        self.prev_packets = current;
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use swift_guard::api::{ApplySummary, BenchReport, ReconcileSummary, RuleStats, SelfTestReport};
+    use swift_guard::error::ErrorCode;
+
+    fn mitigation_event() -> SecurityEvent {
+        SecurityEvent::Mitigation {
+            label: "auto-syn-192.168.1.10".to_string(),
+            action: "drop".to_string(),
+            src_ip: "192.168.1.10".to_string(),
+            syn_pps: 5000,
+            dry_run: false,
+        }
+    }
+
+    #[test]
+    fn test_format_message() {
+        let message = format_message(&mitigation_event(), 16, "guard-01", 1700000000, 42);
+        assert_eq!(message, "<132>1 2023-11-14T22:13:20Z guard-01 swift-guard 42 MITIGATION \
+            [swiftguard@32473 label=\"auto-syn-192.168.1.10\" src_ip=\"192.168.1.10\" action=\"drop\" \
+            syn_pps=\"5000\" dry_run=\"false\"] SYN flood from 192.168.1.10 (5000 SYN/s), installed auto-syn-192.168.1.10");
+
+        // 빈 호스트 이름은 NILVALUE, 매개변수 값은 이스케이프
+        let event = SecurityEvent::WasmBlock {
+            module: "http-inspector".to_string(),
+            reason: "path \"/admin]\" \\ denied".to_string(),
+            src_ip: None,
+        };
+        let message = format_message(&event, 3, "", 0, 1);
+        assert!(message.starts_with("<28>1 1970-01-01T00:00:00Z - swift-guard 1 WASM_BLOCK "));
+        assert!(message.contains("reason=\"path \\\"/admin\\]\\\" \\\\ denied\"]"));
+        assert!(!message.contains("src_ip"));
+    }
+
+    #[test]
+    fn test_rule_hit_event() {
+        let hit = RuleHitEvent {
+            label: "block-web".to_string(),
+            rule_key: "192.168.1.100/32".to_string(),
+            action: "drop".to_string(),
+            protocol: "tcp".to_string(),
+            src_ip: "192.168.1.100".to_string(),
+            src_port: 40000,
+            dst_ip: "10.0.0.1".to_string(),
+            dst_port: 80,
+            timestamp_ns: 0,
+            geo: None,
+        };
+        let event = SecurityEvent::from(&hit);
+        assert_eq!(event.severity(), Severity::Informational);
+
+        let message = format_message(&event, 16, "guard-01", 1700000000, 42);
+        assert_eq!(message, "<134>1 2023-11-14T22:13:20Z guard-01 swift-guard 42 RULE_HIT \
+            [swiftguard@32473 label=\"block-web\" src=\"192.168.1.100:40000\" dst=\"10.0.0.1:80\" \
+            protocol=\"tcp\" action=\"drop\"] Rule 'block-web' matched tcp 192.168.1.100:40000 -> 10.0.0.1:80 (drop)");
+    }
+
+    #[test]
+    fn test_parse_facility() {
+        assert_eq!(parse_facility("daemon").unwrap(), 3);
+        assert_eq!(parse_facility("LOCAL7").unwrap(), 23);
+        assert!(parse_facility("local8").is_err());
+    }
+
+    #[test]
+    fn test_parse_target() {
+        assert_eq!(SyslogTarget::parse("/dev/log").unwrap(), SyslogTarget::Unix(PathBuf::from("/dev/log")));
+        assert_eq!(SyslogTarget::parse("udp://siem.local").unwrap(), SyslogTarget::Udp("siem.local:514".to_string()));
+        assert_eq!(SyslogTarget::parse("tcp://10.0.0.5:6514").unwrap(), SyslogTarget::Tcp("10.0.0.5:6514".to_string()));
+        assert!(SyslogTarget::parse("siem.local:514").is_err());
+        assert!(SyslogTarget::parse("udp://siem.local:abc").is_err());
+        assert!(SyslogTarget::parse("tcp://").is_err());
+    }
+
+    #[test]
+    fn test_audit_event() {
+        let subject = AuditSubject {
+            namespace: Some("tenant-a".to_string()),
+            request_id: Some("req-1".to_string()),
+            ..audit_subject(&ApiRequest::DeleteRule { label: "web".to_string() }).unwrap()
+        };
+        let response = ApiResponse::NotFound { resource: "rule".to_string(), name: "web".to_string() };
+        let event = SecurityEvent::audit(subject, "peer:10.0.0.1", &response);
+        assert_eq!(event, SecurityEvent::Audit {
+            actor: "peer:10.0.0.1".to_string(),
+            operation: "delete_rule".to_string(),
+            target: "web".to_string(),
+            namespace: Some("tenant-a".to_string()),
+            request_id: Some("req-1".to_string()),
+            outcome: "failed: rule 'web' not found".to_string(),
+            success: false,
+        });
+        assert_eq!(event.severity(), Severity::Notice);
+        assert!(event.params().contains(&("namespace", "tenant-a".to_string())));
+        assert!(event.params().contains(&("request_id", "req-1".to_string())));
+
+        let subject = audit_subject(&ApiRequest::ApplyRuleset { rules: Vec::new(), prune: true, dry_run: false, progress: false }).unwrap();
+        let response = ApiResponse::RulesetApplied {
+            summary: ApplySummary { deleted: vec!["old".to_string()], ..Default::default() },
+        };
+        let event = SecurityEvent::audit(subject, "peer:10.0.0.1", &response);
+        assert_eq!(event.severity(), Severity::Informational);
+
+        // 조회와 드라이런은 감사 대상 아님
+        assert!(audit_subject(&ApiRequest::GetStats {}).is_none());
+        assert!(audit_subject(&ApiRequest::ApplyRuleset { rules: Vec::new(), prune: true, dry_run: true, progress: false }).is_none());
+        assert!(audit_subject(&ApiRequest::ReconcileMaps { delete_orphans: false, reinstall_missing: false }).is_none());
+        let subject = audit_subject(&ApiRequest::ReconcileMaps { delete_orphans: true, reinstall_missing: false }).unwrap();
+        assert_eq!(subject.operation, "reconcile_maps");
+        let response = ApiResponse::MapsReconciled {
+            summary: ReconcileSummary { deleted_orphans: 2, ..Default::default() },
+        };
+        assert!(matches!(SecurityEvent::audit(subject, "peer:10.0.0.1", &response),
+            SecurityEvent::Audit { success: true, ref outcome, .. } if outcome.starts_with("2 orphaned entries deleted")));
+
+        let subject = audit_subject(&ApiRequest::RepairRules {}).unwrap();
+        assert_eq!(subject.operation, "repair_rules");
+
+        // 자가 진단은 임시 규칙을 설치하므로 감사 대상
+        let subject = audit_subject(&ApiRequest::SelfTest { interface: "lo".to_string() }).unwrap();
+        let response = ApiResponse::SelfTest {
+            report: SelfTestReport { interface: "lo".to_string(), packets_sent: 5, packets_observed: Some(3), ..Default::default() },
+        };
+        assert!(matches!(SecurityEvent::audit(subject, "peer:10.0.0.1", &response),
+            SecurityEvent::Audit { success: false, ref outcome, ref operation, .. }
+                if operation == "self_test" && outcome == "failed: 3/5 packets counted on lo"));
+
+        // 벤치마크는 veth와 규칙을 만들었다 지우므로 감사 대상
+        let request = ApiRequest::RunBenchmark {
+            interface: None, peer: None, duration: 10, pkt_size: 64, rules: 1000, modes: Vec::new(), confirm: true,
+        };
+        let subject = audit_subject(&request).unwrap();
+        assert_eq!((subject.operation, subject.target.as_str()), ("run_benchmark", "veth pair"));
+        let response = ApiResponse::Benchmark {
+            report: BenchReport { interface: "sgbench0".to_string(), aborted: true, ..Default::default() },
+        };
+        assert!(matches!(SecurityEvent::audit(subject, "peer:10.0.0.1", &response),
+            SecurityEvent::Audit { success: false, ref outcome, .. } if outcome.ends_with("(aborted)")));
+
+        let subject = audit_subject(&ApiRequest::Detach { interface: "eth0".to_string() }).unwrap();
+        let error = ApiResponse::Error { message: "boom".to_string(), code: ErrorCode::Internal };
+        assert!(matches!(SecurityEvent::audit(subject, "peer:10.0.0.1", &error),
+            SecurityEvent::Audit { success: false, .. }));
+    }
+
+    fn rule(label: &str, packets: u64) -> RuleInfo {
+        RuleInfo {
+            label: label.to_string(),
+            src_ip: Some("10.0.0.0/8".to_string()),
+            protocol: "tcp".to_string(),
+            stats: RuleStats { packets, ..RuleStats::default() },
+            ..RuleInfo::default()
+        }
+    }
+
+    #[test]
+    fn test_rule_match_monitor() {
+        let mut monitor = RuleMatchMonitor::new(100);
+
+        // 첫 관측은 기준값만 기록
+        assert!(monitor.observe(&[rule("busy", 1000), rule("quiet", 0)], 10.0).is_empty());
+
+        let events = monitor.observe(&[rule("busy", 3000), rule("quiet", 50)], 10.0);
+        assert_eq!(events, vec![SecurityEvent::RuleMatch {
+            label: "busy".to_string(),
+            action: "drop".to_string(),
+            src_ip: Some("10.0.0.0/8".to_string()),
+            packets_per_sec: 200,
+        }]);
+
+        // 다시 생성된 규칙은 카운터가 0부터 시작
+        assert!(monitor.observe(&[rule("busy", 10)], 10.0).is_empty());
+
+        let mut disabled = RuleMatchMonitor::new(0);
+        disabled.observe(&[rule("busy", 0)], 1.0);
+        assert!(disabled.observe(&[rule("busy", 1_000_000)], 1.0).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_udp_delivery() {
+        let receiver = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let config = SyslogConfig {
+            enabled: true,
+            address: format!("udp://{}", receiver.local_addr().unwrap()),
+            ..SyslogConfig::default()
+        };
+
+        let sink = SyslogSink::start(&config).unwrap().unwrap();
+        sink.send(&mitigation_event());
+
+        let mut buf = [0u8; 1024];
+        let n = tokio::time::timeout(Duration::from_secs(5), receiver.recv(&mut buf)).await
+            .expect("syslog message not delivered")
+            .unwrap();
+        let message = std::str::from_utf8(&buf[..n]).unwrap();
+        assert!(message.starts_with("<132>1 "));
+        assert!(message.contains(" MITIGATION [swiftguard@32473 "));
+    }
+
+    #[tokio::test]
+    async fn test_tcp_reconnects() {
+        use tokio::io::AsyncReadExt;
+        use tokio::net::TcpListener;
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let config = SyslogConfig {
+            enabled: true,
+            address: format!("tcp://{}", listener.local_addr().unwrap()),
+            ..SyslogConfig::default()
+        };
+        let sink = SyslogSink::start(&config).unwrap().unwrap();
+
+        async fn read_frame(listener: &TcpListener) -> String {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buf = vec![0u8; 1024];
+            let n = stream.read(&mut buf).await.unwrap();
+            String::from_utf8_lossy(&buf[..n]).into_owned()
+        }
+
+        sink.send(&mitigation_event());
+        let frame = tokio::time::timeout(Duration::from_secs(5), read_frame(&listener)).await.unwrap();
+        let (len, message) = frame.split_once(' ').unwrap();
+        assert_eq!(len.parse::<usize>().unwrap(), message.len());
+
+        // 수신 측이 연결을 끊으면 다음 이벤트들 중 하나로 다시 연결
+        let reconnected = async {
+            loop {
+                sink.send(&mitigation_event());
+                tokio::time::sleep(Duration::from_millis(50)).await;
+            }
+        };
+        tokio::select! {
+            frame = read_frame(&listener) => assert!(frame.contains("MITIGATION")),
+            _ = reconnected => unreachable!(),
+            _ = tokio::time::sleep(Duration::from_secs(10)) => panic!("syslog did not reconnect"),
+        }
+    }
+}
@@ -1,31 +1,81 @@
 //! 텔레메트리 수집기 모듈
 //! 성능 및 운영 메트릭 수집
 
-use anyhow::{anyhow, Context, Result};
-use log::{debug, error, info, warn};
-use std::sync::{Arc, Mutex};
+use anyhow::{anyhow, Result};
+use log::{error, info, warn};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex, MutexGuard};
 use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use tokio::time;
 
+use crate::apimetrics::ApiMetrics;
+use crate::backend::MapBackend;
+use crate::baseline::BaselineTracker;
 use crate::bpf::XdpFilterSkel;
 use crate::config::DaemonConfig;
+use crate::events::EventDispatcher;
+use crate::expiry::{ExpiryNotice, ExpiryNotifier, OwnerWebhooks};
+use crate::hithistory::HitHistory;
+use crate::logthrottle;
+use crate::maps::MapManager;
+use crate::metrics;
+use crate::mitigation::{MitigationEvent, SynFloodDetector};
+use crate::procstat::SelfSampler;
+use crate::quota::{QuotaChange, QuotaTracker};
+use crate::statslog::{ActionTotals, StatsLine};
+use crate::syslog::{RuleMatchMonitor, SyslogSink};
+use crate::wasm::WasmManager;
+use crate::webhook::WebhookMetrics;
 //use crate::api::SystemStats;
 
-use swift_guard::api::SystemStats;
-use libbpf_rs::MapFlags;
-use libbpf_rs::Map;
+use swift_guard::api::{
+    AnomalyEvent, DaemonEvent, DaemonSelfStats, ProtocolStats, RuleExpirySummary, RuleInfo, RuleMatchedEvent, SizeHistogram,
+    SystemStats, TrafficBaseline, XdpAbortedEvent,
+};
+use swift_guard::layout::GlobalStats;
+use swift_guard::types::{SIZE_BUCKET_COUNT, SIZE_HIST_SUM_INDEX};
 
 /// 텔레메트리 수집기
 //#[derive(Debug)]
 pub struct TelemetryCollector<'a> {
     /// 통계 맵 참조
-    stats_map: &'a  Map,
+    stats_map: &'a dyn MapBackend,
+    /// 패킷 크기 히스토그램 맵 참조 (이전 BPF 오브젝트에는 없음)
+    size_hist_map: Option<&'a dyn MapBackend>,
+    /// 소스별 SYN 카운터 맵 참조
+    syn_src_map: Option<&'a dyn MapBackend>,
     /// 구성 정보
     config: DaemonConfig,
     /// 수집된 통계
     stats: Arc<Mutex<CollectedStats>>,
     /// 마지막 수집 시간
-    last_collection: Instant,
+    last_collection: Mutex<Instant>,
+    /// 데몬 자체 통계 수집기
+    self_sampler: Mutex<SelfSampler>,
+    /// 지연을 보고할 이벤트 디스패처
+    events: Option<EventDispatcher>,
+    /// API 서버 메트릭 (서버와 공유)
+    api_metrics: Option<Arc<ApiMetrics>>,
+    /// 웹훅 전송 카운터 (서버와 공유)
+    webhook_metrics: Option<Arc<WebhookMetrics>>,
+    /// WASM 모듈 관리자 (검사 시간 메트릭, 서버와 공유)
+    wasm: Option<Arc<WasmManager>>,
+    /// 규칙별 매치율과 마지막 규칙 통계 수집 시간
+    rule_rates: Mutex<(RuleRateTracker, Option<Instant>)>,
+    /// 규칙별 최근 60분 분당 매치 기록
+    hit_history: Mutex<HitHistory>,
+    /// 규칙 할당량 사용량 추적기 (없으면 할당량을 적용하지 않음)
+    quota: Option<Mutex<QuotaTracker>>,
+    /// 규칙 만료 알림기와 소유자 웹훅 전송기 (없으면 만료 알림을 보내지 않음)
+    expiry: Option<Mutex<(ExpiryNotifier, OwnerWebhooks)>>,
+    /// XDP_ABORTED 경보 상태
+    abort_alert: Mutex<AbortAlert>,
+    /// 트래픽 기준선
+    baseline: Mutex<BaselineTracker>,
+    /// SYN 플러드 탐지기와 마지막 탐지 시간 (없으면 자동 완화하지 않음)
+    mitigation: Option<Mutex<(SynFloodDetector, Option<Instant>)>>,
+    /// 매치율 급증 규칙을 보고할 syslog 싱크, 감시기와 마지막 감시 시간
+    rule_matches: Option<Mutex<(SyslogSink, RuleMatchMonitor, Option<Instant>)>>,
 }
 
 /// 수집된 통계
@@ -39,12 +89,53 @@ pub struct CollectedStats {
     pub packets_per_sec: u64,
     /// Mbps
     pub mbps: f64,
+    /// 초당 TCP SYN 패킷 수
+    pub syn_per_sec: u64,
+    /// XDP_ABORTED로 끝난 누적 패킷 수
+    pub aborted_packets: u64,
+    /// 중단 패킷이 있는 규칙 (많은 순, 규칙 통계 수집 때 갱신)
+    pub aborted_rules: Vec<String>,
+    /// 마지막 수집 구간이 기준선을 넘었는지
+    pub anomaly: bool,
+    /// 마지막 수집 구간을 반영한 기준선
+    pub baseline: TrafficBaseline,
+    /// 프로토콜별 패킷 수
+    pub protocols: ProtocolStats,
+    /// 패킷 크기 히스토그램
+    pub size_histogram: SizeHistogram,
+    /// 데몬 자체 통계
+    pub daemon: DaemonSelfStats,
+    /// 규칙 액션별 누적 패킷 수 (규칙 통계 수집 때 갱신)
+    pub actions: ActionTotals,
     /// 마지막 업데이트 시간
     pub last_update: u64,
     /// 이전 패킷 수
     prev_packets: u64,
     /// 이전 바이트
     prev_bytes: u64,
+    /// 이전 SYN 패킷 수
+    prev_syn_packets: u64,
+    /// 이전 중단 패킷 수
+    prev_aborted_packets: u64,
+}
+
+impl CollectedStats {
+    /// 통계 로그 줄 값
+    pub fn log_line(&self) -> StatsLine {
+        StatsLine {
+            packets: self.total_packets,
+            bytes: self.total_bytes,
+            pps: self.packets_per_sec,
+            mbps: self.mbps,
+            syn_pps: self.syn_per_sec,
+            tcp: self.protocols.tcp,
+            udp: self.protocols.udp,
+            icmp: self.protocols.icmp,
+            other: self.protocols.other,
+            aborted: self.aborted_packets,
+            actions: self.actions,
+        }
+    }
 }
 
 // Debug 구현
@@ -63,83 +154,299 @@ impl<'a> TelemetryCollector<'a> {
         let stats_map = skel.maps().stats_map()
             .ok_or_else(|| anyhow!("Failed to get stats_map"))?;
         
-        Ok(Self {
+        Ok(Self::from_maps(
             stats_map,
+            skel.maps().size_histogram().map(|map| map as &dyn MapBackend),
+            skel.maps().syn_sources().map(|map| map as &dyn MapBackend),
+            config,
+        ))
+    }
+    
+    /// 맵 백엔드로 수집기 생성 (테스트는 `MemoryMap`)
+    pub fn from_maps(
+        stats_map: &'a dyn MapBackend,
+        size_hist_map: Option<&'a dyn MapBackend>,
+        syn_src_map: Option<&'a dyn MapBackend>,
+        config: &DaemonConfig,
+    ) -> Self {
+        Self {
+            stats_map,
+            size_hist_map,
+            syn_src_map,
             config: config.clone(),
             stats: Arc::new(Mutex::new(CollectedStats {
                 total_packets: 0,
                 total_bytes: 0,
                 packets_per_sec: 0,
                 mbps: 0.0,
+                syn_per_sec: 0,
+                aborted_packets: 0,
+                aborted_rules: Vec::new(),
+                anomaly: false,
+                baseline: TrafficBaseline::default(),
+                protocols: ProtocolStats::default(),
+                size_histogram: SizeHistogram::default(),
+                daemon: DaemonSelfStats::default(),
+                actions: ActionTotals::default(),
                 last_update: 0,
                 prev_packets: 0,
                 prev_bytes: 0,
+                prev_syn_packets: 0,
+                prev_aborted_packets: 0,
             })),
-            last_collection: Instant::now(),
-        })
+            last_collection: Mutex::new(Instant::now()),
+            self_sampler: Mutex::new(SelfSampler::new()),
+            events: None,
+            api_metrics: None,
+            webhook_metrics: None,
+            wasm: None,
+            rule_rates: Mutex::new((RuleRateTracker::default(), None)),
+            hit_history: Mutex::new(HitHistory::default()),
+            quota: None,
+            expiry: None,
+            abort_alert: Mutex::new(AbortAlert::new(config.telemetry.abort_alert_intervals)),
+            baseline: Mutex::new(BaselineTracker::new(config.telemetry.anomaly_k)),
+            mitigation: None,
+            rule_matches: None,
+        }
+    }
+
+    /// 이벤트 채널 지연 보고 및 규칙 매치 이벤트 기록
+    pub fn with_events(mut self, events: EventDispatcher) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    /// API 서버 메트릭 보고 (`ApiServer::with_metrics`와 같은 값)
+    pub fn with_api_metrics(mut self, metrics: Arc<ApiMetrics>) -> Self {
+        self.api_metrics = Some(metrics);
+        self
+    }
+
+    /// 웹훅 전송 통계 보고 (`ApiServer::with_webhook_metrics`와 같은 값)
+    pub fn with_webhook_metrics(mut self, metrics: Arc<WebhookMetrics>) -> Self {
+        self.webhook_metrics = Some(metrics);
+        self
+    }
+
+    /// WASM 모듈 검사 시간 보고 (`ApiServer::with_wasm`과 같은 값)
+    pub fn with_wasm(mut self, manager: Arc<WasmManager>) -> Self {
+        self.wasm = Some(manager);
+        self
+    }
+
+    /// 규칙 할당량 적용 (`QuotaTracker::load`로 복원한 추적기)
+    pub fn with_quota(mut self, tracker: QuotaTracker) -> Self {
+        self.quota = Some(Mutex::new(tracker));
+        self
+    }
+    
+    /// 규칙 만료 알림 (`ExpiryNotifier::load`로 복원한 알림기)
+    pub fn with_expiry(mut self, notifier: ExpiryNotifier, webhooks: OwnerWebhooks) -> Self {
+        self.expiry = Some(Mutex::new((notifier, webhooks)));
+        self
+    }
+    
+    /// SYN 플러드 자동 완화 (`mitigation.enabled`일 때 만든 탐지기)
+    pub fn with_mitigation(mut self, detector: SynFloodDetector) -> Self {
+        self.mitigation = Some(Mutex::new((detector, None)));
+        self
+    }
+    
+    /// `logging.syslog.rule_match_pps_threshold` 이상으로 매치하는 규칙을 syslog로 보고
+    pub fn with_syslog(mut self, sink: SyslogSink) -> Self {
+        let monitor = RuleMatchMonitor::new(self.config.logging.syslog.rule_match_pps_threshold);
+        self.rule_matches = Some(Mutex::new((sink, monitor, None)));
+        self
+    }
+    
+    /// `telemetry.interval`마다 수집 주기 실행 (반환하지 않음)
+    pub async fn run(&self, map_manager: &Mutex<MapManager<'_>>) {
+        let mut ticker = time::interval(Duration::from_secs(self.config.telemetry.interval.max(1)));
+        ticker.tick().await; // 첫 틱은 즉시 완료
+        loop {
+            ticker.tick().await;
+            if let Err(e) = self.tick(map_manager).await {
+                throttled_warn!("telemetry:collect", logthrottle::HOT_PATH_INTERVAL,
+                    "Failed to collect statistics: {:#}", e);
+            }
+        }
+    }
+    
+    /// 수집 주기 한 번: 규칙별 통계와 전역 통계 수집 뒤 맵 관리자가 필요한 주기 작업 실행
+    ///
+    /// 액션별 합계와 중단 패킷이 있는 규칙은 통계 로그와 중단 경보에 쓰이므로 규칙별
+    /// 통계를 먼저 수집한다.
+    pub async fn tick(&self, map_manager: &Mutex<MapManager<'_>>) -> Result<()> {
+        {
+            let map_manager = map_manager.lock()
+                .map_err(|_| anyhow!("Failed to lock map_manager"))?;
+            self.collect_rule_rates(&map_manager)?;
+        }
+        self.collect_stats().await?;
+        
+        let mut map_manager = map_manager.lock()
+            .map_err(|_| anyhow!("Failed to lock map_manager"))?;
+        self.expire_rules(&mut map_manager)?;
+        self.enforce_quotas(&mut map_manager)?;
+        self.report_rule_matches(&map_manager)?;
+        self.mitigate(&mut map_manager)?;
+        Ok(())
     }
     
     /// 통계 수집
     pub async fn collect_stats(&self) -> Result<()> {
         let now = Instant::now();
-        let elapsed = now.duration_since(self.last_collection).as_secs_f64();
+        let elapsed = now.duration_since(*self.lock_last_collection()?).as_secs_f64();
         
         // 최소 간격 확인
         if elapsed < 0.1 {
             return Ok(());
         }
         
-        let key = 0u32.to_le_bytes();
+        let daemon = self.sample_self();
+        self.stats.lock()
+            .map_err(|_| anyhow!("Failed to lock stats"))?
+            .daemon = daemon;
         
-        // 맵에서 통계 읽기
-//        if let Ok(value) = self.stats_map.lookup(&key, 0) {
-        if let Ok(Some(value)) = self.stats_map.lookup(&key, MapFlags::empty()) {
-            if value.len() >= 16 {
-                // 통계 데이터 파싱
-                let packets = u64::from_le_bytes([
-                    value[0], value[1], value[2], value[3],
-                    value[4], value[5], value[6], value[7],
-                ]);
-                
-                let bytes = u64::from_le_bytes([
-                    value[8], value[9], value[10], value[11],
-                    value[12], value[13], value[14], value[15],
-                ]);
-                
-                // 통계 업데이트
-                let mut stats = self.stats.lock()
-                    .map_err(|_| anyhow!("Failed to lock stats"))?;
-                
-                // 초당 패킷 수 및 Mbps 계산
-                let packets_diff = packets.saturating_sub(stats.prev_packets);
-                let bytes_diff = bytes.saturating_sub(stats.prev_bytes);
-                
-                stats.packets_per_sec = (packets_diff as f64 / elapsed) as u64;
-                stats.mbps = (bytes_diff as f64 * 8.0 / elapsed) / 1_000_000.0;
-                
-                // 총계 업데이트
-                stats.total_packets = packets;
-                stats.total_bytes = bytes;
-                stats.last_update = SystemTime::now()
-                    .duration_since(UNIX_EPOCH)
-                    .map_err(|_| anyhow!("Failed to get system time"))?
-                    .as_secs();
-                
-                // 이전 값 저장
-                stats.prev_packets = packets;
-                stats.prev_bytes = bytes;
-                
-                // 로그 기록 (구성에서 활성화된 경우)
-                if self.config.telemetry.log_stats {
-                    debug!("Stats - Packets: {}, Bytes: {}, PPS: {}, Mbps: {:.2}",
-                        packets, bytes, stats.packets_per_sec, stats.mbps);
-                }
+        // 맵에서 통계 읽기 (CPU별 값 합산)
+        if let Some(global) = read_global_stats(self.stats_map) {
+            let packets = global.packets;
+            let bytes = global.bytes;
+            *self.lock_last_collection()? = now;
+
+            // 통계 업데이트
+            let mut stats = self.stats.lock()
+                .map_err(|_| anyhow!("Failed to lock stats"))?;
+            
+            // 초당 패킷 수 및 Mbps 계산
+            let packets_diff = packets.saturating_sub(stats.prev_packets);
+            let bytes_diff = bytes.saturating_sub(stats.prev_bytes);
+            
+            stats.packets_per_sec = (packets_diff as f64 / elapsed) as u64;
+            stats.mbps = (bytes_diff as f64 * 8.0 / elapsed) / 1_000_000.0;
+            stats.syn_per_sec = (global.syn_packets.saturating_sub(stats.prev_syn_packets) as f64
+                / elapsed) as u64;
+            
+            // 총계 업데이트
+            stats.total_packets = packets;
+            stats.total_bytes = bytes;
+            stats.protocols = global.protocol_stats();
+            if let Some(histogram) = self.size_hist_map.and_then(read_size_histogram) {
+                stats.size_histogram = histogram;
+            }
+            stats.last_update = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_err(|_| anyhow!("Failed to get system time"))?
+                .as_secs();
+            
+            // 중단 패킷은 대부분 잘못된 리디렉션 대상이므로 바로 알림
+            let aborted = global.aborted_packets.saturating_sub(stats.prev_aborted_packets);
+            stats.aborted_packets = global.aborted_packets;
+            if aborted > 0 {
+                throttled_warn!("telemetry:xdp-aborted", logthrottle::HOT_PATH_INTERVAL,
+                    "{} packet(s) ended in XDP_ABORTED in the last {:.0}s ({} total); check redirect targets",
+                    aborted, elapsed, global.aborted_packets);
+            }
+            self.raise_abort_alert(&stats, aborted)?;
+            self.update_baseline(&mut stats)?;
+            
+            // 이전 값 저장
+            stats.prev_packets = packets;
+            stats.prev_bytes = bytes;
+            stats.prev_syn_packets = global.syn_packets;
+            stats.prev_aborted_packets = global.aborted_packets;
+            
+            // 로그 기록 (구성에서 활성화된 경우)
+            if let Some(line) = self.stats_log_line(&stats) {
+                info!("{}", line);
             }
         }
         
         Ok(())
     }
     
+    /// 구간마다 남기는 통계 로그 줄 (`telemetry.log_stats`가 꺼져 있으면 None)
+    fn stats_log_line(&self, stats: &CollectedStats) -> Option<String> {
+        self.config.telemetry.log_stats
+            .then(|| self.config.telemetry.stats_log_format.render(&stats.log_line()))
+    }
+    
+    /// 마지막 수집 시간 잠금
+    fn lock_last_collection(&self) -> Result<MutexGuard<'_, Instant>> {
+        self.last_collection.lock()
+            .map_err(|_| anyhow!("Failed to lock last collection time"))
+    }
+    
+    /// 중단 패킷이 연속한 구간마다 관측되면 경보 (경고 로그와 xdp_aborted 이벤트)
+    fn raise_abort_alert(&self, stats: &CollectedStats, aborted: u64) -> Result<()> {
+        let mut alert = self.abort_alert.lock()
+            .map_err(|_| anyhow!("Failed to lock abort alert"))?;
+        let Some(window) = alert.observe(aborted) else {
+            return Ok(());
+        };
+        
+        warn!("Packets ended in XDP_ABORTED for {} consecutive intervals ({} in total, rules: {})",
+            alert.intervals, window,
+            if stats.aborted_rules.is_empty() { "unknown".to_string() } else { stats.aborted_rules.join(", ") });
+        if let Some(events) = &self.events {
+            events.record_xdp_aborted(XdpAbortedEvent {
+                aborted: window,
+                total: stats.aborted_packets,
+                intervals: alert.intervals,
+                rules: stats.aborted_rules.clone(),
+                timestamp: swift_guard::utils::current_time_secs(),
+            });
+        }
+        Ok(())
+    }
+    
+    /// 트래픽 기준선 갱신과 이상 판정 (이상 상태로 바뀔 때만 경고 로그와 anomaly 이벤트)
+    fn update_baseline(&self, stats: &mut CollectedStats) -> Result<()> {
+        let mut baseline = self.baseline.lock()
+            .map_err(|_| anyhow!("Failed to lock traffic baseline"))?;
+        let before = baseline.snapshot();
+        let exceeded = baseline.observe(stats.packets_per_sec as f64, stats.mbps);
+        let was_anomaly = stats.anomaly;
+        stats.anomaly = !exceeded.is_empty();
+        stats.baseline = baseline.snapshot();
+        if !stats.anomaly || was_anomaly {
+            return Ok(());
+        }
+
+        warn!("Traffic above baseline ({}): {} pps (mean {:.0}, stddev {:.0}), {:.2} Mbps (mean {:.2}, stddev {:.2})",
+            exceeded.join(", "), stats.packets_per_sec, before.pps_mean, before.pps_stddev,
+            stats.mbps, before.mbps_mean, before.mbps_stddev);
+        if let Some(events) = &self.events {
+            events.record_anomaly(AnomalyEvent {
+                pps: stats.packets_per_sec,
+                mbps: stats.mbps,
+                metrics: exceeded.iter().map(|metric| metric.to_string()).collect(),
+                baseline: before,
+                timestamp: stats.last_update,
+            });
+        }
+        Ok(())
+    }
+    
+    /// 데몬 자체 통계 수집 (/proc을 읽지 못해도 작업 수와 이벤트 지연은 보고)
+    fn sample_self(&self) -> DaemonSelfStats {
+        let mut daemon = match self.self_sampler.lock() {
+            Ok(mut sampler) => sampler.sample().unwrap_or_else(|e| {
+                throttled_warn!("telemetry:self-stats", logthrottle::HOT_PATH_INTERVAL,
+                    "Failed to read daemon process stats: {:#}", e);
+                DaemonSelfStats::default()
+            }),
+            Err(_) => DaemonSelfStats::default(),
+        };
+        
+        daemon.tokio_tasks = tokio::runtime::Handle::try_current()
+            .map(|handle| handle.metrics().num_alive_tasks() as u64)
+            .unwrap_or(0);
+        daemon.event_lag = self.events.as_ref().map(|events| events.backlog()).unwrap_or(0);
+        daemon
+    }
+    
     /// 현재 통계 획득
     pub fn get_stats(&self) -> Result<SystemStats> {
         let stats = self.stats.lock()
@@ -150,6 +457,996 @@ impl<'a> TelemetryCollector<'a> {
             total_bytes: stats.total_bytes,
             packets_per_sec: stats.packets_per_sec,
             mbps: stats.mbps,
+            aborted_packets: stats.aborted_packets,
+            anomaly: stats.anomaly,
+            baseline: stats.baseline,
+            protocols: stats.protocols,
+            size_histogram: stats.size_histogram.clone(),
+            // 규칙 요약은 규칙 캐시를 가진 API 서버가 채운다
+            rules: RuleExpirySummary::default(),
+            daemon: stats.daemon,
+            api: self.api_metrics.as_ref().map(|metrics| metrics.snapshot()).unwrap_or_default(),
+            webhooks: self.webhook_metrics.as_ref().map(|metrics| metrics.snapshot()).unwrap_or_default(),
         })
     }
+
+    /// 초당 TCP SYN 패킷 수 획득
+    pub fn syn_packets_per_sec(&self) -> Result<u64> {
+        let stats = self.stats.lock()
+            .map_err(|_| anyhow!("Failed to lock stats"))?;
+
+        Ok(stats.syn_per_sec)
+    }
+
+    /// 소스 IP별 누적 SYN 패킷 수 획득
+    pub fn syn_source_counts(&self) -> HashMap<u32, u64> {
+        self.syn_src_map
+            .map(read_syn_sources)
+            .unwrap_or_default()
+    }
+    
+    /// 규칙별 통계 수집 (텔레메트리 수집 주기마다 호출)
+    ///
+    /// 직전 수집 이후 카운터 변화로 규칙별 초당 매치율을 갱신하고, 매치가 있었던
+    /// 규칙마다 RuleMatched 이벤트를 기록하고 분당 매치 기록에 더한다. 첫 수집은 기준값만 저장한다.
+    /// 통계 로그의 액션별 합계(`{dropped}` 등)와 중단 패킷이 있는 규칙도 이때 갱신한다.
+    pub fn collect_rule_rates(&self, map_manager: &MapManager) -> Result<Vec<RuleMatchedEvent>> {
+        let rules = map_manager.list_rules(true)?;
+        let now = Instant::now();
+
+        {
+            let mut stats = self.stats.lock()
+                .map_err(|_| anyhow!("Failed to lock stats"))?;
+            stats.actions = ActionTotals::from_rules(&rules);
+            stats.aborted_rules = aborted_rules(&rules);
+        }
+
+        let mut guard = self.rule_rates.lock()
+            .map_err(|_| anyhow!("Failed to lock rule rates"))?;
+        let (tracker, last) = &mut *guard;
+        let elapsed = last.map(|last| now.duration_since(last).as_secs_f64()).unwrap_or(0.0);
+        *last = Some(now);
+
+        let timestamp = swift_guard::utils::current_time_secs();
+        let matched: Vec<RuleMatchedEvent> = tracker.observe(&rules, elapsed).into_iter()
+            .map(|(label, rate)| RuleMatchedEvent {
+                label,
+                packets: rate.packets,
+                bytes: rate.bytes,
+                pps: rate.pps,
+                bps: rate.bps,
+                timestamp,
+            })
+            .collect();
+        drop(guard);
+
+        {
+            let mut history = self.hit_history.lock()
+                .map_err(|_| anyhow!("Failed to lock hit history"))?;
+            for event in &matched {
+                history.record(&event.label, event.packets, timestamp);
+            }
+            let keys: HashSet<String> = rules.iter().map(RuleInfo::key).collect();
+            history.retain(|key| keys.contains(key));
+        }
+
+        if let Some(events) = &self.events {
+            for event in &matched {
+                events.record_rule_matched(event.clone());
+            }
+        }
+
+        Ok(matched)
+    }
+
+    /// 규칙 할당량 적용 (텔레메트리 수집 주기마다 호출)
+    ///
+    /// 사용량을 갱신한 뒤 초과 상태가 바뀐 규칙의 커널 액션을 바꾸고 상태 파일을
+    /// 저장한다. 한 규칙을 바꾸지 못해도 나머지 규칙은 계속 처리한다.
+    pub fn enforce_quotas(&self, map_manager: &mut MapManager) -> Result<Vec<QuotaChange>> {
+        let quota = match &self.quota {
+            Some(quota) => quota,
+            None => return Ok(Vec::new()),
+        };
+
+        let rules = map_manager.list_rules(true)?;
+        let mut tracker = quota.lock()
+            .map_err(|_| anyhow!("Failed to lock quota tracker"))?;
+        let changes = tracker.observe(&rules, swift_guard::utils::current_time_secs());
+
+        for change in &changes {
+            match map_manager.set_quota_exceeded(&change.label, change.exceeded) {
+                Ok(_) if change.exceeded => warn!("Rule '{}' exceeded its quota ({} bytes, {} packets)",
+                    change.label, change.usage.bytes, change.usage.packets),
+                Ok(_) => info!("Rule '{}' quota reset, original action restored", change.label),
+                Err(e) => error!("Failed to update quota state of rule '{}': {}", change.label, e),
+            }
+        }
+
+        if let Err(e) = tracker.save() {
+            throttled_warn!("telemetry:quota-state", logthrottle::HOT_PATH_INTERVAL,
+                "Failed to save quota state: {:#}", e);
+        }
+
+        Ok(changes)
+    }
+
+    /// 규칙 만료 알림과 만료 규칙 삭제 (텔레메트리 수집 주기마다, 완화 처리보다 먼저 호출)
+    ///
+    /// 알림은 이벤트 기록에 추가하고, 소유자 웹훅이 있는 규칙은 웹훅으로도 보낸다.
+    /// 더 이상 어느 규칙도 쓰지 않는 웹훅의 전송기는 해제한다.
+    pub fn expire_rules(&self, map_manager: &mut MapManager) -> Result<Vec<ExpiryNotice>> {
+        let expiry = match &self.expiry {
+            Some(expiry) => expiry,
+            None => return Ok(Vec::new()),
+        };
+
+        let mut guard = expiry.lock()
+            .map_err(|_| anyhow!("Failed to lock expiry notifier"))?;
+        let (notifier, webhooks) = &mut *guard;
+        let notices = notifier.tick(map_manager, swift_guard::utils::current_time_secs())?;
+
+        for notice in &notices {
+            webhooks.send(notice);
+            if let Some(events) = &self.events {
+                match &notice.event {
+                    DaemonEvent::RuleExpiring(event) => events.record_rule_expiring(event.clone()),
+                    DaemonEvent::RuleExpired(event) => events.record_rule_expired(event.clone()),
+                    _ => {}
+                }
+            }
+        }
+
+        let owners: HashSet<&str> = map_manager.rules().iter()
+            .filter_map(|rule| rule.owner_webhook.as_deref())
+            .collect();
+        webhooks.retain(&owners);
+
+        Ok(notices)
+    }
+
+    /// 매치율이 임계값 이상인 규칙을 syslog로 보고 (텔레메트리 수집 주기마다 호출)
+    pub fn report_rule_matches(&self, map_manager: &MapManager) -> Result<usize> {
+        let rule_matches = match &self.rule_matches {
+            Some(rule_matches) => rule_matches,
+            None => return Ok(0),
+        };
+
+        let rules = map_manager.list_rules(true)?;
+        let mut guard = rule_matches.lock()
+            .map_err(|_| anyhow!("Failed to lock rule match monitor"))?;
+        let (sink, monitor, last) = &mut *guard;
+        let now = Instant::now();
+        let elapsed = last.map(|last| now.duration_since(last).as_secs_f64()).unwrap_or(0.0);
+        *last = Some(now);
+
+        let events = monitor.observe(&rules, elapsed);
+        for event in &events {
+            sink.send(event);
+        }
+        Ok(events.len())
+    }
+
+    /// SYN 플러드 탐지와 자동 규칙 설치 (텔레메트리 수집 주기마다 호출)
+    pub fn mitigate(&self, map_manager: &mut MapManager) -> Result<Vec<MitigationEvent>> {
+        let mitigation = match &self.mitigation {
+            Some(mitigation) => mitigation,
+            None => return Ok(Vec::new()),
+        };
+
+        let mut guard = mitigation.lock()
+            .map_err(|_| anyhow!("Failed to lock SYN flood detector"))?;
+        let (detector, last) = &mut *guard;
+        let now = Instant::now();
+        let elapsed = last.map(|last| now.duration_since(last).as_secs_f64()).unwrap_or(0.0);
+        *last = Some(now);
+
+        detector.tick(self, map_manager, elapsed, swift_guard::utils::current_time_secs())
+    }
+
+    /// 규칙 목록에 최근 매치율 채우기 (ListRules include_rates)
+    pub fn fill_rule_rates(&self, rules: &mut [RuleInfo]) -> Result<()> {
+        let guard = self.rule_rates.lock()
+            .map_err(|_| anyhow!("Failed to lock rule rates"))?;
+        guard.0.fill(rules);
+        Ok(())
+    }
+
+    /// 규칙의 최근 60분 분당 매치 패킷 수 (GetRule history_1m)
+    pub fn rule_history(&self, key: &str, now: u64) -> Result<Vec<u64>> {
+        let history = self.hit_history.lock()
+            .map_err(|_| anyhow!("Failed to lock hit history"))?;
+        Ok(history.history(key, now))
+    }
+    
+    /// Prometheus 텍스트 형식 메트릭 획득 (규칙별 카운터 포함)
+    pub fn prometheus_metrics(&self, map_manager: &MapManager) -> Result<String> {
+        let stats = self.get_stats()?;
+        let rules = map_manager.list_rules(true)?;
+        let mut text = metrics::render_prometheus(&stats, &rules);
+        if let Some(wasm) = &self.wasm {
+            let modules: Vec<_> = wasm.all_module_stats()?.into_iter()
+                .map(|(name, stats)| (name, stats.latency))
+                .collect();
+            text.push_str(&metrics::render_wasm_latency(&modules));
+            text.push_str(&metrics::render_wasm_overloads(wasm.overload_count()));
+        }
+        Ok(text)
+    }
+}
+
+/// XDP_ABORTED 경보 규칙
+///
+/// 중단 패킷이 `intervals`번 연속한 수집 구간마다 관측되면 경보를 한 번 올린다.
+/// 중단 패킷이 없는 구간이 오면 다시 센다. `intervals`가 0이면 경보를 올리지 않는다.
+#[derive(Debug)]
+pub struct AbortAlert {
+    /// 경보까지 필요한 연속 구간 수
+    intervals: u32,
+    /// 중단 패킷이 이어서 관측된 구간 수
+    streak: u32,
+    /// 이어진 구간 동안의 중단 패킷 합
+    window: u64,
+}
+
+impl AbortAlert {
+    /// 연속 구간 수로 경보 규칙 생성
+    pub fn new(intervals: u32) -> Self {
+        Self { intervals, streak: 0, window: 0 }
+    }
+
+    /// 한 구간의 중단 패킷 수 관측 (경보를 올릴 때만 이어진 구간 동안의 합)
+    pub fn observe(&mut self, aborted: u64) -> Option<u64> {
+        if aborted == 0 {
+            self.streak = 0;
+            self.window = 0;
+            return None;
+        }
+
+        self.streak = self.streak.saturating_add(1);
+        self.window += aborted;
+        (self.intervals > 0 && self.streak == self.intervals).then_some(self.window)
+    }
+}
+
+/// 중단 패킷이 있는 규칙의 키 (많은 순, 같으면 키 순)
+pub fn aborted_rules(rules: &[RuleInfo]) -> Vec<String> {
+    let mut aborted: Vec<(&RuleInfo, u64)> = rules.iter()
+        .filter(|rule| rule.stats.aborted_packets > 0)
+        .map(|rule| (rule, rule.stats.aborted_packets))
+        .collect();
+    aborted.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.key().cmp(&b.0.key())));
+    aborted.into_iter().map(|(rule, _)| rule.key()).collect()
+}
+
+/// 규칙 하나의 수집 구간 변화
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RuleRate {
+    /// 구간 동안 매치된 패킷 수
+    pub packets: u64,
+    /// 구간 동안 매치된 바이트 수
+    pub bytes: u64,
+    pub pps: u64,
+    /// 초당 바이트 수
+    pub bps: u64,
+}
+
+/// 규칙별 누적 카운터의 구간 변화 추적
+///
+/// 카운터가 이전 값보다 작으면 규칙이 같은 레이블로 다시 설치되는 등 카운터가
+/// 0부터 다시 시작한 것으로 보고 현재 값을 구간 변화로 쓴다. 따라서 매치율은
+/// 음수가 되거나 u64로 넘치지 않는다. 목록에 없는 레이블(삭제된 규칙)은 잊는다.
+#[derive(Debug, Default)]
+pub struct RuleRateTracker {
+    /// 레이블별 직전 누적 (packets, bytes)
+    previous: HashMap<String, (u64, u64)>,
+    /// 레이블별 최근 구간 변화
+    rates: HashMap<String, RuleRate>,
+}
+
+impl RuleRateTracker {
+    /// 누적 카운터 관측 (`elapsed_secs`는 직전 관측 이후 경과 시간)
+    ///
+    /// 구간 동안 매치가 있었던 규칙의 변화를 레이블 순으로 반환한다.
+    pub fn observe(&mut self, rules: &[RuleInfo], elapsed_secs: f64) -> Vec<(String, RuleRate)> {
+        let mut previous = HashMap::with_capacity(rules.len());
+        let mut rates = HashMap::with_capacity(rules.len());
+        let mut matched = Vec::new();
+
+        for rule in rules {
+            let key = rule.key();
+            let current = (rule.stats.packets, rule.stats.bytes);
+            previous.insert(key.clone(), current);
+
+            let Some(&(prev_packets, prev_bytes)) = self.previous.get(&key) else {
+                continue;
+            };
+            if elapsed_secs <= 0.0 {
+                continue;
+            }
+
+            let packets = counter_delta(prev_packets, current.0);
+            let bytes = counter_delta(prev_bytes, current.1);
+            let rate = RuleRate {
+                packets,
+                bytes,
+                pps: (packets as f64 / elapsed_secs) as u64,
+                bps: (bytes as f64 / elapsed_secs) as u64,
+            };
+
+            if packets > 0 {
+                matched.push((key.clone(), rate));
+            }
+            rates.insert(key, rate);
+        }
+
+        self.previous = previous;
+        self.rates = rates;
+        matched.sort_by(|a, b| a.0.cmp(&b.0));
+        matched
+    }
+
+    /// 규칙 키(`RuleInfo::key`)의 최근 구간 변화 (아직 두 번 관측되지 않았으면 None)
+    pub fn rate(&self, key: &str) -> Option<RuleRate> {
+        self.rates.get(key).copied()
+    }
+
+    /// 규칙 목록의 `stats.pps`, `stats.bps` 채우기
+    pub fn fill(&self, rules: &mut [RuleInfo]) {
+        for rule in rules {
+            let rate = self.rate(&rule.key());
+            rule.stats.pps = rate.map(|rate| rate.pps);
+            rule.stats.bps = rate.map(|rate| rate.bps);
+        }
+    }
+}
+
+/// 누적 카운터 변화 (감소했으면 0부터 다시 센 것으로 보고 현재 값)
+pub fn counter_delta(previous: u64, current: u64) -> u64 {
+    if current >= previous { current - previous } else { current }
+}
+
+/// 전역 통계 맵 읽기 (CPU별 값 합산)
+pub fn read_global_stats(stats_map: &dyn MapBackend) -> Option<GlobalStats> {
+    let key = 0u32.to_le_bytes();
+
+    let values = stats_map.lookup_percpu(&key).ok()??;
+    let mut total = GlobalStats::default();
+    let mut found = false;
+
+    for value in values {
+        if let Some(cpu_stats) = GlobalStats::from_bytes(&value) {
+            total.merge(&cpu_stats);
+            found = true;
+        }
+    }
+
+    if found {
+        Some(total)
+    } else {
+        None
+    }
+}
+
+/// 소스별 SYN 카운터 맵 읽기
+///
+/// 키는 네트워크 순서 IPv4 주소이며 호스트 순서 u32로 변환해 반환한다.
+pub fn read_syn_sources(map: &dyn MapBackend) -> HashMap<u32, u64> {
+    let mut result = HashMap::new();
+
+    for key in map.keys() {
+        if key.len() < 4 {
+            continue;
+        }
+
+        if let Ok(Some(value)) = map.lookup(&key) {
+            if value.len() >= 8 {
+                let addr = u32::from_be_bytes([key[0], key[1], key[2], key[3]]);
+                let count = u64::from_le_bytes([
+                    value[0], value[1], value[2], value[3],
+                    value[4], value[5], value[6], value[7],
+                ]);
+                result.insert(addr, count);
+            }
+        }
+    }
+
+    result
+}
+
+/// CPU별 u64 카운터 합산
+fn read_percpu_u64(map: &dyn MapBackend, index: u32) -> Option<u64> {
+    let values = map.lookup_percpu(&index.to_le_bytes()).ok()??;
+
+    Some(values.iter()
+        .filter(|value| value.len() >= 8)
+        .map(|value| u64::from_le_bytes([
+            value[0], value[1], value[2], value[3],
+            value[4], value[5], value[6], value[7],
+        ]))
+        .sum())
+}
+
+/// 패킷 크기 히스토그램 맵 읽기
+pub fn read_size_histogram(map: &dyn MapBackend) -> Option<SizeHistogram> {
+    let mut counts = Vec::with_capacity(SIZE_BUCKET_COUNT);
+    for index in 0..SIZE_BUCKET_COUNT as u32 {
+        counts.push(read_percpu_u64(map, index)?);
+    }
+
+    Some(SizeHistogram {
+        counts,
+        sum: read_percpu_u64(map, SIZE_HIST_SUM_INDEX).unwrap_or(0),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::MemoryMap;
+    use crate::config::{LoggingConfig, MitigationConfig, SyslogConfig};
+    use crate::maps::{FilterRule, MapBackends};
+    use crate::quota::RuleQuota;
+    use crate::statslog::StatsLogFormat;
+    use crate::webhook::{WebhookOptions, WebhookSender};
+    use swift_guard::api::RuleStats;
+    use swift_guard::layout::FilterStats;
+    use swift_guard::types::ActionType;
+
+    fn rule(label: &str, packets: u64, bytes: u64) -> RuleInfo {
+        RuleInfo {
+            label: label.to_string(),
+            protocol: "tcp".to_string(),
+            stats: RuleStats { packets, bytes, ..RuleStats::default() },
+            ..RuleInfo::default()
+        }
+    }
+
+    /// struct global_stats 레이아웃대로 맵 값 생성 (TCP, UDP 외의 패킷은 기타)
+    fn global_stats(packets: u64, bytes: u64, tcp: u64, udp: u64, syn: u64) -> Vec<u8> {
+        [packets, bytes, 0, tcp, udp, 0, packets - tcp - udp, syn, 0].iter()
+            .flat_map(|value| value.to_le_bytes())
+            .collect()
+    }
+
+    /// 마지막 수집 시각을 `secs`초 앞당김 (수집 간격 흉내)
+    fn backdate(collector: &TelemetryCollector, secs: u64) {
+        *collector.last_collection.lock().unwrap() -= Duration::from_secs(secs);
+    }
+
+    /// 커널이 규칙 항목에 기록한 매치 카운터 흉내
+    fn set_rule_counters(filter_rules: &MemoryMap, packets: u64, bytes: u64) {
+        for key in filter_rules.keys() {
+            let mut value = filter_rules.lookup(&key).unwrap().unwrap();
+            let stats = value.len() - FilterStats::SIZE;
+            value[stats..stats + 8].copy_from_slice(&packets.to_le_bytes());
+            value[stats + 8..stats + 16].copy_from_slice(&bytes.to_le_bytes());
+            filter_rules.update(&key, &value).unwrap();
+        }
+    }
+
+    /// 소스 주소 하나를 차단하는 규칙
+    fn block_rule(label: &str) -> FilterRule {
+        FilterRule {
+            src_ip: Some((0xC0A8010A, 32)),
+            action: ActionType::Drop as u8,
+            label: label.to_string(),
+            ..FilterRule::default()
+        }
+    }
+
+    fn rate(packets: u64, bytes: u64, pps: u64, bps: u64) -> RuleRate {
+        RuleRate { packets, bytes, pps, bps }
+    }
+
+    #[test]
+    fn test_rule_rate_deltas() {
+        let mut tracker = RuleRateTracker::default();
+
+        // 첫 관측은 기준값만 저장
+        assert!(tracker.observe(&[rule("web", 100, 10_000), rule("ssh", 5, 300)], 0.0).is_empty());
+        assert_eq!(tracker.rate("web"), None);
+
+        // 2초 동안 web 200개, ssh 변화 없음
+        let matched = tracker.observe(&[rule("web", 300, 30_000), rule("ssh", 5, 300)], 2.0);
+        assert_eq!(matched, [("web".to_string(), rate(200, 20_000, 100, 10_000))]);
+        assert_eq!(tracker.rate("ssh"), Some(RuleRate::default()));
+
+        // 새 규칙은 다음 관측부터
+        let matched = tracker.observe(&[rule("web", 300, 30_000), rule("ssh", 15, 900), rule("new", 50, 500)], 1.0);
+        assert_eq!(matched, [("ssh".to_string(), rate(10, 600, 10, 600))]);
+        assert_eq!(tracker.rate("new"), None);
+        assert_eq!(tracker.previous.len(), 3);
+    }
+
+    #[test]
+    fn test_rule_rate_counter_reset_and_pruning() {
+        let mut tracker = RuleRateTracker::default();
+        tracker.observe(&[rule("web", 1_000, 100_000), rule("ssh", 10, 600)], 0.0);
+
+        // 카운터가 줄면 0부터 다시 센 것으로 보고 음수가 되지 않음
+        let matched = tracker.observe(&[rule("web", 40, 4_000), rule("ssh", 10, 600)], 2.0);
+        assert_eq!(matched, [("web".to_string(), rate(40, 4_000, 20, 2_000))]);
+
+        // 0으로 초기화된 직후에는 매치 없음
+        assert!(tracker.observe(&[rule("web", 0, 0)], 1.0).is_empty());
+        assert_eq!(tracker.rate("web"), Some(RuleRate::default()));
+
+        // 삭제된 규칙은 잊고, 같은 레이블로 다시 생기면 기준값부터
+        assert_eq!(tracker.rate("ssh"), None);
+        assert_eq!(tracker.previous.len(), 1);
+        assert!(tracker.observe(&[rule("web", 0, 0), rule("ssh", 500, 30_000)], 1.0).is_empty());
+        assert_eq!(tracker.rate("ssh"), None);
+    }
+
+    #[test]
+    fn test_fill_rule_rates() {
+        let mut tracker = RuleRateTracker::default();
+        tracker.observe(&[rule("web", 0, 0)], 0.0);
+        tracker.observe(&[rule("web", 30, 3_000)], 3.0);
+
+        let mut rules = vec![rule("web", 30, 3_000), rule("unseen", 7, 70)];
+        tracker.fill(&mut rules);
+        assert_eq!((rules[0].stats.pps, rules[0].stats.bps), (Some(10), Some(1_000)));
+        assert_eq!((rules[1].stats.pps, rules[1].stats.bps), (None, None));
+    }
+
+    #[test]
+    fn test_abort_alert() {
+        let mut alert = AbortAlert::new(3);
+
+        // 세 구간 연속이어야 경보, 경보는 한 번만
+        assert_eq!(alert.observe(2), None);
+        assert_eq!(alert.observe(1), None);
+        assert_eq!(alert.observe(4), Some(7));
+        assert_eq!(alert.observe(1), None);
+
+        // 중단 없는 구간이 오면 다시 센다
+        assert_eq!(alert.observe(0), None);
+        assert_eq!(alert.observe(5), None);
+        assert_eq!(alert.observe(0), None);
+        assert_eq!(alert.observe(1), None);
+        assert_eq!(alert.observe(1), None);
+        assert_eq!(alert.observe(1), Some(3));
+
+        // 0이면 경보 없음
+        let mut disabled = AbortAlert::new(0);
+        assert!((0..5).all(|_| disabled.observe(10).is_none()));
+    }
+
+    #[test]
+    fn test_aborted_rules() {
+        let mut rules = vec![rule("web", 10, 0), rule("lb-b", 10, 0), rule("lb-a", 10, 0), rule("lb-c", 10, 0)];
+        rules[1].stats.aborted_packets = 3;
+        rules[2].stats.aborted_packets = 3;
+        rules[3].stats.aborted_packets = 9;
+
+        assert_eq!(aborted_rules(&rules), ["lb-c", "lb-a", "lb-b"]);
+        assert!(aborted_rules(&rules[..1]).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_get_stats_after_collection() {
+        let key = 0u32.to_le_bytes();
+        let stats_map = MemoryMap::new();
+        stats_map.update(&key, &global_stats(1_000, 64_000, 600, 300, 50)).unwrap();
+        let hist_map = MemoryMap::new();
+        for index in 0..SIZE_BUCKET_COUNT as u32 {
+            hist_map.update(&index.to_le_bytes(), &(index as u64 * 10).to_le_bytes()).unwrap();
+        }
+        hist_map.update(&SIZE_HIST_SUM_INDEX.to_le_bytes(), &64_000u64.to_le_bytes()).unwrap();
+        let collector = TelemetryCollector::from_maps(&stats_map, Some(&hist_map), None, &DaemonConfig::default());
+
+        // 수집 전에는 비어 있음
+        assert_eq!(collector.get_stats().unwrap().total_packets, 0);
+
+        backdate(&collector, 2);
+        collector.collect_stats().await.unwrap();
+        let stats = collector.get_stats().unwrap();
+        assert_eq!((stats.total_packets, stats.total_bytes), (1_000, 64_000));
+        assert_eq!((stats.protocols.tcp, stats.protocols.udp, stats.protocols.other), (600, 300, 100));
+        assert_eq!(stats.size_histogram.counts.len(), SIZE_BUCKET_COUNT);
+        assert_eq!(stats.size_histogram.counts[2], 20);
+        assert_eq!(stats.size_histogram.sum, 64_000);
+
+        // 다음 수집은 직전 수집 이후의 변화로 초당 값을 계산 (2초 동안 2000개)
+        stats_map.update(&key, &global_stats(3_000, 192_000, 1_800, 900, 250)).unwrap();
+        backdate(&collector, 2);
+        collector.collect_stats().await.unwrap();
+        let stats = collector.get_stats().unwrap();
+        assert_eq!(stats.total_packets, 3_000);
+        assert!((990..=1_000).contains(&stats.packets_per_sec), "pps {}", stats.packets_per_sec);
+        assert!((99..=100).contains(&collector.syn_packets_per_sec().unwrap()));
+
+        // 최소 간격 안에서는 다시 수집하지 않음
+        stats_map.update(&key, &global_stats(9_000, 576_000, 0, 0, 0)).unwrap();
+        collector.collect_stats().await.unwrap();
+        assert_eq!(collector.get_stats().unwrap().total_packets, 3_000);
+    }
+
+    #[tokio::test]
+    async fn test_tick_samples_daemon_process() {
+        let stats_map = MemoryMap::new();
+        stats_map.update(&0u32.to_le_bytes(), &global_stats(100, 6_400, 100, 0, 0)).unwrap();
+        let filter_rules = MemoryMap::new();
+        let map_manager = Mutex::new(MapManager::with_backends(MapBackends {
+            filter_rules: Some(&filter_rules),
+            ..Default::default()
+        }));
+        let collector = TelemetryCollector::from_maps(&stats_map, None, None, &DaemonConfig::default());
+        assert_eq!(collector.get_stats().unwrap().daemon.rss_bytes, 0);
+
+        // 수집 주기마다 /proc/self에서 데몬 프로세스 통계를 읽음
+        backdate(&collector, 1);
+        collector.tick(&map_manager).await.unwrap();
+        let daemon = collector.get_stats().unwrap().daemon;
+        assert!(daemon.rss_bytes > 0 && daemon.open_fds > 0, "{:?}", daemon);
+
+        let text = collector.prometheus_metrics(&map_manager.lock().unwrap()).unwrap();
+        assert!(text.contains(&format!("swift_guard_daemon_resident_memory_bytes {}", daemon.rss_bytes)), "{}", text);
+    }
+
+    #[test]
+    fn test_prometheus_includes_api_metrics() {
+        let stats_map = MemoryMap::new();
+        let filter_rules = MemoryMap::new();
+        let map_manager = MapManager::with_backends(MapBackends {
+            filter_rules: Some(&filter_rules),
+            ..Default::default()
+        });
+        let metrics = Arc::new(ApiMetrics::new());
+        let collector = TelemetryCollector::from_maps(&stats_map, None, None, &DaemonConfig::default())
+            .with_api_metrics(Arc::clone(&metrics));
+
+        // API 서버가 기록한 요청이 통계와 내보내기에 보임
+        metrics.record("ping", Duration::from_micros(40), false);
+        metrics.record("add_rule", Duration::from_micros(900), true);
+        let api = collector.get_stats().unwrap().api;
+        assert_eq!((api.requests_total, api.errors_total), (2, 1));
+        let text = collector.prometheus_metrics(&map_manager).unwrap();
+        assert!(text.contains("swift_guard_api_requests_total{kind=\"ping\"} 1"), "{}", text);
+        assert!(text.contains("swift_guard_api_errors_total{kind=\"add_rule\"} 1"), "{}", text);
+    }
+
+    #[tokio::test]
+    async fn test_tick_tracks_rule_rates() {
+        let stats_map = MemoryMap::new();
+        let filter_rules = MemoryMap::new();
+        let map_manager = Mutex::new(MapManager::with_backends(MapBackends {
+            filter_rules: Some(&filter_rules),
+            ..Default::default()
+        }));
+        map_manager.lock().unwrap().add_rule(block_rule("block")).unwrap();
+        let events = EventDispatcher::with_clock_offset(0);
+        let collector = TelemetryCollector::from_maps(&stats_map, None, None, &DaemonConfig::default())
+            .with_events(events.clone());
+
+        // 첫 주기는 기준값, 다음 주기부터 매치율과 RuleMatched 이벤트
+        for packets in [0, 2_000] {
+            set_rule_counters(&filter_rules, packets, packets * 100);
+            if let Some(last) = &mut collector.rule_rates.lock().unwrap().1 {
+                *last -= Duration::from_secs(2);
+            }
+            collector.tick(&map_manager).await.unwrap();
+        }
+
+        let mut rules = map_manager.lock().unwrap().list_rules(true).unwrap();
+        collector.fill_rule_rates(&mut rules).unwrap();
+        let pps = rules[0].stats.pps.unwrap();
+        assert!((990..=1_000).contains(&pps), "pps {}", pps);
+        let matched: Vec<_> = events.history().query(None, 10, &["rule_matched".to_string()]).events.into_iter()
+            .filter_map(|event| match event.event {
+                DaemonEvent::RuleMatched(event) => Some((event.label, event.packets)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(matched, [("block".to_string(), 2_000)]);
+    }
+
+    #[tokio::test]
+    async fn test_tick_records_hit_history() {
+        let stats_map = MemoryMap::new();
+        let filter_rules = MemoryMap::new();
+        let map_manager = Mutex::new(MapManager::with_backends(MapBackends {
+            filter_rules: Some(&filter_rules),
+            ..Default::default()
+        }));
+        map_manager.lock().unwrap().add_rule(block_rule("block")).unwrap();
+        let collector = TelemetryCollector::from_maps(&stats_map, None, None, &DaemonConfig::default());
+
+        // 매치 전에는 기록이 없고, 주기마다 늘어난 패킷 수를 분 단위로 쌓음
+        for packets in [0, 1_500, 2_000] {
+            set_rule_counters(&filter_rules, packets, packets * 100);
+            if let Some(last) = &mut collector.rule_rates.lock().unwrap().1 {
+                *last -= Duration::from_secs(1);
+            }
+            collector.tick(&map_manager).await.unwrap();
+            if packets == 0 {
+                assert!(collector.rule_history("block", swift_guard::utils::current_time_secs()).unwrap().is_empty());
+            }
+        }
+        let history = collector.rule_history("block", swift_guard::utils::current_time_secs()).unwrap();
+        assert_eq!((history.len(), history.iter().sum::<u64>()), (60, 2_000));
+
+        // 삭제한 규칙의 기록은 다음 주기에 해제
+        map_manager.lock().unwrap().delete_rule("block").unwrap();
+        collector.tick(&map_manager).await.unwrap();
+        assert!(collector.rule_history("block", swift_guard::utils::current_time_secs()).unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_stats_include_webhook_metrics() {
+        let stats_map = MemoryMap::new();
+        let filter_rules = MemoryMap::new();
+        let map_manager = MapManager::with_backends(MapBackends {
+            filter_rules: Some(&filter_rules),
+            ..Default::default()
+        });
+        let metrics = Arc::new(WebhookMetrics::new());
+        let collector = TelemetryCollector::from_maps(&stats_map, None, None, &DaemonConfig::default())
+            .with_webhook_metrics(Arc::clone(&metrics));
+
+        // 받는 쪽이 없는 웹훅은 기한이 지나 실패로 집계
+        let options = WebhookOptions {
+            request_timeout: Duration::from_millis(100),
+            initial_backoff: Duration::from_millis(10),
+            max_backoff: Duration::from_millis(10),
+            retry_deadline: Duration::from_millis(50),
+            ..WebhookOptions::default()
+        };
+        let (sender, task) = WebhookSender::spawn("http://127.0.0.1:1/hook", options, metrics).unwrap();
+        sender.send(DaemonEvent::RuleMatched(RuleMatchedEvent {
+            label: "block".to_string(),
+            packets: 10,
+            bytes: 1_000,
+            pps: 5,
+            bps: 500,
+            timestamp: 1700000000,
+        }));
+        drop(sender);
+        task.await.unwrap();
+
+        assert_eq!(collector.get_stats().unwrap().webhooks.failed, 1);
+        let text = collector.prometheus_metrics(&map_manager).unwrap();
+        assert!(text.contains("swift_guard_webhook_events_total{outcome=\"failed\"} 1"), "{}", text);
+    }
+
+    #[tokio::test]
+    async fn test_tick_enforces_quota() {
+        let stats_map = MemoryMap::new();
+        let filter_rules = MemoryMap::new();
+        let map_manager = Mutex::new(MapManager::with_backends(MapBackends {
+            filter_rules: Some(&filter_rules),
+            ..Default::default()
+        }));
+        let quota = RuleQuota { bytes: Some(10_000), packets: None, action: ActionType::Drop as u8 };
+        map_manager.lock().unwrap().add_rule(FilterRule {
+            action: ActionType::Pass as u8,
+            quota: Some(quota),
+            ..block_rule("backup")
+        }).unwrap();
+        let collector = TelemetryCollector::from_maps(&stats_map, None, None, &DaemonConfig::default())
+            .with_quota(QuotaTracker::new(0).unwrap());
+
+        // 할당량 안에서는 원래 액션, 넘으면 할당량 액션
+        for (bytes, exceeded) in [(5_000, false), (20_000, true)] {
+            set_rule_counters(&filter_rules, bytes / 100, bytes);
+            backdate(&collector, 1);
+            collector.tick(&map_manager).await.unwrap();
+            let rules = map_manager.lock().unwrap().list_rules(true).unwrap();
+            assert_eq!(rules[0].quota_exceeded, exceeded, "{} bytes", bytes);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tick_expires_rules() {
+        let stats_map = MemoryMap::new();
+        let filter_rules = MemoryMap::new();
+        let map_manager = Mutex::new(MapManager::with_backends(MapBackends {
+            filter_rules: Some(&filter_rules),
+            ..Default::default()
+        }));
+        let now = swift_guard::utils::current_time_secs();
+        let mut expired = FilterRule { expire: 60, creation_time: now - 100, ..block_rule("temp") };
+        expired.src_ip = Some((0xC0A8010B, 32));
+        let expiring = FilterRule { expire: 120, creation_time: now, ..block_rule("soon") };
+        for rule in [expired, expiring] {
+            map_manager.lock().unwrap().add_rule(rule).unwrap();
+        }
+        let events = EventDispatcher::with_clock_offset(0);
+        let collector = TelemetryCollector::from_maps(&stats_map, None, None, &DaemonConfig::default())
+            .with_events(events.clone())
+            .with_expiry(ExpiryNotifier::new(300),
+                OwnerWebhooks::new(WebhookOptions::default(), Arc::new(WebhookMetrics::new())));
+
+        // 만료된 규칙은 알린 뒤 삭제하고, 만료가 가까운 규칙은 예고만
+        backdate(&collector, 1);
+        collector.tick(&map_manager).await.unwrap();
+        let labels: Vec<String> = map_manager.lock().unwrap().rules().iter().map(|rule| rule.label.clone()).collect();
+        assert_eq!(labels, ["soon"]);
+        let kinds = ["rule_expired".to_string(), "rule_expiring".to_string()];
+        let notified: Vec<_> = events.history().query(None, 10, &kinds).events.into_iter()
+            .filter_map(|event| match event.event {
+                DaemonEvent::RuleExpired(event) => Some(("expired", event.rule.label)),
+                DaemonEvent::RuleExpiring(event) => Some(("expiring", event.rule.label)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(notified, [("expiring", "soon".to_string()), ("expired", "temp".to_string())]);
+    }
+
+    #[tokio::test]
+    async fn test_tick_raises_abort_alert() {
+        let key = 0u32.to_le_bytes();
+        let stats_map = MemoryMap::new();
+        let filter_rules = MemoryMap::new();
+        let map_manager = Mutex::new(MapManager::with_backends(MapBackends {
+            filter_rules: Some(&filter_rules),
+            ..Default::default()
+        }));
+        map_manager.lock().unwrap().add_rule(block_rule("lb")).unwrap();
+        let events = EventDispatcher::with_clock_offset(0);
+        let collector = TelemetryCollector::from_maps(&stats_map, None, None, &DaemonConfig::default())
+            .with_events(events.clone());
+
+        // 전역과 규칙 항목의 중단 카운터가 세 구간 연속 늘면 xdp_aborted 이벤트 한 번
+        for aborted in [0u64, 2, 4, 6, 8] {
+            let mut global = global_stats(100, 6_400, 100, 0, 0);
+            global[64..72].copy_from_slice(&aborted.to_le_bytes());
+            stats_map.update(&key, &global).unwrap();
+            for rule_key in filter_rules.keys() {
+                let mut value = filter_rules.lookup(&rule_key).unwrap().unwrap();
+                let stats = value.len() - FilterStats::SIZE;
+                value[stats + 40..stats + 48].copy_from_slice(&aborted.to_le_bytes());
+                filter_rules.update(&rule_key, &value).unwrap();
+            }
+            backdate(&collector, 1);
+            collector.tick(&map_manager).await.unwrap();
+        }
+
+        assert_eq!(collector.stats.lock().unwrap().aborted_packets, 8);
+        let alerts: Vec<_> = events.history().query(None, 10, &["xdp_aborted".to_string()]).events.into_iter()
+            .filter_map(|event| match event.event {
+                DaemonEvent::XdpAborted(event) => Some((event.aborted, event.total, event.intervals, event.rules)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(alerts, [(6, 6, 3, vec!["lb".to_string()])]);
+    }
+
+    #[tokio::test]
+    async fn test_tick_flags_traffic_anomaly() {
+        let key = 0u32.to_le_bytes();
+        let stats_map = MemoryMap::new();
+        let map_manager = Mutex::new(MapManager::with_backends(MapBackends::default()));
+        let events = EventDispatcher::with_clock_offset(0);
+        let collector = TelemetryCollector::from_maps(&stats_map, None, None, &DaemonConfig::default())
+            .with_events(events.clone());
+
+        // 기준선을 쌓는 동안은 판정하지 않고, 쌓인 뒤 급증한 구간만 이상
+        let mut packets = 0;
+        for pps in std::iter::repeat_n(1_000, crate::baseline::WARMUP_SAMPLES as usize + 2).chain([50_000, 1_000]) {
+            packets += pps;
+            stats_map.update(&key, &global_stats(packets, packets * 64, packets, 0, 0)).unwrap();
+            backdate(&collector, 1);
+            collector.tick(&map_manager).await.unwrap();
+            assert_eq!(collector.stats.lock().unwrap().anomaly, pps > 1_000, "{} pps", pps);
+        }
+
+        // 이상 구간마다 이벤트 한 번
+        let anomalies: Vec<_> = events.history().query(None, 10, &["anomaly".to_string()]).events.into_iter()
+            .filter_map(|event| match event.event {
+                DaemonEvent::Anomaly(event) => Some(event),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(anomalies.len(), 1);
+        assert!(anomalies[0].metrics.contains(&"pps".to_string()), "{:?}", anomalies[0]);
+        assert!(anomalies[0].pps > 40_000 && anomalies[0].baseline.pps_mean < 2_000.0, "{:?}", anomalies[0]);
+    }
+
+    #[tokio::test]
+    async fn test_tick_renders_stats_log_line() {
+        let key = 0u32.to_le_bytes();
+        let stats_map = MemoryMap::new();
+        let filter_rules = MemoryMap::new();
+        let map_manager = Mutex::new(MapManager::with_backends(MapBackends {
+            filter_rules: Some(&filter_rules),
+            ..Default::default()
+        }));
+        map_manager.lock().unwrap().add_rule(block_rule("block")).unwrap();
+        let mut config = DaemonConfig::default();
+        config.telemetry.stats_log_format = StatsLogFormat::parse("pps={pps} drops={dropped}").unwrap();
+        let collector = TelemetryCollector::from_maps(&stats_map, None, None, &config);
+
+        // 액션별 합계는 같은 주기에 수집한 규칙 카운터에서
+        stats_map.update(&key, &global_stats(0, 0, 0, 0, 0)).unwrap();
+        backdate(&collector, 1);
+        collector.tick(&map_manager).await.unwrap();
+        stats_map.update(&key, &global_stats(500, 32_000, 500, 0, 0)).unwrap();
+        set_rule_counters(&filter_rules, 300, 19_200);
+        backdate(&collector, 1);
+        collector.tick(&map_manager).await.unwrap();
+
+        let line = collector.stats_log_line(&collector.stats.lock().unwrap()).unwrap();
+        let pps: u64 = line.strip_prefix("pps=").and_then(|rest| rest.split(' ').next()).unwrap().parse().unwrap();
+        assert!((490..=500).contains(&pps), "{}", line);
+        assert!(line.ends_with(" drops=300"), "{}", line);
+
+        // log_stats가 꺼져 있으면 남기지 않음
+        config.telemetry.log_stats = false;
+        let quiet = TelemetryCollector::from_maps(&stats_map, None, None, &config);
+        assert_eq!(quiet.stats_log_line(&quiet.stats.lock().unwrap()), None);
+    }
+
+    #[tokio::test]
+    async fn test_tick_installs_synflood_rule() {
+        let key = 0u32.to_le_bytes();
+        let stats_map = MemoryMap::new();
+        let syn_map = MemoryMap::new();
+        let filter_rules = MemoryMap::new();
+        let map_manager = Mutex::new(MapManager::with_backends(MapBackends {
+            filter_rules: Some(&filter_rules),
+            ..Default::default()
+        }));
+        let config = DaemonConfig {
+            mitigation: MitigationConfig {
+                enabled: true,
+                syn_pps_threshold: 1000,
+                consecutive_intervals: 2,
+                ..MitigationConfig::default()
+            },
+            ..DaemonConfig::default()
+        };
+        let collector = TelemetryCollector::from_maps(&stats_map, None, Some(&syn_map), &config)
+            .with_mitigation(SynFloodDetector::new(&config.mitigation));
+
+        // 첫 주기는 기준값, 이후 두 주기 연속 초과하면 자동 규칙 설치
+        let attacker = 0xC0A8010Au32; // 192.168.1.10
+        for (round, syn) in [0u64, 5_000, 10_000, 15_000].into_iter().enumerate() {
+            stats_map.update(&key, &global_stats(syn, syn * 60, syn, 0, syn)).unwrap();
+            syn_map.update(&attacker.to_be_bytes(), &syn.to_le_bytes()).unwrap();
+            backdate(&collector, 1);
+            collector.tick(&map_manager).await.unwrap();
+            assert_eq!(map_manager.lock().unwrap().has_rule("auto-synflood-192.168.1.10"), round >= 2,
+                "round {}", round);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tick_reports_rule_match_to_syslog() {
+        let receiver = tokio::net::UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let config = DaemonConfig {
+            logging: LoggingConfig {
+                syslog: SyslogConfig {
+                    enabled: true,
+                    address: format!("udp://{}", receiver.local_addr().unwrap()),
+                    rule_match_pps_threshold: 100,
+                    ..SyslogConfig::default()
+                },
+            },
+            ..DaemonConfig::default()
+        };
+        let stats_map = MemoryMap::new();
+        let filter_rules = MemoryMap::new();
+        let map_manager = Mutex::new(MapManager::with_backends(MapBackends {
+            filter_rules: Some(&filter_rules),
+            ..Default::default()
+        }));
+        map_manager.lock().unwrap().add_rule(block_rule("block")).unwrap();
+        let sink = SyslogSink::start(&config.logging.syslog).unwrap().unwrap();
+        let collector = TelemetryCollector::from_maps(&stats_map, None, None, &config).with_syslog(sink);
+
+        // 첫 주기는 기준값, 다음 주기에 임계값을 넘으면 보고
+        for packets in [0, 10_000] {
+            set_rule_counters(&filter_rules, packets, packets * 100);
+            backdate(&collector, 1);
+            collector.tick(&map_manager).await.unwrap();
+        }
+
+        let mut buf = [0u8; 1024];
+        let len = time::timeout(Duration::from_secs(5), receiver.recv(&mut buf)).await.unwrap().unwrap();
+        let message = String::from_utf8_lossy(&buf[..len]);
+        assert!(message.contains("RULE_MATCH") && message.contains("label=\"block\""), "{}", message);
+    }
 }
@@ -3,13 +3,42 @@
 
 use anyhow::{anyhow, Context, Result};
 use log::{debug, error, info, warn};
+use sha2::{Digest, Sha256};
 use std::fs::File;
 use std::io::Read;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::net::IpAddr;
 use std::path::{Path, PathBuf};
-use std::sync::{Arc, Mutex};
-use std::time::Duration;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use wasmtime::*;
 
+use crate::capture::{CaptureReason, CaptureSink};
+use crate::config::{OverloadPolicy, WasmConfig};
+use crate::error::DaemonError;
+use crate::inspectctx::{PacketContext, CONTEXT_SIZE};
+use crate::logthrottle;
+use crate::syslog::{SecurityEvent, SyslogSink};
+use swift_guard::api::{WasmErrorPolicy, WasmLogLine, WasmModuleInfo, WasmModuleMode};
+use swift_guard::error::InvalidArgument;
+use swift_guard::histogram::{LatencyHistogram, LatencySnapshot};
+use swift_guard::types::WASM_LATENCY_BUCKET_BOUNDS_US;
+use swift_guard::utils;
+
+/// 기본 검사 대기열 크기 (패킷 수)
+pub const DEFAULT_QUEUE_SIZE: usize = 1024;
+
+/// 모듈별 기본 보관 로그 줄 수
+pub const DEFAULT_LOG_LINES: usize = 1000;
+
+/// 로그 한 줄의 최대 길이 (바이트, 넘는 부분은 버림)
+const MAX_LOG_LINE_LEN: usize = 1024;
+
+/// 모듈 설정의 최대 크기 (바이트)
+pub const MAX_MODULE_CONFIG_LEN: usize = 64 * 1024;
+
 /// WASM 모듈 상태
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ModuleState {
@@ -18,15 +47,32 @@ pub enum ModuleState {
     /// 로드됨
     Loaded,
     /// 실행 중
+    #[allow(dead_code)]
     Running,
     /// 일시 중지됨
+    #[allow(dead_code)]
     Paused,
     /// 오류 발생
     Error,
 }
 
+impl ModuleState {
+    /// API 응답용 이름
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ModuleState::Initialized => "initialized",
+            ModuleState::Loaded => "loaded",
+            ModuleState::Running => "running",
+            ModuleState::Paused => "paused",
+            ModuleState::Error => "error",
+        }
+    }
+}
+
 /// WASM 검사 모듈
-//#[derive(Debug)]
+///
+/// 컴파일된 모듈과 통계를 모든 워커가 공유한다. 실행 상태(스토어, 인스턴스)는
+/// 워커마다 `InspectorInstance`로 따로 가진다.
 pub struct WasmInspector {
     /// 모듈 ID
     id: String,
@@ -36,40 +82,183 @@ pub struct WasmInspector {
     state: ModuleState,
     /// wasmtime 엔진
     engine: Engine,
-    /// wasmtime 스토어
-    store: Option<Store<WasmInspectorData>>,
-    /// wasmtime 인스턴스
-    instance: Option<Instance>,
-    /// 처리된 패킷 수
-    processed_packets: u64,
-    /// 차단된 패킷 수
-    blocked_packets: u64,
+    /// 컴파일된 모듈
+    module: Option<Module>,
+    /// 로드 시각 (UNIX 초)
+    loaded_at: u64,
+    /// 로드 순서 (검사 순서)
+    seq: u64,
+    /// 검사 오류 시 처리
+    on_error: WasmErrorPolicy,
+    /// 오류로 비활성화됨 (재개할 때까지 검사에서 제외)
+    disabled: AtomicBool,
+    /// shadow 모드 (검사는 하지만 판정을 적용하지 않음, 검사 중에도 바꿀 수 있음)
+    shadow: AtomicBool,
+    /// 마지막 검사 오류
+    last_error: Mutex<Option<String>>,
+    /// 모듈이 남긴 로그 (모든 워커 인스턴스 공유)
+    log: Arc<ModuleLog>,
+    /// 워커 인스턴스별 통계
+    workers: Mutex<Vec<Arc<WorkerStats>>>,
+    /// 모듈 설정 (`read_config`로 전달, 없으면 빈 값)
+    config: Arc<Vec<u8>>,
 }
 
 /// WASM 모듈 컨텍스트 데이터
 #[derive(Debug)]
 pub struct WasmInspectorData {
     /// 메모리 버퍼
+    #[allow(dead_code)]
     memory_buffer: Vec<u8>,
-    /// 패킷 데이터
-    packet_data: Vec<u8>,
     /// 패킷 길이
     packet_len: usize,
     /// 결과 버퍼
+    #[allow(dead_code)]
     result_buffer: Vec<u8>,
-    /// 로그 버퍼
+    /// 현재 패킷을 검사하는 동안 남긴 로그 (차단 사유용)
     log_buffer: String,
+    /// 모듈 로그
+    module_log: Arc<ModuleLog>,
+    /// 모듈 설정
+    config: Arc<Vec<u8>>,
+}
+
+/// 모듈 로그 (최근 줄만 보관하는 링)
+///
+/// 여러 줄 메시지는 줄 단위로 나눠 저장하고, 가득 차면 가장 오래된 줄을 통째로
+/// 버린다. 검사 경로의 기록과 API의 조회는 내부 잠금으로 직렬화한다.
+#[derive(Debug)]
+pub struct ModuleLog {
+    /// 보관할 최대 줄 수
+    capacity: usize,
+    /// 보관 중인 줄과 다음 순번
+    inner: Mutex<(VecDeque<WasmLogLine>, u64)>,
+}
+
+impl ModuleLog {
+    /// 최대 `capacity`줄을 보관하는 로그 생성
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            inner: Mutex::new((VecDeque::new(), 1)),
+        }
+    }
+    
+    /// 메시지 기록 (줄 단위로 나눔)
+    pub fn push(&self, message: &str, timestamp_ms: u64) {
+        let mut inner = match self.inner.lock() {
+            Ok(inner) => inner,
+            Err(_) => return,
+        };
+        let (lines, next_seq) = &mut *inner;
+        
+        for line in message.lines() {
+            let mut end = line.len().min(MAX_LOG_LINE_LEN);
+            while !line.is_char_boundary(end) {
+                end -= 1;
+            }
+            
+            if lines.len() == self.capacity {
+                lines.pop_front();
+            }
+            lines.push_back(WasmLogLine { seq: *next_seq, timestamp_ms, message: line[..end].to_string() });
+            *next_seq += 1;
+        }
+    }
+    
+    /// 최근 줄 조회 (`after` 순번 이후, 최대 `count`줄, 0이면 전체)
+    pub fn tail(&self, count: usize, after: Option<u64>) -> Vec<WasmLogLine> {
+        let inner = match self.inner.lock() {
+            Ok(inner) => inner,
+            Err(_) => return Vec::new(),
+        };
+        
+        let newer: Vec<&WasmLogLine> = inner.0.iter()
+            .filter(|line| after.is_none_or(|after| line.seq > after))
+            .collect();
+        let skip = if count == 0 { 0 } else { newer.len().saturating_sub(count) };
+        newer[skip..].iter().map(|line| (*line).clone()).collect()
+    }
+}
+
+/// 현재 시각 (UNIX 밀리초)
+fn now_ms() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_millis() as u64).unwrap_or(0)
+}
+
+/// 워커 인스턴스 하나의 통계
+#[derive(Debug)]
+struct WorkerStats {
+    /// 처리된 패킷 수
+    processed_packets: AtomicU64,
+    /// 차단된 패킷 수
+    blocked_packets: AtomicU64,
+    /// shadow 모드에서 차단했을 패킷 수
+    would_block: AtomicU64,
+    /// 검사에 쓴 시간 (나노초)
+    busy_ns: AtomicU64,
+    /// 검사 오류 수
+    errors: AtomicU64,
+    /// 패킷별 검사 시간 분포
+    latency: LatencyHistogram,
+}
+
+impl Default for WorkerStats {
+    fn default() -> Self {
+        Self {
+            processed_packets: AtomicU64::new(0),
+            blocked_packets: AtomicU64::new(0),
+            would_block: AtomicU64::new(0),
+            busy_ns: AtomicU64::new(0),
+            errors: AtomicU64::new(0),
+            latency: LatencyHistogram::new(&WASM_LATENCY_BUCKET_BOUNDS_US),
+        }
+    }
+}
+
+impl WorkerStats {
+    /// 모든 카운터와 검사 시간 분포를 0으로
+    fn reset(&self) {
+        for counter in [&self.processed_packets, &self.blocked_packets, &self.would_block, &self.busy_ns, &self.errors] {
+            counter.store(0, Ordering::Relaxed);
+        }
+        self.latency.reset();
+    }
+}
+
+/// 모듈 통계 (모든 워커의 합)
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModuleStats {
+    /// 모듈 상태
+    pub state: ModuleState,
+    /// 처리된 패킷 수
+    pub processed_packets: u64,
+    /// 차단된 패킷 수
+    pub blocked_packets: u64,
+    /// 패킷당 평균 검사 시간 (마이크로초)
+    pub avg_processing_time_us: f64,
+    /// 검사 오류 수
+    pub errors: u64,
+    /// 마지막 검사 오류
+    pub last_error: Option<String>,
+    /// 패킷별 검사 시간 분포 (`types::WASM_LATENCY_BUCKET_BOUNDS_US` 버킷)
+    pub latency: LatencySnapshot,
+    /// 판정 적용 방식
+    pub mode: WasmModuleMode,
+    /// shadow 모드에서 차단했을 패킷 수
+    pub would_block_packets: u64,
 }
 
 // Debug 구현
 impl std::fmt::Debug for WasmInspector {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (processed_packets, blocked_packets) = self.stats();
         f.debug_struct("WasmInspector")
             .field("id", &self.id)
             .field("path", &self.path)
             .field("state", &self.state)
-            .field("processed_packets", &self.processed_packets)
-            .field("blocked_packets", &self.blocked_packets)
+            .field("processed_packets", &processed_packets)
+            .field("blocked_packets", &blocked_packets)
             .finish()
     }
 }
@@ -84,16 +273,25 @@ impl WasmInspector {
             path: path.to_path_buf(),
             state: ModuleState::Initialized,
             engine,
-            store: None,
-            instance: None,
-            processed_packets: 0,
-            blocked_packets: 0,
+            module: None,
+            loaded_at: 0,
+            seq: 0,
+            on_error: WasmErrorPolicy::Pass,
+            disabled: AtomicBool::new(false),
+            shadow: AtomicBool::new(false),
+            last_error: Mutex::new(None),
+            log: Arc::new(ModuleLog::new(DEFAULT_LOG_LINES)),
+            workers: Mutex::new(Vec::new()),
+            config: Arc::new(Vec::new()),
         })
     }
     
-    /// 모듈 로드
-    pub fn load(&mut self) -> Result<()> {
+    /// 모듈 로드 (컴파일 후 시험 인스턴스로 검증)
+    ///
+    /// 캐시가 있으면 같은 내용으로 컴파일해 둔 모듈을 재사용한다.
+    pub fn load(&mut self, cache: Option<&ModuleCache>) -> Result<LoadReport> {
         debug!("Loading WASM module: {}", self.path.display());
+        let started = Instant::now();
         
         // WASM 파일 읽기
         let mut file = File::open(&self.path)
@@ -103,212 +301,2051 @@ impl WasmInspector {
         file.read_to_end(&mut wasm_bytes)
             .context("Failed to read WASM file")?;
         
-        // 모듈 및 인스턴스 생성
-        let module = Module::new(&self.engine, wasm_bytes)
-            .context("Failed to compile WASM module")?;
+        let (module, cached) = match cache {
+            Some(cache) => cache.compile(&self.engine, &wasm_bytes)?,
+            None => {
+                let module = Module::new(&self.engine, wasm_bytes)
+                    .context("Failed to compile WASM module")?;
+                (module, false)
+            },
+        };
+        
+        // 워커가 인스턴스를 만들 때 실패하지 않도록 미리 한 번 생성
+        instantiate(&self.engine, &module, Arc::clone(&self.log), Arc::clone(&self.config))?;
+        
+        self.module = Some(module);
+        self.state = ModuleState::Loaded;
+        self.loaded_at = utils::current_time_secs();
+        
+        let report = LoadReport { elapsed: started.elapsed(), cached };
+        info!("WASM module loaded: {} ({})", self.id, report);
+        Ok(report)
+    }
+    
+    /// 상태 획득 (오류로 비활성화되었으면 Error)
+    pub fn state(&self) -> ModuleState {
+        if self.is_disabled() { ModuleState::Error } else { self.state }
+    }
+    
+    /// 오류로 비활성화되었는지 여부
+    pub fn is_disabled(&self) -> bool {
+        self.disabled.load(Ordering::Relaxed)
+    }
+    
+    /// 판정 적용 방식
+    pub fn mode(&self) -> WasmModuleMode {
+        if self.shadow.load(Ordering::Relaxed) { WasmModuleMode::Shadow } else { WasmModuleMode::Enforce }
+    }
+    
+    /// 판정 적용 방식 변경 (다음 패킷부터 적용, 통계는 유지)
+    pub fn set_mode(&self, mode: WasmModuleMode) {
+        self.shadow.store(mode == WasmModuleMode::Shadow, Ordering::Relaxed);
+    }
+    
+    /// 보관할 로그 줄 수 설정 (로드 전)
+    pub fn set_log_lines(&mut self, lines: usize) {
+        self.log = Arc::new(ModuleLog::new(lines));
+    }
+    
+    /// 모듈 로그
+    pub fn log(&self) -> &ModuleLog {
+        &self.log
+    }
+    
+    /// 모듈 설정 지정 (로드 전, 모듈이 `init`에서 읽음)
+    pub fn set_config(&mut self, config: Vec<u8>) {
+        self.config = Arc::new(config);
+    }
+    
+    /// 검사 오류 시 처리 설정
+    pub fn set_error_policy(&mut self, policy: WasmErrorPolicy) {
+        self.on_error = policy;
+    }
+    
+    /// 검사 오류 기록 후 정책 적용 (차단해야 하면 true)
+    ///
+    /// disable_module 정책이면 처음 오류를 낸 워커가 모듈을 비활성화한다.
+    fn handle_error(&self, error: &anyhow::Error) -> bool {
+        let message = format!("{:#}", error);
+        if let Ok(mut last) = self.last_error.lock() {
+            *last = Some(message.clone());
+        }
         
-        let mut store = Store::new(
-            &self.engine,
-            WasmInspectorData {
-                memory_buffer: Vec::new(),
-                packet_data: Vec::new(),
-                packet_len: 0,
-                result_buffer: Vec::new(),
-                log_buffer: String::new(),
+        match self.on_error {
+            WasmErrorPolicy::Pass => false,
+            WasmErrorPolicy::Drop => true,
+            WasmErrorPolicy::DisableModule => {
+                if !self.disabled.swap(true, Ordering::Relaxed) {
+                    error!("Disabling WASM module {} after inspection error: {}", self.id, message);
+                }
+                false
             },
-        );
+        }
+    }
+    
+    /// 비활성화된 모듈 재개 (비활성화 상태가 아니면 false)
+    fn resume(&self) -> bool {
+        self.disabled.swap(false, Ordering::Relaxed)
+    }
+    
+    /// 통계 획득 (모든 워커의 합)
+    pub fn stats(&self) -> (u64, u64) {
+        self.worker_stats().into_iter()
+            .fold((0, 0), |(processed, blocked), (p, b)| (processed + p, blocked + b))
+    }
+    
+    /// 평균 검사 시간을 포함한 통계 획득
+    pub fn module_stats(&self) -> ModuleStats {
+        let (processed_packets, blocked_packets) = self.stats();
+        let mut latency = LatencyHistogram::new(&WASM_LATENCY_BUCKET_BOUNDS_US).snapshot();
+        let (busy_ns, errors) = match self.workers.lock() {
+            Ok(workers) => workers.iter().fold((0, 0), |(busy, errors), stats| {
+                latency.merge(&stats.latency.snapshot());
+                (busy + stats.busy_ns.load(Ordering::Relaxed), errors + stats.errors.load(Ordering::Relaxed))
+            }),
+            Err(_) => (0, 0),
+        };
+        let would_block_packets = self.would_block_packets();
         
-        // WASM에 노출할 호스트 함수 정의
-        let log_func = Func::wrap(&mut store, |mut caller: Caller<'_, WasmInspectorData>, ptr: i32, len: i32| -> i32 {
-            let mem = match caller.get_export("memory") {
-                Some(Extern::Memory(mem)) => mem,
-                _ => return -1,
-            };
-            
-            let data = match mem.data(&caller).get(ptr as usize..(ptr + len) as usize) {
-                Some(data) => data,
-                None => return -1,
-            };
-            
-            let message = match std::str::from_utf8(data) {
-                Ok(s) => s,
-                Err(_) => return -1,
-            };
-            
-            info!("[WASM] {}", message);
+        ModuleStats {
+            state: self.state(),
+            processed_packets,
+            blocked_packets,
+            avg_processing_time_us: if processed_packets > 0 {
+                busy_ns as f64 / processed_packets as f64 / 1000.0
+            } else {
+                0.0
+            },
+            errors,
+            last_error: self.last_error.lock().ok().and_then(|last| last.clone()),
+            latency,
+            mode: self.mode(),
+            would_block_packets,
+        }
+    }
+    
+    /// shadow 모드에서 차단했을 패킷 수 (모든 워커의 합)
+    pub fn would_block_packets(&self) -> u64 {
+        match self.workers.lock() {
+            Ok(workers) => workers.iter().map(|stats| stats.would_block.load(Ordering::Relaxed)).sum(),
+            Err(_) => 0,
+        }
+    }
+    
+    /// 모든 워커의 카운터와 검사 시간 분포 초기화 (상태와 오류 기록은 유지)
+    pub fn reset_stats(&self) {
+        if let Ok(workers) = self.workers.lock() {
+            for stats in workers.iter() {
+                stats.reset();
+            }
+        }
+    }
+    
+    /// API 응답용 모듈 정보
+    pub fn info(&self) -> WasmModuleInfo {
+        WasmModuleInfo {
+            name: self.id.clone(),
+            state: self.state().as_str().to_string(),
+            loaded_at: self.loaded_at,
+            mode: self.mode(),
+            would_block_packets: self.would_block_packets(),
+        }
+    }
+    
+    /// 워커별 통계 획득 (인스턴스를 만든 워커만)
+    pub fn worker_stats(&self) -> Vec<(u64, u64)> {
+        let workers = match self.workers.lock() {
+            Ok(workers) => workers,
+            Err(_) => return Vec::new(),
+        };
+        
+        workers.iter()
+            .map(|stats| (
+                stats.processed_packets.load(Ordering::Relaxed),
+                stats.blocked_packets.load(Ordering::Relaxed),
+            ))
+            .collect()
+    }
+    
+    /// 모듈 ID 획득
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+}
+
+/// 패킷 링 슬롯 수
+pub const PACKET_SLOTS: u32 = 8;
+
+/// 패킷 링 슬롯 크기 (바이트, 더 긴 프레임은 잘라서 전달)
+pub const PACKET_SLOT_SIZE: u32 = 2048;
+
+/// 게스트 메모리로 패킷을 넘기는 방식
+enum Handoff {
+    /// `get_packet_buffer`가 돌려준 게스트 쪽 링에 직접 기록하고 슬롯 번호를 전달
+    Ring {
+        /// 링 시작 주소
+        base: u32,
+        /// 다음에 쓸 슬롯
+        next: u32,
+    },
+    /// 패킷마다 `allocate`로 받은 주소에 기록
+    Allocate(TypedFunc<i32, i32>),
+    /// 고정 오프셋(1024)에 기록
+    Fixed,
+}
+
+impl Handoff {
+    /// 모듈이 내보낸 함수에 따라 전달 방식 결정
+    fn negotiate(store: &mut Store<WasmInspectorData>, instance: &Instance, memory: &Memory) -> Result<Self> {
+        if let Ok(get_buffer) = instance.get_typed_func::<(), i32>(&mut *store, "get_packet_buffer") {
+            let base = get_buffer.call(&mut *store, ())
+                .context("Failed to call get_packet_buffer function")?;
             
-            caller.data_mut().log_buffer.push_str(message);
-            caller.data_mut().log_buffer.push('\n');
+            let end = (base as u32 as u64) + (PACKET_SLOTS * PACKET_SLOT_SIZE) as u64;
+            if base < 0 || end > memory.data_size(&*store) as u64 {
+                return Err(anyhow!("Packet buffer at {} does not fit {} slots of {} bytes in WASM memory",
+                    base, PACKET_SLOTS, PACKET_SLOT_SIZE));
+            }
             
-            0
-        });
+            return Ok(Handoff::Ring { base: base as u32, next: 0 });
+        }
         
-        // WASM 인스턴스 생성 및 링커 설정
-        let mut linker = Linker::new(&self.engine);
-//        linker.define("env", "log", log_func)
-        linker.define(&mut store, "env", "log", log_func)
-            .context("Failed to define host function: log")?;
+        match instance.get_typed_func::<i32, i32>(&mut *store, "allocate") {
+            Ok(alloc) => Ok(Handoff::Allocate(alloc)),
+            Err(_) => Ok(Handoff::Fixed),
+        }
+    }
+}
+
+impl std::fmt::Debug for Handoff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Handoff::Ring { base, .. } => write!(f, "packet ring at {}", base),
+            Handoff::Allocate(_) => write!(f, "allocate"),
+            Handoff::Fixed => write!(f, "fixed offset"),
+        }
+    }
+}
+
+/// 모듈이 내보낸 검사 함수
+enum InspectFunc {
+    /// `inspect_packet(패킷, 길이)`
+    V1(TypedFunc<(i32, i32), i32>),
+    /// `inspect_packet_v2(패킷, 길이, 문맥 주소)` (문맥은 `get_context_buffer`가 돌려준 주소에 기록)
+    V2 {
+        func: TypedFunc<(i32, i32, i32), i32>,
+        context: u32,
+    },
+}
+
+impl InspectFunc {
+    /// 내보낸 검사 함수 확인 (`inspect_packet_v2`가 있으면 우선)
+    fn lookup(store: &mut Store<WasmInspectorData>, instance: &Instance) -> Result<Self> {
+        if let Ok(func) = instance.get_typed_func::<(i32, i32, i32), i32>(&mut *store, "inspect_packet_v2") {
+            return Ok(InspectFunc::V2 { func, context: 0 });
+        }
         
-        let instance = linker.instantiate(&mut store, &module)
-            .context("Failed to instantiate WASM module")?;
+        instance.get_typed_func::<(i32, i32), i32>(&mut *store, "inspect_packet")
+            .map(InspectFunc::V1)
+            .context("WASM module has no inspect_packet function")
+    }
+    
+    /// v2 검사 함수의 문맥 버퍼 확인 (`init` 뒤에 호출)
+    fn bind_context(&mut self, store: &mut Store<WasmInspectorData>, instance: &Instance, memory: &Memory) -> Result<()> {
+        let InspectFunc::V2 { context, .. } = self else {
+            return Ok(());
+        };
         
-        // 메모리 획득
-        let memory = instance
-            .get_memory(&mut store, "memory")
-            .ok_or_else(|| anyhow!("WASM module has no exported memory"))?;
+        let get_buffer = instance.get_typed_func::<(), i32>(&mut *store, "get_context_buffer")
+            .context("WASM module exports inspect_packet_v2 but no get_context_buffer function")?;
+        let base = get_buffer.call(&mut *store, ())
+            .context("Failed to call get_context_buffer function")?;
         
-        // 초기화 함수 호출 (있는 경우)
-        if let Ok(init_func) = instance.get_typed_func::<(), ()>(&mut store, "init") {
-            init_func.call(&mut store, ())
-                .context("Failed to call init function")?;
-            debug!("WASM module initialized");
+        let end = (base as u32 as u64) + CONTEXT_SIZE as u64;
+        if base < 0 || end > memory.data_size(&*store) as u64 {
+            return Err(anyhow!("Context buffer at {} does not fit {} bytes in WASM memory", base, CONTEXT_SIZE));
         }
         
-        self.store = Some(store);
-        self.instance = Some(instance);
-        self.state = ModuleState::Loaded;
-        
-        info!("WASM module loaded: {}", self.id);
+        *context = base as u32;
         Ok(())
     }
+}
+
+impl std::fmt::Debug for InspectFunc {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InspectFunc::V1(_) => write!(f, "inspect_packet"),
+            InspectFunc::V2 { context, .. } => write!(f, "inspect_packet_v2 with context at {}", context),
+        }
+    }
+}
+
+/// 인스턴스화한 게스트와 검사에 쓰는 내보낸 항목
+struct Guest {
+    /// wasmtime 스토어
+    store: Store<WasmInspectorData>,
+    /// 게스트 메모리
+    memory: Memory,
+    /// 검사 함수
+    inspect_func: InspectFunc,
+    /// 패킷 전달 방식
+    handoff: Handoff,
+}
+
+/// 모듈 인스턴스 생성 (호스트 함수 연결 후 `init` 호출)
+fn instantiate(engine: &Engine, module: &Module, log: Arc<ModuleLog>, config: Arc<Vec<u8>>) -> Result<Guest> {
+    let mut store = Store::new(
+        engine,
+        WasmInspectorData {
+            memory_buffer: Vec::new(),
+            packet_len: 0,
+            result_buffer: Vec::new(),
+            log_buffer: String::new(),
+            module_log: log,
+            config,
+        },
+    );
+    
+    // WASM에 노출할 호스트 함수 정의
+    let log_func = Func::wrap(&mut store, |mut caller: Caller<'_, WasmInspectorData>, ptr: i32, len: i32| -> i32 {
+        let mem = match caller.get_export("memory") {
+            Some(Extern::Memory(mem)) => mem,
+            _ => return -1,
+        };
+        
+        let data = match mem.data(&caller).get(ptr as usize..(ptr + len) as usize) {
+            Some(data) => data,
+            None => return -1,
+        };
+        
+        let message = match std::str::from_utf8(data) {
+            Ok(s) => s.to_string(),
+            Err(_) => return -1,
+        };
+        
+        info!("[WASM] {}", message);
+        
+        let data = caller.data_mut();
+        data.module_log.push(&message, now_ms());
+        data.log_buffer.push_str(&message);
+        data.log_buffer.push('\n');
+        
+        0
+    });
     
-    /// 패킷 검사
-    pub fn inspect_packet(&mut self, packet: &[u8]) -> Result<bool> {
-        if self.state != ModuleState::Loaded && self.state != ModuleState::Running {
-            return Err(anyhow!("WASM module not loaded"));
+    // 모듈 설정 길이
+    let config_len_func = Func::wrap(&mut store, |caller: Caller<'_, WasmInspectorData>| -> i32 {
+        caller.data().config.len() as i32
+    });
+    
+    // 모듈 설정을 게스트 메모리로 복사 (최대 len 바이트, 복사한 바이트 수 반환)
+    let read_config_func = Func::wrap(&mut store, |mut caller: Caller<'_, WasmInspectorData>, ptr: i32, len: i32| -> i32 {
+        let mem = match caller.get_export("memory") {
+            Some(Extern::Memory(mem)) => mem,
+            _ => return -1,
+        };
+        if ptr < 0 || len < 0 {
+            return -1;
         }
         
-        let store = self.store.as_mut()
-            .ok_or_else(|| anyhow!("WASM store not initialized"))?;
+        let config = Arc::clone(&caller.data().config);
+        let count = config.len().min(len as usize);
+        match mem.write(&mut caller, ptr as usize, &config[..count]) {
+            Ok(()) => count as i32,
+            Err(_) => -1,
+        }
+    });
+    
+    // WASM 인스턴스 생성 및 링커 설정
+    let mut linker = Linker::new(engine);
+    linker.define(&mut store, "env", "log", log_func)
+        .context("Failed to define host function: log")?;
+    linker.define(&mut store, "env", "config_len", config_len_func)
+        .context("Failed to define host function: config_len")?;
+    linker.define(&mut store, "env", "read_config", read_config_func)
+        .context("Failed to define host function: read_config")?;
+    
+    let instance = linker.instantiate(&mut store, module)
+        .context("Failed to instantiate WASM module")?;
+    
+    // 메모리 획득
+    let memory = instance
+        .get_memory(&mut store, "memory")
+        .ok_or_else(|| anyhow!("WASM module has no exported memory"))?;
+    
+    // 검사 함수 획득
+    let mut inspect_func = InspectFunc::lookup(&mut store, &instance)?;
+    
+    // 초기화 함수 호출 (있는 경우)
+    if let Ok(init_func) = instance.get_typed_func::<(), ()>(&mut store, "init") {
+        init_func.call(&mut store, ())
+            .context("Failed to call init function")?;
+        debug!("WASM module initialized");
+    }
+    
+    // 링은 init 이후에 요청 (모듈이 init에서 버퍼를 준비할 수 있도록)
+    let handoff = Handoff::negotiate(&mut store, &instance, &memory)?;
+    debug!("WASM packet handoff: {:?}", handoff);
+    inspect_func.bind_context(&mut store, &instance, &memory)?;
+    debug!("WASM inspect function: {:?}", inspect_func);
+    
+    Ok(Guest { store, memory, inspect_func, handoff })
+}
+
+/// 워커 하나가 가진 모듈 인스턴스
+struct InspectorInstance {
+    /// 공유 모듈
+    inspector: Arc<WasmInspector>,
+    /// 인스턴스화한 게스트
+    guest: Guest,
+    /// 이 인스턴스의 통계 (모듈 통계에 합산)
+    stats: Arc<WorkerStats>,
+}
+
+impl InspectorInstance {
+    /// 공유 모듈에서 인스턴스 생성
+    fn new(inspector: &Arc<WasmInspector>) -> Result<Self> {
+        let module = inspector.module.as_ref()
+            .ok_or_else(|| anyhow!("WASM module not loaded"))?;
+        let guest = instantiate(&inspector.engine, module, Arc::clone(&inspector.log), Arc::clone(&inspector.config))?;
         
-        let instance = self.instance.as_ref()
-            .ok_or_else(|| anyhow!("WASM instance not initialized"))?;
+        let stats = Arc::new(WorkerStats::default());
+        inspector.workers.lock()
+            .map_err(|_| anyhow!("Failed to lock worker stats"))?
+            .push(Arc::clone(&stats));
         
-        // 메모리 획득
-        let memory = instance
-            .get_memory(&mut *store, "memory")
-            .ok_or_else(|| anyhow!("WASM module has no exported memory"))?;
-            
-        // 검사 함수 획득
-        let inspect_func = instance
-            .get_typed_func::<(i32, i32), i32>(store, "inspect_packet")
-            .context("WASM module has no inspect_packet function")?;
+        Ok(Self {
+            inspector: Arc::clone(inspector),
+            guest,
+            stats,
+        })
+    }
+    
+    /// 패킷 검사 (차단하면 차단 사유 반환)
+    ///
+    /// shadow 모드 모듈의 차단 판정은 통과로 돌려주고, 차단했을 패킷 수와 사유만
+    /// 통계와 모듈 로그에 남긴다.
+    ///
+    /// 패킷 링을 쓰는 모듈에는 다음 슬롯에 패킷을 한 번만 복사하고 `inspect_packet(슬롯, 길이)`를
+    /// 호출한다. 슬롯 내용은 링이 한 바퀴 돌 때(`PACKET_SLOTS`개 패킷 뒤)까지 유지되며,
+    /// `PACKET_SLOT_SIZE`보다 긴 프레임은 앞부분만 전달한다. 그 밖의 모듈에는
+    /// `inspect_packet(주소, 길이)`를 호출한다. `inspect_packet_v2`를 내보낸 모듈에는
+    /// 문맥 버퍼에 `context`를 쓰고 세 번째 인자로 그 주소를 넘긴다.
+    fn inspect(&mut self, packet: &[u8], context: &PacketContext) -> Result<Option<String>> {
+        let Guest { store, memory, inspect_func, handoff } = &mut self.guest;
         
-        // 패킷 데이터를 WASM 메모리에 복사
-        store.data_mut().packet_data = packet.to_vec();
         store.data_mut().packet_len = packet.len();
+        store.data_mut().log_buffer.clear();
         
-        // 메모리 할당 (필요한 경우)
-        let alloc_func = instance.get_typed_func::<i32, i32>(store, "allocate");
-        let ptr = if let Ok(alloc) = alloc_func {
-            alloc.call(store, packet.len() as i32)
-                .context("Failed to allocate memory in WASM")?
-        } else {
-            // 할당 함수가 없는 경우 고정 오프셋 사용
-            1024
+        let args = match handoff {
+            Handoff::Ring { base, next } => {
+                let slot = *next;
+                *next = (slot + 1) % PACKET_SLOTS;
+                
+                let len = packet.len().min(PACKET_SLOT_SIZE as usize);
+                let offset = (*base + slot * PACKET_SLOT_SIZE) as usize;
+                memory.write(&mut *store, offset, &packet[..len])
+                    .context("Failed to write packet data to WASM packet buffer")?;
+                
+                (slot as i32, len as i32)
+            },
+            Handoff::Allocate(alloc) => {
+                let ptr = alloc.call(&mut *store, packet.len() as i32)
+                    .context("Failed to allocate memory in WASM")?;
+                memory.write(&mut *store, ptr as usize, packet)
+                    .context("Failed to write packet data to WASM memory")?;
+                
+                (ptr, packet.len() as i32)
+            },
+            Handoff::Fixed => {
+                memory.write(&mut *store, 1024, packet)
+                    .context("Failed to write packet data to WASM memory")?;
+                
+                (1024, packet.len() as i32)
+            },
         };
         
-        // 패킷 데이터 복사
-        memory.write(store, ptr as usize, packet)
-            .context("Failed to write packet data to WASM memory")?;
-        
         // 검사 함수 호출
-        let result = inspect_func.call(store, (ptr, packet.len() as i32))
-            .context("Failed to call inspect_packet function")?;
+        let started = Instant::now();
+        let result = match inspect_func {
+            InspectFunc::V1(func) => func.call(&mut *store, args)
+                .context("Failed to call inspect_packet function")?,
+            InspectFunc::V2 { func, context: base } => {
+                memory.write(&mut *store, *base as usize, &context.to_bytes())
+                    .context("Failed to write packet context to WASM memory")?;
+                func.call(&mut *store, (args.0, args.1, *base as i32))
+                    .context("Failed to call inspect_packet_v2 function")?
+            },
+        };
         
-        self.processed_packets += 1;
+        let elapsed = started.elapsed();
+        self.stats.busy_ns.fetch_add(elapsed.as_nanos() as u64, Ordering::Relaxed);
+        self.stats.latency.record(elapsed);
+        self.stats.processed_packets.fetch_add(1, Ordering::Relaxed);
         
         // 결과 해석 (1 = 차단, 0 = 통과)
-        if result != 0 {
-            self.blocked_packets += 1;
-            Ok(true) // 차단
-        } else {
-            Ok(false) // 통과
+        if result == 0 {
+            return Ok(None);
         }
+        
+        let reason = block_reason(&store.data().log_buffer, result);
+        if self.inspector.shadow.load(Ordering::Relaxed) {
+            self.stats.would_block.fetch_add(1, Ordering::Relaxed);
+            self.inspector.log.push(&format!("shadow: would block: {}", reason), now_ms());
+            return Ok(None);
+        }
+        
+        self.stats.blocked_packets.fetch_add(1, Ordering::Relaxed);
+        
+        Ok(Some(reason))
     }
-    
-    /// 상태 획득
-    pub fn state(&self) -> ModuleState {
-        self.state
-    }
-    
-    /// 통계 획득
-    pub fn stats(&self) -> (u64, u64) {
-        (self.processed_packets, self.blocked_packets)
+}
+
+/// 차단 사유 (검사 중 모듈이 남긴 마지막 로그, 없으면 판정 코드)
+fn block_reason(log: &str, verdict: i32) -> String {
+    log.lines()
+        .rev()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("verdict {}", verdict))
+}
+
+/// 이더넷 프레임의 소스 주소 (IP 헤더가 온전할 때만)
+fn packet_src_ip(packet: &[u8]) -> Option<IpAddr> {
+    crate::packet::parse_headers(packet).map(|headers| headers.src_ip)
+}
+
+/// 차단 판정을 동적 차단 대기열에 추가 (가득 차면 버림)
+fn queue_block(blocks: &Mutex<Vec<BlockVerdict>>, verdict: BlockVerdict) {
+    let Ok(mut blocks) = blocks.lock() else { return };
+    if blocks.len() < MAX_PENDING_BLOCKS {
+        blocks.push(verdict);
+    } else {
+        throttled_warn!("wasm:blocks", logthrottle::HOT_PATH_INTERVAL,
+            "Dynamic block queue full, dropping block of {} by {}", utils::ipv4_to_string(verdict.src_ip), verdict.module);
     }
-    
-    /// 모듈 ID 획득
-    pub fn id(&self) -> &str {
-        &self.id
+}
+
+/// 모듈 로드 결과
+#[derive(Debug, Clone, Copy)]
+pub struct LoadReport {
+    /// 파일 읽기부터 시험 인스턴스 생성까지 걸린 시간
+    pub elapsed: Duration,
+    /// 캐시된 컴파일 결과를 사용했는지
+    pub cached: bool,
+}
+
+impl std::fmt::Display for LoadReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:.1} ms, {}", self.elapsed.as_secs_f64() * 1000.0,
+            if self.cached { "from cache" } else { "compiled" })
     }
 }
 
-/// WASM 검사 모듈 관리자
-#[derive(Debug)]
-pub struct WasmManager {
-    /// 로드된 검사 모듈
-    inspectors: Arc<Mutex<Vec<WasmInspector>>>,
+/// 컴파일된 모듈 캐시
+///
+/// 모듈 파일의 SHA-256을 키로 `<dir>/<sha256>.cwasm`에 직렬화한 모듈을 둔다.
+/// 엔진(wasmtime 버전, 컴파일 설정)이 바뀌면 처음 사용할 때 캐시를 비운다.
+#[derive(Debug, Clone)]
+pub struct ModuleCache {
+    dir: PathBuf,
 }
 
-impl WasmManager {
-    /// 새로운 WASM 관리자 생성
-    pub fn new() -> Self {
-        Self {
-            inspectors: Arc::new(Mutex::new(Vec::new())),
-        }
+/// 엔진 지문 파일 이름
+const ENGINE_STAMP: &str = "ENGINE";
+
+/// 캐시 파일 확장자
+const CACHE_EXTENSION: &str = "cwasm";
+
+impl ModuleCache {
+    /// 디렉토리에 캐시 생성 (디렉토리는 처음 저장할 때 만든다)
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+    
+    /// 작업 디렉토리 아래 기본 위치의 캐시
+    pub fn in_work_dir(work_dir: &Path) -> Self {
+        Self::new(work_dir.join("wasm-cache"))
     }
     
-    /// 모듈 로드
-    pub fn load_module(&self, id: &str, path: &Path) -> Result<()> {
-        let mut inspector = WasmInspector::new(id, path)?;
-        inspector.load()?;
+    /// 캐시 디렉토리
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+    
+    /// 모듈 컴파일 (캐시에 있으면 역직렬화, 캐시 사용 여부 함께 반환)
+    ///
+    /// 캐시를 읽거나 쓰지 못하면 경고만 남기고 직접 컴파일한다.
+    pub fn compile(&self, engine: &Engine, wasm: &[u8]) -> Result<(Module, bool)> {
+        if let Err(e) = self.check_engine(engine) {
+            warn!("Failed to validate WASM module cache {}: {:#}", self.dir.display(), e);
+        }
         
-        let mut inspectors = self.inspectors.lock()
-            .map_err(|_| anyhow!("Failed to lock inspectors"))?;
+        let path = self.dir.join(format!("{}.{}", sha256_hex(wasm), CACHE_EXTENSION));
+        if let Ok(serialized) = std::fs::read(&path) {
+            // 안전성: 캐시 디렉토리는 데몬만 쓰며 같은 엔진 설정으로 직렬화한 모듈만 둔다.
+            // wasmtime은 버전·설정이 다른 모듈을 역직렬화하지 않고 오류를 반환한다.
+            match unsafe { Module::deserialize(engine, &serialized) } {
+                Ok(module) => {
+                    debug!("Loaded compiled WASM module from {}", path.display());
+                    return Ok((module, true));
+                },
+                Err(e) => warn!("Ignoring stale WASM cache entry {}: {:#}", path.display(), e),
+            }
+        }
         
-        inspectors.push(inspector);
+        let module = Module::new(engine, wasm)
+            .context("Failed to compile WASM module")?;
+        if let Err(e) = self.store(&path, &module) {
+            warn!("Failed to cache compiled WASM module: {:#}", e);
+        }
         
-        Ok(())
+        Ok((module, false))
     }
     
-    /// 패킷 검사 (모든 모듈)
-    pub fn inspect_packet(&self, packet: &[u8]) -> Result<bool> {
-        let mut inspectors = self.inspectors.lock()
-            .map_err(|_| anyhow!("Failed to lock inspectors"))?;
-        
-        for inspector in inspectors.iter_mut() {
-            if inspector.state() == ModuleState::Loaded || inspector.state() == ModuleState::Running {
-                if inspector.inspect_packet(packet)? {
-                    return Ok(true); // 하나라도 차단하면 차단으로 처리
-                }
+    /// 캐시 비우기 (삭제한 모듈 수)
+    pub fn clear(&self) -> Result<usize> {
+        let entries = match std::fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+            Err(e) => return Err(e).context(format!("Failed to read {}", self.dir.display())),
+        };
+        
+        let mut removed = 0;
+        for entry in entries {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == CACHE_EXTENSION) {
+                std::fs::remove_file(&path)
+                    .context(format!("Failed to remove {}", path.display()))?;
+                removed += 1;
             }
         }
         
-        Ok(false) // 모든 모듈이 통과하면 통과로 처리
+        Ok(removed)
     }
     
-    /// 모듈 목록 획득
-    pub fn list_modules(&self) -> Result<Vec<(String, ModuleState, u64, u64)>> {
-        let inspectors = self.inspectors.lock()
-            .map_err(|_| anyhow!("Failed to lock inspectors"))?;
+    /// 엔진 지문이 바뀌었으면 캐시를 비우고 새 지문 기록
+    fn check_engine(&self, engine: &Engine) -> Result<()> {
+        let fingerprint = engine_fingerprint(engine)?;
+        let stamp = self.dir.join(ENGINE_STAMP);
+        if std::fs::read_to_string(&stamp).ok().as_deref() == Some(fingerprint.as_str()) {
+            return Ok(());
+        }
         
-        let mut result = Vec::new();
-        for inspector in inspectors.iter() {
-            let (processed, blocked) = inspector.stats();
-            result.push((
-                inspector.id().to_string(),
-                inspector.state(),
-                processed,
-                blocked,
-            ));
+        let removed = self.clear()?;
+        if removed > 0 {
+            info!("WASM engine changed, removed {} cached modules", removed);
         }
         
-        Ok(result)
+        std::fs::create_dir_all(&self.dir)
+            .context(format!("Failed to create {}", self.dir.display()))?;
+        std::fs::write(&stamp, fingerprint)
+            .context(format!("Failed to write {}", stamp.display()))
+    }
+    
+    /// 직렬화한 모듈 저장 (임시 파일에 쓴 뒤 이름 변경)
+    fn store(&self, path: &Path, module: &Module) -> Result<()> {
+        let serialized = module.serialize()
+            .context("Failed to serialize WASM module")?;
+        
+        std::fs::create_dir_all(&self.dir)
+            .context(format!("Failed to create {}", self.dir.display()))?;
+        let tmp = path.with_extension("tmp");
+        std::fs::write(&tmp, serialized)
+            .context(format!("Failed to write {}", tmp.display()))?;
+        std::fs::rename(&tmp, path)
+            .context(format!("Failed to rename {}", tmp.display()))
     }
 }
 
-impl Default for WasmManager {
-    fn default() -> Self {
-        Self::new()
+/// 엔진 지문 (빈 모듈을 미리 컴파일한 결과의 해시)
+///
+/// 미리 컴파일한 모듈에는 wasmtime 버전과 컴파일 설정이 들어 있으므로 둘 중
+/// 하나라도 바뀌면 지문이 달라진다.
+fn engine_fingerprint(engine: &Engine) -> Result<String> {
+    const EMPTY_MODULE: &[u8] = b"\0asm\x01\0\0\0";
+    
+    let precompiled = engine.precompile_module(EMPTY_MODULE)
+        .context("Failed to precompile empty WASM module")?;
+    Ok(sha256_hex(&precompiled))
+}
+
+/// SHA-256 16진수 문자열
+fn sha256_hex(data: &[u8]) -> String {
+    Sha256::digest(data).iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// 물리 코어 수 (/proc/cpuinfo에서 읽지 못하면 온라인 CPU 수)
+pub fn physical_core_count() -> usize {
+    std::fs::read_to_string("/proc/cpuinfo").ok()
+        .and_then(|cpuinfo| count_physical_cores(&cpuinfo))
+        .unwrap_or_else(|| crate::maps::online_cpu_count() as usize)
+}
+
+/// cpuinfo의 (physical id, core id) 쌍 수 (코어 정보가 없으면 None)
+fn count_physical_cores(cpuinfo: &str) -> Option<usize> {
+    let mut cores = HashSet::new();
+    for block in cpuinfo.split("\n\n") {
+        let field = |name: &str| block.lines().find_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            (key.trim() == name).then(|| value.trim())
+        });
+        if let (Some(physical), Some(core)) = (field("physical id"), field("core id")) {
+            cores.insert((physical, core));
+        }
+    }
+    
+    if cores.is_empty() { None } else { Some(cores.len()) }
+}
+
+/// 검사 작업
+struct Job {
+    /// 패킷 데이터
+    packet: Vec<u8>,
+    /// 패킷을 검사로 보낸 규칙 문맥
+    context: PacketContext,
+    /// 판정 회신 채널
+    reply: SyncSender<Result<bool>>,
+}
+
+/// 검사 요청 결과
+#[derive(Debug)]
+pub enum Submission {
+    /// 대기열에 들어감 (판정은 수신자로 전달)
+    Queued(Receiver<Result<bool>>),
+    /// 대기열이 가득 차 과부하 정책으로 처리함
+    Overloaded {
+        /// 차단 여부 (drop 정책이면 true)
+        blocked: bool,
+    },
+}
+
+/// 동적 차단을 기다릴 수 있는 최대 차단 판정 수 (넘으면 새 판정을 버림)
+const MAX_PENDING_BLOCKS: usize = 1024;
+
+/// enforce 모듈의 차단 판정 (소스 주소 동적 차단 대기)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockVerdict {
+    /// 차단한 모듈
+    pub module: String,
+    /// 패킷의 IPv4 소스 주소
+    pub src_ip: u32,
+    /// 차단 사유
+    pub reason: String,
+}
+
+/// 로드된 모듈 테이블
+///
+/// 로드와 언로드만 쓰기 잠금을 잡고 맵을 바꾼 뒤 세대 번호를 올린다. 워커는
+/// 세대 번호가 바뀌었을 때만 읽기 잠금으로 모듈 목록을 다시 읽으므로 패킷
+/// 검사 경로에서는 잠금을 잡지 않는다.
+#[derive(Debug, Default)]
+struct ModuleTable {
+    /// 이름별 모듈
+    modules: RwLock<HashMap<String, Arc<WasmInspector>>>,
+    /// 변경 세대 번호
+    generation: AtomicU64,
+    /// 다음 로드 순서
+    next_seq: AtomicU64,
+}
+
+impl ModuleTable {
+    /// 현재 세대 번호
+    fn generation(&self) -> u64 {
+        self.generation.load(Ordering::Acquire)
+    }
+    
+    /// 이름으로 모듈 조회
+    fn get(&self, id: &str) -> Result<Arc<WasmInspector>, DaemonError> {
+        self.modules.read()
+            .map_err(|_| lock_error())?
+            .get(id)
+            .cloned()
+            .ok_or_else(|| DaemonError::ModuleNotFound(id.to_string()))
+    }
+    
+    /// 로드 순서의 모듈 목록
+    fn snapshot(&self) -> Result<Vec<Arc<WasmInspector>>, DaemonError> {
+        let mut modules: Vec<Arc<WasmInspector>> = self.modules.read()
+            .map_err(|_| lock_error())?
+            .values()
+            .cloned()
+            .collect();
+        modules.sort_by_key(|inspector| inspector.seq);
+        Ok(modules)
+    }
+    
+    /// 모듈 추가 (같은 이름이 있으면 실패)
+    fn insert(&self, mut inspector: WasmInspector) -> Result<(), DaemonError> {
+        let mut modules = self.modules.write()
+            .map_err(|_| lock_error())?;
+        if modules.contains_key(inspector.id()) {
+            return Err(DaemonError::DuplicateModule(inspector.id().to_string()));
+        }
+        
+        inspector.seq = self.next_seq.fetch_add(1, Ordering::Relaxed);
+        modules.insert(inspector.id().to_string(), Arc::new(inspector));
+        self.generation.fetch_add(1, Ordering::Release);
+        Ok(())
+    }
+    
+    /// 모듈 제거
+    fn remove(&self, id: &str) -> Result<Arc<WasmInspector>, DaemonError> {
+        let mut modules = self.modules.write()
+            .map_err(|_| lock_error())?;
+        let removed = modules.remove(id)
+            .ok_or_else(|| DaemonError::ModuleNotFound(id.to_string()))?;
+        self.generation.fetch_add(1, Ordering::Release);
+        Ok(removed)
+    }
+}
+
+fn lock_error() -> DaemonError {
+    DaemonError::Wasm(anyhow!("Failed to lock WASM modules"))
+}
+
+/// 검사 워커
+///
+/// 로드된 모듈마다 자신의 인스턴스를 가지며, 모듈 테이블이 바뀌면 다음 패킷을
+/// 검사하기 전에 새 모듈의 인스턴스를 만들고 언로드된 모듈의 인스턴스를 버린다.
+/// 언로드 중에 이미 시작한 검사는 공유 모듈을 잡고 있으므로 그대로 끝난다.
+struct Worker {
+    /// 로드된 검사 모듈
+    modules: Arc<ModuleTable>,
+    /// 마지막으로 반영한 모듈 테이블 세대
+    generation: u64,
+    /// 로드 순서의 모듈 인스턴스 (생성에 실패한 모듈은 None)
+    instances: Vec<(Arc<WasmInspector>, Option<InspectorInstance>)>,
+    /// 차단 이벤트 syslog 싱크
+    syslog: Option<SyslogSink>,
+    /// 차단 패킷 캡처
+    capture: Option<CaptureSink>,
+    /// 동적 차단을 기다리는 차단 판정
+    blocks: Arc<Mutex<Vec<BlockVerdict>>>,
+}
+
+impl Worker {
+    /// 대기열이 닫힐 때까지 검사 작업 처리
+    fn run(mut self, jobs: Arc<Mutex<Receiver<Job>>>) {
+        loop {
+            let job = match jobs.lock() {
+                Ok(jobs) => jobs.recv(),
+                Err(_) => break,
+            };
+            let job = match job {
+                Ok(job) => job,
+                Err(_) => break,
+            };
+            
+            let _ = job.reply.send(self.inspect(&job.packet, &job.context));
+        }
+    }
+    
+    /// 모듈 테이블 변경 반영 (새 모듈 인스턴스 생성, 언로드된 모듈 인스턴스 제거)
+    fn sync_instances(&mut self) -> Result<()> {
+        let generation = self.modules.generation();
+        if generation == self.generation {
+            return Ok(());
+        }
+        
+        let mut previous = std::mem::take(&mut self.instances);
+        for inspector in self.modules.snapshot()? {
+            let known = previous.iter().position(|(known, _)| Arc::ptr_eq(known, &inspector));
+            let slot = match known {
+                Some(index) => previous.swap_remove(index),
+                None => {
+                    let instance = InspectorInstance::new(&inspector)
+                        .map_err(|e| error!("Failed to instantiate WASM module {}: {:#}", inspector.id(), e))
+                        .ok();
+                    (inspector, instance)
+                },
+            };
+            self.instances.push(slot);
+        }
+        
+        self.generation = generation;
+        Ok(())
+    }
+    
+    /// 패킷 검사 (모든 모듈)
+    ///
+    /// 모듈 오류는 모듈의 오류 정책대로 처리하고 다음 모듈로 넘어간다. 비활성화된
+    /// 모듈은 건너뛰고, shadow 모드 모듈은 drop 오류 정책이어도 차단하지 않는다.
+    fn inspect(&mut self, packet: &[u8], context: &PacketContext) -> Result<bool> {
+        self.sync_instances()?;
+        
+        for instance in self.instances.iter_mut().filter_map(|(_, instance)| instance.as_mut()) {
+            if instance.inspector.is_disabled() {
+                continue;
+            }
+            
+            let verdict = match instance.inspect(packet, context) {
+                Ok(verdict) => verdict,
+                Err(e) => {
+                    throttled_error!(&format!("wasm:trap:{}", instance.inspector.id()), logthrottle::HOT_PATH_INTERVAL,
+                        "WASM module {} failed to inspect packet: {:#}", instance.inspector.id(), e);
+                    instance.stats.errors.fetch_add(1, Ordering::Relaxed);
+                    if instance.inspector.handle_error(&e) && instance.inspector.mode() == WasmModuleMode::Enforce {
+                        return Ok(true); // drop 정책
+                    }
+                    continue;
+                },
+            };
+            if let Some(reason) = verdict {
+                // 동적 차단은 IPv4 소스만 (규칙 맵이 IPv4 프리픽스)
+                let src_ip = packet_src_ip(packet);
+                if let Some(IpAddr::V4(addr)) = src_ip {
+                    queue_block(&self.blocks, BlockVerdict {
+                        module: instance.inspector.id().to_string(),
+                        src_ip: u32::from(addr),
+                        reason: reason.clone(),
+                    });
+                }
+                if let Some(sink) = &self.syslog {
+                    sink.send(&SecurityEvent::WasmBlock {
+                        module: instance.inspector.id().to_string(),
+                        reason,
+                        src_ip: src_ip.map(|ip| ip.to_string()),
+                    });
+                }
+                if let Some(capture) = &self.capture {
+                    capture.record(CaptureReason::Blocked, packet);
+                }
+                return Ok(true); // 하나라도 차단하면 차단으로 처리
+            }
+        }
+        
+        Ok(false) // 모든 모듈이 통과하면 통과로 처리
+    }
+}
+
+/// WASM 검사 모듈 관리자
+///
+/// 패킷은 크기가 제한된 대기열을 거쳐 워커 스레드에서 검사한다. 워커마다
+/// 모듈 인스턴스(메모리 포함)를 따로 가지므로 모듈은 패킷 사이의 상태를
+/// 인스턴스 메모리에 쌓는다고 가정하면 안 된다.
+///
+/// 순서: 패킷은 대기열 순서로 워커에 배정되지만 워커 간 완료 순서는 보장하지
+/// 않는다. 같은 흐름의 패킷도 서로 다른 워커에서 동시에 검사될 수 있다. 판정은
+/// 패킷마다 `Submission::Queued`의 수신자로 전달되므로 순서가 필요하면 호출자가
+/// 수신자를 제출 순서대로 기다려야 한다.
+///
+/// 대기열이 가득 차면 패킷을 검사하지 않고 과부하 정책(pass, drop)을 적용하며
+/// 과부하 이벤트 수를 센다.
+///
+/// 잠금: 모듈 목록과 통계 조회는 읽기 잠금만 잡고, 로드와 언로드는 컴파일을
+/// 마친 뒤 맵을 바꾸는 동안만 쓰기 잠금을 잡는다. 검사는 워커가 가진 인스턴스로
+/// 하므로 한 모듈의 검사가 다른 모듈의 로드나 통계 조회를 막지 않는다.
+#[derive(Debug)]
+pub struct WasmManager {
+    /// 로드된 검사 모듈
+    modules: Arc<ModuleTable>,
+    /// 차단 이벤트 syslog 싱크
+    syslog: Option<SyslogSink>,
+    /// 차단 패킷 캡처
+    capture: Option<CaptureSink>,
+    /// 검사 워커 수
+    workers: usize,
+    /// 검사 대기열 크기
+    queue_size: usize,
+    /// 대기열이 가득 찼을 때 처리
+    overload_policy: OverloadPolicy,
+    /// 검사 대기열 (첫 검사 요청 때 워커와 함께 시작)
+    queue: OnceLock<SyncSender<Job>>,
+    /// 과부하 이벤트 수
+    overloaded: AtomicU64,
+    /// 컴파일된 모듈 캐시
+    cache: Option<ModuleCache>,
+    /// 로드 시 지정하지 않은 모듈의 검사 오류 처리
+    on_error: WasmErrorPolicy,
+    /// 모듈별 보관 로그 줄 수
+    log_lines: usize,
+    /// 동적 차단을 기다리는 차단 판정
+    blocks: Arc<Mutex<Vec<BlockVerdict>>>,
+}
+
+impl WasmManager {
+    /// 새로운 WASM 관리자 생성 (물리 코어 수만큼 워커)
+    pub fn new() -> Self {
+        Self {
+            modules: Arc::new(ModuleTable::default()),
+            syslog: None,
+            capture: None,
+            workers: physical_core_count(),
+            queue_size: DEFAULT_QUEUE_SIZE,
+            overload_policy: OverloadPolicy::Pass,
+            queue: OnceLock::new(),
+            overloaded: AtomicU64::new(0),
+            cache: None,
+            on_error: WasmErrorPolicy::Pass,
+            log_lines: DEFAULT_LOG_LINES,
+            blocks: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+    
+    /// 구성에 따라 WASM 관리자 생성
+    pub fn from_config(config: &WasmConfig, work_dir: &Path) -> Self {
+        let workers = config.workers.unwrap_or_else(physical_core_count);
+        let manager = Self::new()
+            .with_pool(workers, config.queue_size, config.overload_policy)
+            .with_error_policy(config.on_error)
+            .with_log_lines(config.log_lines);
+        
+        if config.cache {
+            manager.with_cache(ModuleCache::in_work_dir(work_dir))
+        } else {
+            manager
+        }
+    }
+    
+    /// 워커 수, 대기열 크기, 과부하 정책 설정
+    pub fn with_pool(mut self, workers: usize, queue_size: usize, overload_policy: OverloadPolicy) -> Self {
+        self.workers = workers.max(1);
+        self.queue_size = queue_size;
+        self.overload_policy = overload_policy;
+        self
+    }
+    
+    /// 기본 검사 오류 처리 설정
+    pub fn with_error_policy(mut self, policy: WasmErrorPolicy) -> Self {
+        self.on_error = policy;
+        self
+    }
+    
+    /// 모듈별 보관 로그 줄 수 설정
+    pub fn with_log_lines(mut self, lines: usize) -> Self {
+        self.log_lines = lines.max(1);
+        self
+    }
+    
+    /// 컴파일된 모듈 캐시 사용
+    pub fn with_cache(mut self, cache: ModuleCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+    
+    /// 차단 이벤트를 syslog로 전달
+    pub fn with_syslog(mut self, sink: SyslogSink) -> Self {
+        self.syslog = Some(sink);
+        self
+    }
+    
+    /// 차단된 패킷을 pcap 파일로 저장
+    pub fn with_capture(mut self, sink: CaptureSink) -> Self {
+        self.capture = Some(sink);
+        self
+    }
+    
+    /// 모듈 로드 (같은 이름의 모듈이 있으면 실패)
+    ///
+    /// 컴파일은 잠금 없이 하므로 로드하는 동안에도 다른 모듈의 검사와 조회가 계속된다.
+    pub fn load_module(&self, id: &str, path: &Path) -> Result<LoadReport, DaemonError> {
+        self.load_module_with_policy(id, path, None, Vec::new(), WasmModuleMode::Enforce)
+    }
+    
+    /// 검사 오류 처리(None이면 관리자 기본값), 모듈 설정, 판정 적용 방식을 지정해 모듈 로드
+    pub fn load_module_with_policy(
+        &self,
+        id: &str,
+        path: &Path,
+        on_error: Option<WasmErrorPolicy>,
+        config: Vec<u8>,
+        mode: WasmModuleMode,
+    ) -> Result<LoadReport, DaemonError> {
+        if self.modules.get(id).is_ok() {
+            return Err(DaemonError::DuplicateModule(id.to_string()));
+        }
+        if config.len() > MAX_MODULE_CONFIG_LEN {
+            return Err(InvalidArgument::new("config", format!("Module config is {} bytes (max {})",
+                config.len(), MAX_MODULE_CONFIG_LEN)).into());
+        }
+        
+        let mut inspector = WasmInspector::new(id, path).map_err(DaemonError::Wasm)?;
+        inspector.set_error_policy(on_error.unwrap_or(self.on_error));
+        inspector.set_config(config);
+        inspector.set_log_lines(self.log_lines);
+        inspector.set_mode(mode);
+        let report = inspector.load(self.cache.as_ref()).map_err(DaemonError::Wasm)?;
+        self.modules.insert(inspector)?;
+        
+        Ok(report)
+    }
+    
+    /// 모듈 언로드
+    ///
+    /// 진행 중인 검사는 끝까지 실행되고, 워커는 다음 패킷부터 이 모듈을 건너뛴다.
+    pub fn unload_module(&self, id: &str) -> Result<(), DaemonError> {
+        self.modules.remove(id)?;
+        info!("WASM module unloaded: {}", id);
+        Ok(())
+    }
+    
+    /// 컴파일된 모듈 캐시 (비활성화면 None)
+    pub fn cache(&self) -> Option<&ModuleCache> {
+        self.cache.as_ref()
+    }
+    
+    /// 검사 대기열 (처음 호출할 때 워커 시작)
+    fn queue(&self) -> &SyncSender<Job> {
+        self.queue.get_or_init(|| {
+            let (tx, rx) = mpsc::sync_channel(self.queue_size);
+            let jobs = Arc::new(Mutex::new(rx));
+            
+            for n in 0..self.workers {
+                let worker = Worker {
+                    modules: Arc::clone(&self.modules),
+                    generation: u64::MAX,
+                    instances: Vec::new(),
+                    syslog: self.syslog.clone(),
+                    capture: self.capture.clone(),
+                    blocks: Arc::clone(&self.blocks),
+                };
+                let jobs = Arc::clone(&jobs);
+                
+                if let Err(e) = std::thread::Builder::new()
+                    .name(format!("wasm-worker-{}", n))
+                    .spawn(move || worker.run(jobs))
+                {
+                    error!("Failed to start WASM worker {}: {}", n, e);
+                }
+            }
+            
+            info!("Started {} WASM inspection workers (queue size {})", self.workers, self.queue_size);
+            tx
+        })
+    }
+    
+    /// 규칙 문맥과 함께 패킷 검사 요청 (대기하지 않음)
+    pub fn submit_with_context(&self, packet: &[u8], context: PacketContext) -> Result<Submission, DaemonError> {
+        let (reply, verdict) = mpsc::sync_channel(1);
+        
+        match self.queue().try_send(Job { packet: packet.to_vec(), context, reply }) {
+            Ok(()) => Ok(Submission::Queued(verdict)),
+            Err(TrySendError::Full(_)) => {
+                self.overloaded.fetch_add(1, Ordering::Relaxed);
+                debug!("WASM inspection queue full, applying {:?} policy", self.overload_policy);
+                Ok(Submission::Overloaded { blocked: self.overload_policy == OverloadPolicy::Drop })
+            },
+            Err(TrySendError::Disconnected(_)) => Err(DaemonError::Wasm(anyhow!("No WASM inspection workers running"))),
+        }
+    }
+    
+    /// 패킷 검사 (모든 모듈, 판정까지 대기)
+    #[allow(dead_code)]
+    pub fn inspect_packet(&self, packet: &[u8]) -> Result<bool, DaemonError> {
+        self.inspect_packet_with_context(packet, PacketContext::default())
+    }
+    
+    /// 규칙 문맥과 함께 패킷 검사 (모든 모듈, 판정까지 대기)
+    pub fn inspect_packet_with_context(&self, packet: &[u8], context: PacketContext) -> Result<bool, DaemonError> {
+        match self.submit_with_context(packet, context)? {
+            Submission::Queued(verdict) => verdict.recv()
+                .map_err(|_| anyhow!("WASM worker stopped before inspecting packet"))
+                .and_then(|verdict| verdict)
+                .map_err(DaemonError::Wasm),
+            Submission::Overloaded { blocked } => Ok(blocked),
+        }
+    }
+    
+    /// 과부하 이벤트 수 (대기열이 가득 차 검사하지 않은 패킷 수)
+    pub fn overload_count(&self) -> u64 {
+        self.overloaded.load(Ordering::Relaxed)
+    }
+    
+    /// 모듈 목록 획득 (로드 순서)
+    pub fn list_modules(&self) -> Result<Vec<WasmModuleInfo>, DaemonError> {
+        Ok(self.modules.snapshot()?.iter().map(|inspector| inspector.info()).collect())
+    }
+    
+    /// 오류로 비활성화된 모듈 재개 (오류 기록은 유지)
+    pub fn resume_module(&self, id: &str) -> Result<(), DaemonError> {
+        if !self.modules.get(id)?.resume() {
+            return Err(InvalidArgument::new("name", format!("WASM module {} is not disabled", id)).into());
+        }
+        
+        info!("WASM module resumed: {}", id);
+        Ok(())
+    }
+    
+    /// 모듈 판정 적용 방식 변경 (통계와 로그는 유지)
+    pub fn set_module_mode(&self, id: &str, mode: WasmModuleMode) -> Result<(), DaemonError> {
+        let inspector = self.modules.get(id)?;
+        if inspector.mode() != mode {
+            inspector.set_mode(mode);
+            info!("WASM module {} switched to {} mode", id, mode.as_str());
+        }
+        Ok(())
+    }
+    
+    /// 모듈 통계 획득
+    pub fn module_stats(&self, id: &str) -> Result<ModuleStats, DaemonError> {
+        Ok(self.modules.get(id)?.module_stats())
+    }
+    
+    /// 모든 모듈의 통계 획득 (로드 순서)
+    pub fn all_module_stats(&self) -> Result<Vec<(String, ModuleStats)>, DaemonError> {
+        Ok(self.modules.snapshot()?.iter()
+            .map(|inspector| (inspector.id().to_string(), inspector.module_stats()))
+            .collect())
+    }
+    
+    /// 모듈 통계 초기화
+    pub fn reset_module_stats(&self, id: &str) -> Result<(), DaemonError> {
+        self.modules.get(id)?.reset_stats();
+        info!("WASM module stats reset: {}", id);
+        Ok(())
+    }
+    
+    /// 동적 차단을 기다리는 차단 판정을 꺼냄 (판정 순서)
+    pub fn take_blocks(&self) -> Vec<BlockVerdict> {
+        self.blocks.lock()
+            .map(|mut blocks| std::mem::take(&mut *blocks))
+            .unwrap_or_default()
+    }
+    
+    /// 구성의 자동 로드 모듈을 `modules_dir`에서 로드 (모듈 이름은 파일 이름에서 확장자를 뺀 것)
+    ///
+    /// 로드하지 못한 모듈은 기록만 하고 건너뛰어 나머지 모듈과 데몬 시작을 막지 않는다.
+    pub fn auto_load(&self, config: &WasmConfig) -> usize {
+        if !config.auto_load {
+            return 0;
+        }
+        
+        let mut loaded = 0;
+        for name in &config.auto_load_modules {
+            let mut path = Path::new(&config.modules_dir).join(name);
+            if path.extension().is_none() {
+                path.set_extension("wasm");
+            }
+            let id = path.file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_else(|| name.clone());
+            
+            match self.load_module(&id, &path) {
+                Ok(report) => {
+                    info!("WASM module {} loaded from {} in {:.1?}{}", id, path.display(), report.elapsed,
+                        if report.cached { ", from cache" } else { "" });
+                    loaded += 1;
+                },
+                Err(e) => error!("Failed to auto-load WASM module {}: {}", path.display(), e),
+            }
+        }
+        loaded
+    }
+    
+    /// 모듈 로그 획득 (`after` 순번 이후의 최근 `lines`줄, 0이면 전체)
+    pub fn module_log(&self, id: &str, lines: usize, after: Option<u64>) -> Result<Vec<WasmLogLine>, DaemonError> {
+        Ok(self.modules.get(id)?.log().tail(lines, after))
+    }
+}
+
+impl Default for WasmManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::inspectctx::ContextSource;
+    use std::sync::atomic::AtomicUsize;
+
+    static NEXT_MODULE: AtomicUsize = AtomicUsize::new(0);
+
+    fn uleb(mut value: u32, out: &mut Vec<u8>) {
+        loop {
+            let byte = (value & 0x7f) as u8;
+            value >>= 7;
+            if value == 0 {
+                out.push(byte);
+                return;
+            }
+            out.push(byte | 0x80);
+        }
+    }
+
+    fn sleb(mut value: i32, out: &mut Vec<u8>) {
+        loop {
+            let byte = (value & 0x7f) as u8;
+            value >>= 7;
+            if (value == 0 && byte & 0x40 == 0) || (value == -1 && byte & 0x40 != 0) {
+                out.push(byte);
+                return;
+            }
+            out.push(byte | 0x80);
+        }
+    }
+
+    /// i32 매개변수를 받아 i32를 돌려주는 함수들과 메모리 1페이지를 내보내는 모듈
+    ///
+    /// 함수마다 (매개변수 수, 내보낼 이름, 지역 변수 선언을 포함한 본문)
+    fn build_module(funcs: &[(u8, &str, Vec<u8>)]) -> Vec<u8> {
+        build_module_with_imports(&[], funcs)
+    }
+
+    /// `env`에서 가져온 호스트 함수 (매개변수 수, 이름)를 쓰는 모듈
+    ///
+    /// 가져온 함수가 앞 번호를 차지하므로 본문의 `call`은 가져온 함수를 0번부터 부른다.
+    fn build_module_with_imports(imports: &[(u8, &str)], funcs: &[(u8, &str, Vec<u8>)]) -> Vec<u8> {
+        fn section(id: u8, count: usize, items: Vec<u8>, out: &mut Vec<u8>) {
+            let mut content = Vec::new();
+            uleb(count as u32, &mut content);
+            content.extend(items);
+            out.push(id);
+            uleb(content.len() as u32, out);
+            out.extend(content);
+        }
+
+        fn name(name: &str, out: &mut Vec<u8>) {
+            uleb(name.len() as u32, out);
+            out.extend_from_slice(name.as_bytes());
+        }
+
+        let mut types = Vec::new();
+        let mut import_entries = Vec::new();
+        let mut functions = Vec::new();
+        let mut exports = Vec::new();
+        let mut code = Vec::new();
+
+        let mut signature = |params: u8| {
+            types.extend_from_slice(&[0x60, params]);
+            types.extend(std::iter::repeat_n(0x7f, params as usize));
+            types.extend_from_slice(&[0x01, 0x7f]);
+        };
+
+        for (index, (params, import)) in imports.iter().enumerate() {
+            signature(*params);
+            name("env", &mut import_entries);
+            name(import, &mut import_entries);
+            import_entries.extend_from_slice(&[0x00, index as u8]);
+        }
+
+        name("memory", &mut exports);
+        exports.extend_from_slice(&[0x02, 0x00]);
+
+        for (offset, (params, export, body)) in funcs.iter().enumerate() {
+            let index = (imports.len() + offset) as u8;
+            signature(*params);
+            functions.push(index);
+            name(export, &mut exports);
+            exports.extend_from_slice(&[0x00, index]);
+            uleb(body.len() as u32, &mut code);
+            code.extend_from_slice(body);
+        }
+
+        let mut wasm = vec![0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00];
+        section(0x01, imports.len() + funcs.len(), types, &mut wasm);
+        if !imports.is_empty() {
+            section(0x02, imports.len(), import_entries, &mut wasm);
+        }
+        section(0x03, funcs.len(), functions, &mut wasm);
+        section(0x05, 1, vec![0x00, 0x01], &mut wasm);
+        section(0x07, funcs.len() + 1, exports, &mut wasm);
+        section(0x0a, funcs.len(), code, &mut wasm);
+        wasm
+    }
+
+    /// `iterations`번 도는 루프 후 패킷 첫 바이트를 판정으로 돌려주는 모듈
+    fn stub_module(iterations: i32) -> Vec<u8> {
+        let mut body = vec![0x01, 0x01, 0x7f]; // i32 지역 변수 1개
+        body.push(0x41); // i32.const iterations
+        sleb(iterations, &mut body);
+        body.extend_from_slice(&[
+            0x21, 0x02, // local.set 2
+            0x03, 0x40, // loop
+            0x20, 0x02, 0x41, 0x01, 0x6b, 0x22, 0x02, // local.get 2; i32.const 1; i32.sub; local.tee 2
+            0x0d, 0x00, // br_if 0
+            0x0b, // end
+            0x20, 0x00, 0x2d, 0x00, 0x00, // local.get 0; i32.load8_u
+            0x0b, // end
+        ]);
+
+        build_module(&[(2, "inspect_packet", body)])
+    }
+
+    /// 링 시작 주소
+    const RING_BASE: i32 = 4096;
+
+    /// `base`에 패킷 링을 두는 모듈 (`inspect`는 지역 변수 선언을 포함한 검사 함수 본문)
+    fn ring_module_with(base: i32, inspect: Vec<u8>) -> Vec<u8> {
+        let mut get_buffer = vec![0x00, 0x41];
+        sleb(base, &mut get_buffer);
+        get_buffer.push(0x0b);
+
+        build_module(&[(2, "inspect_packet", inspect), (0, "get_packet_buffer", get_buffer)])
+    }
+
+    /// 슬롯 첫 바이트를 읽는 명령 (`base + 슬롯 * PACKET_SLOT_SIZE`)
+    fn load_slot_byte(base: i32) -> Vec<u8> {
+        let mut code = vec![0x20, 0x00, 0x41]; // local.get 0; i32.const PACKET_SLOT_SIZE
+        sleb(PACKET_SLOT_SIZE as i32, &mut code);
+        code.extend_from_slice(&[0x6c, 0x41]); // i32.mul; i32.const base
+        sleb(base, &mut code);
+        code.extend_from_slice(&[0x6a, 0x2d, 0x00, 0x00]); // i32.add; i32.load8_u
+        code
+    }
+
+    /// 패킷 링을 두고 `길이 << 16 | 슬롯 첫 바이트 << 8 | (슬롯 + 1)`을 판정으로 돌려주는 모듈
+    fn ring_module(base: i32) -> Vec<u8> {
+        let mut inspect = vec![
+            0x00,
+            0x20, 0x01, 0x41, 0x10, 0x74, // local.get 1; i32.const 16; i32.shl
+        ];
+        inspect.extend(load_slot_byte(base));
+        inspect.extend_from_slice(&[
+            0x41, 0x08, 0x74, 0x72, // i32.const 8; i32.shl; i32.or
+            0x20, 0x00, 0x41, 0x01, 0x6a, 0x72, // local.get 0; i32.const 1; i32.add; i32.or
+            0x0b,
+        ]);
+
+        ring_module_with(base, inspect)
+    }
+
+    /// 모듈 바이트로 워커 인스턴스 생성
+    fn instance(wasm: &[u8]) -> Result<InspectorInstance> {
+        let mut inspector = WasmInspector::new("test", Path::new("test.wasm"))?;
+        inspector.module = Some(Module::new(&inspector.engine, wasm)?);
+        inspector.state = ModuleState::Loaded;
+        InspectorInstance::new(&Arc::new(inspector))
+    }
+
+    /// 스텁 모듈을 임시 파일로 저장
+    fn write_module(iterations: i32) -> PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "swift-guard-wasm-{}-{}.wasm", std::process::id(), NEXT_MODULE.fetch_add(1, Ordering::SeqCst)));
+        std::fs::write(&path, stub_module(iterations)).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_packet_ring_slots_wrap() {
+        let mut instance = instance(&ring_module(RING_BASE)).unwrap();
+        assert!(matches!(instance.guest.handoff, Handoff::Ring { base: 4096, next: 0 }));
+
+        // 슬롯 번호는 링 크기마다 되돌아가고 패킷은 해당 슬롯에 기록
+        for i in 0..PACKET_SLOTS * 2 + 3 {
+            let frame = vec![i as u8 + 1; 1500];
+            let verdict = 1500 << 16 | (i as i32 + 1) << 8 | ((i % PACKET_SLOTS) as i32 + 1);
+            assert_eq!(instance.inspect(&frame, &PacketContext::default()).unwrap(), Some(format!("verdict {}", verdict)));
+        }
+
+        // 앞선 슬롯은 링이 돌 때까지 유지
+        let data = instance.guest.memory.data(&instance.guest.store);
+        let first = RING_BASE as usize;
+        assert_eq!(data[first], PACKET_SLOTS as u8 * 2 + 1);
+        assert_eq!(data[first + PACKET_SLOT_SIZE as usize * 3], PACKET_SLOTS as u8 + 4);
+    }
+
+    #[test]
+    fn test_packet_ring_oversized_frame() {
+        let mut instance = instance(&ring_module(RING_BASE)).unwrap();
+
+        // 슬롯보다 긴 프레임은 잘라서 전달하고 다음 슬롯을 덮어쓰지 않음
+        let mut frame = vec![7u8; 9000];
+        frame[PACKET_SLOT_SIZE as usize..].fill(9);
+        let verdict = (PACKET_SLOT_SIZE as i32) << 16 | 7 << 8 | 1;
+        assert_eq!(instance.inspect(&frame, &PacketContext::default()).unwrap(), Some(format!("verdict {}", verdict)));
+
+        let data = instance.guest.memory.data(&instance.guest.store);
+        let next = (RING_BASE + PACKET_SLOT_SIZE as i32) as usize;
+        assert_eq!(data[next - 1], 7);
+        assert_eq!(data[next], 0);
+
+        // 메모리에 들어가지 않는 링은 인스턴스 생성 실패
+        let err = instance_err(&ring_module(65536 - 4096));
+        assert!(err.contains("does not fit"), "{}", err);
+    }
+
+    #[test]
+    fn test_module_config() {
+        // read_config(0, config_len())로 설정을 0번지에 복사하고
+        // `설정 길이 << 8 | 첫 바이트`를 판정으로 돌려주는 모듈
+        let inspect = vec![
+            0x00,
+            0x41, 0x00, 0x10, 0x00, 0x10, 0x01, 0x1a, // i32.const 0; call config_len; call read_config; drop
+            0x41, 0x00, 0x2d, 0x00, 0x00, // i32.const 0; i32.load8_u
+            0x10, 0x00, 0x41, 0x08, 0x74, 0x72, // call config_len; i32.const 8; i32.shl; i32.or
+            0x0b,
+        ];
+        let wasm = build_module_with_imports(&[(0, "config_len"), (2, "read_config")], &[(2, "inspect_packet", inspect)]);
+
+        let mut inspector = WasmInspector::new("test", Path::new("test.wasm")).unwrap();
+        inspector.module = Some(Module::new(&inspector.engine, &wasm).unwrap());
+        inspector.set_config(b"pattern".to_vec());
+        let mut configured = InspectorInstance::new(&Arc::new(inspector)).unwrap();
+        assert_eq!(configured.inspect(&[0u8; 64], &PacketContext::default()).unwrap(), Some(format!("verdict {}", 7 << 8 | b'p' as i32)));
+
+        // 설정이 없으면 길이 0, 복사할 것도 없음
+        let mut unconfigured = instance(&wasm).unwrap();
+        assert_eq!(unconfigured.inspect(&[0u8; 64], &PacketContext::default()).unwrap(), None);
+
+        let path = write_module(1);
+        let manager = WasmManager::new();
+        let err = manager.load_module_with_policy("big", &path, None, vec![0; MAX_MODULE_CONFIG_LEN + 1], WasmModuleMode::Enforce).unwrap_err();
+        assert!(matches!(err, DaemonError::Validation(ref e) if e.field == "config"), "{}", err);
+        manager.load_module_with_policy("small", &path, None, vec![0; MAX_MODULE_CONFIG_LEN], WasmModuleMode::Enforce).unwrap();
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    /// 문맥 주소
+    const CONTEXT_BASE: i32 = 2048;
+
+    /// `base`에 문맥 버퍼를 두고 `source << 16 | action << 8 | 레이블 첫 바이트`를 판정으로 돌려주는 v2 모듈
+    fn context_module(base: Option<i32>) -> Vec<u8> {
+        let inspect = vec![
+            0x00,
+            0x20, 0x02, 0x2d, 0x00, 0x00, // local.get 2; i32.load8_u
+            0x20, 0x02, 0x2d, 0x00, 0x20, 0x41, 0x08, 0x74, 0x72, // local.get 2; i32.load8_u offset=32; i32.const 8; i32.shl; i32.or
+            0x20, 0x02, 0x2d, 0x00, 0x21, 0x41, 0x10, 0x74, 0x72, // local.get 2; i32.load8_u offset=33; i32.const 16; i32.shl; i32.or
+            0x0b,
+        ];
+        let mut funcs = vec![(3, "inspect_packet_v2", inspect)];
+        if let Some(base) = base {
+            let mut get_buffer = vec![0x00, 0x41];
+            sleb(base, &mut get_buffer);
+            get_buffer.push(0x0b);
+            funcs.push((0, "get_context_buffer", get_buffer));
+        }
+        build_module(&funcs)
+    }
+
+    #[test]
+    fn test_inspect_packet_v2_context() {
+        let mut instance = instance(&context_module(Some(CONTEXT_BASE))).unwrap();
+        assert!(matches!(instance.guest.inspect_func, InspectFunc::V2 { context: 2048, .. }));
+
+        let matched = PacketContext { label: "untrusted-ingress".to_string(), action: 4, source: ContextSource::Rematch };
+        assert_eq!(instance.inspect(&[0u8; 64], &matched).unwrap(), Some(format!("verdict {}", 1 << 16 | 4 << 8 | b'u' as i32)));
+
+        // 짧은 레이블이 앞 패킷의 레이블 뒷부분을 남기지 않음
+        let short = PacketContext { label: "web".to_string(), action: 2, source: ContextSource::Rematch };
+        assert_eq!(instance.inspect(&[0u8; 64], &short).unwrap(), Some(format!("verdict {}", 1 << 16 | 2 << 8 | b'w' as i32)));
+        let data = instance.guest.memory.data(&instance.guest.store);
+        assert_eq!(&data[CONTEXT_BASE as usize..CONTEXT_BASE as usize + 4], b"web\0");
+
+        // 규칙 정보가 없으면 빈 문맥 (이 모듈은 통과)
+        assert_eq!(instance.inspect(&[0u8; 64], &PacketContext::default()).unwrap(), None);
+
+        // 문맥 버퍼가 없거나 메모리를 넘음
+        assert!(instance_err(&context_module(None)).contains("no get_context_buffer function"));
+        assert!(instance_err(&context_module(Some(65536 - 16))).contains("does not fit"));
+
+        // 관리자를 거친 검사 (문맥 없는 요청은 빈 문맥)
+        let path = std::env::temp_dir().join(format!(
+            "swift-guard-wasm-{}-{}.wasm", std::process::id(), NEXT_MODULE.fetch_add(1, Ordering::SeqCst)));
+        std::fs::write(&path, context_module(Some(CONTEXT_BASE))).unwrap();
+        let manager = WasmManager::new();
+        manager.load_module("ctx", &path).unwrap();
+        assert!(manager.inspect_packet_with_context(&[0u8; 64], matched).unwrap());
+        assert!(!manager.inspect_packet(&[0u8; 64]).unwrap());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    fn instance_err(wasm: &[u8]) -> String {
+        match instance(wasm) {
+            Ok(_) => panic!("instance should fail"),
+            Err(e) => format!("{:#}", e),
+        }
+    }
+
+    #[test]
+    #[ignore]  // 실제 실행시 --ignored 플래그로 실행
+    fn test_packet_handoff_speed() {
+        const PACKETS: u32 = 200_000;
+
+        // 두 모듈 모두 패킷 첫 바이트(0)를 판정으로 돌려줌
+        let frame = vec![0u8; 1500];
+        let mut inspect = vec![0x00];
+        inspect.extend(load_slot_byte(RING_BASE));
+        inspect.push(0x0b);
+        let mut ring = instance(&ring_module_with(RING_BASE, inspect)).unwrap();
+        let mut copy = instance(&build_module(&[
+            (2, "inspect_packet", vec![0x00, 0x20, 0x00, 0x2d, 0x00, 0x00, 0x0b]),
+            (1, "allocate", vec![0x00, 0x41, 0x80, 0x08, 0x0b]), // 1024
+        ])).unwrap();
+        assert!(matches!(copy.guest.handoff, Handoff::Allocate(_)));
+
+        let elapsed = |instance: &mut InspectorInstance| {
+            let started = Instant::now();
+            for _ in 0..PACKETS {
+                instance.inspect(&frame, &PacketContext::default()).unwrap();
+            }
+            started.elapsed()
+        };
+        // 첫 실행은 워밍업
+        elapsed(&mut ring);
+        elapsed(&mut copy);
+        let copy_time = elapsed(&mut copy);
+        let ring_time = elapsed(&mut ring);
+
+        println!("{} x 1500-byte frames: packet ring {:?} ({:.0} ns/packet), allocate path {:?} ({:.0} ns/packet)",
+            PACKETS, ring_time, ring_time.as_nanos() as f64 / PACKETS as f64,
+            copy_time, copy_time.as_nanos() as f64 / PACKETS as f64);
+    }
+
+    #[test]
+    fn test_overload_policy() {
+        let path = write_module(20_000_000);
+        let manager = WasmManager::new().with_pool(1, 1, OverloadPolicy::Drop);
+        manager.load_module("slow", &path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        // 느린 워커 하나와 대기열 하나: 나머지는 과부하
+        let submissions: Vec<Submission> = (0..20)
+            .map(|_| manager.submit_with_context(&[0u8; 64], PacketContext::default()).unwrap())
+            .collect();
+
+        let mut queued = 0;
+        let mut overloaded = 0;
+        for submission in submissions {
+            match submission {
+                Submission::Queued(verdict) => {
+                    assert!(!verdict.recv().unwrap().unwrap());
+                    queued += 1;
+                },
+                Submission::Overloaded { blocked } => {
+                    assert!(blocked);
+                    overloaded += 1;
+                },
+            }
+        }
+
+        assert!(overloaded > 0);
+        assert_eq!(manager.overload_count(), overloaded);
+        assert_eq!(manager.module_stats("slow").unwrap().processed_packets, queued);
+
+        // 대기열이 비면 다시 검사
+        assert!(!manager.inspect_packet(&[0u8; 64]).unwrap());
+    }
+
+    #[test]
+    fn test_worker_stats_roll_up() {
+        let path = write_module(1);
+        let manager = WasmManager::new().with_pool(4, 64, OverloadPolicy::Pass);
+        manager.load_module("stub", &path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        // 판정은 완료 순서와 관계없이 패킷별로 전달
+        let verdicts: Vec<Receiver<Result<bool>>> = (0..40u8)
+            .map(|i| match manager.submit_with_context(&[i % 2; 64], PacketContext::default()).unwrap() {
+                Submission::Queued(verdict) => verdict,
+                Submission::Overloaded { .. } => panic!("queue should not overflow"),
+            })
+            .collect();
+        for (i, verdict) in verdicts.into_iter().enumerate() {
+            assert_eq!(verdict.recv().unwrap().unwrap(), i % 2 == 1);
+        }
+
+        let inspector = manager.modules.get("stub").unwrap();
+        let workers = inspector.worker_stats();
+        assert!(!workers.is_empty() && workers.len() <= 4);
+        assert_eq!(workers.iter().map(|(p, _)| p).sum::<u64>(), 40);
+        assert_eq!(inspector.stats(), (40, 20));
+        let stats = manager.module_stats("stub").unwrap();
+        assert!(stats.avg_processing_time_us > 0.0);
+        assert_eq!(stats.latency.bounds_us, WASM_LATENCY_BUCKET_BOUNDS_US);
+        assert_eq!(stats.latency.total(), 40);
+        assert!(stats.latency.percentile(0.99).is_some());
+        assert_eq!(manager.overload_count(), 0);
+
+        // 초기화하면 모든 워커의 카운터와 분포가 0
+        manager.reset_module_stats("stub").unwrap();
+        let stats = manager.module_stats("stub").unwrap();
+        assert_eq!((stats.processed_packets, stats.blocked_packets, stats.errors), (0, 0, 0));
+        assert_eq!(stats.latency.total(), 0);
+        assert!(matches!(manager.reset_module_stats("missing"), Err(DaemonError::ModuleNotFound(_))));
+    }
+
+    /// 컴파일이 오래 걸리는 모듈 (`ops`개의 덧셈을 푼 본문)
+    fn write_heavy_module(ops: usize) -> PathBuf {
+        let mut body = vec![0x00, 0x20, 0x00]; // local.get 0
+        for i in 0..ops {
+            body.push(0x41); // i32.const i
+            sleb(i as i32, &mut body);
+            body.push(0x6a); // i32.add
+        }
+        body.extend_from_slice(&[0x1a, 0x41, 0x00, 0x0b]); // drop; i32.const 0; end
+
+        let path = write_module(1);
+        std::fs::write(&path, build_module(&[(2, "inspect_packet", body)])).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_load_does_not_block_inspection() {
+        let stub = write_module(1);
+        let heavy = write_heavy_module(20_000);
+        let manager = Arc::new(WasmManager::new().with_pool(2, 64, OverloadPolicy::Pass));
+        manager.load_module("a", &stub).unwrap();
+        assert!(manager.inspect_packet(&[1u8; 64]).unwrap());
+
+        // 모듈 B를 컴파일하는 동안 모듈 A의 검사와 조회가 계속됨
+        let loader = {
+            let manager = Arc::clone(&manager);
+            let heavy = heavy.clone();
+            std::thread::spawn(move || {
+                let started = Instant::now();
+                manager.load_module("b", &heavy).unwrap();
+                started.elapsed()
+            })
+        };
+
+        let mut inspected = 0;
+        let mut slowest = Duration::ZERO;
+        while !loader.is_finished() {
+            let started = Instant::now();
+            assert!(manager.inspect_packet(&[1u8; 64]).unwrap());
+            assert!(!manager.list_modules().unwrap().is_empty());
+            slowest = slowest.max(started.elapsed());
+            inspected += 1;
+        }
+        let load_time = loader.join().unwrap();
+
+        assert!(inspected > 10, "only {} packets inspected during a {:?} load", inspected, load_time);
+        assert!(slowest < Duration::from_millis(50).max(load_time / 10),
+            "inspection blocked for {:?} during a {:?} load", slowest, load_time);
+
+        let names: Vec<String> = manager.list_modules().unwrap().into_iter().map(|m| m.name).collect();
+        assert_eq!(names, vec!["a", "b"]);
+        let err = manager.load_module("a", &stub).unwrap_err();
+        assert!(matches!(err, DaemonError::DuplicateModule(ref id) if id == "a"));
+        assert!(matches!(manager.load_module("c", Path::new("/nonexistent.wasm")), Err(DaemonError::Wasm(_))));
+
+        std::fs::remove_file(&stub).unwrap();
+        std::fs::remove_file(&heavy).unwrap();
+    }
+
+    #[test]
+    fn test_unload_while_inspecting() {
+        let stub = write_module(100);
+        let manager = Arc::new(WasmManager::new().with_pool(4, 256, OverloadPolicy::Pass));
+        manager.load_module("a", &stub).unwrap();
+        manager.load_module("b", &stub).unwrap();
+
+        let inspectors: Vec<_> = (0..4u8)
+            .map(|n| {
+                let manager = Arc::clone(&manager);
+                std::thread::spawn(move || {
+                    for i in 0..500u32 {
+                        let blocked = (i + n as u32) % 2 == 1;
+                        assert_eq!(manager.inspect_packet(&[blocked as u8; 64]).unwrap(), blocked);
+                    }
+                })
+            })
+            .collect();
+
+        // 검사 중에 모듈 B를 반복해서 언로드/로드
+        for _ in 0..50 {
+            manager.unload_module("b").unwrap();
+            let err = manager.module_stats("b").unwrap_err();
+            assert_eq!(err.to_string(), "WASM module b is not loaded");
+            manager.load_module("b", &stub).unwrap();
+            manager.module_stats("b").unwrap();
+        }
+
+        for inspector in inspectors {
+            inspector.join().unwrap();
+        }
+
+        assert_eq!(manager.module_stats("a").unwrap().processed_packets, 2000);
+        manager.unload_module("a").unwrap();
+        manager.unload_module("b").unwrap();
+        assert!(matches!(manager.unload_module("a"), Err(DaemonError::ModuleNotFound(ref id)) if id == "a"));
+        assert!(!manager.inspect_packet(&[1u8; 64]).unwrap());
+        assert!(manager.list_modules().unwrap().is_empty());
+
+        std::fs::remove_file(&stub).unwrap();
+    }
+
+    /// 패킷 첫 바이트가 0xff면 트랩, 아니면 첫 바이트를 판정으로 돌려주는 모듈
+    fn write_trap_module() -> PathBuf {
+        let body = vec![
+            0x00,
+            0x20, 0x00, 0x2d, 0x00, 0x00, // local.get 0; i32.load8_u
+            0x41, 0xff, 0x01, 0x46, // i32.const 255; i32.eq
+            0x04, 0x40, 0x00, 0x0b, // if; unreachable; end
+            0x20, 0x00, 0x2d, 0x00, 0x00, // local.get 0; i32.load8_u
+            0x0b,
+        ];
+        let path = write_module(1);
+        std::fs::write(&path, build_module(&[(2, "inspect_packet", body)])).unwrap();
+        path
+    }
+
+    /// 패킷 두 번째 바이트를 판정으로 돌려주는 모듈
+    fn write_second_byte_module() -> PathBuf {
+        let body = vec![0x00, 0x20, 0x00, 0x2d, 0x00, 0x01, 0x0b]; // local.get 0; i32.load8_u offset=1
+        let path = write_module(1);
+        std::fs::write(&path, build_module(&[(2, "inspect_packet", body)])).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_error_policy() {
+        let trap = write_trap_module();
+        let second = write_second_byte_module();
+        let manager_with = |default: WasmErrorPolicy, policy: Option<WasmErrorPolicy>| {
+            let manager = WasmManager::new().with_pool(1, 8, OverloadPolicy::Pass).with_error_policy(default);
+            manager.load_module_with_policy("trap", &trap, policy, Vec::new(), WasmModuleMode::Enforce).unwrap();
+            manager.load_module("second", &second).unwrap();
+            manager
+        };
+
+        // pass: 오류 난 모듈은 판정 없이 넘어가고 다음 모듈은 계속 검사
+        let manager = manager_with(WasmErrorPolicy::Pass, None);
+        assert!(!manager.inspect_packet(&[0xff, 0]).unwrap());
+        assert!(manager.inspect_packet(&[0xff, 1]).unwrap());
+        assert!(manager.inspect_packet(&[1, 0]).unwrap());
+        let stats = manager.module_stats("trap").unwrap();
+        assert_eq!((stats.state, stats.errors, stats.processed_packets), (ModuleState::Loaded, 2, 1));
+        assert!(stats.last_error.unwrap().contains("inspect_packet"));
+
+        // drop: 오류가 나면 차단 (로드 시 지정한 정책이 기본값보다 우선)
+        let manager = manager_with(WasmErrorPolicy::Pass, Some(WasmErrorPolicy::Drop));
+        assert!(manager.inspect_packet(&[0xff, 0]).unwrap());
+        assert!(!manager.inspect_packet(&[0, 0]).unwrap());
+        assert_eq!(manager.module_stats("trap").unwrap().errors, 1);
+        assert_eq!(manager.module_stats("second").unwrap().processed_packets, 1);
+
+        // disable_module: 재개할 때까지 검사에서 제외, 오류는 유지
+        let manager = manager_with(WasmErrorPolicy::DisableModule, None);
+        let err = manager.resume_module("trap").unwrap_err();
+        assert!(matches!(err, DaemonError::Validation(_)));
+        assert!(err.to_string().contains("not disabled"));
+        assert!(!manager.inspect_packet(&[0xff, 0]).unwrap());
+        assert!(!manager.inspect_packet(&[1, 0]).unwrap());
+        let stats = manager.module_stats("trap").unwrap();
+        assert_eq!((stats.state, stats.errors, stats.processed_packets), (ModuleState::Error, 1, 0));
+        assert!(stats.last_error.is_some());
+        assert_eq!(manager.list_modules().unwrap()[0].state, "error");
+        assert_eq!(manager.module_stats("second").unwrap().processed_packets, 2);
+
+        manager.resume_module("trap").unwrap();
+        assert!(manager.inspect_packet(&[1, 0]).unwrap());
+        let stats = manager.module_stats("trap").unwrap();
+        assert_eq!((stats.state, stats.errors), (ModuleState::Loaded, 1));
+        assert!(stats.last_error.is_some());
+        assert!(matches!(manager.resume_module("missing"), Err(DaemonError::ModuleNotFound(_))));
+
+        std::fs::remove_file(&trap).unwrap();
+        std::fs::remove_file(&second).unwrap();
+    }
+
+    #[test]
+    fn test_shadow_mode() {
+        let trap = write_trap_module();
+        let second = write_second_byte_module();
+        let manager = WasmManager::new().with_pool(2, 8, OverloadPolicy::Pass);
+        manager.load_module_with_policy("trap", &trap, Some(WasmErrorPolicy::Drop), Vec::new(), WasmModuleMode::Shadow).unwrap();
+        manager.load_module("second", &second).unwrap();
+
+        // shadow 모듈의 차단 판정과 drop 오류 정책은 패킷에 적용되지 않음
+        for _ in 0..3 {
+            assert!(!manager.inspect_packet(&[1, 0]).unwrap());
+        }
+        assert!(!manager.inspect_packet(&[0, 0]).unwrap());
+        assert!(!manager.inspect_packet(&[0xff, 0]).unwrap());
+        // enforce 모듈의 판정은 그대로 적용
+        assert!(manager.inspect_packet(&[1, 1]).unwrap());
+
+        let stats = manager.module_stats("trap").unwrap();
+        assert_eq!(stats.mode, WasmModuleMode::Shadow);
+        assert_eq!((stats.processed_packets, stats.blocked_packets, stats.would_block_packets, stats.errors), (5, 0, 4, 1));
+        let log = manager.module_log("trap", 0, None).unwrap();
+        assert_eq!(log.iter().filter(|line| line.message == "shadow: would block: verdict 1").count(), 4);
+        let info = &manager.list_modules().unwrap()[0];
+        assert_eq!((info.mode, info.would_block_packets), (WasmModuleMode::Shadow, 4));
+
+        // enforce로 바꿔도 카운터는 유지되고 다음 패킷부터 판정을 적용
+        manager.set_module_mode("trap", WasmModuleMode::Enforce).unwrap();
+        assert!(manager.inspect_packet(&[1, 0]).unwrap());
+        assert!(manager.inspect_packet(&[0xff, 0]).unwrap());
+        let stats = manager.module_stats("trap").unwrap();
+        assert_eq!(stats.mode, WasmModuleMode::Enforce);
+        assert_eq!((stats.processed_packets, stats.blocked_packets, stats.would_block_packets, stats.errors), (6, 1, 4, 2));
+        assert!(matches!(manager.set_module_mode("missing", WasmModuleMode::Shadow), Err(DaemonError::ModuleNotFound(_))));
+
+        std::fs::remove_file(&trap).unwrap();
+        std::fs::remove_file(&second).unwrap();
+    }
+
+    #[test]
+    fn test_module_log_trims_whole_lines() {
+        let log = ModuleLog::new(3);
+        log.push("one", 1);
+        log.push("two\nthree\nfour", 2);
+        
+        // 여러 줄 메시지도 줄 단위로 밀려남
+        let lines = log.tail(0, None);
+        let messages: Vec<&str> = lines.iter().map(|line| line.message.as_str()).collect();
+        assert_eq!(messages, ["two", "three", "four"]);
+        assert_eq!(lines.iter().map(|line| line.seq).collect::<Vec<_>>(), [2, 3, 4]);
+        assert_eq!(lines[0].timestamp_ms, 2);
+        
+        assert_eq!(log.tail(1, None)[0].message, "four");
+        assert_eq!(log.tail(10, Some(2)).len(), 2);
+        assert!(log.tail(0, Some(4)).is_empty());
+        
+        // 긴 줄은 문자 경계에서 자름
+        log.push(&"가".repeat(MAX_LOG_LINE_LEN), 3);
+        let long = log.tail(1, None).pop().unwrap();
+        assert!(long.message.len() <= MAX_LOG_LINE_LEN);
+        assert!(long.message.chars().all(|c| c == '가'));
+    }
+    
+    #[test]
+    fn test_module_log_concurrent_writes() {
+        let log = Arc::new(ModuleLog::new(100));
+        let writers: Vec<_> = (0..4)
+            .map(|writer| {
+                let log = Arc::clone(&log);
+                std::thread::spawn(move || {
+                    for i in 0..500 {
+                        log.push(&format!("writer {} line {}", writer, i), i);
+                    }
+                })
+            })
+            .collect();
+        
+        // 기록 중에 읽어도 줄이 섞이거나 순번이 어긋나지 않음
+        while writers.iter().any(|writer| !writer.is_finished()) {
+            let lines = log.tail(0, None);
+            assert!(lines.len() <= 100);
+            assert!(lines.windows(2).all(|pair| pair[1].seq == pair[0].seq + 1));
+            assert!(lines.iter().all(|line| line.message.starts_with("writer ")));
+        }
+        for writer in writers {
+            writer.join().unwrap();
+        }
+        
+        let lines = log.tail(0, None);
+        assert_eq!(lines.len(), 100);
+        assert_eq!(lines.last().unwrap().seq, 2000);
+    }
+    
+    #[test]
+    fn test_manager_module_log() {
+        let path = write_module(1);
+        let manager = WasmManager::new().with_pool(1, 8, OverloadPolicy::Pass).with_log_lines(2);
+        manager.load_module("stub", &path).unwrap();
+        assert!(manager.module_log("stub", 10, None).unwrap().is_empty());
+        
+        manager.modules.get("stub").unwrap().log().push("a\nb\nc", 1);
+        let lines = manager.module_log("stub", 10, None).unwrap();
+        assert_eq!(lines.iter().map(|line| line.message.as_str()).collect::<Vec<_>>(), ["b", "c"]);
+        assert!(matches!(manager.module_log("missing", 10, None), Err(DaemonError::ModuleNotFound(_))));
+        
+        std::fs::remove_file(&path).unwrap();
+    }
+    
+    #[test]
+    fn test_module_cache() {
+        let path = write_module(1);
+        let dir = path.with_extension("cache");
+        let manager = WasmManager::new().with_pool(1, 8, OverloadPolicy::Pass)
+            .with_cache(ModuleCache::new(&dir));
+
+        // 처음에는 컴파일, 같은 내용이면 캐시 사용
+        assert!(!manager.load_module("first", &path).unwrap().cached);
+        let report = manager.load_module("second", &path).unwrap();
+        assert!(report.cached);
+        assert!(report.to_string().ends_with("from cache"));
+        assert!(manager.inspect_packet(&[1u8; 64]).unwrap());
+
+        // 엔진 지문이 바뀌면 캐시를 비우고 다시 컴파일
+        std::fs::write(dir.join(ENGINE_STAMP), "older engine").unwrap();
+        assert!(!manager.load_module("third", &path).unwrap().cached);
+        assert!(manager.load_module("fourth", &path).unwrap().cached);
+
+        // 다른 모듈은 별도 항목
+        let other = write_module(2);
+        assert!(!manager.load_module("other", &other).unwrap().cached);
+
+        let cache = manager.cache().unwrap();
+        assert_eq!(cache.clear().unwrap(), 2);
+        assert_eq!(cache.clear().unwrap(), 0);
+        assert!(!manager.load_module("fifth", &path).unwrap().cached);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        std::fs::remove_file(&other).unwrap();
+    }
+
+    #[test]
+    fn test_count_physical_cores() {
+        // 코어 2개에 하이퍼스레드 2개씩
+        let cpuinfo = (0..4)
+            .map(|cpu| format!("processor\t: {}\nphysical id\t: 0\ncore id\t\t: {}\n", cpu, cpu % 2))
+            .collect::<Vec<_>>()
+            .join("\n");
+        assert_eq!(count_physical_cores(&cpuinfo), Some(2));
+
+        // 코어 정보가 없는 경우
+        assert_eq!(count_physical_cores("processor\t: 0\nBogoMIPS\t: 50.00\n"), None);
+    }
+
+    #[test]
+    fn test_block_reason() {
+        assert_eq!(block_reason("checking\nSQL injection in query string\n", 1), "SQL injection in query string");
+        assert_eq!(block_reason("", 3), "verdict 3");
+    }
+
+    #[test]
+    fn test_packet_src_ip() {
+        let mut frame = crate::packet::UdpFrame::new(0xC0A8010A, 0xC0A80101, 40000, 53).build();
+        assert_eq!(packet_src_ip(&frame), Some(IpAddr::from([192, 168, 1, 10])));
+
+        // 이더넷 유형과 다른 IP 버전 또는 잘린 프레임
+        frame[12..14].copy_from_slice(&[0x86, 0xdd]);
+        assert_eq!(packet_src_ip(&frame), None);
+        assert_eq!(packet_src_ip(&frame[..20]), None);
+    }
+
+    #[test]
+    fn test_block_verdicts_queued() {
+        let path = write_module(1);
+        let manager = WasmManager::new().with_pool(1, 8, OverloadPolicy::Pass);
+        manager.load_module("stub", &path).unwrap();
+
+        // 스텁 모듈은 첫 바이트(목적지 MAC)를 판정으로 돌려줌
+        let frame = |dst_mac: [u8; 6]| crate::packet::UdpFrame {
+            dst_mac,
+            ..crate::packet::UdpFrame::new(0xC0A8010A, 0xC0A80101, 40000, 53)
+        }
+        .build();
+        assert!(manager.inspect_packet(&frame([1, 0, 0, 0, 0, 0])).unwrap());
+        // 통과 판정과 IP 헤더가 없는 프레임은 동적 차단 대상이 아님
+        assert!(!manager.inspect_packet(&frame([0; 6])).unwrap());
+        assert!(manager.inspect_packet(&[1, 0]).unwrap());
+
+        let expected = BlockVerdict { module: "stub".to_string(), src_ip: 0xC0A8010A, reason: "verdict 1".to_string() };
+        assert_eq!(manager.take_blocks(), vec![expected]);
+        assert!(manager.take_blocks().is_empty());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_auto_load() {
+        let dir = std::env::temp_dir().join(format!("swift-guard-wasm-auto-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("first.wasm"), stub_module(1)).unwrap();
+        std::fs::write(dir.join("second.wasm"), stub_module(1)).unwrap();
+
+        let mut config = crate::config::DaemonConfig::default().wasm;
+        config.modules_dir = dir.to_string_lossy().into_owned();
+        config.auto_load_modules = vec!["first.wasm".to_string(), "second".to_string(), "missing".to_string()];
+        let manager = WasmManager::new();
+        assert_eq!(manager.auto_load(&config), 0);
+
+        // 확장자가 없는 이름은 .wasm을 붙이고, 없는 모듈은 건너뜀
+        config.auto_load = true;
+        assert_eq!(manager.auto_load(&config), 2);
+        let names: Vec<String> = manager.list_modules().unwrap().into_iter().map(|info| info.name).collect();
+        assert_eq!(names, vec!["first", "second"]);
+
+        std::fs::remove_dir_all(&dir).unwrap();
     }
 }
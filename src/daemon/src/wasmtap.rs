@@ -0,0 +1,152 @@
+//! WASM 검사 탭 모듈
+//! 검사 인터페이스(`wasm.interface`)로 들어온 프레임을 WASM 모듈로 검사하고 차단 판정을 동적 차단으로 반영
+//!
+//! redirect 규칙이 XDP에서 넘긴 프레임은 이미 검사 인터페이스로 전달됐으므로 판정으로 그
+//! 프레임을 되돌릴 수는 없다. enforce 모듈이 차단한 패킷의 소스 주소는 동적 차단 규칙으로
+//! 막아 다음 패킷부터 필터 단계에서 버린다. 판정은 워커가 끝내는 대로 모아 주기적으로
+//! 규칙 맵에 반영한다.
+
+use anyhow::{anyhow, Context, Result};
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::io::unix::AsyncFd;
+use tokio::io::Interest;
+use tokio::time;
+
+use crate::dynblock::{self, DynamicBlockLimits};
+use crate::inspectctx::PacketContext;
+use crate::logthrottle;
+use crate::maps::MapManager;
+use crate::netif;
+use crate::wasm::WasmManager;
+use swift_guard::utils;
+
+/// 차단 판정 반영 주기
+const BLOCK_INTERVAL: Duration = Duration::from_millis(200);
+
+/// 수신 버퍼 크기 (바이트, 더 긴 프레임은 잘림)
+const FRAME_BUFFER_SIZE: usize = 65536;
+
+/// 한 번 깨어날 때 읽는 최대 프레임 수 (판정 반영이 밀리지 않도록)
+const FRAME_BATCH: usize = 256;
+
+/// 검사 인터페이스 수신 소켓
+pub struct WasmTap {
+    /// 검사 인터페이스 이름
+    interface: String,
+    /// AF_PACKET raw 소켓
+    socket: AsyncFd<OwnedFd>,
+}
+
+impl WasmTap {
+    /// 인터페이스의 모든 프레임을 받는 AF_PACKET 소켓 열기 (CAP_NET_RAW 필요)
+    pub fn open(interface: &str) -> Result<Self> {
+        let ifindex = netif::if_index(interface)?;
+        let protocol = (libc::ETH_P_ALL as u16).to_be();
+
+        // SAFETY: 새 소켓은 바로 OwnedFd가 소유하고, addr은 bind 호출 동안 유효하다
+        let socket = unsafe {
+            let fd = libc::socket(libc::AF_PACKET, libc::SOCK_RAW | libc::SOCK_NONBLOCK | libc::SOCK_CLOEXEC,
+                i32::from(protocol));
+            if fd < 0 {
+                return Err(std::io::Error::last_os_error()).context("Failed to open raw packet socket");
+            }
+            let socket = OwnedFd::from_raw_fd(fd);
+
+            let mut addr: libc::sockaddr_ll = std::mem::zeroed();
+            addr.sll_family = libc::AF_PACKET as u16;
+            addr.sll_protocol = protocol;
+            addr.sll_ifindex = ifindex as i32;
+            if libc::bind(fd, &addr as *const libc::sockaddr_ll as *const libc::sockaddr,
+                std::mem::size_of::<libc::sockaddr_ll>() as libc::socklen_t) < 0
+            {
+                return Err(std::io::Error::last_os_error())
+                    .with_context(|| format!("Failed to bind raw packet socket to {}", interface));
+            }
+            socket
+        };
+
+        let socket = AsyncFd::with_interest(socket, Interest::READABLE)
+            .with_context(|| format!("Failed to watch raw packet socket on {}", interface))?;
+        Ok(Self { interface: interface.to_string(), socket })
+    }
+
+    /// 검사 인터페이스 이름
+    pub fn interface(&self) -> &str {
+        &self.interface
+    }
+
+    /// 프레임을 읽어 검사로 보내고 차단 판정을 동적 차단으로 반영 (소켓 오류가 날 때까지 계속)
+    pub async fn run(&self, wasm: &WasmManager, map_manager: &Mutex<MapManager<'_>>, limits: DynamicBlockLimits) -> Result<()> {
+        let mut frame = vec![0u8; FRAME_BUFFER_SIZE];
+        let mut apply = time::interval(BLOCK_INTERVAL);
+
+        loop {
+            tokio::select! {
+                guard = self.socket.readable() => {
+                    let mut guard = guard
+                        .with_context(|| format!("Failed to wait for frames on {}", self.interface))?;
+                    for _ in 0..FRAME_BATCH {
+                        match self.recv(&mut frame)? {
+                            Some(len) => self.submit(wasm, map_manager, &frame[..len]),
+                            None => {
+                                guard.clear_ready();
+                                break;
+                            },
+                        }
+                    }
+                },
+                _ = apply.tick() => {
+                    let verdicts = wasm.take_blocks();
+                    if !verdicts.is_empty() {
+                        let mut map_manager = map_manager.lock()
+                            .map_err(|_| anyhow!("Failed to lock map_manager"))?;
+                        dynblock::apply(&mut map_manager, verdicts, limits, utils::current_time_secs());
+                    }
+                },
+            }
+        }
+    }
+
+    /// 들어온 프레임 하나 읽기 (읽을 프레임이 없으면 None, 나가는 프레임은 건너뜀)
+    fn recv(&self, frame: &mut [u8]) -> Result<Option<usize>> {
+        loop {
+            // SAFETY: sockaddr_ll은 모든 필드가 0이어도 유효한 C 구조체다
+            let mut addr: libc::sockaddr_ll = unsafe { std::mem::zeroed() };
+            let mut addr_len = std::mem::size_of::<libc::sockaddr_ll>() as libc::socklen_t;
+
+            // SAFETY: frame과 addr은 호출 동안 유효하고 길이를 함께 넘긴다
+            let len = unsafe {
+                libc::recvfrom(self.socket.as_raw_fd(), frame.as_mut_ptr() as *mut libc::c_void, frame.len(), 0,
+                    &mut addr as *mut libc::sockaddr_ll as *mut libc::sockaddr, &mut addr_len)
+            };
+            if len < 0 {
+                let error = std::io::Error::last_os_error();
+                return match error.kind() {
+                    std::io::ErrorKind::WouldBlock => Ok(None),
+                    std::io::ErrorKind::Interrupted => continue,
+                    _ => Err(error).with_context(|| format!("Failed to read frame on {}", self.interface)),
+                };
+            }
+            if addr.sll_pkttype == libc::PACKET_OUTGOING {
+                continue;
+            }
+            return Ok(Some(len as usize));
+        }
+    }
+
+    /// 프레임을 규칙 문맥과 함께 검사 대기열에 넣음
+    ///
+    /// 판정은 워커가 동적 차단 대기열에 남기므로 기다리지 않는다.
+    fn submit(&self, wasm: &WasmManager, map_manager: &Mutex<MapManager<'_>>, frame: &[u8]) {
+        let context = map_manager.lock()
+            .map(|map_manager| map_manager.packet_context(frame))
+            .unwrap_or_else(|_| PacketContext::default());
+
+        if let Err(e) = wasm.submit_with_context(frame, context) {
+            throttled_warn!("wasmtap:submit", logthrottle::HOT_PATH_INTERVAL,
+                "Failed to submit frame from {} for WASM inspection: {}", self.interface, e);
+        }
+    }
+}
@@ -0,0 +1,541 @@
+//! 웹훅 모듈
+//! 이벤트를 HTTP POST(JSON)로 외부에 전달
+//!
+//! 이벤트는 제한된 크기의 큐에 넣기만 하고 전송은 별도 작업이 맡으므로 느린
+//! 웹훅 대상이 텔레메트리 루프나 이벤트 배포를 막지 않는다. 전송 작업은 큐에
+//! 쌓인 이벤트를 최대 `max_batch`개씩 한 요청으로 묶고, 실패하면 재시도 기한까지
+//! 지수 백오프로 다시 보낸다. 큐가 가득 차 버린 이벤트와 기한 안에 보내지 못한
+//! 이벤트는 따로 집계하여 Ping과 Prometheus 메트릭으로 보고한다.
+//!
+//! 본문 형식(`schema_version` 1):
+//! `{"schema_version":1,"sent_at":<UNIX 초>,"events":[{"kind":"rule_hit","data":{...}}]}`
+//! `kind`는 `DaemonEvent::kind`와 같고 `data`는 해당 이벤트 구조체의 JSON이다.
+
+use anyhow::{anyhow, Context, Result};
+use log::{debug, warn};
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+
+use crate::logthrottle;
+
+use swift_guard::api::{
+    AnomalyEvent, DaemonEvent, LinkStateEvent, MitigationEvent, RuleExpiryEvent, RuleHitEvent, RuleMatchedEvent, WebhookStats,
+    XdpAbortedEvent,
+};
+
+/// 웹훅 본문 형식 버전 (필드를 빼거나 의미를 바꿀 때만 올림)
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// 전송 실패 경고 간격
+const FAILURE_LOG_INTERVAL: Duration = Duration::from_secs(30);
+
+/// 웹훅 대상 (http://host[:port]/path)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WebhookTarget {
+    pub host: String,
+    pub port: u16,
+    pub path: String,
+}
+
+impl WebhookTarget {
+    /// URL 파싱 (http만 지원)
+    pub fn parse(url: &str) -> Result<Self> {
+        let rest = url.strip_prefix("http://")
+            .ok_or_else(|| anyhow!("Unsupported webhook URL (http only): {}", url))?;
+
+        let (authority, path) = match rest.find('/') {
+            Some(pos) => (&rest[..pos], &rest[pos..]),
+            None => (rest, "/"),
+        };
+
+        let (host, port) = match authority.rsplit_once(':') {
+            Some((host, port)) => (host, port.parse::<u16>()
+                .map_err(|_| anyhow!("Invalid webhook port: {}", port))?),
+            None => (authority, 80),
+        };
+
+        if host.is_empty() {
+            return Err(anyhow!("Invalid webhook URL: {}", url));
+        }
+
+        Ok(Self {
+            host: host.to_string(),
+            port,
+            path: path.to_string(),
+        })
+    }
+}
+
+/// 웹훅 본문
+#[derive(Debug, Serialize)]
+struct Payload<'a> {
+    schema_version: u32,
+    /// 전송 시각 (UNIX 초)
+    sent_at: u64,
+    events: Vec<PayloadEvent<'a>>,
+}
+
+/// 본문의 이벤트 항목 (`{"kind": ..., "data": {...}}`)
+#[derive(Debug, Serialize)]
+#[serde(tag = "kind", content = "data", rename_all = "snake_case")]
+enum PayloadEvent<'a> {
+    RuleHit(&'a RuleHitEvent),
+    Mitigation(&'a MitigationEvent),
+    RuleMatched(&'a RuleMatchedEvent),
+    LinkState(&'a LinkStateEvent),
+    RuleExpiring(&'a RuleExpiryEvent),
+    RuleExpired(&'a RuleExpiryEvent),
+    XdpAborted(&'a XdpAbortedEvent),
+    Anomaly(&'a AnomalyEvent),
+}
+
+impl<'a> From<&'a DaemonEvent> for PayloadEvent<'a> {
+    fn from(event: &'a DaemonEvent) -> Self {
+        match event {
+            DaemonEvent::RuleHit(event) => Self::RuleHit(event),
+            DaemonEvent::Mitigation(event) => Self::Mitigation(event),
+            DaemonEvent::RuleMatched(event) => Self::RuleMatched(event),
+            DaemonEvent::LinkState(event) => Self::LinkState(event),
+            DaemonEvent::RuleExpiring(event) => Self::RuleExpiring(event),
+            DaemonEvent::RuleExpired(event) => Self::RuleExpired(event),
+            DaemonEvent::XdpAborted(event) => Self::XdpAborted(event),
+            DaemonEvent::Anomaly(event) => Self::Anomaly(event),
+        }
+    }
+}
+
+/// 이벤트 묶음의 웹훅 본문 직렬화
+pub fn encode_payload(events: &[DaemonEvent], sent_at: u64) -> Result<Vec<u8>> {
+    let payload = Payload {
+        schema_version: SCHEMA_VERSION,
+        sent_at,
+        events: events.iter().map(PayloadEvent::from).collect(),
+    };
+    serde_json::to_vec(&payload).context("Failed to serialize webhook payload")
+}
+
+/// JSON 본문 POST (2xx가 아니면 오류)
+pub async fn post(url: &str, payload: &[u8]) -> Result<()> {
+    let target = WebhookTarget::parse(url)?;
+
+    let mut stream = TcpStream::connect((target.host.as_str(), target.port))
+        .await
+        .context(format!("Failed to connect to webhook {}", url))?;
+
+    let header = format!(
+        "POST {} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        target.path, target.host, payload.len()
+    );
+    stream.write_all(header.as_bytes()).await
+        .context("Failed to send webhook request")?;
+    stream.write_all(payload).await
+        .context("Failed to send webhook payload")?;
+
+    // 상태 줄만 확인
+    let mut buf = [0u8; 64];
+    let n = stream.read(&mut buf).await
+        .context("Failed to read webhook response")?;
+    let status_line = String::from_utf8_lossy(&buf[..n]);
+    let status = status_line.split_whitespace().nth(1).unwrap_or("");
+
+    if !status.starts_with('2') {
+        return Err(anyhow!("Webhook {} returned status {}", url, status));
+    }
+
+    Ok(())
+}
+
+/// 웹훅 전송 옵션
+#[derive(Debug, Clone)]
+pub struct WebhookOptions {
+    /// 전송 대기 큐 크기 (이벤트 수)
+    pub queue_size: usize,
+    /// 요청 하나에 묶는 최대 이벤트 수
+    pub max_batch: usize,
+    /// 요청 하나의 제한 시간 (연결부터 상태 줄 수신까지)
+    pub request_timeout: Duration,
+    /// 첫 재시도 전 대기 시간 (실패할 때마다 두 배)
+    pub initial_backoff: Duration,
+    /// 재시도 대기 시간 상한
+    pub max_backoff: Duration,
+    /// 첫 시도부터 포기할 때까지의 기한
+    pub retry_deadline: Duration,
+}
+
+impl Default for WebhookOptions {
+    fn default() -> Self {
+        Self {
+            queue_size: 1024,
+            max_batch: 50,
+            request_timeout: Duration::from_secs(5),
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+            retry_deadline: Duration::from_secs(60),
+        }
+    }
+}
+
+/// 웹훅 전송 카운터 (여러 전송기가 공유)
+#[derive(Debug, Default)]
+pub struct WebhookMetrics {
+    delivered: AtomicU64,
+    failed: AtomicU64,
+    dropped: AtomicU64,
+    retries: AtomicU64,
+    queued: AtomicU64,
+}
+
+impl WebhookMetrics {
+    /// 새로운 카운터 생성
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 현재 값 스냅샷
+    pub fn snapshot(&self) -> WebhookStats {
+        WebhookStats {
+            delivered: self.delivered.load(Ordering::Relaxed),
+            failed: self.failed.load(Ordering::Relaxed),
+            dropped: self.dropped.load(Ordering::Relaxed),
+            retries: self.retries.load(Ordering::Relaxed),
+            queued: self.queued.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// 웹훅 전송기
+///
+/// 복제한 전송기는 같은 큐를 쓴다. 모든 복제본이 해제되면 전송 작업은 큐에
+/// 남은 이벤트를 보낸 뒤 끝난다.
+#[derive(Debug, Clone)]
+pub struct WebhookSender {
+    url: Arc<str>,
+    queue: mpsc::Sender<DaemonEvent>,
+    metrics: Arc<WebhookMetrics>,
+}
+
+impl WebhookSender {
+    /// 전송 작업 시작 (URL은 여기서 검증)
+    pub fn spawn(url: &str, options: WebhookOptions, metrics: Arc<WebhookMetrics>) -> Result<(Self, JoinHandle<()>)> {
+        WebhookTarget::parse(url)?;
+        if options.queue_size == 0 || options.max_batch == 0 {
+            return Err(anyhow!("Webhook queue size and batch size must be positive"));
+        }
+
+        let (queue, receiver) = mpsc::channel(options.queue_size);
+        let sender = Self { url: Arc::from(url), queue, metrics: metrics.clone() };
+        let task = tokio::spawn(run(url.to_string(), options, receiver, metrics));
+
+        Ok((sender, task))
+    }
+
+    /// 이벤트를 큐에 넣음 (기다리지 않으며, 큐가 가득 차면 버리고 집계)
+    pub fn send(&self, event: DaemonEvent) {
+        match self.queue.try_send(event) {
+            Ok(()) => {
+                self.metrics.queued.fetch_add(1, Ordering::Relaxed);
+            }
+            Err(_) => {
+                self.metrics.dropped.fetch_add(1, Ordering::Relaxed);
+                throttled_warn!(&format!("webhook:dropped:{}", self.url), logthrottle::HOT_PATH_INTERVAL,
+                    "Webhook queue for {} is full, dropping event", self.url);
+            }
+        }
+    }
+}
+
+/// 전송 작업 본체
+async fn run(url: String, options: WebhookOptions, mut receiver: mpsc::Receiver<DaemonEvent>, metrics: Arc<WebhookMetrics>) {
+    while let Some(first) = receiver.recv().await {
+        let mut batch = vec![first];
+        while batch.len() < options.max_batch {
+            match receiver.try_recv() {
+                Ok(event) => batch.push(event),
+                Err(_) => break,
+            }
+        }
+        metrics.queued.fetch_sub(batch.len() as u64, Ordering::Relaxed);
+
+        deliver(&url, &batch, &options, &metrics).await;
+    }
+}
+
+/// 이벤트 묶음 전송 (재시도 기한까지 지수 백오프로 재시도)
+async fn deliver(url: &str, batch: &[DaemonEvent], options: &WebhookOptions, metrics: &WebhookMetrics) {
+    let count = batch.len() as u64;
+    let sent_at = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    let payload = match encode_payload(batch, sent_at) {
+        Ok(payload) => payload,
+        Err(e) => {
+            metrics.failed.fetch_add(count, Ordering::Relaxed);
+            warn!("{:#}", e);
+            return;
+        }
+    };
+
+    let deadline = Instant::now() + options.retry_deadline;
+    let mut backoff = options.initial_backoff;
+
+    loop {
+        let result = match tokio::time::timeout(options.request_timeout, post(url, &payload)).await {
+            Ok(result) => result,
+            Err(_) => Err(anyhow!("Webhook {} timed out after {:?}", url, options.request_timeout)),
+        };
+
+        let error = match result {
+            Ok(()) => {
+                metrics.delivered.fetch_add(count, Ordering::Relaxed);
+                debug!("Webhook delivered {} events to {}", count, url);
+                return;
+            }
+            Err(e) => e,
+        };
+
+        if Instant::now() + backoff > deadline {
+            metrics.failed.fetch_add(count, Ordering::Relaxed);
+            throttled_warn!(&format!("webhook:failed:{}", url), FAILURE_LOG_INTERVAL,
+                "Giving up on {} webhook events: {:#}", count, error);
+            return;
+        }
+
+        debug!("Webhook delivery failed, retrying in {:?}: {:#}", backoff, error);
+        metrics.retries.fetch_add(1, Ordering::Relaxed);
+        tokio::time::sleep(backoff).await;
+        backoff = (backoff * 2).min(options.max_backoff);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hyper::service::{make_service_fn, service_fn};
+    use hyper::{Body, Request, Response, Server, StatusCode};
+    use std::collections::VecDeque;
+    use std::convert::Infallible;
+    use std::sync::Mutex;
+
+    /// 테스트 서버의 응답 동작
+    #[derive(Debug, Clone, Copy)]
+    enum Reply {
+        Status(u16),
+        /// 응답하지 않고 대기 (요청 제한 시간 초과)
+        Hang,
+    }
+
+    /// 받은 요청 본문 (JSON)
+    type Received = Arc<Mutex<Vec<serde_json::Value>>>;
+
+    /// 정해진 순서로 응답하는 로컬 웹훅 서버 (순서가 끝나면 200)
+    fn start_server(replies: Vec<Reply>) -> (String, Received) {
+        let replies = Arc::new(Mutex::new(VecDeque::from(replies)));
+        let received: Received = Arc::default();
+
+        let make_service = {
+            let received = received.clone();
+            make_service_fn(move |_conn| {
+                let (replies, received) = (replies.clone(), received.clone());
+                async move {
+                    Ok::<_, Infallible>(service_fn(move |request: Request<Body>| {
+                        let (replies, received) = (replies.clone(), received.clone());
+                        async move {
+                            let body = hyper::body::to_bytes(request.into_body()).await.unwrap();
+                            received.lock().unwrap().push(serde_json::from_slice(&body).unwrap());
+
+                            let reply = replies.lock().unwrap().pop_front().unwrap_or(Reply::Status(200));
+                            let status = match reply {
+                                Reply::Status(status) => status,
+                                Reply::Hang => {
+                                    tokio::time::sleep(Duration::from_secs(30)).await;
+                                    200
+                                }
+                            };
+                            let mut response = Response::new(Body::empty());
+                            *response.status_mut() = StatusCode::from_u16(status).unwrap();
+                            Ok::<_, Infallible>(response)
+                        }
+                    }))
+                }
+            })
+        };
+
+        let server = Server::bind(&([127, 0, 0, 1], 0).into()).serve(make_service);
+        let url = format!("http://{}/hooks", server.local_addr());
+        tokio::spawn(server);
+        (url, received)
+    }
+
+    fn fast_options() -> WebhookOptions {
+        WebhookOptions {
+            queue_size: 16,
+            max_batch: 10,
+            request_timeout: Duration::from_millis(200),
+            initial_backoff: Duration::from_millis(10),
+            max_backoff: Duration::from_millis(40),
+            retry_deadline: Duration::from_secs(5),
+        }
+    }
+
+    fn mitigation(src_ip: &str) -> DaemonEvent {
+        DaemonEvent::Mitigation(MitigationEvent {
+            kind: "synflood".to_string(),
+            src_ip: src_ip.to_string(),
+            syn_pps: 5000,
+            label: format!("auto-syn-{}", src_ip),
+            action: "drop".to_string(),
+            expire: 300,
+            dry_run: false,
+            timestamp: 1700000000,
+            geo: None,
+        })
+    }
+
+    /// 전송기를 모두 해제하고 큐가 빌 때까지 대기
+    async fn finish(sender: WebhookSender, task: JoinHandle<()>) -> WebhookStats {
+        let metrics = sender.metrics.clone();
+        drop(sender);
+        task.await.unwrap();
+        metrics.snapshot()
+    }
+
+    #[test]
+    fn test_encode_payload() {
+        let payload = encode_payload(&[mitigation("10.0.0.1")], 1700000100).unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&payload).unwrap();
+
+        assert_eq!(json, serde_json::json!({
+            "schema_version": 1,
+            "sent_at": 1700000100,
+            "events": [{
+                "kind": "mitigation",
+                "data": {
+                    "kind": "synflood", "src_ip": "10.0.0.1", "syn_pps": 5000, "label": "auto-syn-10.0.0.1",
+                    "action": "drop", "expire": 300, "dry_run": false, "timestamp": 1700000000,
+                },
+            }],
+        }));
+    }
+
+    #[tokio::test]
+    async fn test_retries_server_errors_until_delivered() {
+        let (url, received) = start_server(vec![Reply::Status(500), Reply::Status(503)]);
+        let metrics = Arc::new(WebhookMetrics::new());
+        let (sender, task) = WebhookSender::spawn(&url, fast_options(), metrics).unwrap();
+
+        sender.send(mitigation("10.0.0.1"));
+        let stats = finish(sender, task).await;
+
+        assert_eq!(stats, WebhookStats { delivered: 1, failed: 0, dropped: 0, retries: 2, queued: 0 });
+        // 재시도는 같은 본문을 다시 보냄
+        let received = received.lock().unwrap();
+        assert_eq!(received.len(), 3);
+        assert!(received.iter().all(|body| body == &received[0]));
+        assert_eq!(received[0]["schema_version"], 1);
+    }
+
+    #[tokio::test]
+    async fn test_gives_up_after_deadline() {
+        // 계속 500이면 기한이 지나 실패로 집계
+        let (url, received) = start_server(vec![Reply::Status(500); 100]);
+        let metrics = Arc::new(WebhookMetrics::new());
+        let options = WebhookOptions { retry_deadline: Duration::from_millis(100), ..fast_options() };
+        let (sender, task) = WebhookSender::spawn(&url, options, metrics).unwrap();
+
+        sender.send(mitigation("10.0.0.1"));
+        sender.send(mitigation("10.0.0.2"));
+        let stats = finish(sender, task).await;
+
+        assert_eq!((stats.delivered, stats.failed, stats.dropped), (0, 2, 0));
+        assert!(stats.retries >= 2);
+        assert_eq!(received.lock().unwrap().len() as u64, stats.retries + 1);
+    }
+
+    #[tokio::test]
+    async fn test_request_timeout_is_retried() {
+        // 첫 요청은 응답이 없어 제한 시간 초과, 재시도는 성공
+        let (url, received) = start_server(vec![Reply::Hang]);
+        let metrics = Arc::new(WebhookMetrics::new());
+        let options = WebhookOptions { request_timeout: Duration::from_millis(50), ..fast_options() };
+        let (sender, task) = WebhookSender::spawn(&url, options, metrics).unwrap();
+
+        sender.send(mitigation("10.0.0.1"));
+        let stats = finish(sender, task).await;
+
+        assert_eq!(stats, WebhookStats { delivered: 1, failed: 0, dropped: 0, retries: 1, queued: 0 });
+        assert_eq!(received.lock().unwrap().len(), 2);
+
+        // 기한 안에 한 번도 응답하지 않으면 실패
+        let (url, _) = start_server(vec![Reply::Hang; 10]);
+        let metrics = Arc::new(WebhookMetrics::new());
+        let options = WebhookOptions {
+            request_timeout: Duration::from_millis(50),
+            retry_deadline: Duration::from_millis(80),
+            ..fast_options()
+        };
+        let (sender, task) = WebhookSender::spawn(&url, options, metrics).unwrap();
+
+        sender.send(mitigation("10.0.0.1"));
+        let stats = finish(sender, task).await;
+        assert_eq!((stats.delivered, stats.failed), (0, 1));
+    }
+
+    #[tokio::test]
+    async fn test_batches_and_drops_when_queue_is_full() {
+        let (url, received) = start_server(Vec::new());
+        let metrics = Arc::new(WebhookMetrics::new());
+        let options = WebhookOptions { queue_size: 5, max_batch: 3, ..fast_options() };
+        let (sender, task) = WebhookSender::spawn(&url, options, metrics.clone()).unwrap();
+
+        // 전송 작업이 돌기 전에 큐를 넘치게 채움 (기다리지 않음)
+        for i in 1..=7 {
+            sender.send(mitigation(&format!("10.0.0.{}", i)));
+        }
+        assert_eq!(metrics.snapshot(), WebhookStats { queued: 5, dropped: 2, ..WebhookStats::default() });
+
+        let stats = finish(sender, task).await;
+        assert_eq!(stats, WebhookStats { delivered: 5, failed: 0, dropped: 2, retries: 0, queued: 0 });
+
+        let received = received.lock().unwrap();
+        let batches: Vec<Vec<&str>> = received.iter()
+            .map(|body| body["events"].as_array().unwrap().iter()
+                .map(|event| event["data"]["src_ip"].as_str().unwrap())
+                .collect())
+            .collect();
+        assert_eq!(batches, vec![
+            vec!["10.0.0.1", "10.0.0.2", "10.0.0.3"],
+            vec!["10.0.0.4", "10.0.0.5"],
+        ]);
+    }
+
+    #[test]
+    fn test_spawn_rejects_invalid_settings() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let _guard = runtime.enter();
+        let metrics = Arc::new(WebhookMetrics::new());
+
+        assert!(WebhookSender::spawn("https://hooks.local/", WebhookOptions::default(), metrics.clone()).is_err());
+        let options = WebhookOptions { max_batch: 0, ..WebhookOptions::default() };
+        assert!(WebhookSender::spawn("http://hooks.local/", options, metrics).is_err());
+    }
+
+    #[test]
+    fn test_parse_webhook_target() {
+        let target = WebhookTarget::parse("http://hooks.local:8080/swift-guard/events").unwrap();
+        assert_eq!(target.host, "hooks.local");
+        assert_eq!(target.port, 8080);
+        assert_eq!(target.path, "/swift-guard/events");
+
+        let target = WebhookTarget::parse("http://10.0.0.1").unwrap();
+        assert_eq!(target.port, 80);
+        assert_eq!(target.path, "/");
+
+        assert!(WebhookTarget::parse("https://example.com/").is_err());
+        assert!(WebhookTarget::parse("http://host:abc/").is_err());
+    }
+}
@@ -0,0 +1,247 @@
+// Swift-Guard 테스트 지원 모듈
+// 데몬과 같은 길이 접두 JSON 프로토콜을 사용하는 스텁 API 서버
+
+#![allow(dead_code)]
+
+pub mod netns;
+
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::process::{Child, Command, Output, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use rustls::{ServerConfig, ServerConnection, StreamOwned};
+use serde_json::Value;
+use swift_guard::api::{ApiRequest, ApiResponse, RequestFrame};
+
+/// xdp-filter 바이너리 경로
+pub const XDP_FILTER: &str = env!("CARGO_BIN_EXE_xdp-filter");
+
+/// 한 응답 안의 프레임 구분자 (직렬화한 JSON에는 줄바꿈이 없음)
+const FRAME_SEPARATOR: &str = "\n";
+
+/// 스텁 API 서버
+///
+/// 요청 종류(`AddRule`, `GetStats` 등 variant 이름)별로 고정 응답을 돌려주고,
+/// 받은 요청을 순서대로 기록한다. 응답이 지정되지 않은 요청에는 내부 오류로 응답한다.
+/// 응답을 여러 개 지정하면 같은 종류의 요청마다 차례로 돌려주고 마지막 응답을 반복한다.
+pub struct StubServer {
+    addr: String,
+    requests: Arc<Mutex<Vec<Value>>>,
+    /// 멈춘 동안은 요청을 읽지 않고 연결을 닫음 (데몬 재시작 재현용)
+    stopped: Arc<AtomicBool>,
+}
+
+impl StubServer {
+    /// 요청 종류별 응답으로 서버 시작
+    pub fn start(responses: HashMap<&'static str, ApiResponse>) -> Self {
+        Self::start_sequence(responses.into_iter()
+            .map(|(kind, response)| (kind, vec![response]))
+            .collect())
+    }
+
+    /// 요청 종류별 응답 순서로 서버 시작 (폴링 재현용)
+    pub fn start_sequence(responses: HashMap<&'static str, Vec<ApiResponse>>) -> Self {
+        Self::start_with(encode(responses), None)
+    }
+
+    /// 요청 종류별로 한 연결에 여러 응답 프레임을 차례로 보내는 서버 시작 (진행 상황 프레임 재현용)
+    pub fn start_frames(responses: HashMap<&'static str, Vec<ApiResponse>>) -> Self {
+        Self::start_with(encode(responses).into_iter()
+            .map(|(kind, frames)| (kind, vec![frames.join(FRAME_SEPARATOR)]))
+            .collect(), None)
+    }
+
+    /// 요청 종류별 JSON 원문 응답으로 서버 시작 (이전 형식 응답 재현용)
+    pub fn start_raw(responses: HashMap<&'static str, String>) -> Self {
+        Self::start_with(responses.into_iter()
+            .map(|(kind, response)| (kind, vec![response]))
+            .collect(), None)
+    }
+
+    /// TLS를 종료하는 서버 시작 (핸드셰이크 실패 연결은 기록 없이 닫음)
+    pub fn start_tls(responses: HashMap<&'static str, ApiResponse>, config: Arc<ServerConfig>) -> Self {
+        Self::start_with(encode(responses.into_iter()
+            .map(|(kind, response)| (kind, vec![response]))
+            .collect()), Some(config))
+    }
+
+    fn start_with(responses: HashMap<&'static str, Vec<String>>, tls: Option<Arc<ServerConfig>>) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind stub server");
+        let addr = listener.local_addr().unwrap().to_string();
+        let requests = Arc::new(Mutex::new(Vec::new()));
+
+        let stopped = Arc::new(AtomicBool::new(false));
+
+        let recorded = requests.clone();
+        let down = stopped.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                match stream {
+                    Ok(_) if down.load(Ordering::SeqCst) => {},
+                    Ok(stream) => match &tls {
+                        Some(config) => {
+                            let connection = ServerConnection::new(config.clone()).expect("Invalid TLS config");
+                            serve(StreamOwned::new(connection, stream), &responses, &recorded);
+                        },
+                        None => serve(stream, &responses, &recorded),
+                    },
+                    Err(_) => break,
+                }
+            }
+        });
+
+        Self { addr, requests, stopped }
+    }
+
+    /// 서버 멈춤 (이후 연결은 응답 없이 닫음)
+    pub fn stop(&self) {
+        self.stopped.store(true, Ordering::SeqCst);
+    }
+
+    /// 멈춘 서버 다시 시작 (응답 순서는 멈추기 전에서 이어짐)
+    pub fn restart(&self) {
+        self.stopped.store(false, Ordering::SeqCst);
+    }
+
+    /// 바인드 주소
+    pub fn addr(&self) -> &str {
+        &self.addr
+    }
+
+    /// 받은 요청 (JSON 원문)
+    pub fn raw_requests(&self) -> Vec<Value> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    /// 받은 요청 (데몬과 같은 타입으로 역직렬화, 토큰과 네임스페이스 제외)
+    pub fn requests(&self) -> Vec<ApiRequest> {
+        self.raw_requests().into_iter()
+            .map(|value| serde_json::from_value::<RequestFrame>(value).expect("CLI sent a request the daemon cannot parse").request)
+            .collect()
+    }
+
+    /// 이 서버를 대상으로 xdp-filter 실행
+    pub fn run(&self, args: &[&str]) -> Output {
+        xdp_filter(&self.addr, args)
+    }
+
+    /// 이 서버를 대상으로 xdp-filter를 백그라운드로 시작 (`--watch` 등 끝나지 않는 명령)
+    pub fn spawn(&self, args: &[&str]) -> Child {
+        Command::new(XDP_FILTER)
+            .env("SWIFT_GUARD_CLI_CONFIG", std::env::temp_dir().join("swift-guard-test-no-cli-config.yaml"))
+            .env_remove("SWIFT_GUARD_TOKEN")
+            .args(["--api-server", &self.addr])
+            .args(args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .expect("Failed to start xdp-filter")
+    }
+}
+
+/// 응답을 JSON 원문으로 변환
+fn encode(responses: HashMap<&'static str, Vec<ApiResponse>>) -> HashMap<&'static str, Vec<String>> {
+    responses.into_iter()
+        .map(|(kind, responses)| (kind, responses.iter()
+            .map(|response| serde_json::to_string(response).unwrap())
+            .collect()))
+        .collect()
+}
+
+/// 요청 종류 (프레임 필드가 아닌 첫 번째 키)
+fn request_kind(request: &Value) -> String {
+    request.as_object()
+        .and_then(|object| object.keys().find(|key| !matches!(key.as_str(), "token" | "namespace" | "request_id")).cloned())
+        .unwrap_or_default()
+}
+
+/// 연결 하나 처리
+fn serve(mut stream: impl Read + Write, responses: &HashMap<&'static str, Vec<String>>, requests: &Mutex<Vec<Value>>) {
+    let mut len = [0u8; 4];
+    if stream.read_exact(&mut len).is_err() {
+        return;
+    }
+    let mut body = vec![0u8; u32::from_be_bytes(len) as usize];
+    if stream.read_exact(&mut body).is_err() {
+        return;
+    }
+
+    let request: Value = serde_json::from_slice(&body).expect("CLI sent invalid JSON");
+    let kind = request_kind(&request);
+    let seen = {
+        let mut requests = requests.lock().unwrap();
+        let seen = requests.iter().filter(|previous| request_kind(previous) == kind).count();
+        requests.push(request);
+        seen
+    };
+
+    let response = responses.get(kind.as_str())
+        .and_then(|sequence| sequence.get(seen).or_else(|| sequence.last()))
+        .cloned()
+        .unwrap_or_else(|| {
+            format!(r#"{{"Error":{{"message":"stub: no response for {}","code":"internal"}}}}"#, kind)
+        });
+    for frame in response.split(FRAME_SEPARATOR) {
+        let _ = stream.write_all(&(frame.len() as u32).to_be_bytes());
+        let _ = stream.write_all(frame.as_bytes());
+    }
+}
+
+/// 아무도 수신하지 않는 주소
+pub fn closed_addr() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    listener.local_addr().unwrap().to_string()
+}
+
+/// 주어진 API 서버 주소로 xdp-filter 실행
+pub fn xdp_filter(addr: &str, args: &[&str]) -> Output {
+    xdp_filter_with(addr, &[], args)
+}
+
+/// 전역 옵션(`--tls` 등)을 추가해 xdp-filter 실행
+pub fn xdp_filter_with(addr: &str, global: &[&str], args: &[&str]) -> Output {
+    let mut all = vec!["--api-server", addr];
+    all.extend_from_slice(global);
+    all.extend_from_slice(args);
+    xdp_filter_env(&[], &all)
+}
+
+/// 환경 변수를 지정해 xdp-filter 실행
+///
+/// 사용자의 CLI 설정 파일과 `SWIFT_GUARD_*` 환경 변수는 보이지 않게 한다.
+pub fn xdp_filter_env(env: &[(&str, &str)], args: &[&str]) -> Output {
+    let mut command = Command::new(XDP_FILTER);
+    clear_cli_env(&mut command)
+        .envs(env.iter().copied())
+        .args(args)
+        .output()
+        .expect("Failed to execute xdp-filter")
+}
+
+/// 사용자의 CLI 설정 파일과 `SWIFT_GUARD_*` 환경 변수를 명령에서 가림
+pub fn clear_cli_env(command: &mut Command) -> &mut Command {
+    for (key, _) in std::env::vars().filter(|(key, _)| key.starts_with("SWIFT_GUARD_")) {
+        command.env_remove(key);
+    }
+    command.env("SWIFT_GUARD_CLI_CONFIG", std::env::temp_dir().join("swift-guard-test-no-cli-config.yaml"))
+}
+
+/// 종료 코드
+pub fn exit_code(output: &Output) -> i32 {
+    output.status.code().expect("xdp-filter terminated by signal")
+}
+
+/// 표준 출력 문자열
+pub fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+/// 표준 오류 문자열
+pub fn stderr(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).into_owned()
+}
@@ -0,0 +1,388 @@
+// Swift-Guard 테스트 네트워크 네임스페이스
+// veth로 연결한 두 네트워크 네임스페이스에서 데몬을 실행하고 트래픽을 보낸다.
+//
+// 네임스페이스는 이름(/var/run/netns) 없이 파일 기술자로만 붙잡는다. 테스트가 패닉해도 가드가
+// 드롭되면 데몬을 종료하고 기술자를 닫으므로, 커널이 네임스페이스와 그 안의 veth를 함께 지운다.
+// 호스트의 인터페이스는 건드리지 않으며, 링크 설정은 `ip`를 실행하지 않고 rtnetlink로 한다.
+
+use std::ffi::OsStr;
+use std::fs::{self, File};
+use std::future::Future;
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, TcpStream, UdpSocket};
+use std::os::unix::io::{AsRawFd, FromRawFd, OwnedFd};
+use std::os::unix::process::CommandExt;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Output, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, bail, Context, Result};
+use futures::TryStreamExt;
+use netlink_packet_route::link::nlas::{Nla, Xdp, XdpAttached};
+use rtnetlink::Handle;
+
+use super::{clear_cli_env, XDP_FILTER};
+
+/// 데몬 실행 파일 경로
+pub const DAEMON: &str = env!("CARGO_BIN_EXE_swift-guard-daemon");
+
+/// 데몬이 불러올 BPF 오브젝트
+pub const BPF_OBJECT: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/src/bpf/xdp_filter.o");
+
+/// 데몬 구성의 바탕 (작업 디렉토리만 바꿔 씀)
+const CONFIG_TEMPLATE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/config/swift-guard.yaml");
+
+/// 데몬 API 주소 (네임스페이스마다 따로 있으므로 고정)
+const API_ADDR: &str = "127.0.0.1:7654";
+
+/// 데몬이 API를 열 때까지 기다리는 시간
+const DAEMON_START_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 데몬 작업 디렉토리 구분용 번호
+static DAEMON_SEQ: AtomicUsize = AtomicUsize::new(0);
+
+/// 루트 권한으로 실행 중인지 (네임스페이스 생성과 XDP 연결에 필요)
+pub fn has_root_privileges() -> bool {
+    // SAFETY: 인수가 없고 실패하지 않는 시스템 호출
+    unsafe { libc::geteuid() == 0 }
+}
+
+/// 파일 기술자로 붙잡은 네트워크 네임스페이스
+pub struct Netns {
+    /// 오류 메시지용 이름
+    name: &'static str,
+    /// `/proc/thread-self/ns/net` (닫으면 네임스페이스가 사라짐)
+    file: File,
+}
+
+impl Netns {
+    /// 새 네트워크 네임스페이스 생성 (루프백을 올린 상태)
+    pub fn new(name: &'static str) -> Result<Self> {
+        // unshare는 호출한 스레드만 옮기므로 버릴 스레드에서 만들고 기술자만 가져옴
+        let file = thread::spawn(|| -> Result<File> {
+            // SAFETY: 이 스레드의 네트워크 네임스페이스만 바꾼다
+            if unsafe { libc::unshare(libc::CLONE_NEWNET) } != 0 {
+                return Err(io::Error::last_os_error()).context("Failed to create network namespace");
+            }
+            File::open("/proc/thread-self/ns/net").context("Failed to open network namespace")
+        }).join().map_err(|_| anyhow!("Namespace thread panicked"))?
+            .with_context(|| format!("Failed to set up namespace {}", name))?;
+
+        let netns = Self { name, file };
+        netns.netlink(|handle| async move {
+            let lo = link_index(&handle, "lo").await?;
+            handle.link().set(lo).up().execute().await?;
+            Ok(())
+        }).with_context(|| format!("Failed to bring up lo in namespace {}", name))?;
+        Ok(netns)
+    }
+
+    /// 현재 스레드를 네임스페이스로 옮김
+    fn enter(&self) -> Result<()> {
+        // SAFETY: 열려 있는 네임스페이스 기술자이며 이 스레드만 옮긴다
+        if unsafe { libc::setns(self.file.as_raw_fd(), libc::CLONE_NEWNET) } != 0 {
+            return Err(io::Error::last_os_error()).with_context(|| format!("Failed to enter namespace {}", self.name));
+        }
+        Ok(())
+    }
+
+    /// 네임스페이스 안의 스레드에서 `f` 실행
+    ///
+    /// 그 안에서 만든 소켓은 스레드가 끝난 뒤에도 이 네임스페이스에 속한다.
+    /// `f`의 패닉(단언 실패)은 호출한 테스트로 그대로 전달한다.
+    pub fn run<T: Send>(&self, f: impl FnOnce() -> Result<T> + Send) -> Result<T> {
+        thread::scope(|scope| {
+            scope.spawn(|| {
+                self.enter()?;
+                f()
+            }).join().unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+        })
+    }
+
+    /// 네임스페이스 안에서 rtnetlink 요청 실행
+    pub fn netlink<T, F, Fut>(&self, f: F) -> Result<T>
+    where
+        T: Send,
+        F: FnOnce(Handle) -> Fut + Send,
+        Fut: Future<Output = Result<T>>,
+    {
+        self.run(|| {
+            let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build()?;
+            runtime.block_on(async {
+                let (connection, handle, _) = rtnetlink::new_connection().context("Failed to open rtnetlink socket")?;
+                tokio::spawn(connection);
+                f(handle).await
+            })
+        })
+    }
+
+    /// 네임스페이스 안에서 실행할 명령
+    pub fn command(&self, program: impl AsRef<OsStr>) -> Command {
+        let mut command = Command::new(program);
+        let fd = self.file.as_raw_fd();
+        // SAFETY: fork 뒤 exec 전에는 비동기 시그널 안전한 setns만 호출한다
+        unsafe {
+            command.pre_exec(move || match libc::setns(fd, libc::CLONE_NEWNET) {
+                0 => Ok(()),
+                _ => Err(io::Error::last_os_error()),
+            });
+        }
+        command
+    }
+
+    /// 링크에 XDP 프로그램이 연결되어 있는지
+    pub fn xdp_attached(&self, ifname: &str) -> Result<bool> {
+        let ifname = ifname.to_string();
+        self.netlink(|handle| async move {
+            let link = handle.link().get().match_name(ifname.clone()).execute().try_next().await?
+                .ok_or_else(|| anyhow!("Link {} not found", ifname))?;
+            Ok(link.nlas.iter().any(|nla| match nla {
+                Nla::Xdp(xdp) => xdp.iter().any(|attr| matches!(attr, Xdp::Attached(mode) if *mode != XdpAttached::None)),
+                _ => false,
+            }))
+        })
+    }
+
+    /// 네임스페이스 안에 UDP 소켓 바인드 (수신 대기 시간 포함)
+    pub fn udp_socket(&self, addr: SocketAddr) -> Result<UdpSocket> {
+        self.run(|| {
+            let socket = UdpSocket::bind(addr).with_context(|| format!("Failed to bind UDP socket to {}", addr))?;
+            socket.set_read_timeout(Some(Duration::from_millis(500)))?;
+            Ok(socket)
+        })
+    }
+
+    /// 링크로 들어오는 모든 프레임을 받는 소켓
+    pub fn capture(&self, ifname: &str) -> Result<Capture> {
+        self.run(|| Capture::open(ifname))
+            .with_context(|| format!("Failed to capture on {} in namespace {}", ifname, self.name))
+    }
+}
+
+/// 이름으로 링크 색인 조회
+async fn link_index(handle: &Handle, name: &str) -> Result<u32> {
+    let mut links = handle.link().get().match_name(name.to_string()).execute();
+    match links.try_next().await? {
+        Some(link) => Ok(link.header.index),
+        None => bail!("Link {} not found", name),
+    }
+}
+
+/// 주소를 붙이고 링크 올리기
+async fn configure_link(handle: &Handle, name: &str, addr: Ipv4Addr) -> Result<()> {
+    let index = link_index(handle, name).await?;
+    handle.address().add(index, IpAddr::V4(addr), 24).execute().await
+        .with_context(|| format!("Failed to add {}/24 to {}", addr, name))?;
+    handle.link().set(index).up().execute().await
+        .with_context(|| format!("Failed to bring up {}", name))?;
+    Ok(())
+}
+
+/// AF_PACKET 수신 소켓
+pub struct Capture {
+    fd: OwnedFd,
+}
+
+impl Capture {
+    /// 현재 스레드의 네임스페이스에서 링크에 바인드
+    fn open(ifname: &str) -> Result<Self> {
+        let name = std::ffi::CString::new(ifname)?;
+        // SAFETY: NUL로 끝나는 이름을 넘긴다
+        let ifindex = unsafe { libc::if_nametoindex(name.as_ptr()) };
+        if ifindex == 0 {
+            bail!("Link {} not found", ifname);
+        }
+
+        let protocol = (libc::ETH_P_ALL as u16).to_be();
+        // SAFETY: 반환된 기술자는 바로 OwnedFd가 소유한다
+        let fd = unsafe { libc::socket(libc::AF_PACKET, libc::SOCK_RAW | libc::SOCK_CLOEXEC, protocol as i32) };
+        if fd < 0 {
+            return Err(io::Error::last_os_error()).context("Failed to open packet socket");
+        }
+        // SAFETY: 방금 연 기술자이며 다른 곳에서 소유하지 않는다
+        let fd = unsafe { OwnedFd::from_raw_fd(fd) };
+
+        // SAFETY: sockaddr_ll은 0으로 초기화해도 유효하다
+        let mut addr: libc::sockaddr_ll = unsafe { std::mem::zeroed() };
+        addr.sll_family = libc::AF_PACKET as u16;
+        addr.sll_protocol = protocol;
+        addr.sll_ifindex = ifindex as i32;
+        // SAFETY: addr와 그 크기를 함께 넘긴다
+        let bound = unsafe {
+            libc::bind(fd.as_raw_fd(), &addr as *const libc::sockaddr_ll as *const libc::sockaddr,
+                std::mem::size_of::<libc::sockaddr_ll>() as libc::socklen_t)
+        };
+        if bound != 0 {
+            return Err(io::Error::last_os_error()).context("Failed to bind packet socket");
+        }
+
+        // 마감 시간을 다시 확인할 수 있게 수신을 짧게 끊음
+        let timeout = libc::timeval { tv_sec: 0, tv_usec: 100_000 };
+        // SAFETY: timeout과 그 크기를 함께 넘긴다
+        let set = unsafe {
+            libc::setsockopt(fd.as_raw_fd(), libc::SOL_SOCKET, libc::SO_RCVTIMEO,
+                &timeout as *const libc::timeval as *const libc::c_void,
+                std::mem::size_of::<libc::timeval>() as libc::socklen_t)
+        };
+        if set != 0 {
+            return Err(io::Error::last_os_error()).context("Failed to set packet socket timeout");
+        }
+
+        Ok(Self { fd })
+    }
+
+    /// `timeout` 안에 `needle`을 담은 프레임을 받았는지
+    pub fn wait_for(&self, needle: &[u8], timeout: Duration) -> bool {
+        let deadline = Instant::now() + timeout;
+        let mut frame = [0u8; 2048];
+        while Instant::now() < deadline {
+            // SAFETY: frame 버퍼와 그 크기를 함께 넘긴다
+            let len = unsafe {
+                libc::recv(self.fd.as_raw_fd(), frame.as_mut_ptr() as *mut libc::c_void, frame.len(), 0)
+            };
+            if len > 0 && frame[..len as usize].windows(needle.len()).any(|window| window == needle) {
+                return true;
+            }
+        }
+        false
+    }
+}
+
+/// veth로 연결한 두 네임스페이스
+///
+/// `daemon` 쪽에서 데몬을 실행하고 `peer` 쪽에서 트래픽을 보내거나 받는다.
+pub struct TestNet {
+    pub daemon: Netns,
+    pub peer: Netns,
+}
+
+impl TestNet {
+    /// 링크가 없는 두 네임스페이스 생성
+    pub fn new() -> Result<Self> {
+        Ok(Self { daemon: Netns::new("daemon")?, peer: Netns::new("peer")? })
+    }
+
+    /// veth 쌍 추가
+    ///
+    /// `name`은 데몬 쪽, `peer_name`은 상대 쪽 링크이며 두 주소는 같은 /24에 있어야 한다.
+    /// 양쪽 모두 주소를 붙이고 올린다.
+    pub fn add_veth(&self, name: &str, addr: Ipv4Addr, peer_name: &str, peer_addr: Ipv4Addr) -> Result<()> {
+        let peer_fd = self.peer.file.as_raw_fd();
+        let (name, peer_name) = (name.to_string(), peer_name.to_string());
+
+        // 데몬 쪽에서 쌍을 만들고 한쪽을 상대 네임스페이스로 옮김
+        self.daemon.netlink(|handle| {
+            let (name, peer_name) = (name.clone(), peer_name.clone());
+            async move {
+                handle.link().add().veth(name.clone(), peer_name.clone()).execute().await
+                    .with_context(|| format!("Failed to create veth pair {}/{}", name, peer_name))?;
+                let peer_index = link_index(&handle, &peer_name).await?;
+                handle.link().set(peer_index).setns_by_fd(peer_fd).execute().await
+                    .with_context(|| format!("Failed to move {} to the peer namespace", peer_name))?;
+                configure_link(&handle, &name, addr).await
+            }
+        })?;
+        self.peer.netlink(|handle| async move { configure_link(&handle, &peer_name, peer_addr).await })
+    }
+}
+
+/// 네임스페이스 안에서 실행 중인 데몬 (드롭 시 종료하고 작업 디렉토리 삭제)
+pub struct Daemon<'a> {
+    netns: &'a Netns,
+    child: Child,
+    work_dir: PathBuf,
+}
+
+impl<'a> Daemon<'a> {
+    /// 데몬을 시작하고 API가 열릴 때까지 대기
+    ///
+    /// 작업 디렉토리는 테스트마다 임시 디렉토리에 새로 만들고, 데몬 로그도 그 안에 남긴다.
+    pub fn start(netns: &'a Netns) -> Result<Self> {
+        let seq = DAEMON_SEQ.fetch_add(1, Ordering::Relaxed);
+        let work_dir = std::env::temp_dir().join(format!("swift-guard-netns-{}-{}", std::process::id(), seq));
+        fs::create_dir_all(&work_dir)?;
+
+        let child = match Self::spawn(netns, &work_dir) {
+            Ok(child) => child,
+            Err(e) => {
+                let _ = fs::remove_dir_all(&work_dir);
+                return Err(e);
+            }
+        };
+        let mut daemon = Self { netns, child, work_dir };
+        daemon.wait_ready()?;
+        Ok(daemon)
+    }
+
+    /// 작업 디렉토리에 구성을 쓰고 데몬 프로세스 시작
+    fn spawn(netns: &Netns, work_dir: &Path) -> Result<Child> {
+        let template = fs::read_to_string(CONFIG_TEMPLATE)
+            .with_context(|| format!("Failed to read {}", CONFIG_TEMPLATE))?;
+        let mut config: serde_yaml::Value = serde_yaml::from_str(&template)?;
+        config["general"]["work_dir"] = work_dir.display().to_string().into();
+        config["general"]["pid_file"] = work_dir.join("swift-guard.pid").display().to_string().into();
+        let config_path = work_dir.join("config.yaml");
+        fs::write(&config_path, serde_yaml::to_string(&config)?)?;
+
+        let log = File::create(work_dir.join("daemon.log"))?;
+        netns.command(DAEMON)
+            .arg("--bpf-obj").arg(BPF_OBJECT)
+            .arg("--config").arg(&config_path)
+            .args(["--api-addr", API_ADDR])
+            .stdin(Stdio::null())
+            .stdout(log.try_clone()?)
+            .stderr(log)
+            .spawn()
+            .context("Failed to start swift-guard-daemon")
+    }
+
+    /// API 포트가 열릴 때까지 대기 (먼저 종료하면 로그와 함께 실패)
+    fn wait_ready(&mut self) -> Result<()> {
+        let deadline = Instant::now() + DAEMON_START_TIMEOUT;
+        loop {
+            if let Some(status) = self.child.try_wait()? {
+                bail!("Daemon exited during startup ({}):\n{}", status, self.log());
+            }
+            if self.netns.run(|| Ok(TcpStream::connect(API_ADDR).is_ok()))? {
+                return Ok(());
+            }
+            if Instant::now() >= deadline {
+                bail!("Daemon did not open its API within {:?}:\n{}", DAEMON_START_TIMEOUT, self.log());
+            }
+            thread::sleep(Duration::from_millis(100));
+        }
+    }
+
+    /// 지금까지의 데몬 로그
+    pub fn log(&self) -> String {
+        fs::read_to_string(self.work_dir.join("daemon.log")).unwrap_or_default()
+    }
+
+    /// 이 데몬을 대상으로 xdp-filter 실행
+    pub fn cli(&self, args: &[&str]) -> Output {
+        let mut command = self.netns.command(XDP_FILTER);
+        clear_cli_env(&mut command)
+            .args(["--api-server", API_ADDR])
+            .args(args)
+            .output()
+            .expect("Failed to execute xdp-filter")
+    }
+
+    /// xdp-filter를 실행하고 성공을 확인한 뒤 표준 출력 반환
+    pub fn cli_ok(&self, args: &[&str]) -> String {
+        let output = self.cli(args);
+        assert!(output.status.success(), "xdp-filter {} failed: {}{}\ndaemon log:\n{}", args.join(" "),
+            String::from_utf8_lossy(&output.stdout), String::from_utf8_lossy(&output.stderr), self.log());
+        String::from_utf8_lossy(&output.stdout).into_owned()
+    }
+}
+
+impl Drop for Daemon<'_> {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+        let _ = fs::remove_dir_all(&self.work_dir);
+    }
+}
@@ -1,170 +1,180 @@
 // Swift-Guard 기본 필터링 통합 테스트
+// veth로 연결한 테스트 네임스페이스에서 실제 데몬을 실행하고, 상대 네임스페이스에서 보낸 트래픽으로 확인한다.
+// 루트 권한이 필요하므로 `cargo test --test basic_filtering -- --ignored`로 실행한다.
 
-use std::process::Command;
-use std::thread;
+mod support;
+
+use std::net::{Ipv4Addr, SocketAddr};
 use std::time::Duration;
 
-#[test]
-#[ignore]  // 실제 실행시 --ignored 플래그로 실행
-fn test_attach_detach() {
-    // 이 테스트는 로컬 시스템에 루트 권한이 필요하므로 CI 환경에서는 스킵됩니다.
-    if !has_root_privileges() {
-        println!("Skipping test_attach_detach: root privileges required");
-        return;
-    }
+use support::netns::{has_root_privileges, Daemon, Netns, TestNet};
 
-    // 테스트용 인터페이스가 있는지 확인
-    let test_if = "lo";  // 루프백 인터페이스 사용
-    if !interface_exists(test_if) {
-        panic!("Test interface {} does not exist", test_if);
-    }
+/// 데몬 쪽 veth와 주소
+const DAEMON_IF: &str = "sg0";
+const DAEMON_ADDR: Ipv4Addr = Ipv4Addr::new(10, 201, 0, 1);
 
-    // 대몬 시작
-    let daemon_handle = start_daemon();
-    thread::sleep(Duration::from_secs(2)); // 대몬 시작 대기
+/// 상대 쪽 veth와 주소
+const PEER_IF: &str = "sg0p";
+const PEER_ADDR: Ipv4Addr = Ipv4Addr::new(10, 201, 0, 2);
 
-    // XDP 프로그램 연결
-    let attach_output = Command::new("sudo")
-        .args(&["target/debug/xdp-filter", "attach", test_if, "--mode", "generic"])
-        .output()
-        .expect("Failed to execute attach command");
+/// 리디렉션 대상 veth 쌍 (데몬 쪽 / 상대 쪽)
+const REDIRECT_IF: &str = "sg1";
+const REDIRECT_ADDR: Ipv4Addr = Ipv4Addr::new(10, 201, 1, 1);
+const REDIRECT_PEER_IF: &str = "sg1p";
+const REDIRECT_PEER_ADDR: Ipv4Addr = Ipv4Addr::new(10, 201, 1, 2);
 
-    assert!(attach_output.status.success(), "Attach command failed: {}", 
-            String::from_utf8_lossy(&attach_output.stderr));
+/// veth 한 쌍으로 연결한 테스트 네트워크 (루트가 아니면 None)
+fn test_net(test: &str) -> Option<TestNet> {
+    // 이 테스트는 로컬 시스템에 루트 권한이 필요하므로 CI 환경에서는 스킵됩니다.
+    if !has_root_privileges() {
+        println!("Skipping {}: root privileges required", test);
+        return None;
+    }
+    let net = TestNet::new().expect("Failed to create test namespaces");
+    net.add_veth(DAEMON_IF, DAEMON_ADDR, PEER_IF, PEER_ADDR).expect("Failed to create veth pair");
+    Some(net)
+}
 
-    // 연결 확인
-    let check_output = Command::new("ip")
-        .args(&["link", "show", "dev", test_if])
-        .output()
-        .expect("Failed to execute ip link command");
+/// 상대 네임스페이스에서 보낸 UDP 데이터그램이 데몬 네임스페이스의 소켓에 도착하는지
+fn udp_delivered(net: &TestNet, port: u16, payload: &[u8]) -> bool {
+    let receiver = net.daemon.udp_socket(SocketAddr::from((DAEMON_ADDR, port))).unwrap();
+    let sender = net.peer.udp_socket(SocketAddr::from((PEER_ADDR, 0))).unwrap();
+
+    // 첫 데이터그램은 ARP 해석 중에 늦을 수 있으므로 몇 번 보냄
+    let mut buf = [0u8; 1500];
+    (0..3).any(|_| {
+        sender.send_to(payload, (DAEMON_ADDR, port)).unwrap();
+        matches!(receiver.recv(&mut buf), Ok(len) if &buf[..len] == payload)
+    })
+}
 
-    let output_str = String::from_utf8_lossy(&check_output.stdout);
-    assert!(output_str.contains("xdp"), "XDP program not attached to interface");
+/// 데몬 쪽 veth에 generic 모드로 연결
+fn attach(daemon: &Daemon, interface: &str) {
+    daemon.cli_ok(&["attach", interface, "--mode", "generic"]);
+}
 
-    // XDP 프로그램 분리
-    let detach_output = Command::new("sudo")
-        .args(&["target/debug/xdp-filter", "detach", test_if])
-        .output()
-        .expect("Failed to execute detach command");
+#[test]
+#[ignore]  // 실제 실행시 --ignored 플래그로 실행
+fn test_attach_detach() {
+    let Some(net) = test_net("test_attach_detach") else { return };
+    let daemon = Daemon::start(&net.daemon).expect("Failed to start daemon");
 
-    assert!(detach_output.status.success(), "Detach command failed: {}",
-            String::from_utf8_lossy(&detach_output.stderr));
+    // XDP 프로그램 연결 (커널이 보고하는 링크 속성으로 확인)
+    attach(&daemon, DAEMON_IF);
+    assert!(net.daemon.xdp_attached(DAEMON_IF).unwrap(), "XDP program not attached to interface");
+    assert!(!net.peer.xdp_attached(PEER_IF).unwrap());
 
-    // 대몬 종료
-    stop_daemon(daemon_handle);
+    // XDP 프로그램 분리
+    daemon.cli_ok(&["detach", DAEMON_IF]);
+    assert!(!net.daemon.xdp_attached(DAEMON_IF).unwrap(), "XDP program still attached after detach");
 }
 
 #[test]
 #[ignore]
 fn test_rule_management() {
-    // 이 테스트는 로컬 시스템에 루트 권한이 필요하므로 CI 환경에서는 스킵됩니다.
-    if !has_root_privileges() {
-        println!("Skipping test_rule_management: root privileges required");
-        return;
-    }
-
-    // 테스트용 인터페이스가 있는지 확인
-    let test_if = "lo";  // 루프백 인터페이스 사용
-    if !interface_exists(test_if) {
-        panic!("Test interface {} does not exist", test_if);
-    }
-
-    // 대몬 시작
-    let daemon_handle = start_daemon();
-    thread::sleep(Duration::from_secs(2)); // 대몬 시작 대기
-
-    // XDP 프로그램 연결
-    let _ = Command::new("sudo")
-        .args(&["target/debug/xdp-filter", "attach", test_if, "--mode", "generic"])
-        .output()
-        .expect("Failed to execute attach command");
+    let Some(net) = test_net("test_rule_management") else { return };
+    let daemon = Daemon::start(&net.daemon).expect("Failed to start daemon");
+    attach(&daemon, DAEMON_IF);
 
     // 규칙 추가
-    let add_rule_output = Command::new("sudo")
-        .args(&[
-            "target/debug/xdp-filter", "add-rule",
-            "--src-ip", "192.168.1.100",
-            "--dst-port", "80",
-            "--protocol", "tcp",
-            "--action", "drop",
-            "--label", "test-rule"
-        ])
-        .output()
-        .expect("Failed to execute add-rule command");
-
-    assert!(add_rule_output.status.success(), "Add rule command failed: {}",
-            String::from_utf8_lossy(&add_rule_output.stderr));
+    daemon.cli_ok(&[
+        "add-rule",
+        "--src-ip", &PEER_ADDR.to_string(),
+        "--dst-port", "80",
+        "--protocol", "tcp",
+        "--action", "drop",
+        "--label", "test-rule",
+    ]);
 
     // 규칙 목록 조회
-    let list_rules_output = Command::new("sudo")
-        .args(&["target/debug/xdp-filter", "list-rules"])
-        .output()
-        .expect("Failed to execute list-rules command");
-
-    let output_str = String::from_utf8_lossy(&list_rules_output.stdout);
-    assert!(output_str.contains("test-rule"), "Added rule not found in rules list");
-
-    // 규칙 삭제
-    let delete_rule_output = Command::new("sudo")
-        .args(&["target/debug/xdp-filter", "delete-rule", "--label", "test-rule"])
-        .output()
-        .expect("Failed to execute delete-rule command");
-
-    assert!(delete_rule_output.status.success(), "Delete rule command failed: {}",
-            String::from_utf8_lossy(&delete_rule_output.stderr));
+    let rules = daemon.cli_ok(&["list-rules"]);
+    assert!(rules.contains("test-rule"), "Added rule not found in rules list: {}", rules);
 
-    // 규칙이 삭제되었는지 확인
-    let list_rules_output = Command::new("sudo")
-        .args(&["target/debug/xdp-filter", "list-rules"])
-        .output()
-        .expect("Failed to execute list-rules command");
-
-    let output_str = String::from_utf8_lossy(&list_rules_output.stdout);
-    assert!(!output_str.contains("test-rule"), "Rule was not deleted properly");
-
-    // XDP 프로그램 분리
-    let _ = Command::new("sudo")
-        .args(&["target/debug/xdp-filter", "detach", test_if])
-        .output()
-        .expect("Failed to execute detach command");
-
-    // 대몬 종료
-    stop_daemon(daemon_handle);
+    // 규칙 삭제 후 목록에서 사라졌는지 확인
+    daemon.cli_ok(&["delete-rule", "--label", "test-rule"]);
+    let rules = daemon.cli_ok(&["list-rules"]);
+    assert!(!rules.contains("test-rule"), "Rule was not deleted properly: {}", rules);
 }
 
-// 헬퍼 함수들
-
-// 루트 권한 확인
-fn has_root_privileges() -> bool {
-    let output = Command::new("id")
-        .arg("-u")
-        .output()
-        .expect("Failed to execute id command");
-
-    let uid = String::from_utf8_lossy(&output.stdout).trim().parse::<u32>().unwrap_or(1000);
-    uid == 0
+#[test]
+#[ignore]
+fn test_drop_rule_filters_traffic() {
+    let Some(net) = test_net("test_drop_rule_filters_traffic") else { return };
+    let daemon = Daemon::start(&net.daemon).expect("Failed to start daemon");
+    attach(&daemon, DAEMON_IF);
+    assert!(udp_delivered(&net, 9000, b"before-rule"), "Traffic blocked before any rule was added");
+
+    // 상대 주소에서 오는 UDP 9000을 드롭 (다른 포트는 통과)
+    daemon.cli_ok(&[
+        "add-rule",
+        "--src-ip", &PEER_ADDR.to_string(),
+        "--dst-port", "9000",
+        "--protocol", "udp",
+        "--action", "drop",
+        "--label", "drop-udp",
+    ]);
+    assert!(!udp_delivered(&net, 9000, b"dropped"), "Drop rule did not filter traffic");
+    assert!(udp_delivered(&net, 9001, b"other-port"), "Drop rule filtered a port it does not match");
+
+    // 카운터에 드롭한 패킷이 보임
+    let rule = daemon.cli_ok(&["show-rule", "--label", "drop-udp", "--format", "json"]);
+    let rule: serde_json::Value = serde_json::from_str(&rule).unwrap();
+    assert!(rule["stats"]["packets"].as_u64().unwrap() > 0, "{}", rule);
+
+    // 규칙을 지우면 다시 통과
+    daemon.cli_ok(&["delete-rule", "--label", "drop-udp"]);
+    assert!(udp_delivered(&net, 9000, b"after-delete"), "Traffic still blocked after the rule was deleted");
 }
 
-// 인터페이스 존재 확인
-fn interface_exists(interface: &str) -> bool {
-    let output = Command::new("ip")
-        .args(&["link", "show", "dev", interface])
-        .output()
-        .expect("Failed to execute ip link command");
-
-    output.status.success()
+#[test]
+#[ignore]
+fn test_redirect_to_peer_veth() {
+    let Some(net) = test_net("test_redirect_to_peer_veth") else { return };
+    net.add_veth(REDIRECT_IF, REDIRECT_ADDR, REDIRECT_PEER_IF, REDIRECT_PEER_ADDR)
+        .expect("Failed to create redirect veth pair");
+    let daemon = Daemon::start(&net.daemon).expect("Failed to start daemon");
+    attach(&daemon, DAEMON_IF);
+
+    // sg0으로 들어온 UDP 9100을 sg1로 내보냄 → 상대 쪽 sg1p에 도착해야 함
+    daemon.cli_ok(&[
+        "add-rule",
+        "--src-ip", &PEER_ADDR.to_string(),
+        "--dst-port", "9100",
+        "--protocol", "udp",
+        "--action", "redirect",
+        "--redirect-if", REDIRECT_IF,
+        "--label", "redirect-udp",
+    ]);
+
+    let capture = net.peer.capture(REDIRECT_PEER_IF).unwrap();
+    let payload = b"swift-guard-redirect-probe";
+    assert!(!udp_delivered(&net, 9100, payload), "Redirected traffic reached the local socket");
+    assert!(capture.wait_for(payload, Duration::from_secs(2)),
+        "Redirected packets did not arrive on {}\ndaemon log:\n{}", REDIRECT_PEER_IF, daemon.log());
+
+    let rule = daemon.cli_ok(&["show-rule", "--label", "redirect-udp", "--format", "json"]);
+    let rule: serde_json::Value = serde_json::from_str(&rule).unwrap();
+    assert!(rule["stats"]["redirected_packets"].as_u64().unwrap() > 0, "{}", rule);
 }
 
-// 대몬 시작
-fn start_daemon() -> std::process::Child {
-    Command::new("sudo")
-        .args(&["target/debug/swift-guard-daemon"])
-        .spawn()
-        .expect("Failed to start daemon")
-}
+#[test]
+#[ignore]
+fn test_selftest_loopback() {
+    // 루프백에서 보낸 패킷은 수신 경로로 다시 들어옴 (veth는 상대 쪽으로 나가므로 쓸 수 없음)
+    // 데몬 네임스페이스의 lo를 쓰므로 호스트의 lo에는 연결하지 않는다.
+    if !has_root_privileges() {
+        println!("Skipping test_selftest_loopback: root privileges required");
+        return;
+    }
+    let netns = Netns::new("daemon").expect("Failed to create test namespace");
+    let daemon = Daemon::start(&netns).expect("Failed to start daemon");
+    attach(&daemon, "lo");
+
+    // 자가 진단 (표식 패킷 전송, 카운터 확인)
+    let output = daemon.cli_ok(&["selftest", "--interface", "lo"]);
+    assert!(output.contains("Self-test passed"), "Unexpected selftest output: {}", output);
 
-// 대몬 종료
-fn stop_daemon(mut daemon_handle: std::process::Child) {
-    daemon_handle.kill().expect("Failed to kill daemon");
-    daemon_handle.wait().expect("Failed to wait for daemon to exit");
+    // 임시 규칙이 남아 있지 않은지 확인
+    let rules = daemon.cli_ok(&["list-rules"]);
+    assert!(!rules.contains("selftest-"), "Temporary selftest rule was not removed");
 }
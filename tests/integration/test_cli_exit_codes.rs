@@ -0,0 +1,89 @@
+// Swift-Guard CLI 종료 코드 통합 테스트
+// 고정 응답을 돌려주는 스텁 API 서버에 대해 xdp-filter 바이너리를 실행한다.
+
+mod support;
+
+use std::collections::HashMap;
+use std::process::Command;
+
+use support::{closed_addr, exit_code, stderr, xdp_filter, StubServer, XDP_FILTER};
+
+fn stub(kind: &'static str, response: &str) -> StubServer {
+    StubServer::start_raw(HashMap::from([(kind, response.to_string())]))
+}
+
+#[test]
+fn test_exit_success() {
+    let server = stub("DeleteRule", r#"{"Success":{"message":"Rule 'web' deleted successfully"}}"#);
+    let output = server.run(&["delete-rule", "--label", "web"]);
+    assert_eq!(exit_code(&output), 0);
+}
+
+#[test]
+fn test_exit_invalid_argument_client_side() {
+    let output = xdp_filter(&closed_addr(), &["add-rule", "--action", "drop", "--label", "block-🔥"]);
+    assert_eq!(exit_code(&output), 2);
+
+    let output = xdp_filter(&closed_addr(), &["add-rule", "--action", "explode", "--label", "x"]);
+    assert_eq!(exit_code(&output), 2);
+}
+
+#[test]
+fn test_exit_invalid_argument_from_daemon() {
+    let server = stub("AddRule", r#"{"InvalidArgument":{"field":"redirect_if","message":"Interface veth9 does not exist"}}"#);
+    let output = server.run(&["add-rule", "--action", "redirect", "--redirect-if", "veth9", "--label", "x"]);
+    assert_eq!(exit_code(&output), 2);
+    assert!(stderr(&output).contains("veth9"));
+}
+
+#[test]
+fn test_exit_unreachable() {
+    let output = xdp_filter(&closed_addr(), &["list-rules"]);
+    assert_eq!(exit_code(&output), 3);
+}
+
+#[test]
+fn test_exit_not_found() {
+    let server = stub("GetRule", r#"{"NotFound":{"resource":"rule","name":"web"}}"#);
+    let output = server.run(&["show-rule", "--label", "web"]);
+    assert_eq!(exit_code(&output), 4);
+}
+
+#[test]
+fn test_exit_already_exists() {
+    let server = stub("AddRule", r#"{"Error":{"message":"Rule 'web' already exists","code":"already_exists"}}"#);
+    let output = server.run(&["add-rule", "--action", "drop", "--label", "web"]);
+    assert_eq!(exit_code(&output), 5);
+}
+
+#[test]
+fn test_exit_permission_denied() {
+    let server = stub("DeleteRule",
+        r#"{"Error":{"message":"Token is read-only and cannot send delete_rule requests","code":"permission_denied"}}"#);
+    let output = server.run(&["delete-rule", "--label", "web"]);
+    assert_eq!(exit_code(&output), 6);
+    assert!(stderr(&output).contains("Token is read-only"));
+}
+
+#[test]
+fn test_exit_rate_limited() {
+    let server = stub("ListRules", r#"{"RateLimited":{"retry_after_ms":120,"limit":5}}"#);
+    let output = server.run(&["list-rules"]);
+    assert_eq!(exit_code(&output), 7);
+    assert!(stderr(&output).contains("retry after 120 ms"));
+}
+
+#[test]
+fn test_exit_internal_error() {
+    // code가 없는 이전 형식의 오류 응답은 내부 오류로 취급
+    let server = stub("DeleteRule", r#"{"Error":{"message":"map update failed"}}"#);
+    let output = server.run(&["delete-rule", "--label", "web"]);
+    assert_eq!(exit_code(&output), 10);
+}
+
+#[test]
+fn test_help_documents_exit_codes() {
+    let output = Command::new(XDP_FILTER).arg("--help").output().unwrap();
+    assert_eq!(exit_code(&output), 0);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Exit codes:"));
+}
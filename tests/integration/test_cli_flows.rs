@@ -0,0 +1,1860 @@
+// Swift-Guard CLI 기본 흐름 통합 테스트
+// 루트 권한이나 실제 데몬 없이 스텁 API 서버로 요청 직렬화와 응답 출력을 확인한다.
+
+mod support;
+
+use std::collections::HashMap;
+
+use swift_guard::api::{
+    AddressFamily, ApiRequest, ApiResponse, ApiServerStats, ApplySummary, BenchModeResult, BenchReport, BpfLoadFailure, BpfMapInfo, BpfProgramInfo, CapabilityCheck, CheckStatus, CounterInfo, CountryBlockInfo,
+    DaemonEvent, DaemonSelfStats, DynamicBlockInfo, EventGap, FieldChange, GeoInfo, InstallState, MapAudit, MissingEntry, MitigationEvent, OrphanEntry,
+    PeerIdentity, PlanCapacity, PrefixOverlap, PrefixSetInfo, PresetInfo, PresetParamInfo, ProtocolStats, ReconcileSummary, RedirectTarget, RuleDiff, RuleError, RuleChangeKind, RuleDetail, RuleExpirySummary, RuleHitEvent, RuleInfo, RuleMap, RuleSpaceStats, RuleStats, SelfTestReport, SequencedEvent, SizeHistogram, SystemStats,
+    TrafficBaseline, WasmErrorPolicy, WasmLogLine, WasmModuleInfo, WasmModuleMode, WebhookStats,
+};
+use swift_guard::build_info::BuildInfo;
+use swift_guard::error::ErrorCode;
+use swift_guard::histogram::LatencySnapshot;
+use swift_guard::types::XdpFeatures;
+use support::{closed_addr, exit_code, stderr, stdout, xdp_filter, xdp_filter_env, StubServer};
+
+fn success(message: &str) -> ApiResponse {
+    ApiResponse::Success { message: message.to_string(), warning: None }
+}
+
+/// 한 페이지에 모두 담긴 규칙 목록
+fn rules(rules: Vec<RuleInfo>) -> ApiResponse {
+    ApiResponse::Rules { total_count: rules.len(), rules, next_cursor: None, seq: None }
+}
+
+fn rule_fixture() -> RuleInfo {
+    RuleInfo {
+        label: "test-rule".to_string(),
+        src_ip: Some("192.168.1.100".to_string()),
+        dst_port: Some("80".to_string()),
+        protocol: "tcp".to_string(),
+        priority: 10,
+        stats: RuleStats { packets: 7, bytes: 700, ..RuleStats::default() },
+        ..RuleInfo::default()
+    }
+}
+
+#[test]
+fn test_add_rule() {
+    let server = StubServer::start(HashMap::from([("AddRule", success("Rule 'test-rule' added successfully"))]));
+    let output = server.run(&[
+        "add-rule",
+        "--src-ip", "192.168.1.100",
+        "--dst-port", "80",
+        "--protocol", "tcp",
+        "--action", "drop",
+        "--label", "test-rule",
+    ]);
+
+    assert_eq!(exit_code(&output), 0);
+    assert!(stdout(&output).contains("added successfully"));
+
+    match &server.requests()[..] {
+        [ApiRequest::AddRule(rule)] => {
+            assert_eq!(rule.src_ip.as_deref(), Some("192.168.1.100"));
+            assert_eq!((rule.dst_port_min, rule.dst_port_max), (80, 80));
+            assert_eq!(rule.protocol, 6);
+            assert_eq!(rule.action, 2);
+            assert_eq!(rule.label, "test-rule");
+        },
+        other => panic!("unexpected requests: {:?}", other),
+    }
+}
+
+#[test]
+fn test_add_rule_human_units() {
+    let server = StubServer::start(HashMap::from([("AddRule", success("Rule 'web' added successfully"))]));
+    let output = server.run(&["add-rule", "--action", "drop", "--label", "web", "--expire", "2h", "--rate-limit", "10k"]);
+    assert_eq!(exit_code(&output), 0);
+
+    match &server.requests()[..] {
+        [ApiRequest::AddRule(rule)] => assert_eq!((rule.expire, rule.rate_limit), (7200, 10_000)),
+        other => panic!("unexpected requests: {:?}", other),
+    }
+
+    // 오류 메시지는 받을 수 있는 접미사를 알려 줌
+    let output = server.run(&["add-rule", "--action", "drop", "--label", "web", "--expire", "2w"]);
+    assert_ne!(exit_code(&output), 0);
+    assert!(stderr(&output).contains("s, m, h or d"));
+    assert_eq!(server.requests().len(), 1);
+}
+
+#[test]
+fn test_add_rule_reports_all_issues() {
+    let server = StubServer::start(HashMap::from([("AddRule", success("Rule 'web' added successfully"))]));
+
+    // 데몬에 보내기 전에 문제를 모두 한 번에 보고
+    let output = server.run(&["add-rule", "--action", "redirect", "--label", "web", "--dst-ports", "80,443",
+        "--counter-group", "web-total", "--notify-before", "10m"]);
+    assert_eq!(exit_code(&output), 2);
+    let err = stderr(&output);
+    assert!(err.contains("Invalid rule (4 issues):"), "{}", err);
+    assert!(err.contains("  - redirect_if: redirect action requires redirect_if or redirect_cpu"));
+    assert!(err.contains("  - dst_port_list: dst_port_list requires the tcp or udp protocol"));
+    assert!(err.contains("  - counter_group: counter_group requires the count action"));
+    assert!(err.contains("  - notify_before: notify_before requires an expire time"));
+    assert!(server.requests().is_empty());
+}
+
+#[test]
+fn test_add_rule_with_port_lists() {
+    let server = StubServer::start(HashMap::from([("AddRule", success("Rule 'reflectors' added successfully"))]));
+    let output = server.run(&[
+        "add-rule", "--src-ports", "19,53,123,1900", "--protocol", "udp", "--action", "count", "--label", "reflectors",
+    ]);
+    assert_eq!(exit_code(&output), 0);
+    let output = server.run(&[
+        "add-rule", "--dst-ports", "80, 443,8080", "--protocol", "tcp", "--action", "pass", "--label", "web",
+    ]);
+    assert_eq!(exit_code(&output), 0);
+
+    match &server.requests()[..] {
+        [ApiRequest::AddRule(dns), ApiRequest::AddRule(web)] => {
+            assert_eq!((dns.src_port_min, dns.src_port_max), (0, 65535));
+            assert_eq!(dns.src_port_list, &[19, 53, 123, 1900]);
+            assert_eq!((web.dst_port_min, web.dst_port_max), (0, 65535));
+            assert_eq!(web.dst_port_list, &[80, 443, 8080]);
+            assert!(web.src_port_list.is_empty());
+        },
+        other => panic!("unexpected requests: {:?}", other),
+    }
+
+    // 포트 목록은 tcp, udp 규칙만, 범위와 함께 쓸 수 없음 (요청 전에 거부)
+    let output = server.run(&["add-rule", "--src-ports", "53,123", "--action", "drop", "--label", "x"]);
+    assert_eq!(exit_code(&output), 2);
+    assert!(stderr(&output).contains("requires the tcp or udp protocol"));
+    let output = server.run(&[
+        "add-rule", "--dst-port", "80-90", "--dst-ports", "80,443", "--protocol", "tcp", "--action", "drop", "--label", "x",
+    ]);
+    assert_eq!(exit_code(&output), 2);
+    assert!(stderr(&output).contains("cannot be used with"));
+    let output = server.run(&["add-rule", "--dst-ports", "80,8000-8080", "--protocol", "tcp", "--action", "drop", "--label", "x"]);
+    assert_eq!(exit_code(&output), 2);
+    assert!(stderr(&output).contains("Port ranges cannot be used in a port list"));
+    assert_eq!(server.requests().len(), 2);
+}
+
+#[test]
+fn test_add_rule_with_quota() {
+    let server = StubServer::start(HashMap::from([("AddRule", success("Rule 'backup' added successfully"))]));
+    let output = server.run(&[
+        "add-rule", "--dst-port", "873", "--action", "pass", "--label", "backup",
+        "--quota-bytes", "10G", "--quota-packets", "1.5M", "--quota-action", "count",
+    ]);
+    assert_eq!(exit_code(&output), 0);
+
+    match &server.requests()[..] {
+        [ApiRequest::AddRule(rule)] => {
+            assert_eq!(rule.quota_bytes, Some(10 << 30));
+            assert_eq!(rule.quota_packets, Some(1_500_000));
+            assert_eq!(rule.quota_action, Some(4));
+        },
+        other => panic!("unexpected requests: {:?}", other),
+    }
+
+    // 잘못된 크기나 할당량 없는 액션은 요청을 보내지 않음
+    let output = server.run(&["add-rule", "--action", "pass", "--label", "backup", "--quota-bytes", "10X"]);
+    assert_ne!(exit_code(&output), 0);
+    assert!(stderr(&output).contains("10X"));
+
+    let output = server.run(&["add-rule", "--action", "pass", "--label", "backup", "--quota-action", "drop"]);
+    assert_ne!(exit_code(&output), 0);
+    assert_eq!(server.requests().len(), 1);
+}
+
+#[test]
+fn test_add_rule_src_host() {
+    let server = StubServer::start(HashMap::from([("AddRule", success("Rule 'api' added successfully"))]));
+    let output = server.run(&["add-rule", "--src-host", "api.example.com", "--action", "drop", "--label", "api"]);
+    assert_eq!(exit_code(&output), 0);
+
+    match &server.requests()[..] {
+        [ApiRequest::AddRule(rule)] => {
+            assert_eq!(rule.src_ip, None);
+            assert_eq!(rule.src_host.as_deref(), Some("api.example.com"));
+            assert_eq!(rule.dst_host, None);
+        },
+        other => panic!("unexpected requests: {:?}", other),
+    }
+
+    // 주소와 호스트 이름은 함께 쓸 수 없음
+    let output = server.run(&["add-rule", "--src-ip", "10.0.0.1", "--src-host", "api.example.com",
+        "--action", "drop", "--label", "api"]);
+    assert_eq!(exit_code(&output), 2);
+    assert_eq!(server.requests().len(), 1);
+}
+
+#[test]
+fn test_add_rule_warns_without_interfaces() {
+    let response = ApiResponse::Success {
+        message: "Rule 'telnet' added successfully".to_string(),
+        warning: Some("rule installed but no interfaces are attached".to_string()),
+    };
+    let server = StubServer::start(HashMap::from([("AddRule", response)]));
+    let output = server.run(&["add-rule", "--protocol", "tcp", "--dst-port", "23", "--action", "drop", "--label", "telnet"]);
+
+    assert_eq!(exit_code(&output), 0);
+    // 터미널이 아니므로 색상 없이 출력
+    assert!(stdout(&output).contains("\nWarning: rule installed but no interfaces are attached\n"));
+}
+
+#[test]
+fn test_add_rule_unsupported_feature() {
+    let args = ["add-rule", "--protocol", "tcp", "--dst-ports", "80,443", "--action", "drop", "--label", "web"];
+    let server = StubServer::start_sequence(HashMap::from([("AddRule", vec![
+        ApiResponse::InvalidArgument {
+            field: "dst_port_list".to_string(),
+            message: "dst_port_list requires the port_list feature, which the loaded XDP program does not support (supported: tcp_flags)".to_string(),
+        },
+        ApiResponse::Success {
+            message: "Rule 'web' added successfully".to_string(),
+            warning: Some("the loaded XDP program does not support port_list; dst_port_list will be ignored".to_string()),
+        },
+    ])]));
+
+    // 데몬이 빠진 기능을 밝혀 거부
+    let output = server.run(&args);
+    assert_eq!(exit_code(&output), 2);
+    assert!(stderr(&output).contains("requires the port_list feature"), "{}", stderr(&output));
+
+    // --force면 추가하고 무시될 필드를 경고
+    let output = server.run(&[&args[..], &["--force"]].concat());
+    assert_eq!(exit_code(&output), 0);
+    assert!(stdout(&output).contains("\nWarning: the loaded XDP program does not support port_list; dst_port_list will be ignored\n"));
+    assert!(matches!(&server.requests()[..],
+        [ApiRequest::AddRule(checked), ApiRequest::AddRule(forced)] if !checked.force && forced.force));
+}
+
+#[test]
+fn test_show_rule_history() {
+    let detail = |history_1m: Vec<u64>| ApiResponse::RuleDetail { rule: RuleDetail {
+        info: rule_fixture(),
+        ttl_remaining: None,
+        lpm_key: None,
+        wildcard_slot: None,
+        installed: true,
+        interfaces: vec!["eth0".to_string()],
+        down_interfaces: Vec::new(),
+        recent_matches: Vec::new(),
+        history_1m,
+        src_geo: None,
+    }};
+    let mut history = vec![0; 56];
+    history.extend([3, 0, 1500, 750]);
+    let server = StubServer::start_sequence(HashMap::from([("GetRule", vec![detail(history), detail(Vec::new())])]));
+
+    // 최근 60분을 한 줄로, 오래된 분부터
+    let output = server.run(&["show-rule", "--label", "test-rule"]);
+    assert_eq!(exit_code(&output), 0);
+    let expected = format!("\nLast hour:    {}▂▁█▅ (peak 1.5k/min)\n", "▁".repeat(56));
+    assert!(stdout(&output).contains(&expected), "{}", stdout(&output));
+
+    // 매치된 적이 없으면 표시하지 않음
+    let output = server.run(&["show-rule", "--label", "test-rule"]);
+    assert_eq!(exit_code(&output), 0);
+    assert!(!stdout(&output).contains("Last hour:"));
+}
+
+#[test]
+fn test_delete_rule() {
+    let server = StubServer::start(HashMap::from([("DeleteRule", success("Rule 'test-rule' deleted successfully"))]));
+    let output = server.run(&["delete-rule", "--label", "test-rule"]);
+
+    assert_eq!(exit_code(&output), 0);
+    assert!(matches!(&server.requests()[..], [ApiRequest::DeleteRule { label }] if label == "test-rule"));
+}
+
+#[test]
+fn test_namespace_and_token() {
+    let server = StubServer::start(HashMap::from([
+        ("DeleteRule", success("Rule 'web' deleted successfully")),
+        ("ListRules", ApiResponse::Error { message: "Invalid API token".to_string(), code: ErrorCode::PermissionDenied }),
+    ]));
+    let output = server.run(&["--namespace", "team-a", "--token", "s3cret", "delete-rule", "--label", "web"]);
+    assert_eq!(exit_code(&output), 0);
+
+    // 환경 변수로도 지정, 권한 오류는 종료 코드 6
+    let output = support::xdp_filter_env(&[("SWIFT_GUARD_TOKEN", "wrong")],
+        &["--api-server", server.addr(), "list-rules", "--all-namespaces"]);
+    assert_eq!(exit_code(&output), 6);
+    assert!(stderr(&output).contains("Invalid API token"));
+
+    // 토큰과 네임스페이스는 요청과 같은 JSON 객체에 실림
+    let raw = server.raw_requests();
+    assert_eq!((&raw[0]["token"], &raw[0]["namespace"]), (&serde_json::json!("s3cret"), &serde_json::json!("team-a")));
+    assert_eq!(raw[1]["token"], "wrong");
+    assert!(raw[1].get("namespace").is_none());
+    assert!(matches!(&server.requests()[..], [
+        ApiRequest::DeleteRule { label },
+        ApiRequest::ListRules { all_namespaces: true, .. },
+    ] if label == "web"));
+}
+
+#[test]
+fn test_request_id() {
+    let server = StubServer::start_raw(HashMap::from([
+        ("DeleteRule", r#"{"request_id":"req-echo","Success":{"message":"Rule 'web' deleted successfully"}}"#.to_string()),
+    ]));
+    let output = server.run(&["--verbose", "delete-rule", "--label", "web"]);
+    assert_eq!(exit_code(&output), 0);
+    assert!(stderr(&output).contains("Request ID: req-echo"));
+
+    // 요청 ID는 --verbose에서만 출력
+    let output = server.run(&["delete-rule", "--label", "web"]);
+    assert_eq!(exit_code(&output), 0);
+    assert!(!stderr(&output).contains("Request ID"));
+
+    // 요청마다 새 UUID
+    let raw = server.raw_requests();
+    let ids: Vec<&str> = raw.iter().map(|request| request["request_id"].as_str().unwrap()).collect();
+    assert_eq!(ids[0].len(), 36);
+    assert_ne!(ids[0], ids[1]);
+}
+
+#[test]
+fn test_apply_dry_run() {
+    let path = std::env::temp_dir().join(format!("swift-guard-ruleset-{}.yaml", std::process::id()));
+    std::fs::write(&path, "rules:\n  - label: ssh\n    dst_port: 22\n    protocol: tcp\n    action: drop\n").unwrap();
+
+    let summary = ApplySummary {
+        dry_run: true,
+        added: vec!["ssh".to_string()],
+        updated: vec!["web".to_string()],
+        deleted: vec!["stale".to_string()],
+        diffs: vec![RuleDiff {
+            label: "web".to_string(),
+            changes: vec![FieldChange { field: "action".to_string(), old: "pass".to_string(), new: "drop".to_string() }],
+        }],
+        capacity: Some(PlanCapacity {
+            lpm_entries: 1_024,
+            lpm_capacity: 10_240,
+            fill_percent: 10.0,
+            wildcard_rules: 3,
+            wildcard_capacity: 64,
+        }),
+        ..Default::default()
+    };
+    let server = StubServer::start(HashMap::from([("PlanChanges", ApiResponse::RulesetApplied { summary })]));
+    let output = server.run(&["apply", "-f", path.to_str().unwrap(), "--prune", "--dry-run"]);
+
+    assert_eq!(exit_code(&output), 0);
+    let out = stdout(&output);
+    assert!(out.contains("+ ssh (would add)"));
+    assert!(out.contains("~ web (would update)\n    action: pass -> drop\n"));
+    assert!(out.contains("- stale (would delete)"));
+    assert!(out.contains("After apply: filter_rules 1024/10240 (10.0%), wildcard_rules 3/64"));
+    assert!(!out.contains('\x1b'), "no color when stdout is not a terminal");
+
+    let output = server.run(&["apply", "-f", path.to_str().unwrap(), "--prune", "--dry-run", "--format", "json"]);
+    assert_eq!(exit_code(&output), 0);
+    let json: serde_json::Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(json["diffs"][0]["changes"][0],
+        serde_json::json!({ "field": "action", "old": "pass", "new": "drop" }));
+
+    // 드라이런은 데몬의 계획 요청으로 보냄
+    match &server.requests()[..] {
+        [ApiRequest::PlanChanges { rules, prune: true }, _] => {
+            assert_eq!(rules.len(), 1);
+            assert_eq!(rules[0].label, "ssh");
+            assert_eq!((rules[0].dst_port_min, rules[0].dst_port_max), (22, 22));
+            assert_eq!(rules[0].protocol, 6);
+        },
+        other => panic!("unexpected requests: {:?}", other),
+    }
+
+    // 일부 규칙 적용 실패는 해당 오류 코드의 종료 코드로 보고
+    let summary = ApplySummary {
+        errors: vec![RuleError {
+            label: "ssh".to_string(),
+            message: "Redirect interface not found".to_string(),
+            code: ErrorCode::NotFound,
+        }],
+        ..Default::default()
+    };
+    let server = StubServer::start(HashMap::from([("ApplyRuleset", ApiResponse::RulesetApplied { summary })]));
+    let output = server.run(&["apply", "-f", path.to_str().unwrap()]);
+    assert_eq!(exit_code(&output), 4);
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_apply_streams_progress() {
+    let path = std::env::temp_dir().join(format!("swift-guard-progress-{}.yaml", std::process::id()));
+    std::fs::write(&path, "rules:\n  - label: ssh\n    dst_port: 22\n    protocol: tcp\n    action: drop\n").unwrap();
+
+    let summary = ApplySummary {
+        added: vec!["ssh".to_string()],
+        pending: vec!["web".to_string(), "dns".to_string()],
+        ..Default::default()
+    };
+    let server = StubServer::start_frames(HashMap::from([("ApplyRuleset", vec![
+        ApiResponse::Progress { done: 1, total: 3 },
+        ApiResponse::Progress { done: 2, total: 3 },
+        ApiResponse::RulesetApplied { summary },
+    ])]));
+
+    // 진행 상황 프레임은 건너뛰고 최종 결과 출력 (stderr가 터미널이 아니면 막대 없음)
+    let output = server.run(&["apply", "-f", path.to_str().unwrap()]);
+    assert_eq!(exit_code(&output), 0);
+    let out = stdout(&output);
+    assert!(out.contains("+ ssh (added)"));
+    assert!(out.contains("! apply stopped, 2 change(s) not applied: web, dns"));
+    assert!(!stderr(&output).contains('['));
+    assert!(matches!(&server.requests()[..], [ApiRequest::ApplyRuleset { progress: true, .. }]));
+
+    // 드라이런은 진행 상황이 없는 계획 요청으로 보냄
+    server.run(&["apply", "-f", path.to_str().unwrap(), "--dry-run"]);
+    assert!(matches!(server.requests().last(), Some(ApiRequest::PlanChanges { prune: false, .. })));
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_convert_iptables() {
+    let path = std::env::temp_dir().join(format!("swift-guard-iptables-{}.v4", std::process::id()));
+    std::fs::write(&path, "*filter\n\
+        :INPUT ACCEPT [0:0]\n\
+        -A INPUT -s 203.0.113.0/24 -j DROP\n\
+        -A INPUT -p tcp -m tcp --dport 22 -m comment --comment \"office ssh\" -j ACCEPT\n\
+        -A INPUT -i lo -j ACCEPT\n\
+        COMMIT\n").unwrap();
+    let file = path.to_str().unwrap();
+
+    // 변환만 하면 데몬에 연결하지 않음
+    let output = xdp_filter(&closed_addr(), &["convert", "--from", "iptables", "-f", file]);
+    assert_eq!(exit_code(&output), 0);
+    let out = stdout(&output);
+    assert!(out.starts_with("rules:\n"));
+    assert!(out.contains("label: input-3"));
+    assert!(out.contains("label: office-ssh"));
+    let err = stderr(&output);
+    assert!(err.contains("skipped line 5: interface match -i is not supported (-A INPUT -i lo -j ACCEPT)"));
+    assert!(err.contains("2 rule(s) converted, 1 line(s) skipped"));
+
+    // --apply는 변환한 규칙을 삭제 없이 적용
+    let server = StubServer::start(HashMap::from([("ApplyRuleset", ApiResponse::RulesetApplied {
+        summary: ApplySummary { added: vec!["input-3".to_string(), "office-ssh".to_string()], ..Default::default() },
+    })]));
+    let output = server.run(&["convert", "--from", "iptables", "-f", file, "--apply"]);
+    assert_eq!(exit_code(&output), 0);
+    assert!(stdout(&output).contains("2 added, 0 updated, 0 deleted"));
+    match &server.requests()[..] {
+        [ApiRequest::ApplyRuleset { rules, prune: false, dry_run: false, .. }] => {
+            assert_eq!(rules.len(), 2);
+            assert_eq!((rules[0].src_ip.as_deref(), rules[0].action, rules[0].priority), (Some("203.0.113.0/24"), 2, 2));
+            assert_eq!((rules[1].label.as_str(), rules[1].protocol, rules[1].dst_port_min, rules[1].action), ("office-ssh", 6, 22, 1));
+            assert_eq!(rules[1].description.as_deref(), Some("office ssh"));
+        },
+        other => panic!("unexpected requests: {:?}", other),
+    }
+
+    let output = xdp_filter(&closed_addr(), &["convert", "--from", "nftables", "-f", file]);
+    assert_eq!(exit_code(&output), 2);
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_list_rules() {
+    let server = StubServer::start(HashMap::from([
+        ("ListRules", rules(vec![rule_fixture()])),
+    ]));
+    let output = server.run(&["list-rules", "--stats"]);
+
+    assert_eq!(exit_code(&output), 0);
+    let out = stdout(&output);
+    assert!(out.lines().next().unwrap().starts_with("LABEL"));
+    assert!(out.contains("test-rule"));
+    assert!(out.contains("192.168.1.100"));
+    assert!(matches!(&server.requests()[..], [ApiRequest::ListRules { include_stats: true, expiring_within_secs: None, include_rates: false, all_namespaces: false, .. }]));
+
+    let output = server.run(&["list-rules", "--format", "json"]);
+    let rules: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(rules[0]["label"], "test-rule");
+    assert_eq!(rules[0]["install_state"]["state"], "installed");
+
+    // 만료 예정 규칙 보기는 데몬에서 거르고 TTL 열을 표시
+    let output = server.run(&["list-rules", "--expiring-within", "10m"]);
+    assert_eq!(exit_code(&output), 0);
+    assert!(stdout(&output).lines().next().unwrap().contains("TTL"));
+    assert!(matches!(server.requests().last(), Some(ApiRequest::ListRules { expiring_within_secs: Some(600), .. })));
+
+    assert_eq!(exit_code(&server.run(&["list-rules", "--rates"])), 0);
+    assert!(matches!(server.requests().last(), Some(ApiRequest::ListRules { include_rates: true, .. })));
+
+    // 주소 체계 필터는 데몬에서 거름
+    assert_eq!(exit_code(&server.run(&["list-rules", "--family", "6"])), 0);
+    assert!(matches!(server.requests().last(), Some(ApiRequest::ListRules { family: Some(AddressFamily::V6), .. })));
+    assert_eq!(exit_code(&server.run(&["list-rules", "--family", "5"])), 2);
+}
+
+#[test]
+fn test_list_rules_pages() {
+    // 다음 커서가 없을 때까지 이어서 요청해 모두 표시
+    let second = RuleInfo { label: "web".to_string(), ..rule_fixture() };
+    let server = StubServer::start_sequence(HashMap::from([
+        ("ListRules", vec![
+            ApiResponse::Rules {
+                rules: vec![rule_fixture()],
+                total_count: 2,
+                next_cursor: Some("test-rule".to_string()),
+                seq: None,
+            },
+            ApiResponse::Rules { rules: vec![second.clone()], total_count: 2, next_cursor: None, seq: None },
+            ApiResponse::Rules { rules: vec![second], total_count: 2, next_cursor: None, seq: None },
+        ]),
+    ]));
+
+    let output = server.run(&["list-rules"]);
+    assert_eq!(exit_code(&output), 0);
+    let out = stdout(&output);
+    assert!(out.contains("test-rule") && out.contains("web"), "{}", out);
+    assert!(!out.contains("Showing"), "{}", out);
+    let pages: Vec<(Option<String>, u32, u32)> = server.requests().iter()
+        .map(|request| match request {
+            ApiRequest::ListRules { cursor, offset, limit, .. } => (cursor.clone(), *offset, *limit),
+            other => panic!("unexpected request: {:?}", other),
+        })
+        .collect();
+    assert_eq!(pages, [(None, 0, 1000), (Some("test-rule".to_string()), 0, 1000)]);
+
+    // --limit/--offset은 한 페이지만 요청하고 범위를 표시
+    let output = server.run(&["list-rules", "--offset", "1", "--limit", "1"]);
+    assert_eq!(exit_code(&output), 0);
+    assert!(stdout(&output).contains("Showing 2-2 of 2 rules"), "{}", stdout(&output));
+    assert!(matches!(server.requests().last(), Some(ApiRequest::ListRules { cursor: None, offset: 1, limit: 1, .. })));
+}
+
+#[test]
+fn test_partial_results_retry_with_pagination() {
+    let web = RuleInfo { label: "web".to_string(), ..rule_fixture() };
+    let partial = |returned, total, results| ApiResponse::PartialResults {
+        returned,
+        total,
+        hint: "use pagination".to_string(),
+        results: Box::new(results),
+    };
+    let pages = |server: &StubServer| -> Vec<(Option<String>, u32, u32)> {
+        server.requests().iter()
+            .map(|request| match request {
+                ApiRequest::ListRules { cursor, offset, limit, .. } => (cursor.clone(), *offset, *limit),
+                other => panic!("unexpected request: {:?}", other),
+            })
+            .collect()
+    };
+    let sequence = |total_count| vec![
+        partial(1, 2, rules(vec![rule_fixture()])),
+        ApiResponse::Rules { rules: vec![rule_fixture()], total_count, next_cursor: Some("test-rule".to_string()), seq: None },
+        ApiResponse::Rules { rules: vec![web.clone()], total_count, next_cursor: None, seq: None },
+    ];
+
+    // 페이지가 크기 제한에 걸리면 데몬이 담은 규칙 수로 페이지를 줄여 처음부터 다시 받음
+    let server = StubServer::start_sequence(HashMap::from([("ListRules", sequence(2))]));
+    let output = server.run(&["list-rules"]);
+    assert_eq!(exit_code(&output), 0, "{}", stderr(&output));
+    let out = stdout(&output);
+    assert!(out.contains("test-rule") && out.contains("web"), "{}", out);
+    assert_eq!(pages(&server), [(None, 0, 1000), (None, 0, 1), (Some("test-rule".to_string()), 0, 1)]);
+    // 요청마다 받을 수 있는 응답 크기를 알림
+    assert_eq!(server.raw_requests()[0]["max_response_len"], 16 * 1024 * 1024);
+
+    // 지정한 페이지는 요청한 수를 채울 때까지 나눠 받음
+    let server = StubServer::start_sequence(HashMap::from([("ListRules", sequence(3))]));
+    let output = server.run(&["list-rules", "--offset", "1", "--limit", "2"]);
+    assert_eq!(exit_code(&output), 0, "{}", stderr(&output));
+    assert!(stdout(&output).contains("Showing 2-3 of 3 rules"), "{}", stdout(&output));
+    assert_eq!(pages(&server), [(None, 1, 2), (None, 1, 1), (Some("test-rule".to_string()), 0, 1)]);
+
+    // 규칙 하나도 담지 못하면 더 나눌 수 없음
+    let server = StubServer::start(HashMap::from([("ListRules", partial(0, 2, rules(vec![])))]));
+    let output = server.run(&["list-rules"]);
+    assert_eq!(exit_code(&output), 10);
+    assert!(stderr(&output).contains("could not fit the rule list into a response (0 of 2 rules)"), "{}", stderr(&output));
+
+    // 잘린 이벤트는 받은 앞부분을 출력하고 마지막 순번 다음부터 작은 묶음으로 이어 조회
+    let server = StubServer::start_sequence(HashMap::from([
+        ("GetEvents", vec![
+            partial(1, 2, events(vec![rule_hit(7, "a")], None, 8)),
+            events(vec![rule_hit(8, "b")], None, 8),
+            ApiResponse::Error { message: "daemon shutting down".to_string(), code: ErrorCode::Internal },
+        ]),
+    ]));
+    let output = server.run(&["monitor", "--replay", "2", "--interval", "0"]);
+    assert_ne!(exit_code(&output), 0);
+    let seqs: Vec<String> = stdout(&output).lines().map(|line| line.split(' ').next().unwrap().to_string()).collect();
+    assert_eq!(seqs, ["#7", "#8"]);
+    assert_eq!(event_requests(&server), [(None, 2), (Some(7), 1), (Some(8), 1)]);
+}
+
+#[test]
+fn test_list_rules_watch() {
+    let counted = |packets| RuleInfo { stats: RuleStats { packets, bytes: packets * 64, ..RuleStats::default() }, ..rule_fixture() };
+    let server = StubServer::start_sequence(HashMap::from([
+        ("ListRules", vec![rules(vec![counted(10)]), rules(vec![counted(30)])]),
+    ]));
+
+    // 두 번째 화면을 그리고 세 번째 조회를 보낼 때까지 기다린 뒤 Ctrl+C
+    let child = server.spawn(&["list-rules", "--watch", "1"]);
+    let deadline = std::time::Instant::now() + std::time::Duration::from_secs(10);
+    while server.requests().len() < 3 && std::time::Instant::now() < deadline {
+        std::thread::sleep(std::time::Duration::from_millis(50));
+    }
+    let status = std::process::Command::new("kill").args(["-INT", &child.id().to_string()]).status().unwrap();
+    assert!(status.success());
+    let output = child.wait_with_output().unwrap();
+
+    assert_eq!(exit_code(&output), 0, "{}", stderr(&output));
+    let out = stdout(&output);
+    assert!(out.contains("Every 1s: xdp-filter list-rules (q to quit)"), "{}", out);
+    assert!(out.contains("PACKETS"), "{}", out);
+    assert!(out.contains("* test-rule"), "{}", out);
+    assert!(out.contains("1 of 1 rules matched since the last refresh"), "{}", out);
+    // 종료하면 커서와 원래 화면 복원
+    assert!(out.ends_with("\x1b[?25h\x1b[?1049l"), "{:?}", out);
+    assert!(server.requests().iter().all(|request| matches!(request,
+        ApiRequest::ListRules { include_stats: true, include_rates: false, .. })));
+
+    let output = server.run(&["list-rules", "--watch", "--format", "json"]);
+    assert_ne!(exit_code(&output), 0);
+    assert!(stderr(&output).contains("--watch only supports table output"), "{}", stderr(&output));
+}
+
+#[test]
+fn test_counters() {
+    let counters = vec![CounterInfo {
+        name: "web-total".to_string(),
+        rules: vec!["web-http".to_string(), "web-https".to_string()],
+        packets: 42,
+        bytes: 4200,
+        pps: Some(5),
+    }];
+    let server = StubServer::start(HashMap::from([
+        ("AddRule", success("Rule 'web-https' added successfully")),
+        ("GetCounters", ApiResponse::Counters { counters }),
+    ]));
+
+    let output = server.run(&["add-rule", "--dst-port", "443", "--action", "count", "--label", "web-https",
+        "--counter-group", "web-total"]);
+    assert_eq!(exit_code(&output), 0);
+    // 카운터 그룹은 count 액션 규칙만 가짐
+    let output = server.run(&["add-rule", "--action", "drop", "--label", "x", "--counter-group", "web-total"]);
+    assert_eq!(exit_code(&output), 2);
+    assert!(stderr(&output).contains("requires the count action"));
+
+    let output = server.run(&["counters", "--prefix", "web-"]);
+    assert_eq!(exit_code(&output), 0);
+    let out = stdout(&output);
+    assert!(out.contains("NAME       RULES               PACKETS  BYTES    PPS"), "{}", out);
+    assert!(out.contains("web-total  web-http,web-https  42       4.10 KB  5"), "{}", out);
+
+    let output = server.run(&["counters", "--format", "json"]);
+    let parsed: Vec<CounterInfo> = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(parsed[0].rules.len(), 2);
+
+    match &server.requests()[..] {
+        [ApiRequest::AddRule(rule), ApiRequest::GetCounters { prefix }, ApiRequest::GetCounters { prefix: None }] => {
+            assert_eq!((rule.counter_group.as_deref(), rule.action), (Some("web-total"), 4));
+            assert_eq!(prefix.as_deref(), Some("web-"));
+        },
+        other => panic!("unexpected requests: {:?}", other),
+    }
+}
+
+#[test]
+fn test_presets() {
+    let preset = PresetInfo {
+        name: "icmp-protect".to_string(),
+        description: "Rate-limit ICMP echo to protect against ping floods".to_string(),
+        params: vec![
+            PresetParamInfo { name: "limit".to_string(), description: String::new(), required: true },
+            PresetParamInfo { name: "interface".to_string(), description: String::new(), required: false },
+        ],
+        rules: vec!["icmp-protect.echo".to_string()],
+    };
+    let summary = ApplySummary { added: vec!["icmp-protect.echo".to_string()], ..Default::default() };
+    let server = StubServer::start(HashMap::from([
+        ("ApplyPreset", ApiResponse::RulesetApplied { summary }),
+        ("ListPresets", ApiResponse::Presets { presets: vec![preset] }),
+        ("RemovePreset", success("Preset 'icmp-protect' removed (1 rules deleted)")),
+    ]));
+
+    let output = server.run(&["preset", "icmp-protect", "--limit", "1k", "--interface", "eth0"]);
+    assert_eq!(exit_code(&output), 0);
+    assert!(stdout(&output).contains("+ icmp-protect.echo"), "{}", stdout(&output));
+
+    let output = server.run(&["preset", "list"]);
+    assert_eq!(exit_code(&output), 0);
+    assert!(stdout(&output).contains("icmp-protect  limit [interface]  icmp-protect.echo"), "{}", stdout(&output));
+
+    let output = server.run(&["preset", "remove", "icmp-protect"]);
+    assert_eq!(exit_code(&output), 0);
+    assert!(stdout(&output).contains("1 rules deleted"));
+
+    match &server.requests()[..] {
+        [ApiRequest::ApplyPreset { name, params }, ApiRequest::ListPresets {}, ApiRequest::RemovePreset { name: removed }] => {
+            assert_eq!(name, "icmp-protect");
+            assert_eq!(params.get("limit").map(String::as_str), Some("1000"));
+            assert_eq!(params.get("interface").map(String::as_str), Some("eth0"));
+            assert_eq!(removed, "icmp-protect");
+        },
+        other => panic!("unexpected requests: {:?}", other),
+    }
+}
+
+#[test]
+fn test_preset_amplification_protect() {
+    let summary = ApplySummary { added: vec!["amplification-protect.udp".to_string()], ..Default::default() };
+    let server = StubServer::start(HashMap::from([("ApplyPreset", ApiResponse::RulesetApplied { summary })]));
+
+    // 기본은 세기만 하고, --drop을 주면 같은 규칙이 임계값을 넘는 패킷을 드롭
+    let output = server.run(&["preset", "amplification-protect"]);
+    assert_eq!(exit_code(&output), 0);
+    assert!(stdout(&output).contains("+ amplification-protect.udp"), "{}", stdout(&output));
+    let output = server.run(&["preset", "amplification-protect", "--drop", "--threshold", "5k"]);
+    assert_eq!(exit_code(&output), 0);
+    // 임계값은 --drop과 함께만
+    assert_ne!(exit_code(&server.run(&["preset", "amplification-protect", "--threshold", "5k"])), 0);
+
+    match &server.requests()[..] {
+        [ApiRequest::ApplyPreset { name, params: count }, ApiRequest::ApplyPreset { params: drop, .. }] => {
+            assert_eq!(name, "amplification-protect");
+            assert!(count.is_empty());
+            assert_eq!(drop.get("action").map(String::as_str), Some("drop"));
+            assert_eq!(drop.get("threshold").map(String::as_str), Some("5000"));
+        },
+        other => panic!("unexpected requests: {:?}", other),
+    }
+}
+
+#[test]
+fn test_lookup() {
+    let geo = GeoInfo { country: Some("KR".to_string()), asn: Some(4766), as_org: Some("Korea Telecom".to_string()) };
+    let server = StubServer::start(HashMap::from([
+        ("GeoLookup", ApiResponse::Geo { ip: "203.0.113.5".to_string(), geo }),
+    ]));
+
+    let output = server.run(&["lookup", "203.0.113.5"]);
+    assert_eq!(exit_code(&output), 0);
+    let out = stdout(&output);
+    assert!(out.contains("Country:  KR"), "{}", out);
+    assert!(out.contains("ASN:      AS4766"), "{}", out);
+    assert!(out.contains("AS org:   Korea Telecom"), "{}", out);
+
+    let output = server.run(&["lookup", "203.0.113.5", "--format", "json"]);
+    let json: serde_json::Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(json["geo"]["asn"], 4766);
+
+    // 잘못된 주소는 데몬에 보내지 않음
+    let output = server.run(&["lookup", "not-an-ip"]);
+    assert_ne!(exit_code(&output), 0);
+    assert!(stderr(&output).contains("Invalid IP address"), "{}", stderr(&output));
+
+    assert_eq!(server.requests().len(), 2);
+    assert!(matches!(&server.requests()[0], ApiRequest::GeoLookup { ip } if ip == "203.0.113.5"));
+}
+
+fn country_block(code: &str, change: Option<RuleChangeKind>, error: Option<&str>) -> CountryBlockInfo {
+    CountryBlockInfo {
+        code: code.to_string(),
+        label: format!("country-{}", code),
+        action: "drop".to_string(),
+        set: PrefixSetInfo {
+            name: format!("country-{}", code),
+            entries: 1200,
+            addresses: 45_000_000,
+            source: "geoip".to_string(),
+            version: "GeoLite2-Country 2024-05-03".to_string(),
+        },
+        change,
+        error: error.map(str::to_string),
+        map_entries: 1250,
+        map_capacity: 10240,
+    }
+}
+
+#[test]
+fn test_country_blocks() {
+    let server = StubServer::start(HashMap::from([
+        ("BlockCountry", ApiResponse::CountryBlocks { blocks: vec![country_block("RU", Some(RuleChangeKind::Added), None)] }),
+        ("UnblockCountry", success("Country RU unblocked (rule and prefix set country-RU deleted)")),
+    ]));
+
+    let output = server.run(&["block-country", "--code", "RU", "--expire", "24h"]);
+    assert_eq!(exit_code(&output), 0, "{}", stderr(&output));
+    let out = stdout(&output);
+    assert!(out.contains("country-RU  drop    1200      45000000   geoip   GeoLite2-Country 2024-05-03  added"), "{}", out);
+    assert!(out.contains("filter_rules map: 1250/10240 entries in use"), "{}", out);
+
+    let output = server.run(&["block-country", "--code", "ru", "--action", "count", "--csv", "/etc/swift-guard/ru.csv"]);
+    assert_eq!(exit_code(&output), 0);
+    let output = server.run(&["unblock-country", "--code", "RU"]);
+    assert!(stdout(&output).contains("Country RU unblocked"));
+
+    // 액션 기본값은 drop, 코드 검증과 대문자 변환은 데몬이 맡음
+    let requests = server.requests();
+    assert!(matches!(&requests[0], ApiRequest::BlockCountry { code, action: 2, expire: 86400, csv_path: None } if code == "RU"));
+    assert!(matches!(&requests[1], ApiRequest::BlockCountry { code, action: 4, expire: 0, csv_path: Some(path) }
+        if code == "ru" && path == "/etc/swift-guard/ru.csv"));
+    assert!(matches!(&requests[2], ApiRequest::UnblockCountry { code } if code == "RU"));
+
+    // 갱신에 실패한 국가가 있으면 표는 출력하고 실패로 끝남
+    let server = StubServer::start(HashMap::from([
+        ("RefreshCountryBlocks", ApiResponse::CountryBlocks { blocks: vec![
+            country_block("CN", Some(RuleChangeKind::Updated), None),
+            country_block("RU", None, Some("Failed to read /etc/swift-guard/ru.csv: No such file or directory")),
+        ] }),
+    ]));
+    let output = server.run(&["refresh-countries"]);
+    assert_ne!(exit_code(&output), 0);
+    assert!(stdout(&output).contains("updated"), "{}", stdout(&output));
+    assert!(stdout(&output).contains("error: Failed to read"), "{}", stdout(&output));
+    assert!(stderr(&output).contains("Failed to refresh country block(s): RU"), "{}", stderr(&output));
+    assert!(matches!(&server.requests()[0], ApiRequest::RefreshCountryBlocks { code: None }));
+}
+
+#[test]
+fn test_repair_rules() {
+    // 설치에 실패한 규칙은 목록에서 강조
+    let failed = RuleInfo {
+        install_state: InstallState::Failed { reason: "No space left in map".to_string() },
+        map: RuleMap::Lpm,
+        effective: true,
+        ..rule_fixture()
+    };
+    let server = StubServer::start(HashMap::from([
+        ("ListRules", rules(vec![failed])),
+        ("RepairRules", ApiResponse::RulesRepaired {
+            repaired: vec!["web".to_string()],
+            errors: vec![RuleError {
+                label: "test-rule".to_string(),
+                message: "Failed to update filter_rules map".to_string(),
+                code: ErrorCode::Internal,
+            }],
+        }),
+    ]));
+    assert!(stdout(&server.run(&["list-rules"])).contains("test-rule (FAILED)"));
+
+    let output = server.run(&["repair-rules"]);
+    assert_eq!(exit_code(&output), 10);
+    let out = stdout(&output);
+    assert!(out.contains("+ web"));
+    assert!(out.contains("! test-rule: Failed to update filter_rules map"));
+    assert!(out.contains("1 repaired, 1 failed"));
+    assert!(matches!(server.requests().last(), Some(ApiRequest::RepairRules {})));
+}
+
+#[test]
+fn test_reconcile() {
+    let audit = MapAudit {
+        orphans: vec![OrphanEntry {
+            map: RuleMap::Lpm,
+            object: None,
+            lpm_key: Some("18000000c0a80900".to_string()),
+            wildcard_slot: None,
+            src_ip: Some("192.168.9.0/24".to_string()),
+            label: "stale".to_string(),
+            action: "drop".to_string(),
+            protocol: "tcp".to_string(),
+            dst_port: None,
+            priority: 0,
+        }],
+        missing: vec![MissingEntry {
+            label: "web".to_string(),
+            namespace: "default".to_string(),
+            map: RuleMap::Wildcard,
+            object: None,
+            lpm_key: None,
+            wildcard_slot: Some(3),
+        }],
+    };
+    let server = StubServer::start(HashMap::from([
+        ("AuditMaps", ApiResponse::MapAudit { audit: audit.clone() }),
+        ("ReconcileMaps", ApiResponse::MapsReconciled {
+            summary: ReconcileSummary {
+                audit,
+                deleted_orphans: 1,
+                reinstalled: 0,
+                errors: vec!["rule web: wildcard_rules map is full".to_string()],
+            },
+        }),
+    ]));
+
+    // 옵션이 없으면 감사만 요청
+    let output = server.run(&["reconcile"]);
+    assert_eq!(exit_code(&output), 0);
+    let out = stdout(&output);
+    assert!(out.contains("stale"));
+    assert!(out.contains("192.168.9.0/24"));
+    assert!(out.contains("slot 3"));
+    assert!(out.contains("1 orphaned, 1 missing"));
+    assert!(matches!(server.requests().last(), Some(ApiRequest::AuditMaps {})));
+
+    let output = server.run(&["reconcile", "--delete-orphans", "--reinstall"]);
+    assert_eq!(exit_code(&output), 10);
+    let out = stdout(&output);
+    assert!(out.contains("! rule web: wildcard_rules map is full"));
+    assert!(out.contains("1 orphaned entries deleted, 0 missing entries reinstalled, 1 failed"));
+    assert!(matches!(server.requests().last(),
+        Some(ApiRequest::ReconcileMaps { delete_orphans: true, reinstall_missing: true })));
+}
+
+fn selftest_report(observed: u64, cleanup_error: Option<&str>) -> SelfTestReport {
+    SelfTestReport {
+        interface: "lo".to_string(),
+        passed: observed == 5,
+        label: "selftest-0a0bc123".to_string(),
+        marker: "198.18.10.11:49443 -> 198.19.0.1:9 udp".to_string(),
+        packets_sent: 5,
+        packets_observed: Some(observed),
+        cleanup_error: cleanup_error.map(str::to_string),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_selftest() {
+    let server = StubServer::start(HashMap::from([
+        ("SelfTest", ApiResponse::SelfTest { report: selftest_report(5, None) }),
+    ]));
+
+    let output = server.run(&["selftest"]);
+    assert_eq!(exit_code(&output), 0);
+    let out = stdout(&output);
+    assert!(out.contains("Marker: 198.18.10.11:49443 -> 198.19.0.1:9 udp"));
+    assert!(out.contains("Self-test passed: 5/5 packets counted on lo"));
+    assert!(matches!(server.requests().last(), Some(ApiRequest::SelfTest { interface }) if interface == "lo"));
+
+    let output = server.run(&["selftest", "--interface", "eth0", "--format", "json"]);
+    assert_eq!(exit_code(&output), 0);
+    let report: SelfTestReport = serde_json::from_str(&stdout(&output)).unwrap();
+    assert!(report.passed);
+    assert!(matches!(server.requests().last(), Some(ApiRequest::SelfTest { interface }) if interface == "eth0"));
+
+    // 카운터에 잡히지 않은 패킷은 실패, 남은 임시 규칙은 경고
+    let server = StubServer::start(HashMap::from([
+        ("SelfTest", ApiResponse::SelfTest { report: selftest_report(0, Some("map delete failed")) }),
+    ]));
+    let output = server.run(&["selftest"]);
+    assert_eq!(exit_code(&output), 10);
+    assert!(stdout(&output).contains("Self-test failed: 0/5 packets counted on lo"));
+    let err = stderr(&output);
+    assert!(err.contains("temporary rule 'selftest-0a0bc123' was not removed (map delete failed)"));
+    assert!(err.contains("Self-test failed on lo"));
+}
+
+#[test]
+fn test_bench() {
+    let report = BenchReport {
+        interface: "sgbench0".to_string(),
+        peer: "sgbench1".to_string(),
+        created_pair: true,
+        rules: 2000,
+        pkt_size: 128,
+        duration_secs: 5,
+        modes: vec![
+            BenchModeResult {
+                mode: "generic".to_string(),
+                packets_sent: 5_000_000,
+                packets_processed: 4_000_000,
+                packets_dropped: 4_000_000,
+                elapsed_ms: 5_000,
+                cpu_percent: 35.0,
+                softirq_percent: 30.0,
+                error: None,
+            },
+        ],
+        ..Default::default()
+    };
+    let server = StubServer::start(HashMap::from([
+        ("RunBenchmark", ApiResponse::Benchmark { report: report.clone() }),
+    ]));
+
+    // 터미널이 아니면 --yes 없이 실행하지 않음
+    let output = server.run(&["bench"]);
+    assert_eq!(exit_code(&output), 2);
+    assert!(stderr(&output).contains("Benchmark not confirmed (use --yes"));
+    assert!(server.requests().is_empty());
+
+    let output = server.run(&["bench", "--yes", "--duration", "5s", "--pkt-size", "128", "--rules", "2k", "--mode", "generic"]);
+    assert_eq!(exit_code(&output), 0);
+    let out = stdout(&output);
+    assert!(out.contains("Benchmark on sgbench0 (traffic from sgbench1), 2000 rules, 128-byte frames, 5s per mode"));
+    assert!(out.contains("generic  5000000  1000000  800000  100.0%  35.0%  30.0%    -"));
+    assert!(matches!(server.requests().last(), Some(ApiRequest::RunBenchmark {
+        interface: None, peer: None, duration: 5, pkt_size: 128, rules: 2000, modes, confirm: true,
+    }) if modes == &["generic".to_string()]));
+
+    // 인터페이스는 쌍으로만 지정
+    let output = server.run(&["bench", "--yes", "--interface", "veth0"]);
+    assert_eq!(exit_code(&output), 2);
+
+    let output = server.run(&["bench", "-y", "--interface", "veth0", "--peer", "veth1", "--format", "json"]);
+    assert_eq!(exit_code(&output), 0);
+    let parsed: BenchReport = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(parsed, report);
+    assert!(matches!(server.requests().last(), Some(ApiRequest::RunBenchmark { interface: Some(interface), peer: Some(peer), .. })
+        if interface == "veth0" && peer == "veth1"));
+
+    // 준비 단계 실패는 오류, 정리하지 못한 것은 경고
+    let failed = BenchReport {
+        failed_stage: Some("create_pair".to_string()),
+        error: Some("ip link add sgbench0 type veth peer name sgbench1 failed: File exists".to_string()),
+        cleanup_errors: vec!["remove 1 of 2000 rules: map delete failed".to_string()],
+        modes: Vec::new(),
+        ..report
+    };
+    let server = StubServer::start(HashMap::from([
+        ("RunBenchmark", ApiResponse::Benchmark { report: failed }),
+    ]));
+    let output = server.run(&["bench", "--yes"]);
+    assert_eq!(exit_code(&output), 10);
+    let err = stderr(&output);
+    assert!(err.contains("benchmark cleanup failed: remove 1 of 2000 rules: map delete failed"));
+    assert!(err.contains("Benchmark failed at create_pair: ip link add sgbench0"));
+}
+
+#[test]
+fn test_rulespace() {
+    let mut prefix_lengths = vec![0; 33];
+    prefix_lengths[8] = 1;
+    prefix_lengths[16] = 3;
+    let stats = RuleSpaceStats {
+        prefix_lengths,
+        top_overlaps: vec![PrefixOverlap {
+            prefix: "10.0.0.0/8".to_string(),
+            contains: 3,
+            labels: vec!["all-10".to_string()],
+        }],
+        lpm_entries: 4,
+        kernel_entries: Some(4),
+        wildcard_rules: 2,
+        capacity: 10240,
+        fill_percent: 0.0390625,
+    };
+    let server = StubServer::start(HashMap::from([
+        ("RuleSpaceStats", ApiResponse::RuleSpace { stats: stats.clone() }),
+    ]));
+
+    let output = server.run(&["rulespace"]);
+    assert_eq!(exit_code(&output), 0);
+    let out = stdout(&output);
+    assert!(out.contains("LPM entries: 4 of 10240 (0.0% full)"));
+    assert!(out.contains("Wildcard rules: 2"));
+    assert!(out.contains("/16 |########################################| 3 (75.0%)"));
+    assert!(out.contains("  10.0.0.0/8  3         all-10"));
+    assert!(stderr(&output).is_empty());
+
+    let output = server.run(&["rulespace", "--format", "json"]);
+    let parsed: RuleSpaceStats = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(parsed, stats);
+
+    // 커널 항목 수가 다르면 경고
+    let server = StubServer::start(HashMap::from([
+        ("RuleSpaceStats", ApiResponse::RuleSpace { stats: RuleSpaceStats { kernel_entries: Some(3), ..stats } }),
+    ]));
+    let output = server.run(&["rulespace"]);
+    assert_eq!(exit_code(&output), 0);
+    assert!(stdout(&output).contains("LPM entries: 3 of 10240"));
+    assert!(stderr(&output).contains("filter_rules holds 3 entries but the daemon expects 4"));
+}
+
+#[test]
+fn test_redirects() {
+    let targets = vec![RedirectTarget {
+        slot: 7,
+        ifindex: 7,
+        ifname: "veth0".to_string(),
+        rules: vec!["web".to_string()],
+        manual: false,
+        object: None,
+    }];
+    let server = StubServer::start(HashMap::from([
+        ("ListRedirectTargets", ApiResponse::RedirectTargets { targets: targets.clone() }),
+        ("AddRedirectTarget", success("Redirect target veth1 added (ifindex 9)")),
+        ("RemoveRedirectTarget", ApiResponse::InvalidArgument {
+            field: "interface".to_string(),
+            message: "redirect target veth0 is used by rules: web".to_string(),
+        }),
+    ]));
+
+    let output = server.run(&["redirects"]);
+    assert_eq!(exit_code(&output), 0);
+    assert!(stdout(&output).contains("7     7        veth0   web    no      default"));
+
+    let output = server.run(&["redirects", "--format", "json"]);
+    let listed: Vec<RedirectTarget> = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(listed, targets);
+
+    let output = server.run(&["redirects", "add", "veth1"]);
+    assert_eq!(exit_code(&output), 0);
+    assert!(matches!(server.requests().last(),
+        Some(ApiRequest::AddRedirectTarget { interface }) if interface == "veth1"));
+
+    // 규칙이 참조하는 대상은 데몬이 거부
+    let output = server.run(&["redirects", "remove", "veth0"]);
+    assert_eq!(exit_code(&output), 2);
+    assert!(stderr(&output).contains("used by rules: web"));
+}
+
+#[test]
+fn test_color_output() {
+    let server = StubServer::start(HashMap::from([
+        ("ListRules", rules(vec![rule_fixture()])),
+    ]));
+    let colored = |out: &str| out.contains("\x1b[");
+
+    // 파이프로 받은 출력은 기본적으로 색상 없음
+    let output = server.run(&["list-rules"]);
+    assert_eq!(exit_code(&output), 0);
+    assert!(!colored(&stdout(&output)));
+
+    // --color always는 터미널이 아니어도, NO_COLOR가 있어도 색상 사용
+    let output = server.run(&["--color", "always", "list-rules"]);
+    assert!(stdout(&output).contains("\x1b[31mdrop\x1b[0m"), "{}", stdout(&output));
+    let output = support::xdp_filter_env(&[("NO_COLOR", "1")], &["--api-server", server.addr(), "list-rules", "--color", "always"]);
+    assert!(colored(&stdout(&output)));
+
+    // --no-color가 가장 우선
+    let output = server.run(&["list-rules", "--color", "always", "--no-color"]);
+    assert_eq!(exit_code(&output), 0);
+    assert!(!colored(&stdout(&output)));
+    assert_eq!(exit_code(&server.run(&["--color", "sometimes", "list-rules"])), 2);
+}
+
+#[test]
+fn test_dynamic_blocks() {
+    let block = DynamicBlockInfo {
+        label: "wasm-203.0.113.7".to_string(),
+        ip: "203.0.113.7".to_string(),
+        module: "scanner".to_string(),
+        reason: Some("port scan".to_string()),
+        ttl_remaining: 300,
+        hits: 42,
+        creation_time: 1_700_000_000,
+    };
+    let server = StubServer::start(HashMap::from([
+        ("ListRules", rules(vec![rule_fixture()])),
+        ("ListDynamicBlocks", ApiResponse::DynamicBlocks { blocks: vec![block] }),
+        ("ClearDynamicBlocks", success("Cleared 1 dynamic blocks of WASM module scanner")),
+    ]));
+
+    // 기본 목록은 동적 차단 규칙을 요청하지 않음
+    assert_eq!(exit_code(&server.run(&["list-rules"])), 0);
+    assert_eq!(exit_code(&server.run(&["list-rules", "--all"])), 0);
+    assert_eq!(exit_code(&server.run(&["list-rules", "--origin", "wasm:scanner"])), 0);
+
+    let output = server.run(&["dynamic-blocks", "--module", "scanner"]);
+    assert_eq!(exit_code(&output), 0);
+    let text = stdout(&output);
+    assert!(text.contains("IP           MODULE   REASON     TTL  HITS"), "{}", text);
+    assert!(text.contains("203.0.113.7  scanner  port scan  5m   42"), "{}", text);
+
+    let output = server.run(&["dynamic-blocks", "--clear", "--module", "scanner"]);
+    assert_eq!(exit_code(&output), 0);
+    assert!(stdout(&output).contains("Cleared 1 dynamic blocks"));
+
+    match &server.requests()[..] {
+        [ApiRequest::ListRules { include_dynamic: false, origin: None, .. },
+         ApiRequest::ListRules { include_dynamic: true, origin: None, .. },
+         ApiRequest::ListRules { include_dynamic: false, origin: Some(origin), .. },
+         ApiRequest::ListDynamicBlocks { module: Some(listed) },
+         ApiRequest::ClearDynamicBlocks { module: Some(cleared) }] => {
+            assert_eq!(origin, "wasm:scanner");
+            assert_eq!((listed.as_str(), cleared.as_str()), ("scanner", "scanner"));
+        },
+        other => panic!("unexpected requests: {:?}", other),
+    }
+}
+
+#[test]
+fn test_stats() {
+    let stats = SystemStats {
+        total_packets: 1000,
+        total_bytes: 64000,
+        packets_per_sec: 100,
+        mbps: 0.5,
+        aborted_packets: 4,
+        anomaly: true,
+        baseline: TrafficBaseline { pps_mean: 40.0, pps_stddev: 5.0, mbps_mean: 0.2, mbps_stddev: 0.05, samples: 30, k: 3.0 },
+        protocols: ProtocolStats { tcp: 600, udp: 300, icmp: 100, other: 0 },
+        size_histogram: SizeHistogram::default(),
+        rules: RuleExpirySummary { permanent: 3, expiring: 2, next_expiry: Some(1700000000) },
+        daemon: DaemonSelfStats { cpu_percent: 1.5, rss_bytes: 1048576, open_fds: 21, tokio_tasks: 9,
+            uptime_secs: 3600, event_lag: 0 },
+        api: ApiServerStats { requests_total: 42, errors_total: 1, ..Default::default() },
+        webhooks: WebhookStats { delivered: 12, dropped: 3, ..Default::default() },
+    };
+    let server = StubServer::start(HashMap::from([("GetStats", ApiResponse::Stats { stats })]));
+    let output = server.run(&["stats", "--count", "1"]);
+
+    assert_eq!(exit_code(&output), 0);
+    let out = stdout(&output);
+    assert!(out.contains("Total packets: 1000"));
+    assert!(out.contains("Packets/sec: 100"));
+    assert!(out.contains("XDP aborted: 4 !! packets ended in XDP_ABORTED"));
+    assert!(out.contains("ANOMALY !! traffic above baseline mean + 3.0 stddev"), "{}", out);
+    assert!(out.contains("Baseline: 40 +/- 5 pps, 0.20 +/- 0.05 Mbps (30 intervals)"), "{}", out);
+    assert!(out.contains("Rules: 3 permanent, 2 expiring (next expiry 2023-11-14 22:13:20 UTC)"));
+    assert!(!out.contains("Daemon:"));
+
+    let output = server.run(&["stats", "--count", "1", "--self"]);
+    assert_eq!(exit_code(&output), 0);
+    let out = stdout(&output);
+    assert!(out.contains("Daemon:\n  CPU:        1.5%\n  RSS:        1.00 MB\n  Open fds:   21\n"));
+    assert!(out.contains("  Uptime:     1h 0m\n"));
+    assert!(out.contains("  API: 42 requests, 1 errors, 0 rate limited, 0 oversized, 0 open connections\n"));
+    assert!(out.contains("  Webhooks: 12 delivered, 0 failed, 3 dropped, 0 retries, 0 queued\n"));
+    assert_eq!(server.requests().len(), 2);
+}
+
+#[test]
+fn test_wasm_lifecycle() {
+    let server = StubServer::start(HashMap::from([
+        ("LoadWasmModule", success("Module 'http-inspector' loaded")),
+        ("ListWasmModules", ApiResponse::WasmModules {
+            modules: vec![WasmModuleInfo {
+                name: "http-inspector".to_string(),
+                state: "running".to_string(),
+                loaded_at: 1700000000,
+                mode: WasmModuleMode::Shadow,
+                would_block_packets: 5,
+            }],
+        }),
+        ("WasmModuleStats", ApiResponse::WasmModuleStats {
+            name: "http-inspector".to_string(),
+            processed_packets: 42,
+            blocked_packets: 3,
+            avg_processing_time_us: 1.5,
+            state: Some("error".to_string()),
+            errors: 7,
+            last_error: Some("Failed to call inspect_packet function: wasm trap: unreachable".to_string()),
+            latency: LatencySnapshot { bounds_us: vec![1, 5], counts: vec![30, 10, 2], sum_us: 63 },
+            p50_us: Some(0.7),
+            p95_us: Some(4.0),
+            p99_us: Some(5.0),
+            mode: WasmModuleMode::Shadow,
+            would_block_packets: 9,
+        }),
+        ("ResetWasmModuleStats", success("WASM module http-inspector stats reset")),
+        ("ResumeWasmModule", success("WASM module http-inspector resumed")),
+        ("SetWasmModuleState", success("WASM module http-inspector is in enforce mode")),
+        ("UnloadWasmModule", success("Module 'http-inspector' unloaded")),
+        ("ClearWasmCache", success("Removed 2 cached WASM modules from /var/lib/swift-guard/wasm-cache")),
+    ]));
+
+    let output = server.run(&["wasm", "load", "--name", "http-inspector", "--file", "wasm/http_inspector.wasm",
+        "--on-error", "disable_module"]);
+    assert_eq!(exit_code(&output), 0);
+
+    let output = server.run(&["wasm", "load", "--name", "other", "--file", "other.wasm", "--on-error", "ignore"]);
+    assert_ne!(exit_code(&output), 0);
+
+    let output = server.run(&["wasm", "list"]);
+    assert_eq!(exit_code(&output), 0);
+    assert!(stdout(&output).contains("http-inspector"));
+    assert!(stdout(&output).lines().next().unwrap().contains("MODE    WOULD-BLOCK"));
+    assert!(stdout(&output).contains("shadow"));
+
+    let output = server.run(&["wasm", "stats", "--name", "http-inspector"]);
+    assert_eq!(exit_code(&output), 0);
+    let out = stdout(&output);
+    assert!(out.contains("State: error\nMode: shadow\n"));
+    assert!(out.contains("Blocked packets: 3\nWould-block packets: 9\n"));
+    assert!(out.contains("Errors: 7\nLast error: Failed to call inspect_packet function: wasm trap: unreachable\n"));
+    assert!(out.contains("Processing time: p50 0.70 us, p95 4.00 us, p99 5.00 us\n"));
+    assert!(out.contains("  <=5us |#############                           | 10 (23.8%)\n"));
+    assert!(out.contains("   >5us |###                                     | 2 (4.8%)\n"));
+
+    let output = server.run(&["wasm", "reset-stats", "--name", "http-inspector"]);
+    assert_eq!(exit_code(&output), 0);
+
+    let output = server.run(&["wasm", "resume", "--name", "http-inspector"]);
+    assert_eq!(exit_code(&output), 0);
+
+    let output = server.run(&["wasm", "unload", "--name", "http-inspector"]);
+    assert_eq!(exit_code(&output), 0);
+
+    let output = server.run(&["wasm", "cache-clear"]);
+    assert_eq!(exit_code(&output), 0);
+    assert!(stdout(&output).contains("Removed 2 cached WASM modules"));
+
+    // shadow 모드로 로드하고 나중에 enforce로 전환
+    let output = server.run(&["wasm", "load", "--name", "http-inspector", "--file", "wasm/http_inspector.wasm", "--mode", "shadow"]);
+    assert_eq!(exit_code(&output), 0);
+    let output = server.run(&["wasm", "set-mode", "--name", "http-inspector", "--mode", "enforce"]);
+    assert_eq!(exit_code(&output), 0);
+    let output = server.run(&["wasm", "set-mode", "--name", "http-inspector", "--mode", "audit"]);
+    assert_ne!(exit_code(&output), 0);
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 9);
+    assert!(matches!(&requests[0], ApiRequest::LoadWasmModule { name, file_path, on_error, config: None, mode: WasmModuleMode::Enforce }
+        if name == "http-inspector" && file_path == "wasm/http_inspector.wasm"
+            && *on_error == Some(WasmErrorPolicy::DisableModule)));
+    assert!(matches!(&requests[3], ApiRequest::ResetWasmModuleStats { name } if name == "http-inspector"));
+    assert!(matches!(&requests[4], ApiRequest::ResumeWasmModule { name } if name == "http-inspector"));
+    assert!(matches!(&requests[5], ApiRequest::UnloadWasmModule { name } if name == "http-inspector"));
+    assert!(matches!(&requests[6], ApiRequest::ClearWasmCache {}));
+    assert!(matches!(&requests[7], ApiRequest::LoadWasmModule { mode: WasmModuleMode::Shadow, .. }));
+    assert!(matches!(&requests[8], ApiRequest::SetWasmModuleState { name, mode: WasmModuleMode::Enforce } if name == "http-inspector"));
+}
+
+#[test]
+fn test_wasm_load_config_file() {
+    let path = std::env::temp_dir().join(format!("swift-guard-patterns-{}.conf", std::process::id()));
+    std::fs::write(&path, "sqli 80,8080 UNION SELECT\n").unwrap();
+    let server = StubServer::start(HashMap::from([("LoadWasmModule", success("WASM module patterns loaded"))]));
+
+    // 설정 파일 내용을 그대로 전달
+    let output = server.run(&["wasm", "load", "--name", "patterns", "--file", "pattern_matcher.wasm",
+        "--config-file", path.to_str().unwrap()]);
+    assert_eq!(exit_code(&output), 0);
+    std::fs::remove_file(&path).unwrap();
+
+    let output = server.run(&["wasm", "load", "--name", "patterns", "--file", "pattern_matcher.wasm",
+        "--config-file", path.to_str().unwrap()]);
+    assert_ne!(exit_code(&output), 0);
+    assert!(stderr(&output).contains("Failed to read module config file"));
+
+    match &server.requests()[..] {
+        [ApiRequest::LoadWasmModule { config, .. }] => {
+            assert_eq!(config.as_deref(), Some("sqli 80,8080 UNION SELECT\n"));
+        },
+        other => panic!("unexpected requests: {:?}", other),
+    }
+}
+
+fn log_lines(first: u64, messages: &[&str]) -> ApiResponse {
+    ApiResponse::WasmModuleLog {
+        name: "http-inspector".to_string(),
+        lines: messages.iter().zip(first..)
+            .map(|(message, seq)| WasmLogLine {
+                seq,
+                timestamp_ms: 1700000000000 + seq * 1500,
+                message: message.to_string(),
+            })
+            .collect(),
+    }
+}
+
+#[test]
+fn test_wasm_logs() {
+    let server = StubServer::start(HashMap::from([
+        ("GetWasmModuleLog", log_lines(41, &["GET /index.html", "blocked: SQL injection"])),
+    ]));
+
+    let output = server.run(&["wasm", "logs", "--name", "http-inspector", "--lines", "2"]);
+    assert_eq!(exit_code(&output), 0);
+    assert_eq!(stdout(&output),
+        "2023-11-14 22:14:21.500 GET /index.html\n2023-11-14 22:14:23.000 blocked: SQL injection\n");
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 1);
+    assert!(matches!(&requests[0], ApiRequest::GetWasmModuleLog { name, lines: 2, after: None }
+        if name == "http-inspector"));
+}
+
+#[test]
+fn test_wasm_logs_follow() {
+    // 폴링마다 새 줄을 돌려주다 모듈이 언로드되면 종료
+    let server = StubServer::start_sequence(HashMap::from([
+        ("GetWasmModuleLog", vec![
+            log_lines(1, &["first", "second"]),
+            log_lines(3, &[]),
+            log_lines(3, &["third"]),
+            ApiResponse::Error {
+                message: "WASM module http-inspector is not loaded".to_string(),
+                code: ErrorCode::NotFound,
+            },
+        ]),
+    ]));
+
+    let output = server.run(&["wasm", "logs", "--name", "http-inspector", "--follow", "--interval", "0"]);
+    assert_ne!(exit_code(&output), 0);
+    let out = stdout(&output);
+    let messages: Vec<&str> = out.lines().map(|line| line.splitn(3, ' ').nth(2).unwrap()).collect();
+    assert_eq!(messages, ["first", "second", "third"]);
+
+    // 첫 조회 뒤에는 마지막으로 받은 줄 이후 전체를 요청
+    let afters: Vec<(usize, Option<u64>)> = server.requests().iter()
+        .map(|request| match request {
+            ApiRequest::GetWasmModuleLog { lines, after, .. } => (*lines, *after),
+            other => panic!("unexpected request {:?}", other),
+        })
+        .collect();
+    assert_eq!(afters, [(50, None), (0, Some(2)), (0, Some(2)), (0, Some(3))]);
+}
+
+fn rule_hit(seq: u64, label: &str) -> SequencedEvent {
+    SequencedEvent {
+        seq,
+        event: DaemonEvent::RuleHit(RuleHitEvent {
+            label: label.to_string(),
+            rule_key: "192.168.1.0/24".to_string(),
+            action: "drop".to_string(),
+            protocol: "tcp".to_string(),
+            src_ip: "192.168.1.100".to_string(),
+            src_port: 40000,
+            dst_ip: "10.0.0.1".to_string(),
+            dst_port: 80,
+            timestamp_ns: 1_700_000_000_000_000_000 + seq * 1_000_000,
+            geo: None,
+        }),
+    }
+}
+
+fn events(events: Vec<SequencedEvent>, gap: Option<EventGap>, latest_seq: u64) -> ApiResponse {
+    ApiResponse::Events { events, gap, latest_seq }
+}
+
+fn event_requests(server: &StubServer) -> Vec<(Option<u64>, usize)> {
+    server.requests().iter()
+        .map(|request| match request {
+            ApiRequest::GetEvents { since_seq, limit, .. } => (*since_seq, *limit),
+            other => panic!("unexpected request {:?}", other),
+        })
+        .collect()
+}
+
+#[test]
+fn test_monitor_replay_and_follow() {
+    // 최근 이벤트를 재생한 뒤 마지막 순번 이후를 폴링하다 오류가 나면 종료
+    let server = StubServer::start_sequence(HashMap::from([
+        ("GetEvents", vec![
+            events(vec![rule_hit(7, "a"), rule_hit(8, "b")], None, 8),
+            events(vec![], None, 8),
+            events(vec![rule_hit(9, "c")], None, 9),
+            ApiResponse::Error { message: "daemon shutting down".to_string(), code: ErrorCode::Internal },
+        ]),
+    ]));
+
+    let output = server.run(&["monitor", "--replay", "2", "--interval", "0"]);
+    assert_ne!(exit_code(&output), 0);
+    let out = stdout(&output);
+    let seqs: Vec<&str> = out.lines().map(|line| line.split(' ').next().unwrap()).collect();
+    assert_eq!(seqs, ["#7", "#8", "#9"]);
+    assert!(out.contains("rule_hit c drop tcp 192.168.1.100:40000 -> 10.0.0.1:80"), "{}", out);
+
+    assert_eq!(event_requests(&server), [(None, 2), (Some(8), 1000), (Some(8), 1000), (Some(9), 1000)]);
+}
+
+#[test]
+fn test_monitor_reconnects_after_daemon_restart() {
+    // 재시작한 데몬은 순번을 처음부터 매기므로 마지막으로 본 순번보다 작은 이벤트부터 재생
+    let server = StubServer::start_sequence(HashMap::from([
+        ("GetEvents", vec![
+            events(vec![rule_hit(1, "a"), rule_hit(2, "b")], None, 2),
+            events(vec![rule_hit(1, "c"), rule_hit(2, "d")], None, 2),
+            events(vec![], None, 2),
+        ]),
+    ]));
+    let mut child = server.spawn(&["monitor", "--interval", "1"]);
+    let wait_for = |count: usize| {
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(10);
+        while server.requests().len() < count && std::time::Instant::now() < deadline {
+            std::thread::sleep(std::time::Duration::from_millis(20));
+        }
+    };
+
+    // 첫 조회 뒤 멈췄다가 다시 시작하면 끊긴 동안 기다리다 마지막 순번부터 이어서 조회
+    wait_for(1);
+    server.stop();
+    std::thread::sleep(std::time::Duration::from_millis(1500));
+    server.restart();
+    wait_for(3);
+    child.kill().unwrap();
+    let output = child.wait_with_output().unwrap();
+
+    let seqs: Vec<String> = stdout(&output).lines().map(|line| line.split(' ').next().unwrap().to_string()).collect();
+    assert_eq!(seqs, ["#1", "#2", "#1", "#2"]);
+    let err = stderr(&output);
+    assert_eq!(err.matches("Reconnected to API server after").count(), 1, "{}", err);
+    assert!(err.contains("attempts), 2 events replayed"), "{}", err);
+    assert_eq!(event_requests(&server)[..3], [(None, 0), (Some(2), 1000), (Some(2), 1000)]);
+}
+
+#[test]
+fn test_monitor_since_seq_reports_gap() {
+    let mitigation = SequencedEvent {
+        seq: 10,
+        event: DaemonEvent::Mitigation(MitigationEvent {
+            kind: "synflood".to_string(),
+            src_ip: "192.168.1.10".to_string(),
+            syn_pps: 5000,
+            label: "auto-synflood-192.168.1.10".to_string(),
+            action: "drop".to_string(),
+            expire: 60,
+            dry_run: false,
+            timestamp: 1_700_000_000,
+            geo: None,
+        }),
+    };
+    let server = StubServer::start(HashMap::from([
+        ("GetEvents", events(vec![mitigation], Some(EventGap { first_seq: 6, last_seq: 9 }), 12)),
+    ]));
+
+    let output = server.run(&["monitor", "--since-seq", "5", "--kind", "mitigation", "--no-follow"]);
+    assert_eq!(exit_code(&output), 0);
+    assert_eq!(stdout(&output).trim(),
+        "#10 2023-11-14 22:13:20.000 mitigation auto-synflood-192.168.1.10 drop 192.168.1.10 (5000 SYN/s)");
+    assert!(stderr(&output).contains("missed 4 events (seq 6-9 are no longer in the daemon's history)"));
+
+    match &server.requests()[..] {
+        [ApiRequest::GetEvents { since_seq: Some(5), limit: 1000, kinds }] => assert_eq!(kinds, &["mitigation"]),
+        other => panic!("unexpected requests {:?}", other),
+    }
+
+    // --since-seq와 --replay는 함께 쓸 수 없음
+    assert_eq!(exit_code(&server.run(&["monitor", "--since-seq", "5", "--replay", "10"])), 2);
+}
+
+#[test]
+fn test_monitor_rules() {
+    // 스냅샷 뒤 변경을 순번과 함께 표시하다 데몬이 연결을 닫으면 (다시 연결하지 않을 때) 연결 오류로 종료
+    let web = RuleInfo { label: "web".to_string(), dst_port: Some("443".to_string()), ..rule_fixture() };
+    let server = StubServer::start_frames(HashMap::from([("WatchRules", vec![
+        ApiResponse::Rules { rules: vec![rule_fixture()], total_count: 1, next_cursor: None, seq: Some(3) },
+        ApiResponse::RuleChanged { seq: 5, kind: RuleChangeKind::Added, rule: web },
+        ApiResponse::RuleChanged { seq: 6, kind: RuleChangeKind::Deleted, rule: rule_fixture() },
+    ])]));
+
+    let output = server.run(&["--reconnect-window", "0", "monitor", "--rules"]);
+    assert_eq!(exit_code(&output), 3);
+    assert_eq!(stdout(&output).lines().collect::<Vec<_>>(), [
+        "#3 snapshot 1 rules",
+        "#3 present test-rule drop tcp 192.168.1.100 -> *:80 priority 10",
+        "#5 added web drop tcp 192.168.1.100 -> *:443 priority 10",
+        "#6 deleted test-rule drop tcp 192.168.1.100 -> *:80 priority 10",
+    ]);
+    assert!(stderr(&output).contains("API server closed the rule watch"));
+    assert!(matches!(&server.requests()[..], [ApiRequest::WatchRules { all_namespaces: false }]));
+
+    // 순번이 되돌아간 변경은 재구성할 수 없으므로 거부
+    let server = StubServer::start_frames(HashMap::from([("WatchRules", vec![
+        ApiResponse::Rules { rules: Vec::new(), total_count: 0, next_cursor: None, seq: Some(7) },
+        ApiResponse::RuleChanged { seq: 7, kind: RuleChangeKind::Added, rule: rule_fixture() },
+    ])]));
+    let output = server.run(&["--reconnect-window", "0", "monitor", "--rules", "--all-namespaces"]);
+    assert_eq!(exit_code(&output), 10);
+    assert_eq!(stdout(&output).trim(), "#7 snapshot 0 rules");
+    assert!(stderr(&output).contains("API server sent rule change 7 out of order (after 7)"));
+    assert!(matches!(&server.requests()[..], [ApiRequest::WatchRules { all_namespaces: true }]));
+
+    // 이벤트 조회 옵션과 함께 쓸 수 없음
+    assert_eq!(exit_code(&server.run(&["monitor", "--rules", "--replay", "10"])), 2);
+}
+
+#[test]
+fn test_doctor() {
+    let server = StubServer::start(HashMap::from([
+        ("Ping", ApiResponse::Pong {
+            version: "0.1.0".to_string(),
+            capabilities: vec![
+                CapabilityCheck {
+                    name: "kernel_version".to_string(),
+                    status: CheckStatus::Ok,
+                    detail: "6.8.0".to_string(),
+                    remediation: None,
+                },
+                CapabilityCheck {
+                    name: "xdp_driver_attach".to_string(),
+                    status: CheckStatus::Fail,
+                    detail: "xdpdrv attach failed".to_string(),
+                    remediation: Some("Use --mode generic".to_string()),
+                },
+            ],
+            api: ApiServerStats { requests_total: 7, rate_limited_total: 2, active_connections: 1, ..Default::default() },
+            webhooks: WebhookStats { delivered: 5, failed: 1, retries: 4, ..Default::default() },
+            build: None,
+            load_failure: Some(BpfLoadFailure {
+                object: "/opt/xdp/vendor.o".to_string(),
+                error: "Failed to load BPF object into the kernel: Permission denied (os error 13)".to_string(),
+                log: vec!["0: (61) r2 = *(u32 *)(r1 +4)".to_string(), "invalid bpf_context access off=4 size=4".to_string()],
+                timestamp: 1_700_000_000,
+            }),
+            peer: Some(PeerIdentity { uid: 1000, gid: 1000, pid: Some(4242), user: Some("alice".to_string()) }),
+            features: Some(XdpFeatures::LEGACY.0),
+        }),
+    ]));
+    let output = server.run(&["doctor"]);
+
+    assert_eq!(exit_code(&output), 0);
+    let out = stdout(&output);
+    assert!(out.starts_with("swift-guard-daemon 0.1.0\nConnected as uid 1000 (alice)\nAPI: 7 requests, 0 errors, 2 rate limited, 0 oversized, 1 open connections\n\
+        Webhooks: 5 delivered, 1 failed, 0 dropped, 4 retries, 0 queued\n\
+        XDP match features: tcp_flags (not supported: port_list,redirect_cpu,rate_limit)\n"));
+    assert!(out.contains("[ OK ] kernel_version: 6.8.0"));
+    assert!(out.contains("[FAIL] xdp_driver_attach: xdpdrv attach failed\n       -> Use --mode generic"));
+    assert!(out.contains("1 ok, 0 warnings, 1 failed, 0 skipped"));
+    assert!(out.ends_with(concat!(
+        "Last BPF load failure at 2023-11-14 22:13:20.000: /opt/xdp/vendor.o\n",
+        "  Failed to load BPF object into the kernel: Permission denied (os error 13)\n",
+        "  libbpf log:\n",
+        "    0: (61) r2 = *(u32 *)(r1 +4)\n",
+        "    invalid bpf_context access off=4 size=4\n",
+    )));
+    assert!(matches!(&server.requests()[..], [ApiRequest::Ping {}]));
+}
+
+#[test]
+fn test_version_remote() {
+    let daemon = BuildInfo {
+        version: "0.0.9".to_string(),
+        git_sha: "0123456789ab".to_string(),
+        build_date: "2026-01-02".to_string(),
+        rustc_version: "rustc 1.74.0".to_string(),
+        features: vec!["grpc".to_string()],
+    };
+    let server = StubServer::start(HashMap::from([
+        ("GetVersion", ApiResponse::Version { build: daemon.clone() }),
+    ]));
+
+    // 로컬 정보만 표시할 때는 데몬에 연결하지 않음
+    let output = server.run(&["version"]);
+    assert_eq!(exit_code(&output), 0);
+    assert!(stdout(&output).contains(&format!("Commit    {}", BuildInfo::current().git_sha)), "{}", stdout(&output));
+    assert!(server.requests().is_empty());
+
+    let output = server.run(&["version", "--remote"]);
+    assert_eq!(exit_code(&output), 0);
+    let out = stdout(&output);
+    assert!(out.lines().next().unwrap().contains("CLIENT") && out.contains("DAEMON"), "{}", out);
+    assert!(out.contains("0123456789ab") && out.contains("grpc"), "{}", out);
+    assert!(out.contains("Client and daemon were built from different sources."), "{}", out);
+    assert!(matches!(&server.requests()[..], [ApiRequest::GetVersion {}]));
+
+    let output = server.run(&["version", "--remote", "--format", "json"]);
+    let versions: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(versions["client"]["version"], swift_guard::VERSION);
+    assert_eq!(serde_json::from_value::<BuildInfo>(versions["daemon"].clone()).unwrap(), daemon);
+
+    // --version은 빌드 정보를 포함
+    let output = xdp_filter_env(&[], &["--version"]);
+    assert_eq!(exit_code(&output), 0);
+    assert!(stdout(&output).contains("commit:"), "{}", stdout(&output));
+}
+
+#[test]
+fn test_bpf_info_json() {
+    let server = StubServer::start(HashMap::from([
+        ("GetBpfInfo", ApiResponse::BpfInfo {
+            programs: vec![BpfProgramInfo {
+                name: "xdp_filter_func".to_string(),
+                prog_type: "xdp".to_string(),
+                id: 17,
+                memlock: 4096,
+                pin_path: None,
+            }],
+            maps: vec![BpfMapInfo {
+                name: "filter_rules".to_string(),
+                map_type: "lpm_trie".to_string(),
+                id: 42,
+                max_entries: 10240,
+                key_size: 8,
+                value_size: 64,
+                memlock: 1_048_576,
+                pin_path: Some("/sys/fs/bpf/filter_rules".to_string()),
+            }],
+            layouts: swift_guard::layout::expected(),
+            features: Some(XdpFeatures::ALL.0),
+        }),
+    ]));
+    let output = server.run(&["bpf-info", "--format", "json"]);
+
+    assert_eq!(exit_code(&output), 0);
+    let value: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(value["programs"][0]["name"], "xdp_filter_func");
+    assert_eq!(value["maps"][0]["max_entries"], 10240);
+    assert_eq!(value["maps"][0]["pin_path"], "/sys/fs/bpf/filter_rules");
+    assert_eq!(value["layouts"][0]["name"], "filter_rule");
+    assert_eq!(value["layouts"][0]["size"], 144);
+    assert_eq!(value["features"], serde_json::json!(["tcp_flags", "port_list", "redirect_cpu", "rate_limit"]));
+    assert!(matches!(&server.requests()[..], [ApiRequest::GetBpfInfo {}]));
+
+    // 테이블 출력은 검증된 레이아웃을 함께 표시
+    let output = server.run(&["bpf-info"]);
+    assert_eq!(exit_code(&output), 0);
+    let out = stdout(&output);
+    assert!(out.contains("filter_stats (48 bytes)  last_matched"), "{}", out);
+    assert!(out.ends_with("\nXDP match features: tcp_flags,port_list,redirect_cpu,rate_limit\n"), "{}", out);
+}
+
+#[test]
+fn test_cli_profiles() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let server = StubServer::start(HashMap::from([
+        ("ListRules", rules(vec![rule_fixture()])),
+    ]));
+    let dir = std::env::temp_dir().join(format!("swift-guard-cli-profiles-{}", std::process::id()));
+    let path = dir.join("cli.yaml");
+    let config = path.to_str().unwrap();
+
+    let output = xdp_filter_env(&[], &["--config", config, "config", "set-profile", "prod",
+        "--api-server", server.addr(), "--format", "json", "--default"]);
+    assert_eq!(exit_code(&output), 0);
+    assert_eq!(std::fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+
+    let output = xdp_filter_env(&[], &["--config", config, "config", "set-profile", "lab",
+        "--api-server", &closed_addr()]);
+    assert_eq!(exit_code(&output), 0);
+    let output = xdp_filter_env(&[], &["--config", config, "config", "set-profile", "bad", "--format", "yaml"]);
+    assert_eq!(exit_code(&output), 2);
+
+    let output = xdp_filter_env(&[], &["--config", config, "config", "list-profiles"]);
+    assert_eq!(exit_code(&output), 0);
+    let out = stdout(&output);
+    assert!(out.lines().any(|line| line.starts_with('*') && line.contains("prod") && line.contains("json")));
+    assert!(!out.contains("bad"));
+
+    // 기본 프로필의 주소와 출력 형식 사용
+    let output = xdp_filter_env(&[], &["--config", config, "list-rules"]);
+    assert_eq!(exit_code(&output), 0);
+    assert!(serde_json::from_str::<serde_json::Value>(&stdout(&output)).is_ok());
+
+    // 명령 옵션 > 프로필
+    let output = xdp_filter_env(&[], &["--config", config, "list-rules", "--format", "table"]);
+    assert_eq!(exit_code(&output), 0);
+    assert!(stdout(&output).contains("test-rule"));
+
+    // 환경 변수 프로필 > 기본 프로필, 명령줄 옵션 > 환경 변수
+    let output = xdp_filter_env(&[("SWIFT_GUARD_PROFILE", "lab")], &["--config", config, "list-rules"]);
+    assert_eq!(exit_code(&output), 3);
+    let output = xdp_filter_env(&[("SWIFT_GUARD_API_SERVER", &closed_addr())],
+        &["--config", config, "--profile", "prod", "list-rules"]);
+    assert_eq!(exit_code(&output), 3);
+    let output = xdp_filter_env(&[("SWIFT_GUARD_API_SERVER", &closed_addr())],
+        &["--config", config, "--api-server", server.addr(), "list-rules"]);
+    assert_eq!(exit_code(&output), 0);
+
+    let output = xdp_filter_env(&[], &["--config", config, "--profile", "staging", "list-rules"]);
+    assert_eq!(exit_code(&output), 2);
+
+    let output = xdp_filter_env(&[], &["--config", config, "config", "delete-profile", "prod"]);
+    assert_eq!(exit_code(&output), 0);
+    let output = xdp_filter_env(&[], &["--config", config, "config", "delete-profile", "prod"]);
+    assert_eq!(exit_code(&output), 4);
+
+    assert_eq!(server.requests().len(), 3);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+/// 서버별 결과 표의 (HOST, STATUS) 열
+fn host_statuses(output: &std::process::Output) -> Vec<(String, String)> {
+    stdout(output).lines().skip(2)
+        .map(|line| {
+            let mut cells = line.split_whitespace();
+            (cells.next().unwrap().to_string(), cells.next().unwrap().to_string())
+        })
+        .collect()
+}
+
+#[test]
+fn test_fan_out_mixed_results() {
+    let ok = StubServer::start(HashMap::from([("AddRule", success("Rule 'web' added successfully"))]));
+    let exists = StubServer::start(HashMap::from([("AddRule", ApiResponse::Error {
+        message: "Rule 'web' already exists".to_string(),
+        code: ErrorCode::AlreadyExists,
+    })]));
+    let down = closed_addr();
+    let servers = format!("{},{},{}", ok.addr(), exists.addr(), down);
+
+    let output = xdp_filter_env(&[], &["--api-server", &servers, "add-rule", "--action", "drop", "--label", "web"]);
+    assert_eq!(exit_code(&output), 5, "{}", stderr(&output));
+    assert!(stdout(&output).starts_with("HOST"));
+    assert_eq!(host_statuses(&output), vec![
+        (ok.addr().to_string(), "ok".to_string()),
+        (exists.addr().to_string(), "failed".to_string()),
+        (down.clone(), "failed".to_string()),
+    ]);
+    assert!(stdout(&output).contains("Rule 'web' already exists"));
+    assert!(stderr(&output).contains(&format!("2 of 3 API servers failed: {}, {}", exists.addr(), down)));
+
+    // 모든 서버가 같은 요청을 받음
+    for server in [&ok, &exists] {
+        assert!(matches!(&server.requests()[..], [ApiRequest::AddRule(rule)] if rule.label == "web" && rule.action == 2));
+    }
+
+    // 같은 서버를 두 번 지정하면 인자 오류
+    let servers = format!("{},{}", ok.addr(), ok.addr());
+    assert_eq!(exit_code(&xdp_filter_env(&[], &["--api-server", &servers, "delete-rule", "--label", "web"])), 2);
+
+    // 모두 성공하면 0
+    let second = StubServer::start(HashMap::from([("DeleteRule", success("Rule 'web' deleted"))]));
+    let first = StubServer::start(HashMap::from([("DeleteRule", success("Rule 'web' deleted"))]));
+    let servers = format!("{},{}", first.addr(), second.addr());
+    let output = xdp_filter_env(&[], &["--api-server", &servers, "--parallel", "1", "delete-rule", "--label", "web"]);
+    assert_eq!(exit_code(&output), 0, "{}", stderr(&output));
+    assert_eq!(host_statuses(&output).iter().filter(|(_, status)| status == "ok").count(), 2);
+}
+
+#[test]
+fn test_fan_out_servers_file_and_timeout() {
+    let applied = StubServer::start(HashMap::from([("ApplyRuleset", ApiResponse::RulesetApplied {
+        summary: ApplySummary { added: vec!["web".to_string()], ..Default::default() },
+    })]));
+    // 연결은 받지만 응답하지 않는 서버
+    let silent = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let silent_addr = silent.local_addr().unwrap().to_string();
+
+    let dir = std::env::temp_dir().join(format!("swift-guard-fanout-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let servers = dir.join("hosts.txt");
+    std::fs::write(&servers, format!("# edge nodes\n{}\n{}  # rack 2\n", applied.addr(), silent_addr)).unwrap();
+    let rules = dir.join("rules.yaml");
+    std::fs::write(&rules, "rules:\n  - label: web\n    action: drop\n    dst_port: 80\n    protocol: tcp\n").unwrap();
+
+    let started = std::time::Instant::now();
+    let output = xdp_filter_env(&[], &["--servers-file", servers.to_str().unwrap(), "--host-timeout", "1s",
+        "--output", "jsonl", "apply", "-f", rules.to_str().unwrap()]);
+    assert!(started.elapsed() < std::time::Duration::from_secs(8));
+    assert_eq!(exit_code(&output), 3);
+    let envelope: serde_json::Value = serde_json::from_str(stdout(&output).trim()).unwrap();
+    assert_eq!(envelope["error"]["code"], "unreachable");
+    assert_eq!(envelope["data"], serde_json::json!([
+        { "host": applied.addr(), "status": "ok", "message": "1 added, 0 updated, 0 deleted, 0 unchanged, 0 failed" },
+        { "host": silent_addr, "status": "failed", "message": "No response within 1s" },
+    ]));
+    assert!(matches!(&applied.requests()[..], [ApiRequest::ApplyRuleset { progress: false, .. }]));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_list_rules_merge() {
+    let edge1 = StubServer::start(HashMap::from([("ListRules", rules(vec![rule_fixture()]))]));
+    let edge2 = StubServer::start(HashMap::from([("ListRules", rules(vec![
+        RuleInfo { label: "web".to_string(), ..rule_fixture() },
+    ]))]));
+    let down = closed_addr();
+
+    let servers = format!("{},{}", edge1.addr(), edge2.addr());
+    let output = xdp_filter_env(&[], &["--api-server", &servers, "list-rules", "--merge"]);
+    assert_eq!(exit_code(&output), 0, "{}", stderr(&output));
+    let lines: Vec<String> = stdout(&output).lines().map(str::to_string).collect();
+    assert!(lines[0].starts_with("HOST"));
+    assert!(lines[2].starts_with(edge1.addr()) && lines[2].contains("test-rule"));
+    assert!(lines[3].starts_with(edge2.addr()) && lines[3].contains("web"));
+
+    // 실패한 서버는 stderr에 표시하고 나머지 규칙은 출력
+    let servers = format!("{},{}", edge1.addr(), down);
+    let output = xdp_filter_env(&[], &["--api-server", &servers, "list-rules", "--merge", "--format", "json"]);
+    assert_eq!(exit_code(&output), 3);
+    let merged: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(merged.as_array().unwrap().len(), 1);
+    assert_eq!((merged[0]["host"].as_str(), merged[0]["label"].as_str()), (Some(edge1.addr()), Some("test-rule")));
+    assert!(stderr(&output).contains(&format!("{}: ", down)));
+
+    // 여러 서버의 조회는 --merge가 필요하고, 다른 조회 명령은 지원하지 않음
+    let servers = format!("{},{}", edge1.addr(), edge2.addr());
+    let output = xdp_filter_env(&[], &["--api-server", &servers, "list-rules"]);
+    assert_eq!(exit_code(&output), 2);
+    assert!(stderr(&output).contains("requires --merge"));
+    assert_eq!(exit_code(&xdp_filter_env(&[], &["--api-server", &servers, "stats"])), 2);
+    assert_eq!(edge2.requests().len(), 1);
+}
@@ -0,0 +1,480 @@
+// Swift-Guard CLI JSON lines 출력 통합 테스트
+// `--output jsonl`에서 모든 명령이 stdout에 봉투만 출력하고 종료 코드 규약을 지키는지 확인한다.
+
+mod support;
+
+use std::collections::HashMap;
+
+use serde_json::{json, Value};
+use swift_guard::api::{
+    ApiRequest, ApiResponse, ApiServerStats, ApplySummary, CountryBlockInfo, DaemonEvent, DaemonSelfStats, GeoInfo,
+    MapAudit, PrefixSetInfo, ProtocolStats, ReconcileSummary, RuleChangeKind, RuleDetail, RuleError,
+    RuleExpirySummary, RuleHitEvent, RuleInfo, RuleStats, SelfTestReport, SequencedEvent, SizeHistogram, SystemStats,
+    TrafficBaseline, WasmLogLine, WasmModuleMode, WebhookStats,
+};
+use swift_guard::build_info::BuildInfo;
+use swift_guard::error::ErrorCode;
+use swift_guard::histogram::LatencySnapshot;
+use support::{closed_addr, exit_code, stderr, stdout, xdp_filter_env, xdp_filter_with, StubServer};
+
+fn success(message: &str) -> ApiResponse {
+    ApiResponse::Success { message: message.to_string(), warning: None }
+}
+
+fn rule_fixture() -> RuleInfo {
+    RuleInfo {
+        label: "web".to_string(),
+        src_ip: Some("192.168.1.100".to_string()),
+        dst_port: Some("80".to_string()),
+        protocol: "tcp".to_string(),
+        priority: 10,
+        stats: RuleStats { packets: 7, bytes: 700, ..RuleStats::default() },
+        ..RuleInfo::default()
+    }
+}
+
+fn stats_fixture() -> SystemStats {
+    SystemStats {
+        total_packets: 1000,
+        total_bytes: 64000,
+        packets_per_sec: 100,
+        mbps: 0.5,
+        aborted_packets: 0,
+        anomaly: false,
+        baseline: TrafficBaseline::default(),
+        protocols: ProtocolStats { tcp: 600, udp: 300, icmp: 100, other: 0 },
+        size_histogram: SizeHistogram::default(),
+        rules: RuleExpirySummary { permanent: 3, expiring: 0, next_expiry: None },
+        daemon: DaemonSelfStats { cpu_percent: 1.5, rss_bytes: 1048576, open_fds: 21, tokio_tasks: 9,
+            uptime_secs: 3600, event_lag: 0 },
+        api: ApiServerStats::default(),
+        webhooks: WebhookStats::default(),
+    }
+}
+
+fn country_block(code: &str, error: Option<&str>) -> CountryBlockInfo {
+    CountryBlockInfo {
+        code: code.to_string(),
+        label: format!("country-{}", code),
+        action: "drop".to_string(),
+        set: PrefixSetInfo {
+            name: format!("country-{}", code),
+            entries: 1200,
+            addresses: 45_000_000,
+            source: "geoip".to_string(),
+            version: "GeoLite2-Country 2024-05-03".to_string(),
+        },
+        change: Some(RuleChangeKind::Added),
+        error: error.map(str::to_string),
+        map_entries: 1250,
+        map_capacity: 10240,
+    }
+}
+
+fn rule_hit(seq: u64) -> SequencedEvent {
+    SequencedEvent {
+        seq,
+        event: DaemonEvent::RuleHit(RuleHitEvent {
+            label: "web".to_string(),
+            rule_key: "192.168.1.0/24".to_string(),
+            action: "drop".to_string(),
+            protocol: "tcp".to_string(),
+            src_ip: "192.168.1.100".to_string(),
+            src_port: 40000,
+            dst_ip: "10.0.0.1".to_string(),
+            dst_port: 80,
+            timestamp_ns: 1_700_000_000_000_000_000,
+            geo: None,
+        }),
+    }
+}
+
+fn log_lines(first: u64, messages: &[&str]) -> ApiResponse {
+    ApiResponse::WasmModuleLog {
+        name: "http-inspector".to_string(),
+        lines: messages.iter().zip(first..)
+            .map(|(message, seq)| WasmLogLine { seq, timestamp_ms: 1700000000000, message: message.to_string() })
+            .collect(),
+    }
+}
+
+/// stdout의 모든 봉투 (줄마다 JSON 객체 하나, stderr에는 사람이 읽는 오류를 쓰지 않음)
+fn envelopes(output: &std::process::Output) -> Vec<Value> {
+    assert!(!stderr(output).contains("Error:"), "{}", stderr(output));
+    stdout(output).lines()
+        .map(|line| serde_json::from_str::<Value>(line).unwrap_or_else(|e| panic!("not JSON ({}): {}", e, line)))
+        .inspect(|envelope| {
+            let mut keys: Vec<&String> = envelope.as_object().expect("envelope is an object").keys().collect();
+            keys.sort();
+            assert_eq!(keys, ["data", "error", "ok"]);
+        })
+        .collect()
+}
+
+/// `--output jsonl`로 실행해 종료 코드와 봉투 하나를 돌려줌
+fn run_jsonl(server: &StubServer, args: &[&str]) -> (i32, Value) {
+    let output = xdp_filter_with(server.addr(), &["--output", "jsonl"], args);
+    let mut envelopes = envelopes(&output);
+    assert_eq!(envelopes.len(), 1, "expected one envelope: {}", stdout(&output));
+    (exit_code(&output), envelopes.remove(0))
+}
+
+/// 성공 봉투의 data
+fn ok(server: &StubServer, args: &[&str]) -> Value {
+    let (code, envelope) = run_jsonl(server, args);
+    assert_eq!((code, &envelope["ok"], &envelope["error"]), (0, &json!(true), &Value::Null), "{}", envelope);
+    envelope["data"].clone()
+}
+
+/// 오류 봉투 (종료 코드와 봉투의 exit_code가 같은지 확인)
+fn failed(server: &StubServer, args: &[&str], code: &str) -> Value {
+    let (exit, envelope) = run_jsonl(server, args);
+    assert_eq!(envelope["ok"], json!(false), "{}", envelope);
+    assert_eq!(envelope["error"]["code"], json!(code), "{}", envelope);
+    assert_eq!(envelope["error"]["exit_code"], json!(exit));
+    envelope
+}
+
+#[test]
+fn test_jsonl_rule_commands() {
+    let detail = RuleDetail {
+        info: rule_fixture(),
+        ttl_remaining: None,
+        lpm_key: Some("20c0a80164".to_string()),
+        wildcard_slot: None,
+        installed: true,
+        interfaces: vec!["eth0".to_string()],
+        down_interfaces: Vec::new(),
+        recent_matches: Vec::new(),
+        history_1m: Vec::new(),
+        src_geo: None,
+    };
+    let server = StubServer::start(HashMap::from([
+        ("AddRule", ApiResponse::Success {
+            message: "Rule 'web' added successfully".to_string(),
+            warning: Some("rule installed but no interfaces are attached".to_string()),
+        }),
+        ("DeleteRule", success("Rule 'web' deleted successfully")),
+        ("ListRules", ApiResponse::Rules { rules: vec![rule_fixture()], total_count: 1, next_cursor: None, seq: None }),
+        ("GetRule", ApiResponse::RuleDetail { rule: detail }),
+        ("GetCounters", ApiResponse::Counters { counters: Vec::new() }),
+    ]));
+
+    let data = ok(&server, &["add-rule", "--dst-port", "80", "--action", "drop", "--label", "web"]);
+    assert_eq!(data, json!({
+        "message": "Rule 'web' added successfully",
+        "warning": "rule installed but no interfaces are attached",
+    }));
+    assert_eq!(ok(&server, &["delete-rule", "--label", "web"]), json!({ "message": "Rule 'web' deleted successfully" }));
+
+    // --format보다 우선
+    let data = ok(&server, &["list-rules", "--format", "yaml"]);
+    assert_eq!(data[0]["label"], "web");
+    assert_eq!(data[0]["stats"]["packets"], 7);
+
+    let data = ok(&server, &["show-rule", "--label", "web"]);
+    assert_eq!((&data["label"], &data["installed"]), (&json!("web"), &json!(true)));
+    assert_eq!(ok(&server, &["counters"]), json!([]));
+
+    // 화면 갱신 모드는 지원하지 않음
+    failed(&server, &["list-rules", "--watch", "1"], "invalid_argument");
+    failed(&server, &["counters", "--watch", "1"], "invalid_argument");
+    assert_eq!(server.requests().len(), 5);
+}
+
+#[test]
+fn test_jsonl_apply_repair_reconcile() {
+    let path = std::env::temp_dir().join(format!("swift-guard-jsonl-ruleset-{}.yaml", std::process::id()));
+    std::fs::write(&path, "rules:\n  - label: ssh\n    dst_port: 22\n    protocol: tcp\n    action: drop\n").unwrap();
+
+    let summary = ApplySummary {
+        added: vec!["web".to_string()],
+        errors: vec![RuleError {
+            label: "ssh".to_string(),
+            message: "Rule 'ssh' already exists".to_string(),
+            code: ErrorCode::AlreadyExists,
+        }],
+        ..Default::default()
+    };
+    let server = StubServer::start(HashMap::from([
+        ("ApplyRuleset", ApiResponse::RulesetApplied { summary }),
+        ("RepairRules", ApiResponse::RulesRepaired { repaired: vec!["web".to_string()], errors: Vec::new() }),
+        ("AuditMaps", ApiResponse::MapAudit { audit: MapAudit::default() }),
+        ("ReconcileMaps", ApiResponse::MapsReconciled { summary: ReconcileSummary {
+            errors: vec!["rule web: wildcard_rules map is full".to_string()],
+            ..Default::default()
+        } }),
+    ]));
+
+    // 일부 규칙이 실패하면 결과를 담은 오류 봉투
+    let envelope = failed(&server, &["apply", "-f", path.to_str().unwrap()], "already_exists");
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(envelope["error"]["exit_code"], 5);
+    assert_eq!(envelope["error"]["message"], "1 rule(s) could not be applied");
+    assert_eq!(envelope["data"]["added"], json!(["web"]));
+
+    assert_eq!(ok(&server, &["repair-rules"]), json!({ "repaired": ["web"], "errors": [] }));
+    assert_eq!(ok(&server, &["reconcile"])["audit"]["orphans"], json!([]));
+
+    let envelope = failed(&server, &["reconcile", "--delete-orphans"], "internal");
+    assert_eq!(envelope["data"]["errors"], json!(["rule web: wildcard_rules map is full"]));
+}
+
+#[test]
+fn test_jsonl_interfaces_and_redirects() {
+    let server = StubServer::start(HashMap::from([
+        ("Attach", success("XDP program attached to eth0")),
+        ("Detach", success("XDP program detached from eth0")),
+        ("ListInterfaces", ApiResponse::Interfaces { interfaces: Vec::new() }),
+        ("ListRedirectTargets", ApiResponse::RedirectTargets { targets: Vec::new() }),
+        ("AddRedirectTarget", success("Redirect target veth1 added (ifindex 9)")),
+        ("RemoveRedirectTarget", ApiResponse::InvalidArgument {
+            field: "interface".to_string(),
+            message: "redirect target veth0 is used by rules: web".to_string(),
+        }),
+    ]));
+
+    assert_eq!(ok(&server, &["attach", "eth0"]), json!({ "message": "XDP program attached to eth0" }));
+    assert_eq!(ok(&server, &["detach", "eth0"]), json!({ "message": "XDP program detached from eth0" }));
+    assert_eq!(ok(&server, &["list-interfaces"]), json!([]));
+    assert_eq!(ok(&server, &["redirects"]), json!([]));
+    assert_eq!(ok(&server, &["redirects", "add", "veth1"])["message"], "Redirect target veth1 added (ifindex 9)");
+
+    let envelope = failed(&server, &["redirects", "remove", "veth0"], "invalid_argument");
+    assert!(envelope["error"]["message"].as_str().unwrap().contains("used by rules: web"));
+    assert_eq!(envelope["data"], Value::Null);
+}
+
+#[test]
+fn test_jsonl_presets_countries_lookup() {
+    let server = StubServer::start(HashMap::from([
+        ("ApplyPreset", ApiResponse::RulesetApplied {
+            summary: ApplySummary { added: vec!["icmp-protect.echo".to_string()], ..Default::default() },
+        }),
+        ("ListPresets", ApiResponse::Presets { presets: Vec::new() }),
+        ("RemovePreset", success("Preset 'icmp-protect' removed (1 rules deleted)")),
+        ("BlockCountry", ApiResponse::CountryBlocks { blocks: vec![country_block("RU", None)] }),
+        ("RefreshCountryBlocks", ApiResponse::CountryBlocks { blocks: vec![
+            country_block("CN", None),
+            country_block("RU", Some("Failed to read /etc/swift-guard/ru.csv")),
+        ] }),
+        ("UnblockCountry", success("Country RU unblocked")),
+        ("GeoLookup", ApiResponse::Geo {
+            ip: "203.0.113.5".to_string(),
+            geo: GeoInfo { country: Some("KR".to_string()), asn: Some(4766), as_org: None },
+        }),
+    ]));
+
+    assert_eq!(ok(&server, &["preset", "icmp-protect", "--limit", "1k"])["added"], json!(["icmp-protect.echo"]));
+    assert_eq!(ok(&server, &["preset", "list"]), json!([]));
+    assert_eq!(ok(&server, &["preset", "remove", "icmp-protect"])["message"], "Preset 'icmp-protect' removed (1 rules deleted)");
+
+    assert_eq!(ok(&server, &["block-country", "--code", "RU"])[0]["label"], "country-RU");
+    let envelope = failed(&server, &["refresh-countries"], "invalid_argument");
+    assert_eq!(envelope["data"].as_array().unwrap().len(), 2);
+    assert_eq!(envelope["error"]["message"], "Failed to refresh country block(s): RU");
+    assert_eq!(ok(&server, &["unblock-country", "--code", "RU"]), json!({ "message": "Country RU unblocked" }));
+
+    assert_eq!(ok(&server, &["lookup", "203.0.113.5"]), json!({
+        "ip": "203.0.113.5",
+        "geo": { "country": "KR", "asn": 4766 },
+    }));
+    // 잘못된 주소는 데몬에 보내지 않음
+    failed(&server, &["lookup", "not-an-ip"], "invalid_argument");
+    assert_eq!(server.requests().len(), 7);
+}
+
+#[test]
+fn test_jsonl_wasm() {
+    let server = StubServer::start(HashMap::from([
+        ("LoadWasmModule", success("Module 'http-inspector' loaded")),
+        ("ListWasmModules", ApiResponse::WasmModules { modules: Vec::new() }),
+        ("WasmModuleStats", ApiResponse::WasmModuleStats {
+            name: "http-inspector".to_string(),
+            processed_packets: 42,
+            blocked_packets: 3,
+            avg_processing_time_us: 1.5,
+            state: Some("running".to_string()),
+            errors: 0,
+            last_error: None,
+            latency: LatencySnapshot { bounds_us: vec![1, 5], counts: vec![40, 2, 0], sum_us: 45 },
+            p50_us: Some(0.5),
+            p95_us: Some(1.0),
+            p99_us: Some(3.0),
+            mode: WasmModuleMode::Enforce,
+            would_block_packets: 0,
+        }),
+        ("ResumeWasmModule", success("WASM module http-inspector resumed")),
+        ("UnloadWasmModule", success("Module 'http-inspector' unloaded")),
+        ("ClearWasmCache", success("Removed 2 cached WASM modules")),
+        ("GetWasmModuleLog", log_lines(41, &["GET /index.html", "blocked: SQL injection"])),
+    ]));
+
+    assert_eq!(ok(&server, &["wasm", "load", "--name", "http-inspector", "--file", "http_inspector.wasm"])["message"],
+        "Module 'http-inspector' loaded");
+    assert_eq!(ok(&server, &["wasm", "list"]), json!([]));
+    assert_eq!(ok(&server, &["wasm", "stats", "--name", "http-inspector"]), json!({
+        "name": "http-inspector",
+        "state": "running",
+        "mode": "enforce",
+        "would_block_packets": 0,
+        "processed_packets": 42,
+        "blocked_packets": 3,
+        "avg_processing_time_us": 1.5,
+        "errors": 0,
+        "last_error": null,
+        "latency": { "bounds_us": [1, 5], "counts": [40, 2, 0], "sum_us": 45 },
+        "p50_us": 0.5,
+        "p95_us": 1.0,
+        "p99_us": 3.0,
+    }));
+    assert_eq!(ok(&server, &["wasm", "resume", "--name", "http-inspector"])["message"], "WASM module http-inspector resumed");
+    assert_eq!(ok(&server, &["wasm", "unload", "--name", "http-inspector"])["message"], "Module 'http-inspector' unloaded");
+    assert_eq!(ok(&server, &["wasm", "cache-clear"])["message"], "Removed 2 cached WASM modules");
+
+    let data = ok(&server, &["wasm", "logs", "--name", "http-inspector", "--lines", "2"]);
+    assert_eq!(data.as_array().unwrap().iter().map(|line| line["seq"].as_u64().unwrap()).collect::<Vec<_>>(), [41, 42]);
+}
+
+#[test]
+fn test_jsonl_streams() {
+    // 갱신마다 봉투 하나, 실패하면 마지막 줄이 오류 봉투
+    let server = StubServer::start_sequence(HashMap::from([
+        ("GetStats", vec![ApiResponse::Stats { stats: stats_fixture() }]),
+        ("GetEvents", vec![
+            ApiResponse::Events { events: vec![rule_hit(7), rule_hit(8)], gap: None, latest_seq: 8 },
+            ApiResponse::Events { events: Vec::new(), gap: None, latest_seq: 8 },
+        ]),
+        ("GetWasmModuleLog", vec![
+            log_lines(1, &["first", "second"]),
+            log_lines(3, &[]),
+            log_lines(3, &["third"]),
+            ApiResponse::Error { message: "WASM module http-inspector is not loaded".to_string(), code: ErrorCode::NotFound },
+        ]),
+    ]));
+
+    let output = xdp_filter_with(server.addr(), &["--output", "jsonl"], &["stats", "--count", "2", "--interval", "0"]);
+    assert_eq!(exit_code(&output), 0);
+    let lines = envelopes(&output);
+    assert_eq!(lines.len(), 2);
+    assert!(lines.iter().all(|line| line["ok"] == json!(true) && line["data"]["total_packets"] == json!(1000)));
+
+    let output = xdp_filter_with(server.addr(), &["--output", "jsonl"], &["monitor", "--no-follow", "--interval", "0"]);
+    assert_eq!(exit_code(&output), 0);
+    let seqs: Vec<Value> = envelopes(&output).iter().map(|line| line["data"]["seq"].clone()).collect();
+    assert_eq!(seqs, [json!(7), json!(8)]);
+
+    let output = xdp_filter_with(server.addr(), &["--output", "jsonl"],
+        &["wasm", "logs", "--name", "http-inspector", "--follow", "--interval", "0"]);
+    assert_eq!(exit_code(&output), 4);
+    let lines = envelopes(&output);
+    assert_eq!(lines.len(), 3);
+    assert_eq!((lines[0]["data"].as_array().unwrap().len(), lines[1]["data"][0]["message"].clone()), (2, json!("third")));
+    assert_eq!((&lines[2]["ok"], &lines[2]["error"]["code"]), (&json!(false), &json!("not_found")));
+
+    let server = StubServer::start_frames(HashMap::from([("WatchRules", vec![
+        ApiResponse::Rules { rules: vec![rule_fixture()], total_count: 1, next_cursor: None, seq: Some(3) },
+        ApiResponse::RuleChanged { seq: 5, kind: RuleChangeKind::Deleted, rule: rule_fixture() },
+    ])]));
+    let output = xdp_filter_with(server.addr(), &["--output", "jsonl", "--reconnect-window", "0"], &["monitor", "--rules"]);
+    assert_eq!(exit_code(&output), 3);
+    let lines = envelopes(&output);
+    assert_eq!(lines.len(), 3);
+    assert_eq!((&lines[0]["data"]["kind"], &lines[0]["data"]["rules"][0]["label"]), (&json!("snapshot"), &json!("web")));
+    assert_eq!((&lines[1]["data"]["seq"], &lines[1]["data"]["kind"]), (&json!(5), &json!("deleted")));
+    assert_eq!(lines[2]["error"]["message"], "API server closed the rule watch");
+}
+
+#[test]
+fn test_jsonl_diagnostics() {
+    let server = StubServer::start(HashMap::from([
+        ("GetVersion", ApiResponse::Version { build: BuildInfo::current() }),
+        ("Ping", ApiResponse::Pong {
+            version: "0.1.0".to_string(),
+            capabilities: Vec::new(),
+            api: ApiServerStats::default(),
+            webhooks: WebhookStats::default(),
+            build: None,
+            load_failure: None,
+            peer: None,
+            features: None,
+        }),
+        ("ExplainConflicts", ApiResponse::Conflicts { conflicts: Vec::new() }),
+        ("GetBpfInfo", ApiResponse::BpfInfo { programs: Vec::new(), maps: Vec::new(), layouts: Vec::new(), features: None }),
+        ("ListCaptures", ApiResponse::Captures { captures: Vec::new() }),
+        ("ListConnections", ApiResponse::Connections { connections: Vec::new(), total: 0 }),
+        ("SelfTest", ApiResponse::SelfTest {
+            report: SelfTestReport { interface: "lo".to_string(), passed: true, packets_sent: 5, packets_observed: Some(5), ..Default::default() },
+        }),
+    ]));
+
+    let data = ok(&server, &["version"]);
+    assert_eq!(data["client"]["version"], json!(BuildInfo::current().version));
+    assert!(data.get("daemon").is_none());
+    assert!(ok(&server, &["version", "--remote"])["daemon"].is_object());
+    assert_eq!(ok(&server, &["doctor"]), json!([]));
+    assert_eq!(ok(&server, &["conflicts"]), json!([]));
+    assert_eq!(ok(&server, &["bpf-info"]), json!({ "programs": [], "maps": [], "layouts": [] }));
+    assert_eq!(ok(&server, &["captures"]), json!([]));
+    assert_eq!(ok(&server, &["conntrack"]), json!([]));
+    assert!(matches!(server.requests().last(), Some(ApiRequest::ListConnections { .. })));
+    let data = ok(&server, &["selftest"]);
+    assert_eq!((&data["passed"], &data["packets_observed"]), (&json!(true), &json!(5)));
+}
+
+#[test]
+fn test_jsonl_config() {
+    let dir = std::env::temp_dir().join(format!("swift-guard-jsonl-profiles-{}", std::process::id()));
+    let path = dir.join("cli.yaml");
+    let config = path.to_str().unwrap();
+    let jsonl = |args: &[&str]| {
+        let mut all = vec!["--output", "jsonl", "--config", config, "config"];
+        all.extend_from_slice(args);
+        let output = xdp_filter_env(&[], &all);
+        let mut lines = envelopes(&output);
+        assert_eq!(lines.len(), 1);
+        (exit_code(&output), lines.remove(0))
+    };
+
+    let (code, envelope) = jsonl(&["set-profile", "prod", "--api-server", "10.0.0.1:7654", "--default"]);
+    assert_eq!(code, 0);
+    assert!(envelope["data"]["message"].as_str().unwrap().starts_with("Profile 'prod' saved to "));
+
+    let (code, envelope) = jsonl(&["list-profiles"]);
+    assert_eq!(code, 0);
+    assert_eq!(envelope["data"]["default_profile"], "prod");
+    assert_eq!(envelope["data"]["profiles"]["prod"]["api_server"], "10.0.0.1:7654");
+
+    let (code, envelope) = jsonl(&["delete-profile", "staging"]);
+    assert_eq!((code, &envelope["error"]["code"]), (4, &json!("not_found")));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_jsonl_errors() {
+    // 연결 실패
+    let output = xdp_filter_with(&closed_addr(), &["--output", "jsonl"], &["list-rules"]);
+    assert_eq!(exit_code(&output), 3);
+    let lines = envelopes(&output);
+    assert_eq!((lines.len(), &lines[0]["error"]["code"]), (1, &json!("unreachable")));
+
+    let server = StubServer::start(HashMap::from([
+        ("GetRule", ApiResponse::NotFound { resource: "rule".to_string(), name: "web".to_string() }),
+        ("ListRules", ApiResponse::RateLimited { retry_after_ms: 120, limit: 5 }),
+        ("DeleteRule", ApiResponse::Error { message: "Token is read-only".to_string(), code: ErrorCode::PermissionDenied }),
+    ]));
+    let envelope = failed(&server, &["show-rule", "--label", "web"], "not_found");
+    assert_eq!(envelope["error"], json!({ "code": "not_found", "exit_code": 4, "message": "rule 'web' not found" }));
+    failed(&server, &["list-rules"], "rate_limited");
+    failed(&server, &["delete-rule", "--label", "web"], "permission_denied");
+
+    // 인수 파싱 오류도 봉투로 출력 (옵션은 명령 뒤에 와도 됨)
+    let output = xdp_filter_with(server.addr(), &[], &["delete-rule", "--output=jsonl"]);
+    assert_eq!(exit_code(&output), 2);
+    assert_eq!(stderr(&output), "");
+    let lines = envelopes(&output);
+    assert_eq!(lines[0]["error"]["code"], "invalid_argument");
+    assert!(lines[0]["error"]["message"].as_str().unwrap().contains("--label <LABEL>"), "{}", lines[0]);
+    assert_eq!(server.requests().len(), 3);
+}
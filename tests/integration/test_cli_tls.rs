@@ -0,0 +1,173 @@
+// Swift-Guard CLI TLS 통합 테스트
+// 테스트 안에서 만든 자체 서명 CA와 인증서로 TLS/상호 TLS 연결과 핸드셰이크 오류 메시지를 확인한다.
+
+mod support;
+
+use std::collections::HashMap;
+use std::fs;
+use std::net::{IpAddr, Ipv4Addr};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use rcgen::{BasicConstraints, Certificate, CertificateParams, IsCa, SanType};
+use swift_guard::api::ApiResponse;
+use support::{exit_code, stderr, stdout, xdp_filter_with, StubServer};
+
+static NEXT_DIR: AtomicUsize = AtomicUsize::new(0);
+
+/// 테스트별 인증서 디렉토리
+struct CertDir {
+    path: PathBuf,
+    ca: Certificate,
+}
+
+impl CertDir {
+    fn new() -> Self {
+        let path = std::env::temp_dir().join(format!(
+            "swift-guard-tls-{}-{}", std::process::id(), NEXT_DIR.fetch_add(1, Ordering::SeqCst)));
+        fs::create_dir_all(&path).unwrap();
+
+        let mut params = CertificateParams::new(Vec::new());
+        params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+        let ca = Certificate::from_params(params).unwrap();
+        fs::write(path.join("ca.pem"), ca.serialize_pem().unwrap()).unwrap();
+
+        Self { path, ca }
+    }
+
+    fn file(&self, name: &str) -> PathBuf {
+        self.path.join(name)
+    }
+
+    fn arg(&self, name: &str) -> String {
+        self.file(name).to_string_lossy().into_owned()
+    }
+
+    /// CA로 서명한 인증서와 키를 `<name>.pem`, `<name>-key.pem`으로 저장
+    fn issue(&self, name: &str, params: CertificateParams) {
+        let cert = Certificate::from_params(params).unwrap();
+        fs::write(self.file(&format!("{}.pem", name)), cert.serialize_pem_with_signer(&self.ca).unwrap()).unwrap();
+        fs::write(self.file(&format!("{}-key.pem", name)), cert.serialize_private_key_pem()).unwrap();
+    }
+
+    /// 127.0.0.1용 서버 인증서 발급
+    fn issue_server(&self, name: &str) {
+        self.issue(name, leaf_params(vec![
+            SanType::IpAddress(IpAddr::V4(Ipv4Addr::LOCALHOST)),
+            SanType::DnsName("localhost".to_string()),
+        ]));
+    }
+
+    /// 이 디렉토리의 인증서로 TLS 스텁 서버 시작
+    fn start_server(&self, name: &str, client_ca: Option<&Path>) -> StubServer {
+        let config = swift_guard::tls::server_config(
+            &self.file(&format!("{}.pem", name)),
+            &self.file(&format!("{}-key.pem", name)),
+            client_ca,
+        ).unwrap();
+
+        let mut responses = HashMap::new();
+        responses.insert("DeleteRule", ApiResponse::Success { message: "Rule deleted".to_string(), warning: None });
+        StubServer::start_tls(responses, config)
+    }
+}
+
+impl Drop for CertDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
+
+fn leaf_params(names: Vec<SanType>) -> CertificateParams {
+    let mut params = CertificateParams::new(Vec::new());
+    params.subject_alt_names = names;
+    params
+}
+
+#[test]
+fn test_tls_round_trip() {
+    let certs = CertDir::new();
+    certs.issue_server("server");
+    let server = certs.start_server("server", None);
+
+    let output = xdp_filter_with(server.addr(), &["--tls", "--ca-cert", &certs.arg("ca.pem")],
+        &["delete-rule", "--label", "web"]);
+
+    assert_eq!(exit_code(&output), 0, "stderr: {}", stderr(&output));
+    assert!(stdout(&output).contains("Rule deleted"));
+    assert_eq!(server.raw_requests().len(), 1);
+}
+
+#[test]
+fn test_plain_client_against_tls_server_fails() {
+    let certs = CertDir::new();
+    certs.issue_server("server");
+    let server = certs.start_server("server", None);
+
+    let output = server.run(&["delete-rule", "--label", "web"]);
+
+    assert_eq!(exit_code(&output), 3);
+    assert!(server.raw_requests().is_empty());
+}
+
+#[test]
+fn test_tls_name_mismatch() {
+    let certs = CertDir::new();
+    certs.issue("server", leaf_params(vec![SanType::DnsName("guard.example.com".to_string())]));
+    let server = certs.start_server("server", None);
+
+    let output = xdp_filter_with(server.addr(), &["--tls", "--ca-cert", &certs.arg("ca.pem")],
+        &["delete-rule", "--label", "web"]);
+
+    assert_eq!(exit_code(&output), 3);
+    let err = stderr(&output);
+    assert!(err.contains("not valid for '127.0.0.1'"), "stderr: {}", err);
+}
+
+#[test]
+fn test_tls_expired_certificate() {
+    let certs = CertDir::new();
+    let mut params = leaf_params(vec![SanType::IpAddress(IpAddr::V4(Ipv4Addr::LOCALHOST))]);
+    params.not_before = rcgen::date_time_ymd(2019, 1, 1);
+    params.not_after = rcgen::date_time_ymd(2020, 1, 1);
+    certs.issue("server", params);
+    let server = certs.start_server("server", None);
+
+    let output = xdp_filter_with(server.addr(), &["--tls", "--ca-cert", &certs.arg("ca.pem")],
+        &["delete-rule", "--label", "web"]);
+
+    assert_eq!(exit_code(&output), 3);
+    let err = stderr(&output);
+    assert!(err.contains("certificate has expired"), "stderr: {}", err);
+}
+
+#[test]
+fn test_mutual_tls() {
+    let certs = CertDir::new();
+    certs.issue_server("server");
+    certs.issue("client", leaf_params(vec![SanType::DnsName("operator".to_string())]));
+    let server = certs.start_server("server", Some(&certs.file("ca.pem")));
+
+    // 클라이언트 인증서 없이 연결하면 거부
+    let output = xdp_filter_with(server.addr(), &["--tls", "--ca-cert", &certs.arg("ca.pem")],
+        &["delete-rule", "--label", "web"]);
+    assert_eq!(exit_code(&output), 3);
+    let err = stderr(&output);
+    assert!(err.contains("--client-cert"), "stderr: {}", err);
+    assert!(server.raw_requests().is_empty());
+
+    // 클라이언트 인증서를 제시하면 성공
+    let output = xdp_filter_with(server.addr(), &[
+        "--tls", "--ca-cert", &certs.arg("ca.pem"),
+        "--client-cert", &certs.arg("client.pem"),
+        "--client-key", &certs.arg("client-key.pem"),
+    ], &["delete-rule", "--label", "web"]);
+    assert_eq!(exit_code(&output), 0, "stderr: {}", stderr(&output));
+    assert_eq!(server.raw_requests().len(), 1);
+}
+
+#[test]
+fn test_tls_requires_ca_cert() {
+    let output = xdp_filter_with("127.0.0.1:1", &["--tls"], &["delete-rule", "--label", "web"]);
+    assert_eq!(exit_code(&output), 2);
+}
@@ -69,7 +69,12 @@ EOF
     
     # Copy the source file
     cp "$OLDPWD/$rust_file" src/lib.rs
-    
+
+    # Run the module's unit tests on the host first
+    if grep -q '#\[cfg(test)\]' src/lib.rs; then
+        cargo test --release
+    fi
+
     # Build the WASM module
     cargo build --release --target "$WASM_TARGET"
     
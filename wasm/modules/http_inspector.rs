@@ -1,19 +1,49 @@
 //! HTTP 인스펙터 WASM 모듈
 //! 의심스러운 HTTP 요청을 탐지하는 WASM 모듈
 //! 이 코드는 Rust에서 컴파일하여 WASM으로 변환
+//!
+//! 헤더는 모두 길이를 확인한 뒤 읽으며, 잘렸거나 헤더 길이와 옵션이 맞지 않는 패킷은
+//! 검사하지 않고 통과시킨다. 모듈 설정(`wasm load --config-file`)에 `debug` 줄이 있으면
+//! HTTP 요청마다 요청 줄을 로그로 남긴다 (패킷마다 문자열을 만들므로 기본은 꺼짐).
+//!
+//! `inspect_packet_v2`로 패킷을 검사로 보낸 규칙의 문맥을 받아 판정 로그에 규칙 레이블을
+//! 붙인다. 규칙 정보가 없는 문맥이나 `inspect_packet`으로 받은 패킷은 레이블 없이 남긴다.
 
 use std::mem;
+use std::sync::OnceLock;
 
 // 호스트 함수 선언
+#[cfg(target_arch = "wasm32")]
 extern "C" {
     fn log(ptr: *const u8, len: i32) -> i32;
+    fn config_len() -> i32;
+    fn read_config(ptr: *mut u8, len: i32) -> i32;
+}
+
+/// `init`에서 읽은 설정
+static SETTINGS: OnceLock<Settings> = OnceLock::new();
+
+/// 규칙 문맥 크기 (`label[32]`, `action`, `source`, 예약 6바이트)
+const CONTEXT_SIZE: usize = 40;
+
+/// 문맥의 레이블 필드 크기 (NUL로 채움)
+const CONTEXT_LABEL_LEN: usize = 32;
+
+/// 데몬이 규칙 문맥을 써 넣는 버퍼의 주소 (`get_context_buffer`에서 한 번 할당)
+static CONTEXT_BUFFER: OnceLock<usize> = OnceLock::new();
+
+/// 모듈 설정
+#[derive(Debug, Default, PartialEq)]
+struct Settings {
+    /// HTTP 요청마다 로그 남기기
+    debug: bool,
 }
 
 // 메모리 관리를 위한 전역 할당자
 #[no_mangle]
 pub extern "C" fn allocate(size: i32) -> i32 {
 //    let mut buffer = Vec::with_capacity(size as usize);
-    let mut buffer = vec![0u8; size as usize];
+    let mut buffer = vec![0u8; size.max(0) as usize];
     let ptr = buffer.as_mut_ptr();
     mem::forget(buffer);
     (ptr as *const () as usize) as i32
@@ -21,7 +51,7 @@ pub extern "C" fn allocate(size: i32) -> i32 {
 
 #[no_mangle]
 pub extern "C" fn deallocate(ptr: i32, capacity: i32) {
-    if ptr == 0 {
+    if ptr == 0 || capacity <= 0 {
         return;
     }
     unsafe {
@@ -31,44 +61,164 @@ pub extern "C" fn deallocate(ptr: i32, capacity: i32) {
 }
 
 // 로그 함수
+#[cfg(target_arch = "wasm32")]
 fn log_message(message: &str) {
     unsafe {
         log(message.as_ptr(), message.len() as i32);
     }
 }
 
-// 초기화 함수
+#[cfg(not(target_arch = "wasm32"))]
+fn log_message(_message: &str) {}
+
+// 모듈 설정 읽기 (없으면 빈 값)
+#[cfg(target_arch = "wasm32")]
+fn module_config() -> Vec<u8> {
+    let len = unsafe { config_len() };
+    if len <= 0 {
+        return Vec::new();
+    }
+
+    let mut buffer = vec![0u8; len as usize];
+    let copied = unsafe { read_config(buffer.as_mut_ptr(), len) };
+    buffer.truncate(copied.max(0) as usize);
+    buffer
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn module_config() -> Vec<u8> {
+    Vec::new()
+}
+
+/// 설정 파싱 (빈 줄과 `#`으로 시작하는 줄은 건너뜀)
+fn parse_settings(text: &str) -> Result<Settings, String> {
+    let mut settings = Settings::default();
+    for (index, line) in text.lines().enumerate() {
+        match line.trim() {
+            "" => {},
+            line if line.starts_with('#') => {},
+            "debug" => settings.debug = true,
+            other => return Err(format!("line {}: unknown setting '{}'", index + 1, other)),
+        }
+    }
+    Ok(settings)
+}
+
+// 초기화 함수 (설정이 잘못되면 실패해 로드를 거부)
 #[no_mangle]
 pub extern "C" fn init() {
-    log_message("HTTP Inspector initialized");
+    let config = module_config();
+    let parsed = std::str::from_utf8(&config)
+        .map_err(|_| "config is not valid UTF-8".to_string())
+        .and_then(parse_settings);
+
+    match parsed {
+        Ok(settings) => {
+            log_message(if settings.debug {
+                "HTTP Inspector initialized with per-request logging"
+            } else {
+                "HTTP Inspector initialized"
+            });
+            let _ = SETTINGS.set(settings);
+        },
+        Err(e) => {
+            log_message(&format!("Invalid HTTP inspector config: {}", e));
+            panic!("invalid HTTP inspector config");
+        },
+    }
+}
+
+fn be16(data: &[u8], offset: usize) -> Option<u16> {
+    let bytes = data.get(offset..offset.checked_add(2)?)?;
+    Some(u16::from_be_bytes([bytes[0], bytes[1]]))
+}
+
+/// IP/TCP 옵션 영역이 온전한지 (EOL과 NOP 외의 옵션은 길이가 2 이상이고 영역 안에서 끝남)
+fn options_valid(options: &[u8]) -> bool {
+    let mut offset = 0;
+    while let Some(&kind) = options.get(offset) {
+        match kind {
+            0 => return true,
+            1 => offset += 1,
+            _ => match options.get(offset + 1) {
+                Some(&len) if len >= 2 && offset + len as usize <= options.len() => offset += len as usize,
+                _ => return false,
+            },
+        }
+    }
+    true
+}
+
+/// IPv4 패킷의 (프로토콜, L4 헤더부터 끝까지)
+fn ipv4(packet: &[u8]) -> Option<(u8, &[u8])> {
+    let version_ihl = *packet.first()?;
+    if version_ihl >> 4 != 4 {
+        return None;
+    }
+
+    let header_len = (version_ihl & 0x0f) as usize * 4;
+    let total_len = be16(packet, 2)? as usize;
+    if header_len < 20 || total_len < header_len || !options_valid(packet.get(20..header_len)?) {
+        return None;
+    }
+
+    // 첫 조각이 아닌 조각에는 TCP 헤더가 없음
+    if be16(packet, 6)? & 0x1fff != 0 {
+        return None;
+    }
+
+    // 이더넷 패딩은 빼고, 잘린 프레임이면 있는 만큼만
+    let end = total_len.min(packet.len());
+    Some((*packet.get(9)?, packet.get(header_len..end)?))
+}
+
+/// TCP 세그먼트
+#[derive(Debug, PartialEq)]
+struct TcpSegment<'a> {
+    src_port: u16,
+    dst_port: u16,
+    payload: &'a [u8],
 }
 
 // TCP 패킷 구문 분석
-fn parse_tcp_packet(data: &[u8], offset: usize) -> Option<(u16, u16, u8)> {
-    if data.len() < offset + 20 {
+fn parse_tcp_packet(segment: &[u8]) -> Option<TcpSegment<'_>> {
+    let header_len = (*segment.get(12)? >> 4) as usize * 4;
+    if header_len < 20 || !options_valid(segment.get(20..header_len)?) {
         return None;
     }
-    
-    let source_port = ((data[offset] as u16) << 8) | (data[offset + 1] as u16);
-    let dest_port = ((data[offset + 2] as u16) << 8) | (data[offset + 3] as u16);
-    let flags = data[offset + 13];
-    
-    Some((source_port, dest_port, flags))
+
+    Some(TcpSegment {
+        src_port: be16(segment, 0)?,
+        dst_port: be16(segment, 2)?,
+        payload: segment.get(header_len..)?,
+    })
+}
+
+/// 이더넷 프레임에서 IPv4 TCP 세그먼트 추출
+fn parse_frame(frame: &[u8]) -> Option<TcpSegment<'_>> {
+    if be16(frame, 12)? != 0x0800 {
+        return None;
+    }
+
+    match ipv4(frame.get(14..)?)? {
+        (6, segment) => parse_tcp_packet(segment),
+        _ => None,
+    }
 }
 
 // HTTP 메서드 확인
 fn check_http_method(payload: &[u8]) -> bool {
     let methods: &[&[u8]] = &[
-        b"GET ", b"POST ", b"PUT ", b"DELETE ", b"HEAD ", 
+        b"GET ", b"POST ", b"PUT ", b"DELETE ", b"HEAD ",
         b"OPTIONS ", b"CONNECT ", b"TRACE ", b"PATCH "
     ];
-    
+
     for method in methods {
         if payload.starts_with(method) {
             return true;
         }
     }
-    
+
     false
 }
 
@@ -79,29 +229,29 @@ fn check_suspicious_http(payload: &[u8]) -> bool {
         b"UNION SELECT", b"OR 1=1", b"' OR '", b"DROP TABLE",
         b"--", b"/*", b"*/", b"EXEC(", b"EXECUTE(", b"xp_cmdshell"
     ];
-    
+
     // XSS 패턴
     let xss_patterns: &[&[u8]] = &[
-        b"<script>", b"javascript:", b"onerror=", b"onload=", b"eval(", 
+        b"<script>", b"javascript:", b"onerror=", b"onload=", b"eval(",
         b"document.cookie", b"alert(", b"String.fromCharCode("
     ];
-    
+
     // 경로 순회 패턴
     let traversal_patterns: &[&[u8]] = &[
         b"../", b"..\\", b"/etc/passwd", b"\\windows\\system32", b"C:\\Windows"
     ];
-    
+
     // 명령어 인젝션 패턴
     let cmd_patterns: &[&[u8]] = &[
         b";", b"|", b"&", b"$(", b"`", b"$()", b"${", b">"
     ];
-    
+
     // 페이로드가 너무 큰 경우
     if payload.len() > 4096 {
         log_message(&format!("Large HTTP payload detected: {} bytes", payload.len()));
         return true;
     }
-    
+
     // 패턴 검사
     for pattern in sql_patterns {
         if payload.windows(pattern.len()).any(|window| window == *pattern) {
@@ -109,90 +259,310 @@ fn check_suspicious_http(payload: &[u8]) -> bool {
             return true;
         }
     }
-    
+
     for pattern in xss_patterns {
         if payload.windows(pattern.len()).any(|window| window == *pattern) {
             log_message(&format!("XSS pattern detected: {:?}", pattern));
             return true;
         }
     }
-    
+
     for pattern in traversal_patterns {
         if payload.windows(pattern.len()).any(|window| window == *pattern) {
             log_message(&format!("Path traversal pattern detected: {:?}", pattern));
             return true;
         }
     }
-    
+
     for pattern in cmd_patterns {
         if payload.windows(pattern.len()).any(|window| window == *pattern) {
             log_message(&format!("Command injection pattern detected: {:?}", pattern));
             return true;
         }
     }
-    
+
     false
 }
 
+/// 패킷을 검사로 보낸 규칙
+#[derive(Debug, PartialEq)]
+struct RuleContext {
+    label: String,
+    /// 규칙 액션 (1 = pass, 2 = drop, 3 = redirect, 4 = count)
+    action: u8,
+}
+
+/// 데몬이 쓴 문맥 해석 (액션이 0이면 규칙 정보 없음)
+fn parse_context(context: &[u8]) -> Option<RuleContext> {
+    let action = *context.get(CONTEXT_LABEL_LEN)?;
+    if action == 0 {
+        return None;
+    }
+
+    let label = context.get(..CONTEXT_LABEL_LEN)?;
+    let end = label.iter().position(|&b| b == 0).unwrap_or(CONTEXT_LABEL_LEN - 1);
+    Some(RuleContext {
+        label: String::from_utf8_lossy(&label[..end]).into_owned(),
+        action,
+    })
+}
+
+/// 로그 메시지에 규칙 레이블 붙이기 (규칙 정보가 없으면 그대로)
+fn with_rule(message: &str, rule: Option<&RuleContext>) -> String {
+    match rule {
+        Some(rule) => format!("{} [rule {}]", message, rule.label),
+        None => message.to_string(),
+    }
+}
+
+/// 프레임 판정 (1 = 차단, 0 = 통과)
+fn verdict(frame: &[u8], debug: bool, rule: Option<&RuleContext>) -> i32 {
+    let segment = match parse_frame(frame) {
+        Some(segment) => segment,
+        None => return 0, // 패킷 통과
+    };
+
+    // HTTP 트래픽 확인 (포트 80, 8080, 443, 8443)
+    if !matches!(segment.dst_port, 80 | 8080 | 443 | 8443) || !check_http_method(segment.payload) {
+        return 0;
+    }
+
+    if debug {
+        log_message(&with_rule(&format!("HTTP traffic detected: {}:{} -> {}",
+            segment.src_port, segment.dst_port,
+            String::from_utf8_lossy(&segment.payload[..segment.payload.len().min(20)])
+        ), rule));
+    }
+
+    // 의심스러운 HTTP 요청 확인 (마지막 로그가 차단 사유)
+    if check_suspicious_http(segment.payload) {
+        log_message(&with_rule("Suspicious HTTP request blocked", rule));
+        return 1; // 패킷 차단
+    }
+
+    0
+}
+
 // 패킷 검사 메인 함수 (WASM 인터페이스)
 #[no_mangle]
 pub extern "C" fn inspect_packet(ptr: i32, len: i32) -> i32 {
+    if ptr == 0 || len <= 0 {
+        return 0; // 패킷 통과
+    }
+
     let data = unsafe {
         std::slice::from_raw_parts(ptr as *const u8, len as usize)
     };
-    
-    // 최소 이더넷 + IP 헤더 크기 확인
-    if data.len() < 34 {
+    let debug = SETTINGS.get().is_some_and(|settings| settings.debug);
+
+    verdict(data, debug, None)
+}
+
+// 규칙 문맥 버퍼 주소
+#[no_mangle]
+pub extern "C" fn get_context_buffer() -> i32 {
+    let buffer = CONTEXT_BUFFER.get_or_init(|| {
+        let buffer = vec![0u8; CONTEXT_SIZE].into_boxed_slice();
+        Box::leak(buffer).as_mut_ptr() as usize
+    });
+    *buffer as i32
+}
+
+// 규칙 문맥과 함께 받는 패킷 검사 함수 (WASM 인터페이스, 문맥은 `get_context_buffer` 주소)
+#[no_mangle]
+pub extern "C" fn inspect_packet_v2(ptr: i32, len: i32, context: i32) -> i32 {
+    if ptr == 0 || len <= 0 {
         return 0; // 패킷 통과
     }
-    
-    // 이더넷 헤더 건너뛰기 (14바이트)
-    let eth_type = ((data[12] as u16) << 8) | (data[13] as u16);
-    
-    // IPv4 확인
-    if eth_type != 0x0800 {
-        return 0; // 패킷 통과
+
+    let data = unsafe {
+        std::slice::from_raw_parts(ptr as *const u8, len as usize)
+    };
+    let rule = match CONTEXT_BUFFER.get() {
+        Some(&buffer) if buffer == context as usize => {
+            parse_context(unsafe { std::slice::from_raw_parts(buffer as *const u8, CONTEXT_SIZE) })
+        },
+        _ => None,
+    };
+    let debug = SETTINGS.get().is_some_and(|settings| settings.debug);
+
+    verdict(data, debug, rule.as_ref())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 이더넷 + IPv4 + TCP 프레임 (IP 옵션 `ip_options`, TCP 옵션 `tcp_options`)
+    fn frame(dst_port: u16, ip_options: &[u8], tcp_options: &[u8], payload: &[u8]) -> Vec<u8> {
+        let ip_len = 20 + ip_options.len();
+        let tcp_len = 20 + tcp_options.len();
+        let total_len = (ip_len + tcp_len + payload.len()) as u16;
+
+        let mut frame = vec![0u8; 12];
+        frame.extend_from_slice(&[0x08, 0x00]);
+        frame.extend_from_slice(&[0x40 | (ip_len / 4) as u8, 0x00]);
+        frame.extend_from_slice(&total_len.to_be_bytes());
+        frame.extend_from_slice(&[0, 0, 0x40, 0x00, 64, 6, 0, 0]);
+        frame.extend_from_slice(&[10, 0, 0, 1, 10, 0, 0, 2]);
+        frame.extend_from_slice(ip_options);
+
+        frame.extend_from_slice(&40000u16.to_be_bytes());
+        frame.extend_from_slice(&dst_port.to_be_bytes());
+        frame.extend_from_slice(&[0; 8]);
+        frame.extend_from_slice(&[((tcp_len / 4) as u8) << 4, 0x18, 0xff, 0xff, 0, 0, 0, 0]);
+        frame.extend_from_slice(tcp_options);
+        frame.extend_from_slice(payload);
+        frame
     }
-    
-    // IP 헤더 길이 계산
-    let ip_header_len = (data[14] & 0x0F) as usize * 4;
-    
-    // 프로토콜 확인 (TCP = 6)
-    if data[23] != 6 {
-        return 0; // 패킷 통과
+
+    /// 결정적인 의사 난수 (xorshift64)
+    struct Rng(u64);
+
+    impl Rng {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        fn below(&mut self, bound: usize) -> usize {
+            (self.next() % bound as u64) as usize
+        }
+    }
+
+    #[test]
+    fn test_parse_settings() {
+        assert_eq!(parse_settings(""), Ok(Settings { debug: false }));
+        assert_eq!(parse_settings("# verbose\n\n debug \n"), Ok(Settings { debug: true }));
+        assert_eq!(parse_settings("trace"), Err("line 1: unknown setting 'trace'".to_string()));
+    }
+
+    /// 데몬이 쓰는 문맥 (`label`, `action`, `source`)
+    fn context(label: &str, action: u8, source: u8) -> [u8; CONTEXT_SIZE] {
+        let mut context = [0u8; CONTEXT_SIZE];
+        context[..label.len()].copy_from_slice(label.as_bytes());
+        context[CONTEXT_LABEL_LEN] = action;
+        context[CONTEXT_LABEL_LEN + 1] = source;
+        context
+    }
+
+    #[test]
+    fn test_rule_context() {
+        let rule = parse_context(&context("untrusted-ingress", 4, 1)).unwrap();
+        assert_eq!(rule, RuleContext { label: "untrusted-ingress".to_string(), action: 4 });
+        assert_eq!(with_rule("Suspicious HTTP request blocked", Some(&rule)),
+            "Suspicious HTTP request blocked [rule untrusted-ingress]");
+
+        // 규칙 정보가 없으면 레이블 없이
+        assert_eq!(parse_context(&context("", 0, 0)), None);
+        assert_eq!(parse_context(&[0u8; 8]), None);
+        assert_eq!(with_rule("Suspicious HTTP request blocked", None), "Suspicious HTTP request blocked");
+
+        // 판정은 문맥과 관계없음
+        let attack = frame(80, &[], &[], b"GET /../etc/passwd HTTP/1.1\r\n");
+        assert_eq!(verdict(&attack, true, Some(&rule)), 1);
+        assert_eq!(verdict(&attack, true, None), 1);
+
+        // 문맥 버퍼는 한 번만 할당하고, 다른 주소의 문맥은 무시
+        let buffer = get_context_buffer();
+        assert_eq!(get_context_buffer(), buffer);
+        assert_eq!(inspect_packet_v2(0, 64, buffer), 0);
+    }
+
+    #[test]
+    fn test_verdicts() {
+        let attack = b"GET /../etc/passwd HTTP/1.1\r\n";
+        assert_eq!(verdict(&frame(80, &[], &[], attack), false, None), 1);
+        assert_eq!(verdict(&frame(80, &[], &[], b"GET /index.html HTTP/1.1\r\n"), true, None), 0);
+        assert_eq!(verdict(&frame(22, &[], &[], attack), false, None), 0);
+
+        // 옵션이 있는 헤더와 이더넷 패딩
+        let mss = [2, 4, 0x05, 0xb4];
+        let mut padded = frame(8080, &[1, 1, 1, 0], &mss, attack);
+        assert_eq!(verdict(&padded, false, None), 1);
+        padded.extend_from_slice(&[0xee; 16]);
+        assert_eq!(verdict(&padded, false, None), 1);
     }
-    
-    // TCP 헤더 파싱
-    let ip_offset = 14;
-    let tcp_offset = ip_offset + ip_header_len;
-    
-    if let Some((source_port, dest_port, flags)) = parse_tcp_packet(data, tcp_offset) {
-        // HTTP 트래픽 확인 (포트 80 또는 8080)
-        if dest_port == 80 || dest_port == 8080 || dest_port == 443 || dest_port == 8443 {
-            // TCP 헤더 길이 계산
-            let tcp_header_len = ((data[tcp_offset + 12] >> 4) & 0x0F) as usize * 4;
-            let payload_offset = tcp_offset + tcp_header_len;
-            
-            // 페이로드가 있는 경우
-            if data.len() > payload_offset {
-                let payload = &data[payload_offset..];
-                
-                // HTTP 요청인지 확인
-                if check_http_method(payload) {
-                    log_message(&format!("HTTP traffic detected: {}:{} -> {}",
-                        source_port, dest_port, 
-                        String::from_utf8_lossy(&payload[0..payload.len().min(20)])
-                    ));
-                    
-                    // 의심스러운 HTTP 요청 확인
-                    if check_suspicious_http(payload) {
-                        log_message("Suspicious HTTP request blocked");
-                        return 1; // 패킷 차단
-                    }
-                }
+
+    #[test]
+    fn test_malformed_packets() {
+        let mss = [2, 4, 0x05, 0xb4];
+        let full = frame(80, &[1, 1, 1, 0], &mss, b"GET /?q=UNION SELECT HTTP/1.1\r\n");
+        let payload_start = full.len() - 31;
+
+        // 잘린 프레임은 어느 길이에서도 패닉 없이 통과
+        for len in 0..=payload_start + 12 {
+            assert_eq!(verdict(&full[..len], false, None), 0, "{}", len);
+        }
+        assert_eq!(verdict(&full, false, None), 1);
+
+        // IP 헤더 길이가 최소보다 작거나 프레임을 넘음 (40바이트 IPv4 패킷, 옵션 길이 60)
+        let mut short_ihl = full.clone();
+        short_ihl[14] = 0x44;
+        assert_eq!(parse_frame(&short_ihl), None);
+        let mut long_ihl = full[..54].to_vec();
+        long_ihl[14] = 0x4f;
+        assert_eq!(parse_frame(&long_ihl), None);
+
+        // IP 옵션 길이가 0이거나 옵션 영역을 넘음
+        for bad in [[7, 0, 0, 0], [7, 9, 0, 0], [1, 1, 1, 0x44]] {
+            assert_eq!(parse_frame(&frame(80, &bad, &[], b"GET / HTTP/1.1")), None, "{:?}", bad);
+        }
+
+        // TCP 데이터 오프셋이 최소보다 작거나 세그먼트를 넘음, TCP 옵션 길이 오류
+        let tcp = 14 + 24;
+        let mut short_tcp = full.clone();
+        short_tcp[tcp + 12] = 0x40;
+        assert_eq!(parse_frame(&short_tcp), None);
+        let mut long_tcp = full.clone();
+        long_tcp[tcp + 12] = 0xf0;
+        long_tcp.truncate(tcp + 40);
+        assert_eq!(parse_frame(&long_tcp), None);
+        assert_eq!(parse_frame(&frame(80, &[], &[2, 1, 0, 0], b"GET / HTTP/1.1")), None);
+
+        // 첫 조각이 아닌 조각과 전체 길이가 헤더보다 짧은 패킷
+        let mut fragment = full.clone();
+        fragment[20..22].copy_from_slice(&0x2001u16.to_be_bytes());
+        assert_eq!(verdict(&fragment, false, None), 0);
+        let mut short_total = full.clone();
+        short_total[16..18].copy_from_slice(&10u16.to_be_bytes());
+        assert_eq!(verdict(&short_total, false, None), 0);
+
+        assert_eq!(inspect_packet(0, 64), 0);
+        assert_eq!(inspect_packet(64, -1), 0);
+    }
+
+    #[test]
+    fn test_random_frames() {
+        let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+        let base = frame(80, &[1, 1, 1, 0], &[2, 4, 0x05, 0xb4], b"GET /index.html HTTP/1.1\r\n");
+
+        // 임의 바이트열
+        for _ in 0..20_000 {
+            let len = rng.below(128);
+            let bytes: Vec<u8> = (0..len).map(|_| rng.next() as u8).collect();
+            let _ = verdict(&bytes, true, None);
+        }
+
+        // 정상 프레임의 바이트 몇 개를 바꾸고 임의 길이로 자름
+        for _ in 0..20_000 {
+            let mut mutated = base.clone();
+            for _ in 0..1 + rng.below(4) {
+                let at = rng.below(mutated.len());
+                mutated[at] = rng.next() as u8;
+            }
+            mutated.truncate(rng.below(mutated.len() + 1));
+            let blocked = verdict(&mutated, true, None);
+
+            // 차단은 HTTP 요청으로 해석된 페이로드에 의심 패턴이 있을 때만
+            if blocked == 1 {
+                let segment = parse_frame(&mutated).unwrap();
+                assert!(check_http_method(segment.payload) && check_suspicious_http(segment.payload));
+            } else {
+                assert_eq!(blocked, 0);
             }
         }
     }
-    
-    0 // 패킷 통과
 }
@@ -0,0 +1,665 @@
+//! 패턴 매처 WASM 모듈
+//! 모듈 설정에 적은 바이트 패턴을 TCP/UDP 페이로드에서 찾아 차단하는 범용 모듈
+//! 이 코드는 Rust에서 컴파일하여 WASM으로 변환
+//!
+//! 설정은 한 줄에 패턴 하나인 텍스트다 (`wasm load --config-file`). 빈 줄과 `#`으로
+//! 시작하는 줄은 건너뛴다.
+//!
+//! ```text
+//! # 이름       포트        패턴
+//! sqli         80,8080     UNION SELECT
+//! shellshock   *           ^() {
+//! pe-upload    8000-8100   MZ??\x00\x00*This program
+//! ```
+//!
+//! - 이름: 차단 사유에 쓰는 패턴 이름 (공백 없음, 중복 불가)
+//! - 포트: `*`(모든 포트) 또는 쉼표로 구분한 포트와 범위. 소스나 목적지 포트 중 하나가
+//!   목록에 있는 패킷만 검사
+//! - 패턴: 줄 끝까지 (앞뒤 공백 제외). `?`는 임의의 1바이트, `*`는 임의 길이의
+//!   바이트열, 맨 앞의 `^`는 페이로드 시작에 고정. `\xHH`, `\s`(공백), `\\`, `\?`,
+//!   `\*`, `\^`로 해당 바이트를 그대로 쓴다
+//!
+//! 패턴은 `init`에서 `*`로 나뉜 고정 길이 조각으로 컴파일한다. 설정이 잘못되면 이유를
+//! 로그로 남기고 `init`이 실패하므로 모듈 로드가 거부된다.
+
+use std::sync::OnceLock;
+
+/// 패턴 최대 개수
+const MAX_PATTERNS: usize = 64;
+
+/// 패턴 하나의 최대 길이 (`*`를 뺀 바이트 수)
+const MAX_PATTERN_LEN: usize = 256;
+
+/// 패킷 링 슬롯 수와 슬롯 크기 (데몬과 같은 값)
+const PACKET_SLOTS: usize = 8;
+const PACKET_SLOT_SIZE: usize = 2048;
+
+// 호스트 함수 선언
+#[cfg(target_arch = "wasm32")]
+extern "C" {
+    fn log(ptr: *const u8, len: i32) -> i32;
+    fn config_len() -> i32;
+    fn read_config(ptr: *mut u8, len: i32) -> i32;
+}
+
+/// 데몬이 패킷을 써 넣는 링의 주소 (`get_packet_buffer`에서 한 번 할당)
+static PACKET_RING: OnceLock<usize> = OnceLock::new();
+
+/// `init`에서 컴파일한 패턴
+static PATTERNS: OnceLock<Vec<Pattern>> = OnceLock::new();
+
+// 로그 함수
+#[cfg(target_arch = "wasm32")]
+fn log_message(message: &str) {
+    unsafe {
+        log(message.as_ptr(), message.len() as i32);
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn log_message(_message: &str) {}
+
+// 모듈 설정 읽기 (없으면 빈 값)
+#[cfg(target_arch = "wasm32")]
+fn module_config() -> Vec<u8> {
+    let len = unsafe { config_len() };
+    if len <= 0 {
+        return Vec::new();
+    }
+
+    let mut buffer = vec![0u8; len as usize];
+    let copied = unsafe { read_config(buffer.as_mut_ptr(), len) };
+    buffer.truncate(copied.max(0) as usize);
+    buffer
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn module_config() -> Vec<u8> {
+    Vec::new()
+}
+
+/// 조각의 원소
+#[derive(Debug, Clone, PartialEq)]
+enum Atom {
+    /// 그대로 비교할 바이트열
+    Literal(Vec<u8>),
+    /// 아무 바이트나 n개 (`?`)
+    Any(usize),
+}
+
+/// `*` 사이의 고정 길이 조각
+#[derive(Debug, Clone, Default, PartialEq)]
+struct Piece {
+    atoms: Vec<Atom>,
+    /// 조각 길이 (바이트)
+    len: usize,
+}
+
+impl Piece {
+    fn push_literal(&mut self, byte: u8) {
+        match self.atoms.last_mut() {
+            Some(Atom::Literal(bytes)) => bytes.push(byte),
+            _ => self.atoms.push(Atom::Literal(vec![byte])),
+        }
+        self.len += 1;
+    }
+
+    fn push_any(&mut self) {
+        match self.atoms.last_mut() {
+            Some(Atom::Any(count)) => *count += 1,
+            _ => self.atoms.push(Atom::Any(1)),
+        }
+        self.len += 1;
+    }
+
+    /// `payload[at..]`이 이 조각으로 시작하는지
+    fn matches_at(&self, payload: &[u8], at: usize) -> bool {
+        let window = match payload.get(at..).and_then(|rest| rest.get(..self.len)) {
+            Some(window) => window,
+            None => return false,
+        };
+
+        let mut offset = 0;
+        for atom in &self.atoms {
+            match atom {
+                Atom::Literal(bytes) => {
+                    if !window[offset..].starts_with(bytes) {
+                        return false;
+                    }
+                    offset += bytes.len();
+                },
+                Atom::Any(count) => offset += count,
+            }
+        }
+        true
+    }
+
+    /// `from` 이후 가장 앞선 일치의 끝 위치
+    fn find(&self, payload: &[u8], from: usize) -> Option<usize> {
+        let last = payload.len().checked_sub(self.len)?;
+        (from..=last).find(|&at| self.matches_at(payload, at)).map(|at| at + self.len)
+    }
+}
+
+/// 컴파일한 패턴
+#[derive(Debug, Clone, PartialEq)]
+struct Pattern {
+    name: String,
+    /// 검사할 포트 범위 (비어 있으면 모든 포트)
+    ports: Vec<(u16, u16)>,
+    /// 페이로드 시작에 고정
+    anchored: bool,
+    /// `*`로 나뉜 조각 (순서대로 겹치지 않게 나타나야 일치)
+    pieces: Vec<Piece>,
+}
+
+impl Pattern {
+    /// 소스나 목적지 포트가 포트 목록에 있는지
+    fn applies_to(&self, src_port: u16, dst_port: u16) -> bool {
+        self.ports.is_empty() || self.ports.iter()
+            .any(|&(min, max)| (min..=max).contains(&src_port) || (min..=max).contains(&dst_port))
+    }
+
+    /// 페이로드에 패턴이 있는지
+    ///
+    /// 각 조각을 앞 조각이 끝난 위치부터 가장 앞에서 찾는다. 조각 사이가 `*`뿐이므로
+    /// 가장 앞선 일치를 고르면 뒤 조각이 들어갈 자리가 가장 넓게 남는다.
+    fn matches(&self, payload: &[u8]) -> bool {
+        let mut pos = 0;
+        for (index, piece) in self.pieces.iter().enumerate() {
+            pos = if index == 0 && self.anchored {
+                if !piece.matches_at(payload, 0) {
+                    return false;
+                }
+                piece.len
+            } else {
+                match piece.find(payload, pos) {
+                    Some(end) => end,
+                    None => return false,
+                }
+            };
+        }
+        true
+    }
+}
+
+/// 패턴 문자열을 조각으로 컴파일 (고정 여부, 조각)
+fn compile_pattern(text: &str) -> Result<(bool, Vec<Piece>), String> {
+    let (mut anchored, body) = match text.strip_prefix('^') {
+        Some(rest) => (true, rest),
+        None => (false, text),
+    };
+
+    let mut pieces = Vec::new();
+    let mut piece = Piece::default();
+    let mut bytes = body.bytes();
+    while let Some(byte) = bytes.next() {
+        match byte {
+            b'*' => {
+                if piece.len > 0 {
+                    pieces.push(std::mem::take(&mut piece));
+                } else if pieces.is_empty() {
+                    // `^*`는 고정하지 않은 것과 같음
+                    anchored = false;
+                }
+            },
+            b'?' => piece.push_any(),
+            b'\\' => piece.push_literal(unescape(&mut bytes)?),
+            byte => piece.push_literal(byte),
+        }
+    }
+    if piece.len > 0 {
+        pieces.push(piece);
+    }
+
+    let len: usize = pieces.iter().map(|piece| piece.len).sum();
+    if len > MAX_PATTERN_LEN {
+        return Err(format!("pattern is {} bytes long (max {})", len, MAX_PATTERN_LEN));
+    }
+    let has_literal = pieces.iter()
+        .flat_map(|piece| &piece.atoms)
+        .any(|atom| matches!(atom, Atom::Literal(_)));
+    if !has_literal {
+        return Err("pattern needs at least one literal byte".to_string());
+    }
+
+    Ok((anchored, pieces))
+}
+
+/// `\` 다음 이스케이프를 바이트로
+fn unescape(bytes: &mut std::str::Bytes<'_>) -> Result<u8, String> {
+    match bytes.next() {
+        Some(b'x') => {
+            let high = bytes.next().and_then(hex_digit);
+            let low = bytes.next().and_then(hex_digit);
+            match (high, low) {
+                (Some(high), Some(low)) => Ok(high << 4 | low),
+                _ => Err("\\x needs two hex digits".to_string()),
+            }
+        },
+        Some(b's') => Ok(b' '),
+        Some(byte @ (b'\\' | b'?' | b'*' | b'^')) => Ok(byte),
+        Some(byte) => Err(format!("unknown escape \\{}", byte as char)),
+        None => Err("pattern ends with a backslash".to_string()),
+    }
+}
+
+fn hex_digit(byte: u8) -> Option<u8> {
+    (byte as char).to_digit(16).map(|digit| digit as u8)
+}
+
+/// 포트 목록 (`*`이면 빈 목록)
+fn parse_ports(text: &str) -> Result<Vec<(u16, u16)>, String> {
+    if text == "*" {
+        return Ok(Vec::new());
+    }
+
+    let port = |text: &str| text.parse::<u16>().map_err(|_| format!("invalid port '{}'", text));
+    text.split(',')
+        .map(|item| {
+            let (min, max) = match item.split_once('-') {
+                Some((min, max)) => (port(min)?, port(max)?),
+                None => (port(item)?, port(item)?),
+            };
+            if min > max {
+                return Err(format!("invalid port range '{}'", item));
+            }
+            Ok((min, max))
+        })
+        .collect()
+}
+
+/// 공백으로 구분한 첫 필드와 나머지
+fn split_field(text: &str) -> Option<(&str, &str)> {
+    let end = text.find(char::is_whitespace)?;
+    Some((&text[..end], text[end..].trim_start()))
+}
+
+/// 설정 한 줄을 패턴으로
+fn parse_line(line: &str) -> Result<Pattern, String> {
+    let (name, rest) = split_field(line).ok_or("expected <name> <ports> <pattern>")?;
+    let (ports, pattern) = split_field(rest).ok_or("expected <name> <ports> <pattern>")?;
+    let (anchored, pieces) = compile_pattern(pattern)?;
+
+    Ok(Pattern {
+        name: name.to_string(),
+        ports: parse_ports(ports)?,
+        anchored,
+        pieces,
+    })
+}
+
+/// 설정 전체를 패턴 목록으로 (오류에는 줄 번호를 붙임)
+fn parse_config(text: &str) -> Result<Vec<Pattern>, String> {
+    let mut patterns: Vec<Pattern> = Vec::new();
+
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let pattern = parse_line(line).map_err(|e| format!("line {}: {}", index + 1, e))?;
+        if patterns.iter().any(|existing| existing.name == pattern.name) {
+            return Err(format!("line {}: duplicate pattern name '{}'", index + 1, pattern.name));
+        }
+        patterns.push(pattern);
+    }
+
+    if patterns.len() > MAX_PATTERNS {
+        return Err(format!("{} patterns configured (max {})", patterns.len(), MAX_PATTERNS));
+    }
+    Ok(patterns)
+}
+
+/// 검사할 L4 페이로드
+#[derive(Debug, PartialEq)]
+struct Payload<'a> {
+    protocol: &'static str,
+    src_port: u16,
+    dst_port: u16,
+    data: &'a [u8],
+}
+
+fn be16(data: &[u8], offset: usize) -> Option<u16> {
+    let bytes = data.get(offset..offset.checked_add(2)?)?;
+    Some(u16::from_be_bytes([bytes[0], bytes[1]]))
+}
+
+/// IPv4 패킷의 (프로토콜, L4 헤더부터 끝까지)
+fn ipv4(packet: &[u8]) -> Option<(u8, &[u8])> {
+    let version_ihl = *packet.first()?;
+    if version_ihl >> 4 != 4 {
+        return None;
+    }
+
+    let header_len = (version_ihl & 0x0f) as usize * 4;
+    let total_len = be16(packet, 2)? as usize;
+    if header_len < 20 || total_len < header_len {
+        return None;
+    }
+
+    // 첫 조각이 아닌 조각에는 L4 헤더가 없음
+    if be16(packet, 6)? & 0x1fff != 0 {
+        return None;
+    }
+
+    // 이더넷 패딩은 빼고, 잘린 프레임이면 있는 만큼만
+    let end = total_len.min(packet.len());
+    Some((*packet.get(9)?, packet.get(header_len..end)?))
+}
+
+/// IPv6 패킷의 (다음 헤더, L4 헤더부터 끝까지, 확장 헤더는 따라가지 않음)
+fn ipv6(packet: &[u8]) -> Option<(u8, &[u8])> {
+    if *packet.first()? >> 4 != 6 {
+        return None;
+    }
+
+    let end = (40 + be16(packet, 4)? as usize).min(packet.len());
+    Some((*packet.get(6)?, packet.get(40..end)?))
+}
+
+/// 이더넷 프레임에서 TCP/UDP 페이로드 추출 (VLAN 태그 하나까지)
+fn parse_packet(frame: &[u8]) -> Option<Payload<'_>> {
+    let mut ether_type = be16(frame, 12)?;
+    let mut offset = 14;
+    if ether_type == 0x8100 || ether_type == 0x88a8 {
+        ether_type = be16(frame, 16)?;
+        offset = 18;
+    }
+
+    let (protocol, l4) = match ether_type {
+        0x0800 => ipv4(frame.get(offset..)?)?,
+        0x86dd => ipv6(frame.get(offset..)?)?,
+        _ => return None,
+    };
+
+    let (protocol, data) = match protocol {
+        6 => {
+            let data_offset = (*l4.get(12)? >> 4) as usize * 4;
+            if data_offset < 20 {
+                return None;
+            }
+            ("TCP", l4.get(data_offset..)?)
+        },
+        17 => ("UDP", l4.get(8..)?),
+        _ => return None,
+    };
+
+    Some(Payload {
+        protocol,
+        src_port: be16(l4, 0)?,
+        dst_port: be16(l4, 2)?,
+        data,
+    })
+}
+
+/// 페이로드와 일치하는 첫 패턴
+fn scan<'p>(patterns: &'p [Pattern], payload: &Payload<'_>) -> Option<&'p Pattern> {
+    if payload.data.is_empty() {
+        return None;
+    }
+
+    patterns.iter()
+        .find(|pattern| pattern.applies_to(payload.src_port, payload.dst_port) && pattern.matches(payload.data))
+}
+
+// 초기화 함수 (설정이 잘못되면 실패해 로드를 거부)
+#[no_mangle]
+pub extern "C" fn init() {
+    let config = module_config();
+    let parsed = std::str::from_utf8(&config)
+        .map_err(|_| "config is not valid UTF-8".to_string())
+        .and_then(parse_config);
+
+    match parsed {
+        Ok(patterns) => {
+            if patterns.is_empty() {
+                log_message("Pattern matcher initialized without patterns; every packet passes");
+            } else {
+                log_message(&format!("Pattern matcher initialized with {} patterns", patterns.len()));
+            }
+            let _ = PATTERNS.set(patterns);
+        },
+        Err(e) => {
+            log_message(&format!("Invalid pattern matcher config: {}", e));
+            panic!("invalid pattern matcher config");
+        },
+    }
+}
+
+// 패킷 링 주소
+#[no_mangle]
+pub extern "C" fn get_packet_buffer() -> i32 {
+    let ring = PACKET_RING.get_or_init(|| {
+        let buffer = vec![0u8; PACKET_SLOTS * PACKET_SLOT_SIZE].into_boxed_slice();
+        Box::leak(buffer).as_mut_ptr() as usize
+    });
+    *ring as i32
+}
+
+// 패킷 검사 메인 함수 (WASM 인터페이스, 슬롯 번호와 길이)
+#[no_mangle]
+pub extern "C" fn inspect_packet(slot: i32, len: i32) -> i32 {
+    if !(0..PACKET_SLOTS as i32).contains(&slot) || !(0..=PACKET_SLOT_SIZE as i32).contains(&len) {
+        return 0; // 패킷 통과
+    }
+
+    let ring = match PACKET_RING.get() {
+        Some(ring) => *ring,
+        None => return 0,
+    };
+    let frame = unsafe {
+        std::slice::from_raw_parts((ring + slot as usize * PACKET_SLOT_SIZE) as *const u8, len as usize)
+    };
+
+    let patterns = match PATTERNS.get() {
+        Some(patterns) => patterns,
+        None => return 0,
+    };
+    let payload = match parse_packet(frame) {
+        Some(payload) => payload,
+        None => return 0,
+    };
+
+    match scan(patterns, &payload) {
+        Some(pattern) => {
+            // 마지막 로그가 차단 사유
+            log_message(&format!("Pattern '{}' matched {} payload ({} -> {})",
+                pattern.name, payload.protocol, payload.src_port, payload.dst_port));
+            1 // 패킷 차단
+        },
+        None => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 이더넷 + IPv4 + TCP/UDP 프레임 (이더넷 패딩 `padding`바이트 추가)
+    fn frame_v4(protocol: u8, src_port: u16, dst_port: u16, payload: &[u8], padding: usize) -> Vec<u8> {
+        let l4_len = if protocol == 6 { 20 } else { 8 };
+        let total_len = (20 + l4_len + payload.len()) as u16;
+
+        let mut frame = vec![0u8; 12];
+        frame.extend_from_slice(&[0x08, 0x00]);
+        frame.extend_from_slice(&[0x45, 0x00]);
+        frame.extend_from_slice(&total_len.to_be_bytes());
+        frame.extend_from_slice(&[0, 0, 0x40, 0x00, 64, protocol, 0, 0]);
+        frame.extend_from_slice(&[10, 0, 0, 1, 10, 0, 0, 2]);
+
+        frame.extend_from_slice(&src_port.to_be_bytes());
+        frame.extend_from_slice(&dst_port.to_be_bytes());
+        if protocol == 6 {
+            frame.extend_from_slice(&[0; 8]);
+            frame.extend_from_slice(&[0x50, 0x18, 0xff, 0xff, 0, 0, 0, 0]);
+        } else {
+            frame.extend_from_slice(&(8 + payload.len() as u16).to_be_bytes());
+            frame.extend_from_slice(&[0, 0]);
+        }
+        frame.extend_from_slice(payload);
+        frame.resize(frame.len() + padding, 0xee);
+        frame
+    }
+
+    fn pattern(text: &str) -> Pattern {
+        parse_line(&format!("test * {}", text)).unwrap()
+    }
+
+    fn matched<'p>(patterns: &'p [Pattern], frame: &[u8]) -> Option<&'p str> {
+        scan(patterns, &parse_packet(frame)?).map(|pattern| pattern.name.as_str())
+    }
+
+    #[test]
+    fn test_parse_config() {
+        let config = "\
+            # 이름 포트 패턴\n\
+            \n\
+            sqli  80,8080   UNION SELECT  \n\
+            shell 8000-8100 ^() {\n\
+            bin   *         MZ\\x90\\x00\\s\n";
+        let patterns = parse_config(config).unwrap();
+        assert_eq!(patterns.len(), 3);
+
+        assert_eq!(patterns[0].name, "sqli");
+        assert_eq!(patterns[0].ports, [(80, 80), (8080, 8080)]);
+        assert_eq!(patterns[0].pieces[0].atoms, [Atom::Literal(b"UNION SELECT".to_vec())]);
+        assert_eq!((patterns[1].anchored, patterns[1].ports.as_slice()), (true, &[(8000, 8100)][..]));
+        assert!(patterns[2].ports.is_empty());
+        assert_eq!(patterns[2].pieces[0].atoms, [Atom::Literal(b"MZ\x90\x00 ".to_vec())]);
+
+        // 오류에는 줄 번호가 붙음
+        let err = |config: &str| parse_config(config).unwrap_err();
+        assert_eq!(err("a * x\nbad"), "line 2: expected <name> <ports> <pattern>");
+        assert_eq!(err("a 80 x\na 81 y"), "line 2: duplicate pattern name 'a'");
+        assert_eq!(err("a 70000 x"), "line 1: invalid port '70000'");
+        assert_eq!(err("a 90-80 x"), "line 1: invalid port range '90-80'");
+        assert_eq!(err("a * \\xZZ"), "line 1: \\x needs two hex digits");
+        assert_eq!(err("a * x\\q"), "line 1: unknown escape \\q");
+        assert_eq!(err("a * x\\"), "line 1: pattern ends with a backslash");
+        assert_eq!(err("a * ??*?"), "line 1: pattern needs at least one literal byte");
+        assert_eq!(err(&format!("a * {}", "x".repeat(257))), "line 1: pattern is 257 bytes long (max 256)");
+
+        let many: String = (0..65).map(|i| format!("p{} * x\n", i)).collect();
+        assert_eq!(parse_config(&many).unwrap_err(), "65 patterns configured (max 64)");
+        assert!(parse_config("# only comments\n\n").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_compile_compacts_wildcards() {
+        // 연속한 ?는 하나로, 연속한 *와 빈 조각은 없앰
+        let compiled = pattern("ab???c**d?\\?");
+        assert!(!compiled.anchored);
+        assert_eq!(compiled.pieces, [
+            Piece { atoms: vec![Atom::Literal(b"ab".to_vec()), Atom::Any(3), Atom::Literal(b"c".to_vec())], len: 6 },
+            Piece { atoms: vec![Atom::Literal(b"d".to_vec()), Atom::Any(1), Atom::Literal(b"?".to_vec())], len: 3 },
+        ]);
+
+        // ^* 는 고정하지 않음
+        assert!(!pattern("^*x").anchored);
+        assert!(pattern("^x*").anchored);
+        assert!(pattern("\\^x").pieces[0].matches_at(b"^x", 0));
+    }
+
+    #[test]
+    fn test_pattern_matching() {
+        let cases: &[(&str, &[u8], bool)] = &[
+            ("UNION SELECT", b"id=1 UNION SELECT pass", true),
+            ("UNION SELECT", b"id=1 UNION  SELECT pass", false),
+            ("a?c", b"xxabcxx", true),
+            ("a?c", b"ac", false),
+            ("GET *passwd", b"GET /etc/passwd HTTP/1.1", true),
+            ("GET *passwd", b"passwd GET /", false),
+            ("a*b*c", b"cab", false),
+            ("a*b*c", b"aXbXc", true),
+            ("ab*b", b"ab", false),
+            ("ab*b", b"abb", true),
+            ("^GET ", b"GET / HTTP/1.1", true),
+            ("^GET ", b" GET / HTTP/1.1", false),
+            ("^() {*;", b"() { :; }; /bin/sh", true),
+            ("^MZ??\\x00", b"MZ\x90\x01\x00", true),
+            ("^MZ??\\x00", b"MZ\x90", false),
+            ("tail", b"tai", false),
+        ];
+
+        for (text, payload, expected) in cases {
+            assert_eq!(pattern(text).matches(payload), *expected, "{:?} in {:?}", text, payload);
+        }
+    }
+
+    #[test]
+    fn test_scan_packets() {
+        let patterns = parse_config("web 80,8000-8100 UNION SELECT\ndns 53 \\x07example\nany * EVIL").unwrap();
+
+        // TCP 목적지 포트와 소스 포트 모두 포트 목록에 적용
+        assert_eq!(matched(&patterns, &frame_v4(6, 40000, 80, b"x UNION SELECT y", 0)), Some("web"));
+        assert_eq!(matched(&patterns, &frame_v4(6, 8080, 40000, b"x UNION SELECT y", 0)), Some("web"));
+        assert_eq!(matched(&patterns, &frame_v4(6, 40000, 443, b"x UNION SELECT y", 0)), None);
+
+        assert_eq!(matched(&patterns, &frame_v4(17, 40000, 53, b"\x00\x01\x07example\x03com", 0)), Some("dns"));
+        assert_eq!(matched(&patterns, &frame_v4(17, 40000, 80, b"EVIL", 0)), Some("any"));
+        assert_eq!(matched(&patterns, &frame_v4(6, 40000, 80, b"", 0)), None);
+
+        // 이더넷 패딩은 페이로드가 아님
+        let padded = parse_config("pad * \\xee").unwrap();
+        assert_eq!(matched(&padded, &frame_v4(6, 1, 2, b"ok", 20)), None);
+        assert_eq!(matched(&padded, &frame_v4(6, 1, 2, b"\xee", 20)), Some("pad"));
+
+        // 첫 조각이 아닌 IPv4 조각은 건너뜀
+        let mut fragment = frame_v4(17, 40000, 80, b"EVIL", 0);
+        fragment[20..22].copy_from_slice(&[0x00, 0x10]);
+        assert_eq!(matched(&patterns, &fragment), None);
+
+        // VLAN 태그와 IPv6
+        let mut tagged = frame_v4(6, 40000, 80, b"EVIL", 0);
+        tagged.splice(12..12, [0x81, 0x00, 0x00, 0x0a]);
+        assert_eq!(matched(&patterns, &tagged), Some("any"));
+
+        let mut v6 = vec![0u8; 12];
+        v6.extend_from_slice(&[0x86, 0xdd, 0x60, 0, 0, 0, 0, 12, 17, 64]);
+        v6.extend_from_slice(&[0; 32]);
+        v6.extend_from_slice(&[0x9c, 0x40, 0x00, 0x35, 0, 12, 0, 0]);
+        v6.extend_from_slice(b"EVIL");
+        assert_eq!(parse_packet(&v6).map(|payload| (payload.protocol, payload.dst_port)), Some(("UDP", 53)));
+        assert_eq!(matched(&patterns, &v6), Some("any"));
+    }
+
+    #[test]
+    fn test_malformed_packets() {
+        let patterns = parse_config("any * EVIL").unwrap();
+        let frame = frame_v4(6, 40000, 80, b"xxEVIL", 0);
+
+        // 잘린 프레임은 어느 길이에서도 패닉 없이 통과하거나 남은 바이트만 검사
+        for len in 0..frame.len() {
+            assert_eq!(matched(&patterns, &frame[..len]), None, "{}", len);
+        }
+        assert_eq!(matched(&patterns, &frame), Some("any"));
+
+        // 헤더 길이가 최소보다 작거나 전체 길이를 넘는 패킷
+        let mut short_ihl = frame.clone();
+        short_ihl[14] = 0x44;
+        assert_eq!(parse_packet(&short_ihl), None);
+
+        let mut short_total = frame.clone();
+        short_total[16..18].copy_from_slice(&10u16.to_be_bytes());
+        assert_eq!(parse_packet(&short_total), None);
+
+        let mut short_tcp = frame.clone();
+        short_tcp[46] = 0x40;
+        assert_eq!(parse_packet(&short_tcp), None);
+
+        let mut long_tcp = frame.clone();
+        long_tcp[46] = 0xf0;
+        assert_eq!(parse_packet(&long_tcp), None);
+
+        // 전체 길이가 프레임보다 길면 있는 만큼 검사
+        let mut truncated = frame.clone();
+        truncated[16..18].copy_from_slice(&1500u16.to_be_bytes());
+        assert_eq!(matched(&patterns, &truncated), Some("any"));
+
+        assert_eq!(parse_packet(&[0xff; 64]), None);
+        assert_eq!(inspect_packet(PACKET_SLOTS as i32, 64), 0);
+        assert_eq!(inspect_packet(0, PACKET_SLOT_SIZE as i32 + 1), 0);
+    }
+}